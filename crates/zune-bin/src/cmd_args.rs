@@ -7,20 +7,30 @@
  */
 
 use std::ffi::OsString;
+use std::path::PathBuf;
 
 use clap::builder::PossibleValue;
 use clap::{value_parser, Arg, ArgAction, ArgGroup, Command, ValueEnum};
 use zune_image::codecs::ImageFormat;
+use zune_image::metadata::RegionOfInterest;
 
 use crate::cmd_args::arg_parsers::IColorSpace;
 use crate::cmd_args::help_strings::{
     AFTER_HELP, BOX_BLUR_HELP, BRIGHTEN_HELP, COLORSPACE_HELP, CROP_HELP, GAUSSIAN_BLUR_HELP,
     THRESHOLD_HELP, TRANSPOSE_HELP
 };
+use crate::workflow_file::DECODER_SETTINGS;
 
 pub mod arg_parsers;
 pub mod help_strings;
 
+/// Group of image operations
+pub const OPERATIONS_GROUP: &str = "Operations";
+/// Group of image filters
+pub const FILTERS_GROUP: &str = "filters";
+/// Group of encoder options
+pub const ENCODE_GROUP: &str = "Encode operations";
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum MmapOptions {
     No,
@@ -57,7 +67,9 @@ impl ValueEnum for CmdImageFormats {
             Self::Format(ImageFormat::PNG),
             Self::Format(ImageFormat::PPM),
             Self::Format(ImageFormat::PSD),
-            Self::Format(ImageFormat::QOI)
+            Self::Format(ImageFormat::QOI),
+            Self::Format(ImageFormat::QoiSequence),
+            Self::Format(ImageFormat::TGA)
         ]
     }
 
@@ -70,9 +82,33 @@ impl ValueEnum for CmdImageFormats {
                 ImageFormat::PSD => Some(PossibleValue::new("psd")),
                 ImageFormat::Farbfeld => Some(PossibleValue::new("farbfeld")),
                 ImageFormat::QOI => Some(PossibleValue::new("qoi")),
+                ImageFormat::QoiSequence => Some(PossibleValue::new("qois")),
                 ImageFormat::JPEG_XL => Some(PossibleValue::new("jxl")),
                 ImageFormat::HDR => Some(PossibleValue::new("hdr")),
                 ImageFormat::BMP => Some(PossibleValue::new("bmp")),
+                ImageFormat::TGA => Some(PossibleValue::new("tga")),
+                _ => None
+            }
+        }
+    }
+}
+impl CmdImageFormats {
+    /// Return the file extension used for images of this format
+    #[cfg(feature = "watch")]
+    pub const fn extension(self) -> Option<&'static str> {
+        match self {
+            CmdImageFormats::Format(c) => match c {
+                ImageFormat::JPEG => Some("jpg"),
+                ImageFormat::PNG => Some("png"),
+                ImageFormat::PPM => Some("ppm"),
+                ImageFormat::PSD => Some("psd"),
+                ImageFormat::Farbfeld => Some("ff"),
+                ImageFormat::QOI => Some("qoi"),
+                ImageFormat::QoiSequence => Some("qois"),
+                ImageFormat::JPEG_XL => Some("jxl"),
+                ImageFormat::HDR => Some("hdr"),
+                ImageFormat::BMP => Some("bmp"),
+                ImageFormat::TGA => Some("tga"),
                 _ => None
             }
         }
@@ -93,20 +129,137 @@ pub fn create_cmd_args() -> Command {
         .term_width(200)
         .arg(Arg::new("in")
             .short('i')
-            .help("Input file to read data from")
+            .help("Input file to read data from, '-' reads from stdin")
+            .long_help("Input file to read data from, '-' reads from stdin.\nFor PDF documents, each embedded image is processed on its own and written to numbered outputs, e.g. `-i scan.pdf -o page.png` writes page-0.png, page-1.png, ...")
             .long("input")
             .action(ArgAction::Set)
             .value_parser(value_parser!(OsString))
-            .required(true))
+            .required_unless_present_any(["watch", "montage"]))
+        .arg(Arg::new("montage")
+            .long("montage")
+            .help("Lay out the input files in a grid labeled with their names and use the result as input")
+            .long_help("Lay out the input files in a grid, e.g. a contact sheet, with each image labeled with its file name.\nThe sheet is then processed like a single input, e.g. `--montage a.jpg b.jpg c.jpg -o sheet.png`")
+            .value_name("FILES")
+            .num_args(1..)
+            .conflicts_with_all(["in", "watch"])
+            .value_parser(value_parser!(OsString)))
+        .arg(Arg::new("montage-columns")
+            .long("montage-columns")
+            .help("Number of columns of the montage, defaults to a grid about as wide as tall")
+            .value_name("COLUMNS")
+            .requires("montage")
+            .value_parser(value_parser!(usize)))
+        .arg(Arg::new("montage-tile")
+            .long("montage-tile")
+            .help("Largest width and height of images in the montage, larger ones are scaled down to fit")
+            .num_args(2)
+            .value_names(["WIDTH", "HEIGHT"])
+            .requires("montage")
+            .value_parser(value_parser!(usize)))
+        .arg(Arg::new("montage-padding")
+            .long("montage-padding")
+            .help("Space in pixels between images of the montage, default 8")
+            .value_name("PIXELS")
+            .requires("montage")
+            .value_parser(value_parser!(usize)))
+        .arg(Arg::new("montage-background")
+            .long("montage-background")
+            .help("Background of the montage as a hex color like #ffffff (default) or #00000000 with alpha")
+            .value_name("COLOR")
+            .requires("montage")
+            .value_parser(value_parser!(String)))
+        .arg(Arg::new("montage-label-color")
+            .long("montage-label-color")
+            .help("Color of montage labels as a hex color like #000000 (default)")
+            .value_name("COLOR")
+            .requires("montage")
+            .value_parser(value_parser!(String)))
+        .arg(Arg::new("montage-no-labels")
+            .long("montage-no-labels")
+            .help("Don't label images of the montage with their file names")
+            .action(ArgAction::SetTrue)
+            .requires("montage"))
         .arg(Arg::new("out")
             .short('o')
             .long("out")
-            .help("Output to write the data to")
+            .help("Output to write the data to, '-' writes to stdout")
             .action(ArgAction::Append)
             .value_parser(value_parser!(OsString))
         )
+        .arg(Arg::new("watch")
+            .long("watch")
+            .help("Watch a directory and process new or modified images in it")
+            .long_help("Watch a directory and run the workflow on each new or modified image in it, results are written to the --watch-out directory.\nOutputs keep the input file name, use --output-format to change their format.")
+            .value_name("DIR")
+            .hide(!cfg!(feature = "watch"))
+            .requires("watch-out")
+            .conflicts_with_all(["in", "out", "probe"])
+            .value_parser(value_parser!(PathBuf)))
+        .arg(Arg::new("watch-out")
+            .long("watch-out")
+            .help("Directory to write images processed in watch mode to")
+            .value_name("DIR")
+            .hide(!cfg!(feature = "watch"))
+            .requires("watch")
+            .value_parser(value_parser!(PathBuf)))
+        .arg(Arg::new("workflow")
+            .long("workflow")
+            .help("Run the operations and options stored in a workflow file")
+            .long_help("Run the decoder options, operations and encoder options stored in a JSON workflow file.\nOperations and options can't also be given on the command line, see --save-workflow to create a workflow file")
+            .value_name("FILE")
+            .conflicts_with_all([OPERATIONS_GROUP, FILTERS_GROUP, ENCODE_GROUP, "colorspace"])
+            .conflicts_with_all(DECODER_SETTINGS)
+            .value_parser(value_parser!(PathBuf)))
+        .arg(Arg::new("save-workflow")
+            .long("save-workflow")
+            .help("Save the operations and options given on the command line to a workflow file")
+            .value_name("FILE")
+            .conflicts_with("workflow")
+            .value_parser(value_parser!(PathBuf)))
+        .arg(Arg::new("scope")
+            .long("scope")
+            .help("Write a diagnostic visualization of the output to a file, can be repeated")
+            .long_help("Write a diagnostic visualization of the output to a file alongside the output.\nSupported scopes are histogram, parade and vectorscope e.g. `--scope vectorscope scope.png`")
+            .num_args(2)
+            .value_names(["SCOPE", "FILE"])
+            .action(ArgAction::Append)
+            .value_parser(value_parser!(String)))
+        .arg(Arg::new("sprite-sheet")
+            .long("sprite-sheet")
+            .help("Combine the frames of the output into a sprite sheet with COLUMNS columns, all in one row by default")
+            .long_help("Combine the frames of the output, e.g. an APNG, into a single image with the frames laid out in a grid, after all operations have run.\nFrames go left to right and top to bottom, all in a single row unless COLUMNS is given. See --sprite-manifest for frame positions and durations")
+            .value_name("COLUMNS")
+            .num_args(0..=1)
+            .default_missing_value("0")
+            .value_parser(value_parser!(usize)))
+        .arg(Arg::new("sprite-manifest")
+            .long("sprite-manifest")
+            .help("Write the position and duration in milliseconds of every frame of the --sprite-sheet to a JSON file")
+            .value_name("FILE")
+            .requires("sprite-sheet")
+            .value_parser(value_parser!(PathBuf)))
+        .arg(Arg::new("to-ascii")
+            .long("to-ascii")
+            .help("Print the output to the terminal as text art, MODE is one of ascii (default), color or blocks")
+            .long_help("Print the output to the terminal as text art, useful for previews over SSH.\nascii uses plain characters, color colors them with truecolor escape codes and blocks draws colored half blocks with two pixels per character")
+            .value_name("MODE")
+            .num_args(0..=1)
+            .default_missing_value("ascii")
+            .value_parser(["ascii", "color", "blocks"]))
+        .arg(Arg::new("ascii-width")
+            .long("ascii-width")
+            .help("Width in characters of --to-ascii output, defaults to the COLUMNS environment variable or 80")
+            .value_name("COLUMNS")
+            .requires("to-ascii")
+            .value_parser(value_parser!(usize)))
+        .arg(Arg::new("input-format")
+            .long("input-format")
+            .alias("in-format")
+            .help("Input format to use instead of detecting it, useful with '-i -' to read from stdin")
+            .value_parser(value_parser!(CmdImageFormats)))
         .arg(Arg::new("output-format")
             .long("output-format")
+            .alias("out-format")
             .help("Output format to use when output is command line, to be used in conjunction with '-o -'")
             .value_parser(value_parser!(CmdImageFormats)))
         .arg(Arg::new("mmap")
@@ -123,8 +276,12 @@ pub fn create_cmd_args() -> Command {
             .action(ArgAction::SetTrue))
         .arg(Arg::new("view")
             .long("view")
-            .help("View image effects after carrying out effects")
-            .action(ArgAction::SetTrue))
+            .help("View image effects after carrying out effects, MODE is one of auto (default), app, kitty, iterm or sixel")
+            .long_help("View image effects after carrying out effects.\nImages are drawn inside terminals supporting the kitty, iTerm2 or sixel graphics protocols, which works over SSH, and opened in a viewer in the default browser otherwise.\nThe viewer zooms with the mouse wheel, pans by dragging, shows the coordinates and values of the pixel under the cursor and plays animations.\nauto detects the terminal, the other modes force a protocol or the default app")
+            .value_name("MODE")
+            .num_args(0..=1)
+            .default_missing_value("auto")
+            .value_parser(["auto", "app", "kitty", "iterm", "sixel"]))
         .arg(Arg::new("probe")
             .long("probe")
             .help("Probe file for details")
@@ -210,6 +367,13 @@ fn add_settings() -> Vec<Arg> {
             .help("Do not use unsafe paths routines where possible")
             .long_help("Do not use unsafe paths routines where possible\nMainly used for debugging and testing purposes")
             .action(ArgAction::SetTrue)
+            .default_value("false"),
+        Arg::new("still")
+            .long("still")
+            .help_heading(HELP_HEADING)
+            .help("Only decode and process the first frame of animated images")
+            .long_help("Only decode and process the first frame of animated images\nBy default all frames are decoded, processed and encoded, formats without animation support get one file per frame")
+            .action(ArgAction::SetTrue)
             .default_value("false")
     ];
     // list them in order
@@ -219,7 +383,7 @@ fn add_settings() -> Vec<Arg> {
 
 fn add_operations() -> (Vec<Arg>, ArgGroup) {
     static HELP_HEADING: &str = "Image Operations";
-    static GROUP: &str = "Operations";
+    static GROUP: &str = OPERATIONS_GROUP;
 
     let mut args = [
         Arg::new("grayscale")
@@ -281,6 +445,20 @@ fn add_operations() -> (Vec<Arg>, ArgGroup) {
             .help("Crop an image ")
             .long_help(CROP_HELP)
             .group(GROUP),
+        Arg::new("smart-crop")
+            .long("smart-crop")
+            .value_names(["width", "height", "method"])
+            .num_args(2..=3)
+            .help_heading(HELP_HEADING)
+            .help("Crop to the aspect ratio of width and height keeping the most interesting part, then resize to width x height. Method is attention (default), entropy or center")
+            .group(GROUP),
+        Arg::new("shape-mask")
+            .long("shape-mask")
+            .value_names(["shape", "radius"])
+            .num_args(1..=2)
+            .help_heading(HELP_HEADING)
+            .help("Make pixels outside a shape transparent. Shape is rounded (with a corner radius), circle (after cropping to a centered square) or ellipse")
+            .group(GROUP),
         Arg::new("threshold")
             .long("threshold")
             .value_names(["threshold", "mode"])
@@ -288,6 +466,13 @@ fn add_operations() -> (Vec<Arg>, ArgGroup) {
             .help("Replace pixels in an image depending on intensity of the pixel.")
             .long_help(THRESHOLD_HELP)
             .group(GROUP),
+        Arg::new("lut")
+            .long("lut")
+            .value_names(["file", "interpolation"])
+            .num_args(1..=2)
+            .help_heading(HELP_HEADING)
+            .help("Apply a 3D LUT from a .cube file, interpolation is either tetrahedral (default) or trilinear")
+            .group(GROUP),
         Arg::new("gamma")
             .long("gamma")
             .help("Gamma adjust an image")
@@ -327,6 +512,49 @@ fn add_operations() -> (Vec<Arg>, ArgGroup) {
             .help("Automatically orient the image based on exif tag")
             .action(ArgAction::SetTrue)
             .group(GROUP),
+        Arg::new("auto-exposure")
+            .long("auto-exposure")
+            .help_heading(HELP_HEADING)
+            .action(ArgAction::SetTrue)
+            .help("Correct the exposure of the image, metering regions of interest if given with --roi")
+            .group(GROUP),
+        Arg::new("barcode-preprocess")
+            .long("barcode-preprocess")
+            .help_heading(HELP_HEADING)
+            .help("Prepare the image for barcode, QR code and OCR readers: grayscale, adaptive threshold, deskew and despeckle")
+            .long_help("Prepare the image for barcode, QR code and OCR readers, producing a level black and white image.\nThe image is converted to grayscale, thresholded against the mean of a window of radius (default 20) pixels around each pixel minus offset (default 0.04), deskewed by up to 15 degrees and cleaned of black specks of up to 4 pixels.\nThe radius should be larger than the largest dark area, e.g. QR finder patterns")
+            .value_names(["radius", "offset"])
+            .num_args(0..=2)
+            .default_missing_value("20")
+            .value_parser(value_parser!(f32))
+            .group(GROUP),
+        Arg::new("flatten")
+            .long("flatten")
+            .help_heading(HELP_HEADING)
+            .help("Draw transparent images over a background and remove alpha, BACKGROUND is a hex color like #ffffff (default) or checkerboard")
+            .long_help("Draw transparent images over an opaque background and remove the alpha channel, needed when saving to formats without alpha like JPEG.\nBACKGROUND is a hex color like #ffffff (the default) or checkerboard for a preview of transparency")
+            .value_name("BACKGROUND")
+            .num_args(0..=1)
+            .default_missing_value("#ffffff")
+            .value_parser(value_parser!(String))
+            .group(GROUP),
+        Arg::new("split-sprite-sheet")
+            .long("split-sprite-sheet")
+            .help_heading(HELP_HEADING)
+            .help("Cut a sprite sheet into the frames of an animation, using a manifest written by --sprite-manifest or cells of WIDTH x HEIGHT")
+            .long_help("Cut a sprite sheet into the frames of an animation.\nGiven a manifest written by --sprite-manifest, frames are cut out where it says and keep their durations. Given a width and height, the sheet is cut into cells of that size, read left to right and top to bottom, each shown for 100 ms")
+            .value_names(["MANIFEST|WIDTH", "HEIGHT"])
+            .num_args(1..=2)
+            .value_parser(value_parser!(String))
+            .group(GROUP),
+        Arg::new("roi")
+            .long("roi")
+            .help_heading(HELP_HEADING)
+            .value_name("x,y,width,height[,weight]")
+            .action(ArgAction::Append)
+            .value_parser(|x: &str| x.parse::<RegionOfInterest>())
+            .help("Mark a region of interest, e.g. a face, used by operations after it like smart crop and auto exposure. Can be given multiple times")
+            .group(GROUP),
         Arg::new("exposure")
             .long("exposure")
             .help_heading(HELP_HEADING)
@@ -368,7 +596,7 @@ fn add_operations() -> (Vec<Arg>, ArgGroup) {
 
 fn add_encode_options() -> (Vec<Arg>, ArgGroup) {
     static HELP_HEADING: &str = "Encode Operations";
-    static GROUP: &str = "Encode operations";
+    static GROUP: &str = ENCODE_GROUP;
     let mut args = [
         Arg::new("quality")
             .long("quality")
@@ -404,6 +632,29 @@ fn add_encode_options() -> (Vec<Arg>, ArgGroup) {
             .help("Strip metadata when encoding images (where supported)")
             .action(ArgAction::SetTrue)
             .group(GROUP)
+            .help_heading(HELP_HEADING),
+        Arg::new("max-size")
+            .long("max-size")
+            .value_name("bytes")
+            .help(
+                "Try to fit encoded images in this many bytes by lowering quality (jpeg, jpeg-xl)"
+            )
+            .value_parser(value_parser!(usize))
+            .group(GROUP)
+            .help_heading(HELP_HEADING),
+        Arg::new("subsampling")
+            .long("subsampling")
+            .value_name("subsampling")
+            .help("Chroma subsampling for jpeg encoding, `source` keeps the subsampling of a jpeg input")
+            .value_parser(["auto", "444", "440", "422", "420", "source"])
+            .default_value("auto")
+            .group(GROUP)
+            .help_heading(HELP_HEADING),
+        Arg::new("deterministic")
+            .long("deterministic")
+            .help("Write headers in a fixed order so encoding the same image gives the same bytes")
+            .action(ArgAction::SetTrue)
+            .group(GROUP)
             .help_heading(HELP_HEADING)
     ];
     args.sort_unstable_by(|x, y| x.get_id().cmp(y.get_id()));
@@ -414,7 +665,7 @@ fn add_encode_options() -> (Vec<Arg>, ArgGroup) {
 }
 
 fn add_filters() -> (Vec<Arg>, ArgGroup) {
-    static GROUP: &str = "filters";
+    static GROUP: &str = FILTERS_GROUP;
 
     let mut args = [
         Arg::new("box-blur")
@@ -433,11 +684,88 @@ fn add_filters() -> (Vec<Arg>, ArgGroup) {
             .help_heading(GROUP)
             .value_parser(value_parser!(f32))
             .group(GROUP),
+        Arg::new("motion-blur")
+            .long("motion-blur")
+            .allow_hyphen_values(true)
+            .help("Blur along a line at angle degrees (0 is horizontal), over distance pixels")
+            .value_names(["angle", "distance"])
+            .num_args(2)
+            .help_heading(GROUP)
+            .value_parser(value_parser!(f32))
+            .group(GROUP),
+        Arg::new("zoom-blur")
+            .long("zoom-blur")
+            .help("Blur towards the image center, over a fraction (0-1) of the distance to it")
+            .value_name("amount")
+            .help_heading(GROUP)
+            .value_parser(value_parser!(f32))
+            .group(GROUP),
+        Arg::new("spin-blur")
+            .long("spin-blur")
+            .help("Blur along circles around the image center, over angle degrees")
+            .value_name("angle")
+            .help_heading(GROUP)
+            .value_parser(value_parser!(f32))
+            .group(GROUP),
+        Arg::new("kuwahara")
+            .long("kuwahara")
+            .help("Perform a Kuwahara filter, edge preserving painterly smoothing")
+            .value_name("radius")
+            .help_heading(GROUP)
+            .value_parser(value_parser!(usize))
+            .group(GROUP),
+        Arg::new("anisotropic-kuwahara")
+            .long("anisotropic-kuwahara")
+            .help("Perform an anisotropic Kuwahara filter, following the edges of the image")
+            .value_name("radius")
+            .help_heading(GROUP)
+            .value_parser(value_parser!(usize))
+            .group(GROUP),
+        Arg::new("oil-paint")
+            .long("oil-paint")
+            .help("Give the image the look of an oil painting, with levels (2-256) of intensity")
+            .value_names(["radius", "levels"])
+            .num_args(2)
+            .help_heading(GROUP)
+            .value_parser(value_parser!(usize))
+            .group(GROUP),
+        Arg::new("halftone")
+            .long("halftone")
+            .help("Render the image as printed halftone dots, cell_size pixels apart on a screen rotated by angle (default 45)")
+            .value_names(["cell_size", "angle"])
+            .num_args(1..=2)
+            .help_heading(GROUP)
+            .value_parser(value_parser!(f32))
+            .group(GROUP),
+        Arg::new("ordered-dither")
+            .long("ordered-dither")
+            .help("Reduce every channel to levels (default 2) with a size x size Bayer matrix (2,4,8 or 16), each entry covering cell_size (default 1) pixels")
+            .value_names(["size", "levels", "cell_size"])
+            .num_args(1..=3)
+            .help_heading(GROUP)
+            .value_parser(value_parser!(u16))
+            .group(GROUP),
+        Arg::new("high-pass")
+            .long("high-pass")
+            .help("Keep details smaller than a gaussian blur of sigma, around mid gray")
+            .value_name("sigma")
+            .help_heading(GROUP)
+            .value_parser(value_parser!(f32))
+            .group(GROUP),
+        Arg::new("retinex")
+            .long("retinex")
+            .help("Even out dark or unevenly lit images with multi-scale retinex, e.g. 15 80 250")
+            .value_name("scales")
+            .num_args(1..)
+            .help_heading(GROUP)
+            .value_parser(value_parser!(f32))
+            .group(GROUP),
         Arg::new("unsharpen")
             .long("unsharpen")
-            .help("Perform an unsharp mask")
+            .help("Perform an unsharp mask, adding percentage (default 100) of the detail whose difference exceeds threshold (0-255)")
             .help_heading(GROUP)
-            .value_names(["sigma", "threshold"])
+            .value_names(["sigma", "threshold", "percentage"])
+            .num_args(2..=3)
             .value_parser(value_parser!(f32))
             .group(GROUP),
         Arg::new("statistic")
@@ -474,6 +802,36 @@ fn add_filters() -> (Vec<Arg>, ArgGroup) {
             .num_args(..=49)
             .action(ArgAction::Append)
             .value_parser(value_parser!(f32)),
+        Arg::new("emboss")
+            .long("emboss")
+            .help("Emboss the image, making it look raised as if lit from direction")
+            .value_name("direction")
+            .help_heading(GROUP)
+            .value_parser([
+                "north",
+                "north-east",
+                "east",
+                "south-east",
+                "south",
+                "south-west",
+                "west",
+                "north-west"
+            ])
+            .group(GROUP),
+        Arg::new("sharpen")
+            .long("sharpen")
+            .help("Sharpen the image with a 3x3 kernel, amount 1.0 is a common default")
+            .value_name("amount")
+            .help_heading(GROUP)
+            .value_parser(value_parser!(f32))
+            .group(GROUP),
+        Arg::new("edge-enhance")
+            .long("edge-enhance")
+            .help("Enhance edges in every direction with a 3x3 kernel, amount 0.5 is a common default")
+            .value_name("amount")
+            .help_heading(GROUP)
+            .value_parser(value_parser!(f32))
+            .group(GROUP),
         Arg::new("median-blur")
             .long("median-blur")
             .help("Perform a median blur on an image, this replaces a pixel with the median of it's neighbours")
@@ -8,7 +8,7 @@
 
 use clap::ArgMatches;
 use zune_core::colorspace::ColorSpace;
-use zune_core::options::{DecoderOptions, EncoderOptions};
+use zune_core::options::{ChromaSubsampling, DecoderOptions, EncoderOptions};
 
 pub mod global_options;
 
@@ -21,12 +21,15 @@ pub fn decoder_options(options: &ArgMatches) -> DecoderOptions {
     let use_unsafe = !*options.get_one::<bool>("safe").unwrap();
     let strict_mode = *options.get_one::<bool>("strict").unwrap();
     let jpeg_grayscale = *options.get_one::<bool>("jpeg-grayscale").unwrap_or(&false);
+    let decode_animated = !*options.get_one::<bool>("still").unwrap();
 
     let mut options = DecoderOptions::new_cmd()
         .set_max_height(max_height)
         .set_max_width(max_width)
         .set_use_unsafe(use_unsafe)
-        .set_strict_mode(strict_mode);
+        .set_strict_mode(strict_mode)
+        .png_set_decode_animated(decode_animated)
+        .jxl_set_decode_animated(decode_animated);
 
     if jpeg_grayscale {
         options = options.jpeg_set_out_colorspace(ColorSpace::Luma);
@@ -39,12 +42,28 @@ pub fn encoder_options(options: &ArgMatches) -> EncoderOptions {
     let encode_threads = *options.get_one::<u8>("encode-threads").unwrap();
     let effort = *options.get_one::<u8>("effort").unwrap();
     let progressive = options.contains_id("progressive");
-    let strip_metadata = options.contains_id("strip");
+    let strip_metadata = options.get_flag("strip");
+    let deterministic = options.get_flag("deterministic");
+    let subsampling = match options.get_one::<String>("subsampling").map(|x| x.as_str()) {
+        Some("444") => ChromaSubsampling::Yuv444,
+        Some("440") => ChromaSubsampling::Yuv440,
+        Some("422") => ChromaSubsampling::Yuv422,
+        Some("420") => ChromaSubsampling::Yuv420,
+        Some("source") => ChromaSubsampling::SameAsSource,
+        _ => ChromaSubsampling::Auto
+    };
 
-    EncoderOptions::default()
+    let opts = EncoderOptions::default()
         .set_quality(quality)
         .set_num_threads(encode_threads)
         .set_effort(effort)
         .set_strip_metadata(strip_metadata)
         .set_jpeg_encode_progressive(progressive)
+        .set_deterministic(deterministic)
+        .set_jpeg_subsampling(subsampling);
+
+    if let Some(max_size) = options.get_one::<usize>("max-size") {
+        return opts.set_target_size(*max_size);
+    }
+    opts
 }
@@ -10,9 +10,15 @@ use clap::ArgMatches;
 use log::debug;
 use zune_image::pipelines::Pipeline;
 use zune_imageprocs::box_blur::BoxBlur;
-use zune_imageprocs::convolve::Convolve;
+use zune_imageprocs::convolve::{Convolve, EmbossDirection};
 use zune_imageprocs::gaussian_blur::GaussianBlur;
+use zune_imageprocs::halftone::{Halftone, OrderedDither};
+use zune_imageprocs::high_pass::HighPass;
+use zune_imageprocs::kuwahara::{AnisotropicKuwahara, Kuwahara};
 use zune_imageprocs::median::Median;
+use zune_imageprocs::motion_blur::{MotionBlur, RadialBlur};
+use zune_imageprocs::oil_paint::OilPaint;
+use zune_imageprocs::retinex::Retinex;
 use zune_imageprocs::scharr::Scharr;
 use zune_imageprocs::sobel::Sobel;
 use zune_imageprocs::spatial::SpatialOps;
@@ -35,18 +41,90 @@ pub fn parse_options(
 
         let gaussian_blur = GaussianBlur::new(sigma);
         workflow.chain_operations(Box::new(gaussian_blur));
+    } else if argument == "halftone" {
+        let values: Vec<f32> = args.get_many::<f32>(argument).unwrap().copied().collect();
+        let angle = values.get(1).copied().unwrap_or(45.0);
+        debug!(
+            "Added halftone filter with cell size {} and angle {}",
+            values[0], angle
+        );
+
+        workflow.chain_operations(Box::new(Halftone::new(values[0]).set_angle(angle)));
+    } else if argument == "ordered-dither" {
+        let values: Vec<u16> = args.get_many::<u16>(argument).unwrap().copied().collect();
+        let levels = values.get(1).copied().unwrap_or(2);
+        let cell_size = values.get(2).copied().unwrap_or(1);
+        debug!(
+            "Added ordered dither filter with a {0}x{0} matrix, {1} levels and cell size {2}",
+            values[0], levels, cell_size
+        );
+
+        let dither = OrderedDither::new(usize::from(values[0]), levels)
+            .set_cell_size(usize::from(cell_size));
+        workflow.chain_operations(Box::new(dither));
+    } else if argument == "high-pass" {
+        let sigma = *args.get_one::<f32>(argument).unwrap();
+        debug!("Added high pass filter with sigma {}", sigma);
+
+        workflow.chain_operations(Box::new(HighPass::new(sigma)));
+    } else if argument == "retinex" {
+        let scales: Vec<f32> = args.get_many::<f32>(argument).unwrap().copied().collect();
+        debug!("Added retinex filter with scales {:?}", scales);
+
+        workflow.chain_operations(Box::new(Retinex::new(scales)));
+    } else if argument == "motion-blur" {
+        let values: Vec<f32> = args.get_many::<f32>(argument).unwrap().copied().collect();
+        debug!(
+            "Added motion blur filter with angle {} and distance {}",
+            values[0], values[1]
+        );
+
+        workflow.chain_operations(Box::new(MotionBlur::new(values[0], values[1])));
+    } else if argument == "zoom-blur" {
+        let amount = *args.get_one::<f32>(argument).unwrap();
+        debug!("Added zoom blur filter with amount {}", amount);
+
+        workflow.chain_operations(Box::new(RadialBlur::zoom(amount)));
+    } else if argument == "spin-blur" {
+        let angle = *args.get_one::<f32>(argument).unwrap();
+        debug!("Added spin blur filter with angle {}", angle);
+
+        workflow.chain_operations(Box::new(RadialBlur::spin(angle)));
+    } else if argument == "kuwahara" {
+        let radius = *args.get_one::<usize>(argument).unwrap();
+        debug!("Added kuwahara filter with radius {}", radius);
+
+        workflow.chain_operations(Box::new(Kuwahara::new(radius)));
+    } else if argument == "anisotropic-kuwahara" {
+        let radius = *args.get_one::<usize>(argument).unwrap();
+        debug!("Added anisotropic kuwahara filter with radius {}", radius);
+
+        workflow.chain_operations(Box::new(AnisotropicKuwahara::new(radius)));
+    } else if argument == "oil-paint" {
+        let values: Vec<usize> = args.get_many::<usize>(argument).unwrap().copied().collect();
+        debug!(
+            "Added oil paint filter with radius {} and {} levels",
+            values[0], values[1]
+        );
+
+        workflow.chain_operations(Box::new(OilPaint::new(values[0], values[1])));
     } else if argument == "unsharpen" {
         // parse first one as threshold
         let values: Vec<f32> = args.get_many::<f32>(argument).unwrap().copied().collect();
         let sigma_f32 = values[0];
         let threshold_u16 = values[1];
+        let percentage = values.get(2).copied().unwrap_or(100.0);
 
         debug!(
-            "Added unsharpen filter with sigma={} and threshold={}",
-            sigma_f32, threshold_u16
+            "Added unsharpen filter with sigma={}, threshold={} and percentage={}",
+            sigma_f32, threshold_u16, percentage
         );
 
-        let unsharpen = Unsharpen::new(sigma_f32, threshold_u16 as u16, 0);
+        let unsharpen = Unsharpen::new(
+            sigma_f32,
+            threshold_u16 as u16,
+            percentage.clamp(0.0, 255.0) as u8
+        );
         workflow.chain_operations(Box::new(unsharpen));
     } else if argument == "mean-blur" {
         let radius = *args.get_one::<usize>(argument).unwrap();
@@ -72,6 +150,22 @@ pub fn parse_options(
             .collect();
 
         workflow.chain_operations(Box::new(Convolve::new(values, 1.0)));
+    } else if argument == "emboss" {
+        let direction = args.get_one::<String>(argument).unwrap();
+        debug!("Added emboss filter lit from the {}", direction);
+
+        let direction = EmbossDirection::from_string_result(direction)?;
+        workflow.chain_operations(Box::new(Convolve::emboss(direction)));
+    } else if argument == "sharpen" {
+        let amount = *args.get_one::<f32>(argument).unwrap();
+        debug!("Added sharpen filter with amount {}", amount);
+
+        workflow.chain_operations(Box::new(Convolve::sharpen(amount)));
+    } else if argument == "edge-enhance" {
+        let amount = *args.get_one::<f32>(argument).unwrap();
+        debug!("Added edge enhance filter with amount {}", amount);
+
+        workflow.chain_operations(Box::new(Convolve::edge_enhance(amount)));
     } else if argument == "median-blur" {
         let radius = *args.get_one::<usize>(argument).unwrap();
 
@@ -12,20 +12,30 @@ use zune_core::bit_depth::BitDepth;
 use zune_core::colorspace::ColorSpace;
 use zune_image::core_filters::colorspace::ColorspaceConv;
 use zune_image::core_filters::depth::Depth;
+use zune_image::core_filters::orientation::AutoOrient;
+use zune_image::metadata::RegionOfInterest;
 use zune_image::pipelines::Pipeline;
+use zune_imageprocs::auto_exposure::AutoExposure;
+use zune_imageprocs::barcode_preprocess::BarcodePreprocess;
 use zune_imageprocs::brighten::Brighten;
 use zune_imageprocs::contrast::Contrast;
 use zune_imageprocs::crop::Crop;
 use zune_imageprocs::exposure::Exposure;
+use zune_imageprocs::flatten::{Flatten, FlattenBackground};
 use zune_imageprocs::flip::{Flip, FlipDirection};
 use zune_imageprocs::gamma::Gamma;
 use zune_imageprocs::hsv_adjust::HsvAdjust;
 use zune_imageprocs::invert::Invert;
+use zune_imageprocs::lut3d::{ApplyLut3d, Lut3d, LutInterpolation};
 use zune_imageprocs::mirror::{Mirror, MirrorMode};
 use zune_imageprocs::resize::{Resize, ResizeMethod};
+use zune_imageprocs::roi::SetRegionsOfInterest;
 use zune_imageprocs::rotate::Rotate;
+use zune_imageprocs::shape_mask::{MaskShape, ShapeMask};
+use zune_imageprocs::smart_crop::{SmartCrop, SmartCropMethod};
 use zune_imageprocs::spatial::SpatialOps;
 use zune_imageprocs::spatial_ops::SpatialOperations;
+use zune_imageprocs::sprite_sheet::{SplitSpriteSheet, SpriteSheetManifest};
 use zune_imageprocs::stretch_contrast::StretchContrast;
 use zune_imageprocs::threshold::{Threshold, ThresholdMethod};
 use zune_imageprocs::transpose::Transpose;
@@ -99,6 +109,38 @@ pub fn parse_options(
         );
 
         workflow.chain_operations(Box::new(crop));
+    } else if argument == "smart-crop" {
+        let values: Vec<&String> = args.get_many::<String>(argument).unwrap().collect();
+
+        let width = str::parse::<usize>(values[0]).map_err(|x| x.to_string())?;
+        let height = str::parse::<usize>(values[1]).map_err(|x| x.to_string())?;
+        let method = match values.get(2) {
+            Some(value) => SmartCropMethod::from_string_result(value)?,
+            None => SmartCropMethod::default()
+        };
+        debug!(
+            "Added smart crop with width:{}, height:{} and method {:?}",
+            width, height, method
+        );
+        workflow.chain_operations(Box::new(SmartCrop::new(width, height).set_method(method)));
+    } else if argument == "shape-mask" {
+        let values: Vec<&String> = args.get_many::<String>(argument).unwrap().collect();
+
+        let shape = match (values[0].as_str(), values.get(1)) {
+            ("rounded", Some(radius)) => MaskShape::RoundedRect {
+                radius: str::parse::<f32>(radius).map_err(|x| x.to_string())?
+            },
+            ("rounded", None) => return Err("Rounded shape masks need a radius".to_string()),
+            ("circle", None) => MaskShape::Circle,
+            ("ellipse", None) => MaskShape::Ellipse,
+            (shape, _) => {
+                return Err(format!(
+                    "Unknown shape {shape}, expected rounded <radius>, circle or ellipse"
+                ))
+            }
+        };
+        debug!("Added shape mask with shape {:?}", shape);
+        workflow.chain_operations(Box::new(ShapeMask::new(shape)));
     } else if argument == "threshold" {
         let val: Vec<&String> = args.get_many::<String>(argument).unwrap().collect();
 
@@ -129,6 +171,25 @@ pub fn parse_options(
         );
         let stretch_contrast = StretchContrast::new(lower, upper);
         workflow.chain_operations(Box::new(stretch_contrast));
+    } else if argument == "lut" {
+        let values: Vec<&String> = args.get_many::<String>(argument).unwrap().collect();
+
+        let contents = std::fs::read_to_string(values[0])
+            .map_err(|x| format!("Could not read LUT file {}: {x}", values[0]))?;
+        let lut = Lut3d::from_cube(&contents).map_err(|x| x.to_string())?;
+        let interpolation = match values.get(1) {
+            Some(value) => LutInterpolation::from_string_result(value)?,
+            None => LutInterpolation::default()
+        };
+        debug!(
+            "Added 3D LUT {:?} of size {} with {:?} interpolation",
+            values[0],
+            lut.size(),
+            interpolation
+        );
+        workflow.chain_operations(Box::new(
+            ApplyLut3d::new(lut).set_interpolation(interpolation)
+        ));
     } else if argument == "gamma" {
         let value = *args.get_one::<f32>(argument).unwrap();
         debug!("Added gamma filter with value {}", value);
@@ -179,7 +240,56 @@ pub fn parse_options(
         workflow.chain_operations(Box::new(ColorspaceConv::new(colorspace)));
     } else if argument == "auto-orient" {
         debug!("Add auto orient operation");
-        //workflow.add_operation(Box::new(AutoOrient))
+        workflow.chain_operations(Box::new(AutoOrient));
+    } else if argument == "roi" {
+        let regions: Vec<RegionOfInterest> = args
+            .get_many::<RegionOfInterest>(argument)
+            .unwrap()
+            .copied()
+            .collect();
+        debug!("Added {} regions of interest", regions.len());
+        workflow.chain_operations(Box::new(SetRegionsOfInterest::new(regions)));
+    } else if argument == "auto-exposure" {
+        debug!("Added auto exposure operation");
+        workflow.chain_operations(Box::new(AutoExposure::new()));
+    } else if argument == "barcode-preprocess" {
+        let values: Vec<f32> = args.get_many::<f32>(argument).unwrap().copied().collect();
+        let radius = values[0].max(1.0) as usize;
+        let offset = values.get(1).copied().unwrap_or(0.04);
+        debug!(
+            "Added barcode preprocess operation with radius {} and offset {}",
+            radius, offset
+        );
+
+        let preprocess = BarcodePreprocess::new()
+            .set_radius(radius)
+            .set_offset(offset);
+        workflow.chain_operations(Box::new(preprocess));
+    } else if argument == "flatten" {
+        let value = args.get_one::<String>(argument).unwrap();
+        let background = FlattenBackground::from_string_result(value)?;
+        debug!("Added flatten operation with background {:?}", background);
+
+        workflow.chain_operations(Box::new(Flatten::new(background)));
+    } else if argument == "split-sprite-sheet" {
+        let values: Vec<&String> = args.get_many::<String>(argument).unwrap().collect();
+
+        let split = if let [width, height] = values[..] {
+            let parse = |x: &String| {
+                x.parse::<usize>()
+                    .map_err(|e| format!("Invalid sprite size {x}: {e}"))
+            };
+            SplitSpriteSheet::new(parse(width)?, parse(height)?)
+        } else {
+            let contents = std::fs::read_to_string(values[0])
+                .map_err(|x| format!("Could not read sprite manifest {}: {x}", values[0]))?;
+            let manifest = serde_json::from_str::<SpriteSheetManifest>(&contents)
+                .map_err(|x| format!("Invalid sprite manifest {}: {x}", values[0]))?;
+            SplitSpriteSheet::from_manifest(&manifest)
+        };
+        debug!("Added split sprite sheet operation {:?}", split);
+
+        workflow.chain_operations(Box::new(split));
     } else if argument == "exposure" {
         let exposure = *args.get_one::<f32>(argument).unwrap();
 
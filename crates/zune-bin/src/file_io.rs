@@ -9,20 +9,38 @@ use std::ffi::OsString;
 use std::fs::File;
 use std::io::BufReader;
 
-use zune_core::bytestream::ZCursor;
+use zune_core::bytestream::{ZByteReaderTrait, ZCursor};
 use zune_core::options::DecoderOptions;
+use zune_image::codecs::ImageFormat;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
 use zune_image::traits::IntoImage;
 
+/// Decode an image, guessing the format if `format` is `None`
+fn read_image<T: ZByteReaderTrait>(
+    source: T, format: Option<ImageFormat>, options: DecoderOptions
+) -> Result<Image, ImageErrors> {
+    match format {
+        Some(format) => Image::read_with_format(source, format, options),
+        None => Image::read(source, options)
+    }
+}
+
 pub struct ZuneFile {
     file_path: OsString,
+    format:    Option<ImageFormat>,
     options:   DecoderOptions
 }
 
 impl ZuneFile {
-    pub fn new(file_path: OsString, options: DecoderOptions) -> ZuneFile {
-        ZuneFile { file_path, options }
+    pub fn new(
+        file_path: OsString, format: Option<ImageFormat>, options: DecoderOptions
+    ) -> ZuneFile {
+        ZuneFile {
+            file_path,
+            format,
+            options
+        }
     }
 }
 
@@ -31,21 +49,49 @@ impl IntoImage for ZuneFile {
         // read file
         let fd = BufReader::new(File::open(self.file_path.clone())?);
 
-        Image::read(fd, self.options)
+        read_image(fd, self.format, self.options)
     }
 }
 
 pub struct ZuneMem<T: AsRef<[u8]>> {
     source:  T,
+    format:  Option<ImageFormat>,
     options: DecoderOptions
 }
 impl<T: AsRef<[u8]>> ZuneMem<T> {
-    pub fn new(source: T, options: DecoderOptions) -> ZuneMem<T> {
-        ZuneMem { source, options }
+    pub fn new(source: T, format: Option<ImageFormat>, options: DecoderOptions) -> ZuneMem<T> {
+        ZuneMem {
+            source,
+            format,
+            options
+        }
     }
 }
 impl<T: AsRef<[u8]>> IntoImage for ZuneMem<T> {
     fn into_image(&mut self) -> Result<Image, ImageErrors> {
-        Image::read(ZCursor::new(self.source.as_ref()), self.options)
+        read_image(
+            ZCursor::new(self.source.as_ref()),
+            self.format,
+            self.options
+        )
+    }
+}
+
+/// An image that was already decoded, e.g. one created from several inputs
+pub struct ZuneImage {
+    image: Option<Image>
+}
+
+impl ZuneImage {
+    pub fn new(image: Image) -> ZuneImage {
+        ZuneImage { image: Some(image) }
+    }
+}
+
+impl IntoImage for ZuneImage {
+    fn into_image(&mut self) -> Result<Image, ImageErrors> {
+        self.image
+            .take()
+            .ok_or(ImageErrors::GenericStr("Image was already taken"))
     }
 }
@@ -5,6 +5,15 @@
  *
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
+//! The zune command line tool
+//!
+//! ## Optional features
+//! These are off by default, so the options they add are missing from
+//! the default build
+//!
+//! - `watch`: The `--watch` and `--watch-out` options, which process images as they
+//!   appear in a directory, e.g. `cargo install zune-bin --features watch`
+//! - `server`: The `zune-server` binary, which resizes images on demand over HTTP
 extern crate core;
 
 use std::process::exit;
@@ -18,8 +27,13 @@ mod cmd_parsers;
 mod file_io;
 mod probe_files;
 mod serde;
+#[cfg(feature = "server")]
+pub mod server;
 mod show_gui;
+#[cfg(feature = "watch")]
+mod watch;
 mod workflow;
+mod workflow_file;
 
 pub fn main() {
     let cmd = cmd_args::create_cmd_args();
@@ -32,13 +46,13 @@ pub fn main() {
     let result = create_and_exec_workflow_from_cmd(&options, &parsed_opts);
 
     if result.is_err() {
-        println!();
+        eprintln!();
         error!(
             " Could not complete workflow, reason {:?}",
             result.err().unwrap()
         );
 
-        println!();
+        eprintln!();
         exit(-1);
     }
 }
@@ -0,0 +1,618 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! A small HTTP server resizing images on demand
+//!
+//! Enabled with the `server` feature, it's run with the `zune-server` binary
+//!
+//! - `POST /resize?w=800&format=jpg` resizes the image sent in the request body
+//! - `GET /resize/photos/cat.png?w=800&h=600` resizes a file below `--root`
+//! - `GET /stats` returns request counts and timings as JSON
+//!
+//! Query parameters are all optional
+//! - `w`, `h`: The output size, if only one is given the other one keeps the aspect ratio
+//! - `quality`: The encoder quality, from 0 to 100
+//! - `format`: The output format as a file extension, e.g. `png`, defaults to the input format
+//!
+//! Untrusted input is bounded by the request body size and the maximum image
+//! dimensions, which apply both to decoded and requested images. Only the first
+//! frame of animated images is decoded and served, and a panic while processing
+//! a request is answered with `500 Internal Server Error`.
+//!
+//! Each response carries the time spent decoding, resizing and encoding in
+//! an `X-Processing-Time` header, together with `/stats` this makes the server
+//! usable for load testing the library with any HTTP benchmarking tool.
+use std::io::Read;
+use std::net::SocketAddr;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use clap::{value_parser, Arg, Command};
+use log::{error, info, Level};
+use tiny_http::{Header, Method, Request, Response, Server};
+use zune_core::bytestream::ZCursor;
+use zune_core::options::{DecoderOptions, EncoderOptions};
+use zune_image::codecs::ImageFormat;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+use zune_imageprocs::resize::{Resize, ResizeMethod};
+
+/// Limits protecting the server from large or malicious inputs
+#[derive(Copy, Clone, Debug)]
+pub struct Limits {
+    /// Maximum size of a request body or a file served from the root
+    pub max_body_size: usize,
+    /// Maximum width of input and output images
+    pub max_width:     usize,
+    /// Maximum height of input and output images
+    pub max_height:    usize
+}
+
+/// An error returned to the client
+#[derive(Debug, Eq, PartialEq)]
+pub struct HttpError {
+    pub status:  u16,
+    pub message: String
+}
+
+impl HttpError {
+    fn new(status: u16, message: impl Into<String>) -> HttpError {
+        HttpError {
+            status,
+            message: message.into()
+        }
+    }
+}
+
+/// Options of a single resize request, parsed from the query string
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ResizeRequest {
+    pub width:   Option<usize>,
+    pub height:  Option<usize>,
+    pub quality: Option<u8>,
+    pub format:  Option<ImageFormat>
+}
+
+impl ResizeRequest {
+    /// Parse the query string of a request, without the leading `?`
+    pub fn from_query(query: &str) -> Result<ResizeRequest, HttpError> {
+        let mut request = ResizeRequest::default();
+
+        for pair in query.split('&').filter(|x| !x.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let invalid = || HttpError::new(400, format!("Invalid value for \"{key}\": {value:?}"));
+
+            match key {
+                "w" => request.width = Some(value.parse().map_err(|_| invalid())?),
+                "h" => request.height = Some(value.parse().map_err(|_| invalid())?),
+                "quality" => {
+                    request.quality = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|x| *x <= 100)
+                            .ok_or_else(invalid)?
+                    )
+                }
+                "format" => {
+                    let format = ImageFormat::encoder_for_extension(value.to_ascii_lowercase())
+                        .ok_or_else(|| {
+                            HttpError::new(415, format!("Cannot encode to {value:?}"))
+                        })?;
+                    request.format = Some(format);
+                }
+                _ => return Err(HttpError::new(400, format!("Unknown parameter \"{key}\"")))
+            }
+        }
+        if request.width == Some(0) || request.height == Some(0) {
+            return Err(HttpError::new(
+                400,
+                "Width and height must be greater than zero"
+            ));
+        }
+        Ok(request)
+    }
+
+    /// Return the output size for an image of `width` by `height`,
+    /// a missing dimension is computed from the aspect ratio
+    ///
+    /// Returns `None` if the size overflows, which only happens for
+    /// absurdly large requested dimensions
+    pub fn output_size(&self, width: usize, height: usize) -> Option<(usize, usize)> {
+        let scale = |value: usize, to: usize, from: usize| {
+            let scaled = value.checked_mul(to)?.checked_add(from / 2)?;
+            Some((scaled / from.max(1)).max(1))
+        };
+        match (self.width, self.height) {
+            (Some(w), Some(h)) => Some((w, h)),
+            (Some(w), None) => Some((w, scale(height, w, width)?)),
+            (None, Some(h)) => Some((scale(width, h, height)?, h)),
+            (None, None) => Some((width, height))
+        }
+    }
+}
+
+/// Counters shared by all workers, reported by `/stats`
+#[derive(Default)]
+pub struct Stats {
+    requests:          AtomicU64,
+    failures:          AtomicU64,
+    bytes_in:          AtomicU64,
+    bytes_out:         AtomicU64,
+    processing_micros: AtomicU64
+}
+
+impl Stats {
+    /// Record a request, `bytes_out` is `None` if it failed
+    fn record(&self, bytes_in: usize, bytes_out: Option<usize>, elapsed: Duration) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.bytes_in.fetch_add(bytes_in as u64, Ordering::Relaxed);
+        self.processing_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+
+        match bytes_out {
+            Some(bytes) => {
+                self.bytes_out.fetch_add(bytes as u64, Ordering::Relaxed);
+            }
+            None => {
+                self.failures.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let requests = self.requests.load(Ordering::Relaxed);
+        let micros = self.processing_micros.load(Ordering::Relaxed);
+
+        serde_json::json!({
+            "requests": requests,
+            "failures": self.failures.load(Ordering::Relaxed),
+            "bytes_in": self.bytes_in.load(Ordering::Relaxed),
+            "bytes_out": self.bytes_out.load(Ordering::Relaxed),
+            "mean_processing_micros": micros.checked_div(requests).unwrap_or(0)
+        })
+    }
+}
+
+/// Decode `data`, resize it and encode it as requested
+///
+/// Returns the encoded image and its format
+pub fn process_image(
+    data: &[u8], request: &ResizeRequest, limits: &Limits
+) -> Result<(Vec<u8>, ImageFormat), HttpError> {
+    let (input_format, _) = ImageFormat::guess_format(ZCursor::new(data))
+        .ok_or_else(|| HttpError::new(415, "Unknown image format"))?;
+
+    if !input_format.has_decoder() {
+        return Err(HttpError::new(
+            415,
+            format!("Cannot decode {input_format:?} images")
+        ));
+    }
+    let options = DecoderOptions::new_safe()
+        .set_max_width(limits.max_width)
+        .set_max_height(limits.max_height);
+
+    // only decode the first frame, so an animation can't multiply the
+    // decoded size past the dimension limits
+    let mut image = Image::read_frame(ZCursor::new(data), options, 0)
+        .map_err(|e| HttpError::new(422, format!("Cannot decode image: {e:?}")))?;
+
+    let (width, height) = image.dimensions();
+    let (out_width, out_height) = request
+        .output_size(width, height)
+        .filter(|(w, h)| *w <= limits.max_width && *h <= limits.max_height)
+        .ok_or_else(|| {
+            HttpError::new(
+                400,
+                format!(
+                    "Output size exceeds the limit of {}x{}",
+                    limits.max_width, limits.max_height
+                )
+            )
+        })?;
+
+    if (out_width, out_height) != (width, height) {
+        Resize::new(out_width, out_height, ResizeMethod::Bilinear)
+            .execute(&mut image)
+            .map_err(|e| HttpError::new(500, format!("Cannot resize image: {e:?}")))?;
+    }
+
+    let format = match request.format {
+        Some(format) => format,
+        None if input_format.has_encoder() => input_format,
+        None => ImageFormat::PNG
+    };
+    let mut encoder_options = EncoderOptions::default();
+
+    if let Some(quality) = request.quality {
+        encoder_options = encoder_options.set_quality(quality);
+    }
+    let mut output = vec![];
+    format
+        .encode(&image, encoder_options, &mut output)
+        .map_err(|e| HttpError::new(500, format!("Cannot encode image: {e:?}")))?;
+
+    Ok((output, format))
+}
+
+/// Return the file below `root` a request path points to
+///
+/// Only plain path components are accepted so that requests
+/// can't escape the root directory
+pub fn resolve_path(root: &Path, path: &str) -> Result<PathBuf, HttpError> {
+    let relative = Path::new(path.trim_start_matches('/'));
+
+    if relative.as_os_str().is_empty()
+        || !relative
+            .components()
+            .all(|x| matches!(x, Component::Normal(_)))
+    {
+        return Err(HttpError::new(400, format!("Invalid path {path:?}")));
+    }
+    Ok(root.join(relative))
+}
+
+/// Return the MIME type for images of a format
+const fn content_type(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::JPEG => "image/jpeg",
+        ImageFormat::PNG => "image/png",
+        ImageFormat::JPEG_XL => "image/jxl",
+        ImageFormat::QOI => "image/qoi",
+        ImageFormat::PPM => "image/x-portable-anymap",
+        ImageFormat::HDR => "image/vnd.radiance",
+        ImageFormat::ICO => "image/vnd.microsoft.icon",
+        ImageFormat::TGA => "image/x-tga",
+        _ => "application/octet-stream"
+    }
+}
+
+/// Read the request body, failing if it's larger than the limit
+fn read_body(request: &mut Request, limits: &Limits) -> Result<Vec<u8>, HttpError> {
+    let too_large = || {
+        HttpError::new(
+            413,
+            format!("Request body is larger than {} bytes", limits.max_body_size)
+        )
+    };
+    if request
+        .body_length()
+        .is_some_and(|x| x > limits.max_body_size)
+    {
+        return Err(too_large());
+    }
+    let mut body = vec![];
+    request
+        .as_reader()
+        .take(limits.max_body_size as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| HttpError::new(400, format!("Cannot read request body: {e}")))?;
+
+    if body.len() > limits.max_body_size {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+/// Read a file below the root directory
+fn read_file(root: Option<&Path>, path: &str, limits: &Limits) -> Result<Vec<u8>, HttpError> {
+    let root = root.ok_or_else(|| HttpError::new(404, "No root directory configured"))?;
+    let file = resolve_path(root, path)?;
+
+    let size = std::fs::metadata(&file)
+        .ok()
+        .filter(|x| x.is_file())
+        .ok_or_else(|| HttpError::new(404, format!("{path:?} not found")))?
+        .len();
+
+    if size > limits.max_body_size as u64 {
+        return Err(HttpError::new(413, format!("{path:?} is too large")));
+    }
+    std::fs::read(&file).map_err(|e| HttpError::new(500, format!("Cannot read {path:?}: {e}")))
+}
+
+/// Handle a single request, errors are sent to the client
+fn handle(mut request: Request, root: Option<&Path>, limits: &Limits, stats: &Stats) {
+    let start = Instant::now();
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+
+    if *request.method() == Method::Get && path == "/stats" {
+        let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+        respond(
+            request,
+            Response::from_string(stats.to_json().to_string()).with_header(header)
+        );
+        return;
+    }
+
+    let input = match (request.method(), path) {
+        (Method::Post, "/resize") => read_body(&mut request, limits),
+        (Method::Get, path) if path.starts_with("/resize/") => {
+            read_file(root, &path["/resize/".len()..], limits)
+        }
+        _ => Err(HttpError::new(
+            404,
+            format!("No route for {} {path}", request.method())
+        ))
+    };
+    let bytes_in = input.as_ref().map_or(0, Vec::len);
+
+    let result = input.and_then(|data| {
+        let resize = ResizeRequest::from_query(query)?;
+
+        panic::catch_unwind(AssertUnwindSafe(|| process_image(&data, &resize, limits)))
+            .unwrap_or_else(|_| Err(HttpError::new(500, "Panicked while processing the image")))
+    });
+    let elapsed = start.elapsed();
+    let timing =
+        Header::from_bytes("X-Processing-Time", format!("{}us", elapsed.as_micros())).unwrap();
+
+    match result {
+        Ok((data, format)) => {
+            stats.record(bytes_in, Some(data.len()), elapsed);
+            let header = Header::from_bytes("Content-Type", content_type(format)).unwrap();
+
+            info!(
+                "{} {} -> {} bytes in {:?}",
+                request.method(),
+                url,
+                data.len(),
+                elapsed
+            );
+            respond(
+                request,
+                Response::from_data(data)
+                    .with_header(header)
+                    .with_header(timing)
+            );
+        }
+        Err(e) => {
+            info!("{} {} -> {} {}", request.method(), url, e.status, e.message);
+            stats.record(bytes_in, None, elapsed);
+
+            let response = Response::from_string(e.message)
+                .with_status_code(e.status)
+                .with_header(timing);
+            respond(request, response);
+        }
+    }
+}
+
+fn respond<R: Read>(request: Request, response: Response<R>) {
+    if let Err(e) = request.respond(response) {
+        error!("Could not send response, reason {}", e);
+    }
+}
+
+fn create_server_args() -> Command {
+    Command::new("zune-server")
+        .about("HTTP server resizing images on demand")
+        .arg(
+            Arg::new("addr")
+                .long("addr")
+                .help("Address to listen on")
+                .value_parser(value_parser!(SocketAddr))
+                .default_value("127.0.0.1:8080")
+        )
+        .arg(
+            Arg::new("root")
+                .long("root")
+                .help("Directory images are served from with GET /resize/<path>")
+                .value_parser(value_parser!(PathBuf))
+        )
+        .arg(
+            Arg::new("threads")
+                .long("threads")
+                .help("Number of requests handled in parallel")
+                .value_parser(value_parser!(usize))
+                .default_value("4")
+        )
+        .arg(
+            Arg::new("max-body-size")
+                .long("max-body-size")
+                .help("Maximum size in bytes of an input image")
+                .value_parser(value_parser!(usize))
+                .default_value("20000000")
+        )
+        .arg(
+            Arg::new("max-width")
+                .long("max-width")
+                .help("Maximum width of input and output images")
+                .value_parser(value_parser!(usize))
+                .default_value("8192")
+        )
+        .arg(
+            Arg::new("max-height")
+                .long("max-height")
+                .help("Maximum height of input and output images")
+                .value_parser(value_parser!(usize))
+                .default_value("8192")
+        )
+}
+
+/// Entry point of the `zune-server` binary
+pub fn main() {
+    let args = create_server_args().get_matches();
+
+    simple_logger::init_with_level(Level::Info).unwrap();
+
+    let addr = *args.get_one::<SocketAddr>("addr").unwrap();
+    let threads = (*args.get_one::<usize>("threads").unwrap()).max(1);
+    let root = args.get_one::<PathBuf>("root").cloned();
+    let limits = Limits {
+        max_body_size: *args.get_one::<usize>("max-body-size").unwrap(),
+        max_width:     *args.get_one::<usize>("max-width").unwrap(),
+        max_height:    *args.get_one::<usize>("max-height").unwrap()
+    };
+
+    let server = match Server::http(addr) {
+        Ok(server) => Arc::new(server),
+        Err(e) => {
+            error!("Could not listen on {}, reason {}", addr, e);
+            std::process::exit(-1);
+        }
+    };
+    let stats = Arc::new(Stats::default());
+
+    info!(
+        "Listening on http://{} with {} threads, {:?}",
+        addr, threads, limits
+    );
+
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            let (server, stats, root) = (server.clone(), stats.clone(), root.clone());
+
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    let handled = panic::catch_unwind(AssertUnwindSafe(|| {
+                        handle(request, root.as_deref(), &limits, &stats)
+                    }));
+
+                    if handled.is_err() {
+                        error!("Panicked while handling a request");
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::codecs::ImageFormat;
+    use zune_image::frame::Frame;
+    use zune_image::image::Image;
+
+    use crate::server::{process_image, resolve_path, Limits, ResizeRequest};
+
+    const LIMITS: Limits = Limits {
+        max_body_size: 1 << 20,
+        max_width:     1000,
+        max_height:    1000
+    };
+
+    #[test]
+    fn test_parse_query() {
+        let request = ResizeRequest::from_query("w=100&quality=80&format=jpg").unwrap();
+
+        assert_eq!(request.width, Some(100));
+        assert_eq!(request.height, None);
+        assert_eq!(request.quality, Some(80));
+        assert_eq!(request.format, Some(ImageFormat::JPEG));
+        assert_eq!(request.output_size(400, 300), Some((100, 75)));
+
+        assert_eq!(
+            ResizeRequest::from_query("").unwrap(),
+            ResizeRequest::default()
+        );
+        assert_eq!(ResizeRequest::from_query("w=a").unwrap_err().status, 400);
+        assert_eq!(ResizeRequest::from_query("h=0").unwrap_err().status, 400);
+        assert_eq!(
+            ResizeRequest::from_query("quality=101").unwrap_err().status,
+            400
+        );
+        assert_eq!(
+            ResizeRequest::from_query("format=xyz").unwrap_err().status,
+            415
+        );
+        assert_eq!(ResizeRequest::from_query("size=1").unwrap_err().status, 400);
+    }
+
+    #[test]
+    fn test_process_image() {
+        let image = Image::fill::<u8>(100, ColorSpace::RGB, 200, 100);
+        let png = image.write_to_vec(ImageFormat::PNG).unwrap();
+
+        let request = ResizeRequest::from_query("h=50&format=qoi").unwrap();
+        let (data, format) = process_image(&png, &request, &LIMITS).unwrap();
+        assert_eq!(format, ImageFormat::QOI);
+
+        let decoded = Image::read(
+            zune_core::bytestream::ZCursor::new(&data),
+            Default::default()
+        )
+        .unwrap();
+        assert_eq!(decoded.dimensions(), (100, 50));
+
+        let too_large = ResizeRequest::from_query("w=2000").unwrap();
+        assert_eq!(
+            process_image(&png, &too_large, &LIMITS).unwrap_err().status,
+            400
+        );
+        // scaling the other dimension by this overflows
+        let overflow = ResizeRequest::from_query(&format!("w={}", usize::MAX)).unwrap();
+        assert_eq!(
+            process_image(&png, &overflow, &LIMITS).unwrap_err().status,
+            400
+        );
+
+        let small = Limits {
+            max_width: 50,
+            ..LIMITS
+        };
+        let request = ResizeRequest::default();
+        assert_eq!(
+            process_image(&png, &request, &small).unwrap_err().status,
+            422
+        );
+        assert_eq!(
+            process_image(b"abcd", &request, &LIMITS)
+                .unwrap_err()
+                .status,
+            415
+        );
+    }
+
+    #[test]
+    fn test_process_animation() {
+        let frames = (0..4)
+            .map(|i| Frame::from_u8(&[i * 50; 100 * 100 * 3], ColorSpace::RGB, 1, 10))
+            .collect();
+        let image = Image::new_frames(frames, BitDepth::Eight, 100, 100, ColorSpace::RGB);
+        let sequence = image.write_to_vec(ImageFormat::QoiSequence).unwrap();
+
+        let request = ResizeRequest::from_query("w=50").unwrap();
+        let (data, format) = process_image(&sequence, &request, &LIMITS).unwrap();
+        assert_eq!(format, ImageFormat::QoiSequence);
+
+        let decoded = Image::read(
+            zune_core::bytestream::ZCursor::new(&data),
+            Default::default()
+        )
+        .unwrap();
+        assert_eq!(decoded.dimensions(), (50, 50));
+        assert_eq!(decoded.frames_len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let root = Path::new("/srv/images");
+
+        assert_eq!(
+            resolve_path(root, "a/b.png").unwrap(),
+            Path::new("/srv/images/a/b.png")
+        );
+        assert!(resolve_path(root, "../etc/passwd").is_err());
+        assert!(resolve_path(root, "a/../../b.png").is_err());
+        assert!(resolve_path(root, "").is_err());
+    }
+}
@@ -6,23 +6,311 @@
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 
+//! Showing images to the user
+//!
+//! Images are either opened in the default image viewer of the system or, in terminals
+//! supporting a graphics protocol, drawn inline in the terminal, which also works
+//! on headless servers over SSH
+
 use std::env::temp_dir;
 use std::fs::OpenOptions;
-use std::io::BufWriter;
+use std::io::{BufWriter, Write};
 use std::time::UNIX_EPOCH;
 
-use log::trace;
+use log::{debug, trace};
+use zune_core::bit_depth::BitDepth;
+use zune_core::colorspace::ColorSpace;
 use zune_image::codecs::png::PngEncoder;
+use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::EncoderTrait;
+use zune_image::traits::{EncoderTrait, OperationsTrait};
+use zune_imageprocs::halftone::OrderedDither;
+use zune_imageprocs::resize::{Resize, ResizeMethod};
+
+/// Largest width or height of images drawn in a terminal, larger images are scaled down
+const MAX_TERMINAL_SIZE: usize = 1024;
+/// Largest amount of base64 data in a single kitty graphics command
+const KITTY_CHUNK_SIZE: usize = 4096;
+/// Levels per channel of the sixel palette, giving a 6x6x6 color cube
+const SIXEL_LEVELS: u8 = 6;
+/// Page of the image viewer, `{{IMAGE}}` is replaced with the image as JSON
+const VIEWER_HTML: &str = include_str!("viewer.html");
+
+/// How images are shown
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ViewMode {
+    /// Open the image in the default image viewer
+    App,
+    /// Kitty graphics protocol, also supported by WezTerm, Konsole and Ghostty
+    Kitty,
+    /// iTerm2 inline images, also supported by WezTerm and mintty
+    ITerm,
+    /// Sixel graphics, supported by foot, mlterm, Windows Terminal and xterm among others
+    Sixel
+}
+
+impl ViewMode {
+    /// Parse a view mode, `auto` detects the terminal with [`detect`](Self::detect)
+    pub fn from_string_result(input: &str) -> Result<Self, String> {
+        match input {
+            "auto" => Ok(Self::detect()),
+            "app" => Ok(Self::App),
+            "kitty" => Ok(Self::Kitty),
+            "iterm" => Ok(Self::ITerm),
+            "sixel" => Ok(Self::Sixel),
+            _ => Err(format!(
+                "Unknown view mode {input}, accepted values are auto, app, kitty, iterm and sixel"
+            ))
+        }
+    }
+
+    /// Guess the graphics protocol of the terminal from environment variables
+    ///
+    /// Terminals that can't be recognized open images in the default app
+    pub fn detect() -> ViewMode {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        let term = var("TERM");
+        let program = var("TERM_PROGRAM");
+
+        if !var("KITTY_WINDOW_ID").is_empty() || term.contains("kitty") || program == "ghostty" {
+            ViewMode::Kitty
+        } else if ["iTerm.app", "WezTerm", "mintty"].contains(&program.as_str()) {
+            ViewMode::ITerm
+        } else if term.contains("sixel")
+            || ["foot", "mlterm", "contour"]
+                .iter()
+                .any(|x| term.starts_with(x))
+        {
+            ViewMode::Sixel
+        } else {
+            ViewMode::App
+        }
+    }
+}
+
+/// Show the first frame of an image, terminal protocols write to stdout
+pub fn show_image(image: &Image, mode: ViewMode) -> Result<(), ImageErrors> {
+    debug!("Showing image using {:?}", mode);
+
+    if mode == ViewMode::App {
+        return open_in_default_app(image);
+    }
+    let image = fit_to_terminal(image)?;
+    let mut stdout = BufWriter::new(std::io::stdout().lock());
+
+    match mode {
+        ViewMode::Kitty => write_kitty(&encode_png(&image)?, &mut stdout)?,
+        ViewMode::ITerm => write_iterm(&encode_png(&image)?, &mut stdout)?,
+        _ => write_sixel(&image, &mut stdout)?
+    }
+    stdout.write_all(b"\n")?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Return the first frame of an image as 8 bit RGBA, scaled down to
+/// fit [`MAX_TERMINAL_SIZE`]
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn fit_to_terminal(image: &Image) -> Result<Image, ImageErrors> {
+    let Some(frame) = image.frames_ref().first() else {
+        return Err(ImageErrors::GenericStr("Image has no frames to show"));
+    };
+    let (width, height) = image.dimensions();
+    let mut first = Image::new_frames(
+        vec![frame.clone()],
+        image.depth(),
+        width,
+        height,
+        image.colorspace()
+    );
+    first.convert_color(ColorSpace::RGBA)?;
+    first.convert_depth(BitDepth::Eight)?;
 
-pub fn open_in_default_app(image: &Image) {
+    let largest = width.max(height);
+    if largest > MAX_TERMINAL_SIZE {
+        let scale = MAX_TERMINAL_SIZE as f32 / largest as f32;
+        let new_width = ((width as f32 * scale).round() as usize).max(1);
+        let new_height = ((height as f32 * scale).round() as usize).max(1);
+
+        Resize::new(new_width, new_height, ResizeMethod::Bilinear).execute(&mut first)?;
+    }
+    Ok(first)
+}
+
+fn encode_png(image: &Image) -> Result<Vec<u8>, ImageErrors> {
+    let mut png = vec![];
+    PngEncoder::new().encode(image, &mut png)?;
+    Ok(png)
+}
+
+/// Write a PNG with the kitty graphics protocol, which splits the data into chunks
+fn write_kitty(png: &[u8], out: &mut impl Write) -> std::io::Result<()> {
+    let encoded = base64(png);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        // m=1 tells the terminal more chunks follow
+        let more = u8::from(i + 1 < chunks.len());
+        if i == 0 {
+            write!(out, "\x1b_Ga=T,f=100,m={more};")?;
+        } else {
+            write!(out, "\x1b_Gm={more};")?;
+        }
+        out.write_all(chunk)?;
+        out.write_all(b"\x1b\\")?;
+    }
+    Ok(())
+}
+
+/// Write a PNG with the iTerm2 inline images protocol
+fn write_iterm(png: &[u8], out: &mut impl Write) -> std::io::Result<()> {
+    write!(
+        out,
+        "\x1b]1337;File=inline=1;size={};preserveAspectRatio=1:{}\x07",
+        png.len(),
+        base64(png)
+    )
+}
+
+/// Write an image as sixels, quantized to a 6x6x6 color cube with ordered dithering
+///
+/// Sixels are columns of six pixels, the image is written in bands of six rows
+/// with one pass over a band for every color used in it
+fn write_sixel(image: &Image, out: &mut impl Write) -> Result<(), ImageErrors> {
+    let (width, height) = image.dimensions();
+    let mut image = image.clone();
+    image.convert_color(ColorSpace::RGB)?;
+    OrderedDither::new(4, u16::from(SIXEL_LEVELS)).execute(&mut image)?;
+
+    // dithering leaves multiples of 51, the step between levels
+    let step = u8::MAX / (SIXEL_LEVELS - 1);
+    let colors = usize::from(SIXEL_LEVELS).pow(3);
+    let indices: Vec<usize> = image.flatten_to_u8()[0]
+        .chunks_exact(3)
+        .map(|x| {
+            let [r, g, b] = [x[0], x[1], x[2]].map(|c| usize::from(c / step));
+            (r * usize::from(SIXEL_LEVELS) + g) * usize::from(SIXEL_LEVELS) + b
+        })
+        .collect();
+
+    write!(out, "\x1bPq\"1;1;{width};{height}")?;
+    for color in 0..colors {
+        let levels = usize::from(SIXEL_LEVELS);
+        let percent = |level: usize| level * 100 / (levels - 1);
+        write!(
+            out,
+            "#{color};2;{};{};{}",
+            percent(color / (levels * levels)),
+            percent(color / levels % levels),
+            percent(color % levels)
+        )?;
+    }
+    // sixel bits of every color in the current band
+    let mut bands = vec![0_u8; colors * width];
+    let mut used = vec![false; colors];
+
+    for (band, rows) in indices.chunks(width * 6).enumerate() {
+        bands.fill(0);
+        used.fill(false);
+
+        for (row, pixels) in rows.chunks_exact(width).enumerate() {
+            for (x, color) in pixels.iter().enumerate() {
+                bands[color * width + x] |= 1 << row;
+                used[*color] = true;
+            }
+        }
+        let mut first = true;
+        for (color, sixels) in bands.chunks_exact(width).enumerate() {
+            if !used[color] {
+                continue;
+            }
+            // return to the start of the band to draw the next color
+            if !first {
+                out.write_all(b"$")?;
+            }
+            first = false;
+            write!(out, "#{color}")?;
+            write_sixel_runs(sixels, out)?;
+        }
+        if (band + 1) * 6 < height {
+            out.write_all(b"-")?;
+        }
+    }
+    out.write_all(b"\x1b\\")?;
+    Ok(())
+}
+
+/// Write the sixels of a color in a band, repeated sixels are run length encoded
+fn write_sixel_runs(sixels: &[u8], out: &mut impl Write) -> std::io::Result<()> {
+    let mut runs: Vec<(u8, usize)> = vec![];
+
+    for sixel in sixels {
+        match runs.last_mut() {
+            Some((last, count)) if last == sixel => *count += 1,
+            _ => runs.push((*sixel, 1))
+        }
+    }
+    // empty sixels at the end of a line don't need to be drawn
+    if runs.last().is_some_and(|(sixel, _)| *sixel == 0) {
+        runs.pop();
+    }
+    for (sixel, count) in runs {
+        let character = char::from(63 + sixel);
+        if count > 3 {
+            write!(out, "!{count}{character}")?;
+        } else {
+            for _ in 0..count {
+                write!(out, "{character}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Encode data as standard, padded base64
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0)
+        ];
+        let group = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+
+        for i in 0..4 {
+            // n bytes need n + 1 characters, the rest is padding
+            if i <= chunk.len() {
+                encoded.push(char::from(
+                    ALPHABET[((group >> (18 - 6 * i)) & 63) as usize]
+                ));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Open an image in an HTML viewer in the default browser
+///
+/// The viewer zooms with the mouse wheel, pans by dragging, shows the
+/// coordinates and exact sample values of the pixel under the cursor
+/// and plays animations
+fn open_in_default_app(image: &Image) -> Result<(), ImageErrors> {
     let time = format!(
-        "{}.png",
+        "{}.html",
         std::time::SystemTime::now()
             .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
+            .unwrap_or_default()
+            .as_millis()
     );
     let mut path = temp_dir();
 
@@ -32,32 +320,155 @@ pub fn open_in_default_app(image: &Image) {
         .write(true)
         .truncate(true)
         .create(true)
-        .open(&path)
-        .unwrap();
+        .open(&path)?;
 
     let mut buffered = BufWriter::new(file);
+    let html = viewer_html(image)?;
+    buffered.write_all(html.as_bytes())?;
+    buffered.flush()?;
+    trace!("Wrote {:?} bytes to {:?}", html.len(), path);
 
-    let size = PngEncoder::new().encode(image, &mut buffered).unwrap();
-    trace!("Wrote {:?} bytes", size);
     #[cfg(target_os = "linux")]
     {
-        std::process::Command::new("xdg-open")
-            .arg(path.to_str().unwrap())
-            .spawn()
-            .unwrap();
+        std::process::Command::new("xdg-open").arg(&path).spawn()?;
     }
     #[cfg(target_os = "windows")]
     {
-        std::process::Command::new("start")
-            .arg(path.to_str().unwrap())
-            .spawn()
-            .unwrap();
+        std::process::Command::new("cmd")
+            .args(["/C", "start", ""])
+            .arg(&path)
+            .spawn()?;
     }
     #[cfg(target_os = "macos")]
     {
-        std::process::Command::new("open")
-            .arg(path.to_str().unwrap())
-            .spawn()
-            .unwrap();
+        std::process::Command::new("open").arg(&path).spawn()?;
+    }
+    Ok(())
+}
+
+/// Return the viewer page with all frames of an image embedded
+///
+/// Frames are stored as RGBA in the depth of the image, so the pixel
+/// inspector shows the real values of 16 bit and float images, and
+/// samples are little endian and base64 encoded
+fn viewer_html(image: &Image) -> Result<String, ImageErrors> {
+    let mut rgba = image.clone();
+    rgba.convert_color(ColorSpace::RGBA)?;
+
+    let (depth, frames): (&str, Vec<Vec<u8>>) = match image.depth() {
+        BitDepth::Sixteen => (
+            "u16",
+            rgba.flatten_frames::<u16>()
+                .iter()
+                .map(|x| x.iter().flat_map(|v| v.to_le_bytes()).collect())
+                .collect()
+        ),
+        BitDepth::Float32 => (
+            "f32",
+            rgba.flatten_frames::<f32>()
+                .iter()
+                .map(|x| x.iter().flat_map(|v| v.to_le_bytes()).collect())
+                .collect()
+        ),
+        _ => {
+            rgba.convert_depth(BitDepth::Eight)?;
+            ("u8", rgba.flatten_to_u8())
+        }
+    };
+    let frames: Vec<String> = frames
+        .iter()
+        .zip(image.frames_ref())
+        .map(|(data, frame)| {
+            let (numerator, denominator) = frame.duration();
+            let delay = numerator * 1000 / denominator.max(1);
+            format!("{{\"delay\":{delay},\"data\":\"{}\"}}", base64(data))
+        })
+        .collect();
+    let (width, height) = image.dimensions();
+
+    let json = format!(
+        "{{\"width\":{width},\"height\":{height},\"depth\":\"{depth}\",\"colorspace\":\"{:?}\",\"frames\":[{}]}}",
+        image.colorspace(),
+        frames.join(",")
+    );
+    Ok(VIEWER_HTML.replace("{{IMAGE}}", &json))
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::channel::Channel;
+    use zune_image::frame::Frame;
+    use zune_image::image::Image;
+
+    use crate::show_gui::{
+        base64, viewer_html, write_kitty, write_sixel, write_sixel_runs, ViewMode
+    };
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(&[255, 254, 253]), "//79");
+    }
+
+    #[test]
+    fn test_kitty_chunks() {
+        let mut out = vec![];
+        write_kitty(&[0; 4000], &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        // 4000 bytes are 5336 base64 characters, sent in two chunks
+        assert!(out.starts_with("\x1b_Ga=T,f=100,m=1;AAAA"));
+        assert!(out.contains("\x1b\\\x1b_Gm=0;AAAA"));
+        assert_eq!(out.matches("\x1b_G").count(), 2);
+    }
+
+    #[test]
+    fn test_sixel() {
+        let mut out = vec![];
+        write_sixel_runs(&[1, 1, 1, 1, 2, 2, 0, 0], &mut out).unwrap();
+        assert_eq!(out, b"!4@AA");
+
+        // 2x8 pixels, white above black, take two bands
+        let image = Image::from_fn::<u8, _>(2, 8, ColorSpace::RGBA, |y, _, pix| {
+            let value = if y < 3 { 255 } else { 0 };
+            pix[..4].copy_from_slice(&[value, value, value, 255]);
+        });
+        let mut out = vec![];
+        write_sixel(&image, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.starts_with("\x1bPq\"1;1;2;8#0;2;0;0;0"));
+        assert!(out.contains("#215;2;100;100;100"));
+        // first band: white in the top three rows, black in the bottom three
+        assert!(out.ends_with("#0ww$#215FF-#0BB\x1b\\"));
+
+        assert_eq!(ViewMode::from_string_result("sixel"), Ok(ViewMode::Sixel));
+        assert!(ViewMode::from_string_result("gif").is_err());
+    }
+
+    #[test]
+    fn test_viewer_html() {
+        let frame = |value: u16| Frame::new_with_duration(vec![Channel::from_elm(1, value)], 1, 25);
+        let image = Image::new_frames(
+            vec![frame(258), frame(65535)],
+            BitDepth::Sixteen,
+            1,
+            1,
+            ColorSpace::Luma
+        );
+        let html = viewer_html(&image).unwrap();
+
+        // samples keep their depth, 258 is 0x0102
+        let rgba = base64(&[2, 1, 2, 1, 2, 1, 255, 255]);
+        assert!(html.contains(&format!(
+            "{{\"width\":1,\"height\":1,\"depth\":\"u16\",\"colorspace\":\"Luma\",\"frames\":[{{\"delay\":40,\"data\":\"{rgba}\"}},"
+        )));
+        assert!(!html.contains("{{IMAGE}}"));
     }
 }
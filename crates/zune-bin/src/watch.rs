@@ -0,0 +1,139 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Watch mode, run the workflow on images as they appear in a directory
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use clap::ArgMatches;
+use log::{info, warn};
+use notify::event::{AccessKind, AccessMode, ModifyKind};
+use notify::{EventKind, RecursiveMode, Watcher};
+use zune_image::errors::ImageErrors;
+
+use crate::cmd_args::CmdImageFormats;
+use crate::workflow::exec_workflow;
+use crate::workflow_file::WorkflowFile;
+
+/// How long a file must go without changes before it's processed
+///
+/// Copying a file into the directory generates multiple events,
+/// waiting for them to settle prevents decoding half written images
+const SETTLE_TIME: Duration = Duration::from_millis(500);
+
+/// Watch `watch_dir` and run the workflow on every new or modified image,
+/// writing the results to `out_dir`
+///
+/// This only returns on error
+pub(crate) fn watch_directory(
+    args: &ArgMatches, workflow_file: Option<&WorkflowFile>, watch_dir: &Path, out_dir: &Path
+) -> Result<(), ImageErrors> {
+    std::fs::create_dir_all(out_dir)?;
+    // events carry absolute paths, canonicalize so that we can recognize
+    // and skip our own outputs when out_dir is inside watch_dir
+    let watch_dir = watch_dir.canonicalize()?;
+    let out_dir = out_dir.canonicalize()?;
+
+    let (sender, receiver) = channel();
+
+    let mut watcher = notify::recommended_watcher(sender).map_err(|e| {
+        ImageErrors::GenericString(format!("Could not create file watcher, reason {e}"))
+    })?;
+    watcher
+        .watch(&watch_dir, RecursiveMode::NonRecursive)
+        .map_err(|e| {
+            ImageErrors::GenericString(format!("Could not watch {watch_dir:?}, reason {e}"))
+        })?;
+
+    info!(
+        "Watching {:?} for images, writing results to {:?}",
+        watch_dir, out_dir
+    );
+
+    // files that changed and when they last changed
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+
+    loop {
+        match receiver.recv_timeout(SETTLE_TIME) {
+            Ok(Ok(event)) => {
+                let changed = matches!(
+                    event.kind,
+                    EventKind::Create(_)
+                        | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_))
+                        | EventKind::Modify(ModifyKind::Any)
+                        | EventKind::Access(AccessKind::Close(AccessMode::Write))
+                );
+                if changed {
+                    for path in event.paths {
+                        if !path.starts_with(&out_dir) {
+                            pending.insert(path, Instant::now());
+                        }
+                    }
+                }
+            }
+            Ok(Err(e)) => warn!("Error while watching {:?}: {}", watch_dir, e),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(ImageErrors::GenericStr("File watcher stopped unexpectedly"));
+            }
+        }
+
+        let settled: Vec<PathBuf> = pending
+            .iter()
+            .filter(|(_, changed)| changed.elapsed() >= SETTLE_TIME)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for path in settled {
+            pending.remove(&path);
+            process_file(args, workflow_file, &path, &out_dir);
+        }
+    }
+}
+
+/// Run the workflow on a single file from the watched directory
+///
+/// Errors are logged instead of returned so that one bad file doesn't
+/// stop the watcher
+fn process_file(
+    args: &ArgMatches, workflow_file: Option<&WorkflowFile>, path: &Path, out_dir: &Path
+) {
+    // removed or renamed away before we got to it
+    if !path.is_file() {
+        return;
+    }
+    let Some(file_name) = path.file_name() else {
+        return;
+    };
+    let mut out_file = out_dir.join(file_name);
+
+    if let Some(format) = args.get_one::<CmdImageFormats>("output-format") {
+        match format.extension() {
+            Some(extension) => {
+                out_file.set_extension(extension);
+            }
+            None => warn!(
+                "No file extension known for {:?}, keeping input extension",
+                format
+            )
+        }
+    }
+    info!("Processing {:?} to {:?}", path, out_file);
+
+    if let Err(e) = exec_workflow(
+        args,
+        workflow_file,
+        path.as_os_str(),
+        &[out_file.as_os_str()]
+    ) {
+        warn!("Could not process {:?}, reason {:?}", path, e);
+    }
+}
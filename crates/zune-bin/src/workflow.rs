@@ -6,25 +6,39 @@
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 
+use std::ffi::OsStr;
+use std::fmt::Debug;
 use std::fs::{File, OpenOptions};
-use std::io::{BufWriter, Read};
-use std::path::Path;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
 use std::string::String;
 use std::time::Instant;
 
+use clap::parser::RawValues;
 use clap::parser::ValueSource::CommandLine;
 use clap::ArgMatches;
-use log::{debug, error, info, trace};
+use log::{debug, error, info, trace, warn};
+use zune_core::options::{DecoderOptions, EncoderOptions};
+use zune_image::codecs::pdf::{extract_pdf_images, probe_pdf};
 use zune_image::codecs::ImageFormat;
 use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
 use zune_image::pipelines::Pipeline;
+use zune_image::traits::{IntoImage, OperationsTrait};
+use zune_imageprocs::ascii_art::{AsciiArt, AsciiStyle};
+use zune_imageprocs::montage::Montage;
+use zune_imageprocs::scopes::{Scope, ScopeType};
+use zune_imageprocs::sprite_sheet::SpriteSheet;
 
 use crate::cmd_args::CmdImageFormats;
 use crate::cmd_parsers::global_options::CmdOptions;
 use crate::cmd_parsers::{decoder_options, encoder_options};
-use crate::file_io::{ZuneFile, ZuneMem};
+use crate::file_io::{ZuneFile, ZuneImage, ZuneMem};
 use crate::probe_files::probe_input_files;
-use crate::show_gui::open_in_default_app;
+use crate::show_gui::{show_image, ViewMode};
+#[cfg(feature = "watch")]
+use crate::watch::watch_directory;
+use crate::workflow_file::{WorkflowFile, WorkflowOperation};
 
 struct CmdPipeline {
     inner:   Pipeline,
@@ -40,7 +54,6 @@ impl CmdPipeline {
 }
 
 #[allow(unused_variables)]
-#[allow(clippy::unused_io_amount)] // yes it's what I want
 pub(crate) fn create_and_exec_workflow_from_cmd(
     args: &ArgMatches, cmd_opts: &CmdOptions
 ) -> Result<(), ImageErrors> {
@@ -51,146 +64,488 @@ pub(crate) fn create_and_exec_workflow_from_cmd(
         }
     }
 
+    if let Some(file) = args.get_one::<PathBuf>("save-workflow") {
+        let json = WorkflowFile::json_from_args(args);
+        std::fs::write(file, serde_json::to_string_pretty(&json).unwrap())?;
+        info!("Saved workflow to {:?}", file);
+    }
+
+    let workflow_file = args
+        .get_one::<PathBuf>("workflow")
+        .map(|file| WorkflowFile::open(file))
+        .transpose()
+        .map_err(ImageErrors::GenericString)?;
+
+    #[cfg(feature = "watch")]
+    if let Some(watch_dir) = args.get_one::<PathBuf>("watch") {
+        let out_dir = args.get_one::<PathBuf>("watch-out").unwrap();
+        return watch_directory(args, workflow_file.as_ref(), watch_dir, out_dir);
+    }
+    #[cfg(not(feature = "watch"))]
+    if args.contains_id("watch") {
+        return Err(ImageErrors::GenericStr(
+            "--watch needs zune to be built with the watch feature"
+        ));
+    }
+
     info!("Creating workflows from input");
 
-    let decoder_options = decoder_options(args);
-    let mut buf = [0; 30];
+    let out_files: Vec<&OsStr> = args
+        .get_raw("out")
+        .map(Iterator::collect)
+        .unwrap_or_default();
+
+    if let Some(files) = args.get_raw("montage") {
+        let settings = workflow_file.as_ref().map_or(args, WorkflowFile::settings);
+        let montage = create_montage(args, files, decoder_options(settings))?;
+
+        return run_workflow(
+            args,
+            workflow_file.as_ref(),
+            Box::new(ZuneImage::new(montage)),
+            OsStr::new("montage"),
+            &out_files
+        );
+    }
 
     for in_file in args.get_raw("in").unwrap() {
-        let mut workflow: CmdPipeline = CmdPipeline::new();
-
-        if in_file == "-" {
-            // handle stdin
-            let mut data = Vec::new();
-            let bytes_read = std::io::stdin().read_to_end(&mut data)?;
-            if let Some((format, _)) = ImageFormat::guess_format(std::io::Cursor::new(&data)) {
-                if format.has_decoder() {
-                    workflow
-                        .inner
-                        .chain_decoder(Box::new(ZuneMem::new(data, decoder_options)));
-                } else {
-                    return Err(ImageErrors::ImageDecoderNotImplemented(format));
+        exec_workflow(args, workflow_file.as_ref(), in_file, &out_files)?;
+    }
+
+    Ok(())
+}
+
+/// Run the workflow described by `args` on a single input, writing the results to `out_files`
+///
+/// If present, `workflow_file` provides the options and operations instead of `args`
+#[allow(unused_variables)]
+#[allow(clippy::unused_io_amount)] // yes it's what I want
+pub(crate) fn exec_workflow(
+    args: &ArgMatches, workflow_file: Option<&WorkflowFile>, in_file: &OsStr, out_files: &[&OsStr]
+) -> Result<(), ImageErrors> {
+    let settings = workflow_file.map_or(args, WorkflowFile::settings);
+    let decoder_options = decoder_options(settings);
+    let mut buf = [0; 30];
+
+    let forced_format = args
+        .get_one::<CmdImageFormats>("input-format")
+        .map(|CmdImageFormats::Format(format)| *format);
+
+    let decoder: Box<dyn IntoImage> = if in_file == "-" {
+        // handle stdin
+        let mut data = Vec::new();
+        let bytes_read = std::io::stdin().read_to_end(&mut data)?;
+
+        if forced_format.is_none() && probe_pdf(&data) {
+            return run_pdf_workflow(args, workflow_file, &data, in_file, out_files);
+        }
+        let format = input_format(forced_format, &data)?;
+
+        Box::new(ZuneMem::new(data, Some(format), decoder_options))
+    } else {
+        File::open(in_file)?.read(&mut buf)?;
+
+        if forced_format.is_none() && probe_pdf(&buf) {
+            let data = std::fs::read(in_file)?;
+            return run_pdf_workflow(args, workflow_file, &data, in_file, out_files);
+        }
+        let format = input_format(forced_format, &buf)?;
+
+        Box::new(ZuneFile::new(
+            in_file.to_os_string(),
+            Some(format),
+            decoder_options
+        ))
+    };
+
+    run_workflow(args, workflow_file, decoder, in_file, out_files)
+}
+
+/// Run the workflow on each image embedded in the PDF document `data`
+///
+/// When there is more than one image, the outputs of each are numbered
+/// like the frames of an animation, e.g. `out-2.png`
+fn run_pdf_workflow(
+    args: &ArgMatches, workflow_file: Option<&WorkflowFile>, data: &[u8], name: &OsStr,
+    out_files: &[&OsStr]
+) -> Result<(), ImageErrors> {
+    let settings = workflow_file.map_or(args, WorkflowFile::settings);
+    let images = extract_pdf_images(data, decoder_options(settings))?;
+
+    info!("Extracted {} images from {:?}", images.len(), name);
+
+    if images.is_empty() {
+        warn!("No images found in {:?}", name);
+    }
+    let numbered = images.len() > 1;
+
+    for (n, image) in images.into_iter().enumerate() {
+        let image_out_files: Vec<PathBuf> = out_files
+            .iter()
+            .map(|out_file| match numbered && *out_file != "-" {
+                true => frame_file_name(out_file, n),
+                false => PathBuf::from(out_file)
+            })
+            .collect();
+        let image_out_files: Vec<&OsStr> = image_out_files.iter().map(|x| x.as_os_str()).collect();
+
+        run_workflow(
+            args,
+            workflow_file,
+            Box::new(ZuneImage::new(image)),
+            name,
+            &image_out_files
+        )?;
+    }
+    Ok(())
+}
+
+/// Run the workflow described by `args` on the image from `decoder`, writing the results to
+/// `out_files`
+///
+/// `name` identifies the input in logs
+#[allow(unused_variables)]
+fn run_workflow(
+    args: &ArgMatches, workflow_file: Option<&WorkflowFile>, decoder: Box<dyn IntoImage>,
+    name: &OsStr, out_files: &[&OsStr]
+) -> Result<(), ImageErrors> {
+    let settings = workflow_file.map_or(args, WorkflowFile::settings);
+    let mut workflow: CmdPipeline = CmdPipeline::new();
+
+    match workflow_file {
+        Some(file) => {
+            for operation in file.operations() {
+                match operation {
+                    WorkflowOperation::Args(operation) => {
+                        add_operations(operation, &mut workflow.inner)?
+                    }
+                    WorkflowOperation::Registry { name, params } => {
+                        let operation = WorkflowOperation::create(name, params)?;
+                        workflow.inner.chain_operations(operation);
+                    }
                 }
+            }
+        }
+        None => add_operations(args, &mut workflow.inner)?
+    }
+
+    workflow.inner.chain_decoder(decoder);
+
+    let options = encoder_options(settings);
+
+    for out_file in out_files {
+        if let Some(ext) = Path::new(out_file).extension() {
+            if let Some(encode_type) = ImageFormat::encoder_for_extension(ext.to_str().unwrap()) {
+                debug!("Treating {:?} as a {:?} format", out_file, encode_type);
+                workflow.formats.push(encode_type);
             } else {
-                return Err(ImageErrors::ImageDecoderNotIncluded(ImageFormat::Unknown));
+                error!("Unknown or unsupported format {:?}", out_file)
             }
         } else {
-            File::open(in_file)?.read(&mut buf)?;
-
-            add_operations(args, &mut workflow.inner)?;
-
-            if let Some((format, _)) = ImageFormat::guess_format(std::io::Cursor::new(&buf)) {
-                if format.has_decoder() {
-                    workflow.inner.chain_decoder(Box::new(ZuneFile::new(
-                        in_file.to_os_string(),
-                        decoder_options
-                    )));
-                } else {
-                    return Err(ImageErrors::ImageDecoderNotImplemented(format));
-                }
-            } else {
-                return Err(ImageErrors::ImageDecoderNotIncluded(ImageFormat::Unknown));
+            if *out_file != "-" {
+                error!("Could not determine extension from {:?}", out_file);
             }
         }
+    }
 
-        let options = encoder_options(args);
-
-        if let Some(source) = args.value_source("out") {
-            if source == CommandLine {
-                for out_file in args.get_raw("out").unwrap() {
-                    if let Some(ext) = Path::new(out_file).extension() {
-                        if let Some(encode_type) =
-                            ImageFormat::encoder_for_extension(ext.to_str().unwrap())
-                        {
-                            debug!("Treating {:?} as a {:?} format", out_file, encode_type);
-                            workflow.formats.push(encode_type);
-                        } else {
-                            error!("Unknown or unsupported format {:?}", out_file)
-                        }
-                    } else {
-                        if out_file != "-" {
-                            error!("Could not determine extension from {:?}", out_file);
-                        }
-                    }
-                }
-            }
+    let report = workflow.inner.advance_to_end()?;
+    info!(
+        "Processed {:?} in {:?}, report {}",
+        name,
+        report.total,
+        serde_json::to_string(&report).unwrap_or_default()
+    );
+
+    if let Some(columns) = args.get_one::<usize>("sprite-sheet") {
+        let manifest_file = args.get_one::<PathBuf>("sprite-manifest");
+
+        for image in workflow.inner.images_mut() {
+            create_sprite_sheet(image, *columns, manifest_file)?;
         }
+    }
 
-        workflow.inner.advance_to_end()?;
+    // write to output
 
-        // write to output
+    //  We support multiple format writes per invocation
+    // i.e it's perfectly valid to do -o a.ppm , -o a.png
+    for out_file in out_files {
+        if *out_file == "-" {
+            if let Some(cmd_format) = args.get_one::<CmdImageFormats>("output-format") {
+                let mut out_file = BufWriter::new(std::io::stdout().lock());
 
-        //  We support multiple format writes per invocation
-        // i.e it's perfectly valid to do -o a.ppm , -o a.png
-        if let Some(source) = args.value_source("out") {
-            if source == CommandLine {
-                for out_file in args.get_raw("out").unwrap() {
-                    if out_file == "-" {
-                        if let Some(cmd_format) = args.get_one::<CmdImageFormats>("output-format") {
-                            // test on jpeg only
-                            let mut out_file = std::io::stdout();
+                let CmdImageFormats::Format(format) = cmd_format;
+                for image in workflow.inner.images() {
+                    if image.is_animated() && !format.has_animated_encoder() {
+                        warn!("{:?} cannot store animated images, only the first frame will be written to stdout", format);
+                    }
+                    format.encode(image, options, &mut out_file)?;
+                }
+            } else {
+                error!("You must specify the image format to be used while using output as '-` via the --output-format flag ");
+            }
+        } else {
+            //write to file
+            if let Some(ext) = Path::new(out_file).extension() {
+                for format in &workflow.formats {
+                    if format.has_encoder() {
+                        for image in workflow.inner.images() {
+                            if image.is_animated() && !format.has_animated_encoder() {
+                                // the format can't store animations, so store each
+                                // frame in its own file
+                                info!(
+                                    "{:?} cannot store animated images, writing {} frames as separate files",
+                                    format,
+                                    image.frames_len()
+                                );
+                                for (pos, frame) in image.frames_ref().iter().enumerate() {
+                                    let frame_file = frame_file_name(out_file, pos);
+                                    let mut frame_image = Image::new_frames(
+                                        vec![frame.clone()],
+                                        image.depth(),
+                                        image.dimensions().0,
+                                        image.dimensions().1,
+                                        image.colorspace()
+                                    );
+                                    *frame_image.metadata_mut() = image.metadata().clone();
 
-                            let CmdImageFormats::Format(format) = cmd_format;
-                            for image in workflow.inner.images() {
-                                format.encode(image, options, &mut out_file)?;
-                            }
-                        } else {
-                            error!("You must specify the image format to be used while using output as '-` via the --output-format flag ");
-                        }
-                    } else {
-                        //write to file
-                        if let Some(ext) = Path::new(out_file).extension() {
-                            for format in &workflow.formats {
-                                if format.has_encoder() {
-                                    for image in workflow.inner.images() {
-                                        let fd = OpenOptions::new()
-                                            .create(true)
-                                            .write(true)
-                                            .truncate(true)
-                                            .open(out_file);
-                                        match fd {
-                                            Ok(file) => {
-                                                let mut file_c = BufWriter::new(file);
-                                                let start = Instant::now();
-                                                let bytes =
-                                                    format.encode(image, options, &mut file_c)?;
-                                                let end = Instant::now();
-                                                trace!(
-                                                    "Took {:?} to encode {} bytes to {:?}",
-                                                    end - start,
-                                                    bytes,
-                                                    out_file
-                                                );
-                                            }
-                                            Err(e) => {
-                                                error!(
-                                                    "Cannot encode to file, error opening {:?}",
-                                                    e
-                                                );
-                                            }
-                                        }
-                                    }
+                                    encode_to_file(&frame_image, *format, options, &frame_file)?;
                                 }
+                            } else {
+                                encode_to_file(image, *format, options, out_file)?;
                             }
                         }
                     }
                 }
             }
         }
+    }
 
-        if let Some(view) = args.value_source("view") {
-            if view == CommandLine {
-                for image in workflow.inner.images() {
-                    open_in_default_app(image);
-                }
-            }
+    if let Some(scopes) = args.get_many::<String>("scope") {
+        let scopes = scopes.collect::<Vec<&String>>();
+
+        for scope in scopes.chunks_exact(2) {
+            write_scope(workflow.inner.images(), scope[0], scope[1], options)?;
+        }
+    }
+
+    if let Some(mode) = args.get_one::<String>("to-ascii") {
+        print_ascii(
+            workflow.inner.images(),
+            mode,
+            args.get_one::<usize>("ascii-width")
+        )?;
+    }
+
+    if let Some(mode) = args.get_one::<String>("view") {
+        let mode = ViewMode::from_string_result(mode).map_err(ImageErrors::GenericString)?;
+
+        for image in workflow.inner.images() {
+            show_image(image, mode)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Combine the frames of `image` into a sprite sheet with `columns` columns, or a single row
+/// if zero, writing its manifest to `manifest_file` if given
+fn create_sprite_sheet(
+    image: &mut Image, columns: usize, manifest_file: Option<&PathBuf>
+) -> Result<(), ImageErrors> {
+    let sheet = match columns {
+        0 => SpriteSheet::new(),
+        columns => SpriteSheet::new().set_columns(columns)
+    };
+    let manifest = sheet.manifest(image);
+    sheet.execute(image)?;
+
+    debug!(
+        "Created sprite sheet of {} frames in {} columns",
+        manifest.frames.len(),
+        manifest.columns
+    );
+    if let Some(file) = manifest_file {
+        std::fs::write(file, serde_json::to_string_pretty(&manifest).unwrap())?;
+        info!("Saved sprite sheet manifest to {:?}", file);
+    }
+    Ok(())
+}
+
+/// Decode `files` and lay them out in a grid as described by the `montage-*` arguments
+fn create_montage(
+    args: &ArgMatches, files: RawValues, options: DecoderOptions
+) -> Result<Image, ImageErrors> {
+    let files: Vec<&OsStr> = files.collect();
+    let mut images = Vec::with_capacity(files.len());
+
+    for file in &files {
+        debug!("Decoding {:?} for montage", file);
+        images.push(ZuneFile::new(file.to_os_string(), None, options).into_image()?);
+    }
+
+    let mut montage = Montage::new();
+
+    for (image, file) in images.iter().zip(&files) {
+        if args.get_flag("montage-no-labels") {
+            montage = montage.add_image(image);
+        } else {
+            let label = Path::new(file)
+                .file_name()
+                .unwrap_or(file)
+                .to_string_lossy();
+            montage = montage.add_labeled_image(image, &label);
+        }
+    }
+    if let Some(columns) = args.get_one::<usize>("montage-columns") {
+        montage = montage.set_columns(*columns);
+    }
+    if let Some(tile) = args.get_many::<usize>("montage-tile") {
+        let tile: Vec<usize> = tile.copied().collect();
+        montage = montage.set_tile_size(tile[0], tile[1]);
+    }
+    if let Some(padding) = args.get_one::<usize>("montage-padding") {
+        montage = montage.set_padding(*padding);
+    }
+    if let Some(color) = args.get_one::<String>("montage-background") {
+        montage = montage.set_background(parse_color(color)?);
+    }
+    if let Some(color) = args.get_one::<String>("montage-label-color") {
+        montage = montage.set_label_color(parse_color(color)?);
+    }
+    montage.montage()
+}
+
+/// Parse a hex color like `#ff8000`, or `#ff800080` with alpha, into RGBA
+fn parse_color(color: &str) -> Result<[u8; 4], ImageErrors> {
+    let hex = color.strip_prefix('#').unwrap_or(color);
+    let component = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|x| u8::from_str_radix(x, 16).ok())
+    };
+    let alpha = if hex.len() == 8 { component(6) } else { Some(255) };
+
+    match (hex.len(), component(0), component(2), component(4), alpha) {
+        (6 | 8, Some(r), Some(g), Some(b), Some(a)) => Ok([r, g, b, a]),
+        _ => Err(ImageErrors::GenericString(format!(
+            "Unknown color {color}, expected a hex color like #ffffff or #ffffff80"
+        )))
+    }
+}
+
+/// Return the format to decode an input with
+///
+/// This is `forced` if the user specified one, otherwise it's guessed from
+/// the first bytes of the input
+fn input_format(forced: Option<ImageFormat>, start: &[u8]) -> Result<ImageFormat, ImageErrors> {
+    let format = match forced {
+        Some(format) => format,
+        None => ImageFormat::guess_format(std::io::Cursor::new(start))
+            .map(|(format, _)| format)
+            .ok_or(ImageErrors::ImageDecoderNotIncluded(ImageFormat::Unknown))?
+    };
+    if !format.has_decoder() {
+        return Err(ImageErrors::ImageDecoderNotImplemented(format));
+    }
+    Ok(format)
+}
+
+/// Encode an image to `out_file` using the specified format
+fn encode_to_file<P: AsRef<Path> + Debug>(
+    image: &Image, format: ImageFormat, options: EncoderOptions, out_file: P
+) -> Result<(), ImageErrors> {
+    let fd = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(&out_file);
+    match fd {
+        Ok(file) => {
+            let mut file_c = BufWriter::new(file);
+            let start = Instant::now();
+            let bytes = format.encode(image, options, &mut file_c)?;
+            let end = Instant::now();
+            trace!(
+                "Took {:?} to encode {} bytes to {:?}",
+                end - start,
+                bytes,
+                out_file
+            );
+        }
+        Err(e) => {
+            error!("Cannot encode to file, error opening {:?}", e);
         }
     }
+    Ok(())
+}
+
+/// Render a scope of each image and encode it to `out_file`
+fn write_scope(
+    images: &[Image], scope: &str, out_file: &str, options: EncoderOptions
+) -> Result<(), ImageErrors> {
+    let scope = ScopeType::from_string_result(scope).map_err(ImageErrors::GenericString)?;
+
+    let format = Path::new(out_file)
+        .extension()
+        .and_then(|ext| ImageFormat::encoder_for_extension(ext.to_str()?))
+        .ok_or_else(|| {
+            ImageErrors::GenericString(format!("Unknown or unsupported format {out_file:?}"))
+        })?;
+
+    for image in images {
+        let mut scope_image = image.clone();
+        Scope::new(scope).execute(&mut scope_image)?;
+
+        debug!("Writing {:?} scope to {:?}", scope, out_file);
+        encode_to_file(&scope_image, format, options, out_file)?;
+    }
+    Ok(())
+}
+
+/// Print each image to stdout as text art
+///
+/// Without `columns`, the width of the terminal is read from the `COLUMNS`
+/// environment variable, falling back to 80 characters
+fn print_ascii(images: &[Image], mode: &str, columns: Option<&usize>) -> Result<(), ImageErrors> {
+    let columns = columns.copied().unwrap_or_else(|| {
+        std::env::var("COLUMNS")
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(80)
+    });
+    let renderer = match mode {
+        "color" => AsciiArt::new(columns).set_color(true),
+        "blocks" => AsciiArt::new(columns)
+            .set_style(AsciiStyle::Blocks)
+            .set_color(true),
+        _ => AsciiArt::new(columns)
+    };
+    let mut stdout = std::io::stdout().lock();
 
+    for image in images {
+        stdout.write_all(renderer.render(image)?.as_bytes())?;
+    }
     Ok(())
 }
 
+/// Create the file name for a single frame of an animated image
+///
+/// E.g frame 2 of `out.png` becomes `out-2.png`
+fn frame_file_name(out_file: &OsStr, frame: usize) -> PathBuf {
+    let path = Path::new(out_file);
+    let mut name = path.file_stem().unwrap_or_default().to_os_string();
+
+    name.push(format!("-{frame}"));
+
+    if let Some(ext) = path.extension() {
+        name.push(".");
+        name.push(ext);
+    }
+    path.with_file_name(name)
+}
+
 pub fn add_operations(args: &ArgMatches, workflow: &mut Pipeline) -> Result<(), String> {
+    let mut ids = vec![];
+
     for id in args.ids() {
         if args.try_get_many::<clap::Id>(id.as_str()).is_ok() {
             // ignore groups
@@ -205,9 +560,14 @@ pub fn add_operations(args: &ArgMatches, workflow: &mut Pipeline) -> Result<(),
             // ignore things not passed via command line
             continue;
         }
+        ids.push(id.as_str());
+    }
+    // run operations in the order they were given
+    ids.sort_by_key(|id| args.index_of(id));
 
-        crate::cmd_parsers::operations::parse_options(workflow, id.as_str(), args)?;
-        crate::cmd_parsers::filters::parse_options(workflow, id.as_str(), args)?;
+    for id in ids {
+        crate::cmd_parsers::operations::parse_options(workflow, id, args)?;
+        crate::cmd_parsers::filters::parse_options(workflow, id, args)?;
     }
 
     Ok(())
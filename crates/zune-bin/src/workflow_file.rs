@@ -0,0 +1,412 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Workflow files, repeatable pipelines stored as JSON
+//!
+//! A workflow file stores decoder options, an ordered list of operations and
+//! encoder options, it is run with `--workflow` and can be created from a command
+//! line with `--save-workflow`.
+//!
+//! Keys are the long command line flags without the leading `--` and values are
+//! their arguments, `true` for flags and an array for flags taking multiple values.
+//!
+//! An operation whose value is an object is created from the library's
+//! [`OperationRegistry`] instead, the object holds the fields of the operation
+//! e.g. `{ "gaussian-blur": { "sigma": 2.0 } }`
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "decoder": { "max-width": 4096, "strict": true },
+//!   "operations": [{ "resize": [800, 600] }, { "grayscale": true }, { "blur": 1.5 }],
+//!   "encoder": { "quality": 80, "strip": true }
+//! }
+//! ```
+use std::ffi::OsString;
+use std::path::Path;
+
+use clap::parser::ValueSource;
+use clap::{Arg, ArgMatches, Command};
+use serde_json::{Map, Number, Value};
+use zune_image::traits::OperationsTrait;
+use zune_imageprocs::registry::OperationRegistry;
+
+use crate::cmd_args::{create_cmd_args, ENCODE_GROUP, FILTERS_GROUP, OPERATIONS_GROUP};
+
+/// Version of workflow files we write, files with a newer version are rejected
+pub const WORKFLOW_VERSION: u64 = 1;
+
+/// Settings that configure decoding
+pub const DECODER_SETTINGS: [&str; 6] = [
+    "jpeg-grayscale",
+    "max-height",
+    "max-width",
+    "safe",
+    "still",
+    "strict"
+];
+
+/// Settings that modify the image but aren't in an operation group
+const UNGROUPED_OPERATIONS: [&str; 1] = ["colorspace"];
+
+/// A part of a workflow file
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Section {
+    Decoder,
+    Operations,
+    Encoder
+}
+
+impl Section {
+    const fn key(self) -> &'static str {
+        match self {
+            Section::Decoder => "decoder",
+            Section::Operations => "operations",
+            Section::Encoder => "encoder"
+        }
+    }
+
+    /// Return the section an argument is stored in, or `None` if it
+    /// can't be part of a workflow, e.g. input and output files
+    fn of(cmd: &Command, id: &str) -> Option<Section> {
+        if DECODER_SETTINGS.contains(&id) {
+            return Some(Section::Decoder);
+        }
+        if UNGROUPED_OPERATIONS.contains(&id) {
+            return Some(Section::Operations);
+        }
+        let group = cmd
+            .get_groups()
+            .find(|group| group.get_args().any(|arg| arg == id))?;
+
+        match group.get_id().as_str() {
+            OPERATIONS_GROUP | FILTERS_GROUP => Some(Section::Operations),
+            ENCODE_GROUP => Some(Section::Encoder),
+            _ => None
+        }
+    }
+}
+
+/// An operation of a workflow file
+pub enum WorkflowOperation {
+    /// An operation written as command line arguments
+    Args(ArgMatches),
+    /// An operation created from the registry by name, with its parameters
+    Registry { name: String, params: Value }
+}
+
+impl WorkflowOperation {
+    /// Create a registry operation, operations are recreated for every
+    /// image since they may be consumed by the pipeline
+    pub fn create(name: &str, params: &Value) -> Result<Box<dyn OperationsTrait>, String> {
+        OperationRegistry::new()
+            .create(name, params.clone())
+            .map_err(|e| format!("{e:?}"))
+    }
+}
+
+/// A parsed workflow file
+///
+/// Entries are validated the same way as the command line
+pub struct WorkflowFile {
+    settings:   ArgMatches,
+    operations: Vec<WorkflowOperation>
+}
+
+impl WorkflowFile {
+    /// Read and parse a workflow file
+    pub fn open(path: &Path) -> Result<WorkflowFile, String> {
+        let data =
+            std::fs::read(path).map_err(|e| format!("Cannot read workflow {path:?}: {e}"))?;
+        let json: Value = serde_json::from_slice(&data)
+            .map_err(|e| format!("Invalid workflow file {path:?}: {e}"))?;
+
+        WorkflowFile::from_json(&json)
+    }
+
+    /// Parse a workflow from its JSON representation
+    pub fn from_json(json: &Value) -> Result<WorkflowFile, String> {
+        let version = json
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or("Workflow file is missing a version")?;
+
+        if version > WORKFLOW_VERSION {
+            return Err(format!(
+                "Workflow version {version} is newer than the supported version {WORKFLOW_VERSION}"
+            ));
+        }
+        let cmd = create_cmd_args();
+        let mut settings = vec![];
+
+        for section in [Section::Decoder, Section::Encoder] {
+            if let Some(entries) = json.get(section.key()) {
+                let entries = entries
+                    .as_object()
+                    .ok_or_else(|| format!("\"{}\" must be an object", section.key()))?;
+
+                for (key, value) in entries {
+                    settings.extend(to_args(&cmd, section, key, value)?);
+                }
+            }
+        }
+        let settings = parse_args(&cmd, settings)?;
+
+        let mut operations = vec![];
+
+        if let Some(entries) = json.get("operations") {
+            let entries = entries
+                .as_array()
+                .ok_or("\"operations\" must be an array")?;
+
+            for entry in entries {
+                let (key, value) = entry
+                    .as_object()
+                    .filter(|x| x.len() == 1)
+                    .and_then(|x| x.iter().next())
+                    .ok_or("Each operation must be an object with a single key")?;
+
+                if value.is_object() {
+                    // validate the name and parameters early
+                    WorkflowOperation::create(key, value)?;
+
+                    operations.push(WorkflowOperation::Registry {
+                        name:   key.clone(),
+                        params: value.clone()
+                    });
+                } else {
+                    let args = to_args(&cmd, Section::Operations, key, value)?;
+                    operations.push(WorkflowOperation::Args(parse_args(&cmd, args)?));
+                }
+            }
+        }
+        Ok(WorkflowFile {
+            settings,
+            operations
+        })
+    }
+
+    /// Create the JSON representation of the options and operations
+    /// passed on the command line
+    pub fn json_from_args(args: &ArgMatches) -> Value {
+        let cmd = create_cmd_args();
+
+        let mut decoder = Map::new();
+        let mut encoder = Map::new();
+        let mut operations = vec![];
+
+        for arg in cmd.get_arguments() {
+            let id = arg.get_id().as_str();
+
+            if args.value_source(id) != Some(ValueSource::CommandLine) {
+                continue;
+            }
+            let Some(section) = Section::of(&cmd, id) else {
+                continue;
+            };
+            let key = arg.get_long().unwrap_or(id).to_string();
+            let value = arg_value(arg, args);
+
+            match section {
+                Section::Decoder => {
+                    decoder.insert(key, value);
+                }
+                Section::Encoder => {
+                    encoder.insert(key, value);
+                }
+                Section::Operations => {
+                    let entry = Value::Object(Map::from_iter([(key, value)]));
+                    operations.push((args.index_of(id), entry));
+                }
+            }
+        }
+        // keep the order operations were given in
+        operations.sort_by_key(|(index, _)| *index);
+
+        let mut json = Map::new();
+        json.insert("version".to_string(), Value::from(WORKFLOW_VERSION));
+        json.insert("decoder".to_string(), Value::Object(decoder));
+        json.insert(
+            "operations".to_string(),
+            Value::Array(operations.into_iter().map(|(_, x)| x).collect())
+        );
+        json.insert("encoder".to_string(), Value::Object(encoder));
+
+        Value::Object(json)
+    }
+
+    /// Decoder and encoder options, to be used in place of the command line
+    pub fn settings(&self) -> &ArgMatches {
+        &self.settings
+    }
+
+    /// Operations in the order they should run
+    pub fn operations(&self) -> &[WorkflowOperation] {
+        &self.operations
+    }
+}
+
+/// Convert a workflow entry to command line arguments
+fn to_args(
+    cmd: &Command, section: Section, key: &str, value: &Value
+) -> Result<Vec<OsString>, String> {
+    let unknown = || format!("Unknown {} entry \"{key}\"", section.key());
+
+    let arg = cmd
+        .get_arguments()
+        .find(|arg| arg.get_long() == Some(key))
+        .ok_or_else(unknown)?;
+
+    if Section::of(cmd, arg.get_id().as_str()) != Some(section) {
+        return Err(unknown());
+    }
+    let flag = OsString::from(format!("--{key}"));
+
+    let values = match value {
+        Value::Bool(true) => vec![],
+        // a disabled flag
+        Value::Bool(false) => return Ok(vec![]),
+        Value::Array(values) => values.iter().map(value_string).collect(),
+        value => vec![value_string(value)]
+    };
+    Ok([flag].into_iter().chain(values).collect())
+}
+
+fn value_string(value: &Value) -> OsString {
+    match value {
+        Value::String(value) => value.into(),
+        value => value.to_string().into()
+    }
+}
+
+/// Return the value of an argument passed on the command line, numbers are
+/// stored as JSON numbers so that files are easy to edit
+fn arg_value(arg: &Arg, args: &ArgMatches) -> Value {
+    let id = arg.get_id().as_str();
+
+    if !arg.get_action().takes_values() {
+        return Value::Bool(true);
+    }
+    let mut values: Vec<Value> = args
+        .get_raw(id)
+        .into_iter()
+        .flatten()
+        .map(|value| {
+            let value = value.to_string_lossy();
+
+            if let Ok(number) = value.parse::<i64>() {
+                Value::from(number)
+            } else if let Some(number) = value.parse().ok().and_then(Number::from_f64) {
+                Value::Number(number)
+            } else {
+                Value::String(value.into_owned())
+            }
+        })
+        .collect();
+
+    if values.len() == 1 {
+        values.remove(0)
+    } else {
+        Value::Array(values)
+    }
+}
+
+/// Parse arguments as if they were passed on the command line
+fn parse_args(cmd: &Command, args: Vec<OsString>) -> Result<ArgMatches, String> {
+    // input is required on the command line, it isn't used
+    let args = ["zune", "-i", "-"]
+        .map(OsString::from)
+        .into_iter()
+        .chain(args);
+
+    cmd.clone()
+        .try_get_matches_from(args)
+        .map_err(|e| e.render().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::cmd_args::create_cmd_args;
+    use crate::workflow_file::{WorkflowFile, WorkflowOperation, WORKFLOW_VERSION};
+
+    #[test]
+    fn test_workflow_round_trip() {
+        let args = create_cmd_args().get_matches_from([
+            "zune",
+            "-i",
+            "in.png",
+            "--resize",
+            "80",
+            "60",
+            "--strict",
+            "--blur",
+            "1.5",
+            "--grayscale",
+            "--quality",
+            "75"
+        ]);
+        let json = WorkflowFile::json_from_args(&args);
+
+        assert_eq!(
+            json,
+            json!({
+                "version": WORKFLOW_VERSION,
+                "decoder": { "strict": true },
+                "operations": [{ "resize": [80, 60] }, { "blur": 1.5 }, { "grayscale": true }],
+                "encoder": { "quality": 75 }
+            })
+        );
+        let workflow = WorkflowFile::from_json(&json).unwrap();
+
+        assert_eq!(workflow.operations().len(), 3);
+        assert!(matches!(
+            &workflow.operations()[2],
+            WorkflowOperation::Args(args) if args.get_flag("grayscale")
+        ));
+        assert_eq!(workflow.settings().get_one::<u8>("quality"), Some(&75));
+        assert!(workflow.settings().get_flag("strict"));
+    }
+
+    #[test]
+    fn test_invalid_workflows() {
+        let newer = json!({ "version": WORKFLOW_VERSION + 1 });
+        assert!(WorkflowFile::from_json(&newer).is_err());
+        assert!(WorkflowFile::from_json(&json!({})).is_err());
+        // an encoder option where an operation is expected
+        let misplaced = json!({ "version": 1, "operations": [{ "quality": 10 }] });
+        assert!(WorkflowFile::from_json(&misplaced).is_err());
+        // values are validated like the command line
+        let invalid = json!({ "version": 1, "operations": [{ "resize": ["a", 10] }] });
+        assert!(WorkflowFile::from_json(&invalid).is_err());
+        // registry operations are validated when loading
+        let invalid = json!({ "version": 1, "operations": [{ "gamma": { "value": "a" } }] });
+        assert!(WorkflowFile::from_json(&invalid).is_err());
+        let unknown = json!({ "version": 1, "operations": [{ "unknown": {} }] });
+        assert!(WorkflowFile::from_json(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_registry_operations() {
+        let json = json!({
+            "version": 1,
+            "operations": [{ "gaussian-blur": { "sigma": 2.0 } }, { "invert": true }]
+        });
+        let workflow = WorkflowFile::from_json(&json).unwrap();
+
+        assert!(matches!(
+            &workflow.operations()[0],
+            WorkflowOperation::Registry { name, .. } if name == "gaussian-blur"
+        ));
+        assert!(matches!(
+            &workflow.operations()[1],
+            WorkflowOperation::Args(_)
+        ));
+    }
+}
@@ -15,6 +15,8 @@ pub enum BmpCompression {
     RLE8,
     RLE4,
     BITFIELDS,
+    JPEG,
+    PNG,
     Unknown
 }
 
@@ -25,11 +27,23 @@ impl BmpCompression {
             1 => Some(BmpCompression::RLE8),
             2 => Some(BmpCompression::RLE4),
             3 => Some(BmpCompression::BITFIELDS),
+            4 => Some(BmpCompression::JPEG),
+            5 => Some(BmpCompression::PNG),
             _ => None
         }
     }
 }
 
+/// Format of an image embedded in a BMP file instead of pixel data
+///
+/// See [`BmpDecoder::embedded_image`](crate::BmpDecoder::embedded_image)
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum BmpEmbeddedFormat {
+    JPEG,
+    PNG
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum BmpPixelFormat {
@@ -61,6 +75,46 @@ impl BmpPixelFormat {
     }
 }
 
+/// The `bV5CSType`/`bV4CSType` field of V4 and V5 headers
+///
+/// Describes how the color values of the image should be interpreted
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum BmpColorSpaceType {
+    /// Endpoints and gamma values in the header describe the color space
+    CalibratedRgb,
+    /// The image is in the sRGB color space
+    Srgb,
+    /// The image is in the system default color space, usually sRGB
+    WindowsColorSpace,
+    /// The profile is in a file whose name is stored in the image
+    LinkedProfile,
+    /// The profile is embedded in the image, see
+    /// [`BmpDecoder::icc_profile`](crate::BmpDecoder::icc_profile)
+    EmbeddedProfile
+}
+
+impl BmpColorSpaceType {
+    pub(crate) fn from_u32(num: u32) -> Option<BmpColorSpaceType> {
+        match num {
+            LCS_CALIBRATED_RGB => Some(BmpColorSpaceType::CalibratedRgb),
+            LCS_SRGB => Some(BmpColorSpaceType::Srgb),
+            LCS_WINDOWS_COLOR_SPACE => Some(BmpColorSpaceType::WindowsColorSpace),
+            PROFILE_LINKED => Some(BmpColorSpaceType::LinkedProfile),
+            PROFILE_EMBEDDED => Some(BmpColorSpaceType::EmbeddedProfile),
+            _ => None
+        }
+    }
+}
+
+/// Color values are relative to the endpoints and gamma values in the header
+pub(crate) const LCS_CALIBRATED_RGB: u32 = 0;
+
+/// Color values are in the sRGB color space, `sRGB`
+pub(crate) const LCS_SRGB: u32 = 0x7352_4742;
+
+/// Color values are in the system default color space, `Win `
+pub(crate) const LCS_WINDOWS_COLOR_SPACE: u32 = 0x5769_6E20;
+
 /// This value indicates that bV5ProfileData points to the file name of the profile to use (gamma and endpoints values are ignored).
 pub(crate) const PROFILE_LINKED: u32 = 0x4c494e4b;
 
@@ -95,17 +95,22 @@
 // - We treat OS2-BMPv2 files as if they are WinBMPv3 (i.e. ignore the extra 24
 //   bytes in the info header), which in practice is good enough.
 
+use alloc::string::String;
 use alloc::vec::Vec;
 use alloc::{format, vec};
 
 use log::error;
 use zune_core::bit_depth::BitDepth;
-use zune_core::bytestream::{ZByteIoError, ZByteReaderTrait, ZReader};
+use zune_core::bytestream::{ZByteIoError, ZByteReaderTrait, ZReader, ZSeekFrom};
 use zune_core::colorspace::{ColorPrimaries, ColorSpace, RenderingIntent};
 use zune_core::log::{trace, warn};
 use zune_core::options::DecoderOptions;
+use zune_core::warnings::{DecodeWarning, DecodeWarningKind};
 
-use crate::common::{BmpCompression, BmpPixelFormat, PROFILE_EMBEDDED, PROFILE_LINKED};
+use crate::common::{
+    BmpColorSpaceType, BmpCompression, BmpEmbeddedFormat, BmpPixelFormat, PROFILE_EMBEDDED,
+    PROFILE_LINKED
+};
 use crate::utils::expand_bits_to_byte;
 use crate::BmpDecoderErrors;
 
@@ -222,7 +227,20 @@ where
     /// The bytes of an ICC embedded profile if it exists
     icc_bytes:            Option<Vec<u8>>,
     /// Color primaries if present
-    color_primaries:      Option<ColorPrimaries>
+    color_primaries:      Option<ColorPrimaries>,
+    /// Size of the pixel data from the header, only used
+    /// for embedded images, may be zero
+    image_size:           u32,
+    /// How colors should be interpreted, from V4 and V5 headers
+    colorspace_type:      Option<BmpColorSpaceType>,
+    /// Gamma for the red, green and blue channels, from V4 and V5 headers
+    gamma:                Option<[f32; 3]>,
+    /// Rendering intent, from V5 headers
+    rendering_intent:     Option<RenderingIntent>,
+    /// Horizontal and vertical resolution in pixels per meter
+    pixels_per_meter:     Option<(u32, u32)>,
+    /// Problems the decoder recovered from
+    warnings:             Vec<DecodeWarning>
 }
 
 impl<T> BmpDecoder<T>
@@ -269,7 +287,13 @@ where
             convert_rgba_to_bgra: false,
             image_in_bgra: false,
             icc_bytes: None,
-            color_primaries: None
+            color_primaries: None,
+            image_size: 0,
+            colorspace_type: None,
+            gamma: None,
+            rendering_intent: None,
+            pixels_per_meter: None,
+            warnings: vec![]
         }
     }
 
@@ -339,8 +363,13 @@ where
                     let important_colors = self.bytes.get_u32_le_err()?;
 
                     trace!("Image size {}", size);
+                    self.image_size = size;
                     trace!("X Pixels: {}", x_pixels);
                     trace!("Y Pixels: {}", y_pixels);
+                    // most writers leave these as zero
+                    if x_pixels != 0 && y_pixels != 0 {
+                        self.pixels_per_meter = Some((x_pixels, y_pixels));
+                    }
                     trace!("Color used : {}", color_used);
                     trace!("Important Colors: {}", important_colors);
 
@@ -373,22 +402,28 @@ where
                         primaries.blue.z = (self.bytes.get_u32_le_err()? as f64) / BMP_DENOM;
 
                         self.color_primaries = Some(primaries);
-                        // gamma for red, green and blue
-                        self.bytes.skip(4 * 3)?;
+                        // gamma for red, green and blue, in 16.16 fixed point
+                        let mut gamma = [0.0; 3];
+                        for g in gamma.iter_mut() {
+                            *g = self.bytes.get_u32_le_err()? as f32 / 65536.0;
+                        }
+                        self.colorspace_type = BmpColorSpaceType::from_u32(colorspace_type);
+                        self.gamma = Some(gamma);
+
+                        trace!("Colorspace type: {:?}", self.colorspace_type);
+                        trace!("Gamma: {:?}", gamma);
                     }
 
                     if ihsize > 108 {
                         // bmp version 5, color management info
-                        let mut intent = None;
-
-                        match self.bytes.get_u32_le_err()? {
-                            1 => intent = Some(RenderingIntent::AbsoluteColorimetric),
-                            2 => intent = Some(RenderingIntent::Saturation),
-                            4 => intent = Some(RenderingIntent::RelativeColorimetric),
-                            8 => intent = Some(RenderingIntent::Perceptual),
-                            _ => {}
-                        }
-                        trace!("Intent :{:?}", intent);
+                        self.rendering_intent = match self.bytes.get_u32_le_err()? {
+                            1 => Some(RenderingIntent::AbsoluteColorimetric),
+                            2 => Some(RenderingIntent::Saturation),
+                            4 => Some(RenderingIntent::RelativeColorimetric),
+                            8 => Some(RenderingIntent::Perceptual),
+                            _ => None
+                        };
+                        trace!("Intent :{:?}", self.rendering_intent);
                         let profile_data = self.bytes.get_u32_le_err()?;
                         let profile_size = self.bytes.get_u32_le_err()?;
                         trace!("ICC profile Data Offset: {}", profile_data);
@@ -399,39 +434,40 @@ where
 
                         trace!("True ICC offset: {true_position}");
 
-                        let current_pos = self.bytes.position()?;
-
                         // NB: (cae) [tag=perf]: The main format is usually to have the profile
                         // after pixels, so this may cause unnecessary jumps/seeks
                         // but that's okay, as I prefer it here to confirm to the notion
                         // that all headers are decoded after calling decode_header()
-
-                        let icc_bytes = self.bytes.peek_at(
-                            (true_position.saturating_sub(current_pos)) as usize,
-                            profile_size as usize
-                        );
-                        match icc_bytes {
-                            Ok(bytes) => {
-                                if colorspace_type == PROFILE_LINKED {
-                                    // NB: (cae) [tag=bug]: The format is windows characters.
-                                    // but I don't feel like adding the encoding_rs crate
-                                    // to correctly map the characters, so those that can't
-                                    // be represented by utf-8 will be filled with the replacement
-                                    // character
-
-                                    trace!(
-                                        "File: {}",
-                                        alloc::string::String::from_utf8_lossy(bytes)
-                                    );
+                        if colorspace_type == PROFILE_LINKED || colorspace_type == PROFILE_EMBEDDED
+                        {
+                            match self.read_profile(true_position, profile_size) {
+                                Ok(bytes) => {
+                                    if colorspace_type == PROFILE_LINKED {
+                                        // NB: (cae) [tag=bug]: The format is windows characters.
+                                        // but I don't feel like adding the encoding_rs crate
+                                        // to correctly map the characters, so those that can't
+                                        // be represented by utf-8 will be filled with the replacement
+                                        // character
+
+                                        trace!(
+                                            "File: {}",
+                                            alloc::string::String::from_utf8_lossy(&bytes)
+                                        );
+                                    } else {
+                                        trace!("Read ICC profile from BMP file");
+                                        self.icc_bytes = Some(bytes)
+                                    }
                                 }
-                                if colorspace_type == PROFILE_EMBEDDED {
-                                    trace!("Read ICC profile from BMP file");
-                                    self.icc_bytes = Some(bytes.to_vec())
+                                Err(e) => {
+                                    if self.options.strict_mode() {
+                                        return Err(e);
+                                    }
+                                    self.add_warning(
+                                        DecodeWarningKind::CorruptData,
+                                        format!("Error reading ICC profile. {e:?}")
+                                    );
                                 }
                             }
-                            Err(e) => {
-                                error!("Error reading ICC profile. {:?}", e);
-                            }
                         }
                     }
                 }
@@ -476,6 +512,17 @@ where
         trace!("Width: {}", self.width);
         trace!("Height: {}", self.height);
 
+        if matches!(compression, BmpCompression::JPEG | BmpCompression::PNG) {
+            // pixels are a complete JPEG or PNG file, there is no palette
+            // and the bit depth is usually zero
+            trace!("Compression  : {:?}", compression);
+            self.comp = compression;
+            self.ihszie = ihsize;
+            self.hsize = hsize;
+            self.decoded_headers = true;
+
+            return Ok(());
+        }
         if bpp == 0 {
             return Err(BmpDecoderErrors::GenericStatic(
                 "Depth is zero, invalid image"
@@ -503,7 +550,10 @@ where
             }
             1 | 2 | 4 => {
                 if bpp == 2 {
-                    warn!("Depth of 2 not officially supported");
+                    self.add_warning(
+                        DecodeWarningKind::NonConformance,
+                        "Depth of 2 not officially supported"
+                    );
                 }
 
                 if hsize.wrapping_sub(ihsize).wrapping_sub(14) > 0 {
@@ -537,7 +587,7 @@ where
                     if self.options.strict_mode() {
                         return Err(BmpDecoderErrors::Generic(msg));
                     }
-                    warn!("{}", msg);
+                    self.add_warning(DecodeWarningKind::CorruptData, msg);
                 } else if t != 0 {
                     colors = t as u32;
                 }
@@ -704,6 +754,120 @@ where
         self.icc_bytes.as_ref()
     }
 
+    /// Return problems the decoder found and recovered from so far
+    ///
+    /// In non-strict mode, the decoder tolerates some damaged and non standard
+    /// files, each such problem is reported here.
+    pub fn warnings(&self) -> &[DecodeWarning] {
+        &self.warnings
+    }
+
+    /// Log a warning and keep it for [`warnings`](Self::warnings)
+    fn add_warning(&mut self, kind: DecodeWarningKind, message: impl Into<String>) {
+        let warning = DecodeWarning::new(kind, message);
+        warn!("{}", warning);
+        self.warnings.push(warning);
+    }
+
+    /// Return how the colors of the image should be interpreted, or `None` if
+    /// the image doesn't have a V4 or V5 header
+    pub fn colorspace_type(&self) -> Option<BmpColorSpaceType> {
+        self.colorspace_type
+    }
+
+    /// Return the gamma of the red, green and blue channels, or `None` if
+    /// the image doesn't have a V4 or V5 header
+    ///
+    /// These are only meaningful if [`colorspace_type`](Self::colorspace_type)
+    /// is [`BmpColorSpaceType::CalibratedRgb`]
+    pub fn gamma(&self) -> Option<[f32; 3]> {
+        self.gamma
+    }
+
+    /// Return the rendering intent of the image, or `None` if the
+    /// image doesn't have a V5 header
+    pub fn rendering_intent(&self) -> Option<RenderingIntent> {
+        self.rendering_intent
+    }
+
+    /// Return the density in dots per inch along the x and y axis,
+    /// or `None` if the image doesn't specify it
+    pub fn dpi(&self) -> Option<(f32, f32)> {
+        // 1 inch = 0.0254 meters
+        self.pixels_per_meter
+            .map(|(x, y)| (x as f32 * 0.0254, y as f32 * 0.0254))
+    }
+
+    /// Read a color profile or a linked profile file name stored `size` bytes
+    /// at `position`, restoring the stream position afterwards
+    fn read_profile(&mut self, position: u64, size: u32) -> Result<Vec<u8>, BmpDecoderErrors> {
+        let current_pos = self.bytes.position()?;
+        let end = self.bytes.seek(ZSeekFrom::End(0))?;
+
+        let result = if position.saturating_add(u64::from(size)) > end {
+            Err(BmpDecoderErrors::GenericStatic(
+                "Color profile extends past the end of the file"
+            ))
+        } else {
+            let mut data = vec![0; size as usize];
+            self.bytes.set_position(position as usize)?;
+            self.bytes
+                .read_exact_bytes(&mut data)
+                .map(|_| data)
+                .map_err(Into::into)
+        };
+        self.bytes.set_position(current_pos as usize)?;
+
+        result
+    }
+
+    /// Return the format of the embedded image if the file stores a JPEG or PNG
+    /// file instead of pixels, or `None` if it doesn't or headers weren't decoded
+    pub fn embedded_format(&self) -> Option<BmpEmbeddedFormat> {
+        match self.comp {
+            BmpCompression::JPEG => Some(BmpEmbeddedFormat::JPEG),
+            BmpCompression::PNG => Some(BmpEmbeddedFormat::PNG),
+            _ => None
+        }
+    }
+
+    /// Read the JPEG or PNG file embedded in this image
+    ///
+    /// Such images can't be decoded by this crate, the embedded file has to be passed
+    /// to a JPEG or PNG decoder, see [`embedded_format`](Self::embedded_format) for its format.
+    ///
+    /// # Returns
+    /// - `Ok(Some(bytes))`: The embedded file
+    /// - `Ok(None)`: The image stores pixels and should be decoded normally
+    /// - `Err`: An error occurred decoding headers or reading the embedded file
+    pub fn embedded_image(&mut self) -> Result<Option<Vec<u8>>, BmpDecoderErrors> {
+        self.decode_headers()?;
+
+        if self.embedded_format().is_none() {
+            return Ok(None);
+        }
+        let start = u64::from(self.hsize);
+        let end = self.bytes.seek(ZSeekFrom::End(0))?;
+        let available = end.saturating_sub(start);
+
+        // the size may be zero, in which case the rest of the file is the image
+        let size = match u64::from(self.image_size) {
+            0 => available,
+            size if size <= available => size,
+            _ => {
+                return Err(BmpDecoderErrors::GenericStatic(
+                    "Embedded image extends past the end of the file"
+                ))
+            }
+        };
+        let mut data = vec![0; size as usize];
+
+        self.bytes.set_position(self.hsize as usize)?;
+        self.bytes.read_exact_bytes(&mut data)?;
+
+        Ok(Some(data))
+    }
+
     /// Decode an encoded image into a buffer or return an error
     /// if something bad occurred
     ///
@@ -729,6 +893,13 @@ where
     ) -> Result<(), BmpDecoderErrors> {
         self.decode_headers()?;
 
+        if let Some(format) = self.embedded_format() {
+            return Err(BmpDecoderErrors::Generic(format!(
+                "Image contains an embedded {:?} file, extract it with embedded_image()",
+                format
+            )));
+        }
+
         let output_size = self
             .output_buf_size()
             .ok_or(BmpDecoderErrors::OverFlowOccurred)?;
@@ -817,9 +988,9 @@ where
                                 // Anonymous conversion function
                                 let conv_function = |v: u32, a: &mut [u8]| {
                                     if PRESERVE_BGRA {
-                                        a[0] = shift_signed(v & mb, rshift, rcount) as u8;
+                                        a[0] = shift_signed(v & mb, bshift, bcount) as u8;
                                         a[1] = shift_signed(v & mg, gshift, gcount) as u8;
-                                        a[2] = shift_signed(v & mr, bshift, bcount) as u8;
+                                        a[2] = shift_signed(v & mr, rshift, rcount) as u8;
                                     } else {
                                         a[0] = shift_signed(v & mr, rshift, rcount) as u8;
                                         a[1] = shift_signed(v & mg, gshift, gcount) as u8;
@@ -1019,10 +1190,55 @@ where
             }
             self.image_in_bgra = true;
         }
+        #[cfg(feature = "color-management")]
+        self.convert_to_srgb(buf, PRESERVE_BGRA);
 
         Ok(())
     }
 
+    /// Convert images in a calibrated RGB color space to sRGB using
+    /// the gamma values in the header
+    ///
+    /// Other color space types are already sRGB or are described by an
+    /// ICC profile, which is left to the caller
+    #[cfg(feature = "color-management")]
+    fn convert_to_srgb(&self, buf: &mut [u8], bgra: bool) {
+        let Some(mut gamma) = self.gamma else {
+            return;
+        };
+        let components = self.pix_fmt.num_components();
+
+        if self.colorspace_type != Some(BmpColorSpaceType::CalibratedRgb)
+            || components < 3
+            || gamma.iter().any(|x| *x <= 0.0)
+        {
+            return;
+        }
+        trace!("Converting calibrated RGB with gamma {:?} to sRGB", gamma);
+
+        if bgra {
+            gamma.swap(0, 2);
+        }
+        let mut tables = [[0_u8; 256]; 3];
+
+        for (table, gamma) in tables.iter_mut().zip(gamma) {
+            for (i, value) in table.iter_mut().enumerate() {
+                let linear = (i as f32 / 255.0).powf(gamma);
+                let encoded = if linear <= 0.003_130_8 {
+                    linear * 12.92
+                } else {
+                    1.055 * linear.powf(1.0 / 2.4) - 0.055
+                };
+                *value = (encoded * 255.0 + 0.5).clamp(0.0, 255.0) as u8;
+            }
+        }
+        for pixel in buf.chunks_exact_mut(components) {
+            for (value, table) in pixel.iter_mut().zip(&tables) {
+                *value = table[usize::from(*value)];
+            }
+        }
+    }
+
     /// Expand paletted bmp images to full version
     ///
     ///
@@ -1405,7 +1621,10 @@ where
                     }
                 }
             }
-            warn!("RLE warning, no end of picture code");
+            self.add_warning(
+                DecodeWarningKind::CorruptData,
+                "RLE warning, no end of picture code"
+            );
             Ok(pixels)
         }
     }
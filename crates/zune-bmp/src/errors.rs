@@ -10,6 +10,7 @@ use alloc::string::String;
 use core::fmt::{Debug, Formatter};
 
 use zune_core::bytestream::ZByteIoError;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
 
 /// BMP errors that can occur during decoding
 #[non_exhaustive]
@@ -71,3 +72,18 @@ impl From<ZByteIoError> for BmpDecoderErrors {
         BmpDecoderErrors::IoErrors(value)
     }
 }
+
+impl ZErrorCodeTrait for BmpDecoderErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::InvalidMagicBytes | Self::GenericStatic(_) | Self::Generic(_) => {
+                ZErrorCode::CorruptData
+            }
+            Self::TooSmallBuffer(_, _) => ZErrorCode::InvalidInput,
+            Self::TooLargeDimensions(_, _, _) | Self::OverFlowOccurred => {
+                ZErrorCode::LimitsExceeded
+            }
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
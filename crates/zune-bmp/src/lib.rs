@@ -20,14 +20,20 @@
 //! - RLE (4 bit and 8 bit)
 //! - Paletted images(1 bit, 2 bits, 4  bits and 8 bits)
 //! - Masked images (16 bit and 32 bit formats)
+//! - Embedded PNG and JPEGs, these are extracted but not decoded,
+//!   see [`BmpDecoder::embedded_image`]
 //!
-//! # Unsupported formats
-//! - Embedded PNG and JPEGs
-//! - Images with embedded color profiles. (the embedded color profile is ignored)
+//! # Color management
+//! V4 and V5 headers describe the color space of the image, the embedded ICC profile
+//! is returned by [`BmpDecoder::icc_profile`] and applying it is left to the caller.
+//!
+//! With the `color-management` feature, images in a calibrated RGB color space
+//! (e.g. linear images, which have a gamma of 1.0) are converted to sRGB when decoding.
 //!
 //! # Features
 //!  - `log`: Use the `log` crate features to print image information when decoding
 //!  - `std`: Allow direct decoding from anything that implements `std::io::BufRead` + `std::io::Seek`
+//!  - `color-management`: Convert calibrated RGB images to sRGB, this needs `std` for floating point functions
 //!
 //! # Usage
 //!  It is recommended that if you have an in memory buffer you use
@@ -75,9 +81,12 @@
 extern crate alloc;
 
 extern crate core;
+#[cfg(feature = "color-management")]
+extern crate std;
 
 pub use zune_core;
 
+pub use crate::common::{BmpColorSpaceType, BmpEmbeddedFormat};
 pub use crate::decoder::{probe_bmp, BmpDecoder};
 pub use crate::errors::BmpDecoderErrors;
 
@@ -0,0 +1,27 @@
+//! Generate the C header `zil.h` from the exported functions
+//!
+//! The header is written to `OUT_DIR`, the build never touches the source tree.
+//! `test_header_is_up_to_date` checks that the `include/zil.h` copy matches it,
+//! configuration lives in `cbindgen.toml`
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("cbindgen.toml should be a valid cbindgen configuration");
+
+    match cbindgen::generate_with_config(&crate_dir, config) {
+        Ok(bindings) => {
+            bindings.write_to_file(out_dir.join("zil.h"));
+        }
+        // don't fail the build, the checked in header is still usable,
+        // test_header_is_up_to_date reports the missing header
+        Err(err) => println!("cargo:warning=Could not generate C header: {err}")
+    }
+
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+}
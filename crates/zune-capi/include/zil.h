@@ -1,7 +1,7 @@
 #ifndef ZIL_IMAGE
 #define ZIL_IMAGE
 
-/* Generated with cbindgen:0.26.0 */
+/* Generated with cbindgen:0.29.4 */
 
 /* Warning, this file is autogenerated by cbindgen. Don't modify this manually. */
 
@@ -12,57 +12,67 @@
 #include <stdlib.h>
 
 
-typedef enum ZImageColorspace {
-  /**
-   * Unknown image colorspace
-   */
-  ZilUnknownColorspace = 0,
-  /**
-   * Red, Green , Blue
-   */
-  ZilRGB,
+/**
+ * Version of the C ABI
+ *
+ * This is incremented whenever a change breaks binary compatibility with
+ * programs built against an earlier header, e.g. changing the layout of a struct,
+ * the value of an enum or the signature of a function.
+ *
+ * Compare it with the value returned by `zil_abi_version` to ensure the library
+ * loaded at runtime matches the header used at compile time
+ */
+#define ZIL_ABI_VERSION 1
+
+/**
+ * Various image formats that are supported by the library
+ * in one way or another
+ *
+ * Some of them have partial support, i.e there is only a decoder bundled in
+ * while others have full support
+ */
+typedef enum ZImageFormat {
   /**
-   * Red, Green, Blue, Alpha
+   * Any unknown format
    */
-  ZilRGBA,
+  ZilUnknownFormat = 0,
   /**
-   * YUV colorspace
+   * Joint Photographic Experts Group
    */
-  ZilYCbCr,
+  ZilJPEG,
   /**
-   * Grayscale colorspace
+   * Portable Network Graphics
    */
-  ZilLuma,
+  ZilPNG,
   /**
-   * Grayscale with alpha colorspace
+   * Portable Pixel Map image
    */
-  ZilLumaA,
-  ZilYCCK,
+  ZilPPM,
   /**
-   * Cyan , Magenta, Yellow, Black
+   * Photoshop PSD component
    */
-  ZilCMYK,
+  ZilPSD,
   /**
-   * Blue, Green, Red
+   * Farbfeld format
    */
-  ZilBGR,
+  ZilFarbfeld,
   /**
-   * Blue, Green, Red, Alpha
+   * Quite Okay Image
    */
-  ZilBGRA,
+  ZilQOI,
   /**
-   * Alpha, Blue Green, Red
+   * JPEG XL, new format
    */
-  ZilARGB,
+  ZilJPEG_XL,
   /**
-   * Hue, Saturation, Lightness,
+   * Radiance HDR decoder
    */
-  ZilHSL,
+  ZilHDR,
   /**
-   * Hue, Saturation,Variance
+   * Windows Bitmap Files
    */
-  ZilHSV,
-} ZImageColorspace;
+  ZilBMP,
+} ZImageFormat;
 
 /**
  * Image depth information
@@ -90,107 +100,186 @@ typedef enum ZImageDepth {
    */
   ZilU16 = 2,
   /**
-   * Float 32 images
+   * Float 32 images   
    */
   ZilF32 = 4,
 } ZImageDepth;
 
 /**
- * Various image formats that are supported by the library
- * in one way or another
+ * Various representations of things that may go wrong
  *
- * Some of them have partial support, i.e there is only a decoder bundled in
- * while others have full support
+ * The numeric values are stable and won't change between releases
  */
-typedef enum ZImageFormat {
+typedef enum ZStatusType {
   /**
-   * Any unknown format
+   * Everything is okay, operation succeeded
    */
-  ZilUnknownFormat = 0,
+  ZilOk = 0,
   /**
-   * Joint Photographic Experts Group
+   * The buffer passed to a function wasn't enough to
+   * store the results
    */
-  ZilJPEG,
+  ZilNotEnoughSpaceInDest = 1,
   /**
-   * Portable Network Graphics
+   * An error that doesn't fit into a specific genre
    */
-  ZilPNG,
+  ZilGeneric = 2,
   /**
-   * Portable Pixel Map image
+   * An error originating from decoding
    */
-  ZilPPM,
+  ZilDecodeErrors = 3,
   /**
-   * Photoshop PSD component
+   * An error originating from Input output errors
    */
-  ZilPSD,
+  ZilIoErrors = 4,
   /**
-   * Farbfeld format
+   * Malloc failed
    */
-  ZilFarbfeld,
+  ZilMallocFailed = 5,
   /**
-   * Quite Okay Image
+   * Status is null, indicates the passed status value is null
+   * useful when we have been asked for status code but
+   * passed a null status
    */
-  ZilQOI,
+  ZilNullStatus = 6,
   /**
-   * JPEG XL, new format
+   * Image is null
+   *
+   * An operation expecting a non_null image got a null image
    */
-  ZilJPEG_XL,
+  ZilImageIsNull = 7,
   /**
-   * Radiance HDR decoder
+   * Image operation failed
    */
-  ZilHDR,
+  ZilImageOperationError = 8,
+} ZStatusType;
+
+/**
+ * The broad category of an error
+ *
+ * This mirrors the error codes shared by all zune crates, and allows
+ * callers to branch on the kind of error without parsing messages.
+ *
+ * The numeric values are stable and won't change between releases
+ */
+typedef enum ZErrorCategory {
   /**
-   * Windows Bitmap Files
+   * No error occurred
    */
-  ZilBMP,
-} ZImageFormat;
+  ZilErrorNone = 0,
+  /**
+   * The input is malformed, truncated or otherwise not a valid image
+   */
+  ZilErrorCorruptData = 1,
+  /**
+   * The input is valid but uses a feature that isn't supported
+   */
+  ZilErrorUnsupported = 2,
+  /**
+   * The image exceeds configured limits or memory could not be allocated
+   */
+  ZilErrorLimitsExceeded = 3,
+  /**
+   * Reading from or writing to a file failed
+   */
+  ZilErrorIo = 4,
+  /**
+   * The caller passed arguments that aren't valid for the operation
+   */
+  ZilErrorInvalidInput = 5,
+  /**
+   * An internal error, this is a bug
+   */
+  ZilErrorInternal = 6,
+} ZErrorCategory;
 
 /**
- * Various representations of things that may go wrong
+ * Methods used to composite one image on top of another
  */
-typedef enum ZStatusType {
+typedef enum ZCompositeMethod {
   /**
-   * Everything is okay, operation succeeded
+   * Put the source over the destination
    */
-  Ok = 0,
+  ZilCompositeOver = 0,
   /**
-   * The buffer passed to a function wasn't enough to
-   * store the results
+   * Replace the destination with the source
    */
-  NotEnoughSpaceInDest,
+  ZilCompositeSrc,
   /**
-   * An error that doesn't fit into a specific genre
+   * Keep the destination as is
    */
-  Generic,
+  ZilCompositeDst,
   /**
-   * An error originating from decoding
+   * Mask the destination with the source's alpha channel
    */
-  DecodeErrors,
+  ZilCompositeDstIn,
+} ZCompositeMethod;
+
+typedef enum ZImageColorspace {
   /**
-   * An error originating from Input output errors
+   * Unknown image colorspace
    */
-  IoErrors,
+  ZilUnknownColorspace = 0,
   /**
-   * Malloc failed
+   * Red, Green , Blue
    */
-  MallocFailed,
+  ZilRGB,
   /**
-   * Status is null, indicates the passed status value is null
-   * useful when we have been asked for status code but
-   * passed a null status
+   * Red, Green, Blue, Alpha
    */
-  NullStatus,
+  ZilRGBA,
   /**
-   * Image is null
-   *
-   * An operation expecting a non_null image got a null image
+   * YUV colorspace
    */
-  ImageIsNull,
+  ZilYCbCr,
   /**
-   * Image operation failed
+   * Grayscale colorspace
    */
-  ImageOperationError,
-} ZStatusType;
+  ZilLuma,
+  /**
+   * Grayscale with alpha colorspace
+   */
+  ZilLumaA,
+  ZilYCCK,
+  /**
+   * Cyan , Magenta, Yellow, Black
+   */
+  ZilCMYK,
+  /**
+   * Blue, Green, Red
+   */
+  ZilBGR,
+  /**
+   * Blue, Green, Red, Alpha
+   */
+  ZilBGRA,
+  /**
+   * Alpha, Blue Green, Red
+   */
+  ZilARGB,
+  /**
+   * Hue, Saturation, Lightness,
+   */
+  ZilHSL,
+  /**
+   * Hue, Saturation,Variance
+   */
+  ZilHSV,
+} ZImageColorspace;
+
+/**
+ * Algorithms used to resize an image
+ */
+typedef enum ZResizeMethod {
+  /**
+   * Bilinear interpolation, fast with good quality
+   */
+  ZilBilinear = 0,
+  /**
+   * Bicubic interpolation, slower but sharper
+   */
+  ZilBicubic,
+} ZResizeMethod;
 
 /**
  * Represents a single image
@@ -209,7 +298,14 @@ typedef struct Image Image;
  * For quickly checking if an operation succeeded, you can use `zil_status_ok` that
  * returns a boolean indicating whether something worked, true if operation succeeded, false otherwise
  *
- * To free the structure use
+ * To get the broad category of an error, e.g. to tell corrupt files apart from
+ * unsupported ones, use `zil_status_category`
+ *
+ * Each status owns its message, there is no global error state, so different
+ * threads can use different status structs concurrently. The message stays valid until
+ * the status is reused by another call or freed.
+ *
+ * To free the structure use `zil_status_free`
  *
  */
 typedef struct ZStatus {
@@ -217,7 +313,11 @@ typedef struct ZStatus {
   /**
    * A short message indicating what went wrong
    */
-  uint32_t *message;
+  char *message;
+  /**
+   * The broad category of the error
+   */
+  enum ZErrorCategory category;
 } ZStatus;
 
 typedef struct Image ZImage;
@@ -241,6 +341,14 @@ void __chkstk(void);
 void _fltused(void);
 #endif
 
+/**
+ * Return the ABI version of the library
+ *
+ * This matches `ZIL_ABI_VERSION` of the header the library was built with,
+ * if they differ, the program was compiled against an incompatible header
+ */
+uint32_t zil_abi_version(void);
+
 /**
  * Free a memory region that was allocated by zil_malloc or internally by the library
  *
@@ -431,6 +539,28 @@ void zil_imgproc_brighten(ZImage *image,
  */
 void zil_imgproc_change_depth(ZImage *image, enum ZImageDepth to, struct ZStatus *status);
 
+/**
+ * Composite an image on top of another image
+ *
+ * The source image is placed with its top left corner at `(x,y)` of the destination image,
+ * parts of the source falling outside the destination are ignored.
+ *
+ * Both images must have the same depth and colorspace
+ *
+ * \param image: Destination image, the result is written here
+ * \param src: Image composited on top of `image`, must not be null or the same as `image`
+ * \param x: Horizontal position of the source in the destination
+ * \param y: Vertical position of the source in the destination
+ * \param method: The composite method used to join the two images
+ * \param status: Image operation status, query this to know if the operation succeeded
+ */
+void zil_imgproc_composite(ZImage *image,
+                           const ZImage *src,
+                           size_t x,
+                           size_t y,
+                           enum ZCompositeMethod method,
+                           struct ZStatus *status);
+
 /**
  * Change image colorspace to a different one
  *
@@ -474,7 +604,7 @@ void zil_imgproc_crop(ZImage *image,
  * \code
  * pix = clamp((pix - black) * exposure)
  * \endcode
- *
+ *  
  * where `pix` is the current image pixel
  *
  * \param image: Non null image
@@ -489,37 +619,37 @@ void zil_imgproc_crop(ZImage *image,
 void zil_imgproc_exposure(ZImage *image, float exposure, float black_point, struct ZStatus *status);
 
 /**
- * Flip an image by reflecting pixels on its x-axis
+ * Flop an image by reflecting pixels on its y-axis
  *
  * \code
  * old image     new image
  * ┌─────────┐   ┌──────────┐
- * │a b c d e│   │j i h g f │
- * │f g h i j│   │e d c b a │
+ * │a b c d e│   │e d b c a │
+ * │f g h i j│   │j i h g f │
  * └─────────┘   └──────────┘
+ *
  * \endcode
  *
- * \param image: Image to flip
+ * \param image: Image to flop
  * \param status: Image execution reporter
  */
-void zil_imgproc_flip(ZImage *image, struct ZStatus *status);
+void zil_imgproc_flip_horizontal(ZImage *image, struct ZStatus *status);
 
 /**
- * Flop an image by reflecting pixels on its y-axis
+ * Flip an image by reflecting pixels on its x-axis
  *
  * \code
  * old image     new image
  * ┌─────────┐   ┌──────────┐
- * │a b c d e│   │e d b c a │
- * │f g h i j│   │j i h g f │
+ * │a b c d e│   │j i h g f │
+ * │f g h i j│   │e d c b a │
  * └─────────┘   └──────────┘
- *
  * \endcode
  *
- * \param image: Image to flop
+ * \param image: Image to flip
  * \param status: Image execution reporter
  */
-void zil_imgproc_flop(ZImage *image, struct ZStatus *status);
+void zil_imgproc_flip_mirror_x_axis(ZImage *image, struct ZStatus *status);
 
 /**
  * Gamma adjust an image
@@ -574,6 +704,35 @@ void zil_imgproc_invert(ZImage *image, struct ZStatus *status);
  */
 void zil_imgproc_median_blur(ZImage *image, size_t radius, struct ZStatus *status);
 
+/**
+ * Resize an image to a new width and height
+ *
+ * \param image: Non-null image to resize
+ * \param new_width: The new image width
+ * \param new_height: The new image height
+ * \param method: The algorithm used for resizing
+ * \param status: Image operation status, query this to know if the operation succeeded
+ *
+ * Sizes larger than the decoder limits fail with a limits exceeded error
+ */
+void zil_imgproc_resize(ZImage *image,
+                        size_t new_width,
+                        size_t new_height,
+                        enum ZResizeMethod method,
+                        struct ZStatus *status);
+
+/**
+ * Rotate an image clockwise by `angle` degrees
+ *
+ * Only rotations by 90, 180 and 270 degrees are supported, other angles
+ * fail with an unsupported error
+ *
+ * \param image: Non-null image to rotate
+ * \param angle: Angle to rotate by, one of 90, 180 or 270
+ * \param status: Image operation status, query this to know if the operation succeeded
+ */
+void zil_imgproc_rotate(ZImage *image, float angle, struct ZStatus *status);
+
 /**
  * Carry out scharr operations
  * The matrix for scharr is
@@ -741,9 +900,24 @@ struct ZImageMetadata zil_read_headers_from_memory(const uint8_t *input,
                                                    size_t input_size,
                                                    struct ZStatus *status);
 
+/**
+ * Return the broad category of the error contained in the status
+ *
+ * This can be used to branch on the kind of error, e.g. to tell a corrupt
+ * file apart from a file using an unsupported feature, without parsing the message
+ *
+ * \param status The status struct for which to extract the category from
+ *
+ * \returns ZErrorCategory, `ZilErrorNone` if the operation succeeded, and `ZilErrorInvalidInput`
+ * if status is null
+ */
+enum ZErrorCategory zil_status_category(const struct ZStatus *status);
+
 /**
  * Return the status code contained in the ZImStatus
  *
+ * The numeric values of the codes are stable
+ *
  * \param status The status struct for which to extract a status from
  *
  * \returns ZStatusCode, an enum that indicates if everything is okay or something went wrong
@@ -789,6 +963,13 @@ struct ZStatus *zil_status_new(void);
  */
 bool zil_status_ok(const struct ZStatus *status);
 
+/**
+ * Return the version of the library as a null terminated string, e.g. `"0.5.0"`
+ *
+ * The string is statically allocated and must not be freed
+ */
+const char *zil_version(void);
+
 /**
  * Create a new copy of the image independent from the previous
  * one and return it
@@ -808,6 +989,19 @@ enum ZImageColorspace zil_zimg_colorspace(ZImage *image, struct ZStatus *status)
  */
 enum ZImageDepth zil_zimg_depth(ZImage *image, struct ZStatus *status);
 
+/**
+ * Get the pixel density of the image in dots per inch
+ *
+ * \param image: A non-null image instance
+ * \param x_dpi: Will be filled with the horizontal density, can be null
+ * \param y_dpi: Will be filled with the vertical density, can be null
+ * \param status: Image status, may be null
+ *
+ * \returns true if the image specifies its density, false otherwise, in which case
+ * `x_dpi` and `y_dpi` are left untouched
+ */
+bool zil_zimg_dpi(const ZImage *image, float *x_dpi, float *y_dpi, struct ZStatus *status);
+
 /**
  * Free an image
  *
@@ -884,6 +1078,22 @@ size_t zil_zimg_get_out_buffer_size(ZImage *image, struct ZStatus *status);
  */
 size_t zil_zimg_height(ZImage *image, struct ZStatus *status);
 
+/**
+ * Get the ICC profile embedded in the image
+ *
+ * The returned memory is owned by the image, it stays valid until the image
+ * is modified or freed, copy it if you need it for longer
+ *
+ * \param image: A non-null image instance
+ * \param length: Will be filled with the length of the profile, or 0 if there is no profile, can be null
+ * \param status: Image status, may be null
+ *
+ * \returns A pointer to the first byte of the profile, or null if the image has no profile or is null
+ */
+const uint8_t *zil_zimg_icc(const ZImage *image,
+                            size_t *length,
+                            struct ZStatus *status);
+
 /**
  * Create an empty dummy image struct
  *
@@ -905,6 +1115,38 @@ ZImage *zil_zimg_new(void);
  */
 void zil_zimg_open(const char *file, ZImage *image, struct ZStatus *status);
 
+/**
+ * Get the exif orientation of the image
+ *
+ * This is a value between 1 and 8 as defined by the exif specification, where 1 means
+ * the image is stored in its display orientation, see `zil_imgproc_auto_orient` to apply it
+ *
+ * \param image: A non-null image instance
+ * \param status: Image status, may be null
+ *
+ * \returns The orientation, or 0 if the image doesn't have one or is null
+ */
+uint16_t zil_zimg_orientation(const ZImage *image, struct ZStatus *status);
+
+/**
+ * Get the physical size of the image in inches, i.e. the size it should
+ * have when printed
+ *
+ * This is calculated from the current image dimensions and its pixel density
+ *
+ * \param image: A non-null image instance
+ * \param width: Will be filled with the width in inches, can be null
+ * \param height: Will be filled with the height in inches, can be null
+ * \param status: Image status, may be null
+ *
+ * \returns true if the image specifies its density, false otherwise, in which case
+ * `width` and `height` are left untouched
+ */
+bool zil_zimg_physical_size(const ZImage *image,
+                            float *width,
+                            float *height,
+                            struct ZStatus *status);
+
 /**
  * Decode an image already in memory
  *
@@ -970,4 +1212,4 @@ size_t zil_zimg_write_to_output(const ZImage *image,
                                 size_t output_size,
                                 struct ZStatus *status);
 
-#endif /* ZIL_IMAGE */
+#endif  /* ZIL_IMAGE */
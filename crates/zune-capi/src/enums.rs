@@ -1,6 +1,8 @@
 use zune_core::bit_depth::BitDepth;
 use zune_core::colorspace::ColorSpace;
 use zune_image::codecs::ImageFormat;
+use zune_imageprocs::composite::CompositeMethod;
+use zune_imageprocs::resize::ResizeMethod;
 
 /// Various image formats that are supported by the library
 /// in one way or another
@@ -184,6 +186,53 @@ impl From<ColorSpace> for ZImageColorspace {
     }
 }
 
+/// Algorithms used to resize an image
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[allow(dead_code)] // variants are only constructed by C callers
+pub enum ZResizeMethod {
+    /// Bilinear interpolation, fast with good quality
+    ZilBilinear = 0,
+    /// Bicubic interpolation, slower but sharper
+    ZilBicubic
+}
+
+impl ZResizeMethod {
+    pub(crate) fn to_method(self) -> ResizeMethod {
+        match self {
+            ZResizeMethod::ZilBilinear => ResizeMethod::Bilinear,
+            ZResizeMethod::ZilBicubic => ResizeMethod::Bicubic
+        }
+    }
+}
+
+/// Methods used to composite one image on top of another
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[allow(dead_code)] // variants are only constructed by C callers
+#[allow(clippy::enum_variant_names)]
+pub enum ZCompositeMethod {
+    /// Put the source over the destination
+    ZilCompositeOver = 0,
+    /// Replace the destination with the source
+    ZilCompositeSrc,
+    /// Keep the destination as is
+    ZilCompositeDst,
+    /// Mask the destination with the source's alpha channel
+    ZilCompositeDstIn
+}
+
+impl ZCompositeMethod {
+    pub(crate) fn to_method(self) -> CompositeMethod {
+        match self {
+            ZCompositeMethod::ZilCompositeOver => CompositeMethod::Over,
+            ZCompositeMethod::ZilCompositeSrc => CompositeMethod::Src,
+            ZCompositeMethod::ZilCompositeDst => CompositeMethod::Dst,
+            ZCompositeMethod::ZilCompositeDstIn => CompositeMethod::DstIn
+        }
+    }
+}
+
 ///\brief Creates a new depth that can be passed to functions that require
 /// depth
 ///
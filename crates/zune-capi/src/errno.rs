@@ -2,9 +2,14 @@ use std::ffi::{c_char, CString};
 use std::mem::size_of;
 use std::ptr;
 
+use zune_core::errors::ZErrorCode;
+use zune_image::errors::ImageErrors;
+
 use crate::utils::{zil_free, zil_malloc};
 
 /// Various representations of things that may go wrong
+///
+/// The numeric values are stable and won't change between releases
 #[repr(C)]
 #[derive(Copy, Clone, Eq, PartialEq)]
 #[allow(clippy::enum_variant_names)]
@@ -13,26 +18,84 @@ pub enum ZStatusType {
     ZilOk = 0,
     /// The buffer passed to a function wasn't enough to
     /// store the results
-    ZilNotEnoughSpaceInDest,
+    ZilNotEnoughSpaceInDest = 1,
     /// An error that doesn't fit into a specific genre
-    ZilGeneric,
+    ZilGeneric = 2,
     /// An error originating from decoding
-    ZilDecodeErrors,
+    ZilDecodeErrors = 3,
     /// An error originating from Input output errors
-    ZilIoErrors,
+    ZilIoErrors = 4,
     /// Malloc failed
-    ZilMallocFailed,
+    ZilMallocFailed = 5,
     /// Status is null, indicates the passed status value is null
     /// useful when we have been asked for status code but
     /// passed a null status
-    ZilNullStatus,
+    ZilNullStatus = 6,
     /// Image is null
     ///
     /// An operation expecting a non_null image got a null image
-    ZilImageIsNull,
+    ZilImageIsNull = 7,
     /// Image operation failed
-    ZilImageOperationError // Image encoding failed
-                           //ImageEncodingFailed
+    ZilImageOperationError = 8 // Image encoding failed
+                               //ImageEncodingFailed
+}
+
+impl ZStatusType {
+    /// The category reported for a status when the error that caused it
+    /// doesn't carry one
+    const fn default_category(self) -> ZErrorCategory {
+        match self {
+            ZStatusType::ZilOk => ZErrorCategory::ZilErrorNone,
+            ZStatusType::ZilNotEnoughSpaceInDest
+            | ZStatusType::ZilNullStatus
+            | ZStatusType::ZilImageIsNull => ZErrorCategory::ZilErrorInvalidInput,
+            ZStatusType::ZilDecodeErrors => ZErrorCategory::ZilErrorCorruptData,
+            ZStatusType::ZilIoErrors => ZErrorCategory::ZilErrorIo,
+            ZStatusType::ZilMallocFailed => ZErrorCategory::ZilErrorLimitsExceeded,
+            ZStatusType::ZilGeneric | ZStatusType::ZilImageOperationError => {
+                ZErrorCategory::ZilErrorInternal
+            }
+        }
+    }
+}
+
+/// The broad category of an error
+///
+/// This mirrors the error codes shared by all zune crates, and allows
+/// callers to branch on the kind of error without parsing messages.
+///
+/// The numeric values are stable and won't change between releases
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[allow(clippy::enum_variant_names)]
+pub enum ZErrorCategory {
+    /// No error occurred
+    ZilErrorNone = 0,
+    /// The input is malformed, truncated or otherwise not a valid image
+    ZilErrorCorruptData = 1,
+    /// The input is valid but uses a feature that isn't supported
+    ZilErrorUnsupported = 2,
+    /// The image exceeds configured limits or memory could not be allocated
+    ZilErrorLimitsExceeded = 3,
+    /// Reading from or writing to a file failed
+    ZilErrorIo = 4,
+    /// The caller passed arguments that aren't valid for the operation
+    ZilErrorInvalidInput = 5,
+    /// An internal error, this is a bug
+    ZilErrorInternal = 6
+}
+
+impl From<ZErrorCode> for ZErrorCategory {
+    fn from(value: ZErrorCode) -> Self {
+        match value {
+            ZErrorCode::CorruptData => ZErrorCategory::ZilErrorCorruptData,
+            ZErrorCode::Unsupported => ZErrorCategory::ZilErrorUnsupported,
+            ZErrorCode::LimitsExceeded => ZErrorCategory::ZilErrorLimitsExceeded,
+            ZErrorCode::Io => ZErrorCategory::ZilErrorIo,
+            ZErrorCode::InvalidInput => ZErrorCategory::ZilErrorInvalidInput,
+            _ => ZErrorCategory::ZilErrorInternal
+        }
+    }
 }
 
 /// A status indicator that tells you more about things that went wrong
@@ -46,13 +109,22 @@ pub enum ZStatusType {
 /// For quickly checking if an operation succeeded, you can use `zil_status_ok` that
 /// returns a boolean indicating whether something worked, true if operation succeeded, false otherwise
 ///
-/// To free the structure use
+/// To get the broad category of an error, e.g. to tell corrupt files apart from
+/// unsupported ones, use `zil_status_category`
+///
+/// Each status owns its message, there is no global error state, so different
+/// threads can use different status structs concurrently. The message stays valid until
+/// the status is reused by another call or freed.
+///
+/// To free the structure use `zil_status_free`
 ///
 #[repr(C)]
 pub struct ZStatus {
-    pub status:  ZStatusType,
+    pub status:   ZStatusType,
     /// A short message indicating what went wrong
-    pub message: *mut char
+    pub message:  *mut c_char,
+    /// The broad category of the error
+    pub category: ZErrorCategory
 }
 
 impl ZStatus {
@@ -60,18 +132,39 @@ impl ZStatus {
     where
         T: Into<Vec<u8>>
     {
-        let msg = CString::new(message).unwrap();
+        ZStatus::new_with_category(message, status, status.default_category())
+    }
+    pub fn new_with_category<T>(
+        message: T, status: ZStatusType, category: ZErrorCategory
+    ) -> ZStatus
+    where
+        T: Into<Vec<u8>>
+    {
+        // interior nul bytes would truncate the message on the C side, drop them
+        let msg = CString::new(message).unwrap_or_else(|err| {
+            let mut bytes = err.into_vec();
+            bytes.retain(|x| *x != 0);
+            CString::new(bytes).unwrap()
+        });
         let mem = unsafe { zil_malloc(msg.as_bytes_with_nul().len()) };
-        // copy to memory
-        unsafe {
-            libc::strcpy(mem.cast(), msg.as_ptr());
+        if !mem.is_null() {
+            // copy to memory
+            unsafe {
+                libc::strcpy(mem.cast(), msg.as_ptr());
+            }
         }
 
         ZStatus {
             status,
-            message: mem.cast()
+            message: mem.cast(),
+            category
         }
     }
+    /// Create a status from an image error, the category is
+    /// taken from the error
+    pub fn from_image_error(error: &ImageErrors, status: ZStatusType) -> ZStatus {
+        ZStatus::new_with_category(error.to_string(), status, error.code().into())
+    }
     /// Return okay
     pub fn okay() -> ZStatus {
         ZStatus::new("Ok", ZStatusType::ZilOk)
@@ -118,6 +211,7 @@ pub unsafe extern "C" fn zil_status_new() -> *mut ZStatus {
     if !ptr.is_null() {
         (*ptr.cast::<ZStatus>()).message = mem.cast();
         (*ptr.cast::<ZStatus>()).status = ZStatusType::ZilOk;
+        (*ptr.cast::<ZStatus>()).category = ZErrorCategory::ZilErrorNone;
     }
     // make pointer
     ptr.cast()
@@ -125,6 +219,8 @@ pub unsafe extern "C" fn zil_status_new() -> *mut ZStatus {
 
 /// Return the status code contained in the ZImStatus
 ///
+/// The numeric values of the codes are stable
+///
 /// \param status The status struct for which to extract a status from
 ///
 /// \returns ZStatusCode, an enum that indicates if everything is okay or something went wrong
@@ -137,6 +233,24 @@ pub extern "C" fn zil_status_code(status: *const ZStatus) -> ZStatusType {
     unsafe { (*status).status }
 }
 
+/// Return the broad category of the error contained in the status
+///
+/// This can be used to branch on the kind of error, e.g. to tell a corrupt
+/// file apart from a file using an unsupported feature, without parsing the message
+///
+/// \param status The status struct for which to extract the category from
+///
+/// \returns ZErrorCategory, `ZilErrorNone` if the operation succeeded, and `ZilErrorInvalidInput`
+/// if status is null
+#[no_mangle]
+pub extern "C" fn zil_status_category(status: *const ZStatus) -> ZErrorCategory {
+    if status.is_null() {
+        return ZErrorCategory::ZilErrorInvalidInput;
+    }
+    // safety, checked above if it's null
+    unsafe { (*status).category }
+}
+
 /// Returns a null terminated string that contains more details about
 /// what went wrong
 ///
@@ -148,7 +262,12 @@ pub extern "C" fn zil_status_message(status: *const ZStatus) -> *const c_char {
     if status.is_null() {
         return ptr::null();
     }
-    unsafe { (*status).message.cast() }
+    let message = unsafe { (*status).message };
+    if message.is_null() {
+        // allocating the message failed
+        return c"".as_ptr();
+    }
+    message
 }
 
 /// Destroy a status indicator.
@@ -160,8 +279,7 @@ pub extern "C" fn zil_status_message(status: *const ZStatus) -> *const c_char {
 #[no_mangle]
 pub extern "C" fn zil_status_free(status: *mut ZStatus) {
     if !status.is_null() {
-        unsafe { zil_free((*status).message.cast()) }
-        // free object
+        // free object, this also frees the message
         unsafe { status.drop_in_place() }
 
         // free memory holding it
@@ -62,6 +62,132 @@ extern "C" fn zil_zimg_colorspace(image: *mut ZImage, status: *mut ZStatus) -> Z
     unsafe { ZImageColorspace::from((*image).colorspace()) }
 }
 
+/// Get the exif orientation of the image
+///
+/// This is a value between 1 and 8 as defined by the exif specification, where 1 means
+/// the image is stored in its display orientation, see `zil_imgproc_auto_orient` to apply it
+///
+/// \param image: A non-null image instance
+/// \param status: Image status, may be null
+///
+/// \returns The orientation, or 0 if the image doesn't have one or is null
+#[no_mangle]
+pub extern "C" fn zil_zimg_orientation(image: *const ZImage, status: *mut ZStatus) -> u16 {
+    if image.is_null() {
+        if !status.is_null() {
+            unsafe { *status = ZStatus::new("Image null", ZilImageIsNull) };
+        }
+        return 0;
+    }
+    unsafe { (*image).metadata().orientation().unwrap_or(0) }
+}
+
+/// Get the ICC profile embedded in the image
+///
+/// The returned memory is owned by the image, it stays valid until the image
+/// is modified or freed, copy it if you need it for longer
+///
+/// \param image: A non-null image instance
+/// \param length: Will be filled with the length of the profile, or 0 if there is no profile, can be null
+/// \param status: Image status, may be null
+///
+/// \returns A pointer to the first byte of the profile, or null if the image has no profile or is null
+#[no_mangle]
+pub extern "C" fn zil_zimg_icc(
+    image: *const ZImage, length: *mut usize, status: *mut ZStatus
+) -> *const u8 {
+    if !length.is_null() {
+        unsafe { *length = 0 };
+    }
+    if image.is_null() {
+        if !status.is_null() {
+            unsafe { *status = ZStatus::new("Image null", ZilImageIsNull) };
+        }
+        return ptr::null();
+    }
+    match unsafe { (*image).metadata().icc_chunk() } {
+        Some(icc) => {
+            if !length.is_null() {
+                unsafe { *length = icc.len() };
+            }
+            icc.as_ptr()
+        }
+        None => ptr::null()
+    }
+}
+
+/// Get the pixel density of the image in dots per inch
+///
+/// \param image: A non-null image instance
+/// \param x_dpi: Will be filled with the horizontal density, can be null
+/// \param y_dpi: Will be filled with the vertical density, can be null
+/// \param status: Image status, may be null
+///
+/// \returns true if the image specifies its density, false otherwise, in which case
+/// `x_dpi` and `y_dpi` are left untouched
+#[no_mangle]
+pub extern "C" fn zil_zimg_dpi(
+    image: *const ZImage, x_dpi: *mut f32, y_dpi: *mut f32, status: *mut ZStatus
+) -> bool {
+    if image.is_null() {
+        if !status.is_null() {
+            unsafe { *status = ZStatus::new("Image null", ZilImageIsNull) };
+        }
+        return false;
+    }
+    match unsafe { (*image).metadata().dpi() } {
+        Some((x, y)) => {
+            if !x_dpi.is_null() {
+                unsafe { *x_dpi = x };
+            }
+            if !y_dpi.is_null() {
+                unsafe { *y_dpi = y };
+            }
+            true
+        }
+        None => false
+    }
+}
+
+/// Get the physical size of the image in inches, i.e. the size it should
+/// have when printed
+///
+/// This is calculated from the current image dimensions and its pixel density
+///
+/// \param image: A non-null image instance
+/// \param width: Will be filled with the width in inches, can be null
+/// \param height: Will be filled with the height in inches, can be null
+/// \param status: Image status, may be null
+///
+/// \returns true if the image specifies its density, false otherwise, in which case
+/// `width` and `height` are left untouched
+#[no_mangle]
+pub extern "C" fn zil_zimg_physical_size(
+    image: *const ZImage, width: *mut f32, height: *mut f32, status: *mut ZStatus
+) -> bool {
+    if image.is_null() {
+        if !status.is_null() {
+            unsafe { *status = ZStatus::new("Image null", ZilImageIsNull) };
+        }
+        return false;
+    }
+    let image = unsafe { &*image };
+
+    match image.metadata().dpi() {
+        Some((x_dpi, y_dpi)) if x_dpi > 0.0 && y_dpi > 0.0 => {
+            let (w, h) = image.dimensions();
+            if !width.is_null() {
+                unsafe { *width = w as f32 / x_dpi };
+            }
+            if !height.is_null() {
+                unsafe { *height = h as f32 / y_dpi };
+            }
+            true
+        }
+        _ => false
+    }
+}
+
 /// Get output size, this returns the minimum array needed to hold a single
 /// interleaved frame of an image
 ///
@@ -141,7 +267,7 @@ pub extern "C" fn zil_zimg_write_to_output(
         Err(err) => {
             if !status.is_null() {
                 unsafe {
-                    *status = ZStatus::new(err.to_string(), ZStatusType::ZilImageOperationError)
+                    *status = ZStatus::from_image_error(&err, ZStatusType::ZilImageOperationError)
                 }
             }
             0
@@ -156,8 +282,20 @@ pub extern "C" fn zil_zimg_write_to_output(
 /// This is the preferred way to initialize this, not via memset or malloc+sizeof
 #[no_mangle]
 pub extern "C" fn zil_zimg_new() -> *mut ZImage {
-    let ptr = unsafe { libc::malloc(std::mem::size_of::<ZImage>()) }.cast();
-    unsafe { *ptr = ZImage::new(vec![], BitDepth::Unknown, 1, 1, ColorSpace::Unknown) };
+    let ptr: *mut ZImage = unsafe { libc::malloc(std::mem::size_of::<ZImage>()) }.cast();
+    if ptr.is_null() {
+        return ptr;
+    }
+    // the memory is uninitialized, write without dropping the previous contents
+    unsafe {
+        ptr.write(ZImage::new(
+            vec![],
+            BitDepth::Unknown,
+            1,
+            1,
+            ColorSpace::Unknown
+        ))
+    };
     ptr
 }
 /// Free an image
@@ -198,7 +336,7 @@ pub extern "C" fn zil_zimg_open(file: *const c_char, image: *mut ZImage, status:
             }
             Err(err) => {
                 if !status.is_null() {
-                    unsafe { *status = ZStatus::new(err.to_string(), ZilDecodeErrors) };
+                    unsafe { *status = ZStatus::from_image_error(&err, ZilDecodeErrors) };
                 }
             }
         },
@@ -236,7 +374,7 @@ pub extern "C" fn zil_zimg_read_from_memory(
         }
         Err(err) => {
             if !status.is_null() {
-                unsafe { *status = ZStatus::new(err.to_string(), ZilDecodeErrors) };
+                unsafe { *status = ZStatus::from_image_error(&err, ZilDecodeErrors) };
             }
         }
     };
@@ -269,7 +407,7 @@ pub extern "C" fn zil_zimg_write_to_disk(
         Ok(bytes) => {
             if let Err(e) = image.save(bytes) {
                 if !status.is_null() {
-                    unsafe { *status = ZStatus::new(e.to_string(), ZilDecodeErrors) };
+                    unsafe { *status = ZStatus::from_image_error(&e, ZilDecodeErrors) };
                 }
             }
         }
@@ -310,7 +448,7 @@ pub extern "C" fn zil_zimg_write_to_disk_with_format(
         Ok(bytes) => {
             if let Err(e) = image.save_to(bytes, format.to_format()) {
                 if !status.is_null() {
-                    unsafe { *status = ZStatus::new(e.to_string(), ZilDecodeErrors) };
+                    unsafe { *status = ZStatus::from_image_error(&e, ZilDecodeErrors) };
                 }
             }
         }
@@ -1,5 +1,6 @@
 use std::ffi::c_float;
 
+use zune_core::options::DecoderOptions;
 use zune_image::core_filters::colorspace::ColorspaceConv;
 use zune_image::core_filters::depth::Depth;
 use zune_image::traits::OperationsTrait;
@@ -7,6 +8,7 @@ use zune_imageprocs::auto_orient::AutoOrient;
 use zune_imageprocs::bilateral_filter::BilateralFilter;
 use zune_imageprocs::blend::Blend;
 use zune_imageprocs::brighten::Brighten;
+use zune_imageprocs::composite::Composite;
 use zune_imageprocs::contrast::Contrast;
 use zune_imageprocs::crop::Crop;
 use zune_imageprocs::exposure::Exposure;
@@ -15,13 +17,15 @@ use zune_imageprocs::gamma::Gamma;
 use zune_imageprocs::gaussian_blur::GaussianBlur;
 use zune_imageprocs::invert::Invert;
 use zune_imageprocs::median::Median;
+use zune_imageprocs::resize::Resize;
+use zune_imageprocs::rotate::Rotate;
 use zune_imageprocs::scharr::Scharr;
 use zune_imageprocs::sobel::Sobel;
 use zune_imageprocs::stretch_contrast::StretchContrast;
 use zune_imageprocs::transpose::Transpose;
 
-use crate::enums::{ZImageColorspace, ZImageDepth};
-use crate::errno::{ZStatus, ZStatusType};
+use crate::enums::{ZCompositeMethod, ZImageColorspace, ZImageDepth, ZResizeMethod};
+use crate::errno::{ZErrorCategory, ZStatus, ZStatusType};
 use crate::ZImage;
 
 fn exec_imgproc<T>(image: *mut ZImage, filter: T, status: *mut ZStatus)
@@ -41,7 +45,7 @@ where
 
     if let Err(err) = filter.execute_impl(image) {
         unsafe {
-            *status = ZStatus::new(err.to_string(), ZStatusType::ZilImageOperationError);
+            *status = ZStatus::from_image_error(&err, ZStatusType::ZilImageOperationError);
         }
     }
 }
@@ -395,3 +399,120 @@ pub extern "C" fn zil_imgproc_scharr(image: *mut ZImage, status: *mut ZStatus) {
 pub extern "C" fn zil_imgproc_median_blur(image: *mut ZImage, radius: usize, status: *mut ZStatus) {
     exec_imgproc(image, Median::new(radius), status)
 }
+
+/// Resize an image to a new width and height
+///
+/// \param image: Non-null image to resize
+/// \param new_width: The new image width
+/// \param new_height: The new image height
+/// \param method: The algorithm used for resizing
+/// \param status: Image operation status, query this to know if the operation succeeded
+///
+/// Sizes larger than the decoder limits fail with a limits exceeded error
+#[no_mangle]
+pub extern "C" fn zil_imgproc_resize(
+    image: *mut ZImage, new_width: usize, new_height: usize, method: ZResizeMethod,
+    status: *mut ZStatus
+) {
+    if status.is_null() {
+        return;
+    }
+    if !image.is_null() {
+        let img = unsafe { &*image };
+        let limits = DecoderOptions::default();
+        // a panic from an overflowing size or a failed allocation can't
+        // unwind into C, so reject sizes no decoder would produce first
+        let fits = new_width <= limits.max_width()
+            && new_height <= limits.max_height()
+            && new_width
+                .checked_mul(new_height)
+                .and_then(|x| x.checked_mul(img.colorspace().num_components()))
+                .and_then(|x| x.checked_mul(img.depth().size_of()))
+                .is_some();
+
+        if !fits {
+            unsafe {
+                *status = ZStatus::new_with_category(
+                    format!(
+                        "Resize size {new_width}x{new_height} exceeds the limit of {}x{}",
+                        limits.max_width(),
+                        limits.max_height()
+                    ),
+                    ZStatusType::ZilImageOperationError,
+                    ZErrorCategory::ZilErrorLimitsExceeded
+                );
+            }
+            return;
+        }
+    }
+    let filter = Resize::new(new_width, new_height, method.to_method());
+    exec_imgproc(image, filter, status)
+}
+
+/// Rotate an image clockwise by `angle` degrees
+///
+/// Only rotations by 90, 180 and 270 degrees are supported, other angles
+/// fail with an unsupported error
+///
+/// \param image: Non-null image to rotate
+/// \param angle: Angle to rotate by, one of 90, 180 or 270
+/// \param status: Image operation status, query this to know if the operation succeeded
+#[no_mangle]
+pub extern "C" fn zil_imgproc_rotate(image: *mut ZImage, angle: f32, status: *mut ZStatus) {
+    if status.is_null() {
+        return;
+    }
+    if ![90.0, 180.0, 270.0].contains(&angle) {
+        unsafe {
+            *status = ZStatus::new_with_category(
+                format!("Rotating by {angle} degrees is not supported, use 90, 180 or 270"),
+                ZStatusType::ZilImageOperationError,
+                ZErrorCategory::ZilErrorUnsupported
+            );
+        }
+        return;
+    }
+    exec_imgproc(image, Rotate::new(angle), status)
+}
+
+/// Composite an image on top of another image
+///
+/// The source image is placed with its top left corner at `(x,y)` of the destination image,
+/// parts of the source falling outside the destination are ignored.
+///
+/// Both images must have the same depth and colorspace
+///
+/// \param image: Destination image, the result is written here
+/// \param src: Image composited on top of `image`, must not be null or the same as `image`
+/// \param x: Horizontal position of the source in the destination
+/// \param y: Vertical position of the source in the destination
+/// \param method: The composite method used to join the two images
+/// \param status: Image operation status, query this to know if the operation succeeded
+#[no_mangle]
+pub extern "C" fn zil_imgproc_composite(
+    image: *mut ZImage, src: *const ZImage, x: usize, y: usize, method: ZCompositeMethod,
+    status: *mut ZStatus
+) {
+    if status.is_null() {
+        return;
+    }
+    if src.is_null() {
+        unsafe {
+            *status = ZStatus::new("Source image is null", ZStatusType::ZilImageIsNull);
+        }
+        return;
+    }
+    if std::ptr::eq(image, src) {
+        unsafe {
+            *status = ZStatus::new_with_category(
+                "Cannot composite an image onto itself",
+                ZStatusType::ZilImageOperationError,
+                ZErrorCategory::ZilErrorInvalidInput
+            );
+        }
+        return;
+    }
+    let src = unsafe { &*src };
+    let filter = Composite::new(src, method.to_method(), (x, y));
+    exec_imgproc(image, filter, status);
+}
@@ -15,7 +15,7 @@ use zune_image::codecs::ImageFormat;
 use zune_image::errors::ImageErrors;
 
 use crate::enums::{ZImageColorspace, ZImageDepth, ZImageFormat};
-use crate::errno::{zil_status_ok, ZStatus, ZStatusType};
+use crate::errno::{zil_status_ok, ZErrorCategory, ZStatus, ZStatusType};
 use crate::structs::ZImageMetadata;
 use crate::utils::zil_malloc;
 
@@ -335,7 +335,7 @@ pub extern "C" fn zil_imdecode_into(
             }
 
             if let Err(e) = imdecode_inner(ZCursor::new(contents), buf) {
-                unsafe { *status = ZStatus::new(e.to_string(), ZStatusType::ZilDecodeErrors) };
+                unsafe { *status = ZStatus::from_image_error(&e, ZStatusType::ZilDecodeErrors) };
                 return;
             }
             // write parameters
@@ -353,7 +353,10 @@ pub extern "C" fn zil_imdecode_into(
             }
 
             // safety, we checked above if the status is null
-            unsafe { (*status).status = ZStatusType::ZilOk }
+            unsafe {
+                (*status).status = ZStatusType::ZilOk;
+                (*status).category = ZErrorCategory::ZilErrorNone;
+            }
         }
     }
 }
@@ -9,6 +9,7 @@ mod improc;
 mod imread;
 mod structs;
 mod utils;
+mod version;
 
 pub type ZImage = Image;
 #[no_mangle]
@@ -72,3 +73,200 @@ fn test_status_works() {
         zil_free(c.cast());
     }
 }
+
+#[test]
+fn test_status_category() {
+    use crate::errno::{
+        zil_status_category, zil_status_code, zil_status_free, zil_status_new, ZErrorCategory,
+        ZStatusType
+    };
+    use crate::image::{zil_zimg_free, zil_zimg_new, zil_zimg_read_from_memory};
+
+    // a png signature with nothing after it
+    let data = b"\x89PNG\r\n\x1a\n";
+
+    unsafe {
+        let status = zil_status_new();
+        assert_eq!(zil_status_category(status), ZErrorCategory::ZilErrorNone);
+
+        let image = zil_zimg_new();
+        zil_zimg_read_from_memory(data.as_ptr(), data.len(), image, status);
+
+        assert!(zil_status_code(status) == ZStatusType::ZilDecodeErrors);
+        assert_eq!(
+            zil_status_category(status),
+            ZErrorCategory::ZilErrorCorruptData
+        );
+
+        zil_zimg_free(image);
+        zil_status_free(status);
+    }
+}
+
+#[test]
+fn test_imgproc_resize_rotate() {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::enums::ZResizeMethod;
+    use crate::errno::{
+        zil_status_category, zil_status_free, zil_status_new, zil_status_ok, ZErrorCategory
+    };
+    use crate::improc::{zil_imgproc_resize, zil_imgproc_rotate};
+
+    let mut image = ZImage::fill(10_u8, ColorSpace::RGB, 20, 10);
+
+    unsafe {
+        let status = zil_status_new();
+
+        zil_imgproc_resize(&mut image, 40, 30, ZResizeMethod::ZilBilinear, status);
+        assert!(zil_status_ok(status));
+        assert_eq!(image.dimensions(), (40, 30));
+
+        zil_imgproc_rotate(&mut image, 90.0, status);
+        assert!(zil_status_ok(status));
+        assert_eq!(image.dimensions(), (30, 40));
+
+        zil_imgproc_rotate(&mut image, 45.0, status);
+        assert_eq!(
+            zil_status_category(status),
+            ZErrorCategory::ZilErrorUnsupported
+        );
+
+        zil_status_free(status);
+    }
+}
+
+#[test]
+fn test_imgproc_invalid_sizes_and_aliasing() {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::enums::{ZCompositeMethod, ZResizeMethod};
+    use crate::errno::{zil_status_category, zil_status_free, zil_status_new, ZErrorCategory};
+    use crate::improc::{zil_imgproc_composite, zil_imgproc_resize};
+
+    let mut image = ZImage::fill(10_u8, ColorSpace::RGB, 20, 10);
+
+    unsafe {
+        let status = zil_status_new();
+
+        // the allocation size overflows, this used to panic across the C boundary
+        zil_imgproc_resize(&mut image, usize::MAX, 2, ZResizeMethod::ZilBilinear, status);
+        assert_eq!(
+            zil_status_category(status),
+            ZErrorCategory::ZilErrorLimitsExceeded
+        );
+        assert_eq!(image.dimensions(), (20, 10));
+        zil_status_free(status);
+
+        let status = zil_status_new();
+        let pointer: *mut ZImage = &mut image;
+        zil_imgproc_composite(pointer, pointer, 0, 0, ZCompositeMethod::ZilCompositeOver, status);
+        assert_eq!(
+            zil_status_category(status),
+            ZErrorCategory::ZilErrorInvalidInput
+        );
+        zil_status_free(status);
+    }
+}
+
+#[test]
+fn test_metadata_accessors() {
+    use std::ptr;
+
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::{zil_zimg_dpi, zil_zimg_icc, zil_zimg_orientation, zil_zimg_physical_size};
+
+    let mut image = ZImage::fill(10_u8, ColorSpace::RGB, 300, 150);
+    let (mut x, mut y) = (0.0, 0.0);
+    let mut length = 1;
+
+    unsafe {
+        assert_eq!(zil_zimg_orientation(&image, ptr::null_mut()), 0);
+        assert!(zil_zimg_icc(&image, &mut length, ptr::null_mut()).is_null());
+        assert_eq!(length, 0);
+        assert!(!zil_zimg_dpi(&image, &mut x, &mut y, ptr::null_mut()));
+        assert!(!zil_zimg_physical_size(&image, &mut x, &mut y, ptr::null_mut()));
+
+        image.metadata_mut().set_orientation(6);
+        image.metadata_mut().set_icc_chunk(b"icc".to_vec());
+        image.metadata_mut().set_dpi(300.0, 75.0);
+
+        assert_eq!(zil_zimg_orientation(&image, ptr::null_mut()), 6);
+
+        let icc = zil_zimg_icc(&image, &mut length, ptr::null_mut());
+        assert_eq!(std::slice::from_raw_parts(icc, length), b"icc");
+
+        assert!(zil_zimg_dpi(&image, &mut x, &mut y, ptr::null_mut()));
+        assert_eq!((x, y), (300.0, 75.0));
+
+        assert!(zil_zimg_physical_size(&image, &mut x, &mut y, ptr::null_mut()));
+        assert_eq!((x, y), (1.0, 2.0));
+    }
+}
+
+#[test]
+fn test_header_is_up_to_date() {
+    use std::path::Path;
+
+    // build.rs generates the header into OUT_DIR, the checked in copy has to match it
+    let generated = std::fs::read_to_string(concat!(env!("OUT_DIR"), "/zil.h"))
+        .expect("build.rs should have generated the header, see the build warnings");
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("include/zil.h");
+
+    if std::env::var_os("ZIL_UPDATE_HEADER").is_some() {
+        std::fs::write(&path, &generated).unwrap();
+    }
+    let checked_in = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        checked_in == generated,
+        "include/zil.h is out of date, refresh it with `ZIL_UPDATE_HEADER=1 cargo test`"
+    );
+}
+
+#[test]
+fn test_header_declares_all_symbols() {
+    use std::path::Path;
+
+    // the header is generated from the sources, so this catches functions cbindgen
+    // skipped, e.g. because they aren't pub
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let header = std::fs::read_to_string(dir.join("include/zil.h")).unwrap();
+
+    let abi_define = format!("#define ZIL_ABI_VERSION {}", version::ZIL_ABI_VERSION);
+    assert!(header.contains(&abi_define));
+
+    for entry in std::fs::read_dir(dir.join("src")).unwrap() {
+        let source = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+        let mut lines = source.lines().map(str::trim);
+
+        while let Some(line) = lines.next() {
+            if line != "#[no_mangle]" {
+                continue;
+            }
+            let declaration = lines.find(|x| !x.starts_with('#')).unwrap();
+            let name = declaration
+                .split("fn ")
+                .nth(1)
+                .and_then(|x| x.split('(').next())
+                .unwrap();
+
+            // the name may be preceded by a space or a pointer star
+            let declared = header
+                .match_indices(&format!("{name}("))
+                .any(|(pos, _)| matches!(header.as_bytes()[pos - 1], b' ' | b'*'));
+            assert!(declared, "{name} is not declared in include/zil.h");
+        }
+    }
+}
+
+#[test]
+fn test_version() {
+    use std::ffi::CStr;
+
+    use crate::version::{zil_abi_version, zil_version};
+
+    let version = unsafe { CStr::from_ptr(zil_version()) };
+    assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    assert_eq!(zil_abi_version(), version::ZIL_ABI_VERSION);
+}
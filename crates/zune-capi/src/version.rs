@@ -0,0 +1,28 @@
+use std::ffi::c_char;
+
+/// Version of the C ABI
+///
+/// This is incremented whenever a change breaks binary compatibility with
+/// programs built against an earlier header, e.g. changing the layout of a struct,
+/// the value of an enum or the signature of a function.
+///
+/// Compare it with the value returned by `zil_abi_version` to ensure the library
+/// loaded at runtime matches the header used at compile time
+pub const ZIL_ABI_VERSION: u32 = 1;
+
+/// Return the version of the library as a null terminated string, e.g. `"0.5.0"`
+///
+/// The string is statically allocated and must not be freed
+#[no_mangle]
+pub extern "C" fn zil_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Return the ABI version of the library
+///
+/// This matches `ZIL_ABI_VERSION` of the header the library was built with,
+/// if they differ, the program was compiled against an incompatible header
+#[no_mangle]
+pub extern "C" fn zil_abi_version() -> u32 {
+    ZIL_ABI_VERSION
+}
@@ -16,11 +16,11 @@
 //! here to minimize code reuse
 pub use reader::ZReader;
 pub use traits::*;
-pub use writer::ZWriter;
+pub use writer::{ZBufferedWriter, ZWriter};
 
 pub use crate::bytestream::reader::no_std_readers::*;
 //use crate::bytestream::reader::std_readers::*;
-pub use crate::bytestream::reader::ZByteIoError;
+pub use crate::bytestream::reader::{ZByteIoError, ZSeekFrom};
 
 mod reader;
 mod traits;
@@ -244,7 +244,13 @@ impl<T: ZByteReaderTrait> ZReader<T> {
                 }
                 Ok(&self.temp_buffer)
             }
-            Err(e) => Err(e)
+            Err(e) => {
+                // rewind even on failure so that the position is unchanged
+                if position != 0 {
+                    self.rewind(position)?;
+                }
+                Err(e)
+            }
         }
     }
     /// Read a fixed number of known bytes to a buffer and return the bytes or an error
@@ -341,6 +347,15 @@ impl<T: ZByteReaderTrait> ZReader<T> {
     pub fn read_bytes(&mut self, buf: &mut [u8]) -> Result<usize, ZByteIoError> {
         self.inner.read_bytes(buf)
     }
+
+    /// Read all bytes remaining in the inner reader to `sink`
+    ///
+    /// # Returns
+    /// - `Ok(usize)`: Number of bytes added to the sink
+    /// - An error if something went wrong
+    pub fn read_remaining(&mut self, sink: &mut Vec<u8>) -> Result<usize, ZByteIoError> {
+        self.inner.read_remaining(sink)
+    }
 }
 
 enum Mode {
@@ -5,8 +5,11 @@
  *
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
+pub use buffered_writer::ZBufferedWriter;
+
 use crate::bytestream::{ZByteIoError, ZByteWriterTrait};
 
+mod buffered_writer;
 mod no_std_writer;
 mod std_writer;
 
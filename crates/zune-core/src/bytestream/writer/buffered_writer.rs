@@ -0,0 +1,307 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! A buffered writer for sinks that can't seek
+//!
+//! The encoders in this project write their output front to back, so they
+//! work with any [`ZByteWriterTrait`] sink as is. Formats whose headers hold values only
+//! known after encoding, e.g. chunk sizes, would otherwise need a seekable sink, which
+//! a network socket or an HTTP body isn't. [`ZBufferedWriter`] collects output in chunks and
+//! lets callers hold back part of the stream for patching before it reaches the sink.
+
+use alloc::vec::Vec;
+
+use crate::bytestream::{ZByteIoError, ZByteWriterTrait};
+
+/// Default size of a chunk written to the underlying sink
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A writer adapter that buffers output and writes it to the
+/// underlying sink in fixed size chunks
+///
+/// Bytes written stay in memory until a full chunk is available, this turns
+/// the many small writes done by encoders into few large writes, which matters for
+/// sinks like sockets where each write is a system call.
+///
+/// Calling [`hold`](Self::hold) keeps everything written from that position onwards
+/// in memory until [`release`](Self::release) is called, in the meantime
+/// those bytes can be overwritten with [`patch`](Self::patch). This allows writing a
+/// placeholder header, encoding the data and then filling in the header without
+/// the sink having to support seeking.
+///
+/// Buffered bytes are only guaranteed to reach the sink after
+/// [`flush_bytes`](ZByteWriterTrait::flush_bytes) or [`into_inner`](Self::into_inner),
+/// the writer doesn't flush when dropped.
+///
+/// # Example
+/// - Write a size prefixed payload, the size is patched in after the payload is written
+/// ```
+/// use zune_core::bytestream::{ZBufferedWriter, ZWriter};
+///
+/// let mut sink = vec![];
+/// let mut writer = ZBufferedWriter::with_chunk_size(&mut sink, 4);
+///
+/// let size_position = writer.hold();
+/// {
+///     let mut stream = ZWriter::new(&mut writer);
+///     stream.write_u32_be_err(0).unwrap(); // placeholder
+///     stream.write_all(b"hello world").unwrap();
+/// }
+/// writer.patch(size_position, &11_u32.to_be_bytes()).unwrap();
+/// writer.release().unwrap();
+/// writer.into_inner().unwrap();
+///
+/// assert_eq!(&sink[..4], &[0, 0, 0, 11]);
+/// assert_eq!(&sink[4..], b"hello world");
+/// ```
+pub struct ZBufferedWriter<T: ZByteWriterTrait> {
+    inner:        T,
+    buffer:       Vec<u8>,
+    // stream position of the first byte in buffer
+    buffer_start: usize,
+    chunk_size:   usize,
+    // earliest position that can still be patched, nothing
+    // from here onwards is written to inner
+    hold:         Option<usize>
+}
+
+impl<T: ZByteWriterTrait> ZBufferedWriter<T> {
+    /// Create a new buffered writer with the default chunk size of 64 KiB
+    pub fn new(inner: T) -> ZBufferedWriter<T> {
+        ZBufferedWriter::with_chunk_size(inner, DEFAULT_CHUNK_SIZE)
+    }
+    /// Create a new buffered writer that writes to `inner` in chunks of `chunk_size`
+    /// bytes
+    ///
+    /// A chunk size of zero is treated as one
+    pub fn with_chunk_size(inner: T, chunk_size: usize) -> ZBufferedWriter<T> {
+        ZBufferedWriter {
+            inner,
+            buffer: Vec::new(),
+            buffer_start: 0,
+            chunk_size: chunk_size.max(1),
+            hold: None
+        }
+    }
+    /// Return the number of bytes written so far, including
+    /// those still buffered
+    pub fn position(&self) -> usize {
+        self.buffer_start + self.buffer.len()
+    }
+    /// Return the number of bytes currently buffered
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+    /// Keep everything written from the current position onwards in memory
+    /// so that it can be patched, returning the current position
+    ///
+    /// If a hold is already active, the earlier hold is kept
+    pub fn hold(&mut self) -> usize {
+        let position = self.position();
+        self.hold = Some(self.hold.map_or(position, |hold| hold.min(position)));
+        position
+    }
+    /// Overwrite bytes at `position`, which must not have been
+    /// written to the sink yet
+    ///
+    /// # Errors
+    /// - If the bytes were already written to the sink, see [`hold`](Self::hold)
+    /// - If the range extends past what has been written
+    pub fn patch(&mut self, position: usize, bytes: &[u8]) -> Result<(), ZByteIoError> {
+        if position < self.buffer_start {
+            return Err(ZByteIoError::Generic(
+                "Cannot patch bytes already written to the sink"
+            ));
+        }
+        let start = position - self.buffer_start;
+        let end = start
+            .checked_add(bytes.len())
+            .ok_or(ZByteIoError::Generic("Patch position overflows"))?;
+
+        if end > self.buffer.len() {
+            return Err(ZByteIoError::NotEnoughBuffer(self.buffer.len(), end));
+        }
+        self.buffer[start..end].copy_from_slice(bytes);
+        Ok(())
+    }
+    /// Release a hold, allowing held bytes to be written to the sink
+    pub fn release(&mut self) -> Result<(), ZByteIoError> {
+        self.hold = None;
+        self.write_chunks(false)
+    }
+    /// Flush all buffered bytes and return the underlying sink
+    ///
+    /// Any active hold is released
+    pub fn into_inner(mut self) -> Result<T, ZByteIoError> {
+        self.hold = None;
+        self.write_chunks(true)?;
+        self.inner.flush_bytes()?;
+        Ok(self.inner)
+    }
+    /// Return a reference to the underlying sink
+    pub const fn inner_ref(&self) -> &T {
+        &self.inner
+    }
+
+    fn push(&mut self, buf: &[u8]) -> Result<(), ZByteIoError> {
+        self.buffer.extend_from_slice(buf);
+        self.write_chunks(false)
+    }
+
+    /// Write buffered bytes before the hold to the sink, either only
+    /// whole chunks or everything
+    fn write_chunks(&mut self, everything: bool) -> Result<(), ZByteIoError> {
+        let available = match self.hold {
+            Some(hold) => hold - self.buffer_start,
+            None => self.buffer.len()
+        };
+        let length = if everything { available } else { available - available % self.chunk_size };
+        if length == 0 {
+            return Ok(());
+        }
+        for chunk in self.buffer[..length].chunks(self.chunk_size) {
+            self.inner.write_all_bytes(chunk)?;
+        }
+        self.buffer.drain(..length);
+        self.buffer_start += length;
+        Ok(())
+    }
+
+    fn flush_buffered(&mut self) -> Result<(), ZByteIoError> {
+        self.write_chunks(true)?;
+        self.inner.flush_bytes()
+    }
+}
+
+// With std, ZByteWriterTrait is implemented for everything that implements Write,
+// see std_writer.rs, so implementing Write gives us both.
+#[cfg(feature = "std")]
+impl<T: ZByteWriterTrait> std::io::Write for ZBufferedWriter<T> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.push(buf).map_err(to_io_error)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flush_buffered().map_err(to_io_error)
+    }
+}
+
+#[cfg(feature = "std")]
+fn to_io_error(error: ZByteIoError) -> std::io::Error {
+    match error {
+        ZByteIoError::StdIoError(err) => err,
+        err => std::io::Error::other(alloc::format!("{:?}", err))
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<T: ZByteWriterTrait> ZByteWriterTrait for &mut ZBufferedWriter<T> {
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<usize, ZByteIoError> {
+        self.push(buf)?;
+        Ok(buf.len())
+    }
+
+    fn write_all_bytes(&mut self, buf: &[u8]) -> Result<(), ZByteIoError> {
+        self.push(buf)
+    }
+
+    fn write_const_bytes<const N: usize>(&mut self, buf: &[u8; N]) -> Result<(), ZByteIoError> {
+        self.push(buf)
+    }
+
+    fn flush_bytes(&mut self) -> Result<(), ZByteIoError> {
+        self.flush_buffered()
+    }
+
+    fn reserve_capacity(&mut self, size: usize) -> Result<(), ZByteIoError> {
+        self.inner.reserve_capacity(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::bytestream::{ZBufferedWriter, ZByteIoError, ZByteWriterTrait};
+
+    // without std only a mutable reference to the writer implements the trait
+    fn write(mut writer: impl ZByteWriterTrait, bytes: &[u8]) {
+        writer.write_all_bytes(bytes).unwrap();
+    }
+
+    fn flush(mut writer: impl ZByteWriterTrait) {
+        writer.flush_bytes().unwrap();
+    }
+
+    #[test]
+    fn test_patch_past_held_region() {
+        let mut sink = vec![];
+        let mut writer = ZBufferedWriter::with_chunk_size(&mut sink, 2);
+
+        write(&mut writer, b"abcd");
+        let position = writer.hold();
+        write(&mut writer, b"efg");
+
+        // the first four bytes reached the sink, they can't be patched anymore
+        assert_eq!(writer.inner_ref().as_slice(), b"abcd");
+        assert!(writer.patch(1, b"x").is_err());
+        // ending past what has been written
+        assert!(matches!(
+            writer.patch(position + 1, b"xyz"),
+            Err(ZByteIoError::NotEnoughBuffer(3, 4))
+        ));
+        assert!(writer.patch(usize::MAX, b"x").is_err());
+
+        writer.patch(position + 1, b"xy").unwrap();
+        writer.release().unwrap();
+        writer.into_inner().unwrap();
+        assert_eq!(sink, b"abcdexy");
+    }
+
+    #[test]
+    fn test_nested_holds() {
+        let mut sink = vec![];
+        let mut writer = ZBufferedWriter::with_chunk_size(&mut sink, 1);
+
+        write(&mut writer, b"a");
+        let outer = writer.hold();
+        write(&mut writer, b"bc");
+        let inner = writer.hold();
+        write(&mut writer, b"de");
+
+        // the inner hold doesn't move the outer one forward
+        assert_eq!(writer.inner_ref().as_slice(), b"a");
+        writer.patch(outer, b"B").unwrap();
+        writer.patch(inner, b"D").unwrap();
+
+        // a single release lets everything through
+        writer.release().unwrap();
+        assert_eq!(writer.inner_ref().as_slice(), b"aBcDe");
+        assert_eq!(writer.buffered(), 0);
+    }
+
+    #[test]
+    fn test_flush_while_held() {
+        let mut sink = vec![];
+        let mut writer = ZBufferedWriter::new(&mut sink);
+
+        write(&mut writer, b"head");
+        let position = writer.hold();
+        write(&mut writer, b"size");
+        flush(&mut writer);
+
+        // flushing writes out what is before the hold and keeps the rest
+        assert_eq!(writer.inner_ref().as_slice(), b"head");
+        assert_eq!(writer.position(), 8);
+        writer.patch(position, b"SIZE").unwrap();
+
+        writer.into_inner().unwrap();
+        assert_eq!(sink, b"headSIZE");
+    }
+}
@@ -0,0 +1,107 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Machine readable error categories shared by all zune crates
+//!
+//! Every crate has its own error type carrying details specific to that format,
+//! which is what you want when debugging, but not when mapping errors to
+//! status codes in bindings, HTTP responses and the like.
+//!
+//! All error types implement [`ZErrorCodeTrait`], which maps each error into one of
+//! a small set of [`ZErrorCode`] categories, these categories and their numeric
+//! values are stable and won't change between releases.
+
+use core::fmt::{Display, Formatter};
+
+use crate::bytestream::ZByteIoError;
+
+/// A broad category of an error
+///
+/// The numeric values are stable and can be used across FFI boundaries,
+/// see [`value`](Self::value)
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ZErrorCode {
+    /// The input is malformed, truncated or otherwise not a valid image
+    CorruptData = 1,
+    /// The input is valid but uses a feature that isn't supported
+    Unsupported = 2,
+    /// The image exceeds configured limits, e.g. maximum width and height,
+    /// or a size calculation overflowed
+    LimitsExceeded = 3,
+    /// Reading from the source or writing to the sink failed
+    Io = 4,
+    /// The caller passed arguments that aren't valid for the operation,
+    /// e.g. a too small output buffer
+    InvalidInput = 5,
+    /// Something that shouldn't happen happened, this is a bug
+    Internal = 6
+}
+
+impl ZErrorCode {
+    /// Return the stable numeric value of this code
+    pub const fn value(self) -> u32 {
+        self as u32
+    }
+    /// Return the code for a numeric value returned by [`value`](Self::value)
+    /// or `None` if the value isn't a known code
+    pub const fn from_value(value: u32) -> Option<ZErrorCode> {
+        match value {
+            1 => Some(ZErrorCode::CorruptData),
+            2 => Some(ZErrorCode::Unsupported),
+            3 => Some(ZErrorCode::LimitsExceeded),
+            4 => Some(ZErrorCode::Io),
+            5 => Some(ZErrorCode::InvalidInput),
+            6 => Some(ZErrorCode::Internal),
+            _ => None
+        }
+    }
+    /// Return a short, stable, snake case name of this code
+    /// suitable for logs and serialized responses
+    pub const fn name(self) -> &'static str {
+        match self {
+            ZErrorCode::CorruptData => "corrupt_data",
+            ZErrorCode::Unsupported => "unsupported",
+            ZErrorCode::LimitsExceeded => "limits_exceeded",
+            ZErrorCode::Io => "io",
+            ZErrorCode::InvalidInput => "invalid_input",
+            ZErrorCode::Internal => "internal"
+        }
+    }
+}
+
+impl Display for ZErrorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Errors that can be mapped into a [`ZErrorCode`]
+///
+/// This is implemented by error types of every zune crate
+pub trait ZErrorCodeTrait {
+    /// Return the category of this error
+    fn code(&self) -> ZErrorCode;
+}
+
+impl ZErrorCodeTrait for ZByteIoError {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            #[cfg(feature = "std")]
+            ZByteIoError::StdIoError(_) => ZErrorCode::Io,
+            ZByteIoError::TryFromIntError(_) => ZErrorCode::LimitsExceeded,
+            // a read past the end of the data, the file is truncated
+            ZByteIoError::NotEnoughBytes(_, _) => ZErrorCode::CorruptData,
+            ZByteIoError::NotEnoughBuffer(_, _) => ZErrorCode::InvalidInput,
+            ZByteIoError::Generic(_)
+            | ZByteIoError::SeekError(_)
+            | ZByteIoError::SeekErrorOwned(_) => ZErrorCode::Io
+        }
+    }
+}
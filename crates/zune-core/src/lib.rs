@@ -15,6 +15,8 @@
 //! - Colorspace and bit depth information shared by images
 //! - Image decoder and encoder options
 //! - A simple enum type to hold image decoding results.
+//! - Error categories shared by the error types of all crates
+//! - Warnings decoders report for damaged or non standard files
 //!
 //! This library is `#[no_std]` with `alloc` feature needed for defining `Vec`
 //! which we need for storing decoded  bytes.
@@ -57,6 +59,8 @@ pub use log;
 pub mod bit_depth;
 pub mod bytestream;
 pub mod colorspace;
+pub mod errors;
 pub mod options;
 pub mod result;
 mod serde;
+pub mod warnings;
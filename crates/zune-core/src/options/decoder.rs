@@ -39,7 +39,10 @@ fn fast_options() -> DecoderFlags {
         png_add_alpha_channel:     false,
         png_strip_16_bit_to_8_bit: false,
         png_decode_animated:       true,
-        jxl_decode_animated:       true
+        png_salvage:               false,
+        jxl_decode_animated:       true,
+        dng_use_ahd:               false,
+        zune_auto_orient:          false
     }
 }
 
@@ -67,7 +70,10 @@ fn cmd_options() -> DecoderFlags {
         png_strip_16_bit_to_8_bit: false,
 
         png_decode_animated: true,
-        jxl_decode_animated: true
+        png_salvage:         false,
+        jxl_decode_animated: true,
+        dng_use_ahd:         false,
+        zune_auto_orient:    false
     }
 }
 
@@ -108,7 +114,14 @@ pub struct DecoderFlags {
     png_strip_16_bit_to_8_bit:    bool,
     /// Decode all frames for an animated images
     png_decode_animated:          bool,
-    jxl_decode_animated:          bool
+    /// Whether the png decoder should recover what it can from corrupt images
+    png_salvage:                  bool,
+    jxl_decode_animated:          bool,
+    /// Whether the DNG decoder should use AHD instead of bilinear demosaicing
+    dng_use_ahd:                  bool,
+    /// Whether the image should be oriented according to its exif orientation
+    /// after decoding
+    zune_auto_orient:             bool
 }
 
 /// Decoder options
@@ -157,7 +170,30 @@ pub struct DecoderOptions {
     flags:         DecoderFlags,
     /// The byte endian of the returned bytes will be stored in
     /// in case a single pixel spans more than a byte
-    endianness:    ByteEndian
+    endianness:    ByteEndian,
+    /// Size vector images are rasterized at, as `(width, height)`
+    ///
+    /// A zero dimension is computed from the other one keeping
+    /// the aspect ratio, if both are zero the image's own size is used
+    ///
+    /// - Default value: (0, 0)
+    /// - Respected by: `svg`
+    svg_size:      (usize, usize),
+    /// Preferred size of the image picked from files storing the same
+    /// image at multiple sizes
+    ///
+    /// The entry whose larger dimension is closest to this is used,
+    /// zero picks the largest one
+    ///
+    /// - Default value: 0
+    /// - Respected by: `ico`
+    ico_size:      usize,
+    /// Number of threads decoders may use, zero and one
+    /// decode on the calling thread
+    ///
+    /// - Default value: 1, callers opt in to multithreading
+    /// - Respected by: `jpeg` (baseline images with restart intervals)
+    num_threads:   u8
 }
 
 /// Initializers
@@ -312,6 +348,36 @@ impl DecoderOptions {
     pub const fn byte_endian(&self) -> ByteEndian {
         self.endianness
     }
+
+    /// Set whether the image should be rotated and flipped according to
+    /// its exif orientation after decoding
+    ///
+    /// - Default value: false
+    /// - Respected by: `zune-image` (for formats carrying exif orientation)
+    pub const fn set_auto_orient(mut self, yes: bool) -> Self {
+        self.flags.zune_auto_orient = yes;
+        self
+    }
+    /// Return whether the image should be rotated and flipped according to
+    /// its exif orientation after decoding
+    pub const fn auto_orient(&self) -> bool {
+        self.flags.zune_auto_orient
+    }
+
+    /// Set the number of threads allowed for multithreaded decoding
+    /// where supported
+    ///
+    /// Zero means use a single thread
+    pub const fn set_num_threads(mut self, threads: u8) -> Self {
+        self.num_threads = threads;
+        self
+    }
+
+    /// Return number of threads configured for multithreading
+    /// where possible
+    pub const fn num_threads(&self) -> u8 {
+        self.num_threads
+    }
 }
 
 /// PNG specific options
@@ -393,6 +459,24 @@ impl DecoderOptions {
         self.flags.png_decode_animated = yes;
         self
     }
+
+    /// Set whether the png decoder should salvage corrupt images
+    ///
+    /// In salvage mode, the decoder ignores CRC mismatches and stops at the
+    /// first unreadable chunk instead of erroring out, returning the rows
+    /// it could decode (missing rows are zero) and every ancillary chunk
+    /// parsed before the corruption.
+    ///
+    /// Useful for recovery tools, it's off by default.
+    #[must_use]
+    pub const fn png_set_salvage(mut self, yes: bool) -> Self {
+        self.flags.png_salvage = yes;
+        self
+    }
+    /// Return whether the png decoder should salvage corrupt images
+    pub const fn png_get_salvage(&self) -> bool {
+        self.flags.png_salvage
+    }
 }
 
 /// JPEG specific options
@@ -627,6 +711,61 @@ impl DecoderOptions {
         self
     }
 }
+/// SVG specific options
+impl DecoderOptions {
+    /// Return the size SVG images are rasterized at as `(width, height)`
+    ///
+    /// A zero dimension means it is derived from the image
+    pub const fn svg_get_size(&self) -> (usize, usize) {
+        self.svg_size
+    }
+    /// Set the size SVG images are rasterized at
+    ///
+    /// If one of `width` or `height` is zero, it is computed from the other
+    /// keeping the aspect ratio of the image, if both are zero the
+    /// size stored in the image is used
+    #[must_use]
+    pub const fn svg_set_size(mut self, width: usize, height: usize) -> Self {
+        self.svg_size = (width, height);
+        self
+    }
+}
+
+/// DNG specific options
+impl DecoderOptions {
+    /// Return whether the DNG decoder uses adaptive homogeneity-directed (AHD)
+    /// demosaicing instead of bilinear demosaicing
+    pub const fn dng_get_use_ahd(&self) -> bool {
+        self.flags.dng_use_ahd
+    }
+    /// Set whether the DNG decoder uses adaptive homogeneity-directed (AHD)
+    /// demosaicing instead of bilinear demosaicing
+    ///
+    /// AHD produces fewer artifacts around edges but is slower
+    pub const fn dng_set_use_ahd(mut self, yes: bool) -> Self {
+        self.flags.dng_use_ahd = yes;
+        self
+    }
+}
+/// ICO specific options
+impl DecoderOptions {
+    /// Return the preferred size of the image decoded from an icon
+    ///
+    /// Zero means the largest image is decoded
+    pub const fn ico_get_size(&self) -> usize {
+        self.ico_size
+    }
+    /// Set the preferred size of the image decoded from an icon
+    ///
+    /// Icons store the same image at multiple sizes, the one whose larger
+    /// dimension is closest to `size` is decoded, ties are broken by picking
+    /// the larger image. Zero decodes the largest image.
+    #[must_use]
+    pub const fn ico_set_size(mut self, size: usize) -> Self {
+        self.ico_size = size;
+        self
+    }
+}
 impl Default for DecoderOptions {
     /// Create a default and sane option for decoders
     ///
@@ -652,6 +791,21 @@ impl Default for DecoderOptions {
     ///  - JXL
     ///    - decode_animated: True: All frames in an animated image are decoded
     ///
+    ///  - SVG
+    ///    - size: (0,0): Images are rasterized at the size they specify
+    ///
+    ///  - ICO
+    ///    - size: 0: The largest image in the icon is decoded
+    ///
+    ///  - DNG
+    ///    - use_ahd: False: Bilinear demosaicing is used
+    ///
+    /// - zune-image
+    ///    - auto_orient: False: Images are returned in their stored orientation
+    ///
+    /// - Threads
+    ///    - num_threads: 1: Decoding happens on the calling thread
+    ///
     fn default() -> Self {
         Self {
             out_colorspace: ColorSpace::RGB,
@@ -660,7 +814,10 @@ impl Default for DecoderOptions {
             max_scans:      100,
             deflate_limit:  1 << 30,
             flags:          decoder_error_tolerance_mode(),
-            endianness:     ByteEndian::BE
+            endianness:     ByteEndian::BE,
+            svg_size:       (0, 0),
+            ico_size:       0,
+            num_threads:    1
         }
     }
 }
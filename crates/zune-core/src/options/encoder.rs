@@ -9,6 +9,55 @@
 use crate::bit_depth::BitDepth;
 use crate::colorspace::ColorSpace;
 
+/// Chroma subsampling to use when encoding
+///
+/// Currently only respected by the JPEG encoder
+#[derive(Copy, Debug, Clone, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ChromaSubsampling {
+    /// Let the encoder choose based on other options like quality
+    #[default]
+    Auto,
+    /// No subsampling, chroma is stored at full resolution
+    Yuv444,
+    /// Chroma is stored at half the vertical resolution
+    Yuv440,
+    /// Chroma is stored at half the horizontal resolution
+    Yuv422,
+    /// Chroma is stored at half the horizontal and vertical resolution
+    Yuv420,
+    /// Use the subsampling of the source image if known, otherwise
+    /// behaves like [`Auto`](Self::Auto)
+    SameAsSource
+}
+
+impl ChromaSubsampling {
+    /// Create a subsampling from horizontal and vertical subsampling factors
+    ///
+    /// Factors describe how many luma samples share one chroma sample
+    /// in that direction, returns `None` for unsupported factors
+    pub const fn from_factors(horizontal: u8, vertical: u8) -> Option<ChromaSubsampling> {
+        match (horizontal, vertical) {
+            (1, 1) => Some(ChromaSubsampling::Yuv444),
+            (1, 2) => Some(ChromaSubsampling::Yuv440),
+            (2, 1) => Some(ChromaSubsampling::Yuv422),
+            (2, 2) => Some(ChromaSubsampling::Yuv420),
+            _ => None
+        }
+    }
+    /// Return horizontal and vertical subsampling factors, or `None` for
+    /// [`Auto`](Self::Auto) and [`SameAsSource`](Self::SameAsSource)
+    pub const fn factors(self) -> Option<(u8, u8)> {
+        match self {
+            ChromaSubsampling::Yuv444 => Some((1, 1)),
+            ChromaSubsampling::Yuv440 => Some((1, 2)),
+            ChromaSubsampling::Yuv422 => Some((2, 1)),
+            ChromaSubsampling::Yuv420 => Some((2, 2)),
+            ChromaSubsampling::Auto | ChromaSubsampling::SameAsSource => None
+        }
+    }
+}
+
 /// Encoder options that are flags
 #[derive(Copy, Debug, Clone, Default)]
 struct EncoderFlags {
@@ -17,7 +66,11 @@ struct EncoderFlags {
     /// Whether JPEG images should use optimized huffman tables
     jpeg_optimize_huffman:   bool,
     /// Whether to not preserve metadata across image transformations
-    image_strip_metadata:    bool
+    image_strip_metadata:    bool,
+    /// Whether encoders should produce byte-identical output across runs
+    image_deterministic:     bool,
+    /// Whether animated PNG frames should only store the region that changed
+    png_optimize_frames:     bool
 }
 
 /// Options shared by some of the encoders in
@@ -31,6 +84,8 @@ pub struct EncoderOptions {
     depth:       BitDepth,
     num_threads: u8,
     effort:      u8,
+    target_size: Option<usize>,
+    subsampling: ChromaSubsampling,
     flags:       EncoderFlags
 }
 
@@ -44,6 +99,8 @@ impl Default for EncoderOptions {
             depth:       BitDepth::Eight,
             num_threads: 4,
             effort:      4,
+            target_size: None,
+            subsampling: ChromaSubsampling::Auto,
             flags:       EncoderFlags::default()
         }
     }
@@ -160,6 +217,25 @@ impl EncoderOptions {
         self
     }
 
+    /// Set an approximate size in bytes the encoded image should fit in
+    ///
+    /// Supported encoders will search for encoding parameters (e.g. quality for lossy encoders)
+    /// that produce an image no larger than `bytes`, the configured quality is used as the upper bound.
+    ///
+    /// If the target cannot be reached, the smallest output found is used instead.
+    ///
+    /// Currently supported by the jpeg and jpeg-xl encoders in `zune-image`
+    pub fn set_target_size(mut self, bytes: usize) -> Self {
+        self.target_size = Some(bytes);
+        self
+    }
+    /// Return the configured target size in bytes, if any
+    ///
+    /// Default is `None`, i.e encoders do not try to fit the output in a size budget
+    pub const fn target_size(&self) -> Option<usize> {
+        self.target_size
+    }
+
     /// Return number of threads configured for multithreading
     /// where possible
     ///
@@ -183,7 +259,27 @@ impl EncoderOptions {
     /// The default value is false, and encoders that respect this try to preserve as much
     /// data as possible from one image to another
     pub const fn strip_metadata(&self) -> bool {
-        !self.flags.image_strip_metadata
+        self.flags.image_strip_metadata
+    }
+
+    /// Set whether encoders should write user supplied data in a fixed order
+    ///
+    /// The encoders in `zune-image` write no timestamps and their output doesn't depend
+    /// on the number of threads, so encoding the same pixels with the same options already
+    /// gives byte-identical files between runs.
+    ///
+    /// This flag covers the remaining case where an encoder has a choice of ordering, which
+    /// currently is only the HDR encoder's user supplied headers, written sorted by key
+    /// instead of in hashmap iteration order.
+    pub fn set_deterministic(mut self, yes: bool) -> Self {
+        self.flags.image_deterministic = yes;
+        self
+    }
+    /// Whether encoders should produce reproducible output
+    ///
+    /// Default is `false`
+    pub const fn deterministic(&self) -> bool {
+        self.flags.image_deterministic
     }
 }
 
@@ -207,6 +303,22 @@ impl EncoderOptions {
         self.flags.jpeg_optimize_huffman
     }
 
+    /// The chroma subsampling the jpeg encoder should use
+    ///
+    /// Default is [`ChromaSubsampling::Auto`]
+    pub const fn jpeg_subsampling(&self) -> ChromaSubsampling {
+        self.subsampling
+    }
+
+    /// Set the chroma subsampling the jpeg encoder should use
+    ///
+    /// [`ChromaSubsampling::SameAsSource`] is resolved by the caller, e.g `zune-image`
+    /// uses the subsampling of the decoded jpeg image
+    pub fn set_jpeg_subsampling(mut self, subsampling: ChromaSubsampling) -> Self {
+        self.subsampling = subsampling;
+        self
+    }
+
     /// Set whether the jpeg encoder should encode the imagei in progressive mode
     ///
     /// Default is `false`
@@ -215,3 +327,24 @@ impl EncoderOptions {
         self
     }
 }
+
+/// PNG options
+impl EncoderOptions {
+    /// Whether the png encoder should only store the region of an animation frame
+    /// that changed from the previous frame
+    ///
+    /// Default is `false`
+    pub const fn png_optimize_frames(&self) -> bool {
+        self.flags.png_optimize_frames
+    }
+
+    /// Set whether the png encoder should only store the region of an animation frame
+    /// that changed from the previous frame
+    ///
+    /// This makes animations where little changes between frames smaller, the decoded
+    /// frames are the same
+    pub fn set_png_optimize_frames(mut self, yes: bool) -> Self {
+        self.flags.png_optimize_frames = yes;
+        self
+    }
+}
@@ -14,12 +14,14 @@
 //!  - ColorSpace
 //!  - BitDepth
 //!  - ColorCharacteristics
+//!  - ChromaSubsampling
 use alloc::format;
 
 use serde::ser::*;
 
 use crate::bit_depth::BitDepth;
 use crate::colorspace::{ColorCharacteristics, ColorSpace, RenderingIntent};
+use crate::options::ChromaSubsampling;
 
 impl Serialize for ColorSpace {
     #[allow(clippy::uninlined_format_args)]
@@ -61,3 +63,13 @@ impl Serialize for RenderingIntent {
         serializer.serialize_str(&format!("{:?}", self))
     }
 }
+
+impl Serialize for ChromaSubsampling {
+    #[allow(clippy::uninlined_format_args)]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        serializer.serialize_str(&format!("{:?}", self))
+    }
+}
@@ -0,0 +1,84 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Problems decoders recovered from
+//!
+//! Decoders tolerate many problems in damaged or non standard files, e.g. truncated
+//! data or checksum mismatches, logging them and carrying on. Decoders also keep them
+//! as [`DecodeWarning`]s which can be retrieved after decoding, allowing applications
+//! to tell users that a file is damaged or non standard.
+
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+
+/// A broad category of a warning
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum DecodeWarningKind {
+    /// The data ended early, pixels that couldn't be decoded were filled in
+    Truncated,
+    /// A checksum didn't match the data it covers
+    ChecksumMismatch,
+    /// Corrupt data was skipped or recovered from
+    CorruptData,
+    /// The file doesn't follow the specification but could still be decoded
+    NonConformance,
+    /// Data the decoder doesn't understand was skipped, e.g. unknown markers
+    UnsupportedData
+}
+
+impl DecodeWarningKind {
+    /// Return a short, stable, snake case name of this kind
+    /// suitable for logs and serialized responses
+    pub const fn name(self) -> &'static str {
+        match self {
+            DecodeWarningKind::Truncated => "truncated",
+            DecodeWarningKind::ChecksumMismatch => "checksum_mismatch",
+            DecodeWarningKind::CorruptData => "corrupt_data",
+            DecodeWarningKind::NonConformance => "non_conformance",
+            DecodeWarningKind::UnsupportedData => "unsupported_data"
+        }
+    }
+}
+
+impl Display for DecodeWarningKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A problem found in a file which didn't stop decoding
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DecodeWarning {
+    kind:    DecodeWarningKind,
+    message: String
+}
+
+impl DecodeWarning {
+    /// Create a new warning
+    pub fn new(kind: DecodeWarningKind, message: impl Into<String>) -> DecodeWarning {
+        DecodeWarning {
+            kind,
+            message: message.into()
+        }
+    }
+    /// Return the category of this warning
+    pub const fn kind(&self) -> DecodeWarningKind {
+        self.kind
+    }
+    /// Return a human readable description of the problem
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for DecodeWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}: {}", self.kind, self.message)
+    }
+}
@@ -0,0 +1,40 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+/// Size of the preamble before the `DICM` prefix
+pub const DICOM_PREAMBLE_SIZE: usize = 128;
+
+/// Magic bytes after the preamble
+pub const DICOM_MAGIC: &[u8; 4] = b"DICM";
+
+/// Length value indicating the element is terminated by a delimiter
+pub(crate) const UNDEFINED_LENGTH: u32 = 0xFFFF_FFFF;
+
+/// Nesting of sequences we are willing to skip before bailing
+pub(crate) const MAX_SEQUENCE_DEPTH: usize = 32;
+
+// (group, element) tags the decoder cares about
+pub(crate) const TAG_TRANSFER_SYNTAX: (u16, u16) = (0x0002, 0x0010);
+pub(crate) const TAG_SAMPLES_PER_PIXEL: (u16, u16) = (0x0028, 0x0002);
+pub(crate) const TAG_PHOTOMETRIC: (u16, u16) = (0x0028, 0x0004);
+pub(crate) const TAG_PLANAR_CONFIGURATION: (u16, u16) = (0x0028, 0x0006);
+pub(crate) const TAG_NUMBER_OF_FRAMES: (u16, u16) = (0x0028, 0x0008);
+pub(crate) const TAG_ROWS: (u16, u16) = (0x0028, 0x0010);
+pub(crate) const TAG_COLUMNS: (u16, u16) = (0x0028, 0x0011);
+pub(crate) const TAG_BITS_ALLOCATED: (u16, u16) = (0x0028, 0x0100);
+pub(crate) const TAG_BITS_STORED: (u16, u16) = (0x0028, 0x0101);
+pub(crate) const TAG_PIXEL_REPRESENTATION: (u16, u16) = (0x0028, 0x0103);
+pub(crate) const TAG_WINDOW_CENTER: (u16, u16) = (0x0028, 0x1050);
+pub(crate) const TAG_WINDOW_WIDTH: (u16, u16) = (0x0028, 0x1051);
+pub(crate) const TAG_RESCALE_INTERCEPT: (u16, u16) = (0x0028, 0x1052);
+pub(crate) const TAG_RESCALE_SLOPE: (u16, u16) = (0x0028, 0x1053);
+pub(crate) const TAG_PIXEL_DATA: (u16, u16) = (0x7FE0, 0x0010);
+
+pub(crate) const TAG_ITEM: (u16, u16) = (0xFFFE, 0xE000);
+pub(crate) const TAG_ITEM_DELIMITATION: (u16, u16) = (0xFFFE, 0xE00D);
+pub(crate) const TAG_SEQUENCE_DELIMITATION: (u16, u16) = (0xFFFE, 0xE0DD);
@@ -0,0 +1,865 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! DICOM decoder
+//!
+//! A DICOM file is a 128 byte preamble, the `DICM` magic, a file meta group (`0002`)
+//! which is always explicit VR little endian followed by the dataset in the transfer
+//! syntax named in the meta group.
+//!
+//! We only walk the dataset far enough to find the image pixel module and the pixel
+//! data element, everything else is skipped.
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::{ZByteReaderTrait, ZReader, ZSeekFrom};
+use zune_core::colorspace::ColorSpace;
+use zune_core::log::trace;
+use zune_core::options::DecoderOptions;
+use zune_core::result::DecodingResult;
+
+use crate::constants::*;
+use crate::errors::DicomDecodeErrors;
+use crate::rle::{decode_rle_frame, min_rle_frame_size};
+
+/// Transfer syntaxes understood by the decoder
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum TransferSyntax {
+    /// `1.2.840.10008.1.2`
+    ImplicitVRLittleEndian,
+    /// `1.2.840.10008.1.2.1`
+    ExplicitVRLittleEndian,
+    /// `1.2.840.10008.1.2.2`, retired but still found in the wild
+    ExplicitVRBigEndian,
+    /// `1.2.840.10008.1.2.5`
+    RleLossless
+}
+
+impl TransferSyntax {
+    /// Map a transfer syntax UID to a supported transfer syntax
+    ///
+    /// Returns `None` if the syntax isn't supported
+    pub fn from_uid(uid: &str) -> Option<TransferSyntax> {
+        // UIDs are padded to even length with NUL, some writers use spaces
+        match uid.trim_end_matches(['\0', ' ']) {
+            "1.2.840.10008.1.2" => Some(TransferSyntax::ImplicitVRLittleEndian),
+            "1.2.840.10008.1.2.1" => Some(TransferSyntax::ExplicitVRLittleEndian),
+            "1.2.840.10008.1.2.2" => Some(TransferSyntax::ExplicitVRBigEndian),
+            "1.2.840.10008.1.2.5" => Some(TransferSyntax::RleLossless),
+            _ => None
+        }
+    }
+    const fn is_explicit(self) -> bool {
+        !matches!(self, TransferSyntax::ImplicitVRLittleEndian)
+    }
+    const fn is_big_endian(self) -> bool {
+        matches!(self, TransferSyntax::ExplicitVRBigEndian)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Photometric {
+    /// Grayscale, minimum value is white
+    Monochrome1,
+    /// Grayscale, minimum value is black
+    Monochrome2,
+    Rgb
+}
+
+/// Probe some bytes to see if they consist of a
+/// DICOM file
+pub fn probe_dicom(bytes: &[u8]) -> bool {
+    bytes
+        .get(DICOM_PREAMBLE_SIZE..DICOM_PREAMBLE_SIZE + 4)
+        .map(|magic| magic == DICOM_MAGIC)
+        .unwrap_or(false)
+}
+
+/// VRs whose explicit length is stored as 2 reserved bytes + a 32 bit length
+fn is_long_vr(vr: [u8; 2]) -> bool {
+    matches!(
+        &vr,
+        b"OB"
+            | b"OD"
+            | b"OF"
+            | b"OL"
+            | b"OV"
+            | b"OW"
+            | b"SQ"
+            | b"SV"
+            | b"UC"
+            | b"UN"
+            | b"UR"
+            | b"UT"
+            | b"UV"
+    )
+}
+
+/// Parse the first value of a multi valued decimal or integer string
+fn parse_first_number(value: &[u8]) -> Option<f32> {
+    let string = core::str::from_utf8(value).ok()?;
+    let first = string.split('\\').next()?;
+
+    first.trim_matches(['\0', ' ']).parse::<f32>().ok()
+}
+
+/// A DICOM pixel data decoder
+///
+/// The decoder supports uncompressed (implicit/explicit VR little endian and explicit VR big endian)
+/// and RLE lossless transfer syntaxes, with 8 and 16 bit `MONOCHROME1`, `MONOCHROME2` and `RGB` images.
+///
+/// Pixels are returned as unsigned values, signed images are offset by `2^(bits_stored-1)`
+/// and `MONOCHROME1` images are inverted so that higher values are always brighter.
+///
+/// # Example
+/// ```no_run
+/// use zune_core::bytestream::ZCursor;
+/// use zune_dicom::DicomDecoder;
+///
+/// let mut decoder = DicomDecoder::new(ZCursor::new(b"DICM"));
+/// let pixels = decoder.decode().unwrap();
+/// ```
+pub struct DicomDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    stream:            ZReader<T>,
+    options:           DecoderOptions,
+    decoded_headers:   bool,
+    transfer_syntax:   Option<TransferSyntax>,
+    width:             usize,
+    height:            usize,
+    samples_per_pixel: usize,
+    bits_allocated:    u16,
+    bits_stored:       u16,
+    signed:            bool,
+    planar:            bool,
+    num_frames:        usize,
+    photometric:       Option<Photometric>,
+    window:            Option<(f32, f32)>,
+    rescale:           (f32, f32),
+    pixel_data_length: u32,
+    file_size:         u64
+}
+
+impl<T> DicomDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    /// Create a new DICOM decoder that reads data from `data`
+    ///
+    /// # Arguments
+    /// - `data`: The buffer from which we will read bytes from
+    pub fn new(data: T) -> DicomDecoder<T> {
+        DicomDecoder::new_with_options(data, DecoderOptions::default())
+    }
+    /// Create a new DICOM decoder with the specified options
+    ///
+    /// # Arguments
+    /// - `data`: The buffer from which we will read bytes from
+    /// - `options`: Specialized options for this decoder
+    pub fn new_with_options(data: T, options: DecoderOptions) -> DicomDecoder<T> {
+        DicomDecoder {
+            stream: ZReader::new(data),
+            options,
+            decoded_headers: false,
+            transfer_syntax: None,
+            width: 0,
+            height: 0,
+            samples_per_pixel: 1,
+            bits_allocated: 0,
+            bits_stored: 0,
+            signed: false,
+            planar: false,
+            num_frames: 1,
+            photometric: None,
+            window: None,
+            rescale: (1.0, 0.0),
+            pixel_data_length: 0,
+            file_size: 0
+        }
+    }
+
+    /// Read a tag, returning `(group, element)`
+    fn read_tag(&mut self, big_endian: bool) -> Result<(u16, u16), DicomDecodeErrors> {
+        if big_endian {
+            Ok((self.stream.get_u16_be_err()?, self.stream.get_u16_be_err()?))
+        } else {
+            Ok((self.stream.get_u16_le_err()?, self.stream.get_u16_le_err()?))
+        }
+    }
+
+    fn read_u32(&mut self, big_endian: bool) -> Result<u32, DicomDecodeErrors> {
+        if big_endian {
+            Ok(self.stream.get_u32_be_err()?)
+        } else {
+            Ok(self.stream.get_u32_le_err()?)
+        }
+    }
+
+    /// Read an element header, returning the tag and the value length
+    fn read_element_header(
+        &mut self, syntax: TransferSyntax
+    ) -> Result<((u16, u16), u32), DicomDecodeErrors> {
+        let big_endian = syntax.is_big_endian();
+        let tag = self.read_tag(big_endian)?;
+
+        // item and delimitation tags never carry a VR
+        if tag.0 == 0xFFFE || !syntax.is_explicit() {
+            let length = self.read_u32(big_endian)?;
+            return Ok((tag, length));
+        }
+        let vr = self.stream.read_fixed_bytes_or_error::<2>()?;
+
+        let length = if is_long_vr(vr) {
+            self.stream.skip(2)?;
+            self.read_u32(big_endian)?
+        } else if big_endian {
+            u32::from(self.stream.get_u16_be_err()?)
+        } else {
+            u32::from(self.stream.get_u16_le_err()?)
+        };
+        Ok((tag, length))
+    }
+
+    fn read_value(&mut self, length: u32) -> Result<Vec<u8>, DicomDecodeErrors> {
+        // lengths come from the file, don't allocate more than it can hold
+        if self.stream.position()? + u64::from(length) > self.file_size {
+            return Err(DicomDecodeErrors::GenericStatic(
+                "Element value extends past the end of the file"
+            ));
+        }
+        let mut value = vec![0; length as usize];
+        self.stream.read_exact_bytes(&mut value)?;
+        Ok(value)
+    }
+
+    /// Skip an undefined length element, i.e. one terminated by
+    /// an item or sequence delimitation item
+    fn skip_undefined_length(
+        &mut self, syntax: TransferSyntax, depth: usize
+    ) -> Result<(), DicomDecodeErrors> {
+        if depth > MAX_SEQUENCE_DEPTH {
+            return Err(DicomDecodeErrors::GenericStatic(
+                "Too deeply nested sequences"
+            ));
+        }
+        loop {
+            let (tag, length) = self.read_element_header(syntax)?;
+
+            if tag == TAG_SEQUENCE_DELIMITATION || tag == TAG_ITEM_DELIMITATION {
+                return Ok(());
+            }
+            if length == UNDEFINED_LENGTH {
+                self.skip_undefined_length(syntax, depth + 1)?;
+            } else {
+                self.stream.skip(length as usize)?;
+            }
+        }
+    }
+
+    /// Decode headers of the DICOM file, stopping at the pixel data element
+    ///
+    /// After calling this, information fields like dimensions and colorspace are
+    /// filled in
+    pub fn decode_headers(&mut self) -> Result<(), DicomDecodeErrors> {
+        if self.decoded_headers {
+            return Ok(());
+        }
+        self.file_size = self.stream.seek(ZSeekFrom::End(0))?;
+        self.stream.set_position(0)?;
+
+        self.stream.skip(DICOM_PREAMBLE_SIZE)?;
+
+        if &self.stream.read_fixed_bytes_or_error::<4>()? != DICOM_MAGIC {
+            return Err(DicomDecodeErrors::InvalidMagicBytes);
+        }
+        // file meta group, always explicit VR little endian
+        let mut transfer_syntax = None;
+
+        loop {
+            let group = self.stream.peek_at(0, 2)?;
+
+            if u16::from_le_bytes([group[0], group[1]]) != 0x0002 {
+                break;
+            }
+            let (tag, length) = self.read_element_header(TransferSyntax::ExplicitVRLittleEndian)?;
+
+            if tag == TAG_TRANSFER_SYNTAX {
+                let value = self.read_value(length)?;
+                let uid = String::from_utf8_lossy(&value).to_string();
+
+                trace!("Transfer syntax: {}", uid.trim_end_matches('\0'));
+
+                transfer_syntax = Some(
+                    TransferSyntax::from_uid(&uid)
+                        .ok_or(DicomDecodeErrors::UnsupportedTransferSyntax(uid))?
+                );
+            } else {
+                self.stream.skip(length as usize)?;
+            }
+        }
+        let syntax = transfer_syntax.ok_or(DicomDecodeErrors::GenericStatic(
+            "No transfer syntax found in file meta information"
+        ))?;
+        let big_endian = syntax.is_big_endian();
+
+        let mut window_center = None;
+        let mut window_width = None;
+
+        // dataset
+        loop {
+            let (tag, length) = self.read_element_header(syntax)?;
+
+            if tag == TAG_PIXEL_DATA {
+                self.pixel_data_length = length;
+                break;
+            }
+            if length == UNDEFINED_LENGTH {
+                self.skip_undefined_length(syntax, 0)?;
+                continue;
+            }
+            // only care about the small attributes of the image pixel module,
+            // larger ones are lookup tables and the like
+            if tag.0 != 0x0028 || length > 1024 {
+                self.stream.skip(length as usize)?;
+                continue;
+            }
+            let value = self.read_value(length)?;
+
+            let read_us = || -> Result<u16, DicomDecodeErrors> {
+                let bytes: [u8; 2] = value
+                    .get(..2)
+                    .and_then(|x| x.try_into().ok())
+                    .ok_or(DicomDecodeErrors::GenericStatic("Short US value"))?;
+
+                if big_endian {
+                    Ok(u16::from_be_bytes(bytes))
+                } else {
+                    Ok(u16::from_le_bytes(bytes))
+                }
+            };
+
+            match tag {
+                TAG_SAMPLES_PER_PIXEL => self.samples_per_pixel = usize::from(read_us()?),
+                TAG_PLANAR_CONFIGURATION => self.planar = read_us()? == 1,
+                TAG_ROWS => self.height = usize::from(read_us()?),
+                TAG_COLUMNS => self.width = usize::from(read_us()?),
+                TAG_BITS_ALLOCATED => self.bits_allocated = read_us()?,
+                TAG_BITS_STORED => self.bits_stored = read_us()?,
+                TAG_PIXEL_REPRESENTATION => self.signed = read_us()? == 1,
+                TAG_NUMBER_OF_FRAMES => {
+                    self.num_frames = parse_first_number(&value).unwrap_or(1.0) as usize
+                }
+                TAG_WINDOW_CENTER => window_center = parse_first_number(&value),
+                TAG_WINDOW_WIDTH => window_width = parse_first_number(&value),
+                TAG_RESCALE_INTERCEPT => self.rescale.1 = parse_first_number(&value).unwrap_or(0.0),
+                TAG_RESCALE_SLOPE => self.rescale.0 = parse_first_number(&value).unwrap_or(1.0),
+                TAG_PHOTOMETRIC => {
+                    let photometric = String::from_utf8_lossy(&value);
+
+                    self.photometric = match photometric.trim_matches(['\0', ' ']) {
+                        "MONOCHROME1" => Some(Photometric::Monochrome1),
+                        "MONOCHROME2" => Some(Photometric::Monochrome2),
+                        "RGB" => Some(Photometric::Rgb),
+                        other => {
+                            return Err(DicomDecodeErrors::Unsupported(format!(
+                                "Photometric interpretation {other}"
+                            )))
+                        }
+                    };
+                }
+                _ => ()
+            }
+        }
+        if let (Some(center), Some(width)) = (window_center, window_width) {
+            self.window = Some((center, width));
+        }
+
+        if self.width > self.options.max_width() {
+            return Err(DicomDecodeErrors::TooLargeDimensions(
+                "width",
+                self.options.max_width(),
+                self.width
+            ));
+        }
+        if self.height > self.options.max_height() {
+            return Err(DicomDecodeErrors::TooLargeDimensions(
+                "height",
+                self.options.max_height(),
+                self.height
+            ));
+        }
+        if self.width == 0 || self.height == 0 {
+            return Err(DicomDecodeErrors::GenericStatic(
+                "Zero or missing dimensions"
+            ));
+        }
+        if !matches!(self.bits_allocated, 8 | 16) {
+            return Err(DicomDecodeErrors::Unsupported(format!(
+                "Bits allocated {}",
+                self.bits_allocated
+            )));
+        }
+        if self.bits_stored == 0 || self.bits_stored > self.bits_allocated {
+            self.bits_stored = self.bits_allocated;
+        }
+        if self.num_frames == 0 {
+            return Err(DicomDecodeErrors::GenericStatic("Zero frames in file"));
+        }
+        match (self.photometric, self.samples_per_pixel) {
+            (Some(Photometric::Monochrome1 | Photometric::Monochrome2), 1) => (),
+            (Some(Photometric::Rgb), 3) => (),
+            (photometric, samples) => {
+                return Err(DicomDecodeErrors::Unsupported(format!(
+                    "{samples} samples per pixel with photometric {photometric:?}"
+                )))
+            }
+        }
+        trace!("Width: {}", self.width);
+        trace!("Height: {}", self.height);
+        trace!("Frames: {}", self.num_frames);
+        trace!("Bits allocated: {}", self.bits_allocated);
+        trace!("Bits stored: {}", self.bits_stored);
+
+        self.transfer_syntax = Some(syntax);
+        self.decoded_headers = true;
+
+        Ok(())
+    }
+
+    /// Return the image dimensions as `(width, height)` or `None` if
+    /// headers haven't been decoded
+    pub fn dimensions(&self) -> Option<(usize, usize)> {
+        if !self.decoded_headers {
+            return None;
+        }
+        Some((self.width, self.height))
+    }
+
+    /// Return the colorspace of the decoded pixels or `None` if
+    /// headers haven't been decoded
+    pub fn colorspace(&self) -> Option<ColorSpace> {
+        if !self.decoded_headers {
+            return None;
+        }
+        match self.photometric? {
+            Photometric::Rgb => Some(ColorSpace::RGB),
+            _ => Some(ColorSpace::Luma)
+        }
+    }
+
+    /// Return the bit depth of the decoded pixels or `None` if
+    /// headers haven't been decoded
+    pub fn depth(&self) -> Option<BitDepth> {
+        if !self.decoded_headers {
+            return None;
+        }
+        match self.bits_allocated {
+            8 => Some(BitDepth::Eight),
+            _ => Some(BitDepth::Sixteen)
+        }
+    }
+
+    /// Return the number of frames stored in the file
+    pub fn num_frames(&self) -> Option<usize> {
+        if !self.decoded_headers {
+            return None;
+        }
+        Some(self.num_frames)
+    }
+
+    /// Return the transfer syntax of the dataset
+    pub fn transfer_syntax(&self) -> Option<TransferSyntax> {
+        self.transfer_syntax
+    }
+
+    /// Return the rescale slope and intercept as `(slope, intercept)`
+    ///
+    /// These map stored values into modality units (e.g. Hounsfield units for CT)
+    pub fn rescale(&self) -> (f32, f32) {
+        self.rescale
+    }
+
+    /// Return the first window `(center, width)` stored in the file, mapped
+    /// to the values returned by [`decode`](Self::decode)
+    ///
+    /// The window in the file is in modality units, this undoes the rescale, the
+    /// signed offset and the `MONOCHROME1` inversion so it can be applied directly to
+    /// decoded pixels
+    pub fn window(&self) -> Option<(f32, f32)> {
+        let (center, width) = self.window?;
+        let (slope, intercept) = self.rescale;
+
+        let slope = if slope == 0.0 { 1.0 } else { slope };
+
+        let mut center = (center - intercept) / slope;
+
+        if self.signed {
+            center += (1_u32 << (self.bits_stored - 1)) as f32;
+        }
+        if self.photometric == Some(Photometric::Monochrome1) {
+            center = ((1_u32 << self.bits_stored) - 1) as f32 - center;
+        }
+        Some((center, (width / slope).abs()))
+    }
+
+    /// Bytes of a single frame as stored in the file
+    fn frame_size(&self) -> Option<usize> {
+        self.width
+            .checked_mul(self.height)?
+            .checked_mul(self.samples_per_pixel)?
+            .checked_mul(usize::from(self.bits_allocated / 8))
+    }
+
+    /// Read encapsulated pixel data fragments, skipping the basic offset table
+    ///
+    /// `max_fragment` bounds the size of a single fragment so that corrupt
+    /// lengths do not cause huge allocations
+    fn read_fragments(&mut self, max_fragment: usize) -> Result<Vec<Vec<u8>>, DicomDecodeErrors> {
+        let mut fragments = vec![];
+        let mut first = true;
+
+        loop {
+            let tag = self.read_tag(false)?;
+            let length = self.read_u32(false)?;
+
+            if tag == TAG_SEQUENCE_DELIMITATION {
+                break;
+            }
+            if tag != TAG_ITEM || length == UNDEFINED_LENGTH {
+                return Err(DicomDecodeErrors::GenericStatic(
+                    "Invalid item in encapsulated pixel data"
+                ));
+            }
+            if length as usize > max_fragment {
+                return Err(DicomDecodeErrors::Generic(format!(
+                    "Fragment of {length} bytes is larger than the expected maximum of {max_fragment} bytes"
+                )));
+            }
+            if first {
+                // basic offset table, we read frames in order so we don't need it
+                self.stream.skip(length as usize)?;
+                first = false;
+                continue;
+            }
+            fragments.push(self.read_value(length)?);
+        }
+        Ok(fragments)
+    }
+
+    /// Read frames and return them as interleaved samples in native
+    /// little endian order
+    fn read_frames(&mut self, frame_size: usize) -> Result<Vec<u8>, DicomDecodeErrors> {
+        let syntax = self.transfer_syntax.unwrap();
+        let bytes = usize::from(self.bits_allocated / 8);
+        let samples = self.samples_per_pixel;
+        let plane_size = self.width * self.height;
+
+        let output_size =
+            frame_size
+                .checked_mul(self.num_frames)
+                .ok_or(DicomDecodeErrors::GenericStatic(
+                    "Pixel data size overflows usize"
+                ))?;
+
+        if syntax == TransferSyntax::RleLossless {
+            // worst case PackBits expands one byte in every 128, add the 64 byte header
+            // and allow the offset table to hold 4 bytes for each frame
+            let max_fragment =
+                (frame_size + frame_size / 64 + 64).max(self.num_frames.saturating_mul(4));
+            let mut fragments = self.read_fragments(max_fragment)?;
+
+            if self.num_frames == 1 && fragments.len() > 1 {
+                // a single frame may be split into multiple fragments
+                fragments = vec![fragments.concat()];
+            }
+            if fragments.len() < self.num_frames {
+                return Err(DicomDecodeErrors::Generic(format!(
+                    "Expected {} RLE frames but found {}",
+                    self.num_frames,
+                    fragments.len()
+                )));
+            }
+            // check every fragment can expand to a full frame before allocating
+            // space for them, so the output is bounded by the file size
+            let min_fragment = min_rle_frame_size(samples * bytes, plane_size);
+
+            if fragments
+                .iter()
+                .take(self.num_frames)
+                .any(|x| x.len() < min_fragment)
+            {
+                return Err(DicomDecodeErrors::Generic(format!(
+                    "RLE fragment too short for a frame, expected at least {min_fragment} bytes"
+                )));
+            }
+            let mut output = vec![0; output_size];
+
+            for (fragment, out_frame) in fragments.iter().zip(output.chunks_exact_mut(frame_size)) {
+                let planes = decode_rle_frame(fragment, samples * bytes, plane_size)?;
+
+                // segments are ordered by sample then by byte, most significant byte first
+                for sample in 0..samples {
+                    for byte in 0..bytes {
+                        let plane = &planes[(sample * bytes + byte) * plane_size..][..plane_size];
+                        let out_pos = sample * bytes + (bytes - 1 - byte);
+
+                        for (out, value) in out_frame[out_pos..]
+                            .iter_mut()
+                            .step_by(samples * bytes)
+                            .zip(plane)
+                        {
+                            *out = *value;
+                        }
+                    }
+                }
+            }
+            return Ok(output);
+        }
+        if (self.pixel_data_length as usize) < output_size {
+            return Err(DicomDecodeErrors::Generic(format!(
+                "Pixel data too short, expected {} bytes but found {}",
+                output_size, self.pixel_data_length
+            )));
+        }
+        if self.stream.position()? + output_size as u64 > self.file_size {
+            return Err(DicomDecodeErrors::GenericStatic(
+                "Pixel data extends past the end of the file"
+            ));
+        }
+        let mut output = vec![0; output_size];
+        self.stream.read_exact_bytes(&mut output)?;
+
+        if syntax.is_big_endian() && bytes == 2 {
+            output.chunks_exact_mut(2).for_each(|pair| pair.swap(0, 1));
+        }
+        if self.planar && samples > 1 {
+            for frame in output.chunks_exact_mut(frame_size) {
+                let planar = frame.to_vec();
+
+                for (sample, plane) in planar.chunks_exact(plane_size * bytes).enumerate() {
+                    for (pixel, value) in plane.chunks_exact(bytes).enumerate() {
+                        let pos = (pixel * samples + sample) * bytes;
+                        frame[pos..pos + bytes].copy_from_slice(value);
+                    }
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// Map a stored value into an unsigned, brighter-is-higher value
+    fn normalize(&self, value: u16) -> u16 {
+        let bits = u32::from(self.bits_stored);
+        let mask = ((1_u32 << bits) - 1) as u16;
+
+        let mut value = value & mask;
+
+        if self.signed {
+            // sign extend and add 2^(bits-1) collapses into flipping the sign bit
+            value ^= 1 << (bits - 1);
+        }
+        if self.photometric == Some(Photometric::Monochrome1) {
+            value = mask - value;
+        }
+        value
+    }
+
+    /// Decode all frames in the file
+    ///
+    /// Frames are returned one after the other, each frame containing
+    /// `width * height * components` samples.
+    ///
+    /// 8 bit images return [`DecodingResult::U8`] and 16 bit images
+    /// [`DecodingResult::U16`]
+    pub fn decode(&mut self) -> Result<DecodingResult, DicomDecodeErrors> {
+        self.decode_headers()?;
+
+        let frame_size = self.frame_size().ok_or(DicomDecodeErrors::GenericStatic(
+            "Frame size overflows usize"
+        ))?;
+
+        let raw = self.read_frames(frame_size)?;
+
+        if self.bits_allocated == 8 {
+            let pixels = raw
+                .iter()
+                .map(|x| self.normalize(u16::from(*x)) as u8)
+                .collect();
+            return Ok(DecodingResult::U8(pixels));
+        }
+        let pixels = raw
+            .chunks_exact(2)
+            .map(|x| self.normalize(u16::from_le_bytes([x[0], x[1]])))
+            .collect();
+
+        Ok(DecodingResult::U16(pixels))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::result::DecodingResult;
+
+    use crate::{DicomDecoder, TransferSyntax};
+
+    /// Write an explicit VR little endian element
+    fn element(out: &mut Vec<u8>, tag: (u16, u16), vr: &[u8; 2], value: &[u8]) {
+        out.extend_from_slice(&tag.0.to_le_bytes());
+        out.extend_from_slice(&tag.1.to_le_bytes());
+        out.extend_from_slice(vr);
+
+        if vr == b"OB" || vr == b"OW" {
+            out.extend_from_slice(&[0, 0]);
+            out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        } else {
+            out.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        }
+        out.extend_from_slice(value);
+    }
+
+    fn header(syntax: &[u8]) -> Vec<u8> {
+        let mut out = vec![0; 128];
+        out.extend_from_slice(b"DICM");
+        element(&mut out, (0x0002, 0x0010), b"UI", syntax);
+        out
+    }
+
+    #[test]
+    fn test_explicit_le_signed_monochrome1() {
+        let mut file = header(b"1.2.840.10008.1.2.1\0");
+        element(&mut file, (0x0028, 0x0002), b"US", &1_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x0004), b"CS", b"MONOCHROME1 ");
+        element(&mut file, (0x0028, 0x0010), b"US", &1_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x0011), b"US", &2_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x0100), b"US", &16_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x0101), b"US", &12_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x0103), b"US", &1_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x1050), b"DS", b"0\\10");
+        element(&mut file, (0x0028, 0x1051), b"DS", b"400 ");
+
+        let pixels: Vec<u8> = [-2048_i16, 2047]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        element(&mut file, (0x7FE0, 0x0010), b"OW", &pixels);
+
+        let mut decoder = DicomDecoder::new(ZCursor::new(&file));
+        let DecodingResult::U16(pixels) = decoder.decode().unwrap() else {
+            panic!("Expected 16 bit output")
+        };
+        // most negative becomes brightest after inversion
+        assert_eq!(pixels, [4095, 0]);
+        assert_eq!(decoder.colorspace(), Some(ColorSpace::Luma));
+        assert_eq!(decoder.window(), Some((2047.0, 400.0)));
+    }
+
+    #[test]
+    fn test_rle_rgb() {
+        let mut file = header(b"1.2.840.10008.1.2.5\0");
+        element(&mut file, (0x0028, 0x0002), b"US", &3_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x0004), b"CS", b"RGB ");
+        element(&mut file, (0x0028, 0x0010), b"US", &1_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x0011), b"US", &2_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x0100), b"US", &8_u16.to_le_bytes());
+
+        // rle frame, three segments each a repeat run of 2
+        let mut frame = Vec::new();
+        frame.extend_from_slice(&3_u32.to_le_bytes());
+        for offset in [64_u32, 66, 68] {
+            frame.extend_from_slice(&offset.to_le_bytes());
+        }
+        frame.resize(64, 0);
+        frame.extend_from_slice(&[(-1_i8) as u8, 10, (-1_i8) as u8, 20, (-1_i8) as u8, 30]);
+
+        file.extend_from_slice(&[0xE0, 0x7F, 0x10, 0x00]);
+        file.extend_from_slice(b"OB\0\0");
+        file.extend_from_slice(&u32::MAX.to_le_bytes());
+        // empty basic offset table
+        file.extend_from_slice(&[0xFE, 0xFF, 0x00, 0xE0, 0, 0, 0, 0]);
+        file.extend_from_slice(&[0xFE, 0xFF, 0x00, 0xE0]);
+        file.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        file.extend_from_slice(&frame);
+        file.extend_from_slice(&[0xFE, 0xFF, 0xDD, 0xE0, 0, 0, 0, 0]);
+
+        let mut decoder = DicomDecoder::new(ZCursor::new(&file));
+        let DecodingResult::U8(pixels) = decoder.decode().unwrap() else {
+            panic!("Expected 8 bit output")
+        };
+        assert_eq!(pixels, [10, 20, 30, 10, 20, 30]);
+        assert_eq!(decoder.transfer_syntax(), Some(TransferSyntax::RleLossless));
+        assert_eq!(decoder.colorspace(), Some(ColorSpace::RGB));
+    }
+
+    #[test]
+    fn test_rle_short_fragments() {
+        let mut file = header(b"1.2.840.10008.1.2.5\0");
+        element(&mut file, (0x0028, 0x0002), b"US", &1_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x0004), b"CS", b"MONOCHROME2 ");
+        element(&mut file, (0x0028, 0x0008), b"IS", b"4 ");
+        element(&mut file, (0x0028, 0x0010), b"US", &4096_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x0011), b"US", &4096_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x0100), b"US", &16_u16.to_le_bytes());
+
+        file.extend_from_slice(&[0xE0, 0x7F, 0x10, 0x00]);
+        file.extend_from_slice(b"OB\0\0");
+        file.extend_from_slice(&u32::MAX.to_le_bytes());
+        file.extend_from_slice(&[0xFE, 0xFF, 0x00, 0xE0, 0, 0, 0, 0]);
+
+        // frames with a header but no segment data can't fill 32 MB each
+        for _ in 0..4 {
+            let mut frame = 2_u32.to_le_bytes().to_vec();
+            frame.extend_from_slice(&64_u32.to_le_bytes());
+            frame.extend_from_slice(&64_u32.to_le_bytes());
+            frame.resize(64, 0);
+
+            file.extend_from_slice(&[0xFE, 0xFF, 0x00, 0xE0]);
+            file.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            file.extend_from_slice(&frame);
+        }
+        file.extend_from_slice(&[0xFE, 0xFF, 0xDD, 0xE0, 0, 0, 0, 0]);
+
+        assert!(DicomDecoder::new(ZCursor::new(&file)).decode().is_err());
+    }
+
+    #[test]
+    fn test_untrusted_lengths() {
+        let mut file = header(b"1.2.840.10008.1.2.1\0");
+        element(&mut file, (0x0028, 0x0002), b"US", &1_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x0004), b"CS", b"MONOCHROME2 ");
+        element(&mut file, (0x0028, 0x0008), b"IS", b"100000");
+        element(&mut file, (0x0028, 0x0010), b"US", &512_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x0011), b"US", &512_u16.to_le_bytes());
+        element(&mut file, (0x0028, 0x0100), b"US", &16_u16.to_le_bytes());
+
+        let mut frames = file.clone();
+        element(&mut frames, (0x7FE0, 0x0010), b"OW", &[0; 4]);
+        // 100000 frames of 512 KB aren't in a file this small
+        assert!(DicomDecoder::new(ZCursor::new(&frames)).decode().is_err());
+
+        let mut overflow = file.clone();
+        element(&mut overflow, (0x0028, 0x0008), b"IS", b"1e13");
+        element(&mut overflow, (0x7FE0, 0x0010), b"OW", &[0; 4]);
+        assert!(DicomDecoder::new(ZCursor::new(&overflow)).decode().is_err());
+
+        // a value length far past the end of the file
+        file.extend_from_slice(&[0x28, 0x00, 0x30, 0x00]);
+        file.extend_from_slice(b"DS");
+        file.extend_from_slice(&1000_u16.to_le_bytes());
+        assert!(DicomDecoder::new(ZCursor::new(&file)).decode().is_err());
+    }
+}
@@ -0,0 +1,87 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use alloc::string::String;
+use core::fmt::{Debug, Formatter};
+
+use zune_core::bytestream::ZByteIoError;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
+
+/// DICOM errors that can occur during decoding
+#[non_exhaustive]
+pub enum DicomDecodeErrors {
+    /// The file does not have the `DICM` prefix after
+    /// the 128 byte preamble
+    InvalidMagicBytes,
+    /// The transfer syntax of the file is not supported,
+    /// contains the transfer syntax UID
+    UnsupportedTransferSyntax(String),
+    /// The image uses a feature the decoder doesn't support
+    Unsupported(String),
+    /// Too large dimensions for a given width or
+    /// height
+    TooLargeDimensions(&'static str, usize, usize),
+    /// Generic message
+    GenericStatic(&'static str),
+    /// Generic allocated message
+    Generic(String),
+    IoErrors(ZByteIoError)
+}
+
+impl Debug for DicomDecodeErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidMagicBytes => {
+                writeln!(
+                    f,
+                    "Invalid magic bytes, file does not contain DICM after preamble"
+                )
+            }
+            Self::UnsupportedTransferSyntax(uid) => {
+                writeln!(f, "Unsupported transfer syntax {uid}")
+            }
+            Self::Unsupported(message) => {
+                writeln!(f, "Unsupported: {message}")
+            }
+            Self::TooLargeDimensions(dimension, expected, found) => {
+                writeln!(
+                    f,
+                    "Too large dimensions for {dimension} , {found} exceeds {expected}"
+                )
+            }
+            Self::GenericStatic(message) => {
+                writeln!(f, "{message}")
+            }
+            Self::Generic(message) => {
+                writeln!(f, "{message}")
+            }
+            Self::IoErrors(err) => {
+                writeln!(f, "{:?}", err)
+            }
+        }
+    }
+}
+
+impl From<ZByteIoError> for DicomDecodeErrors {
+    fn from(value: ZByteIoError) -> Self {
+        DicomDecodeErrors::IoErrors(value)
+    }
+}
+
+impl ZErrorCodeTrait for DicomDecodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::InvalidMagicBytes | Self::GenericStatic(_) | Self::Generic(_) => {
+                ZErrorCode::CorruptData
+            }
+            Self::UnsupportedTransferSyntax(_) | Self::Unsupported(_) => ZErrorCode::Unsupported,
+            Self::TooLargeDimensions(_, _, _) => ZErrorCode::LimitsExceeded,
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
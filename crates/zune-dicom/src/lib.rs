@@ -0,0 +1,54 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! A minimal DICOM pixel data decoder
+//!
+//! This crate parses the DICOM file meta information and the image pixel module
+//! and returns the pixels of the image, it isn't a general purpose DICOM library, all other
+//! attributes (patient, study, series etc.) are skipped.
+//!
+//! # Supported transfer syntaxes
+//! - Implicit VR little endian (`1.2.840.10008.1.2`)
+//! - Explicit VR little endian (`1.2.840.10008.1.2.1`)
+//! - Explicit VR big endian (`1.2.840.10008.1.2.2`)
+//! - RLE lossless (`1.2.840.10008.1.2.5`)
+//!
+//! # Supported images
+//! - 8 and 16 bit `MONOCHROME1` and `MONOCHROME2`
+//! - 8 and 16 bit `RGB`, interleaved and planar
+//! - Multi-frame images, frames are returned one after the other
+//!
+//! Signed images are offset to unsigned and `MONOCHROME1` is inverted, window center/width
+//! are mapped to the output values, see [`DicomDecoder::window`]
+//!
+//! # Features
+//!  - `log`: Use the `log` crate features to print image information when decoding
+//!  - `std`: Allow direct decoding from anything that implements `std::io::BufRead` + `std::io::Seek`
+//!
+//! # Usage
+//! ```no_run
+//! use zune_core::bytestream::ZCursor;
+//! use zune_dicom::DicomDecoder;
+//!
+//! let mut decoder = DicomDecoder::new(ZCursor::new(b"DICM"));
+//! let pixels = decoder.decode().unwrap();
+//! let (width, height) = decoder.dimensions().unwrap();
+//! ```
+#![no_std]
+#![macro_use]
+extern crate alloc;
+
+pub use zune_core;
+
+pub use crate::constants::{DICOM_MAGIC, DICOM_PREAMBLE_SIZE};
+pub use crate::decoder::{probe_dicom, DicomDecoder, TransferSyntax};
+pub use crate::errors::DicomDecodeErrors;
+
+mod constants;
+mod decoder;
+mod errors;
+mod rle;
@@ -0,0 +1,110 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! DICOM RLE lossless decoding
+//!
+//! See PS3.5 Annex G, each frame starts with a 64 byte header containing
+//! the number of segments and the offsets to each of them, segments are
+//! PackBits encoded byte planes, most significant byte first for each sample.
+use alloc::format;
+use alloc::vec::Vec;
+
+use crate::errors::DicomDecodeErrors;
+
+const RLE_HEADER_SIZE: usize = 64;
+
+/// Return the smallest RLE frame that can decode to `num_segments` planes
+/// of `plane_size` bytes
+///
+/// A PackBits repeat run stores at most 128 bytes in 2, so each segment needs
+/// at least one byte for every 64 it produces
+pub(crate) fn min_rle_frame_size(num_segments: usize, plane_size: usize) -> usize {
+    RLE_HEADER_SIZE + num_segments * plane_size.div_ceil(128) * 2
+}
+
+/// Decode a single RLE frame into planar byte segments
+///
+/// The output contains `num_segments` planes each of `plane_size` bytes
+/// stored one after the other
+pub(crate) fn decode_rle_frame(
+    data: &[u8], num_segments: usize, plane_size: usize
+) -> Result<Vec<u8>, DicomDecodeErrors> {
+    if data.len() < RLE_HEADER_SIZE {
+        return Err(DicomDecodeErrors::GenericStatic("RLE frame too short"));
+    }
+    let read_u32 = |pos: usize| u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap()) as usize;
+
+    let found_segments = read_u32(0);
+
+    if found_segments != num_segments {
+        return Err(DicomDecodeErrors::Generic(format!(
+            "Expected {num_segments} RLE segments but found {found_segments}"
+        )));
+    }
+    let mut output = alloc::vec![0; num_segments * plane_size];
+
+    for (i, plane) in output.chunks_exact_mut(plane_size).enumerate() {
+        let start = read_u32(4 + i * 4);
+        let end = if i + 1 < num_segments { read_u32(8 + i * 4) } else { data.len() };
+        if start < RLE_HEADER_SIZE || start > end || end > data.len() {
+            return Err(DicomDecodeErrors::GenericStatic(
+                "Invalid RLE segment offset"
+            ));
+        }
+        decode_packbits(&data[start..end], plane);
+    }
+    Ok(output)
+}
+
+/// Decode PackBits data into `output`
+///
+/// Decoding stops when either input or output is exhausted, missing
+/// bytes are left as zero
+fn decode_packbits(input: &[u8], output: &mut [u8]) {
+    let mut in_pos = 0;
+    let mut out_pos = 0;
+
+    while in_pos < input.len() && out_pos < output.len() {
+        let header = input[in_pos] as i8;
+        in_pos += 1;
+
+        if header >= 0 {
+            // copy the next n+1 bytes literally
+            let count = (header as usize + 1)
+                .min(input.len() - in_pos)
+                .min(output.len() - out_pos);
+
+            output[out_pos..out_pos + count].copy_from_slice(&input[in_pos..in_pos + count]);
+            in_pos += header as usize + 1;
+            out_pos += count;
+        } else if header != -128 {
+            // repeat the next byte -n+1 times
+            let count = ((1 - header as isize) as usize).min(output.len() - out_pos);
+
+            if let Some(value) = input.get(in_pos) {
+                output[out_pos..out_pos + count].fill(*value);
+            }
+            in_pos += 1;
+            out_pos += count;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode_packbits;
+
+    #[test]
+    fn test_packbits() {
+        // literal run of 3, repeat of 4, no-op, literal run of 1
+        let input = [2, 1, 2, 3, (-3_i8) as u8, 9, (-128_i8) as u8, 0, 7];
+        let mut output = [0; 8];
+        decode_packbits(&input, &mut output);
+        assert_eq!(output, [1, 2, 3, 9, 9, 9, 9, 7]);
+    }
+}
@@ -0,0 +1,914 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! DNG decoder
+//!
+//! A DNG file is a TIFF file, the first IFD usually holds a preview and
+//! the camera wide tags (color matrices, white balance) while the raw data
+//! lives in a sub IFD with `NewSubFileType` set to zero.
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::colorspace::ColorSpace;
+use zune_core::log::trace;
+use zune_core::options::DecoderOptions;
+
+use crate::demosaic::{demosaic_ahd, demosaic_bilinear, CfaPattern};
+use crate::errors::DngDecodeErrors;
+use crate::ljpeg::decode_lossless_jpeg;
+use crate::tiff::*;
+
+/// Photometric interpretation of color filter array data
+const PHOTOMETRIC_CFA: u32 = 32803;
+/// Photometric interpretation of demosaiced, linear camera data
+const PHOTOMETRIC_LINEAR_RAW: u32 = 34892;
+
+const COMPRESSION_NONE: u32 = 1;
+const COMPRESSION_LOSSLESS_JPEG: u32 = 7;
+
+/// Largest BlackLevelRepeatDim we accept along each axis, real files
+/// repeat the black level over at most the CFA pattern
+const MAX_BLACK_REPEAT: usize = 16;
+
+/// Probe some bytes to see if they consist of a DNG file
+///
+/// This needs the TIFF header and the whole first IFD to be present,
+/// if the bytes are too short this returns false
+pub fn probe_dng(bytes: &[u8]) -> bool {
+    let Ok((tiff, offset)) = Tiff::new(bytes) else {
+        return false;
+    };
+    tiff.read_ifd(offset)
+        .map(|(ifd, _)| ifd.get(TAG_DNG_VERSION).is_some())
+        .unwrap_or(false)
+}
+
+/// Number of bytes needed by [`probe_dng`] to find the `DNGVersion` tag
+///
+/// Call this with the first `8` bytes of the file, if the IFD entry count is
+/// not within `bytes` this returns the size needed to read the count,
+/// call again with that many bytes to get the final size.
+///
+/// Returns `None` if the bytes are not a TIFF header
+pub fn probe_dng_size(bytes: &[u8]) -> Option<usize> {
+    let (tiff, offset) = Tiff::new(bytes).ok()?;
+
+    match tiff.u16(offset) {
+        Ok(entries) => Some(offset + 2 + usize::from(entries) * 12 + 4),
+        Err(_) => Some(offset + 2)
+    }
+}
+
+/// Color information of the camera stored in the DNG
+///
+/// Matrices are 3x3 and stored in row major order, see the DNG
+/// specification chapter 6 on how they map camera values to XYZ
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DngColorInfo {
+    /// Matrix mapping XYZ to camera native space under the first calibration illuminant
+    pub color_matrix_1:           Option<[f32; 9]>,
+    /// Matrix mapping XYZ to camera native space under the second calibration illuminant
+    pub color_matrix_2:           Option<[f32; 9]>,
+    /// Matrix mapping white balanced camera values to XYZ D50 under the first illuminant
+    pub forward_matrix_1:         Option<[f32; 9]>,
+    /// Matrix mapping white balanced camera values to XYZ D50 under the second illuminant
+    pub forward_matrix_2:         Option<[f32; 9]>,
+    /// Per-unit calibration for the first illuminant
+    pub camera_calibration_1:     Option<[f32; 9]>,
+    /// Per-unit calibration for the second illuminant
+    pub camera_calibration_2:     Option<[f32; 9]>,
+    /// Camera native values of a neutral color at the time of shooting,
+    /// i.e. the inverse of the white balance multipliers
+    pub as_shot_neutral:          Option<[f32; 3]>,
+    /// EXIF light source of the first calibration
+    pub calibration_illuminant_1: Option<u16>,
+    /// EXIF light source of the second calibration
+    pub calibration_illuminant_2: Option<u16>
+}
+
+#[derive(Clone, Debug)]
+enum DataLayout {
+    Strips {
+        offsets:        Vec<u32>,
+        byte_counts:    Vec<u32>,
+        rows_per_strip: usize
+    },
+    Tiles {
+        offsets:     Vec<u32>,
+        byte_counts: Vec<u32>,
+        tile_width:  usize,
+        tile_height: usize
+    }
+}
+
+/// Information about the raw image IFD
+#[derive(Clone, Debug)]
+struct RawInfo {
+    /// Stored dimensions, before cropping to the active area
+    width:             usize,
+    height:            usize,
+    bits_per_sample:   u8,
+    samples_per_pixel: usize,
+    compression:       u32,
+    /// `None` for linear raw images
+    cfa:               Option<CfaPattern>,
+    layout:            DataLayout,
+    linearization:     Option<Vec<u16>>,
+    black_repeat:      (usize, usize),
+    black_level:       Vec<f32>,
+    white_level:       Vec<f32>,
+    /// (top, left, bottom, right)
+    active_area:       (usize, usize, usize, usize),
+    big_endian:        bool
+}
+
+/// A DNG decoder
+///
+/// The decoder supports uncompressed and lossless JPEG compressed raw data
+/// stored either as color filter array (2x2 RGB bayer patterns) or as linear
+/// raw data.
+///
+/// Pixels are returned as RGB `f32` values between 0 and 1 in the camera native
+/// color space, black and white levels are applied but no white balance or color
+/// matrix, those are available via [`DngDecoder::color_info`].
+///
+/// # Example
+/// ```no_run
+/// use zune_core::bytestream::ZCursor;
+/// use zune_dng::DngDecoder;
+///
+/// let mut decoder = DngDecoder::new(ZCursor::new(b"II*\0"));
+/// let pixels = decoder.decode().unwrap();
+/// ```
+pub struct DngDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    source:          T,
+    data:            Vec<u8>,
+    options:         DecoderOptions,
+    decoded_headers: bool,
+    raw:             Option<RawInfo>,
+    color_info:      DngColorInfo,
+    orientation:     Option<u16>
+}
+
+impl<T> DngDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    /// Create a new DNG decoder that reads data from `data`
+    ///
+    /// # Arguments
+    /// - `data`: The buffer from which we will read bytes from
+    pub fn new(data: T) -> DngDecoder<T> {
+        DngDecoder::new_with_options(data, DecoderOptions::default())
+    }
+    /// Create a new DNG decoder with the specified options
+    ///
+    /// # Arguments
+    /// - `data`: The buffer from which we will read bytes from
+    /// - `options`: Specialized options for this decoder
+    pub fn new_with_options(data: T, options: DecoderOptions) -> DngDecoder<T> {
+        DngDecoder {
+            source: data,
+            data: Vec::new(),
+            options,
+            decoded_headers: false,
+            raw: None,
+            color_info: DngColorInfo::default(),
+            orientation: None
+        }
+    }
+
+    /// Decode headers of the DNG file
+    ///
+    /// After calling this, information fields like dimensions and
+    /// color information are filled in
+    pub fn decode_headers(&mut self) -> Result<(), DngDecodeErrors> {
+        if self.decoded_headers {
+            return Ok(());
+        }
+        // TIFF offsets can point anywhere, so keep the whole file around
+        self.source.read_remaining(&mut self.data)?;
+
+        let (tiff, first_ifd) = Tiff::new(&self.data)?;
+        let ifds = tiff.read_ifd_chain(first_ifd)?;
+
+        let ifd0 = ifds.first().ok_or(DngDecodeErrors::NotDng)?;
+
+        if ifd0.get(TAG_DNG_VERSION).is_none() {
+            return Err(DngDecodeErrors::NotDng);
+        }
+        self.color_info = read_color_info(&tiff, ifd0)?;
+
+        if let Some(entry) = ifd0.get(TAG_ORIENTATION) {
+            self.orientation = tiff.values_u32(entry)?.first().map(|x| *x as u16);
+        }
+        // the raw image is in the main IFD chain or one level of sub IFDs
+        let mut candidates = ifds.clone();
+
+        for ifd in &ifds {
+            if let Some(entry) = ifd.get(TAG_SUB_IFDS) {
+                for offset in tiff.values_u32(entry)? {
+                    candidates.push(tiff.read_ifd(offset as usize)?.0);
+                }
+            }
+        }
+        let mut raw_ifd: Option<(&Ifd, usize)> = None;
+
+        for ifd in &candidates {
+            let subfile_type = first_value(&tiff, ifd, TAG_NEW_SUBFILE_TYPE)?.unwrap_or(0);
+            let photometric = first_value(&tiff, ifd, TAG_PHOTOMETRIC)?.unwrap_or(0);
+
+            if subfile_type != 0 || !matches!(photometric, PHOTOMETRIC_CFA | PHOTOMETRIC_LINEAR_RAW)
+            {
+                continue;
+            }
+            let width = first_value(&tiff, ifd, TAG_IMAGE_WIDTH)?.unwrap_or(0) as usize;
+
+            if raw_ifd.map(|(_, w)| width > w).unwrap_or(true) {
+                raw_ifd = Some((ifd, width));
+            }
+        }
+        let (raw_ifd, _) = raw_ifd.ok_or(DngDecodeErrors::GenericStatic(
+            "No CFA or LinearRaw image found in file"
+        ))?;
+
+        let raw = self.read_raw_info(&tiff, raw_ifd)?;
+
+        let (top, left, bottom, right) = raw.active_area;
+        let (width, height) = (right - left, bottom - top);
+
+        trace!("Width: {}", width);
+        trace!("Height: {}", height);
+        trace!("Bits per sample: {}", raw.bits_per_sample);
+        trace!("Compression: {}", raw.compression);
+        trace!("CFA: {:?}", raw.cfa);
+
+        self.raw = Some(raw);
+        self.decoded_headers = true;
+
+        Ok(())
+    }
+
+    fn read_raw_info(&self, tiff: &Tiff, ifd: &Ifd) -> Result<RawInfo, DngDecodeErrors> {
+        let required = |tag: u16, name: &str| -> Result<u32, DngDecodeErrors> {
+            first_value(tiff, ifd, tag)?
+                .ok_or_else(|| DngDecodeErrors::Generic(format!("Missing required tag {name}")))
+        };
+        let width = required(TAG_IMAGE_WIDTH, "ImageWidth")? as usize;
+        let height = required(TAG_IMAGE_LENGTH, "ImageLength")? as usize;
+        let photometric = required(TAG_PHOTOMETRIC, "PhotometricInterpretation")?;
+        let compression = first_value(tiff, ifd, TAG_COMPRESSION)?.unwrap_or(COMPRESSION_NONE);
+        let samples_per_pixel =
+            first_value(tiff, ifd, TAG_SAMPLES_PER_PIXEL)?.unwrap_or(1) as usize;
+        let bits_per_sample = first_value(tiff, ifd, TAG_BITS_PER_SAMPLE)?.unwrap_or(1);
+
+        if width == 0 || height == 0 {
+            return Err(DngDecodeErrors::GenericStatic("Zero dimensions"));
+        }
+        // check the stored size, the active area is only a part of it
+        if width > self.options.max_width() {
+            return Err(DngDecodeErrors::TooLargeDimensions(
+                "width",
+                self.options.max_width(),
+                width
+            ));
+        }
+        if height > self.options.max_height() {
+            return Err(DngDecodeErrors::TooLargeDimensions(
+                "height",
+                self.options.max_height(),
+                height
+            ));
+        }
+        if !matches!(compression, COMPRESSION_NONE | COMPRESSION_LOSSLESS_JPEG) {
+            return Err(DngDecodeErrors::Unsupported(format!(
+                "Compression {compression}"
+            )));
+        }
+        if !(1..=16).contains(&bits_per_sample) {
+            return Err(DngDecodeErrors::Unsupported(format!(
+                "Bits per sample {bits_per_sample}"
+            )));
+        }
+        if first_value(tiff, ifd, TAG_PLANAR_CONFIGURATION)?.unwrap_or(1) != 1 {
+            return Err(DngDecodeErrors::Unsupported("Planar configuration".into()));
+        }
+        let cfa = if photometric == PHOTOMETRIC_CFA {
+            if samples_per_pixel != 1 {
+                return Err(DngDecodeErrors::GenericStatic(
+                    "CFA images must have one sample per pixel"
+                ));
+            }
+            let dimensions = ifd
+                .get(TAG_CFA_REPEAT_PATTERN_DIM)
+                .map(|x| tiff.values_u32(x))
+                .transpose()?
+                .unwrap_or(vec![2, 2]);
+
+            if dimensions != [2, 2] {
+                return Err(DngDecodeErrors::Unsupported(format!(
+                    "CFA repeat pattern {dimensions:?}, only 2x2 patterns are supported"
+                )));
+            }
+            let pattern = ifd
+                .get(TAG_CFA_PATTERN)
+                .map(|x| tiff.bytes(x))
+                .ok_or(DngDecodeErrors::GenericStatic("Missing CFAPattern tag"))?;
+
+            if pattern.len() != 4 || pattern.iter().any(|x| *x > 2) {
+                return Err(DngDecodeErrors::Unsupported(format!(
+                    "CFA pattern {pattern:?}, only RGB patterns are supported"
+                )));
+            }
+            Some([[pattern[0], pattern[1]], [pattern[2], pattern[3]]])
+        } else {
+            if samples_per_pixel != 3 {
+                return Err(DngDecodeErrors::Unsupported(format!(
+                    "LinearRaw image with {samples_per_pixel} samples per pixel"
+                )));
+            }
+            None
+        };
+
+        let layout = if let Some(offsets) = ifd.get(TAG_TILE_OFFSETS) {
+            let counts = ifd
+                .get(TAG_TILE_BYTE_COUNTS)
+                .ok_or(DngDecodeErrors::GenericStatic("Missing TileByteCounts tag"))?;
+
+            let tile_width = required(TAG_TILE_WIDTH, "TileWidth")? as usize;
+            let tile_height = required(TAG_TILE_LENGTH, "TileLength")? as usize;
+
+            if tile_width == 0 || tile_height == 0 {
+                return Err(DngDecodeErrors::GenericStatic("Zero tile dimensions"));
+            }
+            // tiles are multiples of 16 pixels, anything larger than the padded
+            // image would only make us allocate space for nothing
+            if tile_width > width.next_multiple_of(16) || tile_height > height.next_multiple_of(16)
+            {
+                return Err(DngDecodeErrors::Generic(format!(
+                    "Tile size {tile_width}x{tile_height} is larger than the image"
+                )));
+            }
+            DataLayout::Tiles {
+                offsets: tiff.values_u32(offsets)?,
+                byte_counts: tiff.values_u32(counts)?,
+                tile_width,
+                tile_height
+            }
+        } else {
+            let offsets = ifd
+                .get(TAG_STRIP_OFFSETS)
+                .ok_or(DngDecodeErrors::GenericStatic("Missing StripOffsets tag"))?;
+            let counts = ifd
+                .get(TAG_STRIP_BYTE_COUNTS)
+                .ok_or(DngDecodeErrors::GenericStatic(
+                    "Missing StripByteCounts tag"
+                ))?;
+
+            let rows_per_strip = first_value(tiff, ifd, TAG_ROWS_PER_STRIP)?
+                .map(|x| x as usize)
+                .unwrap_or(height)
+                .clamp(1, height);
+
+            DataLayout::Strips {
+                offsets: tiff.values_u32(offsets)?,
+                byte_counts: tiff.values_u32(counts)?,
+                rows_per_strip
+            }
+        };
+
+        let linearization = ifd
+            .get(TAG_LINEARIZATION_TABLE)
+            .map(|x| tiff.values_u32(x))
+            .transpose()?
+            .map(|x| x.iter().map(|y| *y as u16).collect::<Vec<u16>>())
+            .filter(|x| !x.is_empty());
+
+        let black_repeat = match ifd
+            .get(TAG_BLACK_LEVEL_REPEAT_DIM)
+            .map(|x| tiff.values_u32(x))
+            .transpose()?
+            .as_deref()
+        {
+            Some([rows, cols])
+                if (1..=MAX_BLACK_REPEAT).contains(&(*rows as usize))
+                    && (1..=MAX_BLACK_REPEAT).contains(&(*cols as usize)) =>
+            {
+                (*rows as usize, *cols as usize)
+            }
+            _ => (1, 1)
+        };
+        let black_count = black_repeat.0 * black_repeat.1 * samples_per_pixel;
+
+        let mut black_level: Vec<f32> = ifd
+            .get(TAG_BLACK_LEVEL)
+            .map(|x| tiff.values_f64(x))
+            .transpose()?
+            .unwrap_or_default()
+            .iter()
+            .map(|x| *x as f32)
+            .collect();
+
+        if black_level.len() == 1 {
+            black_level = vec![black_level[0]; black_count];
+        } else if black_level.len() != black_count {
+            black_level = vec![0.0; black_count];
+        }
+
+        let default_white = ((1_u32 << bits_per_sample) - 1) as f32;
+        let mut white_level: Vec<f32> = ifd
+            .get(TAG_WHITE_LEVEL)
+            .map(|x| tiff.values_f64(x))
+            .transpose()?
+            .unwrap_or_default()
+            .iter()
+            .map(|x| *x as f32)
+            .collect();
+
+        if white_level.len() != samples_per_pixel {
+            let white = white_level.first().copied().unwrap_or(default_white);
+            white_level = vec![white; samples_per_pixel];
+        }
+
+        let active_area = match ifd
+            .get(TAG_ACTIVE_AREA)
+            .map(|x| tiff.values_u32(x))
+            .transpose()?
+            .as_deref()
+        {
+            Some(&[top, left, bottom, right])
+                if top < bottom
+                    && left < right
+                    && bottom as usize <= height
+                    && right as usize <= width =>
+            {
+                (top as usize, left as usize, bottom as usize, right as usize)
+            }
+            _ => (0, 0, height, width)
+        };
+
+        Ok(RawInfo {
+            width,
+            height,
+            bits_per_sample: bits_per_sample as u8,
+            samples_per_pixel,
+            compression,
+            cfa,
+            layout,
+            linearization,
+            black_repeat,
+            black_level,
+            white_level,
+            active_area,
+            big_endian: tiff.is_big_endian()
+        })
+    }
+
+    /// Return the image dimensions as `(width, height)` or `None` if
+    /// headers haven't been decoded
+    ///
+    /// This is the size of the active area, i.e. the decoded image size
+    pub fn dimensions(&self) -> Option<(usize, usize)> {
+        let raw = self.raw.as_ref()?;
+        let (top, left, bottom, right) = raw.active_area;
+
+        Some((right - left, bottom - top))
+    }
+
+    /// Return the colorspace of the decoded pixels or `None` if
+    /// headers haven't been decoded
+    ///
+    /// This is always RGB in the camera native color space
+    pub fn colorspace(&self) -> Option<ColorSpace> {
+        self.raw.as_ref().map(|_| ColorSpace::RGB)
+    }
+
+    /// Return the color matrices and white balance stored in the file
+    /// or `None` if headers haven't been decoded
+    pub fn color_info(&self) -> Option<&DngColorInfo> {
+        self.raw.as_ref().map(|_| &self.color_info)
+    }
+
+    /// Return the exif orientation of the image or `None` if it isn't
+    /// stored in the file or headers haven't been decoded
+    pub const fn orientation(&self) -> Option<u16> {
+        self.orientation
+    }
+
+    /// Read raw samples of the whole stored image
+    fn read_samples(&self, raw: &RawInfo) -> Result<Vec<u16>, DngDecodeErrors> {
+        let spp = raw.samples_per_pixel;
+        let stride = raw.width * spp;
+
+        let total = stride
+            .checked_mul(raw.height)
+            .ok_or(DngDecodeErrors::GenericStatic("Image size overflows usize"))?;
+
+        let mut output = vec![0_u16; total];
+
+        // (offsets, counts, block width, block height, blocks across)
+        let (offsets, counts, block_width, block_height, across) = match &raw.layout {
+            DataLayout::Strips {
+                offsets,
+                byte_counts,
+                rows_per_strip
+            } => (offsets, byte_counts, raw.width, *rows_per_strip, 1),
+            DataLayout::Tiles {
+                offsets,
+                byte_counts,
+                tile_width,
+                tile_height
+            } => (
+                offsets,
+                byte_counts,
+                *tile_width,
+                *tile_height,
+                raw.width.div_ceil(*tile_width)
+            )
+        };
+        let down = raw.height.div_ceil(block_height);
+
+        if offsets.len() < across * down || counts.len() < offsets.len() {
+            return Err(DngDecodeErrors::GenericStatic(
+                "Not enough strips or tiles for image dimensions"
+            ));
+        }
+        let block_samples = block_width
+            .checked_mul(block_height)
+            .and_then(|x| x.checked_mul(spp))
+            .ok_or(DngDecodeErrors::GenericStatic("Tile size overflows usize"))?;
+
+        for (i, (offset, count)) in offsets.iter().zip(counts).take(across * down).enumerate() {
+            let start = *offset as usize;
+            let end = start.saturating_add(*count as usize).min(self.data.len());
+
+            let bytes = self
+                .data
+                .get(start..end)
+                .ok_or(DngDecodeErrors::GenericStatic(
+                    "Strip or tile offset points outside of the file"
+                ))?;
+
+            let block = match raw.compression {
+                COMPRESSION_LOSSLESS_JPEG => decode_lossless_jpeg(bytes, block_samples)?,
+                _ => unpack_samples(bytes, raw, block_width * spp, block_samples)
+            };
+            let (bx, by) = ((i % across) * block_width, (i / across) * block_height);
+            let copy_width = block_width.min(raw.width - bx) * spp;
+
+            for (row, samples) in block
+                .chunks_exact(block_width * spp)
+                .take(raw.height.saturating_sub(by))
+                .enumerate()
+            {
+                let out_start = (by + row) * stride + bx * spp;
+                output[out_start..out_start + copy_width].copy_from_slice(&samples[..copy_width]);
+            }
+        }
+        Ok(output)
+    }
+
+    /// Decode the image
+    ///
+    /// Returns interleaved RGB `f32` samples between 0 and 1, the
+    /// image is cropped to its active area.
+    pub fn decode(&mut self) -> Result<Vec<f32>, DngDecodeErrors> {
+        self.decode_headers()?;
+
+        let raw = self.raw.clone().unwrap();
+        let samples = self.read_samples(&raw)?;
+
+        let (top, left, bottom, right) = raw.active_area;
+        let (width, height) = (right - left, bottom - top);
+        let spp = raw.samples_per_pixel;
+        let (repeat_rows, repeat_cols) = raw.black_repeat;
+
+        let mut normalized = vec![0.0_f32; width * height * spp];
+
+        for (y, out_row) in normalized.chunks_exact_mut(width * spp).enumerate() {
+            let in_row = &samples[((y + top) * raw.width + left) * spp..][..width * spp];
+
+            for (x, (out, value)) in out_row
+                .chunks_exact_mut(spp)
+                .zip(in_row.chunks_exact(spp))
+                .enumerate()
+            {
+                // black levels repeat from the top left of the active area
+                let black =
+                    &raw.black_level[((y % repeat_rows) * repeat_cols + x % repeat_cols) * spp..];
+
+                for s in 0..spp {
+                    let mut sample = value[s];
+
+                    if let Some(table) = &raw.linearization {
+                        sample = table[usize::from(sample).min(table.len() - 1)];
+                    }
+                    let range = (raw.white_level[s] - black[s]).max(1.0);
+
+                    out[s] = ((f32::from(sample) - black[s]) / range).clamp(0.0, 1.0);
+                }
+            }
+        }
+        let Some(cfa) = raw.cfa else {
+            return Ok(normalized);
+        };
+        // the CFA pattern starts at the top left of the stored image
+        let cfa = [
+            [cfa[top & 1][left & 1], cfa[top & 1][(left + 1) & 1]],
+            [
+                cfa[(top + 1) & 1][left & 1],
+                cfa[(top + 1) & 1][(left + 1) & 1]
+            ]
+        ];
+        if self.options.dng_get_use_ahd() {
+            Ok(demosaic_ahd(&normalized, width, height, &cfa))
+        } else {
+            Ok(demosaic_bilinear(&normalized, width, height, &cfa))
+        }
+    }
+}
+
+/// Read the first integer value of a tag
+fn first_value(tiff: &Tiff, ifd: &Ifd, tag: u16) -> Result<Option<u32>, DngDecodeErrors> {
+    match ifd.get(tag) {
+        Some(entry) => Ok(tiff.values_u32(entry)?.first().copied()),
+        None => Ok(None)
+    }
+}
+
+/// Unpack uncompressed samples, rows start at a byte boundary
+fn unpack_samples(bytes: &[u8], raw: &RawInfo, row_samples: usize, total: usize) -> Vec<u16> {
+    let mut output = vec![0; total];
+
+    match raw.bits_per_sample {
+        8 => output
+            .iter_mut()
+            .zip(bytes)
+            .for_each(|(out, value)| *out = u16::from(*value)),
+        16 => output
+            .iter_mut()
+            .zip(bytes.chunks_exact(2))
+            .for_each(|(out, value)| {
+                *out = if raw.big_endian {
+                    u16::from_be_bytes([value[0], value[1]])
+                } else {
+                    u16::from_le_bytes([value[0], value[1]])
+                }
+            }),
+        bits => {
+            let bits = usize::from(bits);
+            let row_bytes = (row_samples * bits).div_ceil(8);
+
+            for (out_row, in_row) in output
+                .chunks_exact_mut(row_samples)
+                .zip(bytes.chunks(row_bytes))
+            {
+                // packed samples are always stored most significant bit first
+                let mut buffer = 0_u32;
+                let mut buffered = 0;
+                let mut input = in_row.iter();
+
+                for out in out_row {
+                    while buffered < bits {
+                        buffer = (buffer << 8) | u32::from(*input.next().unwrap_or(&0));
+                        buffered += 8;
+                    }
+                    buffered -= bits;
+                    *out = ((buffer >> buffered) & ((1 << bits) - 1)) as u16;
+                }
+            }
+        }
+    }
+    output
+}
+
+fn read_color_info(tiff: &Tiff, ifd: &Ifd) -> Result<DngColorInfo, DngDecodeErrors> {
+    let matrix = |tag: u16| -> Result<Option<[f32; 9]>, DngDecodeErrors> {
+        let Some(entry) = ifd.get(tag) else {
+            return Ok(None);
+        };
+        let values = tiff.values_f64(entry)?;
+        // four color cameras have 4x3 matrices, which we do not support
+        if values.len() != 9 {
+            return Ok(None);
+        }
+        let mut matrix = [0.0; 9];
+        matrix
+            .iter_mut()
+            .zip(values)
+            .for_each(|(out, value)| *out = value as f32);
+        Ok(Some(matrix))
+    };
+    let illuminant = |tag: u16| first_value(tiff, ifd, tag).map(|x| x.map(|y| y as u16));
+
+    let as_shot_neutral = match ifd.get(TAG_AS_SHOT_NEUTRAL) {
+        Some(entry) => match tiff.values_f64(entry)?.as_slice() {
+            &[r, g, b] => Some([r as f32, g as f32, b as f32]),
+            _ => None
+        },
+        None => None
+    };
+
+    Ok(DngColorInfo {
+        color_matrix_1: matrix(TAG_COLOR_MATRIX_1)?,
+        color_matrix_2: matrix(TAG_COLOR_MATRIX_2)?,
+        forward_matrix_1: matrix(TAG_FORWARD_MATRIX_1)?,
+        forward_matrix_2: matrix(TAG_FORWARD_MATRIX_2)?,
+        camera_calibration_1: matrix(TAG_CAMERA_CALIBRATION_1)?,
+        camera_calibration_2: matrix(TAG_CAMERA_CALIBRATION_2)?,
+        as_shot_neutral,
+        calibration_illuminant_1: illuminant(TAG_CALIBRATION_ILLUMINANT_1)?,
+        calibration_illuminant_2: illuminant(TAG_CALIBRATION_ILLUMINANT_2)?
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use zune_core::bytestream::ZCursor;
+    use zune_core::options::DecoderOptions;
+
+    use crate::{probe_dng, probe_dng_size, DngDecoder};
+
+    const SHORT: u16 = 3;
+    const LONG: u16 = 4;
+    const SRATIONAL: u16 = 10;
+    const BYTE: u16 = 1;
+
+    fn shorts(values: &[u16]) -> Vec<u8> {
+        values.iter().flat_map(|x| x.to_le_bytes()).collect()
+    }
+
+    fn longs(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|x| x.to_le_bytes()).collect()
+    }
+
+    /// Write a little endian TIFF with the image data first and a
+    /// single IFD containing `entries` as `(tag, type, count, value)`
+    fn build_tiff(image: &[u8], mut entries: Vec<(u16, u16, u32, Vec<u8>)>) -> Vec<u8> {
+        entries.sort_by_key(|x| x.0);
+
+        let ifd_offset = 8 + image.len();
+        let mut extra_offset = ifd_offset + 2 + entries.len() * 12 + 4;
+
+        let mut out = b"II*\0".to_vec();
+        out.extend_from_slice(&(ifd_offset as u32).to_le_bytes());
+        out.extend_from_slice(image);
+        out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+
+        let mut extra = Vec::new();
+
+        for (tag, field_type, count, mut value) in entries {
+            out.extend_from_slice(&tag.to_le_bytes());
+            out.extend_from_slice(&field_type.to_le_bytes());
+            out.extend_from_slice(&count.to_le_bytes());
+
+            if value.len() <= 4 {
+                value.resize(4, 0);
+                out.extend_from_slice(&value);
+            } else {
+                out.extend_from_slice(&(extra_offset as u32).to_le_bytes());
+                extra_offset += value.len();
+                extra.extend_from_slice(&value);
+            }
+        }
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&extra);
+        out
+    }
+
+    fn cfa_entries(width: u32, height: u32, image_len: u32) -> Vec<(u16, u16, u32, Vec<u8>)> {
+        vec![
+            (256, LONG, 1, longs(&[width])),
+            (257, LONG, 1, longs(&[height])),
+            (258, SHORT, 1, shorts(&[16])),
+            (259, SHORT, 1, shorts(&[1])),
+            (262, SHORT, 1, shorts(&[32803])),
+            (273, LONG, 1, longs(&[8])),
+            (277, SHORT, 1, shorts(&[1])),
+            (278, LONG, 1, longs(&[height])),
+            (279, LONG, 1, longs(&[image_len])),
+            (33421, SHORT, 2, shorts(&[2, 2])),
+            // RGGB
+            (33422, BYTE, 4, vec![0, 1, 1, 2]),
+            (50706, BYTE, 4, vec![1, 4, 0, 0]),
+            (50714, SHORT, 1, shorts(&[100])),
+            (50717, SHORT, 1, shorts(&[1100])),
+        ]
+    }
+
+    #[test]
+    fn test_uncompressed_cfa_bilinear() {
+        // 4x4 RGGB mosaic of a flat color (R=0.2, G=0.5, B=0.8) above black level
+        let (r, g, b) = (300_u16, 600, 900);
+        let image: Vec<u16> = (0..16)
+            .map(|i| match ((i / 4) & 1, i & 1) {
+                (0, 0) => r,
+                (1, 1) => b,
+                _ => g
+            })
+            .collect();
+        let image = shorts(&image);
+
+        let mut entries = cfa_entries(4, 4, image.len() as u32);
+        let matrix: Vec<u8> = (1..=9_i32)
+            .flat_map(|x| [x, 10])
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        entries.push((50721, SRATIONAL, 9, matrix));
+        entries.push((
+            50728,
+            SRATIONAL,
+            3,
+            [1_i32, 2, 1, 1, 1, 2]
+                .iter()
+                .flat_map(|x| x.to_le_bytes())
+                .collect()
+        ));
+
+        let file = build_tiff(&image, entries);
+
+        assert!(probe_dng(&file));
+        // the IFD entry count sits right after the image data
+        assert_eq!(probe_dng_size(&file[..8]), Some(8 + image.len() + 2));
+
+        let mut decoder = DngDecoder::new(ZCursor::new(&file));
+        let pixels = decoder.decode().unwrap();
+
+        assert_eq!(decoder.dimensions(), Some((4, 4)));
+        for pixel in pixels.chunks_exact(3) {
+            assert!((pixel[0] - 0.2).abs() < 1e-6);
+            assert!((pixel[1] - 0.5).abs() < 1e-6);
+            assert!((pixel[2] - 0.8).abs() < 1e-6);
+        }
+        let info = decoder.color_info().unwrap();
+        assert_eq!(info.color_matrix_1.unwrap()[8], 0.9);
+        assert_eq!(info.as_shot_neutral, Some([0.5, 1.0, 0.5]));
+        assert_eq!(info.color_matrix_2, None);
+    }
+
+    #[test]
+    fn test_active_area_and_ahd() {
+        // 8x8 mosaic where every photosite reads the same value,
+        // crop one pixel on each side
+        let image = shorts(&[600; 64]);
+        let mut entries = cfa_entries(8, 8, image.len() as u32);
+        entries.push((50829, LONG, 4, longs(&[1, 1, 7, 7])));
+
+        let file = build_tiff(&image, entries);
+        let options = DecoderOptions::default().dng_set_use_ahd(true);
+
+        let mut decoder = DngDecoder::new_with_options(ZCursor::new(&file), options);
+        let pixels = decoder.decode().unwrap();
+
+        assert_eq!(decoder.dimensions(), Some((6, 6)));
+        assert_eq!(pixels.len(), 6 * 6 * 3);
+        assert!(pixels.iter().all(|x| (x - 0.5).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_not_dng() {
+        let image = shorts(&[0; 4]);
+        let entries = cfa_entries(2, 2, image.len() as u32)
+            .into_iter()
+            .filter(|x| x.0 != 50706)
+            .collect();
+        let file = build_tiff(&image, entries);
+
+        assert!(!probe_dng(&file));
+        assert!(DngDecoder::new(ZCursor::new(&file)).decode().is_err());
+    }
+
+    #[test]
+    fn test_untrusted_sizes() {
+        let image = shorts(&[0; 4]);
+
+        // a small active area doesn't make a huge stored image fine
+        let mut entries = cfa_entries(1_000_000, 2, image.len() as u32);
+        entries.push((50829, LONG, 4, longs(&[0, 0, 2, 2])));
+        let file = build_tiff(&image, entries);
+        assert!(DngDecoder::new(ZCursor::new(&file)).decode().is_err());
+
+        // tiles far larger than the image
+        let mut entries: Vec<_> = cfa_entries(2, 2, image.len() as u32)
+            .into_iter()
+            .filter(|x| !matches!(x.0, 273 | 278 | 279))
+            .collect();
+        entries.push((322, LONG, 1, longs(&[1 << 20])));
+        entries.push((323, LONG, 1, longs(&[1 << 20])));
+        entries.push((324, LONG, 1, longs(&[8])));
+        entries.push((325, LONG, 1, longs(&[image.len() as u32])));
+        let file = build_tiff(&image, entries);
+        assert!(DngDecoder::new(ZCursor::new(&file)).decode().is_err());
+    }
+}
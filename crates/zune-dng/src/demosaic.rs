@@ -0,0 +1,254 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Bayer demosaicing
+//!
+//! Bilinear and AHD demosaicing work on 2x2 color filter arrays, the pattern is
+//! given as the color (0 = red, 1 = green, 2 = blue) at each position of
+//! the 2x2 tile.
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A 2x2 color filter array pattern
+pub(crate) type CfaPattern = [[u8; 2]; 2];
+
+#[inline(always)]
+fn color_at(cfa: &CfaPattern, x: usize, y: usize) -> usize {
+    usize::from(cfa[y & 1][x & 1])
+}
+
+/// Call `function` with the position of every neighbour of `(x,y)` in
+/// its 3x3 window that lies inside the image
+#[inline(always)]
+fn for_each_neighbour<F: FnMut(usize, usize)>(
+    x: usize, y: usize, width: usize, height: usize, mut function: F
+) {
+    for yy in y.saturating_sub(1)..(y + 2).min(height) {
+        for xx in x.saturating_sub(1)..(x + 2).min(width) {
+            if (xx, yy) != (x, y) {
+                function(xx, yy);
+            }
+        }
+    }
+}
+
+/// Bilinear demosaicing, missing colors are the average of
+/// the neighbours with that color in the 3x3 window
+pub(crate) fn demosaic_bilinear(
+    raw: &[f32], width: usize, height: usize, cfa: &CfaPattern
+) -> Vec<f32> {
+    let mut output = vec![0.0; width * height * 3];
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut sums = [0.0_f32; 3];
+            let mut counts = [0_u32; 3];
+
+            for_each_neighbour(x, y, width, height, |xx, yy| {
+                let color = color_at(cfa, xx, yy);
+                sums[color] += raw[yy * width + xx];
+                counts[color] += 1;
+            });
+            let own = color_at(cfa, x, y);
+            let pixel = &mut output[(y * width + x) * 3..][..3];
+
+            for (color, value) in pixel.iter_mut().enumerate() {
+                if color == own {
+                    *value = raw[y * width + x];
+                } else if counts[color] != 0 {
+                    *value = sums[color] / counts[color] as f32;
+                }
+            }
+        }
+    }
+    output
+}
+
+/// Interpolate green at red and blue positions using only
+/// horizontal or only vertical neighbours
+fn interpolate_green(
+    raw: &[f32], width: usize, height: usize, cfa: &CfaPattern, horizontal: bool
+) -> Vec<f32> {
+    let mut green = vec![0.0; width * height];
+    let step = if horizontal { 1 } else { width };
+
+    for y in 0..height {
+        for x in 0..width {
+            let pos = y * width + x;
+
+            if color_at(cfa, x, y) == 1 {
+                green[pos] = raw[pos];
+                continue;
+            }
+            let inside =
+                if horizontal { x >= 2 && x + 2 < width } else { y >= 2 && y + 2 < height };
+            if inside {
+                let (left, right) = (raw[pos - step], raw[pos + step]);
+                let value =
+                    ((left + raw[pos] + right) * 2.0 - raw[pos - 2 * step] - raw[pos + 2 * step])
+                        / 4.0;
+                // clamp to the neighbours to avoid overshoot
+                green[pos] = value.clamp(left.min(right), left.max(right));
+            } else {
+                let mut sum = 0.0;
+                let mut count = 0;
+
+                for_each_neighbour(x, y, width, height, |xx, yy| {
+                    if color_at(cfa, xx, yy) == 1 {
+                        sum += raw[yy * width + xx];
+                        count += 1;
+                    }
+                });
+                green[pos] = if count == 0 { 0.0 } else { sum / count as f32 };
+            }
+        }
+    }
+    green
+}
+
+/// Fill in red and blue using color differences against the interpolated green
+fn interpolate_red_blue(
+    raw: &[f32], green: &[f32], width: usize, height: usize, cfa: &CfaPattern
+) -> Vec<f32> {
+    let mut output = vec![0.0; width * height * 3];
+
+    for y in 0..height {
+        for x in 0..width {
+            let pos = y * width + x;
+            let own = color_at(cfa, x, y);
+
+            let mut sums = [0.0_f32; 3];
+            let mut counts = [0_u32; 3];
+
+            for_each_neighbour(x, y, width, height, |xx, yy| {
+                let color = color_at(cfa, xx, yy);
+                let n = yy * width + xx;
+                sums[color] += raw[n] - green[n];
+                counts[color] += 1;
+            });
+            let pixel = &mut output[pos * 3..][..3];
+
+            pixel[1] = green[pos];
+
+            for color in [0, 2] {
+                pixel[color] = if color == own {
+                    raw[pos]
+                } else if counts[color] != 0 {
+                    (green[pos] + sums[color] / counts[color] as f32).max(0.0)
+                } else {
+                    0.0
+                };
+            }
+        }
+    }
+    output
+}
+
+/// Convert linear RGB pixels to CIELab, assuming sRGB primaries
+fn rgb_to_lab(rgb: &[f32]) -> Vec<[f32; 3]> {
+    let f = |t: f32| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    rgb.chunks_exact(3)
+        .map(|p| {
+            let x = (0.412453 * p[0] + 0.357580 * p[1] + 0.180423 * p[2]) / 0.950456;
+            let y = 0.212671 * p[0] + 0.715160 * p[1] + 0.072169 * p[2];
+            let z = (0.019334 * p[0] + 0.119193 * p[1] + 0.950227 * p[2]) / 1.088754;
+
+            let (fx, fy, fz) = (f(x), f(y), f(z));
+
+            [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+        })
+        .collect()
+}
+
+/// Adaptive homogeneity-directed demosaicing
+///
+/// See K. Hirakawa, T.W. Parks, "Adaptive homogeneity-directed demosaicing algorithm",
+/// this follows the structure of the dcraw implementation without the tiling.
+pub(crate) fn demosaic_ahd(raw: &[f32], width: usize, height: usize, cfa: &CfaPattern) -> Vec<f32> {
+    if width < 5 || height < 5 {
+        return demosaic_bilinear(raw, width, height, cfa);
+    }
+    let directions = [true, false].map(|horizontal| {
+        let green = interpolate_green(raw, width, height, cfa, horizontal);
+        interpolate_red_blue(raw, &green, width, height, cfa)
+    });
+    let lab = [rgb_to_lab(&directions[0]), rgb_to_lab(&directions[1])];
+
+    // homogeneity of each direction per pixel
+    let mut homogeneity = [vec![0_u8; width * height], vec![0_u8; width * height]];
+
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let pos = y * width + x;
+            // left, right, up, down
+            let neighbours = [pos - 1, pos + 1, pos - width, pos + width];
+
+            let mut l_diff = [[0.0_f32; 4]; 2];
+            let mut ab_diff = [[0.0_f32; 4]; 2];
+
+            for d in 0..2 {
+                let centre = lab[d][pos];
+
+                for (i, n) in neighbours.iter().enumerate() {
+                    let other = lab[d][*n];
+                    let (da, db) = (centre[1] - other[1], centre[2] - other[2]);
+
+                    l_diff[d][i] = (centre[0] - other[0]).abs();
+                    ab_diff[d][i] = da * da + db * db;
+                }
+            }
+            let l_eps = l_diff[0][0]
+                .max(l_diff[0][1])
+                .min(l_diff[1][2].max(l_diff[1][3]));
+            let ab_eps = ab_diff[0][0]
+                .max(ab_diff[0][1])
+                .min(ab_diff[1][2].max(ab_diff[1][3]));
+
+            for d in 0..2 {
+                homogeneity[d][pos] = (0..4)
+                    .filter(|i| l_diff[d][*i] <= l_eps && ab_diff[d][*i] <= ab_eps)
+                    .count() as u8;
+            }
+        }
+    }
+    let mut output = vec![0.0; width * height * 3];
+
+    for y in 0..height {
+        for x in 0..width {
+            let pos = y * width + x;
+            let mut scores = [0_u32; 2];
+
+            for_each_neighbour(x, y, width, height, |xx, yy| {
+                for d in 0..2 {
+                    scores[d] += u32::from(homogeneity[d][yy * width + xx]);
+                }
+            });
+            for d in 0..2 {
+                scores[d] += u32::from(homogeneity[d][pos]);
+            }
+            let pixel = &mut output[pos * 3..][..3];
+            let horizontal = &directions[0][pos * 3..][..3];
+            let vertical = &directions[1][pos * 3..][..3];
+
+            for c in 0..3 {
+                pixel[c] = match scores[0].cmp(&scores[1]) {
+                    core::cmp::Ordering::Greater => horizontal[c],
+                    core::cmp::Ordering::Less => vertical[c],
+                    core::cmp::Ordering::Equal => (horizontal[c] + vertical[c]) * 0.5
+                };
+            }
+        }
+    }
+    output
+}
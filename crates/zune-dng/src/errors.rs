@@ -0,0 +1,95 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use alloc::string::String;
+use core::fmt::{Debug, Formatter};
+
+use zune_core::bytestream::ZByteIoError;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
+
+/// DNG errors that can occur during decoding
+#[non_exhaustive]
+pub enum DngDecodeErrors {
+    /// The file does not start with a TIFF header
+    InvalidMagicBytes,
+    /// The file is a TIFF but has no `DNGVersion` tag
+    NotDng,
+    /// The image uses a feature the decoder doesn't support
+    Unsupported(String),
+    /// Too large dimensions for a given width or
+    /// height
+    TooLargeDimensions(&'static str, usize, usize),
+    /// Errors from the lossless jpeg decoder
+    LosslessJpeg(&'static str),
+    /// Generic message
+    GenericStatic(&'static str),
+    /// Generic allocated message
+    Generic(String),
+    IoErrors(ZByteIoError)
+}
+
+impl Debug for DngDecodeErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidMagicBytes => {
+                writeln!(
+                    f,
+                    "Invalid magic bytes, file does not start with a TIFF header"
+                )
+            }
+            Self::NotDng => {
+                writeln!(
+                    f,
+                    "File is a TIFF file but not a DNG, no DNGVersion tag found"
+                )
+            }
+            Self::Unsupported(message) => {
+                writeln!(f, "Unsupported: {message}")
+            }
+            Self::TooLargeDimensions(dimension, expected, found) => {
+                writeln!(
+                    f,
+                    "Too large dimensions for {dimension} , {found} exceeds {expected}"
+                )
+            }
+            Self::LosslessJpeg(message) => {
+                writeln!(f, "Lossless JPEG: {message}")
+            }
+            Self::GenericStatic(message) => {
+                writeln!(f, "{message}")
+            }
+            Self::Generic(message) => {
+                writeln!(f, "{message}")
+            }
+            Self::IoErrors(err) => {
+                writeln!(f, "{:?}", err)
+            }
+        }
+    }
+}
+
+impl From<ZByteIoError> for DngDecodeErrors {
+    fn from(value: ZByteIoError) -> Self {
+        DngDecodeErrors::IoErrors(value)
+    }
+}
+
+impl ZErrorCodeTrait for DngDecodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::InvalidMagicBytes
+            | Self::NotDng
+            | Self::LosslessJpeg(_)
+            | Self::GenericStatic(_)
+            | Self::Generic(_) => ZErrorCode::CorruptData,
+            Self::Unsupported(_) => ZErrorCode::Unsupported,
+            Self::TooLargeDimensions(_, _, _) => ZErrorCode::LimitsExceeded,
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
@@ -0,0 +1,53 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! A basic DNG (Adobe digital negative) decoder
+//!
+//! This crate reads the raw image of a DNG file and turns it into RGB, it
+//! is not a raw development pipeline, the output is in the camera native color space
+//! and the color matrices and white balance needed to go further are exposed via
+//! [`DngColorInfo`].
+//!
+//! # Supported images
+//! - Uncompressed data with 1 to 16 bits per sample, in strips or tiles
+//! - Lossless JPEG compressed data, in strips or tiles
+//! - Color filter array images with a 2x2 RGB pattern (bayer), which are demosaiced
+//! - Linear raw images with three samples per pixel
+//!
+//! Linearization tables, black levels (including repeating patterns), white levels and
+//! the active area are applied, values are returned as `f32` between 0 and 1.
+//!
+//! # Demosaicing
+//! Bilinear demosaicing is used by default, adaptive homogeneity-directed (AHD)
+//! demosaicing can be enabled with [`DecoderOptions::dng_set_use_ahd`](zune_core::options::DecoderOptions::dng_set_use_ahd)
+//!
+//! # Features
+//!  - `log`: Use the `log` crate features to print image information when decoding
+//!  - `std`: Allow direct decoding from anything that implements `std::io::BufRead` + `std::io::Seek`
+//!
+//! # Usage
+//! ```no_run
+//! use zune_core::bytestream::ZCursor;
+//! use zune_dng::DngDecoder;
+//!
+//! let mut decoder = DngDecoder::new(ZCursor::new(b"II*\0"));
+//! let pixels = decoder.decode().unwrap();
+//! let (width, height) = decoder.dimensions().unwrap();
+//! let color_info = decoder.color_info().unwrap();
+//! ```
+extern crate alloc;
+
+pub use zune_core;
+
+pub use crate::decoder::{probe_dng, probe_dng_size, DngColorInfo, DngDecoder};
+pub use crate::errors::DngDecodeErrors;
+
+mod decoder;
+mod demosaic;
+mod errors;
+mod ljpeg;
+mod tiff;
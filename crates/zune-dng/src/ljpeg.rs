@@ -0,0 +1,527 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Lossless JPEG (ITU T.81 process 14) decoding
+//!
+//! DNG stores compressed raw data as lossless huffman coded JPEG, each
+//! sample is predicted from its left, top and top-left neighbours and the
+//! difference is huffman coded.
+//!
+//! Only what DNG writers produce is supported, that is non-hierarchical,
+//! non-differential huffman coded streams with a single scan, all components
+//! interleaved with 1x1 sampling.
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::errors::DngDecodeErrors;
+
+const SOF3: u8 = 0xC3;
+const DHT: u8 = 0xC4;
+const SOI: u8 = 0xD8;
+const EOI: u8 = 0xD9;
+const SOS: u8 = 0xDA;
+const DRI: u8 = 0xDD;
+
+/// Maximum components in a frame we decode
+const MAX_COMPONENTS: usize = 4;
+
+/// A canonical huffman table
+#[derive(Clone, Default)]
+struct HuffmanTable {
+    /// Largest code of each length, -1 if no codes of that length
+    max_code: [i32; 17],
+    /// Index into values of the first code of each length minus the code
+    val_ptr:  [i32; 17],
+    values:   Vec<u8>,
+    present:  bool
+}
+
+impl HuffmanTable {
+    fn new(counts: &[u8; 16], values: Vec<u8>) -> HuffmanTable {
+        let mut table = HuffmanTable {
+            max_code: [-1; 17],
+            val_ptr: [0; 17],
+            values,
+            present: true
+        };
+        let mut code = 0_i32;
+        let mut k = 0_i32;
+
+        for length in 1..=16 {
+            let count = i32::from(counts[length - 1]);
+
+            if count != 0 {
+                table.val_ptr[length] = k - code;
+                code += count;
+                k += count;
+                table.max_code[length] = code - 1;
+            }
+            code <<= 1;
+        }
+        table
+    }
+}
+
+/// MSB first bit reader that handles byte stuffing and markers
+struct BitReader<'a> {
+    data:      &'a [u8],
+    position:  usize,
+    buffer:    u64,
+    bits_left: u8,
+    /// Set when we hit a marker, we feed zeros afterwards
+    marker:    bool
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> BitReader<'a> {
+        BitReader {
+            data,
+            position: 0,
+            buffer: 0,
+            bits_left: 0,
+            marker: false
+        }
+    }
+
+    fn refill(&mut self) {
+        while self.bits_left <= 56 {
+            let mut byte = 0;
+
+            if !self.marker {
+                if let Some(&value) = self.data.get(self.position) {
+                    if value == 0xFF {
+                        match self.data.get(self.position + 1) {
+                            Some(0x00) => {
+                                byte = 0xFF;
+                                self.position += 2;
+                            }
+                            _ => self.marker = true
+                        }
+                    } else {
+                        byte = value;
+                        self.position += 1;
+                    }
+                }
+            }
+            self.buffer |= u64::from(byte) << (56 - self.bits_left);
+            self.bits_left += 8;
+        }
+    }
+
+    fn get_bits(&mut self, bits: u8) -> u32 {
+        if bits == 0 {
+            return 0;
+        }
+        if self.bits_left < bits {
+            self.refill();
+        }
+        let value = (self.buffer >> (64 - bits)) as u32;
+        self.buffer <<= bits;
+        self.bits_left -= bits;
+        value
+    }
+
+    fn decode_huffman(&mut self, table: &HuffmanTable) -> Result<u8, DngDecodeErrors> {
+        let mut code = 0_i32;
+
+        for length in 1..=16 {
+            code = (code << 1) | self.get_bits(1) as i32;
+
+            if code <= table.max_code[length] {
+                let index = (table.val_ptr[length] + code) as usize;
+
+                return table
+                    .values
+                    .get(index)
+                    .copied()
+                    .ok_or(DngDecodeErrors::LosslessJpeg("Invalid huffman code"));
+            }
+        }
+        Err(DngDecodeErrors::LosslessJpeg("Invalid huffman code"))
+    }
+
+    /// Read a difference value, see T.81 H.1.2.2 and F.2.2.1
+    fn decode_difference(&mut self, table: &HuffmanTable) -> Result<i32, DngDecodeErrors> {
+        let category = self.decode_huffman(table)?;
+
+        match category {
+            0 => Ok(0),
+            16 => Ok(32768),
+            1..=15 => {
+                let bits = self.get_bits(category) as i32;
+
+                if bits < (1 << (category - 1)) {
+                    Ok(bits - (1 << category) + 1)
+                } else {
+                    Ok(bits)
+                }
+            }
+            _ => Err(DngDecodeErrors::LosslessJpeg("Invalid difference category"))
+        }
+    }
+
+    /// Skip to the restart marker and reset the reader state
+    fn restart(&mut self) -> Result<(), DngDecodeErrors> {
+        // discard the buffered bits and find the RSTn marker
+        self.buffer = 0;
+        self.bits_left = 0;
+        self.marker = false;
+
+        while let Some(&byte) = self.data.get(self.position) {
+            if byte == 0xFF {
+                if let Some(0xD0..=0xD7) = self.data.get(self.position + 1) {
+                    self.position += 2;
+                    return Ok(());
+                }
+            }
+            self.position += 1;
+        }
+        Err(DngDecodeErrors::LosslessJpeg("Missing restart marker"))
+    }
+}
+
+/// Decode a lossless jpeg stream, returning interleaved samples
+/// of all components
+///
+/// `max_samples` bounds the output so corrupt headers don't
+/// cause huge allocations
+pub(crate) fn decode_lossless_jpeg(
+    data: &[u8], max_samples: usize
+) -> Result<Vec<u16>, DngDecodeErrors> {
+    let read_u16 = |pos: usize| -> Result<usize, DngDecodeErrors> {
+        data.get(pos..pos + 2)
+            .map(|x| usize::from(u16::from_be_bytes([x[0], x[1]])))
+            .ok_or(DngDecodeErrors::LosslessJpeg("Unexpected end of stream"))
+    };
+
+    if data.get(..2) != Some(&[0xFF, SOI]) {
+        return Err(DngDecodeErrors::LosslessJpeg("No SOI marker"));
+    }
+    let mut position = 2;
+    let mut tables: [HuffmanTable; 4] = Default::default();
+    let mut restart_interval = 0;
+    // (precision, height, width, component ids)
+    let mut frame: Option<(u8, usize, usize, Vec<u8>)> = None;
+
+    loop {
+        // markers may be preceded by any number of fill bytes
+        while data.get(position) == Some(&0xFF) && data.get(position + 1) == Some(&0xFF) {
+            position += 1;
+        }
+        let marker = match data.get(position..position + 2) {
+            Some([0xFF, marker]) => *marker,
+            _ => return Err(DngDecodeErrors::LosslessJpeg("Expected a marker"))
+        };
+        position += 2;
+
+        if marker == EOI {
+            return Err(DngDecodeErrors::LosslessJpeg("No scan in stream"));
+        }
+        let length = read_u16(position)?;
+
+        let segment = data
+            .get(position + 2..position + length)
+            .ok_or(DngDecodeErrors::LosslessJpeg("Segment extends past stream"))?;
+
+        match marker {
+            SOF3 => {
+                if segment.len() < 6 {
+                    return Err(DngDecodeErrors::LosslessJpeg("Short SOF3 segment"));
+                }
+                let precision = segment[0];
+                let height = read_u16(position + 3)?;
+                let width = read_u16(position + 5)?;
+                let num_components = usize::from(segment[5]);
+
+                if !(2..=16).contains(&precision) {
+                    return Err(DngDecodeErrors::LosslessJpeg("Invalid sample precision"));
+                }
+                if num_components == 0 || num_components > MAX_COMPONENTS {
+                    return Err(DngDecodeErrors::LosslessJpeg(
+                        "Unsupported number of components"
+                    ));
+                }
+                let mut ids = Vec::with_capacity(num_components);
+
+                for i in 0..num_components {
+                    let component = segment
+                        .get(6 + i * 3..9 + i * 3)
+                        .ok_or(DngDecodeErrors::LosslessJpeg("Short SOF3 segment"))?;
+
+                    if component[1] != 0x11 {
+                        return Err(DngDecodeErrors::LosslessJpeg(
+                            "Subsampled components are not supported"
+                        ));
+                    }
+                    ids.push(component[0]);
+                }
+                frame = Some((precision, height, width, ids));
+            }
+            DHT => {
+                let mut table_data = segment;
+
+                while !table_data.is_empty() {
+                    let class_id = table_data[0];
+                    let counts: [u8; 16] = table_data
+                        .get(1..17)
+                        .and_then(|x| x.try_into().ok())
+                        .ok_or(DngDecodeErrors::LosslessJpeg("Short DHT segment"))?;
+
+                    let total: usize = counts.iter().map(|x| usize::from(*x)).sum();
+
+                    let values = table_data
+                        .get(17..17 + total)
+                        .ok_or(DngDecodeErrors::LosslessJpeg("Short DHT segment"))?;
+
+                    let index = usize::from(class_id & 0x0F);
+
+                    if index > 3 {
+                        return Err(DngDecodeErrors::LosslessJpeg("Invalid huffman table id"));
+                    }
+                    tables[index] = HuffmanTable::new(&counts, values.to_vec());
+                    table_data = &table_data[17 + total..];
+                }
+            }
+            DRI => {
+                restart_interval = read_u16(position + 2)?;
+            }
+            SOS => {
+                let (precision, height, width, ids) = frame
+                    .take()
+                    .ok_or(DngDecodeErrors::LosslessJpeg("SOS before SOF3"))?;
+
+                let num_components = usize::from(*segment.first().unwrap_or(&0));
+
+                if num_components != ids.len() {
+                    return Err(DngDecodeErrors::LosslessJpeg(
+                        "Scans with a subset of components are not supported"
+                    ));
+                }
+                let mut component_tables = vec![];
+
+                for i in 0..num_components {
+                    let selector = segment
+                        .get(1 + i * 2..3 + i * 2)
+                        .ok_or(DngDecodeErrors::LosslessJpeg("Short SOS segment"))?;
+
+                    if !ids.contains(&selector[0]) {
+                        return Err(DngDecodeErrors::LosslessJpeg("Unknown component in scan"));
+                    }
+                    let table = &tables[usize::from(selector[1] >> 4) & 3];
+
+                    if !table.present {
+                        return Err(DngDecodeErrors::LosslessJpeg("Missing huffman table"));
+                    }
+                    component_tables.push(table);
+                }
+                let params = segment
+                    .get(1 + num_components * 2..4 + num_components * 2)
+                    .ok_or(DngDecodeErrors::LosslessJpeg("Short SOS segment"))?;
+
+                let predictor = params[0];
+                let point_transform = params[2] & 0x0F;
+
+                if !(1..=7).contains(&predictor) {
+                    return Err(DngDecodeErrors::LosslessJpeg("Invalid predictor"));
+                }
+                let total = width
+                    .checked_mul(height)
+                    .and_then(|x| x.checked_mul(num_components))
+                    .filter(|x| *x <= max_samples)
+                    .ok_or(DngDecodeErrors::LosslessJpeg(
+                        "Stream dimensions larger than expected"
+                    ))?;
+
+                let scan = ScanInfo {
+                    width,
+                    components: num_components,
+                    precision,
+                    predictor,
+                    point_transform,
+                    restart_interval
+                };
+                let mut samples = vec![0; total];
+                decode_scan(
+                    &data[position + length..],
+                    &scan,
+                    &component_tables,
+                    &mut samples
+                )?;
+
+                return Ok(samples);
+            }
+            0xC0..=0xCF if marker != DHT && marker != 0xC8 && marker != 0xCC => {
+                return Err(DngDecodeErrors::LosslessJpeg(
+                    "Only lossless huffman (SOF3) streams are supported"
+                ));
+            }
+            _ => ()
+        }
+        position += length;
+    }
+}
+
+struct ScanInfo {
+    width:            usize,
+    components:       usize,
+    precision:        u8,
+    predictor:        u8,
+    point_transform:  u8,
+    restart_interval: usize
+}
+
+fn decode_scan(
+    data: &[u8], scan: &ScanInfo, tables: &[&HuffmanTable], output: &mut [u16]
+) -> Result<(), DngDecodeErrors> {
+    let mut reader = BitReader::new(data);
+    let components = scan.components;
+    let stride = scan.width * components;
+    let height = output.len() / stride.max(1);
+    let mask = (1_i32 << scan.precision) - 1;
+    let initial = 1_i32 << (scan.precision - scan.point_transform - 1);
+
+    let mut mcus_left = scan.restart_interval;
+    // the first sample of the scan and of every restart interval uses
+    // the initial prediction, the rest of that line uses the left neighbour
+    let mut use_initial = true;
+    let mut first_line = true;
+
+    for row in 0..height {
+        for x in 0..scan.width {
+            if scan.restart_interval != 0 {
+                if mcus_left == 0 {
+                    reader.restart()?;
+                    mcus_left = scan.restart_interval;
+                    use_initial = true;
+                    first_line = true;
+                }
+                mcus_left -= 1;
+            }
+            for (c, table) in tables.iter().enumerate() {
+                let pos = row * stride + x * components + c;
+                let difference = reader.decode_difference(table)?;
+
+                let prediction = if use_initial {
+                    initial
+                } else if first_line {
+                    i32::from(output[pos - components])
+                } else if x == 0 {
+                    i32::from(output[pos - stride])
+                } else {
+                    let ra = i32::from(output[pos - components]);
+                    let rb = i32::from(output[pos - stride]);
+                    let rc = i32::from(output[pos - stride - components]);
+
+                    match scan.predictor {
+                        1 => ra,
+                        2 => rb,
+                        3 => rc,
+                        4 => ra + rb - rc,
+                        5 => ra + ((rb - rc) >> 1),
+                        6 => rb + ((ra - rc) >> 1),
+                        _ => (ra + rb) >> 1
+                    }
+                };
+                output[pos] = ((prediction + difference) & mask) as u16;
+            }
+            use_initial = false;
+        }
+        first_line = false;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::decode_lossless_jpeg;
+
+    /// Encode samples as a lossless jpeg using predictor 1 and a huffman
+    /// table where every category has a 5 bit code equal to the category
+    fn encode(samples: &[u16], width: usize, height: usize, components: usize) -> Vec<u8> {
+        let mut out = vec![0xFF, 0xD8];
+        // SOF3
+        out.extend_from_slice(&[0xFF, 0xC3]);
+        out.extend_from_slice(&(8 + 3 * components as u16).to_be_bytes());
+        out.push(16);
+        out.extend_from_slice(&(height as u16).to_be_bytes());
+        out.extend_from_slice(&(width as u16).to_be_bytes());
+        out.push(components as u8);
+        for c in 0..components {
+            out.extend_from_slice(&[c as u8, 0x11, 0]);
+        }
+        // DHT, 17 symbols of length 5
+        out.extend_from_slice(&[0xFF, 0xC4, 0, 2 + 17 + 17, 0x00]);
+        let mut counts = [0_u8; 16];
+        counts[4] = 17;
+        out.extend_from_slice(&counts);
+        out.extend(0..17_u8);
+        // SOS, predictor 1
+        out.extend_from_slice(&[0xFF, 0xDA]);
+        out.extend_from_slice(&(6 + 2 * components as u16).to_be_bytes());
+        out.push(components as u8);
+        for c in 0..components {
+            out.extend_from_slice(&[c as u8, 0x00]);
+        }
+        out.extend_from_slice(&[1, 0, 0]);
+
+        let mut bits: Vec<bool> = Vec::new();
+        let mut push = |value: u32, count: u32| {
+            for i in (0..count).rev() {
+                bits.push((value >> i) & 1 == 1);
+            }
+        };
+        let stride = width * components;
+
+        for (i, sample) in samples.iter().enumerate() {
+            let (row, pos) = (i / stride, i % stride);
+            let prediction = match (row, pos < components) {
+                (0, true) => 1 << 15,
+                (_, true) => i32::from(samples[i - stride]),
+                _ => i32::from(samples[i - components])
+            };
+            let difference = (i32::from(*sample) - prediction) as i16 as i32;
+            let category = 32 - difference.unsigned_abs().leading_zeros();
+
+            push(category, 5);
+            if category != 0 && category != 16 {
+                let extra =
+                    if difference < 0 { difference + (1 << category) - 1 } else { difference };
+                push(extra as u32, category);
+            }
+        }
+        while !bits.len().is_multiple_of(8) {
+            bits.push(true);
+        }
+        for byte in bits.chunks_exact(8) {
+            let value = byte.iter().fold(0_u8, |acc, x| (acc << 1) | u8::from(*x));
+            out.push(value);
+            if value == 0xFF {
+                out.push(0x00);
+            }
+        }
+        out.extend_from_slice(&[0xFF, 0xD9]);
+        out
+    }
+
+    #[test]
+    fn test_lossless_jpeg_roundtrip() {
+        let (width, height, components) = (5, 3, 2);
+        let samples: Vec<u16> = (0..width * height * components)
+            .map(|x| ((x * 7919) % 65536) as u16)
+            .collect();
+
+        let encoded = encode(&samples, width, height, components);
+        let decoded = decode_lossless_jpeg(&encoded, samples.len()).unwrap();
+
+        assert_eq!(decoded, samples);
+        // refuse streams larger than the caller expects
+        assert!(decode_lossless_jpeg(&encoded, samples.len() - 1).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! A minimal TIFF structure reader
+//!
+//! DNG is a TIFF file, this reads the header and image file directories (IFDs)
+//! from an in memory buffer, values are only decoded when asked for.
+use alloc::vec::Vec;
+
+use crate::errors::DngDecodeErrors;
+
+pub(crate) const TAG_NEW_SUBFILE_TYPE: u16 = 254;
+pub(crate) const TAG_IMAGE_WIDTH: u16 = 256;
+pub(crate) const TAG_IMAGE_LENGTH: u16 = 257;
+pub(crate) const TAG_BITS_PER_SAMPLE: u16 = 258;
+pub(crate) const TAG_COMPRESSION: u16 = 259;
+pub(crate) const TAG_PHOTOMETRIC: u16 = 262;
+pub(crate) const TAG_STRIP_OFFSETS: u16 = 273;
+pub(crate) const TAG_ORIENTATION: u16 = 274;
+pub(crate) const TAG_SAMPLES_PER_PIXEL: u16 = 277;
+pub(crate) const TAG_ROWS_PER_STRIP: u16 = 278;
+pub(crate) const TAG_STRIP_BYTE_COUNTS: u16 = 279;
+pub(crate) const TAG_PLANAR_CONFIGURATION: u16 = 284;
+pub(crate) const TAG_TILE_WIDTH: u16 = 322;
+pub(crate) const TAG_TILE_LENGTH: u16 = 323;
+pub(crate) const TAG_TILE_OFFSETS: u16 = 324;
+pub(crate) const TAG_TILE_BYTE_COUNTS: u16 = 325;
+pub(crate) const TAG_SUB_IFDS: u16 = 330;
+pub(crate) const TAG_CFA_REPEAT_PATTERN_DIM: u16 = 33421;
+pub(crate) const TAG_CFA_PATTERN: u16 = 33422;
+pub(crate) const TAG_DNG_VERSION: u16 = 50706;
+pub(crate) const TAG_LINEARIZATION_TABLE: u16 = 50712;
+pub(crate) const TAG_BLACK_LEVEL_REPEAT_DIM: u16 = 50713;
+pub(crate) const TAG_BLACK_LEVEL: u16 = 50714;
+pub(crate) const TAG_WHITE_LEVEL: u16 = 50717;
+pub(crate) const TAG_COLOR_MATRIX_1: u16 = 50721;
+pub(crate) const TAG_COLOR_MATRIX_2: u16 = 50722;
+pub(crate) const TAG_CAMERA_CALIBRATION_1: u16 = 50723;
+pub(crate) const TAG_CAMERA_CALIBRATION_2: u16 = 50724;
+pub(crate) const TAG_AS_SHOT_NEUTRAL: u16 = 50728;
+pub(crate) const TAG_CALIBRATION_ILLUMINANT_1: u16 = 50778;
+pub(crate) const TAG_CALIBRATION_ILLUMINANT_2: u16 = 50779;
+pub(crate) const TAG_ACTIVE_AREA: u16 = 50829;
+pub(crate) const TAG_FORWARD_MATRIX_1: u16 = 50964;
+pub(crate) const TAG_FORWARD_MATRIX_2: u16 = 50965;
+
+/// Size of each TIFF field type in bytes, indexed by the type
+const TYPE_SIZES: [usize; 19] = [0, 1, 1, 2, 4, 8, 1, 1, 2, 4, 8, 4, 8, 4, 0, 0, 8, 8, 8];
+
+/// Maximum number of IFDs we follow, guards against offset loops
+const MAX_IFDS: usize = 64;
+
+/// A single directory entry
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct IfdEntry {
+    pub(crate) tag: u16,
+    field_type:     u16,
+    count:          usize,
+    /// Absolute position of the value in the file, for values
+    /// of 4 bytes or less this points into the entry itself
+    position:       usize
+}
+
+/// An image file directory
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Ifd {
+    entries: Vec<IfdEntry>
+}
+
+impl Ifd {
+    pub(crate) fn get(&self, tag: u16) -> Option<&IfdEntry> {
+        self.entries.iter().find(|x| x.tag == tag)
+    }
+}
+
+pub(crate) struct Tiff<'a> {
+    data:       &'a [u8],
+    big_endian: bool
+}
+
+impl<'a> Tiff<'a> {
+    /// Parse the TIFF header, returning the reader and the offset of the first IFD
+    pub(crate) fn new(data: &'a [u8]) -> Result<(Tiff<'a>, usize), DngDecodeErrors> {
+        let big_endian = match data.get(..4) {
+            Some(b"II*\0") => false,
+            Some(b"MM\0*") => true,
+            _ => return Err(DngDecodeErrors::InvalidMagicBytes)
+        };
+        let tiff = Tiff { data, big_endian };
+        let first_ifd = tiff.u32(4)? as usize;
+
+        Ok((tiff, first_ifd))
+    }
+
+    pub(crate) fn u16(&self, position: usize) -> Result<u16, DngDecodeErrors> {
+        let bytes: [u8; 2] = self
+            .data
+            .get(position..position + 2)
+            .and_then(|x| x.try_into().ok())
+            .ok_or(DngDecodeErrors::GenericStatic("Unexpected end of file"))?;
+
+        if self.big_endian {
+            Ok(u16::from_be_bytes(bytes))
+        } else {
+            Ok(u16::from_le_bytes(bytes))
+        }
+    }
+
+    pub(crate) fn u32(&self, position: usize) -> Result<u32, DngDecodeErrors> {
+        let bytes: [u8; 4] = self
+            .data
+            .get(position..position + 4)
+            .and_then(|x| x.try_into().ok())
+            .ok_or(DngDecodeErrors::GenericStatic("Unexpected end of file"))?;
+
+        if self.big_endian {
+            Ok(u32::from_be_bytes(bytes))
+        } else {
+            Ok(u32::from_le_bytes(bytes))
+        }
+    }
+
+    pub(crate) const fn is_big_endian(&self) -> bool {
+        self.big_endian
+    }
+
+    /// Read the IFD at `offset`, returning it and the offset of the next IFD
+    /// (zero if this is the last one)
+    pub(crate) fn read_ifd(&self, offset: usize) -> Result<(Ifd, usize), DngDecodeErrors> {
+        let num_entries = usize::from(self.u16(offset)?);
+        let mut entries = Vec::with_capacity(num_entries);
+
+        for i in 0..num_entries {
+            let start = offset + 2 + i * 12;
+
+            let tag = self.u16(start)?;
+            let field_type = self.u16(start + 2)?;
+            let count = self.u32(start + 4)? as usize;
+
+            let Some(&size) = TYPE_SIZES.get(usize::from(field_type)) else {
+                // unknown types are skipped as the spec requires
+                continue;
+            };
+            let length = size
+                .checked_mul(count)
+                .ok_or(DngDecodeErrors::GenericStatic("IFD entry too large"))?;
+
+            let position = if length <= 4 { start + 8 } else { self.u32(start + 8)? as usize };
+            if position.saturating_add(length) > self.data.len() {
+                return Err(DngDecodeErrors::GenericStatic(
+                    "IFD entry points outside of the file"
+                ));
+            }
+            entries.push(IfdEntry {
+                tag,
+                field_type,
+                count,
+                position
+            });
+        }
+        let next = self.u32(offset + 2 + num_entries * 12)? as usize;
+
+        Ok((Ifd { entries }, next))
+    }
+
+    /// Read a chain of IFDs starting at `offset`
+    pub(crate) fn read_ifd_chain(&self, mut offset: usize) -> Result<Vec<Ifd>, DngDecodeErrors> {
+        let mut ifds = Vec::new();
+
+        while offset != 0 {
+            if ifds.len() >= MAX_IFDS {
+                return Err(DngDecodeErrors::GenericStatic("Too many IFDs in file"));
+            }
+            let (ifd, next) = self.read_ifd(offset)?;
+            ifds.push(ifd);
+            offset = next;
+        }
+        Ok(ifds)
+    }
+
+    /// Raw bytes of an entry's value
+    pub(crate) fn bytes(&self, entry: &IfdEntry) -> &'a [u8] {
+        let length = TYPE_SIZES[usize::from(entry.field_type)] * entry.count;
+        // bounds were checked when reading the IFD
+        &self.data[entry.position..entry.position + length]
+    }
+
+    /// Read the values of an integer entry
+    pub(crate) fn values_u32(&self, entry: &IfdEntry) -> Result<Vec<u32>, DngDecodeErrors> {
+        let position = entry.position;
+
+        (0..entry.count)
+            .map(|i| match entry.field_type {
+                // BYTE, UNDEFINED and SBYTE
+                1 | 6 | 7 => Ok(u32::from(self.data[position + i])),
+                // SHORT and SSHORT
+                3 | 8 => self.u16(position + i * 2).map(u32::from),
+                // LONG, SLONG and IFD
+                4 | 9 | 13 => self.u32(position + i * 4),
+                _ => Err(DngDecodeErrors::GenericStatic(
+                    "Expected an integer type for IFD entry"
+                ))
+            })
+            .collect()
+    }
+
+    /// Read the values of any numeric entry as floating point
+    pub(crate) fn values_f64(&self, entry: &IfdEntry) -> Result<Vec<f64>, DngDecodeErrors> {
+        let position = entry.position;
+
+        (0..entry.count)
+            .map(|i| match entry.field_type {
+                1 | 7 => Ok(f64::from(self.data[position + i])),
+                6 => Ok(f64::from(self.data[position + i] as i8)),
+                3 => self.u16(position + i * 2).map(f64::from),
+                8 => self.u16(position + i * 2).map(|x| f64::from(x as i16)),
+                4 | 13 => self.u32(position + i * 4).map(f64::from),
+                9 => self.u32(position + i * 4).map(|x| f64::from(x as i32)),
+                5 | 10 => {
+                    let numerator = self.u32(position + i * 8)?;
+                    let denominator = self.u32(position + i * 8 + 4)?;
+
+                    let (numerator, denominator) = if entry.field_type == 5 {
+                        (f64::from(numerator), f64::from(denominator))
+                    } else {
+                        (f64::from(numerator as i32), f64::from(denominator as i32))
+                    };
+                    if denominator == 0.0 {
+                        return Ok(0.0);
+                    }
+                    Ok(numerator / denominator)
+                }
+                11 => self
+                    .u32(position + i * 4)
+                    .map(|x| f64::from(f32::from_bits(x))),
+                12 => {
+                    let high = u64::from(self.u32(position + i * 8)?);
+                    let low = u64::from(self.u32(position + i * 8 + 4)?);
+
+                    let bits =
+                        if self.big_endian { (high << 32) | low } else { (low << 32) | high };
+                    Ok(f64::from_bits(bits))
+                }
+                _ => Err(DngDecodeErrors::GenericStatic(
+                    "Expected a numeric type for IFD entry"
+                ))
+            })
+            .collect()
+    }
+}
@@ -12,6 +12,7 @@ use core::fmt::{Debug, Formatter};
 use zune_core::bit_depth::BitDepth;
 use zune_core::bytestream::{ZByteIoError, ZByteWriterTrait, ZWriter};
 use zune_core::colorspace::ColorSpace;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
 use zune_core::options::EncoderOptions;
 
 /// Errors possible during encoding
@@ -61,6 +62,19 @@ impl From<ZByteIoError> for FarbFeldEncoderErrors {
         FarbFeldEncoderErrors::IOErrors(value)
     }
 }
+
+impl ZErrorCodeTrait for FarbFeldEncoderErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::TooLargeDimensions(_) => ZErrorCode::LimitsExceeded,
+            Self::UnsupportedBitDepth(_) | Self::UnsupportedColorSpace(_) => {
+                ZErrorCode::Unsupported
+            }
+            Self::TooShortInput(_, _) => ZErrorCode::InvalidInput,
+            Self::IOErrors(err) => err.code()
+        }
+    }
+}
 /// A FarbFeld encoder
 ///
 /// The encoder's entry point is `new` which initializes the encoder
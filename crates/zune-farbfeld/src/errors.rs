@@ -1,6 +1,7 @@
 use core::fmt::{Debug, Formatter};
 
 use zune_core::bytestream::ZByteIoError;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
 
 pub enum FarbFeldErrors {
     Generic(&'static str),
@@ -25,3 +26,12 @@ impl From<ZByteIoError> for FarbFeldErrors {
         FarbFeldErrors::IoError(value)
     }
 }
+
+impl ZErrorCodeTrait for FarbFeldErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::Generic(_) => ZErrorCode::CorruptData,
+            Self::IoError(err) => err.code()
+        }
+    }
+}
@@ -1,31 +1,96 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use zune_core::bit_depth::BitDepth;
 use zune_core::bytestream::{ZByteReaderTrait, ZReader};
+use zune_core::colorspace::ColorSpace;
 use zune_core::log::trace;
 use zune_core::options::DecoderOptions;
 
 use crate::enums::DisposalMethod;
 use crate::errors::GifDecoderErrors;
+use crate::lzw::decode_lzw;
+
+const EXTENSION_INTRODUCER: u8 = 0x21;
+const IMAGE_DESCRIPTOR: u8 = 0x2C;
+const TRAILER: u8 = 0x3B;
+
+const GRAPHIC_CONTROL_LABEL: u8 = 0xF9;
+const APPLICATION_LABEL: u8 = 0xFF;
+
+/// Information about a single frame of a GIF image
+///
+/// The position and size describe the rectangle of the logical screen the
+/// frame covers, the rest of the fields come from the graphic control extension
+/// preceding the frame, if any.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct GifFrameInfo {
+    /// Left position of the frame on the logical screen
+    pub left:              usize,
+    /// Top position of the frame on the logical screen
+    pub top:               usize,
+    /// Frame width
+    pub width:             usize,
+    /// Frame height
+    pub height:            usize,
+    /// How long the frame should be shown, in hundredths of a second
+    pub delay:             u16,
+    /// What to do with the frame area before drawing the next frame
+    pub disposal:          DisposalMethod,
+    /// Palette index that should be treated as transparent
+    pub transparent_index: Option<u8>,
+    /// Whether the frame rows are stored interlaced
+    pub interlaced:        bool,
+    /// Whether the viewer should wait for user input before moving
+    /// to the next frame
+    pub user_input:        bool
+}
 
-#[derive(Default)]
-struct DisposeArea {
-    _left:   usize,
-    _top:    usize,
-    _width:  usize,
-    _height: usize
+/// Values read from a graphic control extension, they apply
+/// to the next image descriptor only
+#[derive(Copy, Clone, Default)]
+struct GraphicControl {
+    delay:             u16,
+    disposal:          u8,
+    transparent_index: Option<u8>,
+    user_input:        bool
 }
+
+/// A GIF decoder
+///
+/// Frames are decoded one at a time with [`decode_into`](Self::decode_into), which
+/// composites the next frame onto the canvas passed in, so the canvas should be reused
+/// between calls.
 pub struct GifDecoder<T: ZByteReaderTrait> {
-    stream:        ZReader<T>,
-    options:       DecoderOptions,
-    width:         usize,
-    height:        usize,
-    flags:         u8,
-    bgindex:       u8,
-    ratio:         u8,
-    read_headers:  bool,
-    _background:   u16, // current b
-    frame_pos:     usize,
-    pal:           [[u8; 4]; 256],
-    _dispose_area: DisposeArea,
-    background:    Vec<u8>
+    stream:         ZReader<T>,
+    options:        DecoderOptions,
+    width:          usize,
+    height:         usize,
+    flags:          u8,
+    bgindex:        u8,
+    ratio:          u8,
+    read_headers:   bool,
+    frame_pos:      usize,
+    pal:            [[u8; 4]; 256],
+    local_pal:      [[u8; 4]; 256],
+    has_local_pal:  bool,
+    loop_count:     Option<u16>,
+    control:        Option<GraphicControl>,
+    current_frame:  Option<GifFrameInfo>,
+    previous_frame: Option<GifFrameInfo>,
+    // canvas contents before the previous frame was drawn, used
+    // for DisposalMethod::Restore
+    restore_buf:    Vec<u8>,
+    indices:        Vec<u8>,
+    lzw_data:       Vec<u8>
 }
 
 impl<T: ZByteReaderTrait> GifDecoder<T> {
@@ -42,13 +107,21 @@ impl<T: ZByteReaderTrait> GifDecoder<T> {
             bgindex: 0,
             ratio: 0,
             read_headers: false,
-            _background: 0,
             frame_pos: 0,
             pal: [[0; 4]; 256],
-            _dispose_area: Default::default(),
-            background: vec![]
+            local_pal: [[0; 4]; 256],
+            has_local_pal: false,
+            loop_count: None,
+            control: None,
+            current_frame: None,
+            previous_frame: None,
+            restore_buf: vec![],
+            indices: vec![],
+            lzw_data: vec![]
         }
     }
+    /// Decode the logical screen descriptor, the global color table and
+    /// the extensions up to the first frame
     pub fn decode_headers(&mut self) -> Result<(), GifDecoderErrors> {
         if self.read_headers {
             return Ok(());
@@ -60,15 +133,17 @@ impl<T: ZByteReaderTrait> GifDecoder<T> {
         self.width = usize::from(self.stream.get_u16_le_err()?);
         self.height = usize::from(self.stream.get_u16_le_err()?);
 
-        // bit 7:	local color table flag
-        // bit 6:	interlace flag
-        // bit 5:	sorted flag
-        // bit 4-3: Reserved
-        // bit 2-0:	size of local color table
+        // bit 7:	global color table flag
+        // bit 6-4:	color resolution
+        // bit 3:	sorted flag
+        // bit 2-0:	size of global color table
         self.flags = self.stream.read_u8_err()?;
         self.bgindex = self.stream.read_u8_err()?;
         self.ratio = self.stream.read_u8_err()?;
 
+        if self.width == 0 || self.height == 0 {
+            return Err(GifDecoderErrors::Static("Image width or height is zero"));
+        }
         if self.width > self.options.max_width() {
             return Err(GifDecoderErrors::TooLargeDimensions(
                 "width",
@@ -85,30 +160,206 @@ impl<T: ZByteReaderTrait> GifDecoder<T> {
         }
         // check if we have a global palette
         if (self.flags & 0x80) > 0 {
-            self.parse_colortable(2 << (self.flags & 0b111), usize::MAX)?;
+            let entries = 2 << (self.flags & 0b111);
+            read_colortable(&mut self.stream, &mut self.pal, entries)?;
         }
         trace!("Image width  :{}", self.width);
         trace!("Image height :{}", self.height);
         trace!("Ratio: {}", self.ratio);
+        trace!("Background index: {}", self.bgindex);
+
         self.read_headers = true;
+        // read up to the first frame, this is where the loop count usually lives
+        self.read_frame_header()?;
 
         Ok(())
     }
-    fn parse_colortable(&mut self, num_entries: usize, transp: usize) -> Result<(), &'static str> {
-        self.pal
-            .iter_mut()
-            .take(num_entries)
-            .enumerate()
-            .for_each(|(pos, x)| {
-                // weird order
-                x[2] = self.stream.read_u8();
-                x[1] = self.stream.read_u8();
-                x[0] = self.stream.read_u8();
-                x[3] = if transp == pos { 0 } else { 255 }
-            });
+
+    /// Read blocks until the next image descriptor or the trailer
+    fn read_frame_header(&mut self) -> Result<(), GifDecoderErrors> {
+        self.current_frame = None;
+
+        loop {
+            if self.stream.eof()? {
+                // a lot of files lack a trailer, treat end of data as one
+                return Ok(());
+            }
+            match self.stream.read_u8_err()? {
+                EXTENSION_INTRODUCER => {
+                    let label = self.stream.read_u8_err()?;
+
+                    match label {
+                        GRAPHIC_CONTROL_LABEL => self.parse_graphic_control()?,
+                        APPLICATION_LABEL => self.parse_application_extension()?,
+                        _ => {
+                            // comments and plain text extensions carry nothing we display
+                            trace!("Skipping extension {:#04X}", label);
+                            self.skip_sub_blocks()?;
+                        }
+                    }
+                }
+                IMAGE_DESCRIPTOR => {
+                    self.parse_image_descriptor()?;
+                    return Ok(());
+                }
+                TRAILER => return Ok(()),
+                block => {
+                    if self.options.strict_mode() {
+                        return Err(GifDecoderErrors::UnknownBlock(block));
+                    }
+                    // garbage after the last frame, stop here
+                    trace!("Unknown block {:#04X}, stopping", block);
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn parse_graphic_control(&mut self) -> Result<(), GifDecoderErrors> {
+        let size = self.stream.read_u8_err()?;
+
+        if size < 4 {
+            return Err(GifDecoderErrors::Static(
+                "Graphic control extension too small"
+            ));
+        }
+        // bit 7-5: reserved
+        // bit 4-2: disposal method
+        // bit 1:   user input flag
+        // bit 0:   transparent color flag
+        let flags = self.stream.read_u8_err()?;
+        let delay = self.stream.get_u16_le_err()?;
+        let transparent = self.stream.read_u8_err()?;
+
+        self.stream.skip(usize::from(size - 4))?;
+        self.skip_sub_blocks()?;
+
+        self.control = Some(GraphicControl {
+            delay,
+            disposal: (flags >> 2) & 0b111,
+            transparent_index: if (flags & 1) != 0 { Some(transparent) } else { None },
+            user_input: (flags & 0b10) != 0
+        });
         Ok(())
     }
 
+    fn parse_application_extension(&mut self) -> Result<(), GifDecoderErrors> {
+        let size = self.stream.read_u8_err()?;
+
+        if size != 11 {
+            // not an application identifier + authentication code, skip it
+            self.stream.skip(usize::from(size))?;
+            return self.skip_sub_blocks();
+        }
+        let mut identifier = [0; 11];
+        self.stream.read_exact_bytes(&mut identifier)?;
+
+        if &identifier == b"NETSCAPE2.0" || &identifier == b"ANIMEXTS1.0" {
+            loop {
+                let length = usize::from(self.stream.read_u8_err()?);
+
+                if length == 0 {
+                    break;
+                }
+                let sub_id = self.stream.read_u8_err()?;
+
+                if sub_id == 1 && length >= 3 {
+                    let count = self.stream.get_u16_le_err()?;
+                    trace!("Loop count: {}", count);
+                    self.loop_count = Some(count);
+                    self.stream.skip(length - 3)?;
+                } else {
+                    self.stream.skip(length - 1)?;
+                }
+            }
+            Ok(())
+        } else {
+            trace!("Skipping application extension {:?}", identifier);
+            self.skip_sub_blocks()
+        }
+    }
+
+    fn parse_image_descriptor(&mut self) -> Result<(), GifDecoderErrors> {
+        let left = usize::from(self.stream.get_u16_le_err()?);
+        let top = usize::from(self.stream.get_u16_le_err()?);
+        let width = usize::from(self.stream.get_u16_le_err()?);
+        let height = usize::from(self.stream.get_u16_le_err()?);
+        // bit 7:   local color table flag
+        // bit 6:   interlace flag
+        // bit 5:   sorted flag
+        // bit 4-3: reserved
+        // bit 2-0: size of local color table
+        let flags = self.stream.read_u8_err()?;
+
+        self.has_local_pal = (flags & 0x80) != 0;
+
+        if self.has_local_pal {
+            let entries = 2 << (flags & 0b111);
+            read_colortable(&mut self.stream, &mut self.local_pal, entries)?;
+        }
+        let control = self.control.take().unwrap_or_default();
+
+        let info = GifFrameInfo {
+            left,
+            top,
+            width,
+            height,
+            delay: control.delay,
+            disposal: DisposalMethod::from_flags(control.disposal),
+            transparent_index: control.transparent_index,
+            interlaced: (flags & 0x40) != 0,
+            user_input: control.user_input
+        };
+        trace!("Frame {}: {:?}", self.frame_pos, info);
+
+        self.current_frame = Some(info);
+        Ok(())
+    }
+
+    fn skip_sub_blocks(&mut self) -> Result<(), GifDecoderErrors> {
+        loop {
+            let length = self.stream.read_u8_err()?;
+            if length == 0 {
+                return Ok(());
+            }
+            self.stream.skip(usize::from(length))?;
+        }
+    }
+
+    /// Read the image data sub-blocks of the current frame into `lzw_data`
+    fn read_image_data(&mut self) -> Result<(), GifDecoderErrors> {
+        self.lzw_data.clear();
+
+        loop {
+            if self.stream.eof()? {
+                // truncated file, decode what we have
+                return Ok(());
+            }
+            let length = usize::from(self.stream.read_u8_err()?);
+            if length == 0 {
+                return Ok(());
+            }
+            let start = self.lzw_data.len();
+            self.lzw_data.resize(start + length, 0);
+
+            if self
+                .stream
+                .read_exact_bytes(&mut self.lzw_data[start..])
+                .is_err()
+            {
+                if self.options.strict_mode() {
+                    return Err(GifDecoderErrors::Static("Truncated image data"));
+                }
+                self.lzw_data.truncate(start);
+                return Ok(());
+            }
+        }
+    }
+
+    /// Return the size of the buffer needed to hold a frame
+    ///
+    /// Or none if the headers haven't been decoded or the
+    /// calculation overflows
     pub fn output_buf_size(&self) -> Option<usize> {
         if self.read_headers {
             return self.width.checked_mul(self.height)?.checked_mul(4);
@@ -116,22 +367,61 @@ impl<T: ZByteReaderTrait> GifDecoder<T> {
         None
     }
 
-    #[inline]
-    fn _fill_rect(dispose_area: &DisposeArea, width: usize, output: &mut [u8], color: u32) {
-        output
-            .chunks_exact_mut(width)
-            .skip(dispose_area._top)
-            .take(dispose_area._height)
-            .for_each(|x| {
-                x.chunks_exact_mut(4)
-                    .skip(dispose_area._left)
-                    .take(dispose_area._width)
-                    .for_each(|x| x.copy_from_slice(&color.to_le_bytes()))
-            });
-    }
-    pub fn decode_into(
-        &mut self, output: &mut [u8], two_back: Option<&[u8]>
-    ) -> Result<(), GifDecoderErrors> {
+    /// Return the dimensions of the logical screen, this is
+    /// the size of every decoded frame
+    pub const fn dimensions(&self) -> Option<(usize, usize)> {
+        if self.read_headers {
+            return Some((self.width, self.height));
+        }
+        None
+    }
+
+    /// Return the colorspace of decoded frames, this is always RGBA
+    pub const fn colorspace(&self) -> Option<ColorSpace> {
+        if self.read_headers {
+            return Some(ColorSpace::RGBA);
+        }
+        None
+    }
+
+    /// Return the bit depth of decoded frames, this is always eight bits
+    pub const fn depth(&self) -> BitDepth {
+        BitDepth::Eight
+    }
+
+    /// Return the number of times the animation should be played
+    ///
+    /// - `None`: The image has no loop extension, it should be played once
+    /// - `Some(0)`: The animation should loop forever
+    /// - `Some(n)`: The animation should be repeated `n` times
+    ///
+    /// The loop extension usually comes before the first frame, but some
+    /// files place it later, so this may change as frames are decoded.
+    pub const fn loop_count(&self) -> Option<u16> {
+        self.loop_count
+    }
+
+    /// Return true if there are more frames to decode
+    pub const fn more_frames(&self) -> bool {
+        self.current_frame.is_some()
+    }
+
+    /// Return information about the next frame to be decoded
+    /// by [`decode_into`](Self::decode_into)
+    ///
+    /// Returns `None` if headers haven't been decoded or there are no more frames
+    pub const fn frame_info(&self) -> Option<GifFrameInfo> {
+        self.current_frame
+    }
+
+    /// Decode the next frame and composite it into `output`
+    ///
+    /// `output` should contain the previously decoded frame, i.e. the same
+    /// buffer should be passed for every frame, since frames only
+    /// cover part of the canvas and the previous frame shows through.
+    ///
+    /// Pixels are returned as 8 bit RGBA
+    pub fn decode_into(&mut self, output: &mut [u8]) -> Result<(), GifDecoderErrors> {
         self.decode_headers()?;
 
         let output_size = self
@@ -145,51 +435,317 @@ impl<T: ZByteReaderTrait> GifDecoder<T> {
         }
         let output = &mut output[..output_size];
 
+        let frame = self
+            .current_frame
+            .ok_or(GifDecoderErrors::Static("No more frames to decode"))?;
+
         if self.frame_pos == 0 {
-            // zero out everything
-            self.background.resize(output_size, 0);
+            // start with a transparent canvas
             output.fill(0);
-        } else {
-            // second frame, figure out how to dispose the first one
-
-            let mut dispose = DisposalMethod::from_flags((self.flags & 0x1C) >> 2);
-            let _pix_count = self.width * self.height;
-
-            if dispose == DisposalMethod::Restore && two_back.is_none() {
-                // fall back to background if we lack a background from two frames back
-                dispose = DisposalMethod::Background;
-            }
-            match dispose {
+        } else if let Some(previous) = self.previous_frame {
+            // figure out how to dispose the previous frame
+            match previous.disposal {
                 DisposalMethod::None | DisposalMethod::InPlace => {
-                    // ignore
+                    // leave it as is
                 }
                 DisposalMethod::Background => {
-                    // use previous
-                    // Self::fill_rect(&self.dispose_area, self.width, output);
+                    fill_rect(&previous, self.width, self.height, output, &[0; 4]);
+                }
+                DisposalMethod::Restore => {
+                    if self.restore_buf.len() == output.len() {
+                        copy_rect(
+                            &previous,
+                            self.width,
+                            self.height,
+                            &self.restore_buf,
+                            output
+                        );
+                    } else {
+                        // nothing to restore to, use the background
+                        fill_rect(&previous, self.width, self.height, output, &[0; 4]);
+                    }
+                }
+            }
+        }
+        if frame.disposal == DisposalMethod::Restore {
+            self.restore_buf.clear();
+            self.restore_buf.extend_from_slice(output);
+        }
+
+        // decode indices
+        let min_code_size = self.stream.read_u8_err()?;
+        self.read_image_data()?;
+
+        let frame_size = frame
+            .width
+            .checked_mul(frame.height)
+            .ok_or(GifDecoderErrors::OverflowError("frame dimensions"))?;
+
+        if frame_size
+            > self
+                .options
+                .max_width()
+                .saturating_mul(self.options.max_height())
+        {
+            return Err(GifDecoderErrors::Static("Frame dimensions too large"));
+        }
+        self.indices.resize(frame_size, 0);
+
+        let decoded = decode_lzw(&self.lzw_data, min_code_size, &mut self.indices)?;
+
+        if decoded < frame_size && self.options.strict_mode() {
+            return Err(GifDecoderErrors::Static(
+                "Image data ended before the frame was complete"
+            ));
+        }
+
+        let palette = if self.has_local_pal { &self.local_pal } else { &self.pal };
+
+        if frame.width != 0 {
+            for (pos, row) in self.indices[..decoded].chunks(frame.width).enumerate() {
+                let y = frame.top + deinterlace_row(pos, frame.height, frame.interlaced);
+
+                if y >= self.height || frame.left >= self.width {
+                    continue;
+                }
+                let visible = row.len().min(self.width - frame.left);
+                let start = (y * self.width + frame.left) * 4;
+                let out_row = &mut output[start..start + visible * 4];
+
+                for (index, pix) in row.iter().zip(out_row.chunks_exact_mut(4)) {
+                    if Some(*index) != frame.transparent_index {
+                        pix.copy_from_slice(&palette[usize::from(*index)]);
+                    }
                 }
-                DisposalMethod::Restore => {}
             }
         }
+
+        self.previous_frame = Some(frame);
         self.frame_pos += 1;
 
+        self.read_frame_header()?;
+
         Ok(())
     }
+
+    /// Decode the first frame of the image returning RGBA pixels
+    ///
+    /// Use [`decode_into`](Self::decode_into) to decode animated images
+    pub fn decode(&mut self) -> Result<Vec<u8>, GifDecoderErrors> {
+        self.decode_headers()?;
+
+        let size = self
+            .output_buf_size()
+            .ok_or(GifDecoderErrors::OverflowError(
+                "cannot calculate output dimensions"
+            ))?;
+        let mut output = vec![0; size];
+
+        self.decode_into(&mut output)?;
+
+        Ok(output)
+    }
+}
+
+/// Read a color table of `num_entries` RGB entries into `pal`
+fn read_colortable<T: ZByteReaderTrait>(
+    stream: &mut ZReader<T>, pal: &mut [[u8; 4]; 256], num_entries: usize
+) -> Result<(), GifDecoderErrors> {
+    for entry in pal.iter_mut().take(num_entries) {
+        let mut rgb = [0; 3];
+        stream.read_exact_bytes(&mut rgb)?;
+        *entry = [rgb[0], rgb[1], rgb[2], 255];
+    }
+    Ok(())
+}
+
+/// Map the `pos`-th stored row of a frame to the row it is displayed at
+///
+/// Interlaced images store every 8th row starting at 0, then every 8th row
+/// starting at 4, every 4th starting at 2 and finally every 2nd starting at 1
+fn deinterlace_row(pos: usize, height: usize, interlaced: bool) -> usize {
+    if !interlaced {
+        return pos;
+    }
+    let mut pos = pos;
+
+    for (start, step) in [(0, 8), (4, 8), (2, 4), (1, 2)] {
+        let rows = height.saturating_sub(start).div_ceil(step);
+        if pos < rows {
+            return start + pos * step;
+        }
+        pos -= rows;
+    }
+    // more rows than the frame height, these get clipped
+    usize::MAX / 2
+}
+
+#[inline]
+fn fill_rect(
+    frame: &GifFrameInfo, width: usize, height: usize, output: &mut [u8], color: &[u8; 4]
+) {
+    let right = (frame.left + frame.width).min(width);
+    let bottom = (frame.top + frame.height).min(height);
+
+    for y in frame.top..bottom {
+        for x in frame.left..right {
+            let start = (y * width + x) * 4;
+            output[start..start + 4].copy_from_slice(color);
+        }
+    }
+}
+
+#[inline]
+fn copy_rect(frame: &GifFrameInfo, width: usize, height: usize, source: &[u8], output: &mut [u8]) {
+    let right = (frame.left + frame.width).min(width);
+    let bottom = (frame.top + frame.height).min(height);
+
+    if frame.left >= right {
+        return;
+    }
+    for y in frame.top..bottom {
+        let start = (y * width + frame.left) * 4;
+        let end = (y * width + right) * 4;
+        output[start..end].copy_from_slice(&source[start..end]);
+    }
 }
 
 fn test_gif<T: ZByteReaderTrait>(buffer: &mut ZReader<T>) -> bool {
-    if buffer.read_u8() != b'G'
-        || buffer.read_u8() != b'I'
-        || buffer.read_u8() != b'F'
-        || buffer.read_u8() != b'8'
-    {
+    let mut magic = [0; 6];
+
+    if buffer.read_exact_bytes(&mut magic).is_err() {
         return false;
     }
-    let sz = buffer.read_u8();
-    if sz != b'9' && sz != b'7' {
-        return false;
+    probe_gif(&magic)
+}
+
+/// Return true if the bytes start with a GIF signature
+pub fn probe_gif(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a")
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use zune_core::bytestream::ZCursor;
+
+    use crate::{DisposalMethod, GifDecoder};
+
+    /// Encode indices with a trivial LZW stream, a clear code before every
+    /// index keeps the code size fixed
+    fn lzw_encode(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1_u32 << min_code_size;
+        let code_size = u32::from(min_code_size) + 1;
+        let mut out = Vec::new();
+        let mut acc = 0_u32;
+        let mut bits = 0;
+
+        let mut push = |code: u32, out: &mut Vec<u8>| {
+            acc |= code << bits;
+            bits += code_size;
+            while bits >= 8 {
+                out.push(acc as u8);
+                acc >>= 8;
+                bits -= 8;
+            }
+        };
+        for index in indices {
+            push(clear, &mut out);
+            push(u32::from(*index), &mut out);
+        }
+        push(clear + 1, &mut out);
+        if bits > 0 {
+            out.push(acc as u8);
+        }
+        out
     }
-    if buffer.read_u8() != b'a' {
-        return false;
+
+    fn push_frame(
+        gif: &mut Vec<u8>, (left, top, width, height): (u16, u16, u16, u16), gce: [u8; 4],
+        interlaced: bool, indices: &[u8]
+    ) {
+        gif.extend_from_slice(&[0x21, 0xF9, 4]);
+        gif.extend_from_slice(&gce);
+        gif.push(0);
+
+        gif.push(0x2C);
+        for v in [left, top, width, height] {
+            gif.extend_from_slice(&v.to_le_bytes());
+        }
+        gif.push(if interlaced { 0x40 } else { 0 });
+        gif.push(2);
+        let data = lzw_encode(indices, 2);
+        for chunk in data.chunks(255) {
+            gif.push(chunk.len() as u8);
+            gif.extend_from_slice(chunk);
+        }
+        gif.push(0);
+    }
+
+    #[test]
+    fn test_animation_interlace_and_loop() {
+        let mut gif = Vec::new();
+        gif.extend_from_slice(b"GIF89a");
+        gif.extend_from_slice(&[4, 0, 8, 0, 0x81, 0, 0]);
+        // 4 colors: black, red, green, blue
+        gif.extend_from_slice(&[0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0, 255]);
+        // loop forever
+        gif.extend_from_slice(&[0x21, 0xFF, 11]);
+        gif.extend_from_slice(b"NETSCAPE2.0");
+        gif.extend_from_slice(&[3, 1, 0, 0, 0]);
+
+        // frame 1, interlaced, row y is filled with index y % 4
+        // stored in pass order 0, 4, 2, 6, 1, 3, 5, 7
+        let order = [0_usize, 4, 2, 6, 1, 3, 5, 7];
+        let indices: Vec<u8> = order.iter().flat_map(|y| [(*y % 4) as u8; 4]).collect();
+        // disposal background, 10 centiseconds
+        push_frame(&mut gif, (0, 0, 4, 8), [2 << 2, 10, 0, 0], true, &indices);
+        // frame 2, 2x2 at (1,1), index 0 is transparent
+        push_frame(&mut gif, (1, 1, 2, 2), [1, 20, 0, 0], false, &[0, 3, 3, 0]);
+        gif.push(0x3B);
+
+        let mut decoder = GifDecoder::new(ZCursor::new(&gif));
+        decoder.decode_headers().unwrap();
+        assert_eq!(decoder.loop_count(), Some(0));
+        assert_eq!(decoder.dimensions(), Some((4, 8)));
+
+        let mut canvas = vec![0; decoder.output_buf_size().unwrap()];
+
+        let info = decoder.frame_info().unwrap();
+        assert!(info.interlaced);
+        assert_eq!(info.delay, 10);
+        assert_eq!(info.disposal, DisposalMethod::Background);
+        decoder.decode_into(&mut canvas).unwrap();
+
+        let palette = [
+            [0, 0, 0, 255],
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255]
+        ];
+        for y in 0..8 {
+            for x in 0..4 {
+                let start = (y * 4 + x) * 4;
+                assert_eq!(canvas[start..start + 4], palette[y % 4], "row {y}");
+            }
+        }
+
+        let info = decoder.frame_info().unwrap();
+        assert_eq!(info.delay, 20);
+        assert_eq!(info.transparent_index, Some(0));
+        decoder.decode_into(&mut canvas).unwrap();
+        assert!(!decoder.more_frames());
+
+        // the first frame was cleared, so only the opaque pixels of frame two remain
+        for y in 0..8 {
+            for x in 0..4 {
+                let start = (y * 4 + x) * 4;
+                let expected =
+                    if (y, x) == (1, 2) || (y, x) == (2, 1) { [0, 0, 255, 255] } else { [0; 4] };
+                assert_eq!(canvas[start..start + 4], expected, "pixel ({x},{y})");
+            }
+        }
     }
-    true
 }
@@ -1,9 +1,25 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
 /// Different GIF disposal methods
-#[derive(Copy, Clone, PartialEq)]
-pub(crate) enum DisposalMethod {
+///
+/// This tells the decoder what to do with the area covered by a frame
+/// before drawing the next frame
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DisposalMethod {
+    /// No disposal specified, the frame is left as is
     None = 0,
+    /// The frame is left in place
     InPlace = 1,
+    /// The area covered by the frame is cleared to transparent
     Background = 2,
+    /// The area covered by the frame is restored to what it was
+    /// before the frame was drawn
     Restore = 3
 }
 
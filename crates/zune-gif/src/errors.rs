@@ -1,8 +1,18 @@
-use core::fmt::Debug;
-use std::fmt::Formatter;
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use core::fmt::{Debug, Formatter};
 
 use zune_core::bytestream::ZByteIoError;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
 
+/// GIF errors that can occur during decoding
+#[non_exhaustive]
 pub enum GifDecoderErrors {
     /// File is not a gif
     NotAGif,
@@ -15,10 +25,16 @@ pub enum GifDecoderErrors {
     /// A calculation that wasn't meant to overflow overflowed
     OverflowError(&'static str),
     /// Too small size
-    TooSmallSize(usize, usize)
+    TooSmallSize(usize, usize),
+    /// The image data contains a code that isn't in the LZW table
+    InvalidLzwCode(u16),
+    /// The LZW minimum code size is outside the range allowed by the spec
+    InvalidLzwCodeSize(u8),
+    /// An unknown block was found in the data stream
+    UnknownBlock(u8)
 }
 impl Debug for GifDecoderErrors {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             GifDecoderErrors::NotAGif => {
                 writeln!(f, "Not a gif, magic bytes didn't match")
@@ -44,6 +60,15 @@ impl Debug for GifDecoderErrors {
             Self::IoErrors(err) => {
                 writeln!(f, "{:?}", err)
             }
+            Self::InvalidLzwCode(code) => {
+                writeln!(f, "Invalid LZW code {code}")
+            }
+            Self::InvalidLzwCodeSize(size) => {
+                writeln!(f, "Invalid LZW minimum code size {size}, expected 1..=11")
+            }
+            Self::UnknownBlock(block) => {
+                writeln!(f, "Unknown block introducer {block:#04X}")
+            }
         }
     }
 }
@@ -59,3 +84,20 @@ impl From<ZByteIoError> for GifDecoderErrors {
         GifDecoderErrors::IoErrors(value)
     }
 }
+
+impl ZErrorCodeTrait for GifDecoderErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::NotAGif
+            | Self::Static(_)
+            | Self::InvalidLzwCode(_)
+            | Self::InvalidLzwCodeSize(_)
+            | Self::UnknownBlock(_) => ZErrorCode::CorruptData,
+            Self::TooLargeDimensions(_, _, _) | Self::OverflowError(_) => {
+                ZErrorCode::LimitsExceeded
+            }
+            Self::TooSmallSize(_, _) => ZErrorCode::InvalidInput,
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
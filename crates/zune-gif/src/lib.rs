@@ -1,6 +1,54 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! A GIF decoder
+//!
+//! # Supported images
+//! - GIF87a and GIF89a images, with global and local color tables
+//! - Interlaced frames
+//! - Animated images, including frame delays, disposal methods and
+//!   transparency from the graphic control extension
+//! - The loop count from the `NETSCAPE2.0` (or `ANIMEXTS1.0`) application extension
+//!
+//! Frames are composited onto a canvas the size of the logical screen and returned as
+//! 8 bit RGBA, this means each decoded frame is the full picture that should be shown
+//! at that point of the animation.
+//!
+//! # Features
+//!  - `log`: Use the `log` crate features to print image information when decoding
+//!  - `std`: Allow direct decoding from anything that implements `std::io::BufRead` + `std::io::Seek`
+//!
+//! # Usage
+//! ```no_run
+//! use zune_core::bytestream::ZCursor;
+//! use zune_gif::GifDecoder;
+//!
+//! let mut decoder = GifDecoder::new(ZCursor::new(&[]));
+//! decoder.decode_headers().unwrap();
+//!
+//! let mut canvas = vec![0; decoder.output_buf_size().unwrap()];
+//!
+//! while decoder.more_frames() {
+//!     let info = decoder.frame_info().unwrap();
+//!     decoder.decode_into(&mut canvas).unwrap();
+//!     // canvas now contains the frame, to be shown for info.delay hundredths of a second
+//! }
+//! ```
+#![no_std]
+#![macro_use]
+extern crate alloc;
+
+pub use zune_core;
+
+pub use crate::decoder::{probe_gif, GifDecoder, GifFrameInfo};
+pub use crate::enums::DisposalMethod;
+pub use crate::errors::GifDecoderErrors;
+
 mod decoder;
 mod enums;
 mod errors;
-
-pub use decoder::GifDecoder;
-pub use errors::GifDecoderErrors;
+mod lzw;
@@ -0,0 +1,131 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! GIF flavoured LZW decompression
+//!
+//! Codes are stored least significant bit first, start at `min_code_size + 1` bits
+//! and grow up to 12 bits, once the table is full the encoder either sends a clear
+//! code or keeps using the full table (deferred clear).
+
+use crate::errors::GifDecoderErrors;
+
+const MAX_CODES: usize = 4096;
+const MAX_CODE_SIZE: u8 = 12;
+
+/// Decompress `data` into `output`, returning the number of indices written
+///
+/// Indices that don't fit into `output` are discarded, and decoding stops at the
+/// end of information code or when the data runs out, whichever comes first,
+/// since many encoders in the wild produce truncated streams.
+pub(crate) fn decode_lzw(
+    data: &[u8], min_code_size: u8, output: &mut [u8]
+) -> Result<usize, GifDecoderErrors> {
+    if !(1..=11).contains(&min_code_size) {
+        return Err(GifDecoderErrors::InvalidLzwCodeSize(min_code_size));
+    }
+    let clear_code = 1_u16 << min_code_size;
+    let end_code = clear_code + 1;
+
+    let mut prefix = [0_u16; MAX_CODES];
+    let mut suffix = [0_u8; MAX_CODES];
+    let mut first = [0_u8; MAX_CODES];
+    let mut stack = [0_u8; MAX_CODES];
+
+    for code in 0..clear_code {
+        suffix[usize::from(code)] = code as u8;
+        first[usize::from(code)] = code as u8;
+    }
+
+    let mut code_size = min_code_size + 1;
+    let mut next_code = clear_code + 2;
+    let mut previous: Option<u16> = None;
+
+    let mut bit_buffer = 0_u32;
+    let mut bits_left = 0_u8;
+    let mut data_pos = 0;
+    let mut out_pos = 0;
+
+    loop {
+        // refill, codes are at most 12 bits so this never overflows
+        while bits_left < code_size && data_pos < data.len() {
+            bit_buffer |= u32::from(data[data_pos]) << bits_left;
+            bits_left += 8;
+            data_pos += 1;
+        }
+        if bits_left < code_size {
+            // truncated stream
+            break;
+        }
+        let code = (bit_buffer & ((1 << code_size) - 1)) as u16;
+        bit_buffer >>= code_size;
+        bits_left -= code_size;
+
+        if code == clear_code {
+            code_size = min_code_size + 1;
+            next_code = clear_code + 2;
+            previous = None;
+            continue;
+        }
+        if code == end_code {
+            break;
+        }
+
+        match previous {
+            None => {
+                // first code after a clear must be a literal
+                if code >= clear_code {
+                    return Err(GifDecoderErrors::InvalidLzwCode(code));
+                }
+            }
+            Some(prev) => {
+                let first_byte = if code < next_code {
+                    first[usize::from(code)]
+                } else if code == next_code {
+                    // the KwKwK case, code is the one we are about to add
+                    first[usize::from(prev)]
+                } else {
+                    return Err(GifDecoderErrors::InvalidLzwCode(code));
+                };
+
+                if usize::from(next_code) < MAX_CODES {
+                    let next = usize::from(next_code);
+
+                    prefix[next] = prev;
+                    suffix[next] = first_byte;
+                    first[next] = first[usize::from(prev)];
+                    next_code += 1;
+
+                    if usize::from(next_code) == (1 << code_size) && code_size < MAX_CODE_SIZE {
+                        code_size += 1;
+                    }
+                }
+            }
+        }
+
+        // unwind the string for this code, it's stored in reverse
+        let mut length = 0;
+        let mut current = code;
+
+        while current >= clear_code {
+            stack[length] = suffix[usize::from(current)];
+            current = prefix[usize::from(current)];
+            length += 1;
+        }
+        stack[length] = current as u8;
+        length += 1;
+
+        for byte in stack[..length].iter().rev() {
+            if out_pos < output.len() {
+                output[out_pos] = *byte;
+                out_pos += 1;
+            }
+        }
+        previous = Some(code);
+    }
+    Ok(out_pos)
+}
@@ -6,6 +6,7 @@
 
 //! Radiance HDR encoder
 
+use alloc::vec::Vec;
 use alloc::{format, vec};
 use std::collections::HashMap;
 
@@ -47,6 +48,9 @@ impl<'a> HdrEncoder<'a> {
     /// # Arguments:
     /// - headers: A hashmap containing keys and values, the values will be encoded as key=value
     /// in the hdr header before encoding
+    ///
+    /// Headers are written in hashmap iteration order unless
+    /// [`EncoderOptions::set_deterministic`] is set, in which case they are sorted by key
     pub fn add_headers(&mut self, headers: &'a HashMap<String, String>) {
         self.headers = Some(headers)
     }
@@ -156,6 +160,12 @@ impl<'a> HdrEncoder<'a> {
             writer.write_all(b"#?RADIANCE\n")?;
             writer.write_all(b"SOFTWARE=zune-hdr\n")?;
             if let Some(headers) = self.headers {
+                let mut headers: Vec<(&String, &String)> = headers.iter().collect();
+                // hashmap iteration order is random, sort so that
+                // the same headers always produce the same file
+                if self.options.deterministic() {
+                    headers.sort_unstable();
+                }
                 for (k, v) in headers {
                     writer.write_all(format!("{}={}\n", k, v).as_bytes())?;
                 }
@@ -346,3 +356,37 @@ fn frexp(s: f32) -> (f32, i32) {
         (signum(s) * x, exp as i32)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::EncoderOptions;
+
+    use crate::HdrEncoder;
+
+    #[test]
+    fn test_deterministic_headers_sorted() {
+        let data = vec![0.5_f32; 10 * 10 * 3];
+        let mut headers = HashMap::new();
+        for i in 0..20 {
+            headers.insert(format!("KEY{i:02}"), format!("{i}"));
+        }
+        let opts =
+            EncoderOptions::new(10, 10, ColorSpace::RGB, BitDepth::Float32).set_deterministic(true);
+
+        let mut encoder = HdrEncoder::new(&data, opts);
+        encoder.add_headers(&headers);
+        let mut out = vec![];
+        encoder.encode(&mut out).unwrap();
+
+        let text = String::from_utf8_lossy(&out);
+        let keys: Vec<&str> = text.lines().filter(|x| x.starts_with("KEY")).collect();
+        let mut sorted = keys.clone();
+        sorted.sort_unstable();
+        assert_eq!(keys.len(), 20);
+        assert_eq!(keys, sorted);
+    }
+}
@@ -13,6 +13,7 @@ use core::num::ParseIntError;
 
 use zune_core::bytestream::ZByteIoError;
 use zune_core::colorspace::ColorSpace;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
 
 /// HDR decoding errors
 pub enum HdrDecodeErrors {
@@ -128,3 +129,27 @@ impl From<ZByteIoError> for HdrEncodeErrors {
         HdrEncodeErrors::IoErrors(value)
     }
 }
+
+impl ZErrorCodeTrait for HdrDecodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::InvalidMagicBytes | Self::ParseError(_) | Self::Generic(_) => {
+                ZErrorCode::CorruptData
+            }
+            Self::UnsupportedOrientation(_, _) => ZErrorCode::Unsupported,
+            Self::TooLargeDimensions(_, _, _) => ZErrorCode::LimitsExceeded,
+            Self::TooSmallOutputArray(_, _) => ZErrorCode::InvalidInput,
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
+
+impl ZErrorCodeTrait for HdrEncodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::UnsupportedColorspace(_) => ZErrorCode::Unsupported,
+            Self::WrongInputSize(_, _) | Self::Static(_) => ZErrorCode::InvalidInput,
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
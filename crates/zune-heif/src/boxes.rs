@@ -0,0 +1,370 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Parsing of the boxes inside the `meta` box
+//!
+//! The `meta` box is small and read into memory whole, so these work
+//! on byte slices
+use alloc::vec::Vec;
+
+use crate::errors::HeifDecodeErrors;
+
+/// A four character code identifying a box or item type
+pub type FourCC = [u8; 4];
+
+/// A reader over an in memory box payload
+pub(crate) struct BoxReader<'a> {
+    data:     &'a [u8],
+    position: usize,
+    name:     &'static str
+}
+
+impl<'a> BoxReader<'a> {
+    pub fn new(data: &'a [u8], name: &'static str) -> BoxReader<'a> {
+        BoxReader {
+            data,
+            position: 0,
+            name
+        }
+    }
+
+    pub fn bytes(&mut self, length: usize) -> Result<&'a [u8], HeifDecodeErrors> {
+        let bytes = self
+            .data
+            .get(self.position..self.position.saturating_add(length))
+            .ok_or(HeifDecodeErrors::InvalidBox(self.name))?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, HeifDecodeErrors> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, HeifDecodeErrors> {
+        Ok(u16::from_be_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, HeifDecodeErrors> {
+        Ok(u32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    pub fn fourcc(&mut self) -> Result<FourCC, HeifDecodeErrors> {
+        Ok(self.bytes(4)?.try_into().unwrap())
+    }
+
+    /// Read an unsigned integer stored in `size` bytes, size is either 0,2,4 or 8
+    pub fn sized(&mut self, size: u8) -> Result<u64, HeifDecodeErrors> {
+        match size {
+            0 => Ok(0),
+            2 => Ok(u64::from(self.u16()?)),
+            4 => Ok(u64::from(self.u32()?)),
+            8 => Ok(u64::from_be_bytes(self.bytes(8)?.try_into().unwrap())),
+            _ => Err(HeifDecodeErrors::InvalidBox(self.name))
+        }
+    }
+
+    /// Read the version and flags of a full box
+    pub fn full_box(&mut self) -> Result<(u8, u32), HeifDecodeErrors> {
+        let value = self.u32()?;
+        Ok(((value >> 24) as u8, value & 0x00FF_FFFF))
+    }
+
+    /// Read a u16 for version 0 boxes or u32 for later versions
+    pub fn item_id(&mut self, version: u8) -> Result<u32, HeifDecodeErrors> {
+        if version == 0 {
+            Ok(u32::from(self.u16()?))
+        } else {
+            self.u32()
+        }
+    }
+
+    /// Return the next child box as its type and payload, or none
+    /// at the end of the data
+    pub fn next_box(&mut self) -> Result<Option<(FourCC, &'a [u8])>, HeifDecodeErrors> {
+        if self.position >= self.data.len() {
+            return Ok(None);
+        }
+        let start = self.position;
+        let size = self.u32()?;
+        let kind = self.fourcc()?;
+
+        let end = match size {
+            0 => self.data.len(),
+            1 => {
+                let size = self.sized(8)?;
+                usize::try_from(size)
+                    .ok()
+                    .and_then(|x| start.checked_add(x))
+                    .ok_or(HeifDecodeErrors::InvalidBox(self.name))?
+            }
+            size => start + size as usize
+        };
+        if end < self.position || end > self.data.len() {
+            return Err(HeifDecodeErrors::InvalidBox(self.name));
+        }
+        let payload = &self.data[self.position..end];
+        self.position = end;
+
+        Ok(Some((kind, payload)))
+    }
+}
+
+/// An entry of the `iinf` box
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct ItemInfo {
+    pub id:        u32,
+    pub item_type: FourCC
+}
+
+/// An entry of the `iloc` box
+#[derive(Clone, Debug)]
+pub(crate) struct ItemLocation {
+    pub id:                  u32,
+    /// 0 for data in the file, 1 for data in the `idat` box
+    pub construction_method: u8,
+    /// Offset and length of each extent, with the base offset added
+    pub extents:             Vec<(u64, u64)>
+}
+
+/// A property in the `ipco` box
+#[derive(Clone, Debug)]
+pub(crate) enum Property {
+    /// `hvcC`, the HEVC decoder configuration record
+    HevcConfig(Vec<u8>),
+    /// `ispe`, width and height of the image
+    Spatial(u32, u32),
+    /// `irot`, anti-clockwise rotation in units of 90 degrees
+    Rotation(u8),
+    /// `colr` with an ICC profile
+    IccProfile(Vec<u8>),
+    /// Properties we don't care about
+    Other
+}
+
+/// An entry of the `iref` box
+#[derive(Clone, Debug)]
+pub(crate) struct ItemReference {
+    pub kind: FourCC,
+    pub from: u32,
+    pub to:   Vec<u32>
+}
+
+/// The parsed contents of the `meta` box
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Meta {
+    pub primary_item: Option<u32>,
+    pub items:        Vec<ItemInfo>,
+    pub locations:    Vec<ItemLocation>,
+    pub properties:   Vec<Property>,
+    /// Item id and one based indices into properties
+    pub associations: Vec<(u32, Vec<usize>)>,
+    pub references:   Vec<ItemReference>,
+    pub idat:         Vec<u8>
+}
+
+impl Meta {
+    pub fn parse(data: &[u8]) -> Result<Meta, HeifDecodeErrors> {
+        let mut reader = BoxReader::new(data, "meta");
+        let mut meta = Meta::default();
+
+        reader.full_box()?;
+
+        while let Some((kind, payload)) = reader.next_box()? {
+            match &kind {
+                b"pitm" => {
+                    let mut reader = BoxReader::new(payload, "pitm");
+                    let (version, _) = reader.full_box()?;
+                    meta.primary_item = Some(reader.item_id(version)?);
+                }
+                b"iinf" => meta.parse_iinf(payload)?,
+                b"iloc" => meta.parse_iloc(payload)?,
+                b"iprp" => meta.parse_iprp(payload)?,
+                b"iref" => meta.parse_iref(payload)?,
+                b"idat" => meta.idat = payload.to_vec(),
+                _ => {}
+            }
+        }
+        Ok(meta)
+    }
+
+    fn parse_iinf(&mut self, data: &[u8]) -> Result<(), HeifDecodeErrors> {
+        let mut reader = BoxReader::new(data, "iinf");
+        let (version, _) = reader.full_box()?;
+        // entry count, the boxes that follow are what matter
+        reader.item_id(version)?;
+
+        while let Some((kind, payload)) = reader.next_box()? {
+            if &kind != b"infe" {
+                continue;
+            }
+            let mut reader = BoxReader::new(payload, "infe");
+            let (version, _) = reader.full_box()?;
+
+            // versions 0 and 1 have no item type and don't describe images
+            if version < 2 {
+                continue;
+            }
+            let id = if version == 2 { u32::from(reader.u16()?) } else { reader.u32()? };
+            // item protection index
+            reader.u16()?;
+            let item_type = reader.fourcc()?;
+
+            self.items.push(ItemInfo { id, item_type });
+        }
+        Ok(())
+    }
+
+    fn parse_iloc(&mut self, data: &[u8]) -> Result<(), HeifDecodeErrors> {
+        let mut reader = BoxReader::new(data, "iloc");
+        let (version, _) = reader.full_box()?;
+
+        if version > 2 {
+            return Err(HeifDecodeErrors::Unsupported(alloc::format!(
+                "iloc version {version}"
+            )));
+        }
+        let sizes = reader.u8()?;
+        let (offset_size, length_size) = (sizes >> 4, sizes & 15);
+        let sizes = reader.u8()?;
+        let base_offset_size = sizes >> 4;
+        let index_size = if version == 0 { 0 } else { sizes & 15 };
+
+        let count = if version < 2 { u32::from(reader.u16()?) } else { reader.u32()? };
+
+        for _ in 0..count {
+            let id = if version < 2 { u32::from(reader.u16()?) } else { reader.u32()? };
+            let construction_method = if version == 0 { 0 } else { (reader.u16()? & 15) as u8 };
+            // data reference index, only the same file is supported
+            reader.u16()?;
+            let base_offset = reader.sized(base_offset_size)?;
+            let extent_count = reader.u16()?;
+
+            let mut extents = Vec::with_capacity(usize::from(extent_count));
+
+            for _ in 0..extent_count {
+                reader.sized(index_size)?;
+                let offset = reader.sized(offset_size)?;
+                let length = reader.sized(length_size)?;
+
+                extents.push((base_offset.saturating_add(offset), length));
+            }
+            self.locations.push(ItemLocation {
+                id,
+                construction_method,
+                extents
+            });
+        }
+        Ok(())
+    }
+
+    fn parse_iprp(&mut self, data: &[u8]) -> Result<(), HeifDecodeErrors> {
+        let mut reader = BoxReader::new(data, "iprp");
+
+        while let Some((kind, payload)) = reader.next_box()? {
+            match &kind {
+                b"ipco" => {
+                    let mut reader = BoxReader::new(payload, "ipco");
+
+                    while let Some((kind, payload)) = reader.next_box()? {
+                        self.properties.push(parse_property(kind, payload)?);
+                    }
+                }
+                b"ipma" => {
+                    let mut reader = BoxReader::new(payload, "ipma");
+                    let (version, flags) = reader.full_box()?;
+                    let count = reader.u32()?;
+
+                    for _ in 0..count {
+                        let id = reader.item_id(version)?;
+                        let associations = reader.u8()?;
+                        let mut indices = Vec::with_capacity(usize::from(associations));
+
+                        for _ in 0..associations {
+                            // the top bit marks essential properties
+                            let index = if flags & 1 == 1 {
+                                usize::from(reader.u16()? & 0x7FFF)
+                            } else {
+                                usize::from(reader.u8()? & 0x7F)
+                            };
+                            indices.push(index);
+                        }
+                        self.associations.push((id, indices));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_iref(&mut self, data: &[u8]) -> Result<(), HeifDecodeErrors> {
+        let mut reader = BoxReader::new(data, "iref");
+        let (version, _) = reader.full_box()?;
+
+        while let Some((kind, payload)) = reader.next_box()? {
+            let mut reader = BoxReader::new(payload, "iref");
+            let from = reader.item_id(version)?;
+            let count = reader.u16()?;
+            let mut to = Vec::with_capacity(usize::from(count));
+
+            for _ in 0..count {
+                to.push(reader.item_id(version)?);
+            }
+            self.references.push(ItemReference { kind, from, to });
+        }
+        Ok(())
+    }
+
+    /// Return information about an item
+    pub fn item(&self, id: u32) -> Option<&ItemInfo> {
+        self.items.iter().find(|x| x.id == id)
+    }
+
+    /// Return the properties associated with an item
+    pub fn item_properties(&self, id: u32) -> impl Iterator<Item = &Property> {
+        self.associations
+            .iter()
+            .filter(move |x| x.0 == id)
+            .flat_map(|x| x.1.iter())
+            .filter_map(|index| index.checked_sub(1).and_then(|x| self.properties.get(x)))
+    }
+
+    /// Return the items an item references with a reference of type `kind`
+    pub fn references_from(&self, id: u32, kind: &FourCC) -> Option<&[u32]> {
+        self.references
+            .iter()
+            .find(|x| x.from == id && &x.kind == kind)
+            .map(|x| x.to.as_slice())
+    }
+}
+
+fn parse_property(kind: FourCC, payload: &[u8]) -> Result<Property, HeifDecodeErrors> {
+    let property = match &kind {
+        b"hvcC" => Property::HevcConfig(payload.to_vec()),
+        b"ispe" => {
+            let mut reader = BoxReader::new(payload, "ispe");
+            reader.full_box()?;
+            Property::Spatial(reader.u32()?, reader.u32()?)
+        }
+        b"irot" => {
+            let mut reader = BoxReader::new(payload, "irot");
+            Property::Rotation(reader.u8()? & 3)
+        }
+        b"colr" => {
+            let mut reader = BoxReader::new(payload, "colr");
+
+            match &reader.fourcc()? {
+                b"prof" | b"rICC" => Property::IccProfile(payload[4..].to_vec()),
+                _ => Property::Other
+            }
+        }
+        _ => Property::Other
+    };
+    Ok(property)
+}
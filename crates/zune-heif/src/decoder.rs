@@ -0,0 +1,636 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Parsing of the HEIF container
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use zune_core::bytestream::{ZByteReaderTrait, ZReader, ZSeekFrom};
+use zune_core::log::trace;
+use zune_core::options::DecoderOptions;
+
+use crate::boxes::{BoxReader, FourCC, Meta, Property};
+use crate::errors::HeifDecodeErrors;
+
+/// Brands whose files contain HEVC coded images
+const HEVC_BRANDS: [&[u8; 4]; 6] = [b"heic", b"heix", b"heim", b"heis", b"hevc", b"hevx"];
+/// Generic image brands, the compatible brands say which codec is used
+const GENERIC_BRANDS: [&[u8; 4]; 2] = [b"mif1", b"msf1"];
+/// The `meta` box holds every image property, anything bigger is most
+/// likely a corrupt file
+const MAX_META_SIZE: u64 = 1 << 24;
+/// Start code preceding each NAL unit in an Annex B byte stream
+const START_CODE: [u8; 4] = [0, 0, 0, 1];
+
+/// Probe some bytes to see if they consist of a HEIF file containing HEVC images
+///
+/// This needs the whole `ftyp` box, whose size is in the first four bytes
+pub fn probe_heif(bytes: &[u8]) -> bool {
+    if bytes.len() < 12 || &bytes[4..8] != b"ftyp" {
+        return false;
+    }
+    let size = u32::from_be_bytes(bytes[0..4].try_into().unwrap()) as usize;
+    let major_brand = &bytes[8..12];
+
+    if HEVC_BRANDS.iter().any(|x| x == &major_brand) {
+        return true;
+    }
+    if GENERIC_BRANDS.iter().any(|x| x == &major_brand) {
+        // compatible brands come after the minor version
+        let end = size.min(bytes.len());
+
+        return bytes
+            .get(16..end)
+            .unwrap_or(&[])
+            .chunks_exact(4)
+            .any(|brand| HEVC_BRANDS.iter().any(|x| x == &brand));
+    }
+    false
+}
+
+/// An HEVC coded image extracted from the container
+#[derive(Clone, Debug)]
+pub struct HevcPayload {
+    /// Width of the coded image, from the `ispe` property
+    pub width:         usize,
+    /// Height of the coded image, from the `ispe` property
+    pub height:        usize,
+    /// Bit depth of the luma samples
+    pub bit_depth:     u8,
+    /// Chroma format, 0 for monochrome, 1 for 4:2:0, 2 for 4:2:2 and 3 for 4:4:4
+    pub chroma_format: u8,
+    /// The image as an Annex B byte stream, i.e the parameter sets of the `hvcC`
+    /// property followed by the image data, each NAL unit preceded by a `00 00 00 01`
+    /// start code
+    pub bitstream:     Vec<u8>
+}
+
+/// Layout of a grid image, which is made up of equally
+/// sized tiles in raster order
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct HeifGrid {
+    /// Number of tile rows
+    pub rows:    usize,
+    /// Number of tile columns
+    pub columns: usize,
+    /// Width of the image, tiles extending past it are cropped
+    pub width:   usize,
+    /// Height of the image, tiles extending past it are cropped
+    pub height:  usize
+}
+
+/// A HEIF container parser
+///
+/// This reads the properties and metadata of the primary image and extracts
+/// its coded data, decoding the HEVC data is left to an external decoder, see [`HeifDecoder::hevc_payloads`].
+pub struct HeifDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    stream:          ZReader<T>,
+    options:         DecoderOptions,
+    decoded_headers: bool,
+    meta:            Meta,
+    file_size:       u64,
+    primary_item:    u32,
+    width:           usize,
+    height:          usize,
+    grid:            Option<HeifGrid>,
+    exif:            Option<Vec<u8>>
+}
+
+impl<T> HeifDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    /// Create a new HEIF decoder that reads data from `data`
+    ///
+    /// # Arguments
+    /// - `data`: The buffer from which we will read bytes from
+    pub fn new(data: T) -> HeifDecoder<T> {
+        HeifDecoder::new_with_options(data, DecoderOptions::default())
+    }
+    /// Create a new HEIF decoder with the specified options
+    ///
+    /// # Arguments
+    /// - `data`: The buffer from which we will read bytes from
+    /// - `options`: Specialized options for this decoder
+    pub fn new_with_options(data: T, options: DecoderOptions) -> HeifDecoder<T> {
+        HeifDecoder {
+            stream: ZReader::new(data),
+            options,
+            decoded_headers: false,
+            meta: Meta::default(),
+            file_size: 0,
+            primary_item: 0,
+            width: 0,
+            height: 0,
+            grid: None,
+            exif: None
+        }
+    }
+
+    /// Read the `ftyp` and `meta` boxes, and the exif and grid
+    /// descriptions of the primary image
+    pub fn decode_headers(&mut self) -> Result<(), HeifDecodeErrors> {
+        if self.decoded_headers {
+            return Ok(());
+        }
+        self.file_size = self.stream.seek(ZSeekFrom::End(0))?;
+        self.stream.set_position(0)?;
+
+        let ftyp_size = self.stream.peek_at(0, 4)?;
+        let ftyp_size = u32::from_be_bytes(ftyp_size.try_into().unwrap()) as usize;
+
+        if !probe_heif(self.stream.peek_at(0, ftyp_size.clamp(12, 256))?) {
+            return Err(HeifDecodeErrors::InvalidMagicBytes);
+        }
+        let mut position = 0;
+        let mut meta = None;
+
+        // top level boxes, we only need meta, everything else is referenced
+        // by offsets in it
+        while position + 8 <= self.file_size {
+            self.stream.set_position(position as usize)?;
+
+            let mut size = u64::from(self.stream.get_u32_be_err()?);
+            let kind = self.stream.read_fixed_bytes_or_error::<4>()?;
+            let mut header_size = 8;
+
+            if size == 1 {
+                size = self.stream.get_u64_be_err()?;
+                header_size = 16;
+            } else if size == 0 {
+                size = self.file_size - position;
+            }
+            let end = position.checked_add(size);
+
+            if size < header_size || end.is_none_or(|end| end > self.file_size) {
+                return Err(HeifDecodeErrors::InvalidBox("top level box"));
+            }
+            trace!("Box {:?}, size {}", core::str::from_utf8(&kind), size);
+
+            if &kind == b"meta" {
+                if size > MAX_META_SIZE {
+                    return Err(HeifDecodeErrors::Unsupported(format!(
+                        "meta box of {size} bytes"
+                    )));
+                }
+                let mut data = vec![0; (size - header_size) as usize];
+                self.stream.read_exact_bytes(&mut data)?;
+
+                meta = Some(Meta::parse(&data)?);
+                break;
+            }
+            position += size;
+        }
+        self.meta = meta.ok_or(HeifDecodeErrors::MissingBox("meta"))?;
+        self.primary_item = self
+            .meta
+            .primary_item
+            .ok_or(HeifDecodeErrors::MissingBox("pitm"))?;
+
+        let (width, height) = self
+            .meta
+            .item_properties(self.primary_item)
+            .find_map(|x| match x {
+                Property::Spatial(width, height) => Some((*width as usize, *height as usize)),
+                _ => None
+            })
+            .ok_or(HeifDecodeErrors::MissingBox("ispe"))?;
+
+        trace!("Image dimensions: ({},{})", width, height);
+
+        if width > self.options.max_width() {
+            return Err(HeifDecodeErrors::TooLargeDimensions(
+                "width",
+                self.options.max_width(),
+                width
+            ));
+        }
+        if height > self.options.max_height() {
+            return Err(HeifDecodeErrors::TooLargeDimensions(
+                "height",
+                self.options.max_height(),
+                height
+            ));
+        }
+        self.width = width;
+        self.height = height;
+
+        if self.codec() == Some(*b"grid") {
+            let data = self.item_data(self.primary_item)?;
+            self.grid = Some(parse_grid(&data)?);
+
+            trace!("Grid: {:?}", self.grid);
+        }
+        // exif describing the primary image, or any exif item if none
+        // references it
+        let exif_items: Vec<u32> = self
+            .meta
+            .items
+            .iter()
+            .filter(|x| &x.item_type == b"Exif")
+            .map(|x| x.id)
+            .collect();
+
+        let exif_item = exif_items
+            .iter()
+            .find(|id| {
+                self.meta
+                    .references_from(**id, b"cdsc")
+                    .is_some_and(|x| x.contains(&self.primary_item))
+            })
+            .or(exif_items.first());
+
+        if let Some(id) = exif_item.copied() {
+            let data = self.item_data(id)?;
+            // the payload starts with the offset of the tiff header
+            let offset = data
+                .get(0..4)
+                .map(|x| u32::from_be_bytes(x.try_into().unwrap()) as usize)
+                .ok_or(HeifDecodeErrors::InvalidBox("Exif"))?;
+
+            self.exif = data.get(4 + offset..).map(|x| x.to_vec());
+        }
+        self.decoded_headers = true;
+
+        Ok(())
+    }
+
+    /// Return the dimensions of the primary image or none if headers
+    /// haven't been decoded
+    pub fn dimensions(&self) -> Option<(usize, usize)> {
+        if !self.decoded_headers {
+            return None;
+        }
+        Some((self.width, self.height))
+    }
+
+    /// Return the item type of the primary image, e.g `hvc1` for HEVC images
+    /// and `grid` for images made up of tiles
+    pub fn codec(&self) -> Option<FourCC> {
+        self.meta.item(self.primary_item).map(|x| x.item_type)
+    }
+
+    /// Return the grid layout if the primary image is made up of tiles
+    pub fn grid(&self) -> Option<HeifGrid> {
+        self.grid
+    }
+
+    /// Return the exif metadata of the primary image, starting at the
+    /// TIFF header
+    pub fn exif(&self) -> Option<&[u8]> {
+        self.exif.as_deref()
+    }
+
+    /// Return the embedded ICC profile of the primary image
+    pub fn icc_profile(&self) -> Option<Vec<u8>> {
+        self.meta
+            .item_properties(self.primary_item)
+            .find_map(|x| match x {
+                Property::IccProfile(profile) => Some(profile.clone()),
+                _ => None
+            })
+    }
+
+    /// Return the anti-clockwise rotation, in degrees, that should be applied
+    /// to the decoded image for display, or none if there is no rotation
+    pub fn rotation(&self) -> Option<u16> {
+        self.meta
+            .item_properties(self.primary_item)
+            .find_map(|x| match x {
+                Property::Rotation(angle) if *angle != 0 => Some(u16::from(*angle) * 90),
+                _ => None
+            })
+    }
+
+    /// Return the luma bit depth and chroma format of the primary image, (or of its
+    /// first tile for grid images), see [`HevcPayload`] for their meanings
+    ///
+    /// This only reads the `hvcC` property and is cheaper than extracting the payloads
+    pub fn hevc_format(&self) -> Option<(u8, u8)> {
+        let id = if self.grid.is_some() {
+            *self
+                .meta
+                .references_from(self.primary_item, b"dimg")?
+                .first()?
+        } else {
+            self.primary_item
+        };
+        self.meta.item_properties(id).find_map(|x| match x {
+            Property::HevcConfig(config) if config.len() > 17 => {
+                Some(((config[17] & 7) + 8, config[16] & 3))
+            }
+            _ => None
+        })
+    }
+
+    /// Return the coded HEVC images making up the primary image
+    ///
+    /// This is a single image, or one image per tile in raster order
+    /// for grid images, see [`HeifDecoder::grid`]
+    pub fn hevc_payloads(&mut self) -> Result<Vec<HevcPayload>, HeifDecodeErrors> {
+        self.decode_headers()?;
+
+        let items = if self.grid.is_some() {
+            self.meta
+                .references_from(self.primary_item, b"dimg")
+                .ok_or(HeifDecodeErrors::MissingBox("dimg"))?
+                .to_vec()
+        } else {
+            vec![self.primary_item]
+        };
+        if let Some(grid) = self.grid {
+            if items.len() != grid.rows * grid.columns {
+                return Err(HeifDecodeErrors::Generic(format!(
+                    "Grid of {}x{} tiles references {} images",
+                    grid.columns,
+                    grid.rows,
+                    items.len()
+                )));
+            }
+        }
+        items.into_iter().map(|id| self.hevc_payload(id)).collect()
+    }
+
+    fn hevc_payload(&mut self, id: u32) -> Result<HevcPayload, HeifDecodeErrors> {
+        let item_type = self.meta.item(id).map(|x| x.item_type);
+
+        if item_type != Some(*b"hvc1") {
+            return Err(HeifDecodeErrors::Unsupported(format!(
+                "Image item of type {:?}, only hvc1 is supported",
+                item_type.map(|x| core::str::from_utf8(&x).map(alloc::string::String::from))
+            )));
+        }
+        let mut config = None;
+        let mut dimensions = None;
+
+        for property in self.meta.item_properties(id) {
+            match property {
+                Property::HevcConfig(data) => config = Some(data.clone()),
+                Property::Spatial(width, height) => {
+                    dimensions = Some((*width as usize, *height as usize));
+                }
+                _ => {}
+            }
+        }
+        let config = config.ok_or(HeifDecodeErrors::MissingBox("hvcC"))?;
+        let (width, height) = dimensions.ok_or(HeifDecodeErrors::MissingBox("ispe"))?;
+
+        let mut reader = BoxReader::new(&config, "hvcC");
+        let header = reader.bytes(23)?;
+
+        let chroma_format = header[16] & 3;
+        let bit_depth = (header[17] & 7) + 8;
+        let length_size = usize::from(header[21] & 3) + 1;
+        let arrays = header[22];
+
+        let mut bitstream = vec![];
+
+        for _ in 0..arrays {
+            // array completeness and NAL unit type
+            reader.u8()?;
+            let count = reader.u16()?;
+
+            for _ in 0..count {
+                let length = usize::from(reader.u16()?);
+                bitstream.extend_from_slice(&START_CODE);
+                bitstream.extend_from_slice(reader.bytes(length)?);
+            }
+        }
+        let data = self.item_data(id)?;
+        let mut reader = BoxReader::new(&data, "hvc1");
+
+        while let Ok(length) = reader.bytes(length_size) {
+            let length = length
+                .iter()
+                .fold(0_usize, |acc, x| (acc << 8) | usize::from(*x));
+
+            bitstream.extend_from_slice(&START_CODE);
+            bitstream.extend_from_slice(reader.bytes(length)?);
+        }
+
+        Ok(HevcPayload {
+            width,
+            height,
+            bit_depth,
+            chroma_format,
+            bitstream
+        })
+    }
+
+    /// Read the data of an item, concatenating all its extents
+    fn item_data(&mut self, id: u32) -> Result<Vec<u8>, HeifDecodeErrors> {
+        let location = self
+            .meta
+            .locations
+            .iter()
+            .find(|x| x.id == id)
+            .ok_or(HeifDecodeErrors::MissingBox("iloc"))?
+            .clone();
+
+        // extents may overlap, so bound what they add up to rather than
+        // each of them, an item can't have more data than the file
+        let total = location
+            .extents
+            .iter()
+            .try_fold(0_u64, |total, (_, length)| total.checked_add(*length));
+
+        if total.is_none_or(|total| total > self.file_size) {
+            return Err(HeifDecodeErrors::Generic(format!(
+                "Item {id} has more data than the file"
+            )));
+        }
+        let mut data = vec![];
+
+        for (offset, length) in location.extents {
+            match location.construction_method {
+                0 => {
+                    if length == 0 || offset.saturating_add(length) > self.file_size {
+                        return Err(HeifDecodeErrors::Generic(format!(
+                            "Item {id} has an extent outside of the file"
+                        )));
+                    }
+                    let start = data.len();
+                    data.resize(start + length as usize, 0);
+
+                    self.stream.set_position(offset as usize)?;
+                    self.stream.read_exact_bytes(&mut data[start..])?;
+                }
+                1 => {
+                    let extent = usize::try_from(offset)
+                        .ok()
+                        .zip(usize::try_from(length).ok())
+                        .and_then(|(offset, length)| {
+                            self.meta.idat.get(offset..offset.checked_add(length)?)
+                        })
+                        .ok_or(HeifDecodeErrors::InvalidBox("idat"))?;
+
+                    data.extend_from_slice(extent);
+                }
+                method => {
+                    return Err(HeifDecodeErrors::Unsupported(format!(
+                        "Item construction method {method}"
+                    )));
+                }
+            }
+        }
+        Ok(data)
+    }
+}
+
+/// Parse the data of a `grid` item
+fn parse_grid(data: &[u8]) -> Result<HeifGrid, HeifDecodeErrors> {
+    let mut reader = BoxReader::new(data, "grid");
+    let _version = reader.u8()?;
+    let flags = reader.u8()?;
+    let rows = usize::from(reader.u8()?) + 1;
+    let columns = usize::from(reader.u8()?) + 1;
+    let size = if flags & 1 == 1 { 4 } else { 2 };
+
+    Ok(HeifGrid {
+        rows,
+        columns,
+        width: reader.sized(size)? as usize,
+        height: reader.sized(size)? as usize
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use zune_core::bytestream::ZCursor;
+
+    use crate::{probe_heif, HeifDecoder};
+
+    fn boxed(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&(payload.len() as u32 + 8).to_be_bytes());
+        data.extend_from_slice(kind);
+        data.extend_from_slice(payload);
+        data
+    }
+
+    fn full_boxed(kind: &[u8; 4], version: u8, payload: &[u8]) -> Vec<u8> {
+        let mut data = alloc::vec![version, 0, 0, 0];
+        data.extend_from_slice(payload);
+        boxed(kind, &data)
+    }
+
+    /// Create a file with an hvc1 image item and an exif item, both
+    /// stored in an mdat box placed after the meta box
+    fn create_heif(exif: &[u8], coded: &[u8]) -> Vec<u8> {
+        let ftyp = boxed(b"ftyp", b"mif1\0\0\0\0mif1heic");
+
+        let mut iinf = alloc::vec![0, 2];
+        iinf.extend(full_boxed(b"infe", 2, b"\0\x01\0\0hvc1"));
+        iinf.extend(full_boxed(b"infe", 2, b"\0\x02\0\0Exif"));
+
+        let mut hvcc = alloc::vec![0_u8; 23];
+        hvcc[0] = 1;
+        hvcc[16] = 0xFD; // 4:2:0
+        hvcc[17] = 0xF8; // 8 bit
+        hvcc[21] = 0x0F; // 4 byte lengths
+        hvcc[22] = 1;
+        // one array with a single VPS
+        hvcc.extend_from_slice(&[0x20, 0, 1, 0, 2, 0x40, 0x01]);
+
+        let mut ipco = boxed(b"hvcC", &hvcc);
+        ipco.extend(full_boxed(b"ispe", 0, &[0, 0, 0, 64, 0, 0, 0, 48]));
+        ipco.extend(boxed(b"irot", &[1]));
+
+        let mut iprp = boxed(b"ipco", &ipco);
+        iprp.extend(full_boxed(
+            b"ipma",
+            0,
+            &[0, 0, 0, 1, 0, 1, 3, 0x81, 0x02, 0x83]
+        ));
+
+        let iref = full_boxed(b"iref", 0, &boxed(b"cdsc", &[0, 2, 0, 1, 0, 1]));
+
+        let mut exif_payload = alloc::vec![0, 0, 0, 6];
+        exif_payload.extend_from_slice(b"Exif\0\0");
+        exif_payload.extend_from_slice(exif);
+
+        // lengths are known, so compute where mdat will start
+        let build = |mdat_start: u32| {
+            let coded_offset = mdat_start + 8;
+            let exif_offset = coded_offset + coded.len() as u32;
+
+            let mut iloc = alloc::vec![0x44, 0x00, 0, 2];
+            for (id, offset, length) in [
+                (1_u16, coded_offset, coded.len() as u32),
+                (2, exif_offset, exif_payload.len() as u32)
+            ] {
+                iloc.extend_from_slice(&id.to_be_bytes());
+                iloc.extend_from_slice(&[0, 0, 0, 1]);
+                iloc.extend_from_slice(&offset.to_be_bytes());
+                iloc.extend_from_slice(&length.to_be_bytes());
+            }
+            let mut meta = full_boxed(b"hdlr", 0, b"\0\0\0\0pict\0\0\0\0\0\0\0\0\0\0\0\0\0");
+            meta.extend(full_boxed(b"pitm", 0, &[0, 1]));
+            meta.extend(full_boxed(b"iinf", 0, &iinf));
+            meta.extend(full_boxed(b"iloc", 0, &iloc));
+            meta.extend(boxed(b"iprp", &iprp));
+            meta.extend(iref.clone());
+
+            full_boxed(b"meta", 0, &meta)
+        };
+        let meta_size = build(0).len() as u32;
+        let meta = build(ftyp.len() as u32 + meta_size);
+
+        let mut mdat = coded.to_vec();
+        mdat.extend_from_slice(&exif_payload);
+
+        let mut file = ftyp;
+        file.extend(meta);
+        file.extend(boxed(b"mdat", &mdat));
+        file
+    }
+
+    #[test]
+    fn test_heif_container() {
+        let coded = [0, 0, 0, 3, 0x26, 0x01, 0xAF, 0, 0, 0, 1, 0x02];
+        let data = create_heif(b"MM\0*", &coded);
+
+        assert!(probe_heif(&data));
+
+        let mut decoder = HeifDecoder::new(ZCursor::new(&data));
+        decoder.decode_headers().unwrap();
+
+        assert_eq!(decoder.dimensions(), Some((64, 48)));
+        assert_eq!(decoder.codec(), Some(*b"hvc1"));
+        assert_eq!(decoder.rotation(), Some(90));
+        assert_eq!(decoder.exif(), Some(&b"MM\0*"[..]));
+        assert_eq!(decoder.grid(), None);
+        assert_eq!(decoder.hevc_format(), Some((8, 1)));
+
+        let payloads = decoder.hevc_payloads().unwrap();
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(payloads[0].bit_depth, 8);
+        assert_eq!(payloads[0].chroma_format, 1);
+        assert_eq!(
+            payloads[0].bitstream,
+            [0, 0, 0, 1, 0x40, 0x01, 0, 0, 0, 1, 0x26, 0x01, 0xAF, 0, 0, 0, 1, 0x02]
+        );
+    }
+
+    #[test]
+    fn test_box_size_overflow() {
+        let mut data = boxed(b"ftyp", b"mif1\0\0\0\0mif1heic");
+        // a 64 bit box size that wraps around when added to the position
+        data.extend_from_slice(&1_u32.to_be_bytes());
+        data.extend_from_slice(b"free");
+        data.extend_from_slice(&u64::MAX.to_be_bytes());
+
+        let mut decoder = HeifDecoder::new(ZCursor::new(&data));
+        assert!(decoder.decode_headers().is_err());
+    }
+}
@@ -0,0 +1,92 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use alloc::string::String;
+use core::fmt::{Debug, Formatter};
+
+use zune_core::bytestream::ZByteIoError;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
+
+/// HEIF errors that can occur during decoding
+#[non_exhaustive]
+pub enum HeifDecodeErrors {
+    /// The file does not start with an `ftyp` box
+    /// with a HEIF brand
+    InvalidMagicBytes,
+    /// A box required to decode the image is missing
+    MissingBox(&'static str),
+    /// A box is too short for its contents
+    InvalidBox(&'static str),
+    /// The image uses a feature the decoder doesn't support
+    Unsupported(String),
+    /// Too large dimensions for a given width or
+    /// height
+    TooLargeDimensions(&'static str, usize, usize),
+    /// Generic message
+    GenericStatic(&'static str),
+    /// Generic allocated message
+    Generic(String),
+    IoErrors(ZByteIoError)
+}
+
+impl Debug for HeifDecodeErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidMagicBytes => {
+                writeln!(
+                    f,
+                    "Invalid magic bytes, file does not start with a HEIF ftyp box"
+                )
+            }
+            Self::MissingBox(name) => {
+                writeln!(f, "Missing required box {name}")
+            }
+            Self::InvalidBox(name) => {
+                writeln!(f, "Invalid or truncated box {name}")
+            }
+            Self::Unsupported(message) => {
+                writeln!(f, "Unsupported: {message}")
+            }
+            Self::TooLargeDimensions(dimension, expected, found) => {
+                writeln!(
+                    f,
+                    "Too large dimensions for {dimension} , {found} exceeds {expected}"
+                )
+            }
+            Self::GenericStatic(message) => {
+                writeln!(f, "{message}")
+            }
+            Self::Generic(message) => {
+                writeln!(f, "{message}")
+            }
+            Self::IoErrors(err) => {
+                writeln!(f, "{:?}", err)
+            }
+        }
+    }
+}
+
+impl From<ZByteIoError> for HeifDecodeErrors {
+    fn from(value: ZByteIoError) -> Self {
+        HeifDecodeErrors::IoErrors(value)
+    }
+}
+
+impl ZErrorCodeTrait for HeifDecodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::InvalidMagicBytes | Self::MissingBox(_) | Self::InvalidBox(_) => {
+                ZErrorCode::CorruptData
+            }
+            Self::GenericStatic(_) | Self::Generic(_) => ZErrorCode::CorruptData,
+            Self::Unsupported(_) => ZErrorCode::Unsupported,
+            Self::TooLargeDimensions(_, _, _) => ZErrorCode::LimitsExceeded,
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
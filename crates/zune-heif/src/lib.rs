@@ -0,0 +1,47 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! A HEIF/HEIC container parser
+//!
+//! This crate parses the ISO base media file format boxes of a HEIF file
+//! and extracts the properties, metadata and coded data of the primary image, it doesn't
+//! contain an HEVC decoder.
+//!
+//! The coded data is returned as an Annex B byte stream, see [`HevcPayload`], which can be
+//! passed to any HEVC decoder, grid images are returned as one stream per tile.
+//!
+//! # Supported metadata
+//! - Dimensions, rotation and ICC profile of the primary image
+//! - EXIF metadata
+//!
+//! # Features
+//!  - `log`: Use the `log` crate features to print image information when decoding
+//!  - `std`: Allow direct decoding from anything that implements `std::io::BufRead` + `std::io::Seek`
+//!
+//! # Usage
+//! ```no_run
+//! use zune_core::bytestream::ZCursor;
+//! use zune_heif::HeifDecoder;
+//!
+//! let mut decoder = HeifDecoder::new(ZCursor::new(&[]));
+//! decoder.decode_headers().unwrap();
+//! let (width, height) = decoder.dimensions().unwrap();
+//! let payloads = decoder.hevc_payloads().unwrap();
+//! ```
+#![no_std]
+#![macro_use]
+extern crate alloc;
+
+pub use zune_core;
+
+pub use crate::boxes::FourCC;
+pub use crate::decoder::{probe_heif, HeifDecoder, HeifGrid, HevcPayload};
+pub use crate::errors::HeifDecodeErrors;
+
+mod boxes;
+mod decoder;
+mod errors;
@@ -39,21 +39,33 @@ use zune_core::log::trace;
 use zune_core::options::{DecoderOptions, EncoderOptions};
 
 use crate::codecs;
+use crate::core_filters::orientation::AutoOrient;
 use crate::errors::ImgEncodeErrors::ImageEncodeErrors;
 use crate::errors::{ImageErrors, ImgEncodeErrors};
 use crate::image::Image;
-use crate::traits::{DecoderTrait, EncoderTrait};
+use crate::traits::{DecoderTrait, EncoderTrait, OperationsTrait};
 
 pub mod bmp;
-mod exr;
+pub mod dicom;
+pub mod dng;
+pub mod exr;
 pub mod farbfeld;
+pub mod gif;
 pub mod hdr;
+pub mod heif;
+pub mod ico;
 pub mod jpeg;
+pub mod jpeg2000;
 pub mod jpeg_xl;
+pub mod pcx;
+pub mod pdf;
 pub mod png;
 pub mod ppm;
 pub mod psd;
 pub mod qoi;
+pub mod svg;
+pub mod tga;
+pub mod tiff;
 pub(crate) fn create_options_for_encoder(
     options: Option<EncoderOptions>, image: &Image
 ) -> EncoderOptions {
@@ -90,12 +102,38 @@ pub enum ImageFormat {
     Farbfeld,
     /// Quite Okay Image
     QOI,
+    /// Multiple Quite Okay Image frames stored in a simple
+    /// container, see `zune_qoi::QoiSequenceEncoder`
+    QoiSequence,
     /// JPEG XL, new format
     JPEG_XL,
     /// Radiance HDR decoder
     HDR,
     /// Windows Bitmap Files
     BMP,
+    /// Digital Imaging and Communications in Medicine
+    DICOM,
+    /// OpenEXR, high dynamic range format used in VFX
+    EXR,
+    /// Adobe Digital Negative, camera raw format
+    DNG,
+    /// Scalable Vector Graphics, rasterized on decode
+    SVG,
+    /// Windows icons and cursors
+    ICO,
+    /// Truevision TARGA
+    TGA,
+    /// ZSoft Paintbrush, common in old archives
+    PCX,
+    /// JPEG 2000, both JP2 files and raw J2K code streams
+    JPEG2000,
+    /// High Efficiency Image File Format, HEVC coded images
+    /// in an ISO base media container, e.g. iPhone photos
+    HEIF,
+    /// Graphics Interchange Format, including animated images
+    GIF,
+    /// Tagged Image File Format, including multi-page documents
+    TIFF,
     /// Any unknown format
     Unknown
 }
@@ -204,6 +242,18 @@ impl ImageFormat {
                     Err(ImageErrors::ImageDecoderNotIncluded(*self))
                 }
             }
+            ImageFormat::QoiSequence => {
+                #[cfg(feature = "qoi")]
+                {
+                    Ok(Box::new(zune_qoi::QoiSequenceDecoder::new_with_options(
+                        data, options
+                    )))
+                }
+                #[cfg(not(feature = "qoi"))]
+                {
+                    Err(ImageErrors::ImageDecoderNotIncluded(*self))
+                }
+            }
             ImageFormat::HDR => {
                 #[cfg(feature = "hdr")]
                 {
@@ -228,6 +278,130 @@ impl ImageFormat {
                     Err(ImageErrors::ImageDecoderNotIncluded(*self))
                 }
             }
+            ImageFormat::DICOM => {
+                #[cfg(feature = "dicom")]
+                {
+                    Ok(Box::new(zune_dicom::DicomDecoder::new_with_options(
+                        data, options
+                    )))
+                }
+                #[cfg(not(feature = "dicom"))]
+                {
+                    Err(ImageErrors::ImageDecoderNotIncluded(*self))
+                }
+            }
+            ImageFormat::DNG => {
+                #[cfg(feature = "dng")]
+                {
+                    Ok(Box::new(zune_dng::DngDecoder::new_with_options(
+                        data, options
+                    )))
+                }
+                #[cfg(not(feature = "dng"))]
+                {
+                    Err(ImageErrors::ImageDecoderNotIncluded(*self))
+                }
+            }
+            ImageFormat::EXR => {
+                #[cfg(feature = "exr")]
+                {
+                    Ok(Box::new(codecs::exr::ExrDecoder::try_new(data, options)?))
+                }
+                #[cfg(not(feature = "exr"))]
+                {
+                    Err(ImageErrors::ImageDecoderNotIncluded(*self))
+                }
+            }
+            ImageFormat::JPEG2000 => {
+                #[cfg(feature = "jpeg2000")]
+                {
+                    Ok(Box::new(codecs::jpeg2000::Jpeg2000Decoder::try_new(
+                        data, options
+                    )?))
+                }
+                #[cfg(not(feature = "jpeg2000"))]
+                {
+                    Err(ImageErrors::ImageDecoderNotIncluded(*self))
+                }
+            }
+            ImageFormat::HEIF => {
+                #[cfg(feature = "heif")]
+                {
+                    Ok(Box::new(codecs::heif::HeifDecoder::new_with_options(
+                        data, options
+                    )))
+                }
+                #[cfg(not(feature = "heif"))]
+                {
+                    Err(ImageErrors::ImageDecoderNotIncluded(*self))
+                }
+            }
+            ImageFormat::GIF => {
+                #[cfg(feature = "gif")]
+                {
+                    Ok(Box::new(zune_gif::GifDecoder::new_with_options(
+                        data, options
+                    )))
+                }
+                #[cfg(not(feature = "gif"))]
+                {
+                    Err(ImageErrors::ImageDecoderNotIncluded(*self))
+                }
+            }
+            ImageFormat::TIFF => {
+                #[cfg(feature = "tiff")]
+                {
+                    Ok(Box::new(zune_tiff::TiffDecoder::new_with_options(
+                        data, options
+                    )))
+                }
+                #[cfg(not(feature = "tiff"))]
+                {
+                    Err(ImageErrors::ImageDecoderNotIncluded(*self))
+                }
+            }
+            ImageFormat::ICO => {
+                #[cfg(feature = "ico")]
+                {
+                    Ok(Box::new(codecs::ico::IcoDecoder::try_new(data, options)?))
+                }
+                #[cfg(not(feature = "ico"))]
+                {
+                    Err(ImageErrors::ImageDecoderNotIncluded(*self))
+                }
+            }
+            ImageFormat::TGA => {
+                #[cfg(feature = "tga")]
+                {
+                    Ok(Box::new(zune_tga::TgaDecoder::new_with_options(
+                        data, options
+                    )))
+                }
+                #[cfg(not(feature = "tga"))]
+                {
+                    Err(ImageErrors::ImageDecoderNotIncluded(*self))
+                }
+            }
+            ImageFormat::PCX => {
+                #[cfg(feature = "legacy-formats")]
+                {
+                    Ok(Box::new(codecs::pcx::PcxDecoder::try_new(data, options)?))
+                }
+                #[cfg(not(feature = "legacy-formats"))]
+                {
+                    Err(ImageErrors::ImageDecoderNotIncluded(*self))
+                }
+            }
+            ImageFormat::SVG => {
+                #[cfg(feature = "svg")]
+                {
+                    Ok(Box::new(codecs::svg::SvgDecoder::try_new(data, options)?))
+                }
+                #[cfg(not(feature = "svg"))]
+                {
+                    Err(ImageErrors::ImageDecoderNotIncluded(*self))
+                }
+            }
             ImageFormat::JPEG_XL => {
                 #[cfg(feature = "jpeg-xl")]
                 {
@@ -257,12 +431,85 @@ impl ImageFormat {
             ImageFormat::PNG => cfg!(feature = "png"),
             ImageFormat::PPM => cfg!(feature = "ppm"),
             ImageFormat::Farbfeld => cfg!(feature = "farbfeld"),
-            ImageFormat::QOI => cfg!(feature = "qoi"),
+            ImageFormat::QOI | ImageFormat::QoiSequence => cfg!(feature = "qoi"),
             ImageFormat::JPEG_XL => cfg!(feature = "jpeg-xl"),
             ImageFormat::HDR => cfg!(feature = "hdr"),
+            ImageFormat::ICO => cfg!(feature = "ico"),
+            ImageFormat::TGA => cfg!(feature = "tga"),
             _ => false
         }
     }
+    /// Return true if the encoder for this format can store all frames
+    /// of an animated image.
+    ///
+    /// Formats which return false will only encode the first frame of an
+    /// animated image, see [`EncoderTrait::supports_animated_images`]
+    pub fn has_animated_encoder(&self) -> bool {
+        match self {
+            ImageFormat::JPEG => {
+                #[cfg(feature = "jpeg")]
+                {
+                    return codecs::jpeg::JpegEncoder::new().supports_animated_images();
+                }
+            }
+            ImageFormat::PNG => {
+                #[cfg(feature = "png")]
+                {
+                    return codecs::png::PngEncoder::new().supports_animated_images();
+                }
+            }
+            ImageFormat::PPM => {
+                #[cfg(feature = "ppm")]
+                {
+                    return codecs::ppm::PPMEncoder::new().supports_animated_images();
+                }
+            }
+            ImageFormat::Farbfeld => {
+                #[cfg(feature = "farbfeld")]
+                {
+                    return codecs::farbfeld::FarbFeldEncoder::new().supports_animated_images();
+                }
+            }
+            ImageFormat::QOI => {
+                #[cfg(feature = "qoi")]
+                {
+                    return codecs::qoi::QoiEncoder::new().supports_animated_images();
+                }
+            }
+            ImageFormat::QoiSequence => {
+                #[cfg(feature = "qoi")]
+                {
+                    return codecs::qoi::QoiSequenceEncoder::new().supports_animated_images();
+                }
+            }
+            ImageFormat::JPEG_XL => {
+                #[cfg(feature = "jpeg-xl")]
+                {
+                    return codecs::jpeg_xl::JxlEncoder::new().supports_animated_images();
+                }
+            }
+            ImageFormat::HDR => {
+                #[cfg(feature = "hdr")]
+                {
+                    return codecs::hdr::HdrEncoder::new().supports_animated_images();
+                }
+            }
+            ImageFormat::ICO => {
+                #[cfg(feature = "ico")]
+                {
+                    return codecs::ico::IcoEncoder::new().supports_animated_images();
+                }
+            }
+            ImageFormat::TGA => {
+                #[cfg(feature = "tga")]
+                {
+                    return codecs::tga::TgaEncoder::new().supports_animated_images();
+                }
+            }
+            _ => {}
+        }
+        false
+    }
     pub fn encode<T: ZByteWriterTrait>(
         &self, image: &Image, encoder_options: EncoderOptions, sink: T
     ) -> Result<usize, ImageErrors> {
@@ -303,6 +550,14 @@ impl ImageFormat {
                     return encoder.encode(image, sink);
                 }
             }
+            ImageFormat::QoiSequence => {
+                #[cfg(feature = "qoi")]
+                {
+                    let mut encoder =
+                        codecs::qoi::QoiSequenceEncoder::new_with_options(encoder_options);
+                    return encoder.encode(image, sink);
+                }
+            }
             ImageFormat::JPEG_XL => {
                 #[cfg(feature = "jpeg-xl")]
                 {
@@ -318,6 +573,20 @@ impl ImageFormat {
                     return encoder.encode(image, sink);
                 }
             }
+            ImageFormat::ICO => {
+                #[cfg(feature = "ico")]
+                {
+                    let mut encoder = codecs::ico::IcoEncoder::new_with_options(encoder_options);
+                    return encoder.encode(image, sink);
+                }
+            }
+            ImageFormat::TGA => {
+                #[cfg(feature = "tga")]
+                {
+                    let mut encoder = codecs::tga::TgaEncoder::new_with_options(encoder_options);
+                    return encoder.encode(image, sink);
+                }
+            }
             _ => {}
         }
         Err(ImageErrors::EncodeErrors(
@@ -344,6 +613,16 @@ impl ImageFormat {
                     None
                 }
             }
+            "qois" => {
+                #[cfg(feature = "qoi")]
+                {
+                    Some(ImageFormat::QoiSequence)
+                }
+                #[cfg(not(feature = "qoi"))]
+                {
+                    None
+                }
+            }
             "ppm" | "pam" | "pgm" | "pbm" | "pfm" => {
                 #[cfg(feature = "ppm")]
                 {
@@ -404,6 +683,26 @@ impl ImageFormat {
                     None
                 }
             }
+            "ico" => {
+                #[cfg(feature = "ico")]
+                {
+                    Some(ImageFormat::ICO)
+                }
+                #[cfg(not(feature = "ico"))]
+                {
+                    None
+                }
+            }
+            "tga" => {
+                #[cfg(feature = "tga")]
+                {
+                    Some(ImageFormat::TGA)
+                }
+                #[cfg(not(feature = "tga"))]
+                {
+                    None
+                }
+            }
             _ => None
         }
     }
@@ -636,11 +935,94 @@ impl Image {
     {
         let decoder = ImageFormat::guess_format(src);
 
+        if let Some((format, src)) = decoder {
+            Image::read_with_format(src, format, options)
+        } else {
+            Err(ImageErrors::ImageDecoderNotImplemented(
+                ImageFormat::Unknown
+            ))
+        }
+    }
+
+    /// Decode an image whose format is already known
+    ///
+    /// This skips format detection, which is useful when the format comes from
+    /// elsewhere, e.g. a content type or a command line flag
+    ///
+    /// # Arguments
+    ///  - `src`: The encoded image
+    ///  - `format`: The format of the encoded image
+    ///  - `options`: The configured decoder options
+    ///
+    /// # Example
+    ///```
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_core::options::DecoderOptions;
+    /// use zune_image::codecs::ImageFormat;
+    /// use zune_image::image::Image;
+    ///
+    /// let image = Image::read_with_format(ZCursor::new(b"P5 1 1 255 1"), ImageFormat::PPM, DecoderOptions::default());
+    ///```
+    pub fn read_with_format<T>(
+        src: T, format: ImageFormat, options: DecoderOptions
+    ) -> Result<Image, ImageErrors>
+    where
+        T: ZByteReaderTrait
+    {
+        let mut image_decoder = format.decoder_with_options(src, options)?;
+        // save format
+        let mut image = image_decoder.decode()?;
+        image.metadata.format = Some(format);
+
+        if options.auto_orient() {
+            AutoOrient.execute(&mut image)?;
+        }
+        Ok(image)
+    }
+
+    /// Decode a single frame of an image from a source, counting from zero
+    ///
+    /// For animated formats, only the frames up to and including `n` are decoded,
+    /// which is faster and uses less memory than decoding the whole
+    /// animation when e.g. only a thumbnail is needed.
+    /// The frame is composited onto earlier frames, honoring their disposal methods,
+    /// so it looks the same as the frame returned by [read](Self::read).
+    ///
+    /// Images that aren't animated have a single frame, `0`.
+    ///
+    /// # Arguments
+    ///  - `src`: The encoded image
+    ///  - `options`: The configured decoder options
+    ///  - `n`: The frame to decode
+    ///
+    /// # Errors
+    ///  - [`ImageErrors::FrameOutOfRange`] if the image has `n` or fewer frames
+    ///
+    /// # Example
+    /// - Read the third frame of an animation
+    ///
+    ///```no_run
+    /// use zune_core::options::DecoderOptions;
+    /// use zune_image::image::Image;
+    ///
+    /// let reader = std::io::BufReader::new(std::fs::File::open("animation.gif").unwrap());
+    /// let image = Image::read_frame(reader, DecoderOptions::default(), 2).unwrap();
+    ///```
+    pub fn read_frame<T>(src: T, options: DecoderOptions, n: usize) -> Result<Image, ImageErrors>
+    where
+        T: ZByteReaderTrait
+    {
+        let decoder = ImageFormat::guess_format(src);
+
         if let Some(format) = decoder {
             let mut image_decoder = format.0.decoder_with_options(format.1, options)?;
-            // save format
-            let mut image = image_decoder.decode()?;
+
+            let mut image = image_decoder.decode_frame(n)?;
             image.metadata.format = Some(format.0);
+
+            if options.auto_orient() {
+                AutoOrient.execute(&mut image)?;
+            }
             Ok(image)
         } else {
             Err(ImageErrors::ImageDecoderNotImplemented(
@@ -722,6 +1104,10 @@ where
         // the best identifier would be 0xFF,0xd8 0xff but nop, some images exist
         // which do not have that
         (&[0xff, 0xd8], ImageFormat::JPEG),
+        (b"P1", ImageFormat::PPM),
+        (b"P2", ImageFormat::PPM),
+        (b"P3", ImageFormat::PPM),
+        (b"P4", ImageFormat::PPM),
         (b"P5", ImageFormat::PPM),
         (b"P6", ImageFormat::PPM),
         (b"P7", ImageFormat::PPM),
@@ -730,8 +1116,12 @@ where
         (b"8BPS", ImageFormat::PSD),
         (b"farbfeld", ImageFormat::Farbfeld),
         (b"qoif", ImageFormat::QOI),
+        (b"qois", ImageFormat::QoiSequence),
+        (b"GIF87a", ImageFormat::GIF),
+        (b"GIF89a", ImageFormat::GIF),
         (b"#?RADIANCE\n", ImageFormat::HDR),
         (b"#?RGBE\n", ImageFormat::HDR),
+        (&[0x76, 0x2f, 0x31, 0x01], ImageFormat::EXR),
         (
             &[
                 0x00, 0x00, 0x00, 0x0C, 0x4A, 0x58, 0x4C, 0x20, 0x0D, 0x0A, 0x87, 0x0A
@@ -739,6 +1129,13 @@ where
             ImageFormat::JPEG_XL
         ),
         (&[0xFF, 0x0A], ImageFormat::JPEG_XL),
+        (
+            &[
+                0x00, 0x00, 0x00, 0x0C, 0x6A, 0x50, 0x20, 0x20, 0x0D, 0x0A, 0x87, 0x0A
+            ],
+            ImageFormat::JPEG2000
+        ),
+        (&[0xFF, 0x4F, 0xFF, 0x51], ImageFormat::JPEG2000),
     ];
 
     for (magic, decoder) in magic_bytes {
@@ -758,6 +1155,148 @@ where
             return Some((ImageFormat::BMP, reader.consume()));
         }
     }
+    #[cfg(feature = "ico")]
+    {
+        // the ico header is mostly zeroes, so also look at the first directory entry
+        if let Ok(reference) = reader.peek_at(0, 22) {
+            if codecs::ico::probe_ico(reference) {
+                return Some((ImageFormat::ICO, reader.consume()));
+            }
+        }
+    }
+    #[cfg(feature = "dicom")]
+    {
+        // dicom magic is after a 128 byte preamble
+        if let Ok(reference) = reader.peek_at(0, zune_dicom::DICOM_PREAMBLE_SIZE + 4) {
+            if zune_dicom::probe_dicom(reference) {
+                return Some((ImageFormat::DICOM, reader.consume()));
+            }
+        }
+    }
+    #[cfg(feature = "dng")]
+    {
+        // DNG is a TIFF with a DNGVersion tag in the first IFD, which can be
+        // anywhere in the file, so peek until we have the whole IFD
+        let mut size = 8;
+
+        while let Ok(reference) = reader.peek_at(0, size) {
+            match zune_dng::probe_dng_size(reference) {
+                Some(needed) if needed > size && needed < (1 << 20) => size = needed,
+                Some(needed) if needed <= size => {
+                    if zune_dng::probe_dng(reference) {
+                        return Some((ImageFormat::DNG, reader.consume()));
+                    }
+                    break;
+                }
+                _ => break
+            }
+        }
+    }
+    #[cfg(feature = "tiff")]
+    {
+        // checked after DNG, which is also a TIFF
+        if let Ok(reference) = reader.peek_at(0, 4) {
+            if zune_tiff::probe_tiff(reference) {
+                return Some((ImageFormat::TIFF, reader.consume()));
+            }
+        }
+    }
+    #[cfg(feature = "heif")]
+    {
+        // the brands are in the ftyp box, whose size is in the first four bytes
+        if let Ok(reference) = reader.peek_at(0, 4) {
+            let size = u32::from_be_bytes(reference.try_into().unwrap()) as usize;
+
+            if let Ok(reference) = reader.peek_at(0, size.clamp(12, 256)) {
+                if zune_heif::probe_heif(reference) {
+                    return Some((ImageFormat::HEIF, reader.consume()));
+                }
+            }
+        }
+    }
+    #[cfg(feature = "svg")]
+    {
+        // SVG is text, the root element may come after an xml declaration,
+        // comments and a doctype so look at as much of the start as we can get
+        for size in [1024, 256, 64, 16] {
+            if let Ok(reference) = reader.peek_at(0, size) {
+                if codecs::svg::probe_svg(reference) {
+                    return Some((ImageFormat::SVG, reader.consume()));
+                }
+                break;
+            }
+        }
+    }
+    #[cfg(feature = "legacy-formats")]
+    {
+        // PCX only has a single magic byte, so the rest of the header is validated too
+        if let Ok(reference) = reader.peek_at(0, codecs::pcx::PCX_HEADER_SIZE) {
+            if codecs::pcx::probe_pcx(reference) {
+                return Some((ImageFormat::PCX, reader.consume()));
+            }
+        }
+    }
+    #[cfg(feature = "tga")]
+    {
+        // TGA has no magic bytes, the header is only checked for valid
+        // values, so it goes last to not shadow other formats
+        if let Ok(reference) = reader.peek_at(0, zune_tga::TGA_HEADER_SIZE) {
+            if zune_tga::probe_tga(reference) {
+                return Some((ImageFormat::TGA, reader.consume()));
+            }
+        }
+    }
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::EncoderOptions;
+
+    use crate::codecs::ImageFormat;
+    use crate::image::Image;
+
+    #[test]
+    fn test_deterministic_encoders() {
+        let create_image = |width, height| {
+            Image::from_fn::<u8, _>(width, height, ColorSpace::RGBA, |x, y, px| {
+                px[0] = (x * 3) as u8;
+                px[1] = (y * 5) as u8;
+                px[2] = (x ^ y) as u8;
+                px[3] = 255 - (y as u8);
+            })
+        };
+        // large enough for the jpeg-xl encoder to split it into groups encoded in parallel,
+        // icons are at most 256 pixels wide
+        let (large, small) = (create_image(300, 280), create_image(60, 40));
+        let formats = [
+            ImageFormat::JPEG,
+            ImageFormat::PNG,
+            ImageFormat::PPM,
+            ImageFormat::Farbfeld,
+            ImageFormat::QOI,
+            ImageFormat::JPEG_XL,
+            ImageFormat::HDR,
+            ImageFormat::ICO,
+            ImageFormat::TGA
+        ];
+        for format in formats.into_iter().filter(ImageFormat::has_encoder) {
+            let image = if format == ImageFormat::ICO { &small } else { &large };
+
+            let encode = |threads| {
+                let options = EncoderOptions::default()
+                    .set_deterministic(true)
+                    .set_num_threads(threads);
+                let mut sink = vec![];
+                format.encode(image, options, &mut sink).unwrap();
+                sink
+            };
+            let first = encode(1);
+
+            assert!(first == encode(1), "{format:?} output changed between runs");
+            assert!(first == encode(4), "{format:?} output depends on the thread count");
+        }
+    }
+}
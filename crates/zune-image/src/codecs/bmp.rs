@@ -11,10 +11,18 @@
 //! BMP decoding support
 //!
 //! Decoding is done by the delegate library [zune-bmp](zune_bmp)
+//!
+//! Files with embedded JPEG or PNG images are decoded by the respective
+//! decoders, this requires the `jpeg` or `png` features
+//!
+//! Embedded ICC profiles of V5 files are stored in the image metadata, with the
+//! `color-management` feature calibrated RGB images are also converted to sRGB
 
 pub use zune_bmp::*;
-use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::bytestream::{ZByteReaderTrait, ZCursor};
 use zune_core::colorspace::ColorSpace;
+use zune_core::errors::ZErrorCodeTrait;
+use zune_core::warnings::DecodeWarning;
 
 use crate::codecs::ImageFormat;
 use crate::errors::ImageErrors;
@@ -27,17 +35,32 @@ where
     T: ZByteReaderTrait
 {
     fn decode(&mut self) -> Result<Image, ImageErrors> {
+        if let Some(mut decoder) = embedded_decoder(self)? {
+            let mut image = decoder.decode()?;
+            image.metadata.format = Some(ImageFormat::BMP);
+
+            return Ok(image);
+        }
         let pixels = self.decode()?;
+        let metadata = self.read_headers()?.unwrap();
         let (width, height) = self.dimensions().unwrap();
         let colorspace = self.colorspace().unwrap();
 
-        Ok(Image::from_u8(&pixels, width, height, colorspace))
+        let mut image = Image::from_u8(&pixels, width, height, colorspace);
+        // keeps the icc profile
+        image.metadata = metadata;
+
+        Ok(image)
     }
 
     fn dimensions(&self) -> Option<(usize, usize)> {
         self.dimensions()
     }
 
+    fn warnings(&self) -> &[DecodeWarning] {
+        self.warnings()
+    }
+
     fn out_colorspace(&self) -> ColorSpace {
         self.colorspace().unwrap()
     }
@@ -49,6 +72,19 @@ where
     fn read_headers(&mut self) -> Result<Option<ImageMetadata>, ImageErrors> {
         self.decode_headers()?;
 
+        if let Some(mut decoder) = embedded_decoder(self)? {
+            let mut metadata = decoder.read_headers()?.unwrap_or_default();
+            metadata.format = Some(ImageFormat::BMP);
+
+            if metadata.icc_chunk.is_none() {
+                metadata.icc_chunk = self.icc_profile().cloned();
+            }
+            if metadata.dpi.is_none() {
+                metadata.dpi = self.dpi();
+            }
+            return Ok(Some(metadata));
+        }
+
         let (width, height) = self.dimensions().unwrap();
         let depth = self.depth();
 
@@ -59,6 +95,7 @@ where
             width: width,
             height: height,
             icc_chunk: self.icc_profile().cloned(),
+            dpi: self.dpi(),
             ..Default::default()
         };
 
@@ -66,9 +103,37 @@ where
     }
 }
 
+/// Create a decoder for the JPEG or PNG file embedded in a BMP file,
+/// or return `None` if the file stores pixels
+fn embedded_decoder<T: ZByteReaderTrait>(
+    decoder: &mut BmpDecoder<T>
+) -> Result<Option<Box<dyn DecoderTrait>>, ImageErrors> {
+    let Some(data) = decoder.embedded_image()? else {
+        return Ok(None);
+    };
+    let format = match decoder.embedded_format().unwrap() {
+        BmpEmbeddedFormat::JPEG => ImageFormat::JPEG,
+        BmpEmbeddedFormat::PNG => ImageFormat::PNG
+    };
+    let mut embedded = format.decoder(ZCursor::new(data))?;
+    embedded.read_headers()?;
+
+    // the bmp header dimensions were checked against the decoder options,
+    // so ensure the embedded image doesn't bypass them
+    if embedded.dimensions() != decoder.dimensions() {
+        return Err(ImageErrors::ImageDecodeErrors(format!(
+            "bmp: Embedded {:?} dimensions {:?} do not match header dimensions {:?}",
+            format,
+            embedded.dimensions(),
+            decoder.dimensions()
+        )));
+    }
+    Ok(Some(embedded))
+}
+
 impl From<BmpDecoderErrors> for ImageErrors {
     fn from(value: BmpDecoderErrors) -> Self {
-        Self::ImageDecodeErrors(format!("bmp: {:?}", value))
+        Self::CodecErrors(value.code(), format!("bmp: {:?}", value))
     }
 }
 
@@ -93,3 +158,89 @@ where
         Ok(self.output_buf_size().unwrap())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::codecs::ImageFormat;
+    use crate::image::Image;
+
+    #[test]
+    fn test_bmp_v5_alpha_mask_and_icc() {
+        let profile = b"fake icc profile";
+        // the alpha mask is only honored for BI_BITFIELDS, uncompressed images
+        // are opaque even though V5 headers always carry masks
+        for (compression, alpha) in [(0, [255, 255]), (3, [128, 0])] {
+            let mut data = b"BM".to_vec();
+            data.extend_from_slice(&(14 + 124 + 8 + profile.len() as u32).to_le_bytes());
+            data.extend_from_slice(&[0; 4]);
+            data.extend_from_slice(&138_u32.to_le_bytes());
+
+            let mut header = [0_u8; 124];
+            let mut put = |offset: usize, value: u32| {
+                header[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+            };
+            put(0, 124);
+            put(4, 2);
+            put(8, 1);
+            // one plane and 32 bits per pixel
+            put(12, 1 | (32 << 16));
+            put(16, compression);
+            // BGRA masks
+            put(40, 0x00FF_0000);
+            put(44, 0x0000_FF00);
+            put(48, 0x0000_00FF);
+            put(52, 0xFF00_0000);
+            put(56, u32::from_be_bytes(*b"MBED"));
+            // profile offset from the start of this header and size
+            put(112, 124 + 8);
+            put(116, profile.len() as u32);
+            data.extend_from_slice(&header);
+
+            data.extend_from_slice(&[1, 2, 3, 128, 4, 5, 6, 0]);
+            data.extend_from_slice(profile);
+
+            let decoded = Image::read(ZCursor::new(&data), DecoderOptions::default()).unwrap();
+
+            assert_eq!(decoded.colorspace(), ColorSpace::RGBA);
+            assert_eq!(
+                decoded.flatten_to_u8()[0],
+                [3, 2, 1, alpha[0], 6, 5, 4, alpha[1]]
+            );
+            assert_eq!(
+                decoded.metadata().icc_chunk().map(|x| x.as_slice()),
+                Some(&profile[..])
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    fn test_bmp_embedded_png() {
+        let pixels: Vec<u8> = (0..7 * 5 * 3).map(|x| (x * 7) as u8).collect();
+        let image = Image::from_u8(&pixels, 7, 5, ColorSpace::RGB);
+        let png = image.write_to_vec(ImageFormat::PNG).unwrap();
+
+        let mut data = b"BM".to_vec();
+        data.extend_from_slice(&(54 + png.len() as u32).to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&54_u32.to_le_bytes());
+        // BITMAPINFOHEADER, zero bits per pixel and BI_PNG compression
+        data.extend_from_slice(&40_u32.to_le_bytes());
+        data.extend_from_slice(&7_i32.to_le_bytes());
+        data.extend_from_slice(&5_i32.to_le_bytes());
+        data.extend_from_slice(&[1, 0, 0, 0]);
+        data.extend_from_slice(&5_u32.to_le_bytes());
+        data.extend_from_slice(&(png.len() as u32).to_le_bytes());
+        data.extend_from_slice(&[0; 16]);
+        data.extend_from_slice(&png);
+
+        let decoded = Image::read(ZCursor::new(&data), DecoderOptions::default()).unwrap();
+
+        assert_eq!(decoded.metadata().image_format(), Some(ImageFormat::BMP));
+        assert_eq!(decoded.flatten_to_u8(), image.flatten_to_u8());
+    }
+}
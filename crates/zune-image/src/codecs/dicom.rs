@@ -0,0 +1,98 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+#![cfg_attr(feature = "docs", doc(cfg(feature = "dicom")))]
+#![cfg(feature = "dicom")]
+
+//! DICOM decoding support
+//!
+//! Decoding is done by the delegate library [zune-dicom](zune_dicom)
+//!
+//! Multi-frame files are decoded into an image with multiple frames, and
+//! the window center/width is exposed via [`ImageMetadata::window_level`]
+
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::colorspace::ColorSpace;
+use zune_core::errors::ZErrorCodeTrait;
+use zune_core::result::DecodingResult;
+pub use zune_dicom::*;
+
+use crate::codecs::ImageFormat;
+use crate::errors::ImageErrors;
+use crate::frame::Frame;
+use crate::image::Image;
+use crate::metadata::ImageMetadata;
+use crate::traits::DecoderTrait;
+
+impl<T> DecoderTrait for DicomDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let pixels = self.decode()?;
+
+        let metadata = self.read_headers()?.unwrap();
+        let (width, height) = (metadata.width, metadata.height);
+        let colorspace = metadata.colorspace;
+
+        let frame_size = width * height * colorspace.num_components();
+
+        let frames: Vec<Frame> = match pixels {
+            DecodingResult::U8(data) => data
+                .chunks_exact(frame_size)
+                .map(|x| Frame::from_u8(x, colorspace, 0, 0))
+                .collect(),
+            DecodingResult::U16(data) => data
+                .chunks_exact(frame_size)
+                .map(|x| Frame::from_u16(x, colorspace, 0, 0))
+                .collect(),
+            _ => unreachable!()
+        };
+        let mut image = Image::new_frames(frames, metadata.depth, width, height, colorspace);
+        image.metadata = metadata;
+
+        Ok(image)
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        self.dimensions()
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        self.colorspace().unwrap_or(ColorSpace::Unknown)
+    }
+
+    fn name(&self) -> &'static str {
+        "DICOM Decoder"
+    }
+
+    fn read_headers(&mut self) -> Result<Option<ImageMetadata>, ImageErrors> {
+        self.decode_headers()?;
+
+        let (width, height) = self.dimensions().unwrap();
+
+        let mut metadata = ImageMetadata {
+            format: Some(ImageFormat::DICOM),
+            colorspace: self.colorspace().unwrap(),
+            depth: self.depth().unwrap(),
+            width: width,
+            height: height,
+            ..Default::default()
+        };
+        if let Some((center, width)) = self.window() {
+            metadata.set_window_level(center, width);
+        }
+
+        Ok(Some(metadata))
+    }
+}
+
+impl From<DicomDecodeErrors> for ImageErrors {
+    fn from(value: DicomDecodeErrors) -> Self {
+        Self::CodecErrors(value.code(), format!("dicom: {:?}", value))
+    }
+}
@@ -0,0 +1,95 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+#![cfg_attr(feature = "docs", doc(cfg(feature = "dng")))]
+#![cfg(feature = "dng")]
+
+//! DNG (camera raw) decoding support
+//!
+//! Decoding is done by the delegate library [zune-dng](zune_dng)
+//!
+//! Images are returned as linear `f32` RGB in the camera native color space,
+//! the color matrices needed to go further are exposed via [`ImageMetadata::camera_color_info`]
+
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::colorspace::{ColorCharacteristics, ColorSpace};
+use zune_core::errors::ZErrorCodeTrait;
+pub use zune_dng::*;
+
+use crate::codecs::ImageFormat;
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::metadata::{CameraColorInfo, ImageMetadata};
+use crate::traits::DecoderTrait;
+
+impl<T> DecoderTrait for DngDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let pixels = self.decode()?;
+
+        let metadata = self.read_headers()?.unwrap();
+        let (width, height) = (metadata.width, metadata.height);
+
+        let mut image = Image::from_f32(&pixels, width, height, ColorSpace::RGB);
+        image.metadata = metadata;
+
+        Ok(image)
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        self.dimensions()
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        self.colorspace().unwrap_or(ColorSpace::Unknown)
+    }
+
+    fn name(&self) -> &'static str {
+        "DNG Decoder"
+    }
+
+    fn read_headers(&mut self) -> Result<Option<ImageMetadata>, ImageErrors> {
+        self.decode_headers()?;
+
+        let (width, height) = self.dimensions().unwrap();
+
+        let mut metadata = ImageMetadata {
+            format: Some(ImageFormat::DNG),
+            colorspace: ColorSpace::RGB,
+            depth: BitDepth::Float32,
+            width: width,
+            height: height,
+            color_trc: Some(ColorCharacteristics::Linear),
+            orientation: self.orientation(),
+            ..Default::default()
+        };
+        if let Some(info) = self.color_info() {
+            metadata.set_camera_color_info(CameraColorInfo {
+                color_matrix_1:           info.color_matrix_1,
+                color_matrix_2:           info.color_matrix_2,
+                forward_matrix_1:         info.forward_matrix_1,
+                forward_matrix_2:         info.forward_matrix_2,
+                camera_calibration_1:     info.camera_calibration_1,
+                camera_calibration_2:     info.camera_calibration_2,
+                as_shot_neutral:          info.as_shot_neutral,
+                calibration_illuminant_1: info.calibration_illuminant_1,
+                calibration_illuminant_2: info.calibration_illuminant_2
+            });
+        }
+
+        Ok(Some(metadata))
+    }
+}
+
+impl From<DngDecodeErrors> for ImageErrors {
+    fn from(value: DngDecodeErrors) -> Self {
+        Self::CodecErrors(value.code(), format!("dng: {:?}", value))
+    }
+}
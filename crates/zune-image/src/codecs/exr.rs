@@ -1,70 +1,292 @@
-// #![cfg(feature = "exr")]
-//
-// use std::io::Cursor;
-//
-// use exr::prelude::{f16, ReadChannels, ReadLayers};
-// use zune_core::colorspace::ColorSpace;
-//
-// use crate::errors::ImageErrors;
-// use crate::image::Image;
-// use crate::traits::DecoderTrait;
-//
-// pub struct ExrDecoder<'a>
-// {
-//     data: &'a [u8]
-// }
-//
-// impl<'a> ExrDecoder<'a>
-// {
-//     fn a(&self)
-//     {
-//         let reader = exr::prelude::read()
-//             .no_deep_data()
-//             .largest_resolution_level()
-//             .rgba_channels(
-//                 // create our image based on the resolution of the file
-//                 |resolution, (r, g, b, a)| {
-//                     let mut output: Vec<f32> = vec![];
-//
-//                     output.resize(resolution.x() * resolution.y() * 4, 0.0);
-//                     output
-//                 },
-//                 // insert a single pixel into out image
-//                 |output, position, (r, g, b, a): (f32, f32, f32, f32)| {
-//                     let first = (position.y() * position.width() + position.x()) * 4;
-//
-//                     output[first] = r;
-//                     output[first + 1] = g;
-//                     output[first + 2] = b;
-//                     output[first + 3] = a;
-//                 }
-//             )
-//             .first_valid_layer()
-//             .all_attributes()
-//             .from_buffered(Cursor::new(self.data))
-//             .unwrap();
-//     }
-// }
-//
-// impl<'a> DecoderTrait<'a> for ExrDecoder<'a>
-// {
-//     fn decode(&mut self) -> Result<Image, ImageErrors>
-//     {
-//         todo!()
-//     }
-//
-//     fn get_dimensions(&self) -> Option<(usize, usize)>
-//     {
-//         todo!()
-//     }
-//
-//     fn get_out_colorspace(&self) -> ColorSpace
-//     {
-//         todo!()
-//     }
-//
-//     fn get_name(&self) -> &'static str
-//     {
-//         "exr"
-//     }
-// }
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+#![cfg_attr(feature = "docs", doc(cfg(feature = "exr")))]
+#![cfg(feature = "exr")]
+//! OpenEXR decoding support
+//!
+//! This uses the delegate library [`exr`](::exr) for decoding images.
+//!
+//! The first layer of the file is decoded, `f16`, `f32` and `u32` samples
+//! are all converted to `f32`. Channels are mapped by name, `R`,`G`,`B` and optionally `A`
+//! become RGB(A), `Y` and optionally `A` become Luma(A) and a file with a single channel
+//! of any name (e.g. a depth `Z` channel) becomes Luma.
+//!
+//! Images are returned in linear light as stored in the file
+use std::io::Cursor;
+use std::mem::size_of;
+
+pub use ::exr;
+use ::exr::meta::header::Header;
+use ::exr::meta::MetaData;
+use ::exr::prelude::{FlatSamples, ReadChannels, ReadLayers};
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::colorspace::{ColorCharacteristics, ColorSpace};
+use zune_core::errors::ZErrorCode;
+use zune_core::log::trace;
+use zune_core::options::DecoderOptions;
+
+use crate::channel::Channel;
+use crate::codecs::ImageFormat;
+use crate::errors::ImageErrors;
+use crate::frame::Frame;
+use crate::image::Image;
+use crate::metadata::ImageMetadata;
+use crate::traits::DecoderTrait;
+
+/// An OpenEXR decoder
+///
+/// The whole file is read into memory on creation, headers are parsed
+/// lazily on the first call to `read_headers` or `decode`
+pub struct ExrDecoder {
+    data:       Vec<u8>,
+    options:    DecoderOptions,
+    /// Parsed header information, (width, height, colorspace and channel indices
+    /// in header order making up the colorspace)
+    image_info: Option<(usize, usize, ColorSpace, Vec<usize>)>
+}
+
+impl ExrDecoder {
+    pub fn try_new<T: ZByteReaderTrait>(
+        mut source: T, options: DecoderOptions
+    ) -> Result<ExrDecoder, ImageErrors> {
+        let mut data = vec![];
+        source
+            .read_remaining(&mut data)
+            .map_err(|x| ImageErrors::ImageDecodeErrors(format!("exr: {:?}", x)))?;
+
+        Ok(ExrDecoder {
+            data,
+            options,
+            image_info: None
+        })
+    }
+
+    fn decode_headers(&mut self) -> Result<(), ImageErrors> {
+        if self.image_info.is_some() {
+            return Ok(());
+        }
+        let metadata = MetaData::read_from_buffered(Cursor::new(&self.data), false)?;
+
+        let header = metadata
+            .headers
+            .first()
+            .ok_or(ImageErrors::ImageDecodeErrors(
+                "exr: No headers in file".to_string()
+            ))?;
+
+        let (width, height) = (header.layer_size.x(), header.layer_size.y());
+
+        trace!("Image dimensions: ({},{})", width, height);
+        trace!("Compression: {:?}", header.compression);
+
+        if width > self.options.max_width() {
+            let msg = format!(
+                "Image width {}, greater than max set width {}",
+                width,
+                self.options.max_width()
+            );
+            return Err(ImageErrors::CodecErrors(ZErrorCode::LimitsExceeded, msg));
+        }
+        if height > self.options.max_height() {
+            let msg = format!(
+                "Image height {}, greater than max set height {}",
+                height,
+                self.options.max_height()
+            );
+            return Err(ImageErrors::CodecErrors(ZErrorCode::LimitsExceeded, msg));
+        }
+        let (colorspace, indices) = channel_layout(header)?;
+
+        trace!("Image colorspace: {:?}", colorspace);
+
+        self.image_info = Some((width, height, colorspace, indices));
+
+        Ok(())
+    }
+}
+
+/// Map channel names of a header into a colorspace, returning the colorspace and
+/// the index of each colorspace component in the header's channel list
+fn channel_layout(header: &Header) -> Result<(ColorSpace, Vec<usize>), ImageErrors> {
+    let channels = &header.channels.list;
+
+    if let Some(channel) = channels.iter().find(|x| x.sampling.area() != 1) {
+        return Err(ImageErrors::CodecErrors(
+            ZErrorCode::Unsupported,
+            format!("exr: Subsampled channel {} is not supported", channel.name)
+        ));
+    }
+    let find = |name: &str| channels.iter().position(|x| x.name.eq(name));
+
+    let layout = match (find("R"), find("G"), find("B"), find("Y"), find("A")) {
+        (Some(r), Some(g), Some(b), _, Some(a)) => (ColorSpace::RGBA, vec![r, g, b, a]),
+        (Some(r), Some(g), Some(b), _, None) => (ColorSpace::RGB, vec![r, g, b]),
+        (_, _, _, Some(y), Some(a)) => (ColorSpace::LumaA, vec![y, a]),
+        (_, _, _, Some(y), None) => (ColorSpace::Luma, vec![y]),
+        _ if channels.len() == 1 => (ColorSpace::Luma, vec![0]),
+        _ => {
+            let names: Vec<String> = channels.iter().map(|x| x.name.to_string()).collect();
+
+            return Err(ImageErrors::CodecErrors(
+                ZErrorCode::Unsupported,
+                format!("exr: Cannot map channels {:?} to a colorspace", names)
+            ));
+        }
+    };
+    Ok(layout)
+}
+
+impl DecoderTrait for ExrDecoder {
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let metadata = self.read_headers()?;
+        let (width, height, colorspace, indices) = self.image_info.clone().unwrap();
+
+        let reader = ::exr::prelude::read()
+            .no_deep_data()
+            .largest_resolution_level()
+            .all_channels()
+            .first_valid_layer()
+            .all_attributes();
+
+        #[cfg(not(feature = "threads"))]
+        let reader = reader.non_parallel();
+
+        let exr_image = reader.from_buffered(Cursor::new(&self.data))?;
+        let layer = exr_image.layer_data;
+
+        let mut channels = Vec::with_capacity(indices.len());
+
+        for index in indices {
+            let mut channel =
+                Channel::new_with_bit_type(width * height * size_of::<f32>(), BitType::F32);
+            let output = channel.reinterpret_as_mut::<f32>()?;
+
+            match &layer.channel_data.list[index].sample_data {
+                FlatSamples::F16(samples) => output
+                    .iter_mut()
+                    .zip(samples)
+                    .for_each(|(out, sample)| *out = sample.to_f32()),
+                FlatSamples::F32(samples) => output.copy_from_slice(samples),
+                FlatSamples::U32(samples) => output
+                    .iter_mut()
+                    .zip(samples)
+                    .for_each(|(out, sample)| *out = *sample as f32)
+            }
+            channels.push(channel);
+        }
+        let frame = Frame::new(channels);
+
+        let mut image =
+            Image::new_frames(vec![frame], BitDepth::Float32, width, height, colorspace);
+
+        if let Some(metadata) = metadata {
+            image.metadata = metadata;
+        }
+        Ok(image)
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        self.image_info.as_ref().map(|x| (x.0, x.1))
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        self.image_info
+            .as_ref()
+            .map(|x| x.2)
+            .unwrap_or(ColorSpace::Unknown)
+    }
+
+    fn name(&self) -> &'static str {
+        "exr-decoder (johannesvollmer)"
+    }
+
+    fn read_headers(&mut self) -> Result<Option<ImageMetadata>, ImageErrors> {
+        self.decode_headers()?;
+
+        let (width, height, colorspace, _) = self.image_info.clone().unwrap();
+
+        let metadata = ImageMetadata {
+            format: Some(ImageFormat::EXR),
+            colorspace: colorspace,
+            depth: BitDepth::Float32,
+            width: width,
+            height: height,
+            color_trc: Some(ColorCharacteristics::Linear),
+            ..Default::default()
+        };
+
+        Ok(Some(metadata))
+    }
+}
+
+impl From<::exr::error::Error> for ImageErrors {
+    fn from(value: ::exr::error::Error) -> Self {
+        let code = match value {
+            ::exr::error::Error::Aborted => ZErrorCode::Internal,
+            ::exr::error::Error::NotSupported(_) => ZErrorCode::Unsupported,
+            ::exr::error::Error::Invalid(_) => ZErrorCode::CorruptData,
+            ::exr::error::Error::Io(_) => ZErrorCode::Io
+        };
+        Self::CodecErrors(code, format!("exr: {}", value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::exr::prelude::*;
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::codecs::ImageFormat;
+    use crate::image::Image;
+
+    fn create_exr(compression: Compression) -> Vec<u8> {
+        let encoding = Encoding {
+            compression,
+            blocks: Blocks::ScanLines,
+            line_order: LineOrder::Increasing
+        };
+        let channels = SpecificChannels::rgba(|position: Vec2<usize>| {
+            (
+                f16::from_f32(position.x() as f32 * 0.5),
+                f16::from_f32(position.y() as f32),
+                f16::from_f32(2.0),
+                1.0_f32
+            )
+        });
+        let image = exr::prelude::Image::from_encoded_channels((40, 20), encoding, channels);
+
+        let mut output = std::io::Cursor::new(vec![]);
+        image.write().to_buffered(&mut output).unwrap();
+        output.into_inner()
+    }
+
+    #[test]
+    fn test_exr_decode_zip_and_piz() {
+        for compression in [Compression::ZIP16, Compression::PIZ] {
+            let data = create_exr(compression);
+
+            assert_eq!(
+                ImageFormat::guess_format(ZCursor::new(&data)).map(|x| x.0),
+                Some(ImageFormat::EXR)
+            );
+            let image = Image::read(ZCursor::new(&data), Default::default()).unwrap();
+
+            assert_eq!(image.dimensions(), (40, 20));
+            assert_eq!(image.colorspace(), ColorSpace::RGBA);
+            assert_eq!(image.depth(), BitDepth::Float32);
+
+            let pixels = &image.flatten_frames::<f32>()[0];
+            // pixel (x=3,y=2)
+            let start = (2 * 40 + 3) * 4;
+            assert_eq!(&pixels[start..start + 4], &[1.5, 2.0, 2.0, 1.0]);
+        }
+    }
+}
@@ -14,6 +14,7 @@
 use zune_core::bit_depth::BitDepth;
 use zune_core::bytestream::{ZByteReaderTrait, ZByteWriterTrait};
 use zune_core::colorspace::ColorSpace;
+use zune_core::errors::ZErrorCodeTrait;
 use zune_core::options::EncoderOptions;
 pub use zune_farbfeld::*;
 
@@ -77,7 +78,7 @@ where
 
 impl From<FarbFeldErrors> for ImageErrors {
     fn from(value: FarbFeldErrors) -> Self {
-        Self::ImageDecodeErrors(format!("ff: {value:?}"))
+        Self::CodecErrors(value.code(), format!("ff: {value:?}"))
     }
 }
 
@@ -152,7 +153,7 @@ impl EncoderTrait for FarbFeldEncoder {
 
 impl From<FarbFeldEncoderErrors> for ImgEncodeErrors {
     fn from(value: FarbFeldEncoderErrors) -> Self {
-        ImgEncodeErrors::ImageEncodeErrors(format!("ff: {:?}", value))
+        ImgEncodeErrors::CodecErrors(value.code(), format!("ff: {:?}", value))
     }
 }
 
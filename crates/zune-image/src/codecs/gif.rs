@@ -0,0 +1,229 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+#![cfg_attr(feature = "docs", doc(cfg(feature = "gif")))]
+#![cfg(feature = "gif")]
+//! GIF decoding support
+//!
+//! This uses the delegate library [`zune-gif`](zune_gif)
+//! for decoding images.
+//!
+//! Every frame of an animated image becomes a frame of the resulting image,
+//! already composited onto the previous ones, with the frame delay stored in
+//! hundredths of a second. The loop count is available via [`ImageMetadata::loop_count`]
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::colorspace::ColorSpace;
+use zune_core::errors::ZErrorCodeTrait;
+pub use zune_gif::*;
+
+use crate::codecs::ImageFormat;
+use crate::errors::ImageErrors;
+use crate::frame::Frame;
+use crate::image::Image;
+use crate::metadata::ImageMetadata;
+use crate::traits::DecoderTrait;
+
+impl<T> DecoderTrait for GifDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let metadata = self.read_headers()?.unwrap();
+
+        // frames are composited, so the canvas carries over between them
+        let mut canvas = vec![0; self.output_buf_size().unwrap()];
+        let mut frames = Vec::new();
+
+        while let Some(info) = self.frame_info() {
+            self.decode_into(&mut canvas)?;
+
+            frames.push(Frame::from_u8(
+                &canvas,
+                ColorSpace::RGBA,
+                usize::from(info.delay),
+                100
+            ));
+        }
+        if frames.is_empty() {
+            return Err(ImageErrors::ImageDecodeErrors(
+                "gif: No frames in image".to_string()
+            ));
+        }
+        let mut image = Image::new_frames(
+            frames,
+            BitDepth::Eight,
+            metadata.width,
+            metadata.height,
+            ColorSpace::RGBA
+        );
+        image.metadata = metadata;
+        // a loop extension may come after the first frame
+        if let Some(count) = self.loop_count() {
+            image.metadata.set_loop_count(count);
+        }
+
+        Ok(image)
+    }
+
+    fn decode_frame(&mut self, n: usize) -> Result<Image, ImageErrors> {
+        let metadata = self.read_headers()?.unwrap();
+
+        let mut canvas = vec![0; self.output_buf_size().unwrap()];
+        let mut position = 0;
+
+        // every frame up to n still has to be decoded, since later
+        // frames are drawn on top of (and disposed over) earlier ones
+        while let Some(info) = self.frame_info() {
+            self.decode_into(&mut canvas)?;
+
+            if position == n {
+                let frame = Frame::from_u8(&canvas, ColorSpace::RGBA, usize::from(info.delay), 100);
+                let mut image = Image::new_frames(
+                    vec![frame],
+                    BitDepth::Eight,
+                    metadata.width,
+                    metadata.height,
+                    ColorSpace::RGBA
+                );
+                image.metadata = metadata;
+
+                return Ok(image);
+            }
+            position += 1;
+        }
+        Err(ImageErrors::FrameOutOfRange(n, position))
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        self.dimensions()
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        ColorSpace::RGBA
+    }
+
+    fn name(&self) -> &'static str {
+        "GIF Decoder"
+    }
+
+    fn read_headers(&mut self) -> Result<Option<ImageMetadata>, ImageErrors> {
+        self.decode_headers()?;
+
+        let (width, height) = self.dimensions().unwrap();
+
+        let mut metadata = ImageMetadata {
+            format: Some(ImageFormat::GIF),
+            colorspace: ColorSpace::RGBA,
+            depth: BitDepth::Eight,
+            width,
+            height,
+            ..Default::default()
+        };
+        if let Some(count) = self.loop_count() {
+            metadata.set_loop_count(count);
+        }
+
+        Ok(Some(metadata))
+    }
+}
+
+impl From<GifDecoderErrors> for ImageErrors {
+    fn from(value: GifDecoderErrors) -> Self {
+        Self::CodecErrors(value.code(), format!("gif: {:?}", value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::codecs::ImageFormat;
+    use crate::errors::ImageErrors;
+    use crate::image::Image;
+
+    /// A 1x1 image with a red frame followed by a green one
+    fn two_frame_gif() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"GIF89a");
+        // 1x1, 4 entry global palette
+        data.extend_from_slice(&[1, 0, 1, 0, 0x81, 0, 0]);
+        data.extend_from_slice(&[0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0, 255]);
+        data.extend_from_slice(&[0x21, 0xFF, 11]);
+        data.extend_from_slice(b"NETSCAPE2.0");
+        data.extend_from_slice(&[3, 1, 3, 0, 0]);
+
+        // clear, index, end of information as 3 bit codes
+        for (delay, lzw) in [(5_u8, [0x4C, 0x01]), (7, [0x54, 0x01])] {
+            data.extend_from_slice(&[0x21, 0xF9, 4, 0, delay, 0, 0, 0]);
+            data.extend_from_slice(&[0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0]);
+            data.extend_from_slice(&[2, 2, lzw[0], lzw[1], 0]);
+        }
+        data.push(0x3B);
+        data
+    }
+
+    #[test]
+    fn test_gif_zero_dimensions() {
+        let strict = DecoderOptions::default().set_strict_mode(true);
+
+        for options in [DecoderOptions::default(), strict] {
+            let mut data = two_frame_gif();
+            // zero height logical screen, this used to panic creating the frames
+            data[8] = 0;
+            assert!(Image::read(ZCursor::new(&data), options).is_err());
+
+            let mut data = two_frame_gif();
+            data[6] = 0;
+            assert!(Image::read(ZCursor::new(&data), options).is_err());
+        }
+    }
+
+    #[test]
+    fn test_gif_animated_frames() {
+        let data = two_frame_gif();
+
+        let (format, reader) = ImageFormat::guess_format(ZCursor::new(&data)).unwrap();
+        assert_eq!(format, ImageFormat::GIF);
+
+        let image = Image::read(reader, DecoderOptions::default()).unwrap();
+        assert_eq!(image.colorspace(), ColorSpace::RGBA);
+        assert_eq!(image.metadata().loop_count(), Some(3));
+
+        let frames = image.frames_ref();
+        assert_eq!(frames.len(), 2);
+        assert_eq!((frames[0].numerator, frames[0].denominator), (5, 100));
+        assert_eq!((frames[1].numerator, frames[1].denominator), (7, 100));
+
+        let pixels = image.flatten_to_u8();
+        assert_eq!(pixels[0], [255, 0, 0, 255]);
+        assert_eq!(pixels[1], [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn test_gif_read_frame() {
+        let data = two_frame_gif();
+
+        let image = Image::read_frame(ZCursor::new(&data), DecoderOptions::default(), 1).unwrap();
+        assert_eq!(image.frames_len(), 1);
+        assert_eq!(image.flatten_to_u8()[0], [0, 255, 0, 255]);
+        assert_eq!(
+            (
+                image.frames_ref()[0].numerator,
+                image.frames_ref()[0].denominator
+            ),
+            (7, 100)
+        );
+
+        match Image::read_frame(ZCursor::new(&data), DecoderOptions::default(), 2) {
+            Ok(_) => panic!("The image only has two frames"),
+            Err(err) => assert!(matches!(err, ImageErrors::FrameOutOfRange(2, 2)))
+        }
+    }
+}
@@ -11,6 +11,7 @@
 use zune_core::bit_depth::BitDepth;
 use zune_core::bytestream::{ZByteReaderTrait, ZByteWriterTrait};
 use zune_core::colorspace::ColorSpace;
+use zune_core::errors::ZErrorCodeTrait;
 use zune_core::options::EncoderOptions;
 pub use zune_hdr::*;
 
@@ -63,7 +64,7 @@ where
 
 impl From<HdrDecodeErrors> for ImageErrors {
     fn from(value: HdrDecodeErrors) -> Self {
-        Self::ImageDecodeErrors(format!("hdr: {value:?}"))
+        Self::CodecErrors(value.code(), format!("hdr: {value:?}"))
     }
 }
 
@@ -132,7 +133,7 @@ impl EncoderTrait for HdrEncoder {
 
 impl From<HdrEncodeErrors> for ImgEncodeErrors {
     fn from(value: HdrEncodeErrors) -> Self {
-        ImgEncodeErrors::ImageEncodeErrors(format!("HDR: {:?}", value))
+        ImgEncodeErrors::CodecErrors(value.code(), format!("HDR: {:?}", value))
     }
 }
 
@@ -0,0 +1,632 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+#![cfg_attr(feature = "docs", doc(cfg(feature = "heif")))]
+#![cfg(feature = "heif")]
+//! HEIF/HEIC decoding support
+//!
+//! The container is parsed by [zune-heif](zune_heif), which extracts the dimensions,
+//! EXIF metadata, ICC profile and coded HEVC data of the primary image.
+//!
+//! There is no HEVC decoder in this library, so reading headers always works but decoding
+//! pixels needs one, callers can plug in their own by implementing [`HevcDecoder`] and either
+//! setting it on a decoder via [`HeifDecoder::set_hevc_decoder`] or process wide via
+//! [`set_default_hevc_decoder`], the latter is what [`Image::read`](crate::image::Image::read)
+//! and friends use.
+//!
+//! With the `libde265` feature, the system libde265 library is used when no decoder has
+//! been set, see [`Libde265Decoder`].
+//!
+//! Grid images are decoded tile by tile and stitched together, alpha and depth
+//! auxiliary images are ignored.
+use std::sync::{Arc, RwLock};
+
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::colorspace::ColorSpace;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
+use zune_core::log::trace;
+use zune_core::options::DecoderOptions;
+pub use zune_heif::{self, HeifGrid, HevcPayload};
+
+use crate::codecs::ImageFormat;
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::metadata::ImageMetadata;
+use crate::traits::DecoderTrait;
+
+/// A decoder for the HEVC images stored inside HEIF files
+///
+/// Implement this to decode HEIF files with an external HEVC decoder
+pub trait HevcDecoder: Send + Sync {
+    /// Decode a single coded image into an image with one frame
+    ///
+    /// The image should be `payload.width` by `payload.height` pixels, any colorspace
+    /// and depth is accepted but all tiles of a grid image are converted to the ones of the
+    /// first tile
+    fn decode_hevc(&self, payload: &HevcPayload) -> Result<Image, ImageErrors>;
+}
+
+/// Decoder used by all HEIF decoders that don't have one set
+static DEFAULT_HEVC_DECODER: RwLock<Option<Arc<dyn HevcDecoder>>> = RwLock::new(None);
+
+/// Set the HEVC decoder used by all HEIF decoders which don't have one set via
+/// [`HeifDecoder::set_hevc_decoder`]
+///
+/// This takes precedence over the `libde265` decoder
+pub fn set_default_hevc_decoder(decoder: Arc<dyn HevcDecoder>) {
+    *DEFAULT_HEVC_DECODER.write().unwrap() = Some(decoder);
+}
+
+/// A HEIF decoder
+///
+/// This wraps [`zune_heif::HeifDecoder`] adding pixel decoding
+/// via a [`HevcDecoder`]
+pub struct HeifDecoder<T: ZByteReaderTrait> {
+    inner:        zune_heif::HeifDecoder<T>,
+    hevc_decoder: Option<Arc<dyn HevcDecoder>>
+}
+
+impl<T: ZByteReaderTrait> HeifDecoder<T> {
+    pub fn new_with_options(data: T, options: DecoderOptions) -> HeifDecoder<T> {
+        HeifDecoder {
+            inner:        zune_heif::HeifDecoder::new_with_options(data, options),
+            hevc_decoder: None
+        }
+    }
+    /// Set the decoder used for the coded images of this file
+    pub fn set_hevc_decoder(&mut self, decoder: Arc<dyn HevcDecoder>) {
+        self.hevc_decoder = Some(decoder);
+    }
+    /// Return the container parser, e.g. to extract the coded images
+    /// without decoding them
+    pub fn inner_mut(&mut self) -> &mut zune_heif::HeifDecoder<T> {
+        &mut self.inner
+    }
+
+    fn hevc_decoder(&self) -> Result<Arc<dyn HevcDecoder>, ImageErrors> {
+        if let Some(decoder) = &self.hevc_decoder {
+            return Ok(decoder.clone());
+        }
+        if let Some(decoder) = DEFAULT_HEVC_DECODER.read().unwrap().as_ref() {
+            return Ok(decoder.clone());
+        }
+        #[cfg(feature = "libde265")]
+        {
+            Ok(Arc::new(Libde265Decoder))
+        }
+        #[cfg(not(feature = "libde265"))]
+        {
+            Err(ImageErrors::CodecErrors(
+                ZErrorCode::Unsupported,
+                "heif: No HEVC decoder available, enable the libde265 feature or set one with set_default_hevc_decoder".to_string()
+            ))
+        }
+    }
+}
+
+impl<T: ZByteReaderTrait> DecoderTrait for HeifDecoder<T> {
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let mut metadata = self.read_headers()?.unwrap();
+        let decoder = self.hevc_decoder()?;
+        let payloads = self.inner.hevc_payloads()?;
+
+        let tiles = payloads
+            .iter()
+            .map(|x| {
+                let tile = decoder.decode_hevc(x)?;
+                if tile.dimensions() != (x.width, x.height) {
+                    return Err(ImageErrors::ImageDecodeErrors(format!(
+                        "heif: Expected a {}x{} image from the HEVC decoder but got {:?}",
+                        x.width,
+                        x.height,
+                        tile.dimensions()
+                    )));
+                }
+                Ok(tile)
+            })
+            .collect::<Result<Vec<Image>, ImageErrors>>()?;
+
+        let mut image = match self.inner.grid() {
+            Some(grid) => assemble_grid(tiles, grid)?,
+            None => tiles.into_iter().next().unwrap()
+        };
+        metadata.colorspace = image.colorspace();
+        metadata.depth = image.depth();
+        image.metadata = metadata;
+
+        Ok(image)
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        self.inner.dimensions()
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        match self.inner.hevc_format() {
+            Some((_, 0)) => ColorSpace::Luma,
+            _ => ColorSpace::RGB
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "HEIF decoder"
+    }
+
+    fn read_headers(&mut self) -> Result<Option<ImageMetadata>, ImageErrors> {
+        self.inner.decode_headers()?;
+
+        let (width, height) = self.inner.dimensions().unwrap();
+        let depth = match self.inner.hevc_format() {
+            Some((bit_depth, _)) if bit_depth > 8 => BitDepth::Sixteen,
+            _ => BitDepth::Eight
+        };
+
+        let mut metadata = ImageMetadata {
+            format: Some(ImageFormat::HEIF),
+            colorspace: self.out_colorspace(),
+            depth: depth,
+            width: width,
+            height: height,
+            ..Default::default()
+        };
+        #[cfg(feature = "metadata")]
+        {
+            if let Some(exif) = self.inner.exif() {
+                metadata.parse_raw_exif(exif)
+            }
+        }
+        // HEIF stores orientation as a rotation property, the exif orientation
+        // is informational and should not be applied
+        if let Some(rotation) = self.inner.rotation() {
+            let orientation = match rotation {
+                90 => 8,
+                180 => 3,
+                _ => 6
+            };
+            metadata.set_orientation(orientation);
+        }
+        if let Some(icc) = self.inner.icc_profile() {
+            metadata.set_icc_chunk(icc);
+        }
+        Ok(Some(metadata))
+    }
+}
+
+/// Stitch the decoded tiles of a grid image together, cropping
+/// tiles extending past the image
+fn assemble_grid(mut tiles: Vec<Image>, grid: HeifGrid) -> Result<Image, ImageErrors> {
+    let (tile_width, tile_height) = tiles[0].dimensions();
+    let colorspace = tiles[0].colorspace();
+    let depth = tiles[0].depth();
+
+    trace!(
+        "Assembling {}x{} tiles of {}x{}",
+        grid.columns,
+        grid.rows,
+        tile_width,
+        tile_height
+    );
+
+    if grid.columns * tile_width < grid.width || grid.rows * tile_height < grid.height {
+        return Err(ImageErrors::ImageDecodeErrors(format!(
+            "heif: {}x{} tiles of {}x{} do not cover the image of {}x{}",
+            grid.columns, grid.rows, tile_width, tile_height, grid.width, grid.height
+        )));
+    }
+    for tile in tiles.iter_mut() {
+        if tile.dimensions() != (tile_width, tile_height) {
+            return Err(ImageErrors::ImageDecodeErrors(
+                "heif: Grid tiles have different dimensions".to_string()
+            ));
+        }
+        tile.convert_color(colorspace)?;
+        tile.convert_depth(depth)?;
+    }
+    let image = match depth {
+        BitDepth::Eight => {
+            let pixels = copy_tiles::<u8>(&tiles, grid, colorspace.num_components());
+            Image::from_u8(&pixels, grid.width, grid.height, colorspace)
+        }
+        BitDepth::Sixteen => {
+            let pixels = copy_tiles::<u16>(&tiles, grid, colorspace.num_components());
+            Image::from_u16(&pixels, grid.width, grid.height, colorspace)
+        }
+        BitDepth::Float32 => {
+            let pixels = copy_tiles::<f32>(&tiles, grid, colorspace.num_components());
+            Image::from_f32(&pixels, grid.width, grid.height, colorspace)
+        }
+        depth => {
+            return Err(ImageErrors::CodecErrors(
+                ZErrorCode::Unsupported,
+                format!("heif: Unsupported tile depth {:?}", depth)
+            ));
+        }
+    };
+    Ok(image)
+}
+
+fn copy_tiles<T>(tiles: &[Image], grid: HeifGrid, components: usize) -> Vec<T>
+where
+    T: Default + Copy + 'static + bytemuck::Pod
+{
+    let (tile_width, tile_height) = tiles[0].dimensions();
+    let mut output = vec![T::default(); grid.width * grid.height * components];
+
+    for (i, tile) in tiles.iter().enumerate() {
+        let (x, y) = (
+            (i % grid.columns) * tile_width,
+            (i / grid.columns) * tile_height
+        );
+
+        if x >= grid.width || y >= grid.height {
+            continue;
+        }
+        let pixels = &tile.flatten_frames::<T>()[0];
+        let row_length = tile_width.min(grid.width - x) * components;
+
+        for (row, out_row) in pixels
+            .chunks_exact(tile_width * components)
+            .zip(output.chunks_exact_mut(grid.width * components).skip(y))
+        {
+            out_row[x * components..][..row_length].copy_from_slice(&row[..row_length]);
+        }
+    }
+    output
+}
+
+/// A [`HevcDecoder`] using the system libde265 library
+///
+/// YCbCr images are converted to RGB using the matrix coefficients
+/// signaled in the stream, monochrome images are returned as luma
+#[cfg(feature = "libde265")]
+pub struct Libde265Decoder;
+
+#[cfg(feature = "libde265")]
+impl HevcDecoder for Libde265Decoder {
+    fn decode_hevc(&self, payload: &HevcPayload) -> Result<Image, ImageErrors> {
+        use libde265_sys::*;
+
+        let length = i32::try_from(payload.bitstream.len()).map_err(|_| {
+            ImageErrors::CodecErrors(
+                ZErrorCode::LimitsExceeded,
+                "libde265: Too large coded image".to_string()
+            )
+        })?;
+
+        // SAFETY: the context is only used between its creation and freeing, and pictures
+        // are copied out before it is freed
+        unsafe {
+            let context = de265_new_decoder();
+
+            if context.is_null() {
+                return Err(ImageErrors::ImageDecodeErrors(
+                    "libde265: Could not create decoder".to_string()
+                ));
+            }
+            let result = libde265_decode(context, &payload.bitstream, length);
+            de265_free_decoder(context);
+
+            result
+        }
+    }
+}
+
+#[cfg(feature = "libde265")]
+unsafe fn libde265_decode(
+    context: *mut libde265_sys::de265_decoder_context, data: &[u8], length: i32
+) -> Result<Image, ImageErrors> {
+    use libde265_sys::*;
+
+    let check = |error: de265_error::Type| {
+        if error == de265_error::DE265_OK
+            || error == de265_error::DE265_ERROR_WAITING_FOR_INPUT_DATA
+        {
+            return Ok(());
+        }
+        let text = std::ffi::CStr::from_ptr(de265_get_error_text(error));
+        Err(ImageErrors::ImageDecodeErrors(format!(
+            "libde265: {}",
+            text.to_string_lossy()
+        )))
+    };
+    check(de265_push_data(
+        context,
+        data.as_ptr().cast(),
+        length,
+        0,
+        std::ptr::null_mut()
+    ))?;
+    check(de265_flush_data(context))?;
+
+    let mut more = 1;
+
+    while more != 0 {
+        more = 0;
+        check(de265_decode(context, &mut more))?;
+
+        let picture = de265_get_next_picture(context);
+
+        if !picture.is_null() {
+            let mut planes = vec![];
+
+            for channel in 0..3 {
+                let width = de265_get_image_width(picture, channel).max(0) as usize;
+                let height = de265_get_image_height(picture, channel).max(0) as usize;
+                let bytes = if de265_get_bits_per_pixel(picture, channel) > 8 { 2 } else { 1 };
+                let mut stride = 0;
+                let plane = de265_get_image_plane(picture, channel, &mut stride);
+
+                if plane.is_null() || width == 0 {
+                    continue;
+                }
+                let mut samples = Vec::with_capacity(width * height);
+
+                for y in 0..height {
+                    let row =
+                        std::slice::from_raw_parts(plane.add(y * stride as usize), width * bytes);
+                    if bytes == 2 {
+                        samples.extend(
+                            row.chunks_exact(2)
+                                .map(|x| u16::from_ne_bytes([x[0], x[1]]))
+                        );
+                    } else {
+                        samples.extend(row.iter().map(|x| u16::from(*x)));
+                    }
+                }
+                planes.push((samples, width, height));
+            }
+            let format = YCbCrFormat {
+                bit_depth:  de265_get_bits_per_pixel(picture, 0) as u8,
+                full_range: de265_get_image_full_range_flag(picture) != 0,
+                matrix:     de265_get_image_matrix_coefficients(picture),
+                monochrome: de265_get_chroma_format(picture) == de265_chroma::de265_chroma_mono
+            };
+            return ycbcr_to_image(&planes, format);
+        }
+    }
+    Err(ImageErrors::ImageDecodeErrors(
+        "libde265: No picture in the coded data".to_string()
+    ))
+}
+
+#[cfg(feature = "libde265")]
+struct YCbCrFormat {
+    bit_depth:  u8,
+    full_range: bool,
+    /// Matrix coefficients as signaled in the video usability information
+    matrix:     i32,
+    monochrome: bool
+}
+
+/// Convert decoded planes to an RGB or luma image, 8 bit images stay 8 bit and
+/// anything deeper is scaled to 16 bits
+#[cfg(feature = "libde265")]
+fn ycbcr_to_image(
+    planes: &[(Vec<u16>, usize, usize)], format: YCbCrFormat
+) -> Result<Image, ImageErrors> {
+    let (luma, width, height) = planes.first().ok_or(ImageErrors::ImageDecodeErrors(
+        "libde265: Picture has no luma plane".to_string()
+    ))?;
+    let (width, height) = (*width, *height);
+    let shift = i32::from(format.bit_depth) - 8;
+    let max = ((1_u32 << format.bit_depth) - 1) as f32;
+
+    // normalize a luma or chroma sample to 0..1 or -0.5..0.5
+    let (luma_offset, luma_scale, chroma_scale) = if format.full_range {
+        (0.0, max, max)
+    } else {
+        (
+            (16 << shift) as f32,
+            (219 << shift) as f32,
+            (224 << shift) as f32
+        )
+    };
+    let chroma_offset = (1_u32 << (format.bit_depth - 1)) as f32;
+    let normalize_luma = |x: u16| (f32::from(x) - luma_offset) / luma_scale;
+    let normalize_chroma = |x: u16| (f32::from(x) - chroma_offset) / chroma_scale;
+
+    let mut samples: Vec<f32>;
+    let colorspace;
+
+    if format.monochrome || planes.len() < 3 {
+        samples = luma.iter().map(|x| normalize_luma(*x)).collect();
+        colorspace = ColorSpace::Luma;
+    } else {
+        let (cb, chroma_width, chroma_height) = &planes[1];
+        let cr = &planes[2].0;
+        let (sub_x, sub_y) = (width / chroma_width, height / chroma_height);
+
+        // (kr, kb) for BT.709, BT.2020 and BT.601 as the default
+        let (kr, kb) = match format.matrix {
+            1 => (0.2126, 0.0722),
+            9 | 10 => (0.2627, 0.0593),
+            _ => (0.299, 0.114)
+        };
+        samples = Vec::with_capacity(width * height * 3);
+
+        for y in 0..height {
+            for x in 0..width {
+                let chroma_pos = (y / sub_y.max(1)).min(chroma_height - 1) * chroma_width
+                    + (x / sub_x.max(1)).min(chroma_width - 1);
+
+                let l = normalize_luma(luma[y * width + x]);
+                let u = normalize_chroma(cb[chroma_pos]);
+                let v = normalize_chroma(cr[chroma_pos]);
+
+                if format.matrix == 0 {
+                    // identity matrix, planes are G, B and R
+                    samples.extend_from_slice(&[v + 0.5, l, u + 0.5]);
+                } else {
+                    let r = l + 2.0 * (1.0 - kr) * v;
+                    let b = l + 2.0 * (1.0 - kb) * u;
+                    let g = (l - kr * r - kb * b) / (1.0 - kr - kb);
+                    samples.extend_from_slice(&[r, g, b]);
+                }
+            }
+        }
+        colorspace = ColorSpace::RGB;
+    }
+    let image = if format.bit_depth > 8 {
+        let pixels: Vec<u16> = samples
+            .iter()
+            .map(|x| (x * 65535.0).round().clamp(0.0, 65535.0) as u16)
+            .collect();
+        Image::from_u16(&pixels, width, height, colorspace)
+    } else {
+        let pixels: Vec<u8> = samples
+            .iter()
+            .map(|x| (x * 255.0).round().clamp(0.0, 255.0) as u8)
+            .collect();
+        Image::from_u8(&pixels, width, height, colorspace)
+    };
+    Ok(image)
+}
+
+impl From<zune_heif::HeifDecodeErrors> for ImageErrors {
+    fn from(value: zune_heif::HeifDecodeErrors) -> Self {
+        Self::CodecErrors(value.code(), format!("heif: {:?}", value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::errors::ZErrorCode;
+    use zune_core::options::DecoderOptions;
+
+    use crate::codecs::heif::{HeifDecoder, HevcDecoder, HevcPayload};
+    use crate::codecs::{guess_format, ImageFormat};
+    use crate::errors::ImageErrors;
+    use crate::image::Image;
+    use crate::traits::DecoderTrait;
+
+    /// Fills each tile with the last byte of its coded data
+    struct FillDecoder;
+
+    impl HevcDecoder for FillDecoder {
+        fn decode_hevc(&self, payload: &HevcPayload) -> Result<Image, ImageErrors> {
+            let value = *payload.bitstream.last().unwrap();
+            Ok(Image::fill(
+                value,
+                ColorSpace::RGB,
+                payload.width,
+                payload.height
+            ))
+        }
+    }
+
+    fn boxed(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut data = (payload.len() as u32 + 8).to_be_bytes().to_vec();
+        data.extend_from_slice(kind);
+        data.extend_from_slice(payload);
+        data
+    }
+
+    fn full_boxed(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut data = vec![0, 0, 0, 0];
+        data.extend_from_slice(payload);
+        boxed(kind, &data)
+    }
+
+    /// A 6x2 grid image made of two 4x2 tiles whose coded data is a single
+    /// byte, the mdat box comes before meta so tile offsets are known upfront
+    fn create_grid_heif() -> Vec<u8> {
+        let ftyp = boxed(b"ftyp", b"heic\0\0\0\0mif1heic");
+        let mdat = boxed(b"mdat", &[0, 0, 0, 1, 10, 0, 0, 0, 1, 20]);
+        let mdat_data = (ftyp.len() + 8) as u32;
+
+        let mut iinf = vec![0, 3];
+        for (id, kind) in [(1_u8, b"grid"), (2, b"hvc1"), (3, b"hvc1")] {
+            let mut infe = vec![2, 0, 0, 0, 0, id, 0, 0];
+            infe.extend_from_slice(kind);
+            iinf.extend(boxed(b"infe", &infe));
+        }
+        // version 1, which allows storing the grid description in idat
+        let mut iloc = vec![1, 0, 0, 0, 0x44, 0x00, 0, 3];
+        for (id, method, offset, length) in [
+            (1_u16, 1_u16, 0_u32, 8_u32),
+            (2, 0, mdat_data, 5),
+            (3, 0, mdat_data + 5, 5)
+        ] {
+            iloc.extend_from_slice(&id.to_be_bytes());
+            iloc.extend_from_slice(&method.to_be_bytes());
+            iloc.extend_from_slice(&[0, 0, 0, 1]);
+            iloc.extend_from_slice(&offset.to_be_bytes());
+            iloc.extend_from_slice(&length.to_be_bytes());
+        }
+        let mut hvcc = vec![0_u8; 23];
+        hvcc[16] = 0xFD;
+        hvcc[17] = 0xF8;
+        hvcc[21] = 0x03;
+
+        let mut ipco = boxed(b"hvcC", &hvcc);
+        ipco.extend(full_boxed(b"ispe", &[0, 0, 0, 4, 0, 0, 0, 2]));
+        ipco.extend(full_boxed(b"ispe", &[0, 0, 0, 6, 0, 0, 0, 2]));
+
+        let mut iprp = boxed(b"ipco", &ipco);
+        iprp.extend(full_boxed(
+            b"ipma",
+            &[0, 0, 0, 3, 0, 1, 1, 3, 0, 2, 2, 1, 2, 0, 3, 2, 1, 2]
+        ));
+
+        let mut meta = full_boxed(b"pitm", &[0, 1]);
+        meta.extend(full_boxed(b"iinf", &iinf));
+        meta.extend(boxed(b"iloc", &iloc));
+        meta.extend(boxed(b"iprp", &iprp));
+        meta.extend(full_boxed(
+            b"iref",
+            &boxed(b"dimg", &[0, 1, 0, 2, 0, 2, 0, 3])
+        ));
+        meta.extend(boxed(b"idat", &[0, 0, 0, 1, 0, 6, 0, 2]));
+
+        let mut file = ftyp;
+        file.extend(mdat);
+        file.extend(full_boxed(b"meta", &meta));
+        file
+    }
+
+    #[test]
+    fn test_heif_grid() {
+        let data = create_grid_heif();
+
+        let (format, _) = guess_format(ZCursor::new(&data)).unwrap();
+        assert_eq!(format, ImageFormat::HEIF);
+
+        let mut decoder =
+            HeifDecoder::new_with_options(ZCursor::new(&data), DecoderOptions::default());
+        let metadata = decoder.read_headers().unwrap().unwrap();
+        assert_eq!((metadata.width, metadata.height), (6, 2));
+        assert_eq!(metadata.colorspace, ColorSpace::RGB);
+
+        decoder.set_hevc_decoder(Arc::new(FillDecoder));
+        let image = decoder.decode().unwrap();
+
+        assert_eq!(image.dimensions(), (6, 2));
+        let row: Vec<u8> = [[10; 3]; 4]
+            .iter()
+            .chain(&[[20; 3]; 2])
+            .flatten()
+            .copied()
+            .collect();
+        assert_eq!(image.flatten_to_u8()[0], [row.clone(), row].concat());
+    }
+
+    #[test]
+    #[cfg(not(feature = "libde265"))]
+    fn test_heif_without_hevc_decoder() {
+        let data = create_grid_heif();
+
+        let mut decoder =
+            HeifDecoder::new_with_options(ZCursor::new(&data), DecoderOptions::default());
+        let err = decoder.decode().err().unwrap();
+        assert_eq!(err.code(), ZErrorCode::Unsupported);
+    }
+}
@@ -0,0 +1,722 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+#![cfg_attr(feature = "docs", doc(cfg(feature = "ico")))]
+#![cfg(feature = "ico")]
+//! ICO and CUR (Windows icon and cursor) decoding and encoding support
+//!
+//! An icon file is a directory of the same picture stored at different sizes, each entry
+//! is either a PNG file or a BMP without the file header followed by a 1 bit transparency
+//! mask. PNG entries are decoded with [`zune-png`](zune_png) and BMP entries with
+//! [`zune-bmp`](zune_bmp).
+//!
+//! Decoding returns a single entry, which one can be configured
+//! with [`DecoderOptions::ico_set_size`], use [`IcoDecoder::decode_all`] to get every entry.
+//!
+//! The encoder writes images smaller than 256 pixels as 32 bit BMPs, which
+//! is understood by all readers, and 256 pixel images as PNG.
+use std::cmp::Reverse;
+
+use zune_bmp::BmpDecoder;
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::{ZByteReaderTrait, ZByteWriterTrait, ZCursor, ZWriter};
+use zune_core::colorspace::ColorSpace;
+use zune_core::errors::ZErrorCode;
+use zune_core::log::trace;
+use zune_core::options::{DecoderOptions, EncoderOptions};
+use zune_png::PngDecoder;
+
+use crate::codecs::ImageFormat;
+use crate::errors::{ImageErrors, ImgEncodeErrors};
+use crate::image::Image;
+use crate::metadata::ImageMetadata;
+use crate::traits::{DecoderTrait, EncoderTrait};
+
+const ICO_HEADER_SIZE: usize = 6;
+const ICO_ENTRY_SIZE: usize = 16;
+const BMP_INFO_HEADER_SIZE: usize = 40;
+const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+/// Largest width or height an icon entry can have
+const MAX_ICO_DIMENSION: usize = 256;
+
+/// Probe some bytes to see if they consist of an ICO or CUR file
+///
+/// This needs the header and the first directory entry, i.e 22 bytes
+pub fn probe_ico(bytes: &[u8]) -> bool {
+    if let Some(header) = bytes.get(..ICO_HEADER_SIZE + ICO_ENTRY_SIZE) {
+        let kind = u16::from_le_bytes([header[2], header[3]]);
+        let count = u16::from_le_bytes([header[4], header[5]]);
+
+        // reserved fields of the header and first entry are zero
+        return header[0..2] == [0, 0] && (kind == 1 || kind == 2) && count != 0 && header[9] == 0;
+    }
+    false
+}
+
+#[inline]
+fn read_u16(bytes: &[u8], position: usize) -> u16 {
+    u16::from_le_bytes([bytes[position], bytes[position + 1]])
+}
+
+#[inline]
+fn read_u32(bytes: &[u8], position: usize) -> u32 {
+    u32::from_le_bytes(bytes[position..position + 4].try_into().unwrap())
+}
+
+/// An image stored in an icon directory
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct IcoEntry {
+    /// Width as stored in the directory
+    pub width:     usize,
+    /// Height as stored in the directory
+    pub height:    usize,
+    /// Bits per pixel as stored in the directory, zero if unknown
+    pub bit_depth: u16,
+    /// Hotspot of a cursor as `(x, y)`, `None` for icons
+    pub hotspot:   Option<(u16, u16)>,
+    /// Whether the entry is stored as a PNG file
+    pub is_png:    bool,
+    offset:        usize,
+    size:          usize
+}
+
+/// An ICO and CUR decoder
+///
+/// The whole file is read into memory on creation
+pub struct IcoDecoder {
+    data:            Vec<u8>,
+    options:         DecoderOptions,
+    entries:         Vec<IcoEntry>,
+    is_cursor:       bool,
+    decoded_headers: bool
+}
+
+impl IcoDecoder {
+    pub fn try_new<T: ZByteReaderTrait>(
+        mut source: T, options: DecoderOptions
+    ) -> Result<IcoDecoder, ImageErrors> {
+        let mut data = vec![];
+        source
+            .read_remaining(&mut data)
+            .map_err(|x| ImageErrors::ImageDecodeErrors(format!("ico: {:?}", x)))?;
+
+        Ok(IcoDecoder {
+            data,
+            options,
+            entries: vec![],
+            is_cursor: false,
+            decoded_headers: false
+        })
+    }
+
+    /// Read the icon directory
+    pub fn decode_headers(&mut self) -> Result<(), ImageErrors> {
+        if self.decoded_headers {
+            return Ok(());
+        }
+        if !probe_ico(&self.data) {
+            return Err(ImageErrors::ImageDecodeErrors(
+                "ico: Invalid magic bytes".to_string()
+            ));
+        }
+        self.is_cursor = read_u16(&self.data, 2) == 2;
+        let count = usize::from(read_u16(&self.data, 4));
+
+        trace!("Number of entries: {}", count);
+
+        if self.data.len() < ICO_HEADER_SIZE + count * ICO_ENTRY_SIZE {
+            return Err(ImageErrors::ImageDecodeErrors(
+                "ico: File too small for icon directory".to_string()
+            ));
+        }
+        for i in 0..count {
+            let start = ICO_HEADER_SIZE + i * ICO_ENTRY_SIZE;
+            let entry = &self.data[start..start + ICO_ENTRY_SIZE];
+
+            let offset = read_u32(entry, 12) as usize;
+            let size = read_u32(entry, 8) as usize;
+
+            if offset.saturating_add(size) > self.data.len() {
+                return Err(ImageErrors::ImageDecodeErrors(format!(
+                    "ico: Entry {} lies outside of the file",
+                    i
+                )));
+            }
+            let (bit_depth, hotspot) = if self.is_cursor {
+                (0, Some((read_u16(entry, 4), read_u16(entry, 6))))
+            } else {
+                (read_u16(entry, 6), None)
+            };
+            // zero means 256 pixels
+            let dimension = |x: u8| if x == 0 { MAX_ICO_DIMENSION } else { usize::from(x) };
+
+            self.entries.push(IcoEntry {
+                width: dimension(entry[0]),
+                height: dimension(entry[1]),
+                bit_depth,
+                hotspot,
+                is_png: self.data[offset..offset + size].starts_with(&PNG_SIGNATURE),
+                offset,
+                size
+            });
+        }
+        self.decoded_headers = true;
+
+        Ok(())
+    }
+
+    /// Return the entries in the icon directory
+    ///
+    /// This is empty if headers haven't been decoded
+    pub fn entries(&self) -> &[IcoEntry] {
+        &self.entries
+    }
+
+    /// Return true if the file is a cursor (CUR) file
+    pub const fn is_cursor(&self) -> bool {
+        self.is_cursor
+    }
+
+    /// Index of the entry picked by the decoder options
+    fn selected_entry(&self) -> usize {
+        let wanted = self.options.ico_get_size();
+
+        (0..self.entries.len())
+            .min_by_key(|i| {
+                let entry = &self.entries[*i];
+                let size = entry.width.max(entry.height);
+                let distance = if wanted == 0 { 0 } else { size.abs_diff(wanted) };
+
+                (distance, Reverse(size), Reverse(entry.bit_depth))
+            })
+            .unwrap_or(0)
+    }
+
+    /// Decode the entry at `index` in the directory into an 8 bit RGBA image
+    pub fn decode_entry(&mut self, index: usize) -> Result<Image, ImageErrors> {
+        self.decode_headers()?;
+
+        let entry = *self.entries.get(index).ok_or_else(|| {
+            ImageErrors::ImageDecodeErrors(format!("ico: No entry at index {}", index))
+        })?;
+        let bytes = &self.data[entry.offset..entry.offset + entry.size];
+
+        let mut image = if entry.is_png {
+            let mut decoder = PngDecoder::new_with_options(ZCursor::new(bytes), self.options);
+            let mut image = DecoderTrait::decode(&mut decoder)?;
+
+            image.convert_color(ColorSpace::RGBA)?;
+            image.convert_depth(BitDepth::Eight)?;
+            image
+        } else {
+            decode_bmp_entry(bytes, &self.options)?
+        };
+        let (width, height) = image.dimensions();
+
+        image.metadata.format = Some(ImageFormat::ICO);
+        image.metadata.width = width;
+        image.metadata.height = height;
+
+        Ok(image)
+    }
+
+    /// Decode every entry in the directory into 8 bit RGBA images
+    ///
+    /// Images are returned in the order they are stored in the file
+    pub fn decode_all(&mut self) -> Result<Vec<Image>, ImageErrors> {
+        self.decode_headers()?;
+
+        (0..self.entries.len())
+            .map(|i| self.decode_entry(i))
+            .collect()
+    }
+}
+
+/// Decode a BMP stored in an icon
+///
+/// The bitmap has no file header and its height covers both the image and
+/// the 1 bit AND mask that follows the pixels
+fn decode_bmp_entry(bytes: &[u8], options: &DecoderOptions) -> Result<Image, ImageErrors> {
+    if bytes.len() < BMP_INFO_HEADER_SIZE {
+        return Err(ImageErrors::ImageDecodeErrors(
+            "ico: BMP entry too small".to_string()
+        ));
+    }
+    let header_size = read_u32(bytes, 0) as usize;
+
+    if header_size < BMP_INFO_HEADER_SIZE || header_size > bytes.len() {
+        return Err(ImageErrors::CodecErrors(
+            ZErrorCode::Unsupported,
+            format!("ico: Unsupported BMP header size {}", header_size)
+        ));
+    }
+    let stored_height = read_u32(bytes, 8) as i32;
+    let width = (read_u32(bytes, 4) as i32).unsigned_abs() as usize;
+    let height = (stored_height.unsigned_abs() / 2) as usize;
+    let bpp = usize::from(read_u16(bytes, 14));
+    let compression = read_u32(bytes, 16);
+    let colors_used = read_u32(bytes, 32) as usize;
+
+    if width == 0 || height == 0 {
+        return Err(ImageErrors::ImageDecodeErrors(
+            "ico: Zero dimensions in BMP entry".to_string()
+        ));
+    }
+    if width > options.max_width() || height > options.max_height() {
+        return Err(ImageErrors::CodecErrors(
+            ZErrorCode::LimitsExceeded,
+            format!(
+                "ico: Image dimensions ({},{}) greater than max set dimensions ({},{})",
+                width,
+                height,
+                options.max_width(),
+                options.max_height()
+            )
+        ));
+    }
+    let palette_size = if bpp <= 8 {
+        (if colors_used == 0 { 1 << bpp } else { colors_used }) * 4
+    } else {
+        0
+    };
+    // BITFIELDS masks follow the header if it doesn't contain them
+    let masks_size = if compression == 3 && header_size == BMP_INFO_HEADER_SIZE { 12 } else { 0 };
+    let pixels_offset = header_size + palette_size + masks_size;
+    let row_size = (width * bpp).div_ceil(32) * 4;
+
+    let mut pixels = vec![0_u8; width * height * 4];
+    let mut has_alpha = false;
+
+    if bpp == 32 && compression == 0 {
+        // zune-bmp treats the fourth byte as padding, icons store alpha there
+        let data = bytes
+            .get(pixels_offset..pixels_offset + row_size * height)
+            .ok_or_else(|| {
+                ImageErrors::ImageDecodeErrors("ico: Truncated BMP entry".to_string())
+            })?;
+
+        for (out, row) in pixels
+            .chunks_exact_mut(width * 4)
+            .zip(data.chunks_exact(row_size).rev())
+        {
+            for (out_px, in_px) in out.chunks_exact_mut(4).zip(row.chunks_exact(4)) {
+                out_px.copy_from_slice(&[in_px[2], in_px[1], in_px[0], in_px[3]]);
+            }
+        }
+        has_alpha = pixels.chunks_exact(4).any(|x| x[3] != 0);
+    } else {
+        // add a file header so that zune-bmp can read it, with the height
+        // adjusted to exclude the mask
+        let mut file = Vec::with_capacity(14 + bytes.len());
+
+        file.extend_from_slice(b"BM");
+        file.extend_from_slice(&((14 + bytes.len()) as u32).to_le_bytes());
+        file.extend_from_slice(&[0; 4]);
+        file.extend_from_slice(&((14 + pixels_offset) as u32).to_le_bytes());
+        file.extend_from_slice(bytes);
+
+        let bmp_height = (height as i32) * stored_height.signum();
+        file[14 + 8..14 + 12].copy_from_slice(&bmp_height.to_le_bytes());
+
+        let mut decoder = BmpDecoder::new_with_options(ZCursor::new(&file), *options);
+        let decoded = decoder.decode()?;
+
+        match decoder.colorspace() {
+            Some(ColorSpace::RGBA) => pixels.copy_from_slice(&decoded),
+            Some(ColorSpace::RGB) => {
+                for (out, rgb) in pixels.chunks_exact_mut(4).zip(decoded.chunks_exact(3)) {
+                    out.copy_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+                }
+            }
+            Some(ColorSpace::Luma) => {
+                for (out, luma) in pixels.chunks_exact_mut(4).zip(decoded.iter()) {
+                    out.copy_from_slice(&[*luma, *luma, *luma, 255]);
+                }
+            }
+            colorspace => {
+                return Err(ImageErrors::ImageDecodeErrors(format!(
+                    "ico: Unexpected BMP colorspace {:?}",
+                    colorspace
+                )));
+            }
+        }
+    }
+    if !has_alpha {
+        // Apply the AND mask, set bits are transparent pixels.
+        // Compressed entries have no known mask location so they stay opaque
+        let mask_row_size = width.div_ceil(32) * 4;
+        let mask_offset = pixels_offset + row_size * height;
+
+        let mask = bytes.get(mask_offset..mask_offset + mask_row_size * height);
+
+        if let (Some(mask), 0 | 3) = (mask, compression) {
+            for (out, row) in pixels
+                .chunks_exact_mut(width * 4)
+                .zip(mask.chunks_exact(mask_row_size).rev())
+            {
+                for (x, pixel) in out.chunks_exact_mut(4).enumerate() {
+                    if (row[x / 8] >> (7 - (x % 8))) & 1 == 1 {
+                        pixel[3] = 0;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(Image::from_u8(&pixels, width, height, ColorSpace::RGBA))
+}
+
+impl DecoderTrait for IcoDecoder {
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let metadata = self.read_headers()?.unwrap();
+        let mut image = self.decode_entry(self.selected_entry())?;
+
+        let (width, height) = image.dimensions();
+        image.metadata = ImageMetadata {
+            width,
+            height,
+            ..metadata
+        };
+        Ok(image)
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        if !self.decoded_headers {
+            return None;
+        }
+        let entry = &self.entries[self.selected_entry()];
+
+        Some((entry.width, entry.height))
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        ColorSpace::RGBA
+    }
+
+    fn name(&self) -> &'static str {
+        "ICO Decoder"
+    }
+
+    fn read_headers(&mut self) -> Result<Option<ImageMetadata>, ImageErrors> {
+        self.decode_headers()?;
+
+        let (width, height) = self.dimensions().unwrap();
+
+        let metadata = ImageMetadata {
+            format: Some(ImageFormat::ICO),
+            colorspace: ColorSpace::RGBA,
+            depth: BitDepth::Eight,
+            width,
+            height,
+            ..Default::default()
+        };
+
+        Ok(Some(metadata))
+    }
+}
+
+/// An ICO and CUR encoder
+///
+/// [`EncoderTrait::encode`] writes a single image icon, use
+/// [`encode_images`](Self::encode_images) to write an icon containing
+/// the same picture at multiple sizes
+#[derive(Default)]
+pub struct IcoEncoder {
+    options: Option<EncoderOptions>,
+    hotspot: Option<(u16, u16)>
+}
+
+impl IcoEncoder {
+    /// Create a new encoder
+    pub fn new() -> IcoEncoder {
+        IcoEncoder::default()
+    }
+    /// Create a new encoder with specified options
+    pub fn new_with_options(options: EncoderOptions) -> IcoEncoder {
+        IcoEncoder {
+            options: Some(options),
+            hotspot: None
+        }
+    }
+    /// Write a cursor (CUR) instead of an icon, with `(x, y)`
+    /// being the hotspot for all images
+    pub fn set_cursor_hotspot(&mut self, x: u16, y: u16) -> &mut Self {
+        self.hotspot = Some((x, y));
+        self
+    }
+
+    /// Encode multiple images into one icon, each image becoming an entry
+    ///
+    /// Images can be at most 256 pixels wide and tall, they are converted
+    /// to 8 bit RGBA if they are in a different format
+    ///
+    /// # Returns
+    /// - `Ok(usize)`: The number of bytes written into `sink`
+    /// - Err: An image couldn't be encoded
+    pub fn encode_images<T: ZByteWriterTrait>(
+        &mut self, images: &[Image], sink: T
+    ) -> Result<usize, ImageErrors> {
+        if images.is_empty() || images.len() > usize::from(u16::MAX) {
+            return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(
+                format!("ico: Cannot store {} images in an icon", images.len())
+            )));
+        }
+        let mut entries = Vec::with_capacity(images.len());
+
+        for image in images {
+            if image.colorspace() == ColorSpace::RGBA && image.depth() == BitDepth::Eight {
+                entries.push(encode_entry(image)?);
+            } else {
+                let mut image = image.clone();
+
+                image.convert_color(ColorSpace::RGBA)?;
+                image.convert_depth(BitDepth::Eight)?;
+                entries.push(encode_entry(&image)?);
+            }
+        }
+        self.write_icon(&entries, sink)
+    }
+
+    fn write_icon<T: ZByteWriterTrait>(
+        &self, entries: &[(usize, usize, Vec<u8>)], sink: T
+    ) -> Result<usize, ImageErrors> {
+        let mut writer = ZWriter::new(sink);
+        let kind = if self.hotspot.is_some() { 2 } else { 1 };
+
+        let mut offset = ICO_HEADER_SIZE + entries.len() * ICO_ENTRY_SIZE;
+
+        let mut header = Vec::with_capacity(offset);
+
+        header.extend_from_slice(&[0, 0]);
+        header.extend_from_slice(&u16::to_le_bytes(kind));
+        header.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+
+        for (width, height, data) in entries {
+            // 256 is stored as zero
+            header.push(*width as u8);
+            header.push(*height as u8);
+            // no palette, reserved
+            header.extend_from_slice(&[0, 0]);
+
+            let (planes, bpp) = self.hotspot.unwrap_or((1, 32));
+            header.extend_from_slice(&planes.to_le_bytes());
+            header.extend_from_slice(&bpp.to_le_bytes());
+            header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            header.extend_from_slice(&(offset as u32).to_le_bytes());
+
+            offset += data.len();
+        }
+        writer.write_all(&header).map_err(encode_error)?;
+
+        for (_, _, data) in entries {
+            writer.write_all(data).map_err(encode_error)?;
+        }
+        Ok(writer.bytes_written())
+    }
+}
+
+fn encode_error<T: core::fmt::Debug>(error: T) -> ImageErrors {
+    ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!(
+        "ico: {:?}",
+        error
+    )))
+}
+
+/// Encode an 8 bit RGBA image as an icon entry, returning the
+/// dimensions and the encoded bytes
+fn encode_entry(image: &Image) -> Result<(usize, usize, Vec<u8>), ImageErrors> {
+    let (width, height) = image.dimensions();
+
+    if width == 0 || height == 0 || width > MAX_ICO_DIMENSION || height > MAX_ICO_DIMENSION {
+        return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(
+            format!(
+                "ico: Image dimensions ({},{}) not in the range 1..={}",
+                width, height, MAX_ICO_DIMENSION
+            )
+        )));
+    }
+    let pixels = &image.flatten_frames::<u8>()[0];
+    let mut data = vec![];
+
+    if width == MAX_ICO_DIMENSION || height == MAX_ICO_DIMENSION {
+        let options = EncoderOptions::new(width, height, ColorSpace::RGBA, BitDepth::Eight);
+
+        zune_png::PngEncoder::new(pixels, options)
+            .encode(&mut data)
+            .map_err(encode_error)?;
+
+        return Ok((width, height, data));
+    }
+    let mask_row_size = width.div_ceil(32) * 4;
+    let image_size = width * height * 4 + mask_row_size * height;
+
+    // BITMAPINFOHEADER, the height includes the AND mask
+    data.extend_from_slice(&(BMP_INFO_HEADER_SIZE as u32).to_le_bytes());
+    data.extend_from_slice(&(width as u32).to_le_bytes());
+    data.extend_from_slice(&(2 * height as u32).to_le_bytes());
+    // planes and bits per pixel
+    data.extend_from_slice(&1_u16.to_le_bytes());
+    data.extend_from_slice(&32_u16.to_le_bytes());
+    // compression
+    data.extend_from_slice(&0_u32.to_le_bytes());
+    data.extend_from_slice(&(image_size as u32).to_le_bytes());
+    // resolution, colors used and important colors
+    data.extend_from_slice(&[0; 16]);
+
+    // pixels are stored bottom up in BGRA order
+    for row in pixels.chunks_exact(width * 4).rev() {
+        for pixel in row.chunks_exact(4) {
+            data.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+        }
+    }
+    // AND mask, readers ignoring alpha use it for transparency
+    for row in pixels.chunks_exact(width * 4).rev() {
+        let mut mask = vec![0_u8; mask_row_size];
+
+        for (x, pixel) in row.chunks_exact(4).enumerate() {
+            if pixel[3] == 0 {
+                mask[x / 8] |= 1 << (7 - (x % 8));
+            }
+        }
+        data.extend_from_slice(&mask);
+    }
+
+    Ok((width, height, data))
+}
+
+impl EncoderTrait for IcoEncoder {
+    fn name(&self) -> &'static str {
+        "ico"
+    }
+
+    fn encode_inner<T: ZByteWriterTrait>(
+        &mut self, image: &Image, sink: T
+    ) -> Result<usize, ImageErrors> {
+        let entry = encode_entry(image)?;
+
+        self.write_icon(&[entry], sink)
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::RGBA]
+    }
+
+    fn format(&self) -> ImageFormat {
+        ImageFormat::ICO
+    }
+
+    fn supported_bit_depth(&self) -> &'static [BitDepth] {
+        &[BitDepth::Eight]
+    }
+
+    fn default_depth(&self, _: BitDepth) -> BitDepth {
+        BitDepth::Eight
+    }
+
+    fn default_colorspace(&self, _: ColorSpace) -> ColorSpace {
+        ColorSpace::RGBA
+    }
+
+    fn set_options(&mut self, opts: EncoderOptions) {
+        self.options = Some(opts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::codecs::ico::{IcoDecoder, IcoEncoder};
+    use crate::codecs::ImageFormat;
+    use crate::image::Image;
+
+    #[test]
+    fn test_ico_multi_size_roundtrip() {
+        let images = [16, 48, 256].map(|size| {
+            Image::from_fn::<u8, _>(size, size, ColorSpace::RGBA, |y, x, px| {
+                px[..4].copy_from_slice(&[x as u8, y as u8, 7, if x < 4 { 0 } else { 255 }]);
+            })
+        });
+        let mut data = vec![];
+        IcoEncoder::new().encode_images(&images, &mut data).unwrap();
+
+        assert_eq!(
+            ImageFormat::guess_format(ZCursor::new(&data)).map(|x| x.0),
+            Some(ImageFormat::ICO)
+        );
+        let mut decoder =
+            IcoDecoder::try_new(ZCursor::new(&data), DecoderOptions::default()).unwrap();
+        let decoded = decoder.decode_all().unwrap();
+
+        assert!(!decoder.entries()[0].is_png);
+        assert!(decoder.entries()[2].is_png);
+
+        for (expected, image) in images.iter().zip(decoded.iter()) {
+            assert_eq!(expected.dimensions(), image.dimensions());
+            assert_eq!(
+                expected.flatten_frames::<u8>(),
+                image.flatten_frames::<u8>()
+            );
+        }
+        // size selection
+        let options = DecoderOptions::default().ico_set_size(40);
+        let image = Image::read(ZCursor::new(&data), options).unwrap();
+        assert_eq!(image.dimensions(), (48, 48));
+
+        let image = Image::read(ZCursor::new(&data), DecoderOptions::default()).unwrap();
+        assert_eq!(image.dimensions(), (256, 256));
+    }
+
+    #[test]
+    fn test_ico_palette_and_mask() {
+        // 8x2 1 bit image, top row black, bottom row white with its right half masked out
+        let mut data = vec![0, 0, 1, 0, 1, 0, 8, 2, 2, 0, 1, 0, 1, 0];
+        data.extend_from_slice(&(40_u32 + 8 + 8 + 8).to_le_bytes());
+        data.extend_from_slice(&22_u32.to_le_bytes());
+
+        data.extend_from_slice(&[40, 0, 0, 0, 8, 0, 0, 0, 4, 0, 0, 0, 1, 0, 1, 0]);
+        data.extend_from_slice(&[0; 24]);
+        // palette
+        data.extend_from_slice(&[0, 0, 0, 0, 255, 255, 255, 0]);
+        // pixels and mask, bottom up
+        data.extend_from_slice(&[0xFF, 0, 0, 0, 0x00, 0, 0, 0]);
+        data.extend_from_slice(&[0x0F, 0, 0, 0, 0x00, 0, 0, 0]);
+
+        let image = Image::read(ZCursor::new(&data), DecoderOptions::default()).unwrap();
+        let pixels = &image.flatten_frames::<u8>()[0];
+
+        assert_eq!(image.dimensions(), (8, 2));
+        assert!(pixels[..32].chunks_exact(4).all(|x| x == [0, 0, 0, 255]));
+        assert!(pixels[32..48]
+            .chunks_exact(4)
+            .all(|x| x == [255, 255, 255, 255]));
+        assert!(pixels[48..].chunks_exact(4).all(|x| x[3] == 0));
+    }
+
+    #[test]
+    fn test_cur_hotspot() {
+        let image = Image::fill(200_u8, ColorSpace::RGB, 32, 32);
+        let mut data = vec![];
+
+        let mut encoder = IcoEncoder::new();
+        encoder.set_cursor_hotspot(3, 5);
+        crate::traits::EncoderTrait::encode(&mut encoder, &image, &mut data).unwrap();
+
+        let mut decoder =
+            IcoDecoder::try_new(ZCursor::new(&data), DecoderOptions::default()).unwrap();
+        let image = decoder.decode_entry(0).unwrap();
+
+        assert!(decoder.is_cursor());
+        assert_eq!(decoder.entries()[0].hotspot, Some((3, 5)));
+        assert_eq!(image.colorspace(), ColorSpace::RGBA);
+        assert!(image.flatten_frames::<u8>()[0]
+            .chunks_exact(4)
+            .all(|x| x == [200, 200, 200, 255]));
+    }
+}
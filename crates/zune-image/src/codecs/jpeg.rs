@@ -14,12 +14,15 @@
 //!
 //! The decoder and encoder both support metadata extraction and saving.
 //!
-use jpeg_encoder::{ColorType, EncodingError, JfifWrite};
+use jpeg_encoder::{ColorType, Density, EncodingError, JfifWrite, SamplingFactor};
 use zune_core::bit_depth::BitDepth;
 use zune_core::bytestream::{ZByteIoError, ZByteReaderTrait, ZByteWriterTrait, ZWriter};
 use zune_core::colorspace::ColorSpace;
-use zune_core::log::warn;
-use zune_core::options::EncoderOptions;
+use zune_core::errors::ZErrorCodeTrait;
+use zune_core::log::{trace, warn};
+use zune_core::options::{ChromaSubsampling, EncoderOptions};
+use zune_core::result::DecodingResult;
+use zune_core::warnings::DecodeWarning;
 use zune_jpeg::errors::DecodeErrors;
 pub use zune_jpeg::{ImageInfo, JpegDecoder};
 
@@ -45,13 +48,25 @@ impl<T: ZByteReaderTrait> DecoderTrait for zune_jpeg::JpegDecoder<T> {
         let metadata = self.read_headers()?.unwrap();
 
         let pixels = self
-            .decode()
+            .decode_any()
             .map_err(<DecodeErrors as Into<ImageErrors>>::into)?;
 
         let colorspace = self.output_colorspace().unwrap();
         let (width, height) = self.dimensions().unwrap();
 
-        let mut image = Image::from_u8(&pixels, width, height, colorspace);
+        let mut image = match pixels {
+            DecodingResult::U8(data) => Image::from_u8(&data, width, height, colorspace),
+            DecodingResult::U16(mut data) => {
+                // 12-bit and lossless images, stretch samples to the full 16-bit range
+                let max_value = (1_u32 << self.info().unwrap().precision) - 1;
+
+                for sample in &mut data {
+                    *sample = ((u32::from(*sample) * 65535 + max_value / 2) / max_value) as u16;
+                }
+                Image::from_u16(&data, width, height, colorspace)
+            }
+            _ => unreachable!()
+        };
         image.metadata = metadata;
         image.metadata.colorspace = self.output_colorspace().unwrap();
         Ok(image)
@@ -69,6 +84,10 @@ impl<T: ZByteReaderTrait> DecoderTrait for zune_jpeg::JpegDecoder<T> {
         "JPEG decoder"
     }
 
+    fn warnings(&self) -> &[DecodeWarning] {
+        self.warnings()
+    }
+
     fn read_headers(&mut self) -> Result<Option<ImageMetadata>, crate::errors::ImageErrors> {
         self.decode_headers()
             .map_err(<DecodeErrors as Into<ImageErrors>>::into)?;
@@ -78,7 +97,7 @@ impl<T: ZByteReaderTrait> DecoderTrait for zune_jpeg::JpegDecoder<T> {
         let mut metadata = ImageMetadata {
             format: Some(ImageFormat::JPEG),
             colorspace: self.input_colorspace().unwrap(),
-            depth: BitDepth::Eight,
+            depth: self.bit_depth().unwrap_or(BitDepth::Eight),
             width: width,
             height: height,
             ..Default::default()
@@ -90,9 +109,22 @@ impl<T: ZByteReaderTrait> DecoderTrait for zune_jpeg::JpegDecoder<T> {
                 metadata.parse_raw_exif(exif)
             }
         }
+        if let Some(exif) = self.exif() {
+            metadata.parse_raw_exif_orientation(exif);
+        }
         if let Some(icc) = self.icc_profile() {
             metadata.set_icc_chunk(icc);
         }
+        if let Some(info) = self.info() {
+            if let Some(subsampling) =
+                ChromaSubsampling::from_factors(info.h_subsampling, info.v_subsampling)
+            {
+                metadata.set_chroma_subsampling(subsampling);
+            }
+            if let Some((x_dpi, y_dpi)) = info.dpi() {
+                metadata.set_dpi(x_dpi, y_dpi);
+            }
+        }
 
         Ok(Some(metadata))
     }
@@ -102,7 +134,7 @@ impl From<zune_jpeg::errors::DecodeErrors> for ImageErrors {
     fn from(from: zune_jpeg::errors::DecodeErrors) -> Self {
         let err = format!("jpg: {from:?}");
 
-        ImageErrors::ImageDecodeErrors(err)
+        ImageErrors::CodecErrors(from.code(), err)
     }
 }
 // Okay I just need to really appreciate jpeg-encoder crate
@@ -126,15 +158,10 @@ impl JpegEncoder {
             options: Some(options)
         }
     }
-}
-
-impl EncoderTrait for JpegEncoder {
-    fn name(&self) -> &'static str {
-        "jpeg-encoder(vstroebel)"
-    }
 
-    fn encode_inner<T: ZByteWriterTrait>(
-        &mut self, image: &Image, sink: T
+    /// Encode the image with the given options, `options.quality()` is used as the quality
+    fn encode_with_options<T: ZByteWriterTrait>(
+        &self, image: &Image, options: EncoderOptions, sink: T
     ) -> Result<usize, ImageErrors> {
         assert_eq!(
             image.depth(),
@@ -159,9 +186,6 @@ impl EncoderTrait for JpegEncoder {
             }
             let mut writer = ZWriter::new(sink);
             let temp_c = TempVt { inner: &mut writer };
-            // create space for our encoder
-
-            let options = create_options_for_encoder(self.options, image);
 
             // create encoder finally
             // vec<u8> supports write so we use that as our encoder
@@ -171,29 +195,37 @@ impl EncoderTrait for JpegEncoder {
             encoder.set_progressive(options.jpeg_encode_progressive());
             encoder.set_optimized_huffman_tables(options.jpeg_optimized_huffman_tables());
 
+            let subsampling = match options.jpeg_subsampling() {
+                ChromaSubsampling::SameAsSource => image
+                    .metadata
+                    .chroma_subsampling()
+                    .unwrap_or(ChromaSubsampling::Auto),
+                subsampling => subsampling
+            };
+            if let Some((h, v)) = subsampling.factors() {
+                if let Some(factor) = SamplingFactor::from_factors(h, v) {
+                    encoder.set_sampling_factor(factor);
+                }
+            }
+
+            if let Some((x_dpi, y_dpi)) = image.metadata.dpi() {
+                // JFIF stores whole dots per inch
+                let to_u16 = |dpi: f32| dpi.round().clamp(1.0, f32::from(u16::MAX)) as u16;
+
+                encoder.set_density(Density::Inch {
+                    x: to_u16(x_dpi),
+                    y: to_u16(y_dpi)
+                });
+            }
+
             #[cfg(feature = "metadata")]
-            {
-                use exif::experimental::Writer;
-
-                if options.strip_metadata() {
-                    // explicit :)
-                } else if let Some(metadata) = &image.metadata.exif {
-                    let mut writer = Writer::new();
-                    // write first tags for exif
-                    let mut buf = std::io::Cursor::new(b"Exif\x00\x00".to_vec());
-                    // set buffer position to be bytes written, to ensure we don't overwrite anything
-                    buf.set_position(6);
-
-                    for metadatum in metadata {
-                        writer.push_field(metadatum);
-                    }
-                    let result = writer.write(&mut buf, false);
-                    if result.is_ok() {
-                        // add the exif tag to APP1 segment
-                        encoder.add_app_segment(1, buf.get_ref())?;
-                    } else {
-                        warn!("Writing exif failed {:?}", result);
-                    }
+            if !options.strip_metadata() {
+                if let Some(exif) = image.metadata.exif_bytes() {
+                    // APP1 exif segments start with the exif identifier
+                    let mut segment = b"Exif\x00\x00".to_vec();
+                    segment.extend_from_slice(&exif);
+
+                    encoder.add_app_segment(1, &segment)?;
                 }
             }
 
@@ -209,6 +241,67 @@ impl EncoderTrait for JpegEncoder {
         }
     }
 
+    /// Binary search for the highest quality (up to the configured quality)
+    /// whose output fits in `target` bytes and write that to the sink
+    fn encode_to_target_size<T: ZByteWriterTrait>(
+        &self, image: &Image, options: EncoderOptions, target: usize, sink: T
+    ) -> Result<usize, ImageErrors> {
+        let mut low = 1;
+        let mut high = options.quality().max(1);
+        let mut best = None;
+
+        while low <= high {
+            let quality = low + (high - low) / 2;
+            let mut output = vec![];
+
+            self.encode_with_options(image, options.set_quality(quality), &mut output)?;
+
+            trace!(
+                "Quality {quality} gives {} bytes, target {target}",
+                output.len()
+            );
+
+            if output.len() <= target {
+                best = Some(output);
+                low = quality + 1;
+            } else {
+                high = quality - 1;
+            }
+        }
+        let output = match best {
+            Some(output) => output,
+            None => {
+                warn!("Could not fit image in {target} bytes, encoding with the lowest quality");
+                let mut output = vec![];
+                self.encode_with_options(image, options.set_quality(1), &mut output)?;
+                output
+            }
+        };
+        let mut writer = ZWriter::new(sink);
+        writer
+            .write_all(&output)
+            .map_err(|e| ImgEncodeErrors::Generic(format!("{e:?}")))?;
+
+        Ok(writer.bytes_written())
+    }
+}
+
+impl EncoderTrait for JpegEncoder {
+    fn name(&self) -> &'static str {
+        "jpeg-encoder(vstroebel)"
+    }
+
+    fn encode_inner<T: ZByteWriterTrait>(
+        &mut self, image: &Image, sink: T
+    ) -> Result<usize, ImageErrors> {
+        let options = create_options_for_encoder(self.options, image);
+
+        if let Some(target) = options.target_size() {
+            return self.encode_to_target_size(image, options, target, sink);
+        }
+        self.encode_with_options(image, options, sink)
+    }
+
     fn supported_colorspaces(&self) -> &'static [ColorSpace] {
         // should match with the
         // jpeg-encoder crate
@@ -279,3 +372,147 @@ where
         Ok(self.output_buffer_size().unwrap())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::{ChromaSubsampling, DecoderOptions, EncoderOptions};
+
+    use crate::codecs::jpeg::JpegEncoder;
+    use crate::image::Image;
+    use crate::traits::EncoderTrait;
+
+    #[test]
+    fn test_jpeg_encode_target_size() {
+        let (width, height) = (64, 64);
+        let pixels: Vec<u8> = (0..width * height * 3)
+            .map(|x| ((x * 7919) % 251) as u8)
+            .collect();
+        let image = Image::from_u8(&pixels, width, height, ColorSpace::RGB);
+
+        let mut full = vec![];
+        JpegEncoder::new_with_options(EncoderOptions::default().set_quality(90))
+            .encode(&image, &mut full)
+            .unwrap();
+
+        let target = full.len() / 2;
+        let options = EncoderOptions::default()
+            .set_quality(90)
+            .set_target_size(target);
+        let mut output = vec![];
+        JpegEncoder::new_with_options(options)
+            .encode(&image, &mut output)
+            .unwrap();
+
+        assert!(output.len() <= target);
+        assert!(!output.is_empty());
+    }
+
+    #[test]
+    fn test_jpeg_subsampling_same_as_source() {
+        let pixels: Vec<u8> = (0..32 * 32 * 3).map(|x| (x % 256) as u8).collect();
+        let image = Image::from_u8(&pixels, 32, 32, ColorSpace::RGB);
+
+        let mut first = vec![];
+        let options = EncoderOptions::default().set_jpeg_subsampling(ChromaSubsampling::Yuv422);
+        JpegEncoder::new_with_options(options)
+            .encode(&image, &mut first)
+            .unwrap();
+
+        let decoded = Image::read(ZCursor::new(&first), DecoderOptions::default()).unwrap();
+        assert_eq!(
+            decoded.metadata().chroma_subsampling(),
+            Some(ChromaSubsampling::Yuv422)
+        );
+
+        let mut second = vec![];
+        let options =
+            EncoderOptions::default().set_jpeg_subsampling(ChromaSubsampling::SameAsSource);
+        JpegEncoder::new_with_options(options)
+            .encode(&decoded, &mut second)
+            .unwrap();
+
+        let re_decoded = Image::read(ZCursor::new(&second), DecoderOptions::default()).unwrap();
+        assert_eq!(
+            re_decoded.metadata().chroma_subsampling(),
+            Some(ChromaSubsampling::Yuv422)
+        );
+    }
+
+    #[test]
+    fn test_jpeg_dpi() {
+        let image = Image::fill(128_u8, ColorSpace::RGB, 16, 16);
+        let mut data = vec![];
+        JpegEncoder::new().encode(&image, &mut data).unwrap();
+
+        // SOI, APP0 marker and length, then the JFIF identifier and version
+        assert_eq!(&data[6..11], b"JFIF\0");
+        // units in dots per centimeter, 100 x 200
+        data[13..18].copy_from_slice(&[2, 0, 100, 0, 200]);
+
+        let decoded = Image::read(ZCursor::new(&data), DecoderOptions::default()).unwrap();
+        let (x_dpi, y_dpi) = decoded.metadata().dpi().unwrap();
+        assert!((x_dpi - 254.0).abs() < 0.01);
+        assert!((y_dpi - 508.0).abs() < 0.01);
+    }
+
+    #[test]
+    #[cfg(feature = "metadata")]
+    fn test_jpeg_exif_round_trip() {
+        let mut image = Image::fill(128_u8, ColorSpace::RGB, 16, 16);
+        image.metadata_mut().set_copyright("zune-image");
+        image.metadata_mut().set_orientation(6);
+
+        let mut data = vec![];
+        JpegEncoder::new().encode(&image, &mut data).unwrap();
+
+        let decoded = Image::read(ZCursor::new(&data), DecoderOptions::default()).unwrap();
+        assert_eq!(
+            decoded.metadata().copyright().as_deref(),
+            Some("zune-image")
+        );
+        assert_eq!(decoded.metadata().orientation(), Some(6));
+
+        let mut stripped = vec![];
+        JpegEncoder::new_with_options(EncoderOptions::default().set_strip_metadata(true))
+            .encode(&image, &mut stripped)
+            .unwrap();
+
+        let decoded = Image::read(ZCursor::new(&stripped), DecoderOptions::default()).unwrap();
+        assert!(decoded.metadata().exif().is_none());
+        assert!(decoded.metadata().orientation().is_none());
+    }
+
+    #[test]
+    fn test_jpeg_dpi_round_trip() {
+        let mut image = Image::fill(128_u8, ColorSpace::RGB, 16, 16);
+        image.metadata_mut().set_dpi(300.0, 72.0);
+
+        let mut data = vec![];
+        JpegEncoder::new().encode(&image, &mut data).unwrap();
+
+        let decoded = Image::read(ZCursor::new(&data), DecoderOptions::default()).unwrap();
+        assert_eq!(decoded.metadata().dpi(), Some((300.0, 72.0)));
+    }
+
+    #[test]
+    fn test_jpeg_high_precision_decodes_to_u16() {
+        let mut file = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        file.pop();
+        file.pop();
+        let data =
+            std::fs::read(file.join("test-images/jpeg/lossless_12bit_grayscale.jpg")).unwrap();
+
+        let decoded = Image::read(ZCursor::new(&data), DecoderOptions::default()).unwrap();
+        assert_eq!(decoded.depth(), BitDepth::Sixteen);
+
+        let samples = decoded.frames_ref()[0].channels_ref(ColorSpace::Luma, false)[0]
+            .reinterpret_as::<u16>()
+            .unwrap()
+            .to_vec();
+        // samples are stretched from 12 to 16 bits
+        assert!(samples.iter().any(|x| *x > 4095));
+    }
+}
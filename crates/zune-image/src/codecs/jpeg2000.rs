@@ -0,0 +1,271 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+#![cfg_attr(feature = "docs", doc(cfg(feature = "jpeg2000")))]
+#![cfg(feature = "jpeg2000")]
+//! JPEG 2000 decoding support
+//!
+//! This uses the delegate library [`hayro-jpeg2000`](hayro_jpeg2000) for decoding
+//! both JP2 files and raw J2K code streams.
+//!
+//! Images with components of at most 8 bits are returned as 8 bit images, anything deeper
+//! (e.g 12 bit medical images) is scaled to 16 bits. Gray, RGB and CMYK images are supported,
+//! with an optional alpha channel for the first two. YCC and CIELab images are converted to
+//! RGB by the delegate library, and embedded ICC profiles are stored in the image metadata.
+pub use hayro_jpeg2000;
+use hayro_jpeg2000::{DecodeError, DecodeSettings, DecoderContext};
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::colorspace::ColorSpace;
+use zune_core::errors::ZErrorCode;
+use zune_core::log::trace;
+use zune_core::options::DecoderOptions;
+
+use crate::codecs::ImageFormat;
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::metadata::ImageMetadata;
+use crate::traits::DecoderTrait;
+
+/// A JPEG 2000 decoder
+///
+/// The whole file is read into memory on creation, headers are parsed
+/// lazily on the first call to `read_headers` or `decode`
+pub struct Jpeg2000Decoder {
+    data:        Vec<u8>,
+    options:     DecoderOptions,
+    /// Parsed header information, (width, height, colorspace and depth)
+    image_info:  Option<(usize, usize, ColorSpace, BitDepth)>,
+    icc_profile: Option<Vec<u8>>
+}
+
+impl Jpeg2000Decoder {
+    pub fn try_new<T: ZByteReaderTrait>(
+        mut source: T, options: DecoderOptions
+    ) -> Result<Jpeg2000Decoder, ImageErrors> {
+        let mut data = vec![];
+        source
+            .read_remaining(&mut data)
+            .map_err(|x| ImageErrors::ImageDecodeErrors(format!("jpeg2000: {:?}", x)))?;
+
+        Ok(Jpeg2000Decoder {
+            data,
+            options,
+            image_info: None,
+            icc_profile: None
+        })
+    }
+
+    fn decode_headers(&mut self) -> Result<(), ImageErrors> {
+        if self.image_info.is_some() {
+            return Ok(());
+        }
+        let image = hayro_jpeg2000::Image::new(&self.data, &DecodeSettings::default())?;
+
+        let (width, height) = (image.width() as usize, image.height() as usize);
+
+        trace!("Image dimensions: ({},{})", width, height);
+        trace!("Image colorspace: {:?}", image.color_space());
+        trace!("Bit depth: {}", image.original_bit_depth());
+
+        if width > self.options.max_width() {
+            let msg = format!(
+                "Image width {}, greater than max set width {}",
+                width,
+                self.options.max_width()
+            );
+            return Err(ImageErrors::CodecErrors(ZErrorCode::LimitsExceeded, msg));
+        }
+        if height > self.options.max_height() {
+            let msg = format!(
+                "Image height {}, greater than max set height {}",
+                height,
+                self.options.max_height()
+            );
+            return Err(ImageErrors::CodecErrors(ZErrorCode::LimitsExceeded, msg));
+        }
+        let channels = image.color_space().num_channels();
+
+        let colorspace = match (image.color_space(), channels, image.has_alpha()) {
+            (hayro_jpeg2000::ColorSpace::CMYK, _, false) => ColorSpace::CMYK,
+            (_, 1, false) => ColorSpace::Luma,
+            (_, 1, true) => ColorSpace::LumaA,
+            (_, 3, false) => ColorSpace::RGB,
+            (_, 3, true) => ColorSpace::RGBA,
+            (_, 4, false) => ColorSpace::CMYK,
+            (color, _, alpha) => {
+                return Err(ImageErrors::CodecErrors(
+                    ZErrorCode::Unsupported,
+                    format!("jpeg2000: Unsupported colorspace {:?}, alpha={}", color, alpha)
+                ));
+            }
+        };
+        let depth =
+            if image.original_bit_depth() > 8 { BitDepth::Sixteen } else { BitDepth::Eight };
+        if let hayro_jpeg2000::ColorSpace::Icc { profile, .. } = image.color_space() {
+            self.icc_profile = Some(profile.clone());
+        }
+        self.image_info = Some((width, height, colorspace, depth));
+
+        Ok(())
+    }
+}
+
+impl DecoderTrait for Jpeg2000Decoder {
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let metadata = self.read_headers()?.unwrap();
+        let (width, height, colorspace, depth) = self.image_info.unwrap();
+
+        let image = hayro_jpeg2000::Image::new(&self.data, &DecodeSettings::default())?;
+        let mut context = DecoderContext::default();
+        let decoded = image.decode(&mut context)?;
+
+        let components = decoded.components();
+
+        if components.len() != colorspace.num_components() {
+            return Err(ImageErrors::ImageDecodeErrors(format!(
+                "jpeg2000: Expected {} components for {:?} but found {}",
+                colorspace.num_components(),
+                colorspace,
+                components.len()
+            )));
+        }
+        let mut image = if depth == BitDepth::Sixteen {
+            // components may have different precisions, scale each to the full
+            // 16 bit range
+            let mut pixels = vec![0_u16; width * height * components.len()];
+
+            for (c, component) in components.iter().enumerate() {
+                let scale = 65535.0 / ((1_u32 << component.bit_depth()) - 1) as f32;
+
+                for (out, sample) in pixels
+                    .iter_mut()
+                    .skip(c)
+                    .step_by(components.len())
+                    .zip(component.samples())
+                {
+                    *out = (sample * scale).round().clamp(0.0, 65535.0) as u16;
+                }
+            }
+            Image::from_u16(&pixels, width, height, colorspace)
+        } else {
+            Image::from_u8(&decoded.data_u8(), width, height, colorspace)
+        };
+        image.metadata = metadata;
+
+        Ok(image)
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        self.image_info.map(|x| (x.0, x.1))
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        self.image_info.map(|x| x.2).unwrap_or(ColorSpace::Unknown)
+    }
+
+    fn name(&self) -> &'static str {
+        "jpeg2000-decoder (hayro-jpeg2000)"
+    }
+
+    fn read_headers(&mut self) -> Result<Option<ImageMetadata>, ImageErrors> {
+        self.decode_headers()?;
+
+        let (width, height, colorspace, depth) = self.image_info.unwrap();
+
+        let mut metadata = ImageMetadata {
+            format: Some(ImageFormat::JPEG2000),
+            colorspace,
+            depth,
+            width,
+            height,
+            ..Default::default()
+        };
+        if let Some(profile) = &self.icc_profile {
+            metadata.set_icc_chunk(profile.clone());
+        }
+
+        Ok(Some(metadata))
+    }
+}
+
+impl From<DecodeError> for ImageErrors {
+    fn from(value: DecodeError) -> Self {
+        Self::ImageDecodeErrors(format!("jpeg2000: {}", value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::codecs::ImageFormat;
+    use crate::image::Image;
+
+    /// Create a single tile code stream with no wavelet decomposition and
+    /// empty packets, so every sample decodes to the DC level shift, i.e. half
+    /// the range of the component
+    fn create_j2k(width: u32, height: u32, components: u16, precision: u8) -> Vec<u8> {
+        let mut data = vec![0xFF, 0x4F];
+        // SIZ
+        data.extend_from_slice(&[0xFF, 0x51]);
+        data.extend_from_slice(&(38 + 3 * components).to_be_bytes());
+        data.extend_from_slice(&[0, 0]);
+        for value in [width, height, 0, 0, width, height, 0, 0] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        data.extend_from_slice(&components.to_be_bytes());
+        for _ in 0..components {
+            data.extend_from_slice(&[precision - 1, 1, 1]);
+        }
+        // COD, LRCP progression, one layer, no decomposition and 64x64 5/3 code blocks
+        data.extend_from_slice(&[0xFF, 0x52, 0, 12, 0, 0, 0, 1, 0, 0, 4, 4, 0, 1]);
+        // QCD, no quantization
+        data.extend_from_slice(&[0xFF, 0x5C, 0, 4, 0x40, (precision + 2) << 3]);
+        // SOT followed by SOD and one empty packet per component
+        let tile_length = 12 + 2 + u32::from(components);
+        data.extend_from_slice(&[0xFF, 0x90, 0, 10, 0, 0]);
+        data.extend_from_slice(&tile_length.to_be_bytes());
+        data.extend_from_slice(&[0, 1, 0xFF, 0x93]);
+        data.resize(data.len() + usize::from(components), 0);
+        // EOC
+        data.extend_from_slice(&[0xFF, 0xD9]);
+        data
+    }
+
+    #[test]
+    fn test_jpeg2000_decode_8_bit() {
+        let data = create_j2k(7, 5, 1, 8);
+
+        assert_eq!(
+            ImageFormat::guess_format(ZCursor::new(&data)).map(|x| x.0),
+            Some(ImageFormat::JPEG2000)
+        );
+        let image = Image::read(ZCursor::new(&data), Default::default()).unwrap();
+
+        assert_eq!(image.dimensions(), (7, 5));
+        assert_eq!(image.colorspace(), ColorSpace::Luma);
+        assert_eq!(image.depth(), BitDepth::Eight);
+        assert!(image.flatten_to_u8()[0].iter().all(|x| *x == 128));
+    }
+
+    #[test]
+    fn test_jpeg2000_decode_16_bit_rgb() {
+        let data = create_j2k(4, 3, 3, 16);
+        let image = Image::read(ZCursor::new(&data), Default::default()).unwrap();
+
+        assert_eq!(image.dimensions(), (4, 3));
+        assert_eq!(image.colorspace(), ColorSpace::RGB);
+        assert_eq!(image.depth(), BitDepth::Sixteen);
+
+        let pixels = &image.flatten_frames::<u16>()[0];
+        assert_eq!(pixels.len(), 4 * 3 * 3);
+        assert!(pixels.iter().all(|x| *x == 32768));
+    }
+}
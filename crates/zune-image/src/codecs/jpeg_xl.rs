@@ -24,9 +24,10 @@ use std::time::Duration;
 pub use jxl_oxide;
 use jxl_oxide::{JxlImage, PixelFormat, RenderResult};
 use zune_core::bit_depth::{BitDepth, BitType};
-use zune_core::bytestream::ZByteWriterTrait;
+use zune_core::bytestream::{ZByteWriterTrait, ZWriter};
 use zune_core::colorspace::ColorSpace;
-use zune_core::log::trace;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
+use zune_core::log::{trace, warn};
 use zune_core::options::{DecoderOptions, EncoderOptions};
 pub use zune_jpegxl::*;
 
@@ -73,6 +74,38 @@ impl EncoderTrait for JxlEncoder {
 
         let data = &image.to_u8()[0];
 
+        if let Some(target) = options.target_size() {
+            // the encoder is lossless, so the only knob we have is effort,
+            // try the configured effort and if that doesn't fit, the maximum effort
+            let mut output = vec![];
+            JxlSimpleEncoder::new(data, options)
+                .encode(&mut output)
+                .map_err(<JxlEncodeErrors as Into<ImgEncodeErrors>>::into)?;
+
+            if output.len() > target {
+                trace!("Output of {} bytes exceeds target of {target} bytes, retrying with maximum effort", output.len());
+                let mut max_output = vec![];
+                JxlSimpleEncoder::new(data, options.set_effort(127))
+                    .encode(&mut max_output)
+                    .map_err(<JxlEncodeErrors as Into<ImgEncodeErrors>>::into)?;
+
+                if max_output.len() < output.len() {
+                    output = max_output;
+                }
+                if output.len() > target {
+                    warn!(
+                        "Lossless jxl encoder cannot fit image in {target} bytes, output is {} bytes",
+                        output.len()
+                    );
+                }
+            }
+            let mut writer = ZWriter::new(sink);
+            writer
+                .write_all(&output)
+                .map_err(|e| ImgEncodeErrors::Generic(format!("{e:?}")))?;
+            return Ok(writer.bytes_written());
+        }
+
         let encoder = JxlSimpleEncoder::new(data, options);
 
         let data = encoder
@@ -113,7 +146,7 @@ impl EncoderTrait for JxlEncoder {
 
 impl From<JxlEncodeErrors> for ImgEncodeErrors {
     fn from(value: JxlEncodeErrors) -> Self {
-        ImgEncodeErrors::ImageEncodeErrors(format!("{:?}", value))
+        ImgEncodeErrors::CodecErrors(value.code(), format!("{:?}", value))
     }
 }
 
@@ -146,10 +179,13 @@ impl DecoderTrait for JxlDecoder {
         let mut total_frames = vec![];
 
         if color == ColorSpace::Unknown {
-            return Err(ImageErrors::ImageDecodeErrors(format!(
-                "Encountered unknown/unsupported colorspace {:?}",
-                self.inner.pixel_format()
-            )));
+            return Err(ImageErrors::CodecErrors(
+                ZErrorCode::Unsupported,
+                format!(
+                    "Encountered unknown/unsupported colorspace {:?}",
+                    self.inner.pixel_format()
+                )
+            ));
         }
         trace!("Image colorspace: {:?}", color);
         trace!("Image dimensions: ({},{})", w, h);
@@ -160,7 +196,7 @@ impl DecoderTrait for JxlDecoder {
                 w,
                 self.options.max_width()
             );
-            return Err(ImageErrors::ImageDecodeErrors(msg));
+            return Err(ImageErrors::CodecErrors(ZErrorCode::LimitsExceeded, msg));
         }
         if h > self.options.max_height() {
             let msg = format!(
@@ -168,7 +204,7 @@ impl DecoderTrait for JxlDecoder {
                 h,
                 self.options.max_height()
             );
-            return Err(ImageErrors::ImageDecodeErrors(msg));
+            return Err(ImageErrors::CodecErrors(ZErrorCode::LimitsExceeded, msg));
         }
 
         let taken = if self.options.jxl_decode_animated() {
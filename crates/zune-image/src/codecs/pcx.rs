@@ -0,0 +1,408 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+#![cfg_attr(feature = "docs", doc(cfg(feature = "legacy-formats")))]
+#![cfg(feature = "legacy-formats")]
+//! PCX (ZSoft Paintbrush) decoding support
+//!
+//! Supported images
+//! - Uncompressed and run length encoded images
+//! - 1 bit monochrome images, decoded to Luma
+//! - 1, 2 and 4 bit paletted images using the 16 color header palette, decoded to RGB
+//! - 8 bit paletted images using the 256 color palette at the end of the file, decoded to RGB
+//! - 24 bit and 32 bit images stored as 3 or 4 planes, decoded to RGB and RGBA
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::colorspace::ColorSpace;
+use zune_core::errors::ZErrorCode;
+use zune_core::log::trace;
+use zune_core::options::DecoderOptions;
+
+use crate::codecs::ImageFormat;
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::metadata::ImageMetadata;
+use crate::traits::DecoderTrait;
+
+/// Size of the PCX header
+pub const PCX_HEADER_SIZE: usize = 128;
+const PCX_MAGIC: u8 = 0x0A;
+/// Marker preceding the 256 color palette at the end of the file
+const VGA_PALETTE_MARKER: u8 = 0x0C;
+const VGA_PALETTE_SIZE: usize = 256 * 3;
+
+/// Probe some bytes to see if they consist of a PCX file
+///
+/// This needs the whole header, i.e [`PCX_HEADER_SIZE`] bytes
+pub fn probe_pcx(bytes: &[u8]) -> bool {
+    if let Some(header) = bytes.get(..PCX_HEADER_SIZE) {
+        let (x_min, y_min) = (read_u16(header, 4), read_u16(header, 6));
+        let (x_max, y_max) = (read_u16(header, 8), read_u16(header, 10));
+
+        return header[0] == PCX_MAGIC
+            && matches!(header[1], 0 | 2 | 3 | 4 | 5)
+            && header[2] <= 1
+            && matches!(header[3], 1 | 2 | 4 | 8)
+            && (1..=4).contains(&header[65])
+            && x_min <= x_max
+            && y_min <= y_max;
+    }
+    false
+}
+
+#[inline]
+fn read_u16(bytes: &[u8], position: usize) -> u16 {
+    u16::from_le_bytes([bytes[position], bytes[position + 1]])
+}
+
+/// A PCX decoder
+///
+/// The whole file is read into memory on creation since the
+/// 256 color palette is stored after the pixels
+pub struct PcxDecoder {
+    data:            Vec<u8>,
+    options:         DecoderOptions,
+    width:           usize,
+    height:          usize,
+    bits_per_plane:  usize,
+    planes:          usize,
+    bytes_per_line:  usize,
+    is_rle:          bool,
+    decoded_headers: bool
+}
+
+impl PcxDecoder {
+    pub fn try_new<T: ZByteReaderTrait>(
+        mut source: T, options: DecoderOptions
+    ) -> Result<PcxDecoder, ImageErrors> {
+        let mut data = vec![];
+        source
+            .read_remaining(&mut data)
+            .map_err(|x| ImageErrors::ImageDecodeErrors(format!("pcx: {:?}", x)))?;
+
+        Ok(PcxDecoder {
+            data,
+            options,
+            width: 0,
+            height: 0,
+            bits_per_plane: 0,
+            planes: 0,
+            bytes_per_line: 0,
+            is_rle: false,
+            decoded_headers: false
+        })
+    }
+
+    /// Read the PCX header
+    pub fn decode_headers(&mut self) -> Result<(), ImageErrors> {
+        if self.decoded_headers {
+            return Ok(());
+        }
+        if !probe_pcx(&self.data) {
+            return Err(ImageErrors::ImageDecodeErrors(
+                "pcx: Invalid or unsupported header".to_string()
+            ));
+        }
+        let header = &self.data[..PCX_HEADER_SIZE];
+
+        self.width = usize::from(read_u16(header, 8) - read_u16(header, 4)) + 1;
+        self.height = usize::from(read_u16(header, 10) - read_u16(header, 6)) + 1;
+        self.is_rle = header[2] == 1;
+        self.bits_per_plane = usize::from(header[3]);
+        self.planes = usize::from(header[65]);
+        self.bytes_per_line = usize::from(read_u16(header, 66));
+
+        trace!("Width: {}", self.width);
+        trace!("Height: {}", self.height);
+        trace!("Bits per plane: {}", self.bits_per_plane);
+        trace!("Planes: {}", self.planes);
+        trace!("RLE: {}", self.is_rle);
+
+        if self.width > self.options.max_width() || self.height > self.options.max_height() {
+            return Err(ImageErrors::CodecErrors(
+                ZErrorCode::LimitsExceeded,
+                format!(
+                    "pcx: Image dimensions ({},{}) greater than max set dimensions ({},{})",
+                    self.width,
+                    self.height,
+                    self.options.max_width(),
+                    self.options.max_height()
+                )
+            ));
+        }
+        // paletted images index either the 16 color header palette or the 256
+        // color palette at the end of the file, anything else is true color
+        let supported = match (self.bits_per_plane, self.planes) {
+            (8, 1 | 3 | 4) => true,
+            (bits, planes) => bits * planes <= 4
+        };
+        if !supported {
+            return Err(ImageErrors::CodecErrors(
+                ZErrorCode::Unsupported,
+                format!(
+                    "pcx: Unsupported combination of {} bits per plane and {} planes",
+                    self.bits_per_plane, self.planes
+                )
+            ));
+        }
+        if self.bytes_per_line * 8 < self.width * self.bits_per_plane {
+            return Err(ImageErrors::ImageDecodeErrors(format!(
+                "pcx: Bytes per line {} too small for width {}",
+                self.bytes_per_line, self.width
+            )));
+        }
+        self.decoded_headers = true;
+
+        Ok(())
+    }
+
+    /// Return the image dimensions or none if headers aren't decoded
+    pub fn dimensions(&self) -> Option<(usize, usize)> {
+        if !self.decoded_headers {
+            return None;
+        }
+        Some((self.width, self.height))
+    }
+
+    /// Return the output colorspace or none if headers aren't decoded
+    pub fn colorspace(&self) -> Option<ColorSpace> {
+        if !self.decoded_headers {
+            return None;
+        }
+        match (self.bits_per_plane, self.planes) {
+            (1, 1) => Some(ColorSpace::Luma),
+            (8, 4) => Some(ColorSpace::RGBA),
+            _ => Some(ColorSpace::RGB)
+        }
+    }
+
+    /// Decode the image returning 8 bit pixels in the colorspace
+    /// returned by [`colorspace`](Self::colorspace)
+    pub fn decode_pixels(&mut self) -> Result<Vec<u8>, ImageErrors> {
+        self.decode_headers()?;
+
+        let colorspace = self.colorspace().unwrap();
+        let line_size = self.bytes_per_line * self.planes;
+        let scanlines = self.read_scanlines(line_size)?;
+        let palette = self.palette();
+
+        let components = colorspace.num_components();
+        let mut pixels = vec![0_u8; self.width * self.height * components];
+
+        for (out, line) in pixels
+            .chunks_exact_mut(self.width * components)
+            .zip(scanlines.chunks_exact(line_size))
+        {
+            let planes: Vec<&[u8]> = line.chunks_exact(self.bytes_per_line).collect();
+
+            if self.bits_per_plane == 8 && self.planes >= 3 {
+                // true color, each plane holds one channel for the whole line
+                for (x, pixel) in out.chunks_exact_mut(components).enumerate() {
+                    for (c, value) in pixel.iter_mut().enumerate() {
+                        *value = planes[c][x];
+                    }
+                }
+                continue;
+            }
+            for (x, pixel) in out.chunks_exact_mut(components).enumerate() {
+                // combine the bits of each plane into a palette index, the
+                // first plane contains the least significant bits
+                let mut index = 0;
+
+                for (p, plane) in planes.iter().enumerate() {
+                    let bit = x * self.bits_per_plane;
+                    let shift = 8 - self.bits_per_plane - (bit % 8);
+                    let mask = (1 << self.bits_per_plane) - 1;
+                    let value = (usize::from(plane[bit / 8]) >> shift) & mask;
+
+                    index |= value << (p * self.bits_per_plane);
+                }
+                if colorspace == ColorSpace::Luma {
+                    pixel[0] = if index == 0 { 0 } else { 255 };
+                } else {
+                    pixel.copy_from_slice(&palette[index * 3..index * 3 + 3]);
+                }
+            }
+        }
+        Ok(pixels)
+    }
+
+    /// Read every scan line, decompressing if needed
+    fn read_scanlines(&self, line_size: usize) -> Result<Vec<u8>, ImageErrors> {
+        let size = line_size * self.height;
+        let data = &self.data[PCX_HEADER_SIZE..];
+
+        if !self.is_rle {
+            return data.get(..size).map(|x| x.to_vec()).ok_or_else(|| {
+                ImageErrors::ImageDecodeErrors("pcx: Truncated pixel data".to_string())
+            });
+        }
+        let mut output = Vec::with_capacity(size);
+        let mut position = 0;
+
+        // runs are allowed to cross scan lines by some encoders, so decode
+        // the whole image as one stream
+        while output.len() < size {
+            let byte = *data.get(position).ok_or_else(|| {
+                ImageErrors::ImageDecodeErrors("pcx: Truncated RLE data".to_string())
+            })?;
+            position += 1;
+
+            if byte & 0xC0 == 0xC0 {
+                let count = usize::from(byte & 0x3F);
+                let value = *data.get(position).ok_or_else(|| {
+                    ImageErrors::ImageDecodeErrors("pcx: Truncated RLE data".to_string())
+                })?;
+                position += 1;
+
+                let count = count.min(size - output.len());
+                output.resize(output.len() + count, value);
+            } else {
+                output.push(byte);
+            }
+        }
+        Ok(output)
+    }
+
+    /// Return the palette as RGB triplets
+    fn palette(&self) -> Vec<u8> {
+        if self.bits_per_plane == 8 {
+            // 256 color palette, stored at the end of the file
+            if self.data.len() > PCX_HEADER_SIZE + VGA_PALETTE_SIZE {
+                let start = self.data.len() - VGA_PALETTE_SIZE;
+
+                if self.data[start - 1] == VGA_PALETTE_MARKER {
+                    return self.data[start..].to_vec();
+                }
+            }
+            trace!("No 256 color palette found, treating image as grayscale");
+
+            return (0..=255).flat_map(|x| [x, x, x]).collect();
+        }
+        // 16 color palette, stored in the header
+        self.data[16..64].to_vec()
+    }
+}
+
+impl DecoderTrait for PcxDecoder {
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let metadata = self.read_headers()?.unwrap();
+        let pixels = self.decode_pixels()?;
+
+        let mut image = Image::from_u8(
+            &pixels,
+            metadata.width,
+            metadata.height,
+            metadata.colorspace
+        );
+        image.metadata = metadata;
+
+        Ok(image)
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        self.dimensions()
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        self.colorspace().unwrap_or(ColorSpace::Unknown)
+    }
+
+    fn name(&self) -> &'static str {
+        "PCX Decoder"
+    }
+
+    fn read_headers(&mut self) -> Result<Option<ImageMetadata>, ImageErrors> {
+        self.decode_headers()?;
+
+        let metadata = ImageMetadata {
+            format: Some(ImageFormat::PCX),
+            colorspace: self.colorspace().unwrap(),
+            depth: BitDepth::Eight,
+            width: self.width,
+            height: self.height,
+            ..Default::default()
+        };
+
+        Ok(Some(metadata))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::errors::ZErrorCode;
+    use zune_core::options::DecoderOptions;
+
+    use crate::codecs::pcx::PCX_HEADER_SIZE;
+    use crate::codecs::ImageFormat;
+    use crate::image::Image;
+
+    fn header(width: u16, height: u16, bits: u8, planes: u8, bytes_per_line: u16) -> Vec<u8> {
+        let mut header = vec![0_u8; PCX_HEADER_SIZE];
+
+        header[0] = 0x0A;
+        header[1] = 5;
+        header[2] = 1;
+        header[3] = bits;
+        header[8..10].copy_from_slice(&(width - 1).to_le_bytes());
+        header[10..12].copy_from_slice(&(height - 1).to_le_bytes());
+        header[65] = planes;
+        header[66..68].copy_from_slice(&bytes_per_line.to_le_bytes());
+        header
+    }
+
+    #[test]
+    fn test_pcx_paletted_rle() {
+        // 4x2 image, first line is a run of index 1, second line is raw
+        let mut data = header(4, 2, 8, 1, 4);
+        data.extend_from_slice(&[0xC4, 1, 0, 1, 2, 3]);
+        data.push(0x0C);
+        data.extend((0..=255_u8).flat_map(|x| [x, 255 - x, 7]));
+
+        let (format, reader) = ImageFormat::guess_format(ZCursor::new(&data)).unwrap();
+        assert_eq!(format, ImageFormat::PCX);
+
+        let image = Image::read(reader, DecoderOptions::default()).unwrap();
+        assert_eq!(image.colorspace(), ColorSpace::RGB);
+
+        let pixels = &image.flatten_to_u8()[0];
+        assert_eq!(&pixels[..3], &[1, 254, 7]);
+        assert_eq!(&pixels[21..], &[3, 252, 7]);
+    }
+
+    #[test]
+    fn test_pcx_24_bit() {
+        // 2x1 image, a line holds the red, green and blue planes
+        let mut data = header(2, 1, 8, 3, 2);
+        data.extend_from_slice(&[10, 20, 0xC2, 30, 40, 50]);
+
+        let image = Image::read(ZCursor::new(&data), DecoderOptions::default()).unwrap();
+        assert_eq!(image.colorspace(), ColorSpace::RGB);
+        assert_eq!(&image.flatten_to_u8()[0], &[10, 30, 40, 20, 30, 50]);
+    }
+
+    #[test]
+    fn test_pcx_error_codes() {
+        // four bit, four plane images aren't supported
+        let mut data = header(2, 1, 4, 4, 1);
+        data.extend_from_slice(&[0; 4]);
+
+        let err = Image::read(ZCursor::new(&data), DecoderOptions::default())
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), ZErrorCode::Unsupported);
+
+        let mut data = header(2, 1, 8, 1, 2);
+        data.extend_from_slice(&[0, 0]);
+
+        let options = DecoderOptions::default().set_max_width(1);
+        let err = Image::read(ZCursor::new(&data), options).err().unwrap();
+        assert_eq!(err.code(), ZErrorCode::LimitsExceeded);
+    }
+}
@@ -0,0 +1,932 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+#![cfg_attr(feature = "docs", doc(cfg(feature = "pdf")))]
+#![cfg(feature = "pdf")]
+//! Extraction of embedded images from PDF documents
+//!
+//! This does not render pages, it walks the objects of a document and decodes
+//! every image it finds on its own, e.g. the scans making up a scanned document.
+//!
+//! Supported images
+//! - `DCTDecode` (JPEG) and `JPXDecode` (JPEG 2000) streams, decoded by the
+//!   matching image decoder when its feature is enabled
+//! - `FlateDecode` and unfiltered streams, with or without PNG and TIFF predictors
+//! - `DeviceGray`, `DeviceRGB`, `DeviceCMYK`, `CalGray`, `CalRGB`, `ICCBased`
+//!   and `Indexed` colorspaces with 1, 2, 4, 8 and 16 bit components
+//! - Image masks, decoded to Luma
+//!
+//! Images using other filters, e.g. `CCITTFaxDecode` or `JBIG2Decode`, are skipped.
+//! Soft masks are images of their own and are returned as separate Luma images
+//! instead of being applied. Encrypted documents are not supported.
+use std::collections::HashMap;
+
+use zune_core::bytestream::ZCursor;
+use zune_core::colorspace::ColorSpace;
+use zune_core::log::warn;
+use zune_core::options::DecoderOptions;
+use zune_inflate::{DeflateDecoder, DeflateOptions};
+
+use crate::errors::ImageErrors;
+use crate::image::Image;
+
+/// Nesting limit for arrays and dictionaries and for following references
+const MAX_DEPTH: usize = 32;
+
+/// How far into the file the header may be, readers have to
+/// accept junk before it
+const HEADER_SEARCH_SIZE: usize = 1024;
+
+/// Probe some bytes to see if they consist of a PDF document
+pub fn probe_pdf(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"%PDF-")
+}
+
+/// Extract the images embedded in a PDF document
+///
+/// Images are returned in the order they appear in the file, which is usually
+/// but not always page order. Images that can't be decoded are skipped with a warning.
+///
+/// # Arguments
+/// - data: The whole PDF document
+/// - options: Options for decoding the images, e.g. size limits
+///
+/// # Returns
+/// - `Ok(images)`: The images that could be decoded, empty if there are none
+/// - `Err(ImageErrors)`: The data isn't a PDF document or it is encrypted
+pub fn extract_pdf_images(data: &[u8], options: DecoderOptions) -> Result<Vec<Image>, ImageErrors> {
+    let header_area = &data[..data.len().min(HEADER_SEARCH_SIZE)];
+
+    if find(header_area, b"%PDF-").is_none() {
+        return Err(ImageErrors::GenericStr("No PDF header found"));
+    }
+    if find(data, b"/Encrypt").is_some() {
+        return Err(ImageErrors::GenericStr(
+            "Encrypted PDF documents are not supported"
+        ));
+    }
+    let document = Document::new(data);
+    let mut images = vec![];
+
+    for &(number, position) in &document.headers {
+        // incremental updates append new versions of objects, only the last one counts
+        if document.objects.get(&number) != Some(&position) {
+            continue;
+        }
+        let Some((Object::Dictionary(dictionary), Some(raw))) = document.object_at(position) else {
+            continue;
+        };
+        if !matches!(dictionary.get(b"Subtype"), Some(Object::Name(b"Image"))) {
+            continue;
+        }
+        match document.decode_image(&dictionary, raw, options) {
+            Ok(image) => images.push(image),
+            Err(_err) => warn!("Skipping PDF image in object {}: {:?}", number, _err)
+        }
+    }
+    Ok(images)
+}
+
+/// A PDF object, streams are handled separately
+#[derive(Clone)]
+enum Object<'a> {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    Name(&'a [u8]),
+    String(Vec<u8>),
+    Array(Vec<Object<'a>>),
+    Dictionary(Dictionary<'a>),
+    /// An indirect reference to another object, the generation is ignored
+    Reference(u32)
+}
+
+#[derive(Clone)]
+struct Dictionary<'a>(Vec<(&'a [u8], Object<'a>)>);
+
+impl<'a> Dictionary<'a> {
+    fn get(&self, key: &[u8]) -> Option<&Object<'a>> {
+        self.0.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+}
+
+/// Stream data after undoing the filters we can
+enum StreamData {
+    /// Packed samples
+    Samples(Vec<u8>),
+    /// A complete JPEG or JPEG 2000 file
+    Encoded(Vec<u8>)
+}
+
+/// How the raw samples of an image are stored
+struct SampleLayout {
+    bits:       usize,
+    /// Components per pixel, one for indexed images
+    components: usize,
+    colorspace: ColorSpace,
+    palette:    Option<Vec<u8>>
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, 0 | b'\t' | b'\n' | 12 | b'\r' | b' ')
+}
+
+fn is_delimiter(byte: u8) -> bool {
+    matches!(
+        byte,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|x| x == needle)
+}
+
+struct Lexer<'a> {
+    data:     &'a [u8],
+    position: usize
+}
+
+impl<'a> Lexer<'a> {
+    fn new(data: &'a [u8], position: usize) -> Lexer<'a> {
+        Lexer { data, position }
+    }
+
+    fn starts_with(&self, token: &[u8]) -> bool {
+        self.data
+            .get(self.position..)
+            .is_some_and(|x| x.starts_with(token))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&byte) = self.data.get(self.position) {
+            if byte == b'%' {
+                // comments run to the end of the line
+                while self
+                    .data
+                    .get(self.position)
+                    .is_some_and(|&x| x != b'\r' && x != b'\n')
+                {
+                    self.position += 1;
+                }
+            } else if is_whitespace(byte) {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Read a run of regular characters, i.e. a number, keyword or name
+    fn token(&mut self) -> &'a [u8] {
+        let start = self.position;
+
+        while self
+            .data
+            .get(self.position)
+            .is_some_and(|&x| !is_whitespace(x) && !is_delimiter(x))
+        {
+            self.position += 1;
+        }
+        &self.data[start..self.position]
+    }
+
+    fn object(&mut self, depth: usize) -> Option<Object<'a>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.skip_whitespace();
+
+        match *self.data.get(self.position)? {
+            b'/' => {
+                self.position += 1;
+                Some(Object::Name(self.token()))
+            }
+            b'<' if self.starts_with(b"<<") => {
+                self.position += 2;
+                let mut entries = vec![];
+
+                loop {
+                    self.skip_whitespace();
+
+                    if self.starts_with(b">>") {
+                        self.position += 2;
+                        return Some(Object::Dictionary(Dictionary(entries)));
+                    }
+                    let Object::Name(key) = self.object(depth + 1)? else {
+                        return None;
+                    };
+                    entries.push((key, self.object(depth + 1)?));
+                }
+            }
+            b'<' => {
+                self.position += 1;
+                let mut digits = vec![];
+
+                loop {
+                    let byte = *self.data.get(self.position)?;
+                    self.position += 1;
+
+                    match byte {
+                        b'>' => break,
+                        _ if is_whitespace(byte) => {}
+                        _ => digits.push((byte as char).to_digit(16)? as u8)
+                    }
+                }
+                // a missing final digit is zero
+                if digits.len() % 2 == 1 {
+                    digits.push(0);
+                }
+                Some(Object::String(
+                    digits.chunks_exact(2).map(|x| x[0] << 4 | x[1]).collect()
+                ))
+            }
+            b'(' => {
+                self.position += 1;
+                self.literal_string()
+            }
+            b'[' => {
+                self.position += 1;
+                let mut items = vec![];
+
+                loop {
+                    self.skip_whitespace();
+
+                    if self.starts_with(b"]") {
+                        self.position += 1;
+                        return Some(Object::Array(items));
+                    }
+                    items.push(self.object(depth + 1)?);
+                }
+            }
+            _ => match self.token() {
+                b"true" => Some(Object::Boolean(true)),
+                b"false" => Some(Object::Boolean(false)),
+                b"null" => Some(Object::Null),
+                token => {
+                    let number = std::str::from_utf8(token).ok()?.parse::<f64>().ok()?;
+
+                    // `number generation R` is a reference to another object
+                    if token.iter().all(u8::is_ascii_digit) {
+                        let position = self.position;
+
+                        self.skip_whitespace();
+                        let generation = self.token();
+                        self.skip_whitespace();
+
+                        if !generation.is_empty()
+                            && generation.iter().all(u8::is_ascii_digit)
+                            && self.token() == b"R"
+                        {
+                            return Some(Object::Reference(number as u32));
+                        }
+                        self.position = position;
+                    }
+                    Some(Object::Number(number))
+                }
+            }
+        }
+    }
+
+    /// Read a string in parentheses, the opening one has been consumed
+    fn literal_string(&mut self) -> Option<Object<'a>> {
+        let mut string = vec![];
+        let mut nesting = 0;
+
+        while let Some(&byte) = self.data.get(self.position) {
+            self.position += 1;
+
+            match byte {
+                b'\\' => {
+                    let escaped = *self.data.get(self.position)?;
+                    self.position += 1;
+
+                    match escaped {
+                        b'n' => string.push(b'\n'),
+                        b'r' => string.push(b'\r'),
+                        b't' => string.push(b'\t'),
+                        b'b' => string.push(8),
+                        b'f' => string.push(12),
+                        b'0'..=b'7' => {
+                            // up to three octal digits
+                            let mut value = u32::from(escaped - b'0');
+
+                            for _ in 0..2 {
+                                match self.data.get(self.position) {
+                                    Some(&digit @ b'0'..=b'7') => {
+                                        value = value * 8 + u32::from(digit - b'0');
+                                        self.position += 1;
+                                    }
+                                    _ => break
+                                }
+                            }
+                            string.push(value as u8);
+                        }
+                        // an escaped end of line continues the string on the next line
+                        b'\r' => {
+                            if self.data.get(self.position) == Some(&b'\n') {
+                                self.position += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        _ => string.push(escaped)
+                    }
+                }
+                b'(' => {
+                    nesting += 1;
+                    string.push(byte);
+                }
+                b')' if nesting == 0 => return Some(Object::String(string)),
+                b')' => {
+                    nesting -= 1;
+                    string.push(byte);
+                }
+                _ => string.push(byte)
+            }
+        }
+        None
+    }
+}
+
+/// Read the number ending at `end` when it is followed by whitespace,
+/// returning it and where it starts
+fn number_before(data: &[u8], end: usize) -> Option<(u32, usize)> {
+    let mut digits_end = end;
+
+    while digits_end > 0 && is_whitespace(data[digits_end - 1]) {
+        digits_end -= 1;
+    }
+    let mut digits_start = digits_end;
+
+    while digits_start > 0 && data[digits_start - 1].is_ascii_digit() {
+        digits_start -= 1;
+    }
+    if digits_end == end || digits_start == digits_end {
+        return None;
+    }
+    let number = std::str::from_utf8(&data[digits_start..digits_end])
+        .ok()?
+        .parse()
+        .ok()?;
+
+    Some((number, digits_start))
+}
+
+struct Document<'a> {
+    data:    &'a [u8],
+    /// `number generation obj` headers in file order, with the position after the keyword
+    headers: Vec<(u32, usize)>,
+    /// Where each object starts, later definitions replace earlier ones
+    objects: HashMap<u32, usize>
+}
+
+impl<'a> Document<'a> {
+    /// Find the objects in a document
+    ///
+    /// This scans for object headers instead of reading the cross reference
+    /// table, so it also works for documents with damaged tables
+    fn new(data: &'a [u8]) -> Document<'a> {
+        let mut headers = vec![];
+        let mut objects = HashMap::new();
+        let mut start = 0;
+
+        while let Some(offset) = find(&data[start..], b"obj") {
+            let keyword = start + offset;
+            start = keyword + 3;
+
+            if data
+                .get(start)
+                .is_some_and(|&x| !is_whitespace(x) && !is_delimiter(x))
+            {
+                continue;
+            }
+            let Some((_, position)) = number_before(data, keyword) else {
+                continue;
+            };
+            let Some((number, position)) = number_before(data, position) else {
+                continue;
+            };
+            if position > 0
+                && !is_whitespace(data[position - 1])
+                && !is_delimiter(data[position - 1])
+            {
+                continue;
+            }
+            headers.push((number, start));
+            objects.insert(number, start);
+        }
+        Document {
+            data,
+            headers,
+            objects
+        }
+    }
+
+    /// Parse the object at `position`, returning it with the raw data
+    /// of its stream if it has one
+    fn object_at(&self, position: usize) -> Option<(Object<'a>, Option<&'a [u8]>)> {
+        let mut lexer = Lexer::new(self.data, position);
+        let object = lexer.object(0)?;
+
+        let Object::Dictionary(dictionary) = &object else {
+            return Some((object, None));
+        };
+        lexer.skip_whitespace();
+
+        if !lexer.starts_with(b"stream") {
+            return Some((object, None));
+        }
+        // the keyword is followed by CRLF or LF
+        let mut start = lexer.position + 6;
+
+        if self.data.get(start) == Some(&b'\r') {
+            start += 1;
+        }
+        if self.data.get(start) == Some(&b'\n') {
+            start += 1;
+        }
+        let start = start.min(self.data.len());
+        let rest = &self.data[start..];
+
+        // trust the length only if it ends at the end of the stream,
+        // it is often wrong in damaged files
+        let length = dictionary
+            .get(b"Length")
+            .and_then(|x| self.usize(x))
+            .filter(|&length| {
+                let mut lexer = Lexer::new(rest, length.min(rest.len()));
+                lexer.skip_whitespace();
+                length <= rest.len() && lexer.starts_with(b"endstream")
+            });
+
+        let end = match length {
+            Some(length) => length,
+            None => {
+                let mut end = find(rest, b"endstream")?;
+
+                if rest[..end].ends_with(b"\n") {
+                    end -= 1;
+                }
+                if rest[..end].ends_with(b"\r") {
+                    end -= 1;
+                }
+                end
+            }
+        };
+        Some((object, Some(&rest[..end])))
+    }
+
+    /// Follow references until we reach a direct object
+    fn resolve(&self, object: &Object<'a>) -> Option<Object<'a>> {
+        let mut object = object.clone();
+
+        for _ in 0..MAX_DEPTH {
+            let Object::Reference(number) = object else {
+                return Some(object);
+            };
+            object = Lexer::new(self.data, *self.objects.get(&number)?).object(0)?;
+        }
+        None
+    }
+
+    fn usize(&self, object: &Object<'a>) -> Option<usize> {
+        match self.resolve(object)? {
+            Object::Number(number) if number >= 0.0 && number <= f64::from(u32::MAX) => {
+                Some(number as usize)
+            }
+            _ => None
+        }
+    }
+
+    fn integer(&self, dictionary: &Dictionary<'a>, key: &[u8]) -> Option<usize> {
+        self.usize(dictionary.get(key)?)
+    }
+
+    /// Undo the filters of a stream, inflating to at most `limit` bytes
+    fn decode_stream(
+        &self, dictionary: &Dictionary<'a>, raw: &[u8], limit: usize
+    ) -> Result<StreamData, ImageErrors> {
+        let names = |object: Option<Object<'a>>| match object {
+            None => Some(vec![]),
+            Some(Object::Array(items)) => items.iter().map(|x| self.resolve(x)).collect(),
+            Some(object) => Some(vec![object])
+        };
+        let filters = names(dictionary.get(b"Filter").and_then(|x| self.resolve(x)))
+            .ok_or(ImageErrors::GenericStr("Invalid PDF stream filters"))?;
+        let parameters =
+            names(dictionary.get(b"DecodeParms").and_then(|x| self.resolve(x))).unwrap_or_default();
+
+        let mut data = raw.to_vec();
+
+        for (i, filter) in filters.iter().enumerate() {
+            match filter {
+                Object::Name(b"FlateDecode" | b"Fl") => {
+                    let options = DeflateOptions::default()
+                        .set_confirm_checksum(false)
+                        .set_limit(limit);
+
+                    data = DeflateDecoder::new_with_options(&data, options)
+                        .decode_zlib()
+                        .map_err(|e| ImageErrors::ImageDecodeErrors(format!("{:?}", e)))?;
+
+                    if let Some(Object::Dictionary(parameters)) = parameters.get(i) {
+                        data = self.undo_predictor(parameters, data)?;
+                    }
+                }
+                Object::Name(b"DCTDecode" | b"DCT" | b"JPXDecode") if i + 1 == filters.len() => {
+                    return Ok(StreamData::Encoded(data));
+                }
+                Object::Name(name) => {
+                    return Err(ImageErrors::GenericString(format!(
+                        "Unsupported PDF stream filter {}",
+                        String::from_utf8_lossy(name)
+                    )));
+                }
+                _ => return Err(ImageErrors::GenericStr("Invalid PDF stream filters"))
+            }
+        }
+        Ok(StreamData::Samples(data))
+    }
+
+    /// Undo the PNG or TIFF predictor a Flate stream was compressed with
+    fn undo_predictor(
+        &self, parameters: &Dictionary<'a>, mut data: Vec<u8>
+    ) -> Result<Vec<u8>, ImageErrors> {
+        let predictor = self.integer(parameters, b"Predictor").unwrap_or(1);
+
+        if predictor == 1 {
+            return Ok(data);
+        }
+        let colors = self.integer(parameters, b"Colors").unwrap_or(1);
+        let bits = self.integer(parameters, b"BitsPerComponent").unwrap_or(8);
+        let columns = self.integer(parameters, b"Columns").unwrap_or(1);
+
+        let pixel_bits = colors
+            .checked_mul(bits)
+            .filter(|&x| x > 0 && x <= 64)
+            .ok_or(ImageErrors::GenericStr("Invalid PDF predictor parameters"))?;
+        let row_size = columns
+            .checked_mul(pixel_bits)
+            .ok_or(ImageErrors::GenericStr("Invalid PDF predictor parameters"))?
+            .div_ceil(8);
+        // filters work on whole bytes, so smaller pixels use the previous byte
+        let bpp = pixel_bits.div_ceil(8);
+
+        if row_size == 0 {
+            return Ok(data);
+        }
+        match predictor {
+            2 if bits == 8 => {
+                for row in data.chunks_mut(row_size) {
+                    for i in bpp..row.len() {
+                        row[i] = row[i].wrapping_add(row[i - bpp]);
+                    }
+                }
+                Ok(data)
+            }
+            // the PNG predictors, each row starts with its own filter type
+            10..=15 => {
+                let mut output = Vec::with_capacity(data.len());
+                let mut previous = vec![0_u8; row_size];
+
+                for row in data.chunks(row_size + 1) {
+                    let mut current = row[1..].to_vec();
+                    current.resize(row_size, 0);
+
+                    for i in 0..row_size {
+                        let left = if i >= bpp { current[i - bpp] } else { 0 };
+                        let upper_left = if i >= bpp { previous[i - bpp] } else { 0 };
+                        let up = previous[i];
+
+                        let prediction = match row[0] {
+                            0 => 0,
+                            1 => left,
+                            2 => up,
+                            3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                            4 => paeth(left, up, upper_left),
+                            _ => return Err(ImageErrors::GenericStr("Invalid PNG filter type"))
+                        };
+                        current[i] = current[i].wrapping_add(prediction);
+                    }
+                    output.extend_from_slice(&current);
+                    previous = current;
+                }
+                Ok(output)
+            }
+            _ => Err(ImageErrors::GenericString(format!(
+                "Unsupported PDF predictor {predictor} with {bits} bit components"
+            )))
+        }
+    }
+
+    /// Return the colorspace of an image and the lookup table if it is indexed
+    fn colorspace(&self, object: &Object<'a>) -> Option<(ColorSpace, Option<Vec<u8>>)> {
+        let object = self.resolve(object)?;
+
+        if let Object::Array(items) = &object {
+            if let [Object::Name(b"Indexed" | b"I"), base, _, lookup] = items.as_slice() {
+                let (base, None) = self.colorspace(base)? else {
+                    return None;
+                };
+                return Some((base, Some(self.lookup_table(lookup)?)));
+            }
+        }
+        let colorspace = match object {
+            Object::Name(b"DeviceGray" | b"CalGray" | b"G") => ColorSpace::Luma,
+            Object::Name(b"DeviceRGB" | b"CalRGB" | b"RGB") => ColorSpace::RGB,
+            Object::Name(b"DeviceCMYK" | b"CMYK") => ColorSpace::CMYK,
+            Object::Array(items) => match items.as_slice() {
+                [Object::Name(b"CalGray"), ..] => ColorSpace::Luma,
+                [Object::Name(b"CalRGB"), ..] => ColorSpace::RGB,
+                [Object::Name(b"ICCBased"), stream, ..] => {
+                    let Object::Dictionary(stream) = self.resolve(stream)? else {
+                        return None;
+                    };
+                    match self.integer(&stream, b"N")? {
+                        1 => ColorSpace::Luma,
+                        3 => ColorSpace::RGB,
+                        4 => ColorSpace::CMYK,
+                        _ => return None
+                    }
+                }
+                _ => return None
+            },
+            _ => return None
+        };
+        Some((colorspace, None))
+    }
+
+    /// Read the palette of an indexed colorspace, either a string or a stream
+    fn lookup_table(&self, object: &Object<'a>) -> Option<Vec<u8>> {
+        match object {
+            Object::String(table) => Some(table.clone()),
+            Object::Reference(number) => match self.object_at(*self.objects.get(number)?)? {
+                (Object::String(table), None) => Some(table),
+                (Object::Dictionary(dictionary), Some(raw)) => {
+                    // at most 256 entries of up to four components
+                    match self.decode_stream(&dictionary, raw, 256 * 4).ok()? {
+                        StreamData::Samples(table) => Some(table),
+                        StreamData::Encoded(_) => None
+                    }
+                }
+                _ => None
+            },
+            _ => None
+        }
+    }
+
+    /// Return how the raw samples of an image are stored
+    fn sample_layout(&self, dictionary: &Dictionary<'a>) -> Result<SampleLayout, ImageErrors> {
+        let image_mask = matches!(dictionary.get(b"ImageMask"), Some(Object::Boolean(true)));
+
+        // image masks are one bit stencils, zero is painted so it maps to black
+        let (bits, colorspace, palette) = if image_mask {
+            (1, ColorSpace::Luma, None)
+        } else {
+            let (colorspace, palette) = dictionary
+                .get(b"ColorSpace")
+                .and_then(|x| self.colorspace(x))
+                .ok_or(ImageErrors::GenericStr("Unsupported PDF image colorspace"))?;
+            let bits = self.integer(dictionary, b"BitsPerComponent").unwrap_or(8);
+
+            (bits, colorspace, palette)
+        };
+        let components = if palette.is_some() { 1 } else { colorspace.num_components() };
+        if !matches!(bits, 1 | 2 | 4 | 8 | 16) || (bits == 16 && palette.is_some()) {
+            return Err(ImageErrors::GenericString(format!(
+                "Unsupported PDF image with {bits} bit components"
+            )));
+        }
+        Ok(SampleLayout {
+            bits,
+            components,
+            colorspace,
+            palette
+        })
+    }
+
+    fn decode_image(
+        &self, dictionary: &Dictionary<'a>, raw: &[u8], options: DecoderOptions
+    ) -> Result<Image, ImageErrors> {
+        let width = self.integer(dictionary, b"Width").unwrap_or(0);
+        let height = self.integer(dictionary, b"Height").unwrap_or(0);
+
+        if width == 0 || height == 0 {
+            return Err(ImageErrors::GenericStr("PDF image has no dimensions"));
+        }
+        if width > options.max_width() || height > options.max_height() {
+            return Err(ImageErrors::GenericString(format!(
+                "PDF image dimensions {width}x{height} are larger than the limits {}x{}",
+                options.max_width(),
+                options.max_height()
+            )));
+        }
+        let layout = self.sample_layout(dictionary);
+        // raw samples never need more than this, plus a filter byte per row for
+        // PNG predictors. JPEG 2000 images may leave out the colorspace, assume
+        // the largest samples for them
+        let row_size = match &layout {
+            Ok(layout) => (width * layout.components * layout.bits).div_ceil(8),
+            Err(_) => width * 4 * 2
+        };
+        let limit = (row_size + 1) * height;
+
+        let data = match self.decode_stream(dictionary, raw, limit)? {
+            StreamData::Samples(data) => data,
+            StreamData::Encoded(data) => return Image::read(ZCursor::new(&data), options)
+        };
+        let SampleLayout {
+            bits,
+            components,
+            colorspace,
+            palette
+        } = layout?;
+        let mut data = data;
+
+        if data.len() < row_size * height {
+            if options.strict_mode() {
+                return Err(ImageErrors::GenericStr("PDF image data is truncated"));
+            }
+            warn!("PDF image data is truncated, filling the rest with zeroes");
+            data.resize(row_size * height, 0);
+        }
+        // a decode array of [1 0] per component inverts the samples
+        let inverted = match dictionary.get(b"Decode").and_then(|x| self.resolve(x)) {
+            Some(Object::Array(items)) if palette.is_none() => {
+                matches!(items.as_slice(), [Object::Number(a), Object::Number(b), ..] if *a == 1.0 && *b == 0.0)
+            }
+            _ => false
+        };
+
+        if bits == 16 {
+            let pixels: Vec<u16> = data[..row_size * height]
+                .chunks_exact(2)
+                .map(|x| u16::from_be_bytes([x[0], x[1]]))
+                .map(|x| if inverted { u16::MAX - x } else { x })
+                .collect();
+
+            return Ok(Image::from_u16(&pixels, width, height, colorspace));
+        }
+        let max_value = (1_u16 << bits) - 1;
+        let mut samples = Vec::with_capacity(width * height * components);
+
+        for row in data.chunks_exact(row_size).take(height) {
+            for i in 0..width * components {
+                let bit = i * bits;
+                let shift = 8 - bits - bit % 8;
+                let value = (u16::from(row[bit / 8]) >> shift) & max_value;
+
+                samples.push(value);
+            }
+        }
+        let pixels: Vec<u8> = match palette {
+            Some(palette) => {
+                let base_components = colorspace.num_components();
+                let last_index = (palette.len() / base_components).saturating_sub(1);
+                let mut pixels = Vec::with_capacity(samples.len() * base_components);
+
+                for index in samples {
+                    let start = usize::from(index).min(last_index) * base_components;
+
+                    match palette.get(start..start + base_components) {
+                        Some(color) => pixels.extend_from_slice(color),
+                        None => pixels.resize(pixels.len() + base_components, 0)
+                    }
+                }
+                pixels
+            }
+            None => samples
+                .into_iter()
+                .map(|x| (x * 255 / max_value) as u8)
+                .map(|x| if inverted { 255 - x } else { x })
+                .collect()
+        };
+        Ok(Image::from_u8(&pixels, width, height, colorspace))
+    }
+}
+
+fn paeth(left: u8, up: u8, upper_left: u8) -> u8 {
+    let estimate = i16::from(left) + i16::from(up) - i16::from(upper_left);
+    let (a, b, c) = (
+        (estimate - i16::from(left)).abs(),
+        (estimate - i16::from(up)).abs(),
+        (estimate - i16::from(upper_left)).abs()
+    );
+    if a <= b && a <= c {
+        left
+    } else if b <= c {
+        up
+    } else {
+        upper_left
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::codecs::pdf::extract_pdf_images;
+
+    /// Build a document from objects, with a cross reference table
+    /// readers would need but the extractor ignores
+    fn document(objects: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = b"%PDF-1.4\n".to_vec();
+
+        for (i, object) in objects.iter().enumerate() {
+            data.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            data.extend_from_slice(object);
+            data.extend_from_slice(b"\nendobj\n");
+        }
+        data.extend_from_slice(b"trailer\n<< /Root 1 0 R >>\n%%EOF\n");
+        data
+    }
+
+    fn stream(dictionary: &str, data: &[u8]) -> Vec<u8> {
+        let mut object =
+            format!("<< {dictionary} /Length {} >>\nstream\n", data.len()).into_bytes();
+        object.extend_from_slice(data);
+        object.extend_from_slice(b"\nendstream");
+        object
+    }
+
+    #[test]
+    fn test_extract_raw_and_indexed_images() {
+        let rgb = [255, 0, 0, 0, 255, 0, 0, 0, 255, 9, 9, 9];
+        let data = document(&[
+            b"<< /Type /Catalog >>".to_vec(),
+            stream(
+                "/Type /XObject /Subtype /Image /Width 2 /Height 2 /ColorSpace /DeviceRGB /BitsPerComponent 8",
+                &rgb
+            ),
+            // a 1 bit image with a two color palette (white, red), 10 01 per row
+            stream(
+                "/Subtype /Image /Width 2 /Height 2 /BitsPerComponent 1 /ColorSpace [/Indexed /DeviceRGB 1 <FFFFFF FF0000>]",
+                &[0b1000_0000, 0b0100_0000]
+            ),
+            // not an image
+            stream("/Type /XObject /Subtype /Form", b"q Q")
+        ]);
+        let images = extract_pdf_images(&data, DecoderOptions::default()).unwrap();
+        assert_eq!(images.len(), 2);
+
+        assert_eq!(images[0].colorspace(), ColorSpace::RGB);
+        assert_eq!(images[0].flatten_to_u8()[0], rgb);
+
+        assert_eq!(images[1].dimensions(), (2, 2));
+        assert_eq!(
+            images[1].flatten_to_u8()[0],
+            [255, 0, 0, 255, 255, 255, 255, 255, 255, 255, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_extract_flate_image_with_png_predictor() {
+        // two rows of 3 gray pixels, using the Sub and Up filters
+        let filtered = [1, 10, 5, 5, 2, 1, 1, 1];
+        let compressed = zune_inflate::DeflateEncoder::new(&filtered).encode_zlib();
+
+        let data = document(&[stream(
+            "/Subtype /Image /Width 3 /Height 2 /ColorSpace 2 0 R /BitsPerComponent 8 /Filter [/FlateDecode] /DecodeParms << /Predictor 15 /Columns 3 >>",
+            &compressed
+        ), b"/DeviceGray".to_vec()]);
+
+        let images = extract_pdf_images(&data, DecoderOptions::default()).unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].colorspace(), ColorSpace::Luma);
+        assert_eq!(images[0].flatten_to_u8()[0], [10, 15, 20, 11, 16, 21]);
+    }
+
+    #[test]
+    fn test_flate_limits_and_truncated_data() {
+        // 1 MB of zeroes for a 4x4 gray image is far more than it needs
+        let bomb = zune_inflate::DeflateEncoder::new(&vec![0; 1 << 20]).encode_zlib();
+        let truncated = zune_inflate::DeflateEncoder::new(&[7; 10]).encode_zlib();
+
+        let data = document(&[
+            stream(
+                "/Subtype /Image /Width 4 /Height 4 /ColorSpace /DeviceGray /BitsPerComponent 8 /Filter /FlateDecode",
+                &bomb
+            ),
+            stream(
+                "/Subtype /Image /Width 4 /Height 4 /ColorSpace /DeviceGray /BitsPerComponent 8 /Filter /FlateDecode",
+                &truncated
+            )
+        ]);
+        let images = extract_pdf_images(&data, DecoderOptions::default()).unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].flatten_to_u8()[0][9..], [7, 0, 0, 0, 0, 0, 0]);
+
+        let strict = DecoderOptions::default().set_strict_mode(true);
+        assert!(extract_pdf_images(&data, strict).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_not_a_pdf() {
+        assert!(extract_pdf_images(b"GIF89a", DecoderOptions::default()).is_err());
+    }
+}
@@ -10,20 +10,19 @@
 #![allow(unused_variables)]
 
 //! Represents an png image decoder and encoder
-use std::io::Cursor;
 
 use zune_core::bit_depth::BitDepth;
 use zune_core::bytestream::{ZByteReaderTrait, ZByteWriterTrait};
 use zune_core::colorspace::ColorSpace;
-use zune_core::log::warn;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
 use zune_core::options::EncoderOptions;
 use zune_core::result::DecodingResult;
+use zune_core::warnings::DecodeWarning;
 use zune_png::error::PngDecodeErrors;
 pub use zune_png::*;
 
 use crate::codecs::{create_options_for_encoder, ImageFormat};
 use crate::errors::ImageErrors;
-use crate::errors::ImageErrors::ImageDecodeErrors;
 use crate::errors::ImgEncodeErrors::ImageEncodeErrors;
 use crate::frame::Frame;
 use crate::image::Image;
@@ -42,43 +41,8 @@ where
         let colorspace = self.colorspace().unwrap();
 
         if self.is_animated() && self.options().png_decode_animated() {
-            // decode apng frames
-            //let mut previous_frame
-            let info = self.info().unwrap().clone();
-            // the output, since we know that no frame will be bigger than the width and height, we can
-            // set this up outside of the loop.
-            let mut output = vec![0; info.width * info.height * colorspace.num_components()];
-            let mut output_frames = Vec::new();
-            while self.more_frames() {
-                self.decode_headers()?;
-
-                let mut frame = self.frame_info().unwrap();
-                if frame.dispose_op == DisposeOp::Previous {
-                    // we don't clear our buffer, so output always contains the previous frame
-                    //
-                    // this means that there is no need to store the previous frame and copy it
-                    frame.dispose_op = DisposeOp::None;
-                }
-                let pix = self.decode()?;
-                match pix {
-                    DecodingResult::U8(pix) => {
-                        post_process_image(
-                            &info,
-                            colorspace,
-                            &frame,
-                            &pix,
-                            None,
-                            &mut output,
-                            None,
-                        )?;
-                        let duration = f64::from(frame.delay_num) / f64::from(frame.delay_denom);
-                        // then build a frame from that
-                        let im_frame = Frame::from_u8(&output, colorspace, usize::from(frame.delay_num),usize::from(frame.delay_denom));
-                        output_frames.push(im_frame);
-                    }
-                    _ => return Err(ImageDecodeErrors("The current image is an  Animated PNG but has a depth of 16, such an image isn't supported".to_string()))
-                }
-            }
+            let output_frames = decode_apng_frames(self, None)?;
+
             let mut image = Image::new_frames(output_frames, depth, width, height, colorspace);
             image.metadata = metadata;
 
@@ -111,6 +75,33 @@ where
         "PNG Decoder"
     }
 
+    fn warnings(&self) -> &[DecodeWarning] {
+        self.warnings()
+    }
+
+    fn decode_frame(&mut self, n: usize) -> Result<Image, ImageErrors> {
+        let metadata = self.read_headers()?.unwrap();
+
+        if !self.is_animated() || !self.options().png_decode_animated() {
+            // a still image has a single frame, as does an animated one
+            // whose animation we are told to ignore
+            if n != 0 {
+                return Err(ImageErrors::FrameOutOfRange(n, 1));
+            }
+            return DecoderTrait::decode(self);
+        }
+        let depth = self.depth().unwrap();
+        let (width, height) = self.dimensions().unwrap();
+        let colorspace = self.colorspace().unwrap();
+
+        let frames = decode_apng_frames(self, Some(n))?;
+
+        let mut image = Image::new_frames(frames, depth, width, height, colorspace);
+        image.metadata = metadata;
+
+        Ok(image)
+    }
+
     fn read_headers(&mut self) -> Result<Option<ImageMetadata>, crate::errors::ImageErrors> {
         self.decode_headers()
             .map_err(<error::PngDecodeErrors as Into<ImageErrors>>::into)?;
@@ -135,20 +126,103 @@ where
                 metadata.parse_raw_exif(exif)
             }
         }
+        if let Some(exif) = &self.info().unwrap().exif {
+            metadata.parse_raw_exif_orientation(exif);
+        }
         // load icc
         if let Some(icc) = &self.info().unwrap().icc_profile {
             metadata.set_icc_chunk(icc.to_owned());
         }
+        if let Some((x_dpi, y_dpi)) = self.info().unwrap().phys_info.and_then(|x| x.dpi()) {
+            metadata.set_dpi(x_dpi, y_dpi);
+        }
+        if let Some(num_plays) = self.num_plays() {
+            metadata.set_loop_count(u16::try_from(num_plays).unwrap_or(u16::MAX));
+        }
 
         Ok(Some(metadata))
     }
 }
 
+/// Decode the frames of an animated png, compositing each onto the previous ones
+///
+/// If `last` is set, decoding stops after that frame and only it is returned
+fn decode_apng_frames<T>(
+    decoder: &mut PngDecoder<T>, last: Option<usize>
+) -> Result<Vec<Frame>, ImageErrors>
+where
+    T: ZByteReaderTrait
+{
+    let info = decoder.info().unwrap().clone();
+    let colorspace = decoder.colorspace().unwrap();
+    let stride = info.width * colorspace.num_components();
+    // the output, since we know that no frame will be bigger than the width and height, we can
+    // set this up outside of the loop.
+    let mut output = vec![0; stride * info.height];
+    // the canvas before the current frame was drawn, restored by DisposeOp::Previous
+    let mut previous = Vec::new();
+    let mut output_frames = Vec::new();
+    let mut position = 0;
+
+    while decoder.more_frames() {
+        decoder.decode_headers()?;
+
+        let mut frame = decoder.frame_info().unwrap();
+        // the frame is disposed of after it is shown, so only let post_process_image draw it
+        let dispose_op = core::mem::replace(&mut frame.dispose_op, DisposeOp::None);
+        if dispose_op == DisposeOp::Previous {
+            // for the first frame this is the transparent black canvas, which is what
+            // the specification says to use
+            previous.clone_from(&output);
+        }
+        let pix = decoder.decode()?;
+        match pix {
+            DecodingResult::U8(pix) => {
+                post_process_image(&info, colorspace, &frame, &pix, None, &mut output, None)?;
+            }
+            _ => return Err(ImageErrors::CodecErrors(ZErrorCode::Unsupported, "The current image is an  Animated PNG but has a depth of 16, such an image isn't supported".to_string()))
+        }
+        if last.is_none() || last == Some(position) {
+            // then build a frame from that
+            let im_frame = Frame::from_u8(
+                &output,
+                colorspace,
+                usize::from(frame.delay_num),
+                usize::from(frame.delay_denom)
+            );
+            output_frames.push(im_frame);
+        }
+        if last == Some(position) {
+            return Ok(output_frames);
+        }
+        // post_process_image checked that the frame region is inside the canvas
+        let start = frame.x_offset * colorspace.num_components();
+        let end = start + frame.width * colorspace.num_components();
+
+        for y in frame.y_offset..frame.y_offset + frame.height {
+            let row = &mut output[y * stride..(y + 1) * stride];
+
+            match dispose_op {
+                DisposeOp::None => break,
+                DisposeOp::Background => row[start..end].fill(0),
+                DisposeOp::Previous => {
+                    row[start..end].copy_from_slice(&previous[y * stride..][start..end]);
+                }
+            }
+        }
+        position += 1;
+    }
+    if let Some(last) = last {
+        return Err(ImageErrors::FrameOutOfRange(last, position));
+    }
+    Ok(output_frames)
+}
+
 impl From<zune_png::error::PngDecodeErrors> for ImageErrors {
     fn from(from: zune_png::error::PngDecodeErrors) -> Self {
         let err = format!("png: {from:?}");
 
-        ImageErrors::ImageDecodeErrors(err)
+        ImageErrors::CodecErrors(from.code(), err)
     }
 }
 
@@ -178,32 +252,21 @@ impl EncoderTrait for PngEncoder {
     ) -> Result<usize, ImageErrors> {
         let options = create_options_for_encoder(self.options, image);
 
+        if image.is_animated() {
+            return encode_apng(image, options, sink);
+        }
         let frame = &image.to_u8_be()[0];
 
         let mut encoder = zune_png::PngEncoder::new(frame, options);
 
-        #[allow(unused_mut)]
-        let mut buf: Cursor<Vec<u8>> = std::io::Cursor::new(vec![]);
-
         #[cfg(feature = "metadata")]
-        {
-            use exif::experimental::Writer;
-
-            if !options.strip_metadata() {
-                if let Some(fields) = &image.metadata.exif {
-                    let mut writer = Writer::new();
-
-                    for metadatum in fields {
-                        writer.push_field(metadatum);
-                    }
-                    let result = writer.write(&mut buf, false);
-                    if result.is_ok() {
-                        encoder.add_exif_segment(buf.get_ref());
-                    } else {
-                        warn!("Writing exif failed {:?}", result);
-                    }
-                }
-            }
+        let exif = if options.strip_metadata() { None } else { image.metadata.exif_bytes() };
+        #[cfg(feature = "metadata")]
+        if let Some(exif) = &exif {
+            encoder.add_exif_segment(exif);
+        }
+        if let Some((x_dpi, y_dpi)) = image.metadata.dpi() {
+            encoder.add_phys_info(PhysInfo::from_dpi(x_dpi, y_dpi));
         }
         encoder
             .encode(sink)
@@ -236,6 +299,42 @@ impl EncoderTrait for PngEncoder {
     fn set_options(&mut self, opts: EncoderOptions) {
         self.options = Some(opts)
     }
+
+    fn supports_animated_images(&self) -> bool {
+        true
+    }
+}
+
+/// Encode all frames of an image as an animated png
+fn encode_apng<T: ZByteWriterTrait>(
+    image: &Image, options: EncoderOptions, sink: T
+) -> Result<usize, ImageErrors> {
+    let frames = image.to_u8_be();
+
+    let mut encoder = ApngEncoder::new(options);
+
+    for (data, frame) in frames.iter().zip(image.frames_ref()) {
+        encoder.add_frame(
+            data,
+            u16::try_from(frame.numerator).unwrap_or(u16::MAX),
+            u16::try_from(frame.denominator).unwrap_or(u16::MAX)
+        );
+    }
+    if let Some(count) = image.metadata.loop_count() {
+        encoder.set_num_plays(u32::from(count));
+    }
+    #[cfg(feature = "metadata")]
+    let exif = if options.strip_metadata() { None } else { image.metadata.exif_bytes() };
+    #[cfg(feature = "metadata")]
+    if let Some(exif) = &exif {
+        encoder.add_exif_segment(exif);
+    }
+    if let Some((x_dpi, y_dpi)) = image.metadata.dpi() {
+        encoder.add_phys_info(PhysInfo::from_dpi(x_dpi, y_dpi));
+    }
+    encoder
+        .encode(sink)
+        .map_err(|e| ImageErrors::EncodeErrors(ImageEncodeErrors(format!("{:?}", e))))
 }
 
 impl<T> DecodeInto for PngDecoder<T>
@@ -264,6 +363,8 @@ where
 mod tests {
     use zune_core::bytestream::ZCursor;
     use zune_core::colorspace::ColorSpace;
+    use zune_core::errors::ZErrorCode;
+    use zune_core::options::DecoderOptions;
     use zune_png::PngDecoder;
 
     use crate::codecs::png::PngEncoder;
@@ -283,4 +384,141 @@ mod tests {
         let mut decoder = PngDecoder::new(ZCursor::new(&img));
         decoder.decode_into(&mut output).unwrap();
     }
+
+    #[test]
+    fn test_png_truncated_error_code() {
+        let img = create_png();
+        match Image::read(ZCursor::new(&img[..20]), DecoderOptions::default()) {
+            Ok(_) => panic!("Truncated image should not decode"),
+            Err(err) => assert_eq!(err.code(), ZErrorCode::CorruptData)
+        }
+    }
+
+    #[test]
+    fn test_png_read_frame_still_image() {
+        let img = create_png();
+
+        let image = Image::read_frame(ZCursor::new(&img), DecoderOptions::default(), 0).unwrap();
+        assert_eq!(image.dimensions(), (100, 100));
+
+        match Image::read_frame(ZCursor::new(&img), DecoderOptions::default(), 1) {
+            Ok(_) => panic!("A still image only has one frame"),
+            Err(err) => assert_eq!(err.code(), ZErrorCode::InvalidInput)
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "metadata")]
+    fn test_png_exif_round_trip() {
+        use zune_core::options::EncoderOptions;
+
+        use crate::traits::EncoderTrait;
+
+        let mut image = Image::fill(10_u8, ColorSpace::RGB, 10, 10);
+        image.metadata_mut().set_copyright("zune-image");
+        image.metadata_mut().set_orientation(3);
+
+        let img = image.write_to_vec(ImageFormat::PNG).unwrap();
+        let decoded = Image::read(ZCursor::new(&img), DecoderOptions::default()).unwrap();
+        assert_eq!(
+            decoded.metadata().copyright().as_deref(),
+            Some("zune-image")
+        );
+        assert_eq!(decoded.metadata().orientation(), Some(3));
+
+        let mut stripped = vec![];
+        PngEncoder::new_with_options(EncoderOptions::default().set_strip_metadata(true))
+            .encode(&image, &mut stripped)
+            .unwrap();
+        let decoded = Image::read(ZCursor::new(&stripped), DecoderOptions::default()).unwrap();
+        assert!(decoded.metadata().exif().is_none());
+    }
+
+    #[test]
+    fn test_png_dpi_round_trip() {
+        let mut image = Image::fill(10_u8, ColorSpace::RGB, 10, 10);
+        image.metadata_mut().set_dpi(300.0, 150.0);
+
+        let img = image.write_to_vec(ImageFormat::PNG).unwrap();
+        let decoded = Image::read(ZCursor::new(&img), DecoderOptions::default()).unwrap();
+        let (x_dpi, y_dpi) = decoded.metadata().dpi().unwrap();
+        // pHYs stores whole pixels per meter
+        assert!((x_dpi - 300.0).abs() < 0.05);
+        assert!((y_dpi - 150.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_apng_round_trip() {
+        use zune_core::bit_depth::BitDepth;
+        use zune_core::options::EncoderOptions;
+
+        use crate::frame::Frame;
+        use crate::traits::EncoderTrait;
+
+        let first = vec![40_u8; 16 * 16 * 4];
+        let mut second = first.clone();
+        second[(5 * 16 + 7) * 4..][..4].copy_from_slice(&[255, 0, 0, 255]);
+
+        let frames = vec![
+            Frame::from_u8(&first, ColorSpace::RGBA, 1, 10),
+            Frame::from_u8(&second, ColorSpace::RGBA, 3, 10),
+        ];
+        let mut image = Image::new_frames(frames, BitDepth::Eight, 16, 16, ColorSpace::RGBA);
+        image.metadata_mut().set_loop_count(2);
+
+        let mut data = vec![];
+        PngEncoder::new_with_options(EncoderOptions::default().set_png_optimize_frames(true))
+            .encode(&image, &mut data)
+            .unwrap();
+
+        let decoded = Image::read(ZCursor::new(&data), DecoderOptions::default()).unwrap();
+        assert_eq!(decoded.metadata().loop_count(), Some(2));
+
+        let frames = decoded.frames_ref();
+        assert_eq!(frames.len(), 2);
+        assert_eq!((frames[1].numerator, frames[1].denominator), (3, 10));
+        assert!(decoded.flatten_to_u8() == [first, second]);
+    }
+
+    #[test]
+    fn test_apng_dispose_previous() {
+        use zune_core::bit_depth::BitDepth;
+        use zune_core::options::EncoderOptions;
+
+        use crate::codecs::png::{ApngEncoder, BlendOp, DisposeOp};
+
+        let red = [255_u8, 0, 0, 255].repeat(4 * 4);
+        // a green pixel, drawn over the red canvas and disposed of afterwards
+        let mut green = vec![0_u8; 4 * 4 * 4];
+        green[..4].copy_from_slice(&[0, 255, 0, 255]);
+        let transparent = vec![0_u8; 4 * 4 * 4];
+
+        let options = EncoderOptions::new(4, 4, ColorSpace::RGBA, BitDepth::Eight);
+        let mut encoder = ApngEncoder::new(options);
+        encoder.add_frame_with_ops(&red, 1, 10, DisposeOp::None, BlendOp::Source);
+        encoder.add_frame_with_ops(&green, 1, 10, DisposeOp::Previous, BlendOp::Over);
+        encoder.add_frame_with_ops(&transparent, 1, 10, DisposeOp::None, BlendOp::Over);
+
+        let mut data = vec![];
+        encoder.encode(&mut data).unwrap();
+
+        let mut with_green = red.clone();
+        with_green[..4].copy_from_slice(&[0, 255, 0, 255]);
+
+        let decoded = Image::read(ZCursor::new(&data), DecoderOptions::default()).unwrap();
+        assert!(decoded.flatten_to_u8() == [red.clone(), with_green, red.clone()]);
+
+        let frame = Image::read_frame(ZCursor::new(&data), DecoderOptions::default(), 2).unwrap();
+        assert!(frame.flatten_to_u8() == [red.clone()]);
+
+        // without animations, the default image is the only frame
+        let options = DecoderOptions::default().png_set_decode_animated(false);
+        let frame = Image::read_frame(ZCursor::new(&data), options, 0).unwrap();
+        assert!(frame.flatten_to_u8() == [red]);
+
+        match Image::read_frame(ZCursor::new(&data), options, 1) {
+            Ok(_) => panic!("Animations are disabled, so there is only one frame"),
+            Err(err) => assert_eq!(err.code(), ZErrorCode::InvalidInput)
+        }
+    }
 }
@@ -11,15 +11,17 @@
 use zune_core::bit_depth::BitDepth;
 use zune_core::bytestream::{ZByteReaderTrait, ZByteWriterTrait};
 use zune_core::colorspace::ColorSpace;
+use zune_core::errors::ZErrorCodeTrait;
 use zune_core::options::EncoderOptions;
 use zune_core::result::DecodingResult;
 pub use zune_ppm::{PPMDecodeErrors, PPMDecoder, PPMEncodeErrors, PPMEncoder as PPMEnc};
 
 use crate::codecs::{create_options_for_encoder, ImageFormat};
+use crate::core_filters::depth::Depth;
 use crate::errors::{ImageErrors, ImgEncodeErrors};
 use crate::image::Image;
 use crate::metadata::ImageMetadata;
-use crate::traits::{DecoderTrait, EncoderTrait};
+use crate::traits::{DecoderTrait, EncoderTrait, OperationsTrait};
 
 #[derive(Copy, Clone, Default)]
 pub struct PPMEncoder {
@@ -45,8 +47,30 @@ impl EncoderTrait for PPMEncoder {
     fn encode_inner<T: ZByteWriterTrait>(
         &mut self, image: &Image, sink: T
     ) -> Result<usize, ImageErrors> {
+        if image.depth() == BitDepth::Float32
+            && !matches!(image.colorspace(), ColorSpace::Luma | ColorSpace::RGB)
+        {
+            // pfm only supports grayscale and rgb, store others as 16 bit P7
+            let mut image_clone = image.clone();
+            Depth::new(BitDepth::Sixteen).execute(&mut image_clone)?;
+            return self.encode_inner(&image_clone, sink);
+        }
         let options = create_options_for_encoder(self.options, image);
 
+        if image.depth() == BitDepth::Float32 {
+            // encoded as pfm
+            let data: Vec<u8> = image.flatten_frames::<f32>()[0]
+                .iter()
+                .flat_map(|x| x.to_ne_bytes())
+                .collect();
+
+            let bytes_written = PPMEnc::new(&data, options)
+                .encode(sink)
+                .map_err(<PPMEncodeErrors as Into<ImgEncodeErrors>>::into)?;
+
+            return Ok(bytes_written);
+        }
+
         let data = &image.to_u8()[0];
 
         let ppm_encoder = PPMEnc::new(data, options);
@@ -72,12 +96,11 @@ impl EncoderTrait for PPMEncoder {
     }
 
     fn supported_bit_depth(&self) -> &'static [BitDepth] {
-        &[BitDepth::Sixteen, BitDepth::Eight]
+        // float32 is encoded as PFM
+        &[BitDepth::Sixteen, BitDepth::Eight, BitDepth::Float32]
     }
 
     /// Get appropriate depth for this image
-    ///
-    /// Float32 types, they are converted to Float16 types
     fn default_depth(&self, depth: BitDepth) -> BitDepth {
         match depth {
             BitDepth::Float32 | BitDepth::Sixteen => BitDepth::Sixteen,
@@ -150,7 +173,7 @@ impl From<zune_ppm::PPMDecodeErrors> for ImageErrors {
     fn from(from: zune_ppm::PPMDecodeErrors) -> Self {
         let err = format!("ppm: {from:?}");
 
-        ImageErrors::ImageDecodeErrors(err)
+        ImageErrors::CodecErrors(from.code(), err)
     }
 }
 
@@ -159,6 +182,6 @@ impl From<zune_ppm::PPMEncodeErrors> for ImgEncodeErrors {
     fn from(error: zune_ppm::PPMEncodeErrors) -> Self {
         let err = format!("ppm: {error:?}");
 
-        ImgEncodeErrors::ImageEncodeErrors(err)
+        ImgEncodeErrors::CodecErrors(error.code(), err)
     }
 }
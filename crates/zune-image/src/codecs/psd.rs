@@ -15,6 +15,7 @@
 
 use zune_core::bytestream::ZByteReaderTrait;
 use zune_core::colorspace::ColorSpace;
+use zune_core::errors::ZErrorCodeTrait;
 use zune_core::result::DecodingResult;
 pub use zune_psd::*;
 
@@ -86,6 +87,6 @@ impl From<zune_psd::errors::PSDDecodeErrors> for ImageErrors {
     fn from(error: zune_psd::errors::PSDDecodeErrors) -> Self {
         let err = format!("psd: {error:?}");
 
-        ImageErrors::ImageDecodeErrors(err)
+        ImageErrors::CodecErrors(error.code(), err)
     }
 }
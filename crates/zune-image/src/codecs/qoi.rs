@@ -15,11 +15,13 @@
 use zune_core::bit_depth::BitDepth;
 use zune_core::bytestream::{ZByteReaderTrait, ZByteWriterTrait};
 use zune_core::colorspace::ColorSpace;
+use zune_core::errors::ZErrorCodeTrait;
 use zune_core::options::EncoderOptions;
 pub use zune_qoi::*;
 
 use crate::codecs::{create_options_for_encoder, ImageFormat};
 use crate::errors::{ImageErrors, ImgEncodeErrors};
+use crate::frame::Frame;
 use crate::image::Image;
 use crate::metadata::ImageMetadata;
 use crate::traits::{DecodeInto, DecoderTrait, EncoderTrait};
@@ -148,11 +150,184 @@ impl EncoderTrait for QoiEncoder {
     }
 }
 
+impl<T> DecoderTrait for QoiSequenceDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let metadata = self.read_headers()?.unwrap();
+
+        let colorspace = self.colorspace().unwrap();
+        let (width, height) = self.dimensions().unwrap();
+        let delays = self.frames().unwrap().to_vec();
+
+        let frames = self
+            .decode()?
+            .iter()
+            .zip(delays)
+            .map(|(pixels, delay)| {
+                Frame::from_u8(
+                    pixels,
+                    colorspace,
+                    usize::from(delay.numerator),
+                    usize::from(delay.denominator)
+                )
+            })
+            .collect();
+
+        let mut image = Image::new_frames(frames, BitDepth::Eight, width, height, colorspace);
+        image.metadata = metadata;
+
+        Ok(image)
+    }
+
+    fn decode_frame(&mut self, n: usize) -> Result<Image, ImageErrors> {
+        let metadata = self.read_headers()?.unwrap();
+        let frames = self.num_frames().unwrap();
+
+        if n >= frames {
+            return Err(ImageErrors::FrameOutOfRange(n, frames));
+        }
+        let info = self.frames().unwrap()[n];
+        let pixels = self.decode_frame(n)?;
+
+        let frame = Frame::from_u8(
+            &pixels,
+            metadata.colorspace,
+            usize::from(info.numerator),
+            usize::from(info.denominator)
+        );
+        let mut image = Image::new_frames(
+            vec![frame],
+            BitDepth::Eight,
+            metadata.width,
+            metadata.height,
+            metadata.colorspace
+        );
+        image.metadata = metadata;
+
+        Ok(image)
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        self.dimensions()
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        self.colorspace().unwrap()
+    }
+
+    fn name(&self) -> &'static str {
+        "QOI Sequence Decoder"
+    }
+
+    fn is_experimental(&self) -> bool {
+        true
+    }
+
+    fn read_headers(&mut self) -> Result<Option<ImageMetadata>, crate::errors::ImageErrors> {
+        self.decode_headers()
+            .map_err(<QoiErrors as Into<ImageErrors>>::into)?;
+
+        let (width, height) = self.dimensions().unwrap();
+
+        let metadata = ImageMetadata {
+            format: Some(ImageFormat::QoiSequence),
+            colorspace: self.colorspace().unwrap(),
+            depth: BitDepth::Eight,
+            width: width,
+            height: height,
+            ..Default::default()
+        };
+
+        Ok(Some(metadata))
+    }
+}
+
+/// An encoder storing all image frames as a QOI sequence
+///
+/// See [`zune_qoi::QoiSequenceEncoder`] for details on the layout
+#[derive(Copy, Clone, Default)]
+pub struct QoiSequenceEncoder {
+    options: Option<EncoderOptions>
+}
+
+impl QoiSequenceEncoder {
+    pub fn new() -> QoiSequenceEncoder {
+        QoiSequenceEncoder::default()
+    }
+
+    pub fn new_with_options(options: EncoderOptions) -> QoiSequenceEncoder {
+        QoiSequenceEncoder {
+            options: Some(options)
+        }
+    }
+}
+
+impl EncoderTrait for QoiSequenceEncoder {
+    fn name(&self) -> &'static str {
+        "QOI Sequence Encoder"
+    }
+
+    fn encode_inner<T: ZByteWriterTrait>(
+        &mut self, image: &Image, sink: T
+    ) -> Result<usize, ImageErrors> {
+        let options = create_options_for_encoder(self.options, image);
+
+        let frames = image.to_u8();
+
+        let mut encoder = zune_qoi::QoiSequenceEncoder::new(options);
+
+        for (data, frame) in frames.iter().zip(image.frames_ref()) {
+            encoder.add_frame(
+                data,
+                u16::try_from(frame.numerator).unwrap_or(u16::MAX),
+                u16::try_from(frame.denominator).unwrap_or(u16::MAX)
+            );
+        }
+        let bytes_written = encoder
+            .encode(sink)
+            .map_err(<QoiEncodeErrors as Into<ImgEncodeErrors>>::into)?;
+
+        Ok(bytes_written)
+    }
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::RGBA, ColorSpace::RGB]
+    }
+
+    fn format(&self) -> ImageFormat {
+        ImageFormat::QoiSequence
+    }
+
+    fn supported_bit_depth(&self) -> &'static [BitDepth] {
+        &[BitDepth::Eight]
+    }
+
+    fn default_depth(&self, _: BitDepth) -> BitDepth {
+        BitDepth::Eight
+    }
+
+    fn default_colorspace(&self, colorspace: ColorSpace) -> ColorSpace {
+        if colorspace.has_alpha() {
+            ColorSpace::RGBA
+        } else {
+            ColorSpace::RGB
+        }
+    }
+    fn set_options(&mut self, opts: EncoderOptions) {
+        self.options = Some(opts)
+    }
+
+    fn supports_animated_images(&self) -> bool {
+        true
+    }
+}
+
 impl From<zune_qoi::QoiErrors> for ImageErrors {
     fn from(error: zune_qoi::QoiErrors) -> Self {
         let err = format!("qoi: {error:?}");
 
-        ImageErrors::ImageDecodeErrors(err)
+        ImageErrors::CodecErrors(error.code(), err)
     }
 }
 
@@ -160,7 +335,7 @@ impl From<zune_qoi::QoiEncodeErrors> for ImgEncodeErrors {
     fn from(error: zune_qoi::QoiEncodeErrors) -> Self {
         let err = format!("qoi: {error:?}");
 
-        ImgEncodeErrors::Generic(err)
+        ImgEncodeErrors::CodecErrors(error.code(), err)
     }
 }
 
@@ -183,3 +358,51 @@ where
         Ok(self.output_buffer_size().unwrap())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::codecs::ImageFormat;
+    use crate::frame::Frame;
+    use crate::image::Image;
+
+    #[test]
+    fn test_qoi_sequence_roundtrip() {
+        let frame_a = Frame::from_u8(&[10; 30 * 20 * 3], ColorSpace::RGB, 1, 10);
+        let frame_b = Frame::from_u8(&[200; 30 * 20 * 3], ColorSpace::RGB, 2, 10);
+
+        let image = Image::new_frames(
+            vec![frame_a, frame_b],
+            zune_core::bit_depth::BitDepth::Eight,
+            30,
+            20,
+            ColorSpace::RGB
+        );
+        let data = image.write_to_vec(ImageFormat::QoiSequence).unwrap();
+
+        let decoded = Image::read(
+            zune_core::bytestream::ZCursor::new(&data),
+            DecoderOptions::default()
+        )
+        .unwrap();
+
+        assert_eq!(
+            decoded.metadata().image_format(),
+            Some(ImageFormat::QoiSequence)
+        );
+        assert_eq!(decoded.frames_len(), 2);
+        assert!(decoded == image);
+
+        let second = Image::read_frame(
+            zune_core::bytestream::ZCursor::new(&data),
+            DecoderOptions::default(),
+            1
+        )
+        .unwrap();
+
+        assert_eq!(second.frames_len(), 1);
+        assert!(second.frames_ref()[0] == image.frames_ref()[1]);
+    }
+}
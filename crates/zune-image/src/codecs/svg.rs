@@ -0,0 +1,229 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+#![cfg_attr(feature = "docs", doc(cfg(feature = "svg")))]
+#![cfg(feature = "svg")]
+//! SVG rasterization support
+//!
+//! This uses the delegate library [`resvg`](::resvg) for parsing and rendering images,
+//! it is built without text support so `<text>` elements are not drawn.
+//!
+//! Images are rendered at the size they specify, a different size can be requested via
+//! [`DecoderOptions::svg_set_size`], the result is always 8 bit RGBA.
+pub use ::resvg;
+use ::resvg::tiny_skia::{Pixmap, Transform};
+use ::resvg::usvg::{Options, Tree};
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::colorspace::{ColorCharacteristics, ColorSpace};
+use zune_core::errors::ZErrorCode;
+use zune_core::log::trace;
+use zune_core::options::DecoderOptions;
+
+use crate::codecs::ImageFormat;
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::metadata::ImageMetadata;
+use crate::traits::DecoderTrait;
+
+/// Probe some bytes to see if they look like an SVG document
+///
+/// This only looks at the start of the document, so `bytes`
+/// doesn't need to contain the whole file
+pub fn probe_svg(bytes: &[u8]) -> bool {
+    let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim_start();
+
+    if text.starts_with("<svg") {
+        return true;
+    }
+    // xml declaration, comments or a doctype may come before the root element
+    (text.starts_with("<?xml") || text.starts_with("<!")) && text.contains("<svg")
+}
+
+/// An SVG rasterizer
+///
+/// The whole file is read into memory on creation, the document is parsed
+/// lazily on the first call to `read_headers` or `decode`
+pub struct SvgDecoder {
+    data:    Vec<u8>,
+    options: DecoderOptions,
+    tree:    Option<Tree>,
+    /// Size the image will be rasterized at
+    size:    (usize, usize)
+}
+
+impl SvgDecoder {
+    pub fn try_new<T: ZByteReaderTrait>(
+        mut source: T, options: DecoderOptions
+    ) -> Result<SvgDecoder, ImageErrors> {
+        let mut data = vec![];
+        source
+            .read_remaining(&mut data)
+            .map_err(|x| ImageErrors::ImageDecodeErrors(format!("svg: {:?}", x)))?;
+
+        Ok(SvgDecoder {
+            data,
+            options,
+            tree: None,
+            size: (0, 0)
+        })
+    }
+
+    fn decode_headers(&mut self) -> Result<(), ImageErrors> {
+        if self.tree.is_some() {
+            return Ok(());
+        }
+        let tree = Tree::from_data(&self.data, &Options::default())?;
+
+        let (svg_width, svg_height) = (tree.size().width(), tree.size().height());
+        let aspect = svg_width / svg_height;
+
+        let (width, height) = match self.options.svg_get_size() {
+            (0, 0) => (svg_width.ceil() as usize, svg_height.ceil() as usize),
+            (0, height) => (((height as f32) * aspect).round() as usize, height),
+            (width, 0) => (width, ((width as f32) / aspect).round() as usize),
+            size => size
+        };
+
+        trace!("SVG size: ({},{})", svg_width, svg_height);
+        trace!("Raster size: ({},{})", width, height);
+
+        if width > self.options.max_width() {
+            let msg = format!(
+                "Image width {}, greater than max set width {}",
+                width,
+                self.options.max_width()
+            );
+            return Err(ImageErrors::CodecErrors(ZErrorCode::LimitsExceeded, msg));
+        }
+        if height > self.options.max_height() {
+            let msg = format!(
+                "Image height {}, greater than max set height {}",
+                height,
+                self.options.max_height()
+            );
+            return Err(ImageErrors::CodecErrors(ZErrorCode::LimitsExceeded, msg));
+        }
+        if width == 0 || height == 0 {
+            return Err(ImageErrors::ImageDecodeErrors(
+                "svg: Zero raster dimensions".to_string()
+            ));
+        }
+        self.size = (width, height);
+        self.tree = Some(tree);
+
+        Ok(())
+    }
+}
+
+impl DecoderTrait for SvgDecoder {
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let metadata = self.read_headers()?.unwrap();
+        let tree = self.tree.as_ref().unwrap();
+        let (width, height) = self.size;
+
+        let mut pixmap = Pixmap::new(width as u32, height as u32).ok_or(
+            ImageErrors::ImageDecodeErrors("svg: Could not allocate raster".to_string())
+        )?;
+
+        let transform = Transform::from_scale(
+            width as f32 / tree.size().width(),
+            height as f32 / tree.size().height()
+        );
+        ::resvg::render(tree, transform, &mut pixmap.as_mut());
+
+        // tiny-skia works with premultiplied alpha, images are expected to be straight
+        let pixels: Vec<u8> = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let color = pixel.demultiply();
+                [color.red(), color.green(), color.blue(), color.alpha()]
+            })
+            .collect();
+
+        let mut image = Image::from_u8(&pixels, width, height, ColorSpace::RGBA);
+        image.metadata = metadata;
+
+        Ok(image)
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        self.tree.as_ref().map(|_| self.size)
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        ColorSpace::RGBA
+    }
+
+    fn name(&self) -> &'static str {
+        "svg-decoder (resvg)"
+    }
+
+    fn read_headers(&mut self) -> Result<Option<ImageMetadata>, ImageErrors> {
+        self.decode_headers()?;
+
+        let (width, height) = self.size;
+
+        let metadata = ImageMetadata {
+            format: Some(ImageFormat::SVG),
+            colorspace: ColorSpace::RGBA,
+            depth: BitDepth::Eight,
+            width,
+            height,
+            color_trc: Some(ColorCharacteristics::sRGB),
+            ..Default::default()
+        };
+
+        Ok(Some(metadata))
+    }
+}
+
+impl From<::resvg::usvg::Error> for ImageErrors {
+    fn from(value: ::resvg::usvg::Error) -> Self {
+        Self::ImageDecodeErrors(format!("svg: {}", value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::codecs::ImageFormat;
+    use crate::image::Image;
+
+    const SVG: &[u8] = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10" viewBox="0 0 20 10">
+  <rect x="0" y="0" width="10" height="10" fill="#ff0000"/>
+</svg>"##;
+
+    #[test]
+    fn test_svg_rasterize_at_size() {
+        assert_eq!(
+            ImageFormat::guess_format(ZCursor::new(SVG)).map(|x| x.0),
+            Some(ImageFormat::SVG)
+        );
+        let image = Image::read(ZCursor::new(SVG), DecoderOptions::default()).unwrap();
+        assert_eq!(image.dimensions(), (20, 10));
+
+        // only the width is given, height follows the aspect ratio
+        let options = DecoderOptions::default().svg_set_size(40, 0);
+        let image = Image::read(ZCursor::new(SVG), options).unwrap();
+
+        assert_eq!(image.dimensions(), (40, 20));
+        assert_eq!(image.colorspace(), ColorSpace::RGBA);
+
+        let pixels = &image.flatten_frames::<u8>()[0];
+        // left half is the red rectangle, right half is transparent
+        assert_eq!(&pixels[(10 * 40 + 5) * 4..][..4], &[255, 0, 0, 255]);
+        assert_eq!(&pixels[(10 * 40 + 30) * 4..][..4], &[0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,207 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+#![cfg_attr(feature = "docs", doc(cfg(feature = "tga")))]
+#![cfg(feature = "tga")]
+//! TGA decoding and encoding support
+//!
+//! This uses the delegate library [`zune-tga`](zune_tga)
+//! for encoding and decoding images
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::{ZByteReaderTrait, ZByteWriterTrait};
+use zune_core::colorspace::ColorSpace;
+use zune_core::errors::ZErrorCodeTrait;
+use zune_core::options::EncoderOptions;
+pub use zune_tga::*;
+
+use crate::codecs::{create_options_for_encoder, ImageFormat};
+use crate::errors::{ImageErrors, ImgEncodeErrors};
+use crate::image::Image;
+use crate::metadata::ImageMetadata;
+use crate::traits::{DecodeInto, DecoderTrait, EncoderTrait};
+
+impl<T> DecoderTrait for TgaDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let metadata = self.read_headers()?.unwrap();
+        let pixels = self.decode()?;
+
+        let mut image = Image::from_u8(
+            &pixels,
+            metadata.width,
+            metadata.height,
+            metadata.colorspace
+        );
+        image.metadata = metadata;
+
+        Ok(image)
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        self.dimensions()
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        self.colorspace().unwrap_or(ColorSpace::Unknown)
+    }
+
+    fn name(&self) -> &'static str {
+        "TGA Decoder"
+    }
+
+    fn read_headers(&mut self) -> Result<Option<ImageMetadata>, ImageErrors> {
+        self.decode_headers()?;
+
+        let (width, height) = self.dimensions().unwrap();
+
+        let metadata = ImageMetadata {
+            format: Some(ImageFormat::TGA),
+            colorspace: self.colorspace().unwrap(),
+            depth: self.depth(),
+            width,
+            height,
+            ..Default::default()
+        };
+
+        Ok(Some(metadata))
+    }
+}
+
+impl From<TgaDecodeErrors> for ImageErrors {
+    fn from(value: TgaDecodeErrors) -> Self {
+        Self::CodecErrors(value.code(), format!("tga: {:?}", value))
+    }
+}
+
+/// A small wrapper against the TGA encoder that ties
+/// the bridge between Image struct and the buffer
+/// which [zune_tga::TgaEncoder](zune_tga::TgaEncoder) understands
+#[derive(Default)]
+pub struct TgaEncoder {
+    options: Option<EncoderOptions>,
+    rle:     bool
+}
+
+impl TgaEncoder {
+    /// Create a new encoder
+    pub fn new() -> TgaEncoder {
+        TgaEncoder::default()
+    }
+    /// Create a new encoder with specified options
+    pub fn new_with_options(options: EncoderOptions) -> TgaEncoder {
+        TgaEncoder {
+            options: Some(options),
+            rle:     false
+        }
+    }
+    /// Whether pixels should be run length encoded
+    ///
+    /// Default is false
+    pub fn set_rle(&mut self, yes: bool) -> &mut Self {
+        self.rle = yes;
+        self
+    }
+}
+
+impl EncoderTrait for TgaEncoder {
+    fn name(&self) -> &'static str {
+        "tga"
+    }
+
+    fn encode_inner<T: ZByteWriterTrait>(
+        &mut self, image: &Image, sink: T
+    ) -> Result<usize, ImageErrors> {
+        let options = create_options_for_encoder(self.options, image);
+
+        let data = &image.to_u8()[0];
+
+        let mut encoder = zune_tga::TgaEncoder::new(data, options);
+        encoder.set_rle(self.rle);
+
+        let written = encoder
+            .encode(sink)
+            .map_err(<TgaEncodeErrors as Into<ImgEncodeErrors>>::into)?;
+
+        Ok(written)
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::Luma,
+            ColorSpace::LumaA,
+            ColorSpace::RGB,
+            ColorSpace::RGBA
+        ]
+    }
+
+    fn format(&self) -> ImageFormat {
+        ImageFormat::TGA
+    }
+
+    fn supported_bit_depth(&self) -> &'static [BitDepth] {
+        &[BitDepth::Eight]
+    }
+
+    fn default_depth(&self, _: BitDepth) -> BitDepth {
+        BitDepth::Eight
+    }
+
+    fn set_options(&mut self, opts: EncoderOptions) {
+        self.options = Some(opts)
+    }
+}
+
+impl From<TgaEncodeErrors> for ImgEncodeErrors {
+    fn from(value: TgaEncodeErrors) -> Self {
+        ImgEncodeErrors::CodecErrors(value.code(), format!("tga: {:?}", value))
+    }
+}
+
+impl<T> DecodeInto for TgaDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    type BufferType = u8;
+
+    fn decode_into(&mut self, buffer: &mut [Self::BufferType]) -> Result<(), ImageErrors> {
+        self.decode_into(buffer)?;
+
+        Ok(())
+    }
+
+    fn decode_output_buffer_size(&mut self) -> Result<usize, ImageErrors> {
+        self.decode_headers()?;
+
+        // unwrap is okay because we successfully decoded image headers
+        Ok(self.output_buffer_size().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::codecs::ImageFormat;
+    use crate::image::Image;
+
+    #[test]
+    fn test_tga_roundtrip_and_guess() {
+        let image = Image::fill(10_u8, ColorSpace::RGBA, 30, 20);
+        let data = image.write_to_vec(ImageFormat::TGA).unwrap();
+
+        assert_eq!(
+            ImageFormat::guess_format(ZCursor::new(&data)).map(|x| x.0),
+            Some(ImageFormat::TGA)
+        );
+        let decoded = Image::read(ZCursor::new(&data), DecoderOptions::default()).unwrap();
+        assert!(decoded == image);
+    }
+}
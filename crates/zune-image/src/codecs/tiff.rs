@@ -0,0 +1,231 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+#![cfg_attr(feature = "docs", doc(cfg(feature = "tiff")))]
+#![cfg(feature = "tiff")]
+//! TIFF decoding support
+//!
+//! This uses the delegate library [`zune-tiff`](zune_tiff)
+//! for decoding images.
+//!
+//! Pages of a multi-page file (e.g. a scanned document or a fax) become frames
+//! of the resulting image. Frames must share a size and pixel format, so pages
+//! that differ from the first one are skipped, use [`Image::read_frame`] to
+//! decode a single page whatever its size.
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::colorspace::ColorSpace;
+use zune_core::errors::ZErrorCodeTrait;
+use zune_core::log::warn;
+use zune_core::result::DecodingResult;
+pub use zune_tiff::*;
+
+use crate::codecs::ImageFormat;
+use crate::errors::ImageErrors;
+use crate::frame::Frame;
+use crate::image::Image;
+use crate::metadata::{AlphaState, ImageMetadata};
+use crate::traits::DecoderTrait;
+
+/// Decode page `n` into a frame, pages have no duration so each
+/// one is given a second
+fn decode_page<T: ZByteReaderTrait>(
+    decoder: &mut TiffDecoder<T>, n: usize, colorspace: ColorSpace
+) -> Result<Frame, ImageErrors> {
+    let frame = match decoder.decode_page(n)? {
+        DecodingResult::U8(data) => Frame::from_u8(&data, colorspace, 1, 1),
+        DecodingResult::U16(data) => Frame::from_u16(&data, colorspace, 1, 1),
+        DecodingResult::F32(data) => Frame::from_f32(&data, colorspace, 1, 1),
+        _ => unreachable!()
+    };
+    Ok(frame)
+}
+
+/// Create image metadata for a page
+fn page_metadata(info: &TiffPageInfo, icc_profile: Option<&[u8]>) -> ImageMetadata {
+    let mut metadata = ImageMetadata {
+        format: Some(ImageFormat::TIFF),
+        colorspace: info.colorspace,
+        depth: info.depth,
+        width: info.width,
+        height: info.height,
+        orientation: info.orientation,
+        ..Default::default()
+    };
+    if info.premultiplied_alpha {
+        metadata.set_alpha(AlphaState::PreMultiplied);
+    }
+    if let Some(icc) = icc_profile {
+        metadata.set_icc_chunk(icc.to_vec());
+    }
+    metadata
+}
+
+impl<T> DecoderTrait for TiffDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let metadata = self.read_headers()?.unwrap();
+        let first = self.page_info(0).unwrap();
+
+        let mut frames = Vec::with_capacity(self.page_count());
+
+        for n in 0..self.page_count() {
+            let info = self.page_info(n).unwrap();
+
+            if (info.width, info.height, info.colorspace, info.depth)
+                != (first.width, first.height, first.colorspace, first.depth)
+            {
+                warn!(
+                    "Skipping TIFF page {} whose size or format differs from the first page",
+                    n
+                );
+                continue;
+            }
+            frames.push(decode_page(self, n, first.colorspace)?);
+        }
+        let mut image = Image::new_frames(
+            frames,
+            first.depth,
+            first.width,
+            first.height,
+            first.colorspace
+        );
+        image.metadata = metadata;
+
+        Ok(image)
+    }
+
+    fn decode_frame(&mut self, n: usize) -> Result<Image, ImageErrors> {
+        self.decode_headers()?;
+
+        let info = self
+            .page_info(n)
+            .ok_or(ImageErrors::FrameOutOfRange(n, self.page_count()))?;
+
+        let frame = decode_page(self, n, info.colorspace)?;
+        let mut image = Image::new_frames(
+            vec![frame],
+            info.depth,
+            info.width,
+            info.height,
+            info.colorspace
+        );
+        // the ICC profile is only read from the first page
+        image.metadata = page_metadata(&info, None);
+
+        Ok(image)
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        self.dimensions()
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        self.colorspace().unwrap_or(ColorSpace::Unknown)
+    }
+
+    fn name(&self) -> &'static str {
+        "TIFF Decoder"
+    }
+
+    fn read_headers(&mut self) -> Result<Option<ImageMetadata>, ImageErrors> {
+        self.decode_headers()?;
+
+        let info = self.page_info(0).unwrap();
+
+        Ok(Some(page_metadata(&info, self.icc_profile())))
+    }
+}
+
+impl From<TiffDecodeErrors> for ImageErrors {
+    fn from(value: TiffDecodeErrors) -> Self {
+        Self::CodecErrors(value.code(), format!("tiff: {:?}", value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::codecs::ImageFormat;
+    use crate::errors::ImageErrors;
+    use crate::image::Image;
+
+    /// A little endian TIFF with one uncompressed 8 bit grayscale page
+    /// per entry of `pages`, given as `(width, height, pixel value)`
+    fn multi_page_tiff(pages: &[(u16, u16, u8)]) -> Vec<u8> {
+        let mut data = b"II*\0".to_vec();
+        data.extend_from_slice(&[0; 4]);
+        let mut next_pointer = 4;
+
+        for &(width, height, value) in pages {
+            let strip = data.len() as u32;
+            let size = u32::from(width) * u32::from(height);
+            data.resize(data.len() + size as usize, value);
+
+            let ifd = data.len() as u32;
+            data[next_pointer..next_pointer + 4].copy_from_slice(&ifd.to_le_bytes());
+
+            // (tag, type, value) with SHORT (3) and LONG (4) types
+            let entries: [(u16, u16, u32); 7] = [
+                (256, 3, u32::from(width)),
+                (257, 3, u32::from(height)),
+                (258, 3, 8),
+                (262, 3, 1),
+                (273, 4, strip),
+                (278, 3, u32::from(height)),
+                (279, 4, size)
+            ];
+            data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+
+            for (tag, field_type, value) in entries {
+                data.extend_from_slice(&tag.to_le_bytes());
+                data.extend_from_slice(&field_type.to_le_bytes());
+                data.extend_from_slice(&1_u32.to_le_bytes());
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+            next_pointer = data.len();
+            data.extend_from_slice(&[0; 4]);
+        }
+        data
+    }
+
+    #[test]
+    fn test_tiff_pages_to_frames() {
+        // the third page has a different size, so it can't be a frame
+        let data = multi_page_tiff(&[(2, 2, 10), (2, 2, 20), (3, 1, 30)]);
+
+        let (format, reader) = ImageFormat::guess_format(ZCursor::new(&data)).unwrap();
+        assert_eq!(format, ImageFormat::TIFF);
+
+        let image = Image::read(reader, DecoderOptions::default()).unwrap();
+        assert_eq!(image.colorspace(), ColorSpace::Luma);
+        assert_eq!(image.dimensions(), (2, 2));
+
+        let pixels = image.flatten_to_u8();
+        assert_eq!(pixels.len(), 2);
+        assert_eq!(pixels[0], [10; 4]);
+        assert_eq!(pixels[1], [20; 4]);
+    }
+
+    #[test]
+    fn test_tiff_read_page() {
+        let data = multi_page_tiff(&[(2, 2, 10), (3, 1, 30)]);
+
+        let image = Image::read_frame(ZCursor::new(&data), DecoderOptions::default(), 1).unwrap();
+        assert_eq!(image.dimensions(), (3, 1));
+        assert_eq!(image.flatten_to_u8()[0], [30; 3]);
+
+        match Image::read_frame(ZCursor::new(&data), DecoderOptions::default(), 2) {
+            Ok(_) => panic!("The image only has two pages"),
+            Err(err) => assert!(matches!(err, ImageErrors::FrameOutOfRange(2, 2)))
+        }
+    }
+}
@@ -11,3 +11,5 @@
 //! running of images
 pub mod colorspace;
 pub mod depth;
+pub mod orientation;
+pub mod swizzle;
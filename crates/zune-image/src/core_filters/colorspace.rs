@@ -48,13 +48,88 @@ mod tests;
 ///
 /// This filter can also be accessed via
 /// [`image.convert_color()`](crate::image::Image::convert_color)
+///
+/// # Example
+/// - Convert an image to grayscale using Rec. 709 weights
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::core_filters::colorspace::{ColorspaceConv, GrayscaleWeights};
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+///
+/// let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+/// ColorspaceConv::new(ColorSpace::Luma)
+///     .set_grayscale_weights(GrayscaleWeights::Rec709)
+///     .execute(&mut image)
+///     .unwrap();
+/// ```
 pub struct ColorspaceConv {
-    to: ColorSpace
+    to:      ColorSpace,
+    weights: GrayscaleWeights
 }
 
 impl ColorspaceConv {
     pub fn new(to: ColorSpace) -> ColorspaceConv {
-        ColorspaceConv { to }
+        ColorspaceConv {
+            to,
+            weights: GrayscaleWeights::default()
+        }
+    }
+    /// Set the weights used to combine red, green and blue
+    /// when converting to `Luma` or `LumaA`
+    ///
+    /// Default is [`GrayscaleWeights::Rec601`]
+    #[must_use]
+    pub fn set_grayscale_weights(mut self, weights: GrayscaleWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+    /// Convert the image to `to` through an intermediate colorspace,
+    /// keeping the options of this conversion
+    fn convert_to(&self, image: &mut Image, to: ColorSpace) -> Result<(), ImageErrors> {
+        ColorspaceConv::new(to)
+            .set_grayscale_weights(self.weights)
+            .execute(image)
+    }
+}
+
+/// Weights used to compute the luminance of a pixel when converting
+/// color images to grayscale
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum GrayscaleWeights {
+    /// ITU-R BT.601 luma, `0.299 R + 0.587 G + 0.114 B`
+    ///
+    /// This is used by JPEG and most image libraries and matches standard definition video
+    #[default]
+    Rec601,
+    /// ITU-R BT.709 luma, `0.2126 R + 0.7152 G + 0.0722 B`
+    ///
+    /// This matches high definition video, the weights are applied to gamma encoded values
+    Rec709,
+    /// True luminance, the sRGB transfer function is removed before applying
+    /// BT.709 weights and the result is encoded back to sRGB
+    ///
+    /// This matches the light emitted by the display, but it is also the slowest
+    Linear
+}
+
+impl GrayscaleWeights {
+    pub fn from_string_result(input: &str) -> Result<Self, String> {
+        match input {
+            "rec601" | "bt601" => Ok(Self::Rec601),
+            "rec709" | "bt709" => Ok(Self::Rec709),
+            "linear" => Ok(Self::Linear),
+            _ => Err(
+                "Unknown grayscale weights, accepted values are rec601,rec709,linear".to_string()
+            )
+        }
+    }
+    /// Return the red, green and blue weights
+    pub const fn coefficients(self) -> [f64; 3] {
+        match self {
+            GrayscaleWeights::Rec601 => [0.2989, 0.5870, 0.1140],
+            GrayscaleWeights::Rec709 | GrayscaleWeights::Linear => [0.2126, 0.7152, 0.0722]
+        }
     }
 }
 impl OperationsTrait for ColorspaceConv {
@@ -73,8 +148,12 @@ impl OperationsTrait for ColorspaceConv {
         match from {
             ColorSpace::RGB => match self.to {
                 ColorSpace::RGBA => convert_adding_opaque_alpha(image)?,
-                ColorSpace::Luma => convert_rgb_to_grayscale(image, self.to, self.to.has_alpha())?,
-                ColorSpace::LumaA => convert_rgb_to_grayscale(image, self.to, self.to.has_alpha())?,
+                ColorSpace::Luma => {
+                    convert_rgb_to_grayscale(image, self.to, self.to.has_alpha(), self.weights)?
+                }
+                ColorSpace::LumaA => {
+                    convert_rgb_to_grayscale(image, self.to, self.to.has_alpha(), self.weights)?
+                }
                 ColorSpace::CMYK => convert_rgb_to_cmyk(image)?,
                 ColorSpace::BGR => convert_rgb_bgr(from, self.to, image)?,
                 ColorSpace::BGRA => convert_rgb_bgr(from, self.to, image)?,
@@ -91,8 +170,12 @@ impl OperationsTrait for ColorspaceConv {
                 ColorSpace::BGR => convert_rgb_bgr(from, self.to, image)?,
                 ColorSpace::BGRA => convert_rgb_bgr(from, self.to, image)?,
                 ColorSpace::ARGB => convert_rgba_to_argb_or_vice_versa(image)?,
-                ColorSpace::LumaA => convert_rgb_to_grayscale(image, self.to, self.to.has_alpha())?,
-                ColorSpace::Luma => convert_rgb_to_grayscale(image, self.to, self.to.has_alpha())?,
+                ColorSpace::LumaA => {
+                    convert_rgb_to_grayscale(image, self.to, self.to.has_alpha(), self.weights)?
+                }
+                ColorSpace::Luma => {
+                    convert_rgb_to_grayscale(image, self.to, self.to.has_alpha(), self.weights)?
+                }
                 ColorSpace::HSV => convert_rgb_to_hsv(image)?,
                 ColorSpace::HSL => convert_rgb_to_hsl(image)?,
                 ColorSpace::CMYK => {
@@ -112,7 +195,7 @@ impl OperationsTrait for ColorspaceConv {
                     // convert to rgb
                     convert_luma_to_rgb(image, ColorSpace::RGB)?;
                     image.set_colorspace(ColorSpace::RGB);
-                    image.convert_color(color)?;
+                    self.convert_to(image, color)?;
                 }
             },
 
@@ -124,7 +207,7 @@ impl OperationsTrait for ColorspaceConv {
                     // convert to rgba
                     convert_luma_to_rgb(image, ColorSpace::RGBA)?;
                     image.set_colorspace(ColorSpace::RGBA);
-                    image.convert_color(color)?;
+                    self.convert_to(image, color)?;
                 }
             },
             ColorSpace::CMYK => {
@@ -132,14 +215,14 @@ impl OperationsTrait for ColorspaceConv {
                 convert_cmyk_to_rgb(image, ColorSpace::RGB)?;
                 image.set_colorspace(ColorSpace::RGB);
                 // convert to desired colorspace
-                image.convert_color(self.to)?;
+                self.convert_to(image, self.to)?;
             }
             ColorSpace::BGR => {
                 // first convert to rgb
                 convert_rgb_bgr(from, ColorSpace::RGB, image)?;
                 // then convert to desired color
                 image.set_colorspace(ColorSpace::RGB);
-                image.convert_color(self.to)?;
+                self.convert_to(image, self.to)?;
             }
             ColorSpace::BGRA => {
                 // BGRA and RGBA are similar with difference being only the R and B are swapped
@@ -148,28 +231,28 @@ impl OperationsTrait for ColorspaceConv {
 
                 // then use RGBA conversions
                 image.set_colorspace(ColorSpace::RGBA);
-                image.convert_color(self.to)?;
+                self.convert_to(image, self.to)?;
             }
 
             ColorSpace::ARGB => {
                 // convert to RGBA
                 convert_rgba_to_argb_or_vice_versa(image)?;
                 image.set_colorspace(ColorSpace::RGBA);
-                image.convert_color(self.to)?;
+                self.convert_to(image, self.to)?;
             }
             ColorSpace::HSL => {
                 // convert to rgb
                 convert_hsl_to_rgb(image)?;
                 image.set_colorspace(ColorSpace::RGB);
                 // convert to desired colorspace
-                image.convert_color(self.to)?;
+                self.convert_to(image, self.to)?;
             }
             ColorSpace::HSV => {
                 // convert to rgb
                 convert_hsv_to_rgb(image)?;
                 image.set_colorspace(ColorSpace::RGB);
                 // convert to desired colorspace
-                image.convert_color(self.to)?;
+                self.convert_to(image, self.to)?;
             }
             ColorSpace::MultiBand(_) => {
                 // handle multi-band images
@@ -6,9 +6,9 @@ use crate::channel::Channel;
 use crate::core_filters::colorspace::grayscale::{
     rgb_to_grayscale_f32, rgb_to_grayscale_u16, rgb_to_grayscale_u8
 };
-use crate::core_filters::colorspace::rgb_to_cmyk;
 use crate::core_filters::colorspace::rgb_to_hsl::{hsl_to_rgb, rgb_to_hsl};
 use crate::core_filters::colorspace::rgb_to_hsv::{hsv_to_rgb, rgb_to_hsv};
+use crate::core_filters::colorspace::{rgb_to_cmyk, GrayscaleWeights};
 use crate::errors::ImageErrors;
 use crate::image::Image;
 
@@ -54,7 +54,7 @@ pub fn convert_adding_opaque_alpha(image: &mut Image) -> Result<(), ImageErrors>
 }
 
 pub fn convert_rgb_to_grayscale(
-    image: &mut Image, to: ColorSpace, preserve_alpha: bool
+    image: &mut Image, to: ColorSpace, preserve_alpha: bool, weights: GrayscaleWeights
 ) -> Result<(), ImageErrors> {
     let im_colorspace = image.colorspace();
 
@@ -87,7 +87,8 @@ pub fn convert_rgb_to_grayscale(
                     g,
                     b,
                     out.reinterpret_as_mut::<u8>().unwrap(),
-                    max_value as u8
+                    max_value as u8,
+                    weights
                 );
 
                 if preserve_alpha && colorspace.has_alpha() {
@@ -110,7 +111,14 @@ pub fn convert_rgb_to_grayscale(
                 let b = channel[2].reinterpret_as::<u16>().unwrap();
                 let mut out = Channel::new_with_length::<u16>(size);
 
-                rgb_to_grayscale_u16(r, g, b, out.reinterpret_as_mut::<u16>().unwrap(), max_value);
+                rgb_to_grayscale_u16(
+                    r,
+                    g,
+                    b,
+                    out.reinterpret_as_mut::<u16>().unwrap(),
+                    max_value,
+                    weights
+                );
 
                 if preserve_alpha && colorspace.has_alpha() {
                     frame.set_channels(vec![out, channel[3].clone()]);
@@ -140,7 +148,8 @@ pub fn convert_rgb_to_grayscale(
                     g,
                     b,
                     out.reinterpret_as_mut::<f32>().unwrap(),
-                    max_value as f32
+                    max_value as f32,
+                    weights
                 );
 
                 if preserve_alpha && colorspace.has_alpha() {
@@ -7,19 +7,76 @@
  */
 
 use crate::core_filters::colorspace::grayscale::scalar::{
-    convert_rgb_to_grayscale_scalar, convert_rgb_to_grayscale_scalar_f32,
-    convert_rgb_to_grayscale_scalar_u16
+    convert_rgb_to_grayscale_linear, convert_rgb_to_grayscale_scalar,
+    convert_rgb_to_grayscale_scalar_f32, convert_rgb_to_grayscale_scalar_u16, linear_to_srgb,
+    srgb_to_linear
 };
+use crate::core_filters::colorspace::GrayscaleWeights;
 
 mod avx2;
 mod scalar;
 mod sse41;
 
-pub fn rgb_to_grayscale_u16(r: &[u16], g: &[u16], b: &[u16], out: &mut [u16], max_value: u16) {
-    convert_rgb_to_grayscale_scalar_u16(r, g, b, out, max_value);
+/// Build a table mapping every sample up to `max_value` to linear light
+fn srgb_to_linear_table(max_value: usize) -> Vec<f32> {
+    let scale = 1.0 / max_value as f32;
+
+    (0..=max_value)
+        .map(|x| srgb_to_linear(x as f32 * scale))
+        .collect()
+}
+
+pub fn rgb_to_grayscale_u16(
+    r: &[u16], g: &[u16], b: &[u16], out: &mut [u16], max_value: u16, weights: GrayscaleWeights
+) {
+    if weights == GrayscaleWeights::Linear {
+        let table = srgb_to_linear_table(usize::from(max_value));
+        let max = f32::from(max_value);
+
+        return convert_rgb_to_grayscale_linear(
+            r,
+            g,
+            b,
+            out,
+            weights.coefficients(),
+            |x| table[usize::from(x.min(max_value))],
+            |x| (linear_to_srgb(x) * max).round().clamp(0.0, max) as u16
+        );
+    }
+    convert_rgb_to_grayscale_scalar_u16(r, g, b, out, max_value, weights.coefficients());
 }
 
-pub fn rgb_to_grayscale_u8(r: &[u8], g: &[u8], b: &[u8], out: &mut [u8], max_value: u8) {
+pub fn rgb_to_grayscale_u8(
+    r: &[u8], g: &[u8], b: &[u8], out: &mut [u8], max_value: u8, weights: GrayscaleWeights
+) {
+    match weights {
+        GrayscaleWeights::Linear => {
+            let table = srgb_to_linear_table(usize::from(max_value));
+            let max = f32::from(max_value);
+
+            return convert_rgb_to_grayscale_linear(
+                r,
+                g,
+                b,
+                out,
+                weights.coefficients(),
+                |x| table[usize::from(x.min(max_value))],
+                |x| (linear_to_srgb(x) * max).round().clamp(0.0, max) as u8
+            );
+        }
+        GrayscaleWeights::Rec709 => {
+            return convert_rgb_to_grayscale_scalar(
+                r,
+                g,
+                b,
+                out,
+                max_value,
+                weights.coefficients()
+            );
+        }
+        // the simd routines have the Rec.601 weights built in
+        GrayscaleWeights::Rec601 => ()
+    }
     #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
     {
         #[cfg(feature = "avx2")]
@@ -44,11 +101,24 @@ pub fn rgb_to_grayscale_u8(r: &[u8], g: &[u8], b: &[u8], out: &mut [u8], max_val
             }
         }
     }
-    convert_rgb_to_grayscale_scalar(r, g, b, out, max_value);
+    convert_rgb_to_grayscale_scalar(r, g, b, out, max_value, weights.coefficients());
 }
 
-pub fn rgb_to_grayscale_f32(r: &[f32], g: &[f32], b: &[f32], out: &mut [f32], max_value: f32) {
-    convert_rgb_to_grayscale_scalar_f32(r, g, b, out, max_value);
+pub fn rgb_to_grayscale_f32(
+    r: &[f32], g: &[f32], b: &[f32], out: &mut [f32], max_value: f32, weights: GrayscaleWeights
+) {
+    if weights == GrayscaleWeights::Linear {
+        return convert_rgb_to_grayscale_linear(
+            r,
+            g,
+            b,
+            out,
+            weights.coefficients(),
+            srgb_to_linear,
+            linear_to_srgb
+        );
+    }
+    convert_rgb_to_grayscale_scalar_f32(r, g, b, out, max_value, weights.coefficients());
 }
 
 #[cfg(feature = "benchmarks")]
@@ -57,6 +127,7 @@ mod benchmarks {
     extern crate test;
 
     use crate::core_filters::colorspace::grayscale::scalar::convert_rgb_to_grayscale_scalar_u16;
+    use crate::core_filters::colorspace::GrayscaleWeights;
 
     #[cfg(feature = "sse41")]
     #[bench]
@@ -110,7 +181,14 @@ mod benchmarks {
 
         let mut c4 = vec![255; dimensions];
         b.iter(|| {
-            convert_rgb_to_grayscale_scalar_u16(&c1, &c2, &c3, &mut c4, 255);
+            convert_rgb_to_grayscale_scalar_u16(
+                &c1,
+                &c2,
+                &c3,
+                &mut c4,
+                255,
+                GrayscaleWeights::Rec601.coefficients()
+            );
         });
     }
 }
@@ -15,6 +15,7 @@ use std::arch::x86::*;
 use std::arch::x86_64::*;
 
 use crate::core_filters::colorspace::grayscale::scalar::convert_rgb_to_grayscale_scalar;
+use crate::core_filters::colorspace::GrayscaleWeights;
 
 #[target_feature(enable = "avx2")]
 #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
@@ -29,8 +30,8 @@ pub(crate) unsafe fn convert_rgb_to_grayscale_u8_avx2(r: &[u8], g: &[u8], b: &[u
 
     for (((r_chunk, g_chunk), b_chunk), out) in r
         .chunks_exact(CHUNK_SIZE)
-        .zip(b.chunks_exact(CHUNK_SIZE))
         .zip(g.chunks_exact(CHUNK_SIZE))
+        .zip(b.chunks_exact(CHUNK_SIZE))
         .zip(gr.chunks_exact_mut(CHUNK_SIZE))
     {
         // Load to memory
@@ -77,12 +78,18 @@ pub(crate) unsafe fn convert_rgb_to_grayscale_u8_avx2(r: &[u8], g: &[u8], b: &[u
         // do the remainder
         let rem = r.len() % CHUNK_SIZE;
         let start = r.len() - rem;
-        let c_start = r.len() - (rem / 3);
 
-        let c1 = &r[c_start..];
-        let c2 = &g[c_start..];
-        let c3 = &b[c_start..];
+        let c1 = &r[start..];
+        let c2 = &g[start..];
+        let c3 = &b[start..];
 
-        convert_rgb_to_grayscale_scalar(c1, c2, c3, &mut gr[start..], 255);
+        convert_rgb_to_grayscale_scalar(
+            c1,
+            c2,
+            c3,
+            &mut gr[start..],
+            255,
+            GrayscaleWeights::Rec601.coefficients()
+        );
     }
 }
@@ -8,13 +8,13 @@
 
 #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
 pub(crate) fn convert_rgb_to_grayscale_scalar(
-    r: &[u8], g: &[u8], b: &[u8], gr: &mut [u8], max_value: u8
+    r: &[u8], g: &[u8], b: &[u8], gr: &mut [u8], max_value: u8, coefficients: [f64; 3]
 ) {
     let max_value = u32::from(max_value);
 
-    let r_coef = (0.2989 * 32768.0 + 0.5) as u32;
-    let g_coef = (0.5870 * 32768.0 + 0.5) as u32;
-    let b_coef = (0.1140 * 32768.0 + 0.5) as u32;
+    let r_coef = (coefficients[0] * 32768.0 + 0.5) as u32;
+    let g_coef = (coefficients[1] * 32768.0 + 0.5) as u32;
+    let b_coef = (coefficients[2] * 32768.0 + 0.5) as u32;
 
     for (((r_v, g_v), b_v), g_out) in r.iter().zip(g.iter()).zip(b.iter()).zip(gr.iter_mut()) {
         // Multiply input elements by 64 for improved accuracy.
@@ -42,13 +42,13 @@ pub(crate) fn convert_rgb_to_grayscale_scalar(
     clippy::unreadable_literal
 )]
 pub(crate) fn convert_rgb_to_grayscale_scalar_u16(
-    r: &[u16], g: &[u16], b: &[u16], gr: &mut [u16], max_value: u16
+    r: &[u16], g: &[u16], b: &[u16], gr: &mut [u16], max_value: u16, coefficients: [f64; 3]
 ) {
     let max_value = u64::from(max_value);
 
-    let r_coef = (0.2989 * 2147483648.0 + 0.5) as u64;
-    let g_coef = (0.5870 * 2147483648.0 + 0.5) as u64;
-    let b_coef = (0.1140 * 2147483648.0 + 0.5) as u64;
+    let r_coef = (coefficients[0] * 2147483648.0 + 0.5) as u64;
+    let g_coef = (coefficients[1] * 2147483648.0 + 0.5) as u64;
+    let b_coef = (coefficients[2] * 2147483648.0 + 0.5) as u64;
 
     for (((r_v, g_v), b_v), g_out) in r.iter().zip(g.iter()).zip(b.iter()).zip(gr.iter_mut()) {
         // Multiply input elements by 64 for improved accuracy.
@@ -66,16 +66,17 @@ pub(crate) fn convert_rgb_to_grayscale_scalar_u16(
     }
 }
 
+#[allow(clippy::cast_possible_truncation)]
 pub(crate) fn convert_rgb_to_grayscale_scalar_f32(
-    r: &[f32], g: &[f32], b: &[f32], gr: &mut [f32], _max_value: f32
+    r: &[f32], g: &[f32], b: &[f32], gr: &mut [f32], _max_value: f32, coefficients: [f64; 3]
 ) {
     /*
      * The algorithm assigns different weights to colors
      * i.e it just doesn't average them
      */
-    let r_coef = 0.2989;
-    let g_coef = 0.5870;
-    let b_coef = 0.1140;
+    let r_coef = coefficients[0] as f32;
+    let g_coef = coefficients[1] as f32;
+    let b_coef = coefficients[2] as f32;
 
     for (((r_v, g_v), b_v), g_out) in r.iter().zip(g.iter()).zip(b.iter()).zip(gr.iter_mut()) {
         let r = r_coef * (*r_v);
@@ -87,3 +88,42 @@ pub(crate) fn convert_rgb_to_grayscale_scalar_f32(
         *g_out = gray;
     }
 }
+
+/// Convert to grayscale by weighting linear light values
+///
+/// `to_linear` maps an sRGB encoded sample to linear light between 0.0 and 1.0
+/// and `from_linear` maps the luminance back to an sRGB encoded sample
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn convert_rgb_to_grayscale_linear<T: Copy>(
+    r: &[T], g: &[T], b: &[T], gr: &mut [T], coefficients: [f64; 3], to_linear: impl Fn(T) -> f32,
+    from_linear: impl Fn(f32) -> T
+) {
+    let r_coef = coefficients[0] as f32;
+    let g_coef = coefficients[1] as f32;
+    let b_coef = coefficients[2] as f32;
+
+    for (((r_v, g_v), b_v), g_out) in r.iter().zip(g.iter()).zip(b.iter()).zip(gr.iter_mut()) {
+        let luminance =
+            r_coef * to_linear(*r_v) + g_coef * to_linear(*g_v) + b_coef * to_linear(*b_v);
+
+        *g_out = from_linear(luminance);
+    }
+}
+
+/// Remove the sRGB transfer function from a value between 0.0 and 1.0
+pub(crate) fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Apply the sRGB transfer function to a linear value between 0.0 and 1.0
+pub(crate) fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -4,7 +4,7 @@ use nanorand::Rng;
 use zune_core::bytestream::ZCursor;
 use zune_core::colorspace::ColorSpace;
 
-use crate::core_filters::colorspace::ColorspaceConv;
+use crate::core_filters::colorspace::{ColorspaceConv, GrayscaleWeights};
 use crate::image::Image;
 use crate::traits::OperationsTrait;
 
@@ -130,3 +130,60 @@ fn test_luma_a_to_other_colors() {
     let [u8_im, u16_im, f32_im] = create_image(ColorSpace::LumaA);
     single_tests(&u8_im, &u16_im, &f32_im);
 }
+
+#[test]
+fn test_grayscale_weights() {
+    let convert = |weights: GrayscaleWeights, pixel: [u8; 3], colorspace: ColorSpace| {
+        let mut image = Image::from_fn::<u8, _>(101, 3, colorspace, |_, _, pix| {
+            pix[..3].copy_from_slice(&pixel);
+        });
+        ColorspaceConv::new(ColorSpace::Luma)
+            .set_grayscale_weights(weights)
+            .execute(&mut image)
+            .unwrap();
+        let pixels = &image.flatten_to_u8()[0];
+        // simd and scalar remainder paths must agree
+        assert!(pixels.iter().all(|x| *x == pixels[0]));
+        pixels[0]
+    };
+    // pure green
+    assert_eq!(
+        convert(GrayscaleWeights::Rec601, [0, 255, 0], ColorSpace::RGB),
+        149
+    );
+    assert_eq!(
+        convert(GrayscaleWeights::Rec709, [0, 255, 0], ColorSpace::RGB),
+        182
+    );
+    assert_eq!(
+        convert(GrayscaleWeights::Linear, [0, 255, 0], ColorSpace::RGB),
+        220
+    );
+    // weights are kept when going through an intermediate colorspace
+    assert_eq!(
+        convert(GrayscaleWeights::Linear, [0, 255, 0], ColorSpace::BGR),
+        220
+    );
+    // neutral colors are unchanged
+    assert_eq!(
+        convert(GrayscaleWeights::Linear, [128, 128, 128], ColorSpace::RGB),
+        128
+    );
+}
+
+#[test]
+fn test_grayscale_weights_linear_f32() {
+    let mut image = Image::fill::<f32>(0.0, ColorSpace::RGB, 10, 10);
+    image.frames_mut()[0].channels_vec()[1]
+        .fill(1.0_f32)
+        .unwrap();
+
+    ColorspaceConv::new(ColorSpace::Luma)
+        .set_grayscale_weights(GrayscaleWeights::Linear)
+        .execute(&mut image)
+        .unwrap();
+    let luma = image.frames_ref()[0].channels_ref(ColorSpace::Luma, false)[0]
+        .reinterpret_as::<f32>()
+        .unwrap()[0];
+    assert!((luma - 0.8625).abs() < 1e-3);
+}
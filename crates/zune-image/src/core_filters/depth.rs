@@ -88,16 +88,170 @@ pub(crate) fn depth_u8_to_u16(from: &[u8], to: &mut [u16], max_value: u16) {
     }
 }
 
+/// How values are rounded when converting to a lower bit depth
+/// without dithering
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum DepthRounding {
+    /// Drop the fractional part, this is the fastest but darkens the image slightly
+    #[default]
+    Truncate,
+    /// Round to the nearest value
+    Nearest
+}
+
+/// Dithering applied when converting to a lower bit depth
+///
+/// Dithering trades the banding visible in smooth gradients for fine grained noise
+/// by varying how neighbouring pixels are rounded, so that on average they keep
+/// the precision of the source.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum DitherMethod {
+    /// No dithering, values are rounded according to [`DepthRounding`]
+    #[default]
+    None,
+    /// Ordered dithering using an 8x8 Bayer matrix
+    ///
+    /// The pattern is fixed, so the result doesn't depend on neighbouring pixels and
+    /// compresses and animates well
+    Ordered,
+    /// Floyd-Steinberg error diffusion
+    ///
+    /// Rounding errors are carried over to neighbouring pixels, this gives the most
+    /// accurate result but is slower
+    FloydSteinberg
+}
+
+/// 8x8 Bayer threshold matrix, values from 0 to 63
+const BAYER_8X8: [u8; 64] = [
+    0, 32, 8, 40, 2, 34, 10, 42, 48, 16, 56, 24, 50, 18, 58, 26, 12, 44, 4, 36, 14, 46, 6, 38, 60,
+    28, 52, 20, 62, 30, 54, 22, 3, 35, 11, 43, 1, 33, 9, 41, 51, 19, 59, 27, 49, 17, 57, 25, 15,
+    47, 7, 39, 13, 45, 5, 37, 63, 31, 55, 23, 61, 29, 53, 21
+];
+
+/// Quantize values to a lower bit depth with rounding or dithering
+///
+/// # Arguments
+/// - `from`: Source pixels of a single channel
+/// - `to`: Destination pixels
+/// - `width`: Image width, used to find the position of pixels for dithering
+/// - `scale`: Multiplier converting a source value to a destination value
+/// - `max`: Maximum destination value, results are clamped to it
+/// - `convert`: Convert a source pixel to f32
+/// - `store`: Convert a quantized value between zero and `max` to a destination pixel
+#[allow(clippy::too_many_arguments, clippy::cast_precision_loss)]
+fn reduce_depth<T: Copy, U>(
+    from: &[T], to: &mut [U], width: usize, scale: f32, max: f32, dither: DitherMethod,
+    rounding: DepthRounding, convert: impl Fn(T) -> f32, store: impl Fn(f32) -> U
+) {
+    let width = width.max(1);
+
+    match dither {
+        DitherMethod::None => {
+            for (old, new) in from.iter().zip(to.iter_mut()) {
+                let value = convert(*old) * scale;
+                let value = match rounding {
+                    DepthRounding::Truncate => value.floor(),
+                    DepthRounding::Nearest => value.round()
+                };
+                *new = store(value.clamp(0.0, max));
+            }
+        }
+        DitherMethod::Ordered => {
+            for (y, (old_row, new_row)) in from.chunks(width).zip(to.chunks_mut(width)).enumerate()
+            {
+                let thresholds = &BAYER_8X8[(y % 8) * 8..(y % 8) * 8 + 8];
+
+                for (x, (old, new)) in old_row.iter().zip(new_row.iter_mut()).enumerate() {
+                    // offset between 0 and 1 so that exact values are unchanged
+                    let offset = (f32::from(thresholds[x % 8]) + 0.5) / 64.0;
+                    let value = (convert(*old) * scale + offset).floor();
+                    *new = store(value.clamp(0.0, max));
+                }
+            }
+        }
+        DitherMethod::FloydSteinberg => {
+            // errors carried to the current and next row, with a
+            // pixel of padding on either side
+            let mut current = vec![0.0_f32; width + 2];
+            let mut next = vec![0.0_f32; width + 2];
+
+            for (old_row, new_row) in from.chunks(width).zip(to.chunks_mut(width)) {
+                for (x, (old, new)) in old_row.iter().zip(new_row.iter_mut()).enumerate() {
+                    let value = convert(*old) * scale + current[x + 1];
+                    let quantized = value.round().clamp(0.0, max);
+                    let error = value - quantized;
+
+                    current[x + 2] += error * (7.0 / 16.0);
+                    next[x] += error * (3.0 / 16.0);
+                    next[x + 1] += error * (5.0 / 16.0);
+                    next[x + 2] += error * (1.0 / 16.0);
+
+                    *new = store(quantized);
+                }
+                core::mem::swap(&mut current, &mut next);
+                next.fill(0.0);
+            }
+        }
+    }
+}
+
 /// Change the image's bit depth from it's initial
 /// value to the one specified by this operation.
+///
+/// When converting to a lower depth, values are truncated by default,
+/// see [`set_rounding`](Self::set_rounding) and [`set_dither`](Self::set_dither)
+/// to avoid banding in gradients.
+///
+/// # Example
+/// - Convert a 16 bit image to 8 bits with error diffusion
+/// ```
+/// use zune_core::bit_depth::BitDepth;
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::core_filters::depth::{Depth, DitherMethod};
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+///
+/// let mut image = Image::fill::<u16>(1000, ColorSpace::RGB, 100, 100);
+/// Depth::new(BitDepth::Eight)
+///     .set_dither(DitherMethod::FloydSteinberg)
+///     .execute(&mut image)
+///     .unwrap();
+/// ```
 #[derive(Copy, Clone)]
 pub struct Depth {
-    depth: BitDepth
+    depth:    BitDepth,
+    dither:   DitherMethod,
+    rounding: DepthRounding
 }
 
 impl Depth {
     pub fn new(depth: BitDepth) -> Depth {
-        Depth { depth }
+        Depth {
+            depth,
+            dither: DitherMethod::None,
+            rounding: DepthRounding::Truncate
+        }
+    }
+    /// Set the dithering used when converting to a lower bit depth
+    ///
+    /// Default is [`DitherMethod::None`]
+    #[must_use]
+    pub fn set_dither(mut self, dither: DitherMethod) -> Depth {
+        self.dither = dither;
+        self
+    }
+    /// Set how values are rounded when converting to a lower bit depth,
+    /// this is ignored when dithering
+    ///
+    /// Default is [`DepthRounding::Truncate`]
+    #[must_use]
+    pub fn set_rounding(mut self, rounding: DepthRounding) -> Depth {
+        self.rounding = rounding;
+        self
+    }
+    /// Return true if conversions to a lower depth need more than truncation
+    fn needs_quantization(&self) -> bool {
+        self.dither != DitherMethod::None || self.rounding != DepthRounding::Truncate
     }
 }
 
@@ -114,8 +268,61 @@ impl OperationsTrait for Depth {
             return Ok(());
         }
 
+        let (width, _) = image.dimensions();
+
         for channel in image.channels_mut(false) {
             match (image_depth, self.depth) {
+                (BitDepth::Sixteen, BitDepth::Eight) if self.needs_quantization() => {
+                    let old_data = channel.reinterpret_as::<u16>().unwrap();
+                    let mut new_channel = Channel::new_with_length::<u8>(channel.len() / 2);
+
+                    reduce_depth(
+                        old_data,
+                        new_channel.reinterpret_as_mut::<u8>().unwrap(),
+                        width,
+                        255.0 / f32::from(image_depth.max_value()),
+                        255.0,
+                        self.dither,
+                        self.rounding,
+                        f32::from,
+                        |x| x as u8
+                    );
+                    *channel = new_channel;
+                }
+                (BitDepth::Float32, BitDepth::Eight) if self.needs_quantization() => {
+                    let old_data = channel.reinterpret_as::<f32>().unwrap();
+                    let mut new_channel = Channel::new_with_length::<u8>(channel.len() / 4);
+
+                    reduce_depth(
+                        old_data,
+                        new_channel.reinterpret_as_mut::<u8>().unwrap(),
+                        width,
+                        255.0,
+                        255.0,
+                        self.dither,
+                        self.rounding,
+                        |x| x,
+                        |x| x as u8
+                    );
+                    *channel = new_channel;
+                }
+                (BitDepth::Float32, BitDepth::Sixteen) if self.needs_quantization() => {
+                    let old_data = channel.reinterpret_as::<f32>().unwrap();
+                    let mut new_channel = Channel::new_with_length::<u16>(channel.len() / 2);
+
+                    reduce_depth(
+                        old_data,
+                        new_channel.reinterpret_as_mut::<u16>().unwrap(),
+                        width,
+                        65535.0,
+                        65535.0,
+                        self.dither,
+                        self.rounding,
+                        |x| x,
+                        |x| x as u16
+                    );
+                    *channel = new_channel;
+                }
                 (BitDepth::Eight, BitDepth::Sixteen) => {
                     let old_data = channel.reinterpret_as().unwrap();
                     let mut new_channel = Channel::new_with_length::<u16>(old_data.len() * 2);
@@ -213,3 +420,44 @@ impl OperationsTrait for Depth {
         &[BitType::U8, BitType::U16, BitType::F32]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::core_filters::depth::{Depth, DepthRounding, DitherMethod};
+    use crate::image::Image;
+    use crate::traits::OperationsTrait;
+
+    /// Convert a 16 bit image halfway between 8 bit values 128 and 129
+    fn reduce(depth: Depth) -> Vec<u8> {
+        let mut image = Image::fill::<u16>(128 * 257 + 129, ColorSpace::Luma, 16, 16);
+        depth.execute(&mut image).unwrap();
+        image.flatten_to_u8().remove(0)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn mean(pixels: &[u8]) -> f32 {
+        pixels.iter().map(|x| f32::from(*x)).sum::<f32>() / pixels.len() as f32
+    }
+
+    #[test]
+    fn test_depth_rounding() {
+        let truncated = reduce(Depth::new(BitDepth::Eight));
+        assert!(truncated.iter().all(|x| *x == 128));
+
+        let rounded = reduce(Depth::new(BitDepth::Eight).set_rounding(DepthRounding::Nearest));
+        assert!(rounded.iter().all(|x| *x == 129));
+    }
+
+    #[test]
+    fn test_depth_dither_preserves_mean() {
+        for dither in [DitherMethod::Ordered, DitherMethod::FloydSteinberg] {
+            let pixels = reduce(Depth::new(BitDepth::Eight).set_dither(dither));
+
+            assert!(pixels.iter().all(|x| *x == 128 || *x == 129));
+            assert!((mean(&pixels) - 128.5).abs() < 0.05, "{dither:?}");
+        }
+    }
+}
@@ -0,0 +1,180 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Orientation normalization
+//!
+//! Some formats (JPEG, PNG via `eXIf`) store an exif orientation tag which
+//! tells viewers how to rotate or flip the stored pixels before display.
+//!
+//! [`AutoOrient`] applies that transformation to the pixels and resets the
+//! orientation to `1` (normal) so that it isn't applied twice.
+//!
+//! The orientation is read from [`ImageMetadata::orientation`](crate::metadata::ImageMetadata::orientation)
+use zune_core::bit_depth::BitType;
+use zune_core::log::{trace, warn};
+
+use crate::channel::Channel;
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::traits::OperationsTrait;
+
+/// Rotate and flip an image according to its exif orientation
+///
+/// This is a no-op if the image does not have an orientation or
+/// the orientation is already normal.
+///
+/// After the operation, the image orientation (and exif orientation tag when the `metadata`
+/// feature is enabled) is set to `1`
+#[derive(Copy, Clone, Default)]
+pub struct AutoOrient;
+
+impl AutoOrient {
+    pub fn new() -> AutoOrient {
+        AutoOrient
+    }
+}
+
+/// Map each output pixel to an input pixel depending on orientation
+///
+/// Orientations 5-8 swap width and height, `out` is expected to be
+/// in the new dimensions
+fn orient<T: Copy>(input: &[T], out: &mut [T], width: usize, height: usize, orientation: u16) {
+    let (out_width, out_height) = if orientation >= 5 {
+        (height, width)
+    } else {
+        (width, height)
+    };
+
+    for (y, out_row) in out.chunks_exact_mut(out_width).take(out_height).enumerate() {
+        for (x, pix) in out_row.iter_mut().enumerate() {
+            let (in_x, in_y) = match orientation {
+                2 => (width - 1 - x, y),
+                3 => (width - 1 - x, height - 1 - y),
+                4 => (x, height - 1 - y),
+                5 => (y, x),
+                6 => (y, height - 1 - x),
+                7 => (width - 1 - y, height - 1 - x),
+                8 => (width - 1 - y, x),
+                _ => (x, y)
+            };
+            *pix = input[in_y * width + in_x];
+        }
+    }
+}
+
+impl OperationsTrait for AutoOrient {
+    fn name(&self) -> &'static str {
+        "Auto orient"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let orientation = match image.metadata().orientation() {
+            Some(1) | None => {
+                trace!("Image has normal orientation, no-op");
+                return Ok(());
+            }
+            Some(value @ 2..=8) => value,
+            Some(value) => {
+                warn!("Unknown exif orientation {value}, ignoring it");
+                return Ok(());
+            }
+        };
+        let (width, height) = image.dimensions();
+        let bit_type = image.depth().bit_type();
+
+        for channel in image.channels_mut(false) {
+            let mut new_channel = Channel::new_with_bit_type(channel.len(), bit_type);
+
+            match bit_type {
+                BitType::U8 => orient::<u8>(
+                    channel.reinterpret_as()?,
+                    new_channel.reinterpret_as_mut()?,
+                    width,
+                    height,
+                    orientation
+                ),
+                BitType::U16 => orient::<u16>(
+                    channel.reinterpret_as()?,
+                    new_channel.reinterpret_as_mut()?,
+                    width,
+                    height,
+                    orientation
+                ),
+                BitType::F32 => orient::<f32>(
+                    channel.reinterpret_as()?,
+                    new_channel.reinterpret_as_mut()?,
+                    width,
+                    height,
+                    orientation
+                ),
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+            *channel = new_channel;
+        }
+        if orientation >= 5 {
+            image.set_dimensions(height, width);
+        }
+        image.metadata_mut().set_orientation(1);
+
+        #[cfg(feature = "metadata")]
+        {
+            use exif::{Tag, Value};
+
+            if let Some(data) = image.metadata_mut().exif_mut() {
+                for field in data {
+                    if field.tag == Tag::Orientation {
+                        field.value = Value::Short(vec![1]);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::core_filters::orientation::AutoOrient;
+    use crate::image::Image;
+    use crate::traits::OperationsTrait;
+
+    #[test]
+    fn test_auto_orient_rotate_90() {
+        // 3x2 image
+        // 0 1 2
+        // 3 4 5
+        let mut image = Image::from_u8(&[0, 1, 2, 3, 4, 5], 3, 2, ColorSpace::Luma);
+        image.metadata_mut().set_orientation(6);
+
+        AutoOrient.execute(&mut image).unwrap();
+
+        // rotated clockwise
+        // 3 0
+        // 4 1
+        // 5 2
+        assert_eq!(image.dimensions(), (2, 3));
+        assert_eq!(image.metadata().orientation(), Some(1));
+        assert_eq!(image.flatten_to_u8()[0], [3, 0, 4, 1, 5, 2]);
+    }
+
+    #[test]
+    fn test_auto_orient_flip_horizontal() {
+        let mut image = Image::from_u8(&[0, 1, 2, 3, 4, 5], 3, 2, ColorSpace::Luma);
+        image.metadata_mut().set_orientation(2);
+
+        AutoOrient.execute(&mut image).unwrap();
+
+        assert_eq!(image.dimensions(), (3, 2));
+        assert_eq!(image.flatten_to_u8()[0], [2, 1, 0, 5, 4, 3]);
+    }
+}
@@ -0,0 +1,160 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Channel reordering
+//!
+//! [`Swizzle`] builds a new set of channels by picking channels of the image by index,
+//! this can reorder channels (BGR to RGB), drop them (RGBA to RGB),
+//! duplicate them (Luma to RGB) or extract a single one (alpha as Luma).
+//!
+//! To operate on channels as separate images, see [`Image::split_channels`]
+//! and [`Image::merge_channels`]
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+
+use crate::errors::{ImageErrors, ImageOperationsErrors};
+use crate::image::{Image, MAX_CHANNELS};
+use crate::traits::OperationsTrait;
+
+/// Reorder, drop or duplicate image channels
+///
+/// Output channel `i` is a copy of input channel `channels[i]`, the image is
+/// then treated as being in `colorspace`, which must have as many components as there
+/// are indices.
+///
+/// Pixel values are not modified, this only moves channels around, to change how
+/// pixels are interpreted, use [`ColorspaceConv`](crate::core_filters::colorspace::ColorspaceConv)
+///
+/// # Example
+/// - Swap BGR channels to RGB and extract the alpha channel as a grayscale image
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::core_filters::swizzle::Swizzle;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+///
+/// let mut image = Image::fill::<u8>(10, ColorSpace::BGRA, 100, 100);
+/// let mut alpha = image.clone();
+///
+/// Swizzle::new(&[2, 1, 0, 3], ColorSpace::RGBA).execute(&mut image).unwrap();
+/// Swizzle::extract(3).execute(&mut alpha).unwrap();
+///
+/// assert_eq!(alpha.colorspace(), ColorSpace::Luma);
+/// ```
+#[derive(Copy, Clone)]
+pub struct Swizzle {
+    channels:   [usize; MAX_CHANNELS],
+    length:     usize,
+    colorspace: ColorSpace
+}
+
+impl Swizzle {
+    /// Create a new swizzle operation
+    ///
+    /// # Arguments
+    /// - channels: Index of the input channel for each output channel, at most
+    ///   [`MAX_CHANNELS`] indices are used
+    /// - colorspace: Colorspace of the output
+    pub fn new(channels: &[usize], colorspace: ColorSpace) -> Swizzle {
+        let length = channels.len().min(MAX_CHANNELS);
+        let mut indices = [0; MAX_CHANNELS];
+        indices[..length].copy_from_slice(&channels[..length]);
+
+        Swizzle {
+            channels: indices,
+            length,
+            colorspace
+        }
+    }
+    /// Create a swizzle that keeps only channel `channel` as a Luma image,
+    /// e.g. `Swizzle::extract(3)` extracts the alpha channel of an RGBA image
+    pub fn extract(channel: usize) -> Swizzle {
+        Swizzle::new(&[channel], ColorSpace::Luma)
+    }
+}
+
+impl OperationsTrait for Swizzle {
+    fn name(&self) -> &'static str {
+        "Swizzle"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let channels = &self.channels[..self.length];
+        let components = image.colorspace().num_components();
+
+        if channels.len() != self.colorspace.num_components() {
+            return Err(ImageOperationsErrors::WrongComponents(
+                self.colorspace.num_components(),
+                channels.len()
+            )
+            .into());
+        }
+        if let Some(index) = channels.iter().find(|x| **x >= components) {
+            return Err(ImageErrors::GenericString(format!(
+                "Channel {index} does not exist, the image has {components} channels"
+            )));
+        }
+
+        for frame in image.frames_mut() {
+            let old_channels = frame.channels_vec();
+            let new_channels = channels.iter().map(|x| old_channels[*x].clone()).collect();
+            *old_channels = new_channels;
+        }
+        image.metadata_mut().set_colorspace(self.colorspace);
+
+        Ok(())
+    }
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::core_filters::swizzle::Swizzle;
+    use crate::image::Image;
+    use crate::traits::OperationsTrait;
+
+    fn bgra_image() -> Image {
+        Image::from_fn::<u8, _>(4, 4, ColorSpace::BGRA, |_, _, pix| {
+            pix.copy_from_slice(&[1, 2, 3, 4]);
+        })
+    }
+
+    #[test]
+    fn test_swizzle_bgr_to_rgb() {
+        let mut image = bgra_image();
+
+        Swizzle::new(&[2, 1, 0], ColorSpace::RGB)
+            .execute(&mut image)
+            .unwrap();
+
+        assert_eq!(image.colorspace(), ColorSpace::RGB);
+        assert_eq!(&image.flatten_to_u8()[0][..3], &[3, 2, 1]);
+
+        assert!(Swizzle::extract(3).execute(&mut image).is_err());
+    }
+
+    #[test]
+    fn test_split_merge_channels() {
+        let image = bgra_image();
+        let mut channels = image.split_channels();
+
+        assert_eq!(channels.len(), 4);
+        assert_eq!(channels[3].colorspace(), ColorSpace::Luma);
+        assert_eq!(channels[3].flatten_to_u8()[0][0], 4);
+
+        channels.swap(0, 2);
+        let merged = Image::merge_channels(&channels, ColorSpace::RGBA).unwrap();
+        assert_eq!(&merged.flatten_to_u8()[0][..4], &[3, 2, 1, 4]);
+
+        assert!(Image::merge_channels(&channels[..3], ColorSpace::RGBA).is_err());
+        assert!(Image::merge_channels(&[], ColorSpace::Luma).is_err());
+    }
+}
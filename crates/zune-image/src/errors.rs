@@ -13,6 +13,7 @@ use std::io::Error;
 
 use zune_core::bit_depth::BitType;
 use zune_core::colorspace::ColorSpace;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
 
 use crate::channel::ChannelErrors;
 use crate::codecs::ImageFormat;
@@ -23,6 +24,9 @@ use crate::codecs::ImageFormat;
 /// all decoding,processing and encoding errors possible
 pub enum ImageErrors {
     ImageDecodeErrors(String),
+    /// An error from one of the codec crates, with the
+    /// category reported by the codec
+    CodecErrors(ZErrorCode, String),
     DimensionsMisMatch(usize, usize),
     UnsupportedColorspace(ColorSpace, &'static str, &'static [ColorSpace]),
     NoImageForOperations,
@@ -37,7 +41,10 @@ pub enum ImageErrors {
     ImageDecoderNotIncluded(ImageFormat),
     ImageDecoderNotImplemented(ImageFormat),
     IoError(std::io::Error),
-    ImageOperationNotImplemented(&'static str, BitType)
+    ImageOperationNotImplemented(&'static str, BitType),
+    /// A frame was requested that the image doesn't have,
+    /// contains the requested frame and the number of frames
+    FrameOutOfRange(usize, usize)
 }
 
 /// Errors that may occur during image operations
@@ -64,15 +71,88 @@ pub enum ImgEncodeErrors {
     GenericStatic(&'static str),
     UnsupportedColorspace(ColorSpace, &'static [ColorSpace]),
     ImageEncodeErrors(String),
+    /// An error from one of the codec crates, with the
+    /// category reported by the codec
+    CodecErrors(ZErrorCode, String),
     NoEncoderForFormat(ImageFormat)
 }
 
+impl ImageErrors {
+    /// Return the broad category of this error
+    ///
+    /// See [`ZErrorCode`] for the possible categories
+    pub fn code(&self) -> ZErrorCode {
+        match self {
+            Self::ImageDecodeErrors(_) => ZErrorCode::CorruptData,
+            Self::CodecErrors(code, _) => *code,
+            Self::DimensionsMisMatch(_, _)
+            | Self::NoImageForOperations
+            | Self::NoImageForEncoding
+            | Self::NoImageBuffer
+            | Self::WrongTypeId(_, _)
+            | Self::ChannelErrors(_)
+            | Self::FrameOutOfRange(_, _) => ZErrorCode::InvalidInput,
+            Self::UnsupportedColorspace(_, _, _)
+            | Self::ImageDecoderNotIncluded(_)
+            | Self::ImageDecoderNotImplemented(_)
+            | Self::ImageOperationNotImplemented(_, _) => ZErrorCode::Unsupported,
+            Self::OperationsError(err) => err.code(),
+            Self::EncodeErrors(err) => err.code(),
+            Self::IoError(_) => ZErrorCode::Io,
+            Self::GenericString(_) | Self::GenericStr(_) => ZErrorCode::Internal
+        }
+    }
+}
+
+impl ImageOperationsErrors {
+    /// Return the broad category of this error
+    pub fn code(&self) -> ZErrorCode {
+        match self {
+            Self::WrongColorspace(_, _)
+            | Self::WrongComponents(_, _)
+            | Self::InvalidChannelLayout(_) => ZErrorCode::InvalidInput,
+            Self::UnsupportedType(_, _) => ZErrorCode::Unsupported,
+            Self::Generic(_) | Self::GenericString(_) => ZErrorCode::Internal
+        }
+    }
+}
+
+impl ImgEncodeErrors {
+    /// Return the broad category of this error
+    pub fn code(&self) -> ZErrorCode {
+        match self {
+            Self::CodecErrors(code, _) => *code,
+            Self::UnsupportedColorspace(_, _) | Self::NoEncoderForFormat(_) => {
+                ZErrorCode::Unsupported
+            }
+            Self::Generic(_) | Self::GenericStatic(_) | Self::ImageEncodeErrors(_) => {
+                ZErrorCode::Internal
+            }
+        }
+    }
+}
+
+impl ZErrorCodeTrait for ImageErrors {
+    fn code(&self) -> ZErrorCode {
+        ImageErrors::code(self)
+    }
+}
+
+impl ZErrorCodeTrait for ImgEncodeErrors {
+    fn code(&self) -> ZErrorCode {
+        ImgEncodeErrors::code(self)
+    }
+}
+
 impl Debug for ImageErrors {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::ImageDecodeErrors(err) => {
                 writeln!(f, "{err}")
             }
+            Self::CodecErrors(_, err) => {
+                writeln!(f, "{err}")
+            }
 
             Self::GenericStr(err) => {
                 writeln!(f, "{err}")
@@ -132,6 +212,12 @@ impl Debug for ImageErrors {
                     op_type, depth
                 )
             }
+            ImageErrors::FrameOutOfRange(frame, frames) => {
+                writeln!(
+                    f,
+                    "Frame {frame} requested but the image only has {frames} frame(s)"
+                )
+            }
         }
     }
 }
@@ -211,7 +297,7 @@ impl Debug for ImgEncodeErrors {
                     "Found colorspace {found:?} but the encoder supports {expected:?}"
                 )
             }
-            Self::ImageEncodeErrors(err) => {
+            Self::ImageEncodeErrors(err) | Self::CodecErrors(_, err) => {
                 writeln!(f, "Image could not be encoded, reason: {err}")
             }
             Self::NoEncoderForFormat(format) => {
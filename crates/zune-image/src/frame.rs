@@ -247,6 +247,21 @@ impl Frame {
         }
     }
 
+    /// Returns how long this frame is shown in an animation, as
+    /// `(numerator, denominator)`, the delay in seconds being `numerator/denominator`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zune_image::channel::Channel;
+    /// use zune_image::frame::Frame;
+    /// let frame = Frame::new_with_duration(vec![Channel::new::<u8>()], 5, 100);
+    /// assert_eq!(frame.duration(), (5, 100));
+    /// ```
+    pub const fn duration(&self) -> (usize, usize) {
+        (self.numerator, self.denominator)
+    }
+
     /// Returns a reference to the channels in this frame
     ///
     /// # Arguments
@@ -616,19 +631,17 @@ impl Frame {
         if color_space.num_components() != self.channels.len() {
             return None;
         }
-        let mut position = color_space.alpha_position().expect("No way!!");
-        if position == 0 {
-            position = 1;
-        }
-        let (src_c1, src_c2) = self.channels.split_at(position);
+        let position = color_space.alpha_position().expect("No way!!");
 
         let src_alpha_channel;
         let src_color_channels;
-        if position == 1 {
+        if position == 0 {
             // argb
+            let (src_c1, src_c2) = self.channels.split_at(1);
             src_alpha_channel = &src_c1[0];
             src_color_channels = src_c2;
         } else {
+            let (src_c1, src_c2) = self.channels.split_at(position);
             src_alpha_channel = &src_c2[0];
             src_color_channels = src_c1;
         }
@@ -660,19 +673,17 @@ impl Frame {
         if color_space.num_components() != self.channels.len() {
             return None;
         }
-        let mut position = color_space.alpha_position().expect("No way!!");
-        if position == 0 {
-            position = 1;
-        }
-        let (src_c1, src_c2) = self.channels.split_at_mut(position);
+        let position = color_space.alpha_position().expect("No way!!");
 
         let src_alpha_channel;
         let src_color_channels;
-        if position == 1 {
+        if position == 0 {
             // argb
+            let (src_c1, src_c2) = self.channels.split_at_mut(1);
             src_alpha_channel = &mut src_c1[0];
             src_color_channels = src_c2;
         } else {
+            let (src_c1, src_c2) = self.channels.split_at_mut(position);
             src_alpha_channel = &mut src_c2[0];
             src_color_channels = src_c1;
         }
@@ -722,11 +733,16 @@ mod tests {
     }
     #[test]
     fn test_color_separation_luma_a() {
-        let image = Image::fill(0f32, ColorSpace::LumaA, 10, 10);
-        let (colors, _) = image.frames[0]
+        let image = Image::from_fn::<u8, _>(10, 10, ColorSpace::LumaA, |_, _, pix| {
+            pix[0] = 1;
+            pix[1] = 2;
+        });
+        let (colors, alpha) = image.frames[0]
             .separate_color_and_alpha_ref(ColorSpace::LumaA)
             .unwrap();
         assert_eq!(colors.len(), 1);
+        assert_eq!(colors[0].reinterpret_as::<u8>().unwrap()[0], 1);
+        assert_eq!(alpha.reinterpret_as::<u8>().unwrap()[0], 2);
     }
 
     #[test]
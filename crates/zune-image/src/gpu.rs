@@ -0,0 +1,279 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! GPU interop via `wgpu` texture types
+//!
+//! [`GpuTexture::from_image`] creates a texture descriptor and packed pixel data
+//! that can be copied to a `wgpu` texture, and [`image_from_texture_data`] creates
+//! an image from data read back from a texture, e.g. a render target.
+//!
+//! `wgpu` re-exports `wgpu-types`, so the types here can be passed to it directly.
+//!
+//! Buffer to texture copies require each row to be a multiple of
+//! [`COPY_BYTES_PER_ROW_ALIGNMENT`] bytes, the data is padded as needed and
+//! [`GpuTexture::layout`] describes the padding.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::gpu::{image_from_texture_data, GpuTexture};
+//! use zune_image::image::Image;
+//!
+//! let image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 50);
+//! let texture = GpuTexture::from_image(&image).unwrap();
+//!
+//! // rows of 100 RGBA pixels are padded from 400 to 512 bytes
+//! assert_eq!(texture.layout.bytes_per_row, Some(512));
+//!
+//! // e.g. queue.write_texture(..., &texture.data, texture.layout, texture.descriptor.size)
+//! let image = image_from_texture_data(
+//!     &texture.data,
+//!     100,
+//!     50,
+//!     512,
+//!     texture.descriptor.format
+//! )
+//! .unwrap();
+//! assert_eq!(image.colorspace(), ColorSpace::RGBA);
+//! ```
+#![cfg(feature = "wgpu")]
+
+use wgpu_types::{
+    Extent3d, TexelCopyBufferLayout, TextureDescriptor, TextureDimension, TextureFormat,
+    TextureUsages, COPY_BYTES_PER_ROW_ALIGNMENT
+};
+use zune_core::bit_depth::BitDepth;
+use zune_core::colorspace::ColorSpace;
+
+use crate::errors::ImageErrors;
+use crate::image::Image;
+
+/// A texture descriptor as accepted by `wgpu::Device::create_texture`
+pub type GpuTextureDescriptor = TextureDescriptor<Option<&'static str>, &'static [TextureFormat]>;
+
+/// Pixel data of an image laid out for copying to a GPU texture
+#[derive(Clone, Debug)]
+pub struct GpuTexture {
+    /// Describes the texture the data should be copied to
+    pub descriptor: GpuTextureDescriptor,
+    /// Describes the layout of `data`, including row padding
+    pub layout:     TexelCopyBufferLayout,
+    /// Packed pixel data, each row padded to `layout.bytes_per_row` bytes
+    pub data:       Vec<u8>
+}
+
+impl GpuTexture {
+    /// Create texture data from the first frame of an image
+    ///
+    /// The texture format depends on the image,
+    ///
+    /// | Colorspace | 8 bit            | 16 bit and float |
+    /// |------------|------------------|------------------|
+    /// | Luma       | `R8Unorm`        | `R32Float`       |
+    /// | LumaA      | `Rg8Unorm`       | `Rg32Float`      |
+    /// | Others     | `Rgba8UnormSrgb` | `Rgba32Float`    |
+    ///
+    /// GPUs have no three channel formats, so other colorspaces are converted to RGBA.
+    /// The formats chosen don't need any optional device features.
+    ///
+    /// The texture is created with `TEXTURE_BINDING` and `COPY_DST` usages, change
+    /// `descriptor.usage` if it's needed for other things.
+    ///
+    /// # Errors
+    /// If the image is empty, too large for a texture or can't be converted to RGBA
+    pub fn from_image(image: &Image) -> Result<GpuTexture, ImageErrors> {
+        let (width, height) = image.dimensions();
+        let colorspace = image.colorspace();
+        let float = image.depth() != BitDepth::Eight;
+
+        let texture_width = texture_dimension(width)?;
+        let texture_height = texture_dimension(height)?;
+
+        let (format, target_colorspace) = match (colorspace, float) {
+            (ColorSpace::Luma, false) => (TextureFormat::R8Unorm, ColorSpace::Luma),
+            (ColorSpace::LumaA, false) => (TextureFormat::Rg8Unorm, ColorSpace::LumaA),
+            (_, false) => (TextureFormat::Rgba8UnormSrgb, ColorSpace::RGBA),
+            (ColorSpace::Luma, true) => (TextureFormat::R32Float, ColorSpace::Luma),
+            (ColorSpace::LumaA, true) => (TextureFormat::Rg32Float, ColorSpace::LumaA),
+            (_, true) => (TextureFormat::Rgba32Float, ColorSpace::RGBA)
+        };
+
+        let converted;
+        let image = if colorspace == target_colorspace {
+            image
+        } else {
+            // only convert the frame we need
+            let frame = image
+                .frames_ref()
+                .first()
+                .ok_or(ImageErrors::GenericStr("Image has no frames"))?;
+            let mut copy = Image::new_frames(
+                vec![frame.clone()],
+                image.depth(),
+                width,
+                height,
+                colorspace
+            );
+            copy.convert_color(target_colorspace)?;
+            converted = copy;
+            &converted
+        };
+
+        let pixels: &[u8] = if float {
+            bytemuck::cast_slice(image.to_interleaved_f32()?)
+        } else {
+            image.to_interleaved_u8()?
+        };
+
+        let row_bytes = pixels.len() / height;
+        let bytes_per_row = padded_bytes_per_row(row_bytes)?;
+
+        let mut data = vec![0; bytes_per_row * height];
+
+        for (out, row) in data
+            .chunks_exact_mut(bytes_per_row)
+            .zip(pixels.chunks_exact(row_bytes))
+        {
+            out[..row_bytes].copy_from_slice(row);
+        }
+
+        let descriptor: GpuTextureDescriptor = TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width:                 texture_width,
+                height:                texture_height,
+                depth_or_array_layers: 1
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            view_formats: &[]
+        };
+        let layout = TexelCopyBufferLayout {
+            offset:         0,
+            bytes_per_row:  Some(texture_dimension(bytes_per_row)?),
+            rows_per_image: Some(texture_height)
+        };
+
+        Ok(GpuTexture {
+            descriptor,
+            layout,
+            data
+        })
+    }
+}
+
+/// Return the number of bytes a row of `row_bytes` bytes occupies in
+/// a buffer copied to or from a texture
+///
+/// This rounds up to a multiple of [`COPY_BYTES_PER_ROW_ALIGNMENT`]
+///
+/// # Errors
+/// If the result overflows
+pub fn padded_bytes_per_row(row_bytes: usize) -> Result<usize, ImageErrors> {
+    let alignment = COPY_BYTES_PER_ROW_ALIGNMENT as usize;
+
+    row_bytes
+        .checked_next_multiple_of(alignment)
+        .ok_or(ImageErrors::GenericStr("Texture row size overflows"))
+}
+
+/// Create an image from pixel data read back from a texture
+///
+/// `data` contains `height` rows of `bytes_per_row` bytes each, the last row
+/// may omit its padding. Supported formats are
+///
+/// - `R8Unorm`, `Rg8Unorm`, `Rgba8Unorm` and `Bgra8Unorm` (and their sRGB variants),
+///   creating 8 bit images
+/// - `R16Unorm`, `Rg16Unorm` and `Rgba16Unorm`, creating 16 bit images
+/// - `R32Float`, `Rg32Float` and `Rgba32Float`, creating float images
+///
+/// # Errors
+/// If the format isn't supported or `data` is too short
+pub fn image_from_texture_data(
+    data: &[u8], width: usize, height: usize, bytes_per_row: usize, format: TextureFormat
+) -> Result<Image, ImageErrors> {
+    let (colorspace, depth) = match format {
+        TextureFormat::R8Unorm => (ColorSpace::Luma, BitDepth::Eight),
+        TextureFormat::Rg8Unorm => (ColorSpace::LumaA, BitDepth::Eight),
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {
+            (ColorSpace::RGBA, BitDepth::Eight)
+        }
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+            (ColorSpace::BGRA, BitDepth::Eight)
+        }
+        TextureFormat::R16Unorm => (ColorSpace::Luma, BitDepth::Sixteen),
+        TextureFormat::Rg16Unorm => (ColorSpace::LumaA, BitDepth::Sixteen),
+        TextureFormat::Rgba16Unorm => (ColorSpace::RGBA, BitDepth::Sixteen),
+        TextureFormat::R32Float => (ColorSpace::Luma, BitDepth::Float32),
+        TextureFormat::Rg32Float => (ColorSpace::LumaA, BitDepth::Float32),
+        TextureFormat::Rgba32Float => (ColorSpace::RGBA, BitDepth::Float32),
+        _ => {
+            return Err(ImageErrors::GenericString(format!(
+                "Unsupported texture format {format:?}"
+            )))
+        }
+    };
+
+    let row_bytes = width
+        .checked_mul(colorspace.num_components() * depth.size_of())
+        .ok_or(ImageErrors::GenericStr("Image dimensions overflow"))?;
+
+    if bytes_per_row < row_bytes {
+        return Err(ImageErrors::GenericString(format!(
+            "Bytes per row {bytes_per_row} is less than the {row_bytes} bytes in a row"
+        )));
+    }
+    let expected_len = bytes_per_row
+        .checked_mul(height.saturating_sub(1))
+        .and_then(|x| x.checked_add(row_bytes))
+        .ok_or(ImageErrors::GenericStr("Image dimensions overflow"))?;
+
+    if height != 0 && data.len() < expected_len {
+        return Err(ImageErrors::GenericString(format!(
+            "Texture data too short, expected {expected_len} bytes but found {}",
+            data.len()
+        )));
+    }
+
+    let mut pixels = Vec::with_capacity(row_bytes * height);
+
+    for row in data.chunks(bytes_per_row).take(height) {
+        pixels.extend_from_slice(&row[..row_bytes]);
+    }
+
+    // the data may not be aligned for wider types, copy it out
+    match depth {
+        BitDepth::Sixteen => {
+            let pixels: Vec<u16> = pixels
+                .chunks_exact(2)
+                .map(|x| u16::from_ne_bytes([x[0], x[1]]))
+                .collect();
+            Image::from_interleaved(&pixels, width, height, colorspace)
+        }
+        BitDepth::Float32 => {
+            let pixels: Vec<f32> = pixels
+                .chunks_exact(4)
+                .map(|x| f32::from_ne_bytes([x[0], x[1], x[2], x[3]]))
+                .collect();
+            Image::from_interleaved(&pixels, width, height, colorspace)
+        }
+        _ => Image::from_interleaved(&pixels, width, height, colorspace)
+    }
+}
+
+fn texture_dimension(value: usize) -> Result<u32, ImageErrors> {
+    u32::try_from(value)
+        .ok()
+        .filter(|x| *x != 0)
+        .ok_or(ImageErrors::GenericString(format!(
+            "Invalid texture dimension {value}"
+        )))
+}
@@ -12,6 +12,8 @@
 //!
 use std::fmt::Debug;
 use std::mem::size_of;
+use std::ops::{Index, IndexMut};
+use std::sync::OnceLock;
 
 use bytemuck::{Pod, Zeroable};
 use zune_core::bit_depth::BitDepth;
@@ -21,10 +23,11 @@ use crate::channel::{Channel, ChannelErrors};
 use crate::core_filters::colorspace::ColorspaceConv;
 use crate::core_filters::depth::Depth;
 use crate::deinterleave::{deinterleave_f32, deinterleave_u16, deinterleave_u8};
-use crate::errors::ImageErrors;
+use crate::errors::{ImageErrors, ImageOperationsErrors};
 use crate::frame::Frame;
 use crate::metadata::ImageMetadata;
 use crate::traits::{OperationsTrait, ZuneInts};
+use crate::utils::swizzle_channels;
 
 /// Maximum supported color channels
 pub const MAX_CHANNELS: usize = 4;
@@ -33,7 +36,24 @@ pub const MAX_CHANNELS: usize = 4;
 #[derive(Clone)]
 pub struct Image {
     pub(crate) frames:   Vec<Frame>,
-    pub(crate) metadata: ImageMetadata
+    pub(crate) metadata: ImageMetadata,
+    interleaved:         InterleavedCache
+}
+
+/// Interleaved copies of the first frame, created on demand
+///
+/// This is cleared whenever the image is mutably accessed, and clones start empty
+/// so that copying an image doesn't copy the cache
+#[derive(Default)]
+struct InterleavedCache {
+    u8:  OnceLock<Vec<u8>>,
+    f32: OnceLock<Vec<f32>>
+}
+
+impl Clone for InterleavedCache {
+    fn clone(&self) -> Self {
+        InterleavedCache::default()
+    }
 }
 
 impl PartialEq<Self> for Image {
@@ -61,8 +81,9 @@ impl Image {
         meta.set_colorspace(colorspace);
 
         Image {
-            frames:   vec![Frame::new(channels)],
-            metadata: meta
+            frames:      vec![Frame::new(channels)],
+            metadata:    meta,
+            interleaved: InterleavedCache::default()
         }
     }
     /// Create an image from multiple frames.
@@ -78,7 +99,8 @@ impl Image {
 
         Image {
             frames,
-            metadata: meta
+            metadata: meta,
+            interleaved: InterleavedCache::default()
         }
     }
 
@@ -107,6 +129,7 @@ impl Image {
     /// Ensure that the image is in a certain depth before changing this
     /// otherwise bad things will happen
     pub fn set_depth(&mut self, depth: BitDepth) {
+        self.invalidate_interleaved();
         self.metadata.set_depth(depth)
     }
 
@@ -120,6 +143,7 @@ impl Image {
     /// Do not modify elements like width and height anyhowly, it may corrupt
     /// the image in ways only God knows
     pub fn metadata_mut(&mut self) -> &mut ImageMetadata {
+        self.invalidate_interleaved();
         &mut self.metadata
     }
 
@@ -136,6 +160,7 @@ impl Image {
     /// Return a mutable reference to all image frames.
     ///
     pub fn frames_mut(&mut self) -> &mut [Frame] {
+        self.invalidate_interleaved();
         &mut self.frames
     }
     /// Return a reference to the underlying channels
@@ -204,6 +229,85 @@ impl Image {
             im_clone.flatten_frames::<u8>()
         }
     }
+    /// Return the first frame as interleaved 8 bit pixels
+    ///
+    /// Pixels are interleaved according to the colorspace, e.g. RGBA images
+    /// return `[R,G,B,A,R,G,B,A]`, which is the layout GUI toolkits and GPU
+    /// uploads expect. Images of other depths are converted to 8 bits.
+    ///
+    /// The buffer is computed on the first call and cached, so repeated calls, e.g.
+    /// redrawing a window, are free until the image is modified.
+    ///
+    /// Use [`flatten_to_u8`](Self::flatten_to_u8) for all frames of animated images
+    ///
+    /// # Example
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::image::Image;
+    ///
+    /// let image = Image::fill::<u16>(u16::MAX, ColorSpace::RGB, 20, 10);
+    /// let pixels = image.to_interleaved_u8().unwrap();
+    ///
+    /// assert_eq!(pixels.len(), 20 * 10 * 3);
+    /// assert!(pixels.iter().all(|x| *x == 255));
+    /// ```
+    pub fn to_interleaved_u8(&self) -> Result<&[u8], ImageErrors> {
+        if let Some(pixels) = self.interleaved.u8.get() {
+            return Ok(pixels);
+        }
+        let pixels = self.interleave_first_frame::<u8>(BitDepth::Eight)?;
+        Ok(self.interleaved.u8.get_or_init(|| pixels))
+    }
+
+    /// Return the first frame as interleaved `f32` pixels in the range `0.0..=1.0`
+    ///
+    /// This behaves like [`to_interleaved_u8`](Self::to_interleaved_u8), images
+    /// of other depths are converted to floats and the result is cached.
+    pub fn to_interleaved_f32(&self) -> Result<&[f32], ImageErrors> {
+        if let Some(pixels) = self.interleaved.f32.get() {
+            return Ok(pixels);
+        }
+        let pixels = self.interleave_first_frame::<f32>(BitDepth::Float32)?;
+        Ok(self.interleaved.f32.get_or_init(|| pixels))
+    }
+
+    /// Interleave the channels of the first frame, converted to `depth`
+    fn interleave_first_frame<T>(&self, depth: BitDepth) -> Result<Vec<T>, ImageErrors>
+    where
+        T: Copy + Default + 'static
+    {
+        let (width, height) = self.dimensions();
+        let colorspace = self.colorspace();
+
+        let frame = self
+            .frames
+            .first()
+            .ok_or(ImageErrors::GenericStr("Image has no frames"))?;
+
+        let converted;
+        let frame = if self.depth() == depth {
+            frame
+        } else {
+            // only convert the frame we need
+            let mut image =
+                Image::new_frames(vec![frame.clone()], self.depth(), width, height, colorspace);
+            image.convert_depth(depth)?;
+            converted = image;
+            &converted.frames[0]
+        };
+        let channels = frame.channels_ref(colorspace, false);
+
+        let mut output = vec![T::default(); width * height * channels.len()];
+        swizzle_channels(channels, &mut output)?;
+
+        Ok(output)
+    }
+
+    /// Drop cached interleaved pixels, called whenever the image may be modified
+    fn invalidate_interleaved(&mut self) {
+        self.interleaved = InterleavedCache::default();
+    }
+
     #[allow(dead_code)]
     pub(crate) fn to_u8_be(&self) -> Vec<Vec<u8>> {
         let colorspace = self.colorspace();
@@ -231,6 +335,7 @@ impl Image {
     ///
     /// Modifies the image in place
     pub fn set_dimensions(&mut self, width: usize, height: usize) {
+        self.invalidate_interleaved();
         self.metadata.set_dimensions(width, height);
     }
 
@@ -238,6 +343,7 @@ impl Image {
     ///
     /// Do not do this without ensuring the image is in that colorspace
     pub(crate) fn set_colorspace(&mut self, colorspace: ColorSpace) {
+        self.invalidate_interleaved();
         self.metadata.set_colorspace(colorspace);
     }
 
@@ -475,6 +581,56 @@ impl Image {
 
         Image::new(pixels, BitDepth::Float32, width, height, colorspace)
     }
+
+    /// Create an image from interleaved pixels of type `T`
+    ///
+    /// Pixels are expected to be interleaved according to the colorspace,
+    /// e.g. for RGBA `[R,G,B,A,R,G,B,A]`, this is the layout used by GUI toolkits
+    /// and GPU textures. The depth is derived from `T`, `u8`, `u16` or `f32`.
+    ///
+    /// Unlike [`from_u8`](Self::from_u8) and friends, this doesn't panic on invalid input.
+    ///
+    /// # Returns
+    ///  - Ok(image): The image, with a single frame
+    ///  - Err(ImageErrors): The pixel count doesn't match the dimensions and colorspace
+    ///
+    /// # Example
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::image::Image;
+    ///
+    /// let pixels = [255_u8, 0, 0, 255, 0, 0, 255, 128];
+    /// let image = Image::from_interleaved(&pixels, 2, 1, ColorSpace::RGBA).unwrap();
+    ///
+    /// assert_eq!(image.to_interleaved_u8().unwrap(), pixels);
+    /// ```
+    pub fn from_interleaved<T>(
+        pixels: &[T], width: usize, height: usize, colorspace: ColorSpace
+    ) -> Result<Image, ImageErrors>
+    where
+        T: ZuneInts<T> + Copy + 'static + Pod
+    {
+        let expected_len = width
+            .checked_mul(height)
+            .and_then(|x| x.checked_mul(colorspace.num_components()))
+            .ok_or(ImageErrors::GenericStr("Image dimensions overflow"))?;
+
+        if pixels.len() != expected_len {
+            return Err(ImageErrors::GenericString(format!(
+                "Length mismatch, expected {expected_len} but found {}",
+                pixels.len()
+            )));
+        }
+        let depth = T::depth();
+
+        let channels = match depth {
+            BitDepth::Eight => deinterleave_u8(bytemuck::cast_slice(pixels), colorspace)?,
+            BitDepth::Sixteen => deinterleave_u16(bytemuck::cast_slice(pixels), colorspace)?,
+            BitDepth::Float32 => deinterleave_f32(bytemuck::cast_slice(pixels), colorspace)?,
+            _ => return Err(ImageErrors::GenericStr("Unsupported pixel type"))
+        };
+        Ok(Image::new(channels, depth, width, height, colorspace))
+    }
     pub fn frames_len(&self) -> usize {
         self.frames.len()
     }
@@ -532,7 +688,7 @@ impl Image {
 
         let (width, height) = self.dimensions();
 
-        for frame in self.frames.iter_mut() {
+        for frame in self.frames_mut().iter_mut() {
             let mut pixel_muts: Vec<&mut [T]> = vec![];
 
             // convert all channels to type T
@@ -565,6 +721,241 @@ impl Image {
     }
 }
 
+/// A single pixel of an image, holding a value for each channel
+///
+/// Only the first `colorspace.num_components()` values are used, e.g. for an RGBA image
+/// `pixel[3]` is the alpha value while for a Luma image only `pixel[0]` is valid.
+///
+/// See [`Image::pixels`] and [`Image::map_pixels`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Pixel<T> {
+    /// Position of the pixel on the x axis
+    pub x:      usize,
+    /// Position of the pixel on the y axis
+    pub y:      usize,
+    values:     [T; MAX_CHANNELS],
+    components: usize
+}
+
+impl<T: Copy + Default> Pixel<T> {
+    /// Read the pixel at `position` from each channel
+    fn read<C: AsRef<[T]>>(channels: &[C], x: usize, y: usize, position: usize) -> Pixel<T> {
+        let mut values = [T::default(); MAX_CHANNELS];
+
+        for (value, channel) in values.iter_mut().zip(channels) {
+            *value = channel.as_ref()[position];
+        }
+        Pixel {
+            x,
+            y,
+            values,
+            components: channels.len()
+        }
+    }
+
+    /// Write the pixel back to each channel at `position`
+    fn write(&self, channels: &mut [&mut [T]], position: usize) {
+        for (value, channel) in self.values.iter().zip(channels) {
+            channel[position] = *value;
+        }
+    }
+
+    /// Return the values of each channel
+    pub fn channels(&self) -> &[T] {
+        &self.values[..self.components]
+    }
+
+    /// Return mutable references to the values of each channel
+    pub fn channels_mut(&mut self) -> &mut [T] {
+        &mut self.values[..self.components]
+    }
+}
+
+impl<T: Copy + Default> Index<usize> for Pixel<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.channels()[index]
+    }
+}
+
+impl<T: Copy + Default> IndexMut<usize> for Pixel<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.channels_mut()[index]
+    }
+}
+
+/// Pixel iteration methods
+impl Image {
+    /// Return an iterator over the pixels of the image
+    ///
+    /// Pixels are visited row by row, starting from the top left, for animated
+    /// images the pixels of each frame are visited one frame after the other.
+    ///
+    /// # Returns
+    ///  - Ok(iterator): An iterator returning a [`Pixel`] per image position
+    ///  - Err(ChannelErrors): The channels could not be converted to type `T`
+    ///
+    /// # Example
+    /// Compute the mean of the red channel
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::image::Image;
+    ///
+    /// let image = Image::fill::<u8>(100, ColorSpace::RGB, 10, 10);
+    ///
+    /// let sum: usize = image.pixels::<u8>().unwrap().map(|pix| usize::from(pix[0])).sum();
+    /// assert_eq!(sum / 100, 100);
+    /// ```
+    pub fn pixels<T>(&self) -> Result<impl Iterator<Item = Pixel<T>> + '_, ChannelErrors>
+    where
+        T: ZuneInts<T> + Default + Copy + 'static + Pod
+    {
+        let colorspace = self.colorspace();
+        let (width, height) = self.dimensions();
+
+        let frames = self
+            .frames
+            .iter()
+            .map(|frame| {
+                frame
+                    .channels_ref(colorspace, false)
+                    .iter()
+                    .map(|channel| channel.reinterpret_as::<T>())
+                    .collect::<Result<Vec<&[T]>, ChannelErrors>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(frames.into_iter().flat_map(move |channels| {
+            (0..width * height).map(move |position| {
+                Pixel::read(&channels, position % width, position / width, position)
+            })
+        }))
+    }
+
+    /// Modify the pixels of the image in place using `func`
+    ///
+    /// This is a higher level alternative to [`modify_pixels_mut`](Self::modify_pixels_mut),
+    /// `func` is called with each [`Pixel`] of each frame and changes to it are written
+    /// back to the image channels.
+    ///
+    /// # Returns
+    ///  - Ok(()): Successful manipulation of image
+    ///  - Err(ChannelErrors): The channels could not be converted to type `T`
+    ///
+    /// # Example
+    /// Swap the red and blue channels
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::image::Image;
+    ///
+    /// let mut image = Image::from_fn::<u8, _>(10, 10, ColorSpace::RGB, |y, x, pix| {
+    ///     pix[0] = 255;
+    /// });
+    /// image.map_pixels::<u8, _>(|pix| pix.channels_mut().swap(0, 2)).unwrap();
+    ///
+    /// assert!(image.pixels::<u8>().unwrap().all(|pix| pix.channels() == [0, 0, 255]));
+    /// ```
+    pub fn map_pixels<T, F>(&mut self, mut func: F) -> Result<(), ChannelErrors>
+    where
+        T: ZuneInts<T> + Default + Copy + 'static + Pod,
+        F: FnMut(&mut Pixel<T>)
+    {
+        let colorspace = self.colorspace();
+        let (width, height) = self.dimensions();
+
+        for frame in self.frames_mut().iter_mut() {
+            let mut channels = frame
+                .channels_mut(colorspace, false)
+                .iter_mut()
+                .map(|channel| channel.reinterpret_as_mut::<T>())
+                .collect::<Result<Vec<&mut [T]>, ChannelErrors>>()?;
+
+            for y in 0..height {
+                for x in 0..width {
+                    let position = y * width + x;
+                    let mut pixel = Pixel::read(&channels, x, y, position);
+
+                    func(&mut pixel);
+                    pixel.write(&mut channels, position);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Modify the pixels of the image in place using `func`, on multiple threads
+    ///
+    /// This behaves like [`map_pixels`](Self::map_pixels) but processes rows in parallel
+    /// using [rayon](https://crates.io/crates/rayon), so pixels are visited in no particular order
+    /// and `func` must be callable from multiple threads.
+    ///
+    /// # Returns
+    ///  - Ok(()): Successful manipulation of image
+    ///  - Err(ChannelErrors): The channels could not be converted to type `T`
+    ///
+    /// # Example
+    /// Invert an image
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::image::Image;
+    ///
+    /// let mut image = Image::fill::<u16>(1000, ColorSpace::RGB, 100, 100);
+    ///
+    /// image
+    ///     .par_map_pixels::<u16, _>(|pix| pix.channels_mut().iter_mut().for_each(|x| *x = u16::MAX - *x))
+    ///     .unwrap();
+    ///
+    /// assert!(image.pixels::<u16>().unwrap().all(|pix| pix[1] == u16::MAX - 1000));
+    /// ```
+    #[cfg(feature = "threads")]
+    pub fn par_map_pixels<T, F>(&mut self, func: F) -> Result<(), ChannelErrors>
+    where
+        T: ZuneInts<T> + Default + Copy + 'static + Pod + Send,
+        F: Fn(&mut Pixel<T>) + Sync
+    {
+        use rayon::prelude::*;
+
+        let colorspace = self.colorspace();
+        let (width, _) = self.dimensions();
+
+        if width == 0 {
+            return Ok(());
+        }
+
+        for frame in self.frames_mut().iter_mut() {
+            let channels = frame
+                .channels_mut(colorspace, false)
+                .iter_mut()
+                .map(|channel| channel.reinterpret_as_mut::<T>())
+                .collect::<Result<Vec<&mut [T]>, ChannelErrors>>()?;
+
+            // group the rows of every channel, so that each row can be
+            // handed to a different thread
+            let mut rows: Vec<Vec<&mut [T]>> = vec![];
+
+            for channel in channels {
+                for (y, row) in channel.chunks_exact_mut(width).enumerate() {
+                    if rows.len() <= y {
+                        rows.push(vec![]);
+                    }
+                    rows[y].push(row);
+                }
+            }
+
+            rows.into_par_iter().enumerate().for_each(|(y, mut row)| {
+                for x in 0..width {
+                    let mut pixel = Pixel::read(&row, x, y, x);
+
+                    func(&mut pixel);
+                    pixel.write(&mut row, x);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
 /// Image conversion routines
 impl Image {
     /// Convert an image from one colorspace to another
@@ -584,6 +975,146 @@ impl Image {
     }
 }
 
+/// Channel manipulation routines
+impl Image {
+    /// Split an image into one single channel image per channel
+    ///
+    /// Each returned image is a Luma image with the same dimensions, depth and frames
+    /// as this image, in the order the channels are stored, e.g. for an RGBA image the
+    /// images are R,G,B and A.
+    ///
+    /// Use [merge_channels](Self::merge_channels) to combine them back
+    ///
+    /// # Example
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::image::Image;
+    ///
+    /// let image = Image::fill::<u8>(10, ColorSpace::RGBA, 100, 100);
+    /// let channels = image.split_channels();
+    ///
+    /// assert_eq!(channels.len(), 4);
+    /// assert_eq!(channels[3].colorspace(), ColorSpace::Luma);
+    /// ```
+    pub fn split_channels(&self) -> Vec<Image> {
+        let components = self.colorspace().num_components();
+
+        (0..components)
+            .map(|index| {
+                let frames = self
+                    .frames
+                    .iter()
+                    .map(|frame| Frame {
+                        channels:    vec![frame.channels[index].clone()],
+                        numerator:   frame.numerator,
+                        denominator: frame.denominator
+                    })
+                    .collect();
+
+                let mut metadata = self.metadata.clone();
+                metadata.set_colorspace(ColorSpace::Luma);
+
+                Image {
+                    frames,
+                    metadata,
+                    interleaved: InterleavedCache::default()
+                }
+            })
+            .collect()
+    }
+
+    /// Combine the channels of several images into one image
+    ///
+    /// The channels of every image are taken in order and must add up to the number
+    /// of components in `colorspace`, e.g. three Luma images can be merged into RGB,
+    /// or an RGB and a Luma image into RGBA.
+    ///
+    /// Metadata and frame delays are taken from the first image
+    ///
+    /// # Errors
+    /// - If there are no images
+    /// - If the images differ in dimensions, depth or number of frames
+    /// - If the number of channels doesn't match `colorspace`
+    ///
+    /// # Example
+    /// - Replace the alpha channel of an image
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::image::Image;
+    ///
+    /// let image = Image::fill::<u8>(10, ColorSpace::RGBA, 100, 100);
+    /// let mask = Image::fill::<u8>(255, ColorSpace::Luma, 100, 100);
+    ///
+    /// let mut channels = image.split_channels();
+    /// channels[3] = mask;
+    ///
+    /// let merged = Image::merge_channels(&channels, ColorSpace::RGBA).unwrap();
+    /// ```
+    pub fn merge_channels(images: &[Image], colorspace: ColorSpace) -> Result<Image, ImageErrors> {
+        let first = images
+            .first()
+            .ok_or(ImageErrors::GenericStr("No images to merge"))?;
+
+        for image in images {
+            if image.dimensions() != first.dimensions() {
+                return Err(ImageErrors::GenericString(format!(
+                    "Cannot merge images with different dimensions, {:?} and {:?}",
+                    first.dimensions(),
+                    image.dimensions()
+                )));
+            }
+            if image.depth() != first.depth() {
+                return Err(ImageErrors::GenericString(format!(
+                    "Cannot merge images with different depths, {:?} and {:?}",
+                    first.depth(),
+                    image.depth()
+                )));
+            }
+            if image.frames_len() != first.frames_len() {
+                return Err(ImageErrors::GenericString(format!(
+                    "Cannot merge images with a different number of frames, {} and {}",
+                    first.frames_len(),
+                    image.frames_len()
+                )));
+            }
+        }
+        let found: usize = images
+            .iter()
+            .map(|image| image.colorspace().num_components())
+            .sum();
+
+        if found != colorspace.num_components() {
+            return Err(ImageErrors::OperationsError(
+                ImageOperationsErrors::WrongComponents(colorspace.num_components(), found)
+            ));
+        }
+
+        let frames = (0..first.frames_len())
+            .map(|index| {
+                let channels = images
+                    .iter()
+                    .flat_map(|image| image.frames[index].channels.iter().cloned())
+                    .collect();
+
+                Frame {
+                    channels,
+                    numerator: first.frames[index].numerator,
+                    denominator: first.frames[index].denominator
+                }
+            })
+            .collect();
+
+        let mut metadata = first.metadata.clone();
+        metadata.set_colorspace(colorspace);
+
+        Ok(Image {
+            frames,
+            metadata,
+            interleaved: InterleavedCache::default()
+        })
+    }
+}
+
 pub(crate) fn checked_mul(
     width: usize, height: usize, depth: usize, colorspace_components: usize
 ) -> usize {
@@ -17,18 +17,34 @@
 //! ### Image decoders and encoders
 //! By default, a feature includes both format decoder and encoder if present.
 //!
+//! The `image_formats` feature, which is enabled by default, includes every format below
+//! except `exr`, `svg`, `pdf`, `jpeg2000`, `heif` and `legacy-formats`, those have to be
+//! enabled explicitly.
+//!
 //!
 //!| Feature      | Decoder       | Encoder        |
 //!|--------------|---------------|----------------|
 //!| bmp          | zune-bmp      |     -          |
+//!| dicom        | zune-dicom    |     -          |
+//!| dng          | zune-dng      |     -          |
+//!| exr          | [exr]         |     -          |
 //!| jpeg         | zune-jpeg     | [jpeg-encoder] |
 //!| png          | zune-png      | zune-png       |
 //!| ppm          | zune-ppm      | zune-ppm       |
 //!| qoi          | zune-qoi      | zune-qoi       |
 //!| farbfeld     | zune-farbfeld | zune-farbfeld  |
+//!| gif          | zune-gif      | -              |
 //!| psd          | zune-psd      | -              |
 //!| jpeg-xl      | [jxl-oxide]   | zune-jpegxl    |
+//!| jpeg2000     | [hayro-jpeg2000] |     -       |
 //!| hdr          | zune-hdr      | zune-hdr       |
+//!| heif         | zune-heif, [libde265] with the `libde265` feature | - |
+//!| ico          | zune-image    | zune-image     |
+//!| svg          | [resvg]       |     -          |
+//!| tga          | zune-tga      | zune-tga       |
+//!| tiff         | zune-tiff     |     -          |
+//!| legacy-formats | zune-image (PCX) |  -          |
+//!| pdf          | zune-image (embedded images) | -  |
 //!
 //!
 //! ### Image filters
@@ -84,10 +100,25 @@
 //! The channels api ([`channels_ref`](image::Image::channels_ref) and [`channels_mut`](image::Image::channels_mut) provide
 //! convenient methods to access image channels. This returns all image channels,traversing frames and concatenating it together
 //!
+//! ### Image pixels
+//! Channels are stored separately (planar), to work with pixels instead,
+//! [`pixels`](image::Image::pixels) iterates over [`Pixel`](image::Pixel)s holding a value from each channel
+//! and [`map_pixels`](image::Image::map_pixels) modifies them in place, with the `threads` feature
+//! [`par_map_pixels`](image::Image::par_map_pixels) does the same on multiple threads.
+//!
+//! For packed buffers, e.g. to display an image or upload it to a GPU, use
+//! [`to_interleaved_u8`](image::Image::to_interleaved_u8) and
+//! [`from_interleaved`](image::Image::from_interleaved).
+//! With the `wgpu` feature, the [`gpu`] module lays out images for `wgpu` textures.
+//!
 //!
 //![image]:https://crates.io/crates/image
 //! [jpeg-encoder]: https://crates.io/crates/jpeg-encoder
 //! [jxl-oxide]: https://crates.io/crates/jxl-oxide
+//! [exr]: https://crates.io/crates/exr
+//! [resvg]: https://crates.io/crates/resvg
+//! [hayro-jpeg2000]: https://crates.io/crates/hayro-jpeg2000
+//! [libde265]: https://github.com/strukturag/libde265
 #![allow(
     clippy::redundant_field_names,
     clippy::uninlined_format_args,
@@ -103,6 +134,7 @@ pub mod core_filters;
 mod deinterleave;
 pub mod errors;
 pub mod frame;
+pub mod gpu;
 pub mod image;
 pub mod metadata;
 mod ops;
@@ -11,12 +11,17 @@
 //! This module provides the ability to store image metadata and transfer it
 //! from one image to another
 
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
 use zune_core::bit_depth::BitDepth;
 use zune_core::colorspace::{ColorCharacteristics, ColorSpace};
+use zune_core::options::ChromaSubsampling;
 
 use crate::codecs::ImageFormat;
 
 mod exif;
+mod orientation;
 
 /// Contains information about whether the image
 /// is pre multiplied with it's alpha
@@ -27,6 +32,155 @@ pub enum AlphaState {
     NonPreMultiplied
 }
 
+/// Color calibration of a camera raw image
+///
+/// Matrices are 3x3 and stored in row major order, see the DNG
+/// specification on how they map camera native values to XYZ
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CameraColorInfo {
+    /// Matrix mapping XYZ to camera native space under the first calibration illuminant
+    pub color_matrix_1:           Option<[f32; 9]>,
+    /// Matrix mapping XYZ to camera native space under the second calibration illuminant
+    pub color_matrix_2:           Option<[f32; 9]>,
+    /// Matrix mapping white balanced camera values to XYZ D50 under the first illuminant
+    pub forward_matrix_1:         Option<[f32; 9]>,
+    /// Matrix mapping white balanced camera values to XYZ D50 under the second illuminant
+    pub forward_matrix_2:         Option<[f32; 9]>,
+    /// Per-unit calibration for the first illuminant
+    pub camera_calibration_1:     Option<[f32; 9]>,
+    /// Per-unit calibration for the second illuminant
+    pub camera_calibration_2:     Option<[f32; 9]>,
+    /// Camera native values of a neutral color at the time of shooting
+    pub as_shot_neutral:          Option<[f32; 3]>,
+    /// EXIF light source of the first calibration
+    pub calibration_illuminant_1: Option<u16>,
+    /// EXIF light source of the second calibration
+    pub calibration_illuminant_2: Option<u16>
+}
+
+/// A part of an image that matters more than the rest, e.g. a face found by a detector
+///
+/// Operations that choose which part of an image to keep or to expose for, like
+/// smart crop and auto exposure, favour these regions.
+///
+/// Coordinates are in pixels. Crop and resize keep regions in place, other operations
+/// that move pixels around don't update them.
+///
+/// Regions can be parsed from and formatted as text in the form `x,y,width,height`
+/// with an optional `,weight` suffix
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RegionOfInterest {
+    /// Column of the top left corner
+    pub x:      usize,
+    /// Row of the top left corner
+    pub y:      usize,
+    pub width:  usize,
+    pub height: usize,
+    /// How much the region matters compared to other regions, 1.0 by default
+    pub weight: f32
+}
+
+impl RegionOfInterest {
+    /// Create a new region with a weight of 1.0
+    pub const fn new(x: usize, y: usize, width: usize, height: usize) -> RegionOfInterest {
+        RegionOfInterest {
+            x,
+            y,
+            width,
+            height,
+            weight: 1.0
+        }
+    }
+    /// Set the weight of the region
+    #[must_use]
+    pub const fn set_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+    /// Return the region in the coordinates of the `width` x `height` sub-image starting
+    /// at `(x, y)`, clipped to it, or `None` if it lies outside
+    #[must_use]
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Option<Self> {
+        let left = self.x.max(x);
+        let top = self.y.max(y);
+        let right = (self.x + self.width).min(x + width);
+        let bottom = (self.y + self.height).min(y + height);
+
+        if left >= right || top >= bottom {
+            return None;
+        }
+        Some(RegionOfInterest {
+            x:      left - x,
+            y:      top - y,
+            width:  right - left,
+            height: bottom - top,
+            weight: self.weight
+        })
+    }
+    /// Return the region after scaling the image by `x_scale` horizontally and
+    /// `y_scale` vertically
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn scale(&self, x_scale: f64, y_scale: f64) -> Self {
+        let scale = |value: usize, scale: f64| (value as f64 * scale).round() as usize;
+        let x = scale(self.x, x_scale);
+        let y = scale(self.y, y_scale);
+
+        RegionOfInterest {
+            x,
+            y,
+            width: (scale(self.x + self.width, x_scale) - x).max(1),
+            height: (scale(self.y + self.height, y_scale) - y).max(1),
+            weight: self.weight
+        }
+    }
+}
+
+impl FromStr for RegionOfInterest {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values: Vec<&str> = s.split(',').map(str::trim).collect();
+
+        if !(4..=5).contains(&values.len()) {
+            return Err(format!(
+                "Invalid region {s:?}, expected x,y,width,height with an optional ,weight"
+            ));
+        }
+        let integer = |value: &str| {
+            value
+                .parse::<usize>()
+                .map_err(|e| format!("Invalid region value {value:?}: {e}"))
+        };
+        let mut region = RegionOfInterest::new(
+            integer(values[0])?,
+            integer(values[1])?,
+            integer(values[2])?,
+            integer(values[3])?
+        );
+        if let Some(weight) = values.get(4) {
+            region.weight = weight
+                .parse::<f32>()
+                .map_err(|e| format!("Invalid region weight {weight:?}: {e}"))?;
+        }
+        Ok(region)
+    }
+}
+
+impl Display for RegionOfInterest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{},{},{}", self.x, self.y, self.width, self.height)?;
+        if self.weight != 1.0 {
+            write!(f, ",{}", self.weight)?;
+        }
+        Ok(())
+    }
+}
+
 /// Image metadata
 ///
 /// Each image type has this information present
@@ -46,7 +200,14 @@ pub struct ImageMetadata {
     pub(crate) alpha:         AlphaState,
     #[cfg(feature = "metadata")]
     pub(crate) exif:          Option<Vec<::exif::Field>>,
-    pub(crate) icc_chunk:     Option<Vec<u8>>
+    pub(crate) icc_chunk:     Option<Vec<u8>>,
+    pub(crate) orientation:   Option<u16>,
+    pub(crate) subsampling:   Option<ChromaSubsampling>,
+    pub(crate) window_level:  Option<(f32, f32)>,
+    pub(crate) camera_color:  Option<CameraColorInfo>,
+    pub(crate) loop_count:    Option<u16>,
+    pub(crate) dpi:           Option<(f32, f32)>,
+    pub(crate) regions:       Vec<RegionOfInterest>
 }
 
 impl Default for ImageMetadata {
@@ -63,7 +224,14 @@ impl Default for ImageMetadata {
             #[cfg(feature = "metadata")]
             exif: None,
 
-            icc_chunk: None
+            icc_chunk:    None,
+            orientation:  None,
+            subsampling:  None,
+            window_level: None,
+            camera_color: None,
+            loop_count:   None,
+            dpi:          None,
+            regions:      Vec::new()
         }
     }
 }
@@ -193,4 +361,135 @@ impl ImageMetadata {
     pub fn icc_chunk(&self) -> Option<&Vec<u8>> {
         self.icc_chunk.as_ref()
     }
+    /// Return the raw exif orientation value of the image
+    ///
+    /// This is a value between 1 and 8 as defined by the exif specification,
+    /// where 1 means the image is stored in its display orientation.
+    ///
+    /// This is read by the decoders regardless of the `metadata` feature,
+    /// and is None if the image does not carry orientation information
+    pub const fn orientation(&self) -> Option<u16> {
+        self.orientation
+    }
+    /// Set the raw exif orientation value of the image
+    ///
+    /// This does not rotate the image, see
+    /// [`AutoOrient`](crate::core_filters::orientation::AutoOrient) for that
+    pub fn set_orientation(&mut self, orientation: u16) {
+        self.orientation = Some(orientation);
+    }
+
+    /// Return the chroma subsampling of the source image
+    ///
+    /// This is set by decoders of formats that store subsampled chroma (currently JPEG)
+    /// and is used by the jpeg encoder when
+    /// [`ChromaSubsampling::SameAsSource`] is requested
+    pub const fn chroma_subsampling(&self) -> Option<ChromaSubsampling> {
+        self.subsampling
+    }
+    /// Set the chroma subsampling of the image
+    pub fn set_chroma_subsampling(&mut self, subsampling: ChromaSubsampling) {
+        self.subsampling = Some(subsampling);
+    }
+
+    /// Return the display window of the image as `(center, width)`
+    ///
+    /// This is set by decoders of formats that carry a VOI window (currently DICOM)
+    /// and is expressed in the units of the decoded pixels, values
+    /// below `center - width / 2` should display as black and values above
+    /// `center + width / 2` as white
+    pub const fn window_level(&self) -> Option<(f32, f32)> {
+        self.window_level
+    }
+    /// Set the display window of the image as `(center, width)`
+    pub fn set_window_level(&mut self, center: f32, width: f32) {
+        self.window_level = Some((center, width));
+    }
+
+    /// Return the color calibration of the camera that produced the image
+    ///
+    /// This is set by decoders of camera raw formats (currently DNG), the pixels of
+    /// such images are in the camera native color space and these matrices are needed
+    /// to convert them into a standard color space
+    pub const fn camera_color_info(&self) -> Option<&CameraColorInfo> {
+        self.camera_color.as_ref()
+    }
+    /// Set the color calibration of the camera that produced the image
+    pub fn set_camera_color_info(&mut self, info: CameraColorInfo) {
+        self.camera_color = Some(info);
+    }
+
+    /// Return the number of times an animated image should be played
+    ///
+    /// This is set by decoders of formats that store it (currently GIF and PNG), `Some(0)`
+    /// means the animation should loop forever while `None` means the file didn't
+    /// specify it
+    pub const fn loop_count(&self) -> Option<u16> {
+        self.loop_count
+    }
+    /// Set the number of times an animated image should be played, `0` means forever
+    pub fn set_loop_count(&mut self, count: u16) {
+        self.loop_count = Some(count);
+    }
+
+    /// Return the horizontal and vertical pixel density of the image in dots per inch
+    ///
+    /// This is set by decoders of formats that store it (currently PNG, JPEG and BMP),
+    /// and is `None` if the file didn't specify it or only gave the pixel aspect ratio
+    pub const fn dpi(&self) -> Option<(f32, f32)> {
+        self.dpi
+    }
+    /// Set the horizontal and vertical pixel density of the image in dots per inch
+    pub fn set_dpi(&mut self, x_dpi: f32, y_dpi: f32) {
+        self.dpi = Some((x_dpi, y_dpi));
+    }
+
+    /// Return the regions of interest of the image
+    ///
+    /// These are set by callers, e.g. from the output of a face detector, no decoder
+    /// reads them from files
+    pub fn regions_of_interest(&self) -> &[RegionOfInterest] {
+        &self.regions
+    }
+    /// Set the regions of interest of the image, replacing existing ones
+    pub fn set_regions_of_interest(&mut self, regions: Vec<RegionOfInterest>) {
+        self.regions = regions;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metadata::RegionOfInterest;
+
+    #[test]
+    fn test_region_of_interest_text() {
+        let region: RegionOfInterest = "10, 20,30,40".parse().unwrap();
+        assert_eq!(region, RegionOfInterest::new(10, 20, 30, 40));
+        assert_eq!(region.to_string(), "10,20,30,40");
+
+        let weighted: RegionOfInterest = "1,2,3,4,2.5".parse().unwrap();
+        assert_eq!(weighted.weight, 2.5);
+        assert_eq!(weighted.to_string(), "1,2,3,4,2.5");
+
+        assert!("1,2,3".parse::<RegionOfInterest>().is_err());
+        assert!("1,2,-3,4".parse::<RegionOfInterest>().is_err());
+    }
+
+    #[test]
+    fn test_region_of_interest_crop_and_scale() {
+        let region = RegionOfInterest::new(10, 10, 20, 20).set_weight(2.0);
+
+        let cropped = region.crop(15, 0, 100, 25).unwrap();
+        assert_eq!(
+            cropped,
+            RegionOfInterest::new(0, 10, 15, 15).set_weight(2.0)
+        );
+        assert_eq!(region.crop(30, 0, 10, 10), None);
+
+        let scaled = region.scale(0.5, 2.0);
+        assert_eq!(
+            (scaled.x, scaled.y, scaled.width, scaled.height),
+            (5, 20, 10, 40)
+        );
+    }
 }
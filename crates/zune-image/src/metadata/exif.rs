@@ -8,7 +8,9 @@
 
 #![cfg(feature = "metadata")]
 
-use zune_core::log::{error, trace};
+use exif::experimental::Writer;
+use exif::{Field, In, Tag, Value};
+use zune_core::log::{error, trace, warn};
 
 use crate::metadata::ImageMetadata;
 
@@ -32,4 +34,137 @@ impl ImageMetadata {
             }
         };
     }
+
+    /// Set an exif field, replacing an existing field with the same
+    /// tag in the same IFD
+    ///
+    /// Fields are written by encoders that support exif (JPEG and PNG)
+    /// unless metadata is stripped via [`EncoderOptions::set_strip_metadata`]
+    ///
+    /// [`EncoderOptions::set_strip_metadata`]: zune_core::options::EncoderOptions::set_strip_metadata
+    #[cfg_attr(feature = "docs", doc(cfg(feature = "metadata")))]
+    pub fn set_exif_field(&mut self, field: Field) {
+        let fields = self.exif.get_or_insert_with(Vec::new);
+
+        match fields
+            .iter_mut()
+            .find(|x| x.tag == field.tag && x.ifd_num == field.ifd_num)
+        {
+            Some(existing) => *existing = field,
+            None => fields.push(field)
+        }
+    }
+
+    /// Remove all exif fields with the given tag
+    ///
+    /// Returns true if a field was removed
+    #[cfg_attr(feature = "docs", doc(cfg(feature = "metadata")))]
+    pub fn remove_exif_field(&mut self, tag: Tag) -> bool {
+        match &mut self.exif {
+            Some(fields) => {
+                let length = fields.len();
+                fields.retain(|x| x.tag != tag);
+                fields.len() != length
+            }
+            None => false
+        }
+    }
+
+    /// Return the copyright notice stored in exif, if present
+    #[cfg_attr(feature = "docs", doc(cfg(feature = "metadata")))]
+    pub fn copyright(&self) -> Option<String> {
+        let field = self
+            .exif
+            .as_ref()?
+            .iter()
+            .find(|x| x.tag == Tag::Copyright && x.ifd_num == In::PRIMARY)?;
+
+        match &field.value {
+            Value::Ascii(strings) => {
+                let notice = strings.first()?;
+                Some(String::from_utf8_lossy(notice).into_owned())
+            }
+            _ => None
+        }
+    }
+
+    /// Set the exif copyright notice of the image
+    #[cfg_attr(feature = "docs", doc(cfg(feature = "metadata")))]
+    pub fn set_copyright(&mut self, copyright: &str) {
+        self.set_exif_field(Field {
+            tag:     Tag::Copyright,
+            ifd_num: In::PRIMARY,
+            value:   Value::Ascii(vec![copyright.as_bytes().to_vec()])
+        });
+    }
+
+    /// Serialize exif fields and the orientation into a big endian TIFF structure
+    ///
+    /// The orientation from [`orientation`](Self::orientation) takes
+    /// precedence over an orientation exif field.
+    ///
+    /// Returns `None` if there is nothing to write or writing failed
+    pub(crate) fn exif_bytes(&self) -> Option<Vec<u8>> {
+        let mut fields = self.exif.clone().unwrap_or_default();
+
+        if let Some(orientation) = self.orientation {
+            fields.retain(|x| !(x.tag == Tag::Orientation && x.ifd_num == In::PRIMARY));
+            fields.push(Field {
+                tag:     Tag::Orientation,
+                ifd_num: In::PRIMARY,
+                value:   Value::Short(vec![orientation])
+            });
+        }
+        if fields.is_empty() {
+            return None;
+        }
+        let mut writer = Writer::new();
+
+        for field in &fields {
+            writer.push_field(field);
+        }
+        let mut buf = std::io::Cursor::new(vec![]);
+
+        match writer.write(&mut buf, false) {
+            Ok(()) => Some(buf.into_inner()),
+            Err(err) => {
+                warn!("Writing exif failed {:?}", err);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use exif::{Field, In, Tag, Value};
+
+    use crate::metadata::ImageMetadata;
+
+    #[test]
+    fn test_set_exif_fields() {
+        let mut metadata = ImageMetadata::default();
+        assert!(metadata.exif_bytes().is_none());
+
+        metadata.set_copyright("first");
+        metadata.set_copyright("second");
+        assert_eq!(metadata.exif().unwrap().len(), 1);
+        assert_eq!(metadata.copyright().as_deref(), Some("second"));
+
+        metadata.set_exif_field(Field {
+            tag:     Tag::Orientation,
+            ifd_num: In::PRIMARY,
+            value:   Value::Short(vec![3])
+        });
+        metadata.set_orientation(6);
+
+        let bytes = metadata.exif_bytes().unwrap();
+        let (fields, _) = exif::parse_exif(&bytes).unwrap();
+        let orientation = fields.iter().find(|x| x.tag == Tag::Orientation).unwrap();
+        assert_eq!(orientation.value.get_uint(0), Some(6));
+
+        assert!(metadata.remove_exif_field(Tag::Copyright));
+        assert!(!metadata.remove_exif_field(Tag::Copyright));
+        assert!(metadata.copyright().is_none());
+    }
 }
@@ -0,0 +1,101 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Minimal exif parsing for the orientation tag
+//!
+//! This doesn't depend on the `metadata` feature so that orientation
+//! is available for all builds.
+
+use zune_core::log::trace;
+
+use crate::metadata::ImageMetadata;
+
+/// Exif orientation tag id
+const ORIENTATION_TAG: u16 = 0x0112;
+/// Exif SHORT type
+const TYPE_SHORT: u16 = 3;
+
+/// Read the orientation value from raw exif data
+///
+/// Data should point to the TIFF header (`II*\0` or `MM\0*`), an `Exif\0\0`
+/// prefix is also accepted
+pub(crate) fn exif_orientation(data: &[u8]) -> Option<u16> {
+    let data = data.strip_prefix(b"Exif\x00\x00").unwrap_or(data);
+
+    let big_endian = match data.get(0..4)? {
+        b"MM\x00*" => true,
+        b"II*\x00" => false,
+        _ => return None
+    };
+    let read_u16 = |pos: usize| -> Option<u16> {
+        let bytes: [u8; 2] = data.get(pos..pos + 2)?.try_into().ok()?;
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    };
+    let read_u32 = |pos: usize| -> Option<u32> {
+        let bytes: [u8; 4] = data.get(pos..pos + 4)?.try_into().ok()?;
+        Some(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    };
+    // the orientation tag lives in IFD0
+    let ifd_start = usize::try_from(read_u32(4)?).ok()?;
+    let entries = usize::from(read_u16(ifd_start)?);
+
+    for entry in 0..entries {
+        let pos = ifd_start + 2 + entry * 12;
+
+        if read_u16(pos)? == ORIENTATION_TAG {
+            if read_u16(pos + 2)? != TYPE_SHORT {
+                return None;
+            }
+            return read_u16(pos + 8).filter(|x| (1..=8).contains(x));
+        }
+    }
+    None
+}
+
+impl ImageMetadata {
+    /// Read the orientation tag from raw exif data and store it
+    ///
+    /// Data should point to the first exif byte
+    pub(crate) fn parse_raw_exif_orientation(&mut self, data: &[u8]) {
+        self.orientation = exif_orientation(data);
+
+        trace!("Exif orientation: {:?}", self.orientation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metadata::orientation::exif_orientation;
+
+    #[test]
+    fn test_exif_orientation() {
+        // little endian, one entry in IFD0 storing orientation 6
+        let le = [
+            b'I', b'I', 0x2A, 0x00, 0x08, 0x00, 0x00, 0x00, 0x01, 0x00, 0x12, 0x01, 0x03, 0x00,
+            0x01, 0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00
+        ];
+        assert_eq!(exif_orientation(&le), Some(6));
+        // big endian with exif prefix, storing orientation 3
+        let mut be = b"Exif\x00\x00".to_vec();
+        be.extend_from_slice(&[
+            b'M', b'M', 0x00, 0x2A, 0x00, 0x00, 0x00, 0x08, 0x00, 0x01, 0x01, 0x12, 0x00, 0x03,
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x03, 0x00, 0x00
+        ]);
+        assert_eq!(exif_orientation(&be), Some(3));
+        // truncated data
+        assert_eq!(exif_orientation(&le[..12]), None);
+    }
+}
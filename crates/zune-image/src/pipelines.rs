@@ -5,8 +5,15 @@
  */
 //! Pipelines, Batch image processing support
 //!
+//! Pipelines measure the work they do, after running, [`Pipeline::report`]
+//! returns an [`ExecutionReport`] with the time each stage and operation took, the
+//! pixels processed and the memory used by images, which can be used to log and meter
+//! processing costs.
+//!
+//! With the `tracing` feature, decoding and each operation also run in a
+//! [tracing](https://crates.io/crates/tracing) span.
 #![allow(unused_variables)]
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use zune_core::log::Level::Trace;
 use zune_core::log::{log_enabled, trace};
@@ -62,6 +69,59 @@ impl EncodeResult {
     }
 }
 
+/// Measurements of a single operation ran by a pipeline
+#[derive(Clone, Debug)]
+pub struct OperationReport {
+    /// The name of the operation, see [`OperationsTrait::name`]
+    pub name:     &'static str,
+    /// Wall time the operation took
+    pub duration: Duration,
+    /// Number of pixels of the image the operation ran on, counting all frames
+    pub pixels:   usize,
+    /// Bytes used by the image after the operation ran
+    pub memory:   usize
+}
+
+/// Measurements of a pipeline run
+///
+/// Operations are reported in the order they ran, an operation applied to
+/// multiple images has a report for each image.
+#[derive(Clone, Debug, Default)]
+pub struct ExecutionReport {
+    /// Wall time spent decoding, `None` if the pipeline didn't decode an image
+    pub decode:      Option<Duration>,
+    /// Measurements of each operation
+    pub operations:  Vec<OperationReport>,
+    /// Wall time of the whole run
+    pub total:       Duration,
+    /// Largest amount of bytes used by the pipeline's images
+    ///
+    /// This is measured between stages, so memory used temporarily
+    /// inside an operation isn't counted
+    pub peak_memory: usize
+}
+
+impl ExecutionReport {
+    /// Total wall time spent running operations
+    pub fn operations_duration(&self) -> Duration {
+        self.operations.iter().map(|x| x.duration).sum()
+    }
+
+    fn record_memory(&mut self, images: &[Image]) {
+        let memory = images.iter().map(image_memory).sum();
+        self.peak_memory = self.peak_memory.max(memory);
+    }
+}
+
+/// Return the number of bytes allocated for an image's pixels
+fn image_memory(image: &Image) -> usize {
+    image
+        .channels_ref(false)
+        .iter()
+        .map(|channel| channel.capacity())
+        .sum()
+}
+
 /// Pipeline, batch image processing
 ///
 /// A pipeline provides an idiomatic way to do batch image processing
@@ -76,7 +136,8 @@ pub struct Pipeline {
     state:      Option<PipelineState>,
     decode:     Option<Box<dyn IntoImage>>,
     image:      Vec<Image>,
-    operations: Vec<Box<dyn OperationsTrait>>
+    operations: Vec<Box<dyn OperationsTrait>>,
+    report:     ExecutionReport
 }
 
 impl Pipeline {
@@ -87,7 +148,8 @@ impl Pipeline {
             image:      vec![],
             state:      Some(PipelineState::Initialized),
             decode:     None,
-            operations: vec![]
+            operations: vec![],
+            report:     ExecutionReport::default()
         }
     }
 
@@ -142,6 +204,10 @@ impl Pipeline {
     pub fn images_mut(&mut self) -> &mut [Image] {
         self.image.as_mut()
     }
+    /// Return measurements of the stages that have run so far
+    pub fn report(&self) -> &ExecutionReport {
+        &self.report
+    }
     /// Advance the workflow one state forward
     ///
     /// The workflow advance is as follows
@@ -153,6 +219,14 @@ impl Pipeline {
     ///
     /// Calling `Workflow::advance()` will run one of this operation
     pub fn advance(&mut self) -> Result<(), ImageErrors> {
+        let start = Instant::now();
+        let result = self.advance_state();
+        self.report.total += start.elapsed();
+
+        result
+    }
+
+    fn advance_state(&mut self) -> Result<(), ImageErrors> {
         if let Some(state) = self.state {
             match state {
                 PipelineState::Decode => {
@@ -160,7 +234,7 @@ impl Pipeline {
                     // do the actual decode
                     if self.decode.is_none() {
                         // we have an image, no need to decode a new one
-                        if self.image.is_empty() {
+                        if !self.image.is_empty() {
                             trace!("Image already present, no need to decode");
                             // move to the next state
                             self.state = state.next();
@@ -177,12 +251,18 @@ impl Pipeline {
 
                     let mut decode_op = self.decode.take().unwrap();
 
+                    #[cfg(feature = "tracing")]
+                    let _span = tracing::info_span!("decode").entered();
+
                     let img = decode_op.into_image()?;
 
                     self.image.push(img);
 
                     let stop = Instant::now();
 
+                    self.report.decode = Some(stop - start);
+                    self.report.record_memory(&self.image);
+
                     self.state = state.next();
 
                     trace!("Finished decoding in {} ms", (stop - start).as_millis());
@@ -197,12 +277,22 @@ impl Pipeline {
                         trace!("Current state: {:?}\n", state);
                     }
 
-                    for image in self.image.iter_mut() {
+                    self.report.record_memory(&self.image);
+
+                    for index in 0..self.image.len() {
                         for operation in &self.operations {
+                            let image = &mut self.image[index];
                             let operation_name = operation.name();
+                            let (width, height) = image.dimensions();
+                            let pixels = width * height * image.frames_len();
 
                             trace!("Running {}", operation_name);
 
+                            #[cfg(feature = "tracing")]
+                            let _span =
+                                tracing::info_span!("operation", name = operation_name, pixels)
+                                    .entered();
+
                             let start = Instant::now();
 
                             operation.execute(image)?;
@@ -213,6 +303,13 @@ impl Pipeline {
                                 "Finished running `{operation_name}` in {} ms",
                                 (stop - start).as_millis()
                             );
+                            self.report.operations.push(OperationReport {
+                                name: operation_name,
+                                duration: stop - start,
+                                pixels,
+                                memory: image_memory(image)
+                            });
+                            self.report.record_memory(&self.image);
                         }
                         self.state = state.next();
                     }
@@ -234,13 +331,30 @@ impl Pipeline {
     /// we finish.
     ///
     /// This will run a decoder, all operations and all encoders
-    /// for this particular workflow
-    pub fn advance_to_end(&mut self) -> Result<(), ImageErrors> {
+    /// for this particular workflow, and return measurements of the run
+    ///
+    /// # Example
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::core_filters::depth::Depth;
+    /// use zune_image::image::Image;
+    /// use zune_image::pipelines::Pipeline;
+    ///
+    /// let mut pipeline = Pipeline::new();
+    /// pipeline.chain_image(Image::fill::<u8>(0, ColorSpace::RGB, 100, 100));
+    /// pipeline.chain_operations(Box::new(Depth::new(zune_core::bit_depth::BitDepth::Sixteen)));
+    ///
+    /// let report = pipeline.advance_to_end().unwrap();
+    ///
+    /// assert_eq!(report.operations[0].pixels, 100 * 100);
+    /// assert_eq!(report.peak_memory, 100 * 100 * 3 * 2);
+    /// ```
+    pub fn advance_to_end(&mut self) -> Result<ExecutionReport, ImageErrors> {
         if self.state.is_some() {
             while self.state.is_some() {
                 self.advance()?;
             }
         }
-        Ok(())
+        Ok(self.report.clone())
     }
 }
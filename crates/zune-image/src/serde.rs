@@ -14,14 +14,51 @@ use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
 
 use crate::codecs::ImageFormat;
-use crate::metadata::ImageMetadata;
+use crate::metadata::{CameraColorInfo, ImageMetadata, RegionOfInterest};
+use crate::pipelines::{ExecutionReport, OperationReport};
+
+impl Serialize for CameraColorInfo {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        let mut state = serializer.serialize_struct("CameraColorInfo", 9)?;
+
+        state.serialize_field("color_matrix_1", &self.color_matrix_1)?;
+        state.serialize_field("color_matrix_2", &self.color_matrix_2)?;
+        state.serialize_field("forward_matrix_1", &self.forward_matrix_1)?;
+        state.serialize_field("forward_matrix_2", &self.forward_matrix_2)?;
+        state.serialize_field("camera_calibration_1", &self.camera_calibration_1)?;
+        state.serialize_field("camera_calibration_2", &self.camera_calibration_2)?;
+        state.serialize_field("as_shot_neutral", &self.as_shot_neutral)?;
+        state.serialize_field("calibration_illuminant_1", &self.calibration_illuminant_1)?;
+        state.serialize_field("calibration_illuminant_2", &self.calibration_illuminant_2)?;
+        state.end()
+    }
+}
+
+impl Serialize for RegionOfInterest {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        let mut state = serializer.serialize_struct("RegionOfInterest", 5)?;
+
+        state.serialize_field("x", &self.x)?;
+        state.serialize_field("y", &self.y)?;
+        state.serialize_field("width", &self.width)?;
+        state.serialize_field("height", &self.height)?;
+        state.serialize_field("weight", &self.weight)?;
+        state.end()
+    }
+}
 
 impl Serialize for ImageMetadata {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer
     {
-        const STRUCT_FIELDS: usize = 7;
+        const STRUCT_FIELDS: usize = 14;
         let mut state = serializer.serialize_struct("Metadata", STRUCT_FIELDS)?;
 
         state.serialize_field("width", &self.width)?;
@@ -31,6 +68,13 @@ impl Serialize for ImageMetadata {
         state.serialize_field("format", &self.format)?;
         state.serialize_field("color_transfer_characteristics", &self.color_trc)?;
         state.serialize_field("gamma_value", &self.default_gamma)?;
+        state.serialize_field("orientation", &self.orientation)?;
+        state.serialize_field("chroma_subsampling", &self.subsampling)?;
+        state.serialize_field("window_level", &self.window_level)?;
+        state.serialize_field("camera_color", &self.camera_color)?;
+        state.serialize_field("loop_count", &self.loop_count)?;
+        state.serialize_field("dpi", &self.dpi)?;
+        state.serialize_field("regions_of_interest", &self.regions)?;
 
         #[cfg(feature = "metadata")]
         {
@@ -75,3 +119,35 @@ impl Serialize for ImageFormat {
         serializer.serialize_str(&format!("{:?}", self))
     }
 }
+
+// durations are stored as microseconds, which is easier to consume
+// by log and metric pipelines than serde's seconds and nanoseconds
+impl Serialize for OperationReport {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        let mut state = serializer.serialize_struct("OperationReport", 4)?;
+
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("duration_us", &self.duration.as_micros())?;
+        state.serialize_field("pixels", &self.pixels)?;
+        state.serialize_field("memory", &self.memory)?;
+        state.end()
+    }
+}
+
+impl Serialize for ExecutionReport {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        let mut state = serializer.serialize_struct("ExecutionReport", 4)?;
+
+        state.serialize_field("decode_us", &self.decode.map(|x| x.as_micros()))?;
+        state.serialize_field("operations", &self.operations)?;
+        state.serialize_field("total_us", &self.total.as_micros())?;
+        state.serialize_field("peak_memory", &self.peak_memory)?;
+        state.end()
+    }
+}
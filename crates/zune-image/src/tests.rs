@@ -44,5 +44,117 @@ fn test_fractal() {
             *px[1] = i as u8;
         })
         .unwrap();
-    image.save_to("a.ppm", ImageFormat::PPM).unwrap()
+    // write outside the source tree so the output never gets committed
+    let path = std::env::temp_dir().join("zune-image-julia-set.ppm");
+    image.save_to(&path, ImageFormat::PPM).unwrap();
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_map_pixels() {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+
+    let mut image = Image::from_fn::<u8, _>(37, 23, ColorSpace::RGBA, |y, x, px| {
+        px[0] = x as u8;
+        px[1] = y as u8;
+        px[3] = 255;
+    });
+    // an animation, each frame is visited
+    image.frames.push(image.frames[0].clone());
+
+    let positions = image.pixels::<u8>().unwrap();
+    assert!(positions
+        .map(|px| (px.x, px.y))
+        .eq((0..2).flat_map(|_| (0..23).flat_map(|y| (0..37).map(move |x| (x, y))))));
+
+    let brighten = |px: &mut crate::image::Pixel<u8>| {
+        px[2] = px[0].wrapping_add(px[1]);
+        px[3] /= 2;
+    };
+    let mut parallel = image.clone();
+
+    image.map_pixels(brighten).unwrap();
+    #[cfg(feature = "threads")]
+    parallel.par_map_pixels(brighten).unwrap();
+    #[cfg(not(feature = "threads"))]
+    parallel.map_pixels(brighten).unwrap();
+
+    assert!(image == parallel);
+    assert!(image
+        .pixels::<u8>()
+        .unwrap()
+        .all(|px| usize::from(px[2]) == px.x + px.y && px[3] == 127));
+    assert!(image.pixels::<u16>().is_err());
+}
+
+#[test]
+fn test_interleaved_round_trip() {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+
+    let pixels: Vec<u16> = (0..7 * 5 * 3).map(|x| x * 600).collect();
+    let mut image = Image::from_interleaved(&pixels, 7, 5, ColorSpace::RGB).unwrap();
+
+    let floats = image.to_interleaved_f32().unwrap();
+    assert_eq!(floats.len(), pixels.len());
+    assert!(floats
+        .iter()
+        .zip(&pixels)
+        .all(|(a, b)| (a - f32::from(*b) / 65535.0).abs() < 1e-6));
+
+    let bytes = image.to_interleaved_u8().unwrap().to_vec();
+    assert_eq!(bytes[3], (3 * 600 / 257) as u8);
+    // the cached buffer is reused until the image is modified
+    assert_eq!(
+        image.to_interleaved_u8().unwrap().as_ptr(),
+        image.to_interleaved_u8().unwrap().as_ptr()
+    );
+    image.map_pixels::<u16, _>(|px| px[0] = 0).unwrap();
+    assert_eq!(image.to_interleaved_u8().unwrap()[3], 0);
+    assert_eq!(image.to_interleaved_u8().unwrap()[4], bytes[4]);
+
+    assert!(Image::from_interleaved(&pixels, 7, 6, ColorSpace::RGB).is_err());
+    assert!(Image::from_interleaved(&pixels, usize::MAX, 6, ColorSpace::RGB).is_err());
+}
+
+#[cfg(feature = "wgpu")]
+#[test]
+fn test_gpu_texture_round_trip() {
+    use wgpu_types::TextureFormat;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::gpu::{image_from_texture_data, padded_bytes_per_row, GpuTexture};
+    use crate::image::Image;
+
+    assert_eq!(padded_bytes_per_row(0).unwrap(), 0);
+    assert_eq!(padded_bytes_per_row(256).unwrap(), 256);
+    assert_eq!(padded_bytes_per_row(257).unwrap(), 512);
+
+    let pixels: Vec<u16> = (0..67 * 3).map(|x| x * 300).collect();
+    let image = Image::from_interleaved(&pixels, 67, 3, ColorSpace::Luma).unwrap();
+
+    let texture = GpuTexture::from_image(&image).unwrap();
+    assert_eq!(texture.descriptor.format, TextureFormat::R32Float);
+    assert_eq!(texture.layout.bytes_per_row, Some(512));
+    assert_eq!(texture.data.len(), 512 * 3);
+
+    // the last row may be unpadded
+    let data = &texture.data[..512 * 2 + 67 * 4];
+    let mut round_trip =
+        image_from_texture_data(data, 67, 3, 512, TextureFormat::R32Float).unwrap();
+    round_trip.convert_depth(image.depth()).unwrap();
+    assert!(round_trip == image);
+
+    assert!(image_from_texture_data(&data[1..], 67, 3, 512, TextureFormat::R32Float).is_err());
+    assert!(image_from_texture_data(data, 67, 3, 100, TextureFormat::R32Float).is_err());
+    assert!(image_from_texture_data(data, 67, 3, 512, TextureFormat::Depth32Float).is_err());
+
+    let rgb = Image::fill::<u8>(20, ColorSpace::RGB, 3, 2);
+    let texture = GpuTexture::from_image(&rgb).unwrap();
+    assert_eq!(texture.descriptor.format, TextureFormat::Rgba8UnormSrgb);
+    assert_eq!(&texture.data[..12], &[20, 20, 20, 255].repeat(3)[..]);
+    assert!(texture.data[12..256].iter().all(|x| *x == 0));
 }
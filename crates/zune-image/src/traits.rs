@@ -19,6 +19,7 @@ use zune_core::bytestream::ZByteWriterTrait;
 use zune_core::colorspace::{ColorSpace, ALL_COLORSPACES};
 use zune_core::log::{trace, warn};
 use zune_core::options::EncoderOptions;
+use zune_core::warnings::DecodeWarning;
 
 use crate::codecs::ImageFormat;
 use crate::core_filters::colorspace::ColorspaceConv;
@@ -88,6 +89,35 @@ pub trait DecoderTrait {
     fn read_headers(&mut self) -> Result<Option<ImageMetadata>, crate::errors::ImageErrors> {
         Ok(None)
     }
+    /// Return problems the decoder found in the file and recovered from,
+    /// e.g. truncated data, allowing callers to flag damaged files
+    ///
+    /// Decoders which don't report warnings return an empty slice
+    fn warnings(&self) -> &[DecodeWarning] {
+        &[]
+    }
+    /// Decode a single frame of the image, counting from zero
+    ///
+    /// The frame is returned as it would appear in the output of [`decode`](Self::decode),
+    /// i.e. for animated images it is composited onto the frames before it.
+    ///
+    /// Decoders for animated formats override this to stop decoding once the frame is
+    /// ready, the default implementation decodes every frame and discards the rest
+    ///
+    /// # Errors
+    ///  - [`ImageErrors::FrameOutOfRange`] if the image has `n` or fewer frames
+    fn decode_frame(&mut self, n: usize) -> Result<Image, crate::errors::ImageErrors> {
+        let mut image = self.decode()?;
+
+        let frames = image.frames_len();
+        if n >= frames {
+            return Err(ImageErrors::FrameOutOfRange(n, frames));
+        }
+        image.frames.swap(0, n);
+        image.frames.truncate(1);
+
+        Ok(image)
+    }
 }
 
 /// This encapsulates an image operation.
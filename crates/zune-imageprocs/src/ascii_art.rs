@@ -0,0 +1,282 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Render images as text for terminals
+//!
+//! The image is divided into a grid of cells, one per character, and every cell is drawn
+//! with a character whose density matches its brightness. This gives a quick preview of
+//! an image where only a terminal is available, e.g. over SSH.
+//!
+//! Two styles are available, see [`AsciiStyle`]
+//! - [`AsciiStyle::Ascii`]: Plain ASCII characters, from a space for black to `@` for white
+//! - [`AsciiStyle::Blocks`]: Unicode block characters, with colors every character shows
+//!   two pixels, stacked vertically, which doubles the vertical resolution
+//!
+//! With [colors](AsciiArt::set_color), characters are colored with 24 bit (truecolor) ANSI
+//! escape codes, which most terminals support.
+//!
+//! The characters assume light text on a dark background, transparent areas are drawn as black.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_imageprocs::ascii_art::{AsciiArt, AsciiStyle};
+//!
+//! let image = Image::from_fn::<u8, _>(100, 100, ColorSpace::RGB, |y, x, pix| {
+//!     pix[..3].fill(((x + y) * 255 / 198) as u8);
+//! });
+//! let text = AsciiArt::new(40).render(&image).unwrap();
+//! assert_eq!(text.lines().count(), 20);
+//!
+//! let text = AsciiArt::new(40)
+//!     .set_style(AsciiStyle::Blocks)
+//!     .set_color(true)
+//!     .render(&image)
+//!     .unwrap();
+//! println!("{text}");
+//! ```
+use core::fmt::Write;
+
+use zune_core::bit_depth::BitDepth;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+
+/// Characters from darkest to brightest
+const ASCII_RAMP: &[char] = &[' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+/// Shades from darkest to brightest
+const BLOCK_RAMP: &[char] = &[' ', '░', '▒', '▓', '█'];
+/// Upper half block, colored with the top pixel while the background shows the bottom one
+const HALF_BLOCK: char = '▀';
+/// Resets colors at the end of a line
+const RESET: &str = "\x1b[0m";
+
+/// Characters used by [`AsciiArt`]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum AsciiStyle {
+    /// Plain ASCII characters, which work everywhere
+    #[default]
+    Ascii,
+    /// Unicode shade and block characters
+    Blocks
+}
+
+impl AsciiStyle {
+    pub fn from_string_result(input: &str) -> Result<Self, String> {
+        match input {
+            "ascii" => Ok(Self::Ascii),
+            "blocks" => Ok(Self::Blocks),
+            _ => Err(format!(
+                "Unknown text style {input}, accepted values are ascii and blocks"
+            ))
+        }
+    }
+}
+
+/// Render an image as text
+#[derive(Copy, Clone, Debug)]
+pub struct AsciiArt {
+    columns: usize,
+    style:   AsciiStyle,
+    color:   bool
+}
+
+impl AsciiArt {
+    /// Create a new renderer
+    ///
+    /// # Arguments
+    /// - columns: Width of the text in characters, images narrower than this
+    ///   are rendered with a character per pixel
+    ///
+    /// The style is [`AsciiStyle::Ascii`], without colors
+    #[must_use]
+    pub fn new(columns: usize) -> AsciiArt {
+        AsciiArt {
+            columns,
+            style: AsciiStyle::Ascii,
+            color: false
+        }
+    }
+    /// Set the characters used to draw the image
+    #[must_use]
+    pub fn set_style(mut self, style: AsciiStyle) -> Self {
+        self.style = style;
+        self
+    }
+    /// Set whether characters are colored with truecolor ANSI escape codes
+    #[must_use]
+    pub fn set_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Render the first frame of an image, lines are separated by `\n`
+    ///
+    /// Characters are about twice as tall as wide, so every line covers about
+    /// twice the height of a column
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn render(&self, image: &Image) -> Result<String, ImageErrors> {
+        let Some(frame) = image.frames_ref().first() else {
+            return Err(ImageErrors::GenericStr("Image has no frames to render"));
+        };
+        let (width, height) = image.dimensions();
+        let columns = self.columns.clamp(1, width.max(1));
+        let lines = ((height * columns) as f32 / (width * 2) as f32)
+            .round()
+            .max(1.0) as usize;
+
+        let mut rgba = Image::new_frames(
+            vec![frame.clone()],
+            image.depth(),
+            width,
+            height,
+            image.colorspace()
+        );
+        rgba.convert_color(ColorSpace::RGBA)?;
+        rgba.convert_depth(BitDepth::Float32)?;
+        let pixels = &rgba.flatten_frames::<f32>()[0];
+
+        let half_blocks = self.style == AsciiStyle::Blocks && self.color;
+        let rows = if half_blocks { lines * 2 } else { lines };
+        let cells = cell_means(pixels, width, height, columns, rows);
+
+        let mut text = String::with_capacity(lines * (columns + 1));
+        // with half blocks every line is made of a pair of rows
+        let line_cells = if half_blocks { columns * 2 } else { columns };
+
+        for line in cells.chunks_exact(line_cells) {
+            if half_blocks {
+                let (above, below) = line.split_at(columns);
+
+                for (top, bottom) in above.iter().zip(below) {
+                    let [r, g, b] = to_u8(*top);
+                    let [br, bg, bb] = to_u8(*bottom);
+                    // writing to a string never fails
+                    let _ = write!(
+                        text,
+                        "\x1b[38;2;{r};{g};{b}m\x1b[48;2;{br};{bg};{bb}m{HALF_BLOCK}"
+                    );
+                }
+            } else {
+                let ramp = match self.style {
+                    AsciiStyle::Ascii => ASCII_RAMP,
+                    AsciiStyle::Blocks => BLOCK_RAMP
+                };
+                for cell in line {
+                    let luma = 0.2126 * cell[0] + 0.7152 * cell[1] + 0.0722 * cell[2];
+                    let index = (luma.clamp(0.0, 1.0) * (ramp.len() - 1) as f32).round();
+                    let character = ramp[index as usize];
+
+                    if self.color {
+                        let [r, g, b] = to_u8(*cell);
+                        let _ = write!(text, "\x1b[38;2;{r};{g};{b}m{character}");
+                    } else {
+                        text.push(character);
+                    }
+                }
+            }
+            if self.color {
+                text.push_str(RESET);
+            }
+            text.push('\n');
+        }
+        Ok(text)
+    }
+}
+
+/// Return the mean color of every cell of a `columns` x `rows` grid over RGBA pixels,
+/// composited over black
+#[allow(clippy::cast_precision_loss)]
+fn cell_means(
+    pixels: &[f32], width: usize, height: usize, columns: usize, rows: usize
+) -> Vec<[f32; 3]> {
+    // bounds of every cell, at least a pixel wide and tall
+    let span = |i: usize, cells: usize, size: usize| {
+        let start = (i * size / cells).min(size - 1);
+        start..((i + 1) * size / cells).clamp(start + 1, size)
+    };
+    let mut cells = Vec::with_capacity(columns * rows);
+
+    for row in 0..rows {
+        let ys = span(row, rows, height);
+
+        for column in 0..columns {
+            let xs = span(column, columns, width);
+            let mut sum = [0.0; 3];
+
+            for y in ys.clone() {
+                for pixel in
+                    pixels[(y * width + xs.start) * 4..(y * width + xs.end) * 4].chunks_exact(4)
+                {
+                    for (total, value) in sum.iter_mut().zip(pixel) {
+                        *total += value * pixel[3];
+                    }
+                }
+            }
+            let count = (ys.len() * xs.len()) as f32;
+            cells.push(sum.map(|x| x / count));
+        }
+    }
+    cells
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn to_u8(color: [f32; 3]) -> [u8; 3] {
+    color.map(|x| (x.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+
+    use crate::ascii_art::{AsciiArt, AsciiStyle};
+
+    #[test]
+    fn test_ascii_art() {
+        // black on the left, white on the right
+        let image = Image::from_fn::<u8, _>(40, 20, ColorSpace::Luma, |_, x, pix| {
+            pix[0] = if x < 20 { 0 } else { 255 };
+        });
+        let text = AsciiArt::new(8).render(&image).unwrap();
+        assert_eq!(text, "    @@@@\n    @@@@\n");
+
+        // more columns than pixels are limited to the width
+        let text = AsciiArt::new(100).render(&image).unwrap();
+        assert_eq!(text.lines().count(), 10);
+        assert!(text.lines().all(|x| x.len() == 40));
+
+        let text = AsciiArt::new(4)
+            .set_style(AsciiStyle::Blocks)
+            .render(&image)
+            .unwrap();
+        assert_eq!(text, "  ██\n");
+    }
+
+    #[test]
+    fn test_colored_blocks() {
+        // red above, transparent blue below
+        let image = Image::from_fn::<u16, _>(4, 4, ColorSpace::RGBA, |y, _, pix| {
+            let color = if y < 2 { [65535, 0, 0, 65535] } else { [0, 0, 65535, 0] };
+            pix[..4].copy_from_slice(&color);
+        });
+        let text = AsciiArt::new(1)
+            .set_style(AsciiStyle::Blocks)
+            .set_color(true)
+            .render(&image)
+            .unwrap();
+        assert_eq!(text, "\x1b[38;2;255;0;0m\x1b[48;2;0;0;0m▀\x1b[0m\n");
+
+        let text = AsciiArt::new(2).set_color(true).render(&image).unwrap();
+        assert_eq!(text, "\x1b[38;2;128;0;0m.\x1b[38;2;128;0;0m.\x1b[0m\n");
+    }
+}
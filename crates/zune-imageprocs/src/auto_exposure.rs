@@ -0,0 +1,179 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Automatic exposure correction
+//!
+//! The brightness of the image is metered and an [`Exposure`] gain bringing it to a
+//! target brightness is applied.
+//!
+//! # Metering
+//! - When the image has [regions of interest](crate::roi), only they are metered,
+//!   each weighted by its area and weight, so that e.g. faces end up correctly exposed
+//!   whatever the background is.
+//! - Otherwise the mean luminance of the whole image is used.
+//!
+//! The gain is limited to a number of stops in each direction, so nearly black or
+//! white images are not blown up.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::auto_exposure::AutoExposure;
+//!
+//! let mut image = Image::fill::<u8>(40, ColorSpace::RGB, 100, 100);
+//! AutoExposure::new().execute(&mut image).unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::exposure::Exposure;
+use crate::utils::frame_luma;
+
+/// Correct the exposure of an image
+///
+/// Alpha is ignored.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct AutoExposure {
+    target:    f32,
+    max_stops: f32
+}
+
+impl Default for AutoExposure {
+    fn default() -> Self {
+        AutoExposure::new()
+    }
+}
+
+impl AutoExposure {
+    /// Create a new auto exposure operation
+    ///
+    /// By default the metered area is brought to middle gray, 0.46 in sRGB, changing
+    /// the exposure by at most 3 stops
+    #[must_use]
+    pub fn new() -> AutoExposure {
+        AutoExposure {
+            target:    0.46,
+            max_stops: 3.0
+        }
+    }
+    /// Set the brightness the metered area is brought to, between 0.0 and 1.0
+    #[must_use]
+    pub fn set_target(mut self, target: f32) -> Self {
+        self.target = target.clamp(0.0, 1.0);
+        self
+    }
+    /// Set the largest exposure change, in stops, in either direction
+    #[must_use]
+    pub fn set_max_stops(mut self, max_stops: f32) -> Self {
+        self.max_stops = max_stops.max(0.0);
+        self
+    }
+
+    /// Return the exposure gain this operation would apply to `image`
+    ///
+    /// Only the first frame is metered.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn gain(&self, image: &Image) -> Result<f32, ImageErrors> {
+        let (width, height) = image.dimensions();
+        let luma = frame_luma(image, 0)?;
+
+        let mut sum = 0.0_f64;
+        let mut total_weight = 0.0_f64;
+
+        for region in image.metadata().regions_of_interest() {
+            let Some(region) = region.crop(0, 0, width, height) else {
+                continue;
+            };
+            let weight = f64::from(region.weight.max(0.0));
+
+            for y in region.y..region.y + region.height {
+                let row = &luma[y * width + region.x..][..region.width];
+                sum += weight * row.iter().map(|x| f64::from(*x)).sum::<f64>();
+            }
+            total_weight += weight * (region.width * region.height) as f64;
+        }
+        if total_weight <= 0.0 {
+            // no usable regions, meter the whole image
+            sum = luma.iter().map(|x| f64::from(*x)).sum();
+            total_weight = luma.len() as f64;
+        }
+        if total_weight <= 0.0 {
+            return Ok(1.0);
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let mean = (sum / total_weight) as f32;
+        let max_gain = self.max_stops.exp2();
+
+        if mean <= 0.0 {
+            return Ok(max_gain);
+        }
+        Ok((self.target / mean).clamp(max_gain.recip(), max_gain))
+    }
+}
+
+impl OperationsTrait for AutoExposure {
+    fn name(&self) -> &'static str {
+        "Auto Exposure"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let gain = self.gain(image)?;
+
+        Exposure::new(gain, 0.0).execute_impl(image)
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::metadata::RegionOfInterest;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::auto_exposure::AutoExposure;
+
+    #[test]
+    fn test_auto_exposure() {
+        let mut image = Image::fill::<u8>(59, ColorSpace::Luma, 10, 10);
+        AutoExposure::new().execute(&mut image).unwrap();
+        // 0.46 * 255
+        assert!(image.flatten_to_u8()[0]
+            .iter()
+            .all(|x| (116..=118).contains(x)));
+
+        // limited to 3 stops
+        let mut image = Image::fill::<u8>(2, ColorSpace::Luma, 10, 10);
+        AutoExposure::new().execute(&mut image).unwrap();
+        assert_eq!(image.flatten_to_u8()[0][0], 16);
+    }
+
+    #[test]
+    fn test_meters_regions_of_interest() {
+        // a dark face on a bright background
+        let mut image = Image::from_fn::<u8, _>(20, 20, ColorSpace::Luma, |y, x, pix| {
+            let face = (5..10).contains(&x) && (5..10).contains(&y);
+            pix[0] = if face { 40 } else { 200 };
+        });
+        assert!(AutoExposure::new().gain(&image).unwrap() < 1.0);
+
+        image
+            .metadata_mut()
+            .set_regions_of_interest(vec![RegionOfInterest::new(5, 5, 5, 5)]);
+        let gain = AutoExposure::new().gain(&image).unwrap();
+        assert!((gain - 0.46 * 255.0 / 40.0).abs() < 0.01, "{gain}");
+    }
+}
@@ -0,0 +1,423 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Preprocessing for barcode, QR code and OCR readers
+//!
+//! Readers work best on clean, level black and white images, this preset
+//! produces one from a photo or scan in a single operation so document
+//! pipelines can share the same settings.
+//!
+//! # Steps
+//! 1. **Grayscale**: The image is converted to Luma, alpha is dropped.
+//! 2. **Adaptive threshold**: A pixel becomes black when it is darker than the mean of
+//!    the window around it by more than an offset, otherwise white. Unlike a global
+//!    threshold this copes with shadows and uneven lighting.
+//! 3. **Deskew**: The skew is the angle at which the rows and columns of black pixels
+//!    line up best, i.e. where the projection profiles are sharpest, the image is
+//!    rotated back by it around its center, keeping its size.
+//! 4. **Despeckle**: Groups of connected black pixels no larger than a size are removed,
+//!    cleaning up noise without eroding thin bars the way a median filter would.
+//!
+//! The window should be larger than the largest dark area, e.g. QR finder patterns,
+//! or they come out hollow.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::barcode_preprocess::BarcodePreprocess;
+//!
+//! let mut image = Image::fill::<u8>(200, ColorSpace::RGB, 100, 100);
+//! BarcodePreprocess::new().set_radius(30).execute(&mut image).unwrap();
+//!
+//! assert_eq!(image.colorspace(), ColorSpace::Luma);
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::utils::{normalized_samples, store_samples};
+
+/// Angle between skews tried by the coarse search, in degrees
+const COARSE_STEP: f32 = 0.5;
+/// Angle between skews tried around the best coarse one, in degrees
+const FINE_STEP: f32 = 0.05;
+/// Skews smaller than this are left alone, in degrees
+const MIN_SKEW: f32 = 0.05;
+/// Black pixels used to estimate the skew, larger images are sampled
+const MAX_SKEW_SAMPLES: usize = 100_000;
+
+/// Turn an image into a level black and white image for barcode and OCR readers
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde-support", serde(default))]
+pub struct BarcodePreprocess {
+    radius:       usize,
+    offset:       f32,
+    max_skew:     f32,
+    speckle_size: usize
+}
+
+impl Default for BarcodePreprocess {
+    fn default() -> Self {
+        BarcodePreprocess::new()
+    }
+}
+
+impl BarcodePreprocess {
+    /// Create the preset with its default settings
+    ///
+    /// These are a threshold window radius of 20 pixels with an offset of 0.04,
+    /// skews of up to 15 degrees and speckles of up to 4 pixels
+    #[must_use]
+    pub fn new() -> BarcodePreprocess {
+        BarcodePreprocess {
+            radius:       20,
+            offset:       0.04,
+            max_skew:     15.0,
+            speckle_size: 4
+        }
+    }
+    /// Set the radius of the window each pixel is compared to, the window is
+    /// `2 * radius + 1` pixels wide
+    #[must_use]
+    pub fn set_radius(mut self, radius: usize) -> Self {
+        self.radius = radius.max(1);
+        self
+    }
+    /// Set how much darker than its surroundings a pixel has to be to become black,
+    /// as a fraction of the largest value, e.g. 0.04 is about 10 levels for 8 bit images
+    ///
+    /// Larger offsets keep faint noise and texture out
+    #[must_use]
+    pub fn set_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+    /// Set the largest skew to correct in either direction, in degrees,
+    /// zero disables deskewing
+    #[must_use]
+    pub fn set_max_skew(mut self, max_skew: f32) -> Self {
+        self.max_skew = max_skew.clamp(0.0, 45.0);
+        self
+    }
+    /// Set the size, in pixels, of the largest black speck to remove,
+    /// zero disables despeckling
+    #[must_use]
+    pub fn set_speckle_size(mut self, speckle_size: usize) -> Self {
+        self.speckle_size = speckle_size;
+        self
+    }
+}
+
+impl OperationsTrait for BarcodePreprocess {
+    fn name(&self) -> &'static str {
+        "Barcode Preprocess"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if image.colorspace() != ColorSpace::Luma {
+            image.convert_color(ColorSpace::Luma)?;
+        }
+
+        let (width, height) = image.dimensions();
+        let bit_type = image.depth().bit_type();
+
+        for frame in image.frames_mut() {
+            let channel = &mut frame.channels_vec()[0];
+            let luma = normalized_samples(channel, bit_type, self.name())?;
+
+            let mut black = adaptive_threshold(&luma, width, height, self.radius, self.offset);
+
+            if self.max_skew > 0.0 {
+                let skew = skew_angle(&black, width, height, self.max_skew);
+
+                if skew.abs() >= MIN_SKEW {
+                    black = rotate_binary(&black, width, height, skew);
+                }
+            }
+            if self.speckle_size > 0 {
+                despeckle(&mut black, width, height, self.speckle_size);
+            }
+            let values: Vec<f32> = black.iter().map(|&x| if x { 0.0 } else { 1.0 }).collect();
+            store_samples(channel, bit_type, self.name(), &values)?;
+        }
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Mark the pixels darker than the mean of the window around them by more than `offset`
+#[allow(clippy::cast_precision_loss)]
+fn adaptive_threshold(
+    luma: &[f32], width: usize, height: usize, radius: usize, offset: f32
+) -> Vec<bool> {
+    // summed area table with a row and column of zeros before the image
+    let stride = width + 1;
+    let mut sums = vec![0.0_f64; stride * (height + 1)];
+
+    for y in 0..height {
+        let mut row_sum = 0.0;
+
+        for x in 0..width {
+            row_sum += f64::from(luma[y * width + x]);
+            sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row_sum;
+        }
+    }
+    let mut black = vec![false; width * height];
+
+    for y in 0..height {
+        let (top, bottom) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+
+        for x in 0..width {
+            let (left, right) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+
+            let sum = sums[bottom * stride + right]
+                - sums[top * stride + right]
+                - sums[bottom * stride + left]
+                + sums[top * stride + left];
+            let mean = sum / ((bottom - top) * (right - left)) as f64;
+
+            black[y * width + x] = f64::from(luma[y * width + x]) < mean - f64::from(offset);
+        }
+    }
+    black
+}
+
+/// Return how sharp the row and column profiles of `points` are when they are
+/// rotated by `angle` degrees, higher is better
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn profile_score(points: &[(f32, f32)], width: usize, height: usize, angle: f32) -> f64 {
+    let tan = angle.to_radians().tan();
+    // a row through (x, y) at this angle starts at y - x * tan, at most width * |tan| away
+    let margin = (width.max(height) as f32 * tan.abs()).ceil() as isize + 1;
+    let size = (width.max(height) as isize + 2 * margin) as usize;
+
+    let mut rows = vec![0_u32; size];
+    let mut columns = vec![0_u32; size];
+
+    for &(x, y) in points {
+        let row = (y - x * tan).round() as isize + margin;
+        let column = (x + y * tan).round() as isize + margin;
+
+        rows[row.clamp(0, size as isize - 1) as usize] += 1;
+        columns[column.clamp(0, size as isize - 1) as usize] += 1;
+    }
+    rows.iter()
+        .chain(&columns)
+        .map(|&x| f64::from(x) * f64::from(x))
+        .sum()
+}
+
+/// Estimate the angle, in degrees, black pixels are rotated clockwise by
+///
+/// Lines of text and the edges of barcode bars are straightest at the angle
+/// where the projection profiles are sharpest
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn skew_angle(black: &[bool], width: usize, height: usize, max_skew: f32) -> f32 {
+    let count = black.iter().filter(|x| **x).count();
+
+    if count == 0 || width < 2 || height < 2 {
+        return 0.0;
+    }
+    let step = count.div_ceil(MAX_SKEW_SAMPLES);
+
+    let points: Vec<(f32, f32)> = black
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| **x)
+        .step_by(step)
+        .map(|(i, _)| ((i % width) as f32, (i / width) as f32))
+        .collect();
+
+    let best = |start: f32, end: f32, step: f32| {
+        let steps = ((end - start) / step).round() as i32;
+
+        (0..=steps)
+            .map(|i| start + i as f32 * step)
+            .map(|angle| (angle, profile_score(&points, width, height, angle)))
+            .fold((0.0_f32, f64::MIN), |best, current| {
+                // prefer the smaller angle on ties, e.g. for images without lines
+                if current.1 > best.1 || (current.1 >= best.1 && current.0.abs() < best.0.abs()) {
+                    current
+                } else {
+                    best
+                }
+            })
+            .0
+    };
+    let coarse = best(-max_skew, max_skew, COARSE_STEP);
+    let fine = best(coarse - COARSE_STEP, coarse + COARSE_STEP, FINE_STEP);
+
+    fine.clamp(-max_skew, max_skew)
+}
+
+/// Rotate a binary image counterclockwise by `angle` degrees around its center,
+/// pixels from outside the image are white
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn rotate_binary(black: &[bool], width: usize, height: usize, angle: f32) -> Vec<bool> {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (center_x, center_y) = ((width as f32 - 1.0) / 2.0, (height as f32 - 1.0) / 2.0);
+
+    let mut output = vec![false; width * height];
+
+    for (y, row) in output.chunks_exact_mut(width).enumerate() {
+        let dy = y as f32 - center_y;
+
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let dx = x as f32 - center_x;
+            let source_x = (center_x + dx * cos - dy * sin).round();
+            let source_y = (center_y + dx * sin + dy * cos).round();
+
+            if source_x >= 0.0
+                && source_y >= 0.0
+                && (source_x as usize) < width
+                && (source_y as usize) < height
+            {
+                *pixel = black[source_y as usize * width + source_x as usize];
+            }
+        }
+    }
+    output
+}
+
+/// Remove groups of 8-connected black pixels with at most `max_size` pixels
+fn despeckle(black: &mut [bool], width: usize, height: usize, max_size: usize) {
+    let mut visited = vec![false; black.len()];
+    let mut group = vec![];
+    let mut stack = vec![];
+
+    for start in 0..black.len() {
+        if !black[start] || visited[start] {
+            continue;
+        }
+        group.clear();
+        stack.push(start);
+        visited[start] = true;
+
+        while let Some(position) = stack.pop() {
+            group.push(position);
+
+            let (x, y) = (position % width, position / width);
+
+            for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                    let neighbour = ny * width + nx;
+
+                    if black[neighbour] && !visited[neighbour] {
+                        visited[neighbour] = true;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+        if group.len() <= max_size {
+            for &position in &group {
+                black[position] = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::barcode_preprocess::{despeckle, rotate_binary, skew_angle, BarcodePreprocess};
+
+    /// Black horizontal lines, 10 pixels apart, rotated clockwise by `angle` degrees
+    fn skewed_lines(width: usize, height: usize, angle: f32) -> Vec<bool> {
+        let mut lines = vec![false; width * height];
+
+        for y in (10..height - 10).step_by(10) {
+            lines[y * width + 10..y * width + width - 10].fill(true);
+        }
+        rotate_binary(&lines, width, height, -angle)
+    }
+
+    #[test]
+    fn test_skew_angle() {
+        for angle in [-7.0_f32, -2.5, 0.0, 4.0] {
+            let lines = skewed_lines(200, 150, angle);
+            let skew = skew_angle(&lines, 200, 150, 15.0);
+
+            assert!((skew - angle).abs() < 0.2, "expected {angle}, found {skew}");
+        }
+    }
+
+    #[test]
+    fn test_despeckle_keeps_thin_bars() {
+        let (width, height) = (20, 20);
+        let mut black = vec![false; width * height];
+        // a one pixel wide bar and two specks, one of them diagonal
+        for y in 2..18 {
+            black[y * width + 5] = true;
+        }
+        black[3 * width + 12] = true;
+        black[10 * width + 14] = true;
+        black[11 * width + 15] = true;
+
+        despeckle(&mut black, width, height, 4);
+
+        assert_eq!(black.iter().filter(|x| **x).count(), 16);
+        assert!(black[2 * width + 5] && black[17 * width + 5]);
+    }
+
+    #[test]
+    fn test_uneven_lighting_and_skew() {
+        let (width, height) = (200, 150);
+        let lines = skewed_lines(width, height, 3.0);
+
+        // dark lines on a background going from dark to light gray, darker than
+        // the lines on the left so no global threshold could separate them
+        let pixels: Vec<u8> = lines
+            .iter()
+            .enumerate()
+            .map(|(i, &black)| {
+                let background = 50 + u8::try_from(i % width / 2).unwrap();
+                if black {
+                    background - 40
+                } else {
+                    background
+                }
+            })
+            .collect();
+        let mut image = Image::from_u8(&pixels, width, height, ColorSpace::Luma);
+
+        BarcodePreprocess::new().execute(&mut image).unwrap();
+        let output = &image.flatten_to_u8()[0];
+
+        assert_eq!(image.colorspace(), ColorSpace::Luma);
+        assert!(output.iter().all(|&x| x == 0 || x == 255));
+
+        // the lines are level again, so they cover few rows near the center, skewed
+        // they would cover about 6 rows each
+        let rows = output
+            .chunks_exact(width)
+            .filter(|row| row[60..140].contains(&0))
+            .count();
+        let expected = (10..height - 10).step_by(10).count();
+        assert!(rows <= expected * 3, "{rows} rows have black pixels");
+    }
+}
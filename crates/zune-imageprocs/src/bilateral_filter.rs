@@ -41,6 +41,7 @@ use crate::utils::execute_on;
 /// filter.execute(&mut image)?;
 /// # Ok::<(),ImageErrors>(())
 /// ```
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct BilateralFilter {
     d:           i32,
     sigma_color: f32,
@@ -0,0 +1,333 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Draw a border around an image
+//!
+//! The canvas is extended by the border thickness on every side, and by the extent of
+//! the drop shadow if one is set. The border can be
+//! - a solid color, or
+//! - a gradient going from one color at the image edge to another at the outer edge
+//!
+//! and have rounded outer corners. Areas of the canvas not covered by the frame, e.g. the
+//! rounded corners and around the shadow, get the background color, which is transparent
+//! white by default. Images without alpha can't be transparent, so there the background is
+//! opaque, convert to RGBA first for transparent corners.
+//!
+//! # Example
+//! - Add a 10 pixel white frame with rounded corners and a soft shadow
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::border::{Border, BorderFill, DropShadow};
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::RGBA, 100, 100);
+//! Border::new(10, BorderFill::Solid([255, 255, 255, 255]))
+//!     .set_radius(12.0)
+//!     .set_shadow(Some(DropShadow::new(4, 4, 3.0, [0, 0, 0, 128])))
+//!     .execute(&mut image)
+//!     .unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::gaussian_blur::gaussian_blur_f32;
+use crate::utils::{
+    composite_over, fill_color, normalized_samples, rounded_rect_coverage, to_channels
+};
+
+/// How a border is filled
+///
+/// Colors are 8 bit RGBA, scaled to the bit depth of the image
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub enum BorderFill {
+    /// A single color
+    Solid([u8; 4]),
+    /// A gradient from `inner` at the edge of the image to `outer` at the outer edge
+    Gradient { inner: [u8; 4], outer: [u8; 4] }
+}
+
+/// A shadow cast by the framed image on the background
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct DropShadow {
+    /// Horizontal offset of the shadow in pixels, positive values move it right
+    pub offset_x: isize,
+    /// Vertical offset of the shadow in pixels, positive values move it down
+    pub offset_y: isize,
+    /// Sigma of the gaussian blur softening the shadow
+    pub blur:     f32,
+    /// 8 bit RGBA color of the shadow, its alpha sets the opacity
+    pub color:    [u8; 4]
+}
+
+impl DropShadow {
+    /// Create a new drop shadow
+    #[must_use]
+    pub const fn new(offset_x: isize, offset_y: isize, blur: f32, color: [u8; 4]) -> DropShadow {
+        DropShadow {
+            offset_x,
+            offset_y,
+            blur,
+            color
+        }
+    }
+}
+
+/// Draw a border around an image
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Border {
+    thickness:  usize,
+    fill:       BorderFill,
+    radius:     f32,
+    shadow:     Option<DropShadow>,
+    background: [u8; 4]
+}
+
+impl Border {
+    /// Create a new border operation
+    ///
+    /// # Arguments
+    /// - thickness: Width of the border in pixels
+    /// - fill: How the border is filled
+    ///
+    /// The border has square corners and no shadow by default
+    #[must_use]
+    pub fn new(thickness: usize, fill: BorderFill) -> Border {
+        Border {
+            thickness,
+            fill,
+            radius: 0.0,
+            shadow: None,
+            background: [255, 255, 255, 0]
+        }
+    }
+    /// Set the radius of the outer corners of the border in pixels
+    #[must_use]
+    pub fn set_radius(mut self, radius: f32) -> Self {
+        self.radius = radius.max(0.0);
+        self
+    }
+    /// Set the drop shadow of the frame, or `None` to remove it
+    #[must_use]
+    pub fn set_shadow(mut self, shadow: Option<DropShadow>) -> Self {
+        self.shadow = shadow;
+        self
+    }
+    /// Set the 8 bit RGBA color of the canvas outside the frame
+    #[must_use]
+    pub fn set_background(mut self, background: [u8; 4]) -> Self {
+        self.background = background;
+        self
+    }
+}
+
+impl OperationsTrait for Border {
+    fn name(&self) -> &'static str {
+        "Border"
+    }
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss
+    )]
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let colorspace = image.colorspace();
+        let bit_type = image.depth().bit_type();
+        let has_alpha = colorspace.has_alpha();
+        let components = colorspace.num_components();
+        let thickness = self.thickness;
+
+        let (frame_width, frame_height) = (width + 2 * thickness, height + 2 * thickness);
+
+        // room around the frame for the shadow, three sigmas of blur cover it
+        let (offset_x, offset_y, grow) = self.shadow.map_or((0, 0, 0), |s| {
+            (
+                s.offset_x,
+                s.offset_y,
+                (s.blur.max(0.0) * 3.0).ceil() as isize
+            )
+        });
+        let left = (grow - offset_x).max(0).unsigned_abs();
+        let top = (grow - offset_y).max(0).unsigned_abs();
+        let canvas_width = left + frame_width + (grow + offset_x).max(0).unsigned_abs();
+        let canvas_height = top + frame_height + (grow + offset_y).max(0).unsigned_abs();
+
+        let frame_rect = (
+            left as f32,
+            top as f32,
+            (left + frame_width) as f32,
+            (top + frame_height) as f32
+        );
+        let center = |x: usize, y: usize| (x as f32 + 0.5, y as f32 + 0.5);
+
+        let shadow = self.shadow.map(|shadow| {
+            let (dx, dy) = (shadow.offset_x as f32, shadow.offset_y as f32);
+            let shadow_rect = (
+                frame_rect.0 + dx,
+                frame_rect.1 + dy,
+                frame_rect.2 + dx,
+                frame_rect.3 + dy
+            );
+            let mut mask: Vec<f32> = (0..canvas_height)
+                .flat_map(|y| (0..canvas_width).map(move |x| (x, y)))
+                .map(|(x, y)| rounded_rect_coverage(center(x, y), shadow_rect, self.radius))
+                .collect();
+            if shadow.blur > 0.0 {
+                let mut scratch = vec![0.0; mask.len()];
+                gaussian_blur_f32(
+                    &mut mask,
+                    &mut scratch,
+                    canvas_width,
+                    canvas_height,
+                    shadow.blur
+                );
+            }
+            (mask, fill_color(shadow.color, colorspace))
+        });
+        let background = fill_color(self.background, colorspace);
+        let (inner, outer) = match self.fill {
+            BorderFill::Solid(color) => (color, color),
+            BorderFill::Gradient { inner, outer } => (inner, outer)
+        };
+        let (inner, outer) = (fill_color(inner, colorspace), fill_color(outer, colorspace));
+
+        // bounds of the image on the canvas
+        let (image_left, image_top) = (left + thickness, top + thickness);
+        let (image_right, image_bottom) = (image_left + width, image_top + height);
+
+        for frame in image.frames_mut() {
+            let planes = frame
+                .channels_ref(colorspace, false)
+                .iter()
+                .map(|c| normalized_samples(c, bit_type, self.name()))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut pixels = vec![0.0; canvas_width * canvas_height * components];
+            let mut color = vec![0.0; components];
+
+            for (i, out) in pixels.chunks_exact_mut(components).enumerate() {
+                let (x, y) = (i % canvas_width, i / canvas_width);
+                out.copy_from_slice(&background);
+
+                if let Some((mask, shadow_color)) = &shadow {
+                    composite_over(out, shadow_color, mask[i], has_alpha);
+                }
+                let coverage = rounded_rect_coverage(center(x, y), frame_rect, self.radius);
+                if coverage <= 0.0 {
+                    continue;
+                }
+                let dx = image_left
+                    .saturating_sub(x)
+                    .max((x + 1).saturating_sub(image_right));
+                let dy = image_top
+                    .saturating_sub(y)
+                    .max((y + 1).saturating_sub(image_bottom));
+
+                if dx == 0 && dy == 0 {
+                    let index = (y - image_top) * width + (x - image_left);
+                    for (c, plane) in color.iter_mut().zip(&planes) {
+                        *c = plane[index];
+                    }
+                } else {
+                    // 0.0 next to the image, 1.0 at the outer edge
+                    let position = (dx.max(dy) as f32 - 0.5) / thickness as f32;
+                    for ((c, inner), outer) in color.iter_mut().zip(&inner).zip(&outer) {
+                        *c = inner + (outer - inner) * position;
+                    }
+                }
+                composite_over(out, &color, coverage, has_alpha);
+            }
+            frame.set_channels(to_channels(&pixels, bit_type, colorspace));
+        }
+        image.set_dimensions(canvas_width, canvas_height);
+
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::LumaA,
+            ColorSpace::Luma
+        ]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::border::{Border, BorderFill, DropShadow};
+
+    #[test]
+    fn test_solid_and_gradient_border() {
+        let mut image = Image::fill::<u8>(10, ColorSpace::Luma, 2, 2);
+        Border::new(1, BorderFill::Solid([200, 200, 200, 255]))
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.dimensions(), (4, 4));
+        assert_eq!(
+            image.flatten_to_u8()[0],
+            [200, 200, 200, 200, 200, 10, 10, 200, 200, 10, 10, 200, 200, 200, 200, 200]
+        );
+
+        let mut image = Image::fill::<u8>(10, ColorSpace::Luma, 2, 2);
+        let gradient = BorderFill::Gradient {
+            inner: [0, 0, 0, 255],
+            outer: [255, 255, 255, 255]
+        };
+        Border::new(4, gradient).execute(&mut image).unwrap();
+        let pixels = &image.flatten_to_u8()[0];
+        // the row through the image, from the outer edge to the image
+        assert_eq!(pixels[4 * 10..4 * 10 + 5], [223, 159, 96, 32, 10]);
+    }
+
+    #[test]
+    fn test_rounded_corners_and_shadow() {
+        let mut image = Image::from_fn::<u8, _>(20, 20, ColorSpace::RGBA, |_, _, pix| {
+            *pix = [100, 100, 100, 255];
+        });
+        Border::new(5, BorderFill::Solid([255, 0, 0, 255]))
+            .set_radius(8.0)
+            .set_shadow(Some(DropShadow::new(3, 3, 1.0, [0, 0, 0, 255])))
+            .execute(&mut image)
+            .unwrap();
+        // 30x30 frame, the shadow needs 3 pixels of blur and 3 of offset
+        assert_eq!(image.dimensions(), (36, 36));
+
+        let pixels = &image.flatten_to_u8()[0];
+        let alpha = |x: usize, y: usize| pixels[(y * 36 + x) * 4 + 3];
+        let red = |x: usize, y: usize| pixels[(y * 36 + x) * 4];
+
+        // the top left corner of the frame is cut, its edge and the image are opaque
+        assert_eq!(alpha(0, 0), 0);
+        assert_eq!(alpha(1, 1), 0);
+        assert_eq!((alpha(2, 15), red(2, 15)), (255, 255));
+        assert_eq!(red(15, 15), 100);
+        // the shadow shows on the right of the frame, below its top edge
+        assert!(alpha(31, 20) > 200, "{}", alpha(31, 20));
+        assert_eq!(red(31, 20), 0);
+        assert_eq!(alpha(34, 1), 0);
+    }
+}
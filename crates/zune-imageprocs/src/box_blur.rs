@@ -32,6 +32,7 @@ use crate::transpose;
 ///
 /// This operation is multithreaded capable
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct BoxBlur {
     radius: usize
 }
@@ -219,11 +220,13 @@ where
     // where a is sum of chunk[0..r], (first of the array), we can keep updating a during the loop
     // and we have a window sum!
 
-    if width <= 1 || diameter <= 1 {
-        // repeated here for the optimizer
+    if width <= 2 || diameter <= 1 {
+        // nothing to blur, callers still expect the result in out_image
+        out_image.copy_from_slice(in_image);
         return;
     }
-    let diameter = diameter.min(width);
+    // the window has to be odd, an even one leaves a pixel unwritten
+    let diameter = diameter.min(width - 1 + width % 2);
     let m_radius = compute_mod_u32(diameter as u64);
 
     for (stride_in, stride_out) in in_image
@@ -236,9 +239,11 @@ where
 
         accumulator += ((half_radius - 1) as u32) * u32::from(stride_in[0]);
 
+        stride_out[0] = T::from_u32(fastdiv_u32(accumulator, m_radius));
+
         for (data_in, data_out) in stride_in[half_radius..]
             .iter()
-            .zip(stride_out.iter_mut())
+            .zip(stride_out[1..].iter_mut())
             .take(half_radius - 1)
         {
             accumulator += u32::from(*data_in);
@@ -261,7 +266,7 @@ where
 
         for (window_in, data_out) in stride_in[1..]
             .windows(diameter)
-            .zip(stride_out[half_radius - 1..].iter_mut())
+            .zip(stride_out[half_radius..].iter_mut())
         {
             accumulator -= window_slide;
             accumulator += u32::from(*window_in.last().unwrap());
@@ -278,15 +283,15 @@ where
             *data_out = T::from_u32(fastdiv_u32(accumulator, m_radius));
         }
 
+        // the right edge drops the start of the window and repeats the last pixel
         let edge_len = stride_out.len() - half_radius;
 
-        let end_stride = &mut stride_out[edge_len..];
+        let end_stride = &mut stride_out[edge_len + 1..];
         let last_item = u32::from(*stride_in.last().unwrap());
 
-        for (data_in, data_out) in stride_in[edge_len..]
+        for (data_in, data_out) in stride_in[stride_in.len() - diameter..]
             .iter()
             .zip(end_stride)
-            .take(half_radius)
         {
             accumulator = accumulator.wrapping_sub(u32::from(*data_in));
             accumulator = accumulator.wrapping_add(last_item);
@@ -305,11 +310,13 @@ pub(crate) fn box_blur_f32_inner(
 ) {
     let diameter = (radius * 2) + 1;
 
-    if width <= 1 || diameter <= 1 {
-        // repeated here for the optimizer
+    if width <= 2 || diameter <= 1 {
+        // nothing to blur, callers still expect the result in out_image
+        out_image.copy_from_slice(in_image);
         return;
     }
-    let diameter = diameter.min(width);
+    // the window has to be odd, an even one leaves a pixel unwritten
+    let diameter = diameter.min(width - 1 + width % 2);
     let recip = 1.0 / diameter as f32;
 
     for (stride_in, stride_out) in in_image
@@ -322,9 +329,11 @@ pub(crate) fn box_blur_f32_inner(
 
         accumulator += (half_radius - 1) as f32 * stride_in[0];
 
+        stride_out[0] = accumulator * recip;
+
         for (data_in, data_out) in stride_in[half_radius..]
             .iter()
-            .zip(stride_out.iter_mut())
+            .zip(stride_out[1..].iter_mut())
             .take(half_radius - 1)
         {
             accumulator += *data_in;
@@ -337,7 +346,7 @@ pub(crate) fn box_blur_f32_inner(
 
         for (window_in, data_out) in stride_in[1..]
             .windows(diameter)
-            .zip(stride_out[half_radius - 1..].iter_mut())
+            .zip(stride_out[half_radius..].iter_mut())
         {
             accumulator -= window_slide;
             accumulator += *window_in.last().unwrap();
@@ -347,15 +356,15 @@ pub(crate) fn box_blur_f32_inner(
             *data_out = accumulator * recip;
         }
 
+        // the right edge drops the start of the window and repeats the last pixel
         let edge_len = stride_out.len() - half_radius;
 
-        let end_stride = &mut stride_out[edge_len..];
+        let end_stride = &mut stride_out[edge_len + 1..];
         let last_item = *stride_in.last().unwrap();
 
-        for (data_in, data_out) in stride_in[edge_len..]
+        for (data_in, data_out) in stride_in[stride_in.len() - diameter..]
             .iter()
             .zip(end_stride)
-            .take(half_radius)
         {
             accumulator -= *data_in;
             accumulator += last_item;
@@ -365,6 +374,48 @@ pub(crate) fn box_blur_f32_inner(
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use crate::gaussian_blur::gaussian_blur_f32;
+
+    #[test]
+    fn test_blur_small_images() {
+        // windows as large as the image, and images narrower than a transpose tile
+        for (width, height) in [(1, 1), (2, 5), (3, 13), (10, 10), (20, 6)] {
+            for sigma in [1.0, 3.0, 30.0] {
+                let mut pixels = vec![0.5_f32; width * height];
+                let mut scratch = vec![0.0; width * height];
+                gaussian_blur_f32(&mut pixels, &mut scratch, width, height, sigma);
+
+                assert!(
+                    pixels.iter().all(|x| (x - 0.5).abs() < 1e-4),
+                    "{width}x{height} {sigma}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_blur_is_centered() {
+        // a single bright pixel spreads evenly around itself, and not much further than sigma
+        let (width, height) = (15, 11);
+        let mut pixels = vec![0.0_f32; width * height];
+        let mut scratch = vec![0.0; width * height];
+        pixels[5 * width + 7] = 1.0;
+        gaussian_blur_f32(&mut pixels, &mut scratch, width, height, 1.0);
+
+        let pixel = |x: usize, y: usize| pixels[y * width + x];
+        assert!((pixels.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+        assert!(pixels.iter().all(|x| *x <= pixel(7, 5)));
+        for d in 1..5 {
+            assert!((pixel(7 - d, 5) - pixel(7 + d, 5)).abs() < 1e-6);
+            assert!((pixel(7, 5 - d) - pixel(7, 5 + d)).abs() < 1e-6);
+        }
+        assert!(pixel(0, 5).abs() < 1e-6);
+        assert!(pixel(7, 0).abs() < 1e-6);
+    }
+}
+
 #[cfg(feature = "benchmarks")]
 #[cfg(test)]
 mod benchmarks {
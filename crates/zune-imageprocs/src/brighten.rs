@@ -53,6 +53,7 @@ use crate::utils::execute_on;
 /// # Ok::<(),ImageErrors>(())
 /// ```
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Brighten {
     value: f32
 }
@@ -0,0 +1,381 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Change the canvas size of an image
+//!
+//! - [`ExtendCanvas`] places the image on a canvas of a new size at a chosen position,
+//!   filling the new area with a color or by extending the edges of the image
+//! - [`Tile`] repeats the image until it fills a canvas of a new size
+//!
+//! Unlike [`Resize`](crate::resize::Resize) the pixels of the image are never scaled.
+//!
+//! # Example
+//! - Place a 100x80 image at the center of a 200x200 white canvas
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::canvas::{CanvasFill, ExtendCanvas};
+//! use zune_imageprocs::Gravity;
+//!
+//! let mut image = Image::fill::<u8>(0, ColorSpace::RGB, 100, 80);
+//! ExtendCanvas::new(200, 200, CanvasFill::Color([255, 255, 255, 255]))
+//!     .set_gravity(Gravity::Center)
+//!     .execute(&mut image)
+//!     .unwrap();
+//! assert_eq!(image.dimensions(), (200, 200));
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::{ColorSpace, ALL_COLORSPACES};
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::{fill_color, gravity_offset, to_sample, Gravity};
+
+/// How the area of a canvas not covered by the image is filled
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub enum CanvasFill {
+    /// Fill with an 8 bit RGBA color, scaled to the bit depth of the image
+    ///
+    /// Grayscale images use the luma of the color, images without alpha ignore it
+    Color([u8; 4]),
+    /// Repeat the pixels at the edges of the image outwards
+    Replicate
+}
+
+/// Extend (or shrink) the canvas of an image
+///
+/// When the canvas is smaller than the image in a direction, the image is cut
+/// to the canvas with the same alignment.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtendCanvas {
+    width:   usize,
+    height:  usize,
+    fill:    CanvasFill,
+    gravity: Gravity
+}
+
+impl ExtendCanvas {
+    /// Create a new operation changing the canvas to `width` x `height`
+    ///
+    /// The image is placed at the top left corner by default
+    #[must_use]
+    pub fn new(width: usize, height: usize, fill: CanvasFill) -> ExtendCanvas {
+        ExtendCanvas {
+            width,
+            height,
+            fill,
+            gravity: Gravity::TopLeft
+        }
+    }
+    /// Set where the image is placed on the canvas
+    #[must_use]
+    pub fn set_gravity(mut self, gravity: Gravity) -> Self {
+        self.gravity = gravity;
+        self
+    }
+}
+
+impl OperationsTrait for ExtendCanvas {
+    fn name(&self) -> &'static str {
+        "Extend Canvas"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let src_dims = image.dimensions();
+        let dst_dims = (self.width, self.height);
+        let offset = gravity_offset(src_dims, dst_dims, self.gravity);
+
+        let colors = match self.fill {
+            CanvasFill::Color(color) => Some(fill_color(color, image.colorspace())),
+            CanvasFill::Replicate => None
+        };
+        remap_channels(image, dst_dims, self.name(), |src, dst, index, depth| {
+            let fill = colors.as_ref().map(|x| x[index]);
+
+            match depth {
+                BitType::U8 => extend::<u8>(
+                    src.reinterpret_as()?,
+                    dst.reinterpret_as_mut()?,
+                    src_dims,
+                    self.width,
+                    offset,
+                    fill
+                ),
+                BitType::U16 => extend::<u16>(
+                    src.reinterpret_as()?,
+                    dst.reinterpret_as_mut()?,
+                    src_dims,
+                    self.width,
+                    offset,
+                    fill
+                ),
+                BitType::F32 => extend::<f32>(
+                    src.reinterpret_as()?,
+                    dst.reinterpret_as_mut()?,
+                    src_dims,
+                    self.width,
+                    offset,
+                    fill
+                ),
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+            Ok(())
+        })
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        match self.fill {
+            CanvasFill::Color(_) => &[
+                ColorSpace::RGB,
+                ColorSpace::RGBA,
+                ColorSpace::LumaA,
+                ColorSpace::Luma
+            ],
+            CanvasFill::Replicate => &ALL_COLORSPACES
+        }
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Repeat an image to fill a canvas
+///
+/// Copies start at the top left corner, the ones on the right and bottom edges
+/// are cut when the canvas isn't a multiple of the image size.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::canvas::Tile;
+///
+/// let mut image = Image::fill::<u8>(0, ColorSpace::RGB, 16, 16);
+/// Tile::new(100, 50).execute(&mut image).unwrap();
+/// assert_eq!(image.dimensions(), (100, 50));
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tile {
+    width:  usize,
+    height: usize
+}
+
+impl Tile {
+    /// Create a new operation tiling the image over a `width` x `height` canvas
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Tile {
+        Tile { width, height }
+    }
+}
+
+impl OperationsTrait for Tile {
+    fn name(&self) -> &'static str {
+        "Tile"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let src_dims = image.dimensions();
+        let dst_dims = (self.width, self.height);
+
+        remap_channels(image, dst_dims, self.name(), |src, dst, _, depth| {
+            match depth {
+                BitType::U8 => {
+                    tile::<u8>(
+                        src.reinterpret_as()?,
+                        dst.reinterpret_as_mut()?,
+                        self.width,
+                        src_dims
+                    );
+                }
+                BitType::U16 => {
+                    tile::<u16>(
+                        src.reinterpret_as()?,
+                        dst.reinterpret_as_mut()?,
+                        self.width,
+                        src_dims
+                    );
+                }
+                BitType::F32 => {
+                    tile::<f32>(
+                        src.reinterpret_as()?,
+                        dst.reinterpret_as_mut()?,
+                        self.width,
+                        src_dims
+                    );
+                }
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+            Ok(())
+        })
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Replace every channel of `image` with a `width` x `height` one filled by `function`,
+/// which takes the old channel, the new one, the index of the channel in the frame and
+/// the bit type
+fn remap_channels<F>(
+    image: &mut Image, (width, height): (usize, usize), name: &'static str, function: F
+) -> Result<(), ImageErrors>
+where
+    F: Fn(&Channel, &mut Channel, usize, BitType) -> Result<(), ImageErrors>
+{
+    let (src_width, src_height) = image.dimensions();
+    if width == 0 || height == 0 || src_width == 0 || src_height == 0 {
+        return Err(ImageErrors::GenericString(format!(
+            "{name}: image and canvas dimensions should not be zero"
+        )));
+    }
+    let depth = image.depth();
+    let length = width * height * depth.size_of();
+
+    for frame in image.frames_mut() {
+        for (index, channel) in frame.channels_vec().iter_mut().enumerate() {
+            let mut new_channel = Channel::new_with_length_and_type(length, channel.type_id());
+            function(channel, &mut new_channel, index, depth.bit_type())?;
+            *channel = new_channel;
+        }
+    }
+    image.set_dimensions(width, height);
+
+    Ok(())
+}
+
+fn extend<T>(
+    src: &[T], dst: &mut [T], (src_width, src_height): (usize, usize), dst_width: usize,
+    (offset_x, offset_y): (isize, isize), fill: Option<f32>
+) where
+    T: Copy + Default + NumOps<T>
+{
+    let fill = fill.map(|x| to_sample::<T>(x * T::max_val().to_f32()));
+
+    // source index of every column and row, None outside the image
+    let source = |position: usize, offset: isize, length: usize| {
+        let position = position.cast_signed() - offset;
+        if (0..length.cast_signed()).contains(&position) || fill.is_none() {
+            Some(position.clamp(0, length.cast_signed() - 1).unsigned_abs())
+        } else {
+            None
+        }
+    };
+    let columns: Vec<Option<usize>> = (0..dst_width)
+        .map(|x| source(x, offset_x, src_width))
+        .collect();
+
+    for (y, row) in dst.chunks_exact_mut(dst_width).enumerate() {
+        let src_row =
+            source(y, offset_y, src_height).map(|y| &src[y * src_width..(y + 1) * src_width]);
+
+        for (out, column) in row.iter_mut().zip(&columns) {
+            *out = match (src_row, column) {
+                (Some(src_row), Some(x)) => src_row[*x],
+                // only reachable with a fill color
+                _ => fill.unwrap_or_default()
+            };
+        }
+    }
+}
+
+fn tile<T: Copy>(
+    src: &[T], dst: &mut [T], dst_width: usize, (src_width, src_height): (usize, usize)
+) {
+    for (y, row) in dst.chunks_exact_mut(dst_width).enumerate() {
+        let src_row = &src[(y % src_height) * src_width..][..src_width];
+
+        for (out, x) in row.iter_mut().zip((0..src_width).cycle()) {
+            *out = src_row[x];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::canvas::{CanvasFill, ExtendCanvas, Tile};
+    use crate::utils::Gravity;
+
+    fn small() -> Image {
+        // 1 2
+        // 3 4
+        Image::from_fn::<u8, _>(2, 2, ColorSpace::Luma, |y, x, pix| {
+            pix[0] = [[1, 2], [3, 4]][y][x];
+        })
+    }
+
+    #[test]
+    fn test_extend_canvas_gravity() {
+        let mut image = small();
+        ExtendCanvas::new(4, 3, CanvasFill::Color([0, 0, 0, 0]))
+            .set_gravity(Gravity::BottomRight)
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.dimensions(), (4, 3));
+        assert_eq!(
+            image.flatten_to_u8()[0],
+            [0, 0, 0, 0, 0, 0, 1, 2, 0, 0, 3, 4]
+        );
+
+        let mut image = small();
+        ExtendCanvas::new(4, 4, CanvasFill::Replicate)
+            .set_gravity(Gravity::Center)
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(
+            image.flatten_to_u8()[0],
+            [1, 1, 2, 2, 1, 1, 2, 2, 3, 3, 4, 4, 3, 3, 4, 4]
+        );
+
+        // smaller canvases cut the image
+        let mut image = small();
+        ExtendCanvas::new(1, 2, CanvasFill::Replicate)
+            .set_gravity(Gravity::Right)
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.flatten_to_u8()[0], [2, 4]);
+    }
+
+    #[test]
+    fn test_extend_canvas_fill_color() {
+        let mut image = Image::fill::<u16>(0, ColorSpace::RGBA, 1, 1);
+        ExtendCanvas::new(2, 1, CanvasFill::Color([255, 0, 255, 128]))
+            .execute(&mut image)
+            .unwrap();
+        let channels = image.frames_ref()[0].channels_ref(ColorSpace::RGBA, false);
+        let right: Vec<u16> = channels
+            .iter()
+            .map(|x| x.reinterpret_as::<u16>().unwrap()[1])
+            .collect();
+        assert_eq!(right, [65535, 0, 65535, 32896]);
+    }
+
+    #[test]
+    fn test_tile() {
+        let mut image = small();
+        Tile::new(5, 3).execute(&mut image).unwrap();
+        assert_eq!(
+            image.flatten_to_u8()[0],
+            [1, 2, 1, 2, 1, 3, 4, 3, 4, 3, 1, 2, 1, 2, 1]
+        );
+        assert!(Tile::new(0, 3).execute(&mut small()).is_err());
+    }
+}
@@ -0,0 +1,293 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Remove a solid color background
+//!
+//! Chroma keying makes pixels close to a key color transparent, e.g. the green or
+//! blue screen of a studio or the plain backdrop of product photos.
+//!
+//! Colors are compared by their chroma, which ignores the luma of a color, so
+//! light shadows and uneven lighting on the backdrop are removed as well. Light reflected from the backdrop
+//! tints the edges of the foreground, spill suppression removes that tint.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::chroma_key::ChromaKey;
+//!
+//! // a red square in front of a green screen
+//! let mut image = Image::from_fn::<u8, _>(100, 100, ColorSpace::RGB, |y, x, pix| {
+//!     let inside = (30..70).contains(&x) && (30..70).contains(&y);
+//!     pix[..3].copy_from_slice(if inside { &[200, 30, 30] } else { &[20, 180, 40] });
+//! });
+//! ChromaKey::new([0, 177, 64]).execute(&mut image).unwrap();
+//!
+//! let pixels = &image.flatten_to_u8()[0];
+//! assert_eq!(image.colorspace(), ColorSpace::RGBA);
+//! assert_eq!(pixels[3], 0);
+//! assert_eq!(pixels[(50 * 100 + 50) * 4 + 3], 255);
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::matting::{estimate_alpha, map_rgba, Region};
+
+/// Make pixels close to a key color transparent
+///
+/// Pixels whose chroma is within `threshold` of the key are transparent,
+/// alpha then increases linearly up to fully opaque at `threshold + softness`.
+/// Distances are between chroma coordinates, where the largest possible
+/// distance, between the most saturated colors, is about 0.8.
+///
+/// The image is converted to RGBA, an existing alpha channel
+/// is multiplied with the computed one.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChromaKey {
+    key:           [u8; 3],
+    threshold:     f32,
+    softness:      f32,
+    spill:         f32,
+    refine_radius: usize
+}
+
+impl ChromaKey {
+    /// Create a new chroma key removing the 8 bit RGB color `key`
+    ///
+    /// The defaults are a threshold of 0.1, a softness of 0.1, a spill
+    /// suppression of 0.5 and no edge refinement
+    #[must_use]
+    pub fn new(key: [u8; 3]) -> ChromaKey {
+        ChromaKey {
+            key,
+            threshold: 0.1,
+            softness: 0.1,
+            spill: 0.5,
+            refine_radius: 0
+        }
+    }
+    /// Set the chroma distance from the key below which pixels are fully transparent
+    #[must_use]
+    pub fn set_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold.max(0.0);
+        self
+    }
+    /// Set the width of the range of chroma distances in which
+    /// pixels become opaque, 0.0 gives hard edges
+    #[must_use]
+    pub fn set_softness(mut self, softness: f32) -> Self {
+        self.softness = softness.max(0.0);
+        self
+    }
+    /// Set how much of the key color is removed from the remaining pixels,
+    /// between 0.0, which keeps colors, and 1.0, which removes all of it
+    #[must_use]
+    pub fn set_spill_suppression(mut self, spill: f32) -> Self {
+        self.spill = spill.clamp(0.0, 1.0);
+        self
+    }
+    /// Refine the alpha of partially transparent pixels with [matting](crate::matting),
+    /// using the foreground and background colors within `radius`
+    ///
+    /// This recovers smooth edges when the foreground contains colors close to the
+    /// key, at the cost of speed. 0 disables refinement, which is the default
+    #[must_use]
+    pub fn set_refine_radius(mut self, radius: usize) -> Self {
+        self.refine_radius = radius;
+        self
+    }
+}
+
+impl OperationsTrait for ChromaKey {
+    fn name(&self) -> &'static str {
+        "Chroma Key"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let key = chroma(self.key.map(|x| f32::from(x) / 255.0));
+        let key_length = key[0].hypot(key[1]);
+        // spill is removed along the direction of the key, gray keys have none
+        let direction = if key_length > 1e-3 {
+            Some([key[0] / key_length, key[1] / key_length])
+        } else {
+            None
+        };
+
+        map_rgba(image, self.name(), |[r, g, b], alpha| {
+            let mut key_alpha = vec![0.0_f32; alpha.len()];
+
+            for (i, key_alpha) in key_alpha.iter_mut().enumerate() {
+                let [cb, cr] = chroma([r[i], g[i], b[i]]);
+                let distance = (cb - key[0]).hypot(cr - key[1]);
+
+                *key_alpha = if distance <= self.threshold {
+                    0.0
+                } else if distance >= self.threshold + self.softness {
+                    1.0
+                } else {
+                    (distance - self.threshold) / self.softness
+                };
+            }
+            if self.refine_radius > 0 {
+                let regions: Vec<Region> = key_alpha
+                    .iter()
+                    .map(|x| match *x {
+                        x if x <= 0.0 => Region::Background,
+                        x if x >= 1.0 => Region::Foreground,
+                        _ => Region::Unknown
+                    })
+                    .collect();
+                key_alpha = estimate_alpha([r, g, b], &regions, width, height, self.refine_radius);
+            }
+            for (i, key_alpha) in key_alpha.iter().enumerate() {
+                alpha[i] *= key_alpha;
+
+                if let Some(direction) = direction {
+                    if *key_alpha > 0.0 && self.spill > 0.0 {
+                        [r[i], g[i], b[i]] =
+                            suppress_spill([r[i], g[i], b[i]], direction, self.spill);
+                    }
+                }
+            }
+        })
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Return the chroma, the Cb and Cr components of BT.601 YCbCr, of a color
+fn chroma([r, g, b]: [f32; 3]) -> [f32; 2] {
+    [
+        -0.168_736 * r - 0.331_264 * g + 0.5 * b,
+        0.5 * r - 0.418_688 * g - 0.081_312 * b
+    ]
+}
+
+/// Remove `amount` of the chroma of `color` along `direction`, keeping its luma
+fn suppress_spill([r, g, b]: [f32; 3], direction: [f32; 2], amount: f32) -> [f32; 3] {
+    let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+    let [mut cb, mut cr] = chroma([r, g, b]);
+    let spill = cb * direction[0] + cr * direction[1];
+
+    if spill <= 0.0 {
+        return [r, g, b];
+    }
+    cb -= amount * spill * direction[0];
+    cr -= amount * spill * direction[1];
+
+    [
+        (luma + 1.402 * cr).clamp(0.0, 1.0),
+        (luma - 0.344_136 * cb - 0.714_136 * cr).clamp(0.0, 1.0),
+        (luma + 1.772 * cb).clamp(0.0, 1.0)
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::chroma_key::ChromaKey;
+
+    const GREEN: [u8; 3] = [0, 177, 64];
+
+    fn pixel(image: &Image, x: usize) -> [u8; 4] {
+        image.flatten_to_u8()[0][x * 4..x * 4 + 4]
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_chroma_key() {
+        let colors: [[u8; 3]; 4] = [
+            // the key, a shadow on it, the foreground and a green tinted gray
+            GREEN,
+            [10, 150, 60],
+            [200, 40, 40],
+            [120, 150, 120]
+        ];
+        let mut image = Image::from_fn::<u8, _>(4, 1, ColorSpace::RGB, |_, x, pix| {
+            pix[..3].copy_from_slice(&colors[x]);
+        });
+        ChromaKey::new(GREEN)
+            .set_spill_suppression(1.0)
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.colorspace(), ColorSpace::RGBA);
+
+        assert_eq!(pixel(&image, 0)[3], 0);
+        assert_eq!(pixel(&image, 1)[3], 0);
+        // unrelated colors are kept
+        assert_eq!(pixel(&image, 2), [200, 40, 40, 255]);
+        // the tint is removed
+        let [r, g, b, a] = pixel(&image, 3);
+        assert_eq!(a, 255);
+        assert!(g <= r.max(b) && g < 150, "{r} {g} {b}");
+    }
+
+    #[test]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn test_chroma_key_soft_edges() {
+        // from the key to red
+        let mut image = Image::from_fn::<u16, _>(11, 1, ColorSpace::RGB, |_, x, pix| {
+            let t = x as f32 / 10.0;
+            pix[0] = (t * 65535.0) as u16;
+            pix[1] = ((1.0 - t) * 177.0 * 257.0) as u16;
+            pix[2] = ((1.0 - t) * 64.0 * 257.0) as u16;
+        });
+        ChromaKey::new(GREEN)
+            .set_threshold(0.2)
+            .set_softness(0.4)
+            .execute(&mut image)
+            .unwrap();
+
+        let alpha: Vec<u8> = (0..11).map(|x| pixel(&image, x)[3]).collect();
+        assert_eq!((alpha[0], alpha[10]), (0, 255));
+        assert!(alpha.windows(2).all(|x| x[0] <= x[1]));
+        assert!(alpha.iter().any(|x| (50..200).contains(x)), "{alpha:?}");
+    }
+
+    #[test]
+    fn test_chroma_key_refine() {
+        // a foreground, a half transparent edge mixing it with the key, and the key
+        let mut image = Image::from_fn::<u8, _>(40, 4, ColorSpace::RGB, |_, x, pix| {
+            pix[..3].copy_from_slice(match x {
+                0..=9 => &[200, 60, 40],
+                10..=29 => &[100, 118, 52],
+                _ => &GREEN
+            });
+        });
+        // a ramp too far from the key underestimates the alpha of the edge
+        let key = ChromaKey::new(GREEN)
+            .set_threshold(0.25)
+            .set_softness(0.2)
+            .set_spill_suppression(0.0);
+
+        let mut plain = image.clone();
+        key.execute(&mut plain).unwrap();
+        key.set_refine_radius(20).execute(&mut image).unwrap();
+
+        let (plain, refined) = (pixel(&plain, 20)[3], pixel(&image, 20)[3]);
+        assert!(plain < 100, "{plain}");
+        assert!(refined.abs_diff(128) <= 8, "{refined}");
+        assert_eq!(pixel(&image, 0)[3], 255);
+        assert_eq!(pixel(&image, 39)[3], 0);
+    }
+}
@@ -84,6 +84,7 @@ use crate::traits::NumOps;
 ///     Ok(())
 /// }
 /// ```
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColorMatrix {
     matrix: [[f32; 5]; 4]
 }
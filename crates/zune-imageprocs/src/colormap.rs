@@ -0,0 +1,310 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Map single channel images to color
+//!
+//! This renders grayscale data, e.g. depth maps, heatmaps or scientific
+//! measurements stored as floats, in false color through one of the built in colormaps.
+//!
+//! Viridis and magma are perceptually uniform, equal steps in value look like equal steps in
+//! color and they remain readable when printed in grayscale, which makes them the better
+//! default. Jet and hot are provided for compatibility with existing tooling
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::to_sample;
+
+/// Polynomial fit of matplotlib's viridis colormap, coefficients for
+/// t^0 to t^6 of each of red, green and blue
+const VIRIDIS: [[f32; 3]; 7] = [
+    [0.277_727_33, 0.005_407_344_5, 0.334_099_8],
+    [0.105_093_04, 1.404_613_5, 1.384_590_2],
+    [-0.330_861_83, 0.214_847_56, 0.095_095_16],
+    [-4.634_230_5, -5.799_101, -19.332_441],
+    [6.228_27, 14.179_933, 56.690_55],
+    [4.776_385, -13.745_146, -65.353_035],
+    [-5.435_456, 4.645_852_6, 26.312_435]
+];
+
+/// Polynomial fit of matplotlib's magma colormap, see [`VIRIDIS`]
+const MAGMA: [[f32; 3]; 7] = [
+    [-0.002_136_485, -0.000_749_655_05, -0.005_386_128],
+    [0.251_660_55, 0.677_523_2, 2.494_026_6],
+    [8.353_717, -3.577_719_4, 0.314_467_9],
+    [-27.668_734, 14.264_731, -13.649_213],
+    [52.176_14, -27.943_607, 12.944_169],
+    [-50.768_524, 29.046_583, 4.234_153],
+    [18.655_705, -11.489_774, -5.601_961_5]
+];
+
+/// A built in colormap
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColormapType {
+    /// Perceptually uniform map from dark blue through green to yellow
+    Viridis,
+    /// Perceptually uniform map from black through purple and orange to light yellow
+    Magma,
+    /// The classic rainbow map from dark blue through cyan, yellow and red to dark red
+    Jet,
+    /// Black through red and yellow to white
+    Hot
+}
+
+impl ColormapType {
+    pub fn from_string_result(input: &str) -> Result<Self, String> {
+        match input {
+            "viridis" => Ok(Self::Viridis),
+            "magma" => Ok(Self::Magma),
+            "jet" => Ok(Self::Jet),
+            "hot" => Ok(Self::Hot),
+            _ => Err("Unknown colormap, accepted values are viridis,magma,jet,hot".to_string())
+        }
+    }
+    /// Return the color of a value between 0.0 and 1.0 as red, green and blue
+    /// components between 0.0 and 1.0
+    ///
+    /// Values outside the range are clamped
+    #[must_use]
+    pub fn sample(self, value: f32) -> [f32; 3] {
+        // NaN is treated as the lowest value
+        let t = if value.is_nan() { 0.0 } else { value.clamp(0.0, 1.0) };
+
+        let [r, g, b] = match self {
+            ColormapType::Viridis => polynomial(&VIRIDIS, t),
+            ColormapType::Magma => polynomial(&MAGMA, t),
+            ColormapType::Jet => [
+                1.5 - (4.0 * t - 3.0).abs(),
+                1.5 - (4.0 * t - 2.0).abs(),
+                1.5 - (4.0 * t - 1.0).abs()
+            ],
+            ColormapType::Hot => [3.0 * t, 3.0 * t - 1.0, 3.0 * t - 2.0]
+        };
+        [r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0)]
+    }
+}
+
+fn polynomial(coefficients: &[[f32; 3]; 7], t: f32) -> [f32; 3] {
+    let mut color = [0.0; 3];
+    // horner's method, highest power first
+    for coefficient in coefficients.iter().rev() {
+        for (c, k) in color.iter_mut().zip(coefficient) {
+            *c = *c * t + k;
+        }
+    }
+    color
+}
+
+/// Render a grayscale image in false color
+///
+/// Luma images become RGB and LumaA images become RGBA, keeping the alpha
+/// channel, the bit depth is unchanged.
+///
+/// Values are mapped linearly from the range set with [`set_range`](Self::set_range) to the
+/// colormap, values outside the range are clipped to its ends.
+/// By default the range covers all values of the bit depth, for float images that is 0.0 to 1.0
+///
+/// # Example
+/// - Render float measurements between -10 and 10 with viridis
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::colormap::{Colormap, ColormapType};
+///
+/// let mut image = Image::from_fn::<f32, _>(100, 100, ColorSpace::Luma, |y, x, pix| {
+///     pix[0] = (x as f32 - y as f32) / 10.0;
+/// });
+/// Colormap::new(ColormapType::Viridis)
+///     .set_range(-10.0, 10.0)
+///     .execute(&mut image)
+///     .unwrap();
+///
+/// assert_eq!(image.colorspace(), ColorSpace::RGB);
+/// ```
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Colormap {
+    colormap: ColormapType,
+    range:    Option<(f32, f32)>
+}
+
+impl Colormap {
+    /// Create a new colormap operation
+    #[must_use]
+    pub fn new(colormap: ColormapType) -> Colormap {
+        Colormap {
+            colormap,
+            range: None
+        }
+    }
+    /// Set the values mapped to the start and end of the colormap,
+    /// in the units of the image's bit depth
+    ///
+    /// If `low` is greater than `high` the colormap is reversed
+    #[must_use]
+    pub fn set_range(mut self, low: f32, high: f32) -> Self {
+        self.range = Some((low, high));
+        self
+    }
+}
+
+impl OperationsTrait for Colormap {
+    fn name(&self) -> &'static str {
+        "Colormap"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let colorspace = image.colorspace();
+        let bit_type = image.depth().bit_type();
+
+        let out_colorspace =
+            if colorspace.has_alpha() { ColorSpace::RGBA } else { ColorSpace::RGB };
+
+        for frame in image.frames_mut() {
+            let channels = frame.channels_vec();
+            let length = channels[0].len();
+
+            let mut output = [
+                Channel::new_with_bit_type(length, bit_type),
+                Channel::new_with_bit_type(length, bit_type),
+                Channel::new_with_bit_type(length, bit_type)
+            ];
+            let [r, g, b] = &mut output;
+
+            match bit_type {
+                BitType::U8 => map_channel::<u8>(
+                    channels[0].reinterpret_as()?,
+                    [
+                        r.reinterpret_as_mut()?,
+                        g.reinterpret_as_mut()?,
+                        b.reinterpret_as_mut()?
+                    ],
+                    self.colormap,
+                    self.range
+                ),
+                BitType::U16 => map_channel::<u16>(
+                    channels[0].reinterpret_as()?,
+                    [
+                        r.reinterpret_as_mut()?,
+                        g.reinterpret_as_mut()?,
+                        b.reinterpret_as_mut()?
+                    ],
+                    self.colormap,
+                    self.range
+                ),
+                BitType::F32 => map_channel::<f32>(
+                    channels[0].reinterpret_as()?,
+                    [
+                        r.reinterpret_as_mut()?,
+                        g.reinterpret_as_mut()?,
+                        b.reinterpret_as_mut()?
+                    ],
+                    self.colormap,
+                    self.range
+                ),
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+            let alpha = colorspace.has_alpha().then(|| channels.pop().unwrap());
+
+            *channels = output.into_iter().chain(alpha).collect();
+        }
+        image.metadata_mut().set_colorspace(out_colorspace);
+
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::Luma, ColorSpace::LumaA]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Map values through the colormap into red, green and blue outputs
+#[allow(clippy::cast_possible_truncation)]
+fn map_channel<T>(
+    input: &[T], output: [&mut [T]; 3], colormap: ColormapType, range: Option<(f32, f32)>
+) where
+    T: Copy + NumOps<T>
+{
+    let max = T::max_val().to_f64() as f32;
+    let (low, high) = range.unwrap_or((T::min_val().to_f64() as f32, max));
+
+    let span = high - low;
+    let [red, green, blue] = output;
+
+    for (((value, red), green), blue) in input.iter().zip(red).zip(green).zip(blue) {
+        let position = if span.abs() < f32::EPSILON {
+            0.0
+        } else {
+            (value.to_f64() as f32 - low) / span
+        };
+        for (out, component) in [red, green, blue]
+            .into_iter()
+            .zip(colormap.sample(position))
+        {
+            *out = to_sample(component * max);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::colormap::{Colormap, ColormapType};
+
+    #[test]
+    fn test_colormap_endpoints() {
+        // reference values from matplotlib
+        let close = |a: [f32; 3], b: [f32; 3]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 0.02);
+
+        assert!(close(
+            ColormapType::Viridis.sample(0.0),
+            [0.267, 0.005, 0.329]
+        ));
+        assert!(close(
+            ColormapType::Viridis.sample(1.0),
+            [0.993, 0.906, 0.144]
+        ));
+        assert!(close(
+            ColormapType::Magma.sample(1.0),
+            [0.987, 0.991, 0.749]
+        ));
+        assert!(close(ColormapType::Jet.sample(0.5), [0.5, 1.0, 0.5]));
+        assert!(close(ColormapType::Hot.sample(2.0), [1.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn test_colormap_range_and_alpha() {
+        let mut image = Image::from_fn::<u8, _>(3, 1, ColorSpace::LumaA, |_, x, pix| {
+            pix[0] = [0, 100, 200][x];
+            pix[1] = 77;
+        });
+        Colormap::new(ColormapType::Hot)
+            .set_range(0.0, 100.0)
+            .execute(&mut image)
+            .unwrap();
+
+        assert_eq!(image.colorspace(), ColorSpace::RGBA);
+        let pixels = &image.flatten_to_u8()[0];
+        assert_eq!(&pixels[0..4], &[0, 0, 0, 77]);
+        // clipped to the top of the range
+        assert_eq!(&pixels[4..8], &[255, 255, 255, 77]);
+        assert_eq!(&pixels[8..12], &[255, 255, 255, 77]);
+    }
+}
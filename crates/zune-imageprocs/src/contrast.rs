@@ -54,6 +54,7 @@ use zune_image::traits::OperationsTrait;
 ///
 /// ```
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Contrast {
     contrast: f32
 }
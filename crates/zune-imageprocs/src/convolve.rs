@@ -13,6 +13,9 @@
 //!
 //! The intermediate calculations are carried in `f32`
 //!
+//! Besides hand written matrices, [`Convolve`] has constructors for common
+//! kernels, see [`Convolve::emboss`], [`Convolve::sharpen`] and [`Convolve::edge_enhance`]
+//!
 
 use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
@@ -57,6 +60,7 @@ use crate::utils::{execute_on, z_prefetch};
 /// # Ok::<(),ImageErrors>(())
 /// ```
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Convolve {
     weights: Vec<f32>,
     scale:   f32
@@ -70,6 +74,113 @@ impl Convolve {
     pub fn new(weights: Vec<f32>, scale: f32) -> Convolve {
         Convolve { weights, scale }
     }
+
+    /// Create a 3x3 emboss kernel, making the image look raised as if lit from `direction`
+    ///
+    /// The weights sum to one, so flat areas keep their color and edges facing the light
+    /// are brightened while edges facing away from it are darkened
+    ///
+    /// # Example
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::image::Image;
+    /// use zune_image::traits::OperationsTrait;
+    /// use zune_imageprocs::convolve::{Convolve, EmbossDirection};
+    ///
+    /// let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+    /// Convolve::emboss(EmbossDirection::NorthWest).execute(&mut image).unwrap();
+    /// ```
+    #[must_use]
+    pub fn emboss(direction: EmbossDirection) -> Convolve {
+        let (light_x, light_y) = direction.offset();
+        let mut weights = Vec::with_capacity(9);
+
+        for y in -1_i8..=1 {
+            for x in -1_i8..=1 {
+                // neighbours towards the light are subtracted, the ones away from it added
+                let weight = -(x * light_x + y * light_y);
+                weights.push(f32::from(weight));
+            }
+        }
+        weights[4] = 1.0;
+        Convolve::new(weights, 1.0)
+    }
+
+    /// Create a 3x3 sharpen kernel
+    ///
+    /// The kernel subtracts `amount` times the four direct neighbours of a pixel and adds
+    /// them to its own weight, so that flat areas are kept. An amount of 1.0 gives the
+    /// common `[0,-1,0,-1,5,-1,0,-1,0]` kernel, 0.0 leaves the image as is
+    ///
+    /// For control over the radius and a threshold, see [`Unsharpen`](crate::unsharpen::Unsharpen)
+    #[must_use]
+    pub fn sharpen(amount: f32) -> Convolve {
+        #[rustfmt::skip]
+        let weights = vec![
+            0.0, -amount, 0.0,
+            -amount, 1.0 + 4.0 * amount, -amount,
+            0.0, -amount, 0.0,
+        ];
+        Convolve::new(weights, 1.0)
+    }
+
+    /// Create a 3x3 edge enhance kernel
+    ///
+    /// Like [`sharpen`](Self::sharpen) but with all eight neighbours of a pixel, which
+    /// brings out edges in every direction more strongly. An amount of 0.5 matches the edge
+    /// enhance filter of most image editors, 1.0 their stronger variant
+    #[must_use]
+    pub fn edge_enhance(amount: f32) -> Convolve {
+        let mut weights = vec![-amount; 9];
+        weights[4] = 1.0 + 8.0 * amount;
+        Convolve::new(weights, 1.0)
+    }
+}
+
+/// The direction light comes from in an [emboss](Convolve::emboss) kernel
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub enum EmbossDirection {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest
+}
+
+impl EmbossDirection {
+    pub fn from_string_result(input: &str) -> Result<Self, String> {
+        match input {
+            "north" | "n" => Ok(Self::North),
+            "north-east" | "ne" => Ok(Self::NorthEast),
+            "east" | "e" => Ok(Self::East),
+            "south-east" | "se" => Ok(Self::SouthEast),
+            "south" | "s" => Ok(Self::South),
+            "south-west" | "sw" => Ok(Self::SouthWest),
+            "west" | "w" => Ok(Self::West),
+            "north-west" | "nw" => Ok(Self::NorthWest),
+            _ => Err(format!(
+                "Unknown emboss direction {input}, accepted values are north, north-east, east, south-east, south, south-west, west and north-west"
+            ))
+        }
+    }
+
+    /// Return the offset of the neighbour pointing towards the light, y goes down
+    const fn offset(self) -> (i8, i8) {
+        match self {
+            EmbossDirection::North => (0, -1),
+            EmbossDirection::NorthEast => (1, -1),
+            EmbossDirection::East => (1, 0),
+            EmbossDirection::SouthEast => (1, 1),
+            EmbossDirection::South => (0, 1),
+            EmbossDirection::SouthWest => (-1, 1),
+            EmbossDirection::West => (-1, 0),
+            EmbossDirection::NorthWest => (-1, -1)
+        }
+    }
 }
 
 impl OperationsTrait for Convolve {
@@ -335,8 +446,11 @@ fn spatial_NxN<T, F, const RADIUS: usize, const OUT_SIZE: usize>(
 #[cfg(test)]
 mod tests {
     use nanorand::Rng;
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
 
-    use crate::convolve::{convolve_3x3, convolve_5x5, convolve_7x7};
+    use crate::convolve::{convolve_3x3, convolve_5x5, convolve_7x7, Convolve, EmbossDirection};
 
     // test that 3x3 convolution works
     #[test]
@@ -368,4 +482,50 @@ mod tests {
         convolve_7x7(&data, &mut out, width, height, &[0.0; 49], 1.);
         assert!(out.iter().all(|x| *x == 0));
     }
+
+    #[test]
+    fn test_preset_kernels() {
+        let emboss = Convolve::emboss(EmbossDirection::NorthWest);
+        assert_eq!(
+            emboss.weights,
+            [-2.0, -1.0, 0.0, -1.0, 1.0, 1.0, 0.0, 1.0, 2.0]
+        );
+        let emboss = Convolve::emboss(EmbossDirection::East);
+        assert_eq!(
+            emboss.weights,
+            [1.0, 0.0, -1.0, 1.0, 1.0, -1.0, 1.0, 0.0, -1.0]
+        );
+        assert_eq!(
+            Convolve::sharpen(1.0).weights,
+            [0.0, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0]
+        );
+        // all presets keep flat areas as they are
+        for kernel in [emboss, Convolve::sharpen(0.7), Convolve::edge_enhance(0.5)] {
+            assert!((kernel.weights.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+
+            let mut image = Image::fill::<u8>(90, ColorSpace::RGB, 10, 10);
+            kernel.execute(&mut image).unwrap();
+            assert!(image.flatten_to_u8()[0].iter().all(|x| *x == 90));
+        }
+    }
+
+    #[test]
+    fn test_emboss_direction() {
+        // a bright square on a dark background, lit from the left
+        let mut image = Image::from_fn::<u8, _>(12, 12, ColorSpace::Luma, |y, x, pix| {
+            pix[0] = if (4..8).contains(&x) && (4..8).contains(&y) { 200 } else { 50 };
+        });
+        Convolve::emboss(EmbossDirection::West)
+            .execute(&mut image)
+            .unwrap();
+        let pixels = &image.flatten_to_u8()[0];
+
+        // the left edge of the square faces the light, the right edge faces away
+        assert_eq!(pixels[6 * 12 + 3..][..6], [255, 255, 200, 200, 0, 0]);
+        assert_eq!(
+            EmbossDirection::from_string_result("nw"),
+            Ok(EmbossDirection::NorthWest)
+        );
+        assert!(EmbossDirection::from_string_result("up").is_err());
+    }
 }
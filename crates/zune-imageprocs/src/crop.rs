@@ -91,6 +91,7 @@ use crate::utils::execute_on;
 ///      Ok(())
 /// }
 /// ```
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Crop {
     x:      usize,
     y:      usize,
@@ -179,6 +180,14 @@ impl OperationsTrait for Crop {
         // safety: We just changed size of array
         image.set_dimensions(self.width, self.height);
 
+        let regions = image
+            .metadata()
+            .regions_of_interest()
+            .iter()
+            .filter_map(|x| x.crop(self.x, self.y, self.width, self.height))
+            .collect();
+        image.metadata_mut().set_regions_of_interest(regions);
+
         Ok(())
     }
     fn supported_types(&self) -> &'static [BitType] {
@@ -0,0 +1,312 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Adjust tones with curves
+//!
+//! A curve maps input values to output values through a smooth line passing through
+//! a few control points, e.g. lifting the shadows while keeping black and white in place,
+//! or an S shaped curve to increase contrast. Curves can be set for the red, green and blue
+//! channels separately and for all of them through a master curve.
+//!
+//! # Algorithm details
+//! Control points are joined with a monotone cubic spline (Fritsch-Carlson), which passes
+//! through every point and, unlike a natural spline, never overshoots between them, so
+//! a curve through increasing points is increasing as well.
+//!
+//! Before the first and after the last control point the curve is flat.
+//!
+//! # Implementation details
+//! - For `u8` and `u16` , we use lookup tables to improve speed
+//! - For `f32` the spline is evaluated for every pixel
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::utils::map_samples;
+
+/// A tone curve through control points
+///
+/// Points are `[input, output]` pairs between 0.0 and 1.0, where 1.0 stands for the maximum value
+/// of the bit depth. They don't need to be sorted, when two points have the same input the last
+/// one is used.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Curve {
+    points: Vec<[f32; 2]>
+}
+
+impl Curve {
+    /// Create a new curve through `points`
+    #[must_use]
+    pub fn new(points: Vec<[f32; 2]>) -> Curve {
+        Curve { points }
+    }
+    /// Create a curve that doesn't change values
+    #[must_use]
+    pub fn identity() -> Curve {
+        Curve::new(vec![[0.0, 0.0], [1.0, 1.0]])
+    }
+    /// Return the output of the curve for `value`
+    ///
+    /// A curve without points returns `value` unchanged
+    #[must_use]
+    pub fn sample(&self, value: f32) -> f32 {
+        Spline::new(&self.points).sample(value)
+    }
+}
+
+/// A monotone cubic spline ready for evaluation
+struct Spline {
+    x:        Vec<f32>,
+    y:        Vec<f32>,
+    tangents: Vec<f32>
+}
+
+impl Spline {
+    #[allow(clippy::many_single_char_names)]
+    fn new(points: &[[f32; 2]]) -> Spline {
+        let mut sorted = points.to_vec();
+        // stable, so the last of points with the same input stays last
+        sorted.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        sorted.reverse();
+        sorted.dedup_by(|a, b| a[0].total_cmp(&b[0]).is_eq());
+        sorted.reverse();
+
+        let x: Vec<f32> = sorted.iter().map(|p| p[0]).collect();
+        let y: Vec<f32> = sorted.iter().map(|p| p[1]).collect();
+        let n = x.len();
+
+        if n < 2 {
+            return Spline {
+                x,
+                y,
+                tangents: vec![0.0; n]
+            };
+        }
+        let secants: Vec<f32> = (0..n - 1)
+            .map(|k| (y[k + 1] - y[k]) / (x[k + 1] - x[k]))
+            .collect();
+
+        let mut tangents = vec![0.0; n];
+        tangents[0] = secants[0];
+        tangents[n - 1] = secants[n - 2];
+        for k in 1..n - 1 {
+            // extrema between segments get flat tangents
+            if secants[k - 1] * secants[k] > 0.0 {
+                tangents[k] = (secants[k - 1] + secants[k]) * 0.5;
+            }
+        }
+        // limit tangents so that segments don't overshoot
+        for (k, secant) in secants.iter().enumerate() {
+            if *secant == 0.0 {
+                tangents[k] = 0.0;
+                tangents[k + 1] = 0.0;
+                continue;
+            }
+            let a = tangents[k] / secant;
+            let b = tangents[k + 1] / secant;
+            let length = a.hypot(b);
+
+            if length > 3.0 {
+                tangents[k] = 3.0 * a / length * secant;
+                tangents[k + 1] = 3.0 * b / length * secant;
+            }
+        }
+        Spline { x, y, tangents }
+    }
+
+    fn sample(&self, value: f32) -> f32 {
+        let (Some(first), Some(last)) = (self.x.first(), self.x.last()) else {
+            return value;
+        };
+        if value.is_nan() || value <= *first {
+            return self.y[0].clamp(0.0, 1.0);
+        }
+        if value >= *last {
+            return self.y[self.y.len() - 1].clamp(0.0, 1.0);
+        }
+        let k = self.x.partition_point(|x| *x <= value) - 1;
+
+        let h = self.x[k + 1] - self.x[k];
+        let t = (value - self.x[k]) / h;
+        let (t2, t3) = (t * t, t * t * t);
+
+        // cubic hermite basis
+        let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+        let h10 = t3 - 2.0 * t2 + t;
+        let h01 = -2.0 * t3 + 3.0 * t2;
+        let h11 = t3 - t2;
+
+        let y = h00 * self.y[k]
+            + h10 * h * self.tangents[k]
+            + h01 * self.y[k + 1]
+            + h11 * h * self.tangents[k + 1];
+
+        y.clamp(0.0, 1.0)
+    }
+}
+
+/// Apply tone curves to an image
+///
+/// The red, green and blue curves are applied to their channels first, then the
+/// master curve to all of them. Grayscale images only use the master curve,
+/// alpha is ignored. Curves that aren't set leave values unchanged.
+///
+/// # Example
+/// - Increase contrast with an S curve
+///
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::curves::{Curve, Curves};
+///
+/// let mut image = Image::fill::<u8>(64, ColorSpace::RGB, 100, 100);
+/// let s_curve = Curve::new(vec![[0.0, 0.0], [0.25, 0.15], [0.75, 0.85], [1.0, 1.0]]);
+///
+/// Curves::new().set_master(s_curve).execute(&mut image).unwrap();
+/// assert!(image.flatten_to_u8()[0][0] < 64);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Curves {
+    master: Option<Curve>,
+    red:    Option<Curve>,
+    green:  Option<Curve>,
+    blue:   Option<Curve>
+}
+
+impl Curves {
+    /// Create a new curves operation without any curves
+    #[must_use]
+    pub fn new() -> Curves {
+        Curves::default()
+    }
+    /// Set the curve applied to all color channels
+    #[must_use]
+    pub fn set_master(mut self, curve: Curve) -> Self {
+        self.master = Some(curve);
+        self
+    }
+    /// Set the curve of the red channel
+    #[must_use]
+    pub fn set_red(mut self, curve: Curve) -> Self {
+        self.red = Some(curve);
+        self
+    }
+    /// Set the curve of the green channel
+    #[must_use]
+    pub fn set_green(mut self, curve: Curve) -> Self {
+        self.green = Some(curve);
+        self
+    }
+    /// Set the curve of the blue channel
+    #[must_use]
+    pub fn set_blue(mut self, curve: Curve) -> Self {
+        self.blue = Some(curve);
+        self
+    }
+}
+
+impl OperationsTrait for Curves {
+    fn name(&self) -> &'static str {
+        "Curves"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let colorspace = image.colorspace();
+        let bit_type = image.depth().bit_type();
+
+        let spline = |curve: &Option<Curve>| curve.as_ref().map(|x| Spline::new(&x.points));
+        let master = spline(&self.master);
+        let channel_splines = if colorspace.is_grayscale() {
+            vec![None]
+        } else {
+            vec![spline(&self.red), spline(&self.green), spline(&self.blue)]
+        };
+
+        for frame in image.frames_mut() {
+            for (channel, curve) in frame
+                .channels_mut(colorspace, true)
+                .iter_mut()
+                .zip(&channel_splines)
+            {
+                if curve.is_none() && master.is_none() {
+                    continue;
+                }
+                map_samples(channel, bit_type, self.name(), |x| {
+                    let x = curve.as_ref().map_or(x, |c| c.sample(x));
+                    master.as_ref().map_or(x, |c| c.sample(x))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::LumaA,
+            ColorSpace::Luma
+        ]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::curves::{Curve, Curves};
+
+    #[test]
+    fn test_curve_spline() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-5;
+
+        let curve = Curve::new(vec![[1.0, 1.0], [0.5, 0.8], [0.0, 0.0]]);
+        // passes through its points
+        assert!(close(curve.sample(0.0), 0.0));
+        assert!(close(curve.sample(0.5), 0.8));
+        assert!(close(curve.sample(1.0), 1.0));
+        // monotone, without overshooting
+        let samples: Vec<f32> = (0..=100_u8).map(|x| curve.sample(f32::from(x) / 100.0)).collect();
+        assert!(samples.windows(2).all(|x| x[0] <= x[1]));
+        assert!(samples.iter().all(|x| (0.0..=1.0).contains(x)));
+
+        // flat outside the points, identity without points
+        let curve = Curve::new(vec![[0.2, 0.3], [0.8, 0.6]]);
+        assert!(close(curve.sample(0.1), 0.3));
+        assert!(close(curve.sample(0.9), 0.6));
+        assert!(close(Curve::new(vec![]).sample(0.4), 0.4));
+        assert!(close(Curve::identity().sample(0.4), 0.4));
+    }
+
+    #[test]
+    fn test_curves_channels() {
+        let mut image = Image::from_fn::<u8, _>(1, 1, ColorSpace::RGBA, |_, _, pix| {
+            pix[..4].copy_from_slice(&[101, 100, 100, 100]);
+        });
+        let invert = Curve::new(vec![[0.0, 1.0], [1.0, 0.0]]);
+        let half = Curve::new(vec![[0.0, 0.0], [1.0, 0.5]]);
+
+        Curves::new()
+            .set_red(invert)
+            .set_master(half)
+            .execute(&mut image)
+            .unwrap();
+        // red is inverted then halved, the others halved, alpha untouched
+        assert_eq!(image.flatten_to_u8()[0], [77, 50, 50, 100]);
+    }
+}
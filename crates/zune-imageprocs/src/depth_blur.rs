@@ -0,0 +1,244 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Variable lens blur driven by a depth map
+//!
+//! Every pixel is blurred with a disc, like the out of focus highlights (bokeh) of a
+//! camera lens, whose radius is read from a second grayscale image. Black pixels of the
+//! map stay sharp, white pixels get the maximum radius and gray pixels something in between,
+//! fractional radii are interpolated so that the blur changes smoothly with the map.
+//!
+//! With a depth map rendered by a 3D program or estimated from a photo, converted so that
+//! the distance from the plane in focus is bright, this approximates a shallow depth of field.
+//! A gradient map gives tilt-shift effects.
+//!
+//! Pixels gather their neighbours within their own radius, so a sharp object in front
+//! of a blurred background does not spill over it as it would through a real lens.
+//!
+//! # Example
+//! - Keep the top of an image sharp and blur it increasingly towards the bottom
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::depth_blur::DepthBlur;
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+//! let map = Image::from_fn::<u8, _>(100, 100, ColorSpace::Luma, |y, _, pix| {
+//!     pix[0] = (y * 255 / 99) as u8;
+//! });
+//! DepthBlur::new(&map, 8.0).execute(&mut image).unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::utils::{execute_on, frame_luma, normalized_samples, store_samples};
+
+/// Blur an image with a disc whose radius varies by pixel
+///
+/// The first frame of the map is used for all frames of the image, it must have the
+/// dimensions of the image. All channels, alpha included, are blurred.
+pub struct DepthBlur<'src> {
+    map:        &'src Image,
+    max_radius: f32
+}
+
+impl<'src> DepthBlur<'src> {
+    /// Create a new depth blur
+    ///
+    /// # Arguments
+    /// - map: The blur radius of every pixel, the luma of the image is used
+    /// - max_radius: The radius in pixels of the blur where the map is white
+    #[must_use]
+    pub fn new(map: &'src Image, max_radius: f32) -> DepthBlur<'src> {
+        DepthBlur { map, max_radius }
+    }
+}
+
+impl OperationsTrait for DepthBlur<'_> {
+    fn name(&self) -> &'static str {
+        "Depth Blur"
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if image.dimensions() != self.map.dimensions() {
+            return Err(ImageErrors::GenericStr(
+                "Depth map dimensions do not match the image"
+            ));
+        }
+        let (width, height) = image.dimensions();
+        let bit_type = image.depth().bit_type();
+        let max_radius = self.max_radius.max(0.0);
+
+        let radii: Vec<f32> = frame_luma(self.map, 0)?
+            .iter()
+            .map(|x| x.clamp(0.0, 1.0) * max_radius)
+            .collect();
+        // the largest disc is one above the largest radius, for interpolation
+        let discs: Vec<Vec<usize>> = (0..=max_radius.ceil() as usize + 1)
+            .map(disc_spans)
+            .collect();
+
+        let blur = |channel: &mut Channel| -> Result<(), ImageErrors> {
+            let values = normalized_samples(channel, bit_type, self.name())?;
+            let sums = row_sums(&values, width, height);
+
+            let blurred: Vec<f32> = radii
+                .iter()
+                .enumerate()
+                .map(|(i, radius)| {
+                    let (x, y) = (i % width, i / width);
+                    let inner = radius.floor();
+                    let fraction = radius - inner;
+
+                    let value = disc_mean(&sums, width, height, x, y, &discs[inner as usize]);
+                    if fraction > 0.0 {
+                        let outer =
+                            disc_mean(&sums, width, height, x, y, &discs[inner as usize + 1]);
+                        value + (outer - value) * fraction
+                    } else {
+                        value
+                    }
+                })
+                .collect();
+
+            store_samples(channel, bit_type, self.name(), &blurred)
+        };
+        execute_on(blur, image, false)
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Return the half width of every row of a disc of `radius`, from its top to its bottom
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn disc_spans(radius: usize) -> Vec<usize> {
+    // half a pixel more keeps single pixels from sticking out at the top, bottom and sides
+    let outer = (radius as f32 + 0.5).powi(2);
+
+    (0..=2 * radius)
+        .map(|row| {
+            let dy = row as f32 - radius as f32;
+            (outer - dy * dy).sqrt() as usize
+        })
+        .collect()
+}
+
+/// Return the running sums of every row, with a leading zero per row
+fn row_sums(values: &[f32], width: usize, height: usize) -> Vec<f64> {
+    let mut sums = vec![0.0; (width + 1) * height];
+
+    for (row, sum) in values
+        .chunks_exact(width)
+        .zip(sums.chunks_exact_mut(width + 1))
+    {
+        for (x, value) in row.iter().enumerate() {
+            sum[x + 1] = sum[x] + f64::from(*value);
+        }
+    }
+    sums
+}
+
+/// Return the mean of the disc described by `spans` centered at `(x, y)`, parts of
+/// the disc outside the image are left out
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn disc_mean(
+    sums: &[f64], width: usize, height: usize, x: usize, y: usize, spans: &[usize]
+) -> f32 {
+    let radius = spans.len() / 2;
+    let mut sum = 0.0;
+    let mut count = 0;
+
+    for (row, half_width) in spans.iter().enumerate() {
+        let Some(source_y) = (y + row).checked_sub(radius) else {
+            continue;
+        };
+        if source_y >= height {
+            break;
+        }
+        let left = x.saturating_sub(*half_width);
+        let right = (x + half_width + 1).min(width);
+        let row_sums = &sums[source_y * (width + 1)..];
+
+        sum += row_sums[right] - row_sums[left];
+        count += right - left;
+    }
+    (sum / count as f64) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::depth_blur::DepthBlur;
+
+    #[test]
+    fn test_depth_blur() {
+        // vertical stripes, sharp on the left half of the map and blurred on the right
+        let stripes = |x: usize| if x & 1 == 0 { 0.0 } else { 1.0 };
+        let mut image = Image::from_fn::<f32, _>(40, 20, ColorSpace::LumaA, |_, x, pix| {
+            pix[0] = stripes(x);
+            pix[1] = 1.0;
+        });
+        let map = Image::from_fn::<u16, _>(40, 20, ColorSpace::Luma, |_, x, pix| {
+            pix[0] = if x < 20 { 0 } else { 65535 };
+        });
+        DepthBlur::new(&map, 3.0).execute(&mut image).unwrap();
+
+        let pixels = &image.flatten_frames::<f32>()[0];
+        let luma = |x: usize, y: usize| pixels[(y * 40 + x) * 2];
+
+        for y in 0..20 {
+            for x in 0..20 {
+                assert!((luma(x, y) - stripes(x)).abs() < 1e-6, "{x} {y}");
+            }
+            // away from the image edges the stripes average out
+            for x in 24..36 {
+                assert!((luma(x, y) - 0.5).abs() < 0.1, "{x} {y} {}", luma(x, y));
+            }
+        }
+        // alpha is blurred too, but is flat
+        assert!(pixels.chunks_exact(2).all(|x| (x[1] - 1.0).abs() < 1e-6));
+
+        let small = Image::fill::<u8>(0, ColorSpace::Luma, 10, 10);
+        assert!(DepthBlur::new(&small, 3.0).execute(&mut image).is_err());
+    }
+
+    #[test]
+    fn test_fractional_radius_interpolates() {
+        // a single bright pixel, blurred with half the radius everywhere
+        let dot = || {
+            Image::from_fn::<f32, _>(11, 11, ColorSpace::Luma, |y, x, pix| {
+                pix[0] = if (x, y) == (5, 5) { 1.0 } else { 0.0 };
+            })
+        };
+        let map = Image::fill::<f32>(0.5, ColorSpace::Luma, 11, 11);
+
+        let mut image = dot();
+        DepthBlur::new(&map, 1.0).execute(&mut image).unwrap();
+        let pixels = &image.flatten_frames::<f32>()[0];
+
+        // halfway between the pixel itself and a disc of radius one, covering 3x3 pixels
+        assert!((pixels[5 * 11 + 5] - 5.0 / 9.0).abs() < 1e-5);
+        assert!((pixels[5 * 11 + 6] - 1.0 / 18.0).abs() < 1e-5);
+        assert!((pixels[6 * 11 + 6] - 1.0 / 18.0).abs() < 1e-5);
+        assert!(pixels[5 * 11 + 7].abs() < 1e-6);
+    }
+}
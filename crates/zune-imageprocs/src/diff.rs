@@ -0,0 +1,324 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Compare two images and visualize where they differ
+//!
+//! The result is a heatmap where every pixel holds the largest difference between
+//! the channels of both images at that position, which makes it useful for
+//! screenshot and rendering regression tests.
+//!
+//! Summary statistics of the comparison are available via [`Diff::stats`]
+//! after the operation has been executed
+use std::sync::Mutex;
+
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::colormap::ColormapType;
+use crate::traits::NumOps;
+use crate::utils::to_channels;
+
+/// Summary statistics of an image comparison
+///
+/// Differences are normalized to the range 0.0..=1.0, where 1.0
+/// is the largest difference representable by the image depth
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct DiffStats {
+    /// The largest difference between two pixels
+    pub max_difference:   f32,
+    /// The mean difference over all pixels
+    pub mean_difference:  f32,
+    /// Number of pixels whose difference is above the threshold
+    pub differing_pixels: usize,
+    /// Number of pixels compared, over all frames
+    pub total_pixels:     usize
+}
+
+impl DiffStats {
+    /// Return the fraction of pixels that differ, between 0.0 and 1.0
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn differing_ratio(&self) -> f32 {
+        if self.total_pixels == 0 {
+            return 0.0;
+        }
+        self.differing_pixels as f32 / self.total_pixels as f32
+    }
+    /// Return true if no pixel differs by more than the threshold
+    #[must_use]
+    pub const fn is_identical(&self) -> bool {
+        self.differing_pixels == 0
+    }
+}
+
+/// Create a difference heatmap of two images
+///
+/// The image the operation is executed on is replaced with a heatmap
+/// of the same dimensions and depth. By default the heatmap is a grayscale
+/// (Luma) image where brighter pixels differ more, with
+/// [`set_colormap`](Self::set_colormap) it is an RGB image colored through a colormap instead.
+///
+/// Both images must have the same dimensions, colorspace, depth and number of frames.
+/// Alpha is compared like any other channel
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::diff::Diff;
+///
+/// let expected = Image::fill::<u8>(100, ColorSpace::RGB, 100, 100);
+/// let mut actual = Image::from_fn::<u8, _>(100, 100, ColorSpace::RGB, |y, x, pix| {
+///     pix.fill(if x < 10 && y < 10 { 200 } else { 100 });
+/// });
+/// let diff = Diff::new(&expected).set_threshold(0.01);
+/// diff.execute(&mut actual).unwrap();
+///
+/// let stats = diff.stats().unwrap();
+/// assert_eq!(stats.differing_pixels, 100);
+/// ```
+pub struct Diff<'src> {
+    image:     &'src Image,
+    normalize: bool,
+    colormap:  Option<ColormapType>,
+    threshold: f32,
+    stats:     Mutex<Option<DiffStats>>
+}
+
+impl<'src> Diff<'src> {
+    /// Create a new diff operation comparing against `image`
+    #[must_use]
+    pub fn new(image: &'src Image) -> Diff<'src> {
+        Diff {
+            image,
+            normalize: false,
+            colormap: None,
+            threshold: 0.0,
+            stats: Mutex::new(None)
+        }
+    }
+    /// Scale the heatmap so that the largest difference found
+    /// has full intensity, making small differences visible
+    ///
+    /// This doesn't affect the statistics. Default is false
+    #[must_use]
+    pub fn set_normalize(mut self, yes: bool) -> Self {
+        self.normalize = yes;
+        self
+    }
+    /// Color the heatmap through `colormap`, producing an RGB image
+    /// instead of a grayscale one
+    ///
+    /// [`ColormapType::Hot`] is a good fit, small differences are dark red
+    /// and large ones are white
+    #[must_use]
+    pub fn set_colormap(mut self, colormap: ColormapType) -> Self {
+        self.colormap = Some(colormap);
+        self
+    }
+    /// Set the normalized difference, between 0.0 and 1.0, a pixel must exceed to be
+    /// counted as differing in the statistics
+    ///
+    /// Default is 0.0, any change counts
+    #[must_use]
+    pub fn set_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+    /// Return statistics of the last comparison, or `None`
+    /// if the operation hasn't been executed yet
+    pub fn stats(&self) -> Option<DiffStats> {
+        *self.stats.lock().unwrap()
+    }
+}
+
+impl OperationsTrait for Diff<'_> {
+    fn name(&self) -> &'static str {
+        "Diff"
+    }
+
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if image.dimensions() != self.image.dimensions() {
+            return Err(ImageErrors::GenericStr(
+                "Image dimensions are incompatible for diff"
+            ));
+        }
+        if image.depth() != self.image.depth() {
+            return Err(ImageErrors::GenericStr(
+                "Image depths do not match for diff"
+            ));
+        }
+        if image.colorspace() != self.image.colorspace() {
+            return Err(ImageErrors::GenericStr(
+                "Image colorspace does not match for diff"
+            ));
+        }
+        if image.frames_len() != self.image.frames_len() {
+            return Err(ImageErrors::GenericStr(
+                "Number of frames does not match for diff"
+            ));
+        }
+        let (width, height) = image.dimensions();
+        let colorspace = image.colorspace();
+        let depth = image.depth();
+        let components = colorspace.num_components();
+
+        let mut maps = Vec::with_capacity(image.frames_len());
+
+        for (src_frame, dst_frame) in self.image.frames_ref().iter().zip(image.frames_ref()) {
+            let mut map = vec![0.0_f32; width * height];
+
+            for (src_chan, dst_chan) in src_frame
+                .channels_ref(colorspace, false)
+                .iter()
+                .zip(dst_frame.channels_ref(colorspace, false))
+                .take(components)
+            {
+                match depth.bit_type() {
+                    BitType::U8 => channel_difference::<u8>(
+                        src_chan.reinterpret_as()?,
+                        dst_chan.reinterpret_as()?,
+                        &mut map
+                    ),
+                    BitType::U16 => channel_difference::<u16>(
+                        src_chan.reinterpret_as()?,
+                        dst_chan.reinterpret_as()?,
+                        &mut map
+                    ),
+                    BitType::F32 => channel_difference::<f32>(
+                        src_chan.reinterpret_as()?,
+                        dst_chan.reinterpret_as()?,
+                        &mut map
+                    ),
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+                }
+            }
+            maps.push(map);
+        }
+
+        let mut stats = DiffStats::default();
+        let mut sum = 0.0_f64;
+
+        for map in &maps {
+            for diff in map {
+                stats.max_difference = stats.max_difference.max(*diff);
+                stats.differing_pixels += usize::from(*diff > self.threshold);
+                sum += f64::from(*diff);
+            }
+            stats.total_pixels += map.len();
+        }
+        if stats.total_pixels != 0 {
+            stats.mean_difference = (sum / stats.total_pixels as f64) as f32;
+        }
+
+        let scale = if self.normalize && stats.max_difference > 0.0 {
+            1.0 / stats.max_difference
+        } else {
+            1.0
+        };
+        let out_colorspace =
+            if self.colormap.is_some() { ColorSpace::RGB } else { ColorSpace::Luma };
+
+        // replace the channels in place, keeping frame delays and metadata
+        for (frame, map) in image.frames_mut().iter_mut().zip(maps) {
+            let pixels: Vec<f32> = if let Some(colormap) = self.colormap {
+                map.iter()
+                    .flat_map(|x| colormap.sample(*x * scale))
+                    .collect()
+            } else {
+                map.iter().map(|x| *x * scale).collect()
+            };
+            frame.set_channels(to_channels(&pixels, depth.bit_type(), out_colorspace));
+        }
+        image.metadata_mut().set_colorspace(out_colorspace);
+        *self.stats.lock().unwrap() = Some(stats);
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Store the per pixel maximum of the normalized difference between `a` and `b` in `out`
+#[allow(clippy::cast_possible_truncation)]
+fn channel_difference<T>(a: &[T], b: &[T], out: &mut [f32])
+where
+    T: Copy + NumOps<T>
+{
+    let max = T::max_val().to_f64();
+
+    for ((x, y), diff) in a.iter().zip(b).zip(out.iter_mut()) {
+        let delta = ((x.to_f64() - y.to_f64()).abs() / max).min(1.0) as f32;
+        *diff = diff.max(delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::colormap::ColormapType;
+    use crate::diff::Diff;
+
+    #[test]
+    fn test_diff_identical_images() {
+        let expected = Image::fill::<u16>(1000, ColorSpace::RGBA, 20, 20);
+        let mut actual = expected.clone();
+
+        let diff = Diff::new(&expected);
+        diff.execute(&mut actual).unwrap();
+
+        let stats = diff.stats().unwrap();
+        assert!(stats.is_identical());
+        assert_eq!(stats.total_pixels, 400);
+        assert!(stats.max_difference.abs() < 1e-6);
+        assert_eq!(actual.colorspace(), ColorSpace::Luma);
+    }
+
+    #[test]
+    fn test_diff_heatmap() {
+        let expected = Image::fill::<u8>(0, ColorSpace::Luma, 4, 1);
+        let mut actual = Image::from_fn::<u8, _>(4, 1, ColorSpace::Luma, |_, x, pix| {
+            pix[0] = [0, 51, 102, 0][x];
+        });
+        let diff = Diff::new(&expected)
+            .set_normalize(true)
+            .set_colormap(ColormapType::Hot);
+        diff.execute(&mut actual).unwrap();
+
+        let stats = diff.stats().unwrap();
+        assert_eq!(stats.differing_pixels, 2);
+        assert!((stats.max_difference - 0.4).abs() < 1e-6);
+        assert!((stats.mean_difference - 0.15).abs() < 1e-6);
+
+        assert_eq!(actual.colorspace(), ColorSpace::RGB);
+        let pixels = &actual.flatten_to_u8()[0];
+        // the largest difference is normalized to white
+        assert_eq!(&pixels[6..9], &[255, 255, 255]);
+        // half of it is between red and yellow
+        assert_eq!(&pixels[3..6], &[255, 128, 0]);
+        assert_eq!(&pixels[9..12], &[0, 0, 0]);
+    }
+
+    #[test]
+    fn test_diff_mismatched_dimensions() {
+        let expected = Image::fill::<u8>(0, ColorSpace::RGB, 10, 10);
+        let mut actual = Image::fill::<u8>(0, ColorSpace::RGB, 10, 11);
+
+        assert!(Diff::new(&expected).execute(&mut actual).is_err());
+    }
+}
@@ -51,6 +51,7 @@ use crate::utils::execute_on;
 ///# Ok::<(),ImageErrors>(())
 /// ```
 ///
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Exposure {
     exposure: f32,
     black:    f32
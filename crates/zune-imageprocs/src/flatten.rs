@@ -0,0 +1,277 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Flatten transparent images onto a background
+//!
+//! Formats like JPEG have no alpha channel, and encoders drop it, so transparent areas
+//! come out in whatever color their pixels happened to have, often black. Flattening
+//! draws the image over an opaque background and removes the alpha channel, RGBA becomes
+//! RGB and LumaA becomes Luma.
+//!
+//! The background is either
+//! - a solid color, white by default, or
+//! - a checkerboard, the usual way of showing transparency in previews
+//!
+//! Premultiplied images are handled, images without alpha are left unchanged.
+//!
+//! # Example
+//! - Flatten a half transparent image onto white before saving it as a JPEG
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::flatten::{Flatten, FlattenBackground};
+//!
+//! let mut image = Image::from_fn::<u8, _>(10, 10, ColorSpace::RGBA, |_, _, pix| {
+//!     pix[..4].copy_from_slice(&[0, 0, 0, 128]);
+//! });
+//! Flatten::new(FlattenBackground::Color([255, 255, 255]))
+//!     .execute(&mut image)
+//!     .unwrap();
+//!
+//! assert_eq!(image.colorspace(), ColorSpace::RGB);
+//! assert_eq!(image.flatten_to_u8()[0][..3], [127, 127, 127]);
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::metadata::AlphaState;
+use zune_image::traits::OperationsTrait;
+
+use crate::utils::{fill_color, normalized_samples, store_samples};
+
+/// What transparent pixels are drawn over
+///
+/// Colors are 8 bit RGB, scaled to the bit depth of the image
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub enum FlattenBackground {
+    /// A single color
+    Color([u8; 3]),
+    /// Squares of `size` pixels alternating between `light` and `dark`,
+    /// starting with `light` at the top left
+    Checkerboard {
+        size:  usize,
+        light: [u8; 3],
+        dark:  [u8; 3]
+    }
+}
+
+impl FlattenBackground {
+    /// A checkerboard of white and light gray squares of `size` pixels
+    #[must_use]
+    pub const fn checkerboard(size: usize) -> FlattenBackground {
+        FlattenBackground::Checkerboard {
+            size,
+            light: [255, 255, 255],
+            dark: [204, 204, 204]
+        }
+    }
+
+    /// Parse a background, either `checkerboard` for 8 pixel squares or a
+    /// hex color like `#ff8000` or `ff8000`
+    pub fn from_string_result(input: &str) -> Result<Self, String> {
+        if input == "checkerboard" {
+            return Ok(Self::checkerboard(8));
+        }
+        let hex = input.strip_prefix('#').unwrap_or(input);
+        let component = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|x| u8::from_str_radix(x, 16).ok())
+        };
+        match (hex.len(), component(0), component(2), component(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Self::Color([r, g, b])),
+            _ => Err(format!(
+                "Unknown background {input}, accepted values are checkerboard and hex colors like #ffffff"
+            ))
+        }
+    }
+
+    /// Return the light and dark colors of the background, the same for a solid color
+    const fn colors(&self) -> ([u8; 3], [u8; 3]) {
+        match *self {
+            FlattenBackground::Color(color) => (color, color),
+            FlattenBackground::Checkerboard { light, dark, .. } => (light, dark)
+        }
+    }
+
+    /// Return whether the background at `(x, y)` has the light color
+    const fn is_light(&self, x: usize, y: usize) -> bool {
+        match *self {
+            FlattenBackground::Color(_) => true,
+            FlattenBackground::Checkerboard { size, .. } => {
+                let size = if size == 0 { 1 } else { size };
+                (x / size + y / size).is_multiple_of(2)
+            }
+        }
+    }
+}
+
+/// Draw an image over an opaque background and remove its alpha channel
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Flatten {
+    background: FlattenBackground
+}
+
+impl Default for Flatten {
+    fn default() -> Self {
+        Flatten::new(FlattenBackground::Color([255, 255, 255]))
+    }
+}
+
+impl Flatten {
+    /// Create a new flatten operation
+    #[must_use]
+    pub const fn new(background: FlattenBackground) -> Flatten {
+        Flatten { background }
+    }
+}
+
+impl OperationsTrait for Flatten {
+    fn name(&self) -> &'static str {
+        "Flatten"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let colorspace = image.colorspace();
+        if !colorspace.has_alpha() {
+            return Ok(());
+        }
+        let out_colorspace =
+            if colorspace == ColorSpace::LumaA { ColorSpace::Luma } else { ColorSpace::RGB };
+        let (width, _) = image.dimensions();
+        let bit_type = image.depth().bit_type();
+        let premultiplied = image.metadata().is_premultiplied_alpha();
+
+        let to_channels = |[r, g, b]: [u8; 3]| fill_color([r, g, b, 255], out_colorspace);
+        let (light, dark) = self.background.colors();
+        let (light, dark) = (to_channels(light), to_channels(dark));
+
+        for frame in image.frames_mut() {
+            let channels = frame.channels_vec();
+            let Some(alpha) = channels.pop() else {
+                continue;
+            };
+            let alpha = normalized_samples(&alpha, bit_type, self.name())?;
+
+            for (c, channel) in channels.iter_mut().enumerate() {
+                let mut values = normalized_samples(channel, bit_type, self.name())?;
+
+                for (i, (value, alpha)) in values.iter_mut().zip(&alpha).enumerate() {
+                    let alpha = alpha.clamp(0.0, 1.0);
+                    let background = if self.background.is_light(i % width, i / width) {
+                        light[c]
+                    } else {
+                        dark[c]
+                    };
+                    let color = if premultiplied { *value } else { *value * alpha };
+                    *value = color + background * (1.0 - alpha);
+                }
+                store_samples(channel, bit_type, self.name(), &values)?;
+            }
+        }
+        image.metadata_mut().set_colorspace(out_colorspace);
+        image.metadata_mut().set_alpha(AlphaState::NonPreMultiplied);
+
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::Luma,
+            ColorSpace::LumaA
+        ]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::metadata::AlphaState;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::flatten::{Flatten, FlattenBackground};
+
+    #[test]
+    fn test_flatten_checkerboard() {
+        // opaque red in the top row, transparent below
+        let mut image = Image::from_fn::<u16, _>(4, 2, ColorSpace::RGBA, |y, _, pix| {
+            let alpha = if y == 0 { 65535 } else { 0 };
+            pix[..4].copy_from_slice(&[65535, 0, 0, alpha]);
+        });
+        let background = FlattenBackground::Checkerboard {
+            size:  1,
+            light: [255, 255, 255],
+            dark:  [0, 0, 255]
+        };
+        Flatten::new(background).execute(&mut image).unwrap();
+
+        assert_eq!(image.colorspace(), ColorSpace::RGB);
+        let pixels = &image.flatten_frames::<u16>()[0];
+        assert!(pixels[..12].chunks_exact(3).all(|x| x == [65535, 0, 0]));
+        // the second row starts on a dark square
+        assert_eq!(
+            pixels[12..],
+            [0, 0, 65535, 65535, 65535, 65535, 0, 0, 65535, 65535, 65535, 65535]
+        );
+    }
+
+    #[test]
+    fn test_flatten_luma_and_premultiplied() {
+        let mut image = Image::fill::<u8>(0, ColorSpace::LumaA, 2, 2);
+        Flatten::new(FlattenBackground::Color([255, 0, 0]))
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.colorspace(), ColorSpace::Luma);
+        // the luma of red
+        assert_eq!(image.flatten_to_u8()[0], [76; 4]);
+
+        // premultiplied white at half opacity
+        let mut image = Image::from_fn::<f32, _>(1, 1, ColorSpace::RGBA, |_, _, pix| {
+            pix[..4].copy_from_slice(&[0.5, 0.5, 0.5, 0.5]);
+        });
+        image.metadata_mut().set_alpha(AlphaState::PreMultiplied);
+        Flatten::new(FlattenBackground::Color([0, 0, 0]))
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.flatten_frames::<f32>()[0], [0.5; 3]);
+        assert!(!image.metadata().is_premultiplied_alpha());
+
+        // nothing to do without alpha
+        let mut image = Image::fill::<u8>(10, ColorSpace::RGB, 2, 2);
+        Flatten::default().execute(&mut image).unwrap();
+        assert_eq!(image.flatten_to_u8()[0], [10; 12]);
+    }
+
+    #[test]
+    fn test_background_from_string() {
+        assert_eq!(
+            FlattenBackground::from_string_result("#ff8001"),
+            Ok(FlattenBackground::Color([255, 128, 1]))
+        );
+        assert_eq!(
+            FlattenBackground::from_string_result("00Ff00"),
+            Ok(FlattenBackground::Color([0, 255, 0]))
+        );
+        assert_eq!(
+            FlattenBackground::from_string_result("checkerboard"),
+            Ok(FlattenBackground::checkerboard(8))
+        );
+        assert!(FlattenBackground::from_string_result("#fff").is_err());
+        assert!(FlattenBackground::from_string_result("#ffé0").is_err());
+    }
+}
@@ -16,6 +16,7 @@ use zune_image::traits::OperationsTrait;
 use crate::utils::execute_on;
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub enum FlipDirection {
     /// Creates a horizontal mirror image by reflecting the pixels around the central y-axis
     ///```text
@@ -55,6 +56,7 @@ pub enum FlipDirection {
 }
 
 /// Flip an image to a certain direction
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Flip {
     flip_direction: FlipDirection
 }
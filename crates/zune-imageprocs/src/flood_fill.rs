@@ -0,0 +1,330 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Fill and select connected regions of similar color
+//!
+//! Starting from a seed pixel, a region grows into neighbouring pixels as long as their
+//! color is within a tolerance of the seed color, the way the bucket fill and magic wand
+//! tools of image editors work.
+//!
+//! [`FloodFill`] paints the region with a new color, [`select_region`] returns it
+//! as a mask for use with other operations.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::flood_fill::{select_region, Connectivity, FloodFill};
+//!
+//! // a white image split by a black vertical line
+//! let mut image = Image::from_fn::<u8, _>(100, 100, ColorSpace::RGB, |_, x, pix| {
+//!     pix[..3].fill(if x == 50 { 0 } else { 255 });
+//! });
+//! // only the left half is selected
+//! let mask = select_region(&image, 10, 10, 0.1, Connectivity::Four).unwrap();
+//! assert_eq!(mask.flatten_to_u8()[0][..100].iter().filter(|x| **x == 255).count(), 50);
+//!
+//! // paint it red
+//! FloodFill::new(10, 10, 0.1, [255, 0, 0, 255]).execute(&mut image).unwrap();
+//! ```
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_core::colorspace::ColorSpace;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::frame::Frame;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::{fill_color, normalized_samples, to_sample};
+
+const SUPPORTED_COLORSPACES: [ColorSpace; 4] = [
+    ColorSpace::Luma,
+    ColorSpace::LumaA,
+    ColorSpace::RGB,
+    ColorSpace::RGBA
+];
+
+/// Which neighbours of a pixel a region grows into
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub enum Connectivity {
+    /// The pixels to the left, right, top and bottom
+    #[default]
+    Four,
+    /// The four direct neighbours and the four diagonal ones
+    Eight
+}
+
+/// Fill the region connected to a seed pixel with a color
+///
+/// A pixel belongs to the region if it is connected to the seed through pixels of
+/// the region and none of its channels, alpha included, differs from the seed
+/// pixel by more than `tolerance`, a fraction of the largest value of the image depth.
+/// A tolerance of 0.0 only fills pixels exactly equal to the seed.
+///
+/// The color is given as 8 bit RGBA, grayscale images are filled with its luma,
+/// alpha is only used by images with an alpha channel. Every frame is filled
+/// from the same seed position.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct FloodFill {
+    x:            usize,
+    y:            usize,
+    tolerance:    f32,
+    color:        [u8; 4],
+    connectivity: Connectivity
+}
+
+impl FloodFill {
+    /// Create a new flood fill painting the region around `(x, y)` with `color`
+    ///
+    /// The region grows to four neighbours by default
+    #[must_use]
+    pub fn new(x: usize, y: usize, tolerance: f32, color: [u8; 4]) -> FloodFill {
+        FloodFill {
+            x,
+            y,
+            tolerance,
+            color,
+            connectivity: Connectivity::Four
+        }
+    }
+    /// Set which neighbours of a pixel the region grows into
+    #[must_use]
+    pub fn set_connectivity(mut self, connectivity: Connectivity) -> Self {
+        self.connectivity = connectivity;
+        self
+    }
+}
+
+impl OperationsTrait for FloodFill {
+    fn name(&self) -> &'static str {
+        "Flood Fill"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let colorspace = image.colorspace();
+        let bit_type = image.depth().bit_type();
+        let color = fill_color(self.color, colorspace);
+
+        for frame in image.frames_mut() {
+            let region = frame_region(
+                frame,
+                colorspace,
+                bit_type,
+                (width, height),
+                (self.x, self.y),
+                self.tolerance,
+                self.connectivity
+            )?;
+
+            for (channel, value) in frame.channels_mut(colorspace, false).iter_mut().zip(&color) {
+                match bit_type {
+                    BitType::U8 => fill(channel.reinterpret_as_mut::<u8>()?, &region, *value),
+                    BitType::U16 => fill(channel.reinterpret_as_mut::<u16>()?, &region, *value),
+                    BitType::F32 => fill(channel.reinterpret_as_mut::<f32>()?, &region, *value),
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &SUPPORTED_COLORSPACES
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Return a mask of the region connected to the pixel at `(x, y)`
+///
+/// The region is grown as in [`FloodFill`]. The mask is an 8 bit Luma image with the
+/// dimensions and number of frames of `image`, pixels in the region are 255,
+/// everything else is 0.
+///
+/// # Errors
+/// If the seed is outside the image, or the image colorspace or depth
+/// is not supported
+pub fn select_region(
+    image: &Image, x: usize, y: usize, tolerance: f32, connectivity: Connectivity
+) -> Result<Image, ImageErrors> {
+    let colorspace = image.colorspace();
+    let bit_type = image.depth().bit_type();
+
+    if !SUPPORTED_COLORSPACES.contains(&colorspace) {
+        return Err(ImageErrors::UnsupportedColorspace(
+            colorspace,
+            "Select Region",
+            &SUPPORTED_COLORSPACES
+        ));
+    }
+    let (width, height) = image.dimensions();
+    let mut frames = Vec::with_capacity(image.frames_len());
+
+    for frame in image.frames_ref() {
+        let region = frame_region(
+            frame,
+            colorspace,
+            bit_type,
+            (width, height),
+            (x, y),
+            tolerance,
+            connectivity
+        )?;
+        let mut mask = Channel::new_with_length::<u8>(width * height);
+        fill(mask.reinterpret_as_mut::<u8>()?, &region, 1.0);
+
+        frames.push(Frame::new(vec![mask]));
+    }
+    Ok(Image::new_frames(
+        frames,
+        BitDepth::Eight,
+        width,
+        height,
+        ColorSpace::Luma
+    ))
+}
+
+/// Set the pixels of `channel` in `region` to `value`, a fraction of the largest value of `T`
+fn fill<T>(channel: &mut [T], region: &[bool], value: f32)
+where
+    T: Copy + NumOps<T>
+{
+    let value = to_sample(value * T::max_val().to_f32());
+
+    for (pixel, selected) in channel.iter_mut().zip(region) {
+        if *selected {
+            *pixel = value;
+        }
+    }
+}
+
+/// Find the region of a frame connected to `seed`
+fn frame_region(
+    frame: &Frame, colorspace: ColorSpace, bit_type: BitType, (width, height): (usize, usize),
+    (x, y): (usize, usize), tolerance: f32, connectivity: Connectivity
+) -> Result<Vec<bool>, ImageErrors> {
+    if x >= width || y >= height {
+        return Err(ImageErrors::GenericStr(
+            "Flood fill seed is outside the image"
+        ));
+    }
+    let channels = frame
+        .channels_ref(colorspace, false)
+        .iter()
+        .map(|x| normalized_samples(x, bit_type, "Flood Fill"))
+        .collect::<Result<Vec<_>, ImageErrors>>()?;
+
+    let seed = y * width + x;
+    let seed_color: Vec<f32> = channels.iter().map(|c| c[seed]).collect();
+    let similar = |i: usize| {
+        channels
+            .iter()
+            .zip(&seed_color)
+            .all(|(c, s)| (c[i] - s).abs() <= tolerance)
+    };
+
+    let mut region = vec![false; width * height];
+    let mut stack = vec![(x, y)];
+    region[seed] = true;
+
+    while let Some((x, y)) = stack.pop() {
+        let (x0, y0) = (x.saturating_sub(1), y.saturating_sub(1));
+        let (x1, y1) = ((x + 1).min(width - 1), (y + 1).min(height - 1));
+
+        for ny in y0..=y1 {
+            for nx in x0..=x1 {
+                let diagonal = nx != x && ny != y;
+                if diagonal && connectivity == Connectivity::Four {
+                    continue;
+                }
+                let i = ny * width + nx;
+                if !region[i] && similar(i) {
+                    region[i] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+    }
+    Ok(region)
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::flood_fill::{select_region, Connectivity, FloodFill};
+
+    fn selected(mask: &Image) -> usize {
+        mask.flatten_to_u8()[0]
+            .iter()
+            .map(|x| usize::from(*x == 255))
+            .sum()
+    }
+
+    /// A 5x5 image with a diagonal line of black pixels on white
+    fn diagonal() -> Image {
+        Image::from_fn::<u8, _>(5, 5, ColorSpace::Luma, |y, x, pix| {
+            pix[0] = if x == y { 0 } else { 255 };
+        })
+    }
+
+    #[test]
+    fn test_select_region_connectivity() {
+        let image = diagonal();
+
+        let four = select_region(&image, 4, 0, 0.0, Connectivity::Four).unwrap();
+        let eight = select_region(&image, 4, 0, 0.0, Connectivity::Eight).unwrap();
+        // the line blocks four connected regions, eight connected ones pass between its pixels
+        assert_eq!(selected(&four), 10);
+        assert_eq!(selected(&eight), 20);
+
+        let line = select_region(&image, 0, 0, 0.0, Connectivity::Four).unwrap();
+        assert_eq!(selected(&line), 1);
+        let line = select_region(&image, 0, 0, 0.0, Connectivity::Eight).unwrap();
+        assert_eq!(selected(&line), 5);
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn test_flood_fill_tolerance() {
+        // a gradient, darker to the right
+        let mut image = Image::from_fn::<u16, _>(10, 2, ColorSpace::RGB, |_, x, pix| {
+            pix[..3].fill(65535 - (x as u16) * 6553);
+        });
+        FloodFill::new(0, 0, 0.25, [255, 0, 0, 255])
+            .execute(&mut image)
+            .unwrap();
+
+        let pixels = &image.flatten_to_u8()[0];
+        for x in 0..10 {
+            let [r, g, b] = pixels[x * 3..x * 3 + 3] else {
+                unreachable!()
+            };
+            assert_eq!((r, g, b) == (255, 0, 0), x <= 2, "{x}");
+            assert_eq!(r == g && g == b, x > 2, "{x}");
+        }
+    }
+
+    #[test]
+    fn test_flood_fill_errors() {
+        let mut image = diagonal();
+        assert!(FloodFill::new(5, 0, 0.0, [0; 4])
+            .execute(&mut image)
+            .is_err());
+        assert!(select_region(&image, 0, 5, 0.0, Connectivity::Four).is_err());
+    }
+}
@@ -37,6 +37,7 @@ use crate::utils::execute_on;
 ///
 /// This operation is internally multithreaded, where supported
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Gamma {
     value: f32
 }
@@ -26,6 +26,7 @@ use zune_image::traits::OperationsTrait;
 use crate::transpose;
 
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct GaussianBlur {
     sigma: f32
 }
@@ -199,8 +200,8 @@ fn create_box_gauss(sigma: f32) -> [usize; 3] {
             }
         }
     }
-    // convert even to odd if there exists such values
-    return radii.map(|c| if (c % 2) == 0 { c + 1 } else { c });
+    // the above are odd box widths, convert them to the radii the box blurs take
+    return radii.map(|c| c / 2);
 }
 
 /// Carry out a gaussian blur on bytes that represent a single image channel
@@ -0,0 +1,221 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Recolor images through a gradient
+//!
+//! A gradient map replaces every pixel with the color a gradient has at the pixel's
+//! luminance, shadows take the colors of the start of the gradient and highlights those
+//! of its end. Gradients of two colors give duotone images, three colors give tritones.
+//!
+//! # Example
+//! - A blue and orange duotone
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::gradient_map::GradientMap;
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+//! GradientMap::duotone([20, 30, 90], [255, 160, 60])
+//!     .execute(&mut image)
+//!     .unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::utils::{normalized_samples, to_channels};
+
+/// A color at a position of a gradient
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct GradientStop {
+    /// Position in the gradient between 0.0, the darkest, and 1.0, the brightest pixels
+    pub position: f32,
+    /// 8 bit RGB color at the position
+    pub color:    [u8; 3]
+}
+
+impl GradientStop {
+    /// Create a new gradient stop
+    #[must_use]
+    pub fn new(position: f32, color: [u8; 3]) -> GradientStop {
+        GradientStop { position, color }
+    }
+}
+
+/// Map the luminance of an image through a gradient
+///
+/// The gradient is interpolated linearly between its stops, luminance below the first
+/// or above the last stop takes the color of that stop. Stops don't need to be sorted.
+///
+/// Grayscale images become RGB, alpha is kept, the bit depth is unchanged.
+/// The gradient must have at least two stops.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct GradientMap {
+    stops: Vec<GradientStop>
+}
+
+impl GradientMap {
+    /// Create a new gradient map from its stops
+    #[must_use]
+    pub fn new(stops: Vec<GradientStop>) -> GradientMap {
+        GradientMap { stops }
+    }
+    /// Create a gradient map from `shadows` to `highlights`
+    #[must_use]
+    pub fn duotone(shadows: [u8; 3], highlights: [u8; 3]) -> GradientMap {
+        GradientMap::new(vec![
+            GradientStop::new(0.0, shadows),
+            GradientStop::new(1.0, highlights),
+        ])
+    }
+    /// Create a gradient map from `shadows` through `midtones` to `highlights`
+    #[must_use]
+    pub fn tritone(shadows: [u8; 3], midtones: [u8; 3], highlights: [u8; 3]) -> GradientMap {
+        GradientMap::new(vec![
+            GradientStop::new(0.0, shadows),
+            GradientStop::new(0.5, midtones),
+            GradientStop::new(1.0, highlights),
+        ])
+    }
+    /// Return the color of the gradient at `position` as red, green and blue
+    /// components between 0.0 and 1.0
+    ///
+    /// A gradient without stops is black
+    #[must_use]
+    pub fn sample(&self, position: f32) -> [f32; 3] {
+        let mut stops = self.stops.clone();
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        sample(&stops, position)
+    }
+}
+
+/// Return the color at `position` of a gradient with sorted stops
+fn sample(stops: &[GradientStop], position: f32) -> [f32; 3] {
+    let color = |stop: &GradientStop| stop.color.map(|x| f32::from(x) / 255.0);
+
+    let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+        return [0.0; 3];
+    };
+    if position.is_nan() || position <= first.position {
+        return color(first);
+    }
+    if position >= last.position {
+        return color(last);
+    }
+    // the first stop after position, there's one since position is below the last
+    let next = stops.partition_point(|x| x.position <= position);
+    let (a, b) = (&stops[next - 1], &stops[next]);
+
+    let t = (position - a.position) / (b.position - a.position);
+    let (from, to) = (color(a), color(b));
+
+    [
+        from[0] + (to[0] - from[0]) * t,
+        from[1] + (to[1] - from[1]) * t,
+        from[2] + (to[2] - from[2]) * t
+    ]
+}
+
+impl OperationsTrait for GradientMap {
+    fn name(&self) -> &'static str {
+        "Gradient Map"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if self.stops.len() < 2 {
+            return Err(ImageErrors::GenericStr(
+                "A gradient map needs at least two stops"
+            ));
+        }
+        let mut stops = self.stops.clone();
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+
+        let colorspace =
+            if image.colorspace().has_alpha() { ColorSpace::RGBA } else { ColorSpace::RGB };
+        image.convert_color(colorspace)?;
+        let bit_type = image.depth().bit_type();
+        let components = colorspace.num_components();
+
+        for frame in image.frames_mut() {
+            let channels = frame
+                .channels_ref(colorspace, false)
+                .iter()
+                .map(|x| normalized_samples(x, bit_type, self.name()))
+                .collect::<Result<Vec<_>, ImageErrors>>()?;
+
+            let mut pixels = vec![0.0; channels[0].len() * components];
+
+            for (i, pixel) in pixels.chunks_exact_mut(components).enumerate() {
+                let luma = 0.299 * channels[0][i] + 0.587 * channels[1][i] + 0.114 * channels[2][i];
+
+                pixel[..3].copy_from_slice(&sample(&stops, luma));
+                if components == 4 {
+                    pixel[3] = channels[3][i];
+                }
+            }
+            frame.set_channels(to_channels(&pixels, bit_type, colorspace));
+        }
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::gradient_map::{GradientMap, GradientStop};
+
+    #[test]
+    fn test_gradient_map_duotone() {
+        let mut image = Image::from_fn::<u8, _>(3, 1, ColorSpace::LumaA, |_, x, pix| {
+            pix[0] = [0, 102, 255][x];
+            pix[1] = 77;
+        });
+        GradientMap::duotone([0, 0, 200], [255, 200, 0])
+            .execute(&mut image)
+            .unwrap();
+
+        assert_eq!(image.colorspace(), ColorSpace::RGBA);
+        assert_eq!(
+            image.flatten_to_u8()[0],
+            [0, 0, 200, 77, 102, 80, 120, 77, 255, 200, 0, 77]
+        );
+    }
+
+    #[test]
+    fn test_gradient_map_stops() {
+        // unsorted, with flat ends
+        let map = GradientMap::new(vec![
+            GradientStop::new(0.75, [255, 255, 255]),
+            GradientStop::new(0.25, [0, 0, 0]),
+            GradientStop::new(0.5, [255, 0, 0]),
+        ]);
+        let close = |a: [f32; 3], b: [f32; 3]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-6);
+
+        assert!(close(map.sample(0.0), [0.0; 3]));
+        assert!(close(map.sample(0.5), [1.0, 0.0, 0.0]));
+        assert!(close(map.sample(0.625), [1.0, 0.5, 0.5]));
+        assert!(close(map.sample(1.0), [1.0; 3]));
+
+        let mut image = Image::fill::<u16>(1000, ColorSpace::RGB, 4, 4);
+        let single = GradientMap::new(vec![GradientStop::new(0.0, [0; 3])]);
+        assert!(single.execute(&mut image).is_err());
+    }
+}
@@ -0,0 +1,310 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Add film grain to an image
+//!
+//! Grain brings back some texture to images that look too smooth after denoising
+//! or upscaling, or imitates the look of film.
+//!
+//! # Algorithm details
+//! A noise field is generated for every frame, with a standard deviation of one, and
+//! added to the pixels scaled by the intensity.
+//!
+//! - [`GrainNoise::Gaussian`] noise is white noise, blurred with a sigma of half the grain
+//!   size when the size is larger than a pixel
+//! - [`GrainNoise::Perlin`] noise is gradient noise with features of about the grain size,
+//!   which gives softer, more organic clumps
+//!
+//! Monochrome grain adds the same noise to every color channel, chroma grain
+//! uses a different field for each channel, giving colored speckles.
+//!
+//! Noise values are derived from a hash of the seed, the frame, the channel and the
+//! pixel position, so the same seed always gives the same output regardless of threads
+//! or of the order operations run in.
+use zune_core::bit_depth::BitType;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::gaussian_blur::gaussian_blur_f32;
+use crate::traits::NumOps;
+use crate::utils::to_sample;
+
+/// Kind of noise used for grain
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub enum GrainNoise {
+    /// Gaussian white noise, blurred to the grain size
+    #[default]
+    Gaussian,
+    /// Perlin gradient noise at the grain size
+    Perlin
+}
+
+/// Add grain to an image
+///
+/// Alpha is ignored.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::grain::{Grain, GrainNoise};
+///
+/// let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+/// Grain::new(0.05)
+///     .set_size(2.0)
+///     .set_noise(GrainNoise::Perlin)
+///     .set_seed(42)
+///     .execute(&mut image)
+///     .unwrap();
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grain {
+    intensity:  f32,
+    size:       f32,
+    monochrome: bool,
+    noise:      GrainNoise,
+    seed:       u64
+}
+
+impl Grain {
+    /// Create a new grain operation
+    ///
+    /// # Arguments
+    /// - intensity: Standard deviation of the grain, where 1.0 is the maximum value of the
+    ///   bit depth, values around 0.02 to 0.1 are typical
+    ///
+    /// The grain is monochrome Gaussian noise with a size of one pixel and a seed of zero
+    /// by default
+    #[must_use]
+    pub fn new(intensity: f32) -> Grain {
+        Grain {
+            intensity:  intensity.max(0.0),
+            size:       1.0,
+            monochrome: true,
+            noise:      GrainNoise::default(),
+            seed:       0
+        }
+    }
+    /// Set the size of grain particles in pixels, values below 1.0 are treated as 1.0
+    #[must_use]
+    pub fn set_size(mut self, size: f32) -> Self {
+        self.size = size.max(1.0);
+        self
+    }
+    /// Set whether the same grain is added to every color channel, or
+    /// a different one to each channel
+    #[must_use]
+    pub fn set_monochrome(mut self, monochrome: bool) -> Self {
+        self.monochrome = monochrome;
+        self
+    }
+    /// Set the kind of noise used
+    #[must_use]
+    pub fn set_noise(mut self, noise: GrainNoise) -> Self {
+        self.noise = noise;
+        self
+    }
+    /// Set the seed of the noise, the same seed always gives the same grain
+    #[must_use]
+    pub fn set_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Return a noise field with a mean of zero and a standard deviation of one
+    #[allow(clippy::cast_precision_loss)]
+    fn field(&self, width: usize, height: usize, stream: u64) -> Vec<f32> {
+        let seed = hash(self.seed ^ hash(stream));
+
+        let mut field: Vec<f32> = match self.noise {
+            GrainNoise::Gaussian => {
+                let mut field: Vec<f32> = (0..width * height)
+                    .map(|i| gaussian(hash(seed ^ i as u64)))
+                    .collect();
+                if self.size > 1.0 && width > 1 && height > 1 {
+                    let mut scratch = vec![0.0; field.len()];
+                    gaussian_blur_f32(&mut field, &mut scratch, width, height, self.size * 0.5);
+                }
+                field
+            }
+            GrainNoise::Perlin => {
+                let scale = 1.0 / self.size;
+                (0..height)
+                    .flat_map(|y| {
+                        (0..width).map(move |x| {
+                            perlin((x as f32 + 0.5) * scale, (y as f32 + 0.5) * scale, seed)
+                        })
+                    })
+                    .collect()
+            }
+        };
+        let count = field.len().max(1) as f32;
+        let mean = field.iter().sum::<f32>() / count;
+        let variance = field.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / count;
+        let scale = if variance > 0.0 { variance.sqrt().recip() } else { 0.0 };
+
+        for x in &mut field {
+            *x = (*x - mean) * scale;
+        }
+        field
+    }
+}
+
+impl OperationsTrait for Grain {
+    fn name(&self) -> &'static str {
+        "Grain"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let colorspace = image.colorspace();
+        let depth = image.depth().bit_type();
+
+        for (frame_index, frame) in image.frames_mut().iter_mut().enumerate() {
+            let mut field = None;
+
+            for (channel_index, channel) in
+                frame.channels_mut(colorspace, true).iter_mut().enumerate()
+            {
+                let stream = ((frame_index as u64) << 8) | channel_index as u64;
+                let noise: &[f32] = if self.monochrome {
+                    field.get_or_insert_with(|| self.field(width, height, stream))
+                } else {
+                    field.insert(self.field(width, height, stream))
+                };
+                match depth {
+                    BitType::U8 => {
+                        add_noise::<u8>(channel.reinterpret_as_mut()?, noise, self.intensity);
+                    }
+                    BitType::U16 => {
+                        add_noise::<u16>(channel.reinterpret_as_mut()?, noise, self.intensity);
+                    }
+                    BitType::F32 => {
+                        add_noise::<f32>(channel.reinterpret_as_mut()?, noise, self.intensity);
+                    }
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+fn add_noise<T: Copy + NumOps<T>>(channel: &mut [T], noise: &[f32], intensity: f32) {
+    let max = T::max_val().to_f32();
+    let amount = intensity * max;
+
+    for (out, noise) in channel.iter_mut().zip(noise) {
+        let value = out.to_f32() + noise * amount;
+        *out = to_sample(value);
+    }
+}
+
+/// Mix the bits of `value`, SplitMix64's finalizer
+fn hash(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Return a normally distributed value from random bits, using the Box-Muller transform
+#[allow(clippy::cast_precision_loss)]
+fn gaussian(bits: u64) -> f32 {
+    // two uniform values from the two halves, u1 is never zero
+    let u1 = ((bits >> 40) as f32 + 1.0) / (1_u64 << 24) as f32;
+    let u2 = ((bits >> 8) & 0xFF_FFFF) as f32 / (1_u64 << 24) as f32;
+
+    (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos()
+}
+
+/// Two dimensional Perlin gradient noise
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_wrap
+)]
+fn perlin(x: f32, y: f32, seed: u64) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as i64, y0 as i64);
+
+    let corner = |cx: i64, cy: i64| {
+        let bits = hash(seed ^ hash(((cx as u64) << 32) ^ (cy as u64 & 0xFFFF_FFFF)));
+        let angle = (bits >> 40) as f32 / (1_u64 << 24) as f32 * std::f32::consts::TAU;
+        let (dx, dy) = (x - cx as f32, y - cy as f32);
+        angle.cos() * dx + angle.sin() * dy
+    };
+    let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let (u, v) = (fade(fx), fade(fy));
+
+    let top = corner(x0, y0) + (corner(x0 + 1, y0) - corner(x0, y0)) * u;
+    let bottom = corner(x0, y0 + 1) + (corner(x0 + 1, y0 + 1) - corner(x0, y0 + 1)) * u;
+
+    top + (bottom - top) * v
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::grain::{Grain, GrainNoise};
+
+    fn grainy(grain: Grain) -> Vec<u8> {
+        let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 32, 32);
+        grain.execute(&mut image).unwrap();
+        image.flatten_to_u8()[0].clone()
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn test_grain_deterministic() {
+        for noise in [GrainNoise::Gaussian, GrainNoise::Perlin] {
+            let grain = Grain::new(0.05).set_size(2.0).set_noise(noise).set_seed(7);
+
+            let first = grainy(grain);
+            assert_eq!(first, grainy(grain));
+            assert_ne!(first, grainy(grain.set_seed(8)));
+
+            // the standard deviation follows the intensity
+            let mean = first.iter().map(|x| f32::from(*x)).sum::<f32>() / first.len() as f32;
+            let variance = first
+                .iter()
+                .map(|x| (f32::from(*x) - mean).powi(2))
+                .sum::<f32>()
+                / first.len() as f32;
+            let expected = 0.05 * 255.0;
+            assert!(
+                (variance.sqrt() - expected).abs() < 1.5,
+                "{noise:?} {}",
+                variance.sqrt()
+            );
+        }
+    }
+
+    #[test]
+    fn test_monochrome_and_chroma_grain() {
+        let mono = grainy(Grain::new(0.1));
+        assert!(mono.chunks_exact(3).all(|x| x[0] == x[1] && x[1] == x[2]));
+
+        let chroma = grainy(Grain::new(0.1).set_monochrome(false));
+        assert!(chroma.chunks_exact(3).any(|x| x[0] != x[1]));
+
+        assert!(grainy(Grain::new(0.0)).iter().all(|x| *x == 128));
+    }
+}
@@ -0,0 +1,380 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Halftone and ordered dither effects
+//!
+//! Both effects render an image with only a few values per channel, arranged in regular
+//! patterns whose density follows the tones of the image, giving a printed or retro look.
+//!
+//! - [`Halftone`] simulates the dots of offset and newspaper print. Every channel is
+//!   reduced to black and white dots on a grid of cells, rotated by the screen angle,
+//!   the dots grow with the darkness of the image until they merge into a checkerboard at 50%
+//!   and leave white holes beyond.
+//! - [`OrderedDither`] reduces every channel to a number of levels with a Bayer
+//!   threshold matrix, which gives the cross hatched pattern of early computer graphics.
+//!
+//! Unlike the dithering done when [reducing the bit depth](zune_image::core_filters::depth::Depth)
+//! of an image, the depth is kept, these effects are meant to be seen.
+//!
+//! Alpha channels are kept as is.
+//!
+//! # Example
+//! - Render an image as a newspaper print, with dots every six pixels
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::halftone::{Halftone, OrderedDither};
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::Luma, 100, 100);
+//! Halftone::new(6.0).execute(&mut image).unwrap();
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+//! OrderedDither::new(4, 2).execute(&mut image).unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::utils::{normalized_samples, store_samples};
+
+/// Width and height of the threshold array of a halftone cell
+const SCREEN_SIZE: usize = 64;
+
+/// Angle in degrees between the screens of consecutive color channels
+const CHANNEL_ANGLE: f32 = 30.0;
+
+/// Simulate printed halftone dots
+///
+/// Color channels are screened as inks, each at an angle 30 degrees from the previous one,
+/// as in print, which keeps the screens from interfering into moiré patterns.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Halftone {
+    cell_size: f32,
+    angle:     f32
+}
+
+impl Halftone {
+    /// Create a new halftone effect
+    ///
+    /// # Arguments
+    /// - cell_size: The distance in pixels between the centers of neighbouring dots,
+    ///   at least 1.0
+    ///
+    /// The screen angle is 45 degrees, the usual angle of black ink
+    #[must_use]
+    pub fn new(cell_size: f32) -> Halftone {
+        Halftone {
+            cell_size,
+            angle: 45.0
+        }
+    }
+    /// Set the angle in degrees of the grid of dots of the first channel
+    #[must_use]
+    pub fn set_angle(mut self, angle: f32) -> Self {
+        self.angle = angle;
+        self
+    }
+}
+
+impl OperationsTrait for Halftone {
+    fn name(&self) -> &'static str {
+        "Halftone"
+    }
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, _) = image.dimensions();
+        let colorspace = image.colorspace();
+        let bit_type = image.depth().bit_type();
+        let cell_size = self.cell_size.max(1.0);
+        let screen = dot_screen();
+
+        for frame in image.frames_mut() {
+            for (i, channel) in frame.channels_mut(colorspace, true).iter_mut().enumerate() {
+                let mut values = normalized_samples(channel, bit_type, self.name())?;
+
+                let (sin, cos) = (self.angle + CHANNEL_ANGLE * i as f32)
+                    .to_radians()
+                    .sin_cos();
+
+                for (y, row) in values.chunks_exact_mut(width).enumerate() {
+                    for (x, value) in row.iter_mut().enumerate() {
+                        // position of the pixel center in the rotated grid of cells
+                        let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                        let u = (px * cos + py * sin) / cell_size;
+                        let v = (py * cos - px * sin) / cell_size;
+
+                        let sx = ((u - u.floor()) * SCREEN_SIZE as f32) as usize;
+                        let sy = ((v - v.floor()) * SCREEN_SIZE as f32) as usize;
+                        let threshold =
+                            screen[sy.min(SCREEN_SIZE - 1) * SCREEN_SIZE + sx.min(SCREEN_SIZE - 1)];
+
+                        let ink = 1.0 - *value;
+                        *value = if ink > threshold { 0.0 } else { 1.0 };
+                    }
+                }
+                store_samples(channel, bit_type, self.name(), &values)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::Luma,
+            ColorSpace::LumaA
+        ]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Return the threshold array of a round dot cell, ink covers the positions whose
+/// threshold is below the darkness of a pixel
+///
+/// Positions are ranked with the euclidean spot function, round dots grow from the cell
+/// center and white holes shrink towards its corners, the thresholds are the ranks
+/// so that the area covered by ink is proportional to darkness
+#[allow(clippy::cast_precision_loss)]
+fn dot_screen() -> Vec<f32> {
+    let area = SCREEN_SIZE * SCREEN_SIZE;
+    let position = |i: usize| ((i as f32 + 0.5) / SCREEN_SIZE as f32 * 2.0 - 1.0).abs();
+
+    let spots: Vec<f32> = (0..area)
+        .map(|i| {
+            let (u, v) = (position(i % SCREEN_SIZE), position(i / SCREEN_SIZE));
+            if u + v <= 1.0 {
+                u * u + v * v - 1.0
+            } else {
+                1.0 - (1.0 - u).powi(2) - (1.0 - v).powi(2)
+            }
+        })
+        .collect();
+
+    let mut order: Vec<usize> = (0..area).collect();
+    order.sort_by(|a, b| spots[*a].total_cmp(&spots[*b]));
+
+    let mut screen = vec![0.0; area];
+    for (rank, index) in order.iter().enumerate() {
+        screen[*index] = (rank as f32 + 0.5) / area as f32;
+    }
+    screen
+}
+
+/// Reduce every channel to a few levels with a Bayer matrix
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrderedDither {
+    matrix_size: usize,
+    levels:      u16,
+    cell_size:   usize
+}
+
+impl OrderedDither {
+    /// Create a new ordered dither effect
+    ///
+    /// # Arguments
+    /// - matrix_size: Width and height of the Bayer matrix, one of 2, 4, 8 or 16,
+    ///   larger matrices can render more tones between two levels
+    /// - levels: Number of levels per channel, including black and white, minimum is 2
+    #[must_use]
+    pub fn new(matrix_size: usize, levels: u16) -> OrderedDither {
+        OrderedDither {
+            matrix_size,
+            levels,
+            cell_size: 1
+        }
+    }
+    /// Set the width and height in pixels of every entry of the matrix, larger cells
+    /// give a chunky, pixelated pattern
+    ///
+    /// Default is 1
+    #[must_use]
+    pub fn set_cell_size(mut self, cell_size: usize) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+}
+
+impl OperationsTrait for OrderedDither {
+    fn name(&self) -> &'static str {
+        "Ordered Dither"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if !matches!(self.matrix_size, 2 | 4 | 8 | 16) {
+            return Err(ImageErrors::GenericStr(
+                "Ordered dither matrix size must be one of 2, 4, 8 or 16"
+            ));
+        }
+        let (width, _) = image.dimensions();
+        let colorspace = image.colorspace();
+        let bit_type = image.depth().bit_type();
+
+        let size = self.matrix_size;
+        let cell_size = self.cell_size.max(1);
+        let steps = f32::from(self.levels.max(2) - 1);
+        let matrix = bayer_matrix(size);
+
+        for frame in image.frames_mut() {
+            for channel in frame.channels_mut(colorspace, true) {
+                let mut values = normalized_samples(channel, bit_type, self.name())?;
+
+                for (y, row) in values.chunks_exact_mut(width).enumerate() {
+                    let thresholds = &matrix[(y / cell_size) % size * size..][..size];
+
+                    for (x, value) in row.iter_mut().enumerate() {
+                        let threshold = thresholds[(x / cell_size) % size];
+                        *value = ((*value * steps + threshold).floor() / steps).clamp(0.0, 1.0);
+                    }
+                }
+                store_samples(channel, bit_type, self.name(), &values)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Return a Bayer matrix of `size`, a power of two, with thresholds between 0.0 and 1.0
+#[allow(clippy::cast_precision_loss)]
+fn bayer_matrix(size: usize) -> Vec<f32> {
+    // each step tiles the previous matrix four times, as 4*m, 4*m+2, 4*m+3 and 4*m+1
+    let mut matrix = vec![0_usize];
+    let mut current = 1;
+
+    while current < size {
+        let next = current * 2;
+        let mut larger = vec![0; next * next];
+
+        for y in 0..next {
+            for x in 0..next {
+                let base = 4 * matrix[(y % current) * current + x % current];
+                larger[y * next + x] = base
+                    + match (x / current, y / current) {
+                        (0, 0) => 0,
+                        (1, 0) => 2,
+                        (0, _) => 3,
+                        _ => 1
+                    };
+            }
+        }
+        matrix = larger;
+        current = next;
+    }
+    let area = (size * size) as f32;
+    // offsets between 0 and 1 so that exact levels are unchanged
+    matrix.iter().map(|x| (*x as f32 + 0.5) / area).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::halftone::{bayer_matrix, Halftone, OrderedDither};
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn test_halftone_coverage() {
+        for (gray, angle) in [(64_u8, 45.0), (128, 30.0), (200, 15.0)] {
+            let mut image = Image::fill::<u8>(gray, ColorSpace::LumaA, 96, 96);
+            Halftone::new(8.0)
+                .set_angle(angle)
+                .execute(&mut image)
+                .unwrap();
+
+            let pixels = &image.flatten_to_u8()[0];
+            assert!(pixels.chunks_exact(2).all(|x| x[1] == gray));
+            assert!(pixels.chunks_exact(2).all(|x| x[0] == 0 || x[0] == 255));
+
+            let white = pixels.chunks_exact(2).filter(|x| x[0] == 255).count();
+            let coverage = white as f32 / (96.0 * 96.0);
+            let expected = f32::from(gray) / 255.0;
+            assert!((coverage - expected).abs() < 0.03, "{gray} {coverage}");
+        }
+        // the dots are round and centered in their cell
+        let mut image = Image::fill::<u8>(230, ColorSpace::Luma, 32, 32);
+        Halftone::new(16.0)
+            .set_angle(0.0)
+            .execute(&mut image)
+            .unwrap();
+        let pixels = &image.flatten_to_u8()[0];
+        assert_eq!(pixels[8 * 32 + 8], 0);
+        assert_eq!(pixels[0], 255);
+        assert_eq!(pixels[8 * 32 + 3], 255);
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn test_bayer_matrix() {
+        let matrix: Vec<usize> = bayer_matrix(4)
+            .iter()
+            .map(|x| (x * 16.0) as usize)
+            .collect();
+        assert_eq!(
+            matrix,
+            [0, 8, 2, 10, 12, 4, 14, 6, 3, 11, 1, 9, 15, 7, 13, 5]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    fn test_ordered_dither() {
+        // a horizontal gradient
+        let mut image = Image::from_fn::<u16, _>(64, 8, ColorSpace::RGB, |_, x, pix| {
+            pix[..3].fill((x * 1040) as u16);
+        });
+        OrderedDither::new(8, 2).execute(&mut image).unwrap();
+
+        let pixels = &image.flatten_frames::<u16>()[0];
+        assert!(pixels.iter().all(|x| *x == 0 || *x == 65535));
+        // the density of white pixels in every 8x8 block follows the gradient
+        for block in 0..8 {
+            let white = (0..8)
+                .flat_map(|y| (0..8).map(move |x| (y, block * 8 + x)))
+                .filter(|(y, x)| pixels[(y * 64 + x) * 3] == 65535)
+                .count();
+            let expected = (block * 8..block * 8 + 8)
+                .map(|x| (x * 1040) as f32 / 65535.0)
+                .sum::<f32>();
+            assert!(
+                (white as f32 - expected * 8.0).abs() <= 2.0,
+                "{block} {white}"
+            );
+        }
+
+        // cells repeat every matrix entry over 2x2 pixels
+        let mut image = Image::fill::<u8>(100, ColorSpace::Luma, 16, 16);
+        OrderedDither::new(4, 4)
+            .set_cell_size(2)
+            .execute(&mut image)
+            .unwrap();
+        let pixels = &image.flatten_to_u8()[0];
+        assert!(pixels.iter().all(|x| [0, 85, 170, 255].contains(x)));
+        assert!(pixels.chunks_exact(2).all(|x| x[0] == x[1]));
+
+        assert!(OrderedDither::new(3, 2).execute(&mut image).is_err());
+    }
+}
@@ -0,0 +1,262 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! High-pass filter and frequency separation
+//!
+//! A high-pass filter keeps the fine details of an image, it subtracts a gaussian blurred
+//! copy from the image and adds an offset, mid gray by default, so that negative details
+//! can be stored
+//!
+//! ```text
+//! high = original - blurred + offset
+//! ```
+//!
+//! Frequency separation splits an image into two layers, the blurred copy holding the
+//! tones and colors (low frequencies) and the high-pass holding the texture (high frequencies).
+//! Each can be retouched on its own, e.g. evening out skin on the low layer while keeping pores
+//! on the high one, before [recombining](HighPass::recombine) them.
+//!
+//! Alpha channels are kept as is
+//!
+//! # Example
+//! - Smooth the tones of an image while keeping its texture
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::gaussian_blur::GaussianBlur;
+//! use zune_imageprocs::high_pass::HighPass;
+//!
+//! let image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+//! let high_pass = HighPass::new(4.0);
+//!
+//! let (mut low, high) = high_pass.split(&image).unwrap();
+//! GaussianBlur::new(8.0).execute(&mut low).unwrap();
+//! let retouched = high_pass.recombine(&low, &high).unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::gaussian_blur::gaussian_blur_f32;
+use crate::utils::{normalized_samples, store_samples};
+
+/// Keep the details of an image smaller than a gaussian blur removes
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct HighPass {
+    sigma:  f32,
+    offset: f32
+}
+
+impl HighPass {
+    /// Create a new high-pass filter
+    ///
+    /// # Arguments
+    /// - sigma: This value is passed to the gaussian filter,consult [it's documentation](crate::gaussian_blur::GaussianBlur)
+    ///   on how to use it, details smaller than it are kept
+    ///
+    /// The offset is 0.5, mid gray
+    #[must_use]
+    pub fn new(sigma: f32) -> HighPass {
+        HighPass { sigma, offset: 0.5 }
+    }
+    /// Set the value, between 0.0 and 1.0, added to the difference between the image
+    /// and its blurred copy
+    #[must_use]
+    pub fn set_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Split an image into its low frequencies, a blurred copy, and its high frequencies,
+    /// the high-pass of the image
+    ///
+    /// Returns `(low, high)`, both with the dimensions, colorspace and depth of the image.
+    /// The high layer is computed from the low layer as stored, so that
+    /// [`recombine`](Self::recombine) gives back the image exactly unless details
+    /// were clamped, which can only happen for integer depths
+    pub fn split(&self, image: &Image) -> Result<(Image, Image), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let colorspace = image.colorspace();
+        let bit_type = image.depth().bit_type();
+        let offset = self.stored_offset(bit_type);
+
+        let mut low = image.clone();
+        let mut high = image.clone();
+
+        for (low_frame, high_frame) in low.frames_mut().iter_mut().zip(high.frames_mut()) {
+            let low_channels = low_frame.channels_mut(colorspace, true);
+            let high_channels = high_frame.channels_mut(colorspace, true);
+
+            for (low_channel, high_channel) in low_channels.iter_mut().zip(high_channels) {
+                let mut values = normalized_samples(high_channel, bit_type, self.name())?;
+
+                let mut blurred = values.clone();
+                let mut scratch = vec![0.0; values.len()];
+                gaussian_blur_f32(&mut blurred, &mut scratch, width, height, self.sigma);
+                store_samples(low_channel, bit_type, self.name(), &blurred)?;
+
+                let blurred = normalized_samples(low_channel, bit_type, self.name())?;
+                values
+                    .iter_mut()
+                    .zip(&blurred)
+                    .for_each(|(v, b)| *v = *v - b + offset);
+                store_samples(high_channel, bit_type, self.name(), &values)?;
+            }
+        }
+        Ok((low, high))
+    }
+
+    /// Recombine low and high frequency layers made by [`split`](Self::split)
+    ///
+    /// The layers must have the same dimensions, colorspace, depth and number of frames,
+    /// alpha is taken from the low layer
+    pub fn recombine(&self, low: &Image, high: &Image) -> Result<Image, ImageErrors> {
+        let colorspace = low.colorspace();
+        let bit_type = low.depth().bit_type();
+
+        if low.dimensions() != high.dimensions()
+            || colorspace != high.colorspace()
+            || low.depth() != high.depth()
+            || low.frames_len() != high.frames_len()
+        {
+            return Err(ImageErrors::GenericStr(
+                "Frequency layers must have the same dimensions, colorspace, depth and frames"
+            ));
+        }
+        let offset = self.stored_offset(bit_type);
+        let mut image = low.clone();
+
+        for (frame, high_frame) in image.frames_mut().iter_mut().zip(high.frames_ref()) {
+            let high_channels = high_frame.channels_ref(colorspace, true);
+
+            for (channel, high_channel) in frame
+                .channels_mut(colorspace, true)
+                .iter_mut()
+                .zip(high_channels)
+            {
+                let mut values = normalized_samples(channel, bit_type, self.name())?;
+                let details = normalized_samples(high_channel, bit_type, self.name())?;
+
+                values
+                    .iter_mut()
+                    .zip(&details)
+                    .for_each(|(v, d)| *v += d - offset);
+                store_samples(channel, bit_type, self.name(), &values)?;
+            }
+        }
+        Ok(image)
+    }
+
+    /// Return the offset rounded to a value integer depths can store, which keeps
+    /// splitting and recombining free of rounding errors
+    fn stored_offset(self, bit_type: BitType) -> f32 {
+        let max = match bit_type {
+            BitType::U8 => f32::from(u8::MAX),
+            BitType::U16 => f32::from(u16::MAX),
+            _ => return self.offset
+        };
+        (self.offset * max).round() / max
+    }
+}
+
+impl OperationsTrait for HighPass {
+    fn name(&self) -> &'static str {
+        "High Pass"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let colorspace = image.colorspace();
+        let bit_type = image.depth().bit_type();
+        let offset = self.stored_offset(bit_type);
+
+        for frame in image.frames_mut() {
+            for channel in frame.channels_mut(colorspace, true) {
+                let mut values = normalized_samples(channel, bit_type, self.name())?;
+
+                let mut blurred = values.clone();
+                let mut scratch = vec![0.0; values.len()];
+                gaussian_blur_f32(&mut blurred, &mut scratch, width, height, self.sigma);
+
+                values
+                    .iter_mut()
+                    .zip(&blurred)
+                    .for_each(|(v, b)| *v = *v - b + offset);
+                store_samples(channel, bit_type, self.name(), &values)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::high_pass::HighPass;
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn textured(width: usize, height: usize) -> Image {
+        Image::from_fn::<u8, _>(width, height, ColorSpace::RGBA, |y, x, pix| {
+            pix[0] = ((x * 37 + y * 11) % 128 + 64) as u8;
+            pix[1] = ((x * x + y) % 100 + 80) as u8;
+            pix[2] = if (x / 4 + y / 4) % 2 == 0 { 200 } else { 50 };
+            pix[3] = 200;
+        })
+    }
+
+    #[test]
+    fn test_high_pass_of_flat_image_is_offset() {
+        let mut image = Image::fill::<u16>(1000, ColorSpace::LumaA, 20, 20);
+        HighPass::new(2.0).execute(&mut image).unwrap();
+
+        let pixels = &image.flatten_frames::<u16>()[0];
+        assert!(pixels.chunks_exact(2).all(|x| x == [32768, 1000]));
+
+        let mut image = Image::fill::<f32>(0.2, ColorSpace::Luma, 20, 20);
+        HighPass::new(2.0)
+            .set_offset(0.0)
+            .execute(&mut image)
+            .unwrap();
+        assert!(image.flatten_frames::<f32>()[0]
+            .iter()
+            .all(|x| x.abs() < 1e-5));
+    }
+
+    #[test]
+    fn test_split_and_recombine() {
+        let image = textured(32, 24);
+        let high_pass = HighPass::new(3.0);
+
+        let (low, high) = high_pass.split(&image).unwrap();
+        let high_pixels = &high.flatten_to_u8()[0];
+        // details average to mid gray, alpha is kept
+        let mean = high_pixels
+            .chunks_exact(4)
+            .map(|x| f32::from(x[0]))
+            .sum::<f32>()
+            / (32.0 * 24.0);
+        assert!((mean - 128.0).abs() < 4.0, "{mean}");
+        assert!(high_pixels.chunks_exact(4).all(|x| x[3] == 200));
+
+        let recombined = high_pass.recombine(&low, &high).unwrap();
+        assert_eq!(recombined.flatten_to_u8(), image.flatten_to_u8());
+
+        let other = Image::fill::<u8>(0, ColorSpace::RGBA, 10, 10);
+        assert!(high_pass.recombine(&low, &other).is_err());
+    }
+}
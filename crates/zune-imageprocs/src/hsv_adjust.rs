@@ -49,6 +49,7 @@ use crate::traits::NumOps;
 ///
 ///# Ok::<(),ImageErrors>(())
 /// ```
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct HsvAdjust {
     hue:        f32,
     saturation: f32,
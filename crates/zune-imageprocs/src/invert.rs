@@ -33,6 +33,7 @@ use crate::utils::execute_on;
 /// (255 for [`u8`],65535 for [`u16`], 1 for [`f32`])
 ///
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Invert;
 
 impl Invert {
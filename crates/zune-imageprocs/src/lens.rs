@@ -0,0 +1,423 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Lens corrections
+//!
+//! Parametric corrections for the common defects of wide angle lenses,
+//! e.g. on drone and action camera footage.
+//!
+//! - [`LensDistortion`]: Radial (barrel and pincushion) distortion
+//! - [`Vignette`]: Darkening of the image towards the corners
+//! - [`ChromaticAberration`]: Lateral chromatic aberration, color fringes towards the corners
+//!
+//! All corrections are relative to the image center and use a radius normalized so that
+//! the center is at `0.0` and the corners are at `1.0`, so the same parameters can be
+//! used for every image of a camera independent of its resolution.
+//!
+//! Since they are all applied on the same radius, chaining them in the order
+//! distortion, chromatic aberration and vignette is usually what is wanted.
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::{execute_on, to_sample};
+
+/// Correct radial lens distortion
+///
+/// Every pixel at a normalized radius `r` is taken from the radius
+/// `r * (1 + k1 * r^2 + k2 * r^4)` of the input, with bilinear interpolation.
+///
+/// Negative values of `k1` correct barrel distortion (straight lines bowing outwards),
+/// positive values correct pincushion distortion. `k2` refines the correction towards the corners.
+///
+/// Pixels mapped from outside the image are set to zero.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::lens::LensDistortion;
+///
+/// let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+/// LensDistortion::new(-0.1, 0.01).execute(&mut image).unwrap();
+/// ```
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct LensDistortion {
+    k1: f32,
+    k2: f32
+}
+
+impl LensDistortion {
+    /// Create a new distortion correction with the given coefficients
+    #[must_use]
+    pub fn new(k1: f32, k2: f32) -> LensDistortion {
+        LensDistortion { k1, k2 }
+    }
+}
+
+impl OperationsTrait for LensDistortion {
+    fn name(&self) -> &'static str {
+        "Lens Distortion"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let depth = image.depth().bit_type();
+
+        let scale = |r2: f32| 1.0 + self.k1 * r2 + self.k2 * r2 * r2;
+
+        let correct_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
+            *channel = remap_channel(channel, depth, width, height, scale, self.name())?;
+            Ok(())
+        };
+        // alpha is moved with the pixels
+        execute_on(correct_fn, image, false)
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Compensate vignetting
+///
+/// The lens is modeled to darken a pixel at a normalized radius `r` by
+/// `1 + k1 * r^2 + k2 * r^4`, the correction divides by that factor.
+///
+/// Negative values of `k1` brighten the corners, e.g. `-0.3` undoes a lens that
+/// loses 30% of the light in the corners. Alpha is not modified.
+///
+/// For integer images values are clamped to the maximum of the bit depth.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::lens::Vignette;
+///
+/// let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+/// Vignette::new(-0.3, 0.0).execute(&mut image).unwrap();
+/// ```
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vignette {
+    k1: f32,
+    k2: f32
+}
+
+impl Vignette {
+    /// Create a new vignette compensation with the given coefficients
+    #[must_use]
+    pub fn new(k1: f32, k2: f32) -> Vignette {
+        Vignette { k1, k2 }
+    }
+}
+
+impl OperationsTrait for Vignette {
+    fn name(&self) -> &'static str {
+        "Vignette"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let depth = image.depth().bit_type();
+
+        // keep the divisor positive for parameters that would black out the corners
+        let gain = |r2: f32| 1.0 / (1.0 + self.k1 * r2 + self.k2 * r2 * r2).max(0.01);
+
+        let correct_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
+            match depth {
+                BitType::U8 => vignette::<u8>(channel.reinterpret_as_mut()?, width, height, gain),
+                BitType::U16 => {
+                    vignette::<u16>(channel.reinterpret_as_mut()?, width, height, gain);
+                }
+                BitType::F32 => {
+                    vignette::<f32>(channel.reinterpret_as_mut()?, width, height, gain);
+                }
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+            Ok(())
+        };
+        execute_on(correct_fn, image, true)
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Correct lateral chromatic aberration
+///
+/// Lenses focus red and blue light at slightly different magnifications than green,
+/// which shows up as color fringes growing towards the corners.
+/// This scales the red and blue channels around the image center to line them up
+/// with the green channel, a scale of `1.0` leaves a channel untouched.
+///
+/// Use a scale above `1.0` for a channel whose fringe lies towards the center of the image
+/// and below `1.0` for one whose fringe lies towards the edges, the needed correction
+/// is usually within 0.1% to 0.5%.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::lens::ChromaticAberration;
+///
+/// let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+/// ChromaticAberration::new(1.002, 0.998).execute(&mut image).unwrap();
+/// ```
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChromaticAberration {
+    red:  f32,
+    blue: f32
+}
+
+impl ChromaticAberration {
+    /// Create a new chromatic aberration correction
+    ///
+    /// # Arguments
+    /// - red: Scale of the red channel relative to green
+    /// - blue: Scale of the blue channel relative to green
+    #[must_use]
+    pub fn new(red: f32, blue: f32) -> ChromaticAberration {
+        ChromaticAberration { red, blue }
+    }
+}
+
+impl OperationsTrait for ChromaticAberration {
+    fn name(&self) -> &'static str {
+        "Chromatic Aberration"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let depth = image.depth().bit_type();
+        let colorspace = image.colorspace();
+
+        let (red_index, blue_index) = match colorspace {
+            ColorSpace::BGR | ColorSpace::BGRA => (2, 0),
+            _ => (0, 2)
+        };
+
+        for frame in image.frames_mut() {
+            let channels = frame.channels_mut(colorspace, true);
+
+            for (index, scale) in [(red_index, self.red), (blue_index, self.blue)] {
+                if (scale - 1.0).abs() < f32::EPSILON {
+                    continue;
+                }
+                // sample from the scaled position, so divide to move the channel by `scale`
+                channels[index] = remap_channel(
+                    &channels[index],
+                    depth,
+                    width,
+                    height,
+                    |_| 1.0 / scale,
+                    self.name()
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::BGR,
+            ColorSpace::BGRA
+        ]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Return the center of the image and the factor normalizing
+/// squared distances from it so that the corners are at `1.0`
+#[allow(clippy::cast_precision_loss)]
+fn center(width: usize, height: usize) -> (f32, f32, f32) {
+    let center_x = (width as f32 - 1.0) / 2.0;
+    let center_y = (height as f32 - 1.0) / 2.0;
+    let corner = center_x * center_x + center_y * center_y;
+
+    (
+        center_x,
+        center_y,
+        if corner > 0.0 { 1.0 / corner } else { 0.0 }
+    )
+}
+
+/// Create a new channel where every pixel is sampled from the input at its
+/// distance from the center multiplied by `scale(r^2)`
+fn remap_channel(
+    channel: &Channel, depth: BitType, width: usize, height: usize, scale: impl Fn(f32) -> f32,
+    name: &'static str
+) -> Result<Channel, ImageErrors> {
+    let mut new_channel = Channel::new_with_length_and_type(channel.len(), channel.type_id());
+
+    match depth {
+        BitType::U8 => remap::<u8>(
+            channel.reinterpret_as()?,
+            new_channel.reinterpret_as_mut()?,
+            width,
+            height,
+            scale
+        ),
+        BitType::U16 => remap::<u16>(
+            channel.reinterpret_as()?,
+            new_channel.reinterpret_as_mut()?,
+            width,
+            height,
+            scale
+        ),
+        BitType::F32 => remap::<f32>(
+            channel.reinterpret_as()?,
+            new_channel.reinterpret_as_mut()?,
+            width,
+            height,
+            scale
+        ),
+        d => return Err(ImageErrors::ImageOperationNotImplemented(name, d))
+    }
+    Ok(new_channel)
+}
+
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn remap<T>(input: &[T], output: &mut [T], width: usize, height: usize, scale: impl Fn(f32) -> f32)
+where
+    T: Copy + NumOps<T>
+{
+    let (center_x, center_y, norm) = center(width, height);
+    let (max_x, max_y) = (width as f32 - 1.0, height as f32 - 1.0);
+
+    for (y, out_row) in output.chunks_exact_mut(width).enumerate() {
+        let dy = y as f32 - center_y;
+
+        for (x, out) in out_row.iter_mut().enumerate() {
+            let dx = x as f32 - center_x;
+            let factor = scale((dx * dx + dy * dy) * norm);
+
+            let src_x = center_x + dx * factor;
+            let src_y = center_y + dy * factor;
+
+            if !(0.0..=max_x).contains(&src_x) || !(0.0..=max_y).contains(&src_y) {
+                *out = T::from_u8(0);
+                continue;
+            }
+            // bilinear interpolation of the four neighbours
+            let (x0, y0) = (src_x as usize, src_y as usize);
+            let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+            let (fx, fy) = (src_x - x0 as f32, src_y - y0 as f32);
+
+            let sample = |x: usize, y: usize| input[y * width + x].to_f64() as f32;
+
+            let top = sample(x0, y0) * (1.0 - fx) + sample(x1, y0) * fx;
+            let bottom = sample(x0, y1) * (1.0 - fx) + sample(x1, y1) * fx;
+            let value = top * (1.0 - fy) + bottom * fy;
+
+            *out = to_sample(value);
+        }
+    }
+}
+
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn vignette<T>(channel: &mut [T], width: usize, height: usize, gain: impl Fn(f32) -> f32)
+where
+    T: Copy + NumOps<T>
+{
+    let (center_x, center_y, norm) = center(width, height);
+
+    for (y, row) in channel.chunks_exact_mut(width).enumerate() {
+        let dy = y as f32 - center_y;
+
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let dx = x as f32 - center_x;
+            let value = pixel.to_f64() as f32 * gain((dx * dx + dy * dy) * norm);
+
+            *pixel = to_sample(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::lens::{ChromaticAberration, LensDistortion, Vignette};
+
+    #[test]
+    fn test_distortion_identity_and_barrel() {
+        let image = Image::from_fn::<u16, _>(21, 21, ColorSpace::RGBA, |y, x, pix| {
+            pix.fill(u16::try_from(y * 21 + x).unwrap());
+        });
+        let mut identity = image.clone();
+        LensDistortion::new(0.0, 0.0)
+            .execute(&mut identity)
+            .unwrap();
+        assert!(identity == image);
+
+        let mut corrected = image.clone();
+        LensDistortion::new(-0.2, 0.0)
+            .execute(&mut corrected)
+            .unwrap();
+        let pixels = &corrected.flatten_frames::<u16>()[0];
+        // the center doesn't move, the corner is pulled in from inside the image
+        assert_eq!(pixels[(10 * 21 + 10) * 4], 10 * 21 + 10);
+        assert_ne!(pixels[0], 0);
+
+        let mut pincushion = image;
+        LensDistortion::new(0.2, 0.0)
+            .execute(&mut pincushion)
+            .unwrap();
+        // the corner maps to outside the image
+        assert_eq!(pincushion.flatten_frames::<u16>()[0][0], 0);
+    }
+
+    #[test]
+    fn test_vignette() {
+        let mut image = Image::fill::<u8>(100, ColorSpace::LumaA, 11, 11);
+        Vignette::new(-0.5, 0.0).execute(&mut image).unwrap();
+
+        let pixels = &image.flatten_to_u8()[0];
+        let center = (5 * 11 + 5) * 2;
+        assert_eq!(pixels[center], 100);
+        assert_eq!(pixels[0], 200);
+        // alpha is untouched
+        assert_eq!(pixels[1], 100);
+    }
+
+    #[test]
+    fn test_chromatic_aberration() {
+        // a vertical line two pixels right of the center
+        let mut image = Image::from_fn::<u8, _>(11, 11, ColorSpace::RGB, |_, x, pix| {
+            pix[..3].fill(if x == 7 { 255 } else { 0 });
+        });
+        ChromaticAberration::new(1.5, 1.0)
+            .execute(&mut image)
+            .unwrap();
+
+        let row = &image.flatten_to_u8()[0][5 * 11 * 3..6 * 11 * 3];
+        // red moved out by 50%, green and blue stayed in place
+        assert_eq!(&row[8 * 3..8 * 3 + 3], &[255, 0, 0]);
+        assert_eq!(&row[7 * 3 + 1..7 * 3 + 3], &[255, 255]);
+    }
+}
@@ -25,6 +25,10 @@
 //! // execute the filter
 //! exposure.execute(&mut image).unwrap();
 //! ```
+//!
+//! # Features
+//! - `serde-support`: Serialize and deserialize operations, and create them by name
+//!   with the [`registry`]
 
 // Benchmark support needs sse
 #![cfg_attr(feature = "benchmarks", feature(test))]
@@ -48,40 +52,87 @@
     clippy::wildcard_imports
 )]
 
+pub use utils::Gravity;
 pub use zune_image;
 
+pub mod ascii_art;
+pub mod auto_exposure;
 pub mod auto_orient;
+pub mod barcode_preprocess;
 pub mod bilateral_filter;
 pub mod blend;
+pub mod border;
 pub mod box_blur;
 pub mod brighten;
+pub mod canvas;
+pub mod chroma_key;
 pub mod color_matrix;
+pub mod colormap;
 pub mod composite;
 pub mod contrast;
 pub mod convolve;
 pub mod crop;
+pub mod curves;
+pub mod depth_blur;
+pub mod diff;
 pub mod exposure;
+pub mod flatten;
 pub mod flip;
+pub mod flood_fill;
+mod font;
 pub mod gamma;
 pub mod gaussian_blur;
+pub mod gradient_map;
+pub mod grain;
+pub mod halftone;
+pub mod high_pass;
 pub mod histogram;
 pub mod hsv_adjust;
 pub mod invert;
+pub mod kuwahara;
+pub mod lens;
+pub mod lut3d;
+pub mod mask;
 pub mod mathops;
+pub mod matting;
 pub mod median;
 pub mod mirror;
+pub mod montage;
+pub mod motion;
+pub mod motion_blur;
+pub mod nine_patch;
+pub mod oil_paint;
 pub mod pad;
+pub mod phash;
+pub mod pixelate;
+pub mod posterize;
 pub mod premul_alpha;
 mod prewitt;
+pub mod registration;
+pub mod registry;
 pub mod resize;
+pub mod retinex;
+pub mod roi;
 pub mod rotate;
 pub mod scharr;
+pub mod scopes;
+pub mod sepia;
+pub mod shadows_highlights;
+pub mod shape_mask;
+pub mod shift;
+pub mod smart_crop;
 pub mod sobel;
+pub mod solarize;
 pub mod spatial;
 pub mod spatial_ops;
+pub mod sprite_sheet;
+pub mod stitch;
 pub mod stretch_contrast;
+pub mod temperature;
 pub mod threshold;
 pub mod traits;
 pub mod transpose;
 pub mod unsharpen;
 mod utils;
+pub mod vibrance;
+pub mod watermark;
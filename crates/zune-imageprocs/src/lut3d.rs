@@ -0,0 +1,546 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Apply 3D color lookup tables
+//!
+//! A 3D lookup table (LUT) maps every RGB color to another one through a cube of
+//! `size x size x size` output colors, it is the common way of sharing color grading looks
+//! between applications.
+//!
+//! Tables are loaded from `.cube` files, the text format used by Adobe and DaVinci Resolve.
+//!
+//! # Algorithm details
+//! The input color is scaled to the cube and the output is interpolated from the
+//! surrounding entries, either with
+//! - trilinear interpolation, which mixes the eight corners of the cell containing the color, or
+//! - tetrahedral interpolation, which splits the cell into six tetrahedra and mixes the four
+//!   corners of the one containing the color. It is cheaper and preserves the gray axis better,
+//!   so it is the default
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::lut3d::{ApplyLut3d, Lut3d};
+//!
+//! // a 2x2x2 table that swaps red and blue
+//! let cube = "
+//! LUT_3D_SIZE 2
+//! 0 0 0
+//! 0 0 1
+//! 0 1 0
+//! 0 1 1
+//! 1 0 0
+//! 1 0 1
+//! 1 1 0
+//! 1 1 1
+//! ";
+//! let lut = Lut3d::from_cube(cube).unwrap();
+//!
+//! let mut image = Image::fill::<u8>(255, ColorSpace::RGB, 10, 10);
+//! ApplyLut3d::new(lut).execute(&mut image).unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::to_sample;
+
+/// Largest `LUT_3D_SIZE` accepted, the specification allows up to 256
+const MAX_LUT_SIZE: usize = 256;
+
+/// Interpolation used to look up colors between the entries of a table
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub enum LutInterpolation {
+    /// Mix the eight corners of the surrounding cell
+    Trilinear,
+    /// Mix the four corners of the surrounding tetrahedron
+    #[default]
+    Tetrahedral
+}
+
+impl LutInterpolation {
+    pub fn from_string_result(input: &str) -> Result<Self, String> {
+        match input {
+            "trilinear" => Ok(Self::Trilinear),
+            "tetrahedral" => Ok(Self::Tetrahedral),
+            _ => Err(
+                "Unknown interpolation type,accepted values are trilinear,tetrahedral".to_string()
+            )
+        }
+    }
+}
+
+/// A 3D color lookup table
+///
+/// Entries are stored with red changing fastest, then green, then blue,
+/// the same order as `.cube` files.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lut3d {
+    title:      Option<String>,
+    size:       usize,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    table:      Vec<[f32; 3]>
+}
+
+fn check_size(size: usize) -> Result<(), ImageErrors> {
+    if !(2..=MAX_LUT_SIZE).contains(&size) {
+        return Err(ImageErrors::GenericString(format!(
+            "LUT size should be between 2 and {MAX_LUT_SIZE}, found {size}"
+        )));
+    }
+    Ok(())
+}
+
+impl Lut3d {
+    /// Create a table from its entries
+    ///
+    /// # Arguments
+    /// - size: Number of entries along every axis of the cube
+    /// - table: `size * size * size` output colors, red changing fastest
+    ///
+    /// # Errors
+    /// If `size` is below 2 or the number of entries doesn't match it
+    pub fn new(size: usize, table: Vec<[f32; 3]>) -> Result<Lut3d, ImageErrors> {
+        check_size(size)?;
+        if table.len() != size * size * size {
+            return Err(ImageErrors::GenericString(format!(
+                "A LUT of size {size} expects {} entries, found {}",
+                size * size * size,
+                table.len()
+            )));
+        }
+        Ok(Lut3d {
+            title: None,
+            size,
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+            table
+        })
+    }
+    /// Create a table that doesn't change colors
+    ///
+    /// # Errors
+    /// If `size` is below 2 or too large
+    #[allow(clippy::cast_precision_loss)]
+    pub fn identity(size: usize) -> Result<Lut3d, ImageErrors> {
+        // before allocating, size * size * size can overflow
+        check_size(size)?;
+
+        let step = 1.0 / (size - 1) as f32;
+        let mut table = Vec::with_capacity(size * size * size);
+
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    table.push([r as f32 * step, g as f32 * step, b as f32 * step]);
+                }
+            }
+        }
+        Lut3d::new(size, table)
+    }
+    /// Parse the contents of a `.cube` file
+    ///
+    /// `TITLE`, `LUT_3D_SIZE`, `DOMAIN_MIN`, `DOMAIN_MAX` and Resolve's
+    /// `LUT_3D_INPUT_RANGE` keywords are understood, comments start with `#`.
+    ///
+    /// # Errors
+    /// If the file is malformed or only contains a 1D table
+    pub fn from_cube(contents: &str) -> Result<Lut3d, ImageErrors> {
+        let error = |line: usize, message: &str| {
+            ImageErrors::GenericString(format!("Invalid cube file, line {}: {message}", line + 1))
+        };
+        let parse_floats = |line: usize, values: &[&str]| -> Result<[f32; 3], ImageErrors> {
+            let mut out = [0.0; 3];
+            if values.len() != 3 {
+                return Err(error(line, "expected three values"));
+            }
+            for (o, v) in out.iter_mut().zip(values) {
+                *o = v
+                    .parse::<f32>()
+                    .map_err(|_| error(line, &format!("{v} is not a number")))?;
+            }
+            Ok(out)
+        };
+
+        let mut title = None;
+        let mut size = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut table = Vec::new();
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or_default();
+            let values: Vec<&str> = words.collect();
+
+            match keyword {
+                "TITLE" => {
+                    let text = line["TITLE".len()..].trim().trim_matches('"');
+                    title = Some(text.to_string());
+                }
+                "LUT_3D_SIZE" => {
+                    let [value] = values[..] else {
+                        return Err(error(number, "expected one value for LUT_3D_SIZE"));
+                    };
+                    let value = value
+                        .parse::<usize>()
+                        .map_err(|_| error(number, "LUT_3D_SIZE is not a number"))?;
+                    size = Some(value);
+                    // don't trust the size for huge allocations
+                    table.reserve(value.min(MAX_LUT_SIZE).pow(3));
+                }
+                "LUT_1D_SIZE" => {
+                    return Err(error(number, "1D tables are not supported"));
+                }
+                "DOMAIN_MIN" => domain_min = parse_floats(number, &values)?,
+                "DOMAIN_MAX" => domain_max = parse_floats(number, &values)?,
+                "LUT_3D_INPUT_RANGE" => {
+                    let [min, max] = values[..] else {
+                        return Err(error(number, "expected two values for LUT_3D_INPUT_RANGE"));
+                    };
+                    let range = parse_floats(number, &[min, max, "0"])?;
+                    domain_min = [range[0]; 3];
+                    domain_max = [range[1]; 3];
+                }
+                word if word.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+                    // unknown keywords, e.g. LUT_IN_VIDEO_RANGE, don't change the table
+                }
+                _ => {
+                    let mut entry = vec![keyword];
+                    entry.extend(values);
+                    table.push(parse_floats(number, &entry)?);
+                }
+            }
+        }
+        let Some(size) = size else {
+            return Err(ImageErrors::GenericStr(
+                "Invalid cube file, missing LUT_3D_SIZE"
+            ));
+        };
+        if domain_min
+            .iter()
+            .zip(&domain_max)
+            .any(|(min, max)| min >= max)
+        {
+            return Err(ImageErrors::GenericStr(
+                "Invalid cube file, DOMAIN_MIN should be less than DOMAIN_MAX"
+            ));
+        }
+        let mut lut = Lut3d::new(size, table)?;
+        lut.title = title;
+        lut.domain_min = domain_min;
+        lut.domain_max = domain_max;
+
+        Ok(lut)
+    }
+    /// Return the title of the table, if it has one
+    #[must_use]
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+    /// Return the number of entries along every axis of the cube
+    #[must_use]
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+    /// Look up a color
+    ///
+    /// Colors outside the domain of the table, usually 0.0 to 1.0, are clamped to it
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn sample(&self, color: [f32; 3], interpolation: LutInterpolation) -> [f32; 3] {
+        let max_index = (self.size - 1) as f32;
+        let mut cell = [0_usize; 3];
+        let mut fraction = [0.0_f32; 3];
+
+        for i in 0..3 {
+            let range = self.domain_max[i] - self.domain_min[i];
+            let position =
+                ((color[i] - self.domain_min[i]) / range * max_index).clamp(0.0, max_index);
+            // NaN ends up in the first cell
+            let base = (position.floor() as usize).min(self.size - 2);
+            cell[i] = base;
+            fraction[i] = if position.is_nan() { 0.0 } else { position - base as f32 };
+        }
+        let entry = |r: usize, g: usize, b: usize| -> [f32; 3] {
+            let index =
+                (cell[0] + r) + (cell[1] + g) * self.size + (cell[2] + b) * self.size * self.size;
+            self.table[index]
+        };
+        let [fr, fg, fb] = fraction;
+
+        let mut out = [0.0; 3];
+        match interpolation {
+            LutInterpolation::Trilinear => {
+                let weights = [
+                    ((0, 0, 0), (1.0 - fr) * (1.0 - fg) * (1.0 - fb)),
+                    ((1, 0, 0), fr * (1.0 - fg) * (1.0 - fb)),
+                    ((0, 1, 0), (1.0 - fr) * fg * (1.0 - fb)),
+                    ((1, 1, 0), fr * fg * (1.0 - fb)),
+                    ((0, 0, 1), (1.0 - fr) * (1.0 - fg) * fb),
+                    ((1, 0, 1), fr * (1.0 - fg) * fb),
+                    ((0, 1, 1), (1.0 - fr) * fg * fb),
+                    ((1, 1, 1), fr * fg * fb)
+                ];
+                for ((r, g, b), weight) in weights {
+                    let value = entry(r, g, b);
+                    for (o, v) in out.iter_mut().zip(value) {
+                        *o += v * weight;
+                    }
+                }
+            }
+            LutInterpolation::Tetrahedral => {
+                // walk from the black corner to the white corner of the cell along
+                // the axes in order of decreasing fraction
+                let (first, second, third) = if fr > fg {
+                    if fg > fb {
+                        ((1, 0, 0), (1, 1, 0), (fr, fg, fb))
+                    } else if fr > fb {
+                        ((1, 0, 0), (1, 0, 1), (fr, fb, fg))
+                    } else {
+                        ((0, 0, 1), (1, 0, 1), (fb, fr, fg))
+                    }
+                } else if fb > fg {
+                    ((0, 0, 1), (0, 1, 1), (fb, fg, fr))
+                } else if fb > fr {
+                    ((0, 1, 0), (0, 1, 1), (fg, fb, fr))
+                } else {
+                    ((0, 1, 0), (1, 1, 0), (fg, fr, fb))
+                };
+                let (d1, d2, d3) = third;
+                let c0 = entry(0, 0, 0);
+                let c1 = entry(first.0, first.1, first.2);
+                let c2 = entry(second.0, second.1, second.2);
+                let c3 = entry(1, 1, 1);
+
+                for i in 0..3 {
+                    out[i] =
+                        (1.0 - d1) * c0[i] + (d1 - d2) * c1[i] + (d2 - d3) * c2[i] + d3 * c3[i];
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Apply a 3D lookup table to an image
+///
+/// Grayscale images are converted to RGB, alpha is kept.
+#[derive(Clone, Debug)]
+pub struct ApplyLut3d {
+    lut:           Lut3d,
+    interpolation: LutInterpolation
+}
+
+impl ApplyLut3d {
+    /// Create a new operation applying `lut` with tetrahedral interpolation
+    #[must_use]
+    pub fn new(lut: Lut3d) -> ApplyLut3d {
+        ApplyLut3d {
+            lut,
+            interpolation: LutInterpolation::default()
+        }
+    }
+    /// Set the interpolation used between table entries
+    #[must_use]
+    pub fn set_interpolation(mut self, interpolation: LutInterpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+}
+
+impl OperationsTrait for ApplyLut3d {
+    fn name(&self) -> &'static str {
+        "Apply 3D LUT"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        match image.colorspace() {
+            ColorSpace::Luma => image.convert_color(ColorSpace::RGB)?,
+            ColorSpace::LumaA => image.convert_color(ColorSpace::RGBA)?,
+            _ => ()
+        }
+        let depth = image.depth().bit_type();
+
+        for frame in image.frames_mut() {
+            let [r, g, b, ..] = frame.channels_vec().as_mut_slice() else {
+                return Err(ImageErrors::GenericStr("Expected at least three channels"));
+            };
+            match depth {
+                BitType::U8 => apply_lut::<u8>(
+                    r.reinterpret_as_mut()?,
+                    g.reinterpret_as_mut()?,
+                    b.reinterpret_as_mut()?,
+                    &self.lut,
+                    self.interpolation
+                ),
+                BitType::U16 => apply_lut::<u16>(
+                    r.reinterpret_as_mut()?,
+                    g.reinterpret_as_mut()?,
+                    b.reinterpret_as_mut()?,
+                    &self.lut,
+                    self.interpolation
+                ),
+                BitType::F32 => apply_lut::<f32>(
+                    r.reinterpret_as_mut()?,
+                    g.reinterpret_as_mut()?,
+                    b.reinterpret_as_mut()?,
+                    &self.lut,
+                    self.interpolation
+                ),
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::LumaA,
+            ColorSpace::Luma
+        ]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+fn apply_lut<T>(r: &mut [T], g: &mut [T], b: &mut [T], lut: &Lut3d, interpolation: LutInterpolation)
+where
+    T: Copy + NumOps<T>
+{
+    let max = T::max_val().to_f32();
+
+    for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+        let color = [r.to_f32() / max, g.to_f32() / max, b.to_f32() / max];
+        let mapped = lut.sample(color, interpolation);
+
+        for (out, value) in [r, g, b].into_iter().zip(mapped) {
+            *out = to_sample(value * max);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::lut3d::{ApplyLut3d, Lut3d, LutInterpolation, MAX_LUT_SIZE};
+
+    fn close(a: [f32; 3], b: [f32; 3]) -> bool {
+        a.iter().zip(&b).all(|(x, y)| (x - y).abs() < 1e-5)
+    }
+
+    #[test]
+    fn test_parse_cube() {
+        let cube = "# made by hand
+TITLE \"Swap red and blue\"
+LUT_3D_SIZE 2
+DOMAIN_MIN 0.0 0.0 0.0
+DOMAIN_MAX 1.0 1.0 1.0
+
+0 0 0
+0 0 1
+0 1 0
+0 1 1
+1 0 0
+1 0 1
+1 1 0
+1 1 1
+";
+        let lut = Lut3d::from_cube(cube).unwrap();
+        assert_eq!(lut.title(), Some("Swap red and blue"));
+        assert_eq!(lut.size(), 2);
+
+        for interpolation in [LutInterpolation::Trilinear, LutInterpolation::Tetrahedral] {
+            let out = lut.sample([0.2, 0.5, 0.9], interpolation);
+            assert!(close(out, [0.9, 0.5, 0.2]), "{interpolation:?} {out:?}");
+        }
+        // entries missing
+        assert!(Lut3d::from_cube("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(Lut3d::from_cube("LUT_3D_SIZE 2\n0 0 zero\n").is_err());
+        assert!(Lut3d::from_cube("LUT_1D_SIZE 2\n0 0 0\n1 1 1\n").is_err());
+    }
+
+    #[test]
+    fn test_identity_interpolation() {
+        let lut = Lut3d::identity(5).unwrap();
+
+        for color in [
+            [0.1, 0.7, 0.3],
+            [0.95, 0.05, 0.5],
+            [0.3, 0.3, 0.3],
+            [1.0, 0.0, 1.0]
+        ] {
+            for interpolation in [LutInterpolation::Trilinear, LutInterpolation::Tetrahedral] {
+                assert!(close(lut.sample(color, interpolation), color));
+            }
+        }
+        // out of domain values are clamped
+        assert!(close(
+            lut.sample([-1.0, 2.0, 0.5], LutInterpolation::Tetrahedral),
+            [0.0, 1.0, 0.5]
+        ));
+    }
+
+    #[test]
+    fn test_identity_size_limits() {
+        assert!(Lut3d::identity(1).is_err());
+        assert!(Lut3d::identity(MAX_LUT_SIZE + 1).is_err());
+        // size cubed overflows, this used to panic in the allocation
+        assert!(Lut3d::identity(usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_apply_lut() {
+        let mut image = Image::from_fn::<u8, _>(2, 1, ColorSpace::RGBA, |_, x, pix| {
+            pix[..4].copy_from_slice(&[[10, 100, 200, 50], [255, 0, 128, 255]][x]);
+        });
+        let mut unchanged = image.clone();
+
+        ApplyLut3d::new(Lut3d::identity(17).unwrap())
+            .set_interpolation(LutInterpolation::Trilinear)
+            .execute(&mut unchanged)
+            .unwrap();
+        assert_eq!(unchanged.flatten_to_u8(), image.flatten_to_u8());
+
+        // a table that inverts colors
+        let mut invert = Lut3d::identity(2).unwrap();
+        invert
+            .table
+            .iter_mut()
+            .flatten()
+            .for_each(|x| *x = 1.0 - *x);
+        ApplyLut3d::new(invert).execute(&mut image).unwrap();
+        assert_eq!(
+            image.flatten_to_u8()[0],
+            [245, 155, 55, 50, 0, 255, 127, 255]
+        );
+    }
+}
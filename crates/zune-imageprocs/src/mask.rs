@@ -0,0 +1,241 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Apply an operation to parts of an image
+//!
+//! This restricts any operation to the pixels selected by a mask, turning
+//! filters into selective adjustments, e.g. blurring only the background of a portrait
+//! or brightening only a region picked with [`select_region`](crate::flood_fill::select_region).
+//!
+//! # Example
+//! - Blur the left half of an image
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::box_blur::BoxBlur;
+//! use zune_imageprocs::mask::Masked;
+//!
+//! let mut image = Image::from_fn::<u8, _>(100, 100, ColorSpace::RGB, |y, x, pix| {
+//!     pix[..3].fill(((x ^ y) & 1) as u8 * 255);
+//! });
+//! let mask = Image::from_fn::<u8, _>(100, 100, ColorSpace::Luma, |_, x, pix| {
+//!     pix[0] = if x < 50 { 255 } else { 0 };
+//! });
+//! let blur = BoxBlur::new(3);
+//! Masked::new(&blur, &mask).execute(&mut image).unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::metadata::AlphaState;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::{frame_luma, to_sample};
+
+/// Execute an operation only where a mask selects pixels
+///
+/// The mask is an image with the dimensions of the image the operation is executed on,
+/// its luminance is the amount of the operation applied to every pixel. Pixels where
+/// the mask is at or below the threshold are kept unchanged, above it the result of the
+/// operation is blended in, reaching the full result where the mask is at its maximum.
+/// Soft mask edges thus give smooth transitions.
+///
+/// The first frame of the mask is used for all frames of the image.
+///
+/// The wrapped operation must not change the dimensions of the image. If it changes the
+/// colorspace or depth, e.g. by adding an alpha channel, the unselected pixels are
+/// converted the same way.
+pub struct Masked<'src> {
+    operation: &'src dyn OperationsTrait,
+    mask:      &'src Image,
+    threshold: f32
+}
+
+impl<'src> Masked<'src> {
+    /// Create a new masked operation executing `operation` where `mask` is non-zero
+    #[must_use]
+    pub fn new(operation: &'src dyn OperationsTrait, mask: &'src Image) -> Masked<'src> {
+        Masked {
+            operation,
+            mask,
+            threshold: 0.0
+        }
+    }
+    /// Set the mask value, between 0.0 and 1.0, at and below which pixels are not modified
+    ///
+    /// Default is 0.0
+    #[must_use]
+    pub fn set_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+}
+
+impl OperationsTrait for Masked<'_> {
+    fn name(&self) -> &'static str {
+        "Masked"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if image.dimensions() != self.mask.dimensions() {
+            return Err(ImageErrors::GenericStr(
+                "Mask dimensions do not match the image"
+            ));
+        }
+        let weights: Vec<f32> = frame_luma(self.mask, 0)?
+            .iter()
+            .map(|x| {
+                if *x <= self.threshold {
+                    0.0
+                } else {
+                    ((x - self.threshold) / (1.0 - self.threshold)).min(1.0)
+                }
+            })
+            .collect();
+
+        let mut result = image.clone();
+        self.operation.execute(&mut result)?;
+
+        if result.dimensions() != image.dimensions() || result.frames_len() != image.frames_len() {
+            return Err(ImageErrors::GenericStr(
+                "Masked operations must not change the image dimensions"
+            ));
+        }
+        image.convert_color(result.colorspace())?;
+        image.convert_depth(result.depth())?;
+
+        let bit_type = image.depth().bit_type();
+
+        for (output, original) in result.frames_mut().iter_mut().zip(image.frames_ref()) {
+            for (output, original) in output
+                .channels_vec()
+                .iter_mut()
+                .zip(original.channels_vec_ref())
+            {
+                match bit_type {
+                    BitType::U8 => blend::<u8>(
+                        original.reinterpret_as()?,
+                        output.reinterpret_as_mut()?,
+                        &weights
+                    ),
+                    BitType::U16 => blend::<u16>(
+                        original.reinterpret_as()?,
+                        output.reinterpret_as_mut()?,
+                        &weights
+                    ),
+                    BitType::F32 => blend::<f32>(
+                        original.reinterpret_as()?,
+                        output.reinterpret_as_mut()?,
+                        &weights
+                    ),
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+                }
+            }
+        }
+        *image = result;
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        self.operation.supported_colorspaces()
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        self.operation.supported_types()
+    }
+
+    fn alpha_state(&self) -> AlphaState {
+        self.operation.alpha_state()
+    }
+}
+
+/// Blend `original` into `output` by `1 - weight`
+fn blend<T>(original: &[T], output: &mut [T], weights: &[f32])
+where
+    T: Copy + NumOps<T>
+{
+
+    for ((output, original), weight) in output.iter_mut().zip(original).zip(weights) {
+        if *weight < 1.0 {
+            let (a, b) = (original.to_f32(), output.to_f32());
+            let value = a + (b - a) * weight;
+
+            *output = to_sample(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::chroma_key::ChromaKey;
+    use crate::invert::Invert;
+    use crate::mask::Masked;
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn test_masked_blends() {
+        let mut image = Image::fill::<u8>(200, ColorSpace::Luma, 5, 1);
+        // a mask ramping from 0 to 255
+        let mask = Image::from_fn::<u8, _>(5, 1, ColorSpace::Luma, |_, x, pix| {
+            pix[0] = (x * 255 / 4) as u8;
+        });
+        Masked::new(&Invert::new(), &mask)
+            .execute(&mut image)
+            .unwrap();
+        // inverted to 55 where fully selected
+        assert_eq!(image.flatten_to_u8()[0], [200, 164, 128, 91, 55]);
+
+        let mut image = Image::fill::<u8>(200, ColorSpace::Luma, 5, 1);
+        Masked::new(&Invert::new(), &mask)
+            .set_threshold(0.5)
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.flatten_to_u8()[0], [200, 200, 200, 128, 55]);
+    }
+
+    #[test]
+    fn test_masked_colorspace_change() {
+        // the key only removes the selected half of a green image
+        let mut image = Image::fill::<u8>(0, ColorSpace::RGB, 4, 1);
+        image.frames_mut()[0].channels_vec()[1]
+            .fill(255_u8)
+            .unwrap();
+
+        let mask = Image::from_fn::<u8, _>(4, 1, ColorSpace::Luma, |_, x, pix| {
+            pix[0] = if x < 2 { 255 } else { 0 };
+        });
+        let key = ChromaKey::new([0, 255, 0]);
+        Masked::new(&key, &mask).execute(&mut image).unwrap();
+
+        assert_eq!(image.colorspace(), ColorSpace::RGBA);
+        let alpha: Vec<u8> = image.flatten_to_u8()[0]
+            .iter()
+            .skip(3)
+            .step_by(4)
+            .copied()
+            .collect();
+        assert_eq!(alpha, [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_masked_dimensions() {
+        let mut image = Image::fill::<u8>(0, ColorSpace::RGB, 4, 4);
+        let mask = Image::fill::<u8>(0, ColorSpace::Luma, 4, 5);
+        assert!(Masked::new(&Invert::new(), &mask)
+            .execute(&mut image)
+            .is_err());
+    }
+}
@@ -0,0 +1,324 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Estimate transparency at the edges of a foreground object
+//!
+//! Alpha matting separates a foreground object from its background where the two mix,
+//! e.g. hair or motion blurred edges. It starts from a trimap, a mask marking which
+//! parts of the image are known foreground, known background, and unknown.
+//!
+//! Every unknown pixel is modeled as a mix of the foreground and background colors
+//! around it, the mean colors of the known pixels nearby, and its alpha is the
+//! fraction of foreground that best explains its color.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::matting::TrimapMatting;
+//!
+//! // a white object on the left of a black background, mixing in between
+//! let mut image = Image::from_fn::<u8, _>(100, 20, ColorSpace::RGB, |_, x, pix| {
+//!     pix.fill((255 - (x * 255 / 99)) as u8);
+//! });
+//! // foreground on the left, background on the right, unknown in between
+//! let trimap = Image::from_fn::<u8, _>(100, 20, ColorSpace::Luma, |_, x, pix| {
+//!     pix[0] = match x {
+//!         0..=19 => 255,
+//!         80.. => 0,
+//!         _ => 128
+//!     };
+//! });
+//! TrimapMatting::new(&trimap).execute(&mut image).unwrap();
+//! assert_eq!(image.colorspace(), ColorSpace::RGBA);
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::utils::{frame_luma, normalized_samples, to_channels};
+
+/// Which part of the image a pixel of a trimap belongs to
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Region {
+    Background,
+    Foreground,
+    Unknown
+}
+
+/// Compute the alpha channel of an image from a trimap
+///
+/// The trimap must have the same dimensions as the image, its luminance marks
+/// the regions, values below 0.25 of the maximum are background, values above 0.75
+/// are foreground and everything in between is unknown.
+///
+/// The image is converted to RGBA, known background is transparent, known foreground
+/// is opaque and unknown pixels get the estimated alpha. An existing alpha channel is
+/// multiplied with the estimate. Colors aren't modified.
+pub struct TrimapMatting<'src> {
+    trimap: &'src Image,
+    radius: usize
+}
+
+impl<'src> TrimapMatting<'src> {
+    /// Create a new matting operation using `trimap`
+    #[must_use]
+    pub fn new(trimap: &'src Image) -> TrimapMatting<'src> {
+        TrimapMatting { trimap, radius: 8 }
+    }
+    /// Set the radius around unknown pixels in which foreground and background
+    /// colors are collected
+    ///
+    /// Larger values reach further into wide unknown regions, smaller ones follow
+    /// changes of the background more closely. Default is 8
+    #[must_use]
+    pub fn set_radius(mut self, radius: usize) -> Self {
+        self.radius = radius;
+        self
+    }
+}
+
+impl OperationsTrait for TrimapMatting<'_> {
+    fn name(&self) -> &'static str {
+        "Trimap Matting"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if image.dimensions() != self.trimap.dimensions() {
+            return Err(ImageErrors::GenericStr(
+                "Trimap dimensions do not match the image"
+            ));
+        }
+        let regions: Vec<Region> = frame_luma(self.trimap, 0)?
+            .iter()
+            .map(|x| match *x {
+                x if x < 0.25 => Region::Background,
+                x if x > 0.75 => Region::Foreground,
+                _ => Region::Unknown
+            })
+            .collect();
+        let (width, height) = image.dimensions();
+        let radius = self.radius;
+
+        map_rgba(image, self.name(), |[r, g, b], alpha| {
+            let estimate = estimate_alpha([r, g, b], &regions, width, height, radius);
+            for (alpha, estimate) in alpha.iter_mut().zip(estimate) {
+                *alpha *= estimate;
+            }
+        })
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Convert `image` to RGBA and call `function` with the normalized color
+/// channels and the alpha channel of every frame
+pub(crate) fn map_rgba(
+    image: &mut Image, name: &'static str, function: impl Fn([&mut [f32]; 3], &mut [f32])
+) -> Result<(), ImageErrors> {
+    image.convert_color(ColorSpace::RGBA)?;
+    let bit_type = image.depth().bit_type();
+
+    for frame in image.frames_mut() {
+        let mut channels = frame
+            .channels_ref(ColorSpace::RGBA, false)
+            .iter()
+            .map(|x| normalized_samples(x, bit_type, name))
+            .collect::<Result<Vec<_>, ImageErrors>>()?;
+
+        let [r, g, b, a] = &mut channels[..] else {
+            return Err(ImageErrors::GenericStr("Expected four channels"));
+        };
+        function([r, g, b], a);
+
+        let pixels: Vec<f32> = (0..r.len())
+            .flat_map(|i| [r[i], g[i], b[i], a[i]])
+            .collect();
+        frame.set_channels(to_channels(&pixels, bit_type, ColorSpace::RGBA));
+    }
+    Ok(())
+}
+
+/// Estimate the alpha of every pixel from the regions of a trimap
+///
+/// Known regions are fully opaque or transparent, an unknown pixel with color `I`
+/// between the mean foreground color `F` and the mean background color `B` within
+/// `radius` gets the alpha minimizing `|I - (alpha * F + (1 - alpha) * B)|`.
+/// Where no known pixel of a region is within `radius`, the mean of the whole
+/// region is used instead.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn estimate_alpha(
+    rgb: [&[f32]; 3], regions: &[Region], width: usize, height: usize, radius: usize
+) -> Vec<f32> {
+    let foreground = RegionSums::new(&rgb, regions, Region::Foreground, width, height);
+    let background = RegionSums::new(&rgb, regions, Region::Background, width, height);
+
+    let mut alpha = Vec::with_capacity(width * height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let i = y * width + x;
+
+            alpha.push(match regions[i] {
+                Region::Background => 0.0,
+                Region::Foreground => 1.0,
+                Region::Unknown => {
+                    let (x0, y0) = (x.saturating_sub(radius), y.saturating_sub(radius));
+                    let x1 = (x + radius + 1).min(width);
+                    let y1 = (y + radius + 1).min(height);
+
+                    match (
+                        foreground.mean(x0, y0, x1, y1),
+                        background.mean(x0, y0, x1, y1)
+                    ) {
+                        (Some(f), Some(b)) => {
+                            let pixel = [rgb[0][i], rgb[1][i], rgb[2][i]];
+                            let mut numerator = 0.0;
+                            let mut denominator = 0.0;
+
+                            for c in 0..3 {
+                                numerator += (pixel[c] - b[c]) * (f[c] - b[c]);
+                                denominator += (f[c] - b[c]) * (f[c] - b[c]);
+                            }
+                            if denominator > 1e-6 {
+                                (numerator / denominator).clamp(0.0, 1.0)
+                            } else {
+                                0.5
+                            }
+                        }
+                        // only one kind of region exists
+                        (Some(_), None) => 1.0,
+                        (None, _) => 0.0
+                    }
+                }
+            });
+        }
+    }
+    alpha
+}
+
+/// Summed area tables of the colors and the count of the pixels of one region,
+/// to find their mean in any rectangle
+struct RegionSums {
+    width:  usize,
+    // one larger than the image in each direction, the first row and column are zero
+    sums:   Vec<[f32; 4]>,
+    global: Option<[f32; 3]>
+}
+
+impl RegionSums {
+    #[allow(clippy::cast_precision_loss)]
+    fn new(
+        rgb: &[&[f32]; 3], regions: &[Region], region: Region, width: usize, height: usize
+    ) -> RegionSums {
+        let stride = width + 1;
+        let mut sums = vec![[0.0_f32; 4]; stride * (height + 1)];
+
+        for y in 0..height {
+            let mut row = [0.0_f32; 4];
+
+            for x in 0..width {
+                let i = y * width + x;
+                if regions[i] == region {
+                    row[0] += rgb[0][i];
+                    row[1] += rgb[1][i];
+                    row[2] += rgb[2][i];
+                    row[3] += 1.0;
+                }
+                let above = sums[y * stride + x + 1];
+                sums[(y + 1) * stride + x + 1] = [
+                    above[0] + row[0],
+                    above[1] + row[1],
+                    above[2] + row[2],
+                    above[3] + row[3]
+                ];
+            }
+        }
+        let total = sums[stride * (height + 1) - 1];
+        let global = (total[3] > 0.0).then(|| {
+            [
+                total[0] / total[3],
+                total[1] / total[3],
+                total[2] / total[3]
+            ]
+        });
+
+        RegionSums {
+            width,
+            sums,
+            global
+        }
+    }
+
+    /// Return the mean color of the region within `x0..x1` and `y0..y1`, the mean
+    /// of the whole region if it doesn't occur there, or `None` if it doesn't exist
+    fn mean(&self, x0: usize, y0: usize, x1: usize, y1: usize) -> Option<[f32; 3]> {
+        let stride = self.width + 1;
+        let at = |x: usize, y: usize| self.sums[y * stride + x];
+
+        let (a, b, c, d) = (at(x1, y1), at(x0, y1), at(x1, y0), at(x0, y0));
+        let sum: [f32; 4] = core::array::from_fn(|i| a[i] - b[i] - c[i] + d[i]);
+
+        if sum[3] >= 0.5 {
+            Some([sum[0] / sum[3], sum[1] / sum[3], sum[2] / sum[3]])
+        } else {
+            self.global
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::matting::TrimapMatting;
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn test_trimap_matting() {
+        // a red object over a blue background, blending over 40 pixels
+        let mut image = Image::from_fn::<u8, _>(100, 10, ColorSpace::RGB, |_, x, pix| {
+            let alpha = (70 - x.clamp(30, 70)) * 255 / 40;
+            pix[0] = alpha as u8;
+            pix[2] = (255 - alpha) as u8;
+        });
+        let trimap = Image::from_fn::<u8, _>(100, 10, ColorSpace::Luma, |_, x, pix| {
+            pix[0] = match x {
+                0..=24 => 255,
+                75.. => 0,
+                _ => 128
+            };
+        });
+        TrimapMatting::new(&trimap)
+            .set_radius(30)
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.colorspace(), ColorSpace::RGBA);
+
+        let pixels = &image.flatten_to_u8()[0];
+        for x in 0..100 {
+            let expected = (70 - x.clamp(30, 70)) * 255 / 40;
+            let alpha = usize::from(pixels[(5 * 100 + x) * 4 + 3]);
+            assert!(alpha.abs_diff(expected) <= 2, "{x}: {alpha} {expected}");
+        }
+    }
+
+    #[test]
+    fn test_trimap_matting_dimensions() {
+        let mut image = Image::fill::<u8>(0, ColorSpace::RGB, 10, 10);
+        let trimap = Image::fill::<u8>(0, ColorSpace::Luma, 10, 11);
+        assert!(TrimapMatting::new(&trimap).execute(&mut image).is_err());
+    }
+}
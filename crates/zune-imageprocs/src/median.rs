@@ -53,6 +53,7 @@ use crate::utils::{execute_on, z_prefetch};
 ///
 /// for example a radius of R will result in a search window length of 2R+1 for each dimension.
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Median {
     radius: usize
 }
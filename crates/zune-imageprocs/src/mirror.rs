@@ -17,6 +17,7 @@ use crate::utils::execute_on;
 
 /// Supported mirror modes
 #[derive(Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub enum MirrorMode {
     ///
     /// ```text           
@@ -61,6 +62,7 @@ pub enum MirrorMode {
 /// To see the effect of this
 /// see the image [mirror-modes](crate::mirror::MirrorMode) documentation
 /// for each used mode
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mirror {
     mode: MirrorMode
 }
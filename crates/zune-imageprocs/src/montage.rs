@@ -0,0 +1,434 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Lay out images in a grid, e.g. a contact sheet
+//!
+//! Every image gets a cell of the same size, large enough for the largest image unless
+//! a [tile size](Montage::set_tile_size) is set, in which case larger images are scaled
+//! down to fit it. Images are centered in their cells and keep their aspect ratio.
+//!
+//! Cells are separated by padding and can have a label below the image, e.g. its file
+//! name, drawn with a small built in font. Labels too wide for a cell are cut short.
+//!
+//! The result is 8 bit RGBA, images of any colorspace and depth can be combined.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_imageprocs::montage::Montage;
+//!
+//! let images: Vec<Image> = (0..5)
+//!     .map(|i| Image::fill::<u8>(i * 50, ColorSpace::RGB, 100, 80))
+//!     .collect();
+//!
+//! let mut montage = Montage::new().set_columns(3).set_padding(10);
+//! for (i, image) in images.iter().enumerate() {
+//!     montage = montage.add_labeled_image(image, &format!("image {i}"));
+//! }
+//! let sheet = montage.montage().unwrap();
+//! // two rows of three cells
+//! assert_eq!(sheet.dimensions().0, 3 * 100 + 4 * 10);
+//! ```
+use zune_core::bit_depth::BitDepth;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+
+use crate::font::{draw_text, text_width, GLYPH_HEIGHT};
+use crate::utils::{composite_over, to_channels};
+
+/// Lay out images in a grid
+pub struct Montage<'src> {
+    images:      Vec<(&'src Image, Option<String>)>,
+    columns:     Option<usize>,
+    tile_size:   Option<(usize, usize)>,
+    padding:     usize,
+    background:  [u8; 4],
+    label_color: [u8; 4],
+    label_scale: usize
+}
+
+impl Default for Montage<'_> {
+    fn default() -> Self {
+        Montage::new()
+    }
+}
+
+impl<'src> Montage<'src> {
+    /// Create a new montage without images
+    ///
+    /// The grid is about as wide as tall, cells are 8 pixels apart on a white
+    /// background and labels are black
+    #[must_use]
+    pub fn new() -> Montage<'src> {
+        Montage {
+            images:      vec![],
+            columns:     None,
+            tile_size:   None,
+            padding:     8,
+            background:  [255, 255, 255, 255],
+            label_color: [0, 0, 0, 255],
+            label_scale: 1
+        }
+    }
+    /// Add an image without a label, only the first frame of animated images is used
+    #[must_use]
+    pub fn add_image(mut self, image: &'src Image) -> Self {
+        self.images.push((image, None));
+        self
+    }
+    /// Add an image with a label drawn below it
+    #[must_use]
+    pub fn add_labeled_image(mut self, image: &'src Image, label: &str) -> Self {
+        self.images.push((image, Some(label.to_string())));
+        self
+    }
+    /// Set the number of columns of the grid, rows are added as needed
+    #[must_use]
+    pub fn set_columns(mut self, columns: usize) -> Self {
+        self.columns = Some(columns.max(1));
+        self
+    }
+    /// Set the largest width and height of images in a cell, larger images are
+    /// scaled down to fit, smaller ones are kept as they are
+    #[must_use]
+    pub fn set_tile_size(mut self, width: usize, height: usize) -> Self {
+        self.tile_size = Some((width.max(1), height.max(1)));
+        self
+    }
+    /// Set the space in pixels between cells and around the grid
+    #[must_use]
+    pub fn set_padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+    /// Set the 8 bit RGBA color of the background
+    #[must_use]
+    pub fn set_background(mut self, background: [u8; 4]) -> Self {
+        self.background = background;
+        self
+    }
+    /// Set the 8 bit RGBA color of labels
+    #[must_use]
+    pub fn set_label_color(mut self, color: [u8; 4]) -> Self {
+        self.label_color = color;
+        self
+    }
+    /// Set how many times labels are enlarged, 1 draws 8 pixel tall text
+    #[must_use]
+    pub fn set_label_scale(mut self, scale: usize) -> Self {
+        self.label_scale = scale.max(1);
+        self
+    }
+
+    /// Return the number of columns and rows of the grid
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn grid(&self) -> (usize, usize) {
+        let count = self.images.len();
+        let columns = self
+            .columns
+            .unwrap_or_else(|| (count as f64).sqrt().ceil() as usize)
+            .clamp(1, count.max(1));
+        (columns, count.div_ceil(columns))
+    }
+
+    /// Return the first frame of an image as RGBA, scaled down to the tile size
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn tile(&self, image: &Image) -> Result<Tile, ImageErrors> {
+        let (width, height) = image.dimensions();
+        let Some(frame) = image.frames_ref().first() else {
+            return Err(ImageErrors::GenericStr(
+                "Cannot add empty images to montage"
+            ));
+        };
+        if width == 0 || height == 0 {
+            return Err(ImageErrors::GenericStr(
+                "Cannot add empty images to montage"
+            ));
+        }
+        let mut rgba = Image::new_frames(
+            vec![frame.clone()],
+            image.depth(),
+            width,
+            height,
+            image.colorspace()
+        );
+        rgba.convert_color(ColorSpace::RGBA)?;
+        rgba.convert_depth(BitDepth::Float32)?;
+        let pixels = rgba.flatten_frames::<f32>().swap_remove(0);
+
+        let scale = self.tile_size.map_or(1.0, |(max_width, max_height)| {
+            (max_width as f64 / width as f64).min(max_height as f64 / height as f64)
+        });
+        if scale >= 1.0 {
+            return Ok(Tile {
+                pixels,
+                width,
+                height
+            });
+        }
+        let new_width = ((width as f64 * scale).round() as usize).max(1);
+        let new_height = ((height as f64 * scale).round() as usize).max(1);
+
+        Ok(Tile {
+            pixels: shrink(&pixels, (width, height), (new_width, new_height)),
+            width:  new_width,
+            height: new_height
+        })
+    }
+
+    /// Draw all images onto a new canvas
+    ///
+    /// # Errors
+    /// - No images were added, or one of them is empty
+    /// - An image can't be converted to RGBA
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn montage(&self) -> Result<Image, ImageErrors> {
+        if self.images.is_empty() {
+            return Err(ImageErrors::GenericStr("No images for montage"));
+        }
+        // every image as RGBA floats, scaled to fit its cell
+        let tiles = self
+            .images
+            .iter()
+            .map(|(image, _)| self.tile(image))
+            .collect::<Result<Vec<Tile>, ImageErrors>>()?;
+
+        let (cell_width, image_height) = self.tile_size.unwrap_or_else(|| {
+            tiles.iter().fold((1, 1), |(w, h), tile| {
+                (w.max(tile.width), h.max(tile.height))
+            })
+        });
+        let has_labels = self.images.iter().any(|(_, label)| label.is_some());
+        let label_height = if has_labels {
+            // labels start two rows below the image
+            (GLYPH_HEIGHT + 2) * self.label_scale
+        } else {
+            0
+        };
+        let cell_height = image_height + label_height;
+
+        let (columns, rows) = self.grid();
+        let width = columns * cell_width + (columns + 1) * self.padding;
+        let height = rows * cell_height + (rows + 1) * self.padding;
+
+        let background = self.background.map(|x| f32::from(x) / 255.0);
+        let mut pixels: Vec<f32> = background
+            .iter()
+            .copied()
+            .cycle()
+            .take(width * height * 4)
+            .collect();
+
+        for (i, (tile, (_, label))) in tiles.iter().zip(&self.images).enumerate() {
+            let cell_x = self.padding + (i % columns) * (cell_width + self.padding);
+            let cell_y = self.padding + (i / columns) * (cell_height + self.padding);
+
+            let x0 = cell_x + (cell_width - tile.width) / 2;
+            let y0 = cell_y + (image_height - tile.height) / 2;
+
+            for (y, row) in tile.pixels.chunks_exact(tile.width * 4).enumerate() {
+                let start = ((y0 + y) * width + x0) * 4;
+                let out = &mut pixels[start..start + tile.width * 4];
+
+                for (dst, src) in out.chunks_exact_mut(4).zip(row.chunks_exact(4)) {
+                    composite_over(dst, src, 1.0, true);
+                }
+            }
+
+            if let Some(label) = label {
+                let label = fit_label(label, cell_width, self.label_scale);
+                let text_x = cell_x + (cell_width - text_width(&label, self.label_scale)) / 2;
+                let text_y = cell_y + image_height + 2 * self.label_scale;
+                let color = self.label_color.map(|x| f32::from(x) / 255.0);
+
+                draw_text(&label, self.label_scale, |x, y| {
+                    let start = ((text_y + y) * width + text_x + x) * 4;
+                    composite_over(&mut pixels[start..start + 4], &color, 1.0, true);
+                });
+            }
+        }
+
+        Ok(Image::new(
+            to_channels(&pixels, BitDepth::Eight.bit_type(), ColorSpace::RGBA),
+            BitDepth::Eight,
+            width,
+            height,
+            ColorSpace::RGBA
+        ))
+    }
+}
+
+/// An image scaled to fit a cell
+struct Tile {
+    /// RGBA pixels between 0.0 and 1.0
+    pixels: Vec<f32>,
+    width:  usize,
+    height: usize
+}
+
+/// Shrink RGBA pixels by averaging the area every new pixel covers, which unlike
+/// interpolation keeps fine detail from aliasing
+///
+/// Colors are weighted by alpha, so transparent pixels don't darken their neighbours
+#[allow(clippy::cast_precision_loss)]
+fn shrink(
+    pixels: &[f32], (width, height): (usize, usize), (new_width, new_height): (usize, usize)
+) -> Vec<f32> {
+    // source pixels covered by a new pixel, at least one
+    let span = |i: usize, new: usize, old: usize| {
+        let start = (i * old / new).min(old - 1);
+        start..((i + 1) * old / new).clamp(start + 1, old)
+    };
+    let mut out = Vec::with_capacity(new_width * new_height * 4);
+
+    for y in 0..new_height {
+        let ys = span(y, new_height, height);
+
+        for x in 0..new_width {
+            let xs = span(x, new_width, width);
+            let mut sum = [0.0_f32; 4];
+
+            for row in ys.clone() {
+                for pixel in
+                    pixels[(row * width + xs.start) * 4..(row * width + xs.end) * 4].chunks_exact(4)
+                {
+                    for (total, value) in sum[..3].iter_mut().zip(pixel) {
+                        *total += value * pixel[3];
+                    }
+                    sum[3] += pixel[3];
+                }
+            }
+            let alpha = sum[3] / (ys.len() * xs.len()) as f32;
+            let colors = sum[..3]
+                .iter()
+                .map(|x| if sum[3] > 0.0 { x / sum[3] } else { 0.0 });
+
+            out.extend(colors);
+            out.push(alpha);
+        }
+    }
+    out
+}
+
+/// Cut `label` short, ending it with `..`, until it fits `width` pixels
+fn fit_label(label: &str, width: usize, scale: usize) -> String {
+    if text_width(label, scale) <= width {
+        return label.to_string();
+    }
+    let mut characters: Vec<char> = label.chars().collect();
+    while !characters.is_empty() {
+        characters.pop();
+        let short: String = characters.iter().chain(&['.', '.']).collect();
+        if text_width(&short, scale) <= width {
+            return short;
+        }
+    }
+    String::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+
+    use crate::montage::{fit_label, shrink, Montage};
+
+    #[test]
+    fn test_montage_layout() {
+        let red = Image::fill::<u8>(255, ColorSpace::Luma, 4, 2);
+        let blue = Image::from_fn::<u16, _>(2, 2, ColorSpace::RGB, |_, _, pix| {
+            pix[..3].copy_from_slice(&[0, 0, 65535]);
+        });
+        let sheet = Montage::new()
+            .add_image(&red)
+            .add_image(&blue)
+            .add_image(&blue)
+            .set_padding(1)
+            .set_background([0, 255, 0, 255])
+            .montage()
+            .unwrap();
+
+        // two columns of 4x2 cells, two rows
+        assert_eq!(sheet.dimensions(), (11, 7));
+        assert_eq!(sheet.colorspace(), ColorSpace::RGBA);
+
+        let pixels = &sheet.flatten_to_u8()[0];
+        let pixel = |x: usize, y: usize| &pixels[(y * 11 + x) * 4..(y * 11 + x) * 4 + 4];
+
+        assert_eq!(pixel(0, 0), [0, 255, 0, 255]);
+        assert_eq!(pixel(1, 1), [255, 255, 255, 255]);
+        assert_eq!(pixel(4, 2), [255, 255, 255, 255]);
+        // smaller images are centered in their cells
+        assert_eq!(pixel(6, 1), [0, 255, 0, 255]);
+        assert_eq!(pixel(7, 1), [0, 0, 255, 255]);
+        assert_eq!(pixel(8, 2), [0, 0, 255, 255]);
+        assert_eq!(pixel(9, 1), [0, 255, 0, 255]);
+        assert_eq!(pixel(2, 4), [0, 0, 255, 255]);
+        // the last cell is empty
+        assert_eq!(pixel(7, 4), [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn test_montage_tiles_and_labels() {
+        let image = Image::fill::<u8>(0, ColorSpace::RGB, 40, 20);
+        let sheet = Montage::new()
+            .add_labeled_image(&image, "a")
+            .set_tile_size(10, 10)
+            .set_padding(0)
+            .set_background([255; 4])
+            .montage()
+            .unwrap();
+        // scaled down to 10x5, with room for the label below
+        assert_eq!(sheet.dimensions(), (10, 20));
+
+        let pixels = &sheet.flatten_to_u8()[0];
+        let black = |rows: std::ops::Range<usize>| {
+            pixels[rows.start * 40..rows.end * 40]
+                .chunks_exact(4)
+                .filter(|x| x[0] == 0)
+                .count()
+        };
+        assert_eq!(black(0..2), 0);
+        assert_eq!(black(2..7), 50);
+        assert_eq!(black(7..10), 0);
+        assert!(black(12..20) > 0);
+
+        assert!(Montage::new().montage().is_err());
+        let empty = Image::fill::<u8>(0, ColorSpace::RGB, 0, 0);
+        assert!(Montage::new().add_image(&empty).montage().is_err());
+    }
+
+    #[test]
+    fn test_shrink_weights_alpha() {
+        // a transparent black pixel next to an opaque white one
+        let pixels = [0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0];
+        assert_eq!(shrink(&pixels, (2, 1), (1, 1)), [1.0, 1.0, 1.0, 0.5]);
+    }
+
+    #[test]
+    fn test_fit_label() {
+        assert_eq!(fit_label("photo.jpg", 60, 1), "photo.jpg");
+        assert_eq!(fit_label("photo.jpg", 30, 1), "pho..");
+        assert_eq!(fit_label("photo.jpg", 60, 2), "pho..");
+        assert_eq!(fit_label("photo.jpg", 6, 1), "");
+    }
+}
@@ -0,0 +1,415 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Estimate motion between two frames
+//!
+//! Block based motion estimation, as done by video encoders. The current frame is split
+//! into square blocks and for every block the best matching block of the previous frame
+//! is searched nearby, giving a motion vector per block.
+//!
+//! This helps deciding how to store animation frames, blocks that neither moved nor changed
+//! don't need to be stored again, and frames where nothing changed are duplicates that
+//! can be dropped.
+//!
+//! Blocks are matched by their luminance with a diamond search (Zhu and Ma, "A new diamond
+//! search algorithm for fast block-matching motion estimation"), which walks a large diamond
+//! towards the best match until its center is best and refines with a small diamond.
+//! It needs a fraction of the comparisons of an exhaustive search, but can settle on a
+//! local best match for large motions of repetitive content.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_imageprocs::motion::MotionEstimator;
+//!
+//! let wave = |y: usize, x: usize| {
+//!     (128.0 + 100.0 * (x as f32 * 0.2).sin() * (y as f32 * 0.15).cos()) as u8
+//! };
+//!
+//! let previous = Image::from_fn::<u8, _>(64, 64, ColorSpace::RGB, |y, x, pix| {
+//!     pix.fill(wave(y, x));
+//! });
+//! // everything moved two pixels to the right
+//! let current = Image::from_fn::<u8, _>(64, 64, ColorSpace::RGB, |y, x, pix| {
+//!     pix.fill(wave(y, x.saturating_sub(2)));
+//! });
+//!
+//! let field = MotionEstimator::new().estimate(&previous, &current).unwrap();
+//! let vector = field.vector(1, 1);
+//! assert_eq!((vector.dx, vector.dy), (2, 0));
+//! ```
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+
+use crate::utils::frame_luma;
+
+/// Motion of a block between two frames
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct MotionVector {
+    /// Horizontal motion in pixels, positive values moved right
+    pub dx:    isize,
+    /// Vertical motion in pixels, positive values moved down
+    pub dy:    isize,
+    /// Mean absolute difference of the luminance of the block and its match
+    /// in the previous frame, between 0.0 and 1.0
+    pub error: f32
+}
+
+impl MotionVector {
+    /// Return true if the block neither moved nor differs by more than `threshold`
+    #[must_use]
+    pub fn is_static(&self, threshold: f32) -> bool {
+        self.dx == 0 && self.dy == 0 && self.error <= threshold
+    }
+}
+
+/// Motion vectors of all blocks of a frame
+#[derive(Clone, Debug, PartialEq)]
+pub struct MotionField {
+    block_size: usize,
+    blocks_x:   usize,
+    blocks_y:   usize,
+    vectors:    Vec<MotionVector>
+}
+
+impl MotionField {
+    /// Return the width and height of the blocks, blocks on the right
+    /// and bottom edge are smaller if the frame isn't a multiple of it
+    #[must_use]
+    pub const fn block_size(&self) -> usize {
+        self.block_size
+    }
+    /// Return the number of blocks horizontally and vertically
+    #[must_use]
+    pub const fn dimensions(&self) -> (usize, usize) {
+        (self.blocks_x, self.blocks_y)
+    }
+    /// Return the motion vectors of all blocks, row by row
+    #[must_use]
+    pub fn vectors(&self) -> &[MotionVector] {
+        &self.vectors
+    }
+    /// Return the motion vector of the block at `(block_x, block_y)`
+    ///
+    /// # Panics
+    /// If the block is outside of the field
+    #[must_use]
+    pub fn vector(&self, block_x: usize, block_y: usize) -> MotionVector {
+        assert!(block_x < self.blocks_x && block_y < self.blocks_y);
+        self.vectors[block_y * self.blocks_x + block_x]
+    }
+    /// Return true if no block moved or differs by more than `threshold`,
+    /// i.e. the frames are duplicates
+    #[must_use]
+    pub fn is_static(&self, threshold: f32) -> bool {
+        self.vectors.iter().all(|x| x.is_static(threshold))
+    }
+}
+
+/// Block based motion estimation between two frames
+///
+/// Frames must have the same dimensions, they may have different colorspaces
+/// and depths as they are compared by luminance.
+pub struct MotionEstimator {
+    block_size:   usize,
+    search_range: usize
+}
+
+impl MotionEstimator {
+    /// Create a new estimator with 16x16 blocks and a search range of 16 pixels
+    #[must_use]
+    pub fn new() -> MotionEstimator {
+        MotionEstimator {
+            block_size:   16,
+            search_range: 16
+        }
+    }
+    /// Set the width and height of the blocks
+    ///
+    /// Smaller blocks follow the motion of smaller objects, but are more likely
+    /// to match the wrong place in flat or repetitive areas. Default is 16
+    #[must_use]
+    pub fn set_block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size.max(1);
+        self
+    }
+    /// Set the largest motion, in pixels, searched for in each direction
+    ///
+    /// Default is 16
+    #[must_use]
+    pub fn set_search_range(mut self, search_range: usize) -> Self {
+        self.search_range = search_range;
+        self
+    }
+
+    /// Estimate the motion from the first frame of `previous` to the first frame of `current`
+    ///
+    /// # Errors
+    /// If the images are empty, have different dimensions or can't be converted to grayscale
+    pub fn estimate(&self, previous: &Image, current: &Image) -> Result<MotionField, ImageErrors> {
+        if previous.dimensions() != current.dimensions() {
+            return Err(ImageErrors::GenericStr(
+                "Image dimensions are incompatible for motion estimation"
+            ));
+        }
+        self.estimate_luma(
+            &frame_luma(previous, 0)?,
+            &frame_luma(current, 0)?,
+            current.dimensions()
+        )
+    }
+
+    /// Estimate the motion between two frames of an animated image
+    ///
+    /// # Errors
+    /// If the image is empty, a frame doesn't exist or the image can't be converted to grayscale
+    pub fn estimate_frames(
+        &self, image: &Image, previous: usize, current: usize
+    ) -> Result<MotionField, ImageErrors> {
+        self.estimate_luma(
+            &frame_luma(image, previous)?,
+            &frame_luma(image, current)?,
+            image.dimensions()
+        )
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn estimate_luma(
+        &self, previous: &[f32], current: &[f32], (width, height): (usize, usize)
+    ) -> Result<MotionField, ImageErrors> {
+        if width == 0 || height == 0 {
+            return Err(ImageErrors::GenericStr(
+                "Cannot estimate motion of an empty image"
+            ));
+        }
+        let size = self.block_size;
+        let (blocks_x, blocks_y) = (width.div_ceil(size), height.div_ceil(size));
+        let mut vectors: Vec<MotionVector> = Vec::with_capacity(blocks_x * blocks_y);
+
+        for block_y in 0..blocks_y {
+            for block_x in 0..blocks_x {
+                let block = Block {
+                    x:      block_x * size,
+                    y:      block_y * size,
+                    width:  size.min(width - block_x * size),
+                    height: size.min(height - block_y * size)
+                };
+                // motion is usually similar to that of the neighbouring blocks
+                let left = (block_x > 0).then(|| vectors[vectors.len() - 1]);
+                let top = (block_y > 0).then(|| vectors[vectors.len() - blocks_x]);
+
+                let cost = |dx: isize, dy: isize| {
+                    self.match_error(previous, current, width, height, &block, dx, dy)
+                };
+                vectors.push(diamond_search(cost, [left, top]));
+            }
+        }
+        Ok(MotionField {
+            block_size: size,
+            blocks_x,
+            blocks_y,
+            vectors
+        })
+    }
+
+    /// Return the mean absolute difference between `block` of the current frame
+    /// and the block it came from if it moved by `(dx, dy)`, or `None` if that is
+    /// out of range
+    #[allow(
+        clippy::too_many_arguments,
+        clippy::cast_precision_loss,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss
+    )]
+    fn match_error(
+        &self, previous: &[f32], current: &[f32], width: usize, height: usize, block: &Block,
+        dx: isize, dy: isize
+    ) -> Option<f32> {
+        let range = self.search_range as isize;
+        let src_x = block.x as isize - dx;
+        let src_y = block.y as isize - dy;
+
+        if dx.abs() > range
+            || dy.abs() > range
+            || src_x < 0
+            || src_y < 0
+            || src_x as usize + block.width > width
+            || src_y as usize + block.height > height
+        {
+            return None;
+        }
+        let (src_x, src_y) = (src_x as usize, src_y as usize);
+        let mut sum = 0.0;
+
+        for y in 0..block.height {
+            let a = &current[(block.y + y) * width + block.x..][..block.width];
+            let b = &previous[(src_y + y) * width + src_x..][..block.width];
+            sum += a.iter().zip(b).map(|(a, b)| (a - b).abs()).sum::<f32>();
+        }
+        Some(sum / (block.width * block.height) as f32)
+    }
+}
+
+impl Default for MotionEstimator {
+    fn default() -> Self {
+        MotionEstimator::new()
+    }
+}
+
+/// A block of the current frame
+struct Block {
+    x:      usize,
+    y:      usize,
+    width:  usize,
+    height: usize
+}
+
+/// Points of the large diamond search pattern around its center
+const LARGE_DIAMOND: [(isize, isize); 8] = [
+    (0, -2),
+    (1, -1),
+    (2, 0),
+    (1, 1),
+    (0, 2),
+    (-1, 1),
+    (-2, 0),
+    (-1, -1)
+];
+/// Points of the small diamond search pattern around its center
+const SMALL_DIAMOND: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Find the motion vector with the smallest `cost`, starting from no motion
+/// or the motion of a neighbouring block, whichever matches best
+fn diamond_search(
+    cost: impl Fn(isize, isize) -> Option<f32>, predictors: [Option<MotionVector>; 2]
+) -> MotionVector {
+    // no motion is always in range
+    let mut best = MotionVector {
+        dx:    0,
+        dy:    0,
+        error: cost(0, 0).unwrap_or(f32::MAX)
+    };
+    let consider = |best: &mut MotionVector, dx: isize, dy: isize| {
+        if let Some(error) = cost(dx, dy) {
+            // ties keep the current vector, preferring no motion
+            if error < best.error {
+                *best = MotionVector { dx, dy, error };
+                return true;
+            }
+        }
+        false
+    };
+    for predictor in predictors.into_iter().flatten() {
+        consider(&mut best, predictor.dx, predictor.dy);
+    }
+    if best.error == 0.0 {
+        return best;
+    }
+    // walk the large diamond until its center is the best match, the error
+    // decreases with every step so this ends
+    loop {
+        let center = best;
+        let mut moved = false;
+
+        for (x, y) in LARGE_DIAMOND {
+            moved |= consider(&mut best, center.dx + x, center.dy + y);
+        }
+        if !moved {
+            break;
+        }
+    }
+    let center = best;
+
+    for (x, y) in SMALL_DIAMOND {
+        consider(&mut best, center.dx + x, center.dy + y);
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::frame::Frame;
+    use zune_image::image::Image;
+
+    use crate::motion::MotionEstimator;
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn wave(x: isize, y: isize) -> u8 {
+        let (x, y) = (x as f32, y as f32);
+        (128.0 + 60.0 * (x * 0.2).sin() + 60.0 * (y * 0.15 + x * 0.05).cos()) as u8
+    }
+
+    /// A frame of `width` by `height` where everything moved by `(dx, dy)`
+    #[allow(clippy::cast_possible_wrap)]
+    fn frame(dx: isize, dy: isize) -> Image {
+        Image::from_fn::<u8, _>(96, 64, ColorSpace::Luma, |y, x, pix| {
+            pix[0] = wave(x as isize - dx, y as isize - dy);
+        })
+    }
+
+    #[test]
+    fn test_motion_estimation() {
+        let previous = frame(0, 0);
+        let current = frame(3, -2);
+
+        let field = MotionEstimator::new()
+            .set_block_size(16)
+            .estimate(&previous, &current)
+            .unwrap();
+        assert_eq!(field.dimensions(), (6, 4));
+
+        // blocks on the edges may have moved in from outside the frame
+        for block_y in 1..3 {
+            for block_x in 1..5 {
+                let vector = field.vector(block_x, block_y);
+                assert_eq!((vector.dx, vector.dy), (3, -2));
+                assert!(vector.error < 0.01);
+            }
+        }
+        assert!(!field.is_static(0.01));
+    }
+
+    #[test]
+    fn test_motion_estimation_frames() {
+        let frames: Vec<Frame> = [frame(0, 0), frame(0, 0), frame(-5, 4)]
+            .iter()
+            .map(|x| x.frames_ref()[0].clone())
+            .collect();
+        let animation = Image::new_frames(
+            frames,
+            zune_core::bit_depth::BitDepth::Eight,
+            96,
+            64,
+            ColorSpace::Luma
+        );
+        let estimator = MotionEstimator::new().set_block_size(8);
+
+        // duplicate frames
+        assert!(estimator
+            .estimate_frames(&animation, 0, 1)
+            .unwrap()
+            .is_static(0.0));
+
+        let vector = estimator
+            .estimate_frames(&animation, 1, 2)
+            .unwrap()
+            .vector(5, 3);
+        assert_eq!((vector.dx, vector.dy), (-5, 4));
+
+        assert!(estimator.estimate_frames(&animation, 0, 3).is_err());
+        assert!(estimator
+            .estimate(&animation, &Image::fill::<u8>(0, ColorSpace::Luma, 10, 10))
+            .is_err());
+    }
+}
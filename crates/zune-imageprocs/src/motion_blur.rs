@@ -0,0 +1,449 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Motion, zoom and spin blurs
+//!
+//! These blurs average every pixel with the pixels along a path instead of those
+//! in a neighbourhood like the box and gaussian blurs
+//!
+//! - [`MotionBlur`] smears the image along a straight line, as if the camera moved
+//!   while taking it
+//! - [`RadialBlur`] smears it along lines towards a center ([zoom](RadialBlurMode::Zoom)),
+//!   as if zooming during the exposure, or along circles around it ([spin](RadialBlurMode::Spin)),
+//!   as if the camera rotated
+//!
+//! Samples between pixels are interpolated bilinearly and pixels outside the image are
+//! taken from its nearest edge. All channels, alpha included, are blurred
+//!
+//! # Example
+//! - Blur an image diagonally and zoom into its top left quarter
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::motion_blur::{MotionBlur, RadialBlur};
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+//! MotionBlur::new(45.0, 10.0).execute(&mut image).unwrap();
+//! RadialBlur::zoom(0.2)
+//!     .set_center(0.25, 0.25)
+//!     .execute(&mut image)
+//!     .unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::utils::{execute_on, normalized_samples, store_samples};
+
+/// Blur an image along a straight line
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct MotionBlur {
+    angle:    f32,
+    distance: f32
+}
+
+impl MotionBlur {
+    /// Create a new motion blur
+    ///
+    /// # Arguments
+    /// - angle: The direction of the motion in degrees, 0.0 is horizontal and
+    ///   positive angles turn counterclockwise
+    /// - distance: The length of the motion in pixels, centered on every pixel
+    #[must_use]
+    pub fn new(angle: f32, distance: f32) -> MotionBlur {
+        MotionBlur { angle, distance }
+    }
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn blur(self, plane: &[f32], width: usize, height: usize) -> Vec<f32> {
+        let distance = self.distance.abs();
+        // one sample per pixel along the motion, so no pixel is skipped, shifts
+        // longer than the image only read its edges so more samples don't help
+        let max_samples = (width as f32).hypot(height as f32) as usize + 1;
+        let samples = (distance.ceil() as usize + 1).min(max_samples);
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+
+        let mut output = vec![0.0; plane.len()];
+        let weight = 1.0 / samples as f32;
+
+        for i in 0..samples {
+            let t = if samples == 1 {
+                0.0
+            } else {
+                (i as f32 / (samples - 1) as f32 - 0.5) * distance
+            };
+            // image rows go down, so positive angles go up
+            add_shifted(
+                &mut output,
+                plane,
+                width,
+                height,
+                (t * cos, -t * sin),
+                weight
+            );
+        }
+        output
+    }
+}
+
+impl OperationsTrait for MotionBlur {
+    fn name(&self) -> &'static str {
+        "Motion Blur"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if !self.distance.is_finite() || !self.angle.is_finite() {
+            return Err(ImageErrors::GenericStr(
+                "Motion blur angle and distance must be finite"
+            ));
+        }
+        let (width, height) = image.dimensions();
+        let bit_type = image.depth().bit_type();
+
+        let blur = |channel: &mut Channel| -> Result<(), ImageErrors> {
+            let values = normalized_samples(channel, bit_type, self.name())?;
+            let blurred = self.blur(&values, width, height);
+            store_samples(channel, bit_type, self.name(), &blurred)
+        };
+        execute_on(blur, image, false)
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// The path a [`RadialBlur`] smears pixels along
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub enum RadialBlurMode {
+    /// Along lines towards the center
+    Zoom,
+    /// Along circles around the center
+    Spin
+}
+
+/// Blur an image towards or around a center
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct RadialBlur {
+    mode:     RadialBlurMode,
+    strength: f32,
+    center_x: f32,
+    center_y: f32
+}
+
+impl RadialBlur {
+    /// Create a new zoom blur
+    ///
+    /// # Arguments
+    /// - amount: The fraction, between 0.0 and 1.0, of the distance to the center
+    ///   every pixel is smeared towards it
+    ///
+    /// The center is the center of the image
+    #[must_use]
+    pub fn zoom(amount: f32) -> RadialBlur {
+        RadialBlur {
+            mode:     RadialBlurMode::Zoom,
+            strength: amount.clamp(0.0, 1.0),
+            center_x: 0.5,
+            center_y: 0.5
+        }
+    }
+    /// Create a new spin blur
+    ///
+    /// # Arguments
+    /// - angle: The angle in degrees every pixel is smeared over, half of it in each
+    ///   direction
+    ///
+    /// The center is the center of the image
+    #[must_use]
+    pub fn spin(angle: f32) -> RadialBlur {
+        RadialBlur {
+            mode:     RadialBlurMode::Spin,
+            strength: angle.abs(),
+            center_x: 0.5,
+            center_y: 0.5
+        }
+    }
+    /// Set the center of the blur, relative to the image dimensions, `(0.0, 0.0)` being
+    /// the top left pixel and `(1.0, 1.0)` the bottom right one
+    #[must_use]
+    pub fn set_center(mut self, x: f32, y: f32) -> Self {
+        self.center_x = x;
+        self.center_y = y;
+        self
+    }
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn blur(self, plane: &[f32], width: usize, height: usize) -> Vec<f32> {
+        let center_x = self.center_x * (width as f32 - 1.0);
+        let center_y = self.center_y * (height as f32 - 1.0);
+        let angle = self.strength.to_radians();
+
+        let mut output = vec![0.0; plane.len()];
+
+        for (y, row) in output.chunks_exact_mut(width).enumerate() {
+            let dy = y as f32 - center_y;
+
+            for (x, out) in row.iter_mut().enumerate() {
+                let dx = x as f32 - center_x;
+                let radius = dx.hypot(dy);
+
+                // pixels further from the center move further, they get more samples so
+                // that no pixel along the path is skipped
+                let length = match self.mode {
+                    RadialBlurMode::Zoom => radius * self.strength,
+                    RadialBlurMode::Spin => radius * angle
+                };
+                let samples = length.ceil() as usize + 1;
+                if samples == 1 {
+                    *out = plane[y * width + x];
+                    continue;
+                }
+                let mut sum = 0.0;
+
+                for i in 0..samples {
+                    let t = i as f32 / (samples - 1) as f32;
+
+                    let (sx, sy) = match self.mode {
+                        RadialBlurMode::Zoom => {
+                            let scale = 1.0 - t * self.strength;
+                            (center_x + dx * scale, center_y + dy * scale)
+                        }
+                        RadialBlurMode::Spin => {
+                            let (sin, cos) = ((t - 0.5) * angle).sin_cos();
+                            (
+                                center_x + dx * cos - dy * sin,
+                                center_y + dx * sin + dy * cos
+                            )
+                        }
+                    };
+                    sum += sample_bilinear(plane, width, height, sx, sy);
+                }
+                *out = sum / samples as f32;
+            }
+        }
+        output
+    }
+}
+
+impl OperationsTrait for RadialBlur {
+    fn name(&self) -> &'static str {
+        "Radial Blur"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let bit_type = image.depth().bit_type();
+
+        let blur = |channel: &mut Channel| -> Result<(), ImageErrors> {
+            let values = normalized_samples(channel, bit_type, self.name())?;
+            let blurred = self.blur(&values, width, height);
+            store_samples(channel, bit_type, self.name(), &blurred)
+        };
+        execute_on(blur, image, false)
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Add `plane` shifted by a fractional offset and scaled by `weight` to `output`
+///
+/// The shift is split into four whole pixel shifts weighted bilinearly
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn add_shifted(
+    output: &mut [f32], plane: &[f32], width: usize, height: usize, (dx, dy): (f32, f32),
+    weight: f32
+) {
+    // anything past the edges reads the same pixels, keep shifts in a range
+    // where the whole pixel offsets below can't overflow
+    let dx = dx.clamp(-(width as f32) - 1.0, width as f32 + 1.0);
+    let dy = dy.clamp(-(height as f32) - 1.0, height as f32 + 1.0);
+    let (x0, y0) = (dx.floor(), dy.floor());
+    let (fx, fy) = (dx - x0, dy - y0);
+    let (x0, y0) = (x0 as isize, y0 as isize);
+
+    for (shift_x, shift_y, w) in [
+        (x0, y0, (1.0 - fx) * (1.0 - fy)),
+        (x0 + 1, y0, fx * (1.0 - fy)),
+        (x0, y0 + 1, (1.0 - fx) * fy),
+        (x0 + 1, y0 + 1, fx * fy)
+    ] {
+        if w > 0.0 {
+            add_shifted_whole(output, plane, width, height, (shift_x, shift_y), weight * w);
+        }
+    }
+}
+
+/// Add `plane` shifted by whole pixels and scaled by `weight` to `output`, pixels
+/// outside the image are taken from its edges
+#[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+fn add_shifted_whole(
+    output: &mut [f32], plane: &[f32], width: usize, height: usize, (dx, dy): (isize, isize),
+    weight: f32
+) {
+    // shifting by more than the width only reads the edges, clamp so
+    // the empty middle part still slices within the row
+    let dx = dx.clamp(-(width as isize), width as isize);
+    // the part of a row whose source lies in the image
+    let start = (-dx).clamp(0, width as isize) as usize;
+    let end = (width as isize - dx).clamp(start as isize, width as isize) as usize;
+
+    for (y, out) in output.chunks_exact_mut(width).enumerate() {
+        let source_y = (y as isize + dy).clamp(0, height as isize - 1) as usize;
+        let source = &plane[source_y * width..][..width];
+
+        let (left, rest) = out.split_at_mut(start);
+        let (middle, right) = rest.split_at_mut(end - start);
+
+        for x in left {
+            *x += weight * source[0];
+        }
+        for x in right {
+            *x += weight * source[width - 1];
+        }
+        // a plain loop over two slices of the same length, which the compiler vectorizes
+        let shifted = &source[(start as isize + dx) as usize..][..middle.len()];
+        for (x, s) in middle.iter_mut().zip(shifted) {
+            *x += weight * s;
+        }
+    }
+}
+
+/// Return the bilinearly interpolated value of `plane` at `(x, y)`, clamped to its edges
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn sample_bilinear(plane: &[f32], width: usize, height: usize, x: f32, y: f32) -> f32 {
+    let x = x.clamp(0.0, (width - 1) as f32);
+    let y = y.clamp(0.0, (height - 1) as f32);
+
+    let (x0, y0) = (x as usize, y as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+
+    let top = plane[y0 * width + x0] * (1.0 - fx) + plane[y0 * width + x1] * fx;
+    let bottom = plane[y1 * width + x0] * (1.0 - fx) + plane[y1 * width + x1] * fx;
+
+    top * (1.0 - fy) + bottom * fy
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::motion_blur::{MotionBlur, RadialBlur};
+
+    #[test]
+    fn test_motion_blur_follows_angle() {
+        // a single bright pixel
+        let dot = || {
+            Image::from_fn::<f32, _>(21, 21, ColorSpace::Luma, |y, x, pix| {
+                pix[0] = if (x, y) == (10, 10) { 1.0 } else { 0.0 };
+            })
+        };
+        let mut image = dot();
+        MotionBlur::new(0.0, 8.0).execute(&mut image).unwrap();
+        let pixels = &image.flatten_frames::<f32>()[0];
+
+        // smeared into a horizontal line of nine pixels
+        for x in 6..=14 {
+            assert!((pixels[10 * 21 + x] - 1.0 / 9.0).abs() < 1e-5, "{x}");
+        }
+        assert!(pixels[10 * 21 + 5].abs() < 1e-6);
+        assert!(pixels[9 * 21 + 10].abs() < 1e-6);
+        assert!((pixels.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+
+        let mut image = dot();
+        MotionBlur::new(90.0, 4.0).execute(&mut image).unwrap();
+        let pixels = &image.flatten_frames::<f32>()[0];
+
+        for y in 8..=12 {
+            assert!((pixels[y * 21 + 10] - 0.2).abs() < 1e-5, "{y}");
+        }
+        assert!(pixels[10 * 21 + 11].abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_motion_blur_longer_than_image() {
+        // a flat image stays flat however far it is smeared
+        for angle in [0.0, 90.0, 180.0, 270.0, 30.0] {
+            let mut image = Image::fill::<u8>(100, ColorSpace::Luma, 4, 4);
+            MotionBlur::new(angle, 10.0).execute(&mut image).unwrap();
+
+            assert!(image.flatten_to_u8()[0].iter().all(|x| *x == 100));
+        }
+
+        // samples are capped by the image size, so this finishes quickly
+        let mut image = Image::fill::<u8>(100, ColorSpace::Luma, 1, 1);
+        MotionBlur::new(30.0, 1e9).execute(&mut image).unwrap();
+        assert_eq!(image.flatten_to_u8()[0], [100]);
+
+        assert!(MotionBlur::new(30.0, f32::INFINITY)
+            .execute(&mut image)
+            .is_err());
+    }
+
+    #[test]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn test_zoom_and_spin() {
+        // a horizontal line through the center and a ring around it
+        let ring = |y: usize, x: usize| {
+            let r = (x as f32 - 10.0).hypot(y as f32 - 10.0);
+            (255.0 * (1.0 - (r - 6.0).abs() / 3.0).max(0.0)) as u8
+        };
+        let pattern = |y: usize, x: usize| if y == 10 { 200 } else { ring(y, x) };
+        let image = Image::from_fn::<u8, _>(21, 21, ColorSpace::Luma, |y, x, pix| {
+            pix[0] = pattern(y, x);
+        });
+
+        // zooming keeps lines towards the center and blurs the ring
+        let mut zoomed = image.clone();
+        RadialBlur::zoom(0.5).execute(&mut zoomed).unwrap();
+        let pixels = &zoomed.flatten_to_u8()[0];
+
+        assert!(pixels[10 * 21..][..21].iter().all(|x| *x == 200));
+        assert!(pixels[4 * 21 + 10] < 150, "{}", pixels[4 * 21 + 10]);
+        assert!(pixels[21 + 10] > 50, "{}", pixels[21 + 10]);
+
+        // spinning keeps the ring and blurs the line away from the center
+        let mut spun = image;
+        RadialBlur::spin(30.0).execute(&mut spun).unwrap();
+        let pixels = &spun.flatten_to_u8()[0];
+
+        assert_eq!(pixels[10 * 21 + 10], 200);
+        assert!(pixels[10 * 21 + 1] < 150, "{}", pixels[10 * 21 + 1]);
+        for (y, x) in [(4, 10), (14, 14), (3, 8), (16, 10)] {
+            let difference = i32::from(pixels[y * 21 + x]) - i32::from(ring(y, x));
+            assert!(difference.abs() < 25, "{y} {x} {difference}");
+        }
+    }
+}
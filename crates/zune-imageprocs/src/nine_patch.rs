@@ -0,0 +1,303 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Nine-patch (nine-slice) scaling
+//!
+//! UI assets like buttons, panels and speech bubbles have corners and borders that
+//! should keep their look at any size. Nine-patch scaling splits the image into
+//! a 3x3 grid using four insets from the edges:
+//!
+//! ```text
+//!   +-----+-----------+-----+
+//!   |  1  |     2     |  3  |  top
+//!   +-----+-----------+-----+
+//!   |  4  |     5     |  6  |
+//!   +-----+-----------+-----+
+//!   |  7  |     8     |  9  |  bottom
+//!   +-----+-----------+-----+
+//!    left              right
+//! ```
+//!
+//! - Corners (1, 3, 7, 9) are copied as is
+//! - Top and bottom edges (2, 8) are only stretched horizontally
+//! - Left and right edges (4, 6) are only stretched vertically
+//! - The center (5) is stretched in both directions
+//!
+//! When the target is smaller than the two insets of a direction, the insets are
+//! shrunk proportionally and the corners are scaled down with them.
+//!
+//! # Example
+//! - Scale a 32x32 button with 8 pixel borders to 200x48
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::nine_patch::{Insets, NinePatch};
+//!
+//! let mut image = Image::fill::<u8>(200, ColorSpace::RGBA, 32, 32);
+//! NinePatch::new(200, 48, Insets::uniform(8)).execute(&mut image).unwrap();
+//! assert_eq!(image.dimensions(), (200, 48));
+//! ```
+use std::ops::Range;
+
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::resize::{resize, ResizeMethod};
+use crate::traits::NumOps;
+use crate::utils::execute_on;
+
+/// Distances in pixels from the edges of an image to the stretchable center
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Insets {
+    pub left:   usize,
+    pub top:    usize,
+    pub right:  usize,
+    pub bottom: usize
+}
+
+impl Insets {
+    /// Create new insets
+    #[must_use]
+    pub const fn new(left: usize, top: usize, right: usize, bottom: usize) -> Insets {
+        Insets {
+            left,
+            top,
+            right,
+            bottom
+        }
+    }
+    /// Create insets with the same distance from every edge
+    #[must_use]
+    pub const fn uniform(inset: usize) -> Insets {
+        Insets::new(inset, inset, inset, inset)
+    }
+}
+
+/// Scale an image to a new size without distorting its corners
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct NinePatch {
+    width:  usize,
+    height: usize,
+    insets: Insets,
+    method: ResizeMethod
+}
+
+impl NinePatch {
+    /// Create a new nine-patch operation scaling the image to `width` x `height`
+    ///
+    /// Edges and the center are stretched with bilinear interpolation by default
+    #[must_use]
+    pub fn new(width: usize, height: usize, insets: Insets) -> NinePatch {
+        NinePatch {
+            width,
+            height,
+            insets,
+            method: ResizeMethod::Bilinear
+        }
+    }
+    /// Set the interpolation used to stretch the edges and the center
+    #[must_use]
+    pub fn set_method(mut self, method: ResizeMethod) -> Self {
+        self.method = method;
+        self
+    }
+}
+
+impl OperationsTrait for NinePatch {
+    fn name(&self) -> &'static str {
+        "Nine Patch"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let insets = self.insets;
+
+        if insets.left + insets.right >= width || insets.top + insets.bottom >= height {
+            return Err(ImageErrors::GenericString(format!(
+                "Insets {insets:?} leave no center in a {width}x{height} image"
+            )));
+        }
+        if self.width == 0 || self.height == 0 {
+            return Err(ImageErrors::GenericStr(
+                "Nine patch target dimensions should not be zero"
+            ));
+        }
+        let columns = slices(insets.left, insets.right, width, self.width);
+        let rows = slices(insets.top, insets.bottom, height, self.height);
+
+        let depth = image.depth();
+        let new_length = self.width * self.height * depth.size_of();
+
+        let nine_patch_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
+            let mut new_channel = Channel::new_with_bit_type(new_length, depth.bit_type());
+
+            match depth.bit_type() {
+                BitType::U8 => nine_patch::<u8>(
+                    channel.reinterpret_as()?,
+                    new_channel.reinterpret_as_mut()?,
+                    (width, self.width),
+                    &columns,
+                    &rows,
+                    self.method
+                ),
+                BitType::U16 => nine_patch::<u16>(
+                    channel.reinterpret_as()?,
+                    new_channel.reinterpret_as_mut()?,
+                    (width, self.width),
+                    &columns,
+                    &rows,
+                    self.method
+                ),
+                BitType::F32 => nine_patch::<f32>(
+                    channel.reinterpret_as()?,
+                    new_channel.reinterpret_as_mut()?,
+                    (width, self.width),
+                    &columns,
+                    &rows,
+                    self.method
+                ),
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+            *channel = new_channel;
+            Ok(())
+        };
+        execute_on(nine_patch_fn, image, false)?;
+        image.set_dimensions(self.width, self.height);
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Split a `src` long axis with insets `start` and `end` into three ranges, and
+/// return them paired with the matching ranges of a `dst` long axis
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn slices(start: usize, end: usize, src: usize, dst: usize) -> [(Range<usize>, Range<usize>); 3] {
+    let (dst_start, dst_end) = if start + end > dst {
+        // not enough room for the borders, shrink them keeping their ratio
+        let scaled = (start as f32 * dst as f32 / (start + end) as f32).round() as usize;
+        (scaled, dst - scaled)
+    } else {
+        (start, end)
+    };
+    [
+        (0..start, 0..dst_start),
+        (start..src - end, dst_start..dst - dst_end),
+        (src - end..src, dst - dst_end..dst)
+    ]
+}
+
+fn nine_patch<T>(
+    src: &[T], dst: &mut [T], (src_width, dst_width): (usize, usize),
+    columns: &[(Range<usize>, Range<usize>); 3], rows: &[(Range<usize>, Range<usize>); 3],
+    method: ResizeMethod
+) where
+    T: Copy + Default + NumOps<T>,
+    f32: From<T>
+{
+    let mut src_patch = vec![];
+    let mut dst_patch = vec![];
+
+    for (src_rows, dst_rows) in rows {
+        for (src_columns, dst_columns) in columns {
+            // empty borders, or borders shrunk to nothing
+            if dst_rows.is_empty() || dst_columns.is_empty() {
+                continue;
+            }
+            src_patch.clear();
+            for y in src_rows.clone() {
+                let start = y * src_width;
+                src_patch
+                    .extend_from_slice(&src[start + src_columns.start..start + src_columns.end]);
+            }
+            dst_patch.resize(dst_rows.len() * dst_columns.len(), T::default());
+            resize(
+                &src_patch,
+                &mut dst_patch,
+                method,
+                src_columns.len(),
+                src_rows.len(),
+                dst_columns.len(),
+                dst_rows.len()
+            );
+            for (y, patch_row) in dst_rows
+                .clone()
+                .zip(dst_patch.chunks_exact(dst_columns.len()))
+            {
+                let start = y * dst_width;
+                dst[start + dst_columns.start..start + dst_columns.end].copy_from_slice(patch_row);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::nine_patch::{Insets, NinePatch};
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn test_corners_preserved() {
+        // every pixel is unique so any distortion of a corner shows
+        let image = Image::from_fn::<u8, _>(8, 8, ColorSpace::Luma, |y, x, pix| {
+            pix[0] = (y * 8 + x) as u8;
+        });
+        let mut scaled = image.clone();
+        NinePatch::new(30, 20, Insets::new(2, 3, 2, 1))
+            .execute(&mut scaled)
+            .unwrap();
+        assert_eq!(scaled.dimensions(), (30, 20));
+
+        let before = &image.flatten_to_u8()[0];
+        let after = &scaled.flatten_to_u8()[0];
+        // top left, top right and bottom right corners
+        for y in 0..3 {
+            assert_eq!(before[y * 8..y * 8 + 2], after[y * 30..y * 30 + 2]);
+            assert_eq!(
+                before[y * 8 + 6..y * 8 + 8],
+                after[y * 30 + 28..y * 30 + 30]
+            );
+        }
+        assert_eq!(before[62..64], after[19 * 30 + 28..]);
+
+        // the top edge keeps its rows, the left edge is only stretched vertically
+        assert!(after[..30].iter().all(|x| *x < 8));
+        assert!((1..20).all(|y| after[y * 30] >= after[(y - 1) * 30]));
+        assert!((0..20).all(|y| after[y * 30 + 1] == after[y * 30] + 1));
+    }
+
+    #[test]
+    fn test_smaller_than_insets() {
+        let mut image = Image::fill::<u16>(1000, ColorSpace::RGB, 20, 20);
+        NinePatch::new(6, 30, Insets::new(8, 4, 4, 4))
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.dimensions(), (6, 30));
+
+        let mut image = Image::fill::<u8>(0, ColorSpace::Luma, 8, 8);
+        let no_center = NinePatch::new(16, 16, Insets::uniform(4));
+        assert!(no_center.execute(&mut image).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Oil paint effect
+//!
+//! The luma of the neighbourhood of every pixel is quantized into a number of intensity
+//! levels, the pixel is replaced with the mean color of the pixels in the most common level.
+//! Details smaller than the neighbourhood are lost while edges stay sharp, which gives
+//! the flat dabs of paint the effect is named after.
+//!
+//! All color channels are filtered and alpha is kept.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::oil_paint::OilPaint;
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+//! OilPaint::new(4, 20).execute(&mut image).unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::utils::map_color_planes;
+
+/// Give an image the look of an oil painting
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct OilPaint {
+    radius: usize,
+    levels: usize
+}
+
+impl OilPaint {
+    /// Create a new oil paint effect
+    ///
+    /// # Arguments
+    /// - radius: The neighbourhood of a pixel is a square of `2*radius+1` pixels
+    /// - levels: Number of intensity levels, between 2 and 256, fewer levels give
+    ///   larger and flatter dabs of paint
+    #[must_use]
+    pub fn new(radius: usize, levels: usize) -> OilPaint {
+        OilPaint { radius, levels }
+    }
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn filter(
+        &self, planes: &[Vec<f32>], luma: &[f32], width: usize, height: usize
+    ) -> Vec<Vec<f32>> {
+        let radius = self.radius;
+        let levels = self.levels.clamp(2, 256);
+        let components = planes.len();
+
+        let bins: Vec<usize> = luma
+            .iter()
+            .map(|x| (x.clamp(0.0, 1.0) * (levels - 1) as f32).round() as usize)
+            .collect();
+
+        let mut output = vec![vec![0.0; width * height]; components];
+        // per level the number of pixels, then the sum of every channel
+        let stride = 1 + components;
+        let mut histogram = vec![0.0_f32; levels * stride];
+
+        for y in 0..height {
+            let rows = y.saturating_sub(radius)..(y + radius + 1).min(height);
+
+            let update_column = |histogram: &mut [f32], x: usize, sign: f32| {
+                for row in rows.clone() {
+                    let index = row * width + x;
+                    let level = &mut histogram[bins[index] * stride..][..stride];
+
+                    level[0] += sign;
+                    for (sum, plane) in level[1..].iter_mut().zip(planes) {
+                        *sum += sign * plane[index];
+                    }
+                }
+            };
+            histogram.fill(0.0);
+            for x in 0..radius.min(width) {
+                update_column(&mut histogram, x, 1.0);
+            }
+            // slide the window along the row, adding the column entering it on the right
+            // and removing the one leaving it on the left
+            for x in 0..width {
+                if x + radius < width {
+                    update_column(&mut histogram, x + radius, 1.0);
+                }
+                if x > radius {
+                    update_column(&mut histogram, x - radius - 1, -1.0);
+                }
+                let level = histogram
+                    .chunks_exact(stride)
+                    .reduce(|a, b| if b[0] > a[0] { b } else { a })
+                    .unwrap();
+
+                for (out, sum) in output.iter_mut().zip(&level[1..]) {
+                    out[y * width + x] = sum / level[0];
+                }
+            }
+        }
+        output
+    }
+}
+
+impl OperationsTrait for OilPaint {
+    fn name(&self) -> &'static str {
+        "Oil Paint"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+
+        map_color_planes(image, self.name(), |planes, luma| {
+            self.filter(planes, luma, width, height)
+        })
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::Luma,
+            ColorSpace::LumaA
+        ]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::oil_paint::OilPaint;
+
+    #[test]
+    fn test_oil_paint() {
+        // mostly dark blue with a few lighter speckles, and a light right edge
+        let mut image = Image::from_fn::<u8, _>(16, 16, ColorSpace::RGB, |y, x, pix| {
+            let color = if x >= 13 {
+                [250, 250, 250]
+            } else if (x * 5 + y * 3) % 7 == 0 {
+                [120, 120, 140]
+            } else {
+                [10, 20, 100]
+            };
+            pix[..3].copy_from_slice(&color);
+        });
+        OilPaint::new(2, 8).execute(&mut image).unwrap();
+
+        let pixels = &image.flatten_to_u8()[0];
+        let pixel = |x: usize, y: usize| &pixels[(y * 16 + x) * 3..][..3];
+
+        // the speckles are painted over, the edge is kept
+        for y in 0..16 {
+            for x in 0..13 {
+                assert_eq!(pixel(x, y), [10, 20, 100], "{x} {y}");
+            }
+            for x in 13..16 {
+                assert_eq!(pixel(x, y), [250, 250, 250], "{x} {y}");
+            }
+        }
+    }
+}
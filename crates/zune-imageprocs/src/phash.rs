@@ -0,0 +1,286 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Perceptual image hashes
+//!
+//! Perceptual hashes summarize how an image looks in 64 bits, visually similar images,
+//! e.g. the same photo resized, recompressed or slightly color corrected,
+//! get hashes that differ in few bits, which makes them useful for finding duplicates.
+//!
+//! Hashes are compared with [`hamming_distance`], as a rule of thumb a distance
+//! below 10 means the images are likely the same.
+//!
+//! - [`average_hash`]: Fastest, compares each pixel of an 8x8 thumbnail to the mean
+//! - [`difference_hash`]: Compares neighbouring pixels of a 9x8 thumbnail, robust to
+//!   brightness and contrast changes
+//! - [`perceptual_hash`]: Compares low frequencies of a DCT of a 32x32 thumbnail,
+//!   the most robust and the slowest
+//!
+//! Hashes are computed on the luminance of the first frame, alpha is ignored.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_imageprocs::phash::{hamming_distance, perceptual_hash};
+//!
+//! let pattern = |y: usize, x: usize| ((x / 10 + y / 20) % 3 * 60 + x) as u8;
+//!
+//! let image = Image::from_fn::<u8, _>(100, 100, ColorSpace::RGB, |y, x, pix| {
+//!     pix.fill(pattern(y, x));
+//! });
+//! let brighter = Image::from_fn::<u8, _>(100, 100, ColorSpace::RGB, |y, x, pix| {
+//!     pix.fill(pattern(y, x) + 10);
+//! });
+//!
+//! let distance = hamming_distance(
+//!     perceptual_hash(&image).unwrap(),
+//!     perceptual_hash(&brighter).unwrap()
+//! );
+//! assert!(distance < 10);
+//! ```
+use zune_core::bit_depth::BitDepth;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+
+/// Size of the thumbnail the DCT of [`perceptual_hash`] is computed on
+const DCT_SIZE: usize = 32;
+/// Size of the low frequency block kept from the DCT
+const HASH_SIZE: usize = 8;
+
+/// A perceptual hash algorithm
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum HashMethod {
+    /// See [`average_hash`]
+    Average,
+    /// See [`difference_hash`]
+    Difference,
+    /// See [`perceptual_hash`]
+    Perceptual
+}
+
+impl HashMethod {
+    pub fn from_string_result(input: &str) -> Result<Self, String> {
+        match input {
+            "ahash" | "average" => Ok(Self::Average),
+            "dhash" | "difference" => Ok(Self::Difference),
+            "phash" | "perceptual" => Ok(Self::Perceptual),
+            _ => Err("Unknown hash method, accepted values are ahash,dhash,phash".to_string())
+        }
+    }
+}
+
+/// Compute the hash of an image with the given method
+///
+/// # Errors
+/// If the image is empty or can't be converted to grayscale
+pub fn image_hash(image: &Image, method: HashMethod) -> Result<u64, ImageErrors> {
+    match method {
+        HashMethod::Average => average_hash(image),
+        HashMethod::Difference => difference_hash(image),
+        HashMethod::Perceptual => perceptual_hash(image)
+    }
+}
+
+/// Compute the average hash (aHash) of an image
+///
+/// The image is shrunk to 8x8, each bit is set if the pixel is brighter
+/// than the mean, starting from the top left pixel at the most significant bit
+///
+/// # Errors
+/// If the image is empty or can't be converted to grayscale
+pub fn average_hash(image: &Image) -> Result<u64, ImageErrors> {
+    let pixels = thumbnail(image, HASH_SIZE, HASH_SIZE)?;
+    #[allow(clippy::cast_precision_loss)]
+    let mean = pixels.iter().sum::<f32>() / pixels.len() as f32;
+
+    Ok(bits(pixels.iter().map(|x| *x > mean)))
+}
+
+/// Compute the difference hash (dHash) of an image
+///
+/// The image is shrunk to 9x8, each bit is set if a pixel is brighter than
+/// the one to its left, starting from the top left at the most significant bit
+///
+/// # Errors
+/// If the image is empty or can't be converted to grayscale
+pub fn difference_hash(image: &Image) -> Result<u64, ImageErrors> {
+    let pixels = thumbnail(image, HASH_SIZE + 1, HASH_SIZE)?;
+
+    Ok(bits(pixels.chunks_exact(HASH_SIZE + 1).flat_map(|row| {
+        row.windows(2).map(|pair| pair[1] > pair[0])
+    })))
+}
+
+/// Compute the DCT based perceptual hash (pHash) of an image
+///
+/// The image is shrunk to 32x32 and transformed with a DCT, each bit is set if the
+/// matching coefficient of the 8x8 lowest frequencies is above their median,
+/// starting from the DC coefficient at the most significant bit
+///
+/// # Errors
+/// If the image is empty or can't be converted to grayscale
+pub fn perceptual_hash(image: &Image) -> Result<u64, ImageErrors> {
+    let pixels = thumbnail(image, DCT_SIZE, DCT_SIZE)?;
+    let coefficients = dct_low_frequencies(&pixels);
+
+    let mut sorted = coefficients;
+    sorted.sort_unstable_by(f32::total_cmp);
+    let median = f32::midpoint(sorted[sorted.len() / 2 - 1], sorted[sorted.len() / 2]);
+
+    Ok(bits(coefficients.iter().map(|x| *x > median)))
+}
+
+/// Return the number of bits that differ between two hashes
+///
+/// 0 means the hashes are identical and 64 that every bit differs
+#[must_use]
+pub const fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Pack up to 64 bits into an integer, first bit at the most significant position
+fn bits(values: impl Iterator<Item = bool>) -> u64 {
+    values.fold(0, |hash, bit| (hash << 1) | u64::from(bit))
+}
+
+/// Return the luminance of the first frame shrunk to `width` by `height`
+/// by averaging the pixels covered by each output pixel
+fn thumbnail(image: &Image, width: usize, height: usize) -> Result<Vec<f32>, ImageErrors> {
+    let (in_width, in_height) = image.dimensions();
+
+    if in_width == 0 || in_height == 0 || image.frames_len() == 0 {
+        return Err(ImageErrors::GenericStr("Cannot hash an empty image"));
+    }
+    let mut luma = image.clone();
+    luma.convert_color(ColorSpace::Luma)?;
+    luma.convert_depth(BitDepth::Float32)?;
+
+    let pixels =
+        luma.frames_ref()[0].channels_ref(ColorSpace::Luma, true)[0].reinterpret_as::<f32>()?;
+
+    let mut sums = vec![0.0_f32; width * height];
+    let mut counts = vec![0_u32; width * height];
+
+    for (y, row) in pixels.chunks_exact(in_width).enumerate() {
+        let out_y = y * height / in_height;
+
+        for (x, pixel) in row.iter().enumerate() {
+            let out = out_y * width + x * width / in_width;
+            sums[out] += pixel;
+            counts[out] += 1;
+        }
+    }
+    // images smaller than the thumbnail leave gaps, fill them with the nearest pixel
+    #[allow(clippy::cast_precision_loss)]
+    let thumbnail = (0..width * height)
+        .map(|i| {
+            if counts[i] == 0 {
+                let (x, y) = (i % width, i / width);
+                pixels[(y * in_height / height) * in_width + x * in_width / width]
+            } else {
+                sums[i] / counts[i] as f32
+            }
+        })
+        .collect();
+
+    Ok(thumbnail)
+}
+
+/// Compute the lowest 8x8 frequencies of the two dimensional DCT-II of a 32x32 block
+#[allow(clippy::cast_precision_loss)]
+fn dct_low_frequencies(pixels: &[f32]) -> [f32; HASH_SIZE * HASH_SIZE] {
+    let mut cosines = [[0.0_f32; DCT_SIZE]; HASH_SIZE];
+
+    for (k, row) in cosines.iter_mut().enumerate() {
+        for (n, value) in row.iter_mut().enumerate() {
+            *value = (std::f32::consts::PI / DCT_SIZE as f32 * (n as f32 + 0.5) * k as f32).cos();
+        }
+    }
+    // transform the rows, keeping the low frequencies
+    let mut rows = [[0.0_f32; HASH_SIZE]; DCT_SIZE];
+
+    for (row, out) in pixels.chunks_exact(DCT_SIZE).zip(rows.iter_mut()) {
+        for (value, cosine) in out.iter_mut().zip(&cosines) {
+            *value = row.iter().zip(cosine).map(|(p, c)| p * c).sum();
+        }
+    }
+    // then the columns
+    let mut coefficients = [0.0_f32; HASH_SIZE * HASH_SIZE];
+
+    for (v, cosine) in cosines.iter().enumerate() {
+        for u in 0..HASH_SIZE {
+            coefficients[v * HASH_SIZE + u] =
+                rows.iter().zip(cosine).map(|(row, c)| row[u] * c).sum();
+        }
+    }
+    coefficients
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+
+    use crate::phash::{
+        average_hash, difference_hash, hamming_distance, image_hash, perceptual_hash, HashMethod
+    };
+
+    fn pattern(width: usize, height: usize, invert: bool) -> Image {
+        Image::from_fn::<u8, _>(width, height, ColorSpace::RGB, |y, x, pix| {
+            let value = ((x * 7 + y * 3) % 256) ^ ((x / 16 + y / 16) % 2 * 255);
+            let value = u8::try_from(value).unwrap();
+            pix.fill(if invert { 255 - value } else { value });
+        })
+    }
+
+    #[test]
+    fn test_hashes_of_similar_images() {
+        let image = pattern(256, 256, false);
+        let small = pattern(128, 128, false);
+        let inverted = pattern(256, 256, true);
+
+        for method in [
+            HashMethod::Average,
+            HashMethod::Difference,
+            HashMethod::Perceptual
+        ] {
+            let hash = image_hash(&image, method).unwrap();
+
+            assert_eq!(hash, image_hash(&image.clone(), method).unwrap());
+            assert!(hamming_distance(hash, image_hash(&inverted, method).unwrap()) > 32);
+            assert!(image_hash(&small, method).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_gradient_hashes() {
+        // brightness increases to the right
+        let image = Image::from_fn::<u16, _>(90, 80, ColorSpace::Luma, |_, x, pix| {
+            pix[0] = u16::try_from(x * 700).unwrap();
+        });
+        assert_eq!(difference_hash(&image).unwrap(), u64::MAX);
+        // the right half is brighter than the mean
+        assert_eq!(average_hash(&image).unwrap(), 0x0f0f_0f0f_0f0f_0f0f);
+
+        let tiny = Image::fill::<u8>(10, ColorSpace::Luma, 3, 2);
+        assert_eq!(
+            perceptual_hash(&tiny).unwrap(),
+            perceptual_hash(&tiny).unwrap()
+        );
+        assert!(average_hash(&Image::fill::<u8>(0, ColorSpace::Luma, 0, 0)).is_err());
+    }
+
+    #[test]
+    fn test_hamming_distance() {
+        assert_eq!(hamming_distance(0, 0), 0);
+        assert_eq!(hamming_distance(0, u64::MAX), 64);
+        assert_eq!(hamming_distance(0b1010, 0b0110), 2);
+    }
+}
@@ -0,0 +1,344 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Pixelate images and redact parts of them
+//!
+//! [`Pixelate`] replaces square blocks of an image with their average color, giving the
+//! mosaic look of a low resolution image.
+//!
+//! [`Redact`] pixelates or blurs a list of rectangles, e.g. faces or license plates found by
+//! an external detector, leaving the rest of the image untouched. Only pixels inside a
+//! rectangle are used to compute its new values, so nothing from outside leaks into it.
+//!
+//! # Example
+//! - Blur two regions of an image
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::pixelate::{Rectangle, Redact, RedactMethod};
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+//! let faces = vec![Rectangle::new(10, 10, 20, 20), Rectangle::new(60, 40, 25, 30)];
+//!
+//! Redact::new(faces, RedactMethod::Blur(8.0)).execute(&mut image).unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::gaussian_blur::gaussian_blur_f32;
+use crate::traits::NumOps;
+use crate::utils::{execute_on, to_sample};
+
+/// A rectangle inside an image
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rectangle {
+    /// Column of the top left corner
+    pub x:      usize,
+    /// Row of the top left corner
+    pub y:      usize,
+    pub width:  usize,
+    pub height: usize
+}
+
+impl Rectangle {
+    /// Create a new rectangle
+    #[must_use]
+    pub const fn new(x: usize, y: usize, width: usize, height: usize) -> Rectangle {
+        Rectangle {
+            x,
+            y,
+            width,
+            height
+        }
+    }
+    /// Return the part of the rectangle inside an image of `width` x `height` pixels,
+    /// or `None` if they don't overlap
+    #[must_use]
+    pub fn clip(&self, width: usize, height: usize) -> Option<Rectangle> {
+        let right = self.x.saturating_add(self.width).min(width);
+        let bottom = self.y.saturating_add(self.height).min(height);
+
+        if self.x >= right || self.y >= bottom {
+            return None;
+        }
+        Some(Rectangle::new(
+            self.x,
+            self.y,
+            right - self.x,
+            bottom - self.y
+        ))
+    }
+}
+
+/// How a region is hidden by [`Redact`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub enum RedactMethod {
+    /// Pixelate the region with blocks of this size
+    Pixelate(usize),
+    /// Blur the region with a gaussian blur of this sigma
+    Blur(f32)
+}
+
+/// Pixelate an image
+///
+/// Blocks start at the top left corner, the blocks of the last row and column are
+/// smaller when the dimensions are not a multiple of the block size.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::pixelate::Pixelate;
+///
+/// let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+/// Pixelate::new(10).execute(&mut image).unwrap();
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pixelate {
+    block_size: usize
+}
+
+impl Pixelate {
+    /// Create a new pixelate operation
+    ///
+    /// # Arguments
+    /// - block_size: Width and height of the blocks in pixels, a size of 1 does nothing
+    #[must_use]
+    pub fn new(block_size: usize) -> Pixelate {
+        Pixelate { block_size }
+    }
+}
+
+impl OperationsTrait for Pixelate {
+    fn name(&self) -> &'static str {
+        "Pixelate"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let full = Rectangle::new(0, 0, width, height);
+
+        Redact::new(vec![full], RedactMethod::Pixelate(self.block_size)).execute_impl(image)
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Pixelate or blur regions of an image
+///
+/// Rectangles are clipped to the image, all channels including alpha are changed.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Redact {
+    regions: Vec<Rectangle>,
+    method:  RedactMethod
+}
+
+impl Redact {
+    /// Create a new redact operation hiding `regions` with `method`
+    #[must_use]
+    pub fn new(regions: Vec<Rectangle>, method: RedactMethod) -> Redact {
+        Redact { regions, method }
+    }
+}
+
+impl OperationsTrait for Redact {
+    fn name(&self) -> &'static str {
+        "Redact"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let depth = image.depth().bit_type();
+
+        let regions: Vec<Rectangle> = self
+            .regions
+            .iter()
+            .filter_map(|x| x.clip(width, height))
+            .collect();
+
+        let redact_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
+            for region in &regions {
+                match depth {
+                    BitType::U8 => {
+                        redact::<u8>(channel.reinterpret_as_mut()?, width, region, self.method);
+                    }
+                    BitType::U16 => {
+                        redact::<u16>(channel.reinterpret_as_mut()?, width, region, self.method);
+                    }
+                    BitType::F32 => {
+                        redact::<f32>(channel.reinterpret_as_mut()?, width, region, self.method);
+                    }
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+                }
+            }
+            Ok(())
+        };
+        execute_on(redact_fn, image, false)
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+fn redact<T>(data: &mut [T], width: usize, region: &Rectangle, method: RedactMethod)
+where
+    T: Copy + NumOps<T>
+{
+    match method {
+        RedactMethod::Pixelate(block_size) => pixelate(data, width, region, block_size),
+        RedactMethod::Blur(sigma) => {
+            if region.width < 2 || region.height < 2 {
+                // too thin to blur, average it instead
+                pixelate(data, width, region, region.width.max(region.height));
+            } else {
+                blur(data, width, region, sigma);
+            }
+        }
+    }
+}
+
+/// Replace blocks of `region`, starting from its top left corner, with their average
+#[allow(clippy::cast_precision_loss)]
+fn pixelate<T>(data: &mut [T], width: usize, region: &Rectangle, block_size: usize)
+where
+    T: Copy + NumOps<T>
+{
+    let block_size = block_size.max(1);
+    if block_size == 1 {
+        return;
+    }
+    for block_y in (region.y..region.y + region.height).step_by(block_size) {
+        let block_height = block_size.min(region.y + region.height - block_y);
+
+        for block_x in (region.x..region.x + region.width).step_by(block_size) {
+            let block_width = block_size.min(region.x + region.width - block_x);
+
+            let rows = || {
+                (block_y..block_y + block_height)
+                    .map(|y| y * width + block_x..y * width + block_x + block_width)
+            };
+            let sum: f32 = rows()
+                .flat_map(|row| data[row].iter().map(|x| x.to_f32()))
+                .sum();
+            let average = to_sample::<T>(sum / (block_width * block_height) as f32);
+
+            for row in rows() {
+                data[row].fill(average);
+            }
+        }
+    }
+}
+
+/// Blur `region`, using only pixels inside it
+fn blur<T>(data: &mut [T], width: usize, region: &Rectangle, sigma: f32)
+where
+    T: Copy + NumOps<T>
+{
+    let rows = || {
+        (region.y..region.y + region.height)
+            .map(|y| y * width + region.x..y * width + region.x + region.width)
+    };
+    let mut pixels: Vec<f32> = rows()
+        .flat_map(|row| data[row].iter().map(|x| x.to_f32()))
+        .collect();
+    let mut scratch = vec![0.0; pixels.len()];
+
+    gaussian_blur_f32(
+        &mut pixels,
+        &mut scratch,
+        region.width,
+        region.height,
+        sigma
+    );
+
+    for (row, blurred) in rows().zip(pixels.chunks_exact(region.width)) {
+        for (out, value) in data[row].iter_mut().zip(blurred) {
+            *out = to_sample::<T>(*value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::pixelate::{Pixelate, Rectangle, Redact, RedactMethod};
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn test_pixelate() {
+        let mut image = Image::from_fn::<u8, _>(6, 2, ColorSpace::Luma, |y, x, pix| {
+            pix[0] = (y * 6 + x) as u8 * 10;
+        });
+        Pixelate::new(4).execute(&mut image).unwrap();
+
+        // a 4x2 block and a 2x2 block
+        assert_eq!(
+            image.flatten_to_u8()[0],
+            [45, 45, 45, 45, 75, 75, 45, 45, 45, 45, 75, 75]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn test_redact_regions() {
+        let image = Image::from_fn::<u8, _>(20, 20, ColorSpace::RGBA, |y, x, pix| {
+            let value = ((x * 7 + y * 13) % 256) as u8;
+            pix[..4].copy_from_slice(&[value, value / 2, 255 - value, 255]);
+        });
+        let inside = |x: usize, y: usize| (5..15).contains(&x) && (5..15).contains(&y);
+
+        for method in [RedactMethod::Pixelate(5), RedactMethod::Blur(3.0)] {
+            let mut redacted = image.clone();
+            // the second rectangle is partly outside the image
+            let regions = vec![Rectangle::new(5, 5, 10, 10), Rectangle::new(30, 0, 4, 4)];
+            Redact::new(regions, method).execute(&mut redacted).unwrap();
+
+            let before = &image.flatten_to_u8()[0];
+            let after = &redacted.flatten_to_u8()[0];
+            for y in 0..20 {
+                for x in 0..20 {
+                    let i = (y * 20 + x) * 4;
+                    if inside(x, y) {
+                        assert_eq!(after[i + 3], 255, "{method:?} {x} {y}");
+                    } else {
+                        assert_eq!(before[i..i + 4], after[i..i + 4], "{method:?} {x} {y}");
+                    }
+                }
+            }
+            let changed = (0..400)
+                .filter(|i| inside(i % 20, i / 20) && before[i * 4] != after[i * 4])
+                .count();
+            assert!(changed > 50, "{method:?} {changed}");
+        }
+    }
+
+    #[test]
+    fn test_rectangle_clip() {
+        assert_eq!(
+            Rectangle::new(8, 2, 5, 5).clip(10, 10),
+            Some(Rectangle::new(8, 2, 2, 5))
+        );
+        assert_eq!(Rectangle::new(10, 2, 5, 5).clip(10, 10), None);
+        assert_eq!(Rectangle::new(2, 2, 0, 5).clip(10, 10), None);
+    }
+}
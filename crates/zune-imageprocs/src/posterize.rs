@@ -0,0 +1,118 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Reduce the number of tones of an image
+//!
+//! Posterization rounds every channel to a few evenly spaced levels,
+//! giving flat areas of color like a screen printed poster.
+//!
+//! # Algorithm details
+//!
+//! ```text
+//! max_value -> maximum value of an image depth
+//! steps -> levels - 1
+//!
+//! pixel = round(pixel / max_value * steps) / steps * max_value
+//! ```
+//!
+//! # Implementation details
+//! - For `u8` and `u16` , we use lookup tables to improve speed
+//! - For `f32` naive execution is used
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::utils::{execute_on, map_samples};
+
+/// Posterize an image
+///
+/// The alpha channel is ignored.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::posterize::Posterize;
+///
+/// let mut image = Image::fill::<u8>(100, ColorSpace::RGB, 100, 100);
+/// // black, gray and white
+/// Posterize::new(3).execute(&mut image).unwrap();
+/// assert_eq!(image.flatten_to_u8()[0][0], 128);
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Posterize {
+    levels: u16
+}
+
+impl Posterize {
+    /// Create a new posterize operation
+    ///
+    /// # Arguments
+    /// - levels: Number of levels per channel, including black and white,
+    ///   values below 2 are treated as 2
+    #[must_use]
+    pub fn new(levels: u16) -> Posterize {
+        Posterize { levels }
+    }
+}
+
+impl OperationsTrait for Posterize {
+    fn name(&self) -> &'static str {
+        "Posterize"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let bit_type = image.depth().bit_type();
+        let steps = f32::from(self.levels.max(2) - 1);
+
+        let posterize_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
+            map_samples(channel, bit_type, self.name(), |x| {
+                (x * steps).round() / steps
+            })
+        };
+        execute_on(posterize_fn, image, true)
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::posterize::Posterize;
+
+    #[test]
+    fn test_posterize() {
+        let mut image = Image::from_fn::<u8, _>(6, 1, ColorSpace::LumaA, |_, x, pix| {
+            pix[0] = [0, 40, 43, 127, 200, 255][x];
+            pix[1] = 100;
+        });
+        Posterize::new(4).execute(&mut image).unwrap();
+
+        let pixels = &image.flatten_to_u8()[0];
+        let luma: Vec<u8> = pixels.iter().step_by(2).copied().collect();
+        assert_eq!(luma, [0, 0, 85, 85, 170, 255]);
+        // alpha is untouched
+        assert!(pixels.iter().skip(1).step_by(2).all(|x| *x == 100));
+    }
+
+    #[test]
+    fn test_posterize_float() {
+        let mut image = Image::fill::<f32>(0.3, ColorSpace::RGB, 4, 4);
+        Posterize::new(2).execute(&mut image).unwrap();
+        assert_eq!(image.flatten_to_u8()[0][0], 0);
+    }
+}
@@ -64,10 +64,21 @@ mod std_simd;
 /// Where alpha is to big to fit into target integer, or zero, there will
 /// be loss of image quality.
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct PremultiplyAlpha {
+    #[cfg_attr(feature = "serde-support", serde(with = "AlphaStateDef"))]
     to: AlphaState
 }
 
+/// Serde definition of [`AlphaState`], which lives in `zune-image`
+#[cfg(feature = "serde-support")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(remote = "AlphaState")]
+enum AlphaStateDef {
+    PreMultiplied,
+    NonPreMultiplied
+}
+
 impl PremultiplyAlpha {
     /// Create a new alpha pre-multiplication operation.
     ///
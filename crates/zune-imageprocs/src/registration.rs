@@ -0,0 +1,382 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Find the translation between two images
+//!
+//! Translations are found with phase correlation, the normalized cross power spectrum
+//! of two images which only differ by a translation has a single peak at that translation.
+//! Since only the phase is compared, it is insensitive to differences in brightness and
+//! contrast, which makes it suitable for aligning exposure brackets and for finding
+//! the overlap between tiles of a mosaic.
+//!
+//! The result can be applied with [`Shift`](crate::shift::Shift) to align two images,
+//! or with [`Stitch::add_image_registered`](crate::stitch::Stitch::add_image_registered)
+//! to place images on a common canvas.
+//!
+//! Images are compared by their luminance, alpha is ignored. Both images are zero
+//! padded to a power of two at least as large as both images combined, so translations
+//! are found for any overlap but large images are slow, registering downscaled copies and
+//! scaling the result back up is much faster.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_imageprocs::registration::register_translation;
+//!
+//! let pattern = |y: usize, x: usize| ((x * x + 3 * y * y + x * y) % 251) as u8;
+//!
+//! let reference = Image::from_fn::<u8, _>(64, 64, ColorSpace::Luma, |y, x, pix| {
+//!     pix[0] = pattern(y, x);
+//! });
+//! // the same scene, taken 5 pixels further right and 3 pixels further down
+//! let moving = Image::from_fn::<u8, _>(64, 64, ColorSpace::Luma, |y, x, pix| {
+//!     pix[0] = pattern(y + 3, x + 5);
+//! });
+//!
+//! let (dx, dy, confidence) = register_translation(&reference, &moving).unwrap();
+//! assert_eq!((dx, dy), (5.0, 3.0));
+//! assert!(confidence > 0.05);
+//! ```
+use std::f64::consts::PI;
+
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+
+use crate::utils::frame_luma;
+
+/// Find the translation of `moving` relative to `reference`
+///
+/// Returns `(dx, dy, confidence)`, where pixel `(x, y)` of `moving` shows what is at
+/// `(x + dx, y + dy)` in `reference`. Shifting `moving` by `(dx, dy)` aligns it with
+/// `reference`, and when stitching `moving` goes at `(dx, dy)` relative to `reference`.
+///
+/// The translation is in whole pixels, see [`register_translation_subpixel`]
+/// for sub pixel precision.
+///
+/// `confidence` is the height of the correlation peak, between 0.0 and 1.0. It is close to
+/// 1.0 for identical images and decreases with less overlap and more differences, values
+/// below `0.05` usually mean no translation was found, e.g. because the images don't overlap
+///
+/// # Errors
+/// If one of the images is empty or can't be converted to grayscale
+pub fn register_translation(
+    reference: &Image, moving: &Image
+) -> Result<(f32, f32, f32), ImageErrors> {
+    register(reference, moving, false)
+}
+
+/// Find the translation of `moving` relative to `reference` with sub pixel precision
+///
+/// This is [`register_translation`], with the position of the correlation
+/// peak refined by fitting a parabola through it and its neighbours.
+///
+/// # Errors
+/// If one of the images is empty or can't be converted to grayscale
+pub fn register_translation_subpixel(
+    reference: &Image, moving: &Image
+) -> Result<(f32, f32, f32), ImageErrors> {
+    register(reference, moving, true)
+}
+
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
+fn register(
+    reference: &Image, moving: &Image, subpixel: bool
+) -> Result<(f32, f32, f32), ImageErrors> {
+    let (ref_width, ref_height) = reference.dimensions();
+    let (mov_width, mov_height) = moving.dimensions();
+
+    // padding to the combined size makes the correlation linear instead of
+    // circular, so every translation where the images overlap is unambiguous
+    let width = (ref_width + mov_width).next_power_of_two();
+    let height = (ref_height + mov_height).next_power_of_two();
+
+    let (mut ref_re, mut ref_im) = (
+        padded_luma(reference, width, height)?,
+        vec![0.0; width * height]
+    );
+    let (mut mov_re, mut mov_im) = (
+        padded_luma(moving, width, height)?,
+        vec![0.0; width * height]
+    );
+
+    fft_2d(&mut ref_re, &mut ref_im, width, height, false);
+    fft_2d(&mut mov_re, &mut mov_im, width, height, false);
+
+    // normalized cross power spectrum, reference times the conjugate of moving
+    for i in 0..width * height {
+        let re = ref_re[i] * mov_re[i] + ref_im[i] * mov_im[i];
+        let im = ref_im[i] * mov_re[i] - ref_re[i] * mov_im[i];
+        let magnitude = re.hypot(im);
+
+        if magnitude > 1e-12 {
+            ref_re[i] = re / magnitude;
+            ref_im[i] = im / magnitude;
+        } else {
+            ref_re[i] = 0.0;
+            ref_im[i] = 0.0;
+        }
+    }
+    fft_2d(&mut ref_re, &mut ref_im, width, height, true);
+
+    let correlation = ref_re;
+    let peak = (0..correlation.len())
+        .max_by(|a, b| correlation[*a].total_cmp(&correlation[*b]))
+        .unwrap();
+    let (peak_x, peak_y) = (peak % width, peak / width);
+
+    let mut dx = signed(peak_x, width) as f32;
+    let mut dy = signed(peak_y, height) as f32;
+
+    if subpixel {
+        let at = |x: usize, y: usize| correlation[(y % height) * width + x % width];
+
+        dx += parabola_peak(
+            at(peak_x + width - 1, peak_y),
+            at(peak_x, peak_y),
+            at(peak_x + 1, peak_y)
+        );
+        dy += parabola_peak(
+            at(peak_x, peak_y + height - 1),
+            at(peak_x, peak_y),
+            at(peak_x, peak_y + 1)
+        );
+    }
+    // the inverse transform isn't scaled, a perfect match peaks at the number of samples
+    let confidence = (correlation[peak] / (width * height) as f32).clamp(0.0, 1.0);
+
+    Ok((dx, dy, confidence))
+}
+
+/// Map a position of a circular correlation of length `len` to a signed translation
+#[allow(clippy::cast_possible_wrap)]
+fn signed(position: usize, len: usize) -> isize {
+    if position > len / 2 {
+        position as isize - len as isize
+    } else {
+        position as isize
+    }
+}
+
+/// Return the offset, between -0.5 and 0.5, of the vertex of the parabola through
+/// three equally spaced samples from the center sample
+fn parabola_peak(left: f32, center: f32, right: f32) -> f32 {
+    let denominator = left - 2.0 * center + right;
+
+    if denominator.abs() < f32::EPSILON {
+        return 0.0;
+    }
+    (0.5 * (left - right) / denominator).clamp(-0.5, 0.5)
+}
+
+/// Return the luminance of the first frame without its mean, tapered towards the
+/// edges and placed at the top left of a zeroed `width` by `height` buffer
+///
+/// Removing the mean and tapering keep the image edges, which the images don't
+/// share, from dominating the correlation
+#[allow(clippy::cast_precision_loss)]
+fn padded_luma(image: &Image, width: usize, height: usize) -> Result<Vec<f32>, ImageErrors> {
+    let (in_width, in_height) = image.dimensions();
+
+    if in_width == 0 || in_height == 0 || image.frames_len() == 0 {
+        return Err(ImageErrors::GenericStr("Cannot register an empty image"));
+    }
+    let pixels = frame_luma(image, 0)?;
+    let mean = pixels.iter().sum::<f32>() / pixels.len() as f32;
+
+    let taper_x = taper(in_width);
+    let taper_y = taper(in_height);
+
+    let mut out = vec![0.0; width * height];
+
+    for (y, (row, out)) in pixels
+        .chunks_exact(in_width)
+        .zip(out.chunks_exact_mut(width))
+        .enumerate()
+    {
+        for (x, (pixel, out)) in row.iter().zip(out.iter_mut()).enumerate() {
+            *out = (pixel - mean) * taper_x[x] * taper_y[y];
+        }
+    }
+    Ok(out)
+}
+
+/// Tukey window of `len` samples, flat in the middle with cosine
+/// tapers over the outer eighth on both sides
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn taper(len: usize) -> Vec<f32> {
+    let edge = (len / 8).max(1);
+
+    (0..len)
+        .map(|i| {
+            let distance = i.min(len - 1 - i);
+            if distance >= edge {
+                1.0
+            } else {
+                let t = (distance as f64 + 0.5) / edge as f64;
+                (0.5 - 0.5 * (PI * t).cos()) as f32
+            }
+        })
+        .collect()
+}
+
+/// Two dimensional FFT, in place, dimensions must be powers of two
+///
+/// The inverse transform isn't scaled
+fn fft_2d(re: &mut [f32], im: &mut [f32], width: usize, height: usize, inverse: bool) {
+    for (row_re, row_im) in re.chunks_exact_mut(width).zip(im.chunks_exact_mut(width)) {
+        fft(row_re, row_im, inverse);
+    }
+    let mut column_re = vec![0.0; height];
+    let mut column_im = vec![0.0; height];
+
+    for x in 0..width {
+        for y in 0..height {
+            column_re[y] = re[y * width + x];
+            column_im[y] = im[y * width + x];
+        }
+        fft(&mut column_re, &mut column_im, inverse);
+
+        for y in 0..height {
+            re[y * width + x] = column_re[y];
+            im[y * width + x] = column_im[y];
+        }
+    }
+}
+
+/// Iterative radix 2 FFT, in place, the length must be a power of two
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn fft(re: &mut [f32], im: &mut [f32], inverse: bool) {
+    let len = re.len();
+    // bit reversal permutation
+    let mut j = 0;
+
+    for i in 1..len {
+        let mut bit = len >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut size = 2;
+
+    while size <= len {
+        let angle = sign * 2.0 * PI / size as f64;
+        let (step_re, step_im) = (angle.cos(), angle.sin());
+
+        for start in (0..len).step_by(size) {
+            let (mut w_re, mut w_im) = (1.0_f64, 0.0_f64);
+
+            for k in 0..size / 2 {
+                let (a, b) = (start + k, start + k + size / 2);
+                let (tw_re, tw_im) = (w_re as f32, w_im as f32);
+
+                let t_re = re[b] * tw_re - im[b] * tw_im;
+                let t_im = re[b] * tw_im + im[b] * tw_re;
+
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+
+                (w_re, w_im) = (
+                    w_re * step_re - w_im * step_im,
+                    w_re * step_im + w_im * step_re
+                );
+            }
+        }
+        size <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+
+    use crate::registration::{register_translation, register_translation_subpixel};
+
+    /// Random values on a grid interpolated bilinearly, so it can be sampled at any position
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn pattern(x: f32, y: f32) -> f32 {
+        let random = |x: i64, y: i64| {
+            let mut hash = (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ (y as u64);
+            hash = (hash ^ (hash >> 31)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            hash = (hash ^ (hash >> 29)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            (hash >> 40) as f32 / (1 << 24) as f32
+        };
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = random(x0, y0) * (1.0 - fx) + random(x0 + 1, y0) * fx;
+        let bottom = random(x0, y0 + 1) * (1.0 - fx) + random(x0 + 1, y0 + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn view(x: f32, y: f32, width: usize, height: usize) -> Image {
+        Image::from_fn::<u8, _>(width, height, ColorSpace::RGB, |py, px, pix| {
+            let value = pattern(px as f32 + x, py as f32 + y);
+            pix[..3].fill((value * 255.0) as u8);
+        })
+    }
+
+    #[test]
+    fn test_register_translation() {
+        let reference = view(0.0, 0.0, 80, 60);
+
+        for (dx, dy) in [(0, 0), (7, -4), (-13, 9), (50, 20)] {
+            #[allow(clippy::cast_precision_loss)]
+            let moving = view(dx as f32, dy as f32, 80, 60);
+            let (x, y, confidence) = register_translation(&reference, &moving).unwrap();
+
+            #[allow(clippy::cast_precision_loss)]
+            let expected = (dx as f32, dy as f32);
+            assert_eq!((x, y), expected);
+            assert!(confidence > 0.05, "{confidence}");
+        }
+    }
+
+    #[test]
+    fn test_register_translation_subpixel() {
+        let reference = view(0.0, 0.0, 64, 64);
+        let moving = view(3.3, -2.6, 64, 64);
+
+        let (x, y, _) = register_translation_subpixel(&reference, &moving).unwrap();
+        assert!((x - 3.3).abs() < 0.25 && (y + 2.6).abs() < 0.25, "{x} {y}");
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn test_register_translation_unrelated() {
+        let reference = view(0.0, 0.0, 64, 64);
+        let noise = Image::from_fn::<u8, _>(64, 64, ColorSpace::RGB, |y, x, pix| {
+            pix[..3].fill(((x * 7919 + y * 104_729) % 251) as u8);
+        });
+        let (_, _, confidence) = register_translation(&reference, &noise).unwrap();
+        let (_, _, matching) = register_translation(&reference, &view(4.0, 4.0, 64, 64)).unwrap();
+
+        assert!(confidence < matching, "{confidence} {matching}");
+    }
+}
@@ -0,0 +1,293 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Create operations by name
+//!
+//! With the `serde-support` feature, operations can be serialized and deserialized,
+//! parameters are the fields of the operation, e.g. `{"sigma": 2.0}` for a gaussian blur.
+//!
+//! The [`OperationRegistry`] maps names to constructors so that pipelines can be described
+//! declaratively, e.g. in a config file or a request to a remote service.
+//!
+//! Operations that need a second image, like blending and compositing, can't be created
+//! by the registry.
+//!
+//! # Example
+//! ```
+//! use serde_json::json;
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_imageprocs::registry::OperationRegistry;
+//!
+//! let registry = OperationRegistry::new();
+//! let blur = registry.create("gaussian-blur", json!({ "sigma": 2.0 })).unwrap();
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+//! blur.execute(&mut image).unwrap();
+//! ```
+#![cfg(feature = "serde-support")]
+
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use zune_image::errors::ImageErrors;
+use zune_image::traits::OperationsTrait;
+
+use crate::auto_exposure::AutoExposure;
+use crate::auto_orient::AutoOrient;
+use crate::barcode_preprocess::BarcodePreprocess;
+use crate::bilateral_filter::BilateralFilter;
+use crate::border::Border;
+use crate::box_blur::BoxBlur;
+use crate::brighten::Brighten;
+use crate::canvas::{ExtendCanvas, Tile};
+use crate::chroma_key::ChromaKey;
+use crate::color_matrix::ColorMatrix;
+use crate::colormap::Colormap;
+use crate::contrast::Contrast;
+use crate::convolve::Convolve;
+use crate::crop::Crop;
+use crate::curves::Curves;
+use crate::exposure::Exposure;
+use crate::flatten::Flatten;
+use crate::flip::Flip;
+use crate::flood_fill::FloodFill;
+use crate::gamma::Gamma;
+use crate::gaussian_blur::GaussianBlur;
+use crate::gradient_map::GradientMap;
+use crate::grain::Grain;
+use crate::halftone::{Halftone, OrderedDither};
+use crate::high_pass::HighPass;
+use crate::hsv_adjust::HsvAdjust;
+use crate::invert::Invert;
+use crate::kuwahara::{AnisotropicKuwahara, Kuwahara};
+use crate::lens::{ChromaticAberration, LensDistortion, Vignette};
+use crate::median::Median;
+use crate::mirror::Mirror;
+use crate::motion_blur::{MotionBlur, RadialBlur};
+use crate::nine_patch::NinePatch;
+use crate::oil_paint::OilPaint;
+use crate::pixelate::{Pixelate, Redact};
+use crate::posterize::Posterize;
+use crate::premul_alpha::PremultiplyAlpha;
+use crate::resize::Resize;
+use crate::retinex::Retinex;
+use crate::rotate::Rotate;
+use crate::scharr::Scharr;
+use crate::scopes::Scope;
+use crate::sepia::Sepia;
+use crate::shadows_highlights::ShadowsHighlights;
+use crate::shape_mask::ShapeMask;
+use crate::shift::Shift;
+use crate::smart_crop::SmartCrop;
+use crate::sobel::Sobel;
+use crate::solarize::Solarize;
+use crate::spatial::SpatialOps;
+use crate::sprite_sheet::{SplitSpriteSheet, SpriteSheet};
+use crate::stretch_contrast::StretchContrast;
+use crate::temperature::Temperature;
+use crate::threshold::Threshold;
+use crate::transpose::Transpose;
+use crate::unsharpen::Unsharpen;
+use crate::vibrance::Vibrance;
+
+/// A function creating an operation from its parameters
+pub type OperationConstructor = fn(Value) -> Result<Box<dyn OperationsTrait>, String>;
+
+/// A mapping of names to operation constructors
+pub struct OperationRegistry {
+    constructors: BTreeMap<String, OperationConstructor>
+}
+
+impl OperationRegistry {
+    /// Create a registry containing the operations of this crate
+    #[must_use]
+    pub fn new() -> OperationRegistry {
+        let mut registry = OperationRegistry::empty();
+
+        registry.register("anisotropic-kuwahara", deserialize::<AnisotropicKuwahara>);
+        registry.register("auto-exposure", deserialize::<AutoExposure>);
+        registry.register("auto-orient", |_| Ok(Box::new(AutoOrient)));
+        registry.register("barcode-preprocess", deserialize::<BarcodePreprocess>);
+        registry.register("bilateral-filter", deserialize::<BilateralFilter>);
+        registry.register("border", deserialize::<Border>);
+        registry.register("box-blur", deserialize::<BoxBlur>);
+        registry.register("brighten", deserialize::<Brighten>);
+        registry.register("chroma-key", deserialize::<ChromaKey>);
+        registry.register("chromatic-aberration", deserialize::<ChromaticAberration>);
+        registry.register("color-matrix", deserialize::<ColorMatrix>);
+        registry.register("colormap", deserialize::<Colormap>);
+        registry.register("contrast", deserialize::<Contrast>);
+        registry.register("convolve", deserialize::<Convolve>);
+        registry.register("crop", deserialize::<Crop>);
+        registry.register("curves", deserialize::<Curves>);
+        registry.register("exposure", deserialize::<Exposure>);
+        registry.register("extend-canvas", deserialize::<ExtendCanvas>);
+        registry.register("flatten", deserialize::<Flatten>);
+        registry.register("flip", deserialize::<Flip>);
+        registry.register("flood-fill", deserialize::<FloodFill>);
+        registry.register("gamma", deserialize::<Gamma>);
+        registry.register("gaussian-blur", deserialize::<GaussianBlur>);
+        registry.register("gradient-map", deserialize::<GradientMap>);
+        registry.register("grain", deserialize::<Grain>);
+        registry.register("halftone", deserialize::<Halftone>);
+        registry.register("high-pass", deserialize::<HighPass>);
+        registry.register("hsv-adjust", deserialize::<HsvAdjust>);
+        registry.register("invert", |_| Ok(Box::new(Invert::new())));
+        registry.register("kuwahara", deserialize::<Kuwahara>);
+        registry.register("lens-distortion", deserialize::<LensDistortion>);
+        registry.register("median", deserialize::<Median>);
+        registry.register("mirror", deserialize::<Mirror>);
+        registry.register("motion-blur", deserialize::<MotionBlur>);
+        registry.register("nine-patch", deserialize::<NinePatch>);
+        registry.register("oil-paint", deserialize::<OilPaint>);
+        registry.register("ordered-dither", deserialize::<OrderedDither>);
+        registry.register("pixelate", deserialize::<Pixelate>);
+        registry.register("posterize", deserialize::<Posterize>);
+        registry.register("premultiply-alpha", deserialize::<PremultiplyAlpha>);
+        registry.register("radial-blur", deserialize::<RadialBlur>);
+        registry.register("redact", deserialize::<Redact>);
+        registry.register("resize", deserialize::<Resize>);
+        registry.register("retinex", deserialize::<Retinex>);
+        registry.register("rotate", deserialize::<Rotate>);
+        registry.register("scharr", |_| Ok(Box::new(Scharr::new())));
+        registry.register("scope", deserialize::<Scope>);
+        registry.register("sepia", deserialize::<Sepia>);
+        registry.register("shadows-highlights", deserialize::<ShadowsHighlights>);
+        registry.register("shape-mask", deserialize::<ShapeMask>);
+        registry.register("shift", deserialize::<Shift>);
+        registry.register("smart-crop", deserialize::<SmartCrop>);
+        registry.register("sobel", |_| Ok(Box::new(Sobel::new())));
+        registry.register("solarize", deserialize::<Solarize>);
+        registry.register("spatial", deserialize::<SpatialOps>);
+        registry.register("split-sprite-sheet", deserialize::<SplitSpriteSheet>);
+        registry.register("sprite-sheet", deserialize::<SpriteSheet>);
+        registry.register("stretch-contrast", deserialize::<StretchContrast>);
+        registry.register("temperature", deserialize::<Temperature>);
+        registry.register("threshold", deserialize::<Threshold>);
+        registry.register("tile", deserialize::<Tile>);
+        registry.register("transpose", |_| Ok(Box::new(Transpose::new())));
+        registry.register("unsharpen", deserialize::<Unsharpen>);
+        registry.register("vibrance", deserialize::<Vibrance>);
+        registry.register("vignette", deserialize::<Vignette>);
+
+        registry
+    }
+
+    /// Create a registry without any operations
+    #[must_use]
+    pub fn empty() -> OperationRegistry {
+        OperationRegistry {
+            constructors: BTreeMap::new()
+        }
+    }
+
+    /// Add an operation to the registry, replacing an operation with the same name
+    pub fn register(&mut self, name: &str, constructor: OperationConstructor) {
+        self.constructors.insert(name.to_string(), constructor);
+    }
+
+    /// Return the names of the registered operations, in sorted order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.constructors.keys().map(String::as_str)
+    }
+
+    /// Create the operation registered as `name` from its parameters
+    ///
+    /// # Errors
+    /// If no operation is registered as `name` or the parameters are invalid
+    pub fn create(
+        &self, name: &str, params: Value
+    ) -> Result<Box<dyn OperationsTrait>, ImageErrors> {
+        let constructor = self
+            .constructors
+            .get(name)
+            .ok_or_else(|| ImageErrors::GenericString(format!("Unknown operation \"{name}\"")))?;
+
+        constructor(params).map_err(|e| {
+            ImageErrors::GenericString(format!("Invalid parameters for \"{name}\": {e}"))
+        })
+    }
+}
+
+impl Default for OperationRegistry {
+    fn default() -> Self {
+        OperationRegistry::new()
+    }
+}
+
+/// Create an operation by deserializing its parameters
+///
+/// # Errors
+/// If the parameters don't match the fields of the operation
+pub fn deserialize<T>(params: Value) -> Result<Box<dyn OperationsTrait>, String>
+where
+    T: OperationsTrait + DeserializeOwned + 'static
+{
+    serde_json::from_value::<T>(params)
+        .map(|operation| Box::new(operation) as Box<dyn OperationsTrait>)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+
+    use crate::registry::OperationRegistry;
+    use crate::resize::{Resize, ResizeMethod};
+
+    #[test]
+    fn test_registry_create() {
+        let registry = OperationRegistry::new();
+
+        let resize = Resize::new(20, 10, ResizeMethod::Bilinear);
+        let params = serde_json::to_value(resize).unwrap();
+        assert_eq!(
+            params,
+            json!({
+                "new_width": 20,
+                "new_height": 10,
+                "method": "Bilinear",
+                "premultiply_alpha": true,
+                "linear_light": false
+            })
+        );
+
+        let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 40, 40);
+        // options added later default when missing
+        registry
+            .create(
+                "resize",
+                json!({ "new_width": 40, "new_height": 40, "method": "Bilinear" })
+            )
+            .unwrap()
+            .execute(&mut image)
+            .unwrap();
+        registry
+            .create("resize", params)
+            .unwrap()
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.dimensions(), (20, 10));
+
+        registry
+            .create("sobel", json!(null))
+            .unwrap()
+            .execute(&mut image)
+            .unwrap();
+
+        assert!(registry
+            .create("resize", json!({ "new_width": 20 }))
+            .is_err());
+        assert!(registry.create("unknown", json!({})).is_err());
+        assert!(registry.names().any(|x| x == "gaussian-blur"));
+    }
+}
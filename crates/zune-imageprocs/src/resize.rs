@@ -12,7 +12,32 @@
 //! Currently only implements a simple bilinear resizer, future plans are to have
 //! more complicated resizers implemented.
 //!
+//! # Alpha
+//! Resizing mixes neighbouring pixels, and mixing colors of transparent pixels, which
+//! are usually black or white, into opaque ones gives dark or light halos around the
+//! edges of sprites and cut out objects. Images with alpha are therefore premultiplied
+//! before and un-premultiplied after resizing, so every pixel contributes in proportion
+//! to its opacity. This can be turned off with [`Resize::set_premultiply_alpha`].
 //!
+//! # Linear light
+//! Images are usually sRGB encoded, and averaging encoded values makes fine bright
+//! details, e.g. text or stars, darker when downscaling. [`Resize::set_linear_light`]
+//! resizes the linear values instead.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::resize::{Resize, ResizeMethod};
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::RGBA, 100, 100);
+//! Resize::new(50, 25, ResizeMethod::Bilinear)
+//!     .set_linear_light(true)
+//!     .execute(&mut image)
+//!     .unwrap();
+//! assert_eq!(image.dimensions(), (50, 25));
+//! ```
 use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
@@ -20,12 +45,13 @@ use zune_image::image::Image;
 use zune_image::traits::OperationsTrait;
 
 use crate::traits::NumOps;
-use crate::utils::execute_on;
+use crate::utils::{execute_on, linear_to_srgb, normalized_samples, srgb_to_linear, store_samples};
 
 mod bicubic;
 mod bilinear;
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub enum ResizeMethod {
     Bilinear,
     Bicubic,
@@ -38,10 +64,20 @@ pub enum ResizeMethod {
 /// Resize an image to a new width and height
 /// using the resize method specified
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Resize {
-    new_width: usize,
-    new_height: usize,
-    method: ResizeMethod,
+    new_width:         usize,
+    new_height:        usize,
+    method:            ResizeMethod,
+    #[cfg_attr(feature = "serde-support", serde(default = "premultiply_by_default"))]
+    premultiply_alpha: bool,
+    #[cfg_attr(feature = "serde-support", serde(default))]
+    linear_light:      bool
+}
+
+#[cfg(feature = "serde-support")]
+const fn premultiply_by_default() -> bool {
+    true
 }
 
 impl Resize {
@@ -51,12 +87,164 @@ impl Resize {
     /// - new_width: The new image width
     /// - new_height: The new image height.
     /// - method: The resize method to use
+    ///
+    /// Alpha is premultiplied and encoded values are resized
     #[must_use]
     pub fn new(new_width: usize, new_height: usize, method: ResizeMethod) -> Resize {
         Resize {
             new_width,
             new_height,
             method,
+            premultiply_alpha: true,
+            linear_light: false
+        }
+    }
+    /// Set whether colors are weighted by alpha when resizing images with alpha,
+    /// which prevents halos around transparent areas. Default is true
+    ///
+    /// Without it every channel, including alpha, is resized on its own
+    #[must_use]
+    pub fn set_premultiply_alpha(mut self, premultiply_alpha: bool) -> Self {
+        self.premultiply_alpha = premultiply_alpha;
+        self
+    }
+    /// Set whether the sRGB transfer function is removed from colors before and
+    /// applied again after resizing, which keeps the brightness of fine details.
+    /// Default is false
+    #[must_use]
+    pub fn set_linear_light(mut self, linear_light: bool) -> Self {
+        self.linear_light = linear_light;
+        self
+    }
+
+    /// Resize the channels of a frame as floats, weighted by alpha if `premultiply`
+    /// and in linear light if set
+    #[allow(clippy::too_many_arguments)]
+    fn resize_float(
+        &self, channels: &mut [Channel], bit_type: BitType, alpha: Option<usize>,
+        was_premultiplied: bool, premultiply: bool, (old_w, old_h): (usize, usize)
+    ) -> Result<(), ImageErrors> {
+        let mut planes = channels
+            .iter()
+            .map(|c| normalized_samples(c, bit_type, self.name()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // transfer functions work on straight colors, so premultiplied colors
+        // are divided for them and multiplied again after
+        let straighten = was_premultiplied && self.linear_light;
+        let reweight = premultiply && (self.linear_light || !was_premultiplied);
+
+        if straighten {
+            divide_alpha(&mut planes, alpha);
+        }
+        if self.linear_light {
+            map_colors(&mut planes, alpha, srgb_to_linear);
+        }
+        if reweight {
+            multiply_alpha(&mut planes, alpha);
+        }
+
+        let resize_plane = |plane: &Vec<f32>| {
+            let mut out = vec![0.0; self.new_width * self.new_height];
+            resize::<f32>(
+                plane,
+                &mut out,
+                self.method,
+                old_w,
+                old_h,
+                self.new_width,
+                self.new_height
+            );
+            out
+        };
+        #[cfg(feature = "threads")]
+        let mut planes: Vec<Vec<f32>> = std::thread::scope(|s| {
+            let handles: Vec<_> = planes
+                .iter()
+                .map(|plane| s.spawn(|| resize_plane(plane)))
+                .collect();
+            handles.into_iter().map(|x| x.join().unwrap()).collect()
+        });
+        #[cfg(not(feature = "threads"))]
+        let mut planes: Vec<Vec<f32>> = planes.iter().map(resize_plane).collect();
+
+        if reweight {
+            divide_alpha(&mut planes, alpha);
+        }
+        if self.linear_light {
+            map_colors(&mut planes, alpha, |x| linear_to_srgb(x.max(0.0)));
+        }
+        if straighten {
+            multiply_alpha(&mut planes, alpha);
+        }
+
+        let new_length = self.new_width * self.new_height * bit_type.to_depth().size_of();
+        for (channel, plane) in channels.iter_mut().zip(&planes) {
+            let mut new_channel = Channel::new_with_bit_type(new_length, bit_type);
+            store_samples(&mut new_channel, bit_type, self.name(), plane)?;
+            *channel = new_channel;
+        }
+        Ok(())
+    }
+}
+
+impl Resize {
+    /// Scale the regions of interest of an image resized from `old_w` x `old_h`
+    #[allow(clippy::cast_precision_loss)]
+    fn scale_regions(&self, image: &mut Image, (old_w, old_h): (usize, usize)) {
+        let (x_scale, y_scale) = (
+            self.new_width as f64 / old_w as f64,
+            self.new_height as f64 / old_h as f64
+        );
+        let regions = image
+            .metadata()
+            .regions_of_interest()
+            .iter()
+            .map(|x| x.scale(x_scale, y_scale))
+            .collect();
+        image.metadata_mut().set_regions_of_interest(regions);
+    }
+}
+
+/// Apply `function` to every sample of the color planes, i.e. all but the alpha plane
+fn map_colors(planes: &mut [Vec<f32>], alpha: Option<usize>, function: impl Fn(f32) -> f32) {
+    for (i, plane) in planes.iter_mut().enumerate() {
+        if Some(i) != alpha {
+            for value in plane.iter_mut() {
+                *value = function(*value);
+            }
+        }
+    }
+}
+
+/// Multiply color planes by the alpha plane
+fn multiply_alpha(planes: &mut [Vec<f32>], alpha: Option<usize>) {
+    let Some(index) = alpha else {
+        return;
+    };
+    let alpha = planes[index].clone();
+
+    for (i, plane) in planes.iter_mut().enumerate() {
+        if i != index {
+            for (value, alpha) in plane.iter_mut().zip(&alpha) {
+                *value *= alpha.clamp(0.0, 1.0);
+            }
+        }
+    }
+}
+
+/// Divide color planes by the alpha plane, fully transparent pixels become black
+fn divide_alpha(planes: &mut [Vec<f32>], alpha: Option<usize>) {
+    let Some(index) = alpha else {
+        return;
+    };
+    let alpha = planes[index].clone();
+
+    for (i, plane) in planes.iter_mut().enumerate() {
+        if i != index {
+            for (value, alpha) in plane.iter_mut().zip(&alpha) {
+                *value = if *alpha > f32::EPSILON { *value / alpha.min(1.0) } else { 0.0 };
+            }
         }
     }
 }
@@ -71,6 +259,29 @@ impl OperationsTrait for Resize {
         let (old_w, old_h) = image.dimensions();
         let depth = image.depth().bit_type();
 
+        let colorspace = image.colorspace();
+        let alpha = colorspace.alpha_position();
+        let was_premultiplied = image.metadata().is_premultiplied_alpha();
+        // whether colors are weighted by alpha while resizing, which premultiplied
+        // images already are
+        let premultiply = alpha.is_some() && (self.premultiply_alpha || was_premultiplied);
+
+        if self.linear_light || (premultiply && !was_premultiplied) {
+            for frame in image.frames_mut() {
+                self.resize_float(
+                    frame.channels_vec(),
+                    depth,
+                    alpha,
+                    was_premultiplied,
+                    premultiply,
+                    (old_w, old_h)
+                )?;
+            }
+            image.set_dimensions(self.new_width, self.new_height);
+            self.scale_regions(image, (old_w, old_h));
+            return Ok(());
+        }
+
         let new_length = self.new_width * self.new_height * image.depth().size_of();
 
         let resize_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
@@ -113,6 +324,7 @@ impl OperationsTrait for Resize {
         };
         execute_on(resize_fn, image, false)?;
         image.set_dimensions(self.new_width, self.new_height);
+        self.scale_regions(image, (old_w, old_h));
 
         Ok(())
     }
@@ -261,7 +473,72 @@ mod benchmarks {
 }
 #[cfg(test)]
 mod tests {
-    use crate::resize::{resize, ResizeMethod};
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::metadata::AlphaState;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::resize::{resize, Resize, ResizeMethod};
+
+    /// Opaque red on the left half, transparent black on the right half
+    fn sprite() -> Image {
+        Image::from_fn::<u8, _>(4, 2, ColorSpace::RGBA, |_, x, pix| {
+            let color = if x < 2 { [255, 0, 0, 255] } else { [0, 0, 0, 0] };
+            pix[..4].copy_from_slice(&color);
+        })
+    }
+
+    #[test]
+    fn test_resize_alpha_has_no_halo() {
+        for method in [ResizeMethod::Bilinear, ResizeMethod::Bicubic] {
+            let mut image = sprite();
+            Resize::new(8, 4, method).execute(&mut image).unwrap();
+
+            let pixels = &image.flatten_to_u8()[0];
+            // the edge is partially transparent but keeps its color
+            assert!(pixels.chunks_exact(4).any(|x| x[3] > 0 && x[3] < 255));
+            for pixel in pixels.chunks_exact(4).filter(|x| x[3] > 0) {
+                assert_eq!(pixel[..3], [255, 0, 0], "{method:?} {pixels:?}");
+            }
+        }
+        // resizing channels on their own mixes in the black of transparent pixels
+        let mut image = sprite();
+        Resize::new(8, 4, ResizeMethod::Bilinear)
+            .set_premultiply_alpha(false)
+            .execute(&mut image)
+            .unwrap();
+        let pixels = &image.flatten_to_u8()[0];
+        assert!(pixels.chunks_exact(4).any(|x| x[3] > 0 && x[0] < 250));
+
+        // premultiplied images stay premultiplied
+        let mut image = sprite();
+        image.metadata_mut().set_alpha(AlphaState::PreMultiplied);
+        Resize::new(8, 4, ResizeMethod::Bilinear)
+            .set_linear_light(true)
+            .execute(&mut image)
+            .unwrap();
+        let pixels = &image.flatten_to_u8()[0];
+        for pixel in pixels.chunks_exact(4) {
+            assert!(pixel[0].abs_diff(pixel[3]) <= 1, "{pixels:?}");
+        }
+    }
+
+    #[test]
+    fn test_resize_linear_light() {
+        // the pixel between black and white gets half of the light, about 73% in sRGB
+        for (linear, expected) in [(false, 32767), (true, 48242)] {
+            let mut image = Image::from_fn::<u16, _>(2, 1, ColorSpace::Luma, |_, x, pix| {
+                pix[0] = if x == 0 { 0 } else { 65535 };
+            });
+            Resize::new(4, 1, ResizeMethod::Bilinear)
+                .set_linear_light(linear)
+                .execute(&mut image)
+                .unwrap();
+
+            let middle = image.flatten_frames::<u16>()[0][1];
+            assert!(middle.abs_diff(expected) < 100, "{linear} {middle}");
+        }
+    }
 
     #[test]
     fn bench_resize_cubic() {
@@ -0,0 +1,281 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Multi-scale retinex with color restoration (MSRCR)
+//!
+//! Retinex separates what a camera sees into the illumination of a scene and the
+//! reflectance of its objects, and keeps the reflectance. The illumination is estimated with
+//! gaussian blurs of the image, for every scale the log of the blurred image is subtracted
+//! from the log of the image and the results of all scales are averaged.
+//!
+//! Small scales bring out local contrast, large ones keep the tones of the image, the
+//! defaults of 15, 80 and 250 pixels work for photos of a few megapixels.
+//!
+//! Removing the illumination grays out colors, so for color images every channel is
+//! weighted by its share of the pixel's total, which restores them. The result is finally
+//! stretched to the full range of the image, clipping a small fraction of the darkest
+//! and brightest values.
+//!
+//! This evens out dark and unevenly lit images, e.g. document photos taken under a lamp or
+//! low light surveillance footage. Alpha is kept and calculations are done in `f32`.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::retinex::Retinex;
+//!
+//! let mut image = Image::fill::<u8>(30, ColorSpace::RGB, 100, 100);
+//! Retinex::new(vec![5.0, 20.0, 60.0]).execute(&mut image).unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::gaussian_blur::gaussian_blur_f32;
+use crate::utils::map_color_planes;
+
+/// Added to values before taking their log, keeping black pixels finite
+const EPSILON: f32 = 1.0 / 255.0;
+
+/// Normalize the illumination of an image
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Retinex {
+    scales:    Vec<f32>,
+    alpha:     f32,
+    beta:      f32,
+    low_clip:  f32,
+    high_clip: f32
+}
+
+impl Default for Retinex {
+    fn default() -> Self {
+        Retinex::new(vec![15.0, 80.0, 250.0])
+    }
+}
+
+impl Retinex {
+    /// Create a new retinex filter
+    ///
+    /// # Arguments
+    /// - scales: The sigmas, in pixels, of the gaussian blurs estimating the illumination,
+    ///   at least one is needed
+    ///
+    /// Color restoration uses an alpha of 125 and a beta of 46, and 1% of the values
+    /// of every channel are clipped at both ends
+    #[must_use]
+    pub fn new(scales: Vec<f32>) -> Retinex {
+        Retinex {
+            scales,
+            alpha: 125.0,
+            beta: 46.0,
+            low_clip: 0.01,
+            high_clip: 0.01
+        }
+    }
+    /// Set the parameters of color restoration
+    ///
+    /// # Arguments
+    /// - alpha: Controls how strongly the share of a channel changes its weight, larger
+    ///   values give a weaker, more uniform restoration
+    /// - beta: Multiplies the weight of every channel
+    #[must_use]
+    pub fn set_color_restoration(mut self, alpha: f32, beta: f32) -> Self {
+        self.alpha = alpha;
+        self.beta = beta;
+        self
+    }
+    /// Set the fractions, between 0.0 and 0.5, of the values of every channel clipped
+    /// to black and to white when stretching the result
+    #[must_use]
+    pub fn set_clip(mut self, low: f32, high: f32) -> Self {
+        self.low_clip = low;
+        self.high_clip = high;
+        self
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn filter(&self, planes: &[Vec<f32>], width: usize, height: usize) -> Vec<Vec<f32>> {
+        let weight = 1.0 / self.scales.len() as f32;
+        let mut scratch = vec![0.0; width * height];
+
+        let mut output: Vec<Vec<f32>> = planes
+            .iter()
+            .map(|plane| {
+                let logs: Vec<f32> = plane.iter().map(|x| log(*x)).collect();
+                let mut retinex = vec![0.0; plane.len()];
+
+                for sigma in &self.scales {
+                    let mut blurred = plane.clone();
+                    gaussian_blur_f32(&mut blurred, &mut scratch, width, height, *sigma);
+
+                    for ((out, value), illumination) in retinex.iter_mut().zip(&logs).zip(&blurred)
+                    {
+                        *out += weight * (value - log(*illumination));
+                    }
+                }
+                retinex
+            })
+            .collect();
+
+        // a single channel has nothing to restore
+        if planes.len() > 1 {
+            let totals: Vec<f32> = (0..width * height)
+                .map(|i| log(planes.iter().map(|plane| plane[i].max(0.0)).sum()))
+                .collect();
+
+            for (out, plane) in output.iter_mut().zip(planes) {
+                for ((value, original), total) in out.iter_mut().zip(plane).zip(&totals) {
+                    *value *= self.beta * (log(*original) + self.alpha.ln() - total);
+                }
+            }
+        }
+        for out in &mut output {
+            stretch(out, self.low_clip, self.high_clip);
+        }
+        output
+    }
+}
+
+impl OperationsTrait for Retinex {
+    fn name(&self) -> &'static str {
+        "Retinex"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if self.scales.is_empty() {
+            return Err(ImageErrors::GenericStr("Retinex needs at least one scale"));
+        }
+        let (width, height) = image.dimensions();
+
+        map_color_planes(image, self.name(), |planes, _| {
+            self.filter(planes, width, height)
+        })
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::Luma,
+            ColorSpace::LumaA
+        ]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+fn log(value: f32) -> f32 {
+    (value.max(0.0) + EPSILON).ln()
+}
+
+/// Stretch values to the range 0.0 to 1.0, clipping the `low` and `high` fractions
+/// of values at either end
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn stretch(values: &mut [f32], low: f32, high: f32) {
+    if values.is_empty() {
+        return;
+    }
+    let last = values.len() - 1;
+    let mut sorted = values.to_vec();
+
+    let low_index = (last as f32 * low.clamp(0.0, 0.5)) as usize;
+    let high_index = (last as f32 * (1.0 - high.clamp(0.0, 0.5))) as usize;
+    let minimum = *sorted.select_nth_unstable_by(low_index, f32::total_cmp).1;
+    let maximum = *sorted.select_nth_unstable_by(high_index, f32::total_cmp).1;
+
+    let range = maximum - minimum;
+    if range <= f32::EPSILON {
+        // nothing to stretch, e.g. a flat image
+        values.fill(0.5);
+        return;
+    }
+    for value in values {
+        *value = ((*value - minimum) / range).clamp(0.0, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::retinex::Retinex;
+
+    #[allow(clippy::cast_precision_loss)]
+    fn half_means(pixels: &[f32], width: usize, height: usize) -> (f32, f32) {
+        let half = |range: std::ops::Range<usize>| {
+            let count = (range.len() * height) as f32;
+            let sum: f32 = pixels
+                .chunks_exact(width)
+                .map(|row| row[range.clone()].iter().sum::<f32>())
+                .sum();
+            sum / count
+        };
+        (half(0..width / 2), half(width / 2..width))
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn test_retinex_evens_out_illumination() {
+        // a checkerboard lit from the right, the left side is nearly black
+        let (width, height) = (64, 32);
+        let mut image = Image::from_fn::<f32, _>(width, height, ColorSpace::Luma, |y, x, pix| {
+            let reflectance = if (x / 4 + y / 4) % 2 == 0 { 0.3 } else { 0.8 };
+            let illumination = 0.05 + 0.95 * x as f32 / (width - 1) as f32;
+            pix[0] = reflectance * illumination;
+        });
+        let (left, right) = half_means(&image.flatten_frames::<f32>()[0], width, height);
+        assert!(right - left > 0.25, "{left} {right}");
+
+        Retinex::new(vec![4.0, 12.0]).execute(&mut image).unwrap();
+
+        let pixels = &image.flatten_frames::<f32>()[0];
+        let (left, right) = half_means(pixels, width, height);
+        assert!((right - left).abs() < 0.1, "{left} {right}");
+        // and the checkerboard is visible on both sides
+        let row = &pixels[17 * width..][..width];
+        assert!(row[14] - row[10] > 0.3, "{} {}", row[14], row[10]);
+        assert!(row[54] - row[50] > 0.3, "{} {}", row[54], row[50]);
+    }
+
+    #[test]
+    fn test_retinex_colors() {
+        let mut image = Image::from_fn::<u8, _>(40, 30, ColorSpace::RGBA, |y, x, pix| {
+            let shade = if (x / 5 + y / 5) % 2 == 0 { 1 } else { 2 };
+            pix[0] = 30 * shade;
+            pix[1] = 10 * shade;
+            pix[2] = 5 * shade;
+            pix[3] = 180;
+        });
+        // scales larger than the image are fine
+        Retinex::default().execute(&mut image).unwrap();
+
+        let pixels = &image.flatten_to_u8()[0];
+        assert!(pixels.chunks_exact(4).all(|x| x[3] == 180));
+        // the image is stretched to the full range
+        assert!(pixels.chunks_exact(4).any(|x| x[0] == 255));
+        assert!(pixels.chunks_exact(4).any(|x| x[0] == 0));
+
+        let mut flat = Image::fill::<u16>(1000, ColorSpace::Luma, 10, 10);
+        Retinex::new(vec![3.0]).execute(&mut flat).unwrap();
+        assert!(flat.flatten_frames::<u16>()[0].iter().all(|x| *x == 32768));
+
+        assert!(Retinex::new(vec![]).execute(&mut flat).is_err());
+    }
+}
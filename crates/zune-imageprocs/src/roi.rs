@@ -0,0 +1,69 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Attach regions of interest to an image
+//!
+//! Regions of interest, e.g. faces found by an external detector, are stored in the
+//! image metadata, see [`RegionOfInterest`]. Operations that choose what part of an
+//! image matters favour them:
+//! - [`SmartCrop`](crate::smart_crop::SmartCrop) keeps as much of them as it can
+//! - [`AutoExposure`](crate::auto_exposure::AutoExposure) meters on them
+//!
+//! This operation sets them in a pipeline, images can also be given regions directly with
+//! [`ImageMetadata::set_regions_of_interest`](zune_image::metadata::ImageMetadata::set_regions_of_interest)
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::metadata::RegionOfInterest;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::roi::SetRegionsOfInterest;
+//! use zune_imageprocs::smart_crop::SmartCrop;
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 640, 480);
+//! let face: RegionOfInterest = "500,100,80,100".parse().unwrap();
+//!
+//! SetRegionsOfInterest::new(vec![face]).execute(&mut image).unwrap();
+//! SmartCrop::new(200, 200).execute(&mut image).unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::metadata::RegionOfInterest;
+use zune_image::traits::OperationsTrait;
+
+/// Set the regions of interest of an image, replacing existing ones
+#[derive(Clone, Debug)]
+pub struct SetRegionsOfInterest {
+    regions: Vec<RegionOfInterest>
+}
+
+impl SetRegionsOfInterest {
+    /// Create a new operation setting `regions` on images
+    #[must_use]
+    pub fn new(regions: Vec<RegionOfInterest>) -> SetRegionsOfInterest {
+        SetRegionsOfInterest { regions }
+    }
+}
+
+impl OperationsTrait for SetRegionsOfInterest {
+    fn name(&self) -> &'static str {
+        "Set Regions Of Interest"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        image
+            .metadata_mut()
+            .set_regions_of_interest(self.regions.clone());
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
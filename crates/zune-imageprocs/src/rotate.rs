@@ -21,6 +21,7 @@ use zune_image::traits::OperationsTrait;
 
 use crate::utils::execute_on;
 
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rotate {
     angle: f32
 }
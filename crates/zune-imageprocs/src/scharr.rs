@@ -40,6 +40,7 @@ use crate::traits::NumOps;
 ///
 /// The window is a 3x3 window.
 #[derive(Default, Copy, Clone)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Scharr;
 
 impl Scharr {
@@ -0,0 +1,349 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Diagnostic visualizations of image colors
+//!
+//! These are the scopes used in color grading, they replace the image with a
+//! picture describing its tonal and color distribution, which makes it easy to spot clipped
+//! highlights, crushed shadows or color casts.
+//!
+//! - [`ScopeType::Histogram`]: Luminance histogram, darks on the left and highlights on the right
+//! - [`ScopeType::Parade`]: RGB parade, a waveform of the red, green and blue channels side by side,
+//!   the horizontal axis follows the image columns and the vertical axis the channel value
+//! - [`ScopeType::Vectorscope`]: Chroma plotted on the Cb (horizontal) and Cr (vertical) plane,
+//!   neutral colors are in the center and saturation increases away from it
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::utils::to_channels;
+
+/// Intensity of the vectorscope crosshair
+const GRATICULE: f32 = 0.25;
+/// Number of histogram bins, more bins than 8 bit levels shows gaps between them
+const HISTOGRAM_BINS: usize = 256;
+
+/// The kind of visualization produced by [`Scope`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScopeType {
+    /// Luminance histogram
+    Histogram,
+    /// Waveforms of the red, green and blue channels side by side
+    Parade,
+    /// Chroma distribution on the Cb/Cr plane
+    Vectorscope
+}
+
+impl ScopeType {
+    pub fn from_string_result(input: &str) -> Result<Self, String> {
+        match input {
+            "histogram" => Ok(Self::Histogram),
+            "parade" => Ok(Self::Parade),
+            "vectorscope" => Ok(Self::Vectorscope),
+            _ => Err("Unknown scope, accepted values are histogram,parade,vectorscope".to_string())
+        }
+    }
+    /// Return the default width and height of the visualization
+    #[must_use]
+    pub const fn default_dimensions(self) -> (usize, usize) {
+        match self {
+            ScopeType::Histogram => (512, 256),
+            ScopeType::Parade => (768, 256),
+            ScopeType::Vectorscope => (256, 256)
+        }
+    }
+}
+
+/// Replace an image with a diagnostic visualization of it
+///
+/// The result is an RGB image with the dimensions of the scope and the depth of
+/// the input, every frame of an animated image gets its own visualization.
+///
+/// Luminance uses Rec.601 weights, like [`ColorspaceConv`](zune_image::core_filters::colorspace::ColorspaceConv)
+/// does by default.
+///
+/// # Example
+/// - Write a vectorscope of an image next to the image
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::scopes::{Scope, ScopeType};
+///
+/// let image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+/// let mut scope = image.clone();
+///
+/// Scope::new(ScopeType::Vectorscope).execute(&mut scope).unwrap();
+///
+/// assert_eq!(scope.dimensions(), (256, 256));
+/// ```
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scope {
+    scope:      ScopeType,
+    dimensions: Option<(usize, usize)>
+}
+
+impl Scope {
+    /// Create a new scope with the default dimensions of the scope type
+    #[must_use]
+    pub fn new(scope: ScopeType) -> Scope {
+        Scope {
+            scope,
+            dimensions: None
+        }
+    }
+    /// Set the width and height of the visualization
+    ///
+    /// The 256 histogram bins are stretched over the width, for the parade each channel takes
+    /// a third of the width
+    #[must_use]
+    pub fn set_dimensions(mut self, width: usize, height: usize) -> Self {
+        self.dimensions = Some((width, height));
+        self
+    }
+}
+
+impl OperationsTrait for Scope {
+    fn name(&self) -> &'static str {
+        "Scope"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = self
+            .dimensions
+            .unwrap_or_else(|| self.scope.default_dimensions());
+
+        if width == 0 || height == 0 {
+            return Err(ImageErrors::GenericStr(
+                "Scope dimensions must be greater than zero"
+            ));
+        }
+        let bit_type = image.depth().bit_type();
+        let in_width = image.dimensions().0;
+
+        // work on normalized rgb values whatever the input is
+        let mut source = image.clone();
+        source.convert_color(ColorSpace::RGB)?;
+        source.convert_depth(BitDepth::Float32)?;
+
+        for (frame, src_frame) in image.frames_mut().iter_mut().zip(source.frames_ref()) {
+            let channels = src_frame.channels_ref(ColorSpace::RGB, true);
+            let rgb = [
+                channels[0].reinterpret_as::<f32>()?,
+                channels[1].reinterpret_as::<f32>()?,
+                channels[2].reinterpret_as::<f32>()?
+            ];
+            let pixels = match self.scope {
+                ScopeType::Histogram => render_histogram(rgb, width, height),
+                ScopeType::Parade => render_parade(rgb, in_width, width, height),
+                ScopeType::Vectorscope => render_vectorscope(rgb, width, height)
+            };
+            frame.set_channels(to_channels(&pixels, bit_type, ColorSpace::RGB));
+        }
+        image.set_dimensions(width, height);
+        image.metadata_mut().set_colorspace(ColorSpace::RGB);
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Map a normalized value to one of `length` positions
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn position(value: f32, length: usize) -> usize {
+    let value = if value.is_nan() { 0.0 } else { value.clamp(0.0, 1.0) };
+    (value * (length - 1) as f32).round() as usize
+}
+
+/// Map hit counts to intensities between 0.0 and 1.0
+///
+/// A logarithmic scale keeps sparse values visible next to dense ones
+#[allow(clippy::cast_precision_loss)]
+fn log_intensities(counts: &[u32]) -> Vec<f32> {
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let scale = if max == 0 { 0.0 } else { 1.0 / (max as f32).ln_1p() };
+
+    counts
+        .iter()
+        .map(|count| (*count as f32).ln_1p() * scale)
+        .collect()
+}
+
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn render_histogram(rgb: [&[f32]; 3], width: usize, height: usize) -> Vec<f32> {
+    let [red, green, blue] = rgb;
+    let mut bins = [0_u32; HISTOGRAM_BINS];
+
+    for ((r, g), b) in red.iter().zip(green).zip(blue) {
+        let luma = 0.2989 * r + 0.5870 * g + 0.1140 * b;
+        bins[position(luma, HISTOGRAM_BINS)] += 1;
+    }
+    // each column shows the bins it covers, or repeats a bin when wider than the bins
+    let columns: Vec<u32> = (0..width)
+        .map(|x| {
+            let start = x * HISTOGRAM_BINS / width;
+            let end = ((x + 1) * HISTOGRAM_BINS / width).max(start + 1);
+            bins[start..end].iter().sum()
+        })
+        .collect();
+    let max = columns.iter().copied().max().unwrap_or(0).max(1) as f32;
+
+    let mut pixels = vec![0.0; width * height * 3];
+
+    for (x, column) in columns.iter().enumerate() {
+        let bar = ((*column as f32 / max) * height as f32).round() as usize;
+
+        for y in height - bar..height {
+            let offset = (y * width + x) * 3;
+            pixels[offset..offset + 3].fill(0.9);
+        }
+    }
+    pixels
+}
+
+fn render_parade(rgb: [&[f32]; 3], in_width: usize, width: usize, height: usize) -> Vec<f32> {
+    let section = (width / 3).max(1);
+    let mut counts = vec![0_u32; width * height];
+
+    for (channel, values) in rgb.iter().enumerate() {
+        let start = channel * section;
+
+        if start >= width {
+            break;
+        }
+        for (i, value) in values.iter().enumerate() {
+            let x = start + (i % in_width) * section / in_width;
+            let y = height - 1 - position(*value, height);
+            counts[y * width + x] += 1;
+        }
+    }
+
+    let mut pixels = vec![0.0; width * height * 3];
+
+    for (i, intensity) in log_intensities(&counts).into_iter().enumerate() {
+        let channel = (i % width) / section;
+
+        if channel < 3 {
+            pixels[i * 3 + channel] = intensity;
+        }
+    }
+    pixels
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn render_vectorscope(rgb: [&[f32]; 3], width: usize, height: usize) -> Vec<f32> {
+    let [red, green, blue] = rgb;
+    let mut counts = vec![0_u32; width * height];
+
+    for ((r, g), b) in red.iter().zip(green).zip(blue) {
+        let cb = -0.168_736 * r - 0.331_264 * g + 0.5 * b;
+        let cr = 0.5 * r - 0.418_688 * g - 0.081_312 * b;
+
+        let column = position(cb + 0.5, width);
+        let row = height - 1 - position(cr + 0.5, height);
+        counts[row * width + column] += 1;
+    }
+
+    let mut pixels = vec![0.0; width * height * 3];
+    let (center_x, center_y) = (width / 2, height / 2);
+
+    for (i, intensity) in log_intensities(&counts).into_iter().enumerate() {
+        let (x, y) = (i % width, i / width);
+        let pixel = &mut pixels[i * 3..i * 3 + 3];
+
+        if intensity > 0.0 {
+            // color each point with the hue it represents
+            let cb = x as f32 / (width - 1).max(1) as f32 - 0.5;
+            let cr = 0.5 - y as f32 / (height - 1).max(1) as f32;
+
+            let color = [
+                0.5 + 1.402 * cr,
+                0.5 - 0.344_136 * cb - 0.714_136 * cr,
+                0.5 + 1.772 * cb
+            ];
+            for (out, component) in pixel.iter_mut().zip(color) {
+                *out = component.clamp(0.0, 1.0) * intensity;
+            }
+        } else if x == center_x || y == center_y {
+            pixel.fill(GRATICULE);
+        }
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::scopes::{Scope, ScopeType};
+
+    #[test]
+    fn test_histogram_scope() {
+        // left half black, right half white
+        let mut image = Image::from_fn::<u8, _>(10, 10, ColorSpace::Luma, |_, x, pix| {
+            pix[0] = if x < 5 { 0 } else { 255 };
+        });
+        Scope::new(ScopeType::Histogram)
+            .set_dimensions(4, 2)
+            .execute(&mut image)
+            .unwrap();
+
+        assert_eq!(image.dimensions(), (4, 2));
+        assert_eq!(image.colorspace(), ColorSpace::RGB);
+
+        let pixels = &image.flatten_to_u8()[0];
+        // full height bars in the first and last bin only
+        assert_eq!(pixels[0], 230);
+        assert_eq!(pixels[3 * 3], 230);
+        assert_eq!(pixels[3], 0);
+        assert_eq!(pixels[(4 + 1) * 3], 0);
+    }
+
+    #[test]
+    fn test_parade_and_vectorscope() {
+        let red = Image::fill::<u16>(u16::MAX, ColorSpace::Luma, 8, 8);
+
+        let mut parade = red.clone();
+        Scope::new(ScopeType::Parade)
+            .set_dimensions(6, 4)
+            .execute(&mut parade)
+            .unwrap();
+        let pixels = &parade.flatten_frames::<u16>()[0];
+        // white input plots at the top of each section in its color
+        assert_eq!(&pixels[0..3], &[u16::MAX, 0, 0]);
+        assert_eq!(&pixels[2 * 3..2 * 3 + 3], &[0, u16::MAX, 0]);
+        assert_eq!(&pixels[(6 * 3) * 3..(6 * 3) * 3 + 3], &[0, 0, 0]);
+
+        let mut vectorscope = red;
+        Scope::new(ScopeType::Vectorscope)
+            .set_dimensions(5, 5)
+            .execute(&mut vectorscope)
+            .unwrap();
+        let pixels = &vectorscope.flatten_frames::<u16>()[0];
+        // neutral colors land in the center
+        let center = (2 * 5 + 2) * 3;
+        assert!(pixels[center] > 0);
+        assert_eq!(pixels[0], 0);
+    }
+}
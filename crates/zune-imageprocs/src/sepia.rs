@@ -0,0 +1,186 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Give an image the brown tones of an old photograph
+//!
+//! # Algorithm details
+//!
+//! The sepia colors are computed with the commonly used matrix
+//! ```text
+//! sepia_r = 0.393 * r + 0.769 * g + 0.189 * b
+//! sepia_g = 0.349 * r + 0.686 * g + 0.168 * b
+//! sepia_b = 0.272 * r + 0.534 * g + 0.131 * b
+//! ```
+//! and mixed with the original colors by the strength of the operation,
+//! results are clamped to the range of the bit depth
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::to_sample;
+
+const SEPIA: [[f32; 3]; 3] = [
+    [0.393, 0.769, 0.189],
+    [0.349, 0.686, 0.168],
+    [0.272, 0.534, 0.131]
+];
+
+/// Apply a sepia tone to an image
+///
+/// Grayscale images are converted to RGB, alpha is kept.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::sepia::Sepia;
+///
+/// let mut image = Image::fill::<u8>(100, ColorSpace::Luma, 100, 100);
+/// Sepia::new(1.0).execute(&mut image).unwrap();
+/// assert_eq!(image.colorspace(), ColorSpace::RGB);
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sepia {
+    strength: f32
+}
+
+impl Sepia {
+    /// Create a new sepia operation
+    ///
+    /// # Arguments
+    /// - strength: How much of the sepia tone is applied, between 0.0 which keeps the
+    ///   image as is and 1.0 for the full effect
+    #[must_use]
+    pub fn new(strength: f32) -> Sepia {
+        Sepia { strength }
+    }
+}
+
+impl OperationsTrait for Sepia {
+    fn name(&self) -> &'static str {
+        "Sepia"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        match image.colorspace() {
+            ColorSpace::Luma => image.convert_color(ColorSpace::RGB)?,
+            ColorSpace::LumaA => image.convert_color(ColorSpace::RGBA)?,
+            _ => ()
+        }
+        let strength = self.strength.clamp(0.0, 1.0);
+        // mix the identity matrix with the sepia one
+        let mut matrix = SEPIA;
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                let identity = if i == j { 1.0 } else { 0.0 };
+                *value = identity + (*value - identity) * strength;
+            }
+        }
+        let depth = image.depth().bit_type();
+
+        for frame in image.frames_mut() {
+            let [r, g, b, ..] = frame.channels_vec().as_mut_slice() else {
+                return Err(ImageErrors::GenericStr("Expected at least three channels"));
+            };
+            match depth {
+                BitType::U8 => sepia::<u8>(
+                    r.reinterpret_as_mut()?,
+                    g.reinterpret_as_mut()?,
+                    b.reinterpret_as_mut()?,
+                    &matrix
+                ),
+                BitType::U16 => sepia::<u16>(
+                    r.reinterpret_as_mut()?,
+                    g.reinterpret_as_mut()?,
+                    b.reinterpret_as_mut()?,
+                    &matrix
+                ),
+                BitType::F32 => sepia::<f32>(
+                    r.reinterpret_as_mut()?,
+                    g.reinterpret_as_mut()?,
+                    b.reinterpret_as_mut()?,
+                    &matrix
+                ),
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::LumaA,
+            ColorSpace::Luma
+        ]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+fn sepia<T>(r: &mut [T], g: &mut [T], b: &mut [T], matrix: &[[f32; 3]; 3])
+where
+    T: Copy + NumOps<T>
+{
+    let max = T::max_val().to_f32();
+
+    for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+        let color = [r.to_f32(), g.to_f32(), b.to_f32()];
+
+        for (out, row) in [r, g, b].into_iter().zip(matrix) {
+            let value = (row[0] * color[0] + row[1] * color[1] + row[2] * color[2]).clamp(0.0, max);
+            *out = to_sample(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::sepia::Sepia;
+
+    #[test]
+    fn test_sepia() {
+        let mut image = Image::from_fn::<u8, _>(2, 1, ColorSpace::RGBA, |_, x, pix| {
+            pix[..4].copy_from_slice(&[[100, 100, 100, 50], [255, 255, 255, 255]][x]);
+        });
+        let mut unchanged = image.clone();
+
+        Sepia::new(1.0).execute(&mut image).unwrap();
+        assert_eq!(
+            image.flatten_to_u8()[0],
+            [135, 120, 94, 50, 255, 255, 239, 255]
+        );
+
+        Sepia::new(0.0).execute(&mut unchanged).unwrap();
+        assert_eq!(
+            unchanged.flatten_to_u8()[0],
+            [100, 100, 100, 50, 255, 255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn test_sepia_float_clamps() {
+        let mut image = Image::fill::<f32>(1.0, ColorSpace::Luma, 2, 2);
+        Sepia::new(1.0).execute(&mut image).unwrap();
+
+        let channels = image.frames_ref()[0].channels_ref(ColorSpace::RGB, false);
+        let red = channels[0].reinterpret_as::<f32>().unwrap();
+        assert!(red.iter().all(|x| (*x - 1.0).abs() < f32::EPSILON));
+    }
+}
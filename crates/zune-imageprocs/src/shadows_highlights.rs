@@ -0,0 +1,232 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Lift shadows and recover highlights
+//!
+//! This brightens the dark areas of an image and darkens the bright ones while keeping
+//! the detail inside them, similar to the shadows and highlights sliders of photo editors.
+//!
+//! # Algorithm details
+//! - Pixels are converted to linear light, as floats. Values above 1.0 in `f32` images,
+//!   e.g. from HDR sources, are kept so their highlights can be brought back into range.
+//! - A luminance mask is built from the perceptual lightness of every pixel and blurred,
+//!   so that it follows large areas and not small details.
+//! - Every pixel is scaled by an exposure gain read from the mask
+//!   ```text
+//!   stops = shadows * 2 * shadow_weight(mask) - highlights * 2 * highlight_weight(mask)
+//!   pixel = pixel * 2^stops
+//!   ```
+//!   where the weights fall smoothly from 1.0 in the deepest shadows (highlights) to 0.0
+//!   at mid gray.
+//!
+//! Because neighbouring pixels get almost the same gain, their ratios, and with them local
+//! contrast and colors, are preserved, unlike a tone curve which flattens the tones it
+//! compresses.
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::gaussian_blur::gaussian_blur_f32;
+use crate::utils::{linear_to_srgb, normalized_samples, srgb_to_linear, store_samples};
+
+/// Exposure change, in stops, of the deepest shadows and brightest highlights at full strength
+const MAX_STOPS: f32 = 2.0;
+
+/// Lift shadows and recover highlights of an image
+///
+/// Alpha is ignored.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::shadows_highlights::ShadowsHighlights;
+///
+/// let mut image = Image::fill::<u16>(4000, ColorSpace::RGB, 100, 100);
+/// ShadowsHighlights::new(0.5, 0.5).execute(&mut image).unwrap();
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShadowsHighlights {
+    shadows:    f32,
+    highlights: f32,
+    radius:     Option<f32>
+}
+
+impl ShadowsHighlights {
+    /// Create a new shadows and highlights operation
+    ///
+    /// # Arguments
+    /// - shadows: How much to brighten shadows, between 0.0 and 1.0
+    /// - highlights: How much to darken highlights, between 0.0 and 1.0
+    #[must_use]
+    pub fn new(shadows: f32, highlights: f32) -> ShadowsHighlights {
+        ShadowsHighlights {
+            shadows:    shadows.clamp(0.0, 1.0),
+            highlights: highlights.clamp(0.0, 1.0),
+            radius:     None
+        }
+    }
+    /// Set the sigma of the blur applied to the luminance mask
+    ///
+    /// Larger values preserve more local contrast but can cause halos around
+    /// edges between bright and dark areas. By default it is 2% of the larger dimension
+    /// of the image
+    #[must_use]
+    pub fn set_radius(mut self, radius: f32) -> Self {
+        self.radius = Some(radius.max(0.0));
+        self
+    }
+}
+
+impl OperationsTrait for ShadowsHighlights {
+    fn name(&self) -> &'static str {
+        "Shadows Highlights"
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let bit_type = image.depth().bit_type();
+        let colors = if image.colorspace().is_grayscale() { 1 } else { 3 };
+        let radius = self
+            .radius
+            .unwrap_or(width.max(height) as f32 * 0.02)
+            .max(1.0);
+
+        for frame in image.frames_mut() {
+            let channels = &mut frame.channels_vec()[..colors];
+
+            let mut linear = channels
+                .iter()
+                .map(|c| normalized_samples(c, bit_type, self.name()))
+                .collect::<Result<Vec<_>, _>>()?;
+            linear
+                .iter_mut()
+                .flatten()
+                .for_each(|x| *x = srgb_to_linear(*x));
+
+            let mut mask: Vec<f32> = if colors == 1 {
+                linear[0].clone()
+            } else {
+                linear[0]
+                    .iter()
+                    .zip(&linear[1])
+                    .zip(&linear[2])
+                    .map(|((r, g), b)| 0.2126 * r + 0.7152 * g + 0.0722 * b)
+                    .collect()
+            };
+            for x in &mut mask {
+                *x = linear_to_srgb(x.max(0.0));
+            }
+
+            let mut scratch = vec![0.0; mask.len()];
+            gaussian_blur_f32(&mut mask, &mut scratch, width, height, radius);
+
+            let gains: Vec<f32> = mask
+                .iter()
+                .map(|m| {
+                    let shadow = 1.0 - smoothstep(0.0, 0.5, *m);
+                    let highlight = smoothstep(0.5, 1.0, *m);
+                    let stops = MAX_STOPS * (self.shadows * shadow - self.highlights * highlight);
+                    stops.exp2()
+                })
+                .collect();
+
+            for (channel, values) in channels.iter_mut().zip(&mut linear) {
+                for (value, gain) in values.iter_mut().zip(&gains) {
+                    *value = linear_to_srgb(*value * gain);
+                }
+                store_samples(channel, bit_type, self.name(), values)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::LumaA,
+            ColorSpace::Luma
+        ]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::shadows_highlights::ShadowsHighlights;
+
+    #[test]
+    fn test_shadows_and_highlights() {
+        // dark left half, bright right half
+        let mut image = Image::from_fn::<u8, _>(40, 20, ColorSpace::Luma, |_, x, pix| {
+            pix[0] = if x < 20 { 30 } else { 230 };
+        });
+        let unchanged = image.clone();
+
+        ShadowsHighlights::new(1.0, 1.0)
+            .set_radius(2.0)
+            .execute(&mut image)
+            .unwrap();
+        let pixels = &image.flatten_to_u8()[0];
+        assert!(pixels[2] > 50, "{}", pixels[2]);
+        assert!(pixels[37] < 210, "{}", pixels[37]);
+
+        let mut noop = unchanged.clone();
+        ShadowsHighlights::new(0.0, 0.0).execute(&mut noop).unwrap();
+        assert_eq!(noop.flatten_to_u8(), unchanged.flatten_to_u8());
+    }
+
+    #[test]
+    fn test_local_contrast_and_colors_preserved() {
+        // fine texture and a color in the shadows
+        let mut image = Image::from_fn::<u16, _>(32, 32, ColorSpace::RGB, |y, x, pix| {
+            let value = if (x + y) % 2 == 0 { 2000 } else { 3000 };
+            pix[..3].copy_from_slice(&[value, value / 2, value / 4]);
+        });
+        ShadowsHighlights::new(1.0, 0.0)
+            .set_radius(4.0)
+            .execute(&mut image)
+            .unwrap();
+
+        let channels = image.frames_ref()[0].channels_ref(ColorSpace::RGB, false);
+        let red = channels[0].reinterpret_as::<u16>().unwrap();
+        let green = channels[1].reinterpret_as::<u16>().unwrap();
+        // brighter, the texture survives and red stays redder than green
+        assert!(red[0] > 3000 && red[1] > red[0], "{} {}", red[0], red[1]);
+        assert!(red[0] > green[0] * 3 / 2);
+    }
+
+    #[test]
+    fn test_hdr_highlights_recovered() {
+        let mut image = Image::fill::<f32>(1.5, ColorSpace::RGB, 16, 16);
+        ShadowsHighlights::new(0.0, 1.0)
+            .execute(&mut image)
+            .unwrap();
+
+        let channels = image.frames_ref()[0].channels_ref(ColorSpace::RGB, false);
+        let red = channels[0].reinterpret_as::<f32>().unwrap();
+        assert!(red[0] < 1.0, "{}", red[0]);
+    }
+}
@@ -0,0 +1,189 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Cut an image to a rounded rectangle, circle or ellipse
+//!
+//! Pixels outside the shape become transparent and its edge is anti-aliased, which
+//! makes e.g. avatars and rounded thumbnails directly from photos.
+//!
+//! - [`MaskShape::RoundedRect`] rounds the corners of the image
+//! - [`MaskShape::Circle`] first crops the image to its centered square, then cuts the
+//!   circle touching its edges
+//! - [`MaskShape::Ellipse`] cuts the ellipse touching the edges of the image
+//!
+//! The image is converted to RGBA, an existing alpha channel is multiplied with
+//! the mask.
+//!
+//! # Example
+//! - Make a round avatar from a landscape photo
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::shape_mask::{MaskShape, ShapeMask};
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 300, 200);
+//! ShapeMask::new(MaskShape::Circle).execute(&mut image).unwrap();
+//!
+//! assert_eq!(image.dimensions(), (200, 200));
+//! assert_eq!(image.colorspace(), ColorSpace::RGBA);
+//! // the corners are transparent
+//! assert_eq!(image.flatten_to_u8()[0][3], 0);
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::crop::Crop;
+use crate::matting::map_rgba;
+use crate::utils::rounded_rect_coverage;
+
+/// The shape kept by a [`ShapeMask`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub enum MaskShape {
+    /// The whole image with corners rounded by `radius` pixels
+    RoundedRect { radius: f32 },
+    /// The largest circle centered in the image, the image is cropped to it
+    Circle,
+    /// The ellipse touching the edges of the image
+    Ellipse
+}
+
+/// Make pixels outside a shape transparent
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShapeMask {
+    shape: MaskShape
+}
+
+impl ShapeMask {
+    /// Create a new shape mask operation
+    #[must_use]
+    pub fn new(shape: MaskShape) -> ShapeMask {
+        ShapeMask { shape }
+    }
+}
+
+impl OperationsTrait for ShapeMask {
+    fn name(&self) -> &'static str {
+        "Shape Mask"
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if self.shape == MaskShape::Circle {
+            let (width, height) = image.dimensions();
+            let side = width.min(height);
+
+            if side != width || side != height {
+                Crop::new(side, side, (width - side) / 2, (height - side) / 2)
+                    .execute_impl(image)?;
+            }
+        }
+        let (width, height) = image.dimensions();
+        let (w, h) = (width as f32, height as f32);
+
+        let coverage = |x: f32, y: f32| match self.shape {
+            MaskShape::RoundedRect { radius } => {
+                rounded_rect_coverage((x, y), (0.0, 0.0, w, h), radius)
+            }
+            MaskShape::Circle | MaskShape::Ellipse => ellipse_coverage((x, y), (w, h))
+        };
+
+        map_rgba(image, self.name(), |_, alpha| {
+            for (i, alpha) in alpha.iter_mut().enumerate() {
+                let (x, y) = ((i % width) as f32 + 0.5, (i / width) as f32 + 0.5);
+                *alpha *= coverage(x, y);
+            }
+        })
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Coverage of a pixel centered at `(x, y)` by the ellipse touching the edges of a
+/// `width` x `height` rectangle
+///
+/// The signed distance to the edge is approximated from the implicit equation of the
+/// ellipse and its gradient, exact for circles and close enough for one pixel of
+/// anti-aliasing otherwise
+fn ellipse_coverage((x, y): (f32, f32), (width, height): (f32, f32)) -> f32 {
+    let (a, b) = (width * 0.5, height * 0.5);
+    if a <= 0.0 || b <= 0.0 {
+        return 0.0;
+    }
+    let (nx, ny) = ((x - a) / a, (y - b) / b);
+    let length = nx.hypot(ny);
+    if length == 0.0 {
+        return 1.0;
+    }
+    // gradient of sqrt((x/a)^2 + (y/b)^2), which is 1 at the edge
+    let gradient = (nx / a).hypot(ny / b) / length;
+    let distance = (length - 1.0) / gradient;
+
+    (0.5 - distance).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::shape_mask::{MaskShape, ShapeMask};
+
+    #[test]
+    fn test_rounded_rect() {
+        let mut image = Image::fill::<u8>(200, ColorSpace::Luma, 40, 20);
+        ShapeMask::new(MaskShape::RoundedRect { radius: 8.0 })
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.colorspace(), ColorSpace::RGBA);
+        assert_eq!(image.dimensions(), (40, 20));
+
+        let pixels = &image.flatten_to_u8()[0];
+        let alpha = |x: usize, y: usize| pixels[(y * 40 + x) * 4 + 3];
+        // corners are cut, edges between them and the center are kept
+        assert_eq!(alpha(0, 0), 0);
+        assert_eq!(alpha(39, 19), 0);
+        assert_eq!(alpha(20, 0), 255);
+        assert_eq!(alpha(0, 10), 255);
+        assert_eq!(alpha(20, 10), 255);
+        // the edge of the curve is anti-aliased
+        assert!((1..255).contains(&alpha(1, 3)), "{}", alpha(1, 3));
+        assert_eq!(pixels[(10 * 40 + 20) * 4], 200);
+    }
+
+    #[test]
+    fn test_circle_and_existing_alpha() {
+        let mut image = Image::from_fn::<u16, _>(30, 20, ColorSpace::LumaA, |_, x, pix| {
+            pix[0] = 1000;
+            pix[1] = if x < 15 { 65535 } else { 0 };
+        });
+        ShapeMask::new(MaskShape::Circle)
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.dimensions(), (20, 20));
+
+        let pixels = &image.flatten_frames::<u16>()[0];
+        let alpha = |x: usize, y: usize| pixels[(y * 20 + x) * 4 + 3];
+
+        assert_eq!(alpha(0, 0), 0);
+        assert_eq!(alpha(1, 10), 65535);
+        assert_eq!(alpha(9, 1), 65535);
+        // transparent before masking
+        assert_eq!(alpha(19, 10), 0);
+        // symmetric around the center
+        assert_eq!(alpha(2, 3), alpha(3, 2));
+        assert_eq!(alpha(2, 3), alpha(3, 17));
+    }
+}
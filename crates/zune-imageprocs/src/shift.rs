@@ -0,0 +1,186 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Move the contents of an image
+//!
+//! This moves pixels within the image without changing its dimensions, e.g. to align
+//! an image with another after finding their translation with
+//! [`register_translation`](crate::registration::register_translation).
+//!
+//! # Example
+//! - Move an image 10 pixels to the right and 5 pixels up
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::shift::Shift;
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+//! Shift::new(10.0, -5.0).execute(&mut image).unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::{execute_on, to_sample};
+
+/// Move the contents of an image by `(dx, dy)` pixels
+///
+/// Positive values move the contents right and down, fractional
+/// values are interpolated bilinearly. Pixels moved in from outside the
+/// image are set to zero, alpha is moved with the pixels.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Shift {
+    dx: f32,
+    dy: f32
+}
+
+impl Shift {
+    /// Create a new shift moving the image contents by `(dx, dy)`
+    #[must_use]
+    pub fn new(dx: f32, dy: f32) -> Shift {
+        Shift { dx, dy }
+    }
+}
+
+impl OperationsTrait for Shift {
+    fn name(&self) -> &'static str {
+        "Shift"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let depth = image.depth().bit_type();
+
+        let shift_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
+            let mut new_channel =
+                Channel::new_with_length_and_type(channel.len(), channel.type_id());
+
+            match depth {
+                BitType::U8 => shift::<u8>(
+                    channel.reinterpret_as()?,
+                    new_channel.reinterpret_as_mut()?,
+                    width,
+                    self.dx,
+                    self.dy
+                ),
+                BitType::U16 => shift::<u16>(
+                    channel.reinterpret_as()?,
+                    new_channel.reinterpret_as_mut()?,
+                    width,
+                    self.dx,
+                    self.dy
+                ),
+                BitType::F32 => shift::<f32>(
+                    channel.reinterpret_as()?,
+                    new_channel.reinterpret_as_mut()?,
+                    width,
+                    self.dx,
+                    self.dy
+                ),
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+            *channel = new_channel;
+            Ok(())
+        };
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        execute_on(shift_fn, image, false)
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn shift<T>(input: &[T], output: &mut [T], width: usize, dx: f32, dy: f32)
+where
+    T: Copy + NumOps<T>
+{
+    let height = input.len() / width;
+
+    let (x0, fx) = (dx.floor(), dx - dx.floor());
+    let (y0, fy) = (dy.floor(), dy - dy.floor());
+
+    // the four source pixels around an output pixel, zero outside the image
+    let sample = |x: f32, y: f32| -> f32 {
+        if x < 0.0 || y < 0.0 || x >= width as f32 || y >= height as f32 {
+            0.0
+        } else {
+            input[y as usize * width + x as usize].to_f32()
+        }
+    };
+
+    for (y, out_row) in output.chunks_exact_mut(width).enumerate() {
+        let src_y = y as f32 - y0;
+
+        for (x, out) in out_row.iter_mut().enumerate() {
+            let src_x = x as f32 - x0;
+
+            let top = sample(src_x, src_y) * (1.0 - fx) + sample(src_x - 1.0, src_y) * fx;
+            let bottom =
+                sample(src_x, src_y - 1.0) * (1.0 - fx) + sample(src_x - 1.0, src_y - 1.0) * fx;
+            let value = top * (1.0 - fy) + bottom * fy;
+
+            *out = to_sample(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::shift::Shift;
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn test_shift_whole_pixels() {
+        let mut image = Image::from_fn::<u8, _>(20, 10, ColorSpace::Luma, |y, x, pix| {
+            pix[0] = (y * 20 + x) as u8;
+        });
+        Shift::new(3.0, -2.0).execute(&mut image).unwrap();
+
+        let pixels = &image.flatten_to_u8()[0];
+        for y in 0..10 {
+            for x in 0..20 {
+                let expected = if x < 3 || y >= 8 { 0 } else { (y + 2) * 20 + x - 3 };
+                assert_eq!(usize::from(pixels[y * 20 + x]), expected);
+            }
+        }
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn test_shift_fractional() {
+        let mut image = Image::from_fn::<u16, _>(8, 1, ColorSpace::Luma, |_, x, pix| {
+            pix[0] = (x * 100) as u16;
+        });
+        Shift::new(0.5, 0.0).execute(&mut image).unwrap();
+
+        let pixels = image.frames_ref()[0].channels_ref(ColorSpace::Luma, false)[0]
+            .reinterpret_as::<u16>()
+            .unwrap()
+            .to_vec();
+        // the first pixel is half the first input pixel and half outside the image
+        assert_eq!(pixels, [0, 50, 150, 250, 350, 450, 550, 650]);
+    }
+}
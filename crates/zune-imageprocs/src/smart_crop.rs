@@ -0,0 +1,512 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Crop an image to an aspect ratio, keeping its most interesting part
+//!
+//! This is mostly useful for generating thumbnails, where the subject of a picture
+//! is rarely at its center.
+//!
+//! # Algorithm details
+//! The largest window with the aspect ratio of the target size is slid along the
+//! longer side of the image, and the position with the highest score is kept.
+//! The image is analysed at a reduced size, so its cost barely depends on the image size.
+//!
+//! Windows are scored by one of the [`SmartCropMethod`]s
+//! - `Attention` sums an interest map of every pixel, made of its edge strength, and its
+//!   color distance to the average color of the image, which finds objects that stand
+//!   out from the background (Achanta et al., "Frequency-tuned Salient Region Detection")
+//! - `Entropy` uses the Shannon entropy of the luminance histogram of the window, which
+//!   prefers busy, detailed areas
+//! - `Center` always picks the center
+//!
+//! [Regions of interest](crate::roi) of the image, e.g. faces, take precedence over
+//! the method, windows keeping the largest weighted part of them win, and the method
+//! only decides between windows that keep as much. `Center` ignores them.
+//!
+//! When scores tie, e.g. for flat images, the window closest to the center wins.
+//!
+//! # Example
+//! - Make a 200x200 thumbnail of a 640x480 picture
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::smart_crop::SmartCrop;
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 640, 480);
+//! SmartCrop::new(200, 200).execute(&mut image).unwrap();
+//! assert_eq!(image.dimensions(), (200, 200));
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::crop::Crop;
+use crate::pixelate::Rectangle;
+use crate::resize::{Resize, ResizeMethod};
+use crate::utils::{frame_luma, normalized_samples};
+
+/// Longest side, in pixels, of the reduced image windows are scored on
+const ANALYSIS_SIZE: usize = 256;
+/// Number of positions scored along the free axis, at most
+const MAX_CANDIDATES: usize = 64;
+/// Number of luminance bins used to compute entropy
+const ENTROPY_BINS: usize = 64;
+
+/// How [`SmartCrop`] scores candidate windows
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub enum SmartCropMethod {
+    /// Prefer edges and regions whose color stands out
+    #[default]
+    Attention,
+    /// Prefer regions with the most varied luminance
+    Entropy,
+    /// Always crop the center
+    Center
+}
+
+impl SmartCropMethod {
+    pub fn from_string_result(input: &str) -> Result<Self, String> {
+        match input {
+            "attention" => Ok(Self::Attention),
+            "entropy" => Ok(Self::Entropy),
+            "center" => Ok(Self::Center),
+            _ => Err(
+                "Unknown smart crop method,accepted values are attention,entropy,center"
+                    .to_string()
+            )
+        }
+    }
+}
+
+/// Crop an image to the aspect ratio of a target size, keeping its most interesting part
+///
+/// The cropped region is then resized to the target size, unless disabled with
+/// [`set_resize`](SmartCrop::set_resize)
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct SmartCrop {
+    width:  usize,
+    height: usize,
+    method: SmartCropMethod,
+    resize: bool
+}
+
+impl SmartCrop {
+    /// Create a new smart crop operation for a `width` x `height` target
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> SmartCrop {
+        SmartCrop {
+            width,
+            height,
+            method: SmartCropMethod::default(),
+            resize: true
+        }
+    }
+    /// Set how candidate windows are scored
+    #[must_use]
+    pub fn set_method(mut self, method: SmartCropMethod) -> Self {
+        self.method = method;
+        self
+    }
+    /// Set whether the cropped region is resized to the target size, otherwise it
+    /// keeps the resolution of the image and only has the target's aspect ratio
+    #[must_use]
+    pub fn set_resize(mut self, resize: bool) -> Self {
+        self.resize = resize;
+        self
+    }
+
+    /// Return the region of the image this operation would crop
+    ///
+    /// Only the first frame is analysed.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn find_region(&self, image: &Image) -> Result<Rectangle, ImageErrors> {
+        let (width, height) = image.dimensions();
+
+        if self.width == 0 || self.height == 0 || width == 0 || height == 0 {
+            return Err(ImageErrors::GenericStr(
+                "Smart crop image and target dimensions should not be zero"
+            ));
+        }
+        // the largest window with the aspect ratio of the target
+        let aspect = self.width as f64 / self.height as f64;
+        let (crop_width, crop_height) = if width as f64 > height as f64 * aspect {
+            (
+                ((height as f64 * aspect).round() as usize).clamp(1, width),
+                height
+            )
+        } else {
+            (
+                width,
+                ((width as f64 / aspect).round() as usize).clamp(1, height)
+            )
+        };
+        let horizontal = crop_width < width;
+        let range = if horizontal { width - crop_width } else { height - crop_height };
+        let region = |position: usize| {
+            if horizontal {
+                Rectangle::new(position, 0, crop_width, crop_height)
+            } else {
+                Rectangle::new(0, position, crop_width, crop_height)
+            }
+        };
+        if range == 0 || self.method == SmartCropMethod::Center {
+            return Ok(region(range / 2));
+        }
+
+        let analysis = Analysis::new(image)?;
+        let scorer = match self.method {
+            SmartCropMethod::Attention => Scorer::Attention(analysis.interest_integral()),
+            _ => Scorer::Entropy
+        };
+
+        // candidates closest to the center come first, so they win ties
+        let step = range.div_ceil(MAX_CANDIDATES).max(1);
+        let mut candidates: Vec<usize> = (0..=range).step_by(step).collect();
+        candidates.extend([range / 2, range]);
+        candidates.sort_by_key(|x| x.abs_diff(range / 2));
+
+        let regions = image.metadata().regions_of_interest();
+        let mut best = ((f32::NEG_INFINITY, f32::NEG_INFINITY), range / 2);
+
+        for position in candidates {
+            let window = region(position);
+            let coverage: f32 = regions
+                .iter()
+                .filter_map(|x| {
+                    let area = (x.width * x.height) as f32;
+                    let inside = x.crop(window.x, window.y, window.width, window.height)?;
+                    Some(x.weight * (inside.width * inside.height) as f32 / area)
+                })
+                .sum();
+
+            let window = analysis.scale_region(&window);
+            let score = match &scorer {
+                Scorer::Attention(integral) => integral.sum(&window),
+                Scorer::Entropy => analysis.entropy(&window)
+            };
+            if (coverage, score) > best.0 {
+                best = ((coverage, score), position);
+            }
+        }
+        Ok(region(best.1))
+    }
+}
+
+impl OperationsTrait for SmartCrop {
+    fn name(&self) -> &'static str {
+        "Smart Crop"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let region = self.find_region(image)?;
+
+        Crop::new(region.width, region.height, region.x, region.y).execute_impl(image)?;
+
+        if self.resize && image.dimensions() != (self.width, self.height) {
+            Resize::new(self.width, self.height, ResizeMethod::Bilinear).execute_impl(image)?;
+        }
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+enum Scorer {
+    Attention(Integral),
+    Entropy
+}
+
+/// A reduced copy of the first frame of an image
+struct Analysis {
+    /// Size of the blocks of the image averaged into one pixel
+    scale:  usize,
+    width:  usize,
+    height: usize,
+    luma:   Vec<f32>,
+    /// Color channels, without alpha
+    colors: Vec<Vec<f32>>
+}
+
+impl Analysis {
+    fn new(image: &Image) -> Result<Analysis, ImageErrors> {
+        let (width, height) = image.dimensions();
+        let scale = width.max(height).div_ceil(ANALYSIS_SIZE);
+        let bit_type = image.depth().bit_type();
+
+        let shrink = |samples: Vec<f32>| downscale(&samples, width, height, scale);
+
+        let luma = shrink(frame_luma(image, 0)?);
+        let colors = match image.frames_ref().first() {
+            Some(frame) if image.colorspace() != ColorSpace::Luma => frame
+                .channels_ref(image.colorspace(), true)
+                .iter()
+                .map(|c| normalized_samples(c, bit_type, "Smart Crop").map(shrink))
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => vec![luma.clone()]
+        };
+
+        Ok(Analysis {
+            scale,
+            width: width.div_ceil(scale),
+            height: height.div_ceil(scale),
+            luma,
+            colors
+        })
+    }
+
+    /// Return `region` of the full image in reduced coordinates, at least one pixel large
+    fn scale_region(&self, region: &Rectangle) -> Rectangle {
+        let x = (region.x / self.scale).min(self.width - 1);
+        let y = (region.y / self.scale).min(self.height - 1);
+
+        Rectangle::new(
+            x,
+            y,
+            (region.width / self.scale).clamp(1, self.width - x),
+            (region.height / self.scale).clamp(1, self.height - y)
+        )
+    }
+
+    /// Build the integral image of the interest map, the sum of the edge strength and
+    /// the distance to the average color, each normalized to a maximum of 1.0
+    #[allow(clippy::cast_precision_loss)]
+    fn interest_integral(&self) -> Integral {
+        let (width, height) = (self.width, self.height);
+        let luma = &self.luma;
+
+        let mut edges = vec![0.0; width * height];
+        for y in 0..height {
+            let (up, down) = (y.saturating_sub(1), (y + 1).min(height - 1));
+            for x in 0..width {
+                let (left, right) = (x.saturating_sub(1), (x + 1).min(width - 1));
+                let dx = luma[y * width + right] - luma[y * width + left];
+                let dy = luma[down * width + x] - luma[up * width + x];
+                edges[y * width + x] = (dx * dx + dy * dy).sqrt();
+            }
+        }
+
+        let means: Vec<f32> = self
+            .colors
+            .iter()
+            .map(|c| c.iter().sum::<f32>() / c.len() as f32)
+            .collect();
+        let saliency: Vec<f32> = (0..width * height)
+            .map(|i| {
+                self.colors
+                    .iter()
+                    .zip(&means)
+                    .map(|(c, mean)| (c[i] - mean) * (c[i] - mean))
+                    .sum::<f32>()
+                    .sqrt()
+            })
+            .collect();
+
+        let normalize = |map: &[f32]| {
+            let max = map.iter().fold(0.0_f32, |a, b| a.max(*b));
+            if max > 0.0 {
+                max.recip()
+            } else {
+                0.0
+            }
+        };
+        let (edge_scale, saliency_scale) = (normalize(&edges), normalize(&saliency));
+        let interest: Vec<f32> = edges
+            .iter()
+            .zip(&saliency)
+            .map(|(e, s)| e * edge_scale + s * saliency_scale)
+            .collect();
+
+        Integral::new(&interest, width, height)
+    }
+
+    /// Shannon entropy of the luminance histogram of `region`
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn entropy(&self, region: &Rectangle) -> f32 {
+        let mut histogram = [0_u32; ENTROPY_BINS];
+
+        for y in region.y..region.y + region.height {
+            let row = &self.luma[y * self.width + region.x..][..region.width];
+            for value in row {
+                let bin = (value.clamp(0.0, 1.0) * (ENTROPY_BINS - 1) as f32).round() as usize;
+                histogram[bin] += 1;
+            }
+        }
+        let count = (region.width * region.height) as f32;
+
+        histogram
+            .iter()
+            .filter(|x| **x > 0)
+            .map(|x| {
+                let p = *x as f32 / count;
+                -p * p.log2()
+            })
+            .sum()
+    }
+}
+
+/// Summed area table, for sums over rectangles in constant time
+struct Integral {
+    width: usize,
+    sums:  Vec<f64>
+}
+
+impl Integral {
+    fn new(values: &[f32], width: usize, height: usize) -> Integral {
+        // one extra row and column of zeros at the top and left
+        let stride = width + 1;
+        let mut sums = vec![0.0; stride * (height + 1)];
+
+        for y in 0..height {
+            let mut row_sum = 0.0;
+            for x in 0..width {
+                row_sum += f64::from(values[y * width + x]);
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row_sum;
+            }
+        }
+        Integral { width, sums }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn sum(&self, region: &Rectangle) -> f32 {
+        let stride = self.width + 1;
+        let (x0, y0) = (region.x, region.y);
+        let (x1, y1) = (x0 + region.width, y0 + region.height);
+
+        (self.sums[y1 * stride + x1] - self.sums[y0 * stride + x1] - self.sums[y1 * stride + x0]
+            + self.sums[y0 * stride + x0]) as f32
+    }
+}
+
+/// Average `scale` x `scale` blocks of a `width` x `height` plane, blocks at the right and
+/// bottom edges can be smaller
+#[allow(clippy::cast_precision_loss)]
+fn downscale(samples: &[f32], width: usize, height: usize, scale: usize) -> Vec<f32> {
+    if scale == 1 {
+        return samples.to_vec();
+    }
+    let (out_width, out_height) = (width.div_ceil(scale), height.div_ceil(scale));
+    let mut sums = vec![0.0_f32; out_width * out_height];
+    let mut counts = vec![0_u32; out_width * out_height];
+
+    for (y, row) in samples.chunks_exact(width).enumerate() {
+        let out_row = (y / scale) * out_width;
+        for (x, value) in row.iter().enumerate() {
+            sums[out_row + x / scale] += value;
+            counts[out_row + x / scale] += 1;
+        }
+    }
+    for (sum, count) in sums.iter_mut().zip(&counts) {
+        *sum /= *count as f32;
+    }
+    sums
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::metadata::RegionOfInterest;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::pixelate::Rectangle;
+    use crate::smart_crop::{SmartCrop, SmartCropMethod};
+
+    /// A flat gray 600x200 image with a detailed square between x = 450 and 550
+    fn subject_on_the_right() -> Image {
+        Image::from_fn::<u8, _>(600, 200, ColorSpace::RGB, |y, x, pix| {
+            let inside = (450..550).contains(&x) && (50..150).contains(&y);
+            if inside && (x / 5 + y / 5) % 2 == 0 {
+                pix[..3].copy_from_slice(&[220, 40, 30]);
+            } else if inside {
+                pix[..3].copy_from_slice(&[20, 20, 200]);
+            } else {
+                pix[..3].copy_from_slice(&[120, 120, 120]);
+            }
+        })
+    }
+
+    #[test]
+    fn test_finds_subject() {
+        let image = subject_on_the_right();
+
+        for method in [SmartCropMethod::Attention, SmartCropMethod::Entropy] {
+            let region = SmartCrop::new(100, 100)
+                .set_method(method)
+                .find_region(&image)
+                .unwrap();
+            // a 200x200 window containing the whole square
+            assert_eq!((region.y, region.width, region.height), (0, 200, 200));
+            assert!(
+                region.x <= 450 && region.x + 200 >= 550,
+                "{method:?} {region:?}"
+            );
+        }
+        let center = SmartCrop::new(100, 100)
+            .set_method(SmartCropMethod::Center)
+            .find_region(&image)
+            .unwrap();
+        assert_eq!(center, Rectangle::new(200, 0, 200, 200));
+    }
+
+    #[test]
+    fn test_regions_of_interest_win() {
+        let mut image = subject_on_the_right();
+        image
+            .metadata_mut()
+            .set_regions_of_interest(vec![RegionOfInterest::new(20, 60, 50, 50)]);
+
+        for method in [SmartCropMethod::Attention, SmartCropMethod::Entropy] {
+            let region = SmartCrop::new(100, 100)
+                .set_method(method)
+                .find_region(&image)
+                .unwrap();
+            assert!(region.x <= 20, "{method:?} {region:?}");
+        }
+        // the region follows the crop and the resize
+        SmartCrop::new(100, 100).execute(&mut image).unwrap();
+        let regions = image.metadata().regions_of_interest();
+        assert_eq!(regions.len(), 1);
+        assert_eq!((regions[0].width, regions[0].height), (25, 25));
+    }
+
+    #[test]
+    fn test_flat_images_use_center() {
+        let image = Image::fill::<u16>(1000, ColorSpace::Luma, 100, 400);
+        let region = SmartCrop::new(4, 3).find_region(&image).unwrap();
+        assert_eq!(region, Rectangle::new(0, 162, 100, 75));
+    }
+
+    #[test]
+    fn test_smart_crop_output() {
+        let mut image = subject_on_the_right();
+        SmartCrop::new(64, 48).execute_impl(&mut image).unwrap();
+        assert_eq!(image.dimensions(), (64, 48));
+
+        let mut image = subject_on_the_right();
+        SmartCrop::new(64, 48)
+            .set_resize(false)
+            .execute_impl(&mut image)
+            .unwrap();
+        assert_eq!(image.dimensions(), (267, 200));
+    }
+}
@@ -42,6 +42,7 @@ use crate::utils::execute_on;
 ///
 /// The window is a 3x3 window.
 #[derive(Default, Copy, Clone)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sobel;
 
 impl Sobel {
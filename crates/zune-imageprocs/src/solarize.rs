@@ -0,0 +1,111 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Invert the bright parts of an image
+//!
+//! Solarization imitates overexposed photographic film, where the brightest
+//! tones turn dark again.
+//!
+//! # Algorithm details
+//!
+//! ```text
+//! max_value -> maximum value of an image depth
+//!
+//! pixel = pixel > threshold * max_value ? max_value - pixel : pixel
+//! ```
+//!
+//! # Implementation details
+//! - For `u8` and `u16` , we use lookup tables to improve speed
+//! - For `f32` naive execution is used
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::utils::{execute_on, map_samples};
+
+/// Solarize an image
+///
+/// The alpha channel is ignored.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::solarize::Solarize;
+///
+/// let mut image = Image::fill::<u8>(200, ColorSpace::RGB, 100, 100);
+/// Solarize::new(0.5).execute(&mut image).unwrap();
+/// assert_eq!(image.flatten_to_u8()[0][0], 55);
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Solarize {
+    threshold: f32
+}
+
+impl Solarize {
+    /// Create a new solarize operation
+    ///
+    /// # Arguments
+    /// - threshold: Value above which pixels are inverted, between 0.0 and 1.0 where 1.0 stands
+    ///   for the maximum value of the bit depth. 0.5 is the classic effect
+    #[must_use]
+    pub fn new(threshold: f32) -> Solarize {
+        Solarize { threshold }
+    }
+}
+
+impl OperationsTrait for Solarize {
+    fn name(&self) -> &'static str {
+        "Solarize"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let bit_type = image.depth().bit_type();
+
+        let solarize_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
+            map_samples(channel, bit_type, self.name(), |x| {
+                if x > self.threshold {
+                    1.0 - x
+                } else {
+                    x
+                }
+            })
+        };
+        execute_on(solarize_fn, image, true)
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::solarize::Solarize;
+
+    #[test]
+    fn test_solarize() {
+        let mut image = Image::from_fn::<u16, _>(4, 1, ColorSpace::Luma, |_, x, pix| {
+            pix[0] = [0, 30000, 40000, 65535][x];
+        });
+        Solarize::new(0.5).execute(&mut image).unwrap();
+
+        let pixels = image.frames_ref()[0].channels_ref(ColorSpace::Luma, false)[0]
+            .reinterpret_as::<u16>()
+            .unwrap()
+            .to_vec();
+        assert_eq!(pixels, [0, 30000, 25535, 0]);
+    }
+}
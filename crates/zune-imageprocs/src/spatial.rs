@@ -22,6 +22,7 @@
 /// larger radius means more compute time.
 ///
 /// for example a radius of R will result in a search window length of 2R+1 for each dimension.
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct SpatialOps {
     radius:    usize,
     operation: SpatialOperations
@@ -15,6 +15,7 @@ use crate::traits::NumOps;
 
 /// Spatial operations implemented for images
 #[derive(Copy, Clone, Debug, PartialOrd, PartialEq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub enum SpatialOperations {
     /// (max-min)/(max+min)
     Contrast,
@@ -0,0 +1,472 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Convert between animations and sprite sheets
+//!
+//! Game engines and CSS animations usually want an animation as a single image,
+//! a sprite sheet, with every frame in a cell of a grid and a manifest saying where
+//! each frame is and how long it's shown.
+//!
+//! - [`SpriteSheet`] lays out the frames of an animated image, e.g. a decoded APNG,
+//!   left to right and top to bottom, in a single row unless a number of columns is set.
+//!   [`SpriteSheet::manifest`] describes the layout and must be called before the
+//!   frames are combined, as the result no longer has frame durations.
+//! - [`SplitSpriteSheet`] does the reverse, cutting a sheet into frames either from a
+//!   manifest or from a grid of equally sized cells.
+//!
+//! Durations in the manifest are in milliseconds.
+//!
+//! # Example
+//! ```
+//! use zune_core::bit_depth::BitDepth;
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::frame::Frame;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::sprite_sheet::{SpriteSheet, SplitSpriteSheet};
+//!
+//! // three 4x4 frames shown for 1/10 of a second each
+//! let frames = (0..3)
+//!     .map(|i| Frame::from_u8(&[i * 50; 4 * 4 * 3], ColorSpace::RGB, 1, 10))
+//!     .collect();
+//! let mut image = Image::new_frames(frames, BitDepth::Eight, 4, 4, ColorSpace::RGB);
+//!
+//! let sheet = SpriteSheet::new().set_columns(2);
+//! let manifest = sheet.manifest(&image);
+//! sheet.execute(&mut image).unwrap();
+//!
+//! assert_eq!(image.dimensions(), (8, 8));
+//! assert_eq!(manifest.frames[2].x, 0);
+//! assert_eq!(manifest.frames[2].y, 4);
+//! assert_eq!(manifest.frames[2].duration, 100);
+//!
+//! // and back to an animation
+//! SplitSpriteSheet::from_manifest(&manifest)
+//!     .execute(&mut image)
+//!     .unwrap();
+//! assert_eq!(image.frames_len(), 3);
+//! assert_eq!(image.frames_ref()[2].duration(), (100, 1000));
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::frame::Frame;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::crop::crop;
+
+/// Where a frame is in a sprite sheet and how long it's shown
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpriteFrame {
+    /// Column of the left edge of the frame, in pixels
+    pub x:        usize,
+    /// Row of the top edge of the frame, in pixels
+    pub y:        usize,
+    /// How long the frame is shown, in milliseconds
+    pub duration: usize
+}
+
+/// The layout of a sprite sheet
+///
+/// With the `serde-support` feature this can be stored as JSON next to the sheet
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpriteSheetManifest {
+    /// Width of every frame
+    pub frame_width:  usize,
+    /// Height of every frame
+    pub frame_height: usize,
+    /// Number of columns of the grid
+    pub columns:      usize,
+    /// Number of rows of the grid
+    pub rows:         usize,
+    /// The frames in the order they are shown
+    pub frames:       Vec<SpriteFrame>
+}
+
+/// Combine the frames of an image into a sprite sheet
+#[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpriteSheet {
+    columns: Option<usize>
+}
+
+impl SpriteSheet {
+    /// Create a sprite sheet with all frames in a single row
+    #[must_use]
+    pub fn new() -> SpriteSheet {
+        SpriteSheet::default()
+    }
+    /// Set the number of columns of the grid, rows are added as needed
+    #[must_use]
+    pub fn set_columns(mut self, columns: usize) -> Self {
+        self.columns = Some(columns.max(1));
+        self
+    }
+
+    /// Describe the sheet this creates from `image`
+    ///
+    /// Frame durations are taken from `image`, so call this before
+    /// executing the operation
+    #[must_use]
+    pub fn manifest(&self, image: &Image) -> SpriteSheetManifest {
+        let (frame_width, frame_height) = image.dimensions();
+        let count = image.frames_len();
+        let columns = self.columns.unwrap_or(count).clamp(1, count.max(1));
+
+        let frames = image
+            .frames_ref()
+            .iter()
+            .enumerate()
+            .map(|(i, frame)| SpriteFrame {
+                x:        (i % columns) * frame_width,
+                y:        (i / columns) * frame_height,
+                duration: duration_ms(frame.duration())
+            })
+            .collect();
+
+        SpriteSheetManifest {
+            frame_width,
+            frame_height,
+            columns,
+            rows: count.div_ceil(columns),
+            frames
+        }
+    }
+}
+
+/// Convert a duration of `numerator / denominator` seconds to milliseconds
+///
+/// A denominator of zero counts as 100, like in APNG
+fn duration_ms((numerator, denominator): (usize, usize)) -> usize {
+    let denominator = if denominator == 0 { 100 } else { denominator };
+    (numerator * 1000 + denominator / 2) / denominator
+}
+
+impl OperationsTrait for SpriteSheet {
+    fn name(&self) -> &'static str {
+        "Sprite Sheet"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let manifest = self.manifest(image);
+        let width = manifest.columns * manifest.frame_width;
+        let height = manifest.rows * manifest.frame_height;
+        let bit_type = image.depth().bit_type();
+        let size = image.depth().size_of();
+        let name = self.name();
+
+        let mut channels: Vec<Channel> = image.frames_ref()[0]
+            .channels_vec_ref()
+            .iter()
+            .map(|x| Channel::new_with_length_and_type(width * height * size, x.type_id()))
+            .collect();
+
+        for (frame, sprite) in image.frames_ref().iter().zip(&manifest.frames) {
+            for (out, channel) in channels.iter_mut().zip(frame.channels_vec_ref()) {
+                let position = (sprite.x, sprite.y, manifest.frame_width);
+
+                match bit_type {
+                    BitType::U8 => paste::<u8>(
+                        channel.reinterpret_as()?,
+                        out.reinterpret_as_mut()?,
+                        width,
+                        position
+                    ),
+                    BitType::U16 => paste::<u16>(
+                        channel.reinterpret_as()?,
+                        out.reinterpret_as_mut()?,
+                        width,
+                        position
+                    ),
+                    BitType::F32 => paste::<f32>(
+                        channel.reinterpret_as()?,
+                        out.reinterpret_as_mut()?,
+                        width,
+                        position
+                    ),
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(name, d))
+                }
+            }
+        }
+        let mut sheet = Image::new_frames(
+            vec![Frame::new(channels)],
+            image.depth(),
+            width,
+            height,
+            image.colorspace()
+        );
+        *sheet.metadata_mut() = image.metadata().clone();
+        sheet.set_dimensions(width, height);
+        // regions of interest don't carry over to the new layout
+        sheet.metadata_mut().set_regions_of_interest(vec![]);
+        *image = sheet;
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Copy `frame`, `frame_width` pixels wide, into `sheet` with its top left corner at `(x, y)`
+fn paste<T: Copy>(
+    frame: &[T], sheet: &mut [T], sheet_width: usize, (x, y, frame_width): (usize, usize, usize)
+) {
+    if frame_width == 0 {
+        return;
+    }
+    for (row, out) in frame
+        .chunks_exact(frame_width)
+        .zip(sheet.chunks_exact_mut(sheet_width).skip(y))
+    {
+        out[x..x + frame_width].copy_from_slice(row);
+    }
+}
+
+/// Cut a sprite sheet into the frames of an animation
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct SplitSpriteSheet {
+    frame_width:  usize,
+    frame_height: usize,
+    /// Frames to cut out, or every cell of the grid if `None`
+    frames:       Option<Vec<SpriteFrame>>,
+    /// Duration of frames cut from a grid, in milliseconds
+    duration:     usize
+}
+
+impl SplitSpriteSheet {
+    /// Cut a sheet into cells of `frame_width` x `frame_height`, read left to right
+    /// and top to bottom, every frame shown for 100 milliseconds
+    #[must_use]
+    pub fn new(frame_width: usize, frame_height: usize) -> SplitSpriteSheet {
+        SplitSpriteSheet {
+            frame_width,
+            frame_height,
+            frames: None,
+            duration: 100
+        }
+    }
+    /// Cut out the frames described by a manifest, with their durations
+    #[must_use]
+    pub fn from_manifest(manifest: &SpriteSheetManifest) -> SplitSpriteSheet {
+        SplitSpriteSheet {
+            frame_width:  manifest.frame_width,
+            frame_height: manifest.frame_height,
+            frames:       Some(manifest.frames.clone()),
+            duration:     100
+        }
+    }
+    /// Set how long frames cut from a grid are shown, in milliseconds
+    #[must_use]
+    pub fn set_duration(mut self, duration: usize) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Return the frames to cut out of a sheet of `width` x `height`
+    fn frames(&self, width: usize, height: usize) -> Vec<SpriteFrame> {
+        if let Some(frames) = &self.frames {
+            return frames.clone();
+        }
+        let columns = width / self.frame_width;
+        let rows = height / self.frame_height;
+
+        (0..rows * columns)
+            .map(|i| SpriteFrame {
+                x:        (i % columns) * self.frame_width,
+                y:        (i / columns) * self.frame_height,
+                duration: self.duration
+            })
+            .collect()
+    }
+}
+
+impl OperationsTrait for SplitSpriteSheet {
+    fn name(&self) -> &'static str {
+        "Split Sprite Sheet"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let (frame_width, frame_height) = (self.frame_width, self.frame_height);
+
+        if frame_width == 0 || frame_height == 0 {
+            return Err(ImageErrors::GenericStr("Sprite frames cannot be empty"));
+        }
+        let sprites = self.frames(width, height);
+
+        if sprites.is_empty() {
+            return Err(ImageErrors::GenericStr(
+                "Sprite sheet is smaller than a single frame"
+            ));
+        }
+        if sprites
+            .iter()
+            .any(|x| x.x + frame_width > width || x.y + frame_height > height)
+        {
+            return Err(ImageErrors::GenericStr(
+                "Sprite frame extends past the sheet"
+            ));
+        }
+        let bit_type = image.depth().bit_type();
+        let size = image.depth().size_of();
+        let name = self.name();
+        let sheet = &image.frames_ref()[0];
+        let mut frames = Vec::with_capacity(sprites.len());
+
+        for sprite in &sprites {
+            let mut channels = Vec::with_capacity(sheet.channels_vec_ref().len());
+
+            for channel in sheet.channels_vec_ref() {
+                let mut out = Channel::new_with_length_and_type(
+                    frame_width * frame_height * size,
+                    channel.type_id()
+                );
+                match bit_type {
+                    BitType::U8 => crop::<u8>(
+                        channel.reinterpret_as()?,
+                        width,
+                        out.reinterpret_as_mut()?,
+                        frame_width,
+                        frame_height,
+                        sprite.x,
+                        sprite.y
+                    ),
+                    BitType::U16 => crop::<u16>(
+                        channel.reinterpret_as()?,
+                        width,
+                        out.reinterpret_as_mut()?,
+                        frame_width,
+                        frame_height,
+                        sprite.x,
+                        sprite.y
+                    ),
+                    BitType::F32 => crop::<f32>(
+                        channel.reinterpret_as()?,
+                        width,
+                        out.reinterpret_as_mut()?,
+                        frame_width,
+                        frame_height,
+                        sprite.x,
+                        sprite.y
+                    ),
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(name, d))
+                }
+                channels.push(out);
+            }
+            frames.push(Frame::new_with_duration(channels, sprite.duration, 1000));
+        }
+        let mut animation = Image::new_frames(
+            frames,
+            image.depth(),
+            frame_width,
+            frame_height,
+            image.colorspace()
+        );
+        *animation.metadata_mut() = image.metadata().clone();
+        animation.set_dimensions(frame_width, frame_height);
+        // regions of interest don't carry over to the new layout
+        animation.metadata_mut().set_regions_of_interest(vec![]);
+        *image = animation;
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::frame::Frame;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::sprite_sheet::{SplitSpriteSheet, SpriteFrame, SpriteSheet};
+
+    /// An animation of `count` 2x1 frames, every frame filled with its index
+    fn animation(count: u16) -> Image {
+        let frames = (0..count)
+            .map(|i| Frame::from_u16(&[i; 2 * 2], ColorSpace::LumaA, 1, 25))
+            .collect();
+        Image::new_frames(frames, BitDepth::Sixteen, 2, 1, ColorSpace::LumaA)
+    }
+
+    #[test]
+    fn test_sprite_sheet_grid() {
+        let mut image = animation(5);
+        let sheet = SpriteSheet::new().set_columns(2);
+        let manifest = sheet.manifest(&image);
+        sheet.execute(&mut image).unwrap();
+
+        assert_eq!((manifest.columns, manifest.rows), (2, 3));
+        assert_eq!(
+            manifest.frames[3],
+            SpriteFrame {
+                x:        2,
+                y:        1,
+                duration: 40
+            }
+        );
+        assert_eq!(image.dimensions(), (4, 3));
+        assert_eq!(image.frames_len(), 1);
+        // the last cell is left empty
+        let luma = image.frames_ref()[0].channels_vec_ref()[0]
+            .reinterpret_as::<u16>()
+            .unwrap()
+            .to_vec();
+        assert_eq!(luma, [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 0, 0]);
+
+        // a single row by default
+        let mut image = animation(3);
+        SpriteSheet::new().execute(&mut image).unwrap();
+        assert_eq!(image.dimensions(), (6, 1));
+    }
+
+    #[test]
+    fn test_sprite_sheet_round_trip() {
+        let original = animation(4);
+        let mut image = original.clone();
+        let sheet = SpriteSheet::new().set_columns(3);
+        let manifest = sheet.manifest(&image);
+        sheet.execute(&mut image).unwrap();
+
+        SplitSpriteSheet::from_manifest(&manifest)
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(image.frames_len(), 4);
+        assert_eq!(
+            image.flatten_frames::<u16>(),
+            original.flatten_frames::<u16>()
+        );
+        assert_eq!(image.frames_ref()[0].duration(), (40, 1000));
+
+        // without a manifest every cell is a frame, including empty ones
+        let mut image = animation(4);
+        sheet.execute(&mut image).unwrap();
+        SplitSpriteSheet::new(2, 1)
+            .set_duration(20)
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.frames_len(), 6);
+        assert_eq!(image.frames_ref()[5].duration(), (20, 1000));
+
+        let mut image = animation(1);
+        assert!(SplitSpriteSheet::new(3, 1).execute(&mut image).is_err());
+        assert!(SplitSpriteSheet::new(0, 1).execute(&mut image).is_err());
+    }
+}
@@ -0,0 +1,713 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Stitch overlapping images into a single larger image
+//!
+//! Images are placed at known offsets on a canvas just large enough to hold all of them,
+//! e.g. microscope fields of view or map tiles. Where images overlap, small differences
+//! in exposure or alignment show up as visible seams, [`StitchBlend`] controls how
+//! these are hidden.
+//!
+//! Parts of the canvas not covered by any image are zero, i.e. black, or transparent
+//! for images with alpha.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_imageprocs::stitch::{Stitch, StitchBlend};
+//!
+//! let left = Image::fill::<u8>(50, ColorSpace::RGB, 100, 80);
+//! let right = Image::fill::<u8>(70, ColorSpace::RGB, 100, 80);
+//!
+//! // the images overlap by 20 pixels
+//! let panorama = Stitch::new(StitchBlend::Feather { width: 10 })
+//!     .add_image(&left, 0, 0)
+//!     .add_image(&right, 80, 0)
+//!     .stitch()
+//!     .unwrap();
+//!
+//! assert_eq!(panorama.dimensions(), (180, 80));
+//! ```
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+
+use crate::registration::register_translation;
+use crate::utils::{normalized_samples, to_channels};
+
+/// How overlapping images are blended
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StitchBlend {
+    /// Images added later cover earlier ones, leaving hard seams
+    Overwrite,
+    /// Average overlapping images, weighting pixels by their distance to the image
+    /// edge so images fade into each other over `width` pixels
+    ///
+    /// Simple and fast, but misaligned detail in wide overlaps shows up twice
+    Feather {
+        /// Distance from the edge, in pixels, at which a pixel gets full weight
+        width: usize
+    },
+    /// Split images into `levels` frequency bands with Laplacian pyramids and
+    /// blend each band over a width matching its frequency
+    ///
+    /// Low frequencies, like exposure differences, are blended over wide regions
+    /// while fine detail switches over a few pixels, hiding seams without ghosting.
+    /// Each level doubles the blend width, 4 to 6 levels work well for most images
+    Multiband {
+        /// Number of pyramid levels, 1 leaves hard seams in the middle of overlaps
+        levels: usize
+    }
+}
+
+/// An image placed on the canvas
+#[derive(Copy, Clone)]
+struct Tile {
+    x:      usize,
+    y:      usize,
+    width:  usize,
+    height: usize
+}
+
+impl Tile {
+    /// Distance of the canvas position `(x, y)` to the closest edge of the tile,
+    /// 1 for pixels on the edge
+    #[allow(clippy::cast_precision_loss)]
+    fn edge_distance(&self, x: usize, y: usize) -> f32 {
+        let x = x - self.x;
+        let y = y - self.y;
+        (x + 1).min(y + 1).min(self.width - x).min(self.height - y) as f32
+    }
+}
+
+/// Stitch images placed at known offsets into one image
+///
+/// All images must have the same colorspace and depth, only the first frame of
+/// animated images is used. Offsets are relative to each other and may be negative,
+/// the canvas starts at the top left corner of the leftmost and topmost image.
+///
+/// Alpha is blended like any other channel.
+///
+/// Images with unknown offsets can be aligned automatically with
+/// [`add_image_registered`](Self::add_image_registered).
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_imageprocs::stitch::{Stitch, StitchBlend};
+///
+/// let tile = Image::fill::<u16>(1000, ColorSpace::Luma, 64, 64);
+///
+/// // a 2x2 grid of tiles overlapping by 8 pixels
+/// let mut stitch = Stitch::new(StitchBlend::Multiband { levels: 4 });
+/// for (x, y) in [(0, 0), (56, 0), (0, 56), (56, 56)] {
+///     stitch = stitch.add_image(&tile, x, y);
+/// }
+/// let mosaic = stitch.stitch().unwrap();
+/// assert_eq!(mosaic.dimensions(), (120, 120));
+/// ```
+pub struct Stitch<'src> {
+    images: Vec<(&'src Image, isize, isize)>,
+    blend:  StitchBlend
+}
+
+impl<'src> Stitch<'src> {
+    /// Create a new stitch without images, blending overlaps with `blend`
+    #[must_use]
+    pub fn new(blend: StitchBlend) -> Stitch<'src> {
+        Stitch {
+            images: vec![],
+            blend
+        }
+    }
+    /// Add an image with its top left corner at `(x, y)`
+    #[must_use]
+    pub fn add_image(mut self, image: &'src Image, x: isize, y: isize) -> Self {
+        self.images.push((image, x, y));
+        self
+    }
+    /// Add an image, placing it where it lines up with the image added before it
+    ///
+    /// The offset is found with [`register_translation`], so the images must overlap,
+    /// the larger the overlap the more reliable the result. Call [`register_translation`]
+    /// directly to check the confidence of the alignment.
+    ///
+    /// # Errors
+    /// If no image was added before, or the images can't be registered
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn add_image_registered(self, image: &'src Image) -> Result<Self, ImageErrors> {
+        let Some((previous, x, y)) = self.images.last().copied() else {
+            return Err(ImageErrors::GenericStr(
+                "Cannot register the first image of a stitch"
+            ));
+        };
+        let (dx, dy, _) = register_translation(previous, image)?;
+
+        Ok(self.add_image(image, x + dx as isize, y + dy as isize))
+    }
+    /// Return the width and height of the stitched image
+    ///
+    /// This is `(0, 0)` if no images were added
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn canvas_dimensions(&self) -> (usize, usize) {
+        let (min_x, min_y, max_x, max_y) = self.bounds();
+        ((max_x - min_x) as usize, (max_y - min_y) as usize)
+    }
+
+    /// Return the smallest and largest x and y coordinates covered by an image
+    #[allow(clippy::cast_possible_wrap)]
+    fn bounds(&self) -> (isize, isize, isize, isize) {
+        if self.images.is_empty() {
+            return (0, 0, 0, 0);
+        }
+        self.images.iter().fold(
+            (isize::MAX, isize::MAX, isize::MIN, isize::MIN),
+            |(min_x, min_y, max_x, max_y), (image, x, y)| {
+                let (width, height) = image.dimensions();
+                (
+                    min_x.min(*x),
+                    min_y.min(*y),
+                    max_x.max(x + width as isize),
+                    max_y.max(y + height as isize)
+                )
+            }
+        )
+    }
+
+    /// Composite all images onto a new canvas
+    ///
+    /// # Errors
+    /// - No images were added, or one of them is empty
+    /// - The images don't have the same colorspace and depth
+    /// - The image depth isn't supported
+    #[allow(clippy::cast_sign_loss)]
+    pub fn stitch(&self) -> Result<Image, ImageErrors> {
+        let Some((first, _, _)) = self.images.first() else {
+            return Err(ImageErrors::GenericStr("No images to stitch"));
+        };
+        let colorspace = first.colorspace();
+        let depth = first.depth();
+
+        for (image, _, _) in &self.images {
+            if image.colorspace() != colorspace {
+                return Err(ImageErrors::GenericStr(
+                    "Image colorspace does not match for stitch"
+                ));
+            }
+            if image.depth() != depth {
+                return Err(ImageErrors::GenericStr(
+                    "Image depths do not match for stitch"
+                ));
+            }
+            if image.dimensions().0 == 0 || image.dimensions().1 == 0 {
+                return Err(ImageErrors::GenericStr("Cannot stitch empty images"));
+            }
+        }
+        let (min_x, min_y, _, _) = self.bounds();
+        let (width, height) = self.canvas_dimensions();
+        let components = colorspace.num_components();
+
+        let tiles: Vec<Tile> = self
+            .images
+            .iter()
+            .map(|(image, x, y)| Tile {
+                x:      (x - min_x) as usize,
+                y:      (y - min_y) as usize,
+                width:  image.dimensions().0,
+                height: image.dimensions().1
+            })
+            .collect();
+
+        let weights = match self.blend {
+            StitchBlend::Overwrite => vec![],
+            StitchBlend::Feather { width: feather } => feather_weights(&tiles, feather),
+            StitchBlend::Multiband { .. } => seam_masks(&tiles, width, height)
+        };
+        let mut pixels = vec![0.0_f32; width * height * components];
+
+        for component in 0..components {
+            let planes = self
+                .images
+                .iter()
+                .map(|(image, _, _)| {
+                    let channel = &image.frames_ref()[0].channels_ref(colorspace, false)[component];
+                    normalized_samples(channel, depth.bit_type(), "Stitch")
+                })
+                .collect::<Result<Vec<_>, ImageErrors>>()?;
+
+            let plane = match self.blend {
+                StitchBlend::Overwrite => overwrite(&tiles, &planes, width, height),
+                StitchBlend::Feather { .. } => feather(&tiles, &planes, &weights, width, height),
+                StitchBlend::Multiband { levels } => {
+                    multiband(&tiles, &planes, &weights, width, height, levels)
+                }
+            };
+            for (pix, sample) in pixels.chunks_exact_mut(components).zip(plane) {
+                pix[component] = sample;
+            }
+        }
+
+        Ok(Image::new(
+            to_channels(&pixels, depth.bit_type(), colorspace),
+            depth,
+            width,
+            height,
+            colorspace
+        ))
+    }
+}
+
+fn overwrite(tiles: &[Tile], planes: &[Vec<f32>], width: usize, height: usize) -> Vec<f32> {
+    let mut out = vec![0.0; width * height];
+
+    for (tile, plane) in tiles.iter().zip(planes) {
+        for (y, row) in plane.chunks_exact(tile.width).enumerate() {
+            let start = (tile.y + y) * width + tile.x;
+            out[start..start + tile.width].copy_from_slice(row);
+        }
+    }
+    out
+}
+
+/// Weights for feathering, per tile, falling off linearly over `feather` pixels
+/// towards the tile edges
+#[allow(clippy::cast_precision_loss)]
+fn feather_weights(tiles: &[Tile], feather: usize) -> Vec<Vec<f32>> {
+    let feather = feather.max(1) as f32;
+
+    tiles
+        .iter()
+        .map(|tile| {
+            let mut weights = Vec::with_capacity(tile.width * tile.height);
+            for y in tile.y..tile.y + tile.height {
+                for x in tile.x..tile.x + tile.width {
+                    weights.push((tile.edge_distance(x, y) / feather).min(1.0));
+                }
+            }
+            weights
+        })
+        .collect()
+}
+
+fn feather(
+    tiles: &[Tile], planes: &[Vec<f32>], weights: &[Vec<f32>], width: usize, height: usize
+) -> Vec<f32> {
+    let mut sum = vec![0.0_f32; width * height];
+    let mut total = vec![0.0_f32; width * height];
+
+    for ((tile, plane), weights) in tiles.iter().zip(planes).zip(weights) {
+        for (y, (row, weights)) in plane
+            .chunks_exact(tile.width)
+            .zip(weights.chunks_exact(tile.width))
+            .enumerate()
+        {
+            let start = (tile.y + y) * width + tile.x;
+
+            for ((sample, weight), (sum, total)) in row.iter().zip(weights).zip(
+                sum[start..start + tile.width]
+                    .iter_mut()
+                    .zip(&mut total[start..start + tile.width])
+            ) {
+                *sum += sample * weight;
+                *total += weight;
+            }
+        }
+    }
+    for (sum, total) in sum.iter_mut().zip(total) {
+        if total > 0.0 {
+            *sum /= total;
+        }
+    }
+    sum
+}
+
+/// Masks, per tile, which are 1.0 where the tile is the one to show
+///
+/// Each canvas pixel belongs to the tile it is furthest inside of,
+/// which puts seams in the middle of overlaps
+fn seam_masks(tiles: &[Tile], width: usize, height: usize) -> Vec<Vec<f32>> {
+    let mut best = vec![0.0_f32; width * height];
+    let mut owner = vec![usize::MAX; width * height];
+
+    for (i, tile) in tiles.iter().enumerate() {
+        for y in tile.y..tile.y + tile.height {
+            for x in tile.x..tile.x + tile.width {
+                let distance = tile.edge_distance(x, y);
+                // later tiles win ties, like they do when overwriting
+                if distance >= best[y * width + x] {
+                    best[y * width + x] = distance;
+                    owner[y * width + x] = i;
+                }
+            }
+        }
+    }
+    tiles
+        .iter()
+        .enumerate()
+        .map(|(i, tile)| {
+            let mut mask = Vec::with_capacity(tile.width * tile.height);
+            for y in tile.y..tile.y + tile.height {
+                for x in tile.x..tile.x + tile.width {
+                    mask.push(f32::from(u8::from(owner[y * width + x] == i)));
+                }
+            }
+            mask
+        })
+        .collect()
+}
+
+/// Multiband blending, Burt and Adelson, "A multiresolution spline with
+/// application to image mosaics"
+///
+/// Every tile is extended to a region aligned to the coarsest level so its
+/// pyramid lines up with the canvas pyramid, the extension continues the
+/// tile as [`extend`] does and has a zero mask
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+fn multiband(
+    tiles: &[Tile], planes: &[Vec<f32>], masks: &[Vec<f32>], width: usize, height: usize,
+    levels: usize
+) -> Vec<f32> {
+    // more levels than the canvas can be halved only add padding
+    let max_levels = (usize::BITS - width.max(height).leading_zeros()) as usize;
+    let levels = levels.clamp(1, max_levels);
+    let align = 1 << (levels - 1);
+
+    let canvas_width = width.next_multiple_of(align);
+    let canvas_height = height.next_multiple_of(align);
+
+    let mut sums: Vec<Vec<f32>> = (0..levels)
+        .map(|l| vec![0.0; (canvas_width >> l) * (canvas_height >> l)])
+        .collect();
+    let mut totals = sums.clone();
+
+    for ((tile, plane), mask) in tiles.iter().zip(planes).zip(masks) {
+        let x0 = tile.x / align * align;
+        let y0 = tile.y / align * align;
+        let region_width = (tile.x + tile.width).next_multiple_of(align) - x0;
+        let region_height = (tile.y + tile.height).next_multiple_of(align) - y0;
+
+        let mut image = Vec::with_capacity(region_width * region_height);
+        let mut weights = Vec::with_capacity(region_width * region_height);
+
+        for y in y0..y0 + region_height {
+            for x in x0..x0 + region_width {
+                let inside = (tile.x..tile.x + tile.width).contains(&x)
+                    && (tile.y..tile.y + tile.height).contains(&y);
+
+                let tx = x as isize - tile.x as isize;
+                let ty = y as isize - tile.y as isize;
+
+                let sample = extend(
+                    &|i| extend(&|j| plane[i * tile.width + j], tx, tile.width, true),
+                    ty,
+                    tile.height,
+                    true
+                );
+                image.push(sample);
+                weights.push(if inside {
+                    mask[ty as usize * tile.width + tx as usize]
+                } else {
+                    0.0
+                });
+            }
+        }
+        let mut w = region_width;
+        let mut h = region_height;
+
+        for level in 0..levels {
+            let (next_image, next_weights) = if level + 1 < levels {
+                (reduce(&image, w, h, true), reduce(&weights, w, h, false))
+            } else {
+                (vec![], vec![])
+            };
+            // the band between this level and the next, the coarsest level is kept as is
+            let band = if level + 1 < levels {
+                let expanded = expand(&next_image, w / 2, h / 2);
+                image.iter().zip(expanded).map(|(a, b)| a - b).collect()
+            } else {
+                image
+            };
+            let stride = canvas_width >> level;
+            let (cx, cy) = (x0 >> level, y0 >> level);
+
+            for y in 0..h {
+                let start = (cy + y) * stride + cx;
+
+                for x in 0..w {
+                    sums[level][start + x] += band[y * w + x] * weights[y * w + x];
+                    totals[level][start + x] += weights[y * w + x];
+                }
+            }
+            image = next_image;
+            weights = next_weights;
+            w /= 2;
+            h /= 2;
+        }
+    }
+    for (sum, total) in sums.iter_mut().zip(&totals) {
+        for (sum, total) in sum.iter_mut().zip(total) {
+            if *total > 1e-6 {
+                *sum /= total;
+            }
+        }
+    }
+    // collapse the blended pyramid, from the coarsest level up
+    let mut result = sums.pop().unwrap();
+
+    for (level, band) in sums.iter().enumerate().rev() {
+        let expanded = expand(
+            &result,
+            canvas_width >> (level + 1),
+            canvas_height >> (level + 1)
+        );
+        result = band.iter().zip(expanded).map(|(a, b)| a + b).collect();
+    }
+    // crop the alignment padding and clear what no tile covers
+    let mut out = vec![0.0; width * height];
+
+    for (tile, mask) in tiles.iter().zip(masks) {
+        for (y, mask) in mask.chunks_exact(tile.width).enumerate() {
+            for (x, covered) in mask.iter().enumerate() {
+                if *covered > 0.0 {
+                    let (x, y) = (tile.x + x, tile.y + y);
+                    out[y * width + x] = result[y * canvas_width + x];
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Return sample `i` of a row or column of `len` samples, extending it past the ends
+///
+/// Images are extended by point reflection, which continues gradients so tiles
+/// agree on coarse levels near their edges, weights repeat the edge instead
+/// as they must not go negative
+#[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+fn extend(get: &dyn Fn(usize) -> f32, i: isize, len: usize, linear: bool) -> f32 {
+    let last = len as isize - 1;
+    let clamp = |i: isize| get(i.clamp(0, last) as usize);
+
+    if (0..=last).contains(&i) {
+        get(i as usize)
+    } else if !linear {
+        clamp(i)
+    } else if i < 0 {
+        2.0 * get(0) - clamp(-i)
+    } else {
+        2.0 * get(last as usize) - clamp(2 * last - i)
+    }
+}
+
+/// Apply `tap` to every row and then every column of an image, producing
+/// an image of `out_width` by `out_height`
+fn separable(
+    image: &[f32], width: usize, height: usize, out_width: usize, out_height: usize,
+    tap: impl Fn(&dyn Fn(isize) -> f32, usize) -> f32, linear: bool
+) -> Vec<f32> {
+    let mut rows = vec![0.0; out_width * height];
+
+    for (row, out) in image
+        .chunks_exact(width)
+        .zip(rows.chunks_exact_mut(out_width))
+    {
+        for (x, out) in out.iter_mut().enumerate() {
+            *out = tap(&|i| extend(&|i| row[i], i, width, linear), x);
+        }
+    }
+    let mut out = vec![0.0; out_width * out_height];
+
+    for y in 0..out_height {
+        for x in 0..out_width {
+            let column = |i| rows[i * out_width + x];
+            out[y * out_width + x] = tap(&|i| extend(&column, i, height, linear), y);
+        }
+    }
+    out
+}
+
+/// Blur with a 5 tap binomial kernel and halve the dimensions, which must be even
+#[allow(clippy::cast_possible_wrap)]
+fn reduce(image: &[f32], width: usize, height: usize, linear: bool) -> Vec<f32> {
+    let tap = |get: &dyn Fn(isize) -> f32, i: usize| {
+        let i = 2 * i as isize;
+        (get(i - 2) + 4.0 * get(i - 1) + 6.0 * get(i) + 4.0 * get(i + 1) + get(i + 2)) / 16.0
+    };
+    separable(image, width, height, width / 2, height / 2, tap, linear)
+}
+
+/// Double the dimensions, interpolating with the kernel used by [`reduce`]
+#[allow(clippy::cast_possible_wrap)]
+fn expand(image: &[f32], width: usize, height: usize) -> Vec<f32> {
+    let tap = |get: &dyn Fn(isize) -> f32, i: usize| {
+        let half = (i / 2) as isize;
+
+        if i.is_multiple_of(2) {
+            (get(half - 1) + 6.0 * get(half) + get(half + 1)) / 8.0
+        } else {
+            (get(half) + get(half + 1)) * 0.5
+        }
+    };
+    separable(image, width, height, width * 2, height * 2, tap, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+
+    use crate::stitch::{Stitch, StitchBlend};
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn gradient() -> Image {
+        Image::from_fn::<u8, _>(120, 90, ColorSpace::RGB, |y, x, pix| {
+            pix[0] = (x * 2) as u8;
+            pix[1] = (y * 2) as u8;
+            pix[2] = ((x + y) % 256) as u8;
+        })
+    }
+
+    fn crop(image: &Image, x: usize, y: usize, width: usize, height: usize) -> Image {
+        let pixels = image.flatten_to_u8();
+        let (image_width, _) = image.dimensions();
+        Image::from_fn::<u8, _>(width, height, ColorSpace::RGB, |ty, tx, pix| {
+            let start = ((y + ty) * image_width + x + tx) * 3;
+            pix[..3].copy_from_slice(&pixels[0][start..start + 3]);
+        })
+    }
+
+    #[test]
+    fn test_stitch_reassembles_tiles() {
+        let image = gradient();
+        // overlapping tiles of the same image, in an arbitrary order
+        let tiles = [
+            (crop(&image, 50, 0, 70, 50), 50, 0),
+            (crop(&image, 0, 0, 60, 50), 0, 0),
+            (crop(&image, 0, 40, 70, 50), 0, 40),
+            (crop(&image, 60, 40, 60, 50), 60, 40)
+        ];
+        for (blend, tolerance) in [
+            (StitchBlend::Overwrite, 0),
+            (StitchBlend::Feather { width: 8 }, 1),
+            (StitchBlend::Multiband { levels: 5 }, 2)
+        ] {
+            let mut stitch = Stitch::new(blend);
+            for (tile, x, y) in &tiles {
+                stitch = stitch.add_image(tile, *x, *y);
+            }
+            let result = stitch.stitch().unwrap();
+            assert_eq!(result.dimensions(), image.dimensions());
+
+            let max_diff = result.flatten_to_u8()[0]
+                .iter()
+                .zip(&image.flatten_to_u8()[0])
+                .map(|(a, b)| a.abs_diff(*b))
+                .max()
+                .unwrap();
+            assert!(max_diff <= tolerance, "{blend:?}: {max_diff}");
+        }
+    }
+
+    #[test]
+    fn test_stitch_blends_seams() {
+        let dark = Image::fill::<u8>(0, ColorSpace::Luma, 60, 10);
+        let bright = Image::fill::<u8>(200, ColorSpace::Luma, 60, 10);
+
+        for blend in [
+            StitchBlend::Feather { width: 20 },
+            StitchBlend::Multiband { levels: 4 }
+        ] {
+            let result = Stitch::new(blend)
+                .add_image(&dark, 0, 0)
+                .add_image(&bright, 40, 0)
+                .stitch()
+                .unwrap();
+            let row = &result.flatten_to_u8()[0][5 * 100..6 * 100];
+
+            assert_eq!((row[0], row[99]), (0, 200));
+            // a gradual transition instead of a hard seam
+            assert!(row.windows(2).all(|x| x[0] <= x[1]), "{blend:?}");
+            assert!(row.windows(2).all(|x| x[1] - x[0] < 100), "{blend:?}");
+        }
+    }
+
+    #[test]
+    fn test_stitch_negative_offsets_and_gaps() {
+        let tile = Image::fill::<u16>(1000, ColorSpace::LumaA, 10, 10);
+
+        let stitch = Stitch::new(StitchBlend::Multiband { levels: 3 })
+            .add_image(&tile, -5, -20)
+            .add_image(&tile, 10, 0);
+        assert_eq!(stitch.canvas_dimensions(), (25, 30));
+
+        let result = stitch.stitch().unwrap();
+        let pixels = result.frames_ref()[0].channels_ref(ColorSpace::LumaA, false)[1]
+            .reinterpret_as::<u16>()
+            .unwrap()
+            .to_vec();
+        // covered pixels keep their value, the gap between tiles stays transparent
+        assert_eq!(pixels[0], 1000);
+        assert_eq!(pixels[25 * 25 + 20], 1000);
+        assert_eq!(pixels[15 * 25], 0);
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn test_stitch_registered() {
+        let image = Image::from_fn::<u8, _>(120, 90, ColorSpace::RGB, |y, x, pix| {
+            pix[..3].fill(((x * x + 3 * y * y + x * y) % 251) as u8);
+        });
+        let first = crop(&image, 0, 0, 80, 70);
+        let second = crop(&image, 40, 20, 80, 70);
+
+        let stitch = Stitch::new(StitchBlend::Overwrite)
+            .add_image(&first, 0, 0)
+            .add_image_registered(&second)
+            .unwrap();
+        let result = stitch.stitch().unwrap();
+
+        // the corners not covered by either tile are black
+        let expected: Vec<u8> = image.flatten_to_u8()[0]
+            .chunks_exact(3)
+            .enumerate()
+            .flat_map(|(i, pix)| {
+                let (x, y) = (i % 120, i / 120);
+                let covered = (x < 80 && y < 70) || (x >= 40 && y >= 20);
+                if covered {
+                    pix.to_vec()
+                } else {
+                    vec![0; 3]
+                }
+            })
+            .collect();
+        assert!(result.flatten_to_u8()[0] == expected);
+
+        assert!(Stitch::new(StitchBlend::Overwrite)
+            .add_image_registered(&first)
+            .is_err());
+    }
+
+    #[test]
+    fn test_stitch_errors() {
+        assert!(Stitch::new(StitchBlend::Overwrite).stitch().is_err());
+
+        let rgb = Image::fill::<u8>(0, ColorSpace::RGB, 10, 10);
+        let luma = Image::fill::<u8>(0, ColorSpace::Luma, 10, 10);
+        let result = Stitch::new(StitchBlend::Overwrite)
+            .add_image(&rgb, 0, 0)
+            .add_image(&luma, 5, 5)
+            .stitch();
+        assert!(result.is_err());
+    }
+}
@@ -10,6 +10,7 @@
 /// Linearly stretches the contrast in an image in place,
 /// sending lower to image minimum and upper to image maximum.
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct StretchContrast {
     lower: f32,
     upper: f32
@@ -0,0 +1,321 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Correct the white balance of an image
+//!
+//! The operation takes the color temperature, in Kelvin, and the tint of the light the
+//! image was taken under and adapts colors so that this light appears neutral, the same way
+//! the temperature and tint sliders of photo editors work.
+//!
+//! - A temperature below 6504 K (the white point of sRGB, D65) describes warm, orange light,
+//!   and makes the image cooler
+//! - A temperature above 6504 K describes cold, blue light, and makes the image warmer
+//! - A positive tint describes green light and makes the image more magenta, a negative tint
+//!   does the opposite
+//!
+//! # Algorithm details
+//! - The white point of the light is taken from the Planckian locus at the given temperature
+//!   (using the approximation of Kim et al.), then moved perpendicular to it by the tint.
+//! - A Bradford chromatic adaptation matrix from that white point to D65 is combined with the
+//!   sRGB to XYZ matrices, giving one 3x3 matrix.
+//! - Pixels are linearized with the sRGB transfer function, multiplied by the matrix and
+//!   converted back.
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::{linear_to_srgb, srgb_to_linear, to_sample};
+
+/// Correlated color temperature of D65, the white point of sRGB
+const D65_TEMPERATURE: f32 = 6504.0;
+/// Distance from the Planckian locus, in CIE 1960 uv, of a tint of 1.0
+const TINT_SCALE: f32 = 0.02;
+
+const BRADFORD: [[f32; 3]; 3] = [
+    [0.8951, 0.2664, -0.1614],
+    [-0.7502, 1.7135, 0.0367],
+    [0.0389, -0.0685, 1.0296]
+];
+const BRADFORD_INVERSE: [[f32; 3]; 3] = [
+    [0.986_993, -0.147_054, 0.159_963],
+    [0.432_305, 0.518_360, 0.049_291],
+    [-0.008_529, 0.040_043, 0.968_487]
+];
+const SRGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.412_456, 0.357_576, 0.180_438],
+    [0.212_673, 0.715_152, 0.072_175],
+    [0.019_334, 0.119_192, 0.950_304]
+];
+const XYZ_TO_SRGB: [[f32; 3]; 3] = [
+    [3.240_454, -1.537_139, -0.498_531],
+    [-0.969_266, 1.876_011, 0.041_556],
+    [0.055_643, -0.204_026, 1.057_225]
+];
+
+/// Adjust the white balance of an image
+///
+/// Grayscale images are converted to RGB, alpha is kept.
+///
+/// # Example
+/// - Correct an image taken under tungsten light
+///
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::temperature::Temperature;
+///
+/// let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+/// Temperature::new(3200.0, 0.0).execute(&mut image).unwrap();
+///
+/// let pixels = &image.flatten_to_u8()[0];
+/// // blue is boosted over red
+/// assert!(pixels[2] > pixels[0]);
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Temperature {
+    kelvin: f32,
+    tint:   f32
+}
+
+impl Temperature {
+    /// Create a new temperature operation
+    ///
+    /// # Arguments
+    /// - kelvin: Color temperature of the light, clamped between 1700 and 25000,
+    ///   6504 leaves the image unchanged
+    /// - tint: Green (positive) or magenta (negative) cast of the light, between -1.0 and 1.0,
+    ///   0.0 is the color of a black body
+    #[must_use]
+    pub fn new(kelvin: f32, tint: f32) -> Temperature {
+        Temperature { kelvin, tint }
+    }
+
+    /// Return the matrix adapting linear sRGB colors under the light to D65
+    fn matrix(self) -> [[f32; 3]; 3] {
+        let source = white_point(self.kelvin, self.tint.clamp(-1.0, 1.0));
+        let destination = white_point(D65_TEMPERATURE, 0.0);
+
+        let source_lms = multiply_vector(&BRADFORD, source);
+        let destination_lms = multiply_vector(&BRADFORD, destination);
+
+        let mut scale = [[0.0; 3]; 3];
+        for i in 0..3 {
+            scale[i][i] = destination_lms[i] / source_lms[i];
+        }
+        let adaptation = multiply(&BRADFORD_INVERSE, &multiply(&scale, &BRADFORD));
+
+        multiply(&XYZ_TO_SRGB, &multiply(&adaptation, &SRGB_TO_XYZ))
+    }
+}
+
+impl OperationsTrait for Temperature {
+    fn name(&self) -> &'static str {
+        "Temperature"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        match image.colorspace() {
+            ColorSpace::Luma => image.convert_color(ColorSpace::RGB)?,
+            ColorSpace::LumaA => image.convert_color(ColorSpace::RGBA)?,
+            _ => ()
+        }
+        let matrix = self.matrix();
+        let depth = image.depth().bit_type();
+
+        for frame in image.frames_mut() {
+            let [r, g, b, ..] = frame.channels_vec().as_mut_slice() else {
+                return Err(ImageErrors::GenericStr("Expected at least three channels"));
+            };
+            match depth {
+                BitType::U8 => adapt::<u8>(
+                    r.reinterpret_as_mut()?,
+                    g.reinterpret_as_mut()?,
+                    b.reinterpret_as_mut()?,
+                    &matrix
+                ),
+                BitType::U16 => adapt::<u16>(
+                    r.reinterpret_as_mut()?,
+                    g.reinterpret_as_mut()?,
+                    b.reinterpret_as_mut()?,
+                    &matrix
+                ),
+                BitType::F32 => adapt::<f32>(
+                    r.reinterpret_as_mut()?,
+                    g.reinterpret_as_mut()?,
+                    b.reinterpret_as_mut()?,
+                    &matrix
+                ),
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::LumaA,
+            ColorSpace::Luma
+        ]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+fn multiply(a: &[[f32; 3]; 3], b: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+fn multiply_vector(a: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    a.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+/// Return the chromaticity of the Planckian locus at `kelvin`
+fn planckian_xy(kelvin: f32) -> (f32, f32) {
+    let t = kelvin.clamp(1667.0, 25000.0);
+    let (t2, t3) = (t * t, t * t * t);
+
+    let x = if t <= 4000.0 {
+        -0.266_123_9e9 / t3 - 0.234_358_9e6 / t2 + 0.877_695_6e3 / t + 0.179_910
+    } else {
+        -3.025_846_9e9 / t3 + 2.107_038e6 / t2 + 0.222_634_7e3 / t + 0.240_390
+    };
+    let (x2, x3) = (x * x, x * x * x);
+
+    let y = if t <= 2222.0 {
+        -1.106_381_4 * x3 - 1.348_110_2 * x2 + 2.185_558_3 * x - 0.202_196_83
+    } else if t <= 4000.0 {
+        -0.954_947_6 * x3 - 1.374_185_9 * x2 + 2.091_37 * x - 0.167_488_67
+    } else {
+        3.081_758 * x3 - 5.873_387 * x2 + 3.751_13 * x - 0.370_014_83
+    };
+    (x, y)
+}
+
+/// Return the XYZ white point, with Y = 1, of light at `kelvin` moved off the
+/// Planckian locus by `tint`
+#[allow(clippy::many_single_char_names)]
+fn white_point(kelvin: f32, tint: f32) -> [f32; 3] {
+    // CIE 1960 uv, where distances to the locus are measured
+    let to_uv = |(x, y): (f32, f32)| {
+        let d = -2.0 * x + 12.0 * y + 3.0;
+        (4.0 * x / d, 6.0 * y / d)
+    };
+    let kelvin = kelvin.clamp(1700.0, 25000.0);
+    let (u, v) = to_uv(planckian_xy(kelvin));
+    let (u2, v2) = to_uv(planckian_xy(kelvin * 0.99));
+
+    // normal to the locus, pointing towards green
+    let (du, dv) = (u2 - u, v2 - v);
+    let length = du.hypot(dv).max(f32::EPSILON);
+    let (mut nu, mut nv) = (-dv / length, du / length);
+    if nv < 0.0 {
+        (nu, nv) = (-nu, -nv);
+    }
+    let u = u + nu * tint * TINT_SCALE;
+    let v = v + nv * tint * TINT_SCALE;
+
+    let d = 2.0 * u - 8.0 * v + 4.0;
+    let (x, y) = (3.0 * u / d, 2.0 * v / d);
+
+    [x / y, 1.0, (1.0 - x - y) / y]
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn adapt<T>(r: &mut [T], g: &mut [T], b: &mut [T], matrix: &[[f32; 3]; 3])
+where
+    T: Copy + NumOps<T>
+{
+    let max = T::max_val().to_f32();
+    let round = max > 1.0;
+    // integers are linearized through a table
+    let table: Vec<f32> = if round {
+        (0..=T::max_val().to_usize())
+            .map(|x| srgb_to_linear(x as f32 / max))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let linear = |x: T| {
+        if round {
+            table[x.to_usize()]
+        } else {
+            srgb_to_linear(x.to_f32())
+        }
+    };
+
+    for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+        let color = [linear(*r), linear(*g), linear(*b)];
+
+        for (out, row) in [r, g, b].into_iter().zip(matrix) {
+            let value = row[0] * color[0] + row[1] * color[1] + row[2] * color[2];
+            let value = linear_to_srgb(value.max(0.0)) * max;
+
+            *out = to_sample(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::temperature::{Temperature, D65_TEMPERATURE};
+
+    fn balance(kelvin: f32, tint: f32, color: [u8; 3]) -> Vec<u8> {
+        let mut image = Image::from_fn::<u8, _>(1, 1, ColorSpace::RGB, |_, _, pix| {
+            pix[..3].copy_from_slice(&color);
+        });
+        Temperature::new(kelvin, tint).execute(&mut image).unwrap();
+        image.flatten_to_u8()[0].clone()
+    }
+
+    #[test]
+    fn test_neutral_temperature() {
+        for color in [[0, 0, 0], [12, 100, 240], [255, 255, 255]] {
+            assert_eq!(balance(D65_TEMPERATURE, 0.0, color), color);
+        }
+    }
+
+    #[test]
+    fn test_temperature_direction() {
+        // warm light is corrected towards blue, cold light towards red
+        let warm = balance(3000.0, 0.0, [128, 128, 128]);
+        assert!(warm[2] > warm[1] && warm[1] > warm[0], "{warm:?}");
+        let cold = balance(10000.0, 0.0, [128, 128, 128]);
+        assert!(cold[0] > cold[1] && cold[1] > cold[2], "{cold:?}");
+
+        // green light is corrected towards magenta
+        let green = balance(D65_TEMPERATURE, 0.5, [128, 128, 128]);
+        assert!(green[0] > green[1] && green[2] > green[1], "{green:?}");
+    }
+
+    #[test]
+    fn test_adapts_light_color_to_gray() {
+        // white under 3000 K light, as encoded by an sRGB camera without white balance
+        let white = [255, 180, 107];
+        let corrected = balance(3000.0, 0.0, white);
+        let spread = corrected.iter().max().unwrap() - corrected.iter().min().unwrap();
+        assert!(spread < 20, "{corrected:?}");
+    }
+}
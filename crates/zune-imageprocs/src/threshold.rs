@@ -17,6 +17,7 @@ use crate::traits::NumOps;
 use crate::utils::execute_on;
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub enum ThresholdMethod {
     Binary,
     BinaryInv,
@@ -50,6 +51,7 @@ impl ThresholdMethod {
 ///  - [ThreshToZero](ThresholdMethod::ThreshToZero) => src(x,y) if src(x,y) > thresh 0 otherwise
 ///           
 ///  See [Wikipedia Article on Thresholding](https://en.wikipedia.org/wiki/Thresholding_(image_processing))
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Threshold {
     method:    ThresholdMethod,
     threshold: f32
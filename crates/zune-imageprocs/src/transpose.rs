@@ -33,6 +33,7 @@ static START: Once = Once::new();
 ///
 /// Done by swapping X and Y indices of the array representation
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transpose;
 
 impl Transpose {
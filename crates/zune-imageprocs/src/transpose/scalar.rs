@@ -63,8 +63,8 @@ pub fn transpose_scalar<T: Copy + Default>(
             }
         }
     }
-    let rem_w = width - (width & 7) - 1;
-    let rem_h = height - (height & 7) - 1;
+    let rem_w = (width - (width & 7)).saturating_sub(1);
+    let rem_h = (height - (height & 7)).saturating_sub(1);
 
     for i in rem_h..height {
         for j in 0..width {
@@ -5,24 +5,84 @@
  *
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
-//! (BROKEN): Do not use
+//! Sharpen an image with an unsharp mask
+//!
+//! The difference between the image and a blurred copy of it holds its fine details,
+//! adding a percentage of it back sharpens them
+//!
+//! ```text
+//! sharpened = original + (original - blurred) * percentage / 100
+//! ```
+//!
+//! Controls photographers expect from an unsharp mask are supported
+//! - threshold: differences smaller than it are left alone, so that noise and film
+//!   grain in flat areas are not sharpened
+//! - [luminosity mode](UnsharpenMode::Luminosity): only the luma is sharpened, which avoids
+//!   the color fringes sharpening each channel adds along colored edges
+//! - [edge masking](Unsharpen::set_edge_mask): sharpening is faded out away from edges,
+//!   keeping skin and skies smooth
+//!
+//! # Example
+//! - Sharpen the luma of a photo, only near edges
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::unsharpen::{Unsharpen, UnsharpenMode};
+//!
+//! let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+//! Unsharpen::new(1.5, 3, 80)
+//!     .set_mode(UnsharpenMode::Luminosity)
+//!     .set_edge_mask(Some(0.1))
+//!     .execute(&mut image)
+//!     .unwrap();
+//! ```
 use zune_core::bit_depth::BitType;
-use zune_core::log::trace;
+use zune_core::colorspace::ColorSpace;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
 use zune_image::traits::OperationsTrait;
 
-use crate::gaussian_blur::{gaussian_blur_u16, gaussian_blur_u8};
+use crate::gaussian_blur::{gaussian_blur_f32, gaussian_blur_u16, gaussian_blur_u8};
+use crate::utils::{frame_luma, normalized_samples, store_samples};
 
-/// Perform an unsharpen mask
-///
-/// This uses the result of a gaussian filter and thresholding to
-/// perform the mask calculation
-#[derive(Default)]
+/// The channels an unsharp mask sharpens
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnsharpenMode {
+    /// Sharpen every color channel on its own
+    #[default]
+    Channels,
+    /// Sharpen the luma, the Y of YCbCr, keeping the chroma of every pixel
+    ///
+    /// Only supported for RGB and grayscale images
+    Luminosity
+}
+
+impl UnsharpenMode {
+    pub fn from_string_result(input: &str) -> Result<Self, String> {
+        match input {
+            "channels" => Ok(UnsharpenMode::Channels),
+            "luminosity" => Ok(UnsharpenMode::Luminosity),
+            _ => Err(format!(
+                "Unknown unsharpen mode {input}, expected one of channels or luminosity"
+            ))
+        }
+    }
+}
+
+/// Perform an unsharp mask
+#[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Unsharpen {
     sigma:      f32,
     threshold:  u16,
-    percentage: u8
+    percentage: u8,
+    #[cfg_attr(feature = "serde-support", serde(default))]
+    mode:       UnsharpenMode,
+    #[cfg_attr(feature = "serde-support", serde(default))]
+    edge_mask:  Option<f32>
 }
 
 impl Unsharpen {
@@ -32,18 +92,88 @@ impl Unsharpen {
     /// - sigma: This value is passed to the gaussian filter,consult [it's documentation](crate::gaussian_blur::GaussianBlur)
     /// on how to use it
     ///
-    /// - threshold: If the result of the blur and the initial image is greater than this,  add the difference, otherwise
-    /// skip
-    ///  - percentage: `threshold*percentage`
+    /// - threshold: Differences between the image and its blurred copy at or below this
+    ///   are not sharpened, in levels of an 8 bit image (0-255) whatever the image depth
+    ///  - percentage: How much of the difference is added, 100 doubles it
     ///
+    /// Every color channel is sharpened everywhere by default
     #[must_use]
     pub fn new(sigma: f32, threshold: u16, percentage: u8) -> Unsharpen {
         Unsharpen {
             sigma,
             threshold,
-            percentage
+            percentage,
+            mode: UnsharpenMode::Channels,
+            edge_mask: None
         }
     }
+    /// Set the channels that are sharpened
+    #[must_use]
+    pub fn set_mode(mut self, mode: UnsharpenMode) -> Self {
+        self.mode = mode;
+        self
+    }
+    /// Fade sharpening out away from edges, or `None` to sharpen everywhere
+    ///
+    /// The value is the luma gradient, the difference between neighbouring pixels of the
+    /// blurred image between 0.0 and 1.0, at which sharpening reaches full strength.
+    /// Where the gradient is lower it is scaled down linearly. Typical values are
+    /// between 0.02 and 0.2
+    #[must_use]
+    pub fn set_edge_mask(mut self, edge_mask: Option<f32>) -> Self {
+        self.edge_mask = edge_mask;
+        self
+    }
+
+    /// Return how much of the sharpening every pixel of a luma plane gets
+    fn edge_weights(&self, mut luma: Vec<f32>, width: usize, height: usize) -> Option<Vec<f32>> {
+        let full_strength = self.edge_mask?.max(f32::EPSILON);
+
+        let mut scratch = vec![0.0; luma.len()];
+        gaussian_blur_f32(&mut luma, &mut scratch, width, height, self.sigma);
+
+        let at = |x: usize, y: usize| luma[y.min(height - 1) * width + x.min(width - 1)];
+
+        let weights = (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                // central differences, one sided at the edges
+                let dx = (at(x + 1, y) - at(x.saturating_sub(1), y)) * 0.5;
+                let dy = (at(x, y + 1) - at(x, y.saturating_sub(1))) * 0.5;
+
+                (dx.hypot(dy) / full_strength).min(1.0)
+            })
+            .collect();
+        Some(weights)
+    }
+
+    /// Sharpen `values` in place, returning the change of every value
+    fn sharpen(
+        &self, values: &mut [f32], weights: Option<&[f32]>, width: usize, height: usize
+    ) -> Vec<f32> {
+        let amount = f32::from(self.percentage) / 100.0;
+        let threshold = f32::from(self.threshold) / 255.0;
+
+        let mut blurred = values.to_vec();
+        let mut scratch = vec![0.0; values.len()];
+        gaussian_blur_f32(&mut blurred, &mut scratch, width, height, self.sigma);
+
+        values
+            .iter_mut()
+            .zip(&blurred)
+            .enumerate()
+            .map(|(i, (value, blurred))| {
+                let difference = *value - blurred;
+                if difference.abs() <= threshold {
+                    return 0.0;
+                }
+                let weight = weights.map_or(1.0, |w| w[i]);
+                let change = difference * amount * weight;
+                *value += change;
+                change
+            })
+            .collect()
+    }
 }
 
 impl OperationsTrait for Unsharpen {
@@ -53,108 +183,58 @@ impl OperationsTrait for Unsharpen {
 
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
         let (width, height) = image.dimensions();
+        let colorspace = image.colorspace();
+        let bit_type = image.depth().bit_type();
 
-        let depth = image.depth();
-
-        #[cfg(not(feature = "threads"))]
+        if self.mode == UnsharpenMode::Luminosity
+            && !matches!(
+                colorspace,
+                ColorSpace::RGB | ColorSpace::RGBA | ColorSpace::Luma | ColorSpace::LumaA
+            )
         {
-            trace!("Running unsharpen in single threaded mode");
-
-            match depth.bit_type() {
-                BitType::U16 => {
-                    let mut blur_buffer = vec![0; width * height];
-                    let mut blur_scratch = vec![0; width * height];
-
-                    for channel in image.channels_mut(true) {
-                        unsharpen_u16(
-                            channel.reinterpret_as_mut::<u16>()?,
-                            &mut blur_buffer,
-                            &mut blur_scratch,
-                            self.sigma,
-                            self.threshold,
-                            self.percentage as u16,
-                            width,
-                            height
-                        );
+            return Err(ImageErrors::GenericString(format!(
+                "Luminosity unsharpen is not supported for {colorspace:?} images"
+            )));
+        }
+        let lumas = if self.edge_mask.is_some() || self.mode == UnsharpenMode::Luminosity {
+            (0..image.frames_len())
+                .map(|i| frame_luma(image, i).map(Some))
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            vec![None; image.frames_len()]
+        };
+
+        for (frame, luma) in image.frames_mut().iter_mut().zip(lumas) {
+            let weights = luma
+                .clone()
+                .and_then(|luma| self.edge_weights(luma, width, height));
+            let channels = frame.channels_mut(colorspace, true);
+
+            match (self.mode, luma) {
+                (UnsharpenMode::Luminosity, Some(mut luma)) => {
+                    // adding the same change to R, G and B changes Y by it and keeps Cb and Cr
+                    let changes = self.sharpen(&mut luma, weights.as_deref(), width, height);
+
+                    for channel in channels {
+                        let mut values = normalized_samples(channel, bit_type, self.name())?;
+                        values.iter_mut().zip(&changes).for_each(|(v, c)| *v += c);
+                        store_samples(channel, bit_type, self.name(), &values)?;
                     }
                 }
-
-                BitType::U8 => {
-                    let mut blur_buffer = vec![0; width * height];
-                    let mut blur_scratch = vec![0; width * height];
-
-                    for channel in image.channels_mut(true) {
-                        unsharpen_u8(
-                            channel.reinterpret_as_mut::<u8>()?,
-                            &mut blur_buffer,
-                            &mut blur_scratch,
-                            self.sigma,
-                            self.threshold as u8,
-                            self.percentage,
-                            width,
-                            height
-                        );
+                _ => {
+                    for channel in channels {
+                        let mut values = normalized_samples(channel, bit_type, self.name())?;
+                        self.sharpen(&mut values, weights.as_deref(), width, height);
+                        store_samples(channel, bit_type, self.name(), &values)?;
                     }
                 }
-                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
             }
         }
-        #[cfg(feature = "threads")]
-        {
-            trace!("Running unsharpen in multithreaded mode");
-            std::thread::scope(|s| {
-                let mut errors = vec![];
-                // blur each channel on a separate thread
-                for channel in image.channels_mut(true) {
-                    let result = s.spawn(|| match depth.bit_type() {
-                        BitType::U16 => {
-                            let mut blur_buffer = vec![0; width * height];
-                            let mut blur_scratch = vec![0; width * height];
-
-                            unsharpen_u16(
-                                channel.reinterpret_as_mut::<u16>()?,
-                                &mut blur_buffer,
-                                &mut blur_scratch,
-                                self.sigma,
-                                self.threshold,
-                                u16::from(self.percentage),
-                                width,
-                                height
-                            );
-                            Ok(())
-                        }
-
-                        BitType::U8 => {
-                            let mut blur_buffer = vec![0; width * height];
-                            let mut blur_scratch = vec![0; width * height];
-
-                            unsharpen_u8(
-                                channel.reinterpret_as_mut::<u8>()?,
-                                &mut blur_buffer,
-                                &mut blur_scratch,
-                                self.sigma,
-                                u8::try_from(self.threshold.clamp(0, 255)).unwrap_or(u8::MAX),
-                                self.percentage,
-                                width,
-                                height
-                            );
-                            Ok(())
-                        }
-                        d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
-                    });
-                    errors.push(result);
-                }
-                errors
-                    .into_iter()
-                    .map(|x| x.join().unwrap())
-                    .collect::<Result<Vec<()>, ImageErrors>>()
-            })?;
-        }
-
         Ok(())
     }
+
     fn supported_types(&self) -> &'static [BitType] {
-        &[BitType::U8, BitType::U16]
+        &[BitType::U8, BitType::U16, BitType::F32]
     }
 }
 
@@ -167,7 +247,7 @@ impl OperationsTrait for Unsharpen {
 ///
 /// The formula is
 ///
-/// sharpened = original + (original − blurred);
+/// sharpened = original + (original − blurred) * percentage / 100;
 ///
 ///
 /// # Arguments
@@ -175,13 +255,18 @@ impl OperationsTrait for Unsharpen {
 /// - blur_buffer: Temporary location we use to store blur coefficients
 /// - blur_scratch_buffer: Temporary location we use during blurring to store blur coefficients
 /// - sigma: Radius of blur
-/// - threshold: If the difference between original and blurred is greater than this, add the diff to
-/// the pixel
+/// - threshold: If the absolute difference between original and blurred is greater than this,
+///   add the scaled difference to the pixel
+/// - percentage: How much of the difference is added
 ///- width,height: Image dimensions.
-#[allow(clippy::too_many_arguments)]
+#[allow(
+    clippy::too_many_arguments,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
 pub fn unsharpen_u16(
     channel: &mut [u16], blur_buffer: &mut [u16], blur_scratch_buffer: &mut [u16], sigma: f32,
-    threshold: u16, _percentage: u16, width: usize, height: usize
+    threshold: u16, percentage: u16, width: usize, height: usize
 ) {
     // copy channel to scratch space
     blur_buffer.copy_from_slice(channel);
@@ -190,19 +275,12 @@ pub fn unsharpen_u16(
     // blur buffer now contains gaussian blurred pixels
     // so iterate replacing them
     for (in_pix, blur_pix) in channel.iter_mut().zip(blur_buffer.iter()) {
-        let diff = in_pix.saturating_sub(*blur_pix);
-        // pull some branchless tricks to help the optimizer
-        // here
-
-        // We conditionally take the added version or whatever we had based on this mask
-        //  godbolt link: https://godbolt.org/z/YYnEaPedM
+        let diff = i64::from(*in_pix) - i64::from(*blur_pix);
 
-        let threshold_mask = u16::from(diff > threshold).wrapping_sub(1);
-
-        // let diff = (diff * percentage) / 100;
-
-        // if diff > threshold { pix = (diff + pix) } else { pix }
-        *in_pix = (in_pix.wrapping_add(diff) & !threshold_mask) | (*in_pix & threshold_mask);
+        if diff.unsigned_abs() > u64::from(threshold) {
+            let sharpened = i64::from(*in_pix) + diff * i64::from(percentage) / 100;
+            *in_pix = sharpened.clamp(0, i64::from(u16::MAX)) as u16;
+        }
     }
 }
 
@@ -215,7 +293,7 @@ pub fn unsharpen_u16(
 ///
 /// The formula is
 ///
-/// sharpened = original + (original − blurred);
+/// sharpened = original + (original − blurred) * percentage / 100;
 ///
 ///
 /// # Arguments
@@ -223,13 +301,18 @@ pub fn unsharpen_u16(
 /// - blur_buffer: Temporary location we use to store blur coefficients
 /// - blur_scratch_buffer: Temporary location we use during blurring to store blur coefficients
 /// - sigma: Radius of blur
-/// - threshold: If the difference between original and blurred is greater than this, add the diff to
-/// the pixel
+/// - threshold: If the absolute difference between original and blurred is greater than this,
+///   add the scaled difference to the pixel
+/// - percentage: How much of the difference is added
 ///- width,height: Image dimensions.
-#[allow(clippy::too_many_arguments)]
+#[allow(
+    clippy::too_many_arguments,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
 pub fn unsharpen_u8(
     channel: &mut [u8], blur_buffer: &mut [u8], blur_scratch_buffer: &mut [u8], sigma: f32,
-    threshold: u8, _percentage: u8, width: usize, height: usize
+    threshold: u8, percentage: u8, width: usize, height: usize
 ) {
     // copy channel to scratch space
     blur_buffer.copy_from_slice(channel);
@@ -238,15 +321,83 @@ pub fn unsharpen_u8(
     // blur buffer now contains gaussian blurred pixels
     // so iterate replacing them
     for (in_pix, blur_pix) in channel.iter_mut().zip(blur_buffer.iter()) {
-        let diff = in_pix.wrapping_sub(*blur_pix);
-        // pull some branchless tricks to help the optimizer
-        // here
+        let diff = i32::from(*in_pix) - i32::from(*blur_pix);
+
+        if diff.unsigned_abs() > u32::from(threshold) {
+            let sharpened = i32::from(*in_pix) + diff * i32::from(percentage) / 100;
+            *in_pix = sharpened.clamp(0, 255) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::unsharpen::{Unsharpen, UnsharpenMode};
+
+    /// A vertical edge between two grays, with a faint texture on both sides
+    fn edge_image(colorspace: ColorSpace) -> Image {
+        Image::from_fn::<u8, _>(40, 20, colorspace, |y, x, pix| {
+            let base: u8 = if x < 20 { 80 } else { 160 };
+            let texture = u8::from((x + y) % 2 == 0);
+            pix.fill(base + texture);
+            if colorspace == ColorSpace::RGB {
+                pix[2] = base / 2;
+            }
+        })
+    }
+
+    #[test]
+    fn test_sharpens_edges_both_ways() {
+        let mut image = edge_image(ColorSpace::Luma);
+        Unsharpen::new(1.0, 0, 100).execute(&mut image).unwrap();
+        let pixels = &image.flatten_to_u8()[0];
+
+        // the dark side gets darker and the bright side brighter next to the edge
+        assert!(pixels[5 * 40 + 19] < 80, "{}", pixels[5 * 40 + 19]);
+        assert!(pixels[5 * 40 + 20] > 161, "{}", pixels[5 * 40 + 20]);
+        // far from the edge only the texture changes
+        assert!((78..=83).contains(&pixels[5 * 40 + 5]));
+    }
+
+    #[test]
+    fn test_threshold_and_edge_mask_keep_texture() {
+        let original = edge_image(ColorSpace::Luma);
+
+        for unsharpen in [
+            Unsharpen::new(1.0, 2, 100),
+            Unsharpen::new(1.0, 0, 100).set_edge_mask(Some(0.1))
+        ] {
+            let mut image = original.clone();
+            unsharpen.execute(&mut image).unwrap();
+            let pixels = &image.flatten_to_u8()[0];
+
+            // the texture away from the edge is untouched, the edge is still sharpened
+            assert_eq!(pixels[..10], original.flatten_to_u8()[0][..10]);
+            assert!(pixels[5 * 40 + 19] < 80, "{}", pixels[5 * 40 + 19]);
+        }
+    }
+
+    #[test]
+    fn test_luminosity_keeps_chroma() {
+        let mut image = edge_image(ColorSpace::RGB);
+        Unsharpen::new(1.0, 0, 100)
+            .set_mode(UnsharpenMode::Luminosity)
+            .execute(&mut image)
+            .unwrap();
+        let pixels = &image.flatten_to_u8()[0];
 
-        // We conditionally take the added version or whatever we had based on this mask
-        //  godbolt link: https://godbolt.org/z/YYnEaPedM
-        let threshold_mask = u8::from(diff > threshold).wrapping_sub(1);
+        // the same change was added to every channel next to the edge
+        let pixel = &pixels[(5 * 40 + 20) * 3..][..3];
+        assert!(pixel[0] > 161);
+        assert_eq!(i32::from(pixel[0]) - i32::from(pixel[2]), 160 - 80);
+        assert_eq!(pixel[0], pixel[1]);
 
-        // if diff > threshold { pix = (diff + pix) } else { pix }
-        *in_pix = (in_pix.saturating_add(diff) & !threshold_mask) | (*in_pix & threshold_mask);
+        let mut image = Image::fill::<u8>(0, ColorSpace::YCbCr, 4, 4);
+        let luminosity = Unsharpen::new(1.0, 0, 100).set_mode(UnsharpenMode::Luminosity);
+        assert!(luminosity.execute(&mut image).is_err());
     }
 }
@@ -5,10 +5,15 @@
  *
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_core::colorspace::ColorSpace;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
+use zune_image::frame::Frame;
 use zune_image::image::Image;
 
+use crate::traits::NumOps;
+
 /// Prefetch data at offset position
 ///
 /// This uses prefetch intrinsics for a specific
@@ -43,6 +48,7 @@ pub fn z_prefetch<T>(data: &[T], position: usize) {
 
 /// The position of the source image on the destination
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub enum Gravity {
     /// Place the image so that it seems like it's from the
     /// center of the canvas
@@ -54,35 +60,105 @@ pub enum Gravity {
     /// Place the image so that it appears from the bottom left of the canvas
     BottomLeft,
     /// Place the image so that it appears from the bottom right of the canvas
-    BottomRight
+    BottomRight,
+    /// Place the image at the middle of the top edge of the canvas
+    Top,
+    /// Place the image at the middle of the bottom edge of the canvas
+    Bottom,
+    /// Place the image at the middle of the left edge of the canvas
+    Left,
+    /// Place the image at the middle of the right edge of the canvas
+    Right
 }
 
 pub fn calculate_gravity(src_image: &Image, dst_image: &Image, gravity: Gravity) -> (usize, usize) {
-    let (src_width, src_height) = src_image.dimensions();
-    let (dst_width, dst_height) = dst_image.dimensions();
-
-    return match gravity {
-        Gravity::Center => {
-            let dst_center_x = dst_width / 2;
-            let dst_center_y = dst_height / 2;
-
-            let src_center_x = src_width / 2;
-            let src_center_y = src_height / 2;
+    let (x, y) = gravity_offset(src_image.dimensions(), dst_image.dimensions(), gravity);
 
-            let orig_y = dst_center_y.saturating_sub(src_center_y);
-            let orig_x = dst_center_x.saturating_sub(src_center_x);
+    (x.max(0).unsigned_abs(), y.max(0).unsigned_abs())
+}
 
-            (orig_x, orig_y)
-        }
+/// Return the position of the top left corner of a `src` sized image placed on a
+/// `dst` sized canvas, negative when the image is larger than the canvas
+#[allow(clippy::cast_possible_wrap)]
+pub(crate) fn gravity_offset(
+    (src_width, src_height): (usize, usize), (dst_width, dst_height): (usize, usize),
+    gravity: Gravity
+) -> (isize, isize) {
+    // 0 for the start, 1 for the center and 2 for the end of each axis
+    let (horizontal, vertical) = match gravity {
+        Gravity::Center => (1, 1),
         Gravity::TopLeft => (0, 0),
-        Gravity::TopRight => (dst_width.saturating_sub(src_width), 0),
-        Gravity::BottomLeft => (0, dst_height.saturating_sub(src_height)),
-        Gravity::BottomRight => (
-            dst_width.saturating_sub(src_width),
-            dst_height.saturating_sub(src_height)
-        )
+        Gravity::TopRight => (2, 0),
+        Gravity::BottomLeft => (0, 2),
+        Gravity::BottomRight => (2, 2),
+        Gravity::Top => (1, 0),
+        Gravity::Bottom => (1, 2),
+        Gravity::Left => (0, 1),
+        Gravity::Right => (2, 1)
     };
+    let place = |src: usize, dst: usize, position: u8| match position {
+        0 => 0,
+        1 => (dst / 2) as isize - (src / 2) as isize,
+        _ => dst as isize - src as isize
+    };
+    (
+        place(src_width, dst_width, horizontal),
+        place(src_height, dst_height, vertical)
+    )
 }
+
+/// Return the channel values of an 8 bit RGBA color for `colorspace`, between 0.0 and 1.0
+///
+/// Grayscale colorspaces get the luma of the color, colorspaces without alpha drop it
+pub(crate) fn fill_color(color: [u8; 4], colorspace: ColorSpace) -> Vec<f32> {
+    let [r, g, b, a] = color.map(|x| f32::from(x) / 255.0);
+    let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+
+    match colorspace {
+        ColorSpace::Luma => vec![luma],
+        ColorSpace::LumaA => vec![luma, a],
+        ColorSpace::RGB => vec![r, g, b],
+        _ => vec![r, g, b, a]
+    }
+}
+
+/// Return how much of the pixel centered at `(x, y)` is covered by a rectangle spanning
+/// `left..right` and `top..bottom` with corners rounded by `radius`, between 0.0 and 1.0
+///
+/// Coverage is estimated from the signed distance to the shape, which anti-aliases
+/// its edges over one pixel
+pub(crate) fn rounded_rect_coverage(
+    (x, y): (f32, f32), (left, top, right, bottom): (f32, f32, f32, f32), radius: f32
+) -> f32 {
+    let (half_width, half_height) = ((right - left) * 0.5, (bottom - top) * 0.5);
+    let radius = radius.clamp(0.0, half_width.min(half_height).max(0.0));
+
+    let qx = (x - (left + right) * 0.5).abs() - half_width + radius;
+    let qy = (y - (top + bottom) * 0.5).abs() - half_height + radius;
+    let distance = qx.max(0.0).hypot(qy.max(0.0)) + qx.max(qy).min(0.0) - radius;
+
+    (0.5 - distance).clamp(0.0, 1.0)
+}
+
+/// Draw `src` over `dst` with `coverage`, both being pixels with alpha last if `has_alpha`
+pub(crate) fn composite_over(dst: &mut [f32], src: &[f32], coverage: f32, has_alpha: bool) {
+    let colors = if has_alpha { dst.len() - 1 } else { dst.len() };
+    let (src_alpha, dst_alpha) =
+        if has_alpha { (src[colors] * coverage, dst[colors]) } else { (coverage, 1.0) };
+    let out_alpha = src_alpha + dst_alpha * (1.0 - src_alpha);
+
+    for (d, s) in dst[..colors].iter_mut().zip(src) {
+        *d = if out_alpha > 0.0 {
+            (s * src_alpha + *d * dst_alpha * (1.0 - src_alpha)) / out_alpha
+        } else {
+            0.0
+        };
+    }
+    if has_alpha {
+        dst[colors] = out_alpha;
+    }
+}
+
 /// A simple helper function to execute on threads
 pub fn execute_on<T: Fn(&mut Channel) -> Result<(), ImageErrors> + Send + Sync>(
     function: T, image: &mut Image, ignore_alpha: bool
@@ -112,3 +188,179 @@ pub fn execute_on<T: Fn(&mut Channel) -> Result<(), ImageErrors> + Send + Sync>(
         Ok(())
     }
 }
+
+/// Convert interleaved pixels between 0.0 and 1.0 into channels of the given bit type
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub(crate) fn to_channels(
+    pixels: &[f32], bit_type: BitType, colorspace: ColorSpace
+) -> Vec<Channel> {
+    let mut frame = match bit_type {
+        BitType::U8 => {
+            let pixels: Vec<u8> = pixels.iter().map(|x| (x * 255.0).round() as u8).collect();
+            Frame::from_u8(&pixels, colorspace, 0, 0)
+        }
+        BitType::U16 => {
+            let pixels: Vec<u16> = pixels
+                .iter()
+                .map(|x| (x * 65535.0).round() as u16)
+                .collect();
+            Frame::from_u16(&pixels, colorspace, 0, 0)
+        }
+        _ => Frame::from_f32(pixels, colorspace, 0, 0)
+    };
+    std::mem::take(frame.channels_vec())
+}
+
+/// Return the samples of a channel scaled to 0.0..=1.0
+pub(crate) fn normalized_samples(
+    channel: &Channel, bit_type: BitType, name: &'static str
+) -> Result<Vec<f32>, ImageErrors> {
+    fn normalize<T: Copy + NumOps<T>>(samples: &[T]) -> Vec<f32> {
+        let scale = 1.0 / T::max_val().to_f32();
+        samples.iter().map(|x| x.to_f32() * scale).collect()
+    }
+    match bit_type {
+        BitType::U8 => Ok(normalize::<u8>(channel.reinterpret_as()?)),
+        BitType::U16 => Ok(normalize::<u16>(channel.reinterpret_as()?)),
+        BitType::F32 => Ok(channel.reinterpret_as::<f32>()?.to_vec()),
+        d => Err(ImageErrors::ImageOperationNotImplemented(name, d))
+    }
+}
+
+/// Convert a value between zero and the largest value of `T` to a sample
+///
+/// Integers are rounded and clamped to their range, floats are kept as is
+pub(crate) fn to_sample<T: NumOps<T>>(value: f32) -> T {
+    let max = T::max_val().to_f32();
+
+    if max > 1.0 {
+        T::from_f32(value.clamp(0.0, max).round())
+    } else {
+        T::from_f32(value)
+    }
+}
+
+/// Write values between 0.0 and 1.0 to a channel of the given bit type, the inverse of
+/// [`normalized_samples`]
+///
+/// Integers are rounded and clamped, floats are kept as is
+pub(crate) fn store_samples(
+    channel: &mut Channel, bit_type: BitType, name: &'static str, values: &[f32]
+) -> Result<(), ImageErrors> {
+    fn store<T: Copy + NumOps<T>>(samples: &mut [T], values: &[f32]) {
+        let max = T::max_val().to_f32();
+
+        for (out, value) in samples.iter_mut().zip(values) {
+            *out = to_sample(value * max);
+        }
+    }
+    match bit_type {
+        BitType::U8 => store::<u8>(channel.reinterpret_as_mut()?, values),
+        BitType::U16 => store::<u16>(channel.reinterpret_as_mut()?, values),
+        BitType::F32 => store::<f32>(channel.reinterpret_as_mut()?, values),
+        d => return Err(ImageErrors::ImageOperationNotImplemented(name, d))
+    }
+    Ok(())
+}
+
+/// Map every sample of a channel through `function`, which takes and returns
+/// values between 0.0 and 1.0
+///
+/// `u8` and `u16` samples are mapped through a lookup table built from `function`,
+/// which keeps the inner loop free of float math, `f32` samples are mapped directly
+pub(crate) fn map_samples(
+    channel: &mut Channel, bit_type: BitType, name: &'static str, function: impl Fn(f32) -> f32
+) -> Result<(), ImageErrors> {
+    #[allow(clippy::cast_precision_loss)]
+    fn map_lut<T: Copy + NumOps<T>>(samples: &mut [T], function: impl Fn(f32) -> f32) {
+        let max = T::max_val().to_usize();
+        let scale = max as f32;
+
+        let lut: Vec<T> = (0..=max)
+            .map(|x| to_sample(function(x as f32 / scale) * scale))
+            .collect();
+
+        for sample in samples {
+            *sample = lut[sample.to_usize().min(max)];
+        }
+    }
+    match bit_type {
+        BitType::U8 => map_lut::<u8>(channel.reinterpret_as_mut()?, function),
+        BitType::U16 => map_lut::<u16>(channel.reinterpret_as_mut()?, function),
+        BitType::F32 => channel
+            .reinterpret_as_mut::<f32>()?
+            .iter_mut()
+            .for_each(|x| *x = function(*x)),
+        d => return Err(ImageErrors::ImageOperationNotImplemented(name, d))
+    }
+    Ok(())
+}
+
+/// Return the luminance of a frame as floats between 0.0 and 1.0
+///
+/// Alpha is ignored
+pub(crate) fn frame_luma(image: &Image, frame: usize) -> Result<Vec<f32>, ImageErrors> {
+    let Some(frame) = image.frames_ref().get(frame) else {
+        return Err(ImageErrors::GenericStr("Frame index out of range"));
+    };
+    let (width, height) = image.dimensions();
+
+    let mut luma = Image::new_frames(
+        vec![frame.clone()],
+        image.depth(),
+        width,
+        height,
+        image.colorspace()
+    );
+    luma.convert_color(ColorSpace::Luma)?;
+    luma.convert_depth(BitDepth::Float32)?;
+
+    Ok(luma.frames_ref()[0].channels_ref(ColorSpace::Luma, true)[0]
+        .reinterpret_as::<f32>()?
+        .to_vec())
+}
+
+/// Replace the color channels of every frame of an image, alpha is kept
+///
+/// `function` receives the color channels, with values between 0.0 and 1.0, and the luma
+/// of a frame and returns its new color channels
+pub(crate) fn map_color_planes(
+    image: &mut Image, name: &'static str, function: impl Fn(&[Vec<f32>], &[f32]) -> Vec<Vec<f32>>
+) -> Result<(), ImageErrors> {
+    let colorspace = image.colorspace();
+    let bit_type = image.depth().bit_type();
+    let lumas = (0..image.frames_len())
+        .map(|i| frame_luma(image, i))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (frame, luma) in image.frames_mut().iter_mut().zip(lumas) {
+        let channels = frame.channels_mut(colorspace, true);
+        let planes = channels
+            .iter()
+            .map(|c| normalized_samples(c, bit_type, name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (channel, values) in channels.iter_mut().zip(function(&planes, &luma)) {
+            store_samples(channel, bit_type, name, &values)?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove the sRGB transfer function from a value, 1.0 being the maximum value
+pub(crate) fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Apply the sRGB transfer function to a linear value, 1.0 being the maximum value
+pub(crate) fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -0,0 +1,222 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Adjust the saturation of muted colors
+//!
+//! Vibrance is a saturation adjustment that changes muted colors more than colors
+//! that are already saturated, and leaves skin tones mostly alone, so it can
+//! make an image more colorful without oversaturating it or turning faces orange.
+//!
+//! Unlike the saturation of [`HsvAdjust`](crate::hsv_adjust::HsvAdjust), which scales
+//! every color by the same factor, the factor of a pixel here depends on its color.
+//!
+//! # Algorithm details
+//! ```text
+//! saturation = (max(r,g,b) - min(r,g,b)) / max(r,g,b)
+//! skin = how close the hue is to skin tones, 1.0 at orange and 0.0 away from it
+//! factor = 1 + vibrance * (1 - saturation)^2 * (1 - skin * protection)
+//!
+//! luma = 0.299 * r + 0.587 * g + 0.114 * b
+//! channel = luma + (channel - luma) * factor
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::to_sample;
+
+/// Hue of the skin tones that are protected, in degrees
+const SKIN_HUE: f32 = 25.0;
+/// Distance from [`SKIN_HUE`] at which hues are no longer protected, in degrees
+const SKIN_HUE_RANGE: f32 = 25.0;
+
+/// Adjust the vibrance of an image
+///
+/// Alpha is ignored.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::vibrance::Vibrance;
+///
+/// let mut image = Image::fill::<u8>(128, ColorSpace::RGB, 100, 100);
+/// Vibrance::new(0.5).execute(&mut image).unwrap();
+/// ```
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vibrance {
+    vibrance:        f32,
+    skin_protection: f32
+}
+
+impl Vibrance {
+    /// Create a new vibrance operation
+    ///
+    /// # Arguments
+    /// - vibrance: The adjustment between -1.0 and 1.0, positive values make muted colors
+    ///   more saturated, negative values desaturate them, 0.0 has no effect
+    ///
+    /// Skin tones are fully protected by default
+    #[must_use]
+    pub fn new(vibrance: f32) -> Vibrance {
+        Vibrance {
+            vibrance:        vibrance.clamp(-1.0, 1.0),
+            skin_protection: 1.0
+        }
+    }
+    /// Set how much skin tones are protected from the adjustment, between 0.0 where they are
+    /// adjusted like any other color and 1.0 where they are mostly left as is
+    #[must_use]
+    pub fn set_skin_protection(mut self, protection: f32) -> Self {
+        self.skin_protection = protection.clamp(0.0, 1.0);
+        self
+    }
+}
+
+impl OperationsTrait for Vibrance {
+    fn name(&self) -> &'static str {
+        "Vibrance"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let depth = image.depth().bit_type();
+
+        for frame in image.frames_mut() {
+            let [r, g, b, ..] = frame.channels_vec().as_mut_slice() else {
+                return Err(ImageErrors::GenericStr("Expected at least three channels"));
+            };
+            match depth {
+                BitType::U8 => vibrance::<u8>(
+                    r.reinterpret_as_mut()?,
+                    g.reinterpret_as_mut()?,
+                    b.reinterpret_as_mut()?,
+                    self.vibrance,
+                    self.skin_protection
+                ),
+                BitType::U16 => vibrance::<u16>(
+                    r.reinterpret_as_mut()?,
+                    g.reinterpret_as_mut()?,
+                    b.reinterpret_as_mut()?,
+                    self.vibrance,
+                    self.skin_protection
+                ),
+                BitType::F32 => vibrance::<f32>(
+                    r.reinterpret_as_mut()?,
+                    g.reinterpret_as_mut()?,
+                    b.reinterpret_as_mut()?,
+                    self.vibrance,
+                    self.skin_protection
+                ),
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::RGB, ColorSpace::RGBA]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Return how close a color is to skin tones, between 0.0 and 1.0
+fn skin_weight(r: f32, g: f32, b: f32) -> f32 {
+    // skin tones have red as the largest and blue as the smallest component
+    if !(r > g && g >= b) {
+        return 0.0;
+    }
+    let hue = 60.0 * (g - b) / (r - b);
+    (1.0 - (hue - SKIN_HUE).abs() / SKIN_HUE_RANGE).max(0.0)
+}
+
+fn vibrance<T>(r: &mut [T], g: &mut [T], b: &mut [T], vibrance: f32, protection: f32)
+where
+    T: Copy + NumOps<T>
+{
+    let max_value = T::max_val().to_f32();
+
+    for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+        let color = [r.to_f32(), g.to_f32(), b.to_f32()];
+        let max = color[0].max(color[1]).max(color[2]);
+        let min = color[0].min(color[1]).min(color[2]);
+
+        if max <= 0.0 || max - min <= 0.0 {
+            // black and grays have no saturation to change
+            continue;
+        }
+        let saturation = (max - min) / max;
+        let skin = skin_weight(color[0], color[1], color[2]);
+        let factor =
+            1.0 + vibrance * (1.0 - saturation) * (1.0 - saturation) * (1.0 - skin * protection);
+
+        let luma = 0.299 * color[0] + 0.587 * color[1] + 0.114 * color[2];
+
+        for (out, c) in [r, g, b].into_iter().zip(color) {
+            let value = (luma + (c - luma) * factor).clamp(0.0, max_value);
+            *out = to_sample(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::vibrance::Vibrance;
+
+    fn saturation(pixel: &[u8]) -> f32 {
+        let max = f32::from(*pixel.iter().max().unwrap());
+        let min = f32::from(*pixel.iter().min().unwrap());
+        (max - min) / max
+    }
+
+    #[test]
+    fn test_vibrance() {
+        let colors: [[u8; 3]; 4] = [
+            // muted blue, saturated blue, a skin tone and gray
+            [100, 110, 140],
+            [20, 30, 240],
+            [224, 172, 140],
+            [128, 128, 128]
+        ];
+        let mut image = Image::from_fn::<u8, _>(4, 1, ColorSpace::RGB, |_, x, pix| {
+            pix[..3].copy_from_slice(&colors[x]);
+        });
+        Vibrance::new(1.0).execute(&mut image).unwrap();
+        let pixels = &image.flatten_to_u8()[0];
+
+        let gain = |i: usize| saturation(&pixels[i * 3..i * 3 + 3]) - saturation(&colors[i]);
+        // muted colors gain the most
+        assert!(gain(0) > 0.1, "{}", gain(0));
+        assert!(gain(1) < 0.02, "{}", gain(1));
+        assert!(gain(2).abs() < 0.02, "{}", gain(2));
+        assert_eq!(pixels[9..12], [128, 128, 128]);
+    }
+
+    #[test]
+    fn test_vibrance_skin_protection() {
+        let skin = [224, 172, 140];
+        let mut image = Image::from_fn::<u8, _>(1, 1, ColorSpace::RGB, |_, _, pix| {
+            pix[..3].copy_from_slice(&skin);
+        });
+        Vibrance::new(1.0)
+            .set_skin_protection(0.0)
+            .execute(&mut image)
+            .unwrap();
+        assert!(saturation(&image.flatten_to_u8()[0]) > saturation(&skin) + 0.05);
+    }
+}
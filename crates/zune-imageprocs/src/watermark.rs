@@ -0,0 +1,320 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Watermark an image with a logo
+//!
+//! The logo is drawn over the image with an opacity, either once at a [`Gravity`]
+//! anchor kept `margin` pixels away from the edges, or tiled over the whole image with
+//! `margin` pixels between the tiles.
+//!
+//! The logo can have any colorspace and depth, it is converted to match the image.
+//! Its alpha channel, if any, is respected. It can also be scaled relative to the
+//! image width, so that the same logo covers the same share of images of any size.
+//!
+//! # Example
+//! - Put a logo at 20% of the image width in the bottom right corner
+//!
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_imageprocs::watermark::Watermark;
+//! use zune_imageprocs::Gravity;
+//!
+//! let logo = Image::fill::<u16>(65535, ColorSpace::LumaA, 50, 20);
+//! let mut image = Image::fill::<u8>(30, ColorSpace::RGB, 400, 300);
+//!
+//! Watermark::new(&logo)
+//!     .set_gravity(Gravity::BottomRight)
+//!     .set_scale(Some(0.2))
+//!     .set_margin(10)
+//!     .set_opacity(0.4)
+//!     .execute(&mut image)
+//!     .unwrap();
+//! ```
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::resize::{Resize, ResizeMethod};
+use crate::utils::{composite_over, gravity_offset, normalized_samples, to_channels, Gravity};
+
+/// Draw a logo over an image
+///
+/// The first frame of the logo is used for all frames of the image.
+pub struct Watermark<'src> {
+    logo:    &'src Image,
+    gravity: Gravity,
+    tiled:   bool,
+    opacity: f32,
+    margin:  usize,
+    scale:   Option<f32>
+}
+
+impl<'src> Watermark<'src> {
+    /// Create a new watermark operation drawing `logo`
+    ///
+    /// By default the logo keeps its size and is drawn once in the bottom right corner
+    /// at half opacity
+    #[must_use]
+    pub fn new(logo: &'src Image) -> Watermark<'src> {
+        Watermark {
+            logo,
+            gravity: Gravity::BottomRight,
+            tiled: false,
+            opacity: 0.5,
+            margin: 0,
+            scale: None
+        }
+    }
+    /// Set where the logo is placed, ignored when tiled
+    #[must_use]
+    pub fn set_gravity(mut self, gravity: Gravity) -> Self {
+        self.gravity = gravity;
+        self
+    }
+    /// Set whether the logo is repeated over the whole image
+    #[must_use]
+    pub fn set_tiled(mut self, tiled: bool) -> Self {
+        self.tiled = tiled;
+        self
+    }
+    /// Set the opacity of the logo, between 0.0 (invisible) and 1.0
+    #[must_use]
+    pub fn set_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+    /// Set the distance in pixels of the logo from the image edges, or between tiles
+    #[must_use]
+    pub fn set_margin(mut self, margin: usize) -> Self {
+        self.margin = margin;
+        self
+    }
+    /// Set the width of the logo as a fraction of the image width, keeping its aspect
+    /// ratio, or `None` to keep its size
+    #[must_use]
+    pub fn set_scale(mut self, scale: Option<f32>) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Return the logo with the channels of a `components` colorspace plus alpha,
+    /// interleaved and between 0.0 and 1.0, and its dimensions
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn logo_pixels(
+        &self, image_width: usize, components: usize
+    ) -> Result<(Vec<f32>, usize, usize), ImageErrors> {
+        let mut logo = self.logo.clone();
+        logo.convert_color(ColorSpace::RGBA)?;
+
+        if let Some(scale) = self.scale {
+            let (width, height) = logo.dimensions();
+            let new_width = (image_width as f32 * scale.max(0.0)).round() as usize;
+            let new_height = (height as f32 * new_width as f32 / width as f32).round() as usize;
+
+            if new_width == 0 || new_height == 0 {
+                return Ok((vec![], 0, 0));
+            }
+            Resize::new(new_width, new_height, ResizeMethod::Bilinear).execute(&mut logo)?;
+        }
+        let (width, height) = logo.dimensions();
+        let bit_type = logo.depth().bit_type();
+
+        let Some(frame) = logo.frames_ref().first() else {
+            return Err(ImageErrors::GenericStr("Watermark logo has no frames"));
+        };
+        let planes = frame
+            .channels_ref(ColorSpace::RGBA, false)
+            .iter()
+            .map(|c| normalized_samples(c, bit_type, self.name()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let [r, g, b, a] = &planes[..] else {
+            return Err(ImageErrors::GenericStr("Expected four channels"));
+        };
+        let pixels = (0..width * height)
+            .flat_map(|i| {
+                let luma = 0.299 * r[i] + 0.587 * g[i] + 0.114 * b[i];
+                // grayscale images get the luma, alpha is always last
+                let colors = if components < 3 { [luma; 3] } else { [r[i], g[i], b[i]] };
+                colors[..components.min(3)]
+                    .iter()
+                    .copied()
+                    .chain([a[i]])
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        Ok((pixels, width, height))
+    }
+}
+
+impl OperationsTrait for Watermark<'_> {
+    fn name(&self) -> &'static str {
+        "Watermark"
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let colorspace = image.colorspace();
+        let bit_type = image.depth().bit_type();
+        let has_alpha = colorspace.has_alpha();
+        let components = colorspace.num_components();
+        let colors = if has_alpha { components - 1 } else { components };
+
+        let (logo, logo_width, logo_height) = self.logo_pixels(width, colors)?;
+        if logo_width == 0 || logo_height == 0 || self.opacity <= 0.0 {
+            return Ok(());
+        }
+        let margin = self.margin;
+
+        let positions: Vec<(isize, isize)> = if self.tiled {
+            let columns = (margin..width).step_by(logo_width + margin);
+            let rows = (margin..height).step_by(logo_height + margin);
+
+            rows.flat_map(|y| columns.clone().map(move |x| (x as isize, y as isize)))
+                .collect()
+        } else {
+            let area = (
+                width.saturating_sub(2 * margin),
+                height.saturating_sub(2 * margin)
+            );
+            let (x, y) = gravity_offset((logo_width, logo_height), area, self.gravity);
+            vec![(x + margin as isize, y + margin as isize)]
+        };
+
+        for frame in image.frames_mut() {
+            let planes = frame
+                .channels_ref(colorspace, false)
+                .iter()
+                .map(|c| normalized_samples(c, bit_type, self.name()))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut pixels: Vec<f32> = (0..width * height)
+                .flat_map(|i| planes.iter().map(move |plane| plane[i]))
+                .collect();
+
+            for (left, top) in &positions {
+                for (logo_y, logo_row) in logo.chunks_exact(logo_width * (colors + 1)).enumerate() {
+                    let Ok(y) = usize::try_from(top + logo_y as isize) else {
+                        continue;
+                    };
+                    if y >= height {
+                        break;
+                    }
+                    for (logo_x, src) in logo_row.chunks_exact(colors + 1).enumerate() {
+                        let Ok(x) = usize::try_from(left + logo_x as isize) else {
+                            continue;
+                        };
+                        if x >= width {
+                            break;
+                        }
+                        let out = &mut pixels[(y * width + x) * components..][..components];
+                        // without alpha in the image the logo alpha only scales the coverage
+                        let coverage =
+                            if has_alpha { self.opacity } else { self.opacity * src[colors] };
+                        composite_over(out, src, coverage, has_alpha);
+                    }
+                }
+            }
+            frame.set_channels(to_channels(&pixels, bit_type, colorspace));
+        }
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::LumaA,
+            ColorSpace::Luma
+        ]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::utils::Gravity;
+    use crate::watermark::Watermark;
+
+    #[test]
+    fn test_anchored_watermark() {
+        // a white logo with a transparent right half, in another colorspace and depth
+        let logo = Image::from_fn::<u16, _>(4, 2, ColorSpace::LumaA, |_, x, pix| {
+            pix[0] = 65535;
+            pix[1] = if x < 2 { 65535 } else { 0 };
+        });
+        let mut image = Image::fill::<u8>(0, ColorSpace::RGB, 10, 10);
+        Watermark::new(&logo)
+            .set_margin(1)
+            .set_opacity(0.5)
+            .execute(&mut image)
+            .unwrap();
+
+        let pixels = &image.flatten_to_u8()[0];
+        let red = |x: usize, y: usize| pixels[(y * 10 + x) * 3];
+        // the logo covers 5..9 x 7..9
+        assert_eq!(red(5, 7), 128);
+        assert_eq!(red(6, 8), 128);
+        assert_eq!(red(7, 7), 0);
+        assert_eq!(red(4, 7), 0);
+        assert_eq!(red(5, 9), 0);
+        assert_eq!(red(5, 6), 0);
+    }
+
+    #[test]
+    fn test_tiled_and_scaled_watermark() {
+        let logo = Image::fill::<u8>(255, ColorSpace::RGB, 10, 10);
+        let mut image = Image::fill::<f32>(0.0, ColorSpace::LumaA, 20, 20);
+        Watermark::new(&logo)
+            .set_tiled(true)
+            .set_scale(Some(0.2))
+            .set_margin(2)
+            .set_opacity(1.0)
+            .execute(&mut image)
+            .unwrap();
+
+        let pixels = &image.flatten_frames::<f32>()[0];
+        let luma = |x: usize, y: usize| pixels[(y * 20 + x) * 2];
+        // 4x4 tiles starting at 2, 8 and 14 on both axes
+        for (x, y) in [(2, 2), (5, 5), (8, 2), (14, 14), (17, 17), (2, 17)] {
+            assert!((luma(x, y) - 1.0).abs() < 1e-6, "{x} {y}");
+            assert!((pixels[(y * 20 + x) * 2 + 1] - 1.0).abs() < 1e-6);
+        }
+        for (x, y) in [(0, 0), (6, 2), (7, 7), (2, 19), (19, 2)] {
+            assert!(luma(x, y).abs() < 1e-6, "{x} {y}");
+        }
+
+        let mut image = Image::fill::<u8>(0, ColorSpace::RGB, 20, 20);
+        Watermark::new(&logo)
+            .set_gravity(Gravity::Center)
+            .set_opacity(1.0)
+            .execute(&mut image)
+            .unwrap();
+        let pixels = &image.flatten_to_u8()[0];
+        assert_eq!(pixels[(5 * 20 + 5) * 3], 255);
+        assert_eq!(pixels[(4 * 20 + 5) * 3], 0);
+        assert_eq!(pixels[(14 * 20 + 14) * 3], 255);
+        assert_eq!(pixels[(15 * 20 + 14) * 3], 0);
+    }
+}
@@ -0,0 +1,582 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Arithmetic entropy decoding
+//!
+//! This implements the adaptive binary arithmetic decoder from ITU-T T.81 Annex D,
+//! and the procedures from Annex F and G that decode coefficients of sequential and
+//! progressive images from it.
+//!
+//! The decoder state is kept in the [`BitStream`], so resetting the stream at restart
+//! markers and scan boundaries also resets the statistics as the spec requires.
+use alloc::boxed::Box;
+use alloc::format;
+
+use zune_core::bytestream::{ZByteReaderTrait, ZReader};
+
+use crate::bitstream::BitStream;
+use crate::decoder::MAX_COMPONENTS;
+use crate::errors::DecodeErrors;
+use crate::marker::Marker;
+use crate::mcu::DCT_BLOCK;
+use crate::misc::UN_ZIGZAG;
+
+/// Number of statistics bins of a DC conditioning table
+const DC_STAT_BINS: usize = 64;
+/// Number of statistics bins of an AC conditioning table
+const AC_STAT_BINS: usize = 256;
+/// Position of the bins of the first AC table
+const AC_STATS: usize = MAX_COMPONENTS * DC_STAT_BINS;
+/// Position of the bin with a fixed probability, used for sign and correction bits
+const FIXED_BIN: usize = AC_STATS + MAX_COMPONENTS * AC_STAT_BINS;
+/// Index of the fixed probability estimate of 0.5 in [`QE_TABLE`]
+const FIXED_STATE: u8 = 113;
+
+/// Probability estimation state machine, Table D.2
+///
+/// Each entry is `(Qe, next index after an LPS, next index after an MPS, switch MPS)`,
+/// the last entry is a non-adapting estimate of 0.5 as recommended by T.851.
+#[rustfmt::skip]
+const QE_TABLE: [(u16, u8, u8, u8); 114] = [
+    (0x5A1D, 1, 1, 1),
+    (0x2586, 14, 2, 0),
+    (0x1114, 16, 3, 0),
+    (0x080B, 18, 4, 0),
+    (0x03D8, 20, 5, 0),
+    (0x01DA, 23, 6, 0),
+    (0x00E5, 25, 7, 0),
+    (0x006F, 28, 8, 0),
+    (0x0036, 30, 9, 0),
+    (0x001A, 33, 10, 0),
+    (0x000D, 35, 11, 0),
+    (0x0006, 9, 12, 0),
+    (0x0003, 10, 13, 0),
+    (0x0001, 12, 13, 0),
+    (0x5A7F, 15, 15, 1),
+    (0x3F25, 36, 16, 0),
+    (0x2CF2, 38, 17, 0),
+    (0x207C, 39, 18, 0),
+    (0x17B9, 40, 19, 0),
+    (0x1182, 42, 20, 0),
+    (0x0CEF, 43, 21, 0),
+    (0x09A1, 45, 22, 0),
+    (0x072F, 46, 23, 0),
+    (0x055C, 48, 24, 0),
+    (0x0406, 49, 25, 0),
+    (0x0303, 51, 26, 0),
+    (0x0240, 52, 27, 0),
+    (0x01B1, 54, 28, 0),
+    (0x0144, 56, 29, 0),
+    (0x00F5, 57, 30, 0),
+    (0x00B7, 59, 31, 0),
+    (0x008A, 60, 32, 0),
+    (0x0068, 62, 33, 0),
+    (0x004E, 63, 34, 0),
+    (0x003B, 32, 35, 0),
+    (0x002C, 33, 9, 0),
+    (0x5AE1, 37, 37, 1),
+    (0x484C, 64, 38, 0),
+    (0x3A0D, 65, 39, 0),
+    (0x2EF1, 67, 40, 0),
+    (0x261F, 68, 41, 0),
+    (0x1F33, 69, 42, 0),
+    (0x19A8, 70, 43, 0),
+    (0x1518, 72, 44, 0),
+    (0x1177, 73, 45, 0),
+    (0x0E74, 74, 46, 0),
+    (0x0BFB, 75, 47, 0),
+    (0x09F8, 77, 48, 0),
+    (0x0861, 78, 49, 0),
+    (0x0706, 79, 50, 0),
+    (0x05CD, 48, 51, 0),
+    (0x04DE, 50, 52, 0),
+    (0x040F, 50, 53, 0),
+    (0x0363, 51, 54, 0),
+    (0x02D4, 52, 55, 0),
+    (0x025C, 53, 56, 0),
+    (0x01F8, 54, 57, 0),
+    (0x01A4, 55, 58, 0),
+    (0x0160, 56, 59, 0),
+    (0x0125, 57, 60, 0),
+    (0x00F6, 58, 61, 0),
+    (0x00CB, 59, 62, 0),
+    (0x00AB, 61, 63, 0),
+    (0x008F, 61, 32, 0),
+    (0x5B12, 65, 65, 1),
+    (0x4D04, 80, 66, 0),
+    (0x412C, 81, 67, 0),
+    (0x37D8, 82, 68, 0),
+    (0x2FE8, 83, 69, 0),
+    (0x293C, 84, 70, 0),
+    (0x2379, 86, 71, 0),
+    (0x1EDF, 87, 72, 0),
+    (0x1AA9, 87, 73, 0),
+    (0x174E, 72, 74, 0),
+    (0x1424, 72, 75, 0),
+    (0x119C, 74, 76, 0),
+    (0x0F6B, 74, 77, 0),
+    (0x0D51, 75, 78, 0),
+    (0x0BB6, 77, 79, 0),
+    (0x0A40, 77, 48, 0),
+    (0x5832, 80, 81, 1),
+    (0x4D1C, 88, 82, 0),
+    (0x438E, 89, 83, 0),
+    (0x3BDD, 90, 84, 0),
+    (0x34EE, 91, 85, 0),
+    (0x2EAE, 92, 86, 0),
+    (0x299A, 93, 87, 0),
+    (0x2516, 86, 71, 0),
+    (0x5570, 88, 89, 1),
+    (0x4CA9, 95, 90, 0),
+    (0x44D9, 96, 91, 0),
+    (0x3E22, 97, 92, 0),
+    (0x3824, 99, 93, 0),
+    (0x32B4, 99, 94, 0),
+    (0x2E17, 93, 86, 0),
+    (0x56A8, 95, 96, 1),
+    (0x4F46, 101, 97, 0),
+    (0x47E5, 102, 98, 0),
+    (0x41CF, 103, 99, 0),
+    (0x3C3D, 104, 100, 0),
+    (0x375E, 99, 93, 0),
+    (0x5231, 105, 102, 0),
+    (0x4C0F, 106, 103, 0),
+    (0x4639, 107, 104, 0),
+    (0x415E, 103, 99, 0),
+    (0x5627, 105, 106, 1),
+    (0x50E7, 108, 107, 0),
+    (0x4B85, 109, 103, 0),
+    (0x5597, 110, 109, 0),
+    (0x504F, 111, 107, 0),
+    (0x5A10, 110, 111, 1),
+    (0x5522, 112, 109, 0),
+    (0x59EB, 112, 111, 1),
+    (0x5A1D, 113, 113, 0),
+];
+
+/// Conditioning of the arithmetic coding tables, set by DAC segments
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ArithmeticConditioning {
+    /// Lower bound of DC difference conditioning(L) of each DC table
+    pub dc_l: [u8; MAX_COMPONENTS],
+    /// Upper bound of DC difference conditioning(U) of each DC table
+    pub dc_u: [u8; MAX_COMPONENTS],
+    /// AC conditioning(Kx) of each AC table
+    pub ac_k: [u8; MAX_COMPONENTS]
+}
+
+impl Default for ArithmeticConditioning {
+    fn default() -> Self {
+        ArithmeticConditioning {
+            dc_l: [0; MAX_COMPONENTS],
+            dc_u: [1; MAX_COMPONENTS],
+            ac_k: [5; MAX_COMPONENTS]
+        }
+    }
+}
+
+/// Registers and adaptive statistics of the arithmetic decoder
+pub(crate) struct ArithmeticState {
+    /// Code register
+    c:          u64,
+    /// Probability interval
+    a:          u32,
+    /// Bits left in the code register before a byte is needed
+    ct:         i32,
+    /// Probability estimates of all bins of all tables
+    stats:      [u8; FIXED_BIN + 1],
+    /// Conditioning category of the last DC difference of each component
+    dc_context: [usize; MAX_COMPONENTS]
+}
+
+impl Default for ArithmeticState {
+    fn default() -> Self {
+        let mut state = ArithmeticState {
+            c:          0,
+            a:          0,
+            ct:         0,
+            stats:      [0; FIXED_BIN + 1],
+            dc_context: [0; MAX_COMPONENTS]
+        };
+        state.reset();
+        state
+    }
+}
+
+impl ArithmeticState {
+    /// Reset the decoder at the start of a scan or restart interval
+    pub(crate) fn reset(&mut self) {
+        self.c = 0;
+        self.a = 0;
+        // forces reading two bytes before the first decision
+        self.ct = -16;
+        self.stats.fill(0);
+        self.stats[FIXED_BIN] = FIXED_STATE;
+        self.dc_context = [0; MAX_COMPONENTS];
+    }
+}
+
+/// An arithmetic decoder reading entropy coded data from a stream
+struct ArithmeticDecoder<'a, T: ZByteReaderTrait> {
+    state:       &'a mut ArithmeticState,
+    reader:      &'a mut ZReader<T>,
+    marker:      &'a mut Option<Marker>,
+    overread_by: &'a mut usize,
+    seen_eoi:    bool
+}
+
+impl<T: ZByteReaderTrait> ArithmeticDecoder<'_, T> {
+    /// Read the next byte of entropy coded data
+    ///
+    /// Once a marker is found, it is stored and zeros are returned in place
+    /// of data, like the Huffman decoder does.
+    fn read_byte(&mut self) -> Result<u8, DecodeErrors> {
+        if self.marker.is_some() || self.seen_eoi {
+            return Ok(0);
+        }
+        let byte = self.reader.read_u8();
+        *self.overread_by += usize::from(self.reader.eof()?);
+
+        if byte != 0xFF {
+            return Ok(byte);
+        }
+        let mut next_byte = self.reader.read_u8();
+
+        // markers may be preceded by fill bytes
+        while next_byte == 0xFF {
+            next_byte = self.reader.read_u8();
+        }
+        if next_byte == 0x00 {
+            // stuffed byte
+            return Ok(0xFF);
+        }
+        *self.marker =
+            Some(Marker::from_u8(next_byte).ok_or_else(|| {
+                DecodeErrors::Format(format!("Unknown marker 0xFF{next_byte:X}"))
+            })?);
+        Ok(0)
+    }
+
+    /// Decode a single decision with the probability estimate in `bin`, D.2
+    fn decode(&mut self, bin: usize) -> Result<bool, DecodeErrors> {
+        // renormalization, D.2.6
+        while self.state.a < 0x8000 {
+            self.state.ct -= 1;
+
+            if self.state.ct < 0 {
+                let byte = self.read_byte()?;
+
+                self.state.c = (self.state.c << 8) | u64::from(byte);
+                self.state.ct += 8;
+
+                if self.state.ct < 0 {
+                    // still reading the first two bytes
+                    self.state.ct += 1;
+
+                    if self.state.ct == 0 {
+                        self.state.a = 0x8000;
+                    }
+                }
+            }
+            self.state.a <<= 1;
+        }
+        let estimate = self.state.stats[bin];
+        let (qe, next_lps, next_mps, switch) = QE_TABLE[usize::from(estimate & 0x7F)];
+        let qe = u32::from(qe);
+        let mps = estimate & 0x80;
+
+        self.state.a -= qe;
+
+        let threshold = u64::from(self.state.a) << self.state.ct;
+
+        let is_lps = if self.state.c >= threshold {
+            self.state.c -= threshold;
+            // conditional exchange, the LPS sub-interval may be the larger one
+            let is_lps = self.state.a >= qe;
+            self.state.a = qe;
+            is_lps
+        } else if self.state.a < 0x8000 {
+            self.state.a < qe
+        } else {
+            // MPS without renormalization, the estimate stays the same
+            return Ok(mps != 0);
+        };
+
+        if is_lps {
+            self.state.stats[bin] = (mps ^ (switch << 7)) | next_lps;
+            Ok(mps == 0)
+        } else {
+            self.state.stats[bin] = mps | next_mps;
+            Ok(mps != 0)
+        }
+    }
+
+    /// Decode a DC difference, F.1.4.4.1
+    fn decode_dc_diff(
+        &mut self, table: usize, component: usize, conditioning: &ArithmeticConditioning
+    ) -> Result<i32, DecodeErrors> {
+        let base = table * DC_STAT_BINS;
+        let mut bin = base + self.state.dc_context[component];
+
+        if !self.decode(bin)? {
+            self.state.dc_context[component] = 0;
+            return Ok(0);
+        }
+        let sign = self.decode(bin + 1)?;
+
+        bin += 2 + usize::from(sign);
+
+        let mut magnitude = i32::from(self.decode(bin)?);
+
+        if magnitude != 0 {
+            bin = base + 20;
+
+            while self.decode(bin)? {
+                magnitude <<= 1;
+
+                if magnitude == 0x8000 {
+                    return Err(DecodeErrors::FormatStatic(
+                        "Arithmetic coded DC difference is too large, corrupt JPEG"
+                    ));
+                }
+                bin += 1;
+            }
+        }
+        // conditioning category of the next difference, F.1.4.4.1.2
+        let lower = (1 << conditioning.dc_l[table]) >> 1;
+        let upper = (1 << conditioning.dc_u[table]) >> 1;
+
+        self.state.dc_context[component] = if magnitude < lower {
+            0
+        } else if magnitude > upper {
+            12 + 4 * usize::from(sign)
+        } else {
+            4 + 4 * usize::from(sign)
+        };
+
+        let value = self.decode_magnitude(bin + 14, magnitude)?;
+
+        Ok(if sign { -value } else { value })
+    }
+
+    /// Decode the bits of a magnitude below its top bit, Figure F.24
+    fn decode_magnitude(&mut self, bin: usize, top_bit: i32) -> Result<i32, DecodeErrors> {
+        let mut value = top_bit;
+        let mut bit = top_bit >> 1;
+
+        while bit != 0 {
+            if self.decode(bin)? {
+                value |= bit;
+            }
+            bit >>= 1;
+        }
+        Ok(value + 1)
+    }
+
+    /// Decode the AC coefficients `start..=end` of a block, F.1.4.4.2
+    ///
+    /// `store` is called with the zigzag position and value of every
+    /// non-zero coefficient
+    fn decode_ac(
+        &mut self, table: usize, conditioning: &ArithmeticConditioning, start: usize, end: usize,
+        mut store: impl FnMut(usize, i32)
+    ) -> Result<(), DecodeErrors> {
+        let base = AC_STATS + table * AC_STAT_BINS;
+        let mut k = start - 1;
+
+        while k < end {
+            let mut bin = base + 3 * k;
+
+            if self.decode(bin)? {
+                // end of block
+                break;
+            }
+            loop {
+                k += 1;
+
+                if self.decode(bin + 1)? {
+                    break;
+                }
+                bin += 3;
+
+                if k >= end {
+                    return Err(DecodeErrors::FormatStatic(
+                        "Arithmetic coded block has too many coefficients, corrupt JPEG"
+                    ));
+                }
+            }
+            let sign = self.decode(FIXED_BIN)?;
+
+            bin += 2;
+
+            let mut magnitude = i32::from(self.decode(bin)?);
+
+            if magnitude != 0 && self.decode(bin)? {
+                magnitude <<= 1;
+                bin = base + if k <= usize::from(conditioning.ac_k[table]) { 189 } else { 217 };
+
+                while self.decode(bin)? {
+                    magnitude <<= 1;
+
+                    if magnitude == 0x8000 {
+                        return Err(DecodeErrors::FormatStatic(
+                            "Arithmetic coded AC coefficient is too large, corrupt JPEG"
+                        ));
+                    }
+                    bin += 1;
+                }
+            }
+            let value = self.decode_magnitude(bin + 14, magnitude)?;
+
+            store(k, if sign { -value } else { value });
+        }
+        Ok(())
+    }
+
+    /// Decode correction bits and new coefficients of a refinement scan, G.1.3.3
+    fn decode_ac_refine(
+        &mut self, table: usize, start: usize, end: usize, shift: u8, block: &mut [i16; 64]
+    ) -> Result<(), DecodeErrors> {
+        let base = AC_STATS + table * AC_STAT_BINS;
+        let positive = 1_i16 << shift;
+        let negative = -1_i16 << shift;
+
+        // end of block of the previous stage, coefficients past it were zero
+        let mut last_nonzero = end;
+
+        while last_nonzero > 0 && block[UN_ZIGZAG[last_nonzero] & 63] == 0 {
+            last_nonzero -= 1;
+        }
+        let mut k = start - 1;
+
+        while k < end {
+            let mut bin = base + 3 * k;
+
+            if k >= last_nonzero && self.decode(bin)? {
+                // end of block
+                break;
+            }
+            loop {
+                k += 1;
+
+                let coefficient = &mut block[UN_ZIGZAG[k] & 63];
+
+                if *coefficient != 0 {
+                    // correction bit of a coefficient which was already non-zero
+                    if self.decode(bin + 2)? {
+                        let correction = if *coefficient < 0 { negative } else { positive };
+                        *coefficient = coefficient.wrapping_add(correction);
+                    }
+                    break;
+                }
+                if self.decode(bin + 1)? {
+                    // coefficient becomes non-zero
+                    *coefficient = if self.decode(FIXED_BIN)? { negative } else { positive };
+                    break;
+                }
+                bin += 3;
+
+                if k >= end {
+                    return Err(DecodeErrors::FormatStatic(
+                        "Arithmetic coded block has too many coefficients, corrupt JPEG"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BitStream {
+    fn arithmetic_decoder<'a, T: ZByteReaderTrait>(
+        &'a mut self, reader: &'a mut ZReader<T>
+    ) -> ArithmeticDecoder<'a, T> {
+        ArithmeticDecoder {
+            state: self.arithmetic.get_or_insert_with(Box::default),
+            reader,
+            marker: &mut self.marker,
+            overread_by: &mut self.overread_by,
+            seen_eoi: self.seen_eoi
+        }
+    }
+
+    /// Decode a block of a sequential arithmetic coded image
+    ///
+    /// This is the arithmetic coding counterpart of `decode_mcu_block`
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn decode_arithmetic_block<T>(
+        &mut self, reader: &mut ZReader<T>, conditioning: &ArithmeticConditioning,
+        component: usize, dc_table: usize, ac_table: usize, qt_table: &[i32; DCT_BLOCK],
+        block: &mut [i32; 64], dc_prediction: &mut i32
+    ) -> Result<(), DecodeErrors>
+    where
+        T: ZByteReaderTrait
+    {
+        let (dc_table, ac_table) = (dc_table % MAX_COMPONENTS, ac_table % MAX_COMPONENTS);
+        let mut decoder = self.arithmetic_decoder(reader);
+
+        let diff = decoder.decode_dc_diff(dc_table, component, conditioning)?;
+        *dc_prediction = dc_prediction.wrapping_add(diff);
+        block[0] = *dc_prediction * qt_table[0];
+
+        decoder.decode_ac(ac_table, conditioning, 1, 63, |k, value| {
+            let pos = UN_ZIGZAG[k] & 63;
+            block[pos] = value * qt_table[pos];
+        })
+    }
+
+    /// Decode the DC coefficient of a block in a progressive arithmetic coded scan
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn decode_arithmetic_dc<T>(
+        &mut self, reader: &mut ZReader<T>, conditioning: &ArithmeticConditioning,
+        component: usize, table: usize, coefficient: &mut i16, dc_prediction: &mut i32
+    ) -> Result<(), DecodeErrors>
+    where
+        T: ZByteReaderTrait
+    {
+        let shift = self.successive_low;
+        let is_refinement = self.successive_high != 0;
+        let mut decoder = self.arithmetic_decoder(reader);
+
+        if is_refinement {
+            if decoder.decode(FIXED_BIN)? {
+                *coefficient |= 1 << shift;
+            }
+        } else {
+            let diff = decoder.decode_dc_diff(table % MAX_COMPONENTS, component, conditioning)?;
+            *dc_prediction = dc_prediction.wrapping_add(diff);
+            *coefficient = (*dc_prediction as i16).wrapping_mul(1 << shift);
+        }
+        Ok(())
+    }
+
+    /// Decode the AC coefficients of a block in a progressive arithmetic coded scan
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn decode_arithmetic_ac<T>(
+        &mut self, reader: &mut ZReader<T>, conditioning: &ArithmeticConditioning, table: usize,
+        block: &mut [i16; 64]
+    ) -> Result<(), DecodeErrors>
+    where
+        T: ZByteReaderTrait
+    {
+        let table = table % MAX_COMPONENTS;
+        let start = usize::from(self.spec_start.max(1));
+        let end = usize::from(self.spec_end);
+        let shift = self.successive_low;
+        let is_refinement = self.successive_high != 0;
+        let mut decoder = self.arithmetic_decoder(reader);
+
+        if is_refinement {
+            decoder.decode_ac_refine(table, start, end, shift, block)
+        } else {
+            decoder.decode_ac(table, conditioning, start, end, |k, value| {
+                block[UN_ZIGZAG[k] & 63] = (value as i16).wrapping_mul(1 << shift);
+            })
+        }
+    }
+}
@@ -46,12 +46,14 @@
 //! (or learn something cool)
 //!
 //! Knock yourself out.
+use alloc::boxed::Box;
 use alloc::format;
 use alloc::string::ToString;
 use core::cmp::min;
 
 use zune_core::bytestream::{ZByteReaderTrait, ZReader};
 
+use crate::arithmetic::ArithmeticState;
 use crate::errors::DecodeErrors;
 use crate::huffman::{HuffmanTable, HUFF_LOOKAHEAD};
 use crate::marker::Marker;
@@ -119,13 +121,15 @@ pub(crate) struct BitStream {
     /// Progressive decoding
     pub successive_high: u8,
     pub successive_low:  u8,
-    spec_start:          u8,
-    spec_end:            u8,
+    pub spec_start:      u8,
+    pub spec_end:        u8,
     pub eob_run:         i32,
     pub overread_by:     usize,
     /// True if we have seen end of image marker.
     /// Don't read anything after that.
-    pub seen_eoi:        bool
+    pub seen_eoi:        bool,
+    /// State of the arithmetic decoder, created when first used
+    pub arithmetic:      Option<Box<ArithmeticState>>
 }
 
 impl BitStream {
@@ -142,7 +146,8 @@ impl BitStream {
             spec_end:        0,
             eob_run:         0,
             overread_by:     0,
-            seen_eoi:        false
+            seen_eoi:        false,
+            arithmetic:      None
         }
     }
 
@@ -160,7 +165,8 @@ impl BitStream {
             spec_end:        spec_end,
             eob_run:         0,
             overread_by:     0,
-            seen_eoi:        false
+            seen_eoi:        false,
+            arithmetic:      None
         }
     }
 
@@ -436,6 +442,37 @@ impl BitStream {
         Ok(())
     }
 
+    /// Decode the difference between a sample of a lossless image and its prediction
+    ///
+    /// Differences are coded like DC coefficients, with an extra category, 16,
+    /// which stands for a difference of 32768 and has no additional bits
+    pub(crate) fn decode_lossless_diff<T>(
+        &mut self, reader: &mut ZReader<T>, table: &HuffmanTable
+    ) -> Result<i32, DecodeErrors>
+    where
+        T: ZByteReaderTrait
+    {
+        if self.bits_left < 32 {
+            self.refill(reader)?;
+        }
+        let mut symbol = self.peek_bits::<HUFF_LOOKAHEAD>();
+        symbol = table.lookup[symbol as usize];
+
+        decode_huff!(self, symbol, table);
+
+        match symbol {
+            0 => Ok(0),
+            1..=15 => {
+                let r = self.get_bits(symbol as u8);
+                Ok(huff_extend(r, symbol))
+            }
+            16 => Ok(32768),
+            _ => Err(DecodeErrors::HuffmanDecode(format!(
+                "Invalid lossless difference category {symbol}, expected a value between 0 and 16"
+            )))
+        }
+    }
+
     /// Get a single bit from the bitstream
     fn get_bit(&mut self) -> u8 {
         let k = (self.aligned_buffer >> 63) as u8;
@@ -648,6 +685,10 @@ impl BitStream {
         self.buffer = 0;
         self.aligned_buffer = 0;
         self.eob_run = 0;
+
+        if let Some(arithmetic) = &mut self.arithmetic {
+            arithmetic.reset();
+        }
     }
 }
 
@@ -0,0 +1,74 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Quantized DCT coefficients of an image
+//!
+//! See [`JpegDecoder::decode_coefficients`](crate::JpegDecoder::decode_coefficients)
+
+use alloc::vec::Vec;
+
+/// The quantized DCT coefficients of a single image component
+///
+/// Coefficients are stored exactly as they are in the file, i.e. before
+/// dequantization, multiply a coefficient by the matching entry of
+/// [`quantization_table`](Self::quantization_table) to get its DCT value.
+///
+/// Blocks cover the component including the padding to a whole number
+/// of MCUs, blocks past the edges of the image hold whatever the encoder
+/// put there.
+#[derive(Clone, Debug)]
+pub struct ComponentCoefficients {
+    /// Component identifier from the start of frame header
+    pub id:                 u8,
+    /// Horizontal sampling factor of the component
+    pub horizontal_sample:  usize,
+    /// Vertical sampling factor of the component
+    pub vertical_sample:    usize,
+    /// Number of blocks in a row of blocks
+    pub width_in_blocks:    usize,
+    /// Number of rows of blocks
+    pub height_in_blocks:   usize,
+    /// Quantization table of the component in natural (row major) order
+    pub quantization_table: [u16; 64],
+    /// Coefficients of all blocks, in raster order of blocks,
+    /// each block has 64 coefficients in natural (row major) order
+    pub coefficients:       Vec<i16>
+}
+
+impl ComponentCoefficients {
+    /// Return the coefficients of the block at column `x` and row `y`
+    ///
+    /// # Panics
+    /// If the block is outside the component
+    #[must_use]
+    pub fn block(&self, x: usize, y: usize) -> &[i16; 64] {
+        let start = self.block_start(x, y);
+        self.coefficients[start..start + 64].try_into().unwrap()
+    }
+
+    /// Return the coefficients of the block at column `x` and row `y` for modification
+    ///
+    /// # Panics
+    /// If the block is outside the component
+    pub fn block_mut(&mut self, x: usize, y: usize) -> &mut [i16; 64] {
+        let start = self.block_start(x, y);
+        (&mut self.coefficients[start..start + 64])
+            .try_into()
+            .unwrap()
+    }
+
+    fn block_start(&self, x: usize, y: usize) -> usize {
+        assert!(
+            x < self.width_in_blocks && y < self.height_in_blocks,
+            "Block ({x},{y}) outside of component with {}x{} blocks",
+            self.width_in_blocks,
+            self.height_in_blocks
+        );
+        64 * (y * self.width_in_blocks + x)
+    }
+}
@@ -9,24 +9,31 @@
 //! Main image logic.
 #![allow(clippy::doc_markdown)]
 
-use alloc::string::ToString;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use alloc::{format, vec};
 
+use zune_core::bit_depth::BitDepth;
 use zune_core::bytestream::{ZByteReaderTrait, ZReader};
 use zune_core::colorspace::ColorSpace;
-use zune_core::log::{error, trace, warn};
+use zune_core::log::{trace, warn};
 use zune_core::options::DecoderOptions;
+use zune_core::result::DecodingResult;
+use zune_core::warnings::{DecodeWarning, DecodeWarningKind};
 
+use crate::arithmetic::ArithmeticConditioning;
+use crate::coefficients::ComponentCoefficients;
 use crate::color_convert::choose_ycbcr_to_rgb_convert_func;
 use crate::components::{Components, SampleRatios};
 use crate::errors::{DecodeErrors, UnsupportedSchemes};
 use crate::headers::{
-    parse_app1, parse_app14, parse_app2, parse_dqt, parse_huffman, parse_sos, parse_start_of_frame
+    parse_app1, parse_app14, parse_app2, parse_dac, parse_dqt, parse_huffman, parse_sos,
+    parse_start_of_frame
 };
 use crate::huffman::HuffmanTable;
 use crate::idct::choose_idct_func;
 use crate::marker::Marker;
+use crate::mcu_prog::PreviewCallback;
 use crate::misc::SOFMarkers;
 use crate::upsampler::{
     choose_horizontal_samp_function, choose_hv_samp_function, choose_v_samp_function,
@@ -112,6 +119,10 @@ pub struct JpegDecoder<T: ZByteReaderTrait> {
     // Progressive image details
     /// Is the image progressive?
     pub(crate) is_progressive:    bool,
+    /// Is the image arithmetic coded?
+    pub(crate) is_arithmetic:     bool,
+    /// Arithmetic coding conditioning, from DAC segments
+    pub(crate) conditioning:      ArithmeticConditioning,
 
     /// Start of spectral scan
     pub(crate) spec_start:       u8,
@@ -136,6 +147,10 @@ pub struct JpegDecoder<T: ZByteReaderTrait> {
     /// restart markers
     pub(crate) restart_interval: usize,
     pub(crate) todo:             usize,
+    // number of the next expected RST marker
+    pub(crate) expected_rst:     u8,
+    // MCUs of corrupt or lost restart intervals which are left empty
+    pub(crate) skip_mcus:        usize,
     // decoder options
     pub(crate) options:          DecoderOptions,
     // byte-stream
@@ -146,9 +161,13 @@ pub struct JpegDecoder<T: ZByteReaderTrait> {
     // exif data, lifted from app2
     pub(crate) exif_data:        Option<Vec<u8>>,
 
-    pub(crate) icc_data: Vec<ICCChunk>,
-    pub(crate) is_mjpeg: bool,
-    pub(crate) coeff:    usize // Solves some weird bug :)
+    pub(crate) icc_data:     Vec<ICCChunk>,
+    pub(crate) is_mjpeg:     bool,
+    pub(crate) coeff:        usize, // Solves some weird bug :)
+    // whether the image data ended before the whole image was decoded
+    pub(crate) is_truncated: bool,
+    // problems the decoder recovered from
+    pub(crate) warnings:     Vec<DecodeWarning>
 }
 
 impl<T> JpegDecoder<T>
@@ -174,6 +193,8 @@ where
             is_interleaved:    false,
             sub_sample_ratio:  SampleRatios::None,
             is_progressive:    false,
+            is_arithmetic:     false,
+            conditioning:      ArithmeticConditioning::default(),
             spec_start:        0,
             spec_end:          0,
             succ_high:         0,
@@ -185,6 +206,8 @@ where
             z_order:           [0; MAX_COMPONENTS],
             restart_interval:  0,
             todo:              0x7fff_ffff,
+            expected_rst:      0,
+            skip_mcus:         0,
             options:           options,
             stream:            ZReader::new(buffer),
             headers_decoded:   false,
@@ -192,7 +215,9 @@ where
             exif_data:         None,
             icc_data:          vec![],
             is_mjpeg:          false,
-            coeff:             1
+            coeff:             1,
+            is_truncated:      false,
+            warnings:          vec![]
         }
     }
     /// Decode a buffer already in memory
@@ -338,10 +363,10 @@ where
     ///  - SOF(O)
     ///  - DQT -> Quantization tables
     ///  - DHT -> Huffman tables
+    ///  - DAC -> Arithmetic coding conditioning
     ///  - SOS -> Start of Scan
     /// # Unsupported Headers
     ///  - SOF(n) -> Decoder images which are not baseline/progressive
-    ///  - DAC -> Images using Arithmetic tables
     ///  - JPG(n)
     fn decode_headers_internal(&mut self) -> Result<(), DecodeErrors> {
         if self.headers_decoded {
@@ -414,10 +439,13 @@ where
                             ));
                         }
 
-                        error!(
-                            "Extra bytes {} before marker 0xFF{:X}",
-                            bytes_before_marker - 3,
-                            m
+                        self.add_warning(
+                            DecodeWarningKind::NonConformance,
+                            format!(
+                                "Extra bytes {} before marker 0xFF{:X}",
+                                bytes_before_marker - 3,
+                                m
+                            )
                         );
                     }
 
@@ -433,7 +461,10 @@ where
                 } else {
                     bytes_before_marker = 0;
 
-                    warn!("Marker 0xFF{:X} not known", m);
+                    self.add_warning(
+                        DecodeWarningKind::UnsupportedData,
+                        format!("Marker 0xFF{m:X} not known")
+                    );
 
                     let length = self.stream.get_u16_be_err()?;
 
@@ -454,16 +485,21 @@ where
     #[allow(clippy::too_many_lines)]
     pub(crate) fn parse_marker_inner(&mut self, m: Marker) -> Result<(), DecodeErrors> {
         match m {
-            Marker::SOF(0..=2) => {
-                let marker = {
-                    // choose marker
-                    if m == Marker::SOF(0) || m == Marker::SOF(1) {
-                        SOFMarkers::BaselineDct
-                    } else {
-                        self.is_progressive = true;
-                        SOFMarkers::ProgressiveDctHuffman
-                    }
+            Marker::SOF(0..=3 | 9 | 10) => {
+                // choose marker
+                let marker = match m {
+                    Marker::SOF(0 | 1) => SOFMarkers::BaselineDct,
+                    Marker::SOF(2) => SOFMarkers::ProgressiveDctHuffman,
+                    Marker::SOF(3) => SOFMarkers::LosslessHuffman,
+                    Marker::SOF(9) => SOFMarkers::ExtendedSequentialDctArithmetic,
+                    _ => SOFMarkers::ProgressiveDctArithmetic
                 };
+                self.is_progressive = marker.is_progressive();
+                self.is_arithmetic = matches!(
+                    marker,
+                    SOFMarkers::ExtendedSequentialDctArithmetic
+                        | SOFMarkers::ProgressiveDctArithmetic
+                );
 
                 trace!("Image encoding scheme =`{:?}`", marker);
                 // get components
@@ -488,7 +524,6 @@ where
                         "Found a marker with invalid length:{length}\n"
                     )));
                 }
-                // skip for now
                 if length > 5 {
                     let mut buffer = [0u8; 5];
                     self.stream.read_exact_bytes(&mut buffer)?;
@@ -496,6 +531,15 @@ where
                         self.is_mjpeg = true;
                     }
                     length -= 5;
+
+                    // JFIF header, version(2), units(1), x density(2), y density (2)
+                    if &buffer == b"JFIF\0" && length >= 2 + 7 {
+                        self.stream.skip(2)?;
+                        self.info.set_density(self.stream.read_u8_err()?);
+                        self.info.set_x(self.stream.get_u16_be_err()?);
+                        self.info.set_y(self.stream.get_u16_be_err()?);
+                        length -= 7;
+                    }
                 }
 
                 self.stream.skip(length.saturating_sub(2) as usize)?;
@@ -527,7 +571,10 @@ where
             }
             Marker::EOI => return Err(DecodeErrors::FormatStatic("Premature End of image")),
 
-            Marker::DAC | Marker::DNL => {
+            Marker::DAC => {
+                parse_dac(self)?;
+            }
+            Marker::DNL => {
                 return Err(DecodeErrors::Format(format!(
                     "Parsing of the following header `{m:?}` is not supported,\
                                 cannot continue"
@@ -549,9 +596,9 @@ where
                 parse_app14(self)?;
             }
             _ => {
-                warn!(
-                    "Capabilities for processing marker \"{:?}\" not implemented",
-                    m
+                self.add_warning(
+                    DecodeWarningKind::UnsupportedData,
+                    format!("Capabilities for processing marker \"{m:?}\" not implemented")
                 );
 
                 let length = self.stream.get_u16_be_err()?;
@@ -693,6 +740,48 @@ where
     ///
     ///
     pub fn decode_into(&mut self, out: &mut [u8]) -> Result<(), DecodeErrors> {
+        self.decode_into_inner(out, None)
+    }
+
+    /// Decode into a pre-allocated buffer, calling `on_scan` with the image
+    /// decoded so far after every scan of a progressive image
+    ///
+    /// This allows showing a preview which gets sharper as the scans of a
+    /// progressive image are decoded. The last call is with the fully decoded
+    /// image, which is also what `out` contains when this returns. Images which
+    /// aren't progressive, and those with more than 8 bits per sample, are
+    /// decoded in one go, so `on_scan` is called once.
+    ///
+    /// It is an error if the buffer size is smaller than
+    /// [`output_buffer_size()`](Self::output_buffer_size)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_jpeg::JpegDecoder;
+    /// let mut decoder = JpegDecoder::new(ZCursor::new(&[]));
+    /// decoder.decode_headers().unwrap();
+    ///
+    /// let mut out = vec![0;decoder.output_buffer_size().unwrap()];
+    ///
+    /// decoder.decode_into_progressive(&mut out, |image| {
+    ///     // show the preview
+    /// }).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// See DecodeErrors for an explanation
+    pub fn decode_into_progressive<F: FnMut(&[u8])>(
+        &mut self, out: &mut [u8], mut on_scan: F
+    ) -> Result<(), DecodeErrors> {
+        self.decode_into_inner(out, Some(&mut on_scan))
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn decode_into_inner(
+        &mut self, out: &mut [u8], mut on_scan: Option<&mut PreviewCallback>
+    ) -> Result<(), DecodeErrors> {
         self.decode_headers_internal()?;
 
         let expected_size = self.output_buffer_size().unwrap();
@@ -706,11 +795,258 @@ where
         let out_len = core::cmp::min(out.len(), expected_size);
         let out = &mut out[0..out_len];
 
-        if self.is_progressive {
-            self.decode_mcu_ycbcr_progressive(out)
+        if self.info.sof.is_lossless() || self.info.precision > 8 {
+            // decode at full precision and keep the top 8 bits
+            let mut samples = vec![0; out_len];
+            self.decode_into_u16(&mut samples)?;
+
+            let shift = self.info.precision.saturating_sub(8);
+
+            for (out, sample) in out.iter_mut().zip(&samples) {
+                *out = (sample >> shift) as u8;
+            }
+        } else if self.is_progressive {
+            self.decode_mcu_ycbcr_progressive(out, on_scan.as_deref_mut())?;
         } else {
-            self.decode_mcu_ycbcr_baseline(out)
+            self.decode_mcu_ycbcr_baseline(out)?;
+        }
+        if let Some(on_scan) = on_scan {
+            on_scan(out);
         }
+        Ok(())
+    }
+
+    /// Return the bit depth of the decoded samples or `None` if the headers
+    /// haven't been decoded
+    ///
+    /// Images with more than 8 bits per sample, i.e 12-bit DCT and most lossless
+    /// images, are [`BitDepth::Sixteen`], their samples are returned by
+    /// [`decode_u16`](Self::decode_u16) and [`decode_any`](Self::decode_any)
+    /// in the range `0..2^precision`, see [`ImageInfo::precision`]
+    #[must_use]
+    pub fn bit_depth(&self) -> Option<BitDepth> {
+        if !self.headers_decoded {
+            return None;
+        }
+        if self.info.precision > 8 {
+            Some(BitDepth::Sixteen)
+        } else {
+            Some(BitDepth::Eight)
+        }
+    }
+
+    /// Decode an image to samples at the precision of the image
+    ///
+    /// This is mainly meant for images with more than 8 bits per sample,
+    /// 8-bit images are decoded as usual and widened.
+    ///
+    /// # Errors
+    /// See DecodeErrors for an explanation
+    pub fn decode_u16(&mut self) -> Result<Vec<u16>, DecodeErrors> {
+        self.decode_headers()?;
+        let size = self.output_buffer_size().unwrap();
+        let mut out = vec![0; size];
+        self.decode_into_u16(&mut out)?;
+        Ok(out)
+    }
+
+    /// Decode into a pre-allocated buffer of samples at the precision of the
+    /// image
+    ///
+    /// It is an error if the buffer size is smaller than
+    /// [`output_buffer_size()`](Self::output_buffer_size)
+    ///
+    /// Only conversions from YCbCr to RGB(A), BGR(A) and grayscale are
+    /// supported for images with more than 8 bits per sample, and
+    /// lossless images are decoded without any color transform.
+    ///
+    /// # Errors
+    /// See DecodeErrors for an explanation
+    pub fn decode_into_u16(&mut self, out: &mut [u16]) -> Result<(), DecodeErrors> {
+        self.decode_headers_internal()?;
+
+        let expected_size = self.output_buffer_size().unwrap();
+
+        if out.len() < expected_size {
+            return Err(DecodeErrors::TooSmallOutput(expected_size, out.len()));
+        }
+        let out = &mut out[0..expected_size];
+
+        if self.info.sof.is_lossless() {
+            self.decode_lossless(out)
+        } else if self.info.precision > 8 {
+            self.decode_dct_u16(out)
+        } else {
+            let mut samples = vec![0; expected_size];
+            self.decode_into(&mut samples)?;
+
+            for (out, sample) in out.iter_mut().zip(&samples) {
+                *out = u16::from(*sample);
+            }
+            Ok(())
+        }
+    }
+
+    /// Decode an image to 8-bit samples or, if the image has more than 8 bits
+    /// per sample, to 16-bit samples at the precision of the image
+    ///
+    /// # Example
+    /// ```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_core::result::DecodingResult;
+    /// use zune_jpeg::JpegDecoder;
+    ///
+    /// let data = std::fs::read("ct_scan.jpg").unwrap();
+    /// let mut decoder = JpegDecoder::new(ZCursor::new(&data));
+    ///
+    /// match decoder.decode_any().unwrap() {
+    ///     DecodingResult::U8(pixels) => println!("8-bit image, {} samples", pixels.len()),
+    ///     DecodingResult::U16(pixels) => println!("16-bit image, {} samples", pixels.len()),
+    ///     _ => unreachable!()
+    /// }
+    /// ```
+    /// # Errors
+    /// See DecodeErrors for an explanation
+    pub fn decode_any(&mut self) -> Result<DecodingResult, DecodeErrors> {
+        self.decode_headers()?;
+
+        if self.bit_depth() == Some(BitDepth::Sixteen) {
+            Ok(DecodingResult::U16(self.decode_u16()?))
+        } else {
+            Ok(DecodingResult::U8(self.decode()?))
+        }
+    }
+
+    /// Decode the image to quantized DCT coefficients instead of pixels
+    ///
+    /// This is the equivalent of libjpeg's `jpeg_read_coefficients`, it skips
+    /// dequantization, IDCT, upsampling and color conversion, returning the
+    /// coefficients of every component in the frame, in the order of the
+    /// start of frame header.
+    ///
+    /// Like pixel decoding, truncated data isn't an error in non-strict mode,
+    /// missing blocks are zero, see [`is_truncated`](Self::is_truncated)
+    ///
+    /// An image can either be decoded to pixels or to coefficients, not both.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_jpeg::JpegDecoder;
+    ///
+    /// let data = std::fs::read("image.jpg").unwrap();
+    /// let mut decoder = JpegDecoder::new(ZCursor::new(&data));
+    /// let components = decoder.decode_coefficients().unwrap();
+    ///
+    /// let luma = &components[0];
+    /// // the DC coefficient of the top left block
+    /// let dc = luma.block(0, 0)[0] * luma.quantization_table[0] as i16;
+    /// println!("{dc}");
+    /// ```
+    /// # Errors
+    /// See DecodeErrors for an explanation
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn decode_coefficients(&mut self) -> Result<Vec<ComponentCoefficients>, DecodeErrors> {
+        self.decode_headers_internal()?;
+
+        if self.info.sof.is_lossless() {
+            return Err(DecodeErrors::FormatStatic(
+                "Lossless images have no DCT coefficients"
+            ));
+        }
+
+        let coefficients = if self.is_progressive {
+            self.decode_coefficients_progressive(None)?
+        } else {
+            self.decode_coefficients_baseline()?
+        };
+
+        let components = self
+            .components
+            .iter()
+            .zip(coefficients)
+            .map(|(component, mut coefficients)| {
+                let width_in_blocks = component.width_stride / 8;
+                let height_in_blocks = if self.is_interleaved {
+                    self.mcu_y * component.vertical_sample
+                } else {
+                    usize::from(self.info.height).div_ceil(8)
+                };
+                // progressive decoding may allocate rows past the last MCU
+                coefficients.resize(64 * width_in_blocks * height_in_blocks, 0);
+
+                ComponentCoefficients {
+                    id: component.id,
+                    horizontal_sample: component.horizontal_sample,
+                    vertical_sample: component.vertical_sample,
+                    width_in_blocks,
+                    height_in_blocks,
+                    quantization_table: component.quantization_table.map(|x| x as u16),
+                    coefficients
+                }
+            })
+            .collect();
+
+        Ok(components)
+    }
+
+    /// Returns true if the image data ended before the whole image was decoded
+    ///
+    /// In non-strict mode (the default), truncated images are not an error,
+    /// decoding returns the part of the image present in the data and
+    /// missing parts are filled with gray, like libjpeg-turbo does.
+    /// In strict mode, decoding returns an error instead.
+    ///
+    /// This is only meaningful after a call to [`decode`](JpegDecoder::decode)
+    /// or [`decode_into`](JpegDecoder::decode_into)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_jpeg::JpegDecoder;
+    ///
+    /// let data = std::fs::read("partial_download.jpg").unwrap();
+    /// let mut decoder = JpegDecoder::new(ZCursor::new(&data));
+    /// let pixels = decoder.decode().unwrap();
+    ///
+    /// if decoder.is_truncated() {
+    ///     println!("Image is incomplete");
+    /// }
+    /// ```
+    #[must_use]
+    pub const fn is_truncated(&self) -> bool {
+        self.is_truncated
+    }
+
+    /// Return problems the decoder found and recovered from so far
+    ///
+    /// In non-strict mode (the default), the decoder tolerates damaged and
+    /// non standard files, e.g. truncated data or junk between markers, each
+    /// such problem is reported here. An empty list means the file looked fine.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_jpeg::JpegDecoder;
+    ///
+    /// let data = std::fs::read("image.jpg").unwrap();
+    /// let mut decoder = JpegDecoder::new(ZCursor::new(&data));
+    /// decoder.decode().unwrap();
+    ///
+    /// for warning in decoder.warnings() {
+    ///     println!("{warning}");
+    /// }
+    /// ```
+    #[must_use]
+    pub fn warnings(&self) -> &[DecodeWarning] {
+        &self.warnings
+    }
+
+    /// Log a warning and keep it for [`warnings`](Self::warnings)
+    pub(crate) fn add_warning(&mut self, kind: DecodeWarningKind, message: impl Into<String>) {
+        let warning = DecodeWarning::new(kind, message);
+        warn!("{}", warning);
+        self.warnings.push(warning);
     }
 
     /// Read only headers from a jpeg image buffer
@@ -847,16 +1183,38 @@ pub struct ImageInfo {
     pub width:         u16,
     /// Height of image
     pub height:        u16,
-    /// PixelDensity
+    /// Units of the pixel density from the JFIF header
+    ///
+    /// `0` means `x_density` and `y_density` only give the pixel aspect ratio,
+    /// `1` means they are in dots per inch and `2` in dots per centimeter
     pub pixel_density: u8,
     /// Start of frame markers
     pub sof:           SOFMarkers,
-    /// Horizontal sample
+    /// Horizontal pixel density
     pub x_density:     u16,
-    /// Vertical sample
+    /// Vertical pixel density
     pub y_density:     u16,
     /// Number of components
-    pub components:    u8
+    pub components:    u8,
+    /// Number of bits per sample
+    ///
+    /// 8 for most images, DCT images may also have 12 bits per sample
+    /// and lossless images anything between 2 and 16 bits
+    pub precision:     u8,
+    /// Horizontal chroma subsampling factor
+    ///
+    /// How many luma samples share one chroma sample horizontally,
+    /// e.g `2` for 4:2:2 and 4:2:0 images, `1` for 4:4:4 images
+    ///
+    /// Zero if the image has no chroma components
+    pub h_subsampling: u8,
+    /// Vertical chroma subsampling factor
+    ///
+    /// How many luma samples share one chroma sample vertically,
+    /// e.g `2` for 4:2:0 images, `1` for 4:4:4 and 4:2:2 images
+    ///
+    /// Zero if the image has no chroma components
+    pub v_subsampling: u8
 }
 
 impl ImageInfo {
@@ -876,10 +1234,9 @@ impl ImageInfo {
         self.height = height;
     }
 
-    /// Set the image density
+    /// Set the units of the image density
     ///
-    /// Found in the start of frame
-
+    /// Found in the APP(0) marker
     pub(crate) fn set_density(&mut self, density: u8) {
         self.pixel_density = density;
     }
@@ -895,7 +1252,6 @@ impl ImageInfo {
     /// Set image x-density(dots per pixel)
     ///
     /// Found in the APP(0) marker
-    #[allow(dead_code)]
     pub(crate) fn set_x(&mut self, sample: u16) {
         self.x_density = sample;
     }
@@ -903,8 +1259,20 @@ impl ImageInfo {
     /// Set image y-density
     ///
     /// Found in the APP(0) marker
-    #[allow(dead_code)]
     pub(crate) fn set_y(&mut self, sample: u16) {
         self.y_density = sample;
     }
+
+    /// Return the density in dots per inch along the x and y axis,
+    /// or `None` if the image doesn't specify the unit of its density
+    #[must_use]
+    pub fn dpi(&self) -> Option<(f32, f32)> {
+        let (x, y) = (f32::from(self.x_density), f32::from(self.y_density));
+        match self.pixel_density {
+            1 => Some((x, y)),
+            // 1 inch = 2.54 cm
+            2 => Some((x * 2.54, y * 2.54)),
+            _ => None
+        }
+    }
 }
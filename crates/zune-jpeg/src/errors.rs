@@ -13,6 +13,7 @@ use alloc::string::String;
 use core::fmt::{Debug, Display, Formatter};
 
 use zune_core::bytestream::ZByteIoError;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
 
 use crate::misc::{
     START_OF_FRAME_EXT_AR, START_OF_FRAME_EXT_SEQ, START_OF_FRAME_LOS_SEQ,
@@ -165,3 +166,24 @@ impl UnsupportedSchemes {
         }
     }
 }
+
+impl ZErrorCodeTrait for DecodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::Format(_)
+            | Self::FormatStatic(_)
+            | Self::IllegalMagicBytes(_)
+            | Self::HuffmanDecode(_)
+            | Self::ZeroError
+            | Self::DqtError(_)
+            | Self::SosError(_)
+            | Self::SofError(_)
+            | Self::MCUError(_)
+            | Self::ExhaustedData => ZErrorCode::CorruptData,
+            Self::Unsupported(_) => ZErrorCode::Unsupported,
+            Self::LargeDimensions(_) => ZErrorCode::LimitsExceeded,
+            Self::TooSmallOutput(_, _) => ZErrorCode::InvalidInput,
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
@@ -16,7 +16,8 @@ use alloc::vec::Vec;
 
 use zune_core::bytestream::ZByteReaderTrait;
 use zune_core::colorspace::ColorSpace;
-use zune_core::log::{debug, error, trace, warn};
+use zune_core::log::{debug, trace, warn};
+use zune_core::warnings::DecodeWarningKind;
 
 use crate::components::Components;
 use crate::decoder::{ICCChunk, JpegDecoder, MAX_COMPONENTS};
@@ -110,6 +111,68 @@ where
     Ok(())
 }
 
+///**B.2.4.3 Arithmetic conditioning table-specification syntax**
+pub(crate) fn parse_dac<T: ZByteReaderTrait>(
+    decoder: &mut JpegDecoder<T>
+) -> Result<(), DecodeErrors> {
+    let length = decoder
+        .stream
+        .get_u16_be_err()?
+        .checked_sub(2)
+        .ok_or(DecodeErrors::FormatStatic("Invalid DAC length in image"))?;
+
+    if length % 2 != 0 {
+        return Err(DecodeErrors::FormatStatic("Invalid DAC length in image"));
+    }
+
+    for _ in 0..length / 2 {
+        let class_and_destination = decoder.stream.read_u8_err()?;
+        let value = decoder.stream.read_u8_err()?;
+
+        let class = class_and_destination >> 4;
+        let index = usize::from(class_and_destination & 0xF);
+
+        if index >= MAX_COMPONENTS {
+            return Err(DecodeErrors::Format(format!(
+                "Invalid arithmetic conditioning table destination {index}, expected a value between 0 and 3"
+            )));
+        }
+        let conditioning = &mut decoder.conditioning;
+
+        match class {
+            // DC, lower and upper bounds of the conditioning
+            0 => {
+                let (lower, upper) = (value & 0xF, value >> 4);
+
+                if lower > upper {
+                    return Err(DecodeErrors::Format(format!(
+                        "Invalid DC conditioning, L={lower} is greater than U={upper}"
+                    )));
+                }
+                conditioning.dc_l[index] = lower;
+                conditioning.dc_u[index] = upper;
+            }
+            // AC, the Kx parameter
+            1 => {
+                if !(1..=63).contains(&value) {
+                    return Err(DecodeErrors::Format(format!(
+                        "Invalid AC conditioning Kx={value}, range should be 1-63"
+                    )));
+                }
+                conditioning.ac_k[index] = value;
+            }
+            _ => {
+                return Err(DecodeErrors::Format(format!(
+                    "Invalid arithmetic conditioning table class {class}"
+                )));
+            }
+        }
+    }
+    trace!("Arithmetic conditioning: {:?}", decoder.conditioning);
+
+    Ok(())
+}
+
 ///**B.2.4.1 Quantization table-specification syntax**
 #[allow(clippy::cast_possible_truncation, clippy::needless_range_loop)]
 pub(crate) fn parse_dqt<T: ZByteReaderTrait>(img: &mut JpegDecoder<T>) -> Result<(), DecodeErrors> {
@@ -186,17 +249,20 @@ pub(crate) fn parse_start_of_frame<T: ZByteReaderTrait>(
     }
     // Get length of the frame header
     let length = img.stream.get_u16_be_err()?;
-    // usually 8, but can be 12 and 16, we currently support only 8
-    // so sorry about that 12 bit images
+    // 8 or 12 bits for DCT images, between 2 and 16 bits for lossless images
     let dt_precision = img.stream.read_u8_err()?;
 
-    if dt_precision != 8 {
+    let is_supported = if sof.is_lossless() {
+        (2..=16).contains(&dt_precision)
+    } else {
+        matches!(dt_precision, 8 | 12)
+    };
+    if !is_supported {
         return Err(DecodeErrors::SofError(format!(
-            "The library can only parse 8-bit images, the image has {dt_precision} bits of precision"
+            "Unsupported sample precision of {dt_precision} bits for {sof:?} images"
         )));
     }
-
-    img.info.set_density(dt_precision);
+    img.info.precision = dt_precision;
 
     // read  and set the image height.
     let img_height = img.stream.get_u16_be_err()?;
@@ -249,6 +315,11 @@ pub(crate) fn parse_start_of_frame<T: ZByteReaderTrait>(
         img.options = img.options.jpeg_set_out_colorspace(ColorSpace::Luma);
         debug!("Overriding default colorspace set to Luma");
     }
+    if num_components == 3 && sof.is_lossless() && img.input_colorspace == ColorSpace::YCbCr {
+        // color transforms aren't lossless, so components are decoded as stored
+        trace!("Lossless image with 3 components, defaulting to RGB colorspace");
+        img.input_colorspace = ColorSpace::RGB;
+    }
     if num_components == 4 && img.input_colorspace == ColorSpace::YCbCr {
         trace!("Input image has 4 components, defaulting to CMYK colorspace");
         // https://entropymine.wordpress.com/2018/10/22/how-is-a-jpeg-images-color-type-determined/
@@ -270,6 +341,24 @@ pub(crate) fn parse_start_of_frame<T: ZByteReaderTrait>(
 
         components.push(component);
     }
+    if components.len() >= 3 {
+        // chroma subsampling is the maximum sampling factor relative to
+        // the chroma (Cb) component
+        let h_max = components
+            .iter()
+            .map(|c| c.horizontal_sample)
+            .max()
+            .unwrap_or(1);
+        let v_max = components
+            .iter()
+            .map(|c| c.vertical_sample)
+            .max()
+            .unwrap_or(1);
+        let chroma = &components[1];
+
+        img.info.h_subsampling = u8::try_from(h_max / chroma.horizontal_sample.max(1)).unwrap_or(0);
+        img.info.v_subsampling = u8::try_from(v_max / chroma.vertical_sample.max(1)).unwrap_or(0);
+    }
     img.seen_sof = true;
 
     img.info.set_sof_marker(sof);
@@ -450,7 +539,10 @@ pub(crate) fn parse_app14<T: ZByteReaderTrait>(
         return Err(DecodeErrors::FormatStatic("Corrupt Adobe App14 segment"));
     } else {
         length = length.saturating_sub(2);
-        error!("Not a valid Adobe APP14 Segment");
+        decoder.add_warning(
+            DecodeWarningKind::NonConformance,
+            "Not a valid Adobe APP14 Segment"
+        );
     }
     // skip any proceeding lengths.
     // we do not need them
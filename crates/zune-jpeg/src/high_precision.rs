@@ -0,0 +1,237 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Decoding of images with more than 8 bits per sample
+//!
+//! 12-bit DCT images share entropy decoding with 8-bit ones, the quantized
+//! coefficients are then turned into 12-bit samples here, one whole
+//! component at a time.
+//!
+//! This is not as fast as the 8-bit path, which interleaves entropy decoding,
+//! IDCT, upsampling and color conversion a row of MCUs at a time, but such
+//! images are rare outside of medical and scientific imaging.
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::cmp::min;
+
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::colorspace::ColorSpace;
+
+use crate::coefficients::ComponentCoefficients;
+use crate::errors::DecodeErrors;
+use crate::idct::scalar::idct_int_12bit;
+use crate::JpegDecoder;
+
+impl<T: ZByteReaderTrait> JpegDecoder<T> {
+    /// Decode a 12-bit DCT image to samples between 0 and 4095
+    pub(crate) fn decode_dct_u16(&mut self, out: &mut [u16]) -> Result<(), DecodeErrors> {
+        let components = self.decode_coefficients()?;
+
+        let width = usize::from(self.info.width);
+        let height = usize::from(self.info.height);
+
+        let h_max = components
+            .iter()
+            .map(|x| x.horizontal_sample)
+            .max()
+            .unwrap_or(1);
+        let v_max = components
+            .iter()
+            .map(|x| x.vertical_sample)
+            .max()
+            .unwrap_or(1);
+
+        let mut planes = Vec::with_capacity(components.len());
+
+        for component in &components {
+            let h_factor = h_max / component.horizontal_sample;
+            let v_factor = v_max / component.vertical_sample;
+
+            if !matches!((h_factor, v_factor), (1 | 2, 1 | 2)) {
+                return Err(DecodeErrors::Format(format!(
+                    "Unknown down-sampling method ({h_factor},{v_factor}), cannot continue"
+                )));
+            }
+            // dimensions of the component before upsampling
+            let component_width = (width * component.horizontal_sample).div_ceil(h_max);
+            let component_height = (height * component.vertical_sample).div_ceil(v_max);
+
+            let mut plane = reconstruct_component(component, component_width, component_height);
+            let mut plane_width = component_width;
+
+            if v_factor == 2 {
+                plane = upsample_vertical(&plane, plane_width);
+            }
+            if h_factor == 2 {
+                plane = upsample_horizontal(&plane, plane_width);
+                plane_width *= 2;
+            }
+            // remove the extra column or row of odd sized components
+            planes.push(
+                plane
+                    .chunks_exact(plane_width)
+                    .take(height)
+                    .flat_map(|row| &row[..width])
+                    .copied()
+                    .collect()
+            );
+        }
+
+        color_convert_u16(
+            &planes,
+            self.input_colorspace,
+            self.options.jpeg_get_out_colorspace(),
+            self.info.precision,
+            out
+        )
+    }
+}
+
+/// Dequantize and carry out IDCT on the blocks of a component, returning
+/// the `width` by `height` samples they cover
+#[allow(clippy::cast_sign_loss)]
+fn reconstruct_component(
+    component: &ComponentCoefficients, width: usize, height: usize
+) -> Vec<u16> {
+    let stride = component.width_in_blocks * 8;
+    let mut samples = vec![0_i16; stride * component.height_in_blocks * 8];
+    let mut tmp = [0_i32; 64];
+
+    for y in 0..component.height_in_blocks {
+        for x in 0..component.width_in_blocks {
+            let block = component.block(x, y);
+
+            for ((out, coeff), qt) in tmp.iter_mut().zip(block).zip(&component.quantization_table) {
+                *out = i32::from(*coeff) * i32::from(*qt);
+            }
+            idct_int_12bit(&mut tmp, &mut samples[(y * stride + x) * 8..], stride);
+        }
+    }
+    // the IDCT clamps samples to 0..4095
+    samples
+        .chunks_exact(stride)
+        .take(height)
+        .flat_map(|row| &row[..width])
+        .map(|x| *x as u16)
+        .collect()
+}
+
+/// Double the width of a component using a triangle filter, like the 8-bit
+/// upsampler does
+#[allow(clippy::cast_possible_truncation)]
+fn upsample_horizontal(samples: &[u16], width: usize) -> Vec<u16> {
+    let mut out = vec![0; samples.len() * 2];
+
+    for (row, out_row) in samples
+        .chunks_exact(width)
+        .zip(out.chunks_exact_mut(width * 2))
+    {
+        for (x, pair) in out_row.chunks_exact_mut(2).enumerate() {
+            let near = 3 * u32::from(row[x]) + 2;
+            let left = u32::from(row[x.saturating_sub(1)]);
+            let right = u32::from(row[min(x + 1, width - 1)]);
+
+            pair[0] = ((near + left) >> 2) as u16;
+            pair[1] = ((near + right) >> 2) as u16;
+        }
+    }
+    out
+}
+
+/// Double the height of a component using a triangle filter, like the 8-bit
+/// upsampler does
+#[allow(clippy::cast_possible_truncation)]
+fn upsample_vertical(samples: &[u16], width: usize) -> Vec<u16> {
+    let height = samples.len() / width;
+    let mut out = vec![0; samples.len() * 2];
+
+    for (y, out_rows) in out.chunks_exact_mut(width * 2).enumerate() {
+        let row = &samples[y * width..(y + 1) * width];
+        let above = &samples[y.saturating_sub(1) * width..][..width];
+        let below = &samples[min(y + 1, height - 1) * width..][..width];
+
+        let (top, bottom) = out_rows.split_at_mut(width);
+
+        for (x, (top, bottom)) in top.iter_mut().zip(bottom).enumerate() {
+            let near = 3 * u32::from(row[x]) + 2;
+
+            *top = ((near + u32::from(above[x])) >> 2) as u16;
+            *bottom = ((near + u32::from(below[x])) >> 2) as u16;
+        }
+    }
+    out
+}
+
+/// Convert whole components of `precision` bit samples to the output
+/// colorspace, interleaving them into `out`
+///
+/// Only conversions from YCbCr are supported, other colorspaces have to be
+/// decoded as they are.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub(crate) fn color_convert_u16(
+    planes: &[Vec<u16>], input_colorspace: ColorSpace, output_colorspace: ColorSpace,
+    precision: u8, out: &mut [u16]
+) -> Result<(), DecodeErrors> {
+    let max_value = (1_i32 << precision) - 1;
+    let center = 1_i32 << (precision - 1);
+
+    match (input_colorspace, output_colorspace) {
+        (input, output) if input == output => {
+            let num_components = input.num_components();
+
+            if planes.len() < num_components {
+                return Err(DecodeErrors::Format(format!(
+                    "Expected {num_components} number of components but found {}",
+                    planes.len()
+                )));
+            }
+            for (pos, pixel) in out.chunks_exact_mut(num_components).enumerate() {
+                for (sample, plane) in pixel.iter_mut().zip(planes) {
+                    *sample = plane[pos];
+                }
+            }
+        }
+        (ColorSpace::YCbCr, ColorSpace::Luma) => {
+            out.copy_from_slice(&planes[0][..out.len()]);
+        }
+        (
+            ColorSpace::YCbCr,
+            ColorSpace::RGB | ColorSpace::RGBA | ColorSpace::BGR | ColorSpace::BGRA
+        ) => {
+            let num_components = output_colorspace.num_components();
+            let is_bgr = matches!(output_colorspace, ColorSpace::BGR | ColorSpace::BGRA);
+
+            for (pos, pixel) in out.chunks_exact_mut(num_components).enumerate() {
+                let y = i32::from(planes[0][pos]);
+                let cb = i32::from(planes[1][pos]) - center;
+                let cr = i32::from(planes[2][pos]) - center;
+
+                // JFIF conversion coefficients, scaled by 2^14
+                let r = y + ((22970 * cr + 8192) >> 14);
+                let g = y - ((5638 * cb + 11700 * cr + 8192) >> 14);
+                let b = y + ((29032 * cb + 8192) >> 14);
+
+                let (r, b) = if is_bgr { (b, r) } else { (r, b) };
+
+                pixel[0] = r.clamp(0, max_value) as u16;
+                pixel[1] = g.clamp(0, max_value) as u16;
+                pixel[2] = b.clamp(0, max_value) as u16;
+
+                if num_components == 4 {
+                    pixel[3] = max_value as u16;
+                }
+            }
+        }
+        _ => {
+            return Err(DecodeErrors::Format(format!(
+                "Unimplemented colorspace mapping from {input_colorspace:?} to {output_colorspace:?} for {precision} bit images"
+            )));
+        }
+    }
+    Ok(())
+}
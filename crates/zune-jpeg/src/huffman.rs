@@ -238,12 +238,13 @@ impl HuffmanTable {
 
         // Validate symbols as being reasonable
         // For AC tables, we make no check, but accept all byte values 0..255
-        // For DC tables, we require symbols to be in range 0..15
+        // For DC tables, we require symbols to be in range 0..16, 16 is only
+        // used by lossless images
         if is_dc {
             for i in 0..num_symbols {
                 let sym = self.values[i];
 
-                if sym > 15 {
+                if sym > 16 {
                     return Err(DecodeErrors::HuffmanDecode("Bad Huffman Table".to_string()));
                 }
             }
@@ -9,8 +9,23 @@
 //! Platform independent IDCT algorithm
 //!
 //! Not as fast as AVX one.
+//!
+//! Corrupt files can hold coefficients that overflow 32 bits, arithmetic wraps
+//! for them so it doesn't panic in debug builds, the output is garbage anyway.
 
-const SCALE_BITS: i32 = 512 + 65536 + (128 << 17);
+/// Carry out IDCT on a block of dequantized coefficients, writing 8-bit samples
+pub fn idct_int(in_vector: &mut [i32; 64], out_vector: &mut [i16], stride: usize) {
+    idct_int_precision::<8>(in_vector, out_vector, stride);
+}
+
+/// Carry out IDCT on a block of dequantized coefficients, writing 12-bit samples
+///
+/// Coefficients of 12-bit images are 16 times larger than 8-bit ones, so
+/// like libjpeg, the first pass keeps one extra bit of precision instead of
+/// two to keep intermediate values within 32 bits.
+pub fn idct_int_12bit(in_vector: &mut [i32; 64], out_vector: &mut [i16], stride: usize) {
+    idct_int_precision::<12>(in_vector, out_vector, stride);
+}
 
 #[allow(unused_assignments)]
 #[allow(
@@ -18,7 +33,17 @@ const SCALE_BITS: i32 = 512 + 65536 + (128 << 17);
     clippy::op_ref,
     clippy::cast_possible_truncation
 )]
-pub fn idct_int(in_vector: &mut [i32; 64], out_vector: &mut [i16], stride: usize) {
+#[inline(always)]
+fn idct_int_precision<const PRECISION: u8>(
+    in_vector: &mut [i32; 64], out_vector: &mut [i16], stride: usize
+) {
+    // bits removed after the first and second pass
+    let (pass1_shift, pass2_shift) = if PRECISION == 8 { (10, 17) } else { (11, 16) };
+    let max_value: i16 = (1 << PRECISION) - 1;
+    let level_shift: i32 = 1 << (PRECISION - 1);
+    // rounding and the level shift, applied before the second pass shift
+    let scale_bits =
+        (1 << (pass2_shift - 8)) + (1 << (pass2_shift - 1)) + (level_shift << pass2_shift);
     // Temporary variables.
 
     let mut pos = 0;
@@ -28,7 +53,7 @@ pub fn idct_int(in_vector: &mut [i32; 64], out_vector: &mut [i16], stride: usize
     // we want to accelerate the case with 63 0 ac coeff
     if &in_vector[1..] == &[0_i32; 63] {
         // okay then if you work, yay, let's write you really quick
-        let coeff = [(((in_vector[0] >> 3) + 128) as i16).clamp(0, 255); 8];
+        let coeff = [clamp((in_vector[0] >> 3) + level_shift, max_value); 8];
 
         macro_rules! store {
             ($index:tt) => {
@@ -61,20 +86,20 @@ pub fn idct_int(in_vector: &mut [i32; 64], out_vector: &mut [i16], stride: usize
             let p2 = in_vector[ptr + 16];
             let p3 = in_vector[ptr + 48];
 
-            let p1 = (p2 + p3).wrapping_mul(2217);
+            let p1 = p2.wrapping_add(p3).wrapping_mul(2217);
 
-            let t2 = p1 + p3 * -7567;
-            let t3 = p1 + p2 * 3135;
+            let t2 = p1.wrapping_add(p3.wrapping_mul(-7567));
+            let t3 = p1.wrapping_add(p2.wrapping_mul(3135));
 
             let p2 = in_vector[ptr];
             let p3 = in_vector[32 + ptr];
-            let t0 = fsh(p2 + p3);
-            let t1 = fsh(p2 - p3);
+            let t0 = fsh(p2.wrapping_add(p3));
+            let t1 = fsh(p2.wrapping_sub(p3));
 
-            let x0 = t0 + t3 + 512;
-            let x3 = t0 - t3 + 512;
-            let x1 = t1 + t2 + 512;
-            let x2 = t1 - t2 + 512;
+            let x0 = t0.wrapping_add(t3).wrapping_add(1 << (pass1_shift - 1));
+            let x3 = t0.wrapping_sub(t3).wrapping_add(1 << (pass1_shift - 1));
+            let x1 = t1.wrapping_add(t2).wrapping_add(1 << (pass1_shift - 1));
+            let x2 = t1.wrapping_sub(t2).wrapping_add(1 << (pass1_shift - 1));
 
             // odd part
             let mut t0 = in_vector[ptr + 56];
@@ -82,37 +107,37 @@ pub fn idct_int(in_vector: &mut [i32; 64], out_vector: &mut [i16], stride: usize
             let mut t2 = in_vector[ptr + 24];
             let mut t3 = in_vector[ptr + 8];
 
-            let p3 = t0 + t2;
-            let p4 = t1 + t3;
-            let p1 = t0 + t3;
-            let p2 = t1 + t2;
-            let p5 = (p3 + p4) * 4816;
+            let p3 = t0.wrapping_add(t2);
+            let p4 = t1.wrapping_add(t3);
+            let p1 = t0.wrapping_add(t3);
+            let p2 = t1.wrapping_add(t2);
+            let p5 = p3.wrapping_add(p4).wrapping_mul(4816);
 
-            t0 *= 1223;
-            t1 *= 8410;
-            t2 *= 12586;
-            t3 *= 6149;
+            t0 = t0.wrapping_mul(1223);
+            t1 = t1.wrapping_mul(8410);
+            t2 = t2.wrapping_mul(12586);
+            t3 = t3.wrapping_mul(6149);
 
-            let p1 = p5 + p1 * -3685;
-            let p2 = p5 + p2 * -10497;
-            let p3 = p3 * -8034;
-            let p4 = p4 * -1597;
+            let p1 = p5.wrapping_add(p1.wrapping_mul(-3685));
+            let p2 = p5.wrapping_add(p2.wrapping_mul(-10497));
+            let p3 = p3.wrapping_mul(-8034);
+            let p4 = p4.wrapping_mul(-1597);
 
-            t3 += p1 + p4;
-            t2 += p2 + p3;
-            t1 += p2 + p4;
-            t0 += p1 + p3;
+            t3 = t3.wrapping_add(p1.wrapping_add(p4));
+            t2 = t2.wrapping_add(p2.wrapping_add(p3));
+            t1 = t1.wrapping_add(p2.wrapping_add(p4));
+            t0 = t0.wrapping_add(p1.wrapping_add(p3));
 
             // constants scaled things up by 1<<12; let's bring them back
-            // down, but keep 2 extra bits of precision
-            in_vector[ptr] = (x0 + t3) >> 10;
-            in_vector[ptr + 8] = (x1 + t2) >> 10;
-            in_vector[ptr + 16] = (x2 + t1) >> 10;
-            in_vector[ptr + 24] = (x3 + t0) >> 10;
-            in_vector[ptr + 32] = (x3 - t0) >> 10;
-            in_vector[ptr + 40] = (x2 - t1) >> 10;
-            in_vector[ptr + 48] = (x1 - t2) >> 10;
-            in_vector[ptr + 56] = (x0 - t3) >> 10;
+            // down, but keep 2 extra bits of precision (1 for 12-bit samples)
+            in_vector[ptr] = x0.wrapping_add(t3) >> pass1_shift;
+            in_vector[ptr + 8] = x1.wrapping_add(t2) >> pass1_shift;
+            in_vector[ptr + 16] = x2.wrapping_add(t1) >> pass1_shift;
+            in_vector[ptr + 24] = x3.wrapping_add(t0) >> pass1_shift;
+            in_vector[ptr + 32] = x3.wrapping_sub(t0) >> pass1_shift;
+            in_vector[ptr + 40] = x2.wrapping_sub(t1) >> pass1_shift;
+            in_vector[ptr + 48] = x1.wrapping_sub(t2) >> pass1_shift;
+            in_vector[ptr + 56] = x0.wrapping_sub(t3) >> pass1_shift;
         }
 
         // This is vectorised in architectures supporting SSE 4.1
@@ -123,51 +148,52 @@ pub fn idct_int(in_vector: &mut [i32; 64], out_vector: &mut [i16], stride: usize
             let p2 = in_vector[i + 2];
             let p3 = in_vector[i + 6];
 
-            let p1 = (p2 + p3) * 2217;
-            let t2 = p1 + p3 * -7567;
-            let t3 = p1 + p2 * 3135;
+            let p1 = p2.wrapping_add(p3).wrapping_mul(2217);
+            let t2 = p1.wrapping_add(p3.wrapping_mul(-7567));
+            let t3 = p1.wrapping_add(p2.wrapping_mul(3135));
 
             let p2 = in_vector[i];
             let p3 = in_vector[i + 4];
 
-            let t0 = fsh(p2 + p3);
-            let t1 = fsh(p2 - p3);
+            let t0 = fsh(p2.wrapping_add(p3));
+            let t1 = fsh(p2.wrapping_sub(p3));
             // constants scaled things up by 1<<12, plus we had 1<<2 from first
             // loop, plus horizontal and vertical each scale by sqrt(8) so together
             // we've got an extra 1<<3, so 1<<17 total we need to remove.
             // so we want to round that, which means adding 0.5 * 1<<17,
             // aka 65536. Also, we'll end up with -128 to 127 that we want
             // to encode as 0..255 by adding 128, so we'll add that before the shift
-            let x0 = t0 + t3 + SCALE_BITS;
-            let x3 = t0 - t3 + SCALE_BITS;
-            let x1 = t1 + t2 + SCALE_BITS;
-            let x2 = t1 - t2 + SCALE_BITS;
+            // (for 12-bit samples, 1<<16 and 2048)
+            let x0 = t0.wrapping_add(t3).wrapping_add(scale_bits);
+            let x3 = t0.wrapping_sub(t3).wrapping_add(scale_bits);
+            let x1 = t1.wrapping_add(t2).wrapping_add(scale_bits);
+            let x2 = t1.wrapping_sub(t2).wrapping_add(scale_bits);
             // odd part
             let mut t0 = in_vector[i + 7];
             let mut t1 = in_vector[i + 5];
             let mut t2 = in_vector[i + 3];
             let mut t3 = in_vector[i + 1];
 
-            let p3 = t0 + t2;
-            let p4 = t1 + t3;
-            let p1 = t0 + t3;
-            let p2 = t1 + t2;
-            let p5 = (p3 + p4) * f2f(1.175875602);
+            let p3 = t0.wrapping_add(t2);
+            let p4 = t1.wrapping_add(t3);
+            let p1 = t0.wrapping_add(t3);
+            let p2 = t1.wrapping_add(t2);
+            let p5 = p3.wrapping_add(p4).wrapping_mul(f2f(1.175875602));
 
             t0 = t0.wrapping_mul(1223);
             t1 = t1.wrapping_mul(8410);
             t2 = t2.wrapping_mul(12586);
             t3 = t3.wrapping_mul(6149);
 
-            let p1 = p5 + p1 * -3685;
-            let p2 = p5 + p2 * -10497;
-            let p3 = p3 * -8034;
-            let p4 = p4 * -1597;
+            let p1 = p5.wrapping_add(p1.wrapping_mul(-3685));
+            let p2 = p5.wrapping_add(p2.wrapping_mul(-10497));
+            let p3 = p3.wrapping_mul(-8034);
+            let p4 = p4.wrapping_mul(-1597);
 
-            t3 += p1 + p4;
-            t2 += p2 + p3;
-            t1 += p2 + p4;
-            t0 += p1 + p3;
+            t3 = t3.wrapping_add(p1.wrapping_add(p4));
+            t2 = t2.wrapping_add(p2.wrapping_add(p3));
+            t1 = t1.wrapping_add(p2.wrapping_add(p4));
+            t0 = t0.wrapping_add(p1.wrapping_add(p3));
 
             let out: &mut [i16; 8] = out_vector
                 .get_mut(pos..pos + 8)
@@ -175,14 +201,14 @@ pub fn idct_int(in_vector: &mut [i32; 64], out_vector: &mut [i16], stride: usize
                 .try_into()
                 .unwrap();
 
-            out[0] = clamp((x0 + t3) >> 17);
-            out[1] = clamp((x1 + t2) >> 17);
-            out[2] = clamp((x2 + t1) >> 17);
-            out[3] = clamp((x3 + t0) >> 17);
-            out[4] = clamp((x3 - t0) >> 17);
-            out[5] = clamp((x2 - t1) >> 17);
-            out[6] = clamp((x1 - t2) >> 17);
-            out[7] = clamp((x0 - t3) >> 17);
+            out[0] = clamp(x0.wrapping_add(t3) >> pass2_shift, max_value);
+            out[1] = clamp(x1.wrapping_add(t2) >> pass2_shift, max_value);
+            out[2] = clamp(x2.wrapping_add(t1) >> pass2_shift, max_value);
+            out[3] = clamp(x3.wrapping_add(t0) >> pass2_shift, max_value);
+            out[4] = clamp(x3.wrapping_sub(t0) >> pass2_shift, max_value);
+            out[5] = clamp(x2.wrapping_sub(t1) >> pass2_shift, max_value);
+            out[6] = clamp(x1.wrapping_sub(t2) >> pass2_shift, max_value);
+            out[7] = clamp(x0.wrapping_sub(t3) >> pass2_shift, max_value);
 
             i += 8;
 
@@ -204,9 +230,9 @@ fn fsh(x: i32) -> i32 {
     x << 12
 }
 
-/// Clamp values between 0 and 255
+/// Clamp values between 0 and `max`
 #[inline]
 #[allow(clippy::cast_possible_truncation)]
-fn clamp(a: i32) -> i16 {
-    a.clamp(0, 255) as i16
+fn clamp(a: i32, max: i16) -> i16 {
+    a.clamp(0, i32::from(max)) as i16
 }
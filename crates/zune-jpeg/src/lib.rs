@@ -111,20 +111,26 @@ pub use zune_core;
 
 pub use crate::decoder::{ImageInfo, JpegDecoder};
 pub use crate::marker::Marker;
+mod arithmetic;
 mod bitstream;
+pub mod coefficients;
 mod color_convert;
 mod components;
 mod decoder;
 pub mod errors;
 mod headers;
+mod high_precision;
 mod huffman;
 #[cfg(not(fuzzing))]
 mod idct;
 #[cfg(fuzzing)]
 pub mod idct;
+mod lossless;
 mod marker;
 mod mcu;
 mod mcu_prog;
+#[cfg(feature = "threads")]
+mod mcu_parallel;
 mod misc;
 mod unsafe_utils;
 mod unsafe_utils_avx2;
@@ -0,0 +1,244 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Decoding of lossless (sequential, Huffman coded) images
+//!
+//! Lossless images don't use the DCT, each sample is coded as the difference
+//! from a prediction made from its left, upper and upper left neighbours,
+//! see T.81 Annex H.
+//!
+//! Samples are decoded as stored, there is no color transform, and images
+//! with subsampled components aren't supported.
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::warnings::DecodeWarningKind;
+
+use crate::bitstream::BitStream;
+use crate::decoder::MAX_COMPONENTS;
+use crate::errors::DecodeErrors;
+use crate::high_precision::color_convert_u16;
+use crate::marker::Marker;
+use crate::mcu_prog::{get_marker, is_end_of_data, warning_kind};
+use crate::JpegDecoder;
+
+impl<T: ZByteReaderTrait> JpegDecoder<T> {
+    /// Decode a lossless image to samples at the precision of the image
+    pub(crate) fn decode_lossless(&mut self, out: &mut [u16]) -> Result<(), DecodeErrors> {
+        let width = usize::from(self.info.width);
+        let height = usize::from(self.info.height);
+
+        if self
+            .components
+            .iter()
+            .any(|x| x.horizontal_sample != 1 || x.vertical_sample != 1)
+        {
+            return Err(DecodeErrors::FormatStatic(
+                "Lossless images with subsampled components are not supported"
+            ));
+        }
+        let mut planes = vec![vec![0_u16; width * height]; self.components.len()];
+        let mut decoded = [false; MAX_COMPONENTS];
+        let mut stream = BitStream::new();
+
+        loop {
+            self.decode_lossless_scan(&mut stream, &mut planes)?;
+
+            for component in &self.z_order[..usize::from(self.num_scans)] {
+                decoded[*component] = true;
+            }
+            if decoded[..self.components.len()].iter().all(|x| *x) {
+                break;
+            }
+            // components are in separate scans, find the next one
+            match self.next_scan(&mut stream) {
+                Ok(true) => stream.reset(),
+                Ok(false) => break,
+                Err(e) => {
+                    if self.options.strict_mode() {
+                        return Err(e);
+                    }
+                    // components we didn't see are left as zero
+                    self.is_truncated |= is_end_of_data(&e);
+                    self.add_warning(warning_kind(&e), format!("{e}"));
+                    break;
+                }
+            }
+        }
+
+        color_convert_u16(
+            &planes,
+            self.input_colorspace,
+            self.options.jpeg_get_out_colorspace(),
+            self.info.precision,
+            out
+        )
+    }
+
+    /// Parse the headers between two scans, returning false if the image
+    /// ended instead of starting another scan
+    fn next_scan(&mut self, stream: &mut BitStream) -> Result<bool, DecodeErrors> {
+        loop {
+            let marker = get_marker(&mut self.stream, stream)?;
+
+            match marker {
+                Marker::EOI => return Ok(false),
+                // left over restart markers
+                Marker::RST(_) => {}
+                _ => {
+                    self.parse_marker_inner(marker)?;
+
+                    if marker == Marker::SOS {
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Decode the samples of the components in the current scan
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn decode_lossless_scan(
+        &mut self, stream: &mut BitStream, planes: &mut [Vec<u16>]
+    ) -> Result<(), DecodeErrors> {
+        let width = usize::from(self.info.width);
+        let height = usize::from(self.info.height);
+        let precision = self.info.precision;
+        // the start of spectral selection holds the predictor and the successive
+        // approximation low bit the point transform
+        let predictor = self.spec_start;
+        let point_transform = self.succ_low;
+
+        if !(1..=7).contains(&predictor) {
+            return Err(DecodeErrors::SosError(format!(
+                "Invalid lossless predictor {predictor}, expected a value between 1 and 7"
+            )));
+        }
+        if point_transform >= precision {
+            return Err(DecodeErrors::SosError(format!(
+                "Invalid point transform {point_transform} for {precision} bit samples"
+            )));
+        }
+        if !self.restart_interval.is_multiple_of(width) {
+            return Err(DecodeErrors::FormatStatic(
+                "Lossless images with restart intervals that don't cover whole rows are not supported"
+            ));
+        }
+        let scan_components = self.z_order;
+        let scan_components = &scan_components[..usize::from(self.num_scans)];
+
+        for component in scan_components {
+            let table = self.components[*component].dc_huff_table;
+
+            if self.dc_huffman_tables[table % MAX_COMPONENTS].is_none() {
+                return Err(DecodeErrors::HuffmanDecode(format!(
+                    "No Huffman DC table for component {:?}",
+                    self.components[*component].component_id
+                )));
+            }
+        }
+        // prediction for the first sample of the scan and of every restart interval
+        let initial_prediction = 1_i32 << (precision - point_transform - 1);
+        // row after the last restart, predicted from the left neighbour only
+        let mut first_row = 0;
+
+        self.todo = if self.restart_interval > 0 { self.restart_interval } else { usize::MAX };
+        self.expected_rst = 0;
+
+        'rows: for y in 0..height {
+            for x in 0..width {
+                if stream.overread_by > 37 {
+                    if self.options.strict_mode() {
+                        return Err(DecodeErrors::FormatStatic("Premature end of buffer"));
+                    }
+                    // missing samples are left as zero
+                    self.add_warning(DecodeWarningKind::Truncated, "Premature end of buffer");
+                    self.is_truncated = true;
+                    break 'rows;
+                }
+                if self.skip_mcus > 0 {
+                    // part of a lost restart interval
+                    self.skip_mcus -= 1;
+                } else {
+                    for component in scan_components {
+                        let table = self.components[*component].dc_huff_table % MAX_COMPONENTS;
+                        let table = self.dc_huffman_tables[table].as_ref().unwrap();
+
+                        let diff = stream.decode_lossless_diff(&mut self.stream, table)?;
+
+                        let plane = &mut planes[*component];
+                        let pos = y * width + x;
+
+                        let prediction = match (x, y) {
+                            (0, y) if y == first_row => initial_prediction,
+                            (_, y) if y == first_row => i32::from(plane[pos - 1]),
+                            (0, _) => i32::from(plane[pos - width]),
+                            _ => predict(
+                                predictor,
+                                i32::from(plane[pos - 1]),
+                                i32::from(plane[pos - width]),
+                                i32::from(plane[pos - width - 1])
+                            )
+                        };
+                        // differences are modulo 2^16
+                        plane[pos] = (prediction + diff) as u16;
+                    }
+                }
+                self.todo = self.todo.saturating_sub(1);
+
+                if self.todo == 0 && y * width + x + 1 < width * height {
+                    self.find_marker(stream)?;
+
+                    if let Some(Marker::RST(_)) = stream.marker {
+                        self.handle_rst(stream)?;
+                        // the restart interval covers whole rows, lost intervals are skipped
+                        first_row = y + 1 + self.skip_mcus / width;
+                    } else if self.options.strict_mode() {
+                        return Err(DecodeErrors::MCUError(format!(
+                            "Marker {:?} found where a restart marker was expected",
+                            stream.marker
+                        )));
+                    } else {
+                        self.add_warning(
+                            DecodeWarningKind::CorruptData,
+                            format!(
+                                "Marker {:?} found where a restart marker was expected",
+                                stream.marker
+                            )
+                        );
+                        break 'rows;
+                    }
+                }
+            }
+        }
+        // undo the point transform
+        let max_value = (1_u32 << precision) - 1;
+
+        for component in scan_components {
+            for sample in &mut planes[*component] {
+                *sample = ((u32::from(*sample) << point_transform) & max_value) as u16;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Predict a sample from its left (`ra`), upper (`rb`) and upper left (`rc`)
+/// neighbours, T.81 Table H.1
+fn predict(predictor: u8, ra: i32, rb: i32, rc: i32) -> i32 {
+    match predictor {
+        1 => ra,
+        2 => rb,
+        3 => rc,
+        4 => ra + rb - rc,
+        5 => ra + ((rb - rc) >> 1),
+        6 => rb + ((ra - rc) >> 1),
+        _ => (ra + rb) >> 1
+    }
+}
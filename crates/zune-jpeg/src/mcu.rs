@@ -6,13 +6,15 @@
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 
+use alloc::vec::Vec;
 use alloc::{format, vec};
 use core::cmp::min;
 
 use zune_core::bytestream::ZByteReaderTrait;
 use zune_core::colorspace::ColorSpace;
 use zune_core::colorspace::ColorSpace::Luma;
-use zune_core::log::{error, trace, warn};
+use zune_core::log::{trace, warn};
+use zune_core::warnings::DecodeWarningKind;
 
 use crate::bitstream::BitStream;
 use crate::components::SampleRatios;
@@ -30,6 +32,10 @@ pub const DCT_BLOCK: usize = 64;
 impl<T: ZByteReaderTrait> JpegDecoder<T> {
     /// Check for existence of DC and AC Huffman Tables
     pub(crate) fn check_tables(&self) -> Result<(), DecodeErrors> {
+        if self.is_arithmetic {
+            // arithmetic coding has default conditioning for every table
+            return Ok(());
+        }
         // check that dc and AC tables exist outside the hot path
         for component in &self.components {
             let _ = &self
@@ -134,13 +140,22 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
         }
 
         if self.input_colorspace == ColorSpace::Luma && self.is_interleaved {
-            warn!("Grayscale image with down-sampled component, resetting component details");
+            self.add_warning(
+                DecodeWarningKind::NonConformance,
+                "Grayscale image with down-sampled component, resetting component details"
+            );
 
             self.reset_params();
 
             mcu_width = ((self.info.width + 7) / 8) as usize;
             mcu_height = ((self.info.height + 7) / 8) as usize;
         }
+        #[cfg(feature = "threads")]
+        if self.coeff == 1 {
+            if let Some(coefficients) = self.decode_coefficients_parallel(mcu_width, mcu_height)? {
+                return self.finish_progressive_decoding(&coefficients, pixels);
+            }
+        }
         let width = usize::from(self.info.width);
 
         let padded_width = calculate_padded_width(width, self.sub_sample_ratio);
@@ -191,12 +206,18 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
                     return Err(DecodeErrors::FormatStatic("Premature end of buffer"));
                 };
 
-                error!("Premature end of buffer");
+                self.add_warning(DecodeWarningKind::Truncated, "Premature end of buffer");
+                self.is_truncated = true;
+                // like libjpeg-turbo, show rows we don't have as gray
+                fill_gray(
+                    &mut pixels[pixels_written..],
+                    self.options.jpeg_get_out_colorspace()
+                );
                 break;
             }
             // decode a whole MCU width,
             // this takes into account interleaved components.
-            let terminate = self.decode_mcu_width(mcu_width, &mut tmp, &mut stream)?;
+            let terminate = self.decode_mcu_width(mcu_width, i, &mut tmp, &mut stream, None)?;
             // process that width up until it's impossible
             self.post_process(
                 pixels,
@@ -212,6 +233,11 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
                 return Ok(());
             }
         }
+        if stream.overread_by > 37 && !self.is_truncated {
+            // data ended in the last row of MCU's
+            self.add_warning(DecodeWarningKind::Truncated, "Premature end of buffer");
+            self.is_truncated = true;
+        }
         // it may happen that some images don't have the whole buffer
         // so we can't panic in case of that
         // assert_eq!(pixels_written, pixels.len());
@@ -220,58 +246,123 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
 
         Ok(())
     }
+    /// Decode the quantized DCT coefficients of a baseline image
+    ///
+    /// Returns the coefficients of each component, laid out like the coefficients
+    /// of progressive images
+    #[allow(clippy::similar_names)]
+    pub(crate) fn decode_coefficients_baseline(
+        &mut self
+    ) -> Result<[Vec<i16>; MAX_COMPONENTS], DecodeErrors> {
+        setup_component_params(self)?;
+
+        self.check_tables()?;
+
+        if self.input_colorspace.num_components() > self.components.len() {
+            let msg = format!(
+                " Expected {} number of components but found {}",
+                self.input_colorspace.num_components(),
+                self.components.len()
+            );
+            return Err(DecodeErrors::Format(msg));
+        }
+
+        if self.input_colorspace == ColorSpace::Luma && self.is_interleaved {
+            self.add_warning(
+                DecodeWarningKind::NonConformance,
+                "Grayscale image with down-sampled component, resetting component details"
+            );
+            self.reset_params();
+        }
+        let (mcu_width, mcu_height) = if self.is_interleaved {
+            (self.mcu_x, self.mcu_y)
+        } else {
+            (
+                usize::from(self.info.width).div_ceil(8),
+                usize::from(self.info.height).div_ceil(8)
+            )
+        };
+
+        #[cfg(feature = "threads")]
+        if let Some(coefficients) = self.decode_coefficients_parallel(mcu_width, mcu_height)? {
+            return Ok(coefficients);
+        }
+        let mut coefficients: [Vec<i16>; MAX_COMPONENTS] = [vec![], vec![], vec![], vec![]];
+
+        for (coefficient, comp) in coefficients.iter_mut().zip(&self.components) {
+            *coefficient = vec![0; comp.width_stride * comp.vertical_sample * 8 * mcu_height];
+        }
+        let mut stream = BitStream::new();
+        let mut tmp = [0_i32; DCT_BLOCK];
+
+        for i in 0..mcu_height {
+            if stream.overread_by > 37 {
+                if self.options.strict_mode() {
+                    return Err(DecodeErrors::FormatStatic("Premature end of buffer"));
+                }
+                // missing blocks are left as zero
+                self.add_warning(DecodeWarningKind::Truncated, "Premature end of buffer");
+                self.is_truncated = true;
+                break;
+            }
+            let terminate = self.decode_mcu_width(
+                mcu_width,
+                i,
+                &mut tmp,
+                &mut stream,
+                Some(&mut coefficients)
+            )?;
+
+            if terminate {
+                break;
+            }
+        }
+        if stream.overread_by > 37 && !self.is_truncated {
+            self.add_warning(DecodeWarningKind::Truncated, "Premature end of buffer");
+            self.is_truncated = true;
+        }
+        Ok(coefficients)
+    }
+
+    /// Decode a row of MCUs
+    ///
+    /// If `coefficients` is present, the quantized coefficients of every block are
+    /// stored there instead of running the IDCT
+    ///
+    /// In non-strict mode, errors in images with restart intervals are not fatal,
+    /// the rest of the interval is left empty and decoding resumes at the next
+    /// restart marker.
     fn decode_mcu_width(
-        &mut self, mcu_width: usize, tmp: &mut [i32; 64], stream: &mut BitStream
+        &mut self, mcu_width: usize, mcu_row: usize, tmp: &mut [i32; 64], stream: &mut BitStream,
+        mut coefficients: Option<&mut [Vec<i16>; MAX_COMPONENTS]>
     ) -> Result<bool, DecodeErrors> {
         for j in 0..mcu_width {
-            // iterate over components
-            for component in &mut self.components {
-                let dc_table = self.dc_huffman_tables[component.dc_huff_table % MAX_COMPONENTS]
-                    .as_ref()
-                    .unwrap();
-
-                let ac_table = self.ac_huffman_tables[component.ac_huff_table % MAX_COMPONENTS]
-                    .as_ref()
-                    .unwrap();
-
-                let qt_table = &component.quantization_table;
-                let channel = &mut component.raw_coeff;
-
-                // If image is interleaved iterate over scan components,
-                // otherwise if it-s non-interleaved, these routines iterate in
-                // trivial scanline order(Y,Cb,Cr)
-                for v_samp in 0..component.vertical_sample {
-                    for h_samp in 0..component.horizontal_sample {
-                        // Fill the array with zeroes, decode_mcu_block expects
-                        // a zero based array.
-                        tmp.fill(0);
-
-                        stream.decode_mcu_block(
-                            &mut self.stream,
-                            dc_table,
-                            ac_table,
-                            qt_table,
-                            tmp,
-                            &mut component.dc_pred
-                        )?;
-
-                        if component.needed {
-                            let idct_position = {
-                                // derived from stb and rewritten for my tastes
-                                let c2 = v_samp * 8;
-                                let c3 = ((j * component.horizontal_sample) + h_samp) * 8;
-
-                                component.width_stride * c2 + c3
-                            };
-
-                            let idct_pos = channel.get_mut(idct_position..).unwrap();
-                            //  call idct.
-                            (self.idct_func)(tmp, idct_pos, component.width_stride);
-                        }
-                    }
+            if self.skip_mcus > 0 {
+                // part of a corrupt or lost restart interval
+                self.skip_mcus -= 1;
+                self.decode_mcu(j, mcu_row, tmp, None, coefficients.as_deref_mut())?;
+            } else if let Err(e) =
+                self.decode_mcu(j, mcu_row, tmp, Some(stream), coefficients.as_deref_mut())
+            {
+                if self.options.strict_mode() || self.restart_interval == 0 {
+                    return Err(e);
                 }
+                self.add_warning(
+                    DecodeWarningKind::CorruptData,
+                    format!("{e}, skipping to the next restart marker")
+                );
+                // blocks decoded before the error may be garbage, empty the whole MCU
+                self.decode_mcu(j, mcu_row, tmp, None, coefficients.as_deref_mut())?;
+                self.find_marker(stream)?;
+                self.skip_mcus = self.todo.saturating_sub(1);
             }
             self.todo = self.todo.saturating_sub(1);
+
+            if self.todo == 0 && self.restart_interval > 0 && stream.marker.is_none() {
+                // the interval is over, so the restart marker should be next, if it
+                // isn't, the interval is corrupt, resynchronize at the next marker
+                self.find_marker(stream)?;
+            }
             // After all interleaved components, that's an MCU
             // handle stream markers
             //
@@ -304,9 +395,11 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
                             "Marker {m:?} found where not expected"
                         )));
                     }
-                    error!(
-                        "Marker `{:?}` Found within Huffman Stream, possibly corrupt jpeg",
-                        m
+                    self.add_warning(
+                        DecodeWarningKind::CorruptData,
+                        format!(
+                            "Marker `{m:?}` Found within Huffman Stream, possibly corrupt jpeg"
+                        )
                     );
                     self.parse_marker_inner(m)?;
                     if m == Marker::SOS {
@@ -317,6 +410,142 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
         }
         Ok(false)
     }
+
+    /// Decode the blocks of a single MCU
+    ///
+    /// If `stream` is `None`, the MCU is left empty, i.e. all coefficients are zero
+    #[allow(clippy::cast_possible_truncation)]
+    fn decode_mcu(
+        &mut self, mcu_col: usize, mcu_row: usize, tmp: &mut [i32; 64],
+        mut stream: Option<&mut BitStream>,
+        mut coefficients: Option<&mut [Vec<i16>; MAX_COMPONENTS]>
+    ) -> Result<(), DecodeErrors> {
+        // multiplying by one keeps coefficients quantized
+        let unit_table = [1; DCT_BLOCK];
+
+        // iterate over components
+        for (pos, component) in self.components.iter_mut().enumerate() {
+            // not present for arithmetic coded images
+            let dc_table =
+                self.dc_huffman_tables[component.dc_huff_table % MAX_COMPONENTS].as_ref();
+            let ac_table =
+                self.ac_huffman_tables[component.ac_huff_table % MAX_COMPONENTS].as_ref();
+
+            let qt_table =
+                if coefficients.is_some() { &unit_table } else { &component.quantization_table };
+            let channel = &mut component.raw_coeff;
+
+            // If image is interleaved iterate over scan components,
+            // otherwise if it-s non-interleaved, these routines iterate in
+            // trivial scanline order(Y,Cb,Cr)
+            for v_samp in 0..component.vertical_sample {
+                for h_samp in 0..component.horizontal_sample {
+                    // Fill the array with zeroes, decode_mcu_block expects
+                    // a zero based array.
+                    tmp.fill(0);
+
+                    if let Some(stream) = stream.as_mut() {
+                        if self.is_arithmetic {
+                            stream.decode_arithmetic_block(
+                                &mut self.stream,
+                                &self.conditioning,
+                                pos,
+                                component.dc_huff_table,
+                                component.ac_huff_table,
+                                qt_table,
+                                tmp,
+                                &mut component.dc_pred
+                            )?;
+                        } else {
+                            stream.decode_mcu_block(
+                                &mut self.stream,
+                                dc_table.unwrap(),
+                                ac_table.unwrap(),
+                                qt_table,
+                                tmp,
+                                &mut component.dc_pred
+                            )?;
+                        }
+                    }
+
+                    if let Some(coefficients) = coefficients.as_mut() {
+                        let x2 = mcu_col * component.horizontal_sample + h_samp;
+                        let y2 = mcu_row * component.vertical_sample + v_samp;
+                        let start = 64 * (x2 + y2 * component.width_stride / 8);
+
+                        let block = coefficients
+                            .get_mut(pos)
+                            .and_then(|x| x.get_mut(start..start + 64));
+
+                        if let Some(block) = block {
+                            for (out, coeff) in block.iter_mut().zip(tmp.iter()) {
+                                *out = *coeff as i16;
+                            }
+                        }
+                    } else if component.needed {
+                        let idct_position = {
+                            // derived from stb and rewritten for my tastes
+                            let c2 = v_samp * 8;
+                            let c3 = ((mcu_col * component.horizontal_sample) + h_samp) * 8;
+
+                            component.width_stride * c2 + c3
+                        };
+
+                        let idct_pos = channel.get_mut(idct_position..).unwrap();
+                        //  call idct.
+                        (self.idct_func)(tmp, idct_pos, component.width_stride);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Skip entropy coded data up to the next marker
+    ///
+    /// Used to resynchronize after a corrupt restart interval, the marker
+    /// found is stored in `stream.marker`, the bits buffered from the
+    /// corrupt interval are dropped.
+    #[cold]
+    pub(crate) fn find_marker(&mut self, stream: &mut BitStream) -> Result<(), DecodeErrors> {
+        if stream.marker.is_some() {
+            // already found while decoding
+            return Ok(());
+        }
+        stream.reset();
+
+        let mut skipped = 0_usize;
+
+        while !self.stream.eof()? {
+            let byte = self.stream.read_u8();
+
+            if byte != 0xFF {
+                skipped += 1;
+                continue;
+            }
+            let mut next_byte = self.stream.read_u8();
+
+            while next_byte == 0xFF {
+                next_byte = self.stream.read_u8();
+            }
+            if next_byte != 0 {
+                if let Some(marker) = Marker::from_u8(next_byte) {
+                    stream.marker = Some(marker);
+                    break;
+                }
+            }
+            // stuffed byte or an unknown marker, part of the corrupt data
+            skipped += 2;
+        }
+        if skipped > 0 {
+            self.add_warning(
+                DecodeWarningKind::CorruptData,
+                format!("Skipped {skipped} corrupt bytes before the next marker")
+            );
+        }
+        Ok(())
+    }
+
     // handle RST markers.
     // No-op if not using restarts
     // this routine is shared with mcu_prog
@@ -324,11 +553,38 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
     pub(crate) fn handle_rst(&mut self, stream: &mut BitStream) -> Result<(), DecodeErrors> {
         self.todo = self.restart_interval;
 
+        if self.is_arithmetic && self.restart_interval > 0 {
+            // the arithmetic decoder may not have read the whole interval
+            self.find_marker(stream)?;
+        }
+
         if let Some(marker) = stream.marker {
             // Found a marker
             // Read stream and see what marker is stored there
             match marker {
-                Marker::RST(_) => {
+                Marker::RST(number) => {
+                    if !self.is_progressive && number != self.expected_rst {
+                        // restart markers count modulo 8, a different number means
+                        // whole intervals were lost
+                        if self.options.strict_mode() {
+                            return Err(DecodeErrors::MCUError(format!(
+                                "Expected RST{} marker but found RST{number}",
+                                self.expected_rst
+                            )));
+                        }
+                        let lost = usize::from((number + 8 - self.expected_rst) % 8);
+
+                        self.add_warning(
+                            DecodeWarningKind::CorruptData,
+                            format!(
+                                "Expected RST{} marker but found RST{number}, {lost} restart intervals are missing",
+                                self.expected_rst
+                            )
+                        );
+                        self.skip_mcus = lost * self.restart_interval;
+                        self.todo += self.skip_mcus;
+                    }
+                    self.expected_rst = (number + 1) % 8;
                     // reset stream
                     stream.reset();
                     // Initialize dc predictions to zero for all components
@@ -338,6 +594,8 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
                 Marker::EOI => {
                     // silent pass
                 }
+                // the end of a progressive scan, the marker is read after the scan
+                _ if self.is_progressive => {}
                 _ => {
                     return Err(DecodeErrors::MCUError(format!(
                         "Marker {marker:?} found in bitstream, possibly corrupt jpeg"
@@ -507,6 +765,18 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
         Ok(())
     }
 }
+/// Fill pixels with mid gray, the color of an MCU with all coefficients
+/// set to zero, alpha channels are made opaque
+fn fill_gray(pixels: &mut [u8], colorspace: ColorSpace) {
+    let components = colorspace.num_components();
+    let alpha = colorspace.alpha_position();
+
+    for pixel in pixels.chunks_mut(components) {
+        for (pos, value) in pixel.iter_mut().enumerate() {
+            *value = if Some(pos) == alpha { 255 } else { 128 };
+        }
+    }
+}
 // #[cfg(test)]
 // mod tests {
 //     use zune_core::bytestream::ZCursor;
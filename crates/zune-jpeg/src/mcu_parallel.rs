@@ -0,0 +1,291 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Parallel decoding of baseline images with restart intervals
+//!
+//! Restart markers split the entropy coded data of a scan into intervals,
+//! each starting on a byte boundary with DC predictions reset to zero,
+//! so intervals can be decoded independently of each other.
+//!
+//! Intervals are decoded to quantized coefficients on multiple threads, the
+//! coefficients are then turned into pixels by the same routine progressive
+//! images use.
+//!
+//! Images whose restart markers are missing or out of order are left to
+//! the sequential decoder, which resynchronizes on corrupt data.
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::cmp::min;
+
+use zune_core::bytestream::{ZByteReaderTrait, ZCursor, ZReader};
+use zune_core::log::trace;
+use zune_core::warnings::DecodeWarningKind;
+
+use crate::bitstream::BitStream;
+use crate::decoder::MAX_COMPONENTS;
+use crate::errors::DecodeErrors;
+use crate::huffman::HuffmanTable;
+use crate::mcu::DCT_BLOCK;
+use crate::JpegDecoder;
+
+/// Details of a component needed to decode its blocks
+struct ScanComponent<'a> {
+    dc_table:          &'a HuffmanTable,
+    ac_table:          &'a HuffmanTable,
+    horizontal_sample: usize,
+    vertical_sample:   usize,
+    width_stride:      usize
+}
+
+/// Coefficients of consecutive MCUs in decoding order, and errors
+/// recovered from while decoding them
+type DecodedIntervals = (Vec<i16>, Vec<DecodeErrors>);
+
+impl<T: ZByteReaderTrait> JpegDecoder<T> {
+    /// Decode the restart intervals of a baseline scan in parallel
+    ///
+    /// Returns the coefficients of each component, laid out like the ones
+    /// returned by `decode_coefficients_baseline`, or `None` if the image
+    /// can't be decoded in parallel, the stream is then left untouched.
+    #[allow(clippy::similar_names, clippy::cast_possible_truncation)]
+    pub(crate) fn decode_coefficients_parallel(
+        &mut self, mcu_width: usize, mcu_height: usize
+    ) -> Result<Option<[Vec<i16>; MAX_COMPONENTS]>, DecodeErrors> {
+        let num_threads = usize::from(self.options.num_threads());
+        let num_mcus = mcu_width * mcu_height;
+
+        if num_threads < 2
+            || self.restart_interval == 0
+            || num_mcus <= self.restart_interval
+            || self.is_arithmetic
+        {
+            return Ok(None);
+        }
+        let num_intervals = num_mcus.div_ceil(self.restart_interval);
+
+        let start = self.stream.position()?;
+        let mut data = vec![];
+        self.stream.read_remaining(&mut data)?;
+
+        let Some((intervals, scan_end)) = split_intervals(&data, num_intervals) else {
+            trace!("Restart markers don't match the image, decoding sequentially");
+            self.stream.set_position(start as usize)?;
+            return Ok(None);
+        };
+        // stop where the sequential decoder would
+        self.stream.set_position(start as usize + scan_end)?;
+
+        let components: Vec<ScanComponent> = self
+            .components
+            .iter()
+            .map(|component| ScanComponent {
+                dc_table:          self.dc_huffman_tables[component.dc_huff_table % MAX_COMPONENTS]
+                    .as_ref()
+                    .unwrap(),
+                ac_table:          self.ac_huffman_tables[component.ac_huff_table % MAX_COMPONENTS]
+                    .as_ref()
+                    .unwrap(),
+                horizontal_sample: component.horizontal_sample,
+                vertical_sample:   component.vertical_sample,
+                width_stride:      component.width_stride
+            })
+            .collect();
+
+        let restart_interval = self.restart_interval;
+        let strict = self.options.strict_mode();
+        // each thread decodes a contiguous run of intervals
+        let intervals_per_thread = num_intervals.div_ceil(num_threads);
+
+        let results: Vec<Result<DecodedIntervals, DecodeErrors>> = std::thread::scope(|s| {
+            let handles: Vec<_> = intervals
+                .chunks(intervals_per_thread)
+                .enumerate()
+                .map(|(pos, intervals)| {
+                    let components = &components;
+                    let first_mcu = pos * intervals_per_thread * restart_interval;
+                    let last_mcu = min(first_mcu + intervals.len() * restart_interval, num_mcus);
+
+                    s.spawn(move || {
+                        decode_intervals(
+                            components,
+                            intervals,
+                            restart_interval,
+                            last_mcu - first_mcu,
+                            strict
+                        )
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|e| std::panic::resume_unwind(e))
+                })
+                .collect()
+        });
+
+        let mut coefficients: [Vec<i16>; MAX_COMPONENTS] = [vec![], vec![], vec![], vec![]];
+
+        for (coefficient, comp) in coefficients.iter_mut().zip(&components) {
+            *coefficient = vec![0; comp.width_stride * comp.vertical_sample * 8 * mcu_height];
+        }
+        let mut decoded_blocks = vec![];
+        let mut errors = vec![];
+
+        for result in results {
+            let (blocks, interval_errors) = result?;
+
+            decoded_blocks.extend_from_slice(&blocks);
+            errors.extend(interval_errors);
+        }
+
+        // move blocks from decoding order to where the sequential decoder puts them
+        let mut blocks = decoded_blocks.chunks_exact(DCT_BLOCK);
+
+        for mcu in 0..num_mcus {
+            let (mcu_row, mcu_col) = (mcu / mcu_width, mcu % mcu_width);
+
+            for (pos, component) in components.iter().enumerate() {
+                for v_samp in 0..component.vertical_sample {
+                    for h_samp in 0..component.horizontal_sample {
+                        let x2 = mcu_col * component.horizontal_sample + h_samp;
+                        let y2 = mcu_row * component.vertical_sample + v_samp;
+                        let start = 64 * (x2 + y2 * component.width_stride / 8);
+
+                        let block = blocks.next().unwrap();
+
+                        if let Some(out) = coefficients[pos].get_mut(start..start + 64) {
+                            out.copy_from_slice(block);
+                        }
+                    }
+                }
+            }
+        }
+        for error in errors {
+            self.add_warning(
+                DecodeWarningKind::CorruptData,
+                format!("{error}, skipping to the next restart marker")
+            );
+        }
+        Ok(Some(coefficients))
+    }
+}
+
+/// Split the entropy coded data of a scan into restart intervals
+///
+/// Returns the intervals and the offset of the marker ending the scan,
+/// or `None` if there aren't `num_intervals` intervals separated by
+/// restart markers in order.
+fn split_intervals(data: &[u8], num_intervals: usize) -> Option<(Vec<&[u8]>, usize)> {
+    let mut intervals = Vec::with_capacity(num_intervals);
+    let mut interval_start = 0;
+    let mut pos = 0;
+
+    while pos < data.len() {
+        if data[pos] != 0xFF {
+            pos += 1;
+            continue;
+        }
+        // markers may be preceded by fill bytes
+        let mut marker_pos = pos + 1;
+
+        while data.get(marker_pos) == Some(&0xFF) {
+            marker_pos += 1;
+        }
+        match data.get(marker_pos) {
+            // stuffed byte
+            Some(0x00) => {}
+            Some(marker @ 0xD0..=0xD7) => {
+                if usize::from(marker - 0xD0) != intervals.len() % 8 {
+                    return None;
+                }
+                intervals.push(&data[interval_start..pos]);
+                interval_start = marker_pos + 1;
+            }
+            Some(_) => {
+                intervals.push(&data[interval_start..pos]);
+
+                return (intervals.len() == num_intervals).then_some((intervals, pos));
+            }
+            // the data ends before the scan does
+            None => return None
+        }
+        pos = marker_pos + 1;
+    }
+    None
+}
+
+/// Decode consecutive restart intervals to quantized coefficients
+///
+/// In non-strict mode, a corrupt interval is an error for its MCUs only,
+/// the rest of it is left empty and the error returned with the coefficients.
+#[allow(clippy::cast_possible_truncation)]
+fn decode_intervals(
+    components: &[ScanComponent], intervals: &[&[u8]], restart_interval: usize, num_mcus: usize,
+    strict: bool
+) -> Result<DecodedIntervals, DecodeErrors> {
+    // multiplying by one keeps coefficients quantized
+    let unit_table = [1; DCT_BLOCK];
+    let blocks_per_mcu: usize = components
+        .iter()
+        .map(|x| x.horizontal_sample * x.vertical_sample)
+        .sum();
+
+    let mut coefficients = vec![0; num_mcus * blocks_per_mcu * DCT_BLOCK];
+    let mut errors = vec![];
+    let mut tmp = [0_i32; DCT_BLOCK];
+
+    let interval_size = restart_interval * blocks_per_mcu * DCT_BLOCK;
+
+    for (interval, out) in intervals.iter().zip(coefficients.chunks_mut(interval_size)) {
+        let mut reader = ZReader::new(ZCursor::new(*interval));
+        let mut stream = BitStream::new();
+        let mut dc_pred = [0_i32; MAX_COMPONENTS];
+
+        for mcu in out.chunks_exact_mut(blocks_per_mcu * DCT_BLOCK) {
+            let mut blocks = mcu.chunks_exact_mut(DCT_BLOCK);
+            let mut result = Ok(());
+
+            'mcu: for (component, pred) in components.iter().zip(dc_pred.iter_mut()) {
+                for _ in 0..component.horizontal_sample * component.vertical_sample {
+                    tmp.fill(0);
+
+                    result = stream.decode_mcu_block(
+                        &mut reader,
+                        component.dc_table,
+                        component.ac_table,
+                        &unit_table,
+                        &mut tmp,
+                        pred
+                    );
+                    if result.is_err() {
+                        break 'mcu;
+                    }
+                    let block = blocks.next().unwrap();
+
+                    for (out, coeff) in block.iter_mut().zip(tmp.iter()) {
+                        *out = *coeff as i16;
+                    }
+                }
+            }
+            if let Err(e) = result {
+                if strict {
+                    return Err(e);
+                }
+                // like the sequential decoder, empty the MCU and the rest of the interval
+                mcu.fill(0);
+                errors.push(e);
+                break;
+            }
+        }
+    }
+    Ok((coefficients, errors))
+}
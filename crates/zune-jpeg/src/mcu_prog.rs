@@ -26,32 +26,65 @@ use core::cmp::min;
 
 use zune_core::bytestream::{ZByteReaderTrait, ZReader};
 use zune_core::colorspace::ColorSpace;
-use zune_core::log::{debug, error, warn};
+use zune_core::log::debug;
+use zune_core::warnings::DecodeWarningKind;
 
 use crate::bitstream::BitStream;
 use crate::components::{ComponentID, SampleRatios};
 use crate::decoder::{JpegDecoder, MAX_COMPONENTS};
 use crate::errors::DecodeErrors;
 use crate::errors::DecodeErrors::Format;
-use crate::headers::{parse_huffman, parse_sos};
+use crate::headers::{parse_dac, parse_huffman, parse_sos};
 use crate::marker::Marker;
 use crate::mcu::DCT_BLOCK;
 use crate::misc::{calculate_padded_width, setup_component_params};
 
+/// Called with a partially decoded image, see [`JpegDecoder::decode_into_progressive`]
+pub(crate) type PreviewCallback<'a> = dyn FnMut(&[u8]) + 'a;
+
+/// Called with the decoder and the coefficients of every component
+pub(crate) type ScanCallback<'a, T> =
+    dyn FnMut(&mut JpegDecoder<T>, &[Vec<i16>; MAX_COMPONENTS]) -> Result<(), DecodeErrors> + 'a;
+
 impl<T: ZByteReaderTrait> JpegDecoder<T> {
     /// Decode a progressive image
     ///
     /// This routine decodes a progressive image, stopping if it finds any error.
+    #[inline(never)]
+    pub(crate) fn decode_mcu_ycbcr_progressive(
+        &mut self, pixels: &mut [u8], on_scan: Option<&mut PreviewCallback>
+    ) -> Result<(), DecodeErrors> {
+        let block = match on_scan {
+            Some(on_scan) => {
+                // show the image decoded from the scans seen so far before
+                // every scan after the first
+                let mut show_scan = |decoder: &mut Self, block: &[Vec<i16>; MAX_COMPONENTS]| {
+                    decoder.finish_progressive_decoding(block, pixels)?;
+                    on_scan(pixels);
+                    Ok(())
+                };
+                self.decode_coefficients_progressive(Some(&mut show_scan))?
+            }
+            None => self.decode_coefficients_progressive(None)?
+        };
+
+        self.finish_progressive_decoding(&block, pixels)
+    }
+
+    /// Decode all scans of a progressive image, returning the
+    /// quantized coefficients of each component
+    ///
+    /// `on_scan` is called with the coefficients decoded so far before
+    /// every scan after the first
     #[allow(
         clippy::needless_range_loop,
         clippy::cast_sign_loss,
         clippy::redundant_else,
         clippy::too_many_lines
     )]
-    #[inline(never)]
-    pub(crate) fn decode_mcu_ycbcr_progressive(
-        &mut self, pixels: &mut [u8]
-    ) -> Result<(), DecodeErrors> {
+    pub(crate) fn decode_coefficients_progressive(
+        &mut self, mut on_scan: Option<&mut ScanCallback<T>>
+    ) -> Result<[Vec<i16>; MAX_COMPONENTS], DecodeErrors> {
         setup_component_params(self)?;
 
         let mut mcu_height;
@@ -63,7 +96,10 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
         let mut seen_scans = 1;
 
         if self.input_colorspace == ColorSpace::Luma && self.is_interleaved {
-            warn!("Grayscale image with down-sampled component, resetting component details");
+            self.add_warning(
+                DecodeWarningKind::NonConformance,
+                "Grayscale image with down-sampled component, resetting component details"
+            );
             self.reset_params();
         }
 
@@ -140,7 +176,13 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
                 Marker::DHT => {
                     parse_huffman(self)?;
                 }
+                Marker::DAC => {
+                    parse_dac(self)?;
+                }
                 Marker::SOS => {
+                    if let Some(on_scan) = on_scan.as_mut() {
+                        on_scan(self, &block)?;
+                    }
                     parse_sos(self)?;
 
                     stream.update_progressive_params(
@@ -172,7 +214,10 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
                             if self.options.strict_mode() {
                                 return Err(msg);
                             }
-                            error!("{:?}", msg);
+                            // scans we didn't see leave their coefficients as zero,
+                            // which shows up as gray or blurry regions
+                            self.is_truncated |= is_end_of_data(&msg);
+                            self.add_warning(warning_kind(&msg), format!("{msg:?}"));
                             break 'eoi;
                         }
                     }
@@ -190,12 +235,13 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
                     if self.options.strict_mode() {
                         return Err(e);
                     }
-                    error!("{}", e);
+                    self.is_truncated |= is_end_of_data(&e);
+                    self.add_warning(warning_kind(&e), format!("{e}"));
                 }
             }
         }
 
-        self.finish_progressive_decoding(&block, mcu_width, pixels)
+        Ok(block)
     }
 
     #[allow(clippy::too_many_lines, clippy::cast_sign_loss)]
@@ -265,7 +311,27 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
                         .try_into()
                         .unwrap();
 
-                    if self.spec_start == 0 {
+                    if self.is_arithmetic {
+                        let component = &mut self.components[k];
+
+                        if self.spec_start == 0 {
+                            stream.decode_arithmetic_dc(
+                                &mut self.stream,
+                                &self.conditioning,
+                                k,
+                                component.dc_huff_table,
+                                &mut data[0],
+                                &mut component.dc_pred
+                            )?;
+                        } else {
+                            stream.decode_arithmetic_ac(
+                                &mut self.stream,
+                                &self.conditioning,
+                                component.ac_huff_table,
+                                data
+                            )?;
+                        }
+                    } else if self.spec_start == 0 {
                         let pos = self.components[k].dc_huff_table & (MAX_COMPONENTS - 1);
                         let dc_table = self
                             .dc_huffman_tables
@@ -344,6 +410,9 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
                         "Cannot find component {n}, corrupt image"
                     )));
                 }
+                if self.is_arithmetic {
+                    continue;
+                }
 
                 let component = &mut self.components[n];
                 let _ = self
@@ -373,14 +442,22 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
                     for k in 0..self.num_scans {
                         let n = self.z_order[k as usize];
                         let component = &mut self.components[n];
-                        let huff_table = self
-                            .dc_huffman_tables
-                            .get(component.dc_huff_table)
-                            .ok_or(DecodeErrors::FormatStatic("No huffman table for component"))?
-                            .as_ref()
-                            .ok_or(DecodeErrors::FormatStatic(
-                                "Huffman table at index not initialized"
-                            ))?;
+                        // arithmetic coded images don't have huffman tables
+                        let huff_table = if self.is_arithmetic {
+                            None
+                        } else {
+                            let table = self
+                                .dc_huffman_tables
+                                .get(component.dc_huff_table)
+                                .ok_or(DecodeErrors::FormatStatic(
+                                    "No huffman table for component"
+                                ))?
+                                .as_ref()
+                                .ok_or(DecodeErrors::FormatStatic(
+                                    "Huffman table at index not initialized"
+                                ))?;
+                            Some(table)
+                        };
 
                         for v_samp in 0..component.vertical_sample {
                             for h_samp in 0..component.horizontal_sample {
@@ -390,15 +467,29 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
 
                                 let data = &mut buffer[n][position];
 
-                                if self.succ_high == 0 {
-                                    stream.decode_prog_dc_first(
-                                        &mut self.stream,
-                                        huff_table,
-                                        data,
-                                        &mut component.dc_pred
-                                    )?;
-                                } else {
-                                    stream.decode_prog_dc_refine(&mut self.stream, data)?;
+                                match huff_table {
+                                    _ if self.is_arithmetic => {
+                                        stream.decode_arithmetic_dc(
+                                            &mut self.stream,
+                                            &self.conditioning,
+                                            n,
+                                            component.dc_huff_table,
+                                            data,
+                                            &mut component.dc_pred
+                                        )?;
+                                    }
+                                    Some(huff_table) if self.succ_high == 0 => {
+                                        stream.decode_prog_dc_first(
+                                            &mut self.stream,
+                                            huff_table,
+                                            data,
+                                            &mut component.dc_pred
+                                        )?;
+                                    }
+                                    // refining scans for this MCU
+                                    _ => {
+                                        stream.decode_prog_dc_refine(&mut self.stream, data)?;
+                                    }
                                 }
                             }
                         }
@@ -418,8 +509,8 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
 
     #[allow(clippy::too_many_lines)]
     #[allow(clippy::needless_range_loop, clippy::cast_sign_loss)]
-    fn finish_progressive_decoding(
-        &mut self, block: &[Vec<i16>; MAX_COMPONENTS], _mcu_width: usize, pixels: &mut [u8]
+    pub(crate) fn finish_progressive_decoding(
+        &mut self, block: &[Vec<i16>; MAX_COMPONENTS], pixels: &mut [u8]
     ) -> Result<(), DecodeErrors> {
         // This function is complicated because we need to replicate
         // the function in mcu.rs
@@ -586,7 +677,26 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
 ///Get a marker from the bit-stream.
 ///
 /// This reads until it gets a marker or end of file is encountered
-fn get_marker<T>(reader: &mut ZReader<T>, stream: &mut BitStream) -> Result<Marker, DecodeErrors>
+/// Returns true if `error` means the data ended before an expected marker
+pub(crate) fn is_end_of_data(error: &DecodeErrors) -> bool {
+    matches!(
+        error,
+        DecodeErrors::ExhaustedData | DecodeErrors::IoErrors(_)
+    )
+}
+
+/// The kind of warning to report when non-strict decoding stops at `error`
+pub(crate) fn warning_kind(error: &DecodeErrors) -> DecodeWarningKind {
+    if is_end_of_data(error) {
+        DecodeWarningKind::Truncated
+    } else {
+        DecodeWarningKind::CorruptData
+    }
+}
+
+pub(crate) fn get_marker<T>(
+    reader: &mut ZReader<T>, stream: &mut BitStream
+) -> Result<Marker, DecodeErrors>
 where
     T: ZByteReaderTrait
 {
@@ -0,0 +1,86 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Arithmetic coded images
+//!
+//! The arithmetic coded images were transcoded losslessly from their Huffman coded
+//! sources, so both should decode to the same coefficients and pixels.
+
+use zune_core::bytestream::ZCursor;
+use zune_core::options::DecoderOptions;
+use zune_jpeg::JpegDecoder;
+
+const IMAGES: [(&str, &str); 6] = [
+    ("sampling_factors_arithmetic.jpg", "sampling_factors.jpg"),
+    (
+        "Kiara_limited_progressive_four_components_arithmetic.jpg",
+        "Kiara_limited_progressive_four_components.jpg"
+    ),
+    ("2029_arithmetic_restart.jpg", "2029.jpg"),
+    ("2029_arithmetic_progressive_restart.jpg", "2029.jpg"),
+    ("cymk_arithmetic.jpg", "cymk.jpg"),
+    (
+        "down_sampled_grayscale_arithmetic_progressive.jpg",
+        "down_sampled_grayscale_prog.jpg"
+    )
+];
+
+fn open(name: &str) -> Vec<u8> {
+    let path = env!("CARGO_MANIFEST_DIR").to_string() + "/../../test-images/jpeg/" + name;
+    std::fs::read(path).unwrap()
+}
+
+#[test]
+fn arithmetic_matches_huffman_coefficients() {
+    for (arithmetic, huffman) in IMAGES {
+        let expected = JpegDecoder::new(ZCursor::new(open(huffman)))
+            .decode_coefficients()
+            .unwrap();
+        let actual = JpegDecoder::new(ZCursor::new(open(arithmetic)))
+            .decode_coefficients()
+            .unwrap();
+
+        assert_eq!(expected.len(), actual.len());
+
+        for (a, b) in expected.iter().zip(&actual) {
+            assert!(a.coefficients == b.coefficients, "{arithmetic}");
+        }
+    }
+}
+
+#[test]
+fn arithmetic_matches_huffman_pixels() {
+    for (arithmetic, huffman) in IMAGES {
+        let mut decoder = JpegDecoder::new(ZCursor::new(open(huffman)));
+        let expected = decoder.decode().unwrap();
+
+        let mut decoder = JpegDecoder::new(ZCursor::new(open(arithmetic)));
+        let actual = decoder.decode().unwrap();
+
+        assert!(expected == actual, "{arithmetic}");
+        assert!(
+            decoder.warnings().is_empty(),
+            "{arithmetic}: {:?}",
+            decoder.warnings()
+        );
+    }
+}
+
+#[test]
+fn truncated_arithmetic_image() {
+    let mut data = open("2029_arithmetic_restart.jpg");
+    data.truncate(data.len() / 2);
+
+    let mut decoder = JpegDecoder::new(ZCursor::new(&data));
+    decoder.decode().unwrap();
+    assert!(decoder.is_truncated());
+
+    let options = DecoderOptions::default().set_strict_mode(true);
+    let mut decoder = JpegDecoder::new_with_options(ZCursor::new(&data), options);
+    assert!(decoder.decode().is_err());
+}
@@ -0,0 +1,119 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use std::f64::consts::PI;
+
+use zune_core::bytestream::ZCursor;
+use zune_core::colorspace::ColorSpace;
+use zune_core::options::DecoderOptions;
+use zune_jpeg::coefficients::ComponentCoefficients;
+use zune_jpeg::JpegDecoder;
+
+fn open(name: &str) -> Vec<u8> {
+    let path = env!("CARGO_MANIFEST_DIR").to_string() + "/../../test-images/jpeg/" + name;
+    std::fs::read(path).unwrap()
+}
+
+/// Dequantize and inverse transform a block with the textbook IDCT
+fn reference_idct(component: &ComponentCoefficients, x: usize, y: usize) -> [f64; 64] {
+    let block = component.block(x, y);
+    let mut out = [0.0; 64];
+
+    for (pos, pixel) in out.iter_mut().enumerate() {
+        let (px, py) = ((pos % 8) as f64, (pos / 8) as f64);
+        let mut sum = 0.0;
+
+        for v in 0..8 {
+            for u in 0..8 {
+                let coeff = f64::from(block[v * 8 + u])
+                    * f64::from(component.quantization_table[v * 8 + u]);
+                let cu = if u == 0 { 1.0 / 2_f64.sqrt() } else { 1.0 };
+                let cv = if v == 0 { 1.0 / 2_f64.sqrt() } else { 1.0 };
+
+                sum += cu
+                    * cv
+                    * coeff
+                    * ((2.0 * px + 1.0) * u as f64 * PI / 16.0).cos()
+                    * ((2.0 * py + 1.0) * v as f64 * PI / 16.0).cos();
+            }
+        }
+        *pixel = (sum / 4.0 + 128.0).clamp(0.0, 255.0);
+    }
+    out
+}
+
+/// Check that the luma blocks match the grayscale output of the decoder
+fn check_luma(name: &str) {
+    let data = open(name);
+
+    let mut decoder = JpegDecoder::new(ZCursor::new(&data));
+    let components = decoder.decode_coefficients().unwrap();
+    let info = decoder.info().unwrap();
+    let (width, height) = (usize::from(info.width), usize::from(info.height));
+
+    let options = DecoderOptions::default().jpeg_set_out_colorspace(ColorSpace::Luma);
+    let pixels = JpegDecoder::new_with_options(ZCursor::new(&data), options)
+        .decode()
+        .unwrap();
+
+    let luma = &components[0];
+    assert_eq!(
+        luma.coefficients.len(),
+        luma.width_in_blocks * luma.height_in_blocks * 64
+    );
+    assert!(luma.width_in_blocks * 8 >= width);
+
+    for (bx, by) in [(0, 0), (3, 2), ((width - 1) / 8, (height - 1) / 8)] {
+        let expected = reference_idct(luma, bx, by);
+
+        for (pos, value) in expected.iter().enumerate() {
+            let (x, y) = (bx * 8 + pos % 8, by * 8 + pos / 8);
+
+            if x < width && y < height {
+                let found = f64::from(pixels[y * width + x]);
+                assert!(
+                    (found - value).abs() <= 2.0,
+                    "{name}: pixel ({x},{y}) expected {value} found {found}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn baseline_coefficients() {
+    check_luma("sampling_factors.jpg");
+}
+
+#[test]
+fn progressive_coefficients() {
+    check_luma("down_sampled_grayscale_prog.jpg");
+}
+
+#[test]
+fn coefficient_layout() {
+    let data = open("sampling_factors.jpg");
+    let mut decoder = JpegDecoder::new(ZCursor::new(&data));
+    let components = decoder.decode_coefficients().unwrap();
+
+    assert_eq!(components.len(), 3);
+
+    let luma = &components[0];
+    for chroma in &components[1..] {
+        // chroma blocks cover as many pixels as luma blocks
+        // scaled by the sampling factors
+        assert_eq!(
+            chroma.width_in_blocks * luma.horizontal_sample,
+            luma.width_in_blocks * chroma.horizontal_sample
+        );
+        assert_eq!(
+            chroma.height_in_blocks * luma.vertical_sample,
+            luma.height_in_blocks * chroma.vertical_sample
+        );
+    }
+}
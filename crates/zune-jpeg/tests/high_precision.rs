@@ -0,0 +1,161 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! 12-bit and lossless images
+//!
+//! The 12-bit images were created with a 12-bit build of libjpeg and the
+//! reference images are its output. The lossless images were encoded from
+//! their reference images.
+
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::ZCursor;
+use zune_core::colorspace::ColorSpace;
+use zune_core::options::DecoderOptions;
+use zune_core::result::DecodingResult;
+use zune_jpeg::JpegDecoder;
+use zune_ppm::PPMDecoder;
+
+fn open(name: &str) -> Vec<u8> {
+    let path = env!("CARGO_MANIFEST_DIR").to_string() + "/../../test-images/jpeg/" + name;
+    std::fs::read(path).unwrap()
+}
+
+fn open_reference(name: &str) -> Vec<u16> {
+    match PPMDecoder::new(ZCursor::new(open(name))).decode().unwrap() {
+        DecodingResult::U16(samples) => samples,
+        DecodingResult::U8(samples) => samples.into_iter().map(u16::from).collect(),
+        _ => unreachable!()
+    }
+}
+
+#[test]
+fn twelve_bit_images() {
+    for (image, reference) in [
+        ("twelve_bit_444.jpg", "twelve_bit_444.ppm"),
+        ("twelve_bit_420.jpg", "twelve_bit_420.ppm"),
+        ("twelve_bit_progressive.jpg", "twelve_bit_progressive.ppm"),
+        ("twelve_bit_grayscale.jpg", "twelve_bit_grayscale.pgm")
+    ] {
+        let mut decoder = JpegDecoder::new(ZCursor::new(open(image)));
+        decoder.decode_headers().unwrap();
+
+        assert_eq!(decoder.info().unwrap().precision, 12);
+        assert_eq!(decoder.bit_depth(), Some(BitDepth::Sixteen));
+
+        let expected = open_reference(reference);
+        let actual = decoder.decode_u16().unwrap();
+
+        assert_eq!(expected.len(), actual.len(), "{image}");
+
+        // upsampling and color conversion round differently from libjpeg
+        let max_diff = expected
+            .iter()
+            .zip(&actual)
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap();
+        assert!(max_diff <= 4, "{image}: {max_diff}");
+    }
+}
+
+#[test]
+fn lossless_images() {
+    for (image, reference, precision) in [
+        (
+            "lossless_16bit_grayscale.jpg",
+            "lossless_16bit_grayscale.pgm",
+            16
+        ),
+        (
+            "lossless_12bit_grayscale.jpg",
+            "lossless_12bit_grayscale.pgm",
+            12
+        ),
+        ("lossless_rgb_restart.jpg", "lossless_rgb_restart.ppm", 8),
+        // one scan per component, with a point transform of 2
+        ("lossless_rgb_scans.jpg", "lossless_rgb_scans.ppm", 8)
+    ] {
+        let mut decoder = JpegDecoder::new(ZCursor::new(open(image)));
+        let actual = decoder.decode_u16().unwrap();
+
+        assert_eq!(decoder.info().unwrap().precision, precision);
+        assert!(actual == open_reference(reference), "{image}");
+        assert!(decoder.warnings().is_empty(), "{image}");
+    }
+}
+
+#[test]
+fn decode_any_depth() {
+    let mut decoder = JpegDecoder::new(ZCursor::new(open("lossless_16bit_grayscale.jpg")));
+    let samples = decoder.decode_any().unwrap();
+    assert!(samples.u16().unwrap() == open_reference("lossless_16bit_grayscale.pgm"));
+
+    let mut decoder = JpegDecoder::new(ZCursor::new(open("lossless_rgb_restart.jpg")));
+    let samples = decoder.decode_any().unwrap();
+    let expected = open_reference("lossless_rgb_restart.ppm");
+    assert!(samples
+        .u8()
+        .unwrap()
+        .iter()
+        .zip(&expected)
+        .all(|(a, b)| u16::from(*a) == *b));
+}
+
+#[test]
+fn decode_twelve_bit_to_u8() {
+    let options = DecoderOptions::default().jpeg_set_out_colorspace(ColorSpace::RGBA);
+
+    let mut decoder =
+        JpegDecoder::new_with_options(ZCursor::new(open("twelve_bit_420.jpg")), options);
+    let samples = decoder.decode_u16().unwrap();
+
+    let mut decoder =
+        JpegDecoder::new_with_options(ZCursor::new(open("twelve_bit_420.jpg")), options);
+    let pixels = decoder.decode().unwrap();
+
+    assert_eq!(samples.len(), pixels.len());
+    assert!(samples
+        .iter()
+        .zip(&pixels)
+        .all(|(a, b)| (a >> 4) as u8 == *b));
+    assert!(pixels.chunks_exact(4).all(|x| x[3] == 255));
+}
+
+#[test]
+fn truncated_lossless_image() {
+    let mut data = open("lossless_rgb_restart.jpg");
+    data.truncate(data.len() / 2);
+
+    let mut decoder = JpegDecoder::new(ZCursor::new(&data));
+    decoder.decode_u16().unwrap();
+    assert!(decoder.is_truncated());
+
+    let options = DecoderOptions::default().set_strict_mode(true);
+    let mut decoder = JpegDecoder::new_with_options(ZCursor::new(&data), options);
+    assert!(decoder.decode_u16().is_err());
+}
+
+#[test]
+fn corrupt_twelve_bit_coefficients() {
+    // single byte changes that made the IDCT overflow, the result doesn't
+    // matter as long as decoding doesn't panic
+    for (image, position, value) in [
+        ("twelve_bit_420.jpg", 1031, 14),
+        ("twelve_bit_420.jpg", 373, 220),
+        ("twelve_bit_420.jpg", 91, 237),
+        ("twelve_bit_444.jpg", 2286, 31),
+        ("twelve_bit_444.jpg", 488, 14),
+        ("twelve_bit_grayscale.jpg", 240, 223),
+        ("twelve_bit_grayscale.jpg", 248, 127)
+    ] {
+        let mut data = open(image);
+        data[position] = value;
+
+        let _ = JpegDecoder::new(ZCursor::new(&data)).decode_u16();
+    }
+}
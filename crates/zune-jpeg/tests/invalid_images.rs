@@ -64,3 +64,32 @@ fn mul_with_overflow() {
         matches!(err, zune_jpeg::errors::DecodeErrors::SofError(x) if x == "Length of start of frame differs from expected 584,value is 65281")
     );
 }
+
+#[test]
+fn truncated_image() {
+    use zune_core::options::DecoderOptions;
+    use zune_core::warnings::DecodeWarningKind;
+
+    let path = env!("CARGO_MANIFEST_DIR").to_string() + "/../../test-images/jpeg/2029.jpg";
+    let data = std::fs::read(path).unwrap();
+    let truncated = &data[..data.len() / 2];
+
+    let mut decoder = JpegDecoder::new(ZCursor::new(&data));
+    decoder.decode().unwrap();
+    assert!(!decoder.is_truncated());
+    assert!(decoder.warnings().is_empty());
+
+    let mut decoder = JpegDecoder::new(ZCursor::new(truncated));
+    let pixels = decoder.decode().unwrap();
+    assert!(decoder.is_truncated());
+    assert!(decoder
+        .warnings()
+        .iter()
+        .any(|x| x.kind() == DecodeWarningKind::Truncated));
+    // rows after the end of the data are gray
+    assert!(pixels.iter().rev().take(1000).all(|x| *x == 128));
+
+    let options = DecoderOptions::default().set_strict_mode(true);
+    let mut decoder = JpegDecoder::new_with_options(ZCursor::new(truncated), options);
+    decoder.decode().unwrap_err();
+}
@@ -0,0 +1,75 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Showing progressive images while their scans are decoded
+
+use zune_core::bytestream::ZCursor;
+use zune_jpeg::JpegDecoder;
+
+fn open(name: &str) -> Vec<u8> {
+    let path = env!("CARGO_MANIFEST_DIR").to_string() + "/../../test-images/jpeg/" + name;
+    std::fs::read(path).unwrap()
+}
+
+/// Decode an image, returning the images passed to the callback
+fn decode_scans(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut decoder = JpegDecoder::new(ZCursor::new(data));
+    decoder.decode_headers().unwrap();
+
+    let mut out = vec![0; decoder.output_buffer_size().unwrap()];
+    let mut scans = vec![];
+
+    decoder
+        .decode_into_progressive(&mut out, |image| scans.push(image.to_vec()))
+        .unwrap();
+
+    assert!(scans.last() == Some(&out));
+    scans
+}
+
+fn mean_difference(a: &[u8], b: &[u8]) -> f64 {
+    let sum: u64 = a
+        .iter()
+        .zip(b)
+        .map(|(x, y)| u64::from(x.abs_diff(*y)))
+        .sum();
+    sum as f64 / a.len() as f64
+}
+
+#[test]
+fn progressive_scans() {
+    // ten scans, the first one holding the DC coefficients of all components
+    let data = open("weird_components.jpg");
+    let expected = JpegDecoder::new(ZCursor::new(&data)).decode().unwrap();
+
+    let scans = decode_scans(&data);
+    assert_eq!(scans.len(), 10);
+    assert!(scans[9] == expected);
+
+    // every scan refines the image
+    let differences: Vec<f64> = scans
+        .iter()
+        .map(|x| mean_difference(x, &expected))
+        .collect();
+
+    assert!(
+        differences.windows(2).all(|x| x[0] >= x[1]),
+        "{differences:?}"
+    );
+    assert!(differences[0] > differences[8]);
+}
+
+#[test]
+fn baseline_single_scan() {
+    let data = open("mjpeg_huffman.jpg");
+    let expected = JpegDecoder::new(ZCursor::new(&data)).decode().unwrap();
+
+    let scans = decode_scans(&data);
+    assert_eq!(scans.len(), 1);
+    assert!(scans[0] == expected);
+}
@@ -0,0 +1,173 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Recovering from corrupt restart intervals
+//!
+//! `mjpeg_huffman.jpg` is a 1280x720 4:2:2 image with a restart interval of
+//! 80 MCUs, i.e. each interval holds a single row of 16x8 MCUs.
+
+use zune_core::bytestream::ZCursor;
+use zune_core::options::DecoderOptions;
+use zune_core::warnings::DecodeWarningKind;
+use zune_jpeg::JpegDecoder;
+
+const ROW_BYTES: usize = 1280 * 3;
+const INTERVAL_ROWS: usize = 8;
+
+fn open(name: &str) -> Vec<u8> {
+    let path = env!("CARGO_MANIFEST_DIR").to_string() + "/../../test-images/jpeg/" + name;
+    std::fs::read(path).unwrap()
+}
+
+/// Offsets of the restart markers in the first scan
+fn restart_markers(data: &[u8]) -> Vec<usize> {
+    let sos = data.windows(2).position(|x| x == [0xFF, 0xDA]).unwrap();
+    let scan_start = sos + 2 + usize::from(u16::from_be_bytes([data[sos + 2], data[sos + 3]]));
+
+    (scan_start..data.len() - 1)
+        .take_while(|&x| !(data[x] == 0xFF && data[x + 1] == 0xD9))
+        .filter(|&x| data[x] == 0xFF && (0xD0..=0xD7).contains(&data[x + 1]))
+        .collect()
+}
+
+fn decode(data: &[u8], options: DecoderOptions) -> (Vec<u8>, Vec<DecodeWarningKind>) {
+    let mut decoder = JpegDecoder::new_with_options(ZCursor::new(data), options);
+    let pixels = decoder.decode().unwrap();
+    let warnings = decoder.warnings().iter().map(|x| x.kind()).collect();
+
+    (pixels, warnings)
+}
+
+/// Check that only the given intervals differ from `expected`, and that
+/// the intervals in `gray` are mid gray
+fn check_intervals(expected: &[u8], actual: &[u8], changed: &[usize], gray: &[usize]) {
+    assert_eq!(expected.len(), actual.len());
+
+    let interval_bytes = ROW_BYTES * INTERVAL_ROWS;
+
+    for (pos, (a, b)) in expected
+        .chunks(interval_bytes)
+        .zip(actual.chunks(interval_bytes))
+        .enumerate()
+    {
+        if gray.contains(&pos) {
+            assert!(b.iter().all(|x| *x == 128), "Interval {pos} isn't gray");
+        } else if !changed.contains(&pos) {
+            assert!(a == b, "Interval {pos} differs");
+        }
+    }
+}
+
+#[test]
+fn valid_restart_intervals() {
+    for threads in [0, 4] {
+        let options = DecoderOptions::default().set_num_threads(threads);
+
+        for name in ["mjpeg_huffman.jpg", "four_components.jpg"] {
+            let (_, warnings) = decode(&open(name), options);
+            assert!(
+                !warnings.contains(&DecodeWarningKind::CorruptData),
+                "{name}: {warnings:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn parallel_decoding_matches_sequential() {
+    for name in ["mjpeg_huffman.jpg", "four_components.jpg"] {
+        let data = open(name);
+        let sequential = DecoderOptions::default().set_num_threads(0);
+
+        for threads in [2, 3, 8] {
+            let parallel = DecoderOptions::default().set_num_threads(threads);
+
+            assert!(decode(&data, sequential).0 == decode(&data, parallel).0);
+
+            let mut decoder = JpegDecoder::new_with_options(ZCursor::new(&data), sequential);
+            let expected = decoder.decode_coefficients().unwrap();
+
+            let mut decoder = JpegDecoder::new_with_options(ZCursor::new(&data), parallel);
+            let actual = decoder.decode_coefficients().unwrap();
+
+            for (a, b) in expected.iter().zip(&actual) {
+                assert!(a.coefficients == b.coefficients, "{name}");
+            }
+        }
+    }
+}
+
+#[test]
+fn corrupt_interval_is_skipped() {
+    let data = open("mjpeg_huffman.jpg");
+    let markers = restart_markers(&data);
+    let (expected, _) = decode(&data, DecoderOptions::default());
+
+    // an invalid marker at the start of interval 10
+    let mut invalid_marker = data.clone();
+    invalid_marker.splice(markers[9] + 2..markers[9] + 2, [0xFF, 0x01, 0x12, 0x34]);
+
+    // interval 10 replaced by set bits, which aren't a valid huffman code,
+    // restart markers are intact so this is also decoded in parallel
+    let mut invalid_code = data.clone();
+    let (start, end) = (markers[9] + 2, markers[10]);
+    invalid_code.splice(start..end, [0xFF, 0x00].repeat((end - start) / 2));
+
+    for corrupt in [invalid_marker, invalid_code] {
+        for threads in [0, 4] {
+            let options = DecoderOptions::default().set_num_threads(threads);
+            let (pixels, warnings) = decode(&corrupt, options);
+
+            assert!(warnings.contains(&DecodeWarningKind::CorruptData));
+            check_intervals(&expected, &pixels, &[], &[10]);
+        }
+    }
+}
+
+#[test]
+fn missing_interval_is_skipped() {
+    let data = open("mjpeg_huffman.jpg");
+    let markers = restart_markers(&data);
+    let (expected, _) = decode(&data, DecoderOptions::default());
+
+    // drop interval 10 and the restart marker after it, interval 11 is
+    // decoded in its place and found to be followed by the wrong marker
+    let mut corrupt = data.clone();
+    corrupt.drain(markers[9] + 2..markers[10] + 2);
+
+    for threads in [0, 4] {
+        let options = DecoderOptions::default().set_num_threads(threads);
+        let (pixels, warnings) = decode(&corrupt, options);
+
+        assert_eq!(warnings, [DecodeWarningKind::CorruptData]);
+        check_intervals(&expected, &pixels, &[10], &[11]);
+    }
+}
+
+#[test]
+fn corrupt_interval_strict_mode() {
+    let data = open("mjpeg_huffman.jpg");
+    let markers = restart_markers(&data);
+
+    let mut corrupt = data.clone();
+    corrupt.splice(markers[9] + 2..markers[9] + 2, [0xFF, 0x01, 0x12, 0x34]);
+
+    let mut missing = data.clone();
+    missing.drain(markers[9] + 2..markers[10] + 2);
+
+    for threads in [0, 4] {
+        let options = DecoderOptions::default()
+            .set_strict_mode(true)
+            .set_num_threads(threads);
+
+        for data in [&corrupt, &missing] {
+            let mut decoder = JpegDecoder::new_with_options(ZCursor::new(data), options);
+            assert!(decoder.decode().is_err());
+        }
+    }
+}
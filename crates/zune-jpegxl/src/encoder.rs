@@ -1293,6 +1293,10 @@ impl<'a> JxlSimpleEncoder<'a> {
             }
             // By this point, all threads have finished since we used
             // scoped threads
+            //
+            // Each group writes into its own bit writers and groups are stitched
+            // in index order, so the output does not depend on thread scheduling
+            // or thread count
 
             // remove Mutex and Arc by consuming previous vector into new one
             let group_data: Vec<[BitWriter; 4]> = Arc::try_unwrap(group_data)
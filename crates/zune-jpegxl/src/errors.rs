@@ -13,6 +13,7 @@ use core::fmt::{Debug, Formatter};
 use zune_core::bit_depth::BitDepth;
 use zune_core::bytestream::ZByteIoError;
 use zune_core::colorspace::ColorSpace;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
 
 const MAX_DIMENSIONS: usize = 1 << 30;
 
@@ -92,3 +93,16 @@ impl core::fmt::Display for JxlEncodeErrors {
 
 #[cfg(feature = "std")]
 impl std::error::Error for JxlEncodeErrors {}
+
+impl ZErrorCodeTrait for JxlEncodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::ZeroDimension(_) | Self::LengthMismatch(_, _) | Self::Generic(_) => {
+                ZErrorCode::InvalidInput
+            }
+            Self::UnsupportedColorspace(_) | Self::UnsupportedDepth(_) => ZErrorCode::Unsupported,
+            Self::TooLargeDimensions(_) => ZErrorCode::LimitsExceeded,
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
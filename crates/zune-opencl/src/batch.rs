@@ -0,0 +1,324 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Run a chain of OpenCL filters on many images
+//!
+//! Running OpenCL filters one after the other copies each image to the
+//! device and back for every filter. An [`OclBatch`] copies all images to the
+//! device once, runs every filter there and copies the results back once,
+//! which matters when transfers dominate, e.g. when creating thumbnails.
+//!
+//! All images are held in device memory at the same time, split very large
+//! batches to stay within the memory of the device.
+//!
+//! # Example
+//! ```no_run
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_opencl::batch::OclBatch;
+//!
+//! let mut images = vec![Image::fill(100_u8, ColorSpace::RGB, 1000, 800); 16];
+//!
+//! let batch = OclBatch::new()
+//!     .unwrap()
+//!     .gaussian_blur(1.0)
+//!     .resize(250, 200)
+//!     .median(1);
+//!
+//! batch.execute(&mut images).unwrap();
+//! assert!(images.iter().all(|x| x.dimensions() == (250, 200)));
+//! ```
+use ocl::{Buffer, ProQue};
+use zune_core::bit_depth::BitType;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+
+use crate::device::OclDevice;
+use crate::ocl_gaussian_blur::{enqueue_gaussian, gaussian_weights};
+use crate::ocl_img::{input_buffer, read_channel, OclType};
+use crate::ocl_median::{enqueue_median, MAX_OCL_RADIUS};
+use crate::ocl_resize::enqueue_resize;
+use crate::ocl_sobel::enqueue_sobel;
+use crate::{ocl_gaussian_blur, ocl_median, ocl_resize, ocl_sobel};
+
+/// A filter in a batch
+#[derive(Copy, Clone, Debug)]
+enum OclStage {
+    GaussianBlur(f32),
+    Resize(usize, usize),
+    Median(usize),
+    Sobel
+}
+
+impl OclStage {
+    /// Whether the stage leaves alpha channels untouched,
+    /// matching the single image filters
+    fn ignores_alpha(self) -> bool {
+        matches!(self, OclStage::Median(_) | OclStage::Sobel)
+    }
+}
+
+/// A channel held in device memory
+struct DeviceChannel<T: OclType> {
+    buffer:   Buffer<T>,
+    is_alpha: bool
+}
+
+/// The channels of an image held in device memory
+struct DeviceImage<T: OclType> {
+    channels: Vec<DeviceChannel<T>>,
+    dims:     (usize, usize)
+}
+
+enum UploadedImage {
+    U8(DeviceImage<u8>),
+    U16(DeviceImage<u16>),
+    F32(DeviceImage<f32>)
+}
+
+/// A chain of OpenCL filters run on many images with a single
+/// upload and download per image
+///
+/// Unlike the single image filters, batches don't fall back to the
+/// CPU, errors are returned instead.
+pub struct OclBatch {
+    pq:     ProQue,
+    stages: Vec<OclStage>
+}
+
+impl OclBatch {
+    /// Create an empty batch on the default device
+    ///
+    /// # Returns
+    /// - Ok(OclBatch): A batch without any filters
+    /// - Err(e): No OpenCL device is available or compiling the kernels failed
+    pub fn new() -> Result<OclBatch, ImageErrors> {
+        let device = OclDevice::default_device()
+            .ok_or(ImageErrors::GenericStr("No OpenCL device available"))?;
+
+        OclBatch::with_device(&device)
+    }
+
+    /// Create an empty batch running on `device`
+    ///
+    /// # Returns
+    /// - Ok(OclBatch): A batch without any filters
+    /// - Err(e): Compiling the kernels for `device` failed
+    pub fn with_device(device: &OclDevice) -> Result<OclBatch, ImageErrors> {
+        let src = [
+            ocl_gaussian_blur::SRC,
+            ocl_resize::SRC,
+            ocl_median::SRC,
+            ocl_sobel::SRC
+        ]
+        .join("\n");
+
+        Ok(OclBatch {
+            pq:     device.build_program(&src)?,
+            stages: vec![]
+        })
+    }
+
+    /// Add a gaussian blur, see [`OclGaussianBlur`](crate::ocl_gaussian_blur::OclGaussianBlur)
+    #[must_use]
+    pub fn gaussian_blur(mut self, sigma: f32) -> OclBatch {
+        self.stages.push(OclStage::GaussianBlur(sigma));
+        self
+    }
+
+    /// Add a bilinear resize, see [`OclResize`](crate::ocl_resize::OclResize)
+    #[must_use]
+    pub fn resize(mut self, new_width: usize, new_height: usize) -> OclBatch {
+        self.stages.push(OclStage::Resize(new_width, new_height));
+        self
+    }
+
+    /// Add a median filter, see [`OclMedian`](crate::ocl_median::OclMedian)
+    ///
+    /// The radius is limited to [`MAX_OCL_RADIUS`]
+    #[must_use]
+    pub fn median(mut self, radius: usize) -> OclBatch {
+        self.stages.push(OclStage::Median(radius));
+        self
+    }
+
+    /// Add a sobel filter, see [`OclSobel`](crate::ocl_sobel::OclSobel)
+    #[must_use]
+    pub fn sobel(mut self) -> OclBatch {
+        self.stages.push(OclStage::Sobel);
+        self
+    }
+
+    /// Run the filters on every frame of every image
+    ///
+    /// Images are only modified if the whole batch succeeds.
+    ///
+    /// # Errors
+    /// - An image has an unsupported depth
+    /// - A stage has invalid parameters, e.g. a zero resize dimension
+    /// - OpenCL errors while copying or running kernels
+    pub fn execute(&self, images: &mut [Image]) -> Result<(), ImageErrors> {
+        self.validate()?;
+
+        // enqueue all uploads first, the queue then runs the kernels of
+        // each image as soon as its data is on the device
+        let mut uploaded = images
+            .iter()
+            .map(|image| self.upload(image))
+            .collect::<Result<Vec<UploadedImage>, ImageErrors>>()?;
+
+        for image in &mut uploaded {
+            match image {
+                UploadedImage::U8(image) => self.run_stages(image)?,
+                UploadedImage::U16(image) => self.run_stages(image)?,
+                UploadedImage::F32(image) => self.run_stages(image)?
+            }
+        }
+
+        let downloaded = uploaded
+            .iter()
+            .map(|image| match image {
+                UploadedImage::U8(image) => download(image),
+                UploadedImage::U16(image) => download(image),
+                UploadedImage::F32(image) => download(image)
+            })
+            .collect::<Result<Vec<_>, ImageErrors>>()?;
+
+        for (image, (channels, (width, height))) in images.iter_mut().zip(downloaded) {
+            for (channel, new_channel) in image.channels_mut(false).into_iter().zip(channels) {
+                *channel = new_channel;
+            }
+            image.set_dimensions(width, height);
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ImageErrors> {
+        for stage in &self.stages {
+            match *stage {
+                OclStage::Resize(width, height) if width == 0 || height == 0 => {
+                    return Err(ImageErrors::GenericStr("Cannot resize to a zero dimension"));
+                }
+                OclStage::Median(radius) if radius > MAX_OCL_RADIUS => {
+                    let message = format!(
+                        "Median radius {radius} is larger than the maximum OpenCL radius {MAX_OCL_RADIUS}"
+                    );
+                    return Err(ImageErrors::GenericString(message));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn upload(&self, image: &Image) -> Result<UploadedImage, ImageErrors> {
+        match image.depth().bit_type() {
+            BitType::U8 => Ok(UploadedImage::U8(upload(&self.pq, image)?)),
+            BitType::U16 => Ok(UploadedImage::U16(upload(&self.pq, image)?)),
+            BitType::F32 => Ok(UploadedImage::F32(upload(&self.pq, image)?)),
+            d => Err(ImageErrors::ImageOperationNotImplemented("OCL Batch", d))
+        }
+    }
+
+    fn run_stages<T: OclType>(&self, image: &mut DeviceImage<T>) -> Result<(), ImageErrors> {
+        for stage in &self.stages {
+            let dims = image.dims;
+            let mut new_dims = dims;
+
+            // weights are shared by all channels of the image
+            let weights = match stage {
+                OclStage::GaussianBlur(sigma) => {
+                    Some(input_buffer(&self.pq, &gaussian_weights(*sigma))?)
+                }
+                _ => None
+            };
+
+            for channel in &mut image.channels {
+                if channel.is_alpha && stage.ignores_alpha() {
+                    continue;
+                }
+                let input = &channel.buffer;
+
+                channel.buffer = unsafe {
+                    match (*stage, &weights) {
+                        (OclStage::GaussianBlur(_), Some(weights)) => {
+                            enqueue_gaussian(&self.pq, weights, input, dims)?
+                        }
+                        (OclStage::Resize(width, height), _) => {
+                            new_dims = (width, height);
+                            enqueue_resize(&self.pq, input, dims, new_dims)?
+                        }
+                        (OclStage::Median(radius), _) => {
+                            enqueue_median(&self.pq, radius, input, dims)?
+                        }
+                        (OclStage::Sobel, _) => enqueue_sobel(&self.pq, input, dims)?,
+                        (OclStage::GaussianBlur(_), None) => unreachable!()
+                    }
+                };
+            }
+            image.dims = new_dims;
+        }
+        Ok(())
+    }
+}
+
+/// Copy the channels of every frame to the device
+fn upload<T: OclType>(pq: &ProQue, image: &Image) -> Result<DeviceImage<T>, ImageErrors> {
+    let colorspace = image.colorspace();
+    let alpha_position = colorspace.alpha_position();
+    let mut channels = vec![];
+
+    for frame in image.frames_ref() {
+        for (position, channel) in frame.channels_ref(colorspace, false).iter().enumerate() {
+            channels.push(DeviceChannel {
+                buffer:   input_buffer(pq, channel.reinterpret_as::<T>()?)?,
+                is_alpha: alpha_position == Some(position)
+            });
+        }
+    }
+    Ok(DeviceImage {
+        channels,
+        dims: image.dimensions()
+    })
+}
+
+/// Copy the channels of an image back from the device
+fn download<T: OclType>(
+    image: &DeviceImage<T>
+) -> Result<(Vec<zune_image::channel::Channel>, (usize, usize)), ImageErrors> {
+    let channels = image
+        .channels
+        .iter()
+        .map(|channel| read_channel(&channel.buffer))
+        .collect::<Result<Vec<_>, ImageErrors>>()?;
+
+    Ok((channels, image.dims))
+}
+
+#[test]
+#[cfg(feature = "tests")]
+fn test_ocl_batch() {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::ocl_resize::OclResize;
+
+    let mut images = vec![
+        Image::fill(100_u8, ColorSpace::RGBA, 100, 100),
+        Image::fill(1000_u16, ColorSpace::Luma, 64, 33),
+    ];
+    let expected = OclResize::new(20, 10)
+        .clone_and_execute(&images[0])
+        .unwrap();
+
+    let batch = OclBatch::new().unwrap().resize(20, 10).median(1);
+    batch.execute(&mut images).unwrap();
+
+    assert!(images[0] == expected);
+    assert_eq!(images[1].dimensions(), (20, 10));
+}
@@ -0,0 +1,130 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! OpenCL device enumeration and selection
+//!
+//! Filters in this crate run on [`OclDevice::default_device`] unless
+//! created with a specific device.
+//!
+//! # Example
+//! ```no_run
+//! use zune_opencl::device::{OclDevice, OclDeviceType};
+//!
+//! for device in OclDevice::all() {
+//!     println!("{} ({:?}) on {}", device.name(), device.device_type(), device.platform_name());
+//! }
+//! // prefer a CPU device
+//! let device = OclDevice::first_of_type(OclDeviceType::Cpu);
+//! ```
+use ocl::flags::DeviceType;
+use ocl::{Device, Platform, ProQue};
+use zune_image::errors::ImageErrors;
+
+use crate::propagate_ocl_error;
+
+/// The kind of an OpenCL device
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OclDeviceType {
+    Gpu,
+    Cpu,
+    /// Accelerators, e.g. FPGAs
+    Accelerator
+}
+
+impl OclDeviceType {
+    fn to_ocl(self) -> DeviceType {
+        match self {
+            OclDeviceType::Gpu => DeviceType::GPU,
+            OclDeviceType::Cpu => DeviceType::CPU,
+            OclDeviceType::Accelerator => DeviceType::ACCELERATOR
+        }
+    }
+}
+
+/// An OpenCL device filters can run on
+#[derive(Clone, Debug)]
+pub struct OclDevice {
+    platform:    Platform,
+    device:      Device,
+    device_type: OclDeviceType
+}
+
+impl OclDevice {
+    /// List all OpenCL devices on all platforms
+    ///
+    /// GPUs are listed first, followed by CPUs and accelerators.
+    ///
+    /// If OpenCL isn't available, this returns an empty list.
+    pub fn all() -> Vec<OclDevice> {
+        let platforms = Platform::list();
+        let mut devices = vec![];
+
+        for device_type in [
+            OclDeviceType::Gpu,
+            OclDeviceType::Cpu,
+            OclDeviceType::Accelerator
+        ] {
+            for platform in &platforms {
+                // platforms without devices of a type return an error
+                let Ok(found) = Device::list(platform, Some(device_type.to_ocl())) else {
+                    continue;
+                };
+                for device in found {
+                    devices.push(OclDevice {
+                        platform: *platform,
+                        device,
+                        device_type
+                    });
+                }
+            }
+        }
+        devices
+    }
+
+    /// Return the device filters use by default, the first GPU if present,
+    /// otherwise the first device
+    pub fn default_device() -> Option<OclDevice> {
+        OclDevice::all().into_iter().next()
+    }
+
+    /// Return the first device of a given type
+    pub fn first_of_type(device_type: OclDeviceType) -> Option<OclDevice> {
+        OclDevice::all()
+            .into_iter()
+            .find(|x| x.device_type == device_type)
+    }
+
+    /// Return the name of the device
+    pub fn name(&self) -> String {
+        self.device
+            .name()
+            .unwrap_or_else(|_| String::from("Unknown device"))
+    }
+
+    /// Return the name of the platform the device belongs to
+    pub fn platform_name(&self) -> String {
+        self.platform
+            .name()
+            .unwrap_or_else(|_| String::from("Unknown platform"))
+    }
+
+    /// Return the kind of device this is
+    pub fn device_type(&self) -> OclDeviceType {
+        self.device_type
+    }
+
+    /// Compile an OpenCL program for this device
+    pub(crate) fn build_program(&self, src: &str) -> Result<ProQue, ImageErrors> {
+        ProQue::builder()
+            .platform(self.platform)
+            .device(self.device)
+            .src(src)
+            .build()
+            .map_err(propagate_ocl_error)
+    }
+}
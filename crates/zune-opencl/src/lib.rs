@@ -6,9 +6,25 @@
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 
+//! OpenCL implementations of image filters
+//!
+//! Filters run on the default OpenCL device, see [`device`] to list
+//! devices and pick one.
+//!
+//! Apart from [`OclSobel`](ocl_sobel::OclSobel), filters fall back to their
+//! `zune-imageprocs` CPU versions when no OpenCL device is usable, so they can be
+//! added to pipelines unconditionally.
+//!
+//! To run a chain of filters on many images without copying them to and from
+//! the device between filters, use [`OclBatch`](batch::OclBatch).
 use zune_image::errors::ImageErrors;
 
+pub mod batch;
+pub mod device;
+pub mod ocl_gaussian_blur;
 mod ocl_img;
+pub mod ocl_median;
+pub mod ocl_resize;
 pub mod ocl_sobel;
 
 fn propagate_ocl_error(error: ocl::Error) -> ImageErrors {
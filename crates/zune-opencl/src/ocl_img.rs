@@ -5,3 +5,143 @@
  *
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
+
+//! Helpers shared by the OpenCL filters
+
+use ocl::{Buffer, MemFlags, OclPrm, ProQue};
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+
+use crate::device::OclDevice;
+use crate::propagate_ocl_error;
+
+/// A compiled OpenCL program, or nothing if OpenCL isn't usable
+/// and the filter should fall back to the CPU
+pub(crate) struct OclProgram {
+    pq:       Option<ProQue>,
+    // whether OpenCL errors while running should fall back to the CPU
+    fallback: bool
+}
+
+impl OclProgram {
+    /// Compile a program for the default device,
+    /// falling back to the CPU if there is no device or compiling fails
+    pub(crate) fn with_fallback(src: &str) -> OclProgram {
+        let pq = OclDevice::default_device().and_then(|device| device.build_program(src).ok());
+
+        OclProgram { pq, fallback: true }
+    }
+
+    /// Compile a program for `device`, errors are returned instead of
+    /// falling back to the CPU
+    pub(crate) fn for_device(device: &OclDevice, src: &str) -> Result<OclProgram, ImageErrors> {
+        Ok(OclProgram {
+            pq:       Some(device.build_program(src)?),
+            fallback: false
+        })
+    }
+
+    /// Whether the program runs on an OpenCL device
+    pub(crate) fn is_available(&self) -> bool {
+        self.pq.is_some()
+    }
+
+    /// Run `ocl_fn` on the device, or `cpu_fn` if OpenCL isn't available
+    /// or `ocl_fn` fails and fallback is enabled
+    ///
+    /// `ocl_fn` must leave the image untouched when it fails
+    pub(crate) fn run<F, C>(
+        &self, image: &mut Image, ocl_fn: F, cpu_fn: C
+    ) -> Result<(), ImageErrors>
+    where
+        F: FnOnce(&ProQue, &mut Image) -> Result<(), ImageErrors>,
+        C: FnOnce(&mut Image) -> Result<(), ImageErrors>
+    {
+        match &self.pq {
+            Some(pq) => match ocl_fn(pq, image) {
+                Err(_) if self.fallback => cpu_fn(image),
+                result => result
+            },
+            None => cpu_fn(image)
+        }
+    }
+}
+
+/// Pixel types the kernels are compiled for
+pub(crate) trait OclType: OclPrm + bytemuck::Pod {
+    /// Suffix of the kernel names for this type, e.g. `SobelFilterU8`
+    const SUFFIX: &'static str;
+}
+
+impl OclType for u8 {
+    const SUFFIX: &'static str = "U8";
+}
+
+impl OclType for u16 {
+    const SUFFIX: &'static str = "U16";
+}
+
+impl OclType for f32 {
+    const SUFFIX: &'static str = "F32";
+}
+
+/// Create a read only buffer holding a copy of `data`
+pub(crate) fn input_buffer<T: OclPrm>(pq: &ProQue, data: &[T]) -> Result<Buffer<T>, ImageErrors> {
+    Buffer::builder()
+        .queue(pq.queue().clone())
+        .flags(MemFlags::READ_ONLY)
+        .len(data.len())
+        .copy_host_slice(data)
+        .build()
+        .map_err(propagate_ocl_error)
+}
+
+/// Create an uninitialized buffer of `len` elements
+pub(crate) fn scratch_buffer<T: OclPrm>(
+    pq: &ProQue, len: usize, flags: MemFlags
+) -> Result<Buffer<T>, ImageErrors> {
+    Buffer::builder()
+        .queue(pq.queue().clone())
+        .flags(flags)
+        .len(len)
+        .build()
+        .map_err(propagate_ocl_error)
+}
+
+/// Copy the contents of `buffer` to a new channel
+pub(crate) fn read_channel<T: OclType>(buffer: &Buffer<T>) -> Result<Channel, ImageErrors> {
+    let mut channel = Channel::new_with_length::<T>(buffer.len() * core::mem::size_of::<T>());
+
+    buffer
+        .read(channel.reinterpret_as_mut::<T>()?)
+        .enq()
+        .map_err(propagate_ocl_error)?;
+
+    Ok(channel)
+}
+
+/// Replace each channel of the image with the result of `function`
+///
+/// Channels are only replaced if `function` succeeds for all of them
+pub(crate) fn map_channels<F>(
+    image: &mut Image, ignore_alpha: bool, mut function: F
+) -> Result<(), ImageErrors>
+where
+    F: FnMut(&Channel) -> Result<Channel, ImageErrors>
+{
+    let new_channels = image
+        .channels_ref(ignore_alpha)
+        .into_iter()
+        .map(&mut function)
+        .collect::<Result<Vec<Channel>, ImageErrors>>()?;
+
+    for (channel, new_channel) in image
+        .channels_mut(ignore_alpha)
+        .into_iter()
+        .zip(new_channels)
+    {
+        *channel = new_channel;
+    }
+    Ok(())
+}
@@ -0,0 +1,170 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use ocl::{Buffer, MemFlags, ProQue};
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+use zune_imageprocs::median::Median;
+
+use crate::device::OclDevice;
+use crate::ocl_img::{
+    input_buffer, map_channels, read_channel, scratch_buffer, OclProgram, OclType
+};
+use crate::propagate_ocl_error;
+
+pub(crate) const SRC: &str = include_str!("./open_cl/ocl_median.cl");
+
+/// The largest radius the OpenCL kernel supports, must match
+/// `MAX_RADIUS` in the kernel source
+pub const MAX_OCL_RADIUS: usize = 4;
+
+/// Enqueue a median filter of `input`, returning the buffer that will hold the result
+pub(crate) unsafe fn enqueue_median<T: OclType>(
+    ocl_pq: &ProQue, radius: usize, input: &Buffer<T>, dims: (usize, usize)
+) -> Result<Buffer<T>, ImageErrors> {
+    let output_image = scratch_buffer::<T>(ocl_pq, input.len(), MemFlags::READ_WRITE)?;
+
+    ocl_pq
+        .kernel_builder(format!("Median{}", T::SUFFIX))
+        .global_work_size(dims)
+        .arg(input)
+        .arg(&output_image)
+        .arg(radius as i32)
+        .arg(dims.0 as i32)
+        .arg(dims.1 as i32)
+        .build()
+        .map_err(propagate_ocl_error)?
+        .enq()
+        .map_err(propagate_ocl_error)?;
+
+    Ok(output_image)
+}
+
+unsafe fn ocl_median_generic<T: OclType>(
+    ocl_pq: &ProQue, radius: usize, channel: &Channel, dims: (usize, usize)
+) -> Result<Channel, ImageErrors> {
+    let input_image = input_buffer(ocl_pq, channel.reinterpret_as::<T>()?)?;
+    let output_image = enqueue_median(ocl_pq, radius, &input_image, dims)?;
+
+    read_channel(&output_image)
+}
+
+/// Median OpenCL filter.
+///
+/// This runs on the default OpenCL device and falls back to
+/// [`Median`] on the CPU if OpenCL isn't available or fails,
+/// use [`try_with_device`](OclMedian::try_with_device) to pick a device.
+///
+/// The kernel sorts the window of each pixel in private memory, radii
+/// larger than [`MAX_OCL_RADIUS`] always run on the CPU.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_opencl::ocl_median::OclMedian;
+///
+/// let mut image = zune_image::image::Image::fill(100_u8, ColorSpace::RGB, 100, 100);
+/// OclMedian::new(2).execute(&mut image).unwrap();
+/// ```
+pub struct OclMedian {
+    radius:  usize,
+    program: OclProgram
+}
+
+impl OclMedian {
+    /// Create a new median filter with the given radius
+    ///
+    /// This compiles the OpenCL kernel for the default device, if that fails
+    /// the filter runs on the CPU.
+    pub fn new(radius: usize) -> OclMedian {
+        OclMedian {
+            radius,
+            program: OclProgram::with_fallback(SRC)
+        }
+    }
+
+    /// Create a new median filter running on `device`
+    ///
+    /// # Returns
+    /// - Ok(OclMedian): A filter that runs on `device`, errors while running aren't
+    ///   hidden by falling back to the CPU
+    /// - Err(e): Compiling the kernel for `device` failed or the radius is larger
+    ///   than [`MAX_OCL_RADIUS`]
+    pub fn try_with_device(radius: usize, device: &OclDevice) -> Result<Self, ImageErrors> {
+        if radius > MAX_OCL_RADIUS {
+            let message = format!(
+                "Median radius {radius} is larger than the maximum OpenCL radius {MAX_OCL_RADIUS}"
+            );
+            return Err(ImageErrors::GenericString(message));
+        }
+        Ok(OclMedian {
+            radius,
+            program: OclProgram::for_device(device, SRC)?
+        })
+    }
+
+    /// Whether the filter runs on an OpenCL device instead of the CPU
+    pub fn uses_opencl(&self) -> bool {
+        self.program.is_available() && self.radius <= MAX_OCL_RADIUS
+    }
+}
+
+impl OperationsTrait for OclMedian {
+    fn name(&self) -> &'static str {
+        "OCL Median"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let dims = image.dimensions();
+        let depth = image.depth().bit_type();
+        let radius = self.radius;
+
+        if radius > MAX_OCL_RADIUS {
+            return Median::new(radius).execute_impl(image);
+        }
+
+        self.program.run(
+            image,
+            |ocl_pq, image| {
+                map_channels(image, true, |channel| unsafe {
+                    match depth {
+                        BitType::U8 => ocl_median_generic::<u8>(ocl_pq, radius, channel, dims),
+                        BitType::U16 => ocl_median_generic::<u16>(ocl_pq, radius, channel, dims),
+                        BitType::F32 => ocl_median_generic::<f32>(ocl_pq, radius, channel, dims),
+                        d => Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+                    }
+                })
+            },
+            |image| Median::new(radius).execute_impl(image)
+        )
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[test]
+#[cfg(feature = "tests")]
+fn test_ocl_median() {
+    use zune_core::colorspace::ColorSpace;
+
+    let mut image = Image::fill(100_u8, ColorSpace::RGB, 100, 100);
+    let ocl_median = OclMedian::new(2);
+
+    for d_type in ocl_median.supported_types() {
+        image.convert_depth(d_type.to_depth()).unwrap();
+        let result = ocl_median.clone_and_execute(&image).unwrap();
+        // the median of a flat image is the same image
+        assert!(result == image);
+    }
+}
@@ -0,0 +1,174 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use ocl::{Buffer, MemFlags, ProQue};
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+use zune_imageprocs::resize::{Resize, ResizeMethod};
+
+use crate::device::OclDevice;
+use crate::ocl_img::{
+    input_buffer, map_channels, read_channel, scratch_buffer, OclProgram, OclType
+};
+use crate::propagate_ocl_error;
+
+pub(crate) const SRC: &str = include_str!("./open_cl/ocl_resize.cl");
+
+/// Enqueue a resize of `input`, returning the buffer that will hold the result
+pub(crate) unsafe fn enqueue_resize<T: OclType>(
+    ocl_pq: &ProQue, input: &Buffer<T>, in_dims: (usize, usize), out_dims: (usize, usize)
+) -> Result<Buffer<T>, ImageErrors> {
+    let output_image = scratch_buffer::<T>(ocl_pq, out_dims.0 * out_dims.1, MemFlags::READ_WRITE)?;
+
+    ocl_pq
+        .kernel_builder(format!("ResizeBilinear{}", T::SUFFIX))
+        .global_work_size(out_dims)
+        .arg(input)
+        .arg(&output_image)
+        .arg(in_dims.0 as i32)
+        .arg(in_dims.1 as i32)
+        .arg(out_dims.0 as i32)
+        .arg(out_dims.1 as i32)
+        .build()
+        .map_err(propagate_ocl_error)?
+        .enq()
+        .map_err(propagate_ocl_error)?;
+
+    Ok(output_image)
+}
+
+unsafe fn ocl_resize_generic<T: OclType>(
+    ocl_pq: &ProQue, channel: &Channel, in_dims: (usize, usize), out_dims: (usize, usize)
+) -> Result<Channel, ImageErrors> {
+    let input_image = input_buffer(ocl_pq, channel.reinterpret_as::<T>()?)?;
+    let output_image = enqueue_resize(ocl_pq, &input_image, in_dims, out_dims)?;
+
+    read_channel(&output_image)
+}
+
+/// Bilinear resize OpenCL filter.
+///
+/// This runs on the default OpenCL device and falls back to
+/// bilinear [`Resize`] on the CPU if OpenCL isn't available or fails,
+/// use [`try_with_device`](OclResize::try_with_device) to pick a device.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_opencl::ocl_resize::OclResize;
+///
+/// let mut image = zune_image::image::Image::fill(100_u8, ColorSpace::RGB, 100, 100);
+/// OclResize::new(50, 25).execute(&mut image).unwrap();
+/// assert_eq!(image.dimensions(), (50, 25));
+/// ```
+pub struct OclResize {
+    new_width:  usize,
+    new_height: usize,
+    program:    OclProgram
+}
+
+impl OclResize {
+    /// Create a new resize filter
+    ///
+    /// This compiles the OpenCL kernel for the default device, if that fails
+    /// the filter runs on the CPU.
+    pub fn new(new_width: usize, new_height: usize) -> OclResize {
+        OclResize {
+            new_width,
+            new_height,
+            program: OclProgram::with_fallback(SRC)
+        }
+    }
+
+    /// Create a new resize filter running on `device`
+    ///
+    /// # Returns
+    /// - Ok(OclResize): A filter that runs on `device`, errors while running aren't
+    ///   hidden by falling back to the CPU
+    /// - Err(e): Compiling the kernel for `device` failed
+    pub fn try_with_device(
+        new_width: usize, new_height: usize, device: &OclDevice
+    ) -> Result<Self, ImageErrors> {
+        Ok(OclResize {
+            new_width,
+            new_height,
+            program: OclProgram::for_device(device, SRC)?
+        })
+    }
+
+    /// Whether the filter runs on an OpenCL device instead of the CPU
+    pub fn uses_opencl(&self) -> bool {
+        self.program.is_available()
+    }
+}
+
+impl OperationsTrait for OclResize {
+    fn name(&self) -> &'static str {
+        "OCL Resize"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let in_dims = image.dimensions();
+        let out_dims = (self.new_width, self.new_height);
+        let depth = image.depth().bit_type();
+
+        if in_dims == out_dims {
+            return Ok(());
+        }
+        if out_dims.0 == 0 || out_dims.1 == 0 {
+            return Err(ImageErrors::GenericStr("Cannot resize to a zero dimension"));
+        }
+
+        self.program.run(
+            image,
+            |ocl_pq, image| {
+                map_channels(image, false, |channel| unsafe {
+                    match depth {
+                        BitType::U8 => ocl_resize_generic::<u8>(ocl_pq, channel, in_dims, out_dims),
+                        BitType::U16 => {
+                            ocl_resize_generic::<u16>(ocl_pq, channel, in_dims, out_dims)
+                        }
+                        BitType::F32 => {
+                            ocl_resize_generic::<f32>(ocl_pq, channel, in_dims, out_dims)
+                        }
+                        d => Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+                    }
+                })?;
+                image.set_dimensions(self.new_width, self.new_height);
+                Ok(())
+            },
+            |image| {
+                Resize::new(self.new_width, self.new_height, ResizeMethod::Bilinear)
+                    .execute_impl(image)
+            }
+        )
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[test]
+#[cfg(feature = "tests")]
+fn test_ocl_resize() {
+    use zune_core::colorspace::ColorSpace;
+
+    let mut image = Image::fill(100_u8, ColorSpace::RGBA, 100, 100);
+    let ocl_resize = OclResize::new(37, 120);
+
+    for d_type in ocl_resize.supported_types() {
+        image.convert_depth(d_type.to_depth()).unwrap();
+        let result = ocl_resize.clone_and_execute(&image).unwrap();
+        assert_eq!(result.dimensions(), (37, 120));
+    }
+}
@@ -14,8 +14,33 @@ use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
 
+use crate::device::OclDevice;
+use crate::ocl_img::{scratch_buffer, OclType};
 use crate::propagate_ocl_error;
 
+pub(crate) const SRC: &str = include_str!("./open_cl/ocl_sobel.cl");
+
+/// Enqueue a sobel filter of `input`, returning the buffer that will hold the result
+pub(crate) unsafe fn enqueue_sobel<T: OclType>(
+    ocl_pq: &ProQue, input: &ocl::Buffer<T>, dims: (usize, usize)
+) -> Result<ocl::Buffer<T>, ImageErrors> {
+    let output_image = scratch_buffer::<T>(ocl_pq, input.len(), ocl::MemFlags::READ_WRITE)?;
+
+    ocl_pq
+        .kernel_builder(format!("SobelFilter{}", T::SUFFIX))
+        .global_work_size(dims)
+        .arg(input)
+        .arg(&output_image)
+        .arg(dims.0 as i32)
+        .arg(dims.1 as i32)
+        .build()
+        .map_err(propagate_ocl_error)?
+        .enq()
+        .map_err(propagate_ocl_error)?;
+
+    Ok(output_image)
+}
+
 unsafe fn ocl_deriv_generic<T: OclPrm + Copy + bytemuck::Pod>(
     ocl_pq: &ocl::ProQue, name: &'static str, ref_channel: &Channel, mut_channel: &mut Channel,
     dims: (usize, usize)
@@ -76,7 +101,7 @@ unsafe fn ocl_deriv_generic<T: OclPrm + Copy + bytemuck::Pod>(
 /// use zune_opencl::ocl_sobel::OclSobel;
 /// // create an image with color type  RGB 100x1000
 /// let mut image = zune_image::image::Image::fill(100_u8,
-///     ColorSpace::RGB, 100, 100).unwrap();
+///     ColorSpace::RGB, 100, 100);
 /// // execute
 /// OclSobel::try_new().unwrap().execute(&mut image).unwrap();
 ///
@@ -102,7 +127,7 @@ impl OclSobel {
     /// we can't build
     pub fn try_new() -> Result<Self, ImageErrors> {
         let ocl_pq = ProQue::builder()
-            .src(include_str!("./open_cl/ocl_sobel.cl"))
+            .src(SRC)
             .build()
             .map_err(propagate_ocl_error)?;
 
@@ -110,6 +135,19 @@ impl OclSobel {
             pq: Mutex::new(ocl_pq)
         })
     }
+
+    /// Try to create a new sobel filter running on `device`
+    ///
+    /// # Returns.
+    /// - Ok(OclSobel): OpenCL sobel kernel runner.
+    /// - Err(e): Compiling the opencl kernel for `device` raised an error.
+    pub fn try_with_device(device: &OclDevice) -> Result<Self, ImageErrors> {
+        let ocl_pq = device.build_program(SRC)?;
+
+        Ok(OclSobel {
+            pq: Mutex::new(ocl_pq)
+        })
+    }
 }
 
 impl zune_image::traits::OperationsTrait for OclSobel {
@@ -181,7 +219,7 @@ fn test_ocr_sobel() {
     use zune_core::colorspace::ColorSpace;
     use zune_image::traits::OperationsTrait;
     // test for all supported bit types and whether they work.
-    let mut image = Image::fill(100_u8, ColorSpace::RGB, 100, 100).unwrap();
+    let mut image = Image::fill(100_u8, ColorSpace::RGB, 100, 100);
     let ocl_sobel = OclSobel::try_new().unwrap();
 
     for d_type in ocl_sobel.supported_types() {
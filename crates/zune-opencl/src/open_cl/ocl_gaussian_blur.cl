@@ -0,0 +1,54 @@
+// Separable gaussian blur, the horizontal pass writes floats to an intermediate
+// buffer which the vertical pass reads and converts back to the image type.
+// Pixels outside the image are clamped to the nearest edge.
+
+#define GAUSSIAN_BLUR(SUFFIX, T, CONVERT)                                           \
+__kernel void GaussianHorizontal##SUFFIX(                                          \
+    global const T* inputImage,                                                    \
+    global float* outputImage,                                                     \
+    constant float* weights,                                                       \
+    const int radius,                                                              \
+    const int width,                                                               \
+    const int height)                                                              \
+{                                                                                  \
+    int x = get_global_id(0);                                                      \
+    int y = get_global_id(1);                                                      \
+                                                                                   \
+    if (x < width && y < height)                                                   \
+    {                                                                              \
+        float sum = 0.0f;                                                          \
+        for (int i = -radius; i <= radius; i++)                                    \
+        {                                                                          \
+            int sx = clamp(x + i, 0, width - 1);                                   \
+            sum += weights[i + radius] * (float)inputImage[y * width + sx];        \
+        }                                                                          \
+        outputImage[y * width + x] = sum;                                          \
+    }                                                                              \
+}                                                                                  \
+                                                                                   \
+__kernel void GaussianVertical##SUFFIX(                                            \
+    global const float* inputImage,                                                \
+    global T* outputImage,                                                         \
+    constant float* weights,                                                       \
+    const int radius,                                                              \
+    const int width,                                                               \
+    const int height)                                                              \
+{                                                                                  \
+    int x = get_global_id(0);                                                      \
+    int y = get_global_id(1);                                                      \
+                                                                                   \
+    if (x < width && y < height)                                                   \
+    {                                                                              \
+        float sum = 0.0f;                                                          \
+        for (int i = -radius; i <= radius; i++)                                    \
+        {                                                                          \
+            int sy = clamp(y + i, 0, height - 1);                                  \
+            sum += weights[i + radius] * inputImage[sy * width + x];               \
+        }                                                                          \
+        outputImage[y * width + x] = CONVERT(sum);                                 \
+    }                                                                              \
+}
+
+GAUSSIAN_BLUR(U8, uchar, convert_uchar_sat_rte)
+GAUSSIAN_BLUR(U16, ushort, convert_ushort_sat_rte)
+GAUSSIAN_BLUR(F32, float, )
@@ -0,0 +1,47 @@
+// Median filter, the window around each pixel is insertion sorted
+// in private memory, so the radius is limited to MAX_RADIUS.
+// Pixels outside the image are clamped to the nearest edge.
+
+#define MAX_RADIUS 4
+#define MAX_WINDOW ((2 * MAX_RADIUS + 1) * (2 * MAX_RADIUS + 1))
+
+#define MEDIAN(SUFFIX, T)                                                           \
+__kernel void Median##SUFFIX(                                                      \
+    global const T* inputImage,                                                    \
+    global T* outputImage,                                                         \
+    const int radius,                                                              \
+    const int width,                                                               \
+    const int height)                                                              \
+{                                                                                  \
+    int x = get_global_id(0);                                                      \
+    int y = get_global_id(1);                                                      \
+                                                                                   \
+    if (x < width && y < height && radius <= MAX_RADIUS)                           \
+    {                                                                              \
+        T window[MAX_WINDOW];                                                      \
+        int n = 0;                                                                 \
+                                                                                   \
+        for (int j = -radius; j <= radius; j++)                                    \
+        {                                                                          \
+            int sy = clamp(y + j, 0, height - 1);                                  \
+            for (int i = -radius; i <= radius; i++)                                \
+            {                                                                      \
+                int sx = clamp(x + i, 0, width - 1);                               \
+                T value = inputImage[sy * width + sx];                             \
+                int k = n;                                                         \
+                while (k > 0 && window[k - 1] > value)                             \
+                {                                                                  \
+                    window[k] = window[k - 1];                                     \
+                    k--;                                                           \
+                }                                                                  \
+                window[k] = value;                                                 \
+                n++;                                                               \
+            }                                                                      \
+        }                                                                          \
+        outputImage[y * width + x] = window[n / 2];                                \
+    }                                                                              \
+}
+
+MEDIAN(U8, uchar)
+MEDIAN(U16, ushort)
+MEDIAN(F32, float)
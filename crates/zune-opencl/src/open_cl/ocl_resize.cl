@@ -0,0 +1,43 @@
+// Bilinear resize, output pixel centers are mapped to the input image
+// and the four nearest input pixels are interpolated.
+
+#define RESIZE_BILINEAR(SUFFIX, T, CONVERT)                                         \
+__kernel void ResizeBilinear##SUFFIX(                                              \
+    global const T* inputImage,                                                    \
+    global T* outputImage,                                                         \
+    const int inWidth,                                                             \
+    const int inHeight,                                                            \
+    const int outWidth,                                                            \
+    const int outHeight)                                                           \
+{                                                                                  \
+    int x = get_global_id(0);                                                      \
+    int y = get_global_id(1);                                                      \
+                                                                                   \
+    if (x < outWidth && y < outHeight)                                             \
+    {                                                                              \
+        float scaleX = (float)inWidth / (float)outWidth;                           \
+        float scaleY = (float)inHeight / (float)outHeight;                         \
+                                                                                   \
+        float sx = clamp(((float)x + 0.5f) * scaleX - 0.5f, 0.0f, (float)(inWidth - 1));  \
+        float sy = clamp(((float)y + 0.5f) * scaleY - 0.5f, 0.0f, (float)(inHeight - 1)); \
+                                                                                   \
+        int x0 = (int)sx;                                                          \
+        int y0 = (int)sy;                                                          \
+        int x1 = min(x0 + 1, inWidth - 1);                                         \
+        int y1 = min(y0 + 1, inHeight - 1);                                        \
+                                                                                   \
+        float fx = sx - (float)x0;                                                 \
+        float fy = sy - (float)y0;                                                 \
+                                                                                   \
+        float top = mix((float)inputImage[y0 * inWidth + x0],                      \
+                        (float)inputImage[y0 * inWidth + x1], fx);                 \
+        float bottom = mix((float)inputImage[y1 * inWidth + x0],                   \
+                           (float)inputImage[y1 * inWidth + x1], fx);              \
+                                                                                   \
+        outputImage[y * outWidth + x] = CONVERT(mix(top, bottom, fy));             \
+    }                                                                              \
+}
+
+RESIZE_BILINEAR(U8, uchar, convert_uchar_sat_rte)
+RESIZE_BILINEAR(U16, ushort, convert_ushort_sat_rte)
+RESIZE_BILINEAR(F32, float, )
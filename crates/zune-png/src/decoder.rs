@@ -4,6 +4,7 @@
  * This software is free software; You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 
+use alloc::string::String;
 use alloc::vec::Vec;
 use alloc::{format, vec};
 use core::cmp::min;
@@ -14,6 +15,8 @@ use zune_core::colorspace::ColorSpace;
 use zune_core::log::{trace, warn};
 use zune_core::options::DecoderOptions;
 use zune_core::result::DecodingResult;
+use zune_core::warnings::{DecodeWarning, DecodeWarningKind};
+use zune_inflate::errors::DecodeErrorStatus;
 use zune_inflate::DeflateOptions;
 
 use crate::apng::{ActlChunk, FrameInfo, SingleFrame};
@@ -30,6 +33,142 @@ use crate::utils::{
     expand_palette, expand_trns, is_le
 };
 
+/// Adam7 pass origins and spacing, see the PNG specification section 8.2
+const XORIG: [usize; 7] = [0, 4, 0, 2, 0, 1, 0];
+const YORIG: [usize; 7] = [0, 0, 4, 0, 2, 0, 1];
+
+const XSPC: [usize; 7] = [8, 8, 4, 4, 2, 2, 1];
+const YSPC: [usize; 7] = [8, 8, 8, 4, 4, 2, 2];
+
+/// Called with a partially decoded image, see [`PngDecoder::decode_into_progressive`]
+type PassCallback<'a> = dyn FnMut(&[u8]) + 'a;
+
+/// How decoded rows of an image, or of an interlaced pass,
+/// are placed in the output
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum RowLayout {
+    /// Rows are stored one after the other
+    Contiguous,
+    /// Rows of 16 bit samples are stored one after the other,
+    /// keeping the top 8 bits of every sample
+    Stripped,
+    /// Rows of an Adam7 pass, every pixel is stored at its position in
+    /// an image `image_width` pixels wide
+    Interlaced {
+        pass:        usize,
+        image_width: usize,
+        strip:       bool
+    }
+}
+
+impl RowLayout {
+    /// Store `row`, the `y`'th row of `width` pixels in `out`
+    ///
+    /// Returns false if the row doesn't fit in `out`
+    fn place_row(self, row: &[u8], y: usize, width: usize, out: &mut [u8]) -> bool {
+        match self {
+            RowLayout::Contiguous => match out.get_mut(y * row.len()..(y + 1) * row.len()) {
+                Some(out_row) => {
+                    out_row.copy_from_slice(row);
+                    true
+                }
+                None => false
+            },
+            RowLayout::Stripped => {
+                let len = row.len() / 2;
+
+                match out.get_mut(y * len..(y + 1) * len) {
+                    Some(out_row) => {
+                        strip_to_8bit(row, out_row);
+                        true
+                    }
+                    None => false
+                }
+            }
+            RowLayout::Interlaced {
+                pass,
+                image_width,
+                strip
+            } => {
+                let in_bytes = row.len() / width;
+                let out_bytes = if strip { in_bytes / 2 } else { in_bytes };
+
+                let out_y = y * YSPC[pass] + YORIG[pass];
+                let mut placed = true;
+
+                for (x, pixel) in row.chunks_exact(in_bytes).enumerate() {
+                    let out_x = x * XSPC[pass] + XORIG[pass];
+                    let start = (out_y * image_width + out_x) * out_bytes;
+
+                    match out.get_mut(start..start + out_bytes) {
+                        Some(out_pixel) if strip => strip_to_8bit(pixel, out_pixel),
+                        Some(out_pixel) => out_pixel.copy_from_slice(pixel),
+                        None => placed = false
+                    }
+                }
+                placed
+            }
+        }
+    }
+}
+
+/// Convert big endian 16 bit samples to 8 bits by keeping their top 8 bits
+fn strip_to_8bit(input: &[u8], output: &mut [u8]) {
+    for (out, sample) in output.iter_mut().zip(input.chunks_exact(2)) {
+        *out = sample[0];
+    }
+}
+
+/// Return the width and height of Adam7 pass `pass` of a `width` by `height` image
+fn pass_dimensions(width: usize, height: usize, pass: usize) -> (usize, usize) {
+    let x = (width
+        .saturating_sub(XORIG[pass])
+        .saturating_add(XSPC[pass])
+        .saturating_sub(1))
+        / XSPC[pass];
+
+    let y = (height
+        .saturating_sub(YORIG[pass])
+        .saturating_add(YSPC[pass])
+        .saturating_sub(1))
+        / YSPC[pass];
+
+    (x, y)
+}
+
+/// Copy the pixels of Adam7 pass `pass` to the pixels of later passes
+/// next to them, so that a partially decoded image can be shown
+fn fill_pass_blocks(out: &mut [u8], width: usize, height: usize, pass: usize) {
+    // the pixels of a pass cover a block up to the next pixel of the pass
+    // and the pixels of earlier passes
+    let block_width = XSPC[pass] - XORIG[pass];
+    let block_height = YSPC[pass] - YORIG[pass];
+
+    let pixel_bytes = out.len() / (width * height);
+    let stride = width * pixel_bytes;
+
+    for y in (YORIG[pass]..height).step_by(YSPC[pass]) {
+        let row = &mut out[y * stride..(y + 1) * stride];
+
+        for x in (XORIG[pass]..width).step_by(XSPC[pass]) {
+            let start = x * pixel_bytes;
+            let end = min(x + block_width, width) * pixel_bytes;
+
+            for pos in (start + pixel_bytes..end).step_by(pixel_bytes) {
+                row.copy_within(start..start + pixel_bytes, pos);
+            }
+        }
+        for block_y in y + 1..min(y + block_height, height) {
+            for x in (XORIG[pass]..width).step_by(XSPC[pass]) {
+                let start = y * stride + x * pixel_bytes;
+                let end = y * stride + min(x + block_width, width) * pixel_bytes;
+
+                out.copy_within(start..end, start + (block_y - y) * stride);
+            }
+        }
+    }
+}
+
 /// A palette entry.
 ///
 /// The alpha field is used if the image has a tRNS
@@ -76,6 +215,46 @@ pub struct TimeInfo {
     pub second: u8
 }
 
+/// Physical pixel dimensions
+///
+/// Extracted from pHYs chunk
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PhysInfo {
+    /// Pixels per unit along the x axis
+    pub x_pixels_per_unit: u32,
+    /// Pixels per unit along the y axis
+    pub y_pixels_per_unit: u32,
+    /// Unit specifier, `1` means the unit is the meter while
+    /// `0` means the unit is unknown and only the pixel aspect ratio is given
+    pub unit:              u8
+}
+
+impl PhysInfo {
+    /// Return the density in dots per inch along the x and y axis,
+    /// or `None` if the unit is unknown
+    pub fn dpi(&self) -> Option<(f32, f32)> {
+        if self.unit != 1 {
+            return None;
+        }
+        // 1 inch = 0.0254 meters
+        Some((
+            self.x_pixels_per_unit as f32 * 0.0254,
+            self.y_pixels_per_unit as f32 * 0.0254
+        ))
+    }
+
+    /// Create a pHYs description from a density in dots per inch
+    /// along the x and y axis
+    pub fn from_dpi(x_dpi: f32, y_dpi: f32) -> PhysInfo {
+        // round to the nearest pixel per meter, f32::round needs std
+        PhysInfo {
+            x_pixels_per_unit: (x_dpi / 0.0254 + 0.5) as u32,
+            y_pixels_per_unit: (y_dpi / 0.0254 + 0.5) as u32,
+            unit:              1
+        }
+    }
+}
+
 /// iTXt details
 ///
 /// UTF-8 encoded text
@@ -122,6 +301,8 @@ pub struct PngInfo {
     pub interlace_method:     InterlaceMethod,
     /// Image time info
     pub time_info:            Option<TimeInfo>,
+    /// Physical pixel dimensions
+    pub phys_info:            Option<PhysInfo>,
     /// Image exif data
     pub exif:                 Option<Vec<u8>>,
     /// Icc profile
@@ -161,21 +342,23 @@ pub struct PngDecoder<T>
 where
     T: ZByteReaderTrait
 {
-    pub(crate) stream:                  ZReader<T>,
-    pub(crate) options:                 DecoderOptions,
-    pub(crate) png_info:                PngInfo,
-    pub(crate) palette:                 Vec<PLTEEntry>,
-    pub(crate) frames:                  Vec<SingleFrame>,
-    pub(crate) actl_info:               Option<ActlChunk>,
-    pub(crate) previous_stride:         Vec<u8>,
-    pub(crate) trns_bytes:              [u16; 4],
-    pub(crate) seen_hdr:                bool,
-    pub(crate) seen_ptle:               bool,
-    pub(crate) seen_headers:            bool,
-    pub(crate) seen_trns:               bool,
-    pub(crate) seen_iend:               bool,
-    pub(crate) current_frame:           usize,
-    pub(crate) called_from_decode_into: bool
+    pub(crate) stream:          ZReader<T>,
+    pub(crate) options:         DecoderOptions,
+    pub(crate) png_info:        PngInfo,
+    pub(crate) palette:         Vec<PLTEEntry>,
+    pub(crate) frames:          Vec<SingleFrame>,
+    pub(crate) actl_info:       Option<ActlChunk>,
+    pub(crate) previous_stride: Vec<u8>,
+    pub(crate) trns_bytes:      [u16; 4],
+    pub(crate) seen_hdr:        bool,
+    pub(crate) seen_ptle:       bool,
+    pub(crate) seen_headers:    bool,
+    pub(crate) seen_trns:       bool,
+    pub(crate) seen_iend:       bool,
+    pub(crate) current_frame:   usize,
+    pub(crate) row_scratch:     Vec<u8>,
+    pub(crate) is_salvaged:     bool,
+    pub(crate) warnings:        Vec<DecodeWarning>
 }
 
 impl<T: ZByteReaderTrait> PngDecoder<T> {
@@ -206,21 +389,23 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
     #[allow(unused_mut, clippy::redundant_field_names)]
     pub fn new_with_options(data: T, options: DecoderOptions) -> PngDecoder<T> {
         PngDecoder {
-            seen_hdr:                false,
-            stream:                  ZReader::new(data),
-            options:                 options,
-            palette:                 Vec::new(),
-            png_info:                PngInfo::default(),
-            actl_info:               None,
-            previous_stride:         vec![],
-            frames:                  vec![],
-            seen_ptle:               false,
-            seen_trns:               false,
-            seen_headers:            false,
-            seen_iend:               false,
-            trns_bytes:              [0; 4],
-            current_frame:           0,
-            called_from_decode_into: true
+            seen_hdr:        false,
+            stream:          ZReader::new(data),
+            options:         options,
+            palette:         Vec::new(),
+            png_info:        PngInfo::default(),
+            actl_info:       None,
+            previous_stride: vec![],
+            frames:          vec![],
+            seen_ptle:       false,
+            seen_trns:       false,
+            seen_headers:    false,
+            seen_iend:       false,
+            trns_bytes:      [0; 4],
+            current_frame:   0,
+            row_scratch:     vec![],
+            is_salvaged:     false,
+            warnings:        vec![]
         }
     }
 
@@ -318,6 +503,14 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
         self.actl_info.is_some() && self.frames.len() > self.current_frame
     }
 
+    /// Return the number of times an animated image should be played, zero
+    /// means forever
+    ///
+    /// Returns `None` if headers haven't been decoded or the image isn't animated
+    pub fn num_plays(&self) -> Option<u32> {
+        self.actl_info.map(|x| x.num_plays)
+    }
+
     /// Return true if image has more frames available
     pub fn more_frames(&self) -> bool {
         self.actl_info.is_some() && self.frames.len() > self.current_frame
@@ -330,9 +523,16 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
 
         let mut crc_bytes = [0; 4];
 
-        let crc_ref = self.stream.peek_at(chunk_length, 4)?;
-
-        crc_bytes.copy_from_slice(crc_ref);
+        // a truncated chunk has no crc, in salvage mode we keep
+        // whatever is left of image data
+        let is_complete = match self.stream.peek_at(chunk_length, 4) {
+            Ok(crc_ref) => {
+                crc_bytes.copy_from_slice(crc_ref);
+                true
+            }
+            Err(_) if self.options.png_get_salvage() && &chunk_type_int == b"IDAT" => false,
+            Err(e) => return Err(e.into())
+        };
 
         let crc = u32::from_be_bytes(crc_bytes);
 
@@ -356,7 +556,7 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             _ => PngChunkType::unkn
         };
 
-        if self.options.png_get_confirm_crc() {
+        if self.options.png_get_confirm_crc() && is_complete {
             use crate::crc::crc32_slice8;
 
             // go back and point to chunk type.
@@ -368,7 +568,16 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             let calc_crc = !crc32_slice8(bytes, u32::MAX);
 
             if crc != calc_crc {
-                return Err(PngDecodeErrors::BadCrc(crc, calc_crc));
+                if !self.options.png_get_salvage() {
+                    return Err(PngDecodeErrors::BadCrc(crc, calc_crc));
+                }
+                self.add_warning(
+                    DecodeWarningKind::ChecksumMismatch,
+                    format!(
+                        "CRC mismatch in {chunk_type:?} chunk, expected {crc} but found {calc_crc}"
+                    )
+                );
+                self.is_salvaged = true;
             }
             // go point after the chunk type
             // The other parts expect the bit-reader to point to the
@@ -408,9 +617,18 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             }
         }
         loop {
-            let header = self.read_chunk_header()?;
+            let header = match self.read_chunk_header() {
+                Ok(header) => header,
+                Err(e) => {
+                    self.salvage_error(e)?;
+                    break;
+                }
+            };
 
-            self.parse_header(header)?;
+            if let Err(e) = self.parse_header(header) {
+                self.salvage_error(e)?;
+                break;
+            }
 
             if header.chunk_type == PngChunkType::IEND {
                 break;
@@ -425,6 +643,30 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
         Ok(())
     }
 
+    /// Return `err` unless the decoder is in salvage mode and can
+    /// stop reading chunks, keeping everything parsed so far
+    fn salvage_error(&mut self, err: PngDecodeErrors) -> Result<(), PngDecodeErrors> {
+        if !(self.options.png_get_salvage() && self.seen_hdr) {
+            return Err(err);
+        }
+        let kind = match err {
+            PngDecodeErrors::IoErrors(_) => DecodeWarningKind::Truncated,
+            _ => DecodeWarningKind::CorruptData
+        };
+        self.add_warning(kind, format!("Stopping at corrupt chunk: {err:?}"));
+        self.is_salvaged = true;
+        // don't try reading more chunks in subsequent calls
+        self.seen_iend = true;
+        Ok(())
+    }
+
+    /// Log a warning and keep it for [`warnings`](Self::warnings)
+    pub(crate) fn add_warning(&mut self, kind: DecodeWarningKind, message: impl Into<String>) {
+        let warning = DecodeWarning::new(kind, message);
+        warn!("{}", warning);
+        self.warnings.push(warning);
+    }
+
     pub(crate) fn parse_header(&mut self, header: PngChunk) -> Result<(), PngDecodeErrors> {
         match header.chunk_type {
             PngChunkType::IHDR => {
@@ -448,6 +690,9 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             PngChunkType::tIME => {
                 self.parse_time(header)?;
             }
+            PngChunkType::pHYs => {
+                self.parse_phys(header)?;
+            }
             PngChunkType::eXIf => {
                 self.parse_exif(header)?;
             }
@@ -489,6 +734,10 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
     /// Return the number of bytes required to hold a decoded image frame
     /// decoded using the given input transformations
     ///
+    /// This is the size of the buffer [`decode_into`](Self::decode_into) needs for
+    /// every color type, bit depth and interlace method, frames of animated images
+    /// are never larger than it.
+    ///
     /// # Returns
     ///  - `Some(usize)`: Minimum size for a buffer needed to decode the image
     ///  - `None`: Indicates the image headers was not decoded.
@@ -511,25 +760,23 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             .checked_mul(out_n)?
             .checked_mul(bytes)
     }
-    /// Return the number of bytes required to hold a decoded image frame
-    /// decoded without regard to the given input transformations
+    /// Return the number of bytes required to hold the next frame decoded
+    /// using the given input transformations
     ///
-    /// # Returns
-    ///  - `Some(usize)`: Minimum size for a buffer needed to decode the image
-    ///  - `None`: Indicates the image headers was not decoded.
+    /// This is the same as [`output_buffer_size`](Self::output_buffer_size) unless
+    /// the image is animated and the frame is smaller than the image
     ///
-    /// # Panics
-    /// In case `width*height*colorspace` calculation may overflow a usize
-    fn inner_buffer_size(&self) -> Option<usize> {
+    /// # Returns
+    ///  - `Some(usize)`: Minimum size for a buffer needed to decode the frame
+    ///  - `None`: Indicates the image headers was not decoded or there are no more frames
+    fn frame_buffer_size(&self) -> Option<usize> {
         if !self.seen_hdr {
             return None;
         }
 
         let info = self.frame_info()?;
         let p_info = &self.png_info;
-        // only difference with output is here we don't care about
-        // stripping 16 bit to 8 bit
-        let bytes = if p_info.depth == 16 { 2 } else { 1 };
+        let bytes = if p_info.depth == 16 && !self.options.png_get_strip_to_8bit() { 2 } else { 1 };
 
         let out_n = self.colorspace()?.num_components();
 
@@ -569,12 +816,67 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
         self.options = options;
     }
 
+    /// Return true if the image was corrupt and the decoder
+    /// recovered from it
+    ///
+    /// This is only possible when [`DecoderOptions::png_set_salvage`] is enabled,
+    /// in that case rows that couldn't be decoded are zero and chunks after the
+    /// corruption are missing from [`info`](Self::info).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_core::options::DecoderOptions;
+    /// use zune_png::PngDecoder;
+    ///
+    /// let options = DecoderOptions::default().png_set_salvage(true);
+    /// let mut decoder = PngDecoder::new_with_options(ZCursor::new(&[]), options);
+    ///
+    /// let pixels = decoder.decode_raw().unwrap();
+    ///
+    /// if decoder.is_salvaged() {
+    ///     println!("Image is corrupt, recovered {} bytes", pixels.len());
+    /// }
+    /// ```
+    pub const fn is_salvaged(&self) -> bool {
+        self.is_salvaged
+    }
+
+    /// Return problems the decoder found and recovered from so far
+    ///
+    /// This includes invalid ancillary chunks which were skipped and, in salvage mode,
+    /// the corruption the decoder stopped at. An empty list means the file looked fine.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_png::PngDecoder;
+    ///
+    /// let mut decoder = PngDecoder::new(ZCursor::new(&[]));
+    /// decoder.decode_raw().unwrap();
+    ///
+    /// for warning in decoder.warnings() {
+    ///     println!("{warning}");
+    /// }
+    /// ```
+    pub fn warnings(&self) -> &[DecodeWarning] {
+        &self.warnings
+    }
+
     /// Decode PNG encoded images and write raw pixels into `out`
     ///
     /// # Arguments
     /// - `out`: The slice which we will write our values into.
     ///         If the slice length is smaller than [`output_buffer_size`](Self::output_buffer_size), it's an error
     ///
+    /// # Allocations
+    /// Pixels are written to `out` directly for every color type, bit depth and interlace
+    /// method, the only allocations are the decompressed image data and scratch space
+    /// for a few rows, so `out` can come from an arena or be reused between images.
+    ///
+    /// For animated images, frames smaller than the image only fill the start of `out`,
+    /// their dimensions are given by [`frame_info`](Self::frame_info)
+    ///
     /// # Converting 16 bit to 8 bit images
     /// When indicated by  [`DecoderOptions::png_set_strip_to_8bit`](zune_core::options::DecoderOptions::png_get_strip_to_8bit)
     /// the library will implicitly convert 16 bit to 8 bit by discarding the lower 8 bits
@@ -589,33 +891,46 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
     ///   samples to little endian
     ///
     pub fn decode_into(&mut self, out: &mut [u8]) -> Result<(), PngDecodeErrors> {
-        // decode headers
-        self.decode_headers()?;
-
-        // in case we are to decode from 16 bit to 8 bit, allocate separate and decode
-        if self.called_from_decode_into
-            && self.png_info.depth == 16
-            && self.options.png_get_strip_to_8bit()
-        {
-            let image_len = self.output_buffer_size().unwrap();
+        self.decode_into_inner(out, None)
+    }
 
-            if out.len() < image_len {
-                return Err(PngDecodeErrors::TooSmallOutput(image_len, out.len()));
-            }
-            // allocate new size
-            let mut temp_alloc = vec![0; self.inner_buffer_size().unwrap()];
-            self.decode_into_inner(&mut temp_alloc)?;
-
-            let out = &mut out[..image_len];
-            // then convert it to 8 bit by taking top bit
-            for (input, output) in temp_alloc.chunks_exact(2).zip(out) {
-                *output = input[0];
-            }
-            return Ok(());
-        }
-        self.decode_into_inner(out)
+    /// Decode PNG encoded images into `out`, calling `on_pass` with the image decoded
+    /// so far after every pass of an interlaced image
+    ///
+    /// This allows showing a low resolution preview which gets refined as the
+    /// seven Adam7 passes are decoded, pixels which are yet to be decoded are filled
+    /// with the nearest decoded pixel. The last call is with the fully decoded image,
+    /// which is also what `out` contains when this returns. Images which aren't
+    /// interlaced only have one pass, so `on_pass` is called once.
+    ///
+    /// Buffer size, endianness and 16 bit to 8 bit conversion work as
+    /// in [`decode_into`](Self::decode_into)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_png::PngDecoder;
+    ///
+    /// let mut decoder = PngDecoder::new(ZCursor::new(&[]));
+    /// decoder.decode_headers().unwrap();
+    ///
+    /// let mut out = vec![0; decoder.output_buffer_size().unwrap()];
+    ///
+    /// decoder
+    ///     .decode_into_progressive(&mut out, |image| {
+    ///         // show the preview
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn decode_into_progressive<F: FnMut(&[u8])>(
+        &mut self, out: &mut [u8], mut on_pass: F
+    ) -> Result<(), PngDecodeErrors> {
+        self.decode_into_inner(out, Some(&mut on_pass))
     }
-    fn decode_into_inner(&mut self, out: &mut [u8]) -> Result<(), PngDecodeErrors> {
+
+    fn decode_into_inner(
+        &mut self, out: &mut [u8], mut on_pass: Option<&mut PassCallback>
+    ) -> Result<(), PngDecodeErrors> {
         // decode headers
         self.decode_headers()?;
 
@@ -631,9 +946,14 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
         let info = self.frames[self.current_frame].fctl_info.unwrap();
 
         let png_info = self.png_info.clone();
+        // 16 bit samples are converted to 8 bit a row at a time, as rows are decoded
+        let strip = png_info.depth == 16 && self.options.png_get_strip_to_8bit();
 
-        let image_len = self.inner_buffer_size()
-            .ok_or(PngDecodeErrors::GenericStatic("Output buffer size overflowed a usize (corrupt png?)"))?;
+        let image_len = self
+            .frame_buffer_size()
+            .ok_or(PngDecodeErrors::GenericStatic(
+                "Output buffer size overflowed a usize (corrupt png?)"
+            ))?;
 
         if out.len() < image_len {
             return Err(PngDecodeErrors::TooSmallOutput(image_len, out.len()));
@@ -650,18 +970,35 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
         // we are already done with them.
 
         if png_info.interlace_method == InterlaceMethod::Standard {
-            // allocate out to be enough to hold raw decoded bytes
             let dims = self.frame_info().unwrap();
-
-            self.create_png_image_raw(&deflate_data, dims.width, dims.height, out, &png_info)?;
+            let layout = if strip { RowLayout::Stripped } else { RowLayout::Contiguous };
+
+            self.create_png_image_raw(
+                &deflate_data,
+                dims.width,
+                dims.height,
+                out,
+                &png_info,
+                layout
+            )?;
         } else if png_info.interlace_method == InterlaceMethod::Adam7 {
-            self.decode_interlaced(&deflate_data, out, &png_info, &info)?;
+            self.decode_interlaced(
+                &deflate_data,
+                out,
+                &png_info,
+                &info,
+                strip,
+                on_pass.as_deref_mut()
+            )?;
         }
 
         // convert to set endian if need be
-        if self.depth().unwrap() == BitDepth::Sixteen {
+        if self.depth().unwrap() == BitDepth::Sixteen && !strip {
             convert_be_to_target_endian_u16(out, self.byte_endian(), self.options.use_sse41());
         }
+        if let Some(on_pass) = on_pass {
+            on_pass(out);
+        }
         // one more frame decoded
         self.current_frame += 1;
         Ok(())
@@ -682,33 +1019,12 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
     ///
     pub fn decode_raw(&mut self) -> Result<Vec<u8>, PngDecodeErrors> {
         self.decode_headers()?;
-        self.called_from_decode_into = false;
 
-        // allocate
-        let new_len = self.output_buffer_size().unwrap();
-        let t = self.inner_buffer_size().unwrap();
-        let mut out: Vec<u8> = vec![0; t];
+        // allocate, when there are no more frames or the size overflows
+        // decode_into returns the error
+        let mut out: Vec<u8> = vec![0; self.frame_buffer_size().unwrap_or(0)];
         //decode
         self.decode_into(&mut out)?;
-        if self.options.png_get_strip_to_8bit() && self.png_info.depth == 16 {
-            // in case we are to convert from 16 bit to 8 bit, we can do it here
-            // we optimize it by using the same buffer the 16 bit data is stored in
-            // and implicitly converting it to 8 bit.
-            //
-            // Do note that to convert it, we only take the top 8 bits of a 16 bit.
-            // so to run [a,a,b,b,c,b,d,b] => [a,b,c,d], the write never catches on the read
-            // hence no override. which works for us
-            //
-            // then convert to 8 bit in place
-            let mut i = 0;
-            let mut j = 0;
-            while j < out.len() {
-                out[i] = out[j];
-                i += 1;
-                j += 2;
-            }
-            out.truncate(new_len);
-        }
 
         Ok(out)
     }
@@ -750,83 +1066,90 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
     }
 
     fn decode_interlaced(
-        &mut self, deflate_data: &[u8], out: &mut [u8], info: &PngInfo, frame_info: &FrameInfo
+        &mut self, deflate_data: &[u8], out: &mut [u8], info: &PngInfo, frame_info: &FrameInfo,
+        strip: bool, mut on_pass: Option<&mut PassCallback>
     ) -> Result<(), PngDecodeErrors> {
-        const XORIG: [usize; 7] = [0, 4, 0, 2, 0, 1, 0];
-        const YORIG: [usize; 7] = [0, 0, 4, 0, 2, 0, 1];
-
-        const XSPC: [usize; 7] = [8, 8, 4, 4, 2, 2, 1];
-        const YSPC: [usize; 7] = [8, 8, 8, 4, 4, 2, 2];
-
-        let bytes = if info.depth == 16 { 2 } else { 1 };
-
-        let out_n = self.colorspace().unwrap().num_components();
-
-        let new_len = frame_info.width * frame_info.height * out_n * bytes;
-
-        // A mad idea would be to make this multithreaded :)
-        // They called me a mad man - Thanos
-        let out_bytes = out_n * bytes;
-
-        // temporary space for  holding interlaced images
-        let mut final_out = vec![0_u8; new_len];
-
         let mut image_offset = 0;
 
+        // the caller shows the image after the last pass
+        let last_pass = (0..7)
+            .rev()
+            .find(|p| {
+                let (x, y) = pass_dimensions(frame_info.width, frame_info.height, *p);
+                x != 0 && y != 0
+            })
+            .unwrap_or(0);
+
         // get the maximum height and width for the whole interlace part
         for p in 0..7 {
-            let x = (frame_info
-                .width
-                .saturating_sub(XORIG[p])
-                .saturating_add(XSPC[p])
-                .saturating_sub(1))
-                / XSPC[p];
-
-            let y = (frame_info
-                .height
-                .saturating_sub(YORIG[p])
-                .saturating_add(YSPC[p])
-                .saturating_sub(1))
-                / YSPC[p];
+            let (x, mut y) = pass_dimensions(frame_info.width, frame_info.height, p);
+
+            let mut is_truncated = false;
 
             if x != 0 && y != 0 {
-                let mut image_len = usize::from(info.color.num_components()) * x;
+                let mut row_len = usize::from(info.color.num_components()) * x;
 
-                image_len *= usize::from(info.depth);
-                image_len += 7;
-                image_len /= 8;
-                image_len += 1; // filter byte
-                image_len *= y;
+                row_len *= usize::from(info.depth);
+                row_len += 7;
+                row_len /= 8;
+                row_len += 1; // filter byte
+
+                let mut image_len = row_len * y;
 
                 if image_offset + image_len > deflate_data.len() {
-                    return Err(PngDecodeErrors::GenericStatic("Too short data"));
+                    if !self.options.png_get_salvage() {
+                        return Err(PngDecodeErrors::GenericStatic("Too short data"));
+                    }
+                    // place the complete rows of this pass and skip the remaining passes
+                    y = deflate_data.len().saturating_sub(image_offset) / row_len;
+                    image_len = row_len * y;
+                    is_truncated = true;
+                    self.is_salvaged = true;
+                    self.add_warning(DecodeWarningKind::Truncated, "Too short data");
                 }
 
                 let deflate_slice = &deflate_data[image_offset..image_offset + image_len];
 
-                self.create_png_image_raw(deflate_slice, x, y, &mut final_out, info)?;
-
-                for j in 0..y {
-                    for i in 0..x {
-                        let out_y = j * YSPC[p] + YORIG[p];
-                        let out_x = i * XSPC[p] + XORIG[p];
+                // rows of the pass are placed directly at their pixels in the output
+                let layout = RowLayout::Interlaced {
+                    pass: p,
+                    image_width: frame_info.width,
+                    strip
+                };
+                self.create_png_image_raw(deflate_slice, x, y, out, info, layout)?;
 
-                        let final_start = out_y * info.width * out_bytes + out_x * out_bytes;
-                        let out_start = (j * x + i) * out_bytes;
+                image_offset += image_len;
 
-                       if let Some(e) =  out.get_mut(final_start..final_start + out_bytes) {
-e                           .copy_from_slice(&final_out[out_start..out_start + out_bytes]);
-                       } else{
-                           warn!("Malformed image, interlace cannot be placed correctly")
-                       }
+                if p != last_pass && !is_truncated {
+                    if let Some(on_pass) = on_pass.as_deref_mut() {
+                        fill_pass_blocks(out, frame_info.width, frame_info.height, p);
+                        self.show_pass(out, strip, on_pass);
                     }
                 }
-                image_offset += image_len;
+            }
+            if is_truncated {
+                break;
             }
         }
         Ok(())
     }
 
+    /// Call `on_pass` with the partially decoded image in `out`
+    fn show_pass(&self, out: &mut [u8], strip: bool, on_pass: &mut PassCallback) {
+        // samples are big endian until the image is decoded, show them in the
+        // configured endianness and then undo that
+        let swap = self.png_info.depth == 16 && !strip;
+
+        if swap {
+            convert_be_to_target_endian_u16(out, self.byte_endian(), self.options.use_sse41());
+        }
+        on_pass(out);
+
+        if swap {
+            convert_be_to_target_endian_u16(out, self.byte_endian(), self.options.use_sse41());
+        }
+    }
+
     /// Decode PNG encoded images and return the vector of raw pixels but for 16-bit images
     /// represent them in a `Vec<u16>` if  [`DecoderOptions::png_set_strip_to_8bit`](zune_core::options::DecoderOptions::png_get_strip_to_8bit)
     /// returns false
@@ -921,15 +1244,16 @@ e                           .copy_from_slice(&final_out[out_start..out_start + o
     }
     /// Create the png data from post deflated data
     ///
-    /// `out` needs to have enough space to hold data, otherwise
-    /// this will panic
+    /// Rows are placed in `out` according to `layout`, `out` needs to have
+    /// enough space to hold them, otherwise this will panic
     ///
     /// This is to allow reuse e.g interlaced images use one big allocation
     /// to and since that ends up calling this multiple times, allocation was moved
     /// away from this method to the caller of this method
     #[allow(clippy::manual_memcpy, clippy::comparison_chain)]
     fn create_png_image_raw(
-        &mut self, deflate_data: &[u8], width: usize, height: usize, out: &mut [u8], info: &PngInfo
+        &mut self, deflate_data: &[u8], width: usize, height: usize, out: &mut [u8],
+        info: &PngInfo, layout: RowLayout
     ) -> Result<(), PngDecodeErrors> {
         let use_sse4 = self.options.use_sse41();
         let use_sse2 = self.options.use_sse2();
@@ -949,15 +1273,32 @@ e                           .copy_from_slice(&final_out[out_start..out_start + o
 
         let image_len = img_width_bytes * height;
 
+        let salvage = self.options.png_get_salvage();
+
+        let mut height = height;
+
         if deflate_data.len() < image_len + height
         // account for filter bytes
         {
-            let msg = format!(
-                "Not enough pixels, expected {} but found {}",
-                image_len,
-                deflate_data.len()
+            if !salvage {
+                let msg = format!(
+                    "Not enough pixels, expected {} but found {}",
+                    image_len,
+                    deflate_data.len()
+                );
+                return Err(PngDecodeErrors::Generic(msg));
+            }
+            // only decode complete rows, the rest stay zero
+            height = deflate_data.len() / (img_width_bytes + 1);
+            self.is_salvaged = true;
+            self.add_warning(
+                DecodeWarningKind::Truncated,
+                format!(
+                    "Not enough pixels, expected {} but found {}",
+                    image_len,
+                    deflate_data.len()
+                )
             );
-            return Err(PngDecodeErrors::Generic(msg));
         }
         // do png  un-filtering
         let mut chunk_size;
@@ -985,9 +1326,7 @@ e                           .copy_from_slice(&final_out[out_start..out_start + o
         // Begin doing loop un-filtering.
         let width_stride = chunk_size - 1;
 
-        let mut prev_row_start = 0;
         let mut first_row = true;
-        let mut out_position = 0;
 
         let mut will_post_process = self.seen_trns | self.seen_ptle | (info.depth < 8);
 
@@ -999,29 +1338,37 @@ e                           .copy_from_slice(&final_out[out_start..out_start + o
         if will_post_process && self.previous_stride.len() < out_chunk_size {
             self.previous_stride.resize(out_chunk_size, 0);
         }
-        let n_components = usize::from(info.color.num_components());
+        // rows that aren't stored one after the other are un-filtered in scratch space,
+        // which holds the previous and the current row and the post processed row
+        let contiguous = layout == RowLayout::Contiguous;
+        let mut scratch = core::mem::take(&mut self.row_scratch);
+
+        if !contiguous && scratch.len() < out_chunk_size * 3 {
+            scratch.resize(out_chunk_size * 3, 0);
+        }
+        let mut corrupt_rows = 0;
+        let mut is_malformed = false;
 
         for (i, in_stride) in chunks.take(height).enumerate() {
-            // Split output into current and previous
             // current points to the start of the row where we are writing de-filtered output to
-            // prev is all rows we already wrote output to.
-
-            let (prev, mut current) = out.split_at_mut(out_position);
-
-            current = &mut current[0..out_chunk_size];
-
-            // get the previous row.
-            //Set this to a dummy to handle special case of first row, if we aren't in the first
-            // row, we actually take the real slice a line down
-            let mut prev_row: &[u8] = &[0_u8];
-
-            if !first_row {
-                // normal bit depth, use the previous row as normal
-                prev_row = &prev[prev_row_start..prev_row_start + out_chunk_size];
-                prev_row_start += out_chunk_size;
-            }
-
-            out_position += out_chunk_size;
+            // prev_row to the row above it, it's empty for the first row and the filters
+            // for the first row don't use it
+            let (prev_row, current): (&[u8], &mut [u8]) = if contiguous {
+                let (prev, current) = out.split_at_mut(i * out_chunk_size);
+
+                (
+                    &prev[prev.len().saturating_sub(out_chunk_size)..],
+                    &mut current[..out_chunk_size]
+                )
+            } else {
+                let (even, odd) = scratch[..out_chunk_size * 2].split_at_mut(out_chunk_size);
+
+                if i % 2 == 0 {
+                    (odd, even)
+                } else {
+                    (even, odd)
+                }
+            };
 
             // take filter
             let filter_byte = in_stride[0];
@@ -1029,8 +1376,19 @@ e                           .copy_from_slice(&final_out[out_start..out_start + o
             let raw = &in_stride[1..];
 
             // get its type
-            let mut filter = FilterMethod::from_int(filter_byte)
-                .ok_or_else(|| PngDecodeErrors::Generic(format!("Unknown filter {filter_byte}")))?;
+            let mut filter = match FilterMethod::from_int(filter_byte) {
+                Some(filter) => filter,
+                // corrupt row, keep going since the pixels are garbage either way
+                None if salvage => {
+                    corrupt_rows += 1;
+                    FilterMethod::None
+                }
+                None => {
+                    return Err(PngDecodeErrors::Generic(format!(
+                        "Unknown filter {filter_byte}"
+                    )))
+                }
+            };
 
             if first_row {
                 // match our filters to special filters for first row
@@ -1069,201 +1427,162 @@ e                           .copy_from_slice(&final_out[out_start..out_start + o
                 FilterMethod::Unknown => unreachable!()
             }
 
-            if will_post_process && i > 0 {
-                // run the post processor two scanlines behind so that we
-                // don't mess with any filters that require previous row
-
-                // read the row we are about to filter
-                let to_filter_row = &mut prev[(i - 1) * out_chunk_size..(i) * out_chunk_size];
-
-                if info.depth < 8 {
-                    // check if we will run any other transform
-                    let extra_transform = self.seen_ptle | self.seen_trns | add_alpha_channel;
-
-                    if extra_transform {
-                        // input data is  in_to_filter_row,
-                        // we write output to previous_stride
-                        // since other parts use previous_stride
-                        expand_bits_to_byte(
-                            width,
-                            usize::from(info.depth),
-                            n_components,
-                            self.seen_ptle,
-                            to_filter_row,
-                            &mut self.previous_stride
-                        )
-                    } else {
-                        // no extra transform, just depth upscaling, so let's
-                        // do that,
-
-                        // copy the row to a temporary space
-                        self.previous_stride[..width_stride]
-                            .copy_from_slice(&to_filter_row[..width_stride]);
-
-                        expand_bits_to_byte(
-                            width,
-                            usize::from(info.depth),
-                            n_components,
-                            self.seen_ptle,
-                            &self.previous_stride,
-                            to_filter_row
-                        )
-                    }
-                } else {
-                    // copy the row to a temporary space
-                    self.previous_stride[..width_stride]
-                        .copy_from_slice(&to_filter_row[..width_stride]);
-                }
+            if contiguous {
+                if will_post_process && i > 0 {
+                    // run the post processor two scanlines behind so that we
+                    // don't mess with any filters that require previous row
+                    let to_filter_row = &mut out[(i - 1) * out_chunk_size..i * out_chunk_size];
 
-                if self.seen_trns && self.png_info.color != PngColor::Palette {
-                    // the expansion is a trns expansion
-                    // bytes are already in position, so finish the business
-
-                    if info.depth <= 8 {
-                        expand_trns::<false>(
-                            &self.previous_stride,
-                            to_filter_row,
-                            info.color,
-                            self.trns_bytes,
-                            info.depth
-                        );
-                    } else if info.depth == 16 {
-                        // Tested by test_palette_trns_16bit.
-                        expand_trns::<true>(
-                            &self.previous_stride,
-                            to_filter_row,
-                            info.color,
-                            self.trns_bytes,
-                            info.depth
-                        );
-                    }
+                    self.post_process_row(width, to_filter_row, info, add_alpha_channel)?;
                 }
+            } else {
+                let (rows, processed) = scratch.split_at_mut(out_chunk_size * 2);
+                let mut row = &rows[(i % 2) * out_chunk_size..][..out_chunk_size];
 
-                if self.seen_ptle && self.png_info.color == PngColor::Palette {
-                    if self.palette.is_empty() {
-                        return Err(PngDecodeErrors::EmptyPalette);
-                    }
-                    let plte_entry: &[PLTEEntry; 256] = self.palette[..256].try_into().unwrap();
-
-                    // so now we have two things
-                    // the palette entries stored in self.previous_stride
-                    // the row to fill the palette sored in to_filter row,
-                    // so we can finally expand the entries
-
-                    if self.seen_trns | add_alpha_channel {
-                        // if tRNS chunk is present in paletted images, it contains
-                        // alpha byte values, so that means we create alpha data from
-                        // raw bytes
-
-                        // if we are to add alpha channel for palette images , we simply just
-                        // read four entries from the palette.
-                        //
-                        // The palette is set that the alpha channel is initialized as 255 for non alpha
-                        // images,
-                        expand_palette(&self.previous_stride, to_filter_row, plte_entry, 4);
-                    } else {
-                        // Normal expansion
-                        expand_palette(&self.previous_stride, to_filter_row, plte_entry, 3);
-                    }
-                } else if add_alpha_channel {
-                    // the image is a normal RGB/ Luma image, which we need to add the alpha channel
-                    // do it here
-                    add_alpha(
-                        &self.previous_stride,
-                        to_filter_row,
-                        self.png_info.color,
-                        self.depth().unwrap()
-                    );
+                if will_post_process {
+                    // the un-filtered row is still needed by the next row's filter
+                    let processed = &mut processed[..out_chunk_size];
+                    processed[..width_stride].copy_from_slice(&row[..width_stride]);
+
+                    self.post_process_row(width, processed, info, add_alpha_channel)?;
+                    row = processed;
                 }
+                is_malformed |= !layout.place_row(row, i, width, out);
             }
         }
 
-        if will_post_process {
-            for i in height..height + min(height, 1) {
-                let to_filter_row = &mut out[(i - 1) * out_chunk_size..i * out_chunk_size];
-
-                // check if we will run any other transform
-                let extra_transform = self.seen_ptle | self.seen_trns;
-
-                if info.depth < 8 {
-                    if extra_transform {
-                        // input data is  in_to_filter_row,
-                        // we write output to previous_stride
-                        // since other parts use previous_stride
-                        expand_bits_to_byte(
-                            width,
-                            usize::from(info.depth),
-                            n_components,
-                            self.seen_ptle,
-                            to_filter_row,
-                            &mut self.previous_stride
-                        )
-                    } else {
-                        // no extra transform, just depth upscaling, so let's
-                        // do that,
-
-                        // copy the row to a temporary space
-                        self.previous_stride[..width_stride]
-                            .copy_from_slice(&to_filter_row[..width_stride]);
-
-                        expand_bits_to_byte(
-                            width,
-                            usize::from(info.depth),
-                            n_components,
-                            self.seen_ptle,
-                            &self.previous_stride,
-                            to_filter_row
-                        )
-                    }
-                } else {
-                    // copy the row to a temporary space
-                    self.previous_stride[..width_stride]
-                        .copy_from_slice(&to_filter_row[..width_stride]);
-                }
-                if self.seen_trns && self.png_info.color != PngColor::Palette {
-                    // the expansion is a trns expansion
-                    // bytes are already in position, so finish the business
-
-                    if info.depth <= 8 {
-                        expand_trns::<false>(
-                            &self.previous_stride,
-                            to_filter_row,
-                            info.color,
-                            self.trns_bytes,
-                            info.depth
-                        );
-                    } else if info.depth == 16 {
-                        // Tested by test_palette_trns_16bit.
-                        expand_trns::<true>(
-                            &self.previous_stride,
-                            to_filter_row,
-                            info.color,
-                            self.trns_bytes,
-                            info.depth
-                        );
-                    }
-                }
-                if self.seen_ptle && self.png_info.color == PngColor::Palette {
-                    if self.palette.is_empty() {
-                        return Err(PngDecodeErrors::EmptyPalette);
-                    }
+        if contiguous && will_post_process && height > 0 {
+            let to_filter_row = &mut out[(height - 1) * out_chunk_size..height * out_chunk_size];
 
-                    let plte_entry: &[PLTEEntry; 256] = self.palette[..256].try_into().unwrap();
+            self.post_process_row(width, to_filter_row, info, add_alpha_channel)?;
+        }
+        self.row_scratch = scratch;
 
-                    if self.seen_trns | add_alpha_channel {
-                        expand_palette(&self.previous_stride, to_filter_row, plte_entry, 4);
-                    } else {
-                        expand_palette(&self.previous_stride, to_filter_row, plte_entry, 3);
-                    }
-                } else if add_alpha_channel {
-                    add_alpha(
-                        &self.previous_stride,
-                        to_filter_row,
-                        self.png_info.color,
-                        self.depth().unwrap()
-                    );
-                }
+        if corrupt_rows > 0 {
+            self.add_warning(
+                DecodeWarningKind::CorruptData,
+                format!("{corrupt_rows} rows with unknown filters")
+            );
+        }
+        if is_malformed {
+            self.add_warning(
+                DecodeWarningKind::CorruptData,
+                "Malformed image, interlace cannot be placed correctly"
+            );
+        }
+        Ok(())
+    }
+
+    /// Expand an un-filtered row of `width` pixels in place to the output
+    /// colorspace, carrying out depth upscaling, tRNS and palette expansion
+    /// and adding an alpha channel as needed
+    fn post_process_row(
+        &mut self, width: usize, to_filter_row: &mut [u8], info: &PngInfo, add_alpha_channel: bool
+    ) -> Result<(), PngDecodeErrors> {
+        let n_components = usize::from(info.color.num_components());
+
+        let mut width_stride = width * n_components;
+        width_stride *= usize::from(info.depth);
+        width_stride += 7;
+        width_stride /= 8;
+
+        if info.depth < 8 {
+            // check if we will run any other transform
+            let extra_transform = self.seen_ptle | self.seen_trns | add_alpha_channel;
+
+            if extra_transform {
+                // input data is  in_to_filter_row,
+                // we write output to previous_stride
+                // since other parts use previous_stride
+                expand_bits_to_byte(
+                    width,
+                    usize::from(info.depth),
+                    n_components,
+                    self.seen_ptle,
+                    to_filter_row,
+                    &mut self.previous_stride
+                )
+            } else {
+                // no extra transform, just depth upscaling, so let's
+                // do that,
+
+                // copy the row to a temporary space
+                self.previous_stride[..width_stride]
+                    .copy_from_slice(&to_filter_row[..width_stride]);
+
+                expand_bits_to_byte(
+                    width,
+                    usize::from(info.depth),
+                    n_components,
+                    self.seen_ptle,
+                    &self.previous_stride,
+                    to_filter_row
+                )
             }
+        } else {
+            // copy the row to a temporary space
+            self.previous_stride[..width_stride].copy_from_slice(&to_filter_row[..width_stride]);
+        }
+
+        if self.seen_trns && self.png_info.color != PngColor::Palette {
+            // the expansion is a trns expansion
+            // bytes are already in position, so finish the business
+
+            if info.depth <= 8 {
+                expand_trns::<false>(
+                    &self.previous_stride,
+                    to_filter_row,
+                    info.color,
+                    self.trns_bytes,
+                    info.depth
+                );
+            } else if info.depth == 16 {
+                // Tested by test_palette_trns_16bit.
+                expand_trns::<true>(
+                    &self.previous_stride,
+                    to_filter_row,
+                    info.color,
+                    self.trns_bytes,
+                    info.depth
+                );
+            }
+        }
+
+        if self.seen_ptle && self.png_info.color == PngColor::Palette {
+            if self.palette.is_empty() {
+                return Err(PngDecodeErrors::EmptyPalette);
+            }
+            let plte_entry: &[PLTEEntry; 256] = self.palette[..256].try_into().unwrap();
+
+            // so now we have two things
+            // the palette entries stored in self.previous_stride
+            // the row to fill the palette sored in to_filter row,
+            // so we can finally expand the entries
+
+            if self.seen_trns | add_alpha_channel {
+                // if tRNS chunk is present in paletted images, it contains
+                // alpha byte values, so that means we create alpha data from
+                // raw bytes
+
+                // if we are to add alpha channel for palette images , we simply just
+                // read four entries from the palette.
+                //
+                // The palette is set that the alpha channel is initialized as 255 for non alpha
+                // images,
+                expand_palette(&self.previous_stride, to_filter_row, plte_entry, 4);
+            } else {
+                // Normal expansion
+                expand_palette(&self.previous_stride, to_filter_row, plte_entry, 3);
+            }
+        } else if add_alpha_channel && !self.seen_trns {
+            // the image is a normal RGB/ Luma image, which we need to add the alpha channel
+            // do it here, images with a tRNS chunk already got it from the expansion
+            add_alpha(
+                &self.previous_stride,
+                to_filter_row,
+                self.png_info.color,
+                self.depth().unwrap()
+            );
         }
         Ok(())
     }
@@ -1303,8 +1622,19 @@ e                           .copy_from_slice(&final_out[out_start..out_start + o
 
         let mut decoder = zune_inflate::DeflateDecoder::new_with_options(&flat_data.fdat, option);
 
-        decoder
-            .decode_zlib()
-            .map_err(PngDecodeErrors::ZlibDecodeErrors)
+        match decoder.decode_zlib() {
+            Ok(data) => Ok(data),
+            Err(e) if self.options.png_get_salvage() => {
+                // keep what was decompressed before the error
+                let kind = match e.error {
+                    DecodeErrorStatus::InsufficientData => DecodeWarningKind::Truncated,
+                    _ => DecodeWarningKind::CorruptData
+                };
+                self.add_warning(kind, format!("Error decoding idat chunks {:?}", e.error));
+                self.is_salvaged = true;
+                Ok(e.data)
+            }
+            Err(e) => Err(PngDecodeErrors::ZlibDecodeErrors(e))
+        }
     }
 }
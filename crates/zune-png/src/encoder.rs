@@ -4,18 +4,20 @@
  * This software is free software; You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 
+use alloc::vec;
 use alloc::vec::Vec;
 
 use zune_core::bytestream::{ZByteIoError, ZByteWriterTrait, ZWriter};
 use zune_core::options::EncoderOptions;
 use zune_inflate::DeflateEncoder;
 
+use crate::apng::{BlendOp, DisposeOp};
 use crate::constants::PNG_SIGNATURE;
-use crate::decoder::PngChunk;
+use crate::decoder::{PhysInfo, PngChunk};
 use crate::enums::{FilterMethod, PngChunkType};
 use crate::filters::{choose_compression_filter, filter_scanline};
 use crate::headers::writers::{
-    write_chunk, write_exif, write_gamma, write_header_fn, write_iend, write_ihdr
+    write_chunk, write_exif, write_gamma, write_header_fn, write_iend, write_ihdr, write_phys
 };
 
 #[derive(Default)]
@@ -26,7 +28,8 @@ pub struct PngEncoder<'a> {
     pub(crate) encoded_chunks:  Vec<u8>,
     pub(crate) filter_scanline: Vec<u8>,
     pub(crate) gamma:           Option<f32>,
-    pub(crate) exif:            Option<&'a [u8]>
+    pub(crate) exif:            Option<&'a [u8]>,
+    pub(crate) phys:            Option<PhysInfo>
 }
 
 impl<'a> PngEncoder<'a> {
@@ -51,6 +54,13 @@ impl<'a> PngEncoder<'a> {
         self.exif = Some(exif);
     }
 
+    /// Add physical pixel dimensions which will be encoded in a pHYs chunk
+    ///
+    /// See [`PhysInfo::from_dpi`] to create one from a dots per inch density
+    pub fn add_phys_info(&mut self, phys: PhysInfo) {
+        self.phys = Some(phys);
+    }
+
     pub fn encode_headers<T: ZByteWriterTrait>(
         &self, writer: &mut ZWriter<T>
     ) -> Result<(), ZByteIoError> {
@@ -62,12 +72,18 @@ impl<'a> PngEncoder<'a> {
         // extra headers
         // need to check their existence because  write_header_fn will do
         // some writing even if they don't exist
+        //
+        // The order here is fixed (eXIf, gAMA then pHYs) and we never write a tIME chunk,
+        // so output is always reproducible, see EncoderOptions::set_deterministic
         if self.exif.is_some() {
             write_header_fn(self, writer, b"eXIf", write_exif)?;
         }
         if self.gamma.is_some() {
             write_header_fn(self, writer, b"gAMA", write_gamma)?;
         }
+        if self.phys.is_some() {
+            write_header_fn(self, writer, b"pHYs", write_phys)?;
+        }
         Ok(())
     }
 
@@ -172,6 +188,320 @@ impl<'a> PngEncoder<'a> {
     }
 }
 
+/// A single frame of an animated png
+struct ApngFrame<'a> {
+    data:        &'a [u8],
+    delay_num:   u16,
+    delay_denom: u16,
+    dispose_op:  DisposeOp,
+    blend_op:    BlendOp
+}
+
+/// A region of the canvas a frame is drawn to
+#[derive(Copy, Clone)]
+struct FrameRegion {
+    x:      usize,
+    y:      usize,
+    width:  usize,
+    height: usize
+}
+
+/// Encoder for animated png (APNG) images
+///
+/// All frames cover the whole canvas, with the width, height, colorspace and depth
+/// of the encoder options. The first frame is also the default image shown by decoders
+/// that don't support animations.
+///
+/// If [`EncoderOptions::png_optimize_frames`] is set, frames only store the region that
+/// changed from what the decoder displays before them
+///
+/// # Example
+/// - Encode two 10 by 10 RGBA frames, each shown for 100 ms
+/// ```
+/// use zune_core::bit_depth::BitDepth;
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_core::options::EncoderOptions;
+/// use zune_png::ApngEncoder;
+///
+/// let frame_a = [0_u8; 10 * 10 * 4];
+/// let frame_b = [255_u8; 10 * 10 * 4];
+///
+/// let options = EncoderOptions::new(10, 10, ColorSpace::RGBA, BitDepth::Eight);
+/// let mut encoder = ApngEncoder::new(options);
+///
+/// encoder.add_frame(&frame_a, 1, 10);
+/// encoder.add_frame(&frame_b, 1, 10);
+///
+/// let mut sink = vec![];
+/// encoder.encode(&mut sink).unwrap();
+/// ```
+pub struct ApngEncoder<'a> {
+    encoder:   PngEncoder<'a>,
+    frames:    Vec<ApngFrame<'a>>,
+    num_plays: u32
+}
+
+impl<'a> ApngEncoder<'a> {
+    /// Create a new animated png encoder
+    ///
+    /// # Arguments
+    /// - options: Encoder details shared by all frames, width, height, colorspace and depth
+    pub fn new(options: EncoderOptions) -> ApngEncoder<'a> {
+        ApngEncoder {
+            encoder:   PngEncoder::new(&[], options),
+            frames:    vec![],
+            num_plays: 0
+        }
+    }
+
+    /// Add a frame to the animation
+    ///
+    /// The frame replaces the canvas and is left as is after its delay
+    ///
+    /// # Arguments
+    /// - data: Pixel data, size must be equal to `width*height*colorspace channels*depth size`,
+    ///   16 bit data is expected to be in big endian
+    /// - delay_num: Frame delay numerator
+    /// - delay_denom: Frame delay denominator, a value of zero means 100, i.e hundredths of a second
+    pub fn add_frame(&mut self, data: &'a [u8], delay_num: u16, delay_denom: u16) {
+        self.add_frame_with_ops(
+            data,
+            delay_num,
+            delay_denom,
+            DisposeOp::None,
+            BlendOp::Source
+        );
+    }
+
+    /// Add a frame to the animation with custom frame operations
+    ///
+    /// # Arguments
+    /// - data: Pixel data, see [`add_frame`](Self::add_frame)
+    /// - delay_num: Frame delay numerator
+    /// - delay_denom: Frame delay denominator
+    /// - dispose_op: What happens to the canvas after the frame's delay
+    /// - blend_op: How the frame is drawn onto the canvas
+    pub fn add_frame_with_ops(
+        &mut self, data: &'a [u8], delay_num: u16, delay_denom: u16, dispose_op: DisposeOp,
+        blend_op: BlendOp
+    ) {
+        self.frames.push(ApngFrame {
+            data,
+            delay_num,
+            delay_denom,
+            dispose_op,
+            blend_op
+        });
+    }
+
+    /// Set the number of times the animation plays, zero, the default,
+    /// loops forever
+    pub fn set_num_plays(&mut self, num_plays: u32) {
+        self.num_plays = num_plays;
+    }
+
+    /// Add exif data which will be encoded
+    pub fn add_exif_segment(&mut self, exif: &'a [u8]) {
+        self.encoder.add_exif_segment(exif);
+    }
+
+    /// Add physical pixel dimensions which will be encoded in a pHYs chunk
+    pub fn add_phys_info(&mut self, phys: PhysInfo) {
+        self.encoder.add_phys_info(phys);
+    }
+
+    /// Encode all frames, writing them to sink
+    ///
+    /// # Returns
+    /// - Ok(size): Actual bytes written to sink
+    /// - Err: The error encountered during encoding
+    pub fn encode<T: ZByteWriterTrait>(&mut self, sink: T) -> Result<usize, ZByteIoError> {
+        let options = self.encoder.options;
+
+        let pixel_size = options.depth().size_of() * options.colorspace().num_components();
+        let expected_data_size = options
+            .width()
+            .checked_mul(options.height())
+            .ok_or(ZByteIoError::Generic("Overflow"))?
+            .checked_mul(pixel_size)
+            .ok_or(ZByteIoError::Generic("Overflow"))?;
+
+        if self.frames.is_empty() {
+            return Err(ZByteIoError::Generic("No frames to encode"));
+        }
+        for frame in &self.frames {
+            if frame.data.len() != expected_data_size {
+                return Err(ZByteIoError::NotEnoughBytes(
+                    expected_data_size,
+                    frame.data.len()
+                ));
+            }
+        }
+        let mut writer = ZWriter::new(sink);
+
+        self.encoder.encode_headers(&mut writer)?;
+
+        let mut actl = [0; 8];
+        actl[..4].copy_from_slice(&(self.frames.len() as u32).to_be_bytes());
+        actl[4..].copy_from_slice(&self.num_plays.to_be_bytes());
+        write_chunk(apng_chunk(b"acTL", actl.len()), &actl, &mut writer)?;
+
+        let full_region = FrameRegion {
+            x:      0,
+            y:      0,
+            width:  options.width(),
+            height: options.height()
+        };
+        // what decoders display before the current frame is drawn, or None
+        // if we can't tell, e.g after blending
+        let mut canvas = Some(vec![0_u8; expected_data_size]);
+        let mut sequence_number = 0_u32;
+
+        for (i, frame) in self.frames.iter().enumerate() {
+            let region = match &canvas {
+                // the first frame must cover the whole canvas
+                Some(canvas)
+                    if i > 0
+                        && options.png_optimize_frames()
+                        && frame.blend_op == BlendOp::Source =>
+                {
+                    changed_region(canvas, frame.data, options.width(), pixel_size)
+                }
+                _ => full_region
+            };
+            let pixels = crop(frame.data, region, options.width(), pixel_size);
+
+            let frame_options = options.set_width(region.width).set_height(region.height);
+            let mut frame_encoder = PngEncoder::new(&pixels, frame_options);
+            frame_encoder.add_filters();
+
+            let mut fctl = [0; 26];
+            fctl[0..4].copy_from_slice(&sequence_number.to_be_bytes());
+            fctl[4..8].copy_from_slice(&(region.width as u32).to_be_bytes());
+            fctl[8..12].copy_from_slice(&(region.height as u32).to_be_bytes());
+            fctl[12..16].copy_from_slice(&(region.x as u32).to_be_bytes());
+            fctl[16..20].copy_from_slice(&(region.y as u32).to_be_bytes());
+            fctl[20..22].copy_from_slice(&frame.delay_num.to_be_bytes());
+            fctl[22..24].copy_from_slice(&frame.delay_denom.to_be_bytes());
+            fctl[24] = frame.dispose_op.to_int();
+            fctl[25] = frame.blend_op.to_int();
+            write_chunk(apng_chunk(b"fcTL", fctl.len()), &fctl, &mut writer)?;
+            sequence_number += 1;
+
+            if i == 0 {
+                frame_encoder.write_idat_chunks(&mut writer)?;
+            } else {
+                for chunk in frame_encoder.encoded_chunks.chunks(8192) {
+                    let mut fdat = Vec::with_capacity(chunk.len() + 4);
+                    fdat.extend_from_slice(&sequence_number.to_be_bytes());
+                    fdat.extend_from_slice(chunk);
+
+                    write_chunk(apng_chunk(b"fdAT", fdat.len()), &fdat, &mut writer)?;
+                    sequence_number += 1;
+                }
+            }
+            // a cropped frame is only drawn over identical pixels, so the
+            // canvas always ends up matching the frame
+            let rendered = match frame.blend_op {
+                BlendOp::Source => Some(frame.data.to_vec()),
+                BlendOp::Over => None
+            };
+            match frame.dispose_op {
+                // the first frame has nothing to go back to, it's cleared instead
+                DisposeOp::Previous if i > 0 => {}
+                DisposeOp::Previous | DisposeOp::Background => {
+                    canvas = rendered.map(|mut rendered| {
+                        let stride = options.width() * pixel_size;
+
+                        for row in rendered
+                            .chunks_exact_mut(stride)
+                            .skip(region.y)
+                            .take(region.height)
+                        {
+                            row[region.x * pixel_size..(region.x + region.width) * pixel_size]
+                                .fill(0);
+                        }
+                        rendered
+                    });
+                }
+                DisposeOp::None => canvas = rendered
+            }
+        }
+        write_header_fn(&self.encoder, &mut writer, b"IEND", write_iend)?;
+
+        Ok(writer.bytes_written())
+    }
+}
+
+/// Create the header of an APNG chunk
+const fn apng_chunk(name: &[u8; 4], length: usize) -> PngChunk {
+    PngChunk {
+        length,
+        chunk_type: PngChunkType::unkn, // not needed
+        chunk: *name,
+        crc: 0 // not needed
+    }
+}
+
+/// Find the smallest region containing all pixels that differ between
+/// `previous` and `current`
+///
+/// If nothing changed, this is the top left pixel, frames can't be empty
+fn changed_region(previous: &[u8], current: &[u8], width: usize, pixel_size: usize) -> FrameRegion {
+    let stride = width * pixel_size;
+
+    let (mut min_x, mut min_y) = (usize::MAX, usize::MAX);
+    let (mut max_x, mut max_y) = (0, 0);
+
+    for (y, (prev_row, row)) in previous
+        .chunks_exact(stride)
+        .zip(current.chunks_exact(stride))
+        .enumerate()
+    {
+        if prev_row == row {
+            continue;
+        }
+        for (x, (a, b)) in prev_row
+            .chunks_exact(pixel_size)
+            .zip(row.chunks_exact(pixel_size))
+            .enumerate()
+        {
+            if a != b {
+                min_x = min_x.min(x);
+                max_x = max_x.max(x);
+            }
+        }
+        min_y = min_y.min(y);
+        max_y = y;
+    }
+    if min_y == usize::MAX {
+        return FrameRegion {
+            x:      0,
+            y:      0,
+            width:  1,
+            height: 1
+        };
+    }
+    FrameRegion {
+        x:      min_x,
+        y:      min_y,
+        width:  max_x - min_x + 1,
+        height: max_y - min_y + 1
+    }
+}
+
+/// Copy the pixels of `region` out of an image `width` pixels wide
+fn crop(data: &[u8], region: FrameRegion, width: usize, pixel_size: usize) -> Vec<u8> {
+    let stride = width * pixel_size;
+    let mut output = Vec::with_capacity(region.width * region.height * pixel_size);
+
+    for row in data.chunks_exact(stride).skip(region.y).take(region.height) {
+        output
+            .extend_from_slice(&row[region.x * pixel_size..(region.x + region.width) * pixel_size]);
+    }
+    output
+}
+
 #[test]
 fn test_simple_write() {
     use zune_core::bit_depth::BitDepth;
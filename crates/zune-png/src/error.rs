@@ -11,6 +11,7 @@ use alloc::string::String;
 use core::fmt::{Debug, Display, Formatter};
 
 use zune_core::bytestream::ZByteIoError;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
 
 /// Errors possible during decoding
 pub enum PngDecodeErrors {
@@ -94,3 +95,24 @@ impl From<ZByteIoError> for PngDecodeErrors {
         Self::IoErrors(val)
     }
 }
+
+impl ZErrorCodeTrait for PngDecodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::BadSignature
+            | Self::GenericStatic(_)
+            | Self::Generic(_)
+            | Self::BadCrc(_, _)
+            | Self::EmptyPalette => ZErrorCode::CorruptData,
+            Self::ZlibDecodeErrors(err) => match err.error {
+                zune_inflate::errors::DecodeErrorStatus::OutputLimitExceeded(_, _) => {
+                    ZErrorCode::LimitsExceeded
+                }
+                _ => ZErrorCode::CorruptData
+            },
+            Self::UnsupportedAPNGImage => ZErrorCode::Unsupported,
+            Self::TooSmallOutput(_, _) => ZErrorCode::InvalidInput,
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
@@ -7,11 +7,12 @@
 use alloc::{format, vec};
 
 use zune_core::bytestream::ZByteReaderTrait;
-use zune_core::log::{trace, warn};
+use zune_core::log::trace;
+use zune_core::warnings::DecodeWarningKind;
 use zune_inflate::DeflateDecoder;
 
 use crate::apng::{ActlChunk, BlendOp, DisposeOp, FrameInfo, SingleFrame};
-use crate::decoder::{ItxtChunk, PLTEEntry, PngChunk, TextChunk, TimeInfo, ZtxtChunk};
+use crate::decoder::{ItxtChunk, PLTEEntry, PhysInfo, PngChunk, TextChunk, TimeInfo, ZtxtChunk};
 use crate::enums::{FilterMethod, InterlaceMethod, PngChunkType, PngColor};
 use crate::error::PngDecodeErrors;
 use crate::PngDecoder;
@@ -163,10 +164,32 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
         // we will later pass these to the deflate decoder as a whole, to get the whole
         // uncompressed stream.
 
+        let salvage = self.options.png_get_salvage();
+
         let chunk = self.frames[0].chunk_mut();
-        let prev_len = chunk.len();
-        chunk.resize(chunk.len() + png_chunk.length, 0);
-        self.stream.read_exact_bytes(&mut chunk[prev_len..])?;
+
+        if salvage {
+            // keep whatever is present of a truncated chunk, reading in pieces
+            // so that a corrupt length doesn't allocate a huge buffer
+            let mut remaining = png_chunk.length;
+
+            while remaining > 0 {
+                let start = chunk.len();
+                chunk.resize(start + remaining.min(1 << 16), 0);
+
+                let bytes_read = self.stream.read_bytes(&mut chunk[start..])?;
+                chunk.truncate(start + bytes_read);
+
+                if bytes_read == 0 {
+                    break;
+                }
+                remaining -= bytes_read;
+            }
+        } else {
+            let prev_len = chunk.len();
+            chunk.resize(chunk.len() + png_chunk.length, 0);
+            self.stream.read_exact_bytes(&mut chunk[prev_len..])?;
+        }
 
         // the first frame always contains the idat chunks
         // so we push this chunk there
@@ -226,7 +249,10 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
         if gama == 0.0 {
             // this is invalid gama
             // warn and set it to 2.2 which is the default gama
-            warn!("Gamma value of 0.0 is invalid, setting it to 2.2");
+            self.add_warning(
+                DecodeWarningKind::NonConformance,
+                "Gamma value of 0.0 is invalid, setting it to 2.2"
+            );
             gama = 1.0 / 2.2;
         }
         self.png_info.gamma = Some(gama);
@@ -239,8 +265,12 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
     /// Parse the animation control chunk
     pub(crate) fn parse_actl(&mut self, chunk: PngChunk) -> Result<(), PngDecodeErrors> {
         if chunk.length != 8 {
-            warn!("Invalid chunk length for ACTL, skipping");
+            self.add_warning(
+                DecodeWarningKind::CorruptData,
+                "Invalid chunk length for ACTL, skipping"
+            );
             self.stream.skip(chunk.length + 4)?;
+            return Ok(());
         }
         // extract num_frames
         let num_frames = self.stream.get_u32_be();
@@ -264,7 +294,10 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             if self.options.strict_mode() {
                 return Err(PngDecodeErrors::GenericStatic("Invalid tIME chunk length"));
             }
-            warn!("Invalid time chunk length {:?}", chunk.length);
+            self.add_warning(
+                DecodeWarningKind::CorruptData,
+                format!("Invalid time chunk length {:?}", chunk.length)
+            );
             // skip chunk + crc
             self.stream.skip(chunk.length + 4)?;
             return Ok(());
@@ -292,8 +325,37 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
         Ok(())
     }
 
+    pub(crate) fn parse_phys(&mut self, chunk: PngChunk) -> Result<(), PngDecodeErrors> {
+        if chunk.length != 9 {
+            if self.options.strict_mode() {
+                return Err(PngDecodeErrors::GenericStatic("Invalid pHYs chunk length"));
+            }
+            self.add_warning(
+                DecodeWarningKind::CorruptData,
+                format!("Invalid pHYs chunk length {:?}", chunk.length)
+            );
+            // skip chunk + crc
+            self.stream.skip(chunk.length + 4)?;
+            return Ok(());
+        }
+
+        let x_pixels_per_unit = self.stream.get_u32_be();
+        let y_pixels_per_unit = self.stream.get_u32_be();
+        let unit = self.stream.read_u8();
+
+        self.png_info.phys_info = Some(PhysInfo {
+            x_pixels_per_unit,
+            y_pixels_per_unit,
+            unit
+        });
+        // skip past crc
+        self.stream.skip(4)?;
+
+        Ok(())
+    }
+
     pub(crate) fn parse_exif(&mut self, chunk: PngChunk) -> Result<(), PngDecodeErrors> {
-        let data = self.stream.peek_at(0, chunk.length).unwrap();
+        let data = self.stream.peek_at(0, chunk.length)?;
 
         // recommended that we check for first four bytes compatibility
         // so do it here
@@ -304,9 +366,11 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
                 return Err(PngDecodeErrors::GenericStatic(
                     "[strict-mode]: Invalid exif chunk"
                 ));
-            } else {
-                warn!("Invalid exif chunk, it doesn't start with the magic bytes")
             }
+            self.add_warning(
+                DecodeWarningKind::NonConformance,
+                "Invalid exif chunk, it doesn't start with the magic bytes"
+            );
             // do not parse
             self.stream.skip(chunk.length + 4)?;
             return Ok(());
@@ -321,7 +385,7 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
     /// Parse the iCCP chunk
     pub(crate) fn parse_iccp(&mut self, chunk: PngChunk) -> Result<(), PngDecodeErrors> {
         let length = core::cmp::min(chunk.length, 79);
-        let keyword_bytes = self.stream.peek_at(0, length).unwrap();
+        let keyword_bytes = self.stream.peek_at(0, length)?;
         let keyword_position = keyword_bytes.iter().position(|x| *x == 0);
 
         if let Some(pos) = keyword_position {
@@ -338,17 +402,22 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             let _ = self.stream.read_u8();
 
             // read remaining chunk
-            let data = self.stream.peek_at(0, remainder).unwrap();
+            let data = self.stream.peek_at(0, remainder)?;
 
             // decode to vec
-            if let Ok(icc_uncompressed) = DeflateDecoder::new(data).decode_zlib() {
-                self.png_info.icc_profile = Some(icc_uncompressed);
-            } else {
-                warn!("Could not decode ICC profile, error with zlib stream");
+            match DeflateDecoder::new(data).decode_zlib() {
+                Ok(icc_uncompressed) => self.png_info.icc_profile = Some(icc_uncompressed),
+                Err(_) => self.add_warning(
+                    DecodeWarningKind::CorruptData,
+                    "Could not decode ICC profile, error with zlib stream"
+                )
             }
             self.stream.skip(remainder)?;
         } else {
-            warn!("Could not find keyword in iCCP chunk, possibly corrupt chunk");
+            self.add_warning(
+                DecodeWarningKind::CorruptData,
+                "Could not find keyword in iCCP chunk, possibly corrupt chunk"
+            );
             // skip the length
             self.stream.skip(chunk.length)?;
         }
@@ -360,7 +429,7 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
     /// Parse the text chunk
     pub(crate) fn parse_text(&mut self, chunk: PngChunk) -> Result<(), PngDecodeErrors> {
         let length = core::cmp::min(chunk.length, 79);
-        let keyword_bytes = self.stream.peek_at(0, length).unwrap();
+        let keyword_bytes = self.stream.peek_at(0, length)?;
         let keyword_position = keyword_bytes.iter().position(|x| *x == 0);
 
         if let Some(pos) = keyword_position {
@@ -372,14 +441,17 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
 
             // read remaining chunk
 
-            let text = self.stream.peek_at(0, remainder).unwrap().to_vec();
+            let text = self.stream.peek_at(0, remainder)?.to_vec();
 
             let text_chunk = TextChunk { keyword, text };
             self.png_info.text_chunk.push(text_chunk);
 
             self.stream.skip(remainder)?;
         } else {
-            warn!("Could not find keyword in text chunk, possibly corrupt chunk");
+            self.add_warning(
+                DecodeWarningKind::CorruptData,
+                "Could not find keyword in text chunk, possibly corrupt chunk"
+            );
             // skip the length
             self.stream.skip(chunk.length)?;
         }
@@ -390,7 +462,7 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
     /// Parse the itXT chunk
     pub(crate) fn parse_itxt(&mut self, chunk: PngChunk) -> Result<(), PngDecodeErrors> {
         let length = core::cmp::min(chunk.length, 79);
-        let keyword_bytes = self.stream.peek_at(0, length).unwrap();
+        let keyword_bytes = self.stream.peek_at(0, length)?;
         let keyword_position = keyword_bytes.iter().position(|x| *x == 0);
 
         if let Some(pos) = keyword_position {
@@ -404,7 +476,7 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
 
             self.stream.skip(bytes_to_skip)?;
             let remainder = chunk.length.saturating_sub(bytes_to_skip);
-            let raw_data = self.stream.peek_at(0, remainder).unwrap().to_vec();
+            let raw_data = self.stream.peek_at(0, remainder)?.to_vec();
 
             let itxt_chunk = ItxtChunk {
                 keyword,
@@ -414,7 +486,10 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             // skip bytes we read
             self.stream.skip(remainder)?;
         } else {
-            warn!("Possibly corrupt iTXT chunk");
+            self.add_warning(
+                DecodeWarningKind::CorruptData,
+                "Possibly corrupt iTXT chunk"
+            );
             self.stream.skip(chunk.length)?;
         }
         // skip crc
@@ -425,7 +500,7 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
     /// Parse zTxt chunk
     pub(crate) fn parse_ztxt(&mut self, chunk: PngChunk) -> Result<(), PngDecodeErrors> {
         let length = core::cmp::min(chunk.length, 79);
-        let keyword_bytes = self.stream.peek_at(0, length).unwrap();
+        let keyword_bytes = self.stream.peek_at(0, length)?;
         let keyword_position = keyword_bytes.iter().position(|x| *x == 0);
 
         if let Some(pos) = keyword_position {
@@ -444,21 +519,28 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             let _ = self.stream.read_u8();
 
             // read remaining chunk
-            let data = self.stream.peek_at(0, remainder).unwrap();
+            let data = self.stream.peek_at(0, remainder)?;
 
             // decode to vec
-            if let Ok(ztxt) = DeflateDecoder::new(data).decode_zlib() {
-                let chunk = ZtxtChunk {
-                    keyword,
-                    text: ztxt
-                };
-                self.png_info.ztxt_chunk.push(chunk);
-            } else {
-                warn!("Could not decode ztxt profile, error with zlib stream");
+            match DeflateDecoder::new(data).decode_zlib() {
+                Ok(ztxt) => {
+                    let chunk = ZtxtChunk {
+                        keyword,
+                        text: ztxt
+                    };
+                    self.png_info.ztxt_chunk.push(chunk);
+                }
+                Err(_) => self.add_warning(
+                    DecodeWarningKind::CorruptData,
+                    "Could not decode ztxt profile, error with zlib stream"
+                )
             }
             self.stream.skip(remainder)?;
         } else {
-            warn!("Could not find keyword in iCCP chunk, possibly corrupt chunk");
+            self.add_warning(
+                DecodeWarningKind::CorruptData,
+                "Could not find keyword in zTXt chunk, possibly corrupt chunk"
+            );
             // skip the length
             self.stream.skip(chunk.length)?;
         }
@@ -520,9 +602,12 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
                 // skip crc
                 self.stream.skip(4)?;
             } else {
-                warn!(
-                    "Found marker {:?} in between fctl when it shouldn't be there",
-                    next_header.chunk_type
+                self.add_warning(
+                    DecodeWarningKind::NonConformance,
+                    format!(
+                        "Found marker {:?} in between fctl when it shouldn't be there",
+                        next_header.chunk_type
+                    )
                 );
                 // Will this recurse?
                 self.parse_header(next_header)?;
@@ -52,6 +52,14 @@ pub fn write_gamma(ctx: &PngEncoder, writer: &mut ZWriter<&mut Vec<u8>>) {
     }
 }
 
+pub fn write_phys(ctx: &PngEncoder, writer: &mut ZWriter<&mut Vec<u8>>) {
+    if let Some(phys) = ctx.phys {
+        writer.write_u32_be(phys.x_pixels_per_unit);
+        writer.write_u32_be(phys.y_pixels_per_unit);
+        writer.write_u8(phys.unit);
+    }
+}
+
 // iend is a no-op
 pub fn write_iend(_: &PngEncoder, _: &mut ZWriter<&mut Vec<u8>>) {}
 
@@ -16,6 +16,8 @@
 //! - Platform specific intrinsics for accelerated decoding on x86
 //! - Endian aware decoding support.
 //! - Support for animated PNG and post processing of the same
+//! - Animated PNG encoding via [`ApngEncoder`]
+//! - Previews of interlaced images after every pass via [`decode_into_progressive`](decoder::PngDecoder::decode_into_progressive)
 //!
 //!
 //! ## portable-simd
@@ -168,8 +170,8 @@ extern crate core;
 #[cfg(feature = "std")]
 pub use apng::post_process_image;
 pub use apng::{BlendOp, DisposeOp};
-pub use decoder::{ItxtChunk, PngDecoder, PngInfo, TextChunk, TimeInfo, ZtxtChunk};
-pub use encoder::PngEncoder;
+pub use decoder::{ItxtChunk, PhysInfo, PngDecoder, PngInfo, TextChunk, TimeInfo, ZtxtChunk};
+pub use encoder::{ApngEncoder, PngEncoder};
 pub use enums::InterlaceMethod;
 pub use zune_core;
 
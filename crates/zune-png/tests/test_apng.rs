@@ -0,0 +1,163 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use zune_core::bit_depth::BitDepth;
+use zune_core::colorspace::ColorSpace;
+use zune_core::options::EncoderOptions;
+use zune_png::{ApngEncoder, BlendOp, DisposeOp};
+
+const WIDTH: usize = 32;
+const HEIGHT: usize = 24;
+
+/// Frame region, as x offset, y offset, width and height
+type Region = (u32, u32, u32, u32);
+
+/// Three frames, the later ones change a small square
+fn create_frames() -> Vec<Vec<u8>> {
+    let first: Vec<u8> = (0..WIDTH * HEIGHT * 4)
+        .map(|x| ((x * 7919) % 251) as u8)
+        .collect();
+
+    let mut second = first.clone();
+    for y in 4..10 {
+        for x in 5..12 {
+            second[(y * WIDTH + x) * 4..][..4].copy_from_slice(&[255, 0, 0, 255]);
+        }
+    }
+    let mut third = second.clone();
+    third[(20 * WIDTH + 30) * 4] = 0;
+
+    vec![first, second, third]
+}
+
+/// Decode an animated png with the reference decoder, returning the frame
+/// regions and the canvas after each frame
+fn decode_ref(data: &[u8]) -> Vec<(Region, Vec<u8>)> {
+    let decoder = png::Decoder::new(data);
+    let mut reader = decoder.read_info().unwrap();
+    let mut canvas = vec![0; WIDTH * HEIGHT * 4];
+    let mut frames = vec![];
+
+    let num_frames = reader.info().animation_control().unwrap().num_frames;
+
+    for _ in 0..num_frames {
+        let mut buf = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut buf).unwrap();
+
+        let fc = *reader.info().frame_control().unwrap();
+        assert_eq!(fc.blend_op, png::BlendOp::Source);
+
+        let (x, y, w, h) = (
+            fc.x_offset as usize,
+            fc.y_offset as usize,
+            fc.width as usize,
+            fc.height as usize
+        );
+        for (row, src) in buf.chunks_exact(w * 4).take(h).enumerate() {
+            canvas[((y + row) * WIDTH + x) * 4..][..w * 4].copy_from_slice(src);
+        }
+        frames.push((
+            (fc.x_offset, fc.y_offset, fc.width, fc.height),
+            canvas.clone()
+        ));
+    }
+    frames
+}
+
+#[test]
+fn test_apng_encode() {
+    let frames = create_frames();
+    let options = EncoderOptions::new(WIDTH, HEIGHT, ColorSpace::RGBA, BitDepth::Eight);
+
+    let mut encoder = ApngEncoder::new(options);
+    for frame in &frames {
+        encoder.add_frame(frame, 1, 20);
+    }
+    encoder.set_num_plays(3);
+
+    let mut data = vec![];
+    encoder.encode(&mut data).unwrap();
+
+    let decoder = png::Decoder::new(data.as_slice());
+    let reader = decoder.read_info().unwrap();
+    let actl = reader.info().animation_control().unwrap();
+    assert_eq!((actl.num_frames, actl.num_plays), (3, 3));
+
+    for ((region, canvas), frame) in decode_ref(&data).iter().zip(&frames) {
+        assert_eq!(*region, (0, 0, WIDTH as u32, HEIGHT as u32));
+        assert!(canvas == frame);
+    }
+}
+
+#[test]
+fn test_apng_encode_optimized_frames() {
+    let frames = create_frames();
+    let options = EncoderOptions::new(WIDTH, HEIGHT, ColorSpace::RGBA, BitDepth::Eight);
+
+    let mut full = vec![];
+    let mut encoder = ApngEncoder::new(options);
+    for frame in &frames {
+        encoder.add_frame(frame, 1, 20);
+    }
+    encoder.encode(&mut full).unwrap();
+
+    let mut optimized = vec![];
+    let mut encoder = ApngEncoder::new(options.set_png_optimize_frames(true));
+    for frame in &frames {
+        encoder.add_frame(frame, 1, 20);
+    }
+    encoder.encode(&mut optimized).unwrap();
+
+    assert!(optimized.len() < full.len());
+
+    let decoded = decode_ref(&optimized);
+    // only the changed regions are stored
+    assert_eq!(decoded[0].0, (0, 0, WIDTH as u32, HEIGHT as u32));
+    assert_eq!(decoded[1].0, (5, 4, 7, 6));
+    assert_eq!(decoded[2].0, (30, 20, 1, 1));
+
+    for ((_, canvas), frame) in decoded.iter().zip(&frames) {
+        assert!(canvas == frame);
+    }
+}
+
+#[test]
+fn test_apng_encode_frame_ops() {
+    let frames = create_frames();
+    let options = EncoderOptions::new(WIDTH, HEIGHT, ColorSpace::RGBA, BitDepth::Eight)
+        .set_png_optimize_frames(true);
+
+    let mut encoder = ApngEncoder::new(options);
+    encoder.add_frame_with_ops(&frames[0], 1, 10, DisposeOp::None, BlendOp::Source);
+    encoder.add_frame_with_ops(&frames[1], 2, 10, DisposeOp::Background, BlendOp::Source);
+    encoder.add_frame_with_ops(&frames[2], 3, 10, DisposeOp::None, BlendOp::Over);
+
+    let mut data = vec![];
+    encoder.encode(&mut data).unwrap();
+
+    let decoder = png::Decoder::new(data.as_slice());
+    let mut reader = decoder.read_info().unwrap();
+    let mut buf = vec![0; reader.output_buffer_size()];
+
+    let expected = [
+        (1, png::DisposeOp::None, png::BlendOp::Source),
+        (2, png::DisposeOp::Background, png::BlendOp::Source),
+        (3, png::DisposeOp::None, png::BlendOp::Over)
+    ];
+    for (delay, dispose_op, blend_op) in expected {
+        reader.next_frame(&mut buf).unwrap();
+        let fc = reader.info().frame_control().unwrap();
+
+        assert_eq!((fc.delay_num, fc.delay_den), (delay, 10));
+        assert_eq!((fc.dispose_op, fc.blend_op), (dispose_op, blend_op));
+        // blending needs the whole frame
+        if blend_op == png::BlendOp::Over {
+            assert_eq!((fc.width, fc.height), (WIDTH as u32, HEIGHT as u32));
+        }
+    }
+}
@@ -0,0 +1,178 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use std::fs::read;
+
+use png::Transformations;
+use zune_core::bit_depth::ByteEndian;
+use zune_core::bytestream::ZCursor;
+use zune_core::options::DecoderOptions;
+use zune_png::error::PngDecodeErrors;
+use zune_png::PngDecoder;
+
+/// Images from the png suite covering every color type, bit depth and interlace method
+fn suite_images() -> Vec<(String, Vec<u8>)> {
+    let dir = env!("CARGO_MANIFEST_DIR").to_string() + "/tests/png_suite/";
+    let mut images = vec![];
+
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_str().unwrap().to_string();
+
+        // images starting with x are corrupt
+        if name.ends_with(".png") && !name.starts_with('x') && name != "PngSuite.png" {
+            images.push((name, read(&path).unwrap()));
+        }
+    }
+    images.sort();
+    images
+}
+
+fn decode_ref(data: &[u8], strip: bool, add_alpha: bool) -> Vec<u8> {
+    let mut decoder = png::Decoder::new(data);
+    let mut transformations = Transformations::EXPAND;
+
+    if strip {
+        transformations |= Transformations::STRIP_16;
+    }
+    if add_alpha {
+        transformations |= Transformations::ALPHA;
+    }
+    decoder.set_transformations(transformations);
+
+    let mut reader = decoder.read_info().unwrap();
+
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).unwrap();
+    buf.truncate(info.buffer_size());
+
+    buf
+}
+
+#[test]
+fn test_decode_into_all_modes() {
+    // a reused buffer, every byte has to be overwritten
+    let mut buffer = vec![];
+
+    for (name, data) in suite_images() {
+        for (strip, add_alpha) in [(false, false), (true, false), (false, true), (true, true)] {
+            let options = DecoderOptions::default()
+                .png_set_strip_to_8bit(strip)
+                .png_set_add_alpha_channel(add_alpha)
+                .set_byte_endian(ByteEndian::BE);
+
+            let mut decoder = PngDecoder::new_with_options(ZCursor::new(&data), options);
+            decoder.decode_headers().unwrap();
+
+            buffer.clear();
+            buffer.resize(decoder.output_buffer_size().unwrap(), 0xAA);
+            decoder.decode_into(&mut buffer).unwrap();
+
+            assert!(
+                buffer == decode_ref(&data, strip, add_alpha),
+                "{name}, strip: {strip}, add alpha: {add_alpha}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_decode_into_16bit_interlaced_little_endian() {
+    for name in [
+        "basi0g16.png",
+        "basi2c16.png",
+        "basi4a16.png",
+        "basi6a16.png"
+    ] {
+        let path = env!("CARGO_MANIFEST_DIR").to_string() + "/tests/png_suite/" + name;
+        let data = read(path).unwrap();
+
+        let options = DecoderOptions::default().set_byte_endian(ByteEndian::LE);
+        let mut decoder = PngDecoder::new_with_options(ZCursor::new(&data), options);
+        decoder.decode_headers().unwrap();
+
+        let mut buffer = vec![0; decoder.output_buffer_size().unwrap()];
+        decoder.decode_into(&mut buffer).unwrap();
+
+        let expected: Vec<u8> = decode_ref(&data, false, false)
+            .chunks_exact(2)
+            .flat_map(|x| [x[1], x[0]])
+            .collect();
+
+        assert!(buffer == expected, "{name}");
+    }
+}
+
+#[test]
+fn test_decode_into_small_buffer() {
+    let path = env!("CARGO_MANIFEST_DIR").to_string() + "/tests/png_suite/basi6a16.png";
+    let data = read(path).unwrap();
+
+    let options = DecoderOptions::default().png_set_strip_to_8bit(true);
+    let mut decoder = PngDecoder::new_with_options(ZCursor::new(&data), options);
+    decoder.decode_headers().unwrap();
+
+    let size = decoder.output_buffer_size().unwrap();
+    let mut buffer = vec![0; size - 1];
+
+    assert!(matches!(
+        decoder.decode_into(&mut buffer),
+        Err(PngDecodeErrors::TooSmallOutput(expected, found)) if expected == size && found == size - 1
+    ));
+}
+
+#[test]
+fn test_decode_into_progressive() {
+    let path = env!("CARGO_MANIFEST_DIR").to_string() + "/tests/png_suite/basi2c16.png";
+    let data = read(path).unwrap();
+    // partially decoded images are shown in the configured endianness
+    let options = DecoderOptions::default().set_byte_endian(ByteEndian::LE);
+
+    let mut decoder = PngDecoder::new_with_options(ZCursor::new(&data), options);
+    decoder.decode_headers().unwrap();
+    let (width, height) = decoder.dimensions().unwrap();
+
+    let mut expected = vec![0; decoder.output_buffer_size().unwrap()];
+    decoder.decode_into(&mut expected).unwrap();
+
+    let mut decoder = PngDecoder::new_with_options(ZCursor::new(&data), options);
+    decoder.decode_headers().unwrap();
+
+    let mut passes = vec![];
+    let mut out = vec![0; decoder.output_buffer_size().unwrap()];
+    decoder
+        .decode_into_progressive(&mut out, |image| passes.push(image.to_vec()))
+        .unwrap();
+
+    assert_eq!(passes.len(), 7);
+    assert!(passes[6] == expected && out == expected);
+
+    // after the first pass, every 8x8 block has the color of its top left pixel
+    let pixel = |image: &[u8], x: usize, y: usize| image[(y * width + x) * 6..][..6].to_vec();
+
+    for y in 0..height {
+        for x in 0..width {
+            assert_eq!(pixel(&passes[0], x, y), pixel(&expected, x & !7, y & !7));
+        }
+    }
+
+    // images which aren't interlaced have a single pass
+    let path = env!("CARGO_MANIFEST_DIR").to_string() + "/tests/png_suite/basn2c16.png";
+    let data = read(path).unwrap();
+
+    let mut decoder = PngDecoder::new(ZCursor::new(&data));
+    decoder.decode_headers().unwrap();
+
+    let mut calls = 0;
+    let mut out = vec![0; decoder.output_buffer_size().unwrap()];
+    decoder
+        .decode_into_progressive(&mut out, |_| calls += 1)
+        .unwrap();
+
+    assert_eq!(calls, 1);
+}
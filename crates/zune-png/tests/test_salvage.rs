@@ -0,0 +1,92 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use std::fs::read;
+
+use zune_core::bytestream::ZCursor;
+use zune_core::options::DecoderOptions;
+use zune_core::warnings::DecodeWarningKind;
+use zune_png::PngDecoder;
+
+// 32x32 4 bit grayscale image with six tEXt chunks before the IDAT chunk
+const PATH: &str = "/tests/png_suite/ct1n0g04.png";
+// offset of the IDAT chunk
+const IDAT_START: usize = 568;
+// offset of the CRC of the last tEXt chunk
+const TEXT_CRC: usize = 564;
+
+fn open() -> Vec<u8> {
+    read(env!("CARGO_MANIFEST_DIR").to_string() + PATH).unwrap()
+}
+
+fn salvage_options() -> DecoderOptions {
+    DecoderOptions::default()
+        .png_set_confirm_crc(true)
+        .png_set_salvage(true)
+}
+
+#[test]
+fn test_salvage_truncated_idat() {
+    let data = open();
+    let expected = PngDecoder::new(ZCursor::new(&data)).decode_raw().unwrap();
+
+    let truncated = &data[..IDAT_START + 8 + 120];
+
+    assert!(PngDecoder::new(ZCursor::new(truncated))
+        .decode_raw()
+        .is_err());
+
+    let mut decoder = PngDecoder::new_with_options(ZCursor::new(truncated), salvage_options());
+    let pixels = decoder.decode_raw().unwrap();
+
+    assert!(decoder.is_salvaged());
+    assert!(decoder
+        .warnings()
+        .iter()
+        .any(|x| x.kind() == DecodeWarningKind::Truncated));
+    assert_eq!(pixels.len(), expected.len());
+    // the first rows are intact
+    assert_eq!(pixels[..32 * 4], expected[..32 * 4]);
+    // ancillary chunks before the corruption are kept
+    assert_eq!(decoder.info().unwrap().text_chunk.len(), 6);
+}
+
+#[test]
+fn test_salvage_bad_crc() {
+    let mut data = open();
+    let expected = PngDecoder::new(ZCursor::new(&data)).decode_raw().unwrap();
+
+    data[TEXT_CRC] ^= 0xFF;
+
+    let options = DecoderOptions::default().png_set_confirm_crc(true);
+    assert!(PngDecoder::new_with_options(ZCursor::new(&data), options)
+        .decode_raw()
+        .is_err());
+
+    let mut decoder = PngDecoder::new_with_options(ZCursor::new(&data), salvage_options());
+    let pixels = decoder.decode_raw().unwrap();
+
+    assert!(decoder.is_salvaged());
+    assert_eq!(
+        decoder.warnings()[0].kind(),
+        DecodeWarningKind::ChecksumMismatch
+    );
+    assert_eq!(pixels, expected);
+    assert_eq!(decoder.info().unwrap().text_chunk.len(), 6);
+}
+
+#[test]
+fn test_salvage_intact_image() {
+    let data = open();
+
+    let mut decoder = PngDecoder::new_with_options(ZCursor::new(&data), salvage_options());
+    decoder.decode_raw().unwrap();
+
+    assert!(!decoder.is_salvaged());
+    assert!(decoder.warnings().is_empty());
+}
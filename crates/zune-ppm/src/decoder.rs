@@ -14,13 +14,17 @@ use core::fmt::{Debug, Formatter};
 use zune_core::bit_depth::{BitDepth, BitType, ByteEndian};
 use zune_core::bytestream::{ZByteIoError, ZByteReaderTrait, ZReader};
 use zune_core::colorspace::ColorSpace;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
 use zune_core::log::trace;
 use zune_core::options::DecoderOptions;
 use zune_core::result::DecodingResult;
 
 /// An instance of a PPM decoder
 ///
-/// The decoder can currently decode P5 and P6 formats
+/// The decoder can currently decode P1-P7 formats and PFM
+///
+/// Bitmaps (P1 and P4) are expanded to 8 bit grayscale where
+/// `0` (black) and `255` (white)
 pub struct PPMDecoder<T>
 where
     T: ZByteReaderTrait
@@ -31,6 +35,9 @@ where
     reader:          ZReader<T>,
     colorspace:      ColorSpace,
     bit_depth:       BitDepth,
+    version:         u8,
+    pfm_scale:       f32,
+    max_value:       usize,
     options:         DecoderOptions
 }
 
@@ -54,6 +61,19 @@ impl From<ZByteIoError> for PPMDecodeErrors {
     }
 }
 
+impl ZErrorCodeTrait for PPMDecodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::Generic(_) | Self::GenericStatic(_) | Self::InvalidHeader(_) => {
+                ZErrorCode::CorruptData
+            }
+            Self::UnsupportedImpl(_) => ZErrorCode::Unsupported,
+            Self::LargeDimensions(_, _) => ZErrorCode::LimitsExceeded,
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
+
 impl Debug for PPMDecodeErrors {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
@@ -124,6 +144,9 @@ where
             reader,
             colorspace: ColorSpace::Unknown,
             bit_depth: BitDepth::Eight,
+            version: 0,
+            pfm_scale: 1.0,
+            max_value: 255,
             options
         }
     }
@@ -149,8 +172,10 @@ where
             return Err(PPMDecodeErrors::InvalidHeader(msg));
         }
 
-        if version == b'5' || version == b'6' {
-            self.decode_p5_and_p6_header(version)?;
+        self.version = version;
+
+        if (b'1'..=b'6').contains(&version) {
+            self.decode_p1_to_p6_header(version)?;
         } else if version == b'7' {
             self.decode_p7_header()?;
         } else if version == b'f' {
@@ -159,7 +184,7 @@ where
             self.decode_pf_header(ColorSpace::RGB)?;
         } else {
             let msg = format!(
-                "Unsupported PPM version `{}`, supported versions are 5,6 and 7",
+                "Unsupported PPM version `{}`, supported versions are 1-7, f and F",
                 version as char
             );
 
@@ -228,6 +253,7 @@ where
         } else {
             self.options = self.options.set_byte_endian(ByteEndian::BE);
         }
+        self.pfm_scale = int_bytes.abs();
         self.decoded_headers = true;
         self.bit_depth = BitDepth::Float32;
 
@@ -303,6 +329,7 @@ where
                     } else {
                         self.bit_depth = BitDepth::Eight;
                     }
+                    self.max_value = max_value;
                     seen_max_val = true;
                 }
                 b"TUPLTYPE " => {
@@ -357,11 +384,11 @@ where
 
         Ok(())
     }
-    /// Decode header types from P5 and P6 format
-    fn decode_p5_and_p6_header(&mut self, version: u8) -> Result<(), PPMDecodeErrors> {
+    /// Decode header types from P1 to P6 formats
+    fn decode_p1_to_p6_header(&mut self, version: u8) -> Result<(), PPMDecodeErrors> {
         let colorspace = match version {
-            b'5' => ColorSpace::Luma,
-            b'6' => ColorSpace::RGB,
+            b'1' | b'2' | b'4' | b'5' => ColorSpace::Luma,
+            b'3' | b'6' => ColorSpace::RGB,
             _ => unreachable!()
         };
         trace!("Colorspace: {:?}", colorspace);
@@ -397,6 +424,18 @@ where
 
         trace!("Width: {}, height: {}", self.width, self.height);
 
+        if version == b'1' || version == b'4' {
+            // bitmaps have no max value, and are expanded to 8 bits
+            if version == b'4' {
+                // a single whitespace separates the header from packed bits
+                // which may themselves look like whitespace
+                self.reader.skip(1)?;
+            }
+            trace!("Bit Depth: {:?}", self.bit_depth);
+            self.decoded_headers = true;
+            return Ok(());
+        }
+
         skip_spaces(&mut self.reader)?;
         // read max value
         let max_value = self.get_integer()?;
@@ -413,6 +452,7 @@ where
             // 16 bit
             self.bit_depth = BitDepth::Sixteen;
         }
+        self.max_value = max_value;
 
         trace!("Bit Depth: {:?}", self.bit_depth);
         self.decoded_headers = true;
@@ -469,6 +509,19 @@ where
             None
         }
     }
+    /// Return the scale stored in the header of PFM images
+    ///
+    /// The returned value is always positive, the sign only
+    /// indicates byte order which the decoder handles.
+    ///
+    /// Returns `None` if headers aren't decoded or the image is not a PFM image
+    pub const fn pfm_scale(&self) -> Option<f32> {
+        if self.decoded_headers && matches!(self.version, b'f' | b'F') {
+            Some(self.pfm_scale)
+        } else {
+            None
+        }
+    }
     /// Return image dimensions or none if image isn't decoded
     ///
     /// # Returns
@@ -537,6 +590,11 @@ where
                 "Zero dimensions not allowed"
             ));
         }
+        match self.version {
+            b'1' | b'2' | b'3' => return self.decode_ascii(),
+            b'4' => return self.decode_packed_bitmap(),
+            _ => ()
+        }
         // okay check if the stream is large enough for the bit depth
         let size =
             self.width * self.height * self.colorspace.num_components() * self.bit_depth.size_of();
@@ -620,6 +678,86 @@ where
     }
 }
 
+impl<T> PPMDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    /// Decode plain (ASCII) P1, P2 and P3 images
+    ///
+    /// Samples take at least two bytes each, so the output grows as they are
+    /// read instead of being allocated up front for what the header claims
+    fn decode_ascii(&mut self) -> Result<DecodingResult, PPMDecodeErrors> {
+        let size = self.width * self.height * self.colorspace.num_components();
+
+        if self.version == b'1' {
+            // P1 samples are single digits which may not be separated by spaces
+            let mut data = Vec::new();
+
+            for _ in 0..size {
+                skip_spaces(&mut self.reader)?;
+
+                data.push(match self.reader.read_u8_err()? {
+                    b'0' => 255,
+                    b'1' => 0,
+                    byte => {
+                        let msg = format!("Invalid P1 sample `{}`", byte as char);
+                        return Err(PPMDecodeErrors::Generic(msg));
+                    }
+                });
+            }
+            return Ok(DecodingResult::U8(data));
+        }
+
+        let mut data = Vec::new();
+
+        for _ in 0..size {
+            skip_spaces(&mut self.reader)?;
+
+            if self.reader.eof()? {
+                return Err(PPMDecodeErrors::GenericStatic(
+                    "Not enough samples in plain PPM image"
+                ));
+            }
+            let value = self.get_integer()?;
+
+            if value > self.max_value && self.options.strict_mode() {
+                let msg = format!("Sample {value} greater than max value {}", self.max_value);
+                return Err(PPMDecodeErrors::Generic(msg));
+            }
+            // out of range samples are clamped outside of strict mode, max
+            // value is checked to fit in u16 during header parsing
+            data.push(value.min(self.max_value) as u16);
+        }
+
+        if self.bit_depth == BitDepth::Sixteen {
+            Ok(DecodingResult::U16(data))
+        } else {
+            Ok(DecodingResult::U8(
+                data.iter().map(|x| (*x).min(255) as u8).collect()
+            ))
+        }
+    }
+
+    /// Decode P4 images, where each row is packed as one bit per pixel
+    fn decode_packed_bitmap(&mut self) -> Result<DecodingResult, PPMDecodeErrors> {
+        let stride = self.width.div_ceil(8);
+        let mut row = vec![0_u8; stride];
+        // grow a row at a time, so a truncated file doesn't allocate the whole image
+        let mut data = Vec::new();
+
+        for _ in 0..self.height {
+            self.reader.read_exact_bytes(&mut row)?;
+
+            data.extend((0..self.width).map(|i| {
+                // most significant bit first, 1 is black
+                let bit = (row[i / 8] >> (7 - (i % 8))) & 1;
+                if bit == 1 { 0 } else { 255 }
+            }));
+        }
+        Ok(DecodingResult::U8(data))
+    }
+}
+
 /// Skip all whitespace characters and comments
 /// until one hits a character that isn't a space or
 /// we reach eof
@@ -676,3 +814,103 @@ fn get_bytes_until_whitespace<T: ZByteReaderTrait>(
     // z.skip(end - start);
     Ok((end - start) as usize)
 }
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::{DecoderOptions, EncoderOptions};
+    use zune_core::result::DecodingResult;
+
+    use crate::{PPMDecoder, PPMEncoder};
+
+    #[test]
+    fn test_decode_plain_formats() {
+        // P1 digits need not be separated
+        let mut decoder = PPMDecoder::new(ZCursor::new(b"P1\n# comment\n3 2\n010\n1 0 1"));
+        let pixels = decoder.decode().unwrap().u8().unwrap();
+        assert_eq!(pixels, [255, 0, 255, 0, 255, 0]);
+
+        let mut decoder = PPMDecoder::new(ZCursor::new(b"P2 2 1 255 12 200"));
+        let pixels = decoder.decode().unwrap().u8().unwrap();
+        assert_eq!(pixels, [12, 200]);
+
+        let mut decoder = PPMDecoder::new(ZCursor::new(b"P3 1 1 65535 1 300 65535"));
+        let pixels = decoder.decode().unwrap().u16().unwrap();
+        assert_eq!(decoder.colorspace(), Some(ColorSpace::RGB));
+        assert_eq!(pixels, [1, 300, 65535]);
+    }
+
+    #[test]
+    fn test_plain_samples_above_max_value() {
+        let mut decoder = PPMDecoder::new(ZCursor::new(b"P2 3 1 100 12 101 70000"));
+        let pixels = decoder.decode().unwrap().u8().unwrap();
+        assert_eq!(pixels, [12, 100, 100]);
+
+        let options = DecoderOptions::default().set_strict_mode(true);
+        let mut decoder =
+            PPMDecoder::new_with_options(ZCursor::new(b"P2 3 1 100 12 101 70000"), options);
+        assert!(decoder.decode().is_err());
+    }
+
+    #[test]
+    fn test_header_only_images() {
+        // the largest images allowed by default, without any pixels
+        for header in [&b"P1 16384 16384 "[..], b"P2 16384 16384 255 ", b"P4 16384 16384 "] {
+            assert!(PPMDecoder::new(ZCursor::new(header)).decode().is_err());
+        }
+    }
+
+    #[test]
+    fn test_bitmap_roundtrip() {
+        // width that isn't a multiple of 8 to test row padding
+        let (width, height) = (10, 3);
+        let data: [u8; 30] = core::array::from_fn(|x| if x % 3 == 0 { 0 } else { 255 });
+
+        let mut encoder = PPMEncoder::new(
+            &data,
+            EncoderOptions::new(width, height, ColorSpace::Luma, BitDepth::Eight)
+        );
+        encoder.set_encode_bitmap(true);
+
+        let mut output = alloc::vec![];
+        encoder.encode(&mut output).unwrap();
+        assert!(output.starts_with(b"P4"));
+
+        let mut decoder = PPMDecoder::new(ZCursor::new(&output));
+        let pixels = decoder.decode().unwrap().u8().unwrap();
+        assert_eq!(decoder.dimensions(), Some((width, height)));
+        assert_eq!(pixels, data);
+    }
+
+    #[test]
+    fn test_pfm_roundtrip() {
+        use zune_core::bit_depth::ByteEndian;
+
+        let (width, height) = (3, 2);
+        let data: [f32; 18] = core::array::from_fn(|x| x as f32 * 0.25);
+        let bytes: alloc::vec::Vec<u8> = data.iter().flat_map(|x| x.to_ne_bytes()).collect();
+
+        for endian in [ByteEndian::LE, ByteEndian::BE] {
+            let mut encoder = PPMEncoder::new(
+                &bytes,
+                EncoderOptions::new(width, height, ColorSpace::RGB, BitDepth::Float32)
+            );
+            encoder.set_pfm_byte_endian(endian);
+            encoder.set_pfm_scale(2.0);
+
+            let mut output = alloc::vec![];
+            encoder.encode(&mut output).unwrap();
+            assert!(output.starts_with(b"PF"));
+
+            let mut decoder = PPMDecoder::new(ZCursor::new(&output));
+            let pixels = match decoder.decode().unwrap() {
+                DecodingResult::F32(pixels) => pixels,
+                _ => panic!("Expected float output")
+            };
+            assert_eq!(decoder.pfm_scale(), Some(2.0));
+            assert_eq!(pixels, data);
+        }
+    }
+}
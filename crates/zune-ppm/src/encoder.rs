@@ -6,12 +6,13 @@
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 
-use alloc::format;
+use alloc::{format, vec};
 use core::fmt::{Debug, Display, Formatter};
 
-use zune_core::bit_depth::BitType;
+use zune_core::bit_depth::{BitType, ByteEndian};
 use zune_core::bytestream::{ZByteIoError, ZByteWriterTrait, ZWriter};
 use zune_core::colorspace::ColorSpace;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
 use zune_core::options::EncoderOptions;
 
 /// Errors occurring during encoding
@@ -47,18 +48,36 @@ impl From<ZByteIoError> for PPMEncodeErrors {
     }
 }
 
+impl ZErrorCodeTrait for PPMEncodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::Static(_) | Self::TooShortInput(_, _) => ZErrorCode::InvalidInput,
+            Self::UnsupportedColorspace(_) => ZErrorCode::Unsupported,
+            Self::IoError(err) => err.code()
+        }
+    }
+}
+
 enum PPMVersions {
+    P4,
     P5,
     P6,
-    P7
+    P7,
+    /// Grayscale PFM
+    Pf,
+    /// RGB PFM
+    PF
 }
 
 impl Display for PPMVersions {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
+            Self::P4 => write!(f, "P4"),
             Self::P6 => write!(f, "P6"),
             Self::P5 => write!(f, "P5"),
-            Self::P7 => write!(f, "P7")
+            Self::P7 => write!(f, "P7"),
+            Self::Pf => write!(f, "Pf"),
+            Self::PF => write!(f, "PF")
         }
     }
 }
@@ -91,8 +110,11 @@ impl Display for PPMVersions {
 /// }
 /// ```
 pub struct PPMEncoder<'a> {
-    data:    &'a [u8],
-    options: EncoderOptions
+    data:       &'a [u8],
+    options:    EncoderOptions,
+    bitmap:     bool,
+    pfm_scale:  f32,
+    pfm_endian: ByteEndian
 }
 
 impl<'a> PPMEncoder<'a> {
@@ -105,17 +127,63 @@ impl<'a> PPMEncoder<'a> {
     ///
     /// One can use [`u16::to_ne_bytes`] for this if data is in a u16 slice
     ///
+    /// 32 bit float data is encoded as PFM (grayscale or RGB only),
+    /// it must also be provided as native endian bytes
+    ///
     /// [`u16::to_ne_bytes`]:u16::to_ne_bytes
     pub fn new(data: &'a [u8], options: EncoderOptions) -> PPMEncoder<'a> {
-        PPMEncoder { data, options }
+        PPMEncoder {
+            data,
+            options,
+            bitmap: false,
+            pfm_scale: 1.0,
+            pfm_endian: ByteEndian::LE
+        }
+    }
+
+    /// Set the scale written to the header of PFM images
+    ///
+    /// The absolute value is written, the sign is determined by the
+    /// byte order, see [`set_pfm_byte_endian`](Self::set_pfm_byte_endian)
+    ///
+    /// Default is `1.0`
+    pub fn set_pfm_scale(&mut self, scale: f32) {
+        self.pfm_scale = scale.abs();
+    }
+
+    /// Set the byte order floats are written in for PFM images
+    ///
+    /// Default is [`ByteEndian::LE`], which is what most tools produce
+    pub fn set_pfm_byte_endian(&mut self, endian: ByteEndian) {
+        self.pfm_endian = endian;
+    }
+
+    /// Encode the image as a P4 (packed bitmap) image
+    ///
+    /// The image must be 8 bit grayscale, pixels less than `128` are
+    /// written as black and the rest as white.
+    ///
+    /// Default is `false`
+    pub fn set_encode_bitmap(&mut self, yes: bool) {
+        self.bitmap = yes;
     }
 
     fn encode_headers<T: ZByteWriterTrait>(
         &self, stream: &mut ZWriter<T>
     ) -> Result<(), PPMEncodeErrors> {
-        let version = version_for_colorspace(self.options.colorspace()).ok_or(
-            PPMEncodeErrors::UnsupportedColorspace(self.options.colorspace())
-        )?;
+        let version = if self.bitmap {
+            PPMVersions::P4
+        } else if self.options.depth().bit_type() == BitType::F32 {
+            match self.options.colorspace() {
+                ColorSpace::Luma => PPMVersions::Pf,
+                ColorSpace::RGB => PPMVersions::PF,
+                colorspace => return Err(PPMEncodeErrors::UnsupportedColorspace(colorspace))
+            }
+        } else {
+            version_for_colorspace(self.options.colorspace()).ok_or(
+                PPMEncodeErrors::UnsupportedColorspace(self.options.colorspace())
+            )?
+        };
 
         let width = self.options.width();
         let height = self.options.height();
@@ -124,6 +192,17 @@ impl<'a> PPMEncoder<'a> {
         let colorspace = self.options.colorspace();
 
         let header = match version {
+            PPMVersions::P4 => {
+                format!("{version}\n{width}\n{height}\n")
+            }
+            PPMVersions::Pf | PPMVersions::PF => {
+                // negative scale indicates little endian data
+                let scale = match self.pfm_endian {
+                    ByteEndian::LE => -self.pfm_scale,
+                    ByteEndian::BE => self.pfm_scale
+                };
+                format!("{version}\n{width} {height}\n{scale:?}\n")
+            }
             PPMVersions::P5 | PPMVersions::P6 => {
                 format!("{version}\n{width}\n{height}\n{max_val}\n")
             }
@@ -157,11 +236,37 @@ impl<'a> PPMEncoder<'a> {
         if expected != found {
             return Err(PPMEncodeErrors::TooShortInput(expected, found));
         }
+        if self.bitmap
+            && (self.options.colorspace() != ColorSpace::Luma
+                || self.options.depth().bit_type() != BitType::U8)
+        {
+            return Err(PPMEncodeErrors::Static(
+                "Bitmap encoding requires 8 bit grayscale input"
+            ));
+        }
         let mut stream = ZWriter::new(out);
         stream.reserve(expected + 37)?; // 37 arbitrary number, chosen by divinity, guaranteed to work
 
         self.encode_headers(&mut stream)?;
 
+        if self.bitmap {
+            let width = self.options.width();
+            let mut packed = vec![0_u8; width.div_ceil(8)];
+
+            for row in self.data.chunks_exact(width) {
+                packed.fill(0);
+
+                for (i, pix) in row.iter().enumerate() {
+                    // most significant bit first, 1 is black
+                    if *pix < 128 {
+                        packed[i / 8] |= 1 << (7 - (i % 8));
+                    }
+                }
+                stream.write_all(&packed)?;
+            }
+            return Ok(stream.bytes_written());
+        }
+
         match self.options.depth().bit_type() {
             BitType::U8 => stream.write_all(self.data)?,
             BitType::U16 => {
@@ -171,6 +276,20 @@ impl<'a> PPMEncoder<'a> {
                     stream.write_u16_be_err(byte)?;
                 }
             }
+            BitType::F32 => {
+                let stride = self.options.width() * self.options.colorspace().num_components() * 4;
+                // pfm stores rows from bottom to top
+                for row in self.data.chunks_exact(stride).rev() {
+                    for slice in row.chunks_exact(4) {
+                        let value = f32::from_ne_bytes(slice.try_into().unwrap()).to_bits();
+
+                        match self.pfm_endian {
+                            ByteEndian::LE => stream.write_u32_le_err(value)?,
+                            ByteEndian::BE => stream.write_u32_be_err(value)?
+                        }
+                    }
+                }
+            }
             _ => unreachable!()
         }
         let position = stream.bytes_written();
@@ -14,11 +14,12 @@
 //!
 //!|Format | Decoder | Encoder |
 //!|-------|--------|--------|
-//!|P1-P3 | No     | No      |
+//!|P1-P3 | Yes    | No      |
+//!| P4   | Yes    | Yes     |
 //!| P5   | Yes    | Yes     |
 //!| P6   | Yes    | Yes     |
 //!| P7   | Yes    | Yes     |
-//!| [PFM]  | Yes    | No     |
+//!| [PFM]  | Yes    | Yes    |
 //!
 //!
 //![PFM]:https://www.pauldebevec.com/Research/HDR/PFM/
@@ -9,6 +9,7 @@
 use core::fmt::{Debug, Formatter};
 
 use zune_core::bytestream::ZByteIoError;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
 
 use crate::constants::{ColorModes, PSD_IDENTIFIER_BE};
 
@@ -99,3 +100,20 @@ impl From<ZByteIoError> for PSDDecodeErrors {
         Self::IoErrors(r)
     }
 }
+
+impl ZErrorCodeTrait for PSDDecodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::WrongMagicBytes(_) | Self::ZeroDimensions | Self::Generic(_) | Self::BadRLE => {
+                ZErrorCode::CorruptData
+            }
+            Self::UnsupportedFileType(_)
+            | Self::UnsupportedChannelCount(_)
+            | Self::UnsupportedBitDepth(_)
+            | Self::UnsupportedColorFormat(_)
+            | Self::UnknownCompression => ZErrorCode::Unsupported,
+            Self::LargeDimensions(_, _) => ZErrorCode::LimitsExceeded,
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
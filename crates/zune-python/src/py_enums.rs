@@ -11,6 +11,7 @@ use zune_core::bit_depth::BitDepth;
 use zune_core::colorspace::ColorSpace as ZColorSpace;
 use zune_image::codecs::ImageFormat as ZImageFormat;
 use zune_image::errors::ImageErrors;
+use zune_imageprocs::convolve::EmbossDirection as ZEmbossDirection;
 use zune_imageprocs::resize::ResizeMethod as ZResizeMethod;
 use zune_imageprocs::threshold::ThresholdMethod;
 
@@ -217,3 +218,32 @@ impl From<ZResizeMethod> for ResizeMethod {
         }
     }
 }
+
+/// The direction light comes from when embossing an image
+#[pyclass]
+#[derive(Copy, Clone)]
+pub enum EmbossDirection {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl EmbossDirection {
+    pub(crate) fn to_direction(self) -> ZEmbossDirection {
+        match self {
+            EmbossDirection::North => ZEmbossDirection::North,
+            EmbossDirection::NorthEast => ZEmbossDirection::NorthEast,
+            EmbossDirection::East => ZEmbossDirection::East,
+            EmbossDirection::SouthEast => ZEmbossDirection::SouthEast,
+            EmbossDirection::South => ZEmbossDirection::South,
+            EmbossDirection::SouthWest => ZEmbossDirection::SouthWest,
+            EmbossDirection::West => ZEmbossDirection::West,
+            EmbossDirection::NorthWest => ZEmbossDirection::NorthWest,
+        }
+    }
+}
@@ -28,6 +28,7 @@ use zune_imageprocs::auto_orient::AutoOrient;
 use zune_imageprocs::bilateral_filter::BilateralFilter;
 use zune_imageprocs::blend::Blend;
 use zune_imageprocs::box_blur::BoxBlur;
+use zune_imageprocs::convolve::Convolve;
 use zune_imageprocs::crop::Crop;
 use zune_imageprocs::exposure::Exposure;
 use zune_imageprocs::flip::{Flip, FlipDirection};
@@ -36,15 +37,19 @@ use zune_imageprocs::gaussian_blur::GaussianBlur;
 use zune_imageprocs::hsv_adjust::HsvAdjust;
 use zune_imageprocs::invert::Invert;
 use zune_imageprocs::median::Median;
+use zune_imageprocs::posterize::Posterize;
 use zune_imageprocs::resize::Resize;
 use zune_imageprocs::scharr::Scharr;
+use zune_imageprocs::sepia::Sepia;
 use zune_imageprocs::sobel::Sobel;
+use zune_imageprocs::solarize::Solarize;
 use zune_imageprocs::stretch_contrast::StretchContrast;
 use zune_imageprocs::threshold::Threshold;
 use zune_imageprocs::transpose::Transpose;
 
 use crate::py_enums::{
-    ColorSpace, ImageDepth, ImageFormat, ImageThresholdType, ResizeMethod, ZImageErrors,
+    ColorSpace, EmbossDirection, ImageDepth, ImageFormat, ImageThresholdType, ResizeMethod,
+    ZImageErrors,
 };
 
 /// Execute a single filter on an image
@@ -331,6 +336,95 @@ impl Image {
         exec_filter(self, Invert, in_place)
     }
 
+    /// Reduce the number of tones of an image
+    ///
+    /// Every channel is rounded to `levels` evenly spaced values, alpha is ignored
+    ///
+    /// # Arguments
+    ///  - levels: Number of levels per channel, including black and white, minimum is 2
+    ///  - in_place: Whether to perform the operation in-place or to clone and return a copy
+    ///
+    /// # Returns
+    ///  - If `in_place=True`: Nothing on success, on error returns error that occurred
+    ///  - If `in_place=False`: An image copy on success on error, returns error that occurred
+    #[pyo3(signature = (levels, in_place = false))]
+    pub fn posterize(&mut self, levels: u16, in_place: bool) -> PyResult<Option<Image>> {
+        exec_filter(self, Posterize::new(levels), in_place)
+    }
+
+    /// Solarize an image, inverting pixels brighter than a threshold
+    ///
+    /// # Arguments
+    ///  - threshold: Value between 0 and 1, where 1 stands for the maximum value of the
+    ///    bit depth, 0.5 is the classic effect
+    ///  - in_place: Whether to perform the operation in-place or to clone and return a copy
+    ///
+    /// # Returns
+    ///  - If `in_place=True`: Nothing on success, on error returns error that occurred
+    ///  - If `in_place=False`: An image copy on success on error, returns error that occurred
+    #[pyo3(signature = (threshold = 0.5, in_place = false))]
+    pub fn solarize(&mut self, threshold: f32, in_place: bool) -> PyResult<Option<Image>> {
+        exec_filter(self, Solarize::new(threshold), in_place)
+    }
+
+    /// Apply a sepia tone to an image, grayscale images are converted to RGB
+    ///
+    /// # Arguments
+    ///  - strength: How much of the effect is applied, between 0 (none) and 1 (full)
+    ///  - in_place: Whether to perform the operation in-place or to clone and return a copy
+    ///
+    /// # Returns
+    ///  - If `in_place=True`: Nothing on success, on error returns error that occurred
+    ///  - If `in_place=False`: An image copy on success on error, returns error that occurred
+    #[pyo3(signature = (strength = 1.0, in_place = false))]
+    pub fn sepia(&mut self, strength: f32, in_place: bool) -> PyResult<Option<Image>> {
+        exec_filter(self, Sepia::new(strength), in_place)
+    }
+
+    /// Emboss an image, making it look raised as if lit from a direction
+    ///
+    /// # Arguments
+    ///  - direction: The direction light comes from
+    ///  - in_place: Whether to perform the operation in-place or to clone and return a copy
+    ///
+    /// # Returns
+    ///  - If `in_place=True`: Nothing on success, on error returns error that occurred
+    ///  - If `in_place=False`: An image copy on success on error, returns error that occurred
+    #[pyo3(signature = (direction = EmbossDirection::NorthWest, in_place = false))]
+    pub fn emboss(
+        &mut self, direction: EmbossDirection, in_place: bool,
+    ) -> PyResult<Option<Image>> {
+        exec_filter(self, Convolve::emboss(direction.to_direction()), in_place)
+    }
+
+    /// Sharpen an image with a 3x3 kernel, alpha is ignored
+    ///
+    /// # Arguments
+    ///  - amount: How much to sharpen, 0 leaves the image as is, 1 is a common default
+    ///  - in_place: Whether to perform the operation in-place or to clone and return a copy
+    ///
+    /// # Returns
+    ///  - If `in_place=True`: Nothing on success, on error returns error that occurred
+    ///  - If `in_place=False`: An image copy on success on error, returns error that occurred
+    #[pyo3(signature = (amount = 1.0, in_place = false))]
+    pub fn sharpen(&mut self, amount: f32, in_place: bool) -> PyResult<Option<Image>> {
+        exec_filter(self, Convolve::sharpen(amount), in_place)
+    }
+
+    /// Enhance the edges of an image in every direction with a 3x3 kernel, alpha is ignored
+    ///
+    /// # Arguments
+    ///  - amount: How much to enhance edges, 0.5 is a common default
+    ///  - in_place: Whether to perform the operation in-place or to clone and return a copy
+    ///
+    /// # Returns
+    ///  - If `in_place=True`: Nothing on success, on error returns error that occurred
+    ///  - If `in_place=False`: An image copy on success on error, returns error that occurred
+    #[pyo3(signature = (amount = 0.5, in_place = false))]
+    pub fn edge_enhance(&mut self, amount: f32, in_place: bool) -> PyResult<Option<Image>> {
+        exec_filter(self, Convolve::edge_enhance(amount), in_place)
+    }
+
     /// Blur the image using a box blur operation
     ///
     /// # Arguments
@@ -15,3 +15,8 @@ pub const QOI_MASK_2: u8 = 0xc0; // (11)000000
 pub const QOI_MAGIC: u32 = u32::from_be_bytes(*b"qoif");
 pub const QOI_HEADER_SIZE: usize = 14;
 pub const QOI_PADDING: usize = 8;
+
+pub const QOIS_MAGIC: u32 = u32::from_be_bytes(*b"qois");
+pub const QOIS_VERSION: u8 = 1;
+/// offset(u64) + length(u32) + numerator(u16) + denominator(u16)
+pub const QOIS_INDEX_ENTRY_SIZE: usize = 16;
@@ -4,7 +4,7 @@
  * This software is free software; You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 
-use zune_core::bytestream::{ZWriter, ZByteWriterTrait};
+use zune_core::bytestream::{ZByteWriterTrait, ZWriter};
 use zune_core::colorspace::{ColorCharacteristics, ColorSpace};
 use zune_core::options::EncoderOptions;
 
@@ -86,44 +86,7 @@ impl<'a> QoiEncoder<'a> {
                 "Expected length doesn't match pixels length"
             ));
         }
-
-        // qoif
-        writer.write_all(&QOI_MAGIC.to_be_bytes())?;
-
-        let options = &self.options;
-        if (options.width() as u64) > u64::from(u32::MAX) {
-            // error out
-            return Err(QoiEncodeErrors::TooLargeDimensions(options.width()));
-        }
-        if (options.height() as u64) > u64::from(u32::MAX) {
-            return Err(QoiEncodeErrors::TooLargeDimensions(options.height()));
-        }
-        // it's safe to convert to u32 here. since we checked
-        // the number can be safely encoded.
-
-        // width
-        writer.write_u32_be_err(options.width() as u32)?;
-        // height
-        writer.write_u32_be_err(options.height() as u32)?;
-        //channel
-        let channel = match self.options.colorspace() {
-            ColorSpace::RGB => 3,
-            ColorSpace::RGBA => 4,
-
-            _ => {
-                return Err(QoiEncodeErrors::UnsupportedColorspace(
-                    self.options.colorspace(),
-                    &SUPPORTED_COLORSPACES
-                ))
-            }
-        };
-
-        writer.write_u8_err(channel)?;
-        // colorspace
-        let xtic = u8::from(self.color_characteristics == ColorCharacteristics::Linear);
-        writer.write_u8_err(xtic)?;
-
-        Ok(())
+        write_headers(&self.options, self.color_characteristics, writer)
     }
     /// Encode into a pre-allocated buffer and error out if
     /// the buffer provided is too small
@@ -139,29 +102,102 @@ impl<'a> QoiEncoder<'a> {
 
         self.encode_headers(&mut stream)?;
 
-        let mut index = [[0_u8; 4]; 64];
-        // starting pixel
-        let mut px = [0, 0, 0, 255];
-        let mut px_prev = [0, 0, 0, 255];
+        let mut state = EncoderState::new();
+        let channel_count = self.options.colorspace().num_components();
 
-        let mut run = 0;
+        state.encode_pixels(self.pixel_data, channel_count, &mut stream)?;
+        state.finish(&mut stream)?;
+        // done
+        let len = stream.bytes_written();
 
-        let channel_count = self.options.colorspace().num_components();
+        Ok(len)
+    }
+}
+
+/// Write the QOI header for an image described by `options`
+fn write_headers<T: ZByteWriterTrait>(
+    options: &EncoderOptions, color_characteristics: ColorCharacteristics, writer: &mut ZWriter<T>
+) -> Result<(), QoiEncodeErrors> {
+    // qoif
+    writer.write_all(&QOI_MAGIC.to_be_bytes())?;
+
+    if (options.width() as u64) > u64::from(u32::MAX) {
+        // error out
+        return Err(QoiEncodeErrors::TooLargeDimensions(options.width()));
+    }
+    if (options.height() as u64) > u64::from(u32::MAX) {
+        return Err(QoiEncodeErrors::TooLargeDimensions(options.height()));
+    }
+    // it's safe to convert to u32 here. since we checked
+    // the number can be safely encoded.
+
+    // width
+    writer.write_u32_be_err(options.width() as u32)?;
+    // height
+    writer.write_u32_be_err(options.height() as u32)?;
+    //channel
+    let channel = match options.colorspace() {
+        ColorSpace::RGB => 3,
+        ColorSpace::RGBA => 4,
+
+        _ => {
+            return Err(QoiEncodeErrors::UnsupportedColorspace(
+                options.colorspace(),
+                &SUPPORTED_COLORSPACES
+            ))
+        }
+    };
+
+    writer.write_u8_err(channel)?;
+    // colorspace
+    let xtic = u8::from(color_characteristics == ColorCharacteristics::Linear);
+    writer.write_u8_err(xtic)?;
+
+    Ok(())
+}
+
+/// Encoder state carried from one pixel to the next
+///
+/// Kept separate from the encoders so that pixels can be fed in
+/// pieces, see [`QoiStreamEncoder`]
+struct EncoderState {
+    index:   [[u8; 4]; 64],
+    px_prev: [u8; 4],
+    run:     u8
+}
+
+impl EncoderState {
+    const fn new() -> EncoderState {
+        EncoderState {
+            index:   [[0_u8; 4]; 64],
+            // starting pixel
+            px_prev: [0, 0, 0, 255],
+            run:     0
+        }
+    }
+
+    fn encode_pixels<T: ZByteWriterTrait>(
+        &mut self, pixels: &[u8], channel_count: usize, stream: &mut ZWriter<T>
+    ) -> Result<(), QoiEncodeErrors> {
+        let mut px = self.px_prev;
+        let index = &mut self.index;
+        let px_prev = &mut self.px_prev;
+        let run = &mut self.run;
 
-        for pix_chunk in self.pixel_data.chunks_exact(channel_count) {
+        for pix_chunk in pixels.chunks_exact(channel_count) {
             px[0..channel_count].copy_from_slice(pix_chunk);
 
-            if px == px_prev {
-                run += 1;
+            if px == *px_prev {
+                *run += 1;
 
-                if run == 62 {
-                    stream.write_u8_err(QOI_OP_RUN | (run - 1))?;
-                    run = 0;
+                if *run == 62 {
+                    stream.write_u8_err(QOI_OP_RUN | (*run - 1))?;
+                    *run = 0;
                 }
             } else {
-                if run > 0 {
-                    stream.write_u8_err(QOI_OP_RUN | (run - 1))?;
-                    run = 0;
+                if *run > 0 {
+                    stream.write_u8_err(QOI_OP_RUN | (*run - 1))?;
+                    *run = 0;
                 }
 
                 let index_pos = (usize::from(px[0]) * 3
@@ -213,25 +249,153 @@ impl<'a> QoiEncoder<'a> {
 
             px_prev.copy_from_slice(&px);
         }
-        if run > 0 {
-            stream.write_u8_err(QOI_OP_RUN | (run - 1))?;
+        Ok(())
+    }
+
+    /// Flush a pending run and write the end marker
+    fn finish<T: ZByteWriterTrait>(
+        &mut self, stream: &mut ZWriter<T>
+    ) -> Result<(), QoiEncodeErrors> {
+        if self.run > 0 {
+            stream.write_u8_err(QOI_OP_RUN | (self.run - 1))?;
+            self.run = 0;
         }
         // write trailing bytes
         stream.write_u64_be_err(0x01)?;
-        // done
-        let len = stream.bytes_written();
+        Ok(())
+    }
+}
 
-        Ok(len)
+/// Incremental Quite Ok Image Encoder
+///
+/// Unlike [`QoiEncoder`], this doesn't need the whole image up front, pixels are
+/// passed in pieces (e.g. a scanline at a time as they come from a capture device)
+/// and encoded straight into the sink, the encoder state carries over between calls
+/// so the output is the same as encoding the whole image at once.
+///
+/// # Example
+/// - Encode a 100 by 100 RGB image a row at a time
+///
+/// ```
+/// use zune_core::bit_depth::BitDepth;
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_core::options::EncoderOptions;
+/// use zune_qoi::QoiStreamEncoder;
+/// use zune_qoi::QoiEncodeErrors;
+///
+/// fn main()->Result<(), QoiEncodeErrors>{
+///     let options = EncoderOptions::new(100, 100, ColorSpace::RGB, BitDepth::Eight);
+///     let mut sink = vec![];
+///     let mut encoder = QoiStreamEncoder::new(options, &mut sink);
+///
+///     for y in 0..100 {
+///         let row = [(y % 256) as u8; 100 * 3];
+///         encoder.write_pixels(&row)?;
+///     }
+///     let size = encoder.finish()?;
+///     Ok(())
+///}
+/// ```
+pub struct QoiStreamEncoder<T: ZByteWriterTrait> {
+    stream:                ZWriter<T>,
+    options:               EncoderOptions,
+    color_characteristics: ColorCharacteristics,
+    state:                 EncoderState,
+    wrote_headers:         bool,
+    // number of pixel bytes received so far
+    bytes_received:        usize
+}
+
+impl<T: ZByteWriterTrait> QoiStreamEncoder<T> {
+    /// Create a new encoder which writes to `sink`
+    ///
+    /// # Arguments
+    /// - options: Encoder details for data, this contains width, height and number of color components
+    /// - sink: Where the encoded image is written to
+    pub fn new(options: EncoderOptions, sink: T) -> QoiStreamEncoder<T> {
+        QoiStreamEncoder {
+            stream: ZWriter::new(sink),
+            options,
+            color_characteristics: ColorCharacteristics::sRGB,
+            state: EncoderState::new(),
+            wrote_headers: false,
+            bytes_received: 0
+        }
+    }
+    /// Set the color characteristics written in the header
+    ///
+    /// This has to be called before the first [`write_pixels`](Self::write_pixels) call,
+    /// since that writes the header
+    pub fn set_color_characteristics(&mut self, characteristics: ColorCharacteristics) {
+        self.color_characteristics = characteristics;
+    }
+
+    fn expected_len(&self) -> usize {
+        self.options.width() * self.options.height() * self.options.colorspace().num_components()
+    }
+
+    /// Encode the next pixels of the image
+    ///
+    /// Pixels are in the colorspace given in the options and in raster order,
+    /// `pixels` can hold any number of whole pixels, e.g. a scanline, a
+    /// group of scanlines or the whole image.
+    ///
+    /// # Errors
+    /// - If `pixels` has a partial pixel
+    /// - If more pixels than `width*height` are written in total
+    pub fn write_pixels(&mut self, pixels: &[u8]) -> Result<(), QoiEncodeErrors> {
+        if !self.wrote_headers {
+            write_headers(&self.options, self.color_characteristics, &mut self.stream)?;
+            self.wrote_headers = true;
+        }
+        let channel_count = self.options.colorspace().num_components();
+
+        if !pixels.len().is_multiple_of(channel_count) {
+            return Err(QoiEncodeErrors::Generic(
+                "Pixels length isn't a multiple of the number of components"
+            ));
+        }
+        if self.bytes_received + pixels.len() > self.expected_len() {
+            return Err(QoiEncodeErrors::Generic(
+                "More pixels written than the image dimensions allow"
+            ));
+        }
+        self.state
+            .encode_pixels(pixels, channel_count, &mut self.stream)?;
+        self.bytes_received += pixels.len();
+
+        Ok(())
+    }
+
+    /// Finish encoding, writing the end marker
+    ///
+    /// # Returns
+    /// - Ok(size): Actual bytes written to the sink
+    /// - Err: If fewer pixels than the image dimensions were written, or the sink errored
+    pub fn finish(mut self) -> Result<usize, QoiEncodeErrors> {
+        if !self.wrote_headers {
+            write_headers(&self.options, self.color_characteristics, &mut self.stream)?;
+        }
+        if self.bytes_received != self.expected_len() {
+            return Err(QoiEncodeErrors::Generic(
+                "Expected length doesn't match pixels length"
+            ));
+        }
+        self.state.finish(&mut self.stream)?;
+
+        Ok(self.stream.bytes_written())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use alloc::vec::Vec;
+
     use zune_core::bytestream::ZCursor;
     use zune_core::colorspace::ColorSpace;
     use zune_core::options::EncoderOptions;
 
-    use crate::QoiEncoder;
+    use crate::{QoiEncoder, QoiStreamEncoder};
 
     #[test]
     fn test_qoi_encode_rgb() {
@@ -268,4 +432,44 @@ mod tests {
         let decoded_pixels = decoder.decode().unwrap();
         assert_eq!(&pixels[..], &decoded_pixels[..]);
     }
+
+    #[test]
+    fn test_qoi_stream_encoder_matches() {
+        use zune_core::bit_depth::BitDepth;
+        const W: usize = 67;
+        const H: usize = 31;
+
+        // runs crossing row boundaries exercise the carried over state
+        let pixels: Vec<u8> = (0..W * H * 4).map(|i| ((i / 37) % 256) as u8).collect();
+        let options = EncoderOptions::new(W, H, ColorSpace::RGBA, BitDepth::Eight);
+
+        let mut expected = vec![];
+        QoiEncoder::new(&pixels, options)
+            .encode(&mut expected)
+            .unwrap();
+
+        let mut output = vec![];
+        let mut encoder = QoiStreamEncoder::new(options, &mut output);
+        // uneven chunks, one and a half rows
+        for chunk in pixels.chunks((W * 3 / 2) * 4) {
+            encoder.write_pixels(chunk).unwrap();
+        }
+        let size = encoder.finish().unwrap();
+
+        assert_eq!(size, expected.len());
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_qoi_stream_encoder_short() {
+        use zune_core::bit_depth::BitDepth;
+
+        let options = EncoderOptions::new(4, 4, ColorSpace::RGB, BitDepth::Eight);
+        let mut output = vec![];
+        let mut encoder = QoiStreamEncoder::new(options, &mut output);
+
+        encoder.write_pixels(&[0; 4 * 3]).unwrap();
+        assert!(encoder.write_pixels(&[0; 2]).is_err());
+        assert!(encoder.finish().is_err());
+    }
 }
@@ -10,6 +10,7 @@ use core::fmt::{Debug, Display, Formatter};
 
 use zune_core::bytestream::ZByteIoError;
 use zune_core::colorspace::ColorSpace;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
 
 /// Possible Errors that may occur during decoding
 pub enum QoiErrors {
@@ -158,3 +159,29 @@ impl From<ZByteIoError> for QoiEncodeErrors {
         Self::IoError(value)
     }
 }
+
+impl ZErrorCodeTrait for QoiErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::WrongMagicBytes
+            | Self::InsufficientData(_, _)
+            | Self::UnknownChannels(_)
+            | Self::UnknownColorspace(_)
+            | Self::Generic(_)
+            | Self::GenericStatic(_) => ZErrorCode::CorruptData,
+            Self::TooSmallOutput(_, _) => ZErrorCode::InvalidInput,
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
+
+impl ZErrorCodeTrait for QoiEncodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::UnsupportedColorspace(_, _) => ZErrorCode::Unsupported,
+            Self::TooLargeDimensions(_) => ZErrorCode::LimitsExceeded,
+            Self::Generic(_) => ZErrorCode::InvalidInput,
+            Self::IoError(err) => err.code()
+        }
+    }
+}
@@ -13,6 +13,8 @@
 //! -`no_std`
 //! - Fast
 //! - Fuzz tested
+//! - Multi-frame sequences via [`QoiSequenceEncoder`] and [`QoiSequenceDecoder`]
+//! - Incremental, row by row encoding via [`QoiStreamEncoder`]
 //!
 //! ## `no_std`
 //! You can use `no_std` with alloc feature to compile for `no_std` endpoints
@@ -25,8 +27,10 @@ extern crate core;
 pub use decoder::*;
 pub use encoder::*;
 pub use errors::*;
+pub use sequence::*;
 pub use zune_core;
 mod constants;
 mod decoder;
 mod encoder;
 mod errors;
+mod sequence;
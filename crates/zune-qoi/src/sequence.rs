@@ -0,0 +1,404 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software; You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! QOI sequences, a simple container for multiple QOI frames
+//!
+//! This is not part of the QOI specification, it is a thin wrapper used
+//! to store animations and image sequences as lossless QOI frames,
+//! useful for fast intermediate caching of animations.
+//!
+//! # Layout
+//! All integers are stored in big endian
+//!
+//! | Field       | Size | Description                                         |
+//! |-------------|------|-----------------------------------------------------|
+//! | magic       | 4    | `qois`                                              |
+//! | version     | 1    | Container version, currently `1`                    |
+//! | frames      | 4    | Number of frames in the sequence                    |
+//! | index       | 16*n | For each frame, `offset:u64`,`length:u32`,`num:u16`,`denom:u16` |
+//! | frame data  | ..   | `frames` concatenated QOI images                     |
+//!
+//! - `offset` is the absolute position of the frame's `qoif` header from the start of the stream
+//! - `length` is the size of the encoded QOI frame
+//! - `num` and `denom` are the frame delay numerator and denominator in seconds
+//!
+//! All frames in a sequence must have the same dimensions and colorspace.
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use zune_core::bytestream::{
+    ZByteReaderTrait, ZByteWriterTrait, ZCursor, ZReader, ZSeekFrom, ZWriter
+};
+use zune_core::colorspace::ColorSpace;
+use zune_core::log::trace;
+use zune_core::options::{DecoderOptions, EncoderOptions};
+
+use crate::constants::{QOIS_INDEX_ENTRY_SIZE, QOIS_MAGIC, QOIS_VERSION};
+use crate::{QoiDecoder, QoiEncodeErrors, QoiEncoder, QoiErrors};
+
+/// A single entry in the sequence index
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct QoiSequenceFrame {
+    /// Absolute position of the frame from the start of the stream
+    pub offset:      u64,
+    /// Length of the encoded frame
+    pub length:      u32,
+    /// Frame delay numerator
+    pub numerator:   u16,
+    /// Frame delay denominator
+    pub denominator: u16
+}
+
+/// Encoder for QOI sequences
+///
+/// # Example
+/// - Encode two 10 by 10 RGB frames
+/// ```
+/// use zune_core::bit_depth::BitDepth;
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_core::options::EncoderOptions;
+/// use zune_qoi::QoiSequenceEncoder;
+///
+/// let frame_a = [0_u8; 10 * 10 * 3];
+/// let frame_b = [255_u8; 10 * 10 * 3];
+///
+/// let options = EncoderOptions::new(10, 10, ColorSpace::RGB, BitDepth::Eight);
+/// let mut encoder = QoiSequenceEncoder::new(options);
+///
+/// encoder.add_frame(&frame_a, 1, 10);
+/// encoder.add_frame(&frame_b, 1, 10);
+///
+/// let mut sink = vec![];
+/// encoder.encode(&mut sink).unwrap();
+/// ```
+pub struct QoiSequenceEncoder<'a> {
+    frames:  Vec<(&'a [u8], u16, u16)>,
+    options: EncoderOptions
+}
+
+impl<'a> QoiSequenceEncoder<'a> {
+    /// Create a new sequence encoder
+    ///
+    /// # Arguments
+    /// - options: Encoder details shared by all frames, width, height and colorspace
+    pub fn new(options: EncoderOptions) -> QoiSequenceEncoder<'a> {
+        QoiSequenceEncoder {
+            frames: vec![],
+            options
+        }
+    }
+    /// Add a frame to the sequence
+    ///
+    /// # Arguments
+    /// - data: Pixel data, size must be equal to `width*height*colorspace channels`
+    /// - numerator: Frame delay numerator
+    /// - denominator: Frame delay denominator
+    pub fn add_frame(&mut self, data: &'a [u8], numerator: u16, denominator: u16) {
+        self.frames.push((data, numerator, denominator));
+    }
+
+    /// Encode all frames added to the sequence, writing them to sink
+    ///
+    /// # Returns
+    /// - Ok(size): Actual bytes written to sink
+    /// - Err: The error encountered during encoding
+    pub fn encode<T: ZByteWriterTrait>(&mut self, sink: T) -> Result<usize, QoiEncodeErrors> {
+        if self.frames.is_empty() {
+            return Err(QoiEncodeErrors::Generic("No frames to encode in QOI sequence"));
+        }
+        if self.frames.len() as u64 > u64::from(u32::MAX) {
+            return Err(QoiEncodeErrors::Generic("Too many frames in QOI sequence"));
+        }
+        // encode the frames first since we need their sizes for the index
+        let mut encoded_frames = Vec::with_capacity(self.frames.len());
+
+        for (data, _, _) in &self.frames {
+            let mut output = vec![];
+            QoiEncoder::new(data, self.options).encode(&mut output)?;
+
+            if output.len() as u64 > u64::from(u32::MAX) {
+                return Err(QoiEncodeErrors::Generic("Too large QOI frame in sequence"));
+            }
+            encoded_frames.push(output);
+        }
+        let mut stream = ZWriter::new(sink);
+
+        stream.write_all(&QOIS_MAGIC.to_be_bytes())?;
+        stream.write_u8_err(QOIS_VERSION)?;
+        stream.write_u32_be_err(self.frames.len() as u32)?;
+
+        let mut offset = (4 + 1 + 4 + QOIS_INDEX_ENTRY_SIZE * self.frames.len()) as u64;
+
+        for ((_, numerator, denominator), frame) in self.frames.iter().zip(&encoded_frames) {
+            stream.write_u64_be_err(offset)?;
+            stream.write_u32_be_err(frame.len() as u32)?;
+            stream.write_u16_be_err(*numerator)?;
+            stream.write_u16_be_err(*denominator)?;
+
+            offset += frame.len() as u64;
+        }
+        for frame in &encoded_frames {
+            stream.write_all(frame)?;
+        }
+        trace!("Encoded {} frames into QOI sequence", self.frames.len());
+
+        Ok(stream.bytes_written())
+    }
+}
+
+/// Decoder for QOI sequences
+///
+/// The decoder reads the index on [`decode_headers`](Self::decode_headers), frames
+/// can then be decoded individually with [`decode_frame`](Self::decode_frame) or all at once
+/// with [`decode`](Self::decode)
+pub struct QoiSequenceDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    width:           usize,
+    height:          usize,
+    colorspace:      ColorSpace,
+    frames:          Vec<QoiSequenceFrame>,
+    decoded_headers: bool,
+    stream:          ZReader<T>,
+    options:         DecoderOptions
+}
+
+impl<T> QoiSequenceDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    /// Create a new QOI sequence decoder with the default options
+    pub fn new(data: T) -> QoiSequenceDecoder<T> {
+        QoiSequenceDecoder::new_with_options(data, DecoderOptions::default())
+    }
+    /// Create a new QOI sequence decoder that obeys specified restrictions
+    ///
+    /// The options are passed to each frame decoder
+    pub fn new_with_options(data: T, options: DecoderOptions) -> QoiSequenceDecoder<T> {
+        QoiSequenceDecoder {
+            width: 0,
+            height: 0,
+            colorspace: ColorSpace::RGB,
+            frames: vec![],
+            decoded_headers: false,
+            stream: ZReader::new(data),
+            options
+        }
+    }
+    /// Decode the sequence header and index, and the header of the first frame
+    pub fn decode_headers(&mut self) -> Result<(), QoiErrors> {
+        if self.decoded_headers {
+            return Ok(());
+        }
+        let magic = self.stream.read_fixed_bytes_or_error::<4>()?;
+
+        if magic != QOIS_MAGIC.to_be_bytes() {
+            return Err(QoiErrors::GenericStatic(
+                "Wrong magic bytes, expected `qois` as sequence start"
+            ));
+        }
+        let version = self.stream.read_u8_err()?;
+
+        if version != QOIS_VERSION {
+            let msg = format!("Unsupported QOI sequence version {version}");
+            return Err(QoiErrors::Generic(msg));
+        }
+        let num_frames = self.stream.get_u32_be_err()? as usize;
+
+        if num_frames == 0 {
+            return Err(QoiErrors::GenericStatic("QOI sequence contains no frames"));
+        }
+        // every frame needs an index entry, so bail out early
+        // on truncated streams before allocating
+        if num_frames.saturating_mul(QOIS_INDEX_ENTRY_SIZE) > self.remaining_bytes()? {
+            return Err(QoiErrors::GenericStatic(
+                "QOI sequence index extends past the end of the stream"
+            ));
+        }
+
+        let mut frames = Vec::with_capacity(num_frames);
+
+        for _ in 0..num_frames {
+            frames.push(QoiSequenceFrame {
+                offset:      self.stream.get_u64_be_err()?,
+                length:      self.stream.get_u32_be_err()?,
+                numerator:   self.stream.get_u16_be_err()?,
+                denominator: self.stream.get_u16_be_err()?
+            });
+        }
+        self.frames = frames;
+
+        // read dimensions from the first frame
+        let first = self.read_frame_bytes(0)?;
+        let mut decoder = QoiDecoder::new_with_options(ZCursor::new(&first), self.options);
+        decoder.decode_headers()?;
+
+        let (width, height) = decoder.dimensions().unwrap();
+
+        self.width = width;
+        self.height = height;
+        self.colorspace = decoder.colorspace().unwrap();
+        self.decoded_headers = true;
+
+        trace!("Sequence frames: {}", self.frames.len());
+        trace!("Sequence width: {}", self.width);
+        trace!("Sequence height: {}", self.height);
+
+        Ok(())
+    }
+
+    /// Return the number of bytes after the current stream position
+    fn remaining_bytes(&mut self) -> Result<usize, QoiErrors> {
+        let position = self.stream.position()?;
+        let end = self.stream.seek(ZSeekFrom::End(0))?;
+        self.stream.set_position(position as usize)?;
+
+        Ok(usize::try_from(end.saturating_sub(position)).unwrap_or(usize::MAX))
+    }
+
+    fn read_frame_bytes(&mut self, frame: usize) -> Result<Vec<u8>, QoiErrors> {
+        let entry = self.frames[frame];
+
+        let offset = usize::try_from(entry.offset)
+            .map_err(|_| QoiErrors::GenericStatic("Too large frame offset"))?;
+
+        self.stream.set_position(offset)?;
+        // confirm we have the bytes before allocating
+        if entry.length as usize > self.remaining_bytes()? {
+            return Err(QoiErrors::GenericStatic(
+                "QOI sequence frame extends past the end of the stream"
+            ));
+        }
+
+        let mut data = vec![0; entry.length as usize];
+        self.stream.read_exact_bytes(&mut data)?;
+
+        Ok(data)
+    }
+
+    /// Decode a single frame from the sequence returning its pixels
+    ///
+    /// # Arguments
+    /// - frame: The frame index, starting from zero
+    pub fn decode_frame(&mut self, frame: usize) -> Result<Vec<u8>, QoiErrors> {
+        self.decode_headers()?;
+
+        if frame >= self.frames.len() {
+            let msg = format!(
+                "Frame {frame} out of range, sequence has {} frames",
+                self.frames.len()
+            );
+            return Err(QoiErrors::Generic(msg));
+        }
+        let data = self.read_frame_bytes(frame)?;
+        let mut decoder = QoiDecoder::new_with_options(ZCursor::new(&data), self.options);
+        let pixels = decoder.decode()?;
+
+        if decoder.dimensions() != Some((self.width, self.height))
+            || decoder.colorspace() != Some(self.colorspace)
+        {
+            let msg = format!("Frame {frame} does not match the sequence dimensions or colorspace");
+            return Err(QoiErrors::Generic(msg));
+        }
+        Ok(pixels)
+    }
+    /// Decode all frames in the sequence
+    pub fn decode(&mut self) -> Result<Vec<Vec<u8>>, QoiErrors> {
+        self.decode_headers()?;
+
+        (0..self.frames.len())
+            .map(|frame| self.decode_frame(frame))
+            .collect()
+    }
+    /// Return the frame index of the sequence or none if headers
+    /// haven't been decoded
+    pub fn frames(&self) -> Option<&[QoiSequenceFrame]> {
+        if self.decoded_headers {
+            return Some(&self.frames);
+        }
+        None
+    }
+    /// Return the number of frames in the sequence or none if headers
+    /// haven't been decoded
+    pub fn num_frames(&self) -> Option<usize> {
+        self.frames().map(|x| x.len())
+    }
+    /// Returns QOI sequence dimensions as (width,height) or none if headers
+    /// haven't been decoded
+    pub fn dimensions(&self) -> Option<(usize, usize)> {
+        if self.decoded_headers {
+            return Some((self.width, self.height));
+        }
+        None
+    }
+    /// Returns the colorspace of the frames or none if headers
+    /// haven't been decoded
+    pub fn colorspace(&self) -> Option<ColorSpace> {
+        if self.decoded_headers {
+            return Some(self.colorspace);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::EncoderOptions;
+
+    use crate::{QoiSequenceDecoder, QoiSequenceEncoder};
+
+    #[test]
+    fn test_qoi_sequence_roundtrip() {
+        const W: usize = 20;
+        const H: usize = 10;
+
+        let frame_a = std::array::from_fn::<u8, { W * H * 4 }, _>(|i| (i % 256) as u8);
+        let frame_b = std::array::from_fn::<u8, { W * H * 4 }, _>(|i| (i % 7) as u8);
+
+        let options = EncoderOptions::new(W, H, ColorSpace::RGBA, BitDepth::Eight);
+        let mut encoder = QoiSequenceEncoder::new(options);
+        encoder.add_frame(&frame_a, 1, 10);
+        encoder.add_frame(&frame_b, 3, 10);
+
+        let mut output = vec![];
+        encoder.encode(&mut output).unwrap();
+
+        let mut decoder = QoiSequenceDecoder::new(ZCursor::new(&output));
+        let frames = decoder.decode().unwrap();
+
+        assert_eq!(decoder.dimensions(), Some((W, H)));
+        assert_eq!(decoder.frames().unwrap()[1].numerator, 3);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(&frames[0][..], &frame_a[..]);
+        assert_eq!(&frames[1][..], &frame_b[..]);
+    }
+
+    #[test]
+    fn test_qoi_sequence_huge_frame_count() {
+        // a 17 byte file claiming 0xFFFFFFFF frames, this used to allocate
+        // room for the whole index before noticing it is missing
+        let mut data = b"qois\x01".to_vec();
+        data.extend_from_slice(&u32::MAX.to_be_bytes());
+        data.extend_from_slice(&[0; 8]);
+
+        let mut decoder = QoiSequenceDecoder::new(ZCursor::new(&data));
+        assert!(decoder.decode_headers().is_err());
+
+        // a single frame whose length is larger than the file
+        let mut data = b"qois\x01".to_vec();
+        data.extend_from_slice(&1_u32.to_be_bytes());
+        data.extend_from_slice(&25_u64.to_be_bytes());
+        data.extend_from_slice(&u32::MAX.to_be_bytes());
+        data.extend_from_slice(&[0, 1, 0, 10]);
+
+        let mut decoder = QoiSequenceDecoder::new(ZCursor::new(&data));
+        assert!(decoder.decode_headers().is_err());
+    }
+}
@@ -0,0 +1,534 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! TGA decoder
+//!
+//! The file starts with an 18 byte header describing the image, followed by an optional
+//! image id and color map, then pixels which may be run length encoded.
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::{ZByteReaderTrait, ZReader, ZSeekFrom};
+use zune_core::colorspace::ColorSpace;
+use zune_core::log::{trace, warn};
+use zune_core::options::DecoderOptions;
+
+use crate::errors::TgaDecodeErrors;
+
+/// Size of the fixed TGA header
+pub const TGA_HEADER_SIZE: usize = 18;
+/// Signature at the end of TGA 2.0 files
+pub const TGA_FOOTER_SIGNATURE: &[u8; 18] = b"TRUEVISION-XFILE.\0";
+
+pub(crate) const IMAGE_TYPE_COLOR_MAPPED: u8 = 1;
+pub(crate) const IMAGE_TYPE_TRUE_COLOR: u8 = 2;
+pub(crate) const IMAGE_TYPE_GRAYSCALE: u8 = 3;
+/// Added to the image type for run length encoded images
+pub(crate) const IMAGE_TYPE_RLE: u8 = 8;
+
+/// Descriptor bit set when pixels are stored right to left
+const DESCRIPTOR_RIGHT_TO_LEFT: u8 = 1 << 4;
+/// Descriptor bit set when pixels are stored top to bottom
+pub(crate) const DESCRIPTOR_TOP_TO_BOTTOM: u8 = 1 << 5;
+
+/// Probe some bytes to see if they consist of a TGA image
+///
+/// TGA has no magic bytes, so this checks that the header fields hold
+/// values a valid file would have, this can have false positives, so formats
+/// with magic bytes should be checked first.
+pub fn probe_tga(bytes: &[u8]) -> bool {
+    let Some(header) = bytes.get(..TGA_HEADER_SIZE) else {
+        return false;
+    };
+    let color_map_type = header[1];
+    let image_type = header[2];
+    let color_map_length = u16::from_le_bytes([header[5], header[6]]);
+    let color_map_depth = header[7];
+    let width = u16::from_le_bytes([header[12], header[13]]);
+    let height = u16::from_le_bytes([header[14], header[15]]);
+    let depth = header[16];
+    let descriptor = header[17];
+
+    let valid_type = match image_type & !IMAGE_TYPE_RLE {
+        IMAGE_TYPE_COLOR_MAPPED => color_map_type == 1 && matches!(depth, 8 | 16),
+        IMAGE_TYPE_TRUE_COLOR => matches!(depth, 15 | 16 | 24 | 32),
+        IMAGE_TYPE_GRAYSCALE => matches!(depth, 8 | 16),
+        _ => false
+    };
+    let valid_color_map = match color_map_type {
+        0 => true,
+        1 => color_map_length != 0 && matches!(color_map_depth, 15 | 16 | 24 | 32),
+        _ => false
+    };
+    // the interleaving bits of the descriptor are obsolete and always zero
+    valid_type && valid_color_map && width != 0 && height != 0 && descriptor & 0xC0 == 0
+}
+
+/// Convert a 15/16, 24 or 32 bit little endian BGR(A) color to RGBA
+#[inline]
+fn read_color(bytes: &[u8]) -> [u8; 4] {
+    match bytes.len() {
+        2 => {
+            let value = u16::from_le_bytes([bytes[0], bytes[1]]);
+            // expand 5 bits to 8 bits
+            let expand = |x: u16| ((x << 3) | (x >> 2)) as u8;
+            let alpha = if value & 0x8000 != 0 { 255 } else { 0 };
+
+            [
+                expand((value >> 10) & 31),
+                expand((value >> 5) & 31),
+                expand(value & 31),
+                alpha
+            ]
+        }
+        3 => [bytes[2], bytes[1], bytes[0], 255],
+        _ => [bytes[2], bytes[1], bytes[0], bytes[3]]
+    }
+}
+
+/// A TGA decoder
+///
+/// Supports uncompressed and RLE compressed color mapped, true color and
+/// grayscale images, pixels are always returned in top-left order
+/// regardless of the origin stored in the file.
+///
+/// # Example
+/// ```no_run
+/// use zune_core::bytestream::ZCursor;
+/// use zune_tga::TgaDecoder;
+///
+/// let mut decoder = TgaDecoder::new(ZCursor::new(&[]));
+/// let pixels = decoder.decode().unwrap();
+/// let (width, height) = decoder.dimensions().unwrap();
+/// ```
+pub struct TgaDecoder<T: ZByteReaderTrait> {
+    stream:            ZReader<T>,
+    options:           DecoderOptions,
+    width:             usize,
+    height:            usize,
+    image_type:        u8,
+    pixel_depth:       u8,
+    alpha_bits:        u8,
+    flip_vertically:   bool,
+    flip_horizontally: bool,
+    palette:           Vec<[u8; 4]>,
+    palette_start:     usize,
+    colorspace:        ColorSpace,
+    decoded_headers:   bool
+}
+
+impl<T> TgaDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    /// Create a new TGA decoder reading data from `data`
+    pub fn new(data: T) -> TgaDecoder<T> {
+        TgaDecoder::new_with_options(data, DecoderOptions::default())
+    }
+    /// Create a new decoder instance with specified options
+    pub fn new_with_options(data: T, options: DecoderOptions) -> TgaDecoder<T> {
+        TgaDecoder {
+            stream: ZReader::new(data),
+            options,
+            width: 0,
+            height: 0,
+            image_type: 0,
+            pixel_depth: 0,
+            alpha_bits: 0,
+            flip_vertically: false,
+            flip_horizontally: false,
+            palette: vec![],
+            palette_start: 0,
+            colorspace: ColorSpace::Unknown,
+            decoded_headers: false
+        }
+    }
+
+    /// Decode the TGA header and color map, storing
+    /// information in the decoder
+    pub fn decode_headers(&mut self) -> Result<(), TgaDecodeErrors> {
+        if self.decoded_headers {
+            return Ok(());
+        }
+        let header = self.stream.read_fixed_bytes_or_error::<TGA_HEADER_SIZE>()?;
+
+        if !probe_tga(&header) {
+            if header[2] == 0 {
+                return Err(TgaDecodeErrors::Unsupported(
+                    "Image contains no image data".into()
+                ));
+            }
+            return Err(TgaDecodeErrors::InvalidHeader(
+                "Unknown image type, pixel depth or color map"
+            ));
+        }
+        let id_length = usize::from(header[0]);
+        let color_map_type = header[1];
+        let image_type = header[2];
+        let color_map_start = usize::from(u16::from_le_bytes([header[3], header[4]]));
+        let color_map_length = usize::from(u16::from_le_bytes([header[5], header[6]]));
+        let color_map_depth = header[7];
+        let width = usize::from(u16::from_le_bytes([header[12], header[13]]));
+        let height = usize::from(u16::from_le_bytes([header[14], header[15]]));
+        let pixel_depth = header[16];
+        let descriptor = header[17];
+
+        trace!("Image type: {}", image_type);
+        trace!("Width: {}", width);
+        trace!("Height: {}", height);
+        trace!("Pixel depth: {}", pixel_depth);
+        trace!("Descriptor: {:#b}", descriptor);
+
+        if width > self.options.max_width() {
+            return Err(TgaDecodeErrors::TooLargeDimensions(
+                "width",
+                self.options.max_width(),
+                width
+            ));
+        }
+        if height > self.options.max_height() {
+            return Err(TgaDecodeErrors::TooLargeDimensions(
+                "height",
+                self.options.max_height(),
+                height
+            ));
+        }
+        // image id, free form data
+        self.stream.skip(id_length)?;
+
+        if color_map_type == 1 {
+            let entry_size = usize::from(color_map_depth).div_ceil(8);
+
+            trace!("Color map entries: {}", color_map_length);
+
+            if image_type & !IMAGE_TYPE_RLE == IMAGE_TYPE_COLOR_MAPPED {
+                self.palette = (0..color_map_length)
+                    .map(|_| {
+                        let mut entry = [0; 4];
+                        self.stream
+                            .read_exact_bytes(&mut entry[..entry_size])
+                            .map(|_| read_color(&entry[..entry_size]))
+                    })
+                    .collect::<Result<Vec<[u8; 4]>, _>>()?;
+                self.palette_start = color_map_start;
+            } else {
+                // color maps are allowed for other image types, but unused
+                self.stream.skip(entry_size * color_map_length)?;
+            }
+        }
+        self.alpha_bits = descriptor & 0x0F;
+
+        let has_alpha = |depth: u8| depth == 32 || (depth == 16 && self.alpha_bits > 0);
+
+        self.colorspace = match image_type & !IMAGE_TYPE_RLE {
+            IMAGE_TYPE_COLOR_MAPPED if has_alpha(color_map_depth) => ColorSpace::RGBA,
+            IMAGE_TYPE_COLOR_MAPPED => ColorSpace::RGB,
+            IMAGE_TYPE_TRUE_COLOR if has_alpha(pixel_depth) => ColorSpace::RGBA,
+            IMAGE_TYPE_TRUE_COLOR => ColorSpace::RGB,
+            _ if pixel_depth == 16 => ColorSpace::LumaA,
+            _ => ColorSpace::Luma
+        };
+        trace!("Colorspace: {:?}", self.colorspace);
+
+        self.width = width;
+        self.height = height;
+        self.image_type = image_type;
+        self.pixel_depth = pixel_depth;
+        self.flip_vertically = descriptor & DESCRIPTOR_TOP_TO_BOTTOM == 0;
+        self.flip_horizontally = descriptor & DESCRIPTOR_RIGHT_TO_LEFT != 0;
+        self.decoded_headers = true;
+
+        Ok(())
+    }
+
+    /// Return the width and height of the image or `None` if
+    /// the headers haven't been decoded
+    pub const fn dimensions(&self) -> Option<(usize, usize)> {
+        if self.decoded_headers {
+            return Some((self.width, self.height));
+        }
+        None
+    }
+
+    /// Return the colorspace of the decoded pixels or `None`
+    /// if the headers haven't been decoded
+    ///
+    /// This is one of Luma, LumaA, RGB or RGBA
+    pub const fn colorspace(&self) -> Option<ColorSpace> {
+        if self.decoded_headers {
+            return Some(self.colorspace);
+        }
+        None
+    }
+
+    /// Return the bit depth of the image
+    ///
+    /// This is always [BitDepth::Eight], 15 and 16 bit
+    /// color images are expanded to 8 bits per channel
+    pub const fn depth(&self) -> BitDepth {
+        BitDepth::Eight
+    }
+
+    /// Return true if the image pixels are run length encoded or `None`
+    /// if the headers haven't been decoded
+    pub const fn is_rle(&self) -> Option<bool> {
+        if self.decoded_headers {
+            return Some(self.image_type & IMAGE_TYPE_RLE != 0);
+        }
+        None
+    }
+
+    /// Return the size of the buffer needed to hold the decoded pixels
+    ///
+    /// Returns `None` if headers haven't been decoded or if the calculation overflows
+    pub fn output_buffer_size(&self) -> Option<usize> {
+        if !self.decoded_headers {
+            return None;
+        }
+        self.width
+            .checked_mul(self.height)?
+            .checked_mul(self.colorspace.num_components())
+    }
+
+    /// Decode the image returning pixels in an allocated buffer
+    ///
+    /// Also see [`decode_into`](Self::decode_into) which decodes into
+    /// a pre-allocated buffer
+    pub fn decode(&mut self) -> Result<Vec<u8>, TgaDecodeErrors> {
+        self.decode_headers()?;
+
+        let size = self
+            .output_buffer_size()
+            .ok_or(TgaDecodeErrors::GenericStatic("Overflow in output size"))?;
+        // fail on truncated files before allocating for their claimed dimensions
+        self.raw_size()?;
+
+        let mut output = vec![0; size];
+
+        self.decode_into(&mut output)?;
+
+        Ok(output)
+    }
+
+    /// Decode the image into `sink`
+    ///
+    /// It is an error if the sink buffer is smaller than
+    /// [`output_buffer_size()`](Self::output_buffer_size)
+    pub fn decode_into(&mut self, sink: &mut [u8]) -> Result<(), TgaDecodeErrors> {
+        self.decode_headers()?;
+
+        let size = self
+            .output_buffer_size()
+            .ok_or(TgaDecodeErrors::GenericStatic("Overflow in output size"))?;
+
+        if sink.len() < size {
+            return Err(TgaDecodeErrors::Generic(format!(
+                "Too small output buffer, expected {size}, found {}",
+                sink.len()
+            )));
+        }
+        let bytes_per_pixel = usize::from(self.pixel_depth).div_ceil(8);
+        let mut raw = vec![0; self.raw_size()?];
+
+        if self.image_type & IMAGE_TYPE_RLE != 0 {
+            self.decode_rle(&mut raw, bytes_per_pixel)?;
+        } else {
+            self.stream.read_exact_bytes(&mut raw)?;
+        }
+        let components = self.colorspace.num_components();
+        let image_type = self.image_type & !IMAGE_TYPE_RLE;
+
+        for (y, row) in raw.chunks_exact(self.width * bytes_per_pixel).enumerate() {
+            let out_y = if self.flip_vertically { self.height - 1 - y } else { y };
+
+            for (x, pixel) in row.chunks_exact(bytes_per_pixel).enumerate() {
+                let out_x = if self.flip_horizontally { self.width - 1 - x } else { x };
+                let position = (out_y * self.width + out_x) * components;
+                let output = &mut sink[position..position + components];
+
+                match image_type {
+                    IMAGE_TYPE_COLOR_MAPPED => {
+                        let index = if bytes_per_pixel == 1 {
+                            usize::from(pixel[0])
+                        } else {
+                            usize::from(u16::from_le_bytes([pixel[0], pixel[1]]))
+                        };
+                        let color = index
+                            .checked_sub(self.palette_start)
+                            .and_then(|x| self.palette.get(x));
+
+                        match color {
+                            Some(color) => output.copy_from_slice(&color[..components]),
+                            None if self.options.strict_mode() => {
+                                return Err(TgaDecodeErrors::Generic(format!(
+                                    "Color map index {index} out of range"
+                                )));
+                            }
+                            None => {
+                                warn!("Color map index {} out of range", index);
+                                output.copy_from_slice(&[0, 0, 0, 255][..components]);
+                            }
+                        }
+                    }
+                    IMAGE_TYPE_TRUE_COLOR => {
+                        output.copy_from_slice(&read_color(pixel)[..components]);
+                    }
+                    // grayscale, with an optional alpha channel
+                    _ => output.copy_from_slice(pixel)
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the size of the stored pixels, checking that the remaining
+    /// input can hold them
+    ///
+    /// A run packet expands to at most 128 pixels, so RLE data is at
+    /// most 128 times smaller than the pixels it holds
+    fn raw_size(&mut self) -> Result<usize, TgaDecodeErrors> {
+        let bytes_per_pixel = usize::from(self.pixel_depth).div_ceil(8);
+        let size = self
+            .width
+            .checked_mul(self.height)
+            .and_then(|x| x.checked_mul(bytes_per_pixel))
+            .ok_or(TgaDecodeErrors::GenericStatic("Overflow in output size"))?;
+
+        let position = self.stream.position()?;
+        let end = self.stream.seek(ZSeekFrom::End(0))?;
+        self.stream.set_position(position as usize)?;
+
+        let expansion = if self.image_type & IMAGE_TYPE_RLE != 0 { 128 } else { 1 };
+        let remaining = usize::try_from(end.saturating_sub(position)).unwrap_or(usize::MAX);
+
+        if remaining.saturating_mul(expansion) < size {
+            return Err(TgaDecodeErrors::Generic(format!(
+                "Not enough data for the image dimensions, expected at least {} bytes, found {remaining}",
+                size.div_ceil(expansion)
+            )));
+        }
+        Ok(size)
+    }
+
+        /// Decode run length encoded pixels into `raw`
+    ///
+    /// Packets may cross scan lines, so the pixels are decoded as one stream
+    fn decode_rle(
+        &mut self, raw: &mut [u8], bytes_per_pixel: usize
+    ) -> Result<(), TgaDecodeErrors> {
+        let mut position = 0;
+
+        while position < raw.len() {
+            let header = self.stream.read_u8_err()?;
+            let count = usize::from(header & 0x7F) + 1;
+            let length = (count * bytes_per_pixel).min(raw.len() - position);
+            let output = &mut raw[position..position + length];
+
+            if header & 0x80 != 0 {
+                // run packet, one pixel repeated count times
+                let mut pixel = [0; 4];
+                self.stream
+                    .read_exact_bytes(&mut pixel[..bytes_per_pixel])?;
+
+                for chunk in output.chunks_exact_mut(bytes_per_pixel) {
+                    chunk.copy_from_slice(&pixel[..bytes_per_pixel]);
+                }
+            } else {
+                // raw packet, count pixels follow
+                self.stream.read_exact_bytes(output)?;
+            }
+            position += length;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::EncoderOptions;
+
+    use crate::{probe_tga, TgaDecoder, TgaEncoder};
+
+    #[test]
+    fn test_decode_rle_bottom_left_color_mapped() {
+        // 3x2 color mapped image with an rle run crossing the scan line
+        let mut data = vec![0, 1, 9, 0, 0, 2, 0, 24, 0, 0, 0, 0, 3, 0, 2, 0, 8, 0];
+        // palette, BGR
+        data.extend_from_slice(&[255, 0, 0, 0, 0, 255]);
+        // bottom row: [0, 0, 0], top row [0, 1, 1]
+        data.extend_from_slice(&[0x83, 0, 0x01, 1, 1]);
+
+        assert!(probe_tga(&data));
+
+        let mut decoder = TgaDecoder::new(ZCursor::new(&data));
+        let pixels = decoder.decode().unwrap();
+
+        assert_eq!(decoder.colorspace(), Some(ColorSpace::RGB));
+        assert_eq!(decoder.is_rle(), Some(true));
+
+        let (blue, red) = ([0, 0, 255], [255, 0, 0]);
+        let expected: Vec<u8> = [blue, red, red, blue, blue, blue].concat();
+        assert_eq!(pixels, expected);
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let (width, height) = (13, 5);
+
+        for colorspace in [
+            ColorSpace::Luma,
+            ColorSpace::LumaA,
+            ColorSpace::RGB,
+            ColorSpace::RGBA
+        ] {
+            let size = width * height * colorspace.num_components();
+            // runs and noise, to exercise both packet types
+            let pixels: Vec<u8> = (0..size)
+                .map(|x| if x % 40 < 20 { 7 } else { (x * 31) as u8 })
+                .collect();
+            let options = EncoderOptions::new(width, height, colorspace, BitDepth::Eight);
+
+            for rle in [false, true] {
+                let mut encoded = vec![];
+                let mut encoder = TgaEncoder::new(&pixels, options);
+                encoder.set_rle(rle);
+                encoder.encode(&mut encoded).unwrap();
+
+                let mut decoder = TgaDecoder::new(ZCursor::new(&encoded));
+                let decoded = decoder.decode().unwrap();
+
+                assert_eq!(decoder.colorspace(), Some(colorspace));
+                assert_eq!(decoder.dimensions(), Some((width, height)));
+                assert_eq!(decoded, pixels, "{colorspace:?} rle:{rle}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_truncated_image_data() {
+        // 16384x16384 grayscale image with a single run packet, which
+        // covers 128 pixels at most
+        let mut data = vec![0, 0, 11, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 64, 0, 64, 8, 0];
+        data.extend_from_slice(&[0xFF, 7]);
+
+        let mut decoder = TgaDecoder::new(ZCursor::new(&data));
+        assert!(decoder.decode().is_err());
+
+        // the same image, uncompressed
+        data[2] = 3;
+        let mut decoder = TgaDecoder::new(ZCursor::new(&data));
+        assert!(decoder.decode().is_err());
+    }
+}
@@ -0,0 +1,190 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Encoding support for TGA images
+use alloc::vec::Vec;
+
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::{ZByteWriterTrait, ZWriter};
+use zune_core::colorspace::ColorSpace;
+use zune_core::options::EncoderOptions;
+
+use crate::decoder::{
+    DESCRIPTOR_TOP_TO_BOTTOM, IMAGE_TYPE_GRAYSCALE, IMAGE_TYPE_RLE, IMAGE_TYPE_TRUE_COLOR,
+    TGA_FOOTER_SIGNATURE, TGA_HEADER_SIZE
+};
+use crate::errors::TgaEncodeErrors;
+
+/// Maximum number of pixels in a single RLE packet
+const MAX_PACKET_SIZE: usize = 128;
+
+/// A TGA encoder
+///
+/// Images are written with a top-left origin and a TGA 2.0 footer,
+/// run length encoding can be enabled with [`set_rle`](Self::set_rle).
+///
+/// Data is expected to be 8 bit Luma, LumaA, RGB or RGBA
+///
+/// # Example
+/// - Encode a 10 by 10 RGB image with run length encoding
+/// ```
+/// use zune_core::bit_depth::BitDepth;
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_core::options::EncoderOptions;
+/// use zune_tga::TgaEncoder;
+///
+/// let pixels = [128_u8; 10 * 10 * 3];
+/// let options = EncoderOptions::new(10, 10, ColorSpace::RGB, BitDepth::Eight);
+///
+/// let mut output = vec![];
+/// TgaEncoder::new(&pixels, options)
+///     .set_rle(true)
+///     .encode(&mut output)
+///     .unwrap();
+/// ```
+pub struct TgaEncoder<'a> {
+    data:    &'a [u8],
+    options: EncoderOptions,
+    rle:     bool
+}
+
+impl<'a> TgaEncoder<'a> {
+    /// Create a new encoder which will encode the specified data
+    /// whose format is contained in options
+    ///
+    /// # Arguments
+    /// - data: The data to encode
+    /// - options: Meta information about the image, i.e its width, height, colorspace and depth
+    pub fn new(data: &'a [u8], options: EncoderOptions) -> TgaEncoder<'a> {
+        TgaEncoder {
+            data,
+            options,
+            rle: false
+        }
+    }
+
+    /// Whether pixels should be run length encoded
+    ///
+    /// Default is false
+    pub fn set_rle(&mut self, yes: bool) -> &mut Self {
+        self.rle = yes;
+        self
+    }
+
+    /// Encode the image writing it to `sink`
+    ///
+    /// # Returns
+    /// - `Ok(usize)`: The number of bytes written
+    /// - Err: An error that occurred during encoding
+    pub fn encode<T: ZByteWriterTrait>(&self, sink: T) -> Result<usize, TgaEncodeErrors> {
+        let (width, height) = (self.options.width(), self.options.height());
+        let colorspace = self.options.colorspace();
+
+        if self.options.depth() != BitDepth::Eight {
+            return Err(TgaEncodeErrors::UnsupportedBitDepth(self.options.depth()));
+        }
+        let (image_type, alpha_bits) = match colorspace {
+            ColorSpace::Luma => (IMAGE_TYPE_GRAYSCALE, 0),
+            ColorSpace::LumaA => (IMAGE_TYPE_GRAYSCALE, 8),
+            ColorSpace::RGB => (IMAGE_TYPE_TRUE_COLOR, 0),
+            ColorSpace::RGBA => (IMAGE_TYPE_TRUE_COLOR, 8),
+            _ => return Err(TgaEncodeErrors::UnsupportedColorSpace(colorspace))
+        };
+        for dimension in [width, height] {
+            if dimension > usize::from(u16::MAX) {
+                return Err(TgaEncodeErrors::TooLargeDimensions(dimension));
+            }
+        }
+        let components = colorspace.num_components();
+        let expected = width * height * components;
+
+        if self.data.len() != expected {
+            return Err(TgaEncodeErrors::TooShortInput(expected, self.data.len()));
+        }
+        let image_type = if self.rle { image_type | IMAGE_TYPE_RLE } else { image_type };
+
+        let mut stream = ZWriter::new(sink);
+
+        stream.reserve(TGA_HEADER_SIZE + expected + 26)?;
+
+        let mut header = [0_u8; TGA_HEADER_SIZE];
+
+        header[2] = image_type;
+        header[12..14].copy_from_slice(&(width as u16).to_le_bytes());
+        header[14..16].copy_from_slice(&(height as u16).to_le_bytes());
+        header[16] = (components * 8) as u8;
+        header[17] = alpha_bits | DESCRIPTOR_TOP_TO_BOTTOM;
+
+        stream.write_all(&header)?;
+
+        let mut row = Vec::with_capacity(width * components);
+
+        for input in self.data.chunks_exact(width * components) {
+            row.clear();
+            // color is stored as BGR(A)
+            match colorspace {
+                ColorSpace::RGB | ColorSpace::RGBA => {
+                    for pixel in input.chunks_exact(components) {
+                        row.extend_from_slice(&[pixel[2], pixel[1], pixel[0]]);
+                        row.extend_from_slice(&pixel[3..]);
+                    }
+                }
+                _ => row.extend_from_slice(input)
+            }
+            if self.rle {
+                write_rle_row(&row, components, &mut stream)?;
+            } else {
+                stream.write_all(&row)?;
+            }
+        }
+        // TGA 2.0 footer, no extension or developer areas
+        stream.write_all(&[0; 8])?;
+        stream.write_all(TGA_FOOTER_SIGNATURE)?;
+
+        Ok(stream.bytes_written())
+    }
+}
+
+/// Run length encode a single scan line
+///
+/// Packets don't cross scan lines as recommended by the specification
+fn write_rle_row<T: ZByteWriterTrait>(
+    row: &[u8], bytes_per_pixel: usize, stream: &mut ZWriter<T>
+) -> Result<(), TgaEncodeErrors> {
+    let pixel = |i: usize| &row[i * bytes_per_pixel..(i + 1) * bytes_per_pixel];
+    let num_pixels = row.len() / bytes_per_pixel;
+
+    let mut i = 0;
+
+    while i < num_pixels {
+        let mut run = 1;
+
+        while i + run < num_pixels && run < MAX_PACKET_SIZE && pixel(i + run) == pixel(i) {
+            run += 1;
+        }
+        if run > 1 {
+            stream.write_u8_err(0x80 | (run - 1) as u8)?;
+            stream.write_all(pixel(i))?;
+            i += run;
+        } else {
+            // raw packet, up to the start of the next run. The first pixel
+            // differs from the next one so this always makes progress
+            let start = i;
+
+            while i < num_pixels
+                && i - start < MAX_PACKET_SIZE
+                && !(i + 1 < num_pixels && pixel(i) == pixel(i + 1))
+            {
+                i += 1;
+            }
+            stream.write_u8_err((i - start - 1) as u8)?;
+            stream.write_all(&row[start * bytes_per_pixel..i * bytes_per_pixel])?;
+        }
+    }
+    Ok(())
+}
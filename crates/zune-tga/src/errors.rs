@@ -0,0 +1,138 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use alloc::string::String;
+use core::fmt::{Debug, Formatter};
+
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::ZByteIoError;
+use zune_core::colorspace::ColorSpace;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
+
+/// TGA errors that can occur during decoding
+#[non_exhaustive]
+pub enum TgaDecodeErrors {
+    /// The header contains values that aren't valid for a TGA file
+    InvalidHeader(&'static str),
+    /// The image uses a feature the decoder doesn't support
+    Unsupported(String),
+    /// Too large dimensions for a given width or
+    /// height
+    TooLargeDimensions(&'static str, usize, usize),
+    /// Generic message
+    GenericStatic(&'static str),
+    /// Generic allocated message
+    Generic(String),
+    IoErrors(ZByteIoError)
+}
+
+impl Debug for TgaDecodeErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidHeader(message) => {
+                writeln!(f, "Invalid TGA header: {message}")
+            }
+            Self::Unsupported(message) => {
+                writeln!(f, "Unsupported: {message}")
+            }
+            Self::TooLargeDimensions(dimension, expected, found) => {
+                writeln!(
+                    f,
+                    "Too large dimensions for {dimension} , {found} exceeds {expected}"
+                )
+            }
+            Self::GenericStatic(message) => {
+                writeln!(f, "{message}")
+            }
+            Self::Generic(message) => {
+                writeln!(f, "{message}")
+            }
+            Self::IoErrors(err) => {
+                writeln!(f, "{:?}", err)
+            }
+        }
+    }
+}
+
+impl From<ZByteIoError> for TgaDecodeErrors {
+    fn from(value: ZByteIoError) -> Self {
+        TgaDecodeErrors::IoErrors(value)
+    }
+}
+
+/// Errors possible during encoding
+pub enum TgaEncodeErrors {
+    /// Too large dimensions, TGA stores width and height in
+    /// 16 bits
+    TooLargeDimensions(usize),
+    /// Unsupported bit depth, only 8 bit images can be encoded
+    UnsupportedBitDepth(BitDepth),
+    /// Unsupported colorspace, only Luma, RGB and RGBA images can be encoded
+    UnsupportedColorSpace(ColorSpace),
+    /// Too short of an input buffer, the buffer size is not same as expected buffer
+    /// size
+    TooShortInput(usize, usize),
+    IoErrors(ZByteIoError)
+}
+
+impl Debug for TgaEncodeErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooLargeDimensions(dims) => {
+                writeln!(f, "Too large dimensions {dims}, TGA supports up to 65535")
+            }
+            Self::UnsupportedBitDepth(depth) => {
+                writeln!(f, "Unsupported bit depth {depth:?}")
+            }
+            Self::UnsupportedColorSpace(color) => {
+                writeln!(f, "Unsupported color space {color:?}")
+            }
+            Self::TooShortInput(expected, found) => {
+                writeln!(
+                    f,
+                    "Too short of input, expected {expected:?}, found {found:?}",
+                )
+            }
+            Self::IoErrors(err) => {
+                writeln!(f, "I/O error {:?}", err)
+            }
+        }
+    }
+}
+
+impl From<ZByteIoError> for TgaEncodeErrors {
+    fn from(value: ZByteIoError) -> Self {
+        TgaEncodeErrors::IoErrors(value)
+    }
+}
+
+impl ZErrorCodeTrait for TgaDecodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::InvalidHeader(_) | Self::GenericStatic(_) | Self::Generic(_) => {
+                ZErrorCode::CorruptData
+            }
+            Self::Unsupported(_) => ZErrorCode::Unsupported,
+            Self::TooLargeDimensions(_, _, _) => ZErrorCode::LimitsExceeded,
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
+
+impl ZErrorCodeTrait for TgaEncodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::TooLargeDimensions(_) => ZErrorCode::LimitsExceeded,
+            Self::UnsupportedBitDepth(_) | Self::UnsupportedColorSpace(_) => {
+                ZErrorCode::Unsupported
+            }
+            Self::TooShortInput(_, _) => ZErrorCode::InvalidInput,
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
@@ -0,0 +1,47 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! A TGA (Truevision TARGA) decoder and encoder
+//!
+//! # Supported images
+//! - Uncompressed and run length encoded images
+//! - 8 and 16 bit color mapped images with 15/16, 24 and 32 bit color maps
+//! - 15/16, 24 and 32 bit true color images
+//! - 8 bit grayscale images, with an optional 8 bit alpha channel
+//!
+//! Images are always returned with a top-left origin, whatever origin
+//! is stored in the file, and with 8 bits per channel.
+//!
+//! The encoder writes 8 bit Luma, LumaA, RGB and RGBA images, optionally
+//! run length encoded.
+//!
+//! # Features
+//!  - `log`: Use the `log` crate features to print image information when decoding
+//!  - `std`: Allow direct decoding from anything that implements `std::io::BufRead` + `std::io::Seek`
+//!
+//! # Usage
+//! ```no_run
+//! use zune_core::bytestream::ZCursor;
+//! use zune_tga::TgaDecoder;
+//!
+//! let mut decoder = TgaDecoder::new(ZCursor::new(&[]));
+//! let pixels = decoder.decode().unwrap();
+//! let colorspace = decoder.colorspace().unwrap();
+//! ```
+#![no_std]
+#![macro_use]
+extern crate alloc;
+
+pub use zune_core;
+
+pub use crate::decoder::{probe_tga, TgaDecoder, TGA_FOOTER_SIGNATURE, TGA_HEADER_SIZE};
+pub use crate::encoder::TgaEncoder;
+pub use crate::errors::{TgaDecodeErrors, TgaEncodeErrors};
+
+mod decoder;
+mod encoder;
+mod errors;
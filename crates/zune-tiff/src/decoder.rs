@@ -0,0 +1,1066 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! TIFF decoder
+//!
+//! Every image file directory (IFD) in the main chain that holds a full
+//! resolution image is a page, reduced resolution copies and transparency
+//! masks are skipped.
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::{ZByteReaderTrait, ZCursor};
+use zune_core::colorspace::ColorSpace;
+use zune_core::log::{trace, warn};
+use zune_core::options::DecoderOptions;
+use zune_core::result::DecodingResult;
+use zune_inflate::{DeflateDecoder, DeflateOptions};
+use zune_jpeg::JpegDecoder;
+
+use crate::errors::TiffDecodeErrors;
+use crate::fax::{decode_fax, FaxCoding};
+use crate::lzw::{decode_lzw, is_old_style_lzw};
+use crate::packbits::decode_packbits;
+use crate::tiff::*;
+
+const COMPRESSION_NONE: u32 = 1;
+const COMPRESSION_CCITT_RLE: u32 = 2;
+const COMPRESSION_CCITT_T4: u32 = 3;
+const COMPRESSION_CCITT_T6: u32 = 4;
+const COMPRESSION_LZW: u32 = 5;
+const COMPRESSION_JPEG: u32 = 7;
+const COMPRESSION_DEFLATE: u32 = 8;
+const COMPRESSION_PACKBITS: u32 = 32773;
+const COMPRESSION_DEFLATE_OLD: u32 = 32946;
+
+const PHOTOMETRIC_WHITE_IS_ZERO: u32 = 0;
+const PHOTOMETRIC_BLACK_IS_ZERO: u32 = 1;
+const PHOTOMETRIC_RGB: u32 = 2;
+const PHOTOMETRIC_PALETTE: u32 = 3;
+const PHOTOMETRIC_SEPARATED: u32 = 5;
+const PHOTOMETRIC_YCBCR: u32 = 6;
+
+const SAMPLE_FORMAT_UINT: u32 = 1;
+const SAMPLE_FORMAT_FLOAT: u32 = 3;
+const SAMPLE_FORMAT_VOID: u32 = 4;
+
+/// `ExtraSamples` value for alpha premultiplied into the color channels
+const EXTRA_SAMPLE_ASSOCIATED_ALPHA: u32 = 1;
+/// `ExtraSamples` value for straight alpha
+const EXTRA_SAMPLE_UNASSOCIATED_ALPHA: u32 = 2;
+/// Most samples per pixel a page can have, enough for CMYK with
+/// a few extra samples
+const MAX_SAMPLES_PER_PIXEL: usize = 8;
+
+/// Probe some bytes to see if they start with a TIFF header
+///
+/// Note that DNG files are also TIFF files, so this returns true for them
+pub fn probe_tiff(bytes: &[u8]) -> bool {
+    matches!(bytes.get(..4), Some(b"II*\0" | b"MM\0*"))
+}
+
+/// Information about a single page of a TIFF file
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TiffPageInfo {
+    /// Page width
+    pub width:               usize,
+    /// Page height
+    pub height:              usize,
+    /// Colorspace of the decoded pixels
+    pub colorspace:          ColorSpace,
+    /// Bit depth of the decoded pixels
+    pub depth:               BitDepth,
+    /// Whether the color channels are premultiplied by alpha
+    pub premultiplied_alpha: bool,
+    /// Exif orientation of the page, if stored
+    pub orientation:         Option<u16>
+}
+
+#[derive(Clone, Debug)]
+enum DataLayout {
+    Strips {
+        offsets:        Vec<u32>,
+        byte_counts:    Vec<u32>,
+        rows_per_strip: usize
+    },
+    Tiles {
+        offsets:     Vec<u32>,
+        byte_counts: Vec<u32>,
+        tile_width:  usize,
+        tile_height: usize
+    }
+}
+
+/// Everything needed to decode a page
+#[derive(Clone, Debug)]
+struct Page {
+    info:              TiffPageInfo,
+    bits_per_sample:   u8,
+    samples_per_pixel: usize,
+    float:             bool,
+    compression:       u32,
+    photometric:       u32,
+    /// Number of color channels in the stored samples, alpha follows them
+    color_channels:    usize,
+    has_alpha:         bool,
+    reverse_bits:      bool,
+    predictor:         u32,
+    planar:            bool,
+    t4_options:        u32,
+    layout:            DataLayout,
+    color_map:         Option<Vec<u16>>,
+    jpeg_tables:       Option<Vec<u8>>,
+    icc_profile:       Option<Vec<u8>>
+}
+
+/// A TIFF decoder
+///
+/// The decoder reads whole pages, use [`page_count`](Self::page_count) and
+/// [`decode_page`](Self::decode_page) to go through multi-page documents
+/// such as scans and faxes.
+///
+/// # Example
+/// ```no_run
+/// use zune_core::bytestream::ZCursor;
+/// use zune_tiff::TiffDecoder;
+///
+/// let mut decoder = TiffDecoder::new(ZCursor::new(b"II*\0"));
+/// decoder.decode_headers().unwrap();
+///
+/// for page in 0..decoder.page_count() {
+///     let pixels = decoder.decode_page(page).unwrap();
+/// }
+/// ```
+pub struct TiffDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    source:          T,
+    data:            Vec<u8>,
+    options:         DecoderOptions,
+    decoded_headers: bool,
+    big_endian:      bool,
+    pages:           Vec<Page>
+}
+
+impl<T> TiffDecoder<T>
+where
+    T: ZByteReaderTrait
+{
+    /// Create a new TIFF decoder that reads data from `data`
+    ///
+    /// # Arguments
+    /// - `data`: The buffer from which we will read bytes from
+    pub fn new(data: T) -> TiffDecoder<T> {
+        TiffDecoder::new_with_options(data, DecoderOptions::default())
+    }
+    /// Create a new TIFF decoder with the specified options
+    ///
+    /// # Arguments
+    /// - `data`: The buffer from which we will read bytes from
+    /// - `options`: Specialized options for this decoder
+    pub fn new_with_options(data: T, options: DecoderOptions) -> TiffDecoder<T> {
+        TiffDecoder {
+            source: data,
+            data: Vec::new(),
+            options,
+            decoded_headers: false,
+            big_endian: false,
+            pages: Vec::new()
+        }
+    }
+
+    /// Decode headers of the TIFF file
+    ///
+    /// This reads the information of every page, a page the decoder
+    /// can't read is skipped unless it's the first one, in which case
+    /// an error is returned
+    pub fn decode_headers(&mut self) -> Result<(), TiffDecodeErrors> {
+        if self.decoded_headers {
+            return Ok(());
+        }
+        // TIFF offsets can point anywhere, so keep the whole file around
+        self.source.read_remaining(&mut self.data)?;
+
+        let (tiff, first_ifd) = Tiff::new(&self.data)?;
+        let ifds = tiff.read_ifd_chain(first_ifd)?;
+
+        let mut pages = Vec::new();
+
+        for ifd in &ifds {
+            // bit 0 marks reduced resolution images, bit 2 transparency masks
+            if first_value(&tiff, ifd, TAG_NEW_SUBFILE_TYPE)?.unwrap_or(0) & 0b101 != 0 {
+                continue;
+            }
+            match self.read_page(&tiff, ifd) {
+                Ok(page) => pages.push(page),
+                Err(err) if pages.is_empty() => return Err(err),
+                Err(_err) => {
+                    warn!("Skipping TIFF page: {:?}", _err);
+                }
+            }
+        }
+        if pages.is_empty() {
+            return Err(TiffDecodeErrors::GenericStatic("No images found in file"));
+        }
+        trace!("Pages: {}", pages.len());
+
+        self.big_endian = tiff.is_big_endian();
+        self.pages = pages;
+        self.decoded_headers = true;
+
+        Ok(())
+    }
+
+    fn read_page(&self, tiff: &Tiff, ifd: &Ifd) -> Result<Page, TiffDecodeErrors> {
+        let required = |tag: u16, name: &str| -> Result<u32, TiffDecodeErrors> {
+            first_value(tiff, ifd, tag)?
+                .ok_or_else(|| TiffDecodeErrors::Generic(format!("Missing required tag {name}")))
+        };
+        let width = required(TAG_IMAGE_WIDTH, "ImageWidth")? as usize;
+        let height = required(TAG_IMAGE_LENGTH, "ImageLength")? as usize;
+        let compression = first_value(tiff, ifd, TAG_COMPRESSION)?.unwrap_or(COMPRESSION_NONE);
+        let samples_per_pixel =
+            first_value(tiff, ifd, TAG_SAMPLES_PER_PIXEL)?.unwrap_or(1) as usize;
+        let bits_per_sample = first_value(tiff, ifd, TAG_BITS_PER_SAMPLE)?.unwrap_or(1);
+        let sample_format =
+            first_value(tiff, ifd, TAG_SAMPLE_FORMAT)?.unwrap_or(SAMPLE_FORMAT_UINT);
+        let predictor = first_value(tiff, ifd, TAG_PREDICTOR)?.unwrap_or(1);
+        let planar = first_value(tiff, ifd, TAG_PLANAR_CONFIGURATION)?.unwrap_or(1) == 2;
+
+        if width == 0 || height == 0 {
+            return Err(TiffDecodeErrors::GenericStatic("Zero dimensions"));
+        }
+        if width > self.options.max_width() {
+            return Err(TiffDecodeErrors::TooLargeDimensions(
+                "width",
+                self.options.max_width(),
+                width
+            ));
+        }
+        if height > self.options.max_height() {
+            return Err(TiffDecodeErrors::TooLargeDimensions(
+                "height",
+                self.options.max_height(),
+                height
+            ));
+        }
+        if samples_per_pixel == 0 {
+            return Err(TiffDecodeErrors::GenericStatic("Zero samples per pixel"));
+        }
+        // fax files are bilevel and may leave out the photometric tag
+        let photometric = match first_value(tiff, ifd, TAG_PHOTOMETRIC)? {
+            Some(photometric) => photometric,
+            None if bits_per_sample == 1 => PHOTOMETRIC_WHITE_IS_ZERO,
+            None => return Err(TiffDecodeErrors::GenericStatic("Missing photometric tag"))
+        };
+
+        let float = match (sample_format, bits_per_sample) {
+            (SAMPLE_FORMAT_UINT | SAMPLE_FORMAT_VOID, 1..=16) => false,
+            (SAMPLE_FORMAT_FLOAT, 32) => true,
+            _ => {
+                return Err(TiffDecodeErrors::Unsupported(format!(
+                    "{bits_per_sample} bit samples with sample format {sample_format}"
+                )))
+            }
+        };
+        match compression {
+            COMPRESSION_NONE
+            | COMPRESSION_LZW
+            | COMPRESSION_DEFLATE
+            | COMPRESSION_DEFLATE_OLD
+            | COMPRESSION_PACKBITS => {}
+            COMPRESSION_CCITT_RLE | COMPRESSION_CCITT_T4 | COMPRESSION_CCITT_T6 => {
+                if bits_per_sample != 1 || samples_per_pixel != 1 {
+                    return Err(TiffDecodeErrors::GenericStatic(
+                        "Fax compression needs one bit per pixel"
+                    ));
+                }
+            }
+            COMPRESSION_JPEG => {
+                if bits_per_sample != 8 || planar {
+                    return Err(TiffDecodeErrors::Unsupported(
+                        "JPEG compressed pages that aren't 8 bit and interleaved".into()
+                    ));
+                }
+            }
+            _ => {
+                return Err(TiffDecodeErrors::Unsupported(format!(
+                    "Compression {compression}"
+                )))
+            }
+        }
+        if !matches!(predictor, 1 | 2) || (predictor == 2 && float) {
+            return Err(TiffDecodeErrors::Unsupported(format!(
+                "Predictor {predictor}"
+            )));
+        }
+
+        let (color_channels, colorspace) = match photometric {
+            PHOTOMETRIC_WHITE_IS_ZERO | PHOTOMETRIC_BLACK_IS_ZERO => (1, ColorSpace::Luma),
+            PHOTOMETRIC_RGB => (3, ColorSpace::RGB),
+            PHOTOMETRIC_PALETTE if !float => (1, ColorSpace::RGB),
+            PHOTOMETRIC_SEPARATED if first_value(tiff, ifd, TAG_INK_SET)?.unwrap_or(1) == 1 => {
+                (4, ColorSpace::CMYK)
+            }
+            // subsampled YCbCr is only supported through the JPEG decoder,
+            // which converts it to RGB
+            PHOTOMETRIC_YCBCR if compression == COMPRESSION_JPEG => (3, ColorSpace::RGB),
+            _ => {
+                return Err(TiffDecodeErrors::Unsupported(format!(
+                    "Photometric interpretation {photometric}"
+                )))
+            }
+        };
+        if samples_per_pixel < color_channels {
+            return Err(TiffDecodeErrors::Generic(format!(
+                "{samples_per_pixel} samples per pixel for photometric interpretation {photometric}"
+            )));
+        }
+        if samples_per_pixel > MAX_SAMPLES_PER_PIXEL {
+            return Err(TiffDecodeErrors::Unsupported(format!(
+                "{samples_per_pixel} samples per pixel, at most {MAX_SAMPLES_PER_PIXEL} are supported"
+            )));
+        }
+        // some writers leave out the tag for RGBA and gray alpha images,
+        // so an extra sample without it is treated as alpha
+        let extra_sample =
+            first_value(tiff, ifd, TAG_EXTRA_SAMPLES)?.unwrap_or(EXTRA_SAMPLE_UNASSOCIATED_ALPHA);
+        let has_alpha = samples_per_pixel > color_channels
+            && photometric != PHOTOMETRIC_PALETTE
+            && colorspace != ColorSpace::CMYK
+            && matches!(
+                extra_sample,
+                EXTRA_SAMPLE_ASSOCIATED_ALPHA | EXTRA_SAMPLE_UNASSOCIATED_ALPHA
+            );
+
+        let colorspace = match (colorspace, has_alpha) {
+            (ColorSpace::Luma, true) => ColorSpace::LumaA,
+            (ColorSpace::RGB, true) => ColorSpace::RGBA,
+            (colorspace, _) => colorspace
+        };
+        let depth = if float {
+            BitDepth::Float32
+        } else if bits_per_sample <= 8 || photometric == PHOTOMETRIC_PALETTE {
+            BitDepth::Eight
+        } else {
+            BitDepth::Sixteen
+        };
+
+        let color_map = if photometric == PHOTOMETRIC_PALETTE {
+            let entry = ifd
+                .get(TAG_COLOR_MAP)
+                .ok_or(TiffDecodeErrors::GenericStatic("Missing ColorMap tag"))?;
+            let map: Vec<u16> = tiff.values_u32(entry)?.iter().map(|x| *x as u16).collect();
+
+            if map.len() != 3 << bits_per_sample {
+                return Err(TiffDecodeErrors::GenericStatic(
+                    "ColorMap size doesn't match bits per sample"
+                ));
+            }
+            Some(map)
+        } else {
+            None
+        };
+
+        let layout = if let Some(offsets) = ifd.get(TAG_TILE_OFFSETS) {
+            let counts = ifd
+                .get(TAG_TILE_BYTE_COUNTS)
+                .ok_or(TiffDecodeErrors::GenericStatic(
+                    "Missing TileByteCounts tag"
+                ))?;
+
+            let tile_width = required(TAG_TILE_WIDTH, "TileWidth")? as usize;
+            let tile_height = required(TAG_TILE_LENGTH, "TileLength")? as usize;
+
+            if tile_width == 0 || tile_height == 0 {
+                return Err(TiffDecodeErrors::GenericStatic("Zero tile dimensions"));
+            }
+            if tile_width > self.options.max_width() || tile_height > self.options.max_height() {
+                return Err(TiffDecodeErrors::GenericStatic("Too large tile dimensions"));
+            }
+            DataLayout::Tiles {
+                offsets: tiff.values_u32(offsets)?,
+                byte_counts: tiff.values_u32(counts)?,
+                tile_width,
+                tile_height
+            }
+        } else {
+            let offsets = ifd
+                .get(TAG_STRIP_OFFSETS)
+                .ok_or(TiffDecodeErrors::GenericStatic("Missing StripOffsets tag"))?;
+            let counts = ifd
+                .get(TAG_STRIP_BYTE_COUNTS)
+                .ok_or(TiffDecodeErrors::GenericStatic(
+                    "Missing StripByteCounts tag"
+                ))?;
+
+            let rows_per_strip = first_value(tiff, ifd, TAG_ROWS_PER_STRIP)?
+                .map(|x| x as usize)
+                .unwrap_or(height)
+                .clamp(1, height);
+
+            DataLayout::Strips {
+                offsets: tiff.values_u32(offsets)?,
+                byte_counts: tiff.values_u32(counts)?,
+                rows_per_strip
+            }
+        };
+        let orientation = first_value(tiff, ifd, TAG_ORIENTATION)?.map(|x| x as u16);
+
+        Ok(Page {
+            info: TiffPageInfo {
+                width,
+                height,
+                colorspace,
+                depth,
+                premultiplied_alpha: has_alpha && extra_sample == EXTRA_SAMPLE_ASSOCIATED_ALPHA,
+                orientation
+            },
+            bits_per_sample: bits_per_sample as u8,
+            samples_per_pixel,
+            float,
+            compression,
+            photometric,
+            color_channels,
+            has_alpha,
+            reverse_bits: first_value(tiff, ifd, TAG_FILL_ORDER)?.unwrap_or(1) == 2,
+            predictor,
+            planar,
+            t4_options: first_value(tiff, ifd, TAG_T4_OPTIONS)?.unwrap_or(0),
+            layout,
+            color_map,
+            jpeg_tables: ifd.get(TAG_JPEG_TABLES).map(|x| tiff.bytes(x).to_vec()),
+            icc_profile: ifd.get(TAG_ICC_PROFILE).map(|x| tiff.bytes(x).to_vec())
+        })
+    }
+
+    /// Return the number of pages in the file, or zero if headers
+    /// haven't been decoded
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Return information about page `page` or `None` if headers haven't
+    /// been decoded or the page doesn't exist
+    pub fn page_info(&self, page: usize) -> Option<TiffPageInfo> {
+        self.pages.get(page).map(|x| x.info)
+    }
+
+    /// Return the dimensions of the first page as `(width, height)` or `None` if
+    /// headers haven't been decoded
+    pub fn dimensions(&self) -> Option<(usize, usize)> {
+        self.page_info(0).map(|x| (x.width, x.height))
+    }
+
+    /// Return the colorspace of the first page or `None` if
+    /// headers haven't been decoded
+    pub fn colorspace(&self) -> Option<ColorSpace> {
+        self.page_info(0).map(|x| x.colorspace)
+    }
+
+    /// Return the bit depth of the first page or `None` if
+    /// headers haven't been decoded
+    pub fn depth(&self) -> Option<BitDepth> {
+        self.page_info(0).map(|x| x.depth)
+    }
+
+    /// Return the exif orientation of the first page or `None` if it isn't
+    /// stored in the file or headers haven't been decoded
+    pub fn orientation(&self) -> Option<u16> {
+        self.page_info(0).and_then(|x| x.orientation)
+    }
+
+    /// Return the ICC profile of the first page or `None` if it isn't
+    /// stored in the file or headers haven't been decoded
+    pub fn icc_profile(&self) -> Option<&[u8]> {
+        self.pages.first().and_then(|x| x.icc_profile.as_deref())
+    }
+
+    /// Decode the first page
+    pub fn decode(&mut self) -> Result<DecodingResult, TiffDecodeErrors> {
+        self.decode_page(0)
+    }
+
+    /// Decode page `page`
+    ///
+    /// Pixels are interleaved in the colorspace and depth reported
+    /// by [`page_info`](Self::page_info)
+    pub fn decode_page(&mut self, page: usize) -> Result<DecodingResult, TiffDecodeErrors> {
+        self.decode_headers()?;
+
+        let page = self
+            .pages
+            .get(page)
+            .ok_or(TiffDecodeErrors::PageOutOfRange(page, self.pages.len()))?;
+
+        if page.float {
+            let samples = self.read_samples(page, |bytes, block| {
+                let mut output = vec![0.0; block];
+
+                for (out, value) in output.iter_mut().zip(bytes.chunks_exact(4)) {
+                    let value = [value[0], value[1], value[2], value[3]];
+
+                    *out = if self.big_endian {
+                        f32::from_be_bytes(value)
+                    } else {
+                        f32::from_le_bytes(value)
+                    };
+                }
+                output
+            })?;
+            return Ok(DecodingResult::F32(select_channels(page, &samples)));
+        }
+        let samples = self.read_samples(page, |bytes, block| {
+            unpack_samples(bytes, page, self.big_endian, block)
+        })?;
+
+        Ok(convert_samples(page, &samples))
+    }
+
+    /// Read the samples of all strips or tiles into an interleaved
+    /// buffer covering the whole page
+    fn read_samples<S: Copy + Default>(
+        &self, page: &Page, unpack: impl Fn(&[u8], usize) -> Vec<S>
+    ) -> Result<Vec<S>, TiffDecodeErrors> {
+        let (width, height) = (page.info.width, page.info.height);
+        let spp = page.samples_per_pixel;
+
+        let total = width
+            .checked_mul(height)
+            .and_then(|x| x.checked_mul(spp))
+            .ok_or(TiffDecodeErrors::GenericStatic(
+                "Image size overflows usize"
+            ))?;
+
+        // (offsets, counts, block width, block height, blocks across)
+        let (offsets, counts, block_width, block_height, across) = match &page.layout {
+            DataLayout::Strips {
+                offsets,
+                byte_counts,
+                rows_per_strip
+            } => (offsets, byte_counts, width, *rows_per_strip, 1),
+            DataLayout::Tiles {
+                offsets,
+                byte_counts,
+                tile_width,
+                tile_height
+            } => (
+                offsets,
+                byte_counts,
+                *tile_width,
+                *tile_height,
+                width.div_ceil(*tile_width)
+            )
+        };
+        let down = height.div_ceil(block_height);
+        let (planes, block_spp) = if page.planar { (spp, 1) } else { (1, spp) };
+
+        if offsets.len() < across * down * planes || counts.len() < offsets.len() {
+            return Err(TiffDecodeErrors::GenericStatic(
+                "Not enough strips or tiles for image dimensions"
+            ));
+        }
+        // the data of every strip or tile has to be in the file, check this
+        // before allocating space for the whole page
+        let stored = counts
+            .iter()
+            .take(across * down * planes)
+            .fold(0_usize, |acc, x| acc.saturating_add(*x as usize));
+
+        if stored > self.data.len() {
+            return Err(TiffDecodeErrors::GenericStatic(
+                "Strip or tile byte counts exceed the file size"
+            ));
+        }
+        let mut output = vec![S::default(); total];
+
+        let row_samples = block_width * block_spp;
+        let block_samples = row_samples
+            .checked_mul(block_height)
+            .ok_or(TiffDecodeErrors::GenericStatic("Tile size overflows usize"))?;
+
+        for (i, (offset, count)) in offsets
+            .iter()
+            .zip(counts)
+            .take(across * down * planes)
+            .enumerate()
+        {
+            let start = *offset as usize;
+            let end = start.saturating_add(*count as usize).min(self.data.len());
+
+            let bytes = self
+                .data
+                .get(start..end)
+                .ok_or(TiffDecodeErrors::GenericStatic(
+                    "Strip or tile offset points outside of the file"
+                ))?;
+
+            let bytes = self.decompress(page, bytes, block_width, block_height, block_spp)?;
+            let block = unpack(&bytes, block_samples);
+
+            let (plane, i) = (i / (across * down), i % (across * down));
+            let (bx, by) = ((i % across) * block_width, (i / across) * block_height);
+            let copy_width = block_width.min(width - bx);
+
+            for (row, samples) in block
+                .chunks_exact(row_samples)
+                .take(height.saturating_sub(by))
+                .enumerate()
+            {
+                let out_row = &mut output[((by + row) * width + bx) * spp..];
+
+                for (out, value) in out_row
+                    .chunks_exact_mut(spp)
+                    .zip(samples.chunks_exact(block_spp))
+                    .take(copy_width)
+                {
+                    out[plane..plane + block_spp].copy_from_slice(value);
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// Decompress a strip or tile, the result is always the size of a full
+    /// block, short data is padded with zeros
+    fn decompress(
+        &self, page: &Page, bytes: &[u8], block_width: usize, block_height: usize, block_spp: usize
+    ) -> Result<Vec<u8>, TiffDecodeErrors> {
+        let row_bytes = (block_width * block_spp * usize::from(page.bits_per_sample)).div_ceil(8);
+        let size = row_bytes * block_height;
+
+        let reversed: Vec<u8>;
+        let bytes = if page.reverse_bits {
+            reversed = bytes.iter().map(|x| x.reverse_bits()).collect();
+            &reversed
+        } else {
+            bytes
+        };
+
+        let mut output = match page.compression {
+            COMPRESSION_NONE => bytes[..bytes.len().min(size)].to_vec(),
+            COMPRESSION_PACKBITS => {
+                let mut output = vec![0; size];
+                decode_packbits(bytes, &mut output);
+                output
+            }
+            COMPRESSION_LZW => {
+                if is_old_style_lzw(bytes) {
+                    return Err(TiffDecodeErrors::Unsupported(
+                        "Old style LZW compression".into()
+                    ));
+                }
+                let mut output = vec![0; size];
+                decode_lzw(bytes, &mut output)?;
+                output
+            }
+            COMPRESSION_DEFLATE | COMPRESSION_DEFLATE_OLD => {
+                let options = DeflateOptions::default()
+                    .set_size_hint(size)
+                    .set_limit(size);
+
+                DeflateDecoder::new_with_options(bytes, options).decode_zlib()?
+            }
+            COMPRESSION_CCITT_RLE => {
+                decode_fax(bytes, block_width, block_height, FaxCoding::ModifiedHuffman)?
+            }
+            COMPRESSION_CCITT_T4 => {
+                let coding = FaxCoding::T4 {
+                    two_dimensional: page.t4_options & 1 != 0
+                };
+                decode_fax(bytes, block_width, block_height, coding)?
+            }
+            COMPRESSION_CCITT_T6 => decode_fax(bytes, block_width, block_height, FaxCoding::T6)?,
+            COMPRESSION_JPEG => self.decode_jpeg(page, bytes)?,
+            _ => unreachable!()
+        };
+        if page.predictor == 2 {
+            undo_horizontal_predictor(&mut output, page, row_bytes, block_spp, self.big_endian);
+        }
+        output.resize(size, 0);
+
+        Ok(output)
+    }
+
+    fn decode_jpeg(&self, page: &Page, bytes: &[u8]) -> Result<Vec<u8>, TiffDecodeErrors> {
+        // tables shared by all strips are stored separately as an
+        // abbreviated stream, splice them in front of the strip
+        let stream = match &page.jpeg_tables {
+            Some(tables) if tables.len() >= 4 && bytes.starts_with(&[0xFF, 0xD8]) => {
+                let tables = tables.strip_suffix(&[0xFF, 0xD9]).unwrap_or(tables);
+
+                let mut stream = tables.to_vec();
+                stream.extend_from_slice(&bytes[2..]);
+                stream
+            }
+            _ => bytes.to_vec()
+        };
+        let mut decoder = JpegDecoder::new_with_options(ZCursor::new(&stream), self.options);
+        decoder.decode_headers()?;
+
+        // leave RGB and CMYK data as stored, YCbCr is converted to RGB
+        if page.samples_per_pixel > 1 && page.photometric != PHOTOMETRIC_YCBCR {
+            if let Some(colorspace) = decoder.input_colorspace() {
+                decoder.set_options(self.options.jpeg_set_out_colorspace(colorspace));
+            }
+        }
+        let output = decoder.decode()?;
+
+        if decoder.output_colorspace().map(|x| x.num_components()) != Some(page.samples_per_pixel) {
+            return Err(TiffDecodeErrors::GenericStatic(
+                "JPEG components don't match samples per pixel"
+            ));
+        }
+        Ok(output)
+    }
+}
+
+/// Read the first integer value of a tag
+fn first_value(tiff: &Tiff, ifd: &Ifd, tag: u16) -> Result<Option<u32>, TiffDecodeErrors> {
+    match ifd.get(tag) {
+        Some(entry) => Ok(tiff.values_u32(entry)?.first().copied()),
+        None => Ok(None)
+    }
+}
+
+/// Undo horizontal differencing on decompressed bytes, in place
+fn undo_horizontal_predictor(
+    bytes: &mut [u8], page: &Page, row_bytes: usize, spp: usize, big_endian: bool
+) {
+    match page.bits_per_sample {
+        8 => {
+            for row in bytes.chunks_exact_mut(row_bytes) {
+                for i in spp..row.len() {
+                    row[i] = row[i].wrapping_add(row[i - spp]);
+                }
+            }
+        }
+        16 => {
+            let read = |x: &[u8]| {
+                if big_endian {
+                    u16::from_be_bytes([x[0], x[1]])
+                } else {
+                    u16::from_le_bytes([x[0], x[1]])
+                }
+            };
+            let write = |x: u16| if big_endian { x.to_be_bytes() } else { x.to_le_bytes() };
+
+            for row in bytes.chunks_exact_mut(row_bytes) {
+                for i in (spp * 2..row.len() - 1).step_by(2) {
+                    let value = read(&row[i..]).wrapping_add(read(&row[i - spp * 2..]));
+                    row[i..i + 2].copy_from_slice(&write(value));
+                }
+            }
+        }
+        // other depths are rare and libtiff doesn't write them either
+        _ => {
+            warn!(
+                "Ignoring predictor for {} bit samples",
+                page.bits_per_sample
+            );
+        }
+    }
+}
+
+/// Unpack integer samples, rows start at a byte boundary
+fn unpack_samples(bytes: &[u8], page: &Page, big_endian: bool, total: usize) -> Vec<u16> {
+    let mut output = vec![0; total];
+
+    match page.bits_per_sample {
+        8 => {
+            for (out, value) in output.iter_mut().zip(bytes) {
+                *out = u16::from(*value);
+            }
+        }
+        16 => {
+            for (out, value) in output.iter_mut().zip(bytes.chunks_exact(2)) {
+                *out = if big_endian {
+                    u16::from_be_bytes([value[0], value[1]])
+                } else {
+                    u16::from_le_bytes([value[0], value[1]])
+                }
+            }
+        }
+        bits => {
+            let bits = usize::from(bits);
+            let row_samples = match page.layout {
+                DataLayout::Strips { .. } => page.info.width,
+                DataLayout::Tiles { tile_width, .. } => tile_width
+            } * if page.planar { 1 } else { page.samples_per_pixel };
+            let row_bytes = (row_samples * bits).div_ceil(8);
+
+            for (out_row, in_row) in output
+                .chunks_exact_mut(row_samples)
+                .zip(bytes.chunks(row_bytes))
+            {
+                // packed samples are always stored most significant bit first
+                let mut buffer = 0_u32;
+                let mut buffered = 0;
+                let mut input = in_row.iter();
+
+                for out in out_row {
+                    while buffered < bits {
+                        buffer = (buffer << 8) | u32::from(*input.next().unwrap_or(&0));
+                        buffered += 8;
+                    }
+                    buffered -= bits;
+                    *out = ((buffer >> buffered) & ((1 << bits) - 1)) as u16;
+                }
+            }
+        }
+    }
+    output
+}
+
+/// Keep the color channels and alpha, dropping any other extra samples
+fn select_channels<S: Copy>(page: &Page, samples: &[S]) -> Vec<S> {
+    let keep = page.color_channels + usize::from(page.has_alpha);
+
+    if keep == page.samples_per_pixel {
+        return samples.to_vec();
+    }
+    samples
+        .chunks_exact(page.samples_per_pixel)
+        .flat_map(|x| &x[..keep])
+        .copied()
+        .collect()
+}
+
+/// Convert integer samples to the page output depth and colorspace
+fn convert_samples(page: &Page, samples: &[u16]) -> DecodingResult {
+    let max = ((1_u32 << page.bits_per_sample) - 1) as u16;
+
+    if let Some(map) = &page.color_map {
+        let entries = 1 << page.bits_per_sample;
+        let mut output = Vec::with_capacity(samples.len() / page.samples_per_pixel * 3);
+
+        for index in samples.chunks_exact(page.samples_per_pixel) {
+            let index = usize::from(index[0]);
+
+            for channel in 0..3 {
+                output.push((map[channel * entries + index] >> 8) as u8);
+            }
+        }
+        return DecodingResult::U8(output);
+    }
+    let mut samples = select_channels(page, samples);
+
+    if page.photometric == PHOTOMETRIC_WHITE_IS_ZERO {
+        let channels = 1 + usize::from(page.has_alpha);
+
+        for pixel in samples.chunks_exact_mut(channels) {
+            pixel[0] = max - pixel[0];
+        }
+    }
+    match (page.info.depth, page.bits_per_sample) {
+        (BitDepth::Eight, 8) => DecodingResult::U8(samples.iter().map(|x| *x as u8).collect()),
+        (BitDepth::Eight, _) => DecodingResult::U8(
+            samples
+                .iter()
+                .map(|x| ((u32::from(*x) * 255 + u32::from(max) / 2) / u32::from(max)) as u8)
+                .collect()
+        ),
+        (_, 16) => DecodingResult::U16(samples),
+        _ => DecodingResult::U16(
+            samples
+                .iter()
+                .map(|x| ((u32::from(*x) * 65535 + u32::from(max) / 2) / u32::from(max)) as u16)
+                .collect()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::{probe_tiff, TiffDecodeErrors, TiffDecoder};
+
+    const SHORT: u16 = 3;
+    const LONG: u16 = 4;
+
+    fn shorts(values: &[u16]) -> Vec<u8> {
+        values.iter().flat_map(|x| x.to_le_bytes()).collect()
+    }
+
+    fn longs(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|x| x.to_le_bytes()).collect()
+    }
+
+    /// An IFD entry as `(tag, type, count, value)`
+    type Entry = (u16, u16, u32, Vec<u8>);
+
+    /// Write a little endian TIFF with one IFD per page, each page is stored as
+    /// `(strip, entries)`, the strip offset and byte count tags are added here
+    fn build_tiff(pages: Vec<(Vec<u8>, Vec<Entry>)>) -> Vec<u8> {
+        let mut out = b"II*\0".to_vec();
+        out.extend_from_slice(&[0; 4]);
+        let mut next_pointer = 4;
+
+        for (image, mut entries) in pages {
+            let strip_offset = out.len() as u32;
+            out.extend_from_slice(&image);
+
+            entries.push((273, LONG, 1, longs(&[strip_offset])));
+            entries.push((279, LONG, 1, longs(&[image.len() as u32])));
+            entries.sort_by_key(|x| x.0);
+
+            let ifd_offset = out.len();
+            out[next_pointer..next_pointer + 4].copy_from_slice(&(ifd_offset as u32).to_le_bytes());
+
+            let mut extra_offset = ifd_offset + 2 + entries.len() * 12 + 4;
+            let mut extra = Vec::new();
+
+            out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+
+            for (tag, field_type, count, mut value) in entries {
+                out.extend_from_slice(&tag.to_le_bytes());
+                out.extend_from_slice(&field_type.to_le_bytes());
+                out.extend_from_slice(&count.to_le_bytes());
+
+                if value.len() <= 4 {
+                    value.resize(4, 0);
+                    out.extend_from_slice(&value);
+                } else {
+                    out.extend_from_slice(&(extra_offset as u32).to_le_bytes());
+                    extra_offset += value.len();
+                    extra.extend_from_slice(&value);
+                }
+            }
+            next_pointer = out.len();
+            out.extend_from_slice(&[0; 4]);
+            out.extend_from_slice(&extra);
+        }
+        out
+    }
+
+    fn entries(
+        width: u32, height: u32, bits: u16, compression: u16, photometric: u16, samples: u16
+    ) -> Vec<Entry> {
+        vec![
+            (256, LONG, 1, longs(&[width])),
+            (257, LONG, 1, longs(&[height])),
+            (258, SHORT, 1, shorts(&[bits])),
+            (259, SHORT, 1, shorts(&[compression])),
+            (262, SHORT, 1, shorts(&[photometric])),
+            (277, SHORT, 1, shorts(&[samples])),
+        ]
+    }
+
+    #[test]
+    fn test_multi_page() {
+        let gray = (vec![0, 64, 128, 255], entries(2, 2, 8, 1, 1, 1));
+
+        let mut thumbnail = entries(1, 1, 8, 1, 1, 1);
+        thumbnail.push((254, LONG, 1, longs(&[1])));
+
+        // PackBits, one literal pixel followed by a repeated pixel
+        let rgb = (vec![2, 10, 20, 30, 0xFE, 40], entries(2, 1, 8, 32773, 2, 3));
+        let file = build_tiff(vec![gray, (vec![7], thumbnail), rgb]);
+
+        assert!(probe_tiff(&file));
+
+        let mut decoder = TiffDecoder::new(ZCursor::new(&file));
+        decoder.decode_headers().unwrap();
+
+        // the reduced resolution image isn't a page
+        assert_eq!(decoder.page_count(), 2);
+        assert_eq!(decoder.dimensions(), Some((2, 2)));
+        assert_eq!(decoder.page_info(1).unwrap().colorspace, ColorSpace::RGB);
+
+        assert_eq!(
+            decoder.decode_page(0).unwrap().u8(),
+            Some(vec![0, 64, 128, 255])
+        );
+        assert_eq!(
+            decoder.decode_page(1).unwrap().u8(),
+            Some(vec![10, 20, 30, 40, 40, 40])
+        );
+        assert!(matches!(
+            decoder.decode_page(2),
+            Err(TiffDecodeErrors::PageOutOfRange(2, 2))
+        ));
+    }
+
+    #[test]
+    fn test_lzw_with_predictor() {
+        // rows of [10, 10, 10, 10] and [5, 5, 5, 5] as differences
+        let data = vec![0x80, 0x02, 0xA0, 0x40, 0xA0, 0x2C, 0x14, 0x0B, 0x01];
+        let mut entries = entries(4, 2, 8, 5, 1, 1);
+        entries.push((317, SHORT, 1, shorts(&[2])));
+
+        let file = build_tiff(vec![(data, entries)]);
+        let pixels = TiffDecoder::new(ZCursor::new(&file)).decode().unwrap();
+
+        assert_eq!(pixels.u8(), Some(vec![10, 20, 30, 40, 5, 10, 15, 20]));
+    }
+
+    #[test]
+    fn test_group_4_fax() {
+        // an all white row coded as vertical mode, then a row of four white and
+        // four black pixels coded in horizontal mode
+        let data = vec![0b1001_1011, 0b0110_0000];
+        let file = build_tiff(vec![(data, entries(8, 2, 1, 4, 0, 1))]);
+
+        let mut decoder = TiffDecoder::new(ZCursor::new(&file));
+        let pixels = decoder.decode().unwrap();
+
+        assert_eq!(decoder.colorspace(), Some(ColorSpace::Luma));
+        assert_eq!(
+            pixels.u8(),
+            Some(vec![
+                255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 0, 0, 0, 0
+            ])
+        );
+    }
+
+    #[test]
+    fn test_too_many_samples_per_pixel() {
+        // a 201 byte file that used to allocate 2 TiB for its samples
+        let file = build_tiff(vec![(vec![0], entries(16384, 16384, 8, 1, 1, 4096))]);
+
+        assert!(matches!(
+            TiffDecoder::new(ZCursor::new(&file)).decode(),
+            Err(TiffDecodeErrors::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_byte_counts_past_end_of_file() {
+        let mut entries = entries(16384, 16384, 8, 1, 1, 1);
+        entries.push((278, LONG, 1, longs(&[16384])));
+
+        let mut file = build_tiff(vec![(vec![0], entries)]);
+        // point the strip byte count far past the end of the file
+        let count = file.len() - 12 - 4 + 8;
+        file[count..count + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        assert!(matches!(
+            TiffDecoder::new(ZCursor::new(&file)).decode(),
+            Err(TiffDecodeErrors::GenericStatic(
+                "Strip or tile byte counts exceed the file size"
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_first_page() {
+        let file = build_tiff(vec![(vec![0; 4], entries(2, 2, 8, 34712, 1, 1))]);
+
+        assert!(TiffDecoder::new(ZCursor::new(&file))
+            .decode_headers()
+            .is_err());
+        assert!(!probe_tiff(b"GIF89a"));
+    }
+}
@@ -0,0 +1,113 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use alloc::string::String;
+use core::fmt::{Debug, Formatter};
+
+use zune_core::bytestream::ZByteIoError;
+use zune_core::errors::{ZErrorCode, ZErrorCodeTrait};
+use zune_inflate::errors::InflateDecodeErrors;
+use zune_jpeg::errors::DecodeErrors;
+
+/// TIFF errors that can occur during decoding
+#[non_exhaustive]
+pub enum TiffDecodeErrors {
+    /// The file does not start with a TIFF header
+    InvalidMagicBytes,
+    /// The image uses a feature the decoder doesn't support
+    Unsupported(String),
+    /// Too large dimensions for a given width or
+    /// height
+    TooLargeDimensions(&'static str, usize, usize),
+    /// A page was requested that isn't in the file,
+    /// contains the page and the number of pages
+    PageOutOfRange(usize, usize),
+    /// Errors from the deflate decoder
+    Deflate(InflateDecodeErrors),
+    /// Errors from the JPEG decoder
+    Jpeg(DecodeErrors),
+    /// Generic message
+    GenericStatic(&'static str),
+    /// Generic allocated message
+    Generic(String),
+    IoErrors(ZByteIoError)
+}
+
+impl Debug for TiffDecodeErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidMagicBytes => {
+                writeln!(
+                    f,
+                    "Invalid magic bytes, file does not start with a TIFF header"
+                )
+            }
+            Self::Unsupported(message) => {
+                writeln!(f, "Unsupported: {message}")
+            }
+            Self::TooLargeDimensions(dimension, expected, found) => {
+                writeln!(
+                    f,
+                    "Too large dimensions for {dimension} , {found} exceeds {expected}"
+                )
+            }
+            Self::PageOutOfRange(page, pages) => {
+                writeln!(f, "Page {page} requested but the file has {pages} pages")
+            }
+            Self::Deflate(err) => {
+                writeln!(f, "Deflate: {:?}", err)
+            }
+            Self::Jpeg(err) => {
+                writeln!(f, "JPEG: {:?}", err)
+            }
+            Self::GenericStatic(message) => {
+                writeln!(f, "{message}")
+            }
+            Self::Generic(message) => {
+                writeln!(f, "{message}")
+            }
+            Self::IoErrors(err) => {
+                writeln!(f, "{:?}", err)
+            }
+        }
+    }
+}
+
+impl From<ZByteIoError> for TiffDecodeErrors {
+    fn from(value: ZByteIoError) -> Self {
+        TiffDecodeErrors::IoErrors(value)
+    }
+}
+
+impl From<InflateDecodeErrors> for TiffDecodeErrors {
+    fn from(value: InflateDecodeErrors) -> Self {
+        TiffDecodeErrors::Deflate(value)
+    }
+}
+
+impl From<DecodeErrors> for TiffDecodeErrors {
+    fn from(value: DecodeErrors) -> Self {
+        TiffDecodeErrors::Jpeg(value)
+    }
+}
+
+impl ZErrorCodeTrait for TiffDecodeErrors {
+    fn code(&self) -> ZErrorCode {
+        match self {
+            Self::InvalidMagicBytes
+            | Self::Deflate(_)
+            | Self::GenericStatic(_)
+            | Self::Generic(_) => ZErrorCode::CorruptData,
+            Self::Unsupported(_) => ZErrorCode::Unsupported,
+            Self::TooLargeDimensions(_, _, _) => ZErrorCode::LimitsExceeded,
+            Self::PageOutOfRange(_, _) => ZErrorCode::InvalidInput,
+            Self::Jpeg(err) => err.code(),
+            Self::IoErrors(err) => err.code()
+        }
+    }
+}
@@ -0,0 +1,418 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! CCITT fax decoding
+//!
+//! This covers the three schemes TIFF uses for bilevel images
+//! - Modified Huffman (compression 2), one dimensional runs with every row
+//!   starting at a byte boundary
+//! - T.4 (compression 3), rows separated by end of line codes and optionally
+//!   coded relative to the row above
+//! - T.6 (compression 4), every row is coded relative to the row above
+//!
+//! Decoded rows are packed, most significant bit first, with every row starting at a
+//! byte boundary. Runs coded as white are zero bits, so the output matches uncompressed
+//! data and the photometric interpretation of the file applies as usual.
+use alloc::vec;
+use alloc::vec::Vec;
+
+use zune_core::log::warn;
+
+use crate::errors::TiffDecodeErrors;
+
+/// How the fax data is coded
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum FaxCoding {
+    ModifiedHuffman,
+    T4 { two_dimensional: bool },
+    T6
+}
+
+/// White run codes as `(length, code, run)`
+#[rustfmt::skip]
+const WHITE_CODES: [(u8, u16, u16); 104] = [
+    (8, 0b00110101, 0), (6, 0b000111, 1), (4, 0b0111, 2), (4, 0b1000, 3), (4, 0b1011, 4),
+    (4, 0b1100, 5), (4, 0b1110, 6), (4, 0b1111, 7), (5, 0b10011, 8), (5, 0b10100, 9),
+    (5, 0b00111, 10), (5, 0b01000, 11), (6, 0b001000, 12), (6, 0b000011, 13),
+    (6, 0b110100, 14), (6, 0b110101, 15), (6, 0b101010, 16), (6, 0b101011, 17),
+    (7, 0b0100111, 18), (7, 0b0001100, 19), (7, 0b0001000, 20), (7, 0b0010111, 21),
+    (7, 0b0000011, 22), (7, 0b0000100, 23), (7, 0b0101000, 24), (7, 0b0101011, 25),
+    (7, 0b0010011, 26), (7, 0b0100100, 27), (7, 0b0011000, 28), (8, 0b00000010, 29),
+    (8, 0b00000011, 30), (8, 0b00011010, 31), (8, 0b00011011, 32), (8, 0b00010010, 33),
+    (8, 0b00010011, 34), (8, 0b00010100, 35), (8, 0b00010101, 36), (8, 0b00010110, 37),
+    (8, 0b00010111, 38), (8, 0b00101000, 39), (8, 0b00101001, 40), (8, 0b00101010, 41),
+    (8, 0b00101011, 42), (8, 0b00101100, 43), (8, 0b00101101, 44), (8, 0b00000100, 45),
+    (8, 0b00000101, 46), (8, 0b00001010, 47), (8, 0b00001011, 48), (8, 0b01010010, 49),
+    (8, 0b01010011, 50), (8, 0b01010100, 51), (8, 0b01010101, 52), (8, 0b00100100, 53),
+    (8, 0b00100101, 54), (8, 0b01011000, 55), (8, 0b01011001, 56), (8, 0b01011010, 57),
+    (8, 0b01011011, 58), (8, 0b01001010, 59), (8, 0b01001011, 60), (8, 0b00110010, 61),
+    (8, 0b00110011, 62), (8, 0b00110100, 63), (5, 0b11011, 64), (5, 0b10010, 128),
+    (6, 0b010111, 192), (7, 0b0110111, 256), (8, 0b00110110, 320), (8, 0b00110111, 384),
+    (8, 0b01100100, 448), (8, 0b01100101, 512), (8, 0b01101000, 576), (8, 0b01100111, 640),
+    (9, 0b011001100, 704), (9, 0b011001101, 768), (9, 0b011010010, 832), (9, 0b011010011, 896),
+    (9, 0b011010100, 960), (9, 0b011010101, 1024), (9, 0b011010110, 1088),
+    (9, 0b011010111, 1152), (9, 0b011011000, 1216), (9, 0b011011001, 1280),
+    (9, 0b011011010, 1344), (9, 0b011011011, 1408), (9, 0b010011000, 1472),
+    (9, 0b010011001, 1536), (9, 0b010011010, 1600), (6, 0b011000, 1664),
+    (9, 0b010011011, 1728), (11, 0b00000001000, 1792), (11, 0b00000001100, 1856),
+    (11, 0b00000001101, 1920), (12, 0b000000010010, 1984), (12, 0b000000010011, 2048),
+    (12, 0b000000010100, 2112), (12, 0b000000010101, 2176), (12, 0b000000010110, 2240),
+    (12, 0b000000010111, 2304), (12, 0b000000011100, 2368), (12, 0b000000011101, 2432),
+    (12, 0b000000011110, 2496), (12, 0b000000011111, 2560)
+];
+
+/// Black run codes as `(length, code, run)`
+#[rustfmt::skip]
+const BLACK_CODES: [(u8, u16, u16); 104] = [
+    (10, 0b0000110111, 0), (3, 0b010, 1), (2, 0b11, 2), (2, 0b10, 3), (3, 0b011, 4),
+    (4, 0b0011, 5), (4, 0b0010, 6), (5, 0b00011, 7), (6, 0b000101, 8), (6, 0b000100, 9),
+    (7, 0b0000100, 10), (7, 0b0000101, 11), (7, 0b0000111, 12), (8, 0b00000100, 13),
+    (8, 0b00000111, 14), (9, 0b000011000, 15), (10, 0b0000010111, 16), (10, 0b0000011000, 17),
+    (10, 0b0000001000, 18), (11, 0b00001100111, 19), (11, 0b00001101000, 20),
+    (11, 0b00001101100, 21), (11, 0b00000110111, 22), (11, 0b00000101000, 23),
+    (11, 0b00000010111, 24), (11, 0b00000011000, 25), (12, 0b000011001010, 26),
+    (12, 0b000011001011, 27), (12, 0b000011001100, 28), (12, 0b000011001101, 29),
+    (12, 0b000001101000, 30), (12, 0b000001101001, 31), (12, 0b000001101010, 32),
+    (12, 0b000001101011, 33), (12, 0b000011010010, 34), (12, 0b000011010011, 35),
+    (12, 0b000011010100, 36), (12, 0b000011010101, 37), (12, 0b000011010110, 38),
+    (12, 0b000011010111, 39), (12, 0b000001101100, 40), (12, 0b000001101101, 41),
+    (12, 0b000011011010, 42), (12, 0b000011011011, 43), (12, 0b000001010100, 44),
+    (12, 0b000001010101, 45), (12, 0b000001010110, 46), (12, 0b000001010111, 47),
+    (12, 0b000001100100, 48), (12, 0b000001100101, 49), (12, 0b000001010010, 50),
+    (12, 0b000001010011, 51), (12, 0b000000100100, 52), (12, 0b000000110111, 53),
+    (12, 0b000000111000, 54), (12, 0b000000100111, 55), (12, 0b000000101000, 56),
+    (12, 0b000001011000, 57), (12, 0b000001011001, 58), (12, 0b000000101011, 59),
+    (12, 0b000000101100, 60), (12, 0b000001011010, 61), (12, 0b000001100110, 62),
+    (12, 0b000001100111, 63), (10, 0b0000001111, 64), (12, 0b000011001000, 128),
+    (12, 0b000011001001, 192), (12, 0b000001011011, 256), (12, 0b000000110011, 320),
+    (12, 0b000000110100, 384), (12, 0b000000110101, 448), (13, 0b0000001101100, 512),
+    (13, 0b0000001101101, 576), (13, 0b0000001001010, 640), (13, 0b0000001001011, 704),
+    (13, 0b0000001001100, 768), (13, 0b0000001001101, 832), (13, 0b0000001110010, 896),
+    (13, 0b0000001110011, 960), (13, 0b0000001110100, 1024), (13, 0b0000001110101, 1088),
+    (13, 0b0000001110110, 1152), (13, 0b0000001110111, 1216), (13, 0b0000001010010, 1280),
+    (13, 0b0000001010011, 1344), (13, 0b0000001010100, 1408), (13, 0b0000001010101, 1472),
+    (13, 0b0000001011010, 1536), (13, 0b0000001011011, 1600), (13, 0b0000001100100, 1664),
+    (13, 0b0000001100101, 1728), (11, 0b00000001000, 1792), (11, 0b00000001100, 1856),
+    (11, 0b00000001101, 1920), (12, 0b000000010010, 1984), (12, 0b000000010011, 2048),
+    (12, 0b000000010100, 2112), (12, 0b000000010101, 2176), (12, 0b000000010110, 2240),
+    (12, 0b000000010111, 2304), (12, 0b000000011100, 2368), (12, 0b000000011101, 2432),
+    (12, 0b000000011110, 2496), (12, 0b000000011111, 2560)
+];
+
+/// Longest run code in bits
+const MAX_CODE_LENGTH: u8 = 13;
+
+/// Two dimensional coding modes
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Mode {
+    Pass,
+    Horizontal,
+    Vertical(i8),
+    Extension
+}
+
+/// Mode codes as `(length, code, mode)`
+const MODE_CODES: [(u8, u16, Mode); 10] = [
+    (1, 0b1, Mode::Vertical(0)),
+    (3, 0b011, Mode::Vertical(1)),
+    (3, 0b010, Mode::Vertical(-1)),
+    (3, 0b001, Mode::Horizontal),
+    (4, 0b0001, Mode::Pass),
+    (6, 0b000011, Mode::Vertical(2)),
+    (6, 0b000010, Mode::Vertical(-2)),
+    (7, 0b0000011, Mode::Vertical(3)),
+    (7, 0b0000010, Mode::Vertical(-3)),
+    (7, 0b0000001, Mode::Extension)
+];
+
+/// Longest mode code in bits, an all zero code of this length
+/// starts an end of line code
+const MAX_MODE_LENGTH: u8 = 7;
+
+/// An end of line code, 11 zeros followed by a one
+const EOL: u16 = 1;
+const EOL_LENGTH: u8 = 12;
+
+struct BitReader<'a> {
+    data:     &'a [u8],
+    /// Position in bits
+    position: usize
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> BitReader<'a> {
+        BitReader { data, position: 0 }
+    }
+
+    /// Return the next `bits` bits without consuming them,
+    /// reading past the end returns zeros
+    fn peek(&self, bits: u8) -> u16 {
+        let byte = self.position / 8;
+        let mut window = 0_u32;
+
+        for i in 0..3 {
+            window = (window << 8) | u32::from(*self.data.get(byte + i).unwrap_or(&0));
+        }
+        let shift = 24 - (self.position % 8) - usize::from(bits);
+
+        ((window >> shift) & ((1 << bits) - 1)) as u16
+    }
+
+    fn consume(&mut self, bits: u8) {
+        self.position += usize::from(bits);
+    }
+
+    fn align(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.data.len() * 8
+    }
+
+    /// Skip an end of line code and the fill bits before it, returning
+    /// whether one was found
+    fn skip_eol(&mut self) -> bool {
+        let start = self.position;
+
+        while !self.is_empty() && self.peek(1) == 0 {
+            self.consume(1);
+        }
+        if self.position - start >= usize::from(EOL_LENGTH) - 1 && !self.is_empty() {
+            self.consume(1);
+            return true;
+        }
+        self.position = start;
+        false
+    }
+}
+
+/// Lookup table indexed by the next [`MAX_CODE_LENGTH`] bits, entries
+/// are `length << 12 | run` and zero for invalid codes
+fn build_run_table(codes: &[(u8, u16, u16)]) -> Vec<u16> {
+    let mut table = vec![0; 1 << MAX_CODE_LENGTH];
+
+    for &(length, code, run) in codes {
+        let shift = MAX_CODE_LENGTH - length;
+        let start = usize::from(code) << shift;
+
+        for entry in &mut table[start..start + (1 << shift)] {
+            *entry = (u16::from(length) << 12) | run;
+        }
+    }
+    table
+}
+
+struct FaxDecoder<'a> {
+    reader: BitReader<'a>,
+    white:  Vec<u16>,
+    black:  Vec<u16>,
+    modes:  [Option<(u8, Mode)>; 1 << MAX_MODE_LENGTH],
+    width:  usize
+}
+
+impl<'a> FaxDecoder<'a> {
+    fn new(data: &'a [u8], width: usize) -> FaxDecoder<'a> {
+        let mut modes = [None; 1 << MAX_MODE_LENGTH];
+
+        for &(length, code, mode) in &MODE_CODES {
+            let shift = MAX_MODE_LENGTH - length;
+            let start = usize::from(code) << shift;
+
+            for entry in &mut modes[start..start + (1 << shift)] {
+                *entry = Some((length, mode));
+            }
+        }
+        FaxDecoder {
+            reader: BitReader::new(data),
+            white: build_run_table(&WHITE_CODES),
+            black: build_run_table(&BLACK_CODES),
+            modes,
+            width
+        }
+    }
+
+    /// Read a run length, made of any number of make up codes and
+    /// a terminating code
+    fn read_run(&mut self, black: bool) -> Result<usize, TiffDecodeErrors> {
+        let table = if black { &self.black } else { &self.white };
+        let mut total = 0;
+
+        loop {
+            let entry = table[usize::from(self.reader.peek(MAX_CODE_LENGTH))];
+
+            if entry == 0 || self.reader.is_empty() {
+                return Err(TiffDecodeErrors::GenericStatic("Invalid fax run code"));
+            }
+            self.reader.consume((entry >> 12) as u8);
+
+            let run = usize::from(entry & 0xFFF);
+            total += run;
+
+            if run < 64 {
+                return Ok(total);
+            }
+        }
+    }
+
+    /// Decode a one dimensionally coded row into the positions where the color changes
+    fn decode_1d(&mut self, changes: &mut Vec<usize>) -> Result<(), TiffDecodeErrors> {
+        changes.clear();
+
+        let mut position = 0;
+        let mut black = false;
+
+        while position < self.width {
+            position = (position + self.read_run(black)?).min(self.width);
+            changes.push(position);
+            black = !black;
+        }
+        Ok(())
+    }
+
+    /// Decode a row coded relative to the changes in `reference`
+    ///
+    /// Returns false if an end of line was found instead of a row, this
+    /// marks the end of the data in T.6
+    fn decode_2d(
+        &mut self, reference: &[usize], changes: &mut Vec<usize>
+    ) -> Result<bool, TiffDecodeErrors> {
+        changes.clear();
+
+        let width = self.width;
+        // a0 starts on an imaginary white pixel before the row
+        let mut a0: Option<usize> = None;
+        let mut black = false;
+        // first change on the reference row to the right of a0
+        let mut index = 0;
+
+        while a0.is_none_or(|x| x < width) {
+            let peeked = self.reader.peek(MAX_MODE_LENGTH);
+
+            let Some((length, mode)) = self.modes[usize::from(peeked)] else {
+                if self.reader.peek(EOL_LENGTH) == EOL {
+                    self.reader.consume(EOL_LENGTH);
+                    return Ok(false);
+                }
+                return Err(TiffDecodeErrors::GenericStatic("Invalid fax mode code"));
+            };
+            if self.reader.is_empty() {
+                return Err(TiffDecodeErrors::GenericStatic("Fax data ended in a row"));
+            }
+            self.reader.consume(length);
+
+            while index < reference.len() && a0.is_some_and(|x| reference[index] <= x) {
+                index += 1;
+            }
+            // b1 is the first change of the opposite color to a0, changes at
+            // even positions go from white to black
+            let b1_index = if (index % 2 == 1) != black { index + 1 } else { index };
+            let b1 = reference.get(b1_index).copied().unwrap_or(width);
+            let b2 = reference.get(b1_index + 1).copied().unwrap_or(width);
+
+            let start = a0.unwrap_or(0);
+
+            match mode {
+                Mode::Pass => {
+                    a0 = Some(b2);
+                }
+                Mode::Horizontal => {
+                    let a1 = (start + self.read_run(black)?).min(width);
+                    let a2 = (a1 + self.read_run(!black)?).min(width);
+
+                    changes.push(a1);
+                    changes.push(a2);
+                    a0 = Some(a2);
+                }
+                Mode::Vertical(delta) => {
+                    let a1 = b1
+                        .saturating_add_signed(isize::from(delta))
+                        .clamp(start, width);
+
+                    changes.push(a1);
+                    a0 = Some(a1);
+                    black = !black;
+                }
+                Mode::Extension => {
+                    return Err(TiffDecodeErrors::Unsupported(
+                        "Uncompressed mode in fax data".into()
+                    ));
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Write a row described by its color changes into `output`, which should be zeroed
+fn fill_row(changes: &[usize], output: &mut [u8]) {
+    for span in changes.chunks_exact(2) {
+        for x in span[0]..span[1] {
+            output[x / 8] |= 0x80 >> (x % 8);
+        }
+    }
+}
+
+/// Decode fax coded data of `height` rows, returning packed rows
+///
+/// A decoding error stops decoding, the rows after it are left white
+pub(crate) fn decode_fax(
+    data: &[u8], width: usize, height: usize, coding: FaxCoding
+) -> Result<Vec<u8>, TiffDecodeErrors> {
+    let row_bytes = width.div_ceil(8);
+    let total = row_bytes
+        .checked_mul(height)
+        .ok_or(TiffDecodeErrors::GenericStatic(
+            "Image size overflows usize"
+        ))?;
+
+    let mut output = vec![0; total];
+    let mut decoder = FaxDecoder::new(data, width);
+    // the row above the first one is imaginary and white
+    let mut reference = Vec::new();
+    let mut changes = Vec::new();
+
+    for row in output.chunks_exact_mut(row_bytes) {
+        let result = match coding {
+            FaxCoding::ModifiedHuffman => {
+                let result = decoder.decode_1d(&mut changes).map(|_| true);
+                decoder.reader.align();
+                result
+            }
+            FaxCoding::T4 { two_dimensional } => {
+                decoder.reader.skip_eol();
+
+                if two_dimensional && decoder.reader.peek(1) == 0 {
+                    decoder.reader.consume(1);
+                    decoder.decode_2d(&reference, &mut changes)
+                } else {
+                    if two_dimensional {
+                        decoder.reader.consume(1);
+                    }
+                    decoder.decode_1d(&mut changes).map(|_| true)
+                }
+            }
+            FaxCoding::T6 => decoder.decode_2d(&reference, &mut changes)
+        };
+        match result {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(_err) => {
+                warn!("Stopped decoding fax data: {:?}", _err);
+                break;
+            }
+        }
+        // odd change counts mean the row ended in black
+        if changes.len() % 2 == 1 {
+            changes.push(width);
+        }
+        fill_row(&changes, row);
+        core::mem::swap(&mut reference, &mut changes);
+    }
+    Ok(output)
+}
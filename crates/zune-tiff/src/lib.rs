@@ -0,0 +1,55 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! A TIFF decoder
+//!
+//! # Supported images
+//! - Little and big endian files, BigTIFF is not supported
+//! - Multi-page files, every full resolution image in the main IFD chain is a page
+//! - Strips and tiles, chunky and planar configurations
+//! - Uncompressed, PackBits, LZW, Deflate and JPEG compressed pages,
+//!   with the horizontal differencing predictor
+//! - CCITT Modified Huffman, T.4 (Group 3) and T.6 (Group 4) fax pages
+//! - Bilevel, grayscale, RGB, palette and CMYK images, with alpha
+//! - 1 to 16 bit unsigned samples and 32 bit floating point samples
+//!
+//! Samples below 8 bits and palette images are decoded to 8 bits, other
+//! integer samples to 16 bits.
+//!
+//! # Features
+//!  - `log`: Use the `log` crate features to print image information when decoding
+//!  - `std`: Allow direct decoding from anything that implements `std::io::BufRead` + `std::io::Seek`
+//!
+//! # Usage
+//! ```no_run
+//! use zune_core::bytestream::ZCursor;
+//! use zune_tiff::TiffDecoder;
+//!
+//! let mut decoder = TiffDecoder::new(ZCursor::new(&[]));
+//! decoder.decode_headers().unwrap();
+//!
+//! for page in 0..decoder.page_count() {
+//!     let info = decoder.page_info(page).unwrap();
+//!     let pixels = decoder.decode_page(page).unwrap();
+//!     // pixels are in info.colorspace and info.depth
+//! }
+//! ```
+#![no_std]
+#![macro_use]
+extern crate alloc;
+
+pub use zune_core;
+
+pub use crate::decoder::{probe_tiff, TiffDecoder, TiffPageInfo};
+pub use crate::errors::TiffDecodeErrors;
+
+mod decoder;
+mod errors;
+mod fax;
+mod lzw;
+mod packbits;
+mod tiff;
@@ -0,0 +1,136 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! TIFF flavoured LZW decompression
+//!
+//! Codes are stored most significant bit first, start at 9 bits and grow up to
+//! 12 bits. Unlike GIF the code size grows one code early, i.e. when the
+//! next free code is one less than a power of two.
+
+use crate::errors::TiffDecodeErrors;
+
+const MAX_CODES: usize = 4096;
+const MAX_CODE_SIZE: u8 = 12;
+const MIN_CODE_SIZE: u8 = 9;
+
+const CLEAR_CODE: u16 = 256;
+const END_CODE: u16 = 257;
+
+/// Returns true if `data` looks like it was written by the LZW encoder of old
+/// libtiff versions, which stored codes least significant bit first
+pub(crate) fn is_old_style_lzw(data: &[u8]) -> bool {
+    // a new style stream starts with a clear code, whose first byte is 0x80
+    matches!(data, [0x00, second, ..] if second & 0x01 != 0)
+}
+
+/// Decompress `data` into `output`, returning the number of bytes written
+///
+/// Bytes that don't fit into `output` are discarded, decoding stops at the
+/// end of information code or when the data runs out.
+pub(crate) fn decode_lzw(data: &[u8], output: &mut [u8]) -> Result<usize, TiffDecodeErrors> {
+    let mut prefix = [0_u16; MAX_CODES];
+    let mut suffix = [0_u8; MAX_CODES];
+    let mut first = [0_u8; MAX_CODES];
+    let mut stack = [0_u8; MAX_CODES];
+
+    for code in 0..CLEAR_CODE {
+        suffix[usize::from(code)] = code as u8;
+        first[usize::from(code)] = code as u8;
+    }
+
+    let mut code_size = MIN_CODE_SIZE;
+    let mut next_code = END_CODE + 1;
+    let mut previous: Option<u16> = None;
+
+    let mut bit_buffer = 0_u32;
+    let mut bits_left = 0_u8;
+    let mut data_pos = 0;
+    let mut out_pos = 0;
+
+    while out_pos < output.len() {
+        // refill, codes are at most 12 bits so this never overflows
+        while bits_left < code_size && data_pos < data.len() {
+            bit_buffer = (bit_buffer << 8) | u32::from(data[data_pos]);
+            bits_left += 8;
+            data_pos += 1;
+        }
+        if bits_left < code_size {
+            // truncated stream
+            break;
+        }
+        bits_left -= code_size;
+        let code = ((bit_buffer >> bits_left) & ((1 << code_size) - 1)) as u16;
+
+        if code == CLEAR_CODE {
+            code_size = MIN_CODE_SIZE;
+            next_code = END_CODE + 1;
+            previous = None;
+            continue;
+        }
+        if code == END_CODE {
+            break;
+        }
+
+        match previous {
+            None => {
+                // first code after a clear must be a literal
+                if code >= CLEAR_CODE {
+                    return Err(TiffDecodeErrors::Generic(alloc::format!(
+                        "Invalid LZW code {code}"
+                    )));
+                }
+            }
+            Some(prev) => {
+                let first_byte = if code < next_code {
+                    first[usize::from(code)]
+                } else if code == next_code {
+                    // the KwKwK case, code is the one we are about to add
+                    first[usize::from(prev)]
+                } else {
+                    return Err(TiffDecodeErrors::Generic(alloc::format!(
+                        "Invalid LZW code {code}"
+                    )));
+                };
+
+                if usize::from(next_code) < MAX_CODES {
+                    let next = usize::from(next_code);
+
+                    prefix[next] = prev;
+                    suffix[next] = first_byte;
+                    first[next] = first[usize::from(prev)];
+                    next_code += 1;
+
+                    if usize::from(next_code) == (1 << code_size) - 1 && code_size < MAX_CODE_SIZE {
+                        code_size += 1;
+                    }
+                }
+            }
+        }
+
+        // unwind the string for this code, it's stored in reverse
+        let mut length = 0;
+        let mut current = code;
+
+        while current > END_CODE {
+            stack[length] = suffix[usize::from(current)];
+            current = prefix[usize::from(current)];
+            length += 1;
+        }
+        stack[length] = current as u8;
+        length += 1;
+
+        for byte in stack[..length].iter().rev() {
+            if out_pos < output.len() {
+                output[out_pos] = *byte;
+                out_pos += 1;
+            }
+        }
+        previous = Some(code);
+    }
+    Ok(out_pos)
+}
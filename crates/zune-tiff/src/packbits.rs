@@ -0,0 +1,51 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! PackBits run length decompression
+//!
+//! Each run starts with a signed header byte `n`, `0..=127` copies the next
+//! `n + 1` bytes literally, `-127..=-1` repeats the next byte `1 - n` times
+//! and `-128` is skipped.
+
+/// Decompress `data` into `output`, returning the number of bytes written
+///
+/// Decoding stops when `output` is full or the data runs out
+pub(crate) fn decode_packbits(data: &[u8], output: &mut [u8]) -> usize {
+    let mut in_pos = 0;
+    let mut out_pos = 0;
+
+    while in_pos < data.len() && out_pos < output.len() {
+        let header = data[in_pos] as i8;
+        in_pos += 1;
+
+        match header {
+            0..=127 => {
+                let length = (usize::from(header as u8) + 1)
+                    .min(data.len() - in_pos)
+                    .min(output.len() - out_pos);
+
+                output[out_pos..out_pos + length].copy_from_slice(&data[in_pos..in_pos + length]);
+                in_pos += usize::from(header as u8) + 1;
+                out_pos += length;
+            }
+            -127..=-1 => {
+                let Some(&value) = data.get(in_pos) else {
+                    break;
+                };
+                let length = (1 - isize::from(header)) as usize;
+                let length = length.min(output.len() - out_pos);
+
+                output[out_pos..out_pos + length].fill(value);
+                in_pos += 1;
+                out_pos += length;
+            }
+            -128 => {}
+        }
+    }
+    out_pos
+}
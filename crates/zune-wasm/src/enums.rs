@@ -11,6 +11,7 @@
 use wasm_bindgen::prelude::wasm_bindgen;
 use zune_core::colorspace::ColorSpace;
 use zune_image::codecs::ImageFormat;
+use zune_imageprocs::convolve::EmbossDirection;
 use zune_imageprocs::pad::PadMethod;
 use zune_imageprocs::spatial_ops::SpatialOperations;
 
@@ -179,3 +180,30 @@ impl From<PadMethod> for WasmPadMethod {
         }
     }
 }
+
+/// The direction light comes from when embossing an image
+#[wasm_bindgen(js_name=EmbossDirection)]
+pub enum WasmEmbossDirection {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest
+}
+impl From<WasmEmbossDirection> for EmbossDirection {
+    fn from(value: WasmEmbossDirection) -> Self {
+        match value {
+            WasmEmbossDirection::North => EmbossDirection::North,
+            WasmEmbossDirection::NorthEast => EmbossDirection::NorthEast,
+            WasmEmbossDirection::East => EmbossDirection::East,
+            WasmEmbossDirection::SouthEast => EmbossDirection::SouthEast,
+            WasmEmbossDirection::South => EmbossDirection::South,
+            WasmEmbossDirection::SouthWest => EmbossDirection::SouthWest,
+            WasmEmbossDirection::West => EmbossDirection::West,
+            WasmEmbossDirection::NorthWest => EmbossDirection::NorthWest
+        }
+    }
+}
@@ -28,6 +28,7 @@ use zune_imageprocs::box_blur::BoxBlur;
 use zune_imageprocs::brighten::Brighten;
 use zune_imageprocs::color_matrix::ColorMatrix;
 use zune_imageprocs::contrast::Contrast;
+use zune_imageprocs::convolve::Convolve;
 use zune_imageprocs::crop::Crop;
 use zune_imageprocs::exposure::Exposure;
 use zune_imageprocs::flip::{Flip, FlipDirection};
@@ -36,13 +37,16 @@ use zune_imageprocs::gaussian_blur::GaussianBlur;
 use zune_imageprocs::hsv_adjust::HsvAdjust;
 use zune_imageprocs::invert::Invert;
 use zune_imageprocs::median::Median;
+use zune_imageprocs::posterize::Posterize;
 use zune_imageprocs::premul_alpha::PremultiplyAlpha;
+use zune_imageprocs::sepia::Sepia;
+use zune_imageprocs::solarize::Solarize;
 use zune_imageprocs::spatial::SpatialOps;
 use zune_imageprocs::spatial_ops::SpatialOperations;
 use zune_imageprocs::stretch_contrast::StretchContrast;
 use zune_imageprocs::threshold::{Threshold, ThresholdMethod};
 
-use crate::enums::{WasmColorspace, WasmImageFormats, WasmSpatialOperations};
+use crate::enums::{WasmColorspace, WasmEmbossDirection, WasmImageFormats, WasmSpatialOperations};
 use crate::utils::set_panic_hook;
 
 mod enums;
@@ -223,6 +227,53 @@ impl WasmImage {
         self.execute_ops(&ops)
     }
 
+    /// Reduce the number of tones of an image
+    ///
+    /// Every channel is rounded to `levels` evenly spaced values
+    ///
+    /// @param levels - Number of levels per channel, including black and white, minimum is 2
+    pub fn posterize(&mut self, levels: u16) -> Result<(), JsError> {
+        self.execute_ops(&Posterize::new(levels))
+    }
+
+    /// Solarize an image, inverting pixels brighter than a threshold
+    ///
+    /// @param threshold - The threshold between 0 and 1, where 1 stands for the
+    /// maximum value of the bit depth, 0.5 is the classic effect
+    pub fn solarize(&mut self, threshold: f32) -> Result<(), JsError> {
+        self.execute_ops(&Solarize::new(threshold))
+    }
+
+    /// Apply a sepia tone to an image
+    ///
+    /// Grayscale images are converted to RGB
+    ///
+    /// @param strength - How much of the effect is applied, between 0 (none) and 1 (full)
+    pub fn sepia(&mut self, strength: f32) -> Result<(), JsError> {
+        self.execute_ops(&Sepia::new(strength))
+    }
+
+    /// Emboss an image, making it look raised as if lit from a direction
+    ///
+    /// @param direction - The direction light comes from
+    pub fn emboss(&mut self, direction: WasmEmbossDirection) -> Result<(), JsError> {
+        self.execute_ops(&Convolve::emboss(direction.into()))
+    }
+
+    /// Sharpen an image with a 3x3 kernel
+    ///
+    /// @param amount - How much to sharpen, 0 leaves the image as is, 1 is a common default
+    pub fn sharpen(&mut self, amount: f32) -> Result<(), JsError> {
+        self.execute_ops(&Convolve::sharpen(amount))
+    }
+
+    /// Enhance the edges of an image in every direction with a 3x3 kernel
+    ///
+    /// @param amount - How much to enhance edges, 0.5 is a common default
+    pub fn edge_enhance(&mut self, amount: f32) -> Result<(), JsError> {
+        self.execute_ops(&Convolve::edge_enhance(amount))
+    }
+
     /// Convert an image to  grayscale
     ///
     /// A convenience function for {@link convert_color}
@@ -0,0 +1,295 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Checking and regenerating hash corpora
+//!
+//! Each decoder has a corpus, a json file in `tests/` listing files and the hash
+//! of their decoded pixels. An entry can pick the [`OptionsPreset`] it is decoded
+//! with, listing a file once per preset checks that e.g. the fast and safe paths
+//! produce the same pixels. An entry without a hash expects decoding to fail.
+//!
+//! The following environment variables control the tests
+//!
+//! - `ZUNE_TESTS_REGENERATE`: Rewrite corpora with the hashes the decoders produce
+//!   instead of checking them. Set to a comma separated list of presets, e.g.
+//!   `fast,safe,strict`, to list every file once per preset, any other value keeps
+//!   the presets of the existing entries.
+//! - `ZUNE_TESTS_REPORT_DIR`: Write a json report of the hashes which changed to
+//!   `<dir>/<corpus>.json`. The report is also printed to stderr.
+//!
+//! E.g. after a refactor of the png decoder
+//! ```text
+//! ZUNE_TESTS_REGENERATE=1 ZUNE_TESTS_REPORT_DIR=/tmp/report cargo test -p zune-tests png
+//! ```
+//! rewrites `tests/png.json` and lists every file whose output changed in `/tmp/report/png.json`
+
+use std::collections::HashSet;
+use std::fs::{create_dir_all, read, write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use zune_core::options::DecoderOptions;
+
+use crate::{hash, JsonColorspace, TestEntry};
+
+/// Decode a file of a corpus with the given options, returning
+/// the pixels or a description of the error
+pub type DecodeFn = fn(&[u8], DecoderOptions) -> Result<Vec<u8>, String>;
+
+/// Options a corpus entry is decoded with
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptionsPreset {
+    /// [`DecoderOptions::new_safe`], used when an entry doesn't specify options
+    #[default]
+    Safe,
+    /// [`DecoderOptions::new_fast`], enables intrinsics and unsafe paths
+    Fast,
+    /// Safe options which error out on non-conforming files
+    Strict
+}
+
+impl OptionsPreset {
+    pub fn from_name(name: &str) -> Option<OptionsPreset> {
+        match name.trim() {
+            "safe" => Some(OptionsPreset::Safe),
+            "fast" => Some(OptionsPreset::Fast),
+            "strict" => Some(OptionsPreset::Strict),
+            _ => None
+        }
+    }
+
+    pub fn to_options(self) -> DecoderOptions {
+        match self {
+            OptionsPreset::Safe => DecoderOptions::new_safe(),
+            OptionsPreset::Fast => DecoderOptions::new_fast(),
+            OptionsPreset::Strict => DecoderOptions::new_safe().set_strict_mode(true)
+        }
+    }
+
+    /// Options of this preset, with the jpeg output colorspace set to `colorspace` if present
+    pub fn with_colorspace(self, colorspace: Option<JsonColorspace>) -> DecoderOptions {
+        let options = self.to_options();
+
+        match colorspace {
+            Some(color) => options.jpeg_set_out_colorspace(color.to_colorspace()),
+            None => options
+        }
+    }
+
+    pub(crate) fn is_safe(&self) -> bool {
+        *self == OptionsPreset::Safe
+    }
+}
+
+/// How an entry differs between the expected and the produced corpus
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// The hash of the entry changed
+    Changed,
+    /// The entry is new, e.g. a preset was added to the matrix
+    Added,
+    /// The entry was dropped, e.g. a preset was removed from the matrix
+    Removed
+}
+
+/// A single entry of a [`DiffReport`]
+///
+/// A `None` hash means decoding failed
+#[derive(Clone, Debug, Serialize)]
+pub struct CorpusChange {
+    pub kind:       ChangeKind,
+    pub name:       String,
+    pub colorspace: Option<JsonColorspace>,
+    pub options:    OptionsPreset,
+    pub old_hash:   Option<u128>,
+    pub new_hash:   Option<u128>,
+    pub error:      Option<String>
+}
+
+/// Machine readable list of entries which differ from a corpus
+#[derive(Clone, Debug, Serialize)]
+pub struct DiffReport {
+    pub corpus:  String,
+    pub changes: Vec<CorpusChange>
+}
+
+/// The result of decoding an entry
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Outcome {
+    hash:  Option<u128>,
+    error: Option<String>
+}
+
+type EntryKey = (String, Option<JsonColorspace>, OptionsPreset);
+
+fn key(entry: &TestEntry) -> EntryKey {
+    (entry.name.clone(), entry.colorspace, entry.options)
+}
+
+fn decode_entry(dir: &Path, entry: &TestEntry, decode: DecodeFn) -> Outcome {
+    let file_name = dir.join(&entry.name);
+    let contents = read(&file_name).unwrap_or_else(|e| panic!("Cannot read {file_name:?}: {e}"));
+
+    let options = entry.options.with_colorspace(entry.colorspace);
+
+    match decode(&contents, options) {
+        Ok(pixels) => Outcome {
+            hash:  Some(hash(&pixels)),
+            error: None
+        },
+        Err(e) => Outcome {
+            hash:  None,
+            error: Some(e)
+        }
+    }
+}
+
+fn corpus_file(corpus: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("tests/{corpus}.json"))
+}
+
+/// Decode every entry of the corpus `tests/<corpus>.json` with files in `dir`
+///
+/// This checks the hashes against the corpus, or rewrites the corpus
+/// if `ZUNE_TESTS_REGENERATE` is set, see the [module docs](self)
+pub fn run_corpus(corpus: &str, dir: &Path, decode: DecodeFn) {
+    let json_file = read(corpus_file(corpus)).unwrap();
+    let entries: Vec<TestEntry> = serde_json::from_slice(&json_file).unwrap();
+
+    match std::env::var("ZUNE_TESTS_REGENERATE") {
+        Ok(matrix) => regenerate_corpus(corpus, dir, &entries, &matrix, decode),
+        Err(_) => check_corpus(corpus, dir, &entries, decode)
+    }
+}
+
+fn check_corpus(corpus: &str, dir: &Path, entries: &[TestEntry], decode: DecodeFn) {
+    let mut changes = vec![];
+
+    for entry in entries {
+        let outcome = decode_entry(dir, entry, decode);
+
+        if outcome.hash != entry.hash {
+            changes.push(change(ChangeKind::Changed, entry, entry.hash, outcome));
+        }
+    }
+    if !changes.is_empty() {
+        let files: Vec<&String> = changes.iter().map(|x| &x.name).collect();
+        let message = format!("Errors found during test decoding\n {files:#?}");
+
+        write_report(corpus, changes);
+        panic!("{message}");
+    }
+}
+
+fn regenerate_corpus(
+    corpus: &str, dir: &Path, entries: &[TestEntry], matrix: &str, decode: DecodeFn
+) {
+    let presets: Option<Vec<OptionsPreset>> =
+        matrix.split(',').map(OptionsPreset::from_name).collect();
+
+    let mut new_entries = match presets {
+        Some(presets) => expand_matrix(entries, &presets),
+        None => entries.to_vec()
+    };
+    let mut changes = vec![];
+
+    for entry in &mut new_entries {
+        let outcome = decode_entry(dir, entry, decode);
+        // never record hashes of decoders that produce different output
+        // for the same input, e.g. from reading uninitialized memory
+        let second_outcome = decode_entry(dir, entry, decode);
+        assert_eq!(
+            outcome, second_outcome,
+            "Decoding {} with {:?} options is not deterministic",
+            entry.name, entry.options
+        );
+
+        match entries.iter().find(|x| key(x) == key(entry)) {
+            Some(old) if old.hash != outcome.hash => {
+                changes.push(change(
+                    ChangeKind::Changed,
+                    entry,
+                    old.hash,
+                    outcome.clone()
+                ));
+            }
+            None => changes.push(change(ChangeKind::Added, entry, None, outcome.clone())),
+            _ => {}
+        }
+        entry.hash = outcome.hash;
+    }
+    let new_keys: HashSet<EntryKey> = new_entries.iter().map(key).collect();
+
+    for old in entries.iter().filter(|x| !new_keys.contains(&key(x))) {
+        let outcome = Outcome {
+            hash:  None,
+            error: None
+        };
+        changes.push(change(ChangeKind::Removed, old, old.hash, outcome));
+    }
+
+    let json = serde_json::to_string_pretty(&new_entries).unwrap();
+    write(corpus_file(corpus), json).unwrap();
+
+    write_report(corpus, changes);
+}
+
+/// List every file once per preset, keeping comments and the order of files
+fn expand_matrix(entries: &[TestEntry], presets: &[OptionsPreset]) -> Vec<TestEntry> {
+    let mut seen = HashSet::new();
+    let mut new_entries = vec![];
+
+    for entry in entries {
+        if !seen.insert((entry.name.clone(), entry.colorspace)) {
+            continue;
+        }
+        for preset in presets {
+            let existing = entries.iter().find(|x| {
+                x.name == entry.name && x.colorspace == entry.colorspace && x.options == *preset
+            });
+            let mut new_entry = existing.unwrap_or(entry).clone();
+            new_entry.options = *preset;
+            new_entries.push(new_entry);
+        }
+    }
+    new_entries
+}
+
+fn change(
+    kind: ChangeKind, entry: &TestEntry, old_hash: Option<u128>, outcome: Outcome
+) -> CorpusChange {
+    CorpusChange {
+        kind,
+        name: entry.name.clone(),
+        colorspace: entry.colorspace,
+        options: entry.options,
+        old_hash,
+        new_hash: outcome.hash,
+        error: outcome.error
+    }
+}
+
+fn write_report(corpus: &str, changes: Vec<CorpusChange>) {
+    if changes.is_empty() {
+        return;
+    }
+    let report = DiffReport {
+        corpus: corpus.to_string(),
+        changes
+    };
+    let json = serde_json::to_string_pretty(&report).unwrap();
+
+    if let Some(dir) = std::env::var_os("ZUNE_TESTS_REPORT_DIR") {
+        let dir = PathBuf::from(dir);
+        create_dir_all(&dir).unwrap();
+        write(dir.join(format!("{corpus}.json")), &json).unwrap();
+    }
+    eprintln!("{json}");
+}
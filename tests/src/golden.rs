@@ -0,0 +1,410 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Comparing decoded images to reference images
+//!
+//! Hash corpora break on any change of the output, even benign ones like
+//! rounding differences from a faster IDCT. Golden tests instead compare the
+//! output to a stored reference image, allowing each entry a tolerance
+//! in the form of
+//!
+//! - `max_diff`: The largest allowed difference between a sample and the
+//!   reference, defaults to 0
+//! - `min_ssim`: The smallest allowed mean [structural similarity] to the
+//!   reference, defaults to 1.0, i.e. identical
+//!
+//! Entries live in `tests/<corpus>_golden.json`, references are 8 bit PNGs in
+//! `test-images/golden/<corpus>`.
+//!
+//! When an entry fails, the output, the reference and an image of the differences
+//! (scaled so that the largest difference is white) are written to
+//! `ZUNE_TESTS_ARTIFACT_DIR`, or `target/golden-failures` if it isn't set.
+//!
+//! Setting `ZUNE_TESTS_REGENERATE` rewrites the references from the output of
+//! entries using the safe preset, entries with other presets are then compared
+//! against those.
+//!
+//! [structural similarity]: https://en.wikipedia.org/wiki/Structural_similarity
+
+use std::fs::{create_dir_all, read, write};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::ZCursor;
+use zune_core::colorspace::ColorSpace;
+use zune_core::options::{DecoderOptions, EncoderOptions};
+use zune_png::{PngDecoder, PngEncoder};
+
+use crate::corpus::OptionsPreset;
+use crate::{sample_path, JsonColorspace};
+
+/// Size of the windows the structural similarity is computed over
+const SSIM_WINDOW: usize = 8;
+/// Distance between windows, windows overlap to not miss
+/// differences on window edges
+const SSIM_STEP: usize = 4;
+
+/// A decoded 8 bit image
+#[derive(Clone, Debug)]
+pub struct GoldenImage {
+    pub pixels:     Vec<u8>,
+    pub width:      usize,
+    pub height:     usize,
+    pub components: usize
+}
+
+/// Decode a file with the given options into an 8 bit image
+pub type GoldenDecodeFn = fn(&[u8], DecoderOptions) -> Result<GoldenImage, String>;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GoldenEntry {
+    pub name:       String,
+    #[serde(default)]
+    pub colorspace: Option<JsonColorspace>,
+    #[serde(default)]
+    pub options:    OptionsPreset,
+    /// Reference image relative to `test-images/golden/<corpus>`,
+    /// defaults to `<name>.png`
+    #[serde(default)]
+    pub reference:  Option<String>,
+    #[serde(default)]
+    pub max_diff:   u8,
+    #[serde(default = "default_min_ssim")]
+    pub min_ssim:   f64,
+    pub comment:    Option<String>
+}
+
+fn default_min_ssim() -> f64 {
+    1.0
+}
+
+impl GoldenEntry {
+    fn reference_name(&self) -> String {
+        self.reference
+            .clone()
+            .unwrap_or_else(|| format!("{}.png", self.name))
+    }
+
+    /// A file name identifying the entry, used for artifacts
+    fn artifact_stem(&self, corpus: &str) -> String {
+        let mut stem = format!(
+            "{corpus}-{}-{:?}",
+            self.name.replace('/', "_"),
+            self.options
+        );
+
+        if let Some(color) = self.colorspace {
+            stem = format!("{stem}-{color:?}");
+        }
+        stem
+    }
+}
+
+/// How an image differs from its reference
+#[derive(Copy, Clone, Debug)]
+pub struct Comparison {
+    /// Largest difference between two samples
+    pub max_diff:   u8,
+    /// Number of samples that differ
+    pub mismatched: usize,
+    /// Mean structural similarity of all channels
+    pub ssim:       f64
+}
+
+/// Compare an image to its reference
+///
+/// # Returns
+/// - Ok(Comparison): How much the images differ
+/// - Err(e): The images have different dimensions or components
+pub fn compare(actual: &GoldenImage, expected: &GoldenImage) -> Result<Comparison, String> {
+    if (actual.width, actual.height, actual.components)
+        != (expected.width, expected.height, expected.components)
+    {
+        return Err(format!(
+            "Expected a {}x{} image with {} components but found {}x{} with {} components",
+            expected.width,
+            expected.height,
+            expected.components,
+            actual.width,
+            actual.height,
+            actual.components
+        ));
+    }
+    let mut max_diff = 0;
+    let mut mismatched = 0;
+
+    for (a, b) in actual.pixels.iter().zip(&expected.pixels) {
+        let diff = a.abs_diff(*b);
+        max_diff = max_diff.max(diff);
+        mismatched += usize::from(diff != 0);
+    }
+
+    Ok(Comparison {
+        max_diff,
+        mismatched,
+        ssim: ssim(actual, expected)
+    })
+}
+
+/// Mean structural similarity of two images of the same size, averaged
+/// over all channels
+///
+/// This returns 1.0 for identical images and smaller values the more the
+/// luminance, contrast and structure of the images differ
+pub fn ssim(a: &GoldenImage, b: &GoldenImage) -> f64 {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+    let window_w = SSIM_WINDOW.min(a.width);
+    let window_h = SSIM_WINDOW.min(a.height);
+    let stride = a.width * a.components;
+
+    let mut total = 0.0;
+    let mut windows = 0_usize;
+
+    for channel in 0..a.components {
+        for y in (0..=a.height - window_h).step_by(SSIM_STEP) {
+            for x in (0..=a.width - window_w).step_by(SSIM_STEP) {
+                let (mut sum_a, mut sum_b) = (0.0, 0.0);
+                let (mut sum_aa, mut sum_bb, mut sum_ab) = (0.0, 0.0, 0.0);
+
+                for wy in y..y + window_h {
+                    for wx in x..x + window_w {
+                        let pos = wy * stride + wx * a.components + channel;
+                        let (pa, pb) = (f64::from(a.pixels[pos]), f64::from(b.pixels[pos]));
+
+                        sum_a += pa;
+                        sum_b += pb;
+                        sum_aa += pa * pa;
+                        sum_bb += pb * pb;
+                        sum_ab += pa * pb;
+                    }
+                }
+                let n = (window_w * window_h) as f64;
+                let (mean_a, mean_b) = (sum_a / n, sum_b / n);
+                let var_a = sum_aa / n - mean_a * mean_a;
+                let var_b = sum_bb / n - mean_b * mean_b;
+                let covariance = sum_ab / n - mean_a * mean_b;
+
+                total += ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+                    / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2));
+                windows += 1;
+            }
+        }
+    }
+    if windows == 0 {
+        return 1.0;
+    }
+    total / windows as f64
+}
+
+fn colorspace_of(components: usize) -> Result<ColorSpace, String> {
+    // references only store samples, channels keep their meaning
+    // e.g. a BGR output is stored as if it was RGB
+    match components {
+        1 => Ok(ColorSpace::Luma),
+        2 => Ok(ColorSpace::LumaA),
+        3 => Ok(ColorSpace::RGB),
+        4 => Ok(ColorSpace::RGBA),
+        c => Err(format!("Cannot store images with {c} components"))
+    }
+}
+
+fn encode_png(image: &GoldenImage) -> Result<Vec<u8>, String> {
+    let colorspace = colorspace_of(image.components)?;
+    let options = EncoderOptions::new(image.width, image.height, colorspace, BitDepth::Eight);
+
+    let mut sink = vec![];
+    PngEncoder::new(&image.pixels, options)
+        .encode(&mut sink)
+        .map_err(|e| format!("{e:?}"))?;
+    Ok(sink)
+}
+
+fn decode_png(data: &[u8]) -> Result<GoldenImage, String> {
+    let mut decoder = PngDecoder::new(ZCursor::new(data));
+    let pixels = decoder.decode_raw().map_err(|e| format!("{e:?}"))?;
+    let (width, height) = decoder.dimensions().unwrap();
+    let colorspace = decoder.colorspace().unwrap();
+
+    Ok(GoldenImage {
+        pixels,
+        width,
+        height,
+        components: colorspace.num_components()
+    })
+}
+
+fn artifact_dir() -> PathBuf {
+    match std::env::var_os("ZUNE_TESTS_ARTIFACT_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => sample_path().join("target/golden-failures")
+    }
+}
+
+/// Write the output, the reference and their differences of a failed entry
+fn write_artifacts(stem: &str, actual: &GoldenImage, expected: Option<&GoldenImage>) -> PathBuf {
+    let dir = artifact_dir();
+    create_dir_all(&dir).unwrap();
+
+    write(
+        dir.join(format!("{stem}.actual.png")),
+        encode_png(actual).unwrap()
+    )
+    .unwrap();
+
+    if let Some(expected) = expected {
+        write(
+            dir.join(format!("{stem}.expected.png")),
+            encode_png(expected).unwrap()
+        )
+        .unwrap();
+    }
+    if let Some(expected) = expected.filter(|x| x.pixels.len() == actual.pixels.len()) {
+        let max_diff = actual
+            .pixels
+            .iter()
+            .zip(&expected.pixels)
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let diff = GoldenImage {
+            pixels:     actual
+                .pixels
+                .iter()
+                .zip(&expected.pixels)
+                .map(|(a, b)| (u32::from(a.abs_diff(*b)) * 255 / u32::from(max_diff)) as u8)
+                .collect(),
+            width:      actual.width,
+            height:     actual.height,
+            components: actual.components
+        };
+        write(
+            dir.join(format!("{stem}.diff.png")),
+            encode_png(&diff).unwrap()
+        )
+        .unwrap();
+    }
+    dir
+}
+
+fn golden_file(corpus: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("tests/{corpus}_golden.json"))
+}
+
+fn decode_entry(
+    dir: &Path, entry: &GoldenEntry, decode: GoldenDecodeFn
+) -> Result<GoldenImage, String> {
+    let file_name = dir.join(&entry.name);
+    let contents = read(&file_name).unwrap_or_else(|e| panic!("Cannot read {file_name:?}: {e}"));
+
+    decode(&contents, entry.options.with_colorspace(entry.colorspace))
+}
+
+/// Check one entry, returning a description of the failure if it fails
+fn check_entry(
+    corpus: &str, dir: &Path, reference_dir: &Path, entry: &GoldenEntry, decode: GoldenDecodeFn
+) -> Option<String> {
+    let actual = match decode_entry(dir, entry, decode) {
+        Ok(image) => image,
+        Err(e) => return Some(format!("{}: decoding failed: {e}", entry.name))
+    };
+    let stem = entry.artifact_stem(corpus);
+
+    let reference_path = reference_dir.join(entry.reference_name());
+    let expected = match read(&reference_path)
+        .map_err(|e| e.to_string())
+        .and_then(|x| decode_png(&x))
+    {
+        Ok(image) => image,
+        Err(e) => {
+            let dir = write_artifacts(&stem, &actual, None);
+            return Some(format!(
+                "{}: cannot load reference {reference_path:?}: {e}, output written to {dir:?}",
+                entry.name
+            ));
+        }
+    };
+
+    let failure = match compare(&actual, &expected) {
+        Ok(result) if result.max_diff <= entry.max_diff && result.ssim >= entry.min_ssim => {
+            return None;
+        }
+        Ok(result) => format!(
+            "{} ({:?}): max difference {} (allowed {}), {} differing samples, SSIM {:.5} (minimum {})",
+            entry.name, entry.options, result.max_diff, entry.max_diff, result.mismatched,
+            result.ssim, entry.min_ssim
+        ),
+        Err(e) => format!("{}: {e}", entry.name)
+    };
+    let dir = write_artifacts(&stem, &actual, Some(&expected));
+
+    Some(format!("{failure}, artifacts written to {dir:?}"))
+}
+
+/// Compare every entry of `tests/<corpus>_golden.json`, decoding files in `dir`,
+/// to its reference image
+///
+/// See the [module docs](self) for details
+pub fn run_golden(corpus: &str, dir: &Path, decode: GoldenDecodeFn) {
+    let json_file = read(golden_file(corpus)).unwrap();
+    let entries: Vec<GoldenEntry> = serde_json::from_slice(&json_file).unwrap();
+
+    let reference_dir = sample_path().join("test-images/golden").join(corpus);
+
+    if std::env::var_os("ZUNE_TESTS_REGENERATE").is_some() {
+        create_dir_all(&reference_dir).unwrap();
+
+        for entry in entries.iter().filter(|x| x.options == OptionsPreset::Safe) {
+            let image = decode_entry(dir, entry, decode)
+                .unwrap_or_else(|e| panic!("Cannot decode {}: {e}", entry.name));
+
+            write(
+                reference_dir.join(entry.reference_name()),
+                encode_png(&image).unwrap()
+            )
+            .unwrap();
+        }
+    }
+
+    let failures: Vec<String> = entries
+        .iter()
+        .filter_map(|entry| check_entry(corpus, dir, &reference_dir, entry, decode))
+        .collect();
+
+    if !failures.is_empty() {
+        panic!("Golden images differ\n{}", failures.join("\n"));
+    }
+}
+
+#[test]
+fn test_ssim() {
+    let width = 32;
+    let height = 20;
+    let image = GoldenImage {
+        pixels: (0..width * height * 3).map(|x| (x % 199) as u8).collect(),
+        width,
+        height,
+        components: 3
+    };
+    let mut noisy = image.clone();
+    noisy
+        .pixels
+        .iter_mut()
+        .step_by(7)
+        .for_each(|x| *x = x.wrapping_add(40));
+
+    assert!((ssim(&image, &image) - 1.0).abs() < 1e-9);
+
+    let result = compare(&noisy, &image).unwrap();
+    assert_eq!(result.max_diff, 40);
+    assert!(result.ssim < 0.99);
+}
@@ -11,18 +11,22 @@
 use std::fs::read;
 use std::path::{Path, PathBuf};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use xxhash_rust::xxh3::xxh3_128;
 use zune_core::colorspace::ColorSpace;
 use zune_core::options::DecoderOptions;
 
+use crate::corpus::OptionsPreset;
+
 mod bmp;
+pub mod corpus;
+pub mod golden;
 mod inflate;
 mod jpeg;
 mod png;
 mod psd;
 
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum JsonColorspace {
     RGB,
@@ -50,11 +54,17 @@ impl JsonColorspace {
     }
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct TestEntry {
     pub name:       String,
-    pub hash:       u128,
+    /// Hash of the decoded pixels, `None` if decoding should fail
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash:       Option<u128>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub colorspace: Option<JsonColorspace>,
+    #[serde(default, skip_serializing_if = "OptionsPreset::is_safe")]
+    pub options:    OptionsPreset,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub comment:    Option<String>
 }
 
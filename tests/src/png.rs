@@ -6,58 +6,41 @@
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 
-use std::fs::read;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 use zune_core::bytestream::ZCursor;
-use zune_core::options::DecoderOptions;
 use zune_png::PngDecoder;
 
-use crate::{hash, sample_path, TestEntry};
+use crate::corpus::run_corpus;
+use crate::golden::{run_golden, GoldenImage};
+use crate::sample_path;
 
 pub fn png_path() -> PathBuf {
     sample_path().join("test-images/png")
 }
 
 #[test]
-#[allow(clippy::uninlined_format_args)]
 fn test_png() {
-    let file = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/png.json");
-
-    let json_file = read(file).unwrap();
-
-    let paths: Vec<TestEntry> = serde_json::from_slice(&json_file).unwrap();
-
-    let default_path = png_path();
-    let mut error = false;
-    let mut files = Vec::new();
-    for path in &paths {
-        let file_name = default_path.join(&path.name);
-
-        let expected_hash = path.hash;
-
-        // load file
-        let file_contents = read(&file_name).unwrap();
-
-        let options = DecoderOptions::default();
-
-        let mut decoder = PngDecoder::new_with_options(ZCursor::new(&file_contents), options);
-        let pixels = decoder.decode_raw().unwrap();
-
-        let hash = hash(&pixels);
+    run_corpus("png", &png_path(), |data, options| {
+        PngDecoder::new_with_options(ZCursor::new(data), options)
+            .decode_raw()
+            .map_err(|e| format!("{e:?}"))
+    });
+}
 
-        if hash != expected_hash {
-            error = true;
-            files.push(path.to_owned());
-            // report error
-            let err = format!(
-                "Hash mismatch for file {:?}\nExpected {} but found {}\nConfig:{:#?}",
-                file_name, expected_hash, hash, path
-            );
-            eprintln!("{}\n", err)
-        }
-    }
-    if error {
-        panic!("Errors found during test decoding\n {:#?}", files);
-    }
+#[test]
+fn test_png_golden() {
+    run_golden("png", &png_path(), |data, options| {
+        let options = options.png_set_strip_to_8bit(true);
+        let mut decoder = PngDecoder::new_with_options(ZCursor::new(data), options);
+        let pixels = decoder.decode_raw().map_err(|e| format!("{e:?}"))?;
+        let (width, height) = decoder.dimensions().unwrap();
+
+        Ok(GoldenImage {
+            pixels,
+            width,
+            height,
+            components: decoder.colorspace().unwrap().num_components()
+        })
+    });
 }